@@ -79,6 +79,15 @@ else
 fi
 cd ../..
 
+# Step 4.1: Embed the VRF worker's build attestation hash (checked by the worker at startup)
+print_step "Embedding VRF WASM build attestation..."
+if VRF_WASM_HASH=$(node scripts/embed-wasm-attestation.mjs "$SOURCE_WASM_VRF/pkg/wasm_vrf_worker_bg.wasm" "$SOURCE_WASM_VRF/pkg/wasm-attestation.json"); then
+    print_success "VRF WASM attestation hash: $VRF_WASM_HASH"
+else
+    print_error "Embedding the VRF WASM attestation failed"
+    exit 1
+fi
+
 # Step 5: Build TypeScript
 print_step "Building TypeScript..."
 if npx tsc -p tsconfig.build.json; then
@@ -119,6 +128,7 @@ if cp "$SOURCE_WASM_VRF/pkg/wasm_vrf_worker_bg.wasm" "$BUILD_WORKERS/" 2>/dev/nu
 else
   print_warning "VRF WASM not found at $SOURCE_WASM_VRF/pkg/wasm_vrf_worker_bg.wasm"
 fi
+cp "$SOURCE_WASM_VRF/pkg/wasm-attestation.json" "$BUILD_WORKERS/" 2>/dev/null || true
 if cp "$SOURCE_WASM_SIGNER/pkg/wasm_signer_worker_bg.wasm" "$BUILD_WORKERS/" 2>/dev/null; then
   print_success "Signer WASM copied to dist/workers/"
 else
@@ -0,0 +1,83 @@
+#!/usr/bin/env node
+// Embeds the build attestation hash in a worker wasm (see wasm_vrf_worker/src/build_info.rs).
+//
+// The attestation hash is the base64url SHA-256 of the module with its `w3a_build_attestation`
+// custom sections removed, so embedding it does not change it. Any existing attestation section
+// is replaced. Prints the hash, which is the value for the SDK release manifest.
+//
+// Usage: embed-wasm-attestation.mjs <module.wasm> [manifest.json]
+// With a manifest path, the hash is also recorded there under the wasm's file name.
+
+import { createHash } from 'node:crypto';
+import { existsSync, readFileSync, writeFileSync } from 'node:fs';
+import { basename } from 'node:path';
+
+const SECTION_NAME = 'w3a_build_attestation';
+const HEADER = [0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+
+function readLeb128(bytes, pos) {
+  let value = 0;
+  for (let shift = 0; shift < 35; shift += 7) {
+    const byte = bytes[pos++];
+    if (byte === undefined) throw new Error('truncated LEB128');
+    value += (byte & 0x7f) * 2 ** shift;
+    if ((byte & 0x80) === 0) return [value, pos];
+  }
+  throw new Error('LEB128 longer than 5 bytes');
+}
+
+function leb128(value) {
+  const out = [];
+  do {
+    let byte = value & 0x7f;
+    value >>>= 7;
+    if (value !== 0) byte |= 0x80;
+    out.push(byte);
+  } while (value !== 0);
+  return out;
+}
+
+/** The module's sections, without attestation sections */
+function sectionsWithoutAttestation(bytes) {
+  if (!HEADER.every((b, i) => bytes[i] === b)) throw new Error('not a WebAssembly module');
+  const kept = [bytes.subarray(0, HEADER.length)];
+  let pos = HEADER.length;
+  while (pos < bytes.length) {
+    const start = pos;
+    const id = bytes[pos++];
+    let size;
+    [size, pos] = readLeb128(bytes, pos);
+    const end = pos + size;
+    if (end > bytes.length) throw new Error(`section at offset ${start} overruns the module`);
+    if (id === 0) {
+      const [nameLen, namePos] = readLeb128(bytes, pos);
+      const name = Buffer.from(bytes.subarray(namePos, namePos + nameLen)).toString('utf8');
+      if (name === SECTION_NAME) {
+        pos = end;
+        continue;
+      }
+    }
+    kept.push(bytes.subarray(start, end));
+    pos = end;
+  }
+  return Buffer.concat(kept);
+}
+
+const [wasmPath, manifestPath] = process.argv.slice(2);
+if (!wasmPath) {
+  console.error('Usage: embed-wasm-attestation.mjs <module.wasm> [manifest.json]');
+  process.exit(1);
+}
+
+const stripped = sectionsWithoutAttestation(readFileSync(wasmPath));
+const hash = createHash('sha256').update(stripped).digest('base64url');
+const contents = [...leb128(SECTION_NAME.length), ...Buffer.from(SECTION_NAME), ...Buffer.from(hash)];
+const section = Buffer.from([0x00, ...leb128(contents.length), ...contents]);
+writeFileSync(wasmPath, Buffer.concat([stripped, section]));
+
+if (manifestPath) {
+  const manifest = existsSync(manifestPath) ? JSON.parse(readFileSync(manifestPath, 'utf8')) : {};
+  manifest[basename(wasmPath)] = { sha256B64u: hash };
+  writeFileSync(manifestPath, JSON.stringify(manifest, null, 2) + '\n');
+}
+console.log(hash);
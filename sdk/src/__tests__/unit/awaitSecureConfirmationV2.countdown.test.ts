@@ -0,0 +1,102 @@
+import { test, expect } from '@playwright/test';
+import { setupBasicPasskeyTest } from '../setup';
+
+const WORKER_PATH = '/sdk/workers/web3authn-signer.worker.js';
+
+// Each test drives awaitSecureConfirmationV2 as a page would: it dispatches the main thread's
+// messages on the worker scope and inspects the countdown report the worker verifies.
+test.describe('awaitSecureConfirmationV2 - auto-proceed countdown handshake', () => {
+  test.beforeEach(async ({ page }) => {
+    await setupBasicPasskeyTest(page);
+  });
+
+  const runScenario = (page: import('@playwright/test').Page, scenario: string) =>
+    page.evaluate(async ({ workerPath, scenario }) => {
+      await import(workerPath);
+      const awaitV2 = (globalThis as any).awaitSecureConfirmationV2 as (json: string, opts?: any) => Promise<any>;
+      const posted: any[] = [];
+      const originalPost = (self as any).postMessage;
+      (self as any).postMessage = (msg: unknown) => { posted.push(msg); };
+      const requestId = `countdown-${scenario}`;
+      const DIGEST = 'digest-1';
+      const DELAY = 300;
+      const dispatch = (type: string, data: Record<string, unknown>) =>
+        self.dispatchEvent(new MessageEvent('message', { data: { type, data: { requestId, ...data } } }));
+      const decide = () => dispatch('USER_PASSKEY_CONFIRM_RESPONSE', { confirmed: true, intentDigest: DIGEST });
+
+      switch (scenario) {
+        case 'skipsRendering':
+          // Confirms immediately without acking COUNTDOWN_STARTED
+          setTimeout(decide, 10);
+          break;
+        case 'neverAcks':
+          // Confirms only after the worker fell back to requireClick
+          setTimeout(decide, 2300);
+          break;
+        case 'confirmsEarly':
+          setTimeout(() => dispatch('COUNTDOWN_STARTED', { displayedDigest: DIGEST }), 10);
+          setTimeout(decide, 20);
+          break;
+        case 'cancelsDuringCountdown':
+          setTimeout(() => dispatch('COUNTDOWN_STARTED', { displayedDigest: DIGEST }), 10);
+          setTimeout(decide, 20);
+          setTimeout(() => dispatch('CANCEL_CONFIRMATION', {}), 100);
+          break;
+        case 'acksOtherDigest':
+          setTimeout(() => dispatch('COUNTDOWN_STARTED', { displayedDigest: 'other-digest' }), 10);
+          setTimeout(decide, 20);
+          break;
+      }
+      try {
+        const response = await awaitV2(JSON.stringify({
+          schemaVersion: 2,
+          requestId,
+          type: 'signTransaction',
+          payload: { intentDigest: DIGEST },
+          confirmationConfig: { uiMode: 'modal', behavior: 'autoProceed', autoProceedDelay: DELAY },
+        }), { timeoutMs: 5000 });
+        return {
+          confirmed: response.confirmed,
+          errorCode: response.error_code,
+          countdown: response.countdown,
+          behaviorChanged: posted.some((m) => m?.type === 'CONFIRMATION_BEHAVIOR_CHANGED' && m?.data?.requestId === requestId),
+          delay: DELAY,
+        };
+      } finally {
+        (self as any).postMessage = originalPost;
+      }
+    }, { workerPath: WORKER_PATH, scenario });
+
+  test('page that skips rendering: no ack is reported, so the worker refuses', async ({ page }) => {
+    const result = await runScenario(page, 'skipsRendering');
+    expect(result.countdown.started_at_ms).toBeUndefined();
+    expect(result.countdown.require_click).toBe(false);
+    expect(result.behaviorChanged).toBe(false);
+  });
+
+  test('page that never acks: falls back to requireClick after 2s', async ({ page }) => {
+    const result = await runScenario(page, 'neverAcks');
+    expect(result.behaviorChanged).toBe(true);
+    expect(result.countdown.require_click).toBe(true);
+    expect(result.countdown.started_at_ms).toBeUndefined();
+  });
+
+  test('early decision is held until the worker timer elapses', async ({ page }) => {
+    const result = await runScenario(page, 'confirmsEarly');
+    expect(result.confirmed).toBe(true);
+    expect(result.countdown.displayed_digest).toBe('digest-1');
+    expect(result.countdown.released_at_ms - result.countdown.started_at_ms).toBeGreaterThanOrEqual(result.delay);
+  });
+
+  test('cancel during the countdown declines the held decision', async ({ page }) => {
+    const result = await runScenario(page, 'cancelsDuringCountdown');
+    expect(result.confirmed).toBe(false);
+    expect(result.errorCode).toBe('UserDeclined');
+    expect(typeof result.countdown.cancelled_at_ms).toBe('number');
+  });
+
+  test('ack for another digest is reported for the worker to refuse', async ({ page }) => {
+    const result = await runScenario(page, 'acksOtherDigest');
+    expect(result.countdown.displayed_digest).toBe('other-digest');
+  });
+});
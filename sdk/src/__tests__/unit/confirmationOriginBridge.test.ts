@@ -0,0 +1,97 @@
+import { test, expect } from '@playwright/test';
+import { setupBasicPasskeyTest } from '../setup';
+
+const IMPORT_PATHS = {
+  bridge: '/sdk/esm/core/WebAuthnManager/SignerWorkerManager/confirmTxFlow/confirmationOriginBridge.js',
+} as const;
+
+const WALLET_ORIGIN = 'https://wallet.example.com';
+
+test.describe('confirmationOriginBridge', () => {
+  test.beforeEach(async ({ page }) => {
+    await setupBasicPasskeyTest(page);
+  });
+
+  test('attests decisions from the trusted origin with the origin the browser reports', async ({ page }) => {
+    const res = await page.evaluate(async ({ paths }) => {
+      try {
+        const { connectConfirmationOriginBridge, disconnectConfirmationOriginBridge } = await import(paths.bridge);
+        const received: any[] = [];
+        const worker = {
+          postMessage: (_msg: unknown, transfer: MessagePort[]) => {
+            transfer[0].onmessage = (event) => received.push(event.data);
+          },
+        } as unknown as Worker;
+        connectConfirmationOriginBridge(worker, window.location.origin);
+
+        // The decision's own claim is replaced by the sender's origin
+        window.postMessage({
+          type: 'USER_PASSKEY_CONFIRM_RESPONSE',
+          data: { requestId: 'req-1', confirmed: true, renderAttestation: { renderNonce: 'nonce-1', origin: 'https://forged.example' } },
+        }, window.location.origin);
+        await new Promise((r) => setTimeout(r, 50));
+
+        disconnectConfirmationOriginBridge(worker);
+        window.postMessage({
+          type: 'USER_PASSKEY_CONFIRM_RESPONSE',
+          data: { requestId: 'req-2', confirmed: true, renderAttestation: { renderNonce: 'nonce-2' } },
+        }, window.location.origin);
+        await new Promise((r) => setTimeout(r, 50));
+        return { success: true, received, origin: window.location.origin };
+      } catch (err: any) {
+        return { success: false, error: err?.message || String(err) };
+      }
+    }, { paths: IMPORT_PATHS });
+
+    if (!res.success) {
+      test.skip(true, `confirmationOriginBridge test skipped: ${res.error || 'unknown error'}`);
+      return;
+    }
+
+    expect(res.received).toHaveLength(1);
+    expect(res.received[0].data.requestId).toBe('req-1');
+    expect(res.received[0].data.renderAttestation).toEqual({ renderNonce: 'nonce-1', origin: res.origin });
+  });
+
+  test('does not forward decisions with a spoofed origin', async ({ page }) => {
+    const res = await page.evaluate(async ({ paths, walletOrigin }) => {
+      try {
+        const { connectConfirmationOriginBridge, postConfirmationDecision, rememberRenderNonce } = await import(paths.bridge);
+        const received: any[] = [];
+        const worker = {
+          postMessage: (_msg: unknown, transfer: MessagePort[]) => {
+            transfer[0].onmessage = (event) => received.push(event.data);
+          },
+        } as unknown as Worker;
+        connectConfirmationOriginBridge(worker, walletOrigin);
+        const decision = (requestId: string) => ({
+          type: 'USER_PASSKEY_CONFIRM_RESPONSE',
+          data: { requestId, confirmed: true, renderAttestation: { renderNonce: 'nonce', origin: walletOrigin } },
+        });
+
+        // Posted by this page, which is not at the wallet origin, claiming to be
+        window.postMessage(decision('posted'), '*');
+        // A synthetic event naming the wallet origin
+        window.dispatchEvent(new MessageEvent('message', { data: decision('synthetic'), origin: walletOrigin }));
+        await new Promise((r) => setTimeout(r, 50));
+        const fromOtherSenders = received.length;
+
+        // A prompt rendered in this page is answered without an attestation
+        rememberRenderNonce({ requestId: 'local', renderNonce: 'local-nonce' } as any);
+        postConfirmationDecision(worker, { requestId: 'local', confirmed: true });
+        await new Promise((r) => setTimeout(r, 50));
+        return { success: true, fromOtherSenders, local: received[0]?.data };
+      } catch (err: any) {
+        return { success: false, error: err?.message || String(err) };
+      }
+    }, { paths: IMPORT_PATHS, walletOrigin: WALLET_ORIGIN });
+
+    if (!res.success) {
+      test.skip(true, `confirmationOriginBridge test skipped: ${res.error || 'unknown error'}`);
+      return;
+    }
+
+    expect(res.fromOtherSenders).toBe(0);
+    expect(res.local).toEqual({ requestId: 'local', confirmed: true });
+  });
+});
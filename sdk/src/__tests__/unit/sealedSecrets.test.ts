@@ -0,0 +1,105 @@
+import { test, expect } from '@playwright/test';
+import { setupBasicPasskeyTest } from '../setup';
+
+const IMPORT_PATHS = {
+  sealedSecrets: '/sdk/esm/core/WebAuthnManager/SignerWorkerManager/sealedSecrets.js',
+} as const;
+
+// RFC 8439 §2.8.2: AEAD_CHACHA20_POLY1305 test vector
+const RFC8439_AEAD = {
+  key: '808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f',
+  nonce: '070000004041424344454647',
+  aad: '50515253c0c1c2c3c4c5c6c7',
+  plaintext: "Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.",
+  ciphertext:
+    'd31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d63dbea45e8ca9671282fafb69da92728b' +
+    '1a71de0a9e060b2905d6a5b67ecd3b3692ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7' +
+    'bc3ff4def08e4b7a9de576d26586cec64b6116',
+  tag: '1ae10b594f09e26a7e902ecbd0600691',
+};
+
+// RFC 7748 §6.1 key pairs: Alice pinned as the ephemeral key, Bob as the worker's session key.
+// The expected box is TS_SEALED_BOX of wasm_signer_worker/src/tests/sealed_secrets_tests.rs,
+// which the Rust side opens with Bob's private key.
+const RFC7748_ALICE_PRIVATE = '77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a';
+const RFC7748_ALICE_PUBLIC = '8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a';
+const RFC7748_BOB_PUBLIC = '3p7bfXt9wbTTW2HC7OQ1Nz-DQ8hbeGdNrfx-FG-IK08';
+const RUST_OPENED_BOX =
+  'AYUg8AmJMKdUdIt93LQ-91oNvzoNJjga9OukqY6qm05qq6J8qIIj1yUhGtkUk4zQFS8acU95X4rM9LuRT_MolNELMA9iWEM';
+
+test.describe('sealedSecrets', () => {
+  test.beforeEach(async ({ page }) => {
+    await setupBasicPasskeyTest(page);
+  });
+
+  test('ChaCha20-Poly1305 matches the RFC 8439 AEAD vector', async ({ page }) => {
+    const res = await page.evaluate(async ({ paths, vector }) => {
+      try {
+        const { chacha20Poly1305Seal } = await import(paths.sealedSecrets);
+        const hex = (h: string) => Uint8Array.from(h.match(/../g)!.map((b) => parseInt(b, 16)));
+        const toHex = (b: Uint8Array) => Array.from(b, (x) => x.toString(16).padStart(2, '0')).join('');
+        const sealed: Uint8Array = chacha20Poly1305Seal(
+          hex(vector.key),
+          hex(vector.nonce),
+          new TextEncoder().encode(vector.plaintext),
+          hex(vector.aad),
+        );
+        return {
+          success: true,
+          ciphertext: toHex(sealed.subarray(0, sealed.length - 16)),
+          tag: toHex(sealed.subarray(sealed.length - 16)),
+        };
+      } catch (err: any) {
+        return { success: false, error: err?.message || String(err) };
+      }
+    }, { paths: IMPORT_PATHS, vector: RFC8439_AEAD });
+
+    if (!res.success) {
+      test.skip(true, `sealedSecrets test skipped: ${res.error || 'unknown error'}`);
+      return;
+    }
+    expect(res.ciphertext).toBe(RFC8439_AEAD.ciphertext);
+    expect(res.tag).toBe(RFC8439_AEAD.tag);
+  });
+
+  test('seals the box the Rust worker opens', async ({ page }) => {
+    const res = await page.evaluate(async ({ paths, alicePrivate, alicePublic, bobPublic }) => {
+      try {
+        const { sealSecret } = await import(paths.sealedSecrets);
+        const { base64UrlEncode } = await import('/sdk/esm/utils/encoders.js');
+        const hex = (h: string) => Uint8Array.from(h.match(/../g)!.map((b) => parseInt(b, 16)));
+        const ephemeral = {
+          privateKey: await crypto.subtle.importKey(
+            'jwk',
+            { kty: 'OKP', crv: 'X25519', d: base64UrlEncode(hex(alicePrivate).buffer), x: base64UrlEncode(hex(alicePublic).buffer) },
+            { name: 'X25519' },
+            false,
+            ['deriveBits'],
+          ),
+          publicKey: await crypto.subtle.importKey('raw', hex(alicePublic), { name: 'X25519' }, true, []),
+        };
+        return {
+          success: true,
+          sealed: await sealSecret(bobPublic, '/chacha20PrfOutput', 'cHJmLW91dHB1dC1ieXRlcw', ephemeral),
+          // Unpinned boxes each get their own ephemeral key
+          fresh: await sealSecret(bobPublic, '/chacha20PrfOutput', 'cHJmLW91dHB1dC1ieXRlcw'),
+        };
+      } catch (err: any) {
+        return { success: false, error: err?.message || String(err) };
+      }
+    }, {
+      paths: IMPORT_PATHS,
+      alicePrivate: RFC7748_ALICE_PRIVATE,
+      alicePublic: RFC7748_ALICE_PUBLIC,
+      bobPublic: RFC7748_BOB_PUBLIC,
+    });
+
+    if (!res.success) {
+      test.skip(true, `sealedSecrets test skipped (WebCrypto X25519 unavailable?): ${res.error || 'unknown error'}`);
+      return;
+    }
+    expect(res.sealed).toBe(RUST_OPENED_BOX);
+    expect(res.fresh).not.toBe(RUST_OPENED_BOX);
+    expect(res.fresh.length).toBe(RUST_OPENED_BOX.length);
+  });
+});
@@ -0,0 +1,62 @@
+import { test, expect } from '@playwright/test';
+import { setupBasicPasskeyTest } from '../setup';
+
+const IMPORT_PATHS = {
+  registry: '/sdk/esm/core/WebAuthnManager/SignerWorkerManager/requestRegistry.js',
+  types: '/sdk/esm/core/types/signer-worker.js',
+} as const;
+
+test.describe('SignerWorkerManager request registry', () => {
+  test.beforeEach(async ({ page }) => {
+    await setupBasicPasskeyTest(page);
+  });
+
+  test('queues past maxConcurrent, rejects past maxQueued and cancels queued requests', async ({ page }) => {
+    const res = await page.evaluate(async ({ paths }) => {
+      try {
+        const { WorkerRequestRegistry } = await import(paths.registry);
+        const { WorkerRequestType } = await import(paths.types);
+        const registry = new WorkerRequestRegistry();
+        registry.setLimits({ maxConcurrent: 1, maxQueued: 1 });
+        const sign = WorkerRequestType.SignTransactionsWithActions;
+
+        await registry.admit('a', sign);
+        let bStarted = false;
+        const b = registry.admit('b', sign).then(() => { bStarted = true; });
+        const full = await registry.admit('c', sign).then(() => 'admitted', (e: Error) => e.message);
+        const queued = registry.list().requests.map((r: { requestId: string; phase: string }) => `${r.requestId}:${r.phase}`);
+
+        registry.release('a');
+        await b;
+        const runningCancel = registry.cancel('b');
+        registry.release('b');
+
+        await registry.admit('d', sign);
+        const d = registry.admit('e', sign).then(() => 'started', (e: Error) => e.message);
+        const cancelled = registry.cancel('e');
+        return {
+          success: true,
+          full,
+          queued,
+          bStarted,
+          runningCancel,
+          cancelled,
+          cancelledOutcome: await d,
+        };
+      } catch (err: any) {
+        return { success: false, error: err?.message || String(err) };
+      }
+    }, { paths: IMPORT_PATHS });
+
+    if (!res.success) {
+      test.skip(true, `request registry test skipped: ${res.error || 'unknown error'}`);
+      return;
+    }
+    expect(res.full).toMatch(/^TooManyPendingRequests/);
+    expect(res.queued).toEqual(['a:running', 'b:queued']);
+    expect(res.bStarted).toBe(true);
+    expect(res.runningCancel).toEqual({ requestId: 'b', cancelled: false, phase: 'running' });
+    expect(res.cancelled).toEqual({ requestId: 'e', cancelled: true, phase: 'queued' });
+    expect(res.cancelledOutcome).toMatch(/^RequestCancelled/);
+  });
+});
@@ -0,0 +1,61 @@
+import { test, expect } from '@playwright/test';
+import { setupBasicPasskeyTest } from '../setup';
+
+const IMPORT_PATHS = {
+  vrfWorker: '/sdk/esm/core/types/vrf-worker.js',
+} as const;
+
+// vrfOutput of vector 0 in wasm_vrf_worker/test_vectors/vrf_input_vectors.json; the expected
+// challenges are the golden values of wasm_signer_worker/src/tests/crypto_tests.rs
+const GOLDEN_VRF_OUTPUT =
+  'XjlYUN-J3STGS3qNK6BYVM8hIMrhUHCa4kEYwCksQM_tFa6K6J_UznE4eMwsvS_BXf4NIVklYkdD1ar_8bMe8A';
+
+test.describe('deriveWebAuthnChallenge', () => {
+  test.beforeEach(async ({ page }) => {
+    await setupBasicPasskeyTest(page);
+  });
+
+  test('matches Rust derive_webauthn_challenge for each challengeLength', async ({ page }) => {
+    const res = await page.evaluate(async ({ paths, vrfOutput }) => {
+      try {
+        const { deriveWebAuthnChallenge, validateVRFChallenge } = await import(paths.vrfWorker);
+        const { base64UrlEncode } = await import('/sdk/esm/utils/encoders.js');
+        const challenge = (challengeLength?: number) => validateVRFChallenge({
+          vrfInput: 'aW5wdXQ',
+          vrfOutput,
+          vrfProof: 'cHJvb2Y',
+          vrfPublicKey: 'cGs',
+          userId: 'vector-0.testnet',
+          rpId: 'example.localhost',
+          blockHeight: '1',
+          blockHash: 'aGFzaA',
+          ...(challengeLength != null ? { challengeLength } : {}),
+        });
+        const derive = async (challengeLength?: number) =>
+          base64UrlEncode(await deriveWebAuthnChallenge(challenge(challengeLength)));
+        let rejected = false;
+        try { challenge(65); } catch { rejected = true; }
+        return {
+          success: true,
+          kept: challenge(48).challengeLength,
+          defaultChallenge: await derive(),
+          sha256Challenge: await derive(16),
+          sha512Challenge: await derive(48),
+          rejected,
+        };
+      } catch (err: any) {
+        return { success: false, error: err?.message || String(err) };
+      }
+    }, { paths: IMPORT_PATHS, vrfOutput: GOLDEN_VRF_OUTPUT });
+
+    if (!res.success) {
+      test.skip(true, `deriveWebAuthnChallenge test skipped: ${res.error || 'unknown error'}`);
+      return;
+    }
+    expect(res.kept).toBe(48);
+    expect(res.defaultChallenge).toBe('XjlYUN-J3STGS3qNK6BYVM8hIMrhUHCa4kEYwCksQM8');
+    expect(res.sha256Challenge).toBe('BhSbSZ0brAtRJQ-8HJ-6Kg');
+    expect(res.sha512Challenge).toBe('td3iIkfvpIeWflxZwB-perySfctf0AQehEZ1mqxb79Nboa5aKi4XsvqKhAel5fB5');
+    expect(res.rejected).toBe(true);
+  });
+});
@@ -67,6 +67,7 @@ export interface ClientAuthenticatorData {
   registered: string; // ISO date string
   syncedAt: string; // When this cache entry was last synced with contract
   vrfPublicKey: string; // Base64-encoded VRF public key (1:1 relationship on client)
+  residentKey?: boolean; // credProps.rk reported at registration (absent when not reported)
 }
 
 interface AppStateEntry<T = any> {
@@ -215,6 +216,80 @@ export class PasskeyClientDBManager {
     }
   }
 
+  /**
+   * Rewrites user records stored by earlier releases in the current schema, once per database.
+   * `migrate` is the signer wasm `migrateStoredRecord` export (JSON string in, JSON string out);
+   * records it cannot parse are left as they are.
+   */
+  async migrateStoredRecords(migrate: (recordJson: string) => string): Promise<number> {
+    const db = await this.getDB();
+    const migrated = await db.get(DB_CONFIG.appStateStore, 'migrated_storedRecordSchema');
+    if (migrated?.value === true) return 0;
+
+    const tx = db.transaction(DB_CONFIG.userStore, 'readwrite');
+    const store = tx.objectStore(DB_CONFIG.userStore);
+    const users: any[] = await store.getAll();
+    let rewritten = 0;
+    for (const user of users) {
+      try {
+        const before = JSON.stringify(user);
+        const after = migrate(before);
+        if (after !== before) {
+          await store.put(JSON.parse(after));
+          rewritten++;
+        }
+      } catch (e) {
+        console.warn('PasskeyClientDB: stored record migration skipped a user record:', e);
+      }
+    }
+    await tx.done;
+    await db.put(DB_CONFIG.appStateStore, { key: 'migrated_storedRecordSchema', value: true });
+    return rewritten;
+  }
+
+  // === ACCOUNT BUNDLE METHODS ===
+
+  /**
+   * Every record of the users, authenticators and appState stores, for ExportAccountBundle
+   */
+  async getAccountBundleRecords(): Promise<{
+    users: ClientUserData[];
+    authenticators: ClientAuthenticatorData[];
+    appState: AppStateEntry[];
+  }> {
+    const db = await this.getDB();
+    const [users, authenticators, appState] = await Promise.all([
+      db.getAll(DB_CONFIG.userStore),
+      db.getAll(DB_CONFIG.authenticatorStore),
+      db.getAll(DB_CONFIG.appStateStore),
+    ]);
+    return { users, authenticators, appState };
+  }
+
+  /**
+   * Writes records returned by ImportAccountBundle in one transaction; records with the same
+   * key are replaced
+   */
+  async putAccountBundleRecords(records: {
+    users: ClientUserData[];
+    authenticators: ClientAuthenticatorData[];
+    appState: AppStateEntry[];
+  }): Promise<void> {
+    const db = await this.getDB();
+    const stores = [DB_CONFIG.userStore, DB_CONFIG.authenticatorStore, DB_CONFIG.appStateStore];
+    const tx = db.transaction(stores, 'readwrite');
+    for (const user of records.users) {
+      await tx.objectStore(DB_CONFIG.userStore).put(user);
+    }
+    for (const authenticator of records.authenticators) {
+      await tx.objectStore(DB_CONFIG.authenticatorStore).put(authenticator);
+    }
+    for (const entry of records.appState) {
+      await tx.objectStore(DB_CONFIG.appStateStore).put(entry);
+    }
+    await tx.done;
+  }
+
   // === APP STATE METHODS ===
 
   async getAppState<T = any>(key: string): Promise<T | undefined> {
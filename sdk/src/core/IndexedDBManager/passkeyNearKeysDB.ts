@@ -1,4 +1,5 @@
 import { openDB, type IDBPDatabase } from 'idb';
+import type { OuterWrapMode } from '../types/signer-worker';
 
 const DB_CONFIG: PasskeyNearKeysDBConfig = {
   dbName: 'PasskeyNearKeys',
@@ -13,6 +14,12 @@ export interface EncryptedKeyData {
   encryptedData: string;
   iv: string;
   timestamp: number;
+  /** Set when encryptedData carries an outer WebCrypto wrap */
+  outerWrap?: OuterWrapMode;
+  /** Key wrapping version: 1 PRF, 2 largeBlob, 3 passphrase (absent on records before fallbacks) */
+  version?: number;
+  /** SLIP-0010 paths of the sub-keys derived for this key; the key itself has no path */
+  subKeyPaths?: string[];
 }
 
 interface PasskeyNearKeysDBConfig {
@@ -150,6 +157,31 @@ export class PasskeyNearKeysDBManager {
     return await db.getAll(this.config.storeName);
   }
 
+  /**
+   * Rewrites key records stored by earlier releases in the current schema.
+   * `migrate` is the signer wasm `migrateStoredRecord` export; it returns current records as is.
+   */
+  async migrateStoredRecords(migrate: (recordJson: string) => string): Promise<number> {
+    const db = await this.getDB();
+    const tx = db.transaction(this.config.storeName, 'readwrite');
+    const records: EncryptedKeyData[] = await tx.store.getAll();
+    let rewritten = 0;
+    for (const record of records) {
+      try {
+        const before = JSON.stringify(record);
+        const after = migrate(before);
+        if (after !== before) {
+          await tx.store.put(JSON.parse(after));
+          rewritten++;
+        }
+      } catch (e) {
+        console.warn('PasskeyNearKeysDB: stored record migration skipped a key record:', e);
+      }
+    }
+    await tx.done;
+    return rewritten;
+  }
+
   /**
    * Check if a key exists for the given account
    */
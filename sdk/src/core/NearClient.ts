@@ -98,12 +98,12 @@ export class SignedTransaction {
 
 /**
  * Serialize a signed transaction-like object to base64.
- * Accepts either our SignedTransaction instance or a plain object
- * with borsh bytes (borsh_bytes | borshBytes) from cross-origin RPC.
+ * Accepts either our SignedTransaction instance or its plain (structured-clone) form
+ * from cross-origin RPC.
  */
 type EncodableSignedTx =
   | SignedTransaction
-  | { borsh_bytes?: number[]; borshBytes?: number[]; encode?: () => ArrayBuffer; base64Encode?: () => string };
+  | { borsh_bytes?: number[]; encode?: () => ArrayBuffer; base64Encode?: () => string };
 
 export function encodeSignedTransactionBase64(signed: EncodableSignedTx): string {
   try {
@@ -113,20 +113,9 @@ export function encodeSignedTransactionBase64(signed: EncodableSignedTx): string
     if (isFunction((signed as { encode?: unknown }).encode)) {
       return base64Encode((signed as { encode: () => ArrayBuffer }).encode());
     }
-    // Support both snake_case (borsh_bytes) and camelCase (borshBytes)
-    const bytesSnake = (signed as { borsh_bytes?: number[] | Uint8Array }).borsh_bytes;
-    if (Array.isArray(bytesSnake)) {
-      return base64Encode(new Uint8Array(bytesSnake).buffer);
-    }
-    if (bytesSnake instanceof Uint8Array) {
-      return base64Encode(bytesSnake.buffer);
-    }
-    const bytesCamel = (signed as { borshBytes?: number[] | Uint8Array }).borshBytes;
-    if (Array.isArray(bytesCamel)) {
-      return base64Encode(new Uint8Array(bytesCamel).buffer);
-    }
-    if (bytesCamel instanceof Uint8Array) {
-      return base64Encode(bytesCamel.buffer);
+    const bytes = (signed as { borsh_bytes?: number[] }).borsh_bytes;
+    if (Array.isArray(bytes)) {
+      return base64Encode(new Uint8Array(bytes).buffer);
     }
   } catch {
     // fall through
@@ -13,7 +13,7 @@ import type { ActionArgs, TransactionInput, TransactionInputWasm } from '../type
 import type { ConfirmationConfig } from '../types/signer-worker';
 import type { TransactionContext } from '../types/rpc';
 import type { PasskeyManagerContext } from './index';
-import type { NearClient, SignedTransaction } from '../NearClient';
+import { MinimalNearClient, type NearClient, type SignedTransaction } from '../NearClient';
 import type { AccountId } from '../types/accountIds';
 import { ActionPhase, ActionStatus, ActionSSEEvent, onProgressEvents } from '../types/passkeyManager';
 import { toError } from '../../utils/errors';
@@ -75,7 +75,7 @@ async function sendTransactionsParallelStaggered({
     return sendTransaction({
       context,
       signedTransaction: tx.signedTransaction,
-      options
+      options: { ...options, broadcastRpcUrl: tx.broadcastRpcUrl }
     });
   }));
 }
@@ -220,7 +220,11 @@ export async function sendTransaction({
   let transactionResult;
   let txId;
   try {
-    transactionResult = await context.nearClient.sendTransaction(
+    // rpcOverrides.broadcast (already checked against allowedRpcOrigins by the signer worker)
+    const broadcastClient = options?.broadcastRpcUrl
+      ? new MinimalNearClient(options.broadcastRpcUrl)
+      : context.nearClient;
+    transactionResult = await broadcastClient.sendTransaction(
       signedTransaction,
       options?.waitUntil
     );
@@ -299,7 +303,7 @@ export async function executeActionInternal({
   confirmationConfigOverride?: ConfirmationConfig | undefined,
 }): Promise<ActionResult> {
 
-  const { onEvent, onError, beforeCall, afterCall, waitUntil } = options || {};
+  const { onEvent, onError, beforeCall, afterCall, waitUntil, rpcOverrides, idempotencyKey } = options || {};
   const actions = Array.isArray(actionArgs) ? actionArgs : [actionArgs];
 
   try {
@@ -321,14 +325,14 @@ export async function executeActionInternal({
         receiverId: receiverId,
         actions: actions,
       }],
-      options: { onEvent, onError, beforeCall, waitUntil },
+      options: { onEvent, onError, beforeCall, waitUntil, rpcOverrides, idempotencyKey },
       confirmationConfigOverride
     });
 
     const txResult = await sendTransaction({
       context,
       signedTransaction: signedTxs[0].signedTransaction,
-      options: { onEvent, onError, afterCall, waitUntil }
+      options: { onEvent, onError, afterCall, waitUntil, broadcastRpcUrl: signedTxs[0].broadcastRpcUrl }
     });
 
     afterCall?.(true, txResult);
@@ -386,6 +390,7 @@ export async function signAndSendTransactionsInternal({
           options: {
             ...options,
             waitUntil: plan.waitUntil ?? options?.waitUntil,
+            broadcastRpcUrl: tx.broadcastRpcUrl,
           }
         });
         txResults.push(txResult);
@@ -426,7 +431,7 @@ export async function signTransactionsWithActionsInternal({
   confirmationConfigOverride?: ConfirmationConfig | undefined,
 }): Promise<VerifyAndSignTransactionResult[]> {
 
-  const { onEvent, onError, beforeCall, waitUntil } = options || {};
+  const { onEvent, onError, beforeCall, waitUntil, rpcOverrides, idempotencyKey } = options || {};
 
   try {
     await beforeCall?.();
@@ -448,7 +453,7 @@ export async function signTransactionsWithActionsInternal({
       context,
       nearAccountId,
       transactionInputs,
-      { onEvent, onError, waitUntil, confirmationConfigOverride } as any
+      { onEvent, onError, waitUntil, confirmationConfigOverride, rpcOverrides, idempotencyKey } as any
     );
 
     return signedTxs;
@@ -519,7 +524,7 @@ async function wasmAuthenticateAndSignTransactions(
   // Per-call override for confirmation behavior (does not persist to IndexedDB)
 ): Promise<VerifyAndSignTransactionResult[]> {
 
-  const { onEvent, onError, confirmationConfigOverride } = options || {};
+  const { onEvent, onError, confirmationConfigOverride, rpcOverrides, idempotencyKey } = options || {};
   const { webAuthnManager } = context;
 
   onEvent?.({
@@ -550,6 +555,8 @@ async function wasmAuthenticateAndSignTransactions(
     },
     // VRF challenge and NEAR data computed in confirmation flow
     confirmationConfigOverride: confirmationConfigOverride,
+    rpcOverrides,
+    idempotencyKey,
     // Pass through the onEvent callback for progress updates
     onEvent: onEvent ? (progressEvent: onProgressEvents) => {
       if (progressEvent.phase === ActionPhase.STEP_4_WEBAUTHN_AUTHENTICATION) {
@@ -12,7 +12,7 @@ import type { PasskeyManagerContext } from './index';
 import type { AccountId } from '../types/accountIds';
 import type { WebAuthnAuthenticationCredential } from '../types/webauthn';
 import { getUserFriendlyErrorMessage } from '../../utils/errors';
-import { createRandomVRFChallenge, ServerEncryptedVrfKeypair, VRFChallenge } from '../types/vrf-worker';
+import { createRandomVRFChallenge, ServerEncryptedVrfKeypair, VRFChallenge, type VrfScope } from '../types/vrf-worker';
 import { authenticatorsToAllowCredentials} from '../WebAuthnManager/touchIdPrompt';
 
 /**
@@ -24,7 +24,7 @@ export async function loginPasskey(
   options?: LoginHooksOptions
 ): Promise<LoginResult> {
 
-  const { onEvent, onError, beforeCall, afterCall } = options || {};
+  const { onEvent, onError, beforeCall, afterCall, vrfScopes } = options || {};
   // Emit started event
   onEvent?.({
     step: 1,
@@ -61,7 +61,8 @@ export async function loginPasskey(
       onEvent,
       onError,
       beforeCall,
-      afterCall
+      afterCall,
+      vrfScopes,
     );
 
   } catch (err: any) {
@@ -108,9 +109,11 @@ async function handleLoginUnlockVRF(
   onError?: (error: Error) => void,
   beforeCall?: BeforeCall,
   afterCall?: AfterCall<any>,
+  vrfScopes?: VrfScope[],
 ): Promise<LoginResult> {
 
   const { webAuthnManager } = context;
+  const canRotate = !vrfScopes || vrfScopes.includes('rotation');
 
   try {
 
@@ -168,6 +171,7 @@ async function handleLoginUnlockVRF(
           kek_s_b64u: shamir.kek_s_b64u,
           ciphertextVrfB64u: shamir.ciphertextVrfB64u,
           serverKeyId: shamir.serverKeyId,
+          scopes: vrfScopes,
         });
 
         if (unlockResult.success) {
@@ -176,7 +180,7 @@ async function handleLoginUnlockVRF(
           if (!active) {
             unlockResult = { success: false, error: 'VRF session inactive after Shamir3Pass' };
           }
-          if (active) {
+          if (active && canRotate) {
             // Proactive rotation if serverKeyId changed and we unlocked via Shamir
             await webAuthnManager.maybeProactiveShamirRefresh(nearAccountId);
           }
@@ -215,6 +219,7 @@ async function handleLoginUnlockVRF(
           chacha20NonceB64u: userData.encryptedVrfKeypair.chacha20NonceB64u,
         },
         credential: credential,
+        scopes: vrfScopes,
       });
       usedFallbackTouchId = unlockResult.success;
     }
@@ -233,7 +238,7 @@ async function handleLoginUnlockVRF(
     // Proactive refresh: if Shamir3Pass failed and we used TouchID, re-encrypt under current server key
     try {
       const relayerUrl = context.configs.relayer?.url;
-      if (usedFallbackTouchId && relayerUrl) {
+      if (usedFallbackTouchId && relayerUrl && canRotate) {
         const refreshed = await webAuthnManager.shamir3PassEncryptCurrentVrfKeypair();
         await webAuthnManager.updateServerEncryptedVrfKeypair(nearAccountId, refreshed);
         console.debug('Refreshed serverEncryptedVrfKeypair after TouchID fallback');
@@ -8,6 +8,7 @@ import type {
   WebAuthnAuthenticationCredential
 } from '../types';
 import type { EncryptedVRFKeypair, ServerEncryptedVrfKeypair } from '../types/vrf-worker';
+import type { OuterWrapMode } from '../types/signer-worker';
 import { validateNearAccountId } from '../../utils/validation';
 import { parseAccountIdFromUserHandle } from '../WebAuthnManager/userHandle';
 import { toAccountId } from '../types/accountIds';
@@ -370,7 +371,8 @@ export async function recoverAccount(
     } catch {}
     const recoveredKeypair = await webAuthnManager.recoverKeypairFromPasskey(
       credential,
-      accountId
+      accountId,
+      options?.subKeyPaths,
     );
 
     const { hasAccess, blockHeight, blockHash } = await Promise.all([
@@ -412,6 +414,8 @@ export async function recoverAccount(
       encryptedKeypair: {
         encryptedPrivateKey: recoveredKeypair.encryptedPrivateKey,
         iv: recoveredKeypair.iv,
+        outerWrap: recoveredKeypair.outerWrap,
+        subKeyPaths: recoveredKeypair.subKeys.map((subKey) => subKey.path),
       },
       credential: credential,
       encryptedVrfResult: {
@@ -516,7 +520,9 @@ async function performAccountRecovery({
   publicKey: string,
   encryptedKeypair: {
     encryptedPrivateKey: string,
-    iv: string
+    iv: string,
+    outerWrap?: OuterWrapMode,
+    subKeyPaths?: string[]
   },
   credential: WebAuthnAuthenticationCredential,
   encryptedVrfResult: {
@@ -649,7 +655,9 @@ async function restoreUserData({
   serverEncryptedVrfKeypair?: ServerEncryptedVrfKeypair,
   encryptedNearKeypair: {
     encryptedPrivateKey: string;
-    iv: string
+    iv: string;
+    outerWrap?: OuterWrapMode;
+    subKeyPaths?: string[]
   },
   credential: WebAuthnAuthenticationCredential
 }) {
@@ -661,7 +669,9 @@ async function restoreUserData({
     deviceNumber,
     encryptedData: encryptedNearKeypair.encryptedPrivateKey,
     iv: encryptedNearKeypair.iv,
-    timestamp: Date.now()
+    timestamp: Date.now(),
+    outerWrap: encryptedNearKeypair.outerWrap,
+    subKeyPaths: encryptedNearKeypair.subKeyPaths?.length ? encryptedNearKeypair.subKeyPaths : undefined,
   });
 
   if (!existingUser) {
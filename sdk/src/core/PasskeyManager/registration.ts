@@ -1,6 +1,7 @@
 import type { NearClient, SignedTransaction } from '../NearClient';
 import { validateNearAccountId } from '../../utils/validation';
 import type {
+  RegistrationDryRunReport,
   RegistrationHooksOptions,
   RegistrationResult,
   RegistrationSSEEvent,
@@ -43,7 +44,7 @@ export async function registerPasskeyInternal(
   confirmationConfigOverride?: ConfirmationConfig
 ): Promise<RegistrationResult> {
 
-  const { onEvent, onError, beforeCall, afterCall } = options;
+  const { onEvent, onError, beforeCall, afterCall, dryRun, idempotencyKey } = options;
   const { webAuthnManager, configs } = context;
 
   // Track registration progress for rollback
@@ -68,7 +69,8 @@ export async function registerPasskeyInternal(
     await beforeCall?.();
 
     // Validate registration inputs
-    await validateRegistrationInputs(context, nearAccountId, onEvent, onError);
+    // A dry run reports an existing account instead of failing here
+    await validateRegistrationInputs(context, nearAccountId, onEvent, onError, !dryRun);
 
     onEvent?.({
       step: 1,
@@ -101,6 +103,18 @@ export async function registerPasskeyInternal(
     const credential: WebAuthnRegistrationCredential = confirm.credential;
     const vrfChallenge = confirm.vrfChallenge as VRFChallenge;
 
+    if (dryRun) {
+      const dryRunResult = await runRegistrationDryRun(
+        context,
+        nearAccountId,
+        credential,
+        vrfChallenge,
+        authenticatorOptions,
+      );
+      afterCall?.(dryRunResult.success, dryRunResult);
+      return dryRunResult;
+    }
+
     onEvent?.({
       step: 1,
       phase: RegistrationPhase.STEP_1_WEBAUTHN_VERIFICATION,
@@ -122,6 +136,7 @@ export async function registerPasskeyInternal(
       webAuthnManager.deriveNearKeypairAndEncryptFromSerialized({
         credential,
         nearAccountId,
+        options: { idempotencyKey },
       }),
       webAuthnManager.checkCanRegisterUser({
         contractId: context.configs.contractId,
@@ -281,6 +296,7 @@ export async function registerPasskeyInternal(
       nearAccountId: nearAccountId,
       clientNearPublicKey: nearKeyResult.publicKey,
       transactionId: registrationState.contractTransactionId,
+      authenticator: nearKeyResult.authenticator,
       vrfRegistration: {
         success: true,
         vrfPublicKey: vrfChallenge.vrfPublicKey,
@@ -396,6 +412,7 @@ const validateRegistrationInputs = async (
   nearAccountId: AccountId,
   onEvent?: (event: RegistrationSSEEvent) => void,
   onError?: (error: Error) => void,
+  checkAvailability: boolean = true,
 ) => {
 
   onEvent?.({
@@ -424,6 +441,10 @@ const validateRegistrationInputs = async (
     throw error;
   }
 
+  if (!checkAvailability) {
+    return;
+  }
+
   // Check if account already exists on-chain
   onEvent?.({
     step: 1,
@@ -452,6 +473,62 @@ const validateRegistrationInputs = async (
   }
 }
 
+/**
+ * Dry-run tail of registerPasskeyInternal: the signer worker runs every local and view-only
+ * check, then the relayer is pinged. Nothing is created on-chain, signed or stored, so the
+ * nonce and device number are left untouched.
+ */
+async function runRegistrationDryRun(
+  context: PasskeyManagerContext,
+  nearAccountId: AccountId,
+  credential: WebAuthnRegistrationCredential,
+  vrfChallenge: VRFChallenge,
+  authenticatorOptions: AuthenticatorOptions,
+): Promise<RegistrationResult> {
+  const check = await context.webAuthnManager.checkCanRegisterUser({
+    contractId: context.configs.contractId,
+    credential,
+    vrfChallenge,
+    authenticatorOptions,
+    dryRun: true,
+  });
+  if (!check.dryRun) {
+    return { success: false, nearAccountId, error: check.error || 'Registration dry run failed' };
+  }
+
+  let report: RegistrationDryRunReport = check.dryRun;
+  if (report.wouldSucceed) {
+    const relayerError = await checkRelayerReachable(context.configs.relayer?.url);
+    if (relayerError) {
+      report = { ...report, outcome: 'RelayerUnavailable', wouldSucceed: false, detail: relayerError };
+    }
+  }
+
+  return {
+    success: report.wouldSucceed,
+    error: report.wouldSucceed ? undefined : report.detail,
+    nearAccountId,
+    clientNearPublicKey: report.nearPublicKey ?? null,
+    dryRun: report,
+  };
+}
+
+/**
+ * Returns an error message when the relay server cannot be reached.
+ * Any HTTP response counts as reachable; /healthz is optional on the server.
+ */
+async function checkRelayerReachable(relayerUrl?: string): Promise<string | undefined> {
+  if (!relayerUrl) {
+    return 'Relay server URL is not configured';
+  }
+  try {
+    await fetch(`${relayerUrl}/healthz`, { method: 'GET' });
+    return undefined;
+  } catch (error: any) {
+    return `Relay server unreachable: ${error?.message || error}`;
+  }
+}
+
 /**
  * Rollback registration data in case of errors
  */
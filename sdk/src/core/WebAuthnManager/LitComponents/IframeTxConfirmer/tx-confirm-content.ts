@@ -6,7 +6,7 @@ import type { TransactionInputWasm } from '../../../types';
 import type { VRFChallenge } from '../../../types/vrf-worker';
 import { fromTransactionInputsWasm } from '../../../types/actions';
 import TxTree from '../TxTree';
-import { buildDisplayTreeFromTxPayloads } from '../TxTree/tx-tree-utils';
+import { buildDeployDisplayTree, buildDisplayTreeFromTxPayloads } from '../TxTree/tx-tree-utils';
 import { TX_TREE_THEMES } from '../TxTree/tx-tree-themes';
 import { W3A_TX_TREE_ID } from '../tags';
 
@@ -23,6 +23,8 @@ export class TxConfirmContentElement extends LitElementWithProps {
   static properties = {
     nearAccountId: { type: String, attribute: 'near-account-id' },
     txSigningRequests: { type: Array },
+    // Message-signing requests (e.g. EVM personal_sign) show their message instead of a TxTree
+    signingMessage: { type: String },
     intentDigest: { type: String, attribute: 'intent-digest' },
     vrfChallenge: { type: Object },
     theme: { type: String },
@@ -39,6 +41,7 @@ export class TxConfirmContentElement extends LitElementWithProps {
 
   declare nearAccountId: string;
   declare txSigningRequests: TransactionInputWasm[];
+  declare signingMessage?: string;
   declare intentDigest?: string;
   declare vrfChallenge?: VRFChallenge;
   declare theme: 'dark' | 'light';
@@ -168,6 +171,23 @@ export class TxConfirmContentElement extends LitElementWithProps {
     }
     .error { color: var(--w3a-colors-error, #ff7a7a); font-size: 13px; margin: 8px 0; }
     .muted { color: var(--w3a-colors-textMuted, rgba(255,255,255,0.6)); font-size: 12px; }
+    .signing-message {
+      margin: 0;
+      padding: 8px;
+      max-height: 240px;
+      overflow: auto;
+      white-space: pre-wrap;
+      word-break: break-word;
+      font-size: 13px;
+      border-radius: 8px;
+      background: var(--w3a-colors-surface, rgba(255,255,255,0.06));
+    }
+    .summary-warning { font-size: 13px; margin: 8px 0; color: var(--w3a-colors-warning, #f5b942); }
+    .summary-warning.danger { color: var(--w3a-colors-error, #ff7a7a); font-weight: 600; }
+    .sr-only {
+      position: absolute; width: 1px; height: 1px; padding: 0; margin: -1px;
+      overflow: hidden; clip: rect(0, 0, 0, 0); white-space: nowrap; border: 0;
+    }
 
     @keyframes tx-confirm-spin {
       to { transform: rotate(360deg); }
@@ -246,8 +266,14 @@ export class TxConfirmContentElement extends LitElementWithProps {
   private _rebuildTree() {
     try {
       const inputs = Array.isArray(this.txSigningRequests) ? this.txSigningRequests : [];
-      const uiTxs = fromTransactionInputsWasm(inputs);
-      this._treeNode = buildDisplayTreeFromTxPayloads(uiTxs);
+      // A chunked deploy is confirmed as one summarized deploy rather than N staging calls
+      this._treeNode = buildDeployDisplayTree(inputs)
+        ?? buildDisplayTreeFromTxPayloads(
+          fromTransactionInputsWasm(inputs),
+          undefined,
+          inputs.map(tx => tx.dependsOn),
+          inputs.map(tx => tx.autoInsertedActions)
+        );
     } catch (e) {
       console.warn('[TxConfirmContent] failed to build TxTree', e);
       this._treeNode = null;
@@ -271,9 +297,31 @@ export class TxConfirmContentElement extends LitElementWithProps {
     try { dispatchLitCancel(this); } catch {}
   };
 
+  // Warnings the signer worker generated for risky actions (summaryBlocks), shown above the tree
+  private _summaryWarnings() {
+    const inputs = Array.isArray(this.txSigningRequests) ? this.txSigningRequests : [];
+    return inputs.flatMap(tx => (tx.summaryBlocks || []).flatMap(block =>
+      block.kind === 'warning' ? [block] : []
+    ));
+  }
+
+  // The worker's one-sentence description of the batch, announced to screen readers
+  private _summarySpeech(): string | undefined {
+    const inputs = Array.isArray(this.txSigningRequests) ? this.txSigningRequests : [];
+    return inputs[0]?.summarySpeech;
+  }
+
   render() {
+    const summarySpeech = this._summarySpeech();
     return html`
       ${this.errorMessage ? html`<div class="error">${this.errorMessage}</div>` : null}
+      ${summarySpeech ? html`<div class="sr-only" role="status">${summarySpeech}</div>` : null}
+      ${this._summaryWarnings().map(w => html`<div
+        class="summary-warning ${w.severity}"
+        role=${w.ariaLive === 'assertive' ? 'alert' : 'status'}
+        aria-live=${w.ariaLive}
+        aria-label=${w.ariaLabel}
+      >${w.text}</div>`)}
       ${
         this._treeNode
         ? html`<div style="width:${this._txTreeWidth}">
@@ -284,7 +332,9 @@ export class TxConfirmContentElement extends LitElementWithProps {
                   .width=${this._txTreeWidth}
                 ></w3a-tx-tree>
               </div>`
-        : html`<div class="muted">No actions</div>`
+        : this.signingMessage !== undefined
+          ? html`<pre class="signing-message">${this.signingMessage}</pre>`
+          : html`<div class="muted">No actions</div>`
       }
       <div class="actions">
         <button
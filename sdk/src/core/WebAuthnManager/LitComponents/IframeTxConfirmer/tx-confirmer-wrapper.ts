@@ -18,6 +18,7 @@ export type Variant = 'modal' | 'drawer';
 export type TxConfirmerVariantElement = (ConfirmUIElement & HTMLElement) & {
   nearAccountId?: string;
   txSigningRequests?: TransactionInputWasm[];
+  signingMessage?: string;
   vrfChallenge?: VRFChallenge;
   theme?: 'dark' | 'light';
   loading?: boolean;
@@ -48,6 +49,7 @@ export class TxConfirmerWrapperElement extends LitElementWithProps {
     variant: { type: String, reflect: true },
     nearAccountId: { type: String, attribute: 'near-account-id' },
     txSigningRequests: { type: Array },
+    signingMessage: { type: String },
     vrfChallenge: { type: Object },
     theme: { type: String },
     loading: { type: Boolean },
@@ -70,6 +72,7 @@ export class TxConfirmerWrapperElement extends LitElementWithProps {
   declare variant: Variant;
   declare nearAccountId: string;
   declare txSigningRequests: TransactionInputWasm[];
+  declare signingMessage?: string;
   declare vrfChallenge?: VRFChallenge;
   declare theme: 'dark' | 'light';
   declare loading: boolean;
@@ -121,6 +124,7 @@ export class TxConfirmerWrapperElement extends LitElementWithProps {
           ${ref(this.childRef)}
           .nearAccountId=${this.nearAccountId}
           .txSigningRequests=${this.txSigningRequests}
+          .signingMessage=${this.signingMessage}
           .vrfChallenge=${this.vrfChallenge}
           .theme=${this.theme}
           .loading=${this.loading}
@@ -138,6 +142,7 @@ export class TxConfirmerWrapperElement extends LitElementWithProps {
         ${ref(this.childRef)}
         .nearAccountId=${this.nearAccountId}
         .txSigningRequests=${this.txSigningRequests}
+        .signingMessage=${this.signingMessage}
         .vrfChallenge=${this.vrfChallenge}
         .theme=${this.theme}
         .loading=${this.loading}
@@ -155,6 +160,7 @@ export class TxConfirmerWrapperElement extends LitElementWithProps {
     if (!child) return;
     child.nearAccountId = this.nearAccountId;
     child.txSigningRequests = this.txSigningRequests;
+    child.signingMessage = this.signingMessage;
     child.vrfChallenge = this.vrfChallenge;
     child.theme = this.theme;
     child.loading = this.loading;
@@ -290,6 +296,13 @@ export class TxConfirmerWrapperElement extends LitElementWithProps {
         actions: tx.actions
           .map((action) => (isActionArgsWasm(action) ? action : toActionArgsWasm(action as ActionArgs)))
           .map((action) => orderActionForDigest(action) as ActionArgsWasm),
+        // The worker digests the annotation (null included) whenever it sends one
+        ...(tx.firstTimeReceiver !== undefined ? { firstTimeReceiver: tx.firstTimeReceiver } : {}),
+        ...(tx.deployStep !== undefined ? { deployStep: tx.deployStep } : {}),
+        ...(tx.summaryBlocks !== undefined ? { summaryBlocks: tx.summaryBlocks } : {}),
+        ...(tx.summarySpeech !== undefined ? { summarySpeech: tx.summarySpeech } : {}),
+        ...(tx.redactions !== undefined ? { redactions: tx.redactions } : {}),
+        ...(tx.truncations !== undefined ? { truncations: tx.truncations } : {}),
       }));
 
     return computeUiIntentDigestFromTxs(txs);
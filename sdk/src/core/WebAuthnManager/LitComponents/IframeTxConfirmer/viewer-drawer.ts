@@ -22,6 +22,7 @@ export class DrawerTxConfirmerElement extends LitElementWithProps implements Con
   static properties = {
     nearAccountId: { type: String, attribute: 'near-account-id' },
     txSigningRequests: { type: Array },
+    signingMessage: { type: String },
     vrfChallenge: { type: Object },
     theme: { type: String },
     loading: { type: Boolean },
@@ -35,6 +36,7 @@ export class DrawerTxConfirmerElement extends LitElementWithProps implements Con
 
   declare nearAccountId: string;
   declare txSigningRequests: TransactionInputWasm[];
+  declare signingMessage?: string;
   declare vrfChallenge?: VRFChallenge;
   declare theme: 'dark' | 'light';
   styles?: ModalTxConfirmerStyles;
@@ -368,6 +370,7 @@ export class DrawerTxConfirmerElement extends LitElementWithProps implements Con
             <w3a-tx-confirm-content
               .nearAccountId=${this.nearAccountId || ''}
               .txSigningRequests=${this.txSigningRequests || []}
+              .signingMessage=${this.signingMessage}
               .vrfChallenge=${this.vrfChallenge}
               theme=${this.theme}
               .loading=${this.loading}
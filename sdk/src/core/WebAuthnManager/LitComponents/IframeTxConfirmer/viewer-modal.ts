@@ -51,6 +51,7 @@ export class ModalTxConfirmElement extends LitElementWithProps implements Confir
     cancelText: { type: String },
     confirmText: { type: String },
     txSigningRequests: { type: Array },
+    signingMessage: { type: String },
     vrfChallenge: { type: Object },
     loading: { type: Boolean },
     errorMessage: { type: String },
@@ -65,6 +66,7 @@ export class ModalTxConfirmElement extends LitElementWithProps implements Confir
   cancelText = 'Cancel';
   confirmText = 'Next';
   txSigningRequests: TransactionInputWasm[] = [];
+  signingMessage?: string;
   vrfChallenge?: VRFChallenge;
   loading = false;
   errorMessage: string | undefined = undefined;
@@ -814,6 +816,7 @@ export class ModalTxConfirmElement extends LitElementWithProps implements Confir
             <w3a-tx-confirm-content
               .nearAccountId=${this['nearAccountId'] || ''}
               .txSigningRequests=${this.txSigningRequests || []}
+              .signingMessage=${this.signingMessage}
               .vrfChallenge=${this.vrfChallenge}
               .theme=${this.theme}
               .loading=${this.loading}
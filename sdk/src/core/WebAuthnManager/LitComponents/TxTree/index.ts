@@ -436,6 +436,12 @@ export class TxTree extends LitElementWithProps {
       border-radius: var(--w3a-tree__highlight-amount__border-radius, 0);
       box-shadow: var(--w3a-tree__highlight-amount__box-shadow, none);
     }
+
+    .auto-inserted {
+      color: var(--w3a-tree__auto-inserted__color, #f5a623);
+      font-style: var(--w3a-tree__auto-inserted__font-style, italic);
+      margin: 0px 4px;
+    }
   `;
 
   // Track which node IDs have recently been copied
@@ -636,7 +642,8 @@ export class TxTree extends LitElementWithProps {
           let depositStr = formatDeposit(a.deposit);
           return html`Calling <span class="highlight-method-name">${method}</span>
               ${depositStr !== '0 NEAR' ? html` with <span class="highlight-method-name">${depositStr}</span>` : ''}
-              ${gasStr ? html` using <span class="highlight-method-name">${gasStr}</span>` : ''}`;
+              ${gasStr ? html` using <span class="highlight-method-name">${gasStr}</span>` : ''}
+              ${treeNode.autoInserted ? html`<span class="auto-inserted">(added automatically)</span>` : ''}`;
         }
         case 'Transfer': {
           let amount = formatDeposit(a.amount);
@@ -694,7 +701,8 @@ export class TxTree extends LitElementWithProps {
           const method = a.methodName;
           const gasStr = formatGas(a.gas);
           const depositStr = formatDeposit(a.deposit);
-          return `Calling ${method} with ${depositStr} using ${gasStr}`;
+          const addedText = treeNode.autoInserted ? ' (added automatically)' : '';
+          return `Calling ${method} with ${depositStr} using ${gasStr}${addedText}`;
         }
         case 'Transfer':
           return `Transfer ${formatDeposit(a.amount)}`;
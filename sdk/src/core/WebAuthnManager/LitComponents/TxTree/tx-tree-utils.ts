@@ -1,5 +1,6 @@
 import { TxTreeStyles } from './tx-tree-themes';
-import type { ActionArgs, TransactionInput } from '../../../types/actions';
+import { ActionType } from '../../../types/actions';
+import type { ActionArgs, FunctionCallAction, TransactionInput, TransactionInputWasm } from '../../../types/actions';
 import { formatArgs, formatDeposit, shortenPubkey, formatCodeSize } from '../common/formatters';
 import { isString } from '../../../WalletIframe/validation';
 
@@ -61,6 +62,8 @@ export interface TreeNode {
    * The index of this action within its transaction, for display purposes.
    */
   actionIndex?: number;
+  /** Set on actions the signer worker inserted (autoStorageDeposit), labelled "added automatically" */
+  autoInserted?: boolean;
   /** Transaction data for transaction-level folder nodes */
   transaction?: TransactionInput;
   /** Index of this transaction in the list */
@@ -69,6 +72,82 @@ export interface TreeNode {
   totalTransactions?: number;
 }
 
+// Multisig contract methods whose args carry a `request` of inner actions
+const MULTISIG_REQUEST_METHODS = ['add_request_and_confirm', 'add_request'];
+
+// Maps an action from the multisig contract's schema (snake_case, base64 args)
+// to ActionArgs so it can be rendered like a top-level action
+function fromMultisigAction(a: any): ActionArgs {
+  switch (a?.type) {
+    case 'Transfer':
+      return { type: ActionType.Transfer, amount: a.amount };
+    case 'CreateAccount':
+      return { type: ActionType.CreateAccount };
+    case 'DeployContract':
+      return { type: ActionType.DeployContract, code: a.code };
+    case 'FunctionCall': {
+      let args: Record<string, any>;
+      try { args = JSON.parse(atob(a.args)); } catch { args = { raw: a.args }; }
+      return {
+        type: ActionType.FunctionCall,
+        methodName: a.method_name,
+        args,
+        gas: a.gas,
+        deposit: a.deposit
+      };
+    }
+    case 'AddKey':
+      return {
+        type: ActionType.AddKey,
+        publicKey: a.public_key,
+        accessKey: {
+          permission: a.permission
+            ? {
+                FunctionCall: {
+                  allowance: a.permission.allowance ?? undefined,
+                  receiverId: a.permission.receiver_id,
+                  methodNames: a.permission.method_names
+                }
+              }
+            : 'FullAccess'
+        }
+      };
+    case 'DeleteKey':
+      return { type: ActionType.DeleteKey, publicKey: a.public_key };
+    default:
+      // Rendered as raw data by buildActionNode
+      return a as ActionArgs;
+  }
+}
+
+// Prefixes ids of a subtree so nested action nodes stay unique within the tree
+function prefixNodeIds(node: TreeNode, prefix: string): TreeNode {
+  return {
+    ...node,
+    id: `${prefix}${node.id}`,
+    children: node.children?.map(child => prefixNodeIds(child, prefix))
+  };
+}
+
+// For multisig `add_request_and_confirm` calls, renders the wrapped request as a
+// nested folder so the inner intended actions are visible alongside the outer call
+function buildMultisigRequestNode(action: FunctionCallAction, idx: number): TreeNode | undefined {
+  if (!MULTISIG_REQUEST_METHODS.includes(action.methodName)) return undefined;
+  const request = action.args?.request;
+  if (!request || !Array.isArray(request.actions)) return undefined;
+
+  const prefix = `a${idx}-multisig-`;
+  return {
+    id: `${prefix}request`,
+    label: `multisig request to ${request.receiver_id}`,
+    type: 'folder',
+    open: true,
+    children: request.actions.map((inner: any, innerIdx: number) =>
+      prefixNodeIds(buildActionNode(fromMultisigAction(inner), innerIdx), prefix)
+    )
+  };
+}
+
 // Builds a TreeNode for a single action
 function buildActionNode(action: ActionArgs, idx: number): TreeNode {
 
@@ -89,6 +168,10 @@ function buildActionNode(action: ActionArgs, idx: number): TreeNode {
           content: formatArgs(action.args)
         }
       ];
+      const multisigRequestNode = buildMultisigRequestNode(action, idx);
+      if (multisigRequestNode) {
+        actionNodes.push(multisigRequestNode);
+      }
       break;
 
     case 'Transfer':
@@ -219,12 +302,14 @@ export function buildTransactionNode(
   tx: TransactionInput,
   tIdx: number,
   totalTransactions: number,
-  styles?: TxTreeStyles
+  styles?: TxTreeStyles,
+  autoInsertedActions?: number[]
 ): TreeNode {
 
-  const actionFolders: TreeNode[] = tx.actions.map((action: ActionArgs, idx: number) =>
-    buildActionNode(action, idx)
-  );
+  const actionFolders: TreeNode[] = tx.actions.map((action: ActionArgs, idx: number) => {
+    const node = buildActionNode(action, idx);
+    return autoInsertedActions?.includes(idx) ? { ...node, autoInserted: true } : node;
+  });
 
   return {
     id: `tx-${tIdx}`,
@@ -242,21 +327,78 @@ export function buildTransactionNode(
 
 // Builds a display tree from transaction payloads for tooltip rendering
 // a two-level tree: Transaction -> Action N -> subfields
+// With `dependsOn` (per transaction, as set by the signer worker), a transaction depending on
+// earlier ones is nested, after its actions, under the latest of them. Actions listed in
+// `autoInsertedActions` (per transaction) are marked as added automatically.
 export function buildDisplayTreeFromTxPayloads(
   txSigningRequests: TransactionInput[],
-  styles?: TxTreeStyles
+  styles?: TxTreeStyles,
+  dependsOn?: Array<number[] | undefined>,
+  autoInsertedActions?: Array<number[] | undefined>
 ): TreeNode {
 
   const totalTransactions = txSigningRequests.length;
   const txFolders: TreeNode[] = txSigningRequests.map((tx: TransactionInput, tIdx: number) =>
-    buildTransactionNode(tx, tIdx, totalTransactions, styles)
+    buildTransactionNode(tx, tIdx, totalTransactions, styles, autoInsertedActions?.[tIdx])
   );
+  const topLevel = txFolders.filter((txFolder, tIdx) => {
+    const parent = Math.max(-1, ...(dependsOn?.[tIdx] ?? []));
+    // The worker refuses forward references; anything else stays at the top level
+    if (parent < 0 || parent >= tIdx) return true;
+    txFolders[parent].children = [...(txFolders[parent].children ?? []), txFolder];
+    return false;
+  });
 
   return {
     id: 'txs-root',
     label: totalTransactions > 1 ? 'Transactions' : 'Transaction',
     type: 'folder',
     open: true,
-    children: txFolders
+    children: topLevel
+  };
+}
+
+// Chunked deploys (DeployLargeContract) render as one summarized node instead of one folder per
+// staging call; each step's chunk hash can be checked against the deploy manifest.
+// Returns undefined unless every transaction carries a deployStep.
+export function buildDeployDisplayTree(txSigningRequests: TransactionInputWasm[]): TreeNode | undefined {
+  const steps = txSigningRequests.map(tx => tx.deployStep);
+  const first = steps[0];
+  if (!first || steps.some(step => !step)) return undefined;
+
+  const stepNodes: TreeNode[] = txSigningRequests.map((tx, idx) => {
+    const { step, stepCount, chunkHash } = tx.deployStep!;
+    return {
+      id: `deploy-step-${idx}`,
+      label: chunkHash
+        ? `${step}/${stepCount} stage chunk ${chunkHash} on ${tx.receiverId}`
+        : `${step}/${stepCount} deploy staged code on ${tx.receiverId}`,
+      type: 'file',
+      open: false,
+      copyValue: chunkHash ?? undefined
+    };
+  });
+
+  return {
+    id: 'txs-root',
+    label: `Deploy ${(first.totalBytes / 1_000_000).toFixed(1)}MB contract in ${first.stepCount} steps`,
+    type: 'folder',
+    open: true,
+    children: [
+      {
+        id: 'deploy-total-hash',
+        label: `code hash: ${first.totalHash}`,
+        type: 'file',
+        open: true,
+        copyValue: first.totalHash
+      },
+      {
+        id: 'deploy-steps',
+        label: `${stepNodes.length} transactions`,
+        type: 'folder',
+        open: false,
+        children: stepNodes
+      }
+    ]
   };
 }
@@ -16,6 +16,7 @@ interface HostTxConfirmerElement extends HTMLElement {
   variant?: 'modal' | 'drawer';
   nearAccountId: string;
   txSigningRequests: TransactionInputWasm[];
+  signingMessage?: string;
   intentDigest?: string;
   vrfChallenge?: VRFChallenge;
   theme?: 'dark' | 'light';
@@ -38,6 +39,7 @@ export async function mountConfirmUI({
   theme,
   uiMode,
   nearAccountIdOverride,
+  onCancel,
 }: {
   ctx: SignerWorkerManagerContext,
   summary: TransactionSummary,
@@ -47,10 +49,12 @@ export async function mountConfirmUI({
   theme?: 'dark' | 'light',
   uiMode: ConfirmationUIMode,
   nearAccountIdOverride?: string,
+  /** Called when the user cancels (e.g. during an auto-proceed countdown) */
+  onCancel?: () => void,
 }): Promise<ConfirmUIHandle> {
   // 'skip' mode should never request a UI mount; callers handle this.
   const variant = uiModeToVariant(uiMode);
-  const { handle } = mountHostElement({
+  const { el, handle } = mountHostElement({
     ctx,
     summary,
     txSigningRequests,
@@ -60,6 +64,9 @@ export async function mountConfirmUI({
     variant,
     nearAccountIdOverride,
   });
+  if (onCancel) {
+    el.addEventListener(WalletIframeDomEvents.TX_CONFIRMER_CANCEL, () => onCancel(), { once: true });
+  }
   return handle;
 }
 
@@ -163,6 +170,13 @@ async function checkIntentDigestGuard(
       actions: (tx.actions || [])
         .map((a) => (isActionArgsWasm(a) ? a : toActionArgsWasm(a as unknown as ActionArgs)))
         .map((a) => orderActionForDigest(a as ActionArgsWasm) as ActionArgsWasm),
+      // The worker digests the annotation (null included) whenever it sends one
+      ...(tx.firstTimeReceiver !== undefined ? { firstTimeReceiver: tx.firstTimeReceiver } : {}),
+      ...(tx.deployStep !== undefined ? { deployStep: tx.deployStep } : {}),
+      ...(tx.summaryBlocks !== undefined ? { summaryBlocks: tx.summaryBlocks } : {}),
+      ...(tx.summarySpeech !== undefined ? { summarySpeech: tx.summarySpeech } : {}),
+      ...(tx.redactions !== undefined ? { redactions: tx.redactions } : {}),
+      ...(tx.truncations !== undefined ? { truncations: tx.truncations } : {}),
     }));
     const uiDigest = await computeUiIntentDigestFromTxs(normalized);
     if (uiDigest !== expected) return 'INTENT_DIGEST_MISMATCH';
@@ -274,6 +288,7 @@ function mountHostElement({
   el.variant = v;
   el.nearAccountId = nearAccountIdOverride || ctx.userPreferencesManager.getCurrentUserAccountId() || '';
   el.txSigningRequests = txSigningRequests || [];
+  if (summary?.signingMessage !== undefined) el.signingMessage = summary.signingMessage;
   // Only enable UI digest validation for transaction-signing flows where txs exist.
   // Registration/link and other non-tx flows should not set intentDigest to avoid
   // spurious INTENT_DIGEST_MISMATCH on confirm.
@@ -0,0 +1,188 @@
+// === CBOR WIRE FORMAT ===
+// Minimal CBOR (RFC 8949) codec for signer worker frames when the worker is initialized
+// with wireFormat 'cbor'. Covers the subset the Rust side (ciborium) produces and accepts:
+// integers, floats, text, byte strings, arrays, maps with text keys, booleans and null.
+// Uint8Array values are encoded as byte strings; the worker decodes them as number arrays.
+
+export const WIRE_FORMAT_MISMATCH = 'WireFormatMismatch';
+
+export function encodeCbor(value: unknown): Uint8Array {
+  const out: number[] = [];
+  writeValue(out, value);
+  return Uint8Array.from(out);
+}
+
+export function decodeCbor(bytes: Uint8Array): unknown {
+  const reader = { bytes, view: new DataView(bytes.buffer, bytes.byteOffset, bytes.byteLength), offset: 0 };
+  const value = readValue(reader);
+  if (reader.offset !== bytes.length) {
+    throw new Error(`CBOR frame has ${bytes.length - reader.offset} trailing bytes`);
+  }
+  return value;
+}
+
+function writeHead(out: number[], major: number, length: number): void {
+  const m = major << 5;
+  if (length < 24) {
+    out.push(m | length);
+  } else if (length < 0x100) {
+    out.push(m | 24, length);
+  } else if (length < 0x10000) {
+    out.push(m | 25, length >> 8, length & 0xff);
+  } else if (length < 0x100000000) {
+    out.push(m | 26, (length >>> 24) & 0xff, (length >>> 16) & 0xff, (length >>> 8) & 0xff, length & 0xff);
+  } else {
+    const high = Math.floor(length / 0x100000000);
+    out.push(m | 27);
+    writeHead32(out, high);
+    writeHead32(out, length >>> 0);
+  }
+}
+
+function writeHead32(out: number[], value: number): void {
+  out.push((value >>> 24) & 0xff, (value >>> 16) & 0xff, (value >>> 8) & 0xff, value & 0xff);
+}
+
+function writeValue(out: number[], value: unknown): void {
+  if (value === null || value === undefined) {
+    out.push(0xf6);
+  } else if (value === false) {
+    out.push(0xf4);
+  } else if (value === true) {
+    out.push(0xf5);
+  } else if (typeof value === 'number') {
+    if (Number.isSafeInteger(value)) {
+      value >= 0 ? writeHead(out, 0, value) : writeHead(out, 1, -1 - value);
+    } else {
+      const buf = new DataView(new ArrayBuffer(8));
+      buf.setFloat64(0, value);
+      out.push(0xfb, ...new Uint8Array(buf.buffer));
+    }
+  } else if (typeof value === 'string') {
+    const utf8 = new TextEncoder().encode(value);
+    writeHead(out, 3, utf8.length);
+    out.push(...utf8);
+  } else if (value instanceof Uint8Array) {
+    writeHead(out, 2, value.length);
+    for (const b of value) out.push(b);
+  } else if (Array.isArray(value)) {
+    writeHead(out, 4, value.length);
+    for (const item of value) writeValue(out, item);
+  } else if (typeof value === 'object') {
+    // Drop undefined fields, as JSON.stringify does
+    const entries = Object.entries(value as Record<string, unknown>).filter(([, v]) => v !== undefined);
+    writeHead(out, 5, entries.length);
+    for (const [key, item] of entries) {
+      writeValue(out, key);
+      writeValue(out, item);
+    }
+  } else {
+    throw new Error(`Cannot CBOR-encode value of type ${typeof value}`);
+  }
+}
+
+interface Reader {
+  bytes: Uint8Array;
+  view: DataView;
+  offset: number;
+}
+
+function need(reader: Reader, n: number): void {
+  if (reader.offset + n > reader.bytes.length) {
+    throw new Error('CBOR frame is truncated');
+  }
+}
+
+function readLength(reader: Reader, info: number): number {
+  if (info < 24) return info;
+  const { view } = reader;
+  switch (info) {
+    case 24: need(reader, 1); return view.getUint8(reader.offset++);
+    case 25: need(reader, 2); reader.offset += 2; return view.getUint16(reader.offset - 2);
+    case 26: need(reader, 4); reader.offset += 4; return view.getUint32(reader.offset - 4);
+    case 27: {
+      need(reader, 8);
+      const value = view.getBigUint64(reader.offset);
+      reader.offset += 8;
+      if (value > BigInt(Number.MAX_SAFE_INTEGER)) {
+        throw new Error('CBOR integer exceeds Number.MAX_SAFE_INTEGER');
+      }
+      return Number(value);
+    }
+    default:
+      throw new Error(`Unsupported CBOR length encoding: ${info}`);
+  }
+}
+
+function readFloat16(bits: number): number {
+  const exponent = (bits >> 10) & 0x1f;
+  const fraction = bits & 0x3ff;
+  const sign = bits & 0x8000 ? -1 : 1;
+  if (exponent === 0) return sign * 2 ** -14 * (fraction / 1024);
+  if (exponent === 0x1f) return fraction ? NaN : sign * Infinity;
+  return sign * 2 ** (exponent - 15) * (1 + fraction / 1024);
+}
+
+function readValue(reader: Reader): unknown {
+  need(reader, 1);
+  const initial = reader.bytes[reader.offset++];
+  const major = initial >> 5;
+  const info = initial & 0x1f;
+
+  switch (major) {
+    case 0:
+      return readLength(reader, info);
+    case 1:
+      return -1 - readLength(reader, info);
+    case 2: {
+      const length = readLength(reader, info);
+      need(reader, length);
+      const bytes = reader.bytes.slice(reader.offset, reader.offset + length);
+      reader.offset += length;
+      return bytes;
+    }
+    case 3: {
+      const length = readLength(reader, info);
+      need(reader, length);
+      const text = new TextDecoder().decode(reader.bytes.subarray(reader.offset, reader.offset + length));
+      reader.offset += length;
+      return text;
+    }
+    case 4: {
+      const length = readLength(reader, info);
+      const items: unknown[] = [];
+      for (let i = 0; i < length; i++) items.push(readValue(reader));
+      return items;
+    }
+    case 5: {
+      const length = readLength(reader, info);
+      const map: Record<string, unknown> = {};
+      for (let i = 0; i < length; i++) {
+        const key = readValue(reader);
+        if (typeof key !== 'string') {
+          throw new Error('CBOR map keys must be text');
+        }
+        map[key] = readValue(reader);
+      }
+      return map;
+    }
+    case 6:
+      // Tags carry no meaning for worker frames; return the tagged value
+      readLength(reader, info);
+      return readValue(reader);
+    default: {
+      const { view } = reader;
+      switch (info) {
+        case 20: return false;
+        case 21: return true;
+        case 22: return null;
+        case 23: return undefined;
+        case 25: need(reader, 2); reader.offset += 2; return readFloat16(view.getUint16(reader.offset - 2));
+        case 26: need(reader, 4); reader.offset += 4; return view.getFloat32(reader.offset - 4);
+        case 27: need(reader, 8); reader.offset += 8; return view.getFloat64(reader.offset - 8);
+        default:
+          throw new Error(`Unsupported CBOR simple value: ${info}`);
+      }
+    }
+  }
+}
@@ -1,4 +1,7 @@
 import {
+  CountdownReport,
+  CredentialCollectionError,
+  RenderAttestation,
   WorkerConfirmationResponse,
   SecureConfirmMessageType,
   SecureConfirmRequest,
@@ -11,15 +14,26 @@ import { TransactionContext } from '../../../types/rpc';
 
 // Narrowing helpers now use shared validator isObject
 
+// Mirrors COUNTDOWN_ACK_TIMEOUT_MS in config.rs
+const COUNTDOWN_ACK_TIMEOUT_MS = 2000;
+
 type ConfirmResponsePayload = {
   requestId: string;
   confirmed: boolean;
   intentDigest?: string;
   credential?: SerializableCredential;
   prfOutput?: string;
+  prfSupported?: boolean;
+  credentialCollectedAtMs?: number;
   vrfChallenge?: VRFChallenge;
   transactionContext?: TransactionContext;
+  reservedNonces?: string[];
+  collectionError?: CredentialCollectionError;
   error?: string;
+  errorCode?: string;
+  // PRF output and credential PRF results sealed to the worker's session key
+  sealedSecrets?: Record<string, string>;
+  renderAttestation?: RenderAttestation;
 };
 
 type ConfirmResponseEnvelope = {
@@ -37,6 +51,46 @@ function isConfirmResponseEnvelope(msg: unknown): msg is ConfirmResponseEnvelope
   return isString(d.requestId) && isBoolean(d.confirmed);
 }
 
+// Countdown handshake messages from the main thread for `requestId`
+function handshakeMessage(msg: unknown, requestId: string): { type: SecureConfirmMessageType; displayedDigest?: string } | undefined {
+  if (!isObject(msg)) return undefined;
+  const type = (msg as { type?: unknown }).type;
+  if (
+    type !== SecureConfirmMessageType.COUNTDOWN_STARTED
+    && type !== SecureConfirmMessageType.CANCEL_CONFIRMATION
+    && type !== SecureConfirmMessageType.CONFIRMATION_BEHAVIOR_CHANGED
+  ) return undefined;
+  const data = (msg as { data?: unknown }).data;
+  if (!isObject(data) || (data as { requestId?: unknown }).requestId !== requestId) return undefined;
+  const displayedDigest = (data as { displayedDigest?: unknown }).displayedDigest;
+  return { type, displayedDigest: isString(displayedDigest) ? displayedDigest : undefined };
+}
+
+// This worker's end of the confirmation origin bridge (confirmationOriginBridge.ts), posted
+// before the request when the manager has a trusted confirmation origin
+let confirmationPort: MessagePort | undefined;
+
+export function setConfirmationPort(port: MessagePort | undefined): void {
+  confirmationPort = port;
+  port?.start();
+}
+
+// A decision's render attestation, forwarded verbatim when the decision arrived over the
+// confirmation origin bridge and dropped otherwise (see confirmation_origin.rs). A worker sees
+// no sender origin on messages from the main thread, so only the bridge, which attests the
+// origins the browser reports, can vouch for one.
+function bridgedRenderAttestation(messageEvent: MessageEvent, attestation: unknown): RenderAttestation | undefined {
+  if (!confirmationPort || messageEvent.target !== confirmationPort || !isObject(attestation)) return undefined;
+  return attestation as unknown as RenderAttestation;
+}
+
+// Auto-proceed delay when the request shows a countdown (modal/drawer + autoProceed)
+function countdownDelayMs(request: SecureConfirmRequest): number | undefined {
+  const cfg = request.confirmationConfig;
+  if (!cfg || cfg.uiMode === 'skip' || cfg.behavior !== 'autoProceed') return undefined;
+  return cfg.autoProceedDelay ?? 0;
+}
+
 /**
  * Bridge function called from Rust to await user confirmation on the main thread
  *
@@ -70,11 +124,24 @@ export function awaitSecureConfirmationV2(
       return reject(new Error(`[signer-worker]: invalid V2 request JSON: ${errorMessage(e)}`));
     }
 
+    // Auto-proceed countdown handshake: the decision is released only after the UI acked
+    // COUNTDOWN_STARTED and this worker's own timer elapsed (see countdown_handshake.rs)
+    const countdownDelay = countdownDelayMs(request);
+    const countdown: CountdownReport | undefined = countdownDelay === undefined
+      ? undefined
+      : { prompted_at_ms: Date.now(), require_click: false, released_at_ms: 0 };
+    let ackTimeoutId: ReturnType<typeof setTimeout> | undefined;
+    let releaseTimeoutId: ReturnType<typeof setTimeout> | undefined;
+    let heldResponse: WorkerConfirmationResponse | undefined;
+
     // 2) Setup cleanup utilities
     let timeoutId: ReturnType<typeof setTimeout> | undefined;
     const cleanup = () => {
       try { if (timeoutId) clearTimeout(timeoutId); } catch {}
+      try { if (ackTimeoutId) clearTimeout(ackTimeoutId); } catch {}
+      try { if (releaseTimeoutId) clearTimeout(releaseTimeoutId); } catch {}
       try { self.removeEventListener('message', onDecisionReceived); } catch {}
+      try { confirmationPort?.removeEventListener('message', onDecisionReceived); } catch {}
       if (opts.signal) {
         try { opts.signal.removeEventListener('abort', onAbort); } catch {}
       }
@@ -85,25 +152,92 @@ export function awaitSecureConfirmationV2(
       reject(new Error('[signer-worker]: confirmation aborted'));
     };
 
+    const release = (response: WorkerConfirmationResponse) => {
+      cleanup();
+      if (countdown) {
+        countdown.released_at_ms = Date.now();
+        response.countdown = { ...countdown };
+      }
+      return resolve(response);
+    };
+
+    const switchToRequireClick = (notifyMainThread: boolean) => {
+      if (!countdown || countdown.require_click || countdown.started_at_ms !== undefined) return;
+      countdown.require_click = true;
+      if (notifyMainThread) {
+        try {
+          self.postMessage({
+            type: SecureConfirmMessageType.CONFIRMATION_BEHAVIOR_CHANGED,
+            data: { requestId: request.requestId, behavior: 'requireClick' },
+          });
+        } catch {}
+      }
+    };
+
+    const onHandshakeMessage = (type: SecureConfirmMessageType, displayedDigest?: string) => {
+      if (!countdown) return;
+      switch (type) {
+        case SecureConfirmMessageType.COUNTDOWN_STARTED:
+          // Only the first ack before the deadline starts the timer
+          if (countdown.started_at_ms !== undefined || countdown.require_click) return;
+          countdown.started_at_ms = Date.now();
+          countdown.displayed_digest = displayedDigest;
+          if (ackTimeoutId) clearTimeout(ackTimeoutId);
+          return;
+        case SecureConfirmMessageType.CANCEL_CONFIRMATION:
+          if (countdown.cancelled_at_ms === undefined) countdown.cancelled_at_ms = Date.now();
+          if (heldResponse) release({ ...heldResponse, confirmed: false, error_code: 'UserDeclined' });
+          return;
+        case SecureConfirmMessageType.CONFIRMATION_BEHAVIOR_CHANGED:
+          switchToRequireClick(false);
+          return;
+      }
+    };
+
     // 3) Wait for matching decision
     const onDecisionReceived = (messageEvent: MessageEvent) => {
       const env = messageEvent?.data as unknown;
+      const handshake = handshakeMessage(env, request.requestId);
+      if (handshake) return onHandshakeMessage(handshake.type, handshake.displayedDigest);
       if (!isConfirmResponseEnvelope(env)) return;
       if (env.data.requestId !== request.requestId) return;
-      cleanup();
+      if (heldResponse) return;
       const response: WorkerConfirmationResponse = {
         request_id: request.requestId,
         intent_digest: env.data.intentDigest,
         confirmed: env.data.confirmed,
         credential: env.data.credential,
         prf_output: env.data.prfOutput,
+        prf_supported: env.data.prfSupported,
+        credential_collected_at_ms: env.data.credentialCollectedAtMs,
         vrf_challenge: env.data.vrfChallenge,
         transaction_context: env.data.transactionContext,
-        error: env.data.error
+        reserved_nonces: env.data.reservedNonces,
+        collection_error: env.data.collectionError,
+        error: env.data.error,
+        error_code: env.data.errorCode,
+        sealed_secrets: env.data.sealedSecrets,
+        render_attestation: bridgedRenderAttestation(messageEvent, env.data.renderAttestation),
       };
-      return resolve(response);
+      // Hold a decision that arrives while the countdown is still running
+      const releaseAtMs = countdown?.started_at_ms !== undefined && !countdown.require_click
+        ? countdown.started_at_ms + (countdownDelay ?? 0)
+        : undefined;
+      const remainingMs = releaseAtMs !== undefined ? releaseAtMs - Date.now() : 0;
+      if (remainingMs > 0 && response.confirmed) {
+        heldResponse = response;
+        releaseTimeoutId = setTimeout(() => release(response), remainingMs);
+        return;
+      }
+      return release(response);
     };
     self.addEventListener('message', onDecisionReceived);
+    confirmationPort?.addEventListener('message', onDecisionReceived);
+
+    // Without an ack in time the UI is told to require a click instead
+    if (countdown) {
+      ackTimeoutId = setTimeout(() => switchToRequireClick(true), COUNTDOWN_ACK_TIMEOUT_MS);
+    }
 
     // Optional timeout / abort support
     if (opts.timeoutMs && opts.timeoutMs > 0) {
@@ -0,0 +1,110 @@
+import { SIGNER_WORKER_CONNECT_CONFIRMATION_PORT } from '../../../types/signer-worker';
+import { isObject, isString } from '../../../WalletIframe/validation';
+import { SecureConfirmMessageType, type SecureConfirmRequest } from './types';
+
+// === CONFIRMATION ORIGIN BRIDGE ===
+// A render attestation (see confirmation_origin.rs) names the origin of the document that
+// rendered the prompt. A decision cannot vouch for that itself: whoever posts it to the worker
+// can write any origin into it, and a worker sees no origin on messages from the main thread.
+// Only the browser can: it sets MessageEvent.origin on a postMessage between windows, and
+// location.origin on a document. So attestations are made here, on the main thread:
+//   - decisions the wallet iframe posts to this window are attested with the event's origin,
+//     when it is the trusted one and the event was dispatched by the browser;
+//   - decisions rendered in this document are attested with its own origin, when it is the
+//     trusted one (this document is the wallet iframe).
+// Attested decisions reach the worker over a MessagePort of its own; the worker drops render
+// attestations arriving any other way (awaitSecureConfirmation.ts).
+
+type DecisionEnvelope = {
+  type: SecureConfirmMessageType.USER_PASSKEY_CONFIRM_RESPONSE;
+  data: { requestId: string; renderAttestation?: unknown };
+};
+
+interface Bridge {
+  port: MessagePort;
+  trustedOrigin: string;
+  dispose: () => void;
+}
+
+// Bridges of workers with a trusted confirmation origin
+const bridges = new WeakMap<Worker, Bridge>();
+
+// Render nonces of prompts rendered in this document and awaiting a decision, by requestId
+const renderNonces = new Map<string, string>();
+
+function isDecisionEnvelope(msg: unknown): msg is DecisionEnvelope {
+  if (!isObject(msg)) return false;
+  if ((msg as { type?: unknown }).type !== SecureConfirmMessageType.USER_PASSKEY_CONFIRM_RESPONSE) return false;
+  const data = (msg as { data?: unknown }).data;
+  return isObject(data) && isString((data as { requestId?: unknown }).requestId);
+}
+
+function attest<T extends { requestId: string }>(decision: T, renderNonce: unknown, origin: string) {
+  const { renderAttestation: _, ...rest } = decision as T & { renderAttestation?: unknown };
+  if (!isString(renderNonce)) return rest;
+  return { ...rest, renderAttestation: { renderNonce, origin } };
+}
+
+/**
+ * Connects `worker` to the bridge for the trusted confirmation origin: decisions that a window
+ * at that origin posts to `target` as `{ type: USER_PASSKEY_CONFIRM_RESPONSE, data }`, with the
+ * prompt's nonce in `data.renderAttestation.renderNonce`, are forwarded to the worker attested
+ * with the sender's origin. Messages from any other origin are not forwarded.
+ */
+export function connectConfirmationOriginBridge(worker: Worker, trustedOrigin: string, target: Window = window): void {
+  // Normalized like the worker's configure_trusted_confirmation_origin (scheme://host[:port])
+  const origin = new URL(trustedOrigin).origin;
+  const channel = new MessageChannel();
+  const onMessage = (event: MessageEvent) => {
+    // A synthetic event (dispatchEvent) can name any origin; only the browser's are believed
+    if (!event.isTrusted || event.origin !== origin || !isDecisionEnvelope(event.data)) return;
+    const renderNonce = (event.data.data.renderAttestation as { renderNonce?: unknown } | undefined)?.renderNonce;
+    channel.port1.postMessage({
+      type: SecureConfirmMessageType.USER_PASSKEY_CONFIRM_RESPONSE,
+      data: attest(event.data.data, renderNonce, event.origin),
+    });
+  };
+  target.addEventListener('message', onMessage);
+  worker.postMessage({ type: SIGNER_WORKER_CONNECT_CONFIRMATION_PORT }, [channel.port2]);
+  bridges.set(worker, {
+    port: channel.port1,
+    trustedOrigin: origin,
+    dispose: () => {
+      target.removeEventListener('message', onMessage);
+      channel.port1.close();
+    },
+  });
+}
+
+/** Stops forwarding decisions to `worker` (once it is terminated) */
+export function disconnectConfirmationOriginBridge(worker: Worker): void {
+  bridges.get(worker)?.dispose();
+  bridges.delete(worker);
+}
+
+/** Remembers a prompt's renderNonce so that its decision can attest it */
+export function rememberRenderNonce(request: SecureConfirmRequest): void {
+  if (isString(request?.requestId) && isString(request?.renderNonce)) {
+    renderNonces.set(request.requestId, request.renderNonce);
+  }
+}
+
+/**
+ * Posts a decision rendered in this document to `worker`. Over the worker's bridge it is
+ * attested with this document's origin when that is the trusted one, and goes without an
+ * attestation otherwise.
+ */
+export function postConfirmationDecision<T extends { requestId: string }>(worker: Worker, decision: T): void {
+  const renderNonce = renderNonces.get(decision.requestId);
+  renderNonces.delete(decision.requestId);
+  const bridge = bridges.get(worker);
+  if (!bridge) {
+    worker.postMessage({ type: SecureConfirmMessageType.USER_PASSKEY_CONFIRM_RESPONSE, data: decision });
+    return;
+  }
+  const origin = window.location.origin;
+  bridge.port.postMessage({
+    type: SecureConfirmMessageType.USER_PASSKEY_CONFIRM_RESPONSE,
+    data: origin === bridge.trustedOrigin ? attest(decision, renderNonce, origin) : decision,
+  });
+}
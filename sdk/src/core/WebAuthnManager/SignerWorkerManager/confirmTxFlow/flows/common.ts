@@ -1,6 +1,7 @@
 import type { SignerWorkerManagerContext } from '../../index';
 import type { ConfirmationConfig, ConfirmationUIMode } from '../../../../types/signer-worker';
 import {
+  SecureConfirmMessageType,
   SecureConfirmRequest,
   SecureConfirmationType,
   SignTransactionPayload,
@@ -30,6 +31,7 @@ export function classifyFlow(request: SecureConfirmRequest): FlowKind {
       return 'Registration';
     case SecureConfirmationType.SIGN_TRANSACTION:
     case SecureConfirmationType.SIGN_NEP413_MESSAGE:
+    case SecureConfirmationType.EVM_PERSONAL_SIGN:
       return 'Signing';
     default:
       // Explicitly mark any unknown/unsupported type
@@ -53,6 +55,9 @@ export function getNearAccountId(request: SecureConfirmRequest): string {
       const p = request.payload as { nearAccountId?: string };
       return p?.nearAccountId || '';
     }
+    case SecureConfirmationType.SIGN_NEP413_MESSAGE:
+    case SecureConfirmationType.EVM_PERSONAL_SIGN:
+      return (request.payload as { nearAccountId?: string })?.nearAccountId || '';
     default:
       return '';
   }
@@ -158,11 +163,14 @@ export async function maybeRefreshVrfChallenge(
           },
           saveInMemory: true,
         })).vrfChallenge
-      : await vrfWorkerManager.generateVrfChallenge({
+      : await vrfWorkerManager.generateSigningVrfChallenge({
           userId: nearAccountId,
           rpId,
           blockHeight: latestCtx.txBlockHeight,
           blockHash: latestCtx.txBlockHash,
+        }).then(({ vrfChallenge, timings }) => {
+          try { console.debug('[SecureConfirm] VRF challenge timings', timings); } catch {}
+          return vrfChallenge;
         });
 
     return { vrfChallenge, transactionContext: latestCtx };
@@ -208,12 +216,15 @@ export async function renderConfirmUI({
   confirmationConfig,
   transactionSummary,
   vrfChallenge,
+  worker,
 }: {
   ctx: SignerWorkerManagerContext,
   request: SecureConfirmRequest,
   confirmationConfig: ConfirmationConfig,
   transactionSummary: TransactionSummary,
   vrfChallenge: VRFChallenge;
+  /** Receives the auto-proceed countdown handshake */
+  worker: Worker;
 }): Promise<{ confirmed: boolean; confirmHandle?: ConfirmUIHandle; error?: string }> {
   const nearAccountIdForUi = getNearAccountId(request);
   try { console.debug('[RenderConfirmUI] start', {
@@ -263,21 +274,15 @@ export async function renderConfirmUI({
     case 'drawer': {
       if (confirmationConfig.behavior === 'autoProceed') {
         try { console.debug('[RenderConfirmUI] drawer + autoProceed'); } catch {}
-        const handle = await mountConfirmUI({
+        return await runAutoProceedCountdown({
           ctx,
-          summary: transactionSummary,
-          txSigningRequests: request.type === SecureConfirmationType.SIGN_TRANSACTION
-            ? (request.payload as SignTransactionPayload).txSigningRequests
-            : [],
+          request,
+          confirmationConfig,
+          transactionSummary,
           vrfChallenge,
-          loading: true,
-          theme: confirmationConfig.theme,
           uiMode: 'drawer',
-          nearAccountIdOverride: nearAccountIdForUi,
+          worker,
         });
-        const delay = confirmationConfig.autoProceedDelay ?? 0;
-        await new Promise((r) => setTimeout(r, delay));
-        return { confirmed: true, confirmHandle: handle };
       } else {
         try { console.debug('[RenderConfirmUI] drawer + requireClick'); } catch {}
         const { confirmed, handle, error } = await awaitConfirmUIDecision({
@@ -298,21 +303,15 @@ export async function renderConfirmUI({
     case 'modal': {
       if (confirmationConfig.behavior === 'autoProceed') {
         try { console.debug('[RenderConfirmUI] modal + autoProceed'); } catch {}
-        const handle = await mountConfirmUI({
+        return await runAutoProceedCountdown({
           ctx,
-          summary: transactionSummary,
-          txSigningRequests: request.type === SecureConfirmationType.SIGN_TRANSACTION
-            ? (request.payload as SignTransactionPayload).txSigningRequests
-            : [],
+          request,
+          confirmationConfig,
+          transactionSummary,
           vrfChallenge,
-          loading: true,
-          theme: confirmationConfig.theme,
           uiMode: 'modal',
-          nearAccountIdOverride: nearAccountIdForUi,
+          worker,
         });
-        const delay = confirmationConfig.autoProceedDelay ?? 0;
-        await new Promise((r) => setTimeout(r, delay));
-        return { confirmed: true, confirmHandle: handle };
       } else {
         try { console.debug('[RenderConfirmUI] modal + requireClick'); } catch {}
         const { confirmed, handle, error } = await awaitConfirmUIDecision({
@@ -349,9 +348,113 @@ export async function renderConfirmUI({
   }
 }
 
+// ===== Auto-proceed countdown handshake (see countdown_handshake.rs) =====
+// The worker releases an auto-proceed decision only after COUNTDOWN_STARTED for the displayed
+// digest and its own timer; CANCEL_CONFIRMATION declines. When the worker gets no ack in time it
+// sends CONFIRMATION_BEHAVIOR_CHANGED, and the UI switches to requireClick.
+const requireClickListeners = new Map<string, () => void>();
+
+/** The worker asked the UI of `requestId` to require a click instead of auto-proceeding */
+export function notifyConfirmationBehaviorChanged(requestId: string): void {
+  requireClickListeners.get(requestId)?.();
+}
+
+export function postCountdownMessage(
+  worker: Worker,
+  type: SecureConfirmMessageType.COUNTDOWN_STARTED | SecureConfirmMessageType.CANCEL_CONFIRMATION | SecureConfirmMessageType.CONFIRMATION_BEHAVIOR_CHANGED,
+  data: { requestId: string; displayedDigest?: string; behavior?: 'requireClick' },
+): void {
+  try { worker.postMessage({ type, data }); } catch (e) {
+    console.warn('[SecureConfirm] countdown handshake postMessage failed', e);
+  }
+}
+
+async function runAutoProceedCountdown({
+  ctx,
+  request,
+  confirmationConfig,
+  transactionSummary,
+  vrfChallenge,
+  uiMode,
+  worker,
+}: {
+  ctx: SignerWorkerManagerContext,
+  request: SecureConfirmRequest,
+  confirmationConfig: ConfirmationConfig,
+  transactionSummary: TransactionSummary,
+  vrfChallenge: VRFChallenge;
+  uiMode: 'modal' | 'drawer';
+  worker: Worker;
+}): Promise<{ confirmed: boolean; confirmHandle?: ConfirmUIHandle; error?: string }> {
+  const nearAccountIdForUi = getNearAccountId(request);
+  const txSigningRequests = request.type === SecureConfirmationType.SIGN_TRANSACTION
+    ? (request.payload as SignTransactionPayload).txSigningRequests
+    : [];
+  let settled = false;
+  let resolveOutcome: (outcome: 'elapsed' | 'cancelled' | 'requireClick') => void = () => {};
+  const outcome = new Promise<'elapsed' | 'cancelled' | 'requireClick'>((resolve) => {
+    resolveOutcome = (o) => { if (!settled) { settled = true; resolve(o); } };
+  });
+  requireClickListeners.set(request.requestId, () => resolveOutcome('requireClick'));
+  try {
+    const handle = await mountConfirmUI({
+      ctx,
+      summary: transactionSummary,
+      txSigningRequests,
+      vrfChallenge,
+      loading: true,
+      theme: confirmationConfig.theme,
+      uiMode,
+      nearAccountIdOverride: nearAccountIdForUi,
+      onCancel: () => resolveOutcome('cancelled'),
+    });
+    // The countdown is on screen: start the worker's timer
+    postCountdownMessage(worker, SecureConfirmMessageType.COUNTDOWN_STARTED, {
+      requestId: request.requestId,
+      displayedDigest: transactionSummary.intentDigest,
+    });
+    const delay = autoProceedDelayMs(confirmationConfig, transactionSummary);
+    const timer = setTimeout(() => resolveOutcome('elapsed'), delay);
+
+    switch (await outcome) {
+      case 'elapsed':
+        return { confirmed: true, confirmHandle: handle };
+      case 'cancelled':
+        clearTimeout(timer);
+        postCountdownMessage(worker, SecureConfirmMessageType.CANCEL_CONFIRMATION, { requestId: request.requestId });
+        return { confirmed: false, confirmHandle: handle };
+      case 'requireClick': {
+        clearTimeout(timer);
+        try { console.debug('[RenderConfirmUI] worker requested requireClick'); } catch {}
+        try { handle.close(false); } catch {}
+        const { confirmed, handle: clickHandle, error } = await awaitConfirmUIDecision({
+          ctx,
+          summary: transactionSummary,
+          txSigningRequests,
+          vrfChallenge,
+          theme: confirmationConfig.theme,
+          uiMode,
+          nearAccountIdOverride: nearAccountIdForUi,
+        });
+        return { confirmed, confirmHandle: clickHandle, error };
+      }
+    }
+  } finally {
+    requireClickListeners.delete(request.requestId);
+  }
+}
+
 // ===== Summary parsing =====
 export interface SummaryType { totalAmount?: string; method?: string }
 
+// Auto-proceed waits at most until the confirmation deadline; later confirmations are rejected
+function autoProceedDelayMs(confirmationConfig: ConfirmationConfig, transactionSummary: TransactionSummary): number {
+  const delay = confirmationConfig.autoProceedDelay ?? 0;
+  const expiresAt = transactionSummary?.confirmationExpiresAtMs;
+  if (typeof expiresAt !== 'number') return delay;
+  return Math.max(0, Math.min(delay, expiresAt - Date.now()));
+}
+
 export function parseTransactionSummary(summaryData: unknown): SummaryType {
   if (!summaryData) return {};
   if (isString(summaryData)) {
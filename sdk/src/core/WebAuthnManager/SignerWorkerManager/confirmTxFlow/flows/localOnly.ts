@@ -2,13 +2,14 @@ import type { SignerWorkerManagerContext } from '../../index';
 import type { ConfirmationConfig } from '../../../../types/signer-worker';
 import {
   SecureConfirmationType,
-  SecureConfirmMessageType,
   TransactionSummary,
   LocalOnlySecureConfirmRequest,
 } from '../types';
 import { VRFChallenge } from '../../../../types';
 import { createRandomVRFChallenge } from '../../../../types/vrf-worker';
 import { renderConfirmUI, getNearAccountId, getIntentDigest, sanitizeForPostMessage } from './common';
+import { postConfirmationDecision } from '../confirmationOriginBridge';
+import { sealConfirmationSecrets } from '../../sealedSecrets';
 import { toAccountId } from '../../../../types/accountIds';
 import { authenticatorsToAllowCredentials } from '../../../touchIdPrompt';
 import { extractPrfFromCredential, serializeAuthenticationCredentialWithPRF } from '../../../credentialsHelpers';
@@ -32,6 +33,7 @@ export async function handleLocalOnlyFlow(
     confirmationConfig,
     transactionSummary,
     vrfChallenge,
+    worker,
   });
 
   // SHOW_SECURE_PRIVATE_KEY_UI: purely visual; keep UI open and return confirmed immediately
@@ -64,6 +66,7 @@ export async function handleLocalOnlyFlow(
         challenge: vrfChallenge,
         allowCredentials: authenticatorsToAllowCredentials(authenticators),
       });
+      const credentialCollectedAtMs = Date.now();
 
       const dualPrfOutputs = extractPrfFromCredential({
         credential,
@@ -83,6 +86,7 @@ export async function handleLocalOnlyFlow(
         confirmed: true,
         credential: serialized,
         prfOutput: dualPrfOutputs.chacha20PrfOutput,
+        credentialCollectedAtMs,
       });
     } catch (err: unknown) {
       const cancelled = isTouchIdCancellationError(err) || (() => {
@@ -105,9 +109,8 @@ export async function handleLocalOnlyFlow(
   }
 }
 
-function send(worker: Worker, response: any) {
-  const sanitized = sanitizeForPostMessage(response);
-  worker.postMessage({ type: SecureConfirmMessageType.USER_PASSKEY_CONFIRM_RESPONSE, data: sanitized });
+async function send(worker: Worker, response: any) {
+  postConfirmationDecision(worker, sanitizeForPostMessage(await sealConfirmationSecrets(worker, response)));
 }
 
 function closeModalSafely(confirmed: boolean, handle?: ConfirmUIHandle) {
@@ -2,14 +2,22 @@ import type { SignerWorkerManagerContext } from '../../index';
 import type { ConfirmationConfig } from '../../../../types/signer-worker';
 import {
   SecureConfirmationType,
-  SecureConfirmMessageType,
   TransactionSummary,
   RegistrationSecureConfirmRequest,
   RegisterAccountPayload,
 } from '../types';
 import { VRFChallenge, TransactionContext } from '../../../../types';
 import { renderConfirmUI, fetchNearContext, maybeRefreshVrfChallenge, getNearAccountId, getIntentDigest, sanitizeForPostMessage } from './common';
-import { serializeRegistrationCredentialWithPRF, extractPrfFromCredential, isSerializedRegistrationCredential } from '../../../credentialsHelpers';
+import { postConfirmationDecision } from '../confirmationOriginBridge';
+import { sealConfirmationSecrets } from '../../sealedSecrets';
+import { buildCredentialCreationOptions } from '../../handlers/buildCredentialCreationOptions';
+import {
+  serializeRegistrationCredential,
+  serializeRegistrationCredentialWithPRF,
+  extractPrfFromCredential,
+  hasPrfResults,
+  isSerializedRegistrationCredential,
+} from '../../../credentialsHelpers';
 import type { WebAuthnRegistrationCredential } from '../../../../types/webauthn';
 import { toError } from '../../../../../utils/errors';
 import type { ConfirmUIHandle } from '../../../LitComponents/confirm-ui';
@@ -73,6 +81,7 @@ export async function handleRegistrationFlow(
     confirmationConfig,
     transactionSummary,
     vrfChallenge: uiVrfChallenge,
+    worker,
   });
   try { console.debug('[RegistrationFlow] renderConfirmUI done', { confirmed, uiError }); } catch {}
   if (!confirmed) {
@@ -101,13 +110,21 @@ export async function handleRegistrationFlow(
   // 5) Collect registration credentials (with duplicate retry)
   let credential: PublicKeyCredential | undefined;
   let deviceNumber = request.payload?.deviceNumber;
-  const tryCreate = async (dn?: number): Promise<PublicKeyCredential> => {
-    try { console.debug('[RegistrationFlow] navigator.credentials.create start', { deviceNumber: dn }); } catch {}
-    return await ctx.touchIdPrompt.generateRegistrationCredentialsInternal({
+  // The first attempt excludes the account's on-chain credentials (fetched by the worker); the
+  // duplicate retry registers a device-specific passkey alongside a synced one, so excludes none
+  const tryCreate = async (dn?: number, excludeRegistered = true): Promise<PublicKeyCredential> => {
+    const publicKey = await buildCredentialCreationOptions({
+      ctx,
       nearAccountId,
-      challenge: uiVrfChallenge,
       deviceNumber: dn,
+      vrfChallenge: uiVrfChallenge,
+      nonce: transactionContext.nextNonce,
+      blockHash: transactionContext.txBlockHash,
+      rpcCall: excludeRegistered ? request.payload?.rpcCall : undefined,
+      existingCredentials: excludeRegistered ? undefined : [],
     });
+    try { console.debug('[RegistrationFlow] navigator.credentials.create start', { deviceNumber: dn }); } catch {}
+    return await ctx.touchIdPrompt.createCredentialFromOptions(publicKey);
   };
   try {
     credential = await tryCreate(deviceNumber);
@@ -120,7 +137,7 @@ export async function handleRegistrationFlow(
     if (isDuplicate) {
       const nextDeviceNumber = (deviceNumber !== undefined && Number.isFinite(deviceNumber)) ? (deviceNumber + 1) : 2;
       try { console.debug('[RegistrationFlow] duplicate credential, retry with next deviceNumber', { nextDeviceNumber }); } catch {}
-      credential = await tryCreate(nextDeviceNumber);
+      credential = await tryCreate(nextDeviceNumber, false);
       (request.payload as RegisterAccountPayload).deviceNumber = nextDeviceNumber;
     } else {
       try { console.error('[RegistrationFlow] credentials.create failed (non-duplicate)', { name, msg }); } catch {}
@@ -128,14 +145,18 @@ export async function handleRegistrationFlow(
     }
   }
 
-  const dualPrfOutputs = extractPrfFromCredential({ credential, firstPrfOutput: true, secondPrfOutput: true });
-  if (!dualPrfOutputs.chacha20PrfOutput) {
-    throw new Error('Failed to extract PRF output from credential');
-  }
+  // Authenticators without PRF still register; the worker applies WorkerPolicy.prfFallbackSchemes
+  // (or refuses with PrfUnsupportedByAuthenticator) when told prfSupported: false
+  const prfSupported = hasPrfResults(credential);
+  const dualPrfOutputs = prfSupported
+    ? extractPrfFromCredential({ credential, firstPrfOutput: true, secondPrfOutput: true })
+    : undefined;
   // Support parent-performed fallback that may already return serialized credential
   const serialized: WebAuthnRegistrationCredential = isSerializedRegistrationCredential(credential as unknown)
     ? (credential as unknown as WebAuthnRegistrationCredential)
-    : serializeRegistrationCredentialWithPRF({ credential, firstPrfOutput: true, secondPrfOutput: true });
+    : prfSupported
+      ? serializeRegistrationCredentialWithPRF({ credential, firstPrfOutput: true, secondPrfOutput: true })
+      : serializeRegistrationCredential(credential);
 
   // 6) Respond + close
   send(worker, {
@@ -143,16 +164,16 @@ export async function handleRegistrationFlow(
     intentDigest: getIntentDigest(request),
     confirmed: true,
     credential: serialized,
-    prfOutput: dualPrfOutputs.chacha20PrfOutput,
+    prfOutput: dualPrfOutputs?.chacha20PrfOutput,
+    prfSupported,
     vrfChallenge: uiVrfChallenge,
     transactionContext,
   });
   closeModalSafely(true, confirmHandle);
 }
 
-function send(worker: Worker, response: any) {
-  const sanitized = sanitizeForPostMessage(response);
-  worker.postMessage({ type: SecureConfirmMessageType.USER_PASSKEY_CONFIRM_RESPONSE, data: sanitized });
+async function send(worker: Worker, response: any) {
+  postConfirmationDecision(worker, sanitizeForPostMessage(await sealConfirmationSecrets(worker, response)));
 }
 
 function closeModalSafely(confirmed: boolean, handle?: ConfirmUIHandle) {
@@ -2,16 +2,27 @@ import type { SignerWorkerManagerContext } from '../../index';
 import type { ConfirmationConfig } from '../../../../types/signer-worker';
 import {
   SecureConfirmationType,
-  SecureConfirmMessageType,
   TransactionSummary,
   SigningSecureConfirmRequest,
+  ChallengeExpiryPolicy,
+  ChallengeExpiryEstimate,
 } from '../types';
 import { VRFChallenge, TransactionContext } from '../../../../types';
 import { renderConfirmUI, fetchNearContext, maybeRefreshVrfChallenge, getNearAccountId, getIntentDigest, getTxCount, sanitizeForPostMessage } from './common';
-import { serializeAuthenticationCredentialWithPRF, extractPrfFromCredential } from '../../../credentialsHelpers';
+import { postConfirmationDecision } from '../confirmationOriginBridge';
+import { sealConfirmationSecrets } from '../../sealedSecrets';
+import {
+  serializeAuthenticationCredentialWithPRF,
+  extractPrfFromCredential,
+  extractLargeBlobFromCredential,
+  serializeAuthenticationCredential,
+  hasPrfResults,
+  withStoredCredentialPublicKey,
+} from '../../../credentialsHelpers';
 import { toAccountId } from '../../../../types/accountIds';
-import { authenticatorsToAllowCredentials } from '../../../touchIdPrompt';
+import { buildCredentialRequestOptions, toKnownAuthenticators } from '../../handlers/buildCredentialRequestOptions';
 import type { ConfirmUIHandle } from '../../../LitComponents/confirm-ui';
+import { toError } from '../../../../../utils/errors';
 
 export async function handleTransactionSigningFlow(
   ctx: SignerWorkerManagerContext,
@@ -19,7 +30,8 @@ export async function handleTransactionSigningFlow(
   worker: Worker,
   opts: { confirmationConfig: ConfirmationConfig; transactionSummary: TransactionSummary },
 ): Promise<void> {
-  const { confirmationConfig, transactionSummary } = opts;
+  const { confirmationConfig } = opts;
+  let transactionSummary = opts.transactionSummary;
   const nearAccountId = getNearAccountId(request);
 
   // 1) NEAR context + nonce reservation
@@ -33,16 +45,61 @@ export async function handleTransactionSigningFlow(
     });
   }
   let transactionContext = nearRpc.transactionContext as TransactionContext;
+  // Message signing sends no transaction: release the reservation at once
+  if (request.type !== SecureConfirmationType.SIGN_TRANSACTION && nearRpc.reservedNonces) {
+    try { nearRpc.reservedNonces.forEach(n => ctx.nonceManager.releaseNonce(n)); } catch {}
+    nearRpc.reservedNonces = undefined;
+  }
 
-  // 2) Initial VRF challenge
+  // 2) Initial VRF challenge: the one the worker got over its peer port, otherwise the
+  // VRF worker's prefetched one when still fresh
   if (!ctx.vrfWorkerManager) throw new Error('VrfWorkerManager not available');
   const rpId = ctx.touchIdPrompt.getRpId();
-  let uiVrfChallenge: VRFChallenge = await ctx.vrfWorkerManager.generateVrfChallenge({
-    userId: nearAccountId,
-    rpId,
-    blockHeight: transactionContext.txBlockHeight,
-    blockHash: transactionContext.txBlockHash,
-  });
+  const peerChallenge = (request.payload as { vrfChallenge?: VRFChallenge }).vrfChallenge;
+  let uiVrfChallenge: VRFChallenge;
+  if (peerChallenge) {
+    uiVrfChallenge = peerChallenge;
+  } else {
+    const initialChallenge = await ctx.vrfWorkerManager.generateSigningVrfChallenge({
+      userId: nearAccountId,
+      rpId,
+      blockHeight: transactionContext.txBlockHeight,
+      blockHash: transactionContext.txBlockHash,
+    });
+    uiVrfChallenge = initialChallenge.vrfChallenge;
+    try { console.debug('[SigningFlow] VRF challenge timings', initialChallenge.timings); } catch {}
+  }
+
+  // 2b) Challenge expiry (same policy the worker enforces); refuse to prompt when already expired
+  const { expiryPolicy, confirmationExpiresAtMs } = request.payload as {
+    expiryPolicy?: ChallengeExpiryPolicy;
+    confirmationExpiresAtMs?: number;
+  };
+  if (typeof confirmationExpiresAtMs === 'number') {
+    transactionSummary = { ...transactionSummary, confirmationExpiresAtMs };
+  }
+  if (request.type === SecureConfirmationType.EVM_PERSONAL_SIGN) {
+    transactionSummary = { ...transactionSummary, signingMessage: request.payload.message };
+  }
+  if (expiryPolicy) {
+    const expiry = estimateChallengeExpiry(
+      expiryPolicy,
+      Number(uiVrfChallenge.blockHeight),
+      Number(transactionContext.txBlockHeight),
+    );
+    if (!expiry) {
+      try { nearRpc.reservedNonces?.forEach(n => ctx.nonceManager.releaseNonce(n)); } catch {}
+      const ageBlocks = Number(transactionContext.txBlockHeight) - Number(uiVrfChallenge.blockHeight);
+      return send(worker, {
+        requestId: request.requestId,
+        intentDigest: getIntentDigest(request),
+        confirmed: false,
+        errorCode: 'ChallengeExpired',
+        error: `Challenge expired: issued at block ${uiVrfChallenge.blockHeight}, chain is at ${transactionContext.txBlockHeight} (${ageBlocks} blocks old, contract accepts ${expiryPolicy.acceptanceWindowBlocks})`,
+      });
+    }
+    transactionSummary = { ...transactionSummary, challengeExpiry: expiry };
+  }
 
   // 3) UI confirm
   const { confirmed, confirmHandle, error: uiError } = await renderConfirmUI({
@@ -51,6 +108,7 @@ export async function handleTransactionSigningFlow(
     confirmationConfig,
     transactionSummary,
     vrfChallenge: uiVrfChallenge,
+    worker,
   });
   if (!confirmed) {
     try { nearRpc.reservedNonces?.forEach(n => ctx.nonceManager.releaseNonce(n)); } catch {}
@@ -63,27 +121,75 @@ export async function handleTransactionSigningFlow(
     });
   }
 
-  // 4) JIT refresh VRF + ctx (best-effort)
-  try {
-    const refreshed = await maybeRefreshVrfChallenge(ctx, request, nearAccountId);
-    uiVrfChallenge = refreshed.vrfChallenge;
-    transactionContext = refreshed.transactionContext;
-    try { confirmHandle?.update?.({ vrfChallenge: uiVrfChallenge }); } catch {}
-  } catch (e) {
-    console.debug('[SigningFlow] VRF JIT refresh skipped', e);
+  // 3b) Confirmation window: the worker would reject a late confirmation, so stop before the passkey prompt
+  if (typeof confirmationExpiresAtMs === 'number' && Date.now() > confirmationExpiresAtMs) {
+    try { nearRpc.reservedNonces?.forEach(n => ctx.nonceManager.releaseNonce(n)); } catch {}
+    closeModalSafely(false, confirmHandle);
+    return send(worker, {
+      requestId: request.requestId,
+      intentDigest: getIntentDigest(request),
+      confirmed: false,
+      errorCode: 'ConfirmationExpired',
+      error: `Confirmation expired: confirmed ${Date.now() - confirmationExpiresAtMs}ms after the confirmation window closed`,
+    });
+  }
+
+  // 4) JIT refresh VRF + ctx (best-effort); a peer challenge is kept, the worker checks it comes back
+  if (!peerChallenge) {
+    try {
+      const refreshed = await maybeRefreshVrfChallenge(ctx, request, nearAccountId);
+      uiVrfChallenge = refreshed.vrfChallenge;
+      transactionContext = refreshed.transactionContext;
+      try { confirmHandle?.update?.({ vrfChallenge: uiVrfChallenge }); } catch {}
+    } catch (e) {
+      console.debug('[SigningFlow] VRF JIT refresh skipped', e);
+    }
   }
 
   // 5) Collect authentication credential
   const authenticators = await ctx.indexedDB.clientDB.getAuthenticatorsByUser(toAccountId(nearAccountId));
-  const credential = await ctx.touchIdPrompt.getAuthenticationCredentialsInternal({
-    nearAccountId,
-    challenge: uiVrfChallenge,
-    allowCredentials: authenticatorsToAllowCredentials(authenticators),
-  });
+  // The key blobs are this device's: allow only its passkey, whose PRF output unlocks them
+  const localDeviceNumber = (await ctx.indexedDB.clientDB.getUser(toAccountId(nearAccountId)))?.deviceNumber;
+  const deviceNumber = authenticators.some(a => a.deviceNumber === localDeviceNumber) ? localDeviceNumber : undefined;
+  let credential: PublicKeyCredential;
+  let credentialCollectedAtMs: number;
+  try {
+    const { publicKey } = await buildCredentialRequestOptions({
+      ctx,
+      nearAccountId,
+      vrfChallenge: uiVrfChallenge,
+      authenticators: toKnownAuthenticators(authenticators),
+      deviceNumber,
+    });
+    credential = await ctx.touchIdPrompt.getCredentialFromOptions(publicKey);
+    credentialCollectedAtMs = Date.now();
+  } catch (e: unknown) {
+    // User dismissed the passkey sheet or it timed out: let the worker release
+    // the request state and report UserDeclined/CredentialTimeout
+    const err = toError(e);
+    try { nearRpc.reservedNonces?.forEach(n => ctx.nonceManager.releaseNonce(n)); } catch {}
+    closeModalSafely(false, confirmHandle);
+    return send(worker, {
+      requestId: request.requestId,
+      intentDigest: getIntentDigest(request),
+      confirmed: false,
+      reservedNonces: nearRpc.reservedNonces,
+      collectionError: { name: err.name, message: err.message },
+      error: err.message,
+    });
+  }
 
-  const dualPrfOutputs = extractPrfFromCredential({ credential, firstPrfOutput: true, secondPrfOutput: false });
-  if (!dualPrfOutputs.chacha20PrfOutput) throw new Error('Failed to extract PRF output from credential');
-  const serialized = serializeAuthenticationCredentialWithPRF({ credential });
+  // Without PRF, the key blob is largeBlob-wrapped and the authenticator's largeBlob unlocks it
+  const largeBlobSecret = hasPrfResults(credential) ? undefined : extractLargeBlobFromCredential(credential);
+  const unlockSecret = largeBlobSecret
+    ?? extractPrfFromCredential({ credential, firstPrfOutput: true, secondPrfOutput: false }).chacha20PrfOutput;
+  if (!unlockSecret) throw new Error('Failed to extract PRF output from credential');
+  const serialized = withStoredCredentialPublicKey(
+    largeBlobSecret
+      ? serializeAuthenticationCredential(credential)
+      : serializeAuthenticationCredentialWithPRF({ credential }),
+    authenticators,
+  );
 
   // 6) Respond; keep nonces reserved for worker to use
   send(worker, {
@@ -91,16 +197,32 @@ export async function handleTransactionSigningFlow(
     intentDigest: getIntentDigest(request),
     confirmed: true,
     credential: serialized,
-    prfOutput: dualPrfOutputs.chacha20PrfOutput,
+    prfOutput: unlockSecret,
+    credentialCollectedAtMs,
     vrfChallenge: uiVrfChallenge,
     transactionContext,
+    reservedNonces: nearRpc.reservedNonces,
   });
   closeModalSafely(true, confirmHandle);
 }
 
-function send(worker: Worker, response: any) {
-  const sanitized = sanitizeForPostMessage(response);
-  worker.postMessage({ type: SecureConfirmMessageType.USER_PASSKEY_CONFIRM_RESPONSE, data: sanitized });
+// Mirrors estimate_challenge_expiry in confirm_tx_details.rs; undefined when expired
+function estimateChallengeExpiry(
+  policy: ChallengeExpiryPolicy,
+  challengeBlockHeight: number,
+  currentBlockHeight: number,
+): ChallengeExpiryEstimate | undefined {
+  const challengeAgeBlocks = Math.max(0, currentBlockHeight - challengeBlockHeight);
+  if (challengeAgeBlocks > policy.acceptanceWindowBlocks) return undefined;
+  const remainingBlocks = policy.acceptanceWindowBlocks - challengeAgeBlocks;
+  return {
+    challengeAgeBlocks,
+    estimatedExpirySeconds: Math.floor(remainingBlocks * policy.averageBlockTimeMs / 1000),
+  };
+}
+
+async function send(worker: Worker, response: any) {
+  postConfirmationDecision(worker, sanitizeForPostMessage(await sealConfirmationSecrets(worker, response)));
 }
 
 function closeModalSafely(confirmed: boolean, handle?: ConfirmUIHandle) {
@@ -14,7 +14,9 @@ import {
   getIntentDigest,
   classifyFlow,
   sanitizeForPostMessage,
+  postCountdownMessage,
 } from './flows/common';
+import { postConfirmationDecision, rememberRenderNonce } from './confirmationOriginBridge';
 import type {
   LocalOnlySecureConfirmRequest,
   RegistrationSecureConfirmRequest,
@@ -35,6 +37,9 @@ export async function handlePromptUserConfirmInJsMainThread(
   worker: Worker
 ): Promise<void> {
 
+  // Every decision sent for this prompt, including errors, attests its render nonce
+  rememberRenderNonce(message.data);
+
   // 1. Validate and parse request
   let request: SecureConfirmRequest;
   let summary: TransactionSummary;
@@ -74,6 +79,15 @@ export async function handlePromptUserConfirmInJsMainThread(
     return;
   }
 
+  // The worker expects a countdown for auto-proceed requests; tell it when this UI requires a click instead
+  const requested = request.confirmationConfig;
+  if (requested && requested.uiMode !== 'skip' && requested.behavior === 'autoProceed' && confirmationConfig.behavior === 'requireClick') {
+    postCountdownMessage(worker, SecureConfirmMessageType.CONFIRMATION_BEHAVIOR_CHANGED, {
+      requestId: request.requestId,
+      behavior: 'requireClick',
+    });
+  }
+
   // 2. Classify and dispatch to per-flow handlers
   const flowKind = classifyFlow(request);
   switch (flowKind) {
@@ -140,10 +154,7 @@ function validateAndParseRequest({ ctx, request }: {
 function sendWorkerResponse(worker: Worker, responseData: SecureConfirmDecision): void {
   // Sanitize payload to ensure postMessage structured-clone safety
   const sanitized = sanitizeForPostMessage(responseData);
-  worker.postMessage({
-    type: SecureConfirmMessageType.USER_PASSKEY_CONFIRM_RESPONSE,
-    data: sanitized
-  });
+  postConfirmationDecision(worker, sanitized);
   try {
     const bh = (sanitized as any)?.vrfChallenge?.blockHeight;
     if (bh) console.debug('[SecureConfirm] Sent VRF challenge block height', bh);
@@ -11,6 +11,42 @@ import { isObject, isString } from '../../../WalletIframe/validation';
 export enum SecureConfirmMessageType {
   PROMPT_USER_CONFIRM_IN_JS_MAIN_THREAD = 'PROMPT_USER_CONFIRM_IN_JS_MAIN_THREAD',
   USER_PASSKEY_CONFIRM_RESPONSE = 'USER_PASSKEY_CONFIRM_RESPONSE',
+  // Auto-proceed countdown handshake (see countdown_handshake.rs)
+  COUNTDOWN_STARTED = 'COUNTDOWN_STARTED',                         // Main → Worker
+  CANCEL_CONFIRMATION = 'CANCEL_CONFIRMATION',                     // Main → Worker
+  CONFIRMATION_BEHAVIOR_CHANGED = 'CONFIRMATION_BEHAVIOR_CHANGED', // Both ways: switch to requireClick
+}
+
+export interface CountdownStartedMessage {
+  type: SecureConfirmMessageType.COUNTDOWN_STARTED;
+  data: { requestId: string; displayedDigest?: string };
+}
+
+export interface CancelConfirmationMessage {
+  type: SecureConfirmMessageType.CANCEL_CONFIRMATION;
+  data: { requestId: string };
+}
+
+export interface ConfirmationBehaviorChangedMessage {
+  type: SecureConfirmMessageType.CONFIRMATION_BEHAVIOR_CHANGED;
+  data: { requestId: string; behavior: 'requireClick' };
+}
+
+// Handshake events seen by the worker's confirmation bridge (worker clock, ms since the epoch)
+export interface CountdownReport {
+  prompted_at_ms: number;
+  started_at_ms?: number;
+  displayed_digest?: string;
+  cancelled_at_ms?: number;
+  require_click: boolean;
+  released_at_ms: number;
+}
+
+// Proof that a decision came from the document that rendered the prompt, forwarded by the
+// worker's bridge only from the trusted confirmation origin (see confirmation_origin.rs)
+export interface RenderAttestation {
+  renderNonce: string;
+  origin: string;
 }
 
 export interface SecureConfirmDecision {
@@ -19,11 +55,13 @@ export interface SecureConfirmDecision {
   confirmed: boolean;
   credential?: SerializableCredential; // Serialized WebAuthn credential
   prfOutput?: string; // Base64url-encoded PRF output
+  credentialCollectedAtMs?: number; // Date.now() right after the WebAuthn assertion
   vrfChallenge?: VRFChallenge; // VRF challenge generated during confirmation
   transactionContext?: TransactionContext; // NEAR data fetched during confirmation
   // This is a private field used to close the confirmation modal
   _confirmHandle?: { close: (confirmed: boolean) => void };
   error?: string;
+  renderAttestation?: RenderAttestation; // Added by the confirmation origin bridge only
 }
 
 export interface TransactionSummary {
@@ -37,9 +75,26 @@ export interface TransactionSummary {
     deterministicVrfPublicKey?: string;
   };
   vrfChallenge?: VRFChallenge;
+  /** Expiry estimate for vrfChallenge, computed with the worker's ChallengeExpiryPolicy */
+  challengeExpiry?: ChallengeExpiryEstimate;
+  /** Confirmation deadline (epoch ms), for a countdown */
+  confirmationExpiresAtMs?: number;
+  /** Message shown by message-signing requests (decoded, as the worker signs it) */
+  signingMessage?: string;
   summary?: unknown;
 }
 
+// Challenge acceptance parameters sent by the worker with signTransaction requests
+export interface ChallengeExpiryPolicy {
+  averageBlockTimeMs: number;
+  acceptanceWindowBlocks: number;
+}
+
+export interface ChallengeExpiryEstimate {
+  challengeAgeBlocks: number;
+  estimatedExpirySeconds: number;
+}
+
 // Payload to return to Rust WASM is snake_case
 export interface WorkerConfirmationResponse {
   request_id: string;
@@ -47,9 +102,24 @@ export interface WorkerConfirmationResponse {
   confirmed: boolean;
   credential?: SerializableCredential;
   prf_output?: string;
+  prf_supported?: boolean;          // false when the registration credential has no PRF results
+  credential_collected_at_ms?: number; // Date.now() after the assertion; bounded by the worker (elevation.rs)
   vrf_challenge?: VRFChallenge;     // VRF challenge generated during confirmation
   transaction_context?: TransactionContext; // NEAR data fetched during confirmation
+  reserved_nonces?: string[];       // Nonces reserved for this request (released by the worker on failure)
+  collection_error?: CredentialCollectionError; // Set when navigator.credentials.get() failed
   error?: string;
+  error_code?: string;              // SignerErrorCode when the flow refused to prompt (e.g. 'ChallengeExpired')
+  countdown?: CountdownReport;      // Auto-proceed handshake, verified by the worker before signing
+  sealed_secrets?: Record<string, string>; // Sealed prf_output / credential PRF results, by JSON pointer
+  render_attestation?: RenderAttestation; // Verbatim, and only when it came over the confirmation origin bridge
+}
+
+// WebAuthn credential collection failure forwarded to the worker
+// (name is the DOMException name, e.g. NotAllowedError | AbortError | TimeoutError)
+export interface CredentialCollectionError {
+  name: string;
+  message: string;
 }
 
 // ===== V2 MESSAGE TYPES =====
@@ -60,6 +130,7 @@ export enum SecureConfirmationType {
   LINK_DEVICE = 'linkDevice',
   DECRYPT_PRIVATE_KEY_WITH_PRF = 'decryptPrivateKeyWithPrf',
   SIGN_NEP413_MESSAGE = 'signNep413Message',
+  EVM_PERSONAL_SIGN = 'evmPersonalSign',
   SHOW_SECURE_PRIVATE_KEY_UI = 'showSecurePrivateKeyUi',
 }
 
@@ -74,6 +145,8 @@ export interface SecureConfirmRequest<TPayload = unknown, TSummary = unknown> {
   // Optional intent digest to echo back in responses for flows that
   // do not have a tx-centric payload (e.g., registration/link flows)
   intentDigest?: string;
+  // Random per prompt; echoed in the decision's renderAttestation
+  renderNonce?: string;
 }
 
 // V2 payloads
@@ -81,6 +154,32 @@ export interface SignTransactionPayload {
   txSigningRequests: TransactionInputWasm[];
   intentDigest: string;
   rpcCall: RpcCallPayload;
+  expiryPolicy?: ChallengeExpiryPolicy;
+  /** Epoch ms after which the worker rejects the confirmation (ConfirmationExpired) */
+  confirmationExpiresAtMs?: number;
+  /** Changes since the last confirmed call to the same receiver+method (not covered by intentDigest) */
+  diffFromPrevious?: ActionDiff[];
+  /** Function-call signing keys: allowance (yoctoNEAR) left after the batch's estimated cost (not covered by intentDigest) */
+  allowanceAfterEstimate?: string;
+  /** With deduplicate: indexes (in the batch as sent) of the duplicate transactions dropped from txSigningRequests */
+  removedDuplicateIndexes?: number[];
+  /** With previewDigest: the digest txSigningRequests was checked to have (the batch previewed) */
+  verifiedPreviewDigest?: string;
+  /** Challenge the worker got from the VRF worker over its peer port; used as-is, never refreshed */
+  vrfChallenge?: VRFChallenge;
+}
+
+export interface ActionDiff {
+  txIndex: number;
+  actionIndex: number;
+  receiverId: string;
+  methodName: string;
+  previousIntentDigest: string;
+  argsChanged: boolean;
+  /** JSON pointers of changed arguments; null when the args were too large to diff */
+  changedArgPaths: string[] | null;
+  deposit: { from: string; to: string } | null;
+  gas: { from: string; to: string } | null;
 }
 
 export interface RegisterAccountPayload {
@@ -108,11 +207,25 @@ export interface SignNep413Payload {
   recipient: string;
 }
 
+export interface EvmPersonalSignPayload {
+  nearAccountId: string;
+  /** Decoded message: its text, or 0x hex when it is not UTF-8 */
+  message: string;
+  messageHex: string;
+  /** base64url of the EIP-191 hash */
+  intentDigest: string;
+  summaryBlocks?: unknown[];
+  /** Worker-generated screen-reader sentence for the whole request */
+  summarySpeech?: string;
+  confirmationExpiresAtMs?: number;
+}
+
 // V2 summaries (render-oriented)
 export interface TxSummary { totalAmount?: string; method?: string; receiverId?: string }
 export interface RegistrationSummary { nearAccountId: string; deviceNumber?: number; contractId?: string }
 export interface ExportSummary { operation: 'Export Private Key'; accountId: string; publicKey: string; warning: string }
 export interface Nep413Summary { operation: 'Sign NEP-413 Message'; message: string; recipient: string; accountId: string }
+export interface EvmPersonalSignSummary { operation: 'Sign EVM Message'; message: string; accountId: string }
 
 // Type guards
 export function isSecureConfirmRequestV2(x: unknown): x is SecureConfirmRequest {
@@ -136,7 +249,8 @@ export type RegistrationSecureConfirmRequest =
 
 export type SigningSecureConfirmRequest =
   | (SecureConfirmRequest<SignTransactionPayload> & { type: SecureConfirmationType.SIGN_TRANSACTION })
-  | (SecureConfirmRequest<SignNep413Payload> & { type: SecureConfirmationType.SIGN_NEP413_MESSAGE });
+  | (SecureConfirmRequest<SignNep413Payload> & { type: SecureConfirmationType.SIGN_NEP413_MESSAGE })
+  | (SecureConfirmRequest<EvmPersonalSignPayload> & { type: SecureConfirmationType.EVM_PERSONAL_SIGN });
 
 export type KnownSecureConfirmRequest =
   | LocalOnlySecureConfirmRequest
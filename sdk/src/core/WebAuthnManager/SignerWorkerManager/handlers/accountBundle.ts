@@ -0,0 +1,92 @@
+
+import type { ClientAuthenticatorData, ClientUserData, EncryptedKeyData } from '../../../IndexedDBManager';
+import {
+  WorkerRequestType,
+  isExportAccountBundleSuccess,
+  isImportAccountBundleSuccess,
+  type AccountBundleRecords,
+} from '../../../types/signer-worker';
+import { SignerWorkerManagerContext } from '..';
+
+/**
+ * Export every IndexedDB record (users, authenticators, encrypted NEAR keys, app state) as one
+ * bundle file, encrypted in the worker under a key derived from `passphrase` with Argon2id.
+ * NEAR keys stay encrypted under the passkey PRF inside the bundle.
+ */
+export async function exportAccountBundle({
+  ctx,
+  passphrase,
+}: {
+  ctx: SignerWorkerManagerContext;
+  passphrase: string;
+}): Promise<{ bundle: Uint8Array; recordCount: number }> {
+  const [clientRecords, nearKeys] = await Promise.all([
+    ctx.indexedDB.clientDB.getAccountBundleRecords(),
+    ctx.indexedDB.nearKeysDB.getAllEncryptedKeys(),
+  ]);
+  const records: AccountBundleRecords = {
+    users: clientRecords.users as unknown as Record<string, unknown>[],
+    authenticators: clientRecords.authenticators.map((authenticator) => ({
+      ...authenticator,
+      credentialPublicKey: Array.from(authenticator.credentialPublicKey),
+    })),
+    nearKeys: nearKeys as unknown as Record<string, unknown>[],
+    appState: clientRecords.appState,
+  };
+
+  const response = await ctx.sendMessage({
+    message: {
+      type: WorkerRequestType.ExportAccountBundle,
+      payload: { passphrase, records },
+    },
+  });
+  if (!isExportAccountBundleSuccess(response)) {
+    throw new Error('Account bundle export failed');
+  }
+  return {
+    bundle: new Uint8Array(response.payload.bundle),
+    recordCount: response.payload.recordCount,
+  };
+}
+
+/**
+ * Decrypt a bundle from exportAccountBundle and write its records to IndexedDB. The worker
+ * validates every record first, so nothing is written for a wrong passphrase, a truncated file
+ * or a bundle from a newer release (the error message starts with the error code).
+ */
+export async function importAccountBundle({
+  ctx,
+  passphrase,
+  bundle,
+}: {
+  ctx: SignerWorkerManagerContext;
+  passphrase: string;
+  bundle: Uint8Array | number[];
+}): Promise<{ recordCount: number; exportedAtMs: number }> {
+  const response = await ctx.sendMessage({
+    message: {
+      type: WorkerRequestType.ImportAccountBundle,
+      payload: { passphrase, bundle: Array.from(bundle) },
+    },
+  });
+  if (!isImportAccountBundleSuccess(response)) {
+    throw new Error('Account bundle import failed');
+  }
+  const { records, exportedAtMs } = response.payload;
+
+  await ctx.indexedDB.clientDB.putAccountBundleRecords({
+    users: records.users as unknown as ClientUserData[],
+    authenticators: records.authenticators.map((authenticator) => ({
+      ...authenticator,
+      credentialPublicKey: new Uint8Array(authenticator.credentialPublicKey as number[]),
+    })) as unknown as ClientAuthenticatorData[],
+    appState: records.appState,
+  });
+  for (const key of records.nearKeys as unknown as EncryptedKeyData[]) {
+    await ctx.indexedDB.nearKeysDB.storeEncryptedKey(key);
+  }
+
+  const recordCount = records.users.length + records.authenticators.length
+    + records.nearKeys.length + records.appState.length;
+  return { recordCount, exportedAtMs };
+}
@@ -0,0 +1,63 @@
+import {
+  WorkerRequestType,
+  isBuildCredentialCreationOptionsSuccess,
+  isWorkerError,
+  type CredentialCreationOptionsJSON,
+  type RpcCallPayload,
+} from '../../../types/signer-worker';
+import type { VRFChallenge } from '../../../types/vrf-worker';
+import { SignerWorkerManagerContext } from '..';
+
+/**
+ * Builds navigator.credentials.create options in the signer worker, so the algorithms,
+ * authenticator selection and PRF salts of every registration come from one place.
+ * excludeCredentials lists `existingCredentials`, or the account's authenticators fetched with
+ * `rpcCall` when that is omitted.
+ */
+export async function buildCredentialCreationOptions({
+  ctx,
+  nearAccountId,
+  deviceNumber,
+  vrfChallenge,
+  nonce,
+  blockHash,
+  rpcCall,
+  existingCredentials,
+}: {
+  ctx: SignerWorkerManagerContext;
+  nearAccountId: string;
+  deviceNumber?: number;
+  vrfChallenge: VRFChallenge;
+  nonce: string;
+  blockHash: string;
+  rpcCall?: RpcCallPayload;
+  existingCredentials?: { credentialId: string; transports?: string[] }[];
+}): Promise<CredentialCreationOptionsJSON> {
+  const response = await ctx.sendMessage({
+    message: {
+      type: WorkerRequestType.BuildCredentialCreationOptions,
+      payload: {
+        registration: { nearAccountId, nonce, blockHash, deviceNumber },
+        rpId: ctx.touchIdPrompt.getRpId(),
+        vrfChallenge: {
+          vrfInput: vrfChallenge.vrfInput,
+          vrfOutput: vrfChallenge.vrfOutput,
+          vrfProof: vrfChallenge.vrfProof,
+          vrfPublicKey: vrfChallenge.vrfPublicKey,
+          userId: vrfChallenge.userId,
+          rpId: vrfChallenge.rpId,
+          blockHeight: vrfChallenge.blockHeight,
+          blockHash: vrfChallenge.blockHash,
+        },
+        existingCredentials,
+        rpcCall,
+      },
+    },
+  });
+
+  if (!isBuildCredentialCreationOptionsSuccess(response)) {
+    const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+    throw new Error(`Building credential creation options failed: ${errorDetails}`);
+  }
+  return response.payload.publicKey;
+}
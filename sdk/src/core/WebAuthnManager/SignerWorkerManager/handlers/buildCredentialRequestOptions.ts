@@ -0,0 +1,78 @@
+import {
+  WorkerRequestType,
+  isBuildCredentialRequestOptionsSuccess,
+  isWorkerError,
+  type BuildCredentialRequestOptionsResult,
+  type KnownAuthenticator,
+  type RpcCallPayload,
+} from '../../../types/signer-worker';
+import type { VRFChallenge } from '../../../types/vrf-worker';
+import type { ClientAuthenticatorData } from '../../../IndexedDBManager';
+import { SignerWorkerManagerContext } from '..';
+
+/** Stored authenticators in the shape BuildCredentialRequestOptions takes */
+export function toKnownAuthenticators(authenticators: ClientAuthenticatorData[]): KnownAuthenticator[] {
+  return authenticators.map((authenticator) => ({
+    credentialId: authenticator.credentialId,
+    transports: authenticator.transports,
+    deviceNumber: authenticator.deviceNumber,
+    residentKey: authenticator.residentKey,
+  }));
+}
+
+/**
+ * Builds navigator.credentials.get options in the signer worker. allowCredentials lists the
+ * account's authenticators (only `deviceNumber`'s when given), or is omitted for discoverable
+ * credentials when every authenticator reported rk=true at registration.
+ */
+export async function buildCredentialRequestOptions({
+  ctx,
+  nearAccountId,
+  vrfChallenge,
+  authenticators,
+  deviceNumber,
+  userVerification,
+  discoverable,
+  rpcCall,
+}: {
+  ctx: SignerWorkerManagerContext;
+  nearAccountId: string;
+  vrfChallenge: VRFChallenge;
+  /** Fetched from the contract with rpcCall when omitted */
+  authenticators?: KnownAuthenticator[];
+  deviceNumber?: number;
+  userVerification?: 'required' | 'preferred' | 'discouraged';
+  discoverable?: boolean;
+  rpcCall?: RpcCallPayload;
+}): Promise<BuildCredentialRequestOptionsResult> {
+  const response = await ctx.sendMessage({
+    message: {
+      type: WorkerRequestType.BuildCredentialRequestOptions,
+      payload: {
+        nearAccountId,
+        rpId: ctx.touchIdPrompt.getRpId(),
+        vrfChallenge: {
+          vrfInput: vrfChallenge.vrfInput,
+          vrfOutput: vrfChallenge.vrfOutput,
+          vrfProof: vrfChallenge.vrfProof,
+          vrfPublicKey: vrfChallenge.vrfPublicKey,
+          userId: vrfChallenge.userId,
+          rpId: vrfChallenge.rpId,
+          blockHeight: vrfChallenge.blockHeight,
+          blockHash: vrfChallenge.blockHash,
+        },
+        authenticators,
+        deviceNumber,
+        userVerification,
+        discoverable,
+        rpcCall,
+      },
+    },
+  });
+
+  if (!isBuildCredentialRequestOptionsSuccess(response)) {
+    const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+    throw new Error(`Building credential request options failed: ${errorDetails}`);
+  }
+  return response.payload;
+}
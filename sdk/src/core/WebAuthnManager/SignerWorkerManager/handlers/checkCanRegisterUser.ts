@@ -9,7 +9,7 @@ import {
 import { toEnumUserVerificationPolicy } from '../../../types/authenticatorOptions';
 import { serializeRegistrationCredentialWithPRF } from '../../credentialsHelpers';
 import { VRFChallenge } from '../../../types/vrf-worker';
-import type { onProgressEvents } from '../../../types/passkeyManager';
+import type { onProgressEvents, RegistrationDryRunReport } from '../../../types/passkeyManager';
 import type { AuthenticatorOptions } from '../../../types/authenticatorOptions';
 import { SignerWorkerManagerContext } from '..';
 import type { WebAuthnRegistrationCredential } from '../../../types/webauthn';
@@ -24,6 +24,7 @@ export async function checkCanRegisterUser({
   contractId,
   nearRpcUrl,
   authenticatorOptions,
+  dryRun,
   onEvent,
 }: {
   ctx: SignerWorkerManagerContext,
@@ -32,6 +33,7 @@ export async function checkCanRegisterUser({
   contractId: string;
   nearRpcUrl: string;
   authenticatorOptions?: AuthenticatorOptions; // Authenticator options for registration check
+  dryRun?: boolean; // Report every check's outcome instead of failing on the first one
   onEvent?: (update: onProgressEvents) => void;
 }): Promise<{
   success: boolean;
@@ -45,6 +47,7 @@ export async function checkCanRegisterUser({
   logs?: string[];
   signedTransactionBorsh?: number[];
   error?: string;
+  dryRun?: RegistrationDryRunReport;
 }> {
   try {
     // Accept either a real PublicKeyCredential or an already-serialized credential
@@ -84,7 +87,9 @@ export async function checkCanRegisterUser({
           authenticatorOptions: authenticatorOptions ? {
             userVerification: toEnumUserVerificationPolicy(authenticatorOptions.userVerification),
             originPolicy: authenticatorOptions.originPolicy,
-          } : undefined
+            allowedAlgorithms: authenticatorOptions.allowedAlgorithms,
+          } : undefined,
+          dryRun: !!dryRun,
         }
       },
       onEvent,
@@ -103,6 +108,7 @@ export async function checkCanRegisterUser({
       registrationInfo: wasmResult.registrationInfo,
       logs: wasmResult.logs,
       error: wasmResult.error,
+      dryRun: wasmResult.dryRun as RegistrationDryRunReport | undefined,
     };
   } catch (error: unknown) {
     // Preserve the detailed error message instead of converting to generic error
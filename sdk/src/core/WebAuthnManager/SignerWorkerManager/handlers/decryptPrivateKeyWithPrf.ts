@@ -4,12 +4,14 @@ import { TouchIdPrompt } from "../../touchIdPrompt";
 import {
   WorkerRequestType,
   isDecryptPrivateKeyWithPrfSuccess,
+  isIssueElevationChallengeSuccess,
+  isWorkerError,
 } from '../../../types/signer-worker';
-import { extractPrfFromCredential } from '../../credentialsHelpers';
+import { extractPrfFromCredential, removePrfOutputGuard } from '../../credentialsHelpers';
 import { AccountId, toAccountId } from "../../../types/accountIds";
+import { base64UrlDecode } from '../../../../utils/encoders';
 
 import { SignerWorkerManagerContext } from '..';
-import { createRandomVRFChallenge, VRFChallenge } from '@/core/types/vrf-worker';
 import { getDeviceNumberForAccount } from '../getDeviceNumber';
 
 
@@ -31,14 +33,22 @@ export async function decryptPrivateKeyWithPrf({
       throw new Error(`No encrypted key found for account: ${nearAccountId}`);
     }
 
-    // For private key export, no VRF challenge is needed.
-    // we can use local random challenge for WebAuthn authentication.
-    // Security comes from device possession + biometrics, not challenge validation
-    const challenge = createRandomVRFChallenge();
+    // No VRF challenge is needed for export, but the worker only releases the key for an
+    // assertion made for a challenge it issued, within WorkerPolicy.elevationMaxAgeMs
+    const challengeResponse = await ctx.sendMessage({
+      message: {
+        type: WorkerRequestType.IssueElevationChallenge,
+        payload: { nearAccountId },
+      }
+    });
+    if (!isIssueElevationChallengeSuccess(challengeResponse)) {
+      const errorDetails = isWorkerError(challengeResponse) ? challengeResponse.payload.error : 'Unknown worker error';
+      throw new Error(`Elevation challenge failed: ${errorDetails}`);
+    }
     // TouchID prompt
     const credential = await ctx.touchIdPrompt.getAuthenticationCredentialsSerialized({
       nearAccountId,
-      challenge: challenge as VRFChallenge,
+      challenge: base64UrlDecode(challengeResponse.payload.challenge),
       allowCredentials: authenticators.map(auth => ({
         id: auth.credentialId,
         type: 'public-key',
@@ -54,6 +64,8 @@ export async function decryptPrivateKeyWithPrf({
     });
     console.debug('WebAuthnManager: Extracted ChaCha20 PRF output for decryption');
 
+    // The assertion goes along without its PRF outputs: the worker checks it was made for the
+    // challenge above, once, and otherwise refuses with errorCode 'ElevationRequired'
     const response = await ctx.sendMessage({
       message: {
         type: WorkerRequestType.DecryptPrivateKeyWithPrf,
@@ -61,7 +73,8 @@ export async function decryptPrivateKeyWithPrf({
           nearAccountId: nearAccountId,
           chacha20PrfOutput: dualPrfOutputs.chacha20PrfOutput, // Use ChaCha20 PRF output for decryption
           encryptedPrivateKeyData: encryptedKeyData.encryptedData,
-          encryptedPrivateKeyIv: encryptedKeyData.iv
+          encryptedPrivateKeyIv: encryptedKeyData.iv,
+          credential: removePrfOutputGuard(credential),
         }
       }
     });
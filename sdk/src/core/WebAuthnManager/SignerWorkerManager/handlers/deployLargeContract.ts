@@ -0,0 +1,126 @@
+
+import { SignedTransaction } from '../../../NearClient';
+import type { onProgressEvents } from '../../../types/passkeyManager';
+import {
+  WorkerRequestType,
+  ConfirmationConfig,
+  isDeployLargeContractSuccess,
+  type DeployManifest,
+  type RpcCallPayload,
+  type RpcOverrides,
+  type StagingContractInterface,
+} from '../../../types/signer-worker';
+import { AccountId, toAccountId } from "../../../types/accountIds";
+import { SignerWorkerManagerContext } from '..';
+import { PASSKEY_MANAGER_DEFAULT_CONFIGS } from '../../../defaultConfigs';
+import { getDeviceNumberForAccount } from '../getDeviceNumber';
+import { workerPolicyFromHooks } from '../signingHooks';
+
+/**
+ * Deploy a contract above the transaction size limit through a staging contract:
+ * the worker splits the code into staging calls plus a deploy-from-staged call and signs them
+ * as one batch (one confirmation, sequential nonces). Broadcast the transactions in the
+ * returned order, each after the previous one lands.
+ */
+export async function deployLargeContract({
+  ctx,
+  code,
+  chunkSize,
+  staging,
+  rpcCall,
+  onEvent,
+  confirmationConfigOverride,
+  rpcOverrides
+}: {
+  ctx: SignerWorkerManagerContext,
+  code: Uint8Array | number[];
+  chunkSize: number;
+  staging: StagingContractInterface;
+  rpcCall: RpcCallPayload;
+  onEvent?: (update: onProgressEvents) => void;
+  confirmationConfigOverride?: ConfirmationConfig;
+  rpcOverrides?: RpcOverrides;
+}): Promise<{
+  signedTransactions: SignedTransaction[];
+  nearAccountId: AccountId;
+  manifest: DeployManifest;
+  logs?: string[];
+  broadcastRpcUrl?: string;
+}> {
+  const nearAccountId = rpcCall.nearAccountId;
+  const deviceNumber = await getDeviceNumberForAccount(ctx, nearAccountId);
+  const encryptedKeyData = await ctx.indexedDB.nearKeysDB.getEncryptedKey(nearAccountId, deviceNumber);
+  if (!encryptedKeyData) {
+    throw new Error(`No encrypted key found for account: ${nearAccountId}`);
+  }
+
+  const resolvedRpcCall = {
+    contractId: rpcCall.contractId || PASSKEY_MANAGER_DEFAULT_CONFIGS.contractId,
+    nearRpcUrl: rpcCall.nearRpcUrl || (PASSKEY_MANAGER_DEFAULT_CONFIGS.nearRpcUrl.split(',')[0] || PASSKEY_MANAGER_DEFAULT_CONFIGS.nearRpcUrl),
+    nearAccountId: rpcCall.nearAccountId,
+  } as RpcCallPayload;
+
+  const hookPolicy = workerPolicyFromHooks(ctx.signingHooks);
+  const basePolicy = ctx.allowedRpcOrigins?.length
+    ? { ...hookPolicy, allowedRpcOrigins: ctx.allowedRpcOrigins }
+    : hookPolicy;
+  const escalationPolicy = ctx.depositEscalationYocto !== undefined
+    ? { ...basePolicy, depositEscalationYocto: ctx.depositEscalationYocto }
+    : basePolicy;
+  const allowancePolicy = ctx.lowAllowanceWarningYocto !== undefined
+    ? { ...escalationPolicy, lowAllowanceWarningYocto: ctx.lowAllowanceWarningYocto }
+    : escalationPolicy;
+  const workerPolicy = ctx.telemetry
+    ? { ...allowancePolicy, telemetry: ctx.telemetry }
+    : allowancePolicy;
+
+  const response = await ctx.sendMessage({
+    message: {
+      type: WorkerRequestType.DeployLargeContract,
+      payload: {
+        rpcCall: resolvedRpcCall,
+        decryption: {
+          encryptedPrivateKeyData: encryptedKeyData.encryptedData,
+          encryptedPrivateKeyIv: encryptedKeyData.iv
+        },
+        code: Array.from(code),
+        chunkSize,
+        staging,
+        confirmationConfig: confirmationConfigOverride || ctx.userPreferencesManager.getConfirmationConfig(),
+        workerPolicy,
+        rpcOverrides
+      }
+    },
+    onEvent
+  });
+
+  if (!isDeployLargeContractSuccess(response)) {
+    console.error('WebAuthnManager: Chunked deploy signing failed:', response);
+    throw new Error('Chunked deploy signing failed');
+  }
+  const result = response.payload;
+  if (!result.success) {
+    throw new Error(result.error || 'Chunked deploy signing failed');
+  }
+  const signedTransactions = result.signedTransactions || [];
+  if (signedTransactions.length !== result.manifest.chunkHashes.length + 1) {
+    throw new Error(`Expected ${result.manifest.chunkHashes.length + 1} signed transactions but received ${signedTransactions.length}`);
+  }
+
+  return {
+    signedTransactions: signedTransactions.map((signedTx, index) => {
+      if (!signedTx || !signedTx.transaction || !signedTx.signature) {
+        throw new Error(`Incomplete signed transaction data received for transaction ${index + 1}`);
+      }
+      return new SignedTransaction({
+        transaction: signedTx.transaction,
+        signature: signedTx.signature,
+        borsh_bytes: Array.from(signedTx.borshBytes || [])
+      });
+    }),
+    nearAccountId: toAccountId(nearAccountId),
+    manifest: result.manifest,
+    logs: result.logs,
+    broadcastRpcUrl: result.broadcastRpcUrl
+  };
+}
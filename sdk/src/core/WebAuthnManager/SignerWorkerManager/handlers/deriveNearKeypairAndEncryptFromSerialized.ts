@@ -4,6 +4,10 @@ import type { AuthenticatorOptions } from '../../../types/authenticatorOptions';
 import {
   WorkerRequestType,
   isDeriveNearKeypairAndEncryptSuccess,
+  type AuthenticatorInfo,
+  type OuterWrapMode,
+  type PrfFallbackScheme,
+  type SubKeyEntry,
 } from '../../../types/signer-worker';
 import { AccountId, toAccountId } from "../../../types/accountIds";
 import { getDeviceNumberForAccount } from '../getDeviceNumber';
@@ -34,39 +38,65 @@ export async function deriveNearKeypairAndEncryptFromSerialized({
     blockHash?: string;
     authenticatorOptions?: AuthenticatorOptions;
     deviceNumber?: number;
+    /** Contract registration interface version; probed via nearRpcUrl when omitted */
+    contractInterfaceVersion?: number;
+    nearRpcUrl?: string;
+    /**
+     * Key wrapping for authenticators without PRF (one of ctx.prfFallbackSchemes; the first when omitted).
+     * 'passphrase' requires the user's passphrase.
+     */
+    prfFallback?: { scheme?: PrfFallbackScheme; passphrase?: string };
+    /** SLIP-0010 paths of sub-keys to derive with the key (needs PRF); recorded with the key */
+    subKeyPaths?: string[];
+    /** Retries with the same key get the first completed result back instead of deriving again */
+    idempotencyKey?: string;
   }
 }): Promise<{
   success: boolean;
   nearAccountId: AccountId;
   publicKey: string;
   signedTransaction?: SignedTransaction;
+  /** COSE algorithm of the credential key, e.g. -8 for EdDSA */
+  negotiatedAlgorithm?: number;
+  /** The sub-keys derived for options.subKeyPaths */
+  subKeys?: SubKeyEntry[];
+  /** The authenticator model the credential was created on (from its AAGUID) */
+  authenticator?: AuthenticatorInfo;
 }> {
   try {
     const first = credential?.clientExtensionResults?.prf?.results?.first as string | undefined;
     const second = credential?.clientExtensionResults?.prf?.results?.second as string | undefined;
     const hasFirst = typeof first === 'string' && first.length > 0;
     const hasSecond = typeof second === 'string' && second.length > 0;
-    if (!hasFirst || !hasSecond) {
-      throw new Error('PRF outputs missing from serialized credential');
-    }
+    // Without both PRF outputs the worker wraps a random key per ctx.prfFallbackSchemes,
+    // or refuses with PrfUnsupportedByAuthenticator
+    const prfSupported = hasFirst && hasSecond;
 
     const response = await ctx.sendMessage<WorkerRequestType.DeriveNearKeypairAndEncrypt>({
       message: {
         type: WorkerRequestType.DeriveNearKeypairAndEncrypt,
         payload: {
-          dualPrfOutputs: { chacha20PrfOutput: first, ed25519PrfOutput: second },
+          dualPrfOutputs: prfSupported ? { chacha20PrfOutput: first!, ed25519PrfOutput: second! } : undefined,
+          prfSupported,
+          prfFallback: prfSupported ? undefined : options?.prfFallback,
+          workerPolicy: ctx.prfFallbackSchemes ? { prfFallbackSchemes: ctx.prfFallbackSchemes } : undefined,
           nearAccountId: nearAccountId,
           credential,
+          subKeyPaths: options?.subKeyPaths,
+          idempotencyKey: options?.idempotencyKey,
           registrationTransaction: (options?.vrfChallenge && options?.contractId && options?.nonce && options?.blockHash && options?.deterministicVrfPublicKey) ? {
             vrfChallenge: options.vrfChallenge,
             contractId: options.contractId,
             nonce: options.nonce,
             blockHash: options.blockHash,
             deterministicVrfPublicKey: options.deterministicVrfPublicKey,
+            contractInterfaceVersion: options.contractInterfaceVersion,
+            nearRpcUrl: options.nearRpcUrl,
           } : undefined,
           authenticatorOptions: options?.authenticatorOptions ? {
             userVerification: toEnumUserVerificationPolicy(options.authenticatorOptions.userVerification),
             originPolicy: options.authenticatorOptions.originPolicy,
+            allowedAlgorithms: options.authenticatorOptions.allowedAlgorithms,
           } : undefined
         }
       }
@@ -77,6 +107,14 @@ export async function deriveNearKeypairAndEncryptFromSerialized({
     }
 
     const wasmResult = response.payload;
+    // The largeBlob secret must reach the authenticator before the blob is stored; without it
+    // the key could never be unlocked
+    if (wasmResult.largeBlobSecret) {
+      await ctx.touchIdPrompt.writeLargeBlob({
+        credentialId: credential.rawId,
+        largeBlobSecret: wasmResult.largeBlobSecret,
+      });
+    }
     // Prefer explicitly provided deviceNumber, else derive from IndexedDB state
     const deviceNumber = (typeof options?.deviceNumber === 'number')
       ? options!.deviceNumber!
@@ -86,7 +124,10 @@ export async function deriveNearKeypairAndEncryptFromSerialized({
       deviceNumber,
       encryptedData: wasmResult.encryptedData,
       iv: wasmResult.iv,
-      timestamp: Date.now()
+      timestamp: Date.now(),
+      outerWrap: wasmResult.outerWrap as OuterWrapMode | undefined,
+      version: wasmResult.version,
+      subKeyPaths: wasmResult.subKeys?.length ? wasmResult.subKeys.map((subKey) => subKey.path) : undefined,
     };
     await ctx.indexedDB.nearKeysDB.storeEncryptedKey(keyData);
 
@@ -103,7 +144,10 @@ export async function deriveNearKeypairAndEncryptFromSerialized({
       success: true,
       nearAccountId: toAccountId(wasmResult.nearAccountId),
       publicKey: wasmResult.publicKey,
-      signedTransaction
+      signedTransaction,
+      negotiatedAlgorithm: wasmResult.negotiatedAlgorithm ?? undefined,
+      subKeys: wasmResult.subKeys ?? [],
+      authenticator: wasmResult.authenticator ?? undefined,
     };
   } catch (error: unknown) {
     console.error('WebAuthnManager: deriveNearKeypairAndEncryptFromSerialized error:', error);
@@ -2,8 +2,19 @@ export * from './checkCanRegisterUser';
 export * from './deriveNearKeypairAndEncryptFromSerialized';
 export * from './decryptPrivateKeyWithPrf';
 export * from './signTransactionsWithActions';
+export * from './deployLargeContract';
+export * from './signingIntent';
+export * from './sessionKeys';
+export * from './remoteConfirmation';
+export * from './submitToRelayer';
+export * from './resumableRegistration';
 export * from './recoverKeypairFromPasskey';
 export * from './extractCosePublicKey';
+export * from './buildCredentialCreationOptions';
+export * from './buildCredentialRequestOptions';
+export * from './recoverPendingOperations';
 export * from './signTransactionWithKeyPair';
 export * from './signNep413Message';
+export * from './signEvmPersonalMessage';
 export * from './requestRegistrationCredentialConfirmation';
+export * from './accountBundle';
@@ -1,27 +1,34 @@
 import {
   WorkerRequestType,  // from wasm worker
   isRecoverKeypairFromPasskeySuccess,
+  type OuterWrapMode,
+  type SubKeyEntry,
 } from '../../../types/signer-worker';
 import type { WebAuthnAuthenticationCredential } from '../../../types/webauthn';
 import { SignerWorkerManagerContext } from '..';
 
 /**
  * Recover keypair from authentication credential for account recovery
- * Uses dual PRF-based Ed25519 key derivation with account-specific HKDF and AES encryption
+ * Uses dual PRF-based Ed25519 key derivation with account-specific HKDF and AES encryption.
+ * The sub-keys at subKeyPaths are derived again from the same PRF output.
  */
 export async function recoverKeypairFromPasskey({
   ctx,
   credential,
   accountIdHint,
+  subKeyPaths,
 }: {
   ctx: SignerWorkerManagerContext;
   credential: WebAuthnAuthenticationCredential;
   accountIdHint?: string;
+  subKeyPaths?: string[];
 }): Promise<{
   publicKey: string;
   encryptedPrivateKey: string;
   iv: string;
   accountIdHint?: string;
+  outerWrap?: OuterWrapMode;
+  subKeys: SubKeyEntry[];
 }> {
   try {
     console.info('SignerWorkerManager: Starting dual PRF-based keypair recovery from authentication credential');
@@ -42,6 +49,7 @@ export async function recoverKeypairFromPasskey({
         payload: {
           credential: credential,
           accountIdHint: accountIdHint,
+          subKeyPaths,
         }
       }
     });
@@ -55,7 +63,9 @@ export async function recoverKeypairFromPasskey({
       publicKey: response.payload.publicKey,
       encryptedPrivateKey: response.payload.encryptedData,
       iv: response.payload.iv,
-      accountIdHint: response.payload.accountIdHint
+      accountIdHint: response.payload.accountIdHint,
+      outerWrap: response.payload.outerWrap as OuterWrapMode | undefined,
+      subKeys: response.payload.subKeys ?? [],
     };
 
   } catch (error: unknown) {
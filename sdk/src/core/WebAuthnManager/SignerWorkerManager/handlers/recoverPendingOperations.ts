@@ -0,0 +1,46 @@
+import {
+  WorkerRequestType,
+  isRecoverPendingOperationsSuccess,
+  isWorkerError,
+  type RecoverPendingOperationsResult,
+} from '../../../types/signer-worker';
+import { SignerWorkerManagerContext } from '..';
+import { MAX_RECOVERABLE_OPERATIONS, readOperationJournal, removeJournalEntries } from '../operationJournal';
+
+/**
+ * Reconciles the operation journal after a crash or reload: reports, for each broadcast or
+ * relayer submission whose result never arrived, whether it is confirmed, pending, not found
+ * or unknown. Confirmed entries leave the journal; the others stay so this can be called
+ * again (it only makes view calls). At most MAX_RECOVERABLE_OPERATIONS entries per call.
+ */
+export async function recoverPendingOperations({
+  ctx,
+  nearRpcUrl,
+}: {
+  ctx: SignerWorkerManagerContext;
+  nearRpcUrl: string;
+}): Promise<RecoverPendingOperationsResult> {
+  // Oldest first; the rest are recovered on the next call
+  const entries = (await readOperationJournal(ctx.indexedDB)).slice(0, MAX_RECOVERABLE_OPERATIONS);
+  if (!entries.length) {
+    return { operations: [], unresolved: 0 };
+  }
+  const response = await ctx.sendMessage({
+    message: {
+      type: WorkerRequestType.RecoverPendingOperations,
+      payload: { nearRpcUrl, entries },
+    },
+  });
+
+  if (!isRecoverPendingOperationsSuccess(response)) {
+    const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+    throw new Error(`Recovering pending operations failed: ${errorDetails}`);
+  }
+  await removeJournalEntries(
+    ctx.indexedDB,
+    response.payload.operations
+      .filter((operation) => operation.status === 'confirmed')
+      .map((operation) => operation.entry),
+  );
+  return response.payload;
+}
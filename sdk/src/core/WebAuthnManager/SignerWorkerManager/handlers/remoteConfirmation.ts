@@ -0,0 +1,182 @@
+
+import { SignedTransaction } from '../../../NearClient';
+import { TransactionInputWasm } from '../../../types/actions';
+import type { onProgressEvents } from '../../../types/passkeyManager';
+import {
+  WorkerRequestType,
+  ConfirmationConfig,
+  RemoteConfirmationRequest,
+  isApproveRemoteConfirmationSuccess,
+  isCompleteRemoteConfirmationSuccess,
+  isCreateRemoteConfirmationSuccess,
+  isWorkerError,
+} from '../../../types/signer-worker';
+import { VRFChallenge } from '../../../types/vrf-worker';
+import { AccountId } from "../../../types/accountIds";
+import { SignerWorkerManagerContext } from '..';
+import { RpcCallPayload, RpcOverrides } from '../../../types/signer-worker';
+import { PASSKEY_MANAGER_DEFAULT_CONFIGS } from '../../../defaultConfigs';
+import { getDeviceNumberForAccount } from '../getDeviceNumber';
+import { signingIntentPolicy, toSignedTransactions, toSigningRequests } from './signingIntent';
+
+/**
+ * Start a confirmation on another device: returns the request to hand over (QR code or
+ * relayer). `vrfChallenge` is what the approving passkey signs; it must be for this account.
+ */
+export async function createRemoteConfirmation({
+  ctx,
+  transactions,
+  rpcCall,
+  vrfChallenge,
+  ttlMs,
+}: {
+  ctx: SignerWorkerManagerContext,
+  transactions: TransactionInputWasm[],
+  rpcCall: RpcCallPayload;
+  vrfChallenge: VRFChallenge;
+  ttlMs?: number;
+}): Promise<RemoteConfirmationRequest> {
+  const { txSigningRequests, resolvedRpcCall } = toSigningRequests(transactions, rpcCall);
+
+  const response = await ctx.sendMessage({
+    message: {
+      type: WorkerRequestType.CreateRemoteConfirmation,
+      payload: {
+        rpcCall: resolvedRpcCall,
+        txSigningRequests,
+        vrfChallenge,
+        workerPolicy: signingIntentPolicy(ctx),
+        ttlMs,
+      }
+    },
+  });
+
+  if (!isCreateRemoteConfirmationSuccess(response)) {
+    const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+    throw new Error(`Remote confirmation creation failed: ${errorDetails}`);
+  }
+  const request = response.payload;
+  return {
+    confirmationRequest: request.confirmationRequest,
+    requestId: request.requestId,
+    sessionPublicKey: request.sessionPublicKey,
+    intentDigest: request.intentDigest,
+    expiresAtMs: request.expiresAtMs,
+  };
+}
+
+/**
+ * On the approving device: show the request's transactions in the confirmation UI and return
+ * the sealed approval for completeRemoteConfirmation. Failures carry the worker's error code
+ * prefix (RemoteConfirmationInvalid, RemoteConfirmationExpired, RemoteConfirmationReplayed,
+ * RemoteConfirmationWrongSession)
+ */
+export async function approveRemoteConfirmation({
+  ctx,
+  confirmationRequest,
+  nearAccountId,
+  onEvent,
+  confirmationConfigOverride,
+}: {
+  ctx: SignerWorkerManagerContext,
+  confirmationRequest: string,
+  nearAccountId: AccountId,
+  onEvent?: (update: onProgressEvents) => void;
+  confirmationConfigOverride?: ConfirmationConfig;
+}): Promise<{ approval: string; requestId: string }> {
+  const confirmationConfig = confirmationConfigOverride
+    || ctx.userPreferencesManager.getConfirmationConfig();
+
+  const response = await ctx.sendMessage({
+    message: {
+      type: WorkerRequestType.ApproveRemoteConfirmation,
+      payload: {
+        confirmationRequest,
+        rpcCall: {
+          contractId: PASSKEY_MANAGER_DEFAULT_CONFIGS.contractId,
+          nearRpcUrl: PASSKEY_MANAGER_DEFAULT_CONFIGS.nearRpcUrl.split(',')[0] || PASSKEY_MANAGER_DEFAULT_CONFIGS.nearRpcUrl,
+          nearAccountId,
+        } as RpcCallPayload,
+        confirmationConfig,
+      }
+    },
+    onEvent
+  });
+
+  if (!isApproveRemoteConfirmationSuccess(response)) {
+    const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+    throw new Error(errorDetails || 'Remote confirmation approval failed');
+  }
+  return {
+    approval: response.payload.approval,
+    requestId: response.payload.requestId,
+  };
+}
+
+/**
+ * On the originating device: sign the transactions approved on the other device with a fresh
+ * nonce and block hash. `transactions` must be the ones given to createRemoteConfirmation.
+ */
+export async function completeRemoteConfirmation({
+  ctx,
+  approval,
+  transactions,
+  rpcCall,
+  onEvent,
+  rpcOverrides,
+}: {
+  ctx: SignerWorkerManagerContext,
+  approval: string,
+  transactions: TransactionInputWasm[],
+  rpcCall: RpcCallPayload;
+  onEvent?: (update: onProgressEvents) => void;
+  rpcOverrides?: RpcOverrides;
+}): Promise<Array<{
+  signedTransaction: SignedTransaction;
+  nearAccountId: AccountId;
+  logs?: string[];
+  broadcastRpcUrl?: string;
+}>> {
+  const { txSigningRequests, resolvedRpcCall } = toSigningRequests(transactions, rpcCall);
+  const nearAccountId = rpcCall.nearAccountId;
+
+  const deviceNumber = await getDeviceNumberForAccount(ctx, nearAccountId);
+  const encryptedKeyData = await ctx.indexedDB.nearKeysDB.getEncryptedKey(nearAccountId, deviceNumber);
+  if (!encryptedKeyData) {
+    throw new Error(`No encrypted key found for account: ${nearAccountId}`);
+  }
+  const transactionContext = await ctx.nonceManager.getNonceBlockHashAndHeight(ctx.nearClient);
+
+  const response = await ctx.sendMessage({
+    message: {
+      type: WorkerRequestType.CompleteRemoteConfirmation,
+      payload: {
+        approval,
+        rpcCall: resolvedRpcCall,
+        decryption: {
+          encryptedPrivateKeyData: encryptedKeyData.encryptedData,
+          encryptedPrivateKeyIv: encryptedKeyData.iv
+        },
+        txSigningRequests,
+        transactionContext: {
+          nearPublicKeyStr: transactionContext.nearPublicKeyStr,
+          nextNonce: transactionContext.nextNonce,
+          txBlockHeight: transactionContext.txBlockHeight,
+          txBlockHash: transactionContext.txBlockHash,
+        },
+        workerPolicy: signingIntentPolicy(ctx),
+        rpcOverrides
+      }
+    },
+    onEvent
+  });
+
+  if (!isCompleteRemoteConfirmationSuccess(response)) {
+    console.error('WebAuthnManager: Remote confirmation signing failed:', response);
+    throw new Error('Remote confirmation signing failed');
+  }
+  if (!response.payload.success) {
+    throw new Error(response.payload.error || 'Remote confirmation signing failed');
+  }
+  return toSignedTransactions(response.payload, transactions.length, nearAccountId);
+}
@@ -0,0 +1,172 @@
+
+import type { EncryptedKeyData } from '../../../IndexedDBManager/passkeyNearKeysDB';
+import {
+  WorkerRequestType,
+  RelayerResult,
+  WorkerPolicy,
+  isCompleteRegistrationSuccess,
+  isPrepareRegistrationSuccess,
+  isWorkerError,
+  registrationResumeAppStateKey,
+  type OuterWrapMode,
+  type PendingRegistration,
+} from '../../../types/signer-worker';
+import { toEnumUserVerificationPolicy } from '../../../types/authenticatorOptions';
+import { VRFChallenge } from '../../../types/vrf-worker';
+import type { AuthenticatorOptions } from '../../../types/authenticatorOptions';
+import type { WebAuthnRegistrationCredential } from '../../../types/webauthn';
+import type { PasskeyManagerConfigs } from '../../../types/passkeyManager';
+import type { AccountId } from '../../../types/accountIds';
+import { getDeviceNumberForAccount } from '../getDeviceNumber';
+import { SignerWorkerManagerContext } from '..';
+import { SIGNER_WORKER_MANAGER_CONFIG } from '../../../../config';
+
+function dualPrfOutputs(credential: WebAuthnRegistrationCredential): { chacha20PrfOutput: string; ed25519PrfOutput: string } {
+  const first = credential?.clientExtensionResults?.prf?.results?.first as string | undefined;
+  const second = credential?.clientExtensionResults?.prf?.results?.second as string | undefined;
+  if (!first || !second) {
+    throw new Error('Resumable registration requires both PRF outputs of the registration credential');
+  }
+  return { chacha20PrfOutput: first, ed25519PrfOutput: second };
+}
+
+/**
+ * Phase 1 of a resumable registration: derives and encrypts the NEAR keypair in the worker
+ * (no network call), stores the encrypted key, and keeps the resume token for completeRegistration.
+ */
+export async function prepareRegistration({
+  ctx,
+  credential,
+  nearAccountId,
+  deviceNumber,
+  subKeyPaths,
+}: {
+  ctx: SignerWorkerManagerContext,
+  credential: WebAuthnRegistrationCredential,
+  nearAccountId: AccountId,
+  deviceNumber?: number,
+  /** SLIP-0010 paths of sub-keys to derive with the key; recorded with the key */
+  subKeyPaths?: string[],
+}): Promise<PendingRegistration> {
+  const response = await ctx.sendMessage({
+    message: {
+      type: WorkerRequestType.PrepareRegistration,
+      payload: {
+        dualPrfOutputs: dualPrfOutputs(credential),
+        nearAccountId,
+        credential,
+        subKeyPaths,
+      }
+    }
+  });
+  if (!isPrepareRegistrationSuccess(response)) {
+    const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+    throw new Error(`Registration prepare failed: ${errorDetails}`);
+  }
+
+  const { keys, resumeToken, resumeExpiresAtMs } = response.payload;
+  const keyData: EncryptedKeyData = {
+    nearAccountId,
+    deviceNumber: typeof deviceNumber === 'number'
+      ? deviceNumber
+      : await getDeviceNumberForAccount(ctx, nearAccountId),
+    encryptedData: keys.encryptedData,
+    iv: keys.iv,
+    timestamp: Date.now(),
+    outerWrap: keys.outerWrap as OuterWrapMode | undefined,
+    version: keys.version,
+    subKeyPaths: keys.subKeys?.length ? keys.subKeys.map((subKey) => subKey.path) : undefined,
+  };
+  // Stored before any network call, so an account the relayer creates always has its key here
+  await ctx.indexedDB.nearKeysDB.storeEncryptedKey(keyData);
+  const pending: PendingRegistration = { publicKey: keys.publicKey, resumeToken, resumeExpiresAtMs };
+  await ctx.indexedDB.clientDB.setAppState(registrationResumeAppStateKey(nearAccountId), pending);
+  return pending;
+}
+
+/**
+ * Phase 2: submits the prepared key to the relayer. Retries (also after a reload) reuse the
+ * stored resume token, so the key submitted is always the one prepareRegistration stored.
+ * Errors start with 'RegistrationResumeInvalid', 'RegistrationResumeExpired' or
+ * 'RegistrationResumeMismatch'; relayer failures are reported in `errorKind`.
+ */
+export async function completeRegistration({
+  ctx,
+  relayer,
+  nearAccountId,
+  credential,
+  vrfChallenge,
+  deterministicVrfPublicKey,
+  deviceNumber,
+  authenticatorOptions,
+  signedDelegateAction,
+  authToken,
+}: {
+  ctx: SignerWorkerManagerContext,
+  relayer: PasskeyManagerConfigs['relayer'],
+  nearAccountId: AccountId,
+  credential: WebAuthnRegistrationCredential,
+  vrfChallenge: VRFChallenge,
+  deterministicVrfPublicKey: string,
+  deviceNumber?: number,
+  authenticatorOptions?: AuthenticatorOptions,
+  signedDelegateAction?: string,
+  authToken?: string,
+}): Promise<RelayerResult> {
+  const stateKey = registrationResumeAppStateKey(nearAccountId);
+  const pending = await ctx.indexedDB.clientDB.getAppState<PendingRegistration>(stateKey);
+  if (!pending?.resumeToken) {
+    throw new Error(`No prepared registration for ${nearAccountId}`);
+  }
+
+  const workerPolicy: WorkerPolicy = {
+    allowedRpcOrigins: ctx.allowedRpcOrigins,
+    relayer: {
+      url: relayer.url,
+      authHeaderName: relayer.authHeaderName,
+      responseSchemaVersion: relayer.responseSchemaVersion,
+    },
+  };
+
+  const response = await ctx.sendMessage({
+    message: {
+      type: WorkerRequestType.CompleteRegistration,
+      payload: {
+        resumeToken: pending.resumeToken,
+        dualPrfOutputs: dualPrfOutputs(credential),
+        nearAccountId,
+        deviceNumber,
+        vrfChallenge: {
+          vrfInput: vrfChallenge.vrfInput,
+          vrfOutput: vrfChallenge.vrfOutput,
+          vrfProof: vrfChallenge.vrfProof,
+          vrfPublicKey: vrfChallenge.vrfPublicKey,
+          userId: vrfChallenge.userId,
+          rpId: vrfChallenge.rpId,
+          blockHeight: vrfChallenge.blockHeight,
+          blockHash: vrfChallenge.blockHash,
+        },
+        credential,
+        deterministicVrfPublicKey,
+        authenticatorOptions: authenticatorOptions ? {
+          userVerification: toEnumUserVerificationPolicy(authenticatorOptions.userVerification),
+          originPolicy: authenticatorOptions.originPolicy,
+          allowedAlgorithms: authenticatorOptions.allowedAlgorithms,
+        } : undefined,
+        signedDelegateAction,
+        authToken,
+        workerPolicy,
+      }
+    },
+    timeoutMs: SIGNER_WORKER_MANAGER_CONFIG.TIMEOUTS.TRANSACTION
+  });
+
+  if (!isCompleteRegistrationSuccess(response)) {
+    const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+    throw new Error(`Registration completion failed: ${errorDetails}`);
+  }
+  if (response.payload.success) {
+    await ctx.indexedDB.clientDB.setAppState(stateKey, null);
+  }
+  return response.payload;
+}
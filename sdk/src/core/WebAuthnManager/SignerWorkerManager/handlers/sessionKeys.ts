@@ -0,0 +1,130 @@
+import { SignedTransaction } from '../../../NearClient';
+import type { ActionArgsWasm } from '../../../types/actions';
+import {
+  WorkerRequestType,
+  isCreateSessionKeySuccess,
+  isRevokeSessionKeySuccess,
+  isSignWithSessionKeySuccess,
+  isWorkerError,
+  type SessionKeyCreated,
+  type SessionKeyRevoked,
+} from '../../../types/signer-worker';
+import { SignerWorkerManagerContext } from '..';
+
+/**
+ * Generate a function-call session key for `receiverId`. The worker keeps the secret key in
+ * its sealed state record, so later workers can sign with it until it expires or is revoked.
+ * Sign and send `addKeyAction` with the account's main key to register it on-chain.
+ */
+export async function createSessionKey({
+  ctx,
+  nearAccountId,
+  receiverId,
+  methodNames = [],
+  allowance,
+  ttlMs,
+}: {
+  ctx: SignerWorkerManagerContext;
+  nearAccountId: string;
+  receiverId: string;
+  methodNames?: string[];
+  allowance: string;
+  ttlMs?: number;
+}): Promise<SessionKeyCreated> {
+  const response = await ctx.sendMessage({
+    message: {
+      type: WorkerRequestType.CreateSessionKey,
+      payload: { nearAccountId, receiverId, methodNames, allowance, ttlMs },
+    },
+  });
+  if (!isCreateSessionKeySuccess(response)) {
+    const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+    throw new Error(`Session key creation failed: ${errorDetails}`);
+  }
+  return response.payload;
+}
+
+/**
+ * Sign a transaction with a session key, without confirmation UI. Receiver, methods and
+ * allowance are enforced by the worker; violations throw with the worker's error code
+ * (SessionKeyNotFound, SessionKeyExpired, SessionKeyPermissionDenied, SessionKeyAllowanceExceeded).
+ */
+export async function signWithSessionKey({
+  ctx,
+  sessionPublicKey,
+  signerAccountId,
+  receiverId,
+  nonce,
+  blockHash,
+  actions,
+  gasPrice,
+  idempotencyKey,
+}: {
+  ctx: SignerWorkerManagerContext;
+  sessionPublicKey: string;
+  signerAccountId: string;
+  receiverId: string;
+  nonce: string;
+  blockHash: string;
+  actions: ActionArgsWasm[];
+  gasPrice?: string;
+  /** Retries with the same key get the first completed result back instead of signing again */
+  idempotencyKey?: string;
+}): Promise<{ signedTransaction: SignedTransaction; logs?: string[] }> {
+  const response = await ctx.sendMessage({
+    message: {
+      type: WorkerRequestType.SignWithSessionKey,
+      payload: {
+        sessionPublicKey,
+        signerAccountId,
+        receiverId,
+        nonce,
+        blockHash,
+        actions: JSON.stringify(actions),
+        gasPrice,
+        idempotencyKey,
+      },
+    },
+  });
+  if (!isSignWithSessionKeySuccess(response)) {
+    const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+    throw new Error(`Signing with session key failed: ${errorDetails}`);
+  }
+  const result = response.payload;
+  const signedTx = result.signedTransactions?.[0];
+  if (!result.success || !signedTx?.transaction || !signedTx.signature) {
+    const code = result.errorCode ? `${result.errorCode}: ` : '';
+    throw new Error(`${code}${result.error || 'Signing with session key failed'}`);
+  }
+  return {
+    signedTransaction: new SignedTransaction({
+      transaction: signedTx.transaction,
+      signature: signedTx.signature,
+      borsh_bytes: Array.from(signedTx.borshBytes || []),
+    }),
+    logs: result.logs,
+  };
+}
+
+/**
+ * Forget a session key in the worker state and get the DeleteKey action removing it on-chain
+ */
+export async function revokeSessionKey({
+  ctx,
+  sessionPublicKey,
+}: {
+  ctx: SignerWorkerManagerContext;
+  sessionPublicKey: string;
+}): Promise<SessionKeyRevoked> {
+  const response = await ctx.sendMessage({
+    message: {
+      type: WorkerRequestType.RevokeSessionKey,
+      payload: { sessionPublicKey },
+    },
+  });
+  if (!isRevokeSessionKeySuccess(response)) {
+    const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+    throw new Error(`Session key revocation failed: ${errorDetails}`);
+  }
+  return response.payload;
+}
@@ -0,0 +1,74 @@
+import {
+  WorkerRequestType,
+  isSignNep413MessageSuccess,
+  type ConfirmationConfig,
+  type EvmSignature,
+} from '../../../types/signer-worker';
+import { getDeviceNumberForAccount } from '../getDeviceNumber';
+import { SignerWorkerManagerContext } from '..';
+import { errorMessage } from '@/utils/errors';
+
+/**
+ * Sign an EVM personal_sign (EIP-191) message with the account's stored secp256k1 key.
+ * The worker shows the decoded message for confirmation and collects the passkey itself.
+ *
+ * @param payload - 0x-prefixed hex or text message, and the NEAR account whose key blob signs it
+ * @returns Promise resolving to r, s, v, the 65-byte signature and the signer's address
+ */
+export async function signEvmPersonalMessage({ ctx, payload }: {
+  ctx: SignerWorkerManagerContext;
+  payload: {
+    message: string;
+    accountId: string;
+    confirmationConfig?: ConfirmationConfig;
+  };
+}): Promise<{
+  success: boolean;
+  accountId: string;
+  publicKey: string;
+  evm?: EvmSignature;
+  error?: string;
+}> {
+  try {
+    const deviceNumber = await getDeviceNumberForAccount(ctx, payload.accountId);
+    const encryptedKeyData = await ctx.indexedDB.nearKeysDB.getEncryptedKey(payload.accountId, deviceNumber);
+
+    if (!encryptedKeyData) {
+      throw new Error(`No encrypted key found for account: ${payload.accountId}`);
+    }
+
+    const response = await ctx.sendMessage<WorkerRequestType.SignNep413Message>({
+      message: {
+        type: WorkerRequestType.SignNep413Message,
+        payload: {
+          chain: { kind: 'evmPersonalSign', message: payload.message },
+          accountId: payload.accountId,
+          encryptedPrivateKeyData: encryptedKeyData.encryptedData,
+          encryptedPrivateKeyIv: encryptedKeyData.iv,
+          confirmationConfig: payload.confirmationConfig,
+        }
+      }
+    });
+
+    if (!isSignNep413MessageSuccess(response)) {
+      console.error('SignerWorkerManager: EVM personal_sign failed:', response);
+      throw new Error('EVM personal_sign failed');
+    }
+
+    return {
+      success: true,
+      accountId: response.payload.accountId,
+      publicKey: response.payload.publicKey,
+      evm: response.payload.evm,
+    };
+
+  } catch (error: unknown) {
+    console.error('SignerWorkerManager: EVM personal_sign error:', error);
+    return {
+      success: false,
+      accountId: '',
+      publicKey: '',
+      error: errorMessage(error) || 'Unknown error',
+    };
+  }
+}
@@ -19,7 +19,8 @@ export async function signTransactionWithKeyPair({
   receiverId,
   nonce,
   blockHash,
-  actions
+  actions,
+  idempotencyKey
 }: {
   ctx: SignerWorkerManagerContext;
   nearPrivateKey: string;
@@ -28,6 +29,8 @@ export async function signTransactionWithKeyPair({
   nonce: string;
   blockHash: string;
   actions: ActionArgsWasm[];
+  /** Retries with the same key get the first completed result back instead of signing again */
+  idempotencyKey?: string;
 }): Promise<{
   signedTransaction: SignedTransaction;
   logs?: string[];
@@ -53,7 +56,8 @@ export async function signTransactionWithKeyPair({
           receiverId,
           nonce,
           blockHash: blockHash,
-          actions: JSON.stringify(actions)
+          actions: JSON.stringify(actions),
+          idempotencyKey
         }
       }
     });
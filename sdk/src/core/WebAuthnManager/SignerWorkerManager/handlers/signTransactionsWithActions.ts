@@ -6,14 +6,19 @@ import {
   WorkerRequestType,
   TransactionPayload,
   ConfirmationConfig,
+  KeyBlobCandidate,
+  ConditionalBatchOptions,
+  ConditionalBatchEntry,
+  AwaitingSecondApproval,
   isSignTransactionsWithActionsSuccess,
 } from '../../../types/signer-worker';
 import { AccountId } from "../../../types/accountIds";
 import { SignerWorkerManagerContext } from '..';
-import { RpcCallPayload } from '../../../types/signer-worker';
+import { RpcCallPayload, RpcOverrides } from '../../../types/signer-worker';
 import { PASSKEY_MANAGER_DEFAULT_CONFIGS } from '../../../defaultConfigs';
 import { toAccountId } from '../../../types/accountIds';
 import { getDeviceNumberForAccount } from '../getDeviceNumber';
+import { workerPolicyFromHooks } from '../signingHooks';
 
 /**
  * Sign multiple transactions with shared VRF challenge and credential
@@ -24,17 +29,61 @@ export async function signTransactionsWithActions({
   transactions,
   rpcCall,
   onEvent,
-  confirmationConfigOverride
+  confirmationConfigOverride,
+  rpcOverrides,
+  broadcast,
+  validUntilBlockHeight,
+  signingPublicKey,
+  keyCandidates,
+  deduplicate,
+  previewDigest,
+  redactPaths,
+  conditionalBatch,
+  approvalToken,
+  autoStorageDeposit,
+  idempotencyKey
 }: {
   ctx: SignerWorkerManagerContext,
   transactions: TransactionInputWasm[],
   rpcCall: RpcCallPayload;
   onEvent?: (update: onProgressEvents) => void;
   confirmationConfigOverride?: ConfirmationConfig;
+  // Verification/broadcast endpoints replacing rpcCall.nearRpcUrl (see WorkerPolicy.allowedRpcOrigins)
+  rpcOverrides?: RpcOverrides;
+  // Broadcast from the worker (InvalidNonce is re-signed there); outcomes come back per tx
+  broadcast?: boolean;
+  // Refused with DeadlineExceeded once the chain is past it (checked before each worker broadcast)
+  validUntilBlockHeight?: number;
+  // Signs with the candidate blob of this key instead of the passkey's key; the worker checks
+  // the key's on-chain permission first (KeyLacksPermission)
+  signingPublicKey?: string;
+  keyCandidates?: KeyBlobCandidate[];
+  // Drop transactions identical to an earlier one; the signed transactions then skip them
+  deduplicate?: boolean;
+  // summarizeTransactions' previewDigest for the batch shown to the user; refused with
+  // PayloadChangedSincePreview when the transactions no longer match it
+  previewDigest?: string;
+  // Args values ("args.email") shown as their hash in the confirmation and the audit log
+  redactPaths?: string[];
+  // Simulate in order and sign only as far as every earlier transaction simulated successfully;
+  // the skipped transactions have no signed transaction
+  conditionalBatch?: ConditionalBatchOptions;
+  // Under WorkerPolicy.requiredApprovals 2: the token of the first approval this one completes
+  approvalToken?: string;
+  // Insert a storage_deposit ahead of token transfers to unregistered receivers; the
+  // confirmation always shows the inserted actions, marked as added automatically
+  autoStorageDeposit?: boolean;
+  // Retries with the same key get the first completed result back instead of signing again
+  idempotencyKey?: string;
 }): Promise<Array<{
   signedTransaction: SignedTransaction;
   nearAccountId: AccountId;
-  logs?: string[]
+  logs?: string[];
+  broadcastRpcUrl?: string;
+  broadcastOutcome?: unknown;
+  validUntilBlockHeight?: number;
+  removedDuplicateIndexes?: number[];
+  conditionalEntries?: ConditionalBatchEntry[];
 }>> {
   try {
     console.info(`WebAuthnManager: Starting batch transaction signing for ${transactions.length} transactions`);
@@ -67,12 +116,20 @@ export async function signTransactionsWithActions({
 
     // Credentials and PRF outputs are collected during user confirmation handshake
 
+    // The worker takes its VRF challenge from the VRF worker, proven with this device's key
+    const authenticators = await ctx.indexedDB.clientDB.getAuthenticatorsByUser(nearAccountId);
+    const vrfPublicKey = authenticators.find(a => a.deviceNumber === deviceNumber)?.vrfPublicKey;
+    const peer = vrfPublicKey
+      ? { vrfPublicKey, rpId: ctx.touchIdPrompt.getRpId() }
+      : undefined;
+
     // Create transaction signing requests
     // NOTE: nonce and blockHash are computed in confirmation flow, not here
     const txSigningRequests: TransactionPayload[] = transactions.map(tx => ({
       nearAccountId: rpcCall.nearAccountId,
       receiverId: tx.receiverId,
-      actions: JSON.stringify(tx.actions)
+      actions: JSON.stringify(tx.actions),
+      ...(tx.dependsOn?.length ? { dependsOn: tx.dependsOn } : {})
     }));
 
     const confirmationConfig = confirmationConfigOverride
@@ -86,6 +143,30 @@ export async function signTransactionsWithActions({
       nearAccountId: rpcCall.nearAccountId,
     } as RpcCallPayload;
 
+    // The worker checks overrides against allowedRpcOrigins before prompting the user
+    const hookPolicy = workerPolicyFromHooks(ctx.signingHooks);
+    const basePolicy = ctx.allowedRpcOrigins?.length
+      ? { ...hookPolicy, allowedRpcOrigins: ctx.allowedRpcOrigins }
+      : hookPolicy;
+    const escalationPolicy = ctx.depositEscalationYocto !== undefined
+      ? { ...basePolicy, depositEscalationYocto: ctx.depositEscalationYocto }
+      : basePolicy;
+    const allowancePolicy = ctx.lowAllowanceWarningYocto !== undefined
+      ? { ...escalationPolicy, lowAllowanceWarningYocto: ctx.lowAllowanceWarningYocto }
+      : escalationPolicy;
+    const schemaPolicy = ctx.argsSchemas?.length
+      ? { ...allowancePolicy, argsSchemas: ctx.argsSchemas }
+      : allowancePolicy;
+    const workerPolicy = ctx.telemetry
+      ? { ...schemaPolicy, telemetry: ctx.telemetry }
+      : schemaPolicy;
+
+    // With a function-call signing key the worker checks the batch's estimated cost against the
+    // key's allowance before prompting; best effort, the confirmation fetches its own context
+    const transactionContext = await ctx.nonceManager
+      .getNonceBlockHashAndHeight(ctx.nearClient)
+      .catch(() => undefined);
+
     const response = await ctx.sendMessage({
       message: {
         type: WorkerRequestType.SignTransactionsWithActions,
@@ -93,13 +174,35 @@ export async function signTransactionsWithActions({
           rpcCall: resolvedRpcCall,
           decryption: {
             encryptedPrivateKeyData: encryptedKeyData.encryptedData,
-            encryptedPrivateKeyIv: encryptedKeyData.iv
+            encryptedPrivateKeyIv: encryptedKeyData.iv,
+            candidates: keyCandidates
           },
           txSigningRequests: txSigningRequests,
-          confirmationConfig: confirmationConfig
+          confirmationConfig: confirmationConfig,
+          workerPolicy,
+          rpcOverrides,
+          broadcast: broadcast ?? false,
+          validUntilBlockHeight,
+          transactionContext: transactionContext && {
+            nearPublicKeyStr: transactionContext.nearPublicKeyStr,
+            nextNonce: transactionContext.nextNonce,
+            txBlockHeight: transactionContext.txBlockHeight,
+            txBlockHash: transactionContext.txBlockHash,
+            accessKeyAllowance: transactionContext.accessKeyAllowance,
+            gasPrice: transactionContext.gasPrice,
+          },
+          signingPublicKey,
+          deduplicate: deduplicate ?? false,
+          previewDigest,
+          redactPaths,
+          conditionalBatch,
+          approvalToken,
+          autoStorageDeposit: autoStorageDeposit ?? false,
+          idempotencyKey
         }
       },
-      onEvent
+      onEvent,
+      peer
     });
 
     if (!isSignTransactionsWithActionsSuccess(response)) {
@@ -107,12 +210,40 @@ export async function signTransactionsWithActions({
       throw new Error('Batch transaction signing failed');
     }
     if (!response.payload.success) {
-      throw new Error(response.payload.error || 'Batch transaction signing failed');
+      // Under dual control the first approval "fails" with the token the second one needs.
+      // After a failed dependency the withheld transactions come back signed, unbroadcast.
+      const { awaitingSecondApproval, withheldTransactions } = response.payload as {
+        awaitingSecondApproval?: AwaitingSecondApproval;
+        withheldTransactions?: number[];
+      };
+      throw Object.assign(
+        new Error(response.payload.error || 'Batch transaction signing failed'),
+        awaitingSecondApproval ? { awaitingSecondApproval } : {},
+        withheldTransactions
+          ? { withheldTransactions, signedTransactions: response.payload.signedTransactions }
+          : {}
+      );
     }
+    // Not exposed on the wasm-bindgen class; present in the serialized result
+    const {
+      broadcastOutcomes,
+      validUntilBlockHeight: deadline,
+      removedDuplicateIndexes,
+      conditionalEntries,
+    } = response.payload as {
+      broadcastOutcomes?: unknown[];
+      validUntilBlockHeight?: number;
+      removedDuplicateIndexes?: number[];
+      conditionalEntries?: ConditionalBatchEntry[];
+    };
+
     // Extract arrays from the single result - wasmResult contains arrays of all transactions
     const signedTransactions = response.payload.signedTransactions || [];
-    if (signedTransactions.length !== transactions.length) {
-      throw new Error(`Expected ${transactions.length} signed transactions but received ${signedTransactions.length}`);
+    const expectedCount = conditionalEntries
+      ? conditionalEntries.filter(entry => entry.status === 'signed').length
+      : transactions.length - (removedDuplicateIndexes?.length ?? 0);
+    if (signedTransactions.length !== expectedCount) {
+      throw new Error(`Expected ${expectedCount} signed transactions but received ${signedTransactions.length}`);
     }
 
     // Process results for each transaction using WASM types directly
@@ -127,7 +258,12 @@ export async function signTransactionsWithActions({
           borsh_bytes: Array.from(signedTx.borshBytes || [])
         }),
         nearAccountId: toAccountId(nearAccountId),
-        logs: response.payload.logs
+        logs: response.payload.logs,
+        broadcastRpcUrl: response.payload.broadcastRpcUrl,
+        broadcastOutcome: broadcastOutcomes?.[index],
+        validUntilBlockHeight: deadline ?? undefined,
+        removedDuplicateIndexes: removedDuplicateIndexes ?? undefined,
+        conditionalEntries: conditionalEntries ?? undefined
       };
     });
 
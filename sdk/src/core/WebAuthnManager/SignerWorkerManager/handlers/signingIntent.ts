@@ -0,0 +1,208 @@
+
+import { SignedTransaction } from '../../../NearClient';
+import { TransactionInputWasm, validateActionArgsWasm } from '../../../types/actions';
+import type { onProgressEvents } from '../../../types/passkeyManager';
+import {
+  WorkerRequestType,
+  TransactionPayload,
+  ConfirmationConfig,
+  SigningIntent,
+  WorkerPolicy,
+  WasmTransactionSignResult,
+  isCreateSigningIntentSuccess,
+  isExecuteSigningIntentSuccess,
+} from '../../../types/signer-worker';
+import { AccountId } from "../../../types/accountIds";
+import { SignerWorkerManagerContext } from '..';
+import { RpcCallPayload, RpcOverrides } from '../../../types/signer-worker';
+import { PASSKEY_MANAGER_DEFAULT_CONFIGS } from '../../../defaultConfigs';
+import { toAccountId } from '../../../types/accountIds';
+import { getDeviceNumberForAccount } from '../getDeviceNumber';
+import { workerPolicyFromHooks } from '../signingHooks';
+
+export function toSigningRequests(
+  transactions: TransactionInputWasm[],
+  rpcCall: RpcCallPayload,
+): { txSigningRequests: TransactionPayload[]; resolvedRpcCall: RpcCallPayload } {
+  if (transactions.length === 0) {
+    throw new Error('No transactions provided');
+  }
+  transactions.forEach((txPayload, txIndex) => {
+    txPayload.actions.forEach((action, actionIndex) => {
+      try {
+        validateActionArgsWasm(action);
+      } catch (error) {
+        throw new Error(`Transaction ${txIndex}, Action ${actionIndex} validation failed: ${error instanceof Error ? error.message : 'Unknown error'}`);
+      }
+    });
+  });
+
+  // Must match the confirmed batch exactly: the worker recomputes its digest on execution
+  const txSigningRequests: TransactionPayload[] = transactions.map(tx => ({
+    nearAccountId: rpcCall.nearAccountId,
+    receiverId: tx.receiverId,
+    actions: JSON.stringify(tx.actions)
+  }));
+  const resolvedRpcCall = {
+    contractId: rpcCall.contractId || PASSKEY_MANAGER_DEFAULT_CONFIGS.contractId,
+    nearRpcUrl: rpcCall.nearRpcUrl || (PASSKEY_MANAGER_DEFAULT_CONFIGS.nearRpcUrl.split(',')[0] || PASSKEY_MANAGER_DEFAULT_CONFIGS.nearRpcUrl),
+    nearAccountId: rpcCall.nearAccountId,
+  } as RpcCallPayload;
+  return { txSigningRequests, resolvedRpcCall };
+}
+
+export function signingIntentPolicy(ctx: SignerWorkerManagerContext): WorkerPolicy | undefined {
+  const policy: WorkerPolicy = { ...workerPolicyFromHooks(ctx.signingHooks) };
+  if (ctx.allowedRpcOrigins?.length) policy.allowedRpcOrigins = ctx.allowedRpcOrigins;
+  if (ctx.maxSigningIntentTtlMs !== undefined) policy.maxSigningIntentTtlMs = ctx.maxSigningIntentTtlMs;
+  if (ctx.depositEscalationYocto !== undefined) policy.depositEscalationYocto = ctx.depositEscalationYocto;
+  if (ctx.lowAllowanceWarningYocto !== undefined) policy.lowAllowanceWarningYocto = ctx.lowAllowanceWarningYocto;
+  if (ctx.argsSchemas?.length) policy.argsSchemas = ctx.argsSchemas;
+  return Object.keys(policy).length ? policy : undefined;
+}
+
+/**
+ * Confirm a batch now and sign it later: runs the usual confirmation and returns an intent
+ * token for executeSigningIntent instead of signatures
+ */
+export async function createSigningIntent({
+  ctx,
+  transactions,
+  rpcCall,
+  ttlMs,
+  onEvent,
+  confirmationConfigOverride,
+}: {
+  ctx: SignerWorkerManagerContext,
+  transactions: TransactionInputWasm[],
+  rpcCall: RpcCallPayload;
+  ttlMs?: number;
+  onEvent?: (update: onProgressEvents) => void;
+  confirmationConfigOverride?: ConfirmationConfig;
+}): Promise<SigningIntent> {
+  const { txSigningRequests, resolvedRpcCall } = toSigningRequests(transactions, rpcCall);
+  const confirmationConfig = confirmationConfigOverride
+    || ctx.userPreferencesManager.getConfirmationConfig();
+
+  const response = await ctx.sendMessage({
+    message: {
+      type: WorkerRequestType.CreateSigningIntent,
+      payload: {
+        rpcCall: resolvedRpcCall,
+        txSigningRequests,
+        confirmationConfig,
+        workerPolicy: signingIntentPolicy(ctx),
+        ttlMs,
+      }
+    },
+    onEvent
+  });
+
+  if (!isCreateSigningIntentSuccess(response)) {
+    console.error('WebAuthnManager: Signing intent creation failed:', response);
+    throw new Error('Signing intent creation failed');
+  }
+  const intent = response.payload;
+  return {
+    intentToken: intent.intentToken,
+    intentId: intent.intentId,
+    nearAccountId: intent.nearAccountId,
+    intentDigest: intent.intentDigest,
+    txCount: intent.txCount,
+    expiresAtMs: intent.expiresAtMs,
+  };
+}
+
+/**
+ * Sign the transactions of a signing intent. Failures carry the worker's error code prefix
+ * (SigningIntentInvalid, SigningIntentExpired, SigningIntentReplayed, SigningIntentPayloadMismatch)
+ */
+export async function executeSigningIntent({
+  ctx,
+  intentToken,
+  transactions,
+  rpcCall,
+  onEvent,
+  rpcOverrides,
+}: {
+  ctx: SignerWorkerManagerContext,
+  intentToken: string,
+  transactions: TransactionInputWasm[],
+  rpcCall: RpcCallPayload;
+  onEvent?: (update: onProgressEvents) => void;
+  rpcOverrides?: RpcOverrides;
+}): Promise<Array<{
+  signedTransaction: SignedTransaction;
+  nearAccountId: AccountId;
+  logs?: string[];
+  broadcastRpcUrl?: string;
+}>> {
+  const { txSigningRequests, resolvedRpcCall } = toSigningRequests(transactions, rpcCall);
+  const nearAccountId = rpcCall.nearAccountId;
+
+  const deviceNumber = await getDeviceNumberForAccount(ctx, nearAccountId);
+  const encryptedKeyData = await ctx.indexedDB.nearKeysDB.getEncryptedKey(nearAccountId, deviceNumber);
+  if (!encryptedKeyData) {
+    throw new Error(`No encrypted key found for account: ${nearAccountId}`);
+  }
+
+  const response = await ctx.sendMessage({
+    message: {
+      type: WorkerRequestType.ExecuteSigningIntent,
+      payload: {
+        intentToken,
+        rpcCall: resolvedRpcCall,
+        decryption: {
+          encryptedPrivateKeyData: encryptedKeyData.encryptedData,
+          encryptedPrivateKeyIv: encryptedKeyData.iv
+        },
+        txSigningRequests,
+        workerPolicy: signingIntentPolicy(ctx),
+        rpcOverrides
+      }
+    },
+    onEvent
+  });
+
+  if (!isExecuteSigningIntentSuccess(response)) {
+    console.error('WebAuthnManager: Signing intent execution failed:', response);
+    throw new Error('Signing intent execution failed');
+  }
+  if (!response.payload.success) {
+    throw new Error(response.payload.error || 'Signing intent execution failed');
+  }
+  return toSignedTransactions(response.payload, transactions.length, nearAccountId);
+}
+
+/** Signed transactions of a worker sign result, checking one came back per requested transaction */
+export function toSignedTransactions(
+  result: WasmTransactionSignResult,
+  expectedCount: number,
+  nearAccountId: string,
+): Array<{
+  signedTransaction: SignedTransaction;
+  nearAccountId: AccountId;
+  logs?: string[];
+  broadcastRpcUrl?: string;
+}> {
+  const signedTransactions = result.signedTransactions || [];
+  if (signedTransactions.length !== expectedCount) {
+    throw new Error(`Expected ${expectedCount} signed transactions but received ${signedTransactions.length}`);
+  }
+
+  return signedTransactions.map((signedTx, index) => {
+    if (!signedTx || !signedTx.transaction || !signedTx.signature) {
+      throw new Error(`Incomplete signed transaction data received for transaction ${index + 1}`);
+    }
+    return {
+      signedTransaction: new SignedTransaction({
+        transaction: signedTx.transaction,
+        signature: signedTx.signature,
+        borsh_bytes: Array.from(signedTx.borshBytes || [])
+      }),
+      nearAccountId: toAccountId(nearAccountId),
+      logs: result.logs,
+      broadcastRpcUrl: result.broadcastRpcUrl
+    };
+  });
+}
@@ -0,0 +1,104 @@
+
+import {
+  WorkerRequestType,
+  RelayerResult,
+  WorkerPolicy,
+  isSubmitToRelayerSuccess,
+  isWorkerError,
+} from '../../../types/signer-worker';
+import { toEnumUserVerificationPolicy } from '../../../types/authenticatorOptions';
+import { serializeRegistrationCredentialWithPRF } from '../../credentialsHelpers';
+import { VRFChallenge } from '../../../types/vrf-worker';
+import type { AuthenticatorOptions } from '../../../types/authenticatorOptions';
+import type { WebAuthnRegistrationCredential } from '../../../types/webauthn';
+import type { PasskeyManagerConfigs } from '../../../types/passkeyManager';
+import { SignerWorkerManagerContext } from '..';
+import { SIGNER_WORKER_MANAGER_CONFIG } from '../../../../config';
+import { isObject, isString } from '../../../WalletIframe/validation';
+
+/**
+ * Create the account through the relayer from the signer worker: the worker posts the
+ * registration args (PRF outputs removed), validates the relayer's response and maps its
+ * failures to errorKind codes (RelayerAccountExists, RelayerQuotaExceeded, RelayerRejected,
+ * RelayerUnavailable, RelayerResponseInvalid). The relayer URL must be on allowedRpcOrigins.
+ */
+export async function submitToRelayer({
+  ctx,
+  relayer,
+  nearAccountId,
+  newPublicKey,
+  deviceNumber,
+  vrfChallenge,
+  credential,
+  deterministicVrfPublicKey,
+  authenticatorOptions,
+  signedDelegateAction,
+  authToken,
+}: {
+  ctx: SignerWorkerManagerContext,
+  relayer: PasskeyManagerConfigs['relayer'],
+  nearAccountId: string,
+  newPublicKey: string,
+  deviceNumber?: number,
+  vrfChallenge: VRFChallenge,
+  credential: WebAuthnRegistrationCredential | PublicKeyCredential,
+  deterministicVrfPublicKey: string,
+  authenticatorOptions?: AuthenticatorOptions,
+  /** Base64 borsh SignedDelegateAction, for delegate-action onboarding */
+  signedDelegateAction?: string,
+  /** Sent in relayer.authHeaderName */
+  authToken?: string,
+}): Promise<RelayerResult> {
+  const isSerialized = isObject(credential)
+    && isString((credential as { response?: { attestationObject?: unknown } }).response?.attestationObject);
+  const serializedCredential = isSerialized
+    ? credential as WebAuthnRegistrationCredential
+    : serializeRegistrationCredentialWithPRF({ credential: credential as PublicKeyCredential });
+
+  const workerPolicy: WorkerPolicy = {
+    allowedRpcOrigins: ctx.allowedRpcOrigins,
+    relayer: {
+      url: relayer.url,
+      authHeaderName: relayer.authHeaderName,
+      responseSchemaVersion: relayer.responseSchemaVersion,
+    },
+  };
+
+  const response = await ctx.sendMessage({
+    message: {
+      type: WorkerRequestType.SubmitToRelayer,
+      payload: {
+        nearAccountId,
+        newPublicKey,
+        deviceNumber,
+        vrfChallenge: {
+          vrfInput: vrfChallenge.vrfInput,
+          vrfOutput: vrfChallenge.vrfOutput,
+          vrfProof: vrfChallenge.vrfProof,
+          vrfPublicKey: vrfChallenge.vrfPublicKey,
+          userId: vrfChallenge.userId,
+          rpId: vrfChallenge.rpId,
+          blockHeight: vrfChallenge.blockHeight,
+          blockHash: vrfChallenge.blockHash,
+        },
+        credential: serializedCredential,
+        deterministicVrfPublicKey,
+        authenticatorOptions: authenticatorOptions ? {
+          userVerification: toEnumUserVerificationPolicy(authenticatorOptions.userVerification),
+          originPolicy: authenticatorOptions.originPolicy,
+          allowedAlgorithms: authenticatorOptions.allowedAlgorithms,
+        } : undefined,
+        signedDelegateAction,
+        authToken,
+        workerPolicy,
+      }
+    },
+    timeoutMs: SIGNER_WORKER_MANAGER_CONFIG.TIMEOUTS.TRANSACTION
+  });
+
+  if (!isSubmitToRelayerSuccess(response)) {
+    const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+    throw new Error(`Relayer submission failed: ${errorDetails}`);
+  }
+  return response.payload;
+}
@@ -13,14 +13,64 @@ import {
   WorkerProgressResponse,
   WorkerErrorResponse,
   WorkerRequestTypeMap,
+  isStandaloneWorkerRequest,
+  type OneShotWorkerRequestType,
+  isGetKeyUsageStatsSuccess,
+  isGetWorkerInfoSuccess,
+  isGetSessionStatusSuccess,
+  isBuildRedirectPayloadSuccess,
+  isValidateDecryptionCapabilitySuccess,
+  isListActiveAccountsSuccess,
+  isWipeAccountStateSuccess,
+  isValidateArgsSchemasSuccess,
+  isSummarizeTransactionsSuccess,
+  isMigrateEncryptedBlobsSuccess,
+  KEY_USAGE_RECORD_APP_STATE_KEY,
+  type ArgsSchema,
+  type TransactionsSummary,
+  type MigrateEncryptedBlobsResult,
+  type CredentialCreationOptionsJSON,
+  type StoredEncryptedBlob,
+  type KeyUsageRecord,
+  type WorkerStateRecord,
+  type PrfFallbackScheme,
+  type TelemetryPolicy,
+  type MessageSizeLimits,
+  type JournalEntry,
+  type RecoverPendingOperationsResult,
+  type RequestLimits,
+  type PendingRequests,
+  type CancelledRequest,
+  type SessionKeyCreated,
+  type SessionKeyRevoked,
 } from '../../types/signer-worker';
 import { VRFChallenge } from '../../types/vrf-worker';
 import { VrfWorkerManager } from '../VrfWorkerManager';
 import type { ActionArgsWasm, TransactionInputWasm } from '../../types/actions';
-import type { onProgressEvents } from '../../types/passkeyManager';
+import type { onProgressEvents, PasskeyManagerConfigs, RegistrationDryRunReport } from '../../types/passkeyManager';
 import type { AuthenticatorOptions } from '../../types/authenticatorOptions';
 import { AccountId } from "../../types/accountIds";
-import { ConfirmationConfig } from '../../types/signer-worker';
+import {
+  ConfirmationConfig,
+  RpcOverrides,
+  type DeployManifest,
+  type KeyBlobCandidate,
+  type ConditionalBatchOptions,
+  type ConditionalBatchEntry,
+  type KeyUsageStats,
+  type PendingRegistration,
+  type RelayerResult,
+  type RemoteConfirmationRequest,
+  type SigningIntent,
+  type StagingContractInterface,
+  type WorkerInfo,
+  type SessionStatus,
+  type RedirectPayload,
+  type DecryptionCapability,
+  type ActiveAccounts,
+  type AccountWipeSummary,
+  type EvmSignature,
+} from '../../types/signer-worker';
 import { toAccountId } from '../../types/accountIds';
 import { getDeviceNumberForAccount } from './getDeviceNumber';
 import { isObject } from '../../WalletIframe/validation';
@@ -29,10 +79,27 @@ import {
   decryptPrivateKeyWithPrf,
   checkCanRegisterUser,
   signTransactionsWithActions,
+  deployLargeContract,
+  createSigningIntent,
+  executeSigningIntent,
+  createSessionKey,
+  signWithSessionKey,
+  revokeSessionKey,
+  createRemoteConfirmation,
+  approveRemoteConfirmation,
+  completeRemoteConfirmation,
+  submitToRelayer,
+  prepareRegistration,
+  completeRegistration,
+  exportAccountBundle,
+  importAccountBundle,
   recoverKeypairFromPasskey,
   extractCosePublicKey,
+  buildCredentialCreationOptions,
+  recoverPendingOperations,
   signTransactionWithKeyPair,
   signNep413Message,
+  signEvmPersonalMessage,
   requestRegistrationCredentialConfirmation,
   deriveNearKeypairAndEncryptFromSerialized,
 } from './handlers';
@@ -40,12 +107,32 @@ import {
   SecureConfirmMessageType,
   handlePromptUserConfirmInJsMainThread,
 } from './confirmTxFlow';
-import { RpcCallPayload } from '../../types/signer-worker';
+import { notifyConfirmationBehaviorChanged } from './confirmTxFlow/flows/common';
+import { connectConfirmationOriginBridge, disconnectConfirmationOriginBridge } from './confirmTxFlow/confirmationOriginBridge';
+import {
+  OuterWrapMode,
+  SubKeyEntry,
+  RpcCallPayload,
+  SignerWireFormat,
+  SecretSealingMode,
+  SIGNER_WORKER_INITIALIZE,
+  SIGNER_WORKER_INITIALIZED,
+  SIGNER_WORKER_CONNECT_PEER_PORT,
+  type SignerPeerSession,
+  type AuthenticatorInfo,
+  type AuthenticatorModel,
+} from '../../types/signer-worker';
 import { UserPreferencesManager } from '../userPreferences';
 import { NonceManager } from '../../nonceManager';
 import { WebAuthnAuthenticationCredential, WebAuthnRegistrationCredential } from '../../types';
 import type { RegistrationCredentialConfirmationPayload } from './handlers/validation';
 import { toError } from '@/utils/errors';
+import { SigningHooks, handleSigningHookMessage } from './signingHooks';
+import { handleOperationJournalMessage, removeJournalEntries } from './operationJournal';
+import { encodeCbor, decodeCbor } from './cborWire';
+import { registerWorkerSessionKey, sealRequestSecrets } from './sealedSecrets';
+import { WorkerRequestRegistry, isTrackedRequest } from './requestRegistry';
+import { WorkerStateStore } from './workerState';
 
 
 export interface SignerWorkerManagerContext {
@@ -56,16 +143,32 @@ export interface SignerWorkerManagerContext {
   userPreferencesManager: UserPreferencesManager;
   nonceManager: NonceManager;
   rpIdOverride?: string;
-  sendMessage: <T extends keyof WorkerRequestTypeMap>(args: {
+  signingHooks?: SigningHooks;
+  allowedRpcOrigins?: string[];
+  maxSigningIntentTtlMs?: number;
+  depositEscalationYocto?: string;
+  lowAllowanceWarningYocto?: string;
+  prfFallbackSchemes?: PrfFallbackScheme[];
+  telemetry?: TelemetryPolicy;
+  argsSchemas?: ArgsSchema[];
+  sendMessage: <T extends OneShotWorkerRequestType>(args: {
     message: {
       type: T;
       payload: WorkerRequestTypeMap[T]['request']
     };
     onEvent?: (update: onProgressEvents) => void;
     timeoutMs?: number;
+    peer?: SignerPeerTarget;
   }) => Promise<WorkerResponseForRequest<T>>;
 }
 
+/** VRF session the signer worker may request challenges from over a peer port */
+export interface SignerPeerTarget {
+  /** Key the challenges must be proven with (the account's authenticator vrfPublicKey) */
+  vrfPublicKey: string;
+  rpId: string;
+}
+
 /**
  * WebAuthnWorkers handles PRF, workers, and COSE operations
  *
@@ -80,6 +183,26 @@ export class SignerWorkerManager {
   private nearClient: NearClient;
   private userPreferencesManager: UserPreferencesManager;
   private nonceManager: NonceManager;
+  private signingHooks?: SigningHooks;
+  private allowedRpcOrigins?: string[];
+  private allowedRedirectOrigins?: string[];
+  private maxSigningIntentTtlMs?: number;
+  private depositEscalationYocto?: string;
+  private lowAllowanceWarningYocto?: string;
+  private prfFallbackSchemes?: PrfFallbackScheme[];
+  private telemetry?: TelemetryPolicy;
+  private argsSchemas?: ArgsSchema[];
+  private messageSizeLimits?: MessageSizeLimits;
+  private maxSessionDurationMs?: number;
+  private trustedConfirmationOrigin?: string;
+  private knownAuthenticators?: AuthenticatorModel[];
+  private wireFormat: SignerWireFormat = 'json';
+  private secretSealing: SecretSealingMode = 'off';
+  private outerWrapKey?: CryptoKey;
+  // Last key usage record a worker handed back (undefined until read from IndexedDB)
+  private keyUsageRecord?: KeyUsageRecord | null;
+  private requestRegistry = new WorkerRequestRegistry();
+  private workerState: WorkerStateStore;
 
   constructor(
     vrfWorkerManager: VrfWorkerManager,
@@ -90,6 +213,7 @@ export class SignerWorkerManager {
     enableSafariGetWebauthnRegistrationFallback: boolean = false,
   ) {
     this.indexedDB = IndexedDBManager;
+    this.workerState = new WorkerStateStore(this.indexedDB);
     this.touchIdPrompt = new TouchIdPrompt(rpIdOverride, enableSafariGetWebauthnRegistrationFallback);
     this.vrfWorkerManager = vrfWorkerManager;
     this.nearClient = nearClient;
@@ -107,9 +231,183 @@ export class SignerWorkerManager {
       userPreferencesManager: this.userPreferencesManager,
       nonceManager: this.nonceManager,
       rpIdOverride: this.touchIdPrompt.getRpId(),
+      signingHooks: this.signingHooks,
+      allowedRpcOrigins: this.allowedRpcOrigins,
+      maxSigningIntentTtlMs: this.maxSigningIntentTtlMs,
+      depositEscalationYocto: this.depositEscalationYocto,
+      lowAllowanceWarningYocto: this.lowAllowanceWarningYocto,
+      prfFallbackSchemes: this.prfFallbackSchemes,
+      telemetry: this.telemetry,
+      argsSchemas: this.argsSchemas,
     };
   }
 
+  /**
+   * Register (or clear) the integrator's pre-sign/post-sign hooks.
+   * Applies to subsequent signTransactionsWithActions requests.
+   */
+  setSigningHooks(hooks?: SigningHooks): void {
+    this.signingHooks = hooks;
+  }
+
+  /**
+   * Origins that per-request rpcOverrides may point at (sent as WorkerPolicy.allowedRpcOrigins).
+   * Without any, the worker refuses overrides.
+   */
+  setAllowedRpcOrigins(origins?: string[]): void {
+    this.allowedRpcOrigins = origins;
+  }
+
+  /**
+   * Origins buildRedirectPayload may send users back to (sent as
+   * WorkerPolicy.allowedRedirectOrigins). Without any, every callback URL is refused.
+   */
+  setAllowedRedirectOrigins(origins?: string[]): void {
+    this.allowedRedirectOrigins = origins;
+  }
+
+  /**
+   * Longest lifetime of signing intents (sent as WorkerPolicy.maxSigningIntentTtlMs).
+   * Without it, the worker's default ceiling (1 hour) applies.
+   */
+  setMaxSigningIntentTtlMs(ttlMs?: number): void {
+    this.maxSigningIntentTtlMs = ttlMs;
+  }
+
+  /**
+   * How long a worker session may use keys before a fresh passkey assertion (sent with every
+   * request as WorkerPolicy.maxSessionDurationMs). Without it, sessions do not expire.
+   */
+  setMaxSessionDurationMs(durationMs?: number): void {
+    this.maxSessionDurationMs = durationMs;
+  }
+
+  /**
+   * Deposit above which signing confirmations warn (sent as WorkerPolicy.depositEscalationYocto).
+   * Without it, the worker's default (10 NEAR) applies.
+   */
+  setDepositEscalationYocto(yocto?: string): void {
+    this.depositEscalationYocto = yocto;
+  }
+
+  /**
+   * Function-call key allowance below which signing confirmations warn (sent as
+   * WorkerPolicy.lowAllowanceWarningYocto). Without it, the worker's default (0.05 NEAR) applies.
+   */
+  setLowAllowanceWarningYocto(yocto?: string): void {
+    this.lowAllowanceWarningYocto = yocto;
+  }
+
+  /**
+   * Key wrapping for registrations whose authenticator has no PRF (sent as WorkerPolicy.prfFallbackSchemes).
+   * Without any, such registrations fail with errorCode 'PrfUnsupportedByAuthenticator'.
+   */
+  setPrfFallbackSchemes(schemes?: PrfFallbackScheme[]): void {
+    this.prfFallbackSchemes = schemes;
+  }
+
+  /**
+   * Remote telemetry sink of signing requests (sent as WorkerPolicy.telemetry).
+   * Without it, workers keep their counters in memory only.
+   */
+  setTelemetry(telemetry?: TelemetryPolicy): void {
+    this.telemetry = telemetry;
+  }
+
+  /**
+   * Largest message frames workers parse (WorkerPolicy.messageSizeLimits), handed to each worker
+   * before its request frame. Without it, the worker's defaults (1 MiB, 8 MiB for deploys) apply.
+   */
+  setMessageSizeLimits(limits?: MessageSizeLimits): void {
+    this.messageSizeLimits = limits;
+  }
+
+  /**
+   * Origin of the wallet iframe that renders confirmations (WorkerPolicy.trustedConfirmationOrigin),
+   * fixed in each worker at Initialize. Each worker gets a confirmation origin bridge, which only
+   * attests decisions from that origin; others fail with errorCode 'UntrustedConfirmationOrigin'.
+   */
+  setTrustedConfirmationOrigin(origin?: string): void {
+    this.trustedConfirmationOrigin = origin;
+  }
+
+  /**
+   * AAGUIDs the worker names in registration results and parsed attestations, on top of its
+   * built-in table (entries with the same AAGUID replace built-in ones). Applied at Initialize.
+   */
+  setKnownAuthenticators(entries?: AuthenticatorModel[]): void {
+    this.knownAuthenticators = entries;
+  }
+
+  /**
+   * Encoding for request/response frames of subsequent worker operations.
+   * 'cbor' avoids JSON number arrays for large binary payloads (e.g. DeployContract code).
+   */
+  setWireFormat(wireFormat: SignerWireFormat): void {
+    this.wireFormat = wireFormat;
+  }
+
+  /**
+   * Sealing of PRF outputs, private keys and passphrases to the worker's session key.
+   * 'seal' falls back to plaintext where sealing is unavailable (no WebCrypto X25519);
+   * 'require' also sets requireEncryptedSecrets, so the worker refuses plaintext secrets.
+   */
+  setSecretSealing(mode: SecretSealingMode): void {
+    this.secretSealing = mode;
+  }
+
+  /**
+   * Register (or clear) a non-extractable AES-GCM CryptoKey for the outer wrap of key blobs.
+   * Newly encrypted blobs are wrapped with it; blobs wrapped earlier need it to decrypt.
+   */
+  setOuterWrapKey(key?: CryptoKey): void {
+    this.outerWrapKey = key;
+  }
+
+  /**
+   * In-flight limits across the worker pool (defaults: 4 running, 16 queued). Raising
+   * maxConcurrent starts queued requests immediately.
+   */
+  setRequestLimits(limits: RequestLimits): void {
+    this.requestRegistry.setLimits(limits);
+  }
+
+  /** Requests running on the pool's workers or waiting for a slot, oldest first */
+  listPendingRequests(): PendingRequests {
+    return this.requestRegistry.list();
+  }
+
+  /**
+   * Cancels a queued request (listPendingRequests names them), which then fails with
+   * RequestCancelled. Running requests are not interrupted.
+   */
+  cancelRequest(requestId: string): CancelledRequest {
+    return this.requestRegistry.cancel(requestId);
+  }
+
+  /** Key usage record for the next worker, read from IndexedDB once */
+  private async getKeyUsageRecord(): Promise<KeyUsageRecord | null> {
+    if (this.keyUsageRecord === undefined) {
+      try {
+        this.keyUsageRecord = (await this.indexedDB.clientDB.getAppState<KeyUsageRecord>(KEY_USAGE_RECORD_APP_STATE_KEY)) ?? null;
+      } catch (error: unknown) {
+        console.warn('SignerWorkerManager: Key usage record unavailable:', error);
+        this.keyUsageRecord = null;
+      }
+    }
+    return this.keyUsageRecord;
+  }
+
+  /** Keeps the record a signing worker handed back for the workers after it */
+  private async storeKeyUsageRecord(record: KeyUsageRecord): Promise<void> {
+    this.keyUsageRecord = record;
+    try {
+      await this.indexedDB.clientDB.setAppState(KEY_USAGE_RECORD_APP_STATE_KEY, record);
+    } catch (error: unknown) {
+      console.warn('SignerWorkerManager: Failed to store key usage record:', error);
+    }
+  }
+
   createSecureWorker(): Worker {
     // Simple path resolution - build:all copies worker files to /workers/
     const workerUrl = new URL(SIGNER_WORKER_MANAGER_CONFIG.WORKER.URL, window.location.origin);
@@ -153,6 +451,7 @@ export class SignerWorkerManager {
   private terminateAndReplaceWorker(worker: Worker): void {
     // Always terminate workers to clear memory
     worker.terminate();
+    disconnectConfirmationOriginBridge(worker);
     // Asynchronously create a replacement worker for the pool
     this.createReplacementWorker();
   }
@@ -246,17 +545,62 @@ export class SignerWorkerManager {
     }
   }
 
-  private async sendMessage<T extends WorkerRequestType>({
+  private async sendMessage<T extends OneShotWorkerRequestType>(args: {
+    message: { type: T; payload: WorkerRequestTypeMap[T]['request'] };
+    onEvent?: (update: onProgressEvents) => void;
+    timeoutMs?: number;
+    peer?: SignerPeerTarget;
+  }): Promise<WorkerResponseForRequest<T>> {
+    // Its workers close after one request, so nothing would be left running to refresh
+    if (isStandaloneWorkerRequest(args.message.type)) {
+      throw new Error(`Request type ${args.message.type} needs a standalone signer worker (see STANDALONE_WORKER_REQUEST_TYPES)`);
+    }
+    const requestId = this.requestRegistry.nextRequestId();
+    // Requests that may change the persisted worker state run one at a time
+    const run = () => this.workerState.exclusive(args.message.type, () => this.runOnWorker({ ...args, requestId }));
+    if (!isTrackedRequest(args.message.type)) {
+      return run();
+    }
+    // Waits for a slot; the timeout only starts once the request runs
+    await this.requestRegistry.admit(requestId, args.message.type);
+    try {
+      return await run();
+    } finally {
+      this.requestRegistry.release(requestId);
+    }
+  }
+
+  private async runOnWorker<T extends WorkerRequestType>({
     message,
     onEvent,
-    timeoutMs = SIGNER_WORKER_MANAGER_CONFIG.TIMEOUTS.DEFAULT // 60s
+    timeoutMs = SIGNER_WORKER_MANAGER_CONFIG.TIMEOUTS.DEFAULT, // 60s
+    peer,
+    requestId,
   }: {
     message: { type: T; payload: WorkerRequestTypeMap[T]['request'] };
     onEvent?: (update: onProgressEvents) => void;
     timeoutMs?: number;
+    peer?: SignerPeerTarget;
+    requestId: string;
   }): Promise<WorkerResponseForRequest<T>> {
 
     const worker = this.getWorkerFromPool();
+    const wireFormat = this.wireFormat;
+    const secretSealing = this.secretSealing;
+    const outerWrapKey = this.outerWrapKey;
+    const messageSizeLimits = this.messageSizeLimits;
+    const maxSessionDurationMs = this.maxSessionDurationMs;
+    const trustedConfirmationOrigin = this.trustedConfirmationOrigin;
+    const knownAuthenticators = this.knownAuthenticators;
+    const keyUsageRecord = await this.getKeyUsageRecord();
+    const workerState = await this.workerState.sidecar();
+    if (peer) {
+      await this.connectPeerPort(worker, peer);
+    }
+    if (trustedConfirmationOrigin) {
+      // An origin the bridge cannot parse is refused by the worker at Initialize
+      try { connectConfirmationOriginBridge(worker, trustedConfirmationOrigin); } catch {}
+    }
 
     return new Promise((resolve, reject) => {
       const timeoutId = setTimeout(() => {
@@ -272,6 +616,8 @@ export class SignerWorkerManager {
       }, timeoutMs);
 
       const responses: WorkerResponseForRequest<T>[] = [];
+      // Journal entries of this request, dropped once it succeeds
+      const journaled: JournalEntry[] = [];
 
       worker.onmessage = async (event) => {
         try {
@@ -279,8 +625,35 @@ export class SignerWorkerManager {
           if (event?.data?.type === 'WORKER_READY' || event?.data?.ready) {
             return; // not a response to an operation
           }
+          // Handshake complete: seal the request's secrets to the session key, then send it
+          if (event?.data?.type === SIGNER_WORKER_INITIALIZED) {
+            if (event.data.error) {
+              clearTimeout(timeoutId);
+              this.terminateAndReplaceWorker(worker);
+              reject(new Error(`Worker initialize failed: ${event.data.error}`));
+              return;
+            }
+            if (secretSealing !== 'off') {
+              registerWorkerSessionKey(worker, event.data.sessionPublicKey);
+              formattedMessage.payload = await this.sealPayload(
+                message.type,
+                formattedMessage.payload,
+                event.data.sessionPublicKey,
+                secretSealing,
+              );
+            }
+            if (wireFormat === 'cbor') {
+              const frame = encodeCbor(formattedMessage);
+              worker.postMessage(frame, [frame.buffer]);
+            } else {
+              worker.postMessage({ ...formattedMessage, ...sidecar });
+            }
+            return;
+          }
+          // CBOR response frames decode to the same shape as JSON responses
+          const data = event.data instanceof Uint8Array ? decodeCbor(event.data) : event.data;
           // Use strong typing from WASM-generated types
-          const response = event.data as WorkerResponseForRequest<T>;
+          const response = data as WorkerResponseForRequest<T>;
           responses.push(response);
 
           // Intercept secure confirm handshake
@@ -296,6 +669,22 @@ export class SignerWorkerManager {
             return; // do not treat as a worker response, continue listening for more messages
           }
 
+          // The worker got no countdown ack in time: the auto-proceed UI switches to requireClick
+          if (event.data.type === SecureConfirmMessageType.CONFIRMATION_BEHAVIOR_CHANGED) {
+            notifyConfirmationBehaviorChanged(String(event.data.data?.requestId ?? ''));
+            return;
+          }
+
+          // Persist journal entries posted before broadcasts and relayer submissions
+          if (await handleOperationJournalMessage(this.indexedDB, event.data, journaled)) {
+            return; // not a worker response
+          }
+
+          // Intercept pre-sign/post-sign hook messages
+          if (await handleSigningHookMessage(this.signingHooks, event.data, worker)) {
+            return; // not a worker response
+          }
+
           // Handle progress updates using WASM-generated numeric enum values
           if (isWorkerProgress(response)) {
             const progressResponse = response as WorkerProgressResponse;
@@ -309,6 +698,7 @@ export class SignerWorkerManager {
             this.terminateAndReplaceWorker(worker);
             const errorResponse = response as WorkerErrorResponse;
             console.error('Worker error response:', errorResponse);
+            await this.workerState.update(message.type, (errorResponse.payload as { workerStateRecord?: WorkerStateRecord })?.workerStateRecord);
             reject(new Error(errorResponse.payload.error));
             return;
           }
@@ -317,6 +707,16 @@ export class SignerWorkerManager {
           if (isWorkerSuccess(response)) {
             clearTimeout(timeoutId);
             this.terminateAndReplaceWorker(worker);
+            if (response.warnings?.length) {
+              console.warn('Worker request used deprecated paths:', response.warnings);
+            }
+            const updatedRecord = (response.payload as { keyUsageRecord?: KeyUsageRecord })?.keyUsageRecord;
+            if (updatedRecord) {
+              await this.storeKeyUsageRecord(updatedRecord);
+            }
+            await this.workerState.update(message.type, (response.payload as { workerStateRecord?: WorkerStateRecord })?.workerStateRecord);
+            // Errors keep their entries: a failed broadcast may still have gone out
+            await removeJournalEntries(this.indexedDB, journaled);
             resolve(response as WorkerResponseForRequest<T>);
             return;
           }
@@ -369,13 +769,84 @@ export class SignerWorkerManager {
       // Format message for Rust SignerWorkerMessage structure using WASM types
       const formattedMessage = {
         type: message.type, // Numeric enum value from WorkerRequestType
-        payload: message.payload,
+        payload: toPlainPayload(message.payload),
+        requestId,
       };
+      if (secretSealing === 'require') {
+        const payload = formattedMessage.payload as { workerPolicy?: Record<string, unknown> };
+        formattedMessage.payload = {
+          ...payload,
+          workerPolicy: { ...payload.workerPolicy, requireEncryptedSecrets: true },
+        } as typeof formattedMessage.payload;
+      }
+      if (maxSessionDurationMs !== undefined) {
+        const payload = formattedMessage.payload as { workerPolicy?: Record<string, unknown> };
+        formattedMessage.payload = {
+          ...payload,
+          workerPolicy: { ...payload.workerPolicy, maxSessionDurationMs },
+        } as typeof formattedMessage.payload;
+      }
 
-      worker.postMessage(formattedMessage);
+      // The CryptoKeys cannot be CBOR-encoded, so they travel with the handshake instead
+      // (as do the key usage and worker state records, which are not part of the request). Size limits are
+      // applied before the frame is parsed, so they travel outside it too, as does the trusted
+      // confirmation origin, which is fixed before any prompt, and the known authenticators.
+      const sidecar = {
+        ...(outerWrapKey ? { outerWrapKey } : {}),
+        ...(keyUsageRecord ? { keyUsageRecord } : {}),
+        ...workerState,
+        ...(messageSizeLimits ? { messageSizeLimits } : {}),
+        ...(trustedConfirmationOrigin ? { trustedConfirmationOrigin } : {}),
+        ...(knownAuthenticators?.length ? { knownAuthenticators } : {}),
+      };
+      if (wireFormat === 'cbor' || secretSealing !== 'off') {
+        // The request is posted once the worker acknowledges the handshake
+        worker.postMessage({ type: SIGNER_WORKER_INITIALIZE, wireFormat, ...sidecar });
+      } else {
+        worker.postMessage({ ...formattedMessage, ...sidecar });
+      }
     });
   }
 
+  /** Request payload with its secret fields sealed; plaintext only when sealing is not required */
+  private async sealPayload<P>(
+    type: WorkerRequestType,
+    payload: P,
+    sessionPublicKey: string | undefined,
+    secretSealing: SecretSealingMode,
+  ): Promise<P> {
+    try {
+      if (!sessionPublicKey) {
+        throw new Error('worker did not return a session public key');
+      }
+      return await sealRequestSecrets(type, payload, sessionPublicKey);
+    } catch (error: unknown) {
+      if (secretSealing === 'require') {
+        throw new Error(`Sealing request secrets failed: ${toError(error).message}`);
+      }
+      console.warn('[SignerWorkerManager] Sealing request secrets failed, sending them unsealed:', error);
+      return payload;
+    }
+  }
+
+  /**
+   * Connects `worker` to the VRF worker with a MessageChannel so it requests its VRF challenge
+   * directly. Best-effort: when the VRF worker cannot take the port, the request keeps the
+   * main-thread challenge path.
+   */
+  private async connectPeerPort(worker: Worker, peer: SignerPeerTarget): Promise<void> {
+    const channel = new MessageChannel();
+    try {
+      await this.vrfWorkerManager.connectPeerPort(channel.port1);
+    } catch (error: unknown) {
+      console.warn('SignerWorkerManager: VRF peer port unavailable, using the main-thread challenge:', error);
+      channel.port2.close();
+      return;
+    }
+    const session: SignerPeerSession = { expectedVrfPublicKey: peer.vrfPublicKey, rpId: peer.rpId };
+    worker.postMessage({ type: SIGNER_WORKER_CONNECT_PEER_PORT, payload: session }, [channel.port2]);
+  }
+
   /**
    * Derive NEAR keypair from a serialized WebAuthn registration credential
    */
@@ -390,12 +861,18 @@ export class SignerWorkerManager {
       blockHash?: string;
       authenticatorOptions?: AuthenticatorOptions;
       deviceNumber?: number;
+      /** Contract registration interface version; probed via nearRpcUrl when omitted */
+      contractInterfaceVersion?: number;
+      nearRpcUrl?: string;
+      /** Retries with the same key get the first completed result back */
+      idempotencyKey?: string;
     };
   }): Promise<{
     success: boolean;
     nearAccountId: AccountId;
     publicKey: string;
     signedTransaction?: SignedTransaction;
+    authenticator?: AuthenticatorInfo;
   }> {
     return deriveNearKeypairAndEncryptFromSerialized({ ctx: this.getContext(), ...args });
   }
@@ -419,6 +896,7 @@ export class SignerWorkerManager {
     contractId: string;
     nearRpcUrl: string;
     authenticatorOptions?: AuthenticatorOptions; // Authenticator options for registration check
+    dryRun?: boolean;
     onEvent?: (update: onProgressEvents) => void;
   }): Promise<{
     success: boolean;
@@ -427,6 +905,7 @@ export class SignerWorkerManager {
     logs?: string[];
     signedTransactionBorsh?: number[];
     error?: string;
+    dryRun?: RegistrationDryRunReport;
   }> {
     return checkCanRegisterUser({ ctx: this.getContext(), ...args });
   }
@@ -442,14 +921,448 @@ export class SignerWorkerManager {
     rpcCall: RpcCallPayload,
     onEvent?: (update: onProgressEvents) => void,
     confirmationConfigOverride?: ConfirmationConfig,
+    rpcOverrides?: RpcOverrides,
+    validUntilBlockHeight?: number,
+    signingPublicKey?: string,
+    keyCandidates?: KeyBlobCandidate[],
+    deduplicate?: boolean,
+    previewDigest?: string,
+    redactPaths?: string[],
+    conditionalBatch?: ConditionalBatchOptions,
+    autoStorageDeposit?: boolean,
+    idempotencyKey?: string,
   }): Promise<Array<{
     signedTransaction: SignedTransaction;
     nearAccountId: AccountId;
-    logs?: string[]
+    logs?: string[];
+    broadcastRpcUrl?: string;
+    validUntilBlockHeight?: number;
+    removedDuplicateIndexes?: number[];
+    conditionalEntries?: ConditionalBatchEntry[];
   }>> {
     return signTransactionsWithActions({ ctx: this.getContext(), ...args });
   }
 
+  /**
+   * Canonical digest of a batch, to show with its preview and pass back to
+   * signTransactionsWithActions as `previewDigest`. With `deduplicate`, the batch is digested
+   * as signing will see it, without its duplicate transactions.
+   */
+  async summarizeTransactions(args: {
+    transactions: TransactionInputWasm[],
+    nearAccountId: AccountId,
+    deduplicate?: boolean,
+  }): Promise<TransactionsSummary> {
+    const response = await this.sendMessage({
+      message: {
+        type: WorkerRequestType.SummarizeTransactions,
+        payload: {
+          txSigningRequests: args.transactions.map(tx => ({
+            nearAccountId: args.nearAccountId,
+            receiverId: tx.receiverId,
+            actions: JSON.stringify(tx.actions)
+          })),
+          deduplicate: args.deduplicate ?? false,
+        },
+      },
+    });
+    if (!isSummarizeTransactionsSuccess(response)) {
+      const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+      throw new Error(`Summarizing transactions failed: ${errorDetails}`);
+    }
+    return response.payload;
+  }
+
+  /**
+   * Sign a chunked deploy: staging calls for each chunk of `code`, then the deploy-from-staged
+   * call, in broadcast order with the manifest of chunk hashes
+   */
+  async deployLargeContract(args: {
+    code: Uint8Array | number[],
+    chunkSize: number,
+    staging: StagingContractInterface,
+    rpcCall: RpcCallPayload,
+    onEvent?: (update: onProgressEvents) => void,
+    confirmationConfigOverride?: ConfirmationConfig,
+    rpcOverrides?: RpcOverrides,
+  }): Promise<{
+    signedTransactions: SignedTransaction[];
+    nearAccountId: AccountId;
+    manifest: DeployManifest;
+    logs?: string[];
+    broadcastRpcUrl?: string;
+  }> {
+    return deployLargeContract({ ctx: this.getContext(), ...args });
+  }
+
+  /**
+   * Confirm transactions now and get an intent token to sign them later without UI
+   */
+  async createSigningIntent(args: {
+    transactions: TransactionInputWasm[],
+    rpcCall: RpcCallPayload,
+    ttlMs?: number,
+    onEvent?: (update: onProgressEvents) => void,
+    confirmationConfigOverride?: ConfirmationConfig,
+  }): Promise<SigningIntent> {
+    return createSigningIntent({ ctx: this.getContext(), ...args });
+  }
+
+  /**
+   * Sign the transactions of a signing intent with a fresh nonce and block hash, without UI
+   */
+  async executeSigningIntent(args: {
+    intentToken: string,
+    transactions: TransactionInputWasm[],
+    rpcCall: RpcCallPayload,
+    onEvent?: (update: onProgressEvents) => void,
+    rpcOverrides?: RpcOverrides,
+  }): Promise<Array<{
+    signedTransaction: SignedTransaction;
+    nearAccountId: AccountId;
+    logs?: string[];
+    broadcastRpcUrl?: string;
+  }>> {
+    return executeSigningIntent({ ctx: this.getContext(), ...args });
+  }
+
+  /**
+   * Generate a function-call session key; later workers sign with it through the worker state
+   * record until it expires or is revoked
+   */
+  async createSessionKey(args: {
+    nearAccountId: string,
+    receiverId: string,
+    methodNames?: string[],
+    allowance: string,
+    ttlMs?: number,
+  }): Promise<SessionKeyCreated> {
+    return createSessionKey({ ctx: this.getContext(), ...args });
+  }
+
+  /**
+   * Sign a transaction with a session key, within its receiver, methods and allowance, without UI
+   */
+  async signWithSessionKey(args: {
+    sessionPublicKey: string,
+    signerAccountId: string,
+    receiverId: string,
+    nonce: string,
+    blockHash: string,
+    actions: ActionArgsWasm[],
+    gasPrice?: string,
+    idempotencyKey?: string,
+  }): Promise<{
+    signedTransaction: SignedTransaction;
+    logs?: string[];
+  }> {
+    return signWithSessionKey({ ctx: this.getContext(), ...args });
+  }
+
+  /**
+   * Forget a session key and get the DeleteKey action that removes it on-chain
+   */
+  async revokeSessionKey(args: { sessionPublicKey: string }): Promise<SessionKeyRevoked> {
+    return revokeSessionKey({ ctx: this.getContext(), ...args });
+  }
+
+  /**
+   * Start a confirmation of transactions on another device holding the same passkey
+   */
+  async createRemoteConfirmation(args: {
+    transactions: TransactionInputWasm[],
+    rpcCall: RpcCallPayload,
+    vrfChallenge: VRFChallenge,
+    ttlMs?: number,
+  }): Promise<RemoteConfirmationRequest> {
+    return createRemoteConfirmation({ ctx: this.getContext(), ...args });
+  }
+
+  /**
+   * Confirm another device's request here and seal the approval for it
+   */
+  async approveRemoteConfirmation(args: {
+    confirmationRequest: string,
+    nearAccountId: AccountId,
+    onEvent?: (update: onProgressEvents) => void,
+    confirmationConfigOverride?: ConfirmationConfig,
+  }): Promise<{ approval: string; requestId: string }> {
+    return approveRemoteConfirmation({ ctx: this.getContext(), ...args });
+  }
+
+  /**
+   * Sign the transactions of a remote confirmation once its approval came back
+   */
+  async completeRemoteConfirmation(args: {
+    approval: string,
+    transactions: TransactionInputWasm[],
+    rpcCall: RpcCallPayload,
+    onEvent?: (update: onProgressEvents) => void,
+    rpcOverrides?: RpcOverrides,
+  }): Promise<Array<{
+    signedTransaction: SignedTransaction;
+    nearAccountId: AccountId;
+    logs?: string[];
+    broadcastRpcUrl?: string;
+  }>> {
+    return completeRemoteConfirmation({ ctx: this.getContext(), ...args });
+  }
+
+  /**
+   * Create the account through the relayer, validating its response in the worker
+   */
+  async submitToRelayer(args: {
+    relayer: PasskeyManagerConfigs['relayer'],
+    nearAccountId: string,
+    newPublicKey: string,
+    deviceNumber?: number,
+    vrfChallenge: VRFChallenge,
+    credential: WebAuthnRegistrationCredential | PublicKeyCredential,
+    deterministicVrfPublicKey: string,
+    authenticatorOptions?: AuthenticatorOptions,
+    signedDelegateAction?: string,
+    authToken?: string,
+  }): Promise<RelayerResult> {
+    return submitToRelayer({ ctx: this.getContext(), ...args });
+  }
+
+  /**
+   * Phase 1 of a resumable registration: derive, encrypt and store the key, then keep the
+   * resume token (no network call)
+   */
+  async prepareRegistration(args: {
+    credential: WebAuthnRegistrationCredential,
+    nearAccountId: AccountId,
+    deviceNumber?: number,
+  }): Promise<PendingRegistration> {
+    return prepareRegistration({ ctx: this.getContext(), ...args });
+  }
+
+  /**
+   * Phase 2: submit the prepared key to the relayer; safe to retry with the same credential
+   */
+  async completeRegistration(args: {
+    relayer: PasskeyManagerConfigs['relayer'],
+    nearAccountId: AccountId,
+    credential: WebAuthnRegistrationCredential,
+    vrfChallenge: VRFChallenge,
+    deterministicVrfPublicKey: string,
+    deviceNumber?: number,
+    authenticatorOptions?: AuthenticatorOptions,
+    signedDelegateAction?: string,
+    authToken?: string,
+  }): Promise<RelayerResult> {
+    return completeRegistration({ ctx: this.getContext(), ...args });
+  }
+
+  /**
+   * Sign counts and last use of each key blob (by its AAD key-check value), most recently used
+   * first. Receivers are only reported as a category and a truncated hash.
+   */
+  async getKeyUsageStats(): Promise<KeyUsageStats> {
+    const response = await this.sendMessage({
+      message: { type: WorkerRequestType.GetKeyUsageStats, payload: {} },
+    });
+    if (!isGetKeyUsageStatsSuccess(response)) {
+      const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+      throw new Error(`Key usage stats failed: ${errorDetails}`);
+    }
+    return response.payload;
+  }
+
+  /**
+   * Optional features compiled into the signer wasm, so callers can hide what this build would
+   * refuse with FeatureNotCompiled
+   */
+  async getWorkerInfo(): Promise<WorkerInfo> {
+    const response = await this.sendMessage({
+      message: { type: WorkerRequestType.GetWorkerInfo, payload: {} },
+    });
+    if (!isGetWorkerInfoSuccess(response)) {
+      const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+      throw new Error(`Worker info failed: ${errorDetails}`);
+    }
+    return response.payload;
+  }
+
+  /**
+   * The worker session's epoch and, under maxSessionDurationMs, the time left before requests
+   * that decrypt or sign without prompting fail with SessionExpired
+   */
+  async getSessionStatus(): Promise<SessionStatus> {
+    const response = await this.sendMessage({
+      message: { type: WorkerRequestType.GetSessionStatus, payload: {} },
+    });
+    if (!isGetSessionStatusSuccess(response)) {
+      const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+      throw new Error(`Session status failed: ${errorDetails}`);
+    }
+    return response.payload;
+  }
+
+  /**
+   * Sign a sign-in result for a non-iframe integration with a session key and return the
+   * callback URL carrying it. The receiving page checks it with verify_redirect_payload and
+   * the session key against the account's access keys; no PRF output or private key is sent.
+   */
+  async buildRedirectPayload(args: {
+    nearAccountId: AccountId;
+    sessionPublicKey: string;
+    vrfChallenge: VRFChallenge;
+    callbackUrl: string;
+    state: string;
+    ttlMs?: number;
+  }): Promise<RedirectPayload> {
+    const response = await this.sendMessage({
+      message: {
+        type: WorkerRequestType.BuildRedirectPayload,
+        payload: {
+          ...args,
+          workerPolicy: { allowedRedirectOrigins: this.allowedRedirectOrigins },
+        },
+      },
+    });
+    if (!isBuildRedirectPayloadSuccess(response)) {
+      const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+      throw new Error(`Redirect payload failed: ${errorDetails}`);
+    }
+    return response.payload;
+  }
+
+  /**
+   * Dry-run decryption of a stored key blob with the PRF output of an earlier authentication.
+   * Nothing is signed and the decrypted key never leaves the worker.
+   */
+  async validateDecryptionCapability(args: {
+    nearAccountId: AccountId;
+    chacha20PrfOutput: string;
+    encryptedPrivateKeyData: string;
+    encryptedPrivateKeyIv: string;
+  }): Promise<DecryptionCapability> {
+    const response = await this.sendMessage({
+      message: {
+        type: WorkerRequestType.ValidateDecryptionCapability,
+        payload: {
+          nearAccountId: args.nearAccountId,
+          chacha20PrfOutput: args.chacha20PrfOutput,
+          decryption: {
+            encryptedPrivateKeyData: args.encryptedPrivateKeyData,
+            encryptedPrivateKeyIv: args.encryptedPrivateKeyIv,
+          },
+        },
+      },
+    });
+    if (!isValidateDecryptionCapabilitySuccess(response)) {
+      const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+      throw new Error(`Decryption capability check failed: ${errorDetails}`);
+    }
+    return response.payload;
+  }
+
+  /**
+   * Re-encrypts stored blobs written by earlier releases in the current formats. Every report
+   * carries the blob to store back; failed blobs come back unchanged, so this can be retried.
+   */
+  async migrateEncryptedBlobs(args: {
+    chacha20PrfOutput: string;
+    blobs: StoredEncryptedBlob[];
+  }): Promise<MigrateEncryptedBlobsResult> {
+    const response = await this.sendMessage({
+      message: {
+        type: WorkerRequestType.MigrateEncryptedBlobs,
+        payload: {
+          chacha20PrfOutput: args.chacha20PrfOutput,
+          blobs: args.blobs,
+        },
+      },
+    });
+    if (!isMigrateEncryptedBlobsSuccess(response)) {
+      const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+      throw new Error(`Encrypted blob migration failed: ${errorDetails}`);
+    }
+    return response.payload;
+  }
+
+  /**
+   * Accounts the signer worker holds session state (nonce reservations, audit log, request
+   * counters) or live session keys for, most recently active first
+   */
+  async listActiveAccounts(): Promise<ActiveAccounts> {
+    const response = await this.sendMessage({
+      message: { type: WorkerRequestType.ListActiveAccounts, payload: {} },
+    });
+    if (!isListActiveAccountsSuccess(response)) {
+      const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+      throw new Error(`Listing active accounts failed: ${errorDetails}`);
+    }
+    return response.payload;
+  }
+
+  /**
+   * Discards one account's session state and session keys; other accounts are untouched
+   */
+  async wipeAccountState(args: { accountId: AccountId }): Promise<AccountWipeSummary> {
+    const response = await this.sendMessage({
+      message: {
+        type: WorkerRequestType.WipeAccountState,
+        payload: { accountId: args.accountId },
+      },
+    });
+    if (!isWipeAccountStateSuccess(response)) {
+      const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+      throw new Error(`Wiping account state failed: ${errorDetails}`);
+    }
+    return response.payload;
+  }
+
+  /**
+   * JSON Schemas that FunctionCall args must satisfy (sent as WorkerPolicy.argsSchemas).
+   * The worker compiles them first, so a schema it cannot check fails here rather than at
+   * signing time; call without schemas to clear them.
+   */
+  async registerArgsSchemas(schemas?: ArgsSchema[]): Promise<void> {
+    if (!schemas?.length) {
+      this.argsSchemas = undefined;
+      return;
+    }
+    const response = await this.sendMessage({
+      message: {
+        type: WorkerRequestType.ValidateArgsSchemas,
+        payload: { argsSchemas: schemas },
+      },
+    });
+    if (!isValidateArgsSchemasSuccess(response)) {
+      const errorDetails = isWorkerError(response) ? response.payload.error : 'Unknown worker error';
+      throw new Error(`Validating args schemas failed: ${errorDetails}`);
+    }
+    if (!response.payload.valid) {
+      const errors = response.payload.errors
+        .map(e => `${e.contractId} ${e.methodName} schema at "${e.path}": ${e.message}`)
+        .join('; ');
+      throw new Error(`Invalid args schemas: ${errors}`);
+    }
+    this.argsSchemas = schemas;
+  }
+
+  /**
+   * Encrypt every stored record into one account bundle file under `passphrase` (Argon2id)
+   */
+  async exportAccountBundle(args: {
+    passphrase: string,
+  }): Promise<{ bundle: Uint8Array; recordCount: number }> {
+    return exportAccountBundle({ ctx: this.getContext(), ...args });
+  }
+
+  /**
+   * Decrypt an account bundle and write its validated records to IndexedDB
+   */
+  async importAccountBundle(args: {
+    passphrase: string,
+    bundle: Uint8Array | number[],
+  }): Promise<{ recordCount: number; exportedAtMs: number }> {
+    return importAccountBundle({ ctx: this.getContext(), ...args });
+  }
+
   /**
    * Recover keypair from authentication credential for account recovery
    * Uses dual PRF-based Ed25519 key derivation with account-specific HKDF and AES encryption
@@ -457,11 +1370,14 @@ export class SignerWorkerManager {
   async recoverKeypairFromPasskey(args: {
     credential: WebAuthnAuthenticationCredential;
     accountIdHint?: string;
+    subKeyPaths?: string[];
   }): Promise<{
     publicKey: string;
     encryptedPrivateKey: string;
     iv: string;
     accountIdHint?: string;
+    outerWrap?: OuterWrapMode;
+    subKeys: SubKeyEntry[];
   }> {
     return recoverKeypairFromPasskey({ ctx: this.getContext(), ...args });
   }
@@ -474,6 +1390,26 @@ export class SignerWorkerManager {
     return extractCosePublicKey({ ctx: this.getContext(), attestationObjectBase64url });
   }
 
+  /**
+   * navigator.credentials.create options for registering a device of an account, with the
+   * account's existing credentials excluded (see handlers/buildCredentialCreationOptions)
+   */
+  async buildCredentialCreationOptions(
+    args: Omit<Parameters<typeof buildCredentialCreationOptions>[0], 'ctx'>
+  ): Promise<CredentialCreationOptionsJSON> {
+    return buildCredentialCreationOptions({ ctx: this.getContext(), ...args });
+  }
+
+  /**
+   * Status of the broadcasts and relayer submissions whose result never arrived (e.g. the
+   * page was closed mid-request); see handlers/recoverPendingOperations
+   */
+  async recoverPendingOperations(
+    args: Omit<Parameters<typeof recoverPendingOperations>[0], 'ctx'>
+  ): Promise<RecoverPendingOperationsResult> {
+    return recoverPendingOperations({ ctx: this.getContext(), ...args });
+  }
+
   /**
    * Sign transaction with raw private key (for key replacement in Option D device linking)
    * No TouchID/PRF required - uses provided private key directly
@@ -485,6 +1421,7 @@ export class SignerWorkerManager {
     nonce: string;
     blockHash: string;
     actions: ActionArgsWasm[];
+    idempotencyKey?: string;
   }): Promise<{
     signedTransaction: SignedTransaction;
     logs?: string[];
@@ -516,6 +1453,26 @@ export class SignerWorkerManager {
     return signNep413Message({ ctx: this.getContext(), payload });
   }
 
+  /**
+   * Sign an EVM personal_sign message with the account's secp256k1 key (confirmed in the worker)
+   *
+   * @param payload - 0x-prefixed hex or text message, account ID and optional confirmation config
+   * @returns Promise resolving to the account ID, secp256k1 public key and r/s/v signature
+   */
+  async signEvmPersonalMessage(payload: {
+    message: string;
+    accountId: string;
+    confirmationConfig?: ConfirmationConfig;
+  }): Promise<{
+    success: boolean;
+    accountId: string;
+    publicKey: string;
+    evm?: EvmSignature;
+    error?: string;
+  }> {
+    return signEvmPersonalMessage({ ctx: this.getContext(), payload });
+  }
+
   /**
    * Prompt user for registration credential confirmation (create() with PRF) and return artifacts
    * Used for registration (device 1) and link-device (device N) flows.
@@ -565,3 +1522,19 @@ export class SignerWorkerManager {
   }
 
 }
+
+/**
+ * Replaces wasm-bindgen class instances (e.g. a ConfirmationConfig built with
+ * `new ConfirmationConfig(...).withTheme(...)`) with their toJSON() form. The instances only
+ * hold a pointer into this thread's WASM memory, so they cannot be cloned or CBOR-encoded as is.
+ */
+function toPlainPayload(value: unknown): unknown {
+  if (Array.isArray(value)) return value.map(toPlainPayload);
+  if (!value || typeof value !== 'object' || ArrayBuffer.isView(value)) return value;
+  const instance = value as { __wbg_ptr?: number; toJSON?: () => unknown };
+  if (typeof instance.__wbg_ptr === 'number' && typeof instance.toJSON === 'function') {
+    return instance.toJSON();
+  }
+  if (Object.getPrototypeOf(value) !== Object.prototype) return value;
+  return Object.fromEntries(Object.entries(value).map(([k, v]) => [k, toPlainPayload(v)]));
+}
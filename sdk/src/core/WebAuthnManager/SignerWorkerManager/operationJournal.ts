@@ -0,0 +1,83 @@
+import {
+  OPERATION_JOURNAL_APP_STATE_KEY,
+  type JournalEntry,
+} from '../../types/signer-worker';
+import type { UnifiedIndexedDBManager } from '../../IndexedDBManager';
+import { isObject, isString, isNumber } from '@/core/WalletIframe/validation';
+import { errorMessage } from '@/utils/errors';
+
+// === OPERATION JOURNAL ===
+// The signer worker posts a JournalEntry right before each broadcast or relayer submission.
+// Entries are kept in IndexedDB until the request succeeds, so that after a crash or reload
+// recoverPendingOperations can tell whether the step went through.
+
+/** Entries one RecoverPendingOperations request takes (MAX_RECOVERABLE_OPERATIONS in config.rs) */
+export const MAX_RECOVERABLE_OPERATIONS = 64;
+
+export enum OperationJournalMessageType {
+  OPERATION_JOURNAL_ENTRY = 'OPERATION_JOURNAL_ENTRY',
+}
+
+export function isJournalEntry(data: unknown): data is JournalEntry {
+  if (!isObject(data)) return false;
+  const d = data as { requestId?: unknown; step?: unknown; accountId?: unknown; createdAtMs?: unknown };
+  return isString(d.requestId)
+    && (d.step === 'broadcast' || d.step === 'relayerSubmission')
+    && isString(d.accountId)
+    && isNumber(d.createdAtMs);
+}
+
+/** Journal entries persisted and not yet resolved */
+export async function readOperationJournal(indexedDB: UnifiedIndexedDBManager): Promise<JournalEntry[]> {
+  try {
+    const entries = await indexedDB.clientDB.getAppState<JournalEntry[]>(OPERATION_JOURNAL_APP_STATE_KEY);
+    return Array.isArray(entries) ? entries.filter(isJournalEntry) : [];
+  } catch (error: unknown) {
+    console.warn('[SignerWorkerManager]: Operation journal unavailable:', errorMessage(error));
+    return [];
+  }
+}
+
+async function writeOperationJournal(indexedDB: UnifiedIndexedDBManager, entries: JournalEntry[]): Promise<void> {
+  try {
+    await indexedDB.clientDB.setAppState(OPERATION_JOURNAL_APP_STATE_KEY, entries);
+  } catch (error: unknown) {
+    console.warn('[SignerWorkerManager]: Failed to store operation journal:', errorMessage(error));
+  }
+}
+
+function sameEntry(a: JournalEntry, b: JournalEntry): boolean {
+  return a.requestId === b.requestId && a.step === b.step && a.txHash === b.txHash;
+}
+
+export async function appendJournalEntry(indexedDB: UnifiedIndexedDBManager, entry: JournalEntry): Promise<void> {
+  const entries = await readOperationJournal(indexedDB);
+  await writeOperationJournal(indexedDB, [...entries.filter((e) => !sameEntry(e, entry)), entry]);
+}
+
+/** Drops `resolved` from the journal */
+export async function removeJournalEntries(indexedDB: UnifiedIndexedDBManager, resolved: JournalEntry[]): Promise<void> {
+  if (!resolved.length) return;
+  const entries = await readOperationJournal(indexedDB);
+  await writeOperationJournal(indexedDB, entries.filter((e) => !resolved.some((r) => sameEntry(e, r))));
+}
+
+/**
+ * Main-thread side of OPERATION_JOURNAL_ENTRY messages: persists the entry and adds it to
+ * `journaled` (the entries of the running request). Returns true when the message was a
+ * journal entry.
+ */
+export async function handleOperationJournalMessage(
+  indexedDB: UnifiedIndexedDBManager,
+  message: { type?: unknown; data?: unknown },
+  journaled: JournalEntry[],
+): Promise<boolean> {
+  if (message.type !== OperationJournalMessageType.OPERATION_JOURNAL_ENTRY) return false;
+  if (!isJournalEntry(message.data)) {
+    console.warn('[SignerWorkerManager]: Ignoring malformed journal entry');
+    return true;
+  }
+  journaled.push(message.data);
+  await appendJournalEntry(indexedDB, message.data);
+  return true;
+}
@@ -0,0 +1,159 @@
+import {
+  WorkerRequestType,
+  type CancelledRequest,
+  type PendingRequests,
+  type RequestLimits,
+} from '../../types/signer-worker';
+
+// === PENDING-REQUEST REGISTRY ===
+// Every signer worker serves one request and is terminated, so the in-flight limit and the
+// queue live with the pool, here. Requests beyond maxConcurrent wait for a slot; beyond
+// maxQueued they fail with TooManyPendingRequests. A queued request can be cancelled (it fails
+// with RequestCancelled without reaching a worker); running requests are not interrupted.
+// The request ID is sent with the request frame, so the worker's audit entries carry it too.
+
+/** Defaults of the registry (DEFAULT_MAX_CONCURRENT_REQUESTS / DEFAULT_MAX_QUEUED_REQUESTS in config.rs) */
+export const DEFAULT_REQUEST_LIMITS: RequestLimits = { maxConcurrent: 4, maxQueued: 16 };
+
+/** Requests that take no slot (mirrors WorkerRequestType::is_tracked) */
+const UNTRACKED_REQUEST_TYPES: ReadonlySet<WorkerRequestType> = new Set([
+  WorkerRequestType.ExtractCosePublicKey,
+  WorkerRequestType.GetBorshSchemas,
+  WorkerRequestType.ListPendingRequests,
+  WorkerRequestType.CancelRequest,
+  WorkerRequestType.WipeAllState,
+  WorkerRequestType.ValidateEncryptedBlobs,
+  WorkerRequestType.ComposeMultisigRequest,
+  WorkerRequestType.GetMemoryStats,
+  WorkerRequestType.TrimCaches,
+  WorkerRequestType.GetRecentReceivers,
+  WorkerRequestType.RunSelfTest,
+  WorkerRequestType.GetTelemetrySnapshot,
+  WorkerRequestType.GetKeyUsageStats,
+  WorkerRequestType.GetWorkerInfo,
+  WorkerRequestType.DumpDiagnostics,
+  WorkerRequestType.ListActiveAccounts,
+  WorkerRequestType.WipeAccountState,
+  WorkerRequestType.ValidateArgsSchemas,
+  WorkerRequestType.SummarizeTransactions,
+  WorkerRequestType.StartBackgroundRefresh,
+  WorkerRequestType.StopBackgroundRefresh,
+  WorkerRequestType.BackgroundRefreshStatus,
+  WorkerRequestType.SuspendHint,
+  WorkerRequestType.ResumeHint,
+  WorkerRequestType.GetSessionStatus,
+]);
+
+export function isTrackedRequest(type: WorkerRequestType): boolean {
+  return !UNTRACKED_REQUEST_TYPES.has(type);
+}
+
+interface RegistryEntry {
+  requestId: string;
+  operation: string;
+  phase: 'queued' | 'running';
+  admittedAtMs: number;
+  /** Settles the admission of a queued request */
+  start?: () => void;
+  cancel?: (error: Error) => void;
+}
+
+export class WorkerRequestRegistry {
+  private entries: RegistryEntry[] = [];
+  private limits: RequestLimits = DEFAULT_REQUEST_LIMITS;
+  private counter = 0;
+
+  /** Updates the limits; raising maxConcurrent starts queued requests immediately */
+  setLimits(limits: RequestLimits): void {
+    if (!Number.isInteger(limits.maxConcurrent) || limits.maxConcurrent < 1) {
+      throw new Error('maxConcurrent must be at least 1');
+    }
+    if (!Number.isInteger(limits.maxQueued) || limits.maxQueued < 0) {
+      throw new Error('maxQueued must be a non-negative integer');
+    }
+    this.limits = { ...limits };
+    this.promoteQueued();
+  }
+
+  nextRequestId(): string {
+    return `signer-${Date.now()}-${++this.counter}`;
+  }
+
+  /**
+   * Resolves once the request holds a running slot; rejects with TooManyPendingRequests when
+   * the queue is full, DuplicateRequestId, or RequestCancelled when cancelled while queued.
+   * Every admitted request must be released.
+   */
+  admit(requestId: string, type: WorkerRequestType): Promise<void> {
+    if (this.entries.some((e) => e.requestId === requestId)) {
+      return Promise.reject(new Error('DuplicateRequestId: A request with this id is already pending'));
+    }
+    const entry: RegistryEntry = {
+      requestId,
+      operation: WorkerRequestType[type] ?? String(type),
+      phase: 'running',
+      admittedAtMs: Date.now(),
+    };
+    if (this.count('running') < this.limits.maxConcurrent) {
+      this.entries.push(entry);
+      return Promise.resolve();
+    }
+    if (this.count('queued') >= this.limits.maxQueued) {
+      return Promise.reject(new Error('TooManyPendingRequests: Too many requests are pending; try again shortly'));
+    }
+    entry.phase = 'queued';
+    this.entries.push(entry);
+    return new Promise<void>((resolve, reject) => {
+      entry.start = resolve;
+      entry.cancel = reject;
+    });
+  }
+
+  /** Frees the request's slot and starts the next queued request */
+  release(requestId: string): void {
+    this.entries = this.entries.filter((e) => e.requestId !== requestId);
+    this.promoteQueued();
+  }
+
+  /** Running and queued requests, oldest first, with the limits */
+  list(): PendingRequests {
+    const now = Date.now();
+    return {
+      requests: this.entries.map((e) => ({
+        requestId: e.requestId,
+        operation: e.operation,
+        phase: e.phase,
+        ageMs: Math.max(0, now - e.admittedAtMs),
+      })),
+      maxConcurrent: this.limits.maxConcurrent,
+      maxQueued: this.limits.maxQueued,
+    };
+  }
+
+  /** Cancels a queued request; running requests are not interrupted (`cancelled: false`) */
+  cancel(requestId: string): CancelledRequest {
+    const entry = this.entries.find((e) => e.requestId === requestId);
+    if (entry?.phase === 'queued') {
+      this.entries = this.entries.filter((e) => e !== entry);
+      entry.cancel?.(new Error('RequestCancelled: The request was cancelled before it started'));
+    }
+    return { requestId, cancelled: entry?.phase === 'queued', phase: entry?.phase };
+  }
+
+  private count(phase: RegistryEntry['phase']): number {
+    return this.entries.filter((e) => e.phase === phase).length;
+  }
+
+  /** Moves queued requests into free slots, oldest first */
+  private promoteQueued(): void {
+    let freeSlots = this.limits.maxConcurrent - this.count('running');
+    for (const entry of this.entries) {
+      if (freeSlots <= 0) break;
+      if (entry.phase === 'queued') {
+        entry.phase = 'running';
+        entry.start?.();
+        freeSlots -= 1;
+      }
+    }
+  }
+}
@@ -0,0 +1,100 @@
+import type { WorkerPolicy } from '../../types/signer-worker';
+import { isObject, isString, isBoolean } from '@/core/WalletIframe/validation';
+import { errorMessage } from '@/utils/errors';
+
+// === SIGNING HOOK TYPES ===
+// Integrator hooks (e.g. a compliance module) that the signer worker consults
+// independently of the dapp page. The pre-sign hook runs after the user confirms a
+// request and before the private key is decrypted; the post-sign hook is notified of
+// every signed transaction.
+
+export enum SigningHookMessageType {
+  PRE_SIGN_HOOK_REQUEST = 'PRE_SIGN_HOOK_REQUEST',
+  PRE_SIGN_HOOK_RESPONSE = 'PRE_SIGN_HOOK_RESPONSE',
+  POST_SIGN_HOOK_NOTIFICATION = 'POST_SIGN_HOOK_NOTIFICATION',
+}
+
+export interface PreSignHookRequest {
+  requestId: string;
+  /** Summary shown in the confirmation UI ({ to, totalAmount }) */
+  txSummary: unknown;
+  /** Intent digest the user confirmed */
+  digest: string;
+}
+
+export interface PreSignHookResponse {
+  requestId: string;
+  allow: boolean;
+  /** Recorded in the worker's audit log when the request is denied */
+  reasonCode?: string;
+}
+
+export interface PostSignHookNotification {
+  requestId: string;
+  txHash: string;
+  digest: string;
+}
+
+export interface SigningHooks {
+  preSign?: (request: PreSignHookRequest) =>
+    Promise<{ allow: boolean; reasonCode?: string }> | { allow: boolean; reasonCode?: string };
+  postSign?: (notification: PostSignHookNotification) => void;
+  /** How long the worker waits for preSign before denying (default 10s) */
+  timeoutMs?: number;
+}
+
+/** WorkerPolicy sent with signing requests; undefined when no hooks are registered */
+export function workerPolicyFromHooks(hooks?: SigningHooks): WorkerPolicy | undefined {
+  if (!hooks?.preSign && !hooks?.postSign) return undefined;
+  return {
+    preSignHook: !!hooks.preSign,
+    postSignHook: !!hooks.postSign,
+    hookTimeoutMs: hooks.timeoutMs,
+  };
+}
+
+export function isPreSignHookResponse(data: unknown): data is PreSignHookResponse {
+  if (!isObject(data)) return false;
+  const d = data as { requestId?: unknown; allow?: unknown };
+  return isString(d.requestId) && isBoolean(d.allow);
+}
+
+/**
+ * Main-thread side of the hook messages posted by the signer worker.
+ * Returns true when the message was a hook message (and has been handled).
+ * A preSign hook that throws denies the request with reasonCode 'HookError'.
+ */
+export async function handleSigningHookMessage(
+  hooks: SigningHooks | undefined,
+  message: { type?: unknown; data?: unknown },
+  worker: Worker
+): Promise<boolean> {
+  switch (message.type) {
+    case SigningHookMessageType.PRE_SIGN_HOOK_REQUEST: {
+      const request = message.data as PreSignHookRequest;
+      let response: PreSignHookResponse;
+      try {
+        if (!hooks?.preSign) {
+          throw new Error('no preSign hook registered');
+        }
+        const decision = await hooks.preSign(request);
+        response = { requestId: request.requestId, allow: decision.allow === true, reasonCode: decision.reasonCode };
+      } catch (error: unknown) {
+        console.warn('[SignerWorkerManager]: preSign hook failed:', errorMessage(error));
+        response = { requestId: request.requestId, allow: false, reasonCode: 'HookError' };
+      }
+      worker.postMessage({ type: SigningHookMessageType.PRE_SIGN_HOOK_RESPONSE, data: response });
+      return true;
+    }
+    case SigningHookMessageType.POST_SIGN_HOOK_NOTIFICATION: {
+      try {
+        hooks?.postSign?.(message.data as PostSignHookNotification);
+      } catch (error: unknown) {
+        console.warn('[SignerWorkerManager]: postSign hook failed:', errorMessage(error));
+      }
+      return true;
+    }
+    default:
+      return false;
+  }
+}
@@ -0,0 +1,124 @@
+import type { UnifiedIndexedDBManager } from '../../IndexedDBManager';
+import {
+  WorkerRequestType,
+  WORKER_STATE_RECORD_APP_STATE_KEY,
+  WORKER_STATE_WRAP_KEY_APP_STATE_KEY,
+  type WorkerStateRecord,
+} from '../../types/signer-worker';
+
+// === PERSISTED WORKER STATE ===
+// Every signer worker serves one request, so the state that must outlive it (see Rust
+// worker_state.rs) comes back sealed as `workerStateRecord` and is handed to the next worker,
+// with the non-extractable AES-GCM CryptoKey its state key is wrapped under. Both live in
+// IndexedDB app state. Each response carries the whole state, so requests that may change it
+// run one at a time; read-only requests get the latest record and their record is ignored.
+
+/** Requests that never change the persisted state */
+const READ_ONLY_REQUEST_TYPES: ReadonlySet<WorkerRequestType> = new Set([
+  WorkerRequestType.ExtractCosePublicKey,
+  WorkerRequestType.GetBorshSchemas,
+  WorkerRequestType.ListPendingRequests,
+  WorkerRequestType.CancelRequest,
+  WorkerRequestType.ValidateEncryptedBlobs,
+  WorkerRequestType.ComposeMultisigRequest,
+  WorkerRequestType.GetMemoryStats,
+  WorkerRequestType.GetRecentReceivers,
+  WorkerRequestType.RunSelfTest,
+  WorkerRequestType.GetTelemetrySnapshot,
+  WorkerRequestType.GetKeyUsageStats,
+  WorkerRequestType.GetWorkerInfo,
+  WorkerRequestType.DumpDiagnostics,
+  WorkerRequestType.ListActiveAccounts,
+  WorkerRequestType.ValidateArgsSchemas,
+  WorkerRequestType.SummarizeTransactions,
+  WorkerRequestType.BackgroundRefreshStatus,
+  WorkerRequestType.GetSessionStatus,
+]);
+
+/** Sidecar fields handing the persisted state to a worker */
+export interface WorkerStateSidecar {
+  workerStateWrapKey?: CryptoKey;
+  workerStateRecord?: WorkerStateRecord;
+}
+
+export class WorkerStateStore {
+  // Last record a worker handed back (undefined until read from IndexedDB)
+  private record?: WorkerStateRecord | null;
+  private wrapKey?: Promise<CryptoKey | undefined>;
+  // Settles when the last request that may change the state is done
+  private tail: Promise<void> = Promise.resolve();
+
+  constructor(private indexedDB: UnifiedIndexedDBManager) {}
+
+  /** Runs `run` once every earlier request that may change the state is done, unless read-only */
+  async exclusive<R>(type: WorkerRequestType, run: () => Promise<R>): Promise<R> {
+    if (READ_ONLY_REQUEST_TYPES.has(type)) {
+      return run();
+    }
+    const previous = this.tail;
+    let done!: () => void;
+    this.tail = new Promise<void>((resolve) => { done = resolve; });
+    try {
+      await previous;
+      return await run();
+    } finally {
+      done();
+    }
+  }
+
+  /** Wrap key and record for the next worker */
+  async sidecar(): Promise<WorkerStateSidecar> {
+    const [workerStateWrapKey, workerStateRecord] = await Promise.all([this.getWrapKey(), this.getRecord()]);
+    return {
+      ...(workerStateWrapKey ? { workerStateWrapKey } : {}),
+      ...(workerStateRecord ? { workerStateRecord } : {}),
+    };
+  }
+
+  /** Keeps the record a worker handed back for the workers after it */
+  async update(type: WorkerRequestType, record: WorkerStateRecord | undefined): Promise<void> {
+    if (!record || READ_ONLY_REQUEST_TYPES.has(type)) return;
+    this.record = record;
+    try {
+      await this.indexedDB.clientDB.setAppState(WORKER_STATE_RECORD_APP_STATE_KEY, record);
+    } catch (error: unknown) {
+      console.warn('SignerWorkerManager: Failed to store worker state record:', error);
+    }
+  }
+
+  private async getRecord(): Promise<WorkerStateRecord | null> {
+    if (this.record === undefined) {
+      try {
+        this.record = (await this.indexedDB.clientDB.getAppState<WorkerStateRecord>(WORKER_STATE_RECORD_APP_STATE_KEY)) ?? null;
+      } catch (error: unknown) {
+        console.warn('SignerWorkerManager: Worker state record unavailable:', error);
+        this.record = null;
+      }
+    }
+    return this.record;
+  }
+
+  /** The stored wrap key, generated (and stored) on first use; undefined without WebCrypto */
+  private getWrapKey(): Promise<CryptoKey | undefined> {
+    this.wrapKey ??= (async () => {
+      try {
+        const stored = await this.indexedDB.clientDB.getAppState<CryptoKey>(WORKER_STATE_WRAP_KEY_APP_STATE_KEY);
+        if (stored) return stored;
+      } catch (error: unknown) {
+        console.warn('SignerWorkerManager: Worker state wrap key unavailable:', error);
+      }
+      try {
+        const key = await crypto.subtle.generateKey({ name: 'AES-GCM', length: 256 }, false, ['encrypt', 'decrypt']);
+        await this.indexedDB.clientDB.setAppState(WORKER_STATE_WRAP_KEY_APP_STATE_KEY, key).catch((error: unknown) => {
+          // Still used for this page's workers
+          console.warn('SignerWorkerManager: Failed to store worker state wrap key:', error);
+        });
+        return key;
+      } catch (error: unknown) {
+        console.warn('SignerWorkerManager: Worker state is not persisted (no WebCrypto):', error);
+        return undefined;
+      }
+    })();
+    return this.wrapKey;
+  }
+}
@@ -19,12 +19,25 @@ import type {
   WasmShamir3PassClientDecryptVrfKeypairRequest,
   WasmUnlockVrfKeypairRequest,
   WasmDeriveVrfKeypairFromPrfRequest,
+  WasmRestoreSessionSnapshotRequest,
+  WasmGenerateVrfChallengesBatchRequest,
+  WasmUpdateBlockInfoRequest,
+  WasmConfigureSessionOptionsRequest,
+  WasmUpdateSessionPolicyRequest,
+  VrfSessionSnapshotExport,
+  VrfSnapshotRevocationState,
+  VrfChallengeBatchResult,
+  VrfChallengeTimings,
+  VrfWorkerInfo,
+  VrfResumedState,
+  VrfScope,
 } from '../../types/vrf-worker';
 import { WebAuthnRegistrationCredential } from '../../types';
-import { VRFChallenge, validateVRFChallenge } from '../../types/vrf-worker';
+import { VRFChallenge, validateVRFChallenge, toVrfInputPayload, hasCompleteVrfInput } from '../../types/vrf-worker';
 import { BUILD_PATHS } from '../../../../build-paths.js';
 import { AccountId, toAccountId } from '../../types/accountIds';
 import { extractPrfFromCredential } from '../credentialsHelpers';
+import { base64UrlEncode } from '../../../utils/encoders.js';
 
 /**
  * VRF Worker Manager
@@ -41,6 +54,10 @@ export class VrfWorkerManager {
   private messageId = 0;
   private config: VrfWorkerManagerConfig;
   private currentVrfAccountId: string | null = null;
+  /** Last snapshot revocation state reported by the worker, handed to its replacement */
+  private snapshotRevocationState: VrfSnapshotRevocationState | null = null;
+  /** The signer session (GetSessionStatus), pushed to the worker by syncSignerSession */
+  private signerSessionSource: (() => Promise<{ epoch: number; maxSessionDurationMs?: number }>) | null = null;
 
   constructor(config: VrfWorkerManagerConfig = {}) {
     this.config = {
@@ -135,6 +152,7 @@ export class VrfWorkerManager {
       };
       // Test communication with the Web Worker
       await this.testWebWorkerCommunication();
+      await this.verifyWorkerBuild();
 
       // Configure Shamir P if provided
       if (this.config.shamirPB64u) {
@@ -164,17 +182,64 @@ export class VrfWorkerManager {
         }
       }
 
+      // Continue the previous worker's snapshot revocation state, if any
+      if (this.snapshotRevocationState) {
+        const resp3 = await this.sendMessage<VrfSnapshotRevocationState>({
+          type: 'LOAD_SNAPSHOT_REVOCATION_STATE',
+          id: this.generateMessageId(),
+          payload: this.snapshotRevocationState
+        });
+        if (!resp3.success) {
+          throw new Error(`Failed to load snapshot revocation state: ${resp3.error}`);
+        }
+      }
+
     } catch (error: any) {
       throw new Error(`VRF Web Worker initialization failed: ${error.message}`);
     }
   }
 
+  /**
+   * Refuse a worker whose wasm does not match its build attestation: the hash embedded at
+   * build time and, when configured, the release manifest's (expectedWasmSha256B64u).
+   * Builds without an embedded hash (local builds) pass unless a manifest hash is configured.
+   */
+  private async verifyWorkerBuild(): Promise<void> {
+    const expected = this.config.expectedWasmSha256B64u;
+    const pingResponse = await this.sendMessage({
+      type: 'PING',
+      id: this.generateMessageId(),
+      payload: {} as WasmVrfWorkerRequestType
+    }, 2000).catch(() => null);
+    const workerInfo = pingResponse?.data?.workerInfo as VrfWorkerInfo | undefined;
+    const attestation = workerInfo?.buildInfo?.attestation;
+
+    const mismatches: string[] = [];
+    if (attestation?.embeddedSha256B64u && !attestation.matches) {
+      mismatches.push(...attestation.mismatches);
+    }
+    if (expected && !attestation) {
+      mismatches.push('worker did not report a build attestation');
+    } else if (expected && attestation!.actualSha256B64u !== expected) {
+      mismatches.push(`wasm hash ${attestation!.actualSha256B64u} does not match the release manifest hash ${expected}`);
+    }
+    if (mismatches.length > 0) {
+      this.vrfWorker?.terminate();
+      this.vrfWorker = null;
+      throw new Error(`VRF worker build attestation failed: ${mismatches.join('; ')}`);
+    }
+    if (this.config.debug) {
+      console.debug('VRF Manager: Worker build info:', workerInfo?.buildInfo);
+    }
+  }
+
   /**
    * Send message to Web Worker and wait for response
    */
   private async sendMessage<T extends WasmVrfWorkerRequestType>(
     message: VRFWorkerMessage<T>,
-    customTimeout?: number
+    customTimeout?: number,
+    transfer: Transferable[] = []
   ): Promise<VRFWorkerResponse> {
     return new Promise((resolve, reject) => {
       if (!this.vrfWorker) {
@@ -197,7 +262,7 @@ export class VrfWorkerManager {
       };
 
       this.vrfWorker.addEventListener('message', handleMessage);
-      this.vrfWorker.postMessage(message);
+      this.vrfWorker.postMessage(message, transfer);
     });
   }
 
@@ -216,11 +281,14 @@ export class VrfWorkerManager {
     credential,
     nearAccountId,
     encryptedVrfKeypair,
+    scopes,
     onEvent,
   }: {
     credential: import('../../types/webauthn').WebAuthnAuthenticationCredential,
     nearAccountId: AccountId,
     encryptedVrfKeypair: EncryptedVRFKeypair,
+    /** What the unlocked keypair may be used for (all scopes when omitted) */
+    scopes?: VrfScope[],
     onEvent?: (event: { type: string, data: { step: string, message: string } }) => void,
   }): Promise<VRFWorkerResponse> {
     await this.ensureWorkerReady(true);
@@ -249,7 +317,8 @@ export class VrfWorkerManager {
       payload: {
         nearAccountId,
         encryptedVrfKeypair: encryptedVrfKeypair,
-        prfKey: chacha20PrfOutput // already a base64url string
+        prfKey: chacha20PrfOutput, // already a base64url string
+        scopes,
       }
     };
 
@@ -269,20 +338,21 @@ export class VrfWorkerManager {
 
   /**
    * Generate VRF challenge using in-memory VRF keypair
-   * This is called during authentication to create WebAuthn challenges
+   * This is called during authentication to create WebAuthn challenges.
+   * With `crossCheckRpcUrl`, the worker looks the block up by hash on that NEAR RPC first and
+   * fails with InvalidBlockHash unless it is at the given blockHeight.
    */
-  async generateVrfChallenge(inputData: VRFInputData): Promise<VRFChallenge> {
+  async generateVrfChallenge(
+    inputData: VRFInputData,
+    options?: { crossCheckRpcUrl?: string }
+  ): Promise<VRFChallenge> {
     await this.ensureWorkerReady(true);
     const message: VRFWorkerMessage<WasmGenerateVrfChallengeRequest> = {
       type: 'GENERATE_VRF_CHALLENGE',
       id: this.generateMessageId(),
       payload: {
-        vrfInputData: {
-          userId: inputData.userId,
-          rpId: inputData.rpId,
-          blockHeight: String(inputData.blockHeight),
-          blockHash: inputData.blockHash,
-        }
+        vrfInputData: toVrfInputPayload(inputData) as WasmGenerateVrfChallengeRequest['vrfInputData'],
+        ...(options?.crossCheckRpcUrl ? { crossCheckRpcUrl: options.crossCheckRpcUrl } : {}),
       }
     };
 
@@ -296,6 +366,185 @@ export class VrfWorkerManager {
     return validateVRFChallenge(response.data);
   }
 
+  /**
+   * Generate the VRF challenge for a signing request. With challenge prefetch enabled
+   * (configureSessionOptions) the worker may answer with the challenge it already proved for a
+   * recent block, reported as `timings.usedPrefetchedChallenge`.
+   */
+  async generateSigningVrfChallenge(inputData: VRFInputData): Promise<{
+    vrfChallenge: VRFChallenge;
+    timings?: VrfChallengeTimings;
+  }> {
+    await this.ensureWorkerReady(true);
+    const message: VRFWorkerMessage<WasmGenerateVrfChallengeRequest> = {
+      type: 'GENERATE_VRF_CHALLENGE',
+      id: this.generateMessageId(),
+      payload: {
+        vrfInputData: toVrfInputPayload(inputData) as WasmGenerateVrfChallengeRequest['vrfInputData'],
+        allowPrefetched: true,
+      }
+    };
+
+    const response = await this.sendMessage(message);
+    if (!response.success || !response.data) {
+      throw new Error(`VRF challenge generation failed: ${response.error}`);
+    }
+    return {
+      vrfChallenge: validateVRFChallenge(response.data),
+      timings: response.data.timings as VrfChallengeTimings | undefined,
+    };
+  }
+
+  /**
+   * Push the latest block (e.g. from NonceManager) to the worker. When challenge prefetch is
+   * enabled, the worker proves the next challenge for it ahead of the signing request.
+   * Returns whether a challenge was prefetched.
+   */
+  async updateBlockInfo(block: { blockHeight: string; blockHash: string }): Promise<boolean> {
+    await this.ensureWorkerReady();
+    const message: VRFWorkerMessage<WasmUpdateBlockInfoRequest> = {
+      type: 'UPDATE_BLOCK_INFO',
+      id: this.generateMessageId(),
+      payload: {
+        blockHeight: String(block.blockHeight),
+        blockHash: block.blockHash,
+      }
+    };
+
+    const response = await this.sendMessage(message);
+    if (!response.success) {
+      throw new Error(`VRF block info update failed: ${response.error}`);
+    }
+    return !!response.data?.prefetched;
+  }
+
+  /**
+   * Hand the worker one end of a MessageChannel whose other end goes to a signer worker.
+   * The signer worker then requests its signing challenges over the port (REQUEST_CHALLENGE)
+   * instead of through the main thread. The port is closed on wipeAllState or replaced by the
+   * next one connected.
+   */
+  async connectPeerPort(port: MessagePort): Promise<void> {
+    await this.ensureWorkerReady(true);
+    const message: VRFWorkerMessage<WasmVrfWorkerRequestType> = {
+      type: 'CONNECT_PEER_PORT',
+      id: this.generateMessageId(),
+      payload: {} as WasmVrfWorkerRequestType
+    };
+
+    const response = await this.sendMessage(message, undefined, [port]);
+    if (!response.success) {
+      throw new Error(`VRF peer port connection failed: ${response.error}`);
+    }
+  }
+
+  /**
+   * Options for the unlocked session, cleared on logout.
+   * `prefetchChallenge` keeps a challenge for (userId, rpId) proven for the latest block
+   * pushed with updateBlockInfo. `autoLockAfterMs` locks the session that long after unlock,
+   * in wall-clock time. `issuanceReceiptKeyB64u` (32 bytes, shared with the integrator's
+   * backend) makes every challenge carry an `issuanceReceiptB64u`.
+   */
+  async configureSessionOptions(options: {
+    prefetchChallenge: boolean;
+    userId?: string;
+    rpId?: string;
+    autoLockAfterMs?: number;
+    issuanceReceiptKeyB64u?: string;
+  }): Promise<void> {
+    await this.ensureWorkerReady(true);
+    const message: VRFWorkerMessage<WasmConfigureSessionOptionsRequest> = {
+      type: 'CONFIGURE_SESSION_OPTIONS',
+      id: this.generateMessageId(),
+      payload: {
+        prefetchChallenge: options.prefetchChallenge,
+        userId: options.userId,
+        rpId: options.rpId,
+        autoLockAfterMs: options.autoLockAfterMs,
+        issuanceReceiptKeyB64u: options.issuanceReceiptKeyB64u,
+      } as WasmConfigureSessionOptionsRequest
+    };
+
+    const response = await this.sendMessage(message);
+    if (!response.success) {
+      throw new Error(`VRF session options failed: ${response.error}`);
+    }
+  }
+
+  /**
+   * Tell the worker the page is being hidden (pagehide): it drops the prefetched challenge and
+   * holds the auto-lock until resumeHint. No-op when the worker was never started.
+   */
+  async suspendHint(): Promise<void> {
+    if (!this.vrfWorker) return;
+    const message: VRFWorkerMessage<WasmVrfWorkerRequestType> = {
+      type: 'SUSPEND_HINT',
+      id: this.generateMessageId(),
+      payload: {} as WasmVrfWorkerRequestType
+    };
+
+    const response = await this.sendMessage(message);
+    if (!response.success) {
+      throw new Error(`VRF suspend hint failed: ${response.error}`);
+    }
+  }
+
+  /**
+   * Tell the worker the page is shown again (pageshow). The worker applies the auto-lock with
+   * the time spent frozen and drops its block info; the report lists what was invalidated.
+   */
+  async resumeHint(): Promise<VrfResumedState | null> {
+    if (!this.vrfWorker) return null;
+    const message: VRFWorkerMessage<WasmVrfWorkerRequestType> = {
+      type: 'RESUME_HINT',
+      id: this.generateMessageId(),
+      payload: {} as WasmVrfWorkerRequestType
+    };
+
+    const response = await this.sendMessage(message);
+    if (!response.success || !response.data) {
+      throw new Error(`VRF resume hint failed: ${response.error}`);
+    }
+    const resumed = response.data as VrfResumedState;
+    if (!resumed.sessionActive) {
+      this.currentVrfAccountId = null;
+    }
+    return resumed;
+  }
+
+  /**
+   * Generate VRF challenges for many inputs at once (e.g. bulk authenticator migration).
+   * Proofs run in parallel when the worker's thread pool is available. Results come back in
+   * input order; a bad input fails only its own item.
+   */
+  async generateVrfChallengesBatch(
+    inputs: VRFInputData[],
+    challengeLength?: number
+  ): Promise<VrfChallengeBatchResult> {
+    await this.ensureWorkerReady(true);
+    const message: VRFWorkerMessage<WasmGenerateVrfChallengesBatchRequest> = {
+      type: 'GENERATE_VRF_CHALLENGES_BATCH',
+      id: this.generateMessageId(),
+      payload: {
+        vrfInputs: inputs.map(toVrfInputPayload) as WasmGenerateVrfChallengesBatchRequest['vrfInputs'],
+        challengeLength,
+      }
+    };
+
+    const response = await this.sendMessage(message);
+    if (!response.success || !response.data) {
+      throw new Error(`VRF challenge batch generation failed: ${response.error}`);
+    }
+
+    const batch = response.data as VrfChallengeBatchResult;
+    return {
+      ...batch,
+      results: batch.results.map(item => item.challenge
+        ? { ...item, challenge: validateVRFChallenge(item.challenge) }
+        : item),
+    };
+  }
+
   /**
    * Get current VRF session status
    */
@@ -320,7 +569,8 @@ export class VrfWorkerManager {
         return {
           active: response.data.active,
           nearAccountId: this.currentVrfAccountId ? toAccountId(this.currentVrfAccountId) : null,
-          sessionDuration: response.data.sessionDuration
+          sessionDuration: response.data.sessionDuration,
+          scopes: response.data.scopes,
         };
       }
 
@@ -360,6 +610,102 @@ export class VrfWorkerManager {
     }
   }
 
+  /**
+   * Where syncSignerSession reads the signer session from (the signer worker's GetSessionStatus)
+   */
+  setSignerSessionSource(source: (() => Promise<{ epoch: number; maxSessionDurationMs?: number }>) | null): void {
+    this.signerSessionSource = source;
+  }
+
+  /**
+   * Push the signer session's epoch and maxSessionDurationMs to the worker, which then refuses
+   * challenges and snapshots past the duration and snapshots from an earlier signer epoch.
+   * No-op without a signer session source.
+   */
+  async syncSignerSession(): Promise<void> {
+    if (!this.signerSessionSource) return;
+    const { epoch, maxSessionDurationMs } = await this.signerSessionSource();
+    await this.ensureWorkerReady();
+    const message: VRFWorkerMessage<WasmUpdateSessionPolicyRequest> = {
+      type: 'UPDATE_SESSION_POLICY',
+      id: this.generateMessageId(),
+      payload: {
+        sessionEpoch: epoch,
+        maxSessionDurationMs,
+      } as WasmUpdateSessionPolicyRequest
+    };
+
+    const response = await this.sendMessage(message);
+    if (!response.success) {
+      throw new Error(`VRF session policy update failed: ${response.error}`);
+    }
+  }
+
+  /**
+   * Export the unlocked VRF session as an encrypted snapshot, so it can be restored into a
+   * restarted worker without another TouchID unlock. Keep the returned token in memory only.
+   */
+  async exportSessionSnapshot(): Promise<VrfSessionSnapshotExport & { nearAccountId: string | null }> {
+    await this.ensureWorkerReady(true);
+    await this.syncSignerSession();
+    const message: VRFWorkerMessage<WasmVrfWorkerRequestType> = {
+      type: 'EXPORT_SESSION_SNAPSHOT',
+      id: this.generateMessageId(),
+      payload: {} as WasmVrfWorkerRequestType
+    };
+
+    const response = await this.sendMessage(message);
+    if (!response.success || !response.data) {
+      throw new Error(`VRF session snapshot export failed: ${response.error}`);
+    }
+    const exported = response.data as VrfSessionSnapshotExport;
+    this.snapshotRevocationState = exported.revocationState;
+    return { ...exported, nearAccountId: this.currentVrfAccountId };
+  }
+
+  /**
+   * Restore a session exported by exportSessionSnapshot. Each snapshot restores once; failures
+   * carry a VrfSessionSnapshotErrorCode prefix (expired, reused, revoked or invalid).
+   */
+  async restoreSessionSnapshot(exported: VrfSessionSnapshotExport & { nearAccountId: string | null }): Promise<void> {
+    await this.ensureWorkerReady(true);
+    await this.syncSignerSession();
+    const message: VRFWorkerMessage<WasmRestoreSessionSnapshotRequest> = {
+      type: 'RESTORE_SESSION_SNAPSHOT',
+      id: this.generateMessageId(),
+      payload: {
+        snapshot: exported.snapshot as WasmRestoreSessionSnapshotRequest['snapshot'],
+        token: exported.token,
+      }
+    };
+
+    const response = await this.sendMessage(message);
+    if (!response.success) {
+      throw new Error(response.error || 'VRF session snapshot restore failed');
+    }
+    this.snapshotRevocationState = response.data?.revocationState ?? this.snapshotRevocationState;
+    this.currentVrfAccountId = exported.nearAccountId;
+  }
+
+  /**
+   * Clear the VRF session and revoke every outstanding session snapshot
+   */
+  async wipeAllState(): Promise<void> {
+    await this.ensureWorkerReady();
+    const message: VRFWorkerMessage<WasmVrfWorkerRequestType> = {
+      type: 'WIPE_ALL_STATE',
+      id: this.generateMessageId(),
+      payload: {} as WasmVrfWorkerRequestType
+    };
+
+    const response = await this.sendMessage(message);
+    if (!response.success) {
+      throw new Error(`VRF state wipe failed: ${response.error}`);
+    }
+    this.snapshotRevocationState = response.data?.revocationState ?? this.snapshotRevocationState;
+    this.currentVrfAccountId = null;
+  }
+
   /**
    * Generate VRF keypair for bootstrapping - stores in memory unencrypted temporarily
    * This is used during registration to generate a VRF keypair that will be used for
@@ -367,7 +713,8 @@ export class VrfWorkerManager {
    *
    * @param saveInMemory - Always true for bootstrap (VRF keypair stored in memory)
    * @param vrfInputParams - Optional parameters to generate VRF challenge/proof in same call
-   * @returns VRF public key and optionally VRF challenge data
+   * @returns VRF public key, VRF challenge data, and the entropy sources the worker mixed into
+   * the keypair seed (main-thread crypto.getRandomValues bytes are sent as extraEntropyB64u)
    */
   async generateVrfKeypairBootstrap({
     vrfInputData,
@@ -378,6 +725,7 @@ export class VrfWorkerManager {
   }): Promise<{
     vrfPublicKey: string;
     vrfChallenge: VRFChallenge;
+    entropySources: string[];
   }> {
     await this.ensureWorkerReady();
     try {
@@ -386,12 +734,11 @@ export class VrfWorkerManager {
         id: this.generateMessageId(),
         payload: {
           // Include VRF input data if provided for challenge generation
-          vrfInputData: vrfInputData ? {
-            userId: vrfInputData.userId,
-            rpId: vrfInputData.rpId,
-            blockHeight: String(vrfInputData.blockHeight),
-            blockHash: vrfInputData.blockHash,
-          } : undefined
+          vrfInputData: vrfInputData
+            ? toVrfInputPayload(vrfInputData) as WasmGenerateVrfKeypairBootstrapRequest['vrfInputData']
+            : undefined,
+          // Mixed into the keypair seed alongside the worker's own RNG
+          extraEntropyB64u: base64UrlEncode(crypto.getRandomValues(new Uint8Array(32))),
         }
       };
 
@@ -425,7 +772,9 @@ export class VrfWorkerManager {
           rpId: challengeData.rpId,
           blockHeight: challengeData.blockHeight,
           blockHash: challengeData.blockHash,
-        })
+          challengeLength: challengeData.challengeLength,
+        }),
+        entropySources: response.data.entropy_sources || [],
       }
 
     } catch (error: any) {
@@ -442,6 +791,7 @@ export class VrfWorkerManager {
    * @param prfOutput - Base64url-encoded PRF output from WebAuthn credential (PRF Output 1)
    * @param nearAccountId - NEAR account ID for key derivation salt
    * @param vrfInputParams - Optional VRF input parameters for challenge generation
+   * @param deviceNumber - Optional device number, recorded in the encrypted keypair's metadata
    * @returns Deterministic VRF public key, optional VRF challenge, and encrypted VRF keypair for storage
    */
   async deriveVrfKeypairFromPrf({
@@ -449,11 +799,13 @@ export class VrfWorkerManager {
     nearAccountId,
     vrfInputData,
     saveInMemory = true,
+    deviceNumber,
   }: {
     credential: import('../../types/webauthn').WebAuthnAuthenticationCredential;
     nearAccountId: AccountId;
     vrfInputData?: VRFInputData; // optional, for challenge generation
     saveInMemory?: boolean; // optional, whether to save in worker memory
+    deviceNumber?: number;
   }): Promise<{
     vrfPublicKey: string;
     vrfChallenge: VRFChallenge | null;
@@ -473,10 +825,7 @@ export class VrfWorkerManager {
       });
 
       // optional VRF Input data, only needed if generating VRF challenge simultaneously
-      const hasVrfInputData = vrfInputData?.blockHash
-        && vrfInputData?.blockHeight
-        && vrfInputData?.userId
-        && vrfInputData?.rpId;
+      const hasVrfInputData = hasCompleteVrfInput(vrfInputData);
 
 
       const message: VRFWorkerMessage<WasmDeriveVrfKeypairFromPrfRequest> = {
@@ -485,14 +834,14 @@ export class VrfWorkerManager {
         payload: {
           prfOutput: chacha20PrfOutput,
           nearAccountId: nearAccountId,
+          // Bound into the encrypted keypair's metadata
+          credentialIdB64u: credential.id,
+          deviceNumber,
           saveInMemory: saveInMemory,
           // Add VRF input parameters if provided for challenge generation
-          vrfInputData: hasVrfInputData ? {
-            userId: vrfInputData.userId,
-            rpId: vrfInputData.rpId,
-            blockHeight: String(vrfInputData.blockHeight),
-            blockHash: vrfInputData.blockHash,
-          } : undefined,
+          vrfInputData: hasVrfInputData
+            ? toVrfInputPayload(vrfInputData) as WasmDeriveVrfKeypairFromPrfRequest['vrfInputData']
+            : undefined,
         }
       };
 
@@ -521,6 +870,7 @@ export class VrfWorkerManager {
             rpId: response.data.vrfChallengeData.rpId,
             blockHeight: response.data.vrfChallengeData.blockHeight,
             blockHash: response.data.vrfChallengeData.blockHash,
+            challengeLength: response.data.vrfChallengeData.challengeLength,
           })
         : null;
 
@@ -566,10 +916,7 @@ export class VrfWorkerManager {
   }> {
     await this.ensureWorkerReady();
 
-    const hasVrfInputData = vrfInputData?.blockHash
-      && vrfInputData?.blockHeight
-      && vrfInputData?.userId
-      && vrfInputData?.rpId;
+    const hasVrfInputData = hasCompleteVrfInput(vrfInputData);
 
     const message: VRFWorkerMessage<WasmDeriveVrfKeypairFromPrfRequest> = {
       type: 'DERIVE_VRF_KEYPAIR_FROM_PRF',
@@ -578,12 +925,9 @@ export class VrfWorkerManager {
         prfOutput,
         nearAccountId: nearAccountId,
         saveInMemory: saveInMemory,
-        vrfInputData: hasVrfInputData ? {
-          userId: vrfInputData.userId,
-          rpId: vrfInputData.rpId,
-          blockHeight: String(vrfInputData.blockHeight),
-          blockHash: vrfInputData.blockHash,
-        } : undefined,
+        vrfInputData: hasVrfInputData
+          ? toVrfInputPayload(vrfInputData) as WasmDeriveVrfKeypairFromPrfRequest['vrfInputData']
+          : undefined,
       }
     };
 
@@ -602,6 +946,7 @@ export class VrfWorkerManager {
           rpId: response.data.vrfChallengeData.rpId,
           blockHeight: response.data.vrfChallengeData.blockHeight,
           blockHash: response.data.vrfChallengeData.blockHash,
+          challengeLength: response.data.vrfChallengeData.challengeLength,
         })
       : null;
 
@@ -627,11 +972,13 @@ export class VrfWorkerManager {
     kek_s_b64u,
     ciphertextVrfB64u,
     serverKeyId,
+    scopes,
   }: {
     nearAccountId: AccountId;
     kek_s_b64u: string;
     ciphertextVrfB64u: string;
     serverKeyId: string;
+    scopes?: VrfScope[];
   }): Promise<VRFWorkerResponse> {
     await this.ensureWorkerReady(true);
     const message: VRFWorkerMessage<WasmShamir3PassClientDecryptVrfKeypairRequest> = {
@@ -643,6 +990,7 @@ export class VrfWorkerManager {
         ciphertextVrfB64u,
         // Required key for server selection
         keyId: serverKeyId,
+        scopes,
       },
     };
     const response = await this.sendMessage(message);
@@ -6,6 +6,7 @@ import {
   type AuthenticationExtensionsClientOutputs,
   type CredentialPropertiesOutput,
 } from '../types/webauthn';
+import type { ClientAuthenticatorData } from '../IndexedDBManager';
 
 /**
  * Dual PRF outputs for separate encryption and signing key derivation
@@ -112,6 +113,33 @@ export function extractPrfFromCredential({
   };
 }
 
+/**
+ * Whether the credential's extension results carry a first PRF output.
+ * Authenticators without the PRF extension (e.g. many security keys) return none.
+ */
+export function hasPrfResults(
+  credential: PublicKeyCredential | { clientExtensionResults?: unknown; getClientExtensionResults?: () => unknown }
+): boolean {
+  try {
+    return !!extractPrfFromCredential({ credential, firstPrfOutput: true, secondPrfOutput: false }).chacha20PrfOutput;
+  } catch {
+    return false;
+  }
+}
+
+/**
+ * Base64url largeBlob read from an authentication credential, if the authenticator returned one
+ */
+export function extractLargeBlobFromCredential(credential: PublicKeyCredential): string | undefined {
+  try {
+    const results = credential.getClientExtensionResults() as { largeBlob?: { blob?: ArrayBuffer } };
+    const blob = results?.largeBlob?.blob;
+    return blob && blob.byteLength > 0 ? base64UrlEncode(blob) : undefined;
+  } catch {
+    return undefined;
+  }
+}
+
 type SerializableCredential = WebAuthnAuthenticationCredential | WebAuthnRegistrationCredential;
 
 /**
@@ -184,6 +212,21 @@ export function serializeAuthenticationCredential(
   };
 }
 
+/**
+ * Adds the COSE public key registration stored for the credential (its IndexedDB authenticator
+ * record), so the signer worker can verify the assertion locally: assertions carry no key.
+ * Returned unchanged when no record matches.
+ */
+export function withStoredCredentialPublicKey<C extends WebAuthnAuthenticationCredential>(
+  credential: C,
+  authenticators: ClientAuthenticatorData[],
+): C {
+  const stored = authenticators.find(a => a.credentialId === credential.rawId);
+  if (!stored?.credentialPublicKey?.length) return credential;
+  const credentialPublicKey = base64UrlEncode(new Uint8Array(stored.credentialPublicKey).buffer);
+  return { ...credential, response: { ...credential.response, credentialPublicKey } };
+}
+
 /**
  * Serialize PublicKeyCredential for both authentication and registration for WASM worker
  * @returns SerializableCredential - The serialized credential
@@ -297,6 +340,7 @@ export function normalizeAuthenticationCredential(input: unknown): WebAuthnAuthe
   if (!isString(resp.authenticatorData)) resp.authenticatorData = '';
   if (!isString(resp.signature)) resp.signature = '';
   if (resp.userHandle !== undefined && !isString(resp.userHandle)) resp.userHandle = undefined;
+  if (resp.credentialPublicKey !== undefined && !isString(resp.credentialPublicKey)) delete resp.credentialPublicKey;
 
   // Normalize client extension results to SDK-local type
   const normalizedExtensions = normalizeClientExtensionOutputs(out.clientExtensionResults);
@@ -17,16 +17,48 @@ import {
   EncryptedVRFKeypair,
   ServerEncryptedVrfKeypair,
   VRFInputData,
-  VRFChallenge
+  VRFChallenge,
+  VrfResumedState,
+  type VrfScope,
 } from '../types/vrf-worker';
 import type { ActionArgsWasm, TransactionInputWasm } from '../types/actions';
-import type { PasskeyManagerConfigs, RegistrationHooksOptions, RegistrationSSEEvent, onProgressEvents } from '../types/passkeyManager';
+import type { PasskeyManagerConfigs, RegistrationHooksOptions, RegistrationSSEEvent, RegistrationDryRunReport, onProgressEvents } from '../types/passkeyManager';
 import type { VerifyAndSignTransactionResult } from '../types/passkeyManager';
 import type { AccountId } from '../types/accountIds';
 import type { AuthenticatorOptions } from '../types/authenticatorOptions';
-import type { ConfirmationConfig, RpcCallPayload } from '../types/signer-worker';
+import type {
+  ConfirmationConfig,
+  DeployManifest,
+  KeyBlobCandidate,
+  ConditionalBatchOptions,
+  KeyUsageStats,
+  OuterWrapMode,
+  SubKeyEntry,
+  PendingRegistration,
+  RpcCallPayload,
+  RelayerResult,
+  RemoteConfirmationRequest,
+  RedirectPayload,
+  RpcOverrides,
+  SignerWireFormat,
+  SecretSealingMode,
+  SigningIntent,
+  StagingContractInterface,
+  WorkerInfo,
+  SessionStatus,
+  DecryptionCapability,
+  ActiveAccounts,
+  AccountWipeSummary,
+  ArgsSchema,
+  TransactionsSummary,
+  MigrateEncryptedBlobsResult,
+  StoredEncryptedBlob,
+  EvmSignature,
+  AuthenticatorInfo,
+} from '../types/signer-worker';
 import { WebAuthnRegistrationCredential, WebAuthnAuthenticationCredential } from '../types';
 import { RegistrationCredentialConfirmationPayload } from './SignerWorkerManager/handlers/validation';
+import type { SigningHooks } from './SignerWorkerManager/signingHooks';
 
 
 /**
@@ -45,6 +77,7 @@ export class WebAuthnManager {
   private readonly userPreferencesManager: UserPreferencesManager;
   private readonly nearClient: NearClient;
   private readonly nonceManager: NonceManager;
+  private readonly resumeListeners = new Set<(state: VrfResumedState) => void>();
 
   readonly passkeyManagerConfigs: PasskeyManagerConfigs;
 
@@ -66,6 +99,7 @@ export class WebAuthnManager {
       relayServerUrl: vrfWorkerConfigs?.shamir3pass?.relayServerUrl,
       applyServerLockRoute: vrfWorkerConfigs?.shamir3pass?.applyServerLockRoute,
       removeServerLockRoute: vrfWorkerConfigs?.shamir3pass?.removeServerLockRoute,
+      expectedWasmSha256B64u: vrfWorkerConfigs?.expectedWasmSha256B64u,
     });
     // Respect rpIdOverride and enable/disable get() bridge fallback (cross-origin wallet scenarios)
     this.touchIdPrompt = new TouchIdPrompt(
@@ -83,10 +117,58 @@ export class WebAuthnManager {
       passkeyManagerConfigs.iframeWallet?.rpIdOverride,
       !!passkeyManagerConfigs.iframeWallet?.enableSafariGetWebauthnRegistrationFallback,
     );
+    this.signerWorkerManager.setAllowedRpcOrigins(passkeyManagerConfigs.allowedRpcOrigins);
+    this.signerWorkerManager.setAllowedRedirectOrigins(passkeyManagerConfigs.allowedRedirectOrigins);
+    this.signerWorkerManager.setMaxSigningIntentTtlMs(passkeyManagerConfigs.maxSigningIntentTtlMs);
+    this.signerWorkerManager.setMaxSessionDurationMs(passkeyManagerConfigs.maxSessionDurationMs);
+    this.vrfWorkerManager.setSignerSessionSource(() => this.signerWorkerManager.getSessionStatus());
+    this.signerWorkerManager.setDepositEscalationYocto(passkeyManagerConfigs.depositEscalationYocto);
+    this.signerWorkerManager.setLowAllowanceWarningYocto(passkeyManagerConfigs.lowAllowanceWarningYocto);
+    this.signerWorkerManager.setPrfFallbackSchemes(passkeyManagerConfigs.prfFallbackSchemes);
+    this.signerWorkerManager.setTelemetry(passkeyManagerConfigs.telemetry);
+    this.signerWorkerManager.setMessageSizeLimits(passkeyManagerConfigs.messageSizeLimits);
+    this.signerWorkerManager.setTrustedConfirmationOrigin(passkeyManagerConfigs.trustedConfirmationOrigin);
+    this.signerWorkerManager.setKnownAuthenticators(passkeyManagerConfigs.knownAuthenticators);
     this.passkeyManagerConfigs = passkeyManagerConfigs;
+    if (vrfWorkerConfigs?.prefetchChallenge) {
+      // Each new block lets the VRF worker prove the next signing challenge ahead of time
+      this.nonceManager.onBlockUpdate(block => {
+        this.vrfWorkerManager.updateBlockInfo(block).catch(() => {});
+      });
+    }
+    if (typeof window !== 'undefined') {
+      // bfcache and frozen mobile tabs: the workers count the freeze in wall-clock time
+      window.addEventListener('pagehide', () => {
+        this.vrfWorkerManager.suspendHint().catch(() => {});
+      });
+      window.addEventListener('pageshow', () => { void this.handlePageResumed(); });
+    }
     // VRF worker initializes on-demand with proper error propagation
   }
 
+  /**
+   * Subscribe to the report the VRF worker sends when the page is shown again after being
+   * hidden, listing what went stale (e.g. the session auto-locked). Returns an unsubscribe.
+   */
+  onWorkersResumed(listener: (state: VrfResumedState) => void): () => void {
+    this.resumeListeners.add(listener);
+    return () => { this.resumeListeners.delete(listener); };
+  }
+
+  private async handlePageResumed(): Promise<void> {
+    try {
+      const state = await this.vrfWorkerManager.resumeHint();
+      if (!state) return;
+      if (state.invalidated.includes('blockInfo')) {
+        // The cached block is as stale as the worker's; the next refresh pushes a fresh one
+        this.nonceManager.clearTransactionContext();
+      }
+      this.resumeListeners.forEach(listener => { try { listener(state); } catch {} });
+    } catch (error) {
+      console.debug('WebAuthnManager: VRF resume hint failed', error);
+    }
+  }
+
   /**
    * Resolve the effective rpId used for WebAuthn operations.
    * Delegates to TouchIdPrompt to centralize rpId selection logic.
@@ -157,8 +239,15 @@ export class WebAuthnManager {
   // VRF MANAGER FUNCTIONS
   ///////////////////////////////////////
 
-  async generateVrfChallenge(vrfInputData: VRFInputData): Promise<VRFChallenge> {
-    return this.vrfWorkerManager.generateVrfChallenge(vrfInputData);
+  /**
+   * With `crossCheckRpcUrl`, the VRF worker first looks the block up by hash on that RPC and
+   * fails with InvalidBlockHash unless it is at `blockHeight`.
+   */
+  async generateVrfChallenge(
+    vrfInputData: VRFInputData,
+    options?: { crossCheckRpcUrl?: string }
+  ): Promise<VRFChallenge> {
+    return this.vrfWorkerManager.generateVrfChallenge(vrfInputData, options);
   }
 
   /**
@@ -220,7 +309,13 @@ export class WebAuthnManager {
     credential: WebAuthnRegistrationCredential;
     nearAccountId: string;
     options?: any;
-  }): Promise<{ success: boolean; nearAccountId: string; publicKey: string; signedTransaction?: SignedTransaction }>{
+  }): Promise<{
+    success: boolean;
+    nearAccountId: string;
+    publicKey: string;
+    signedTransaction?: SignedTransaction;
+    authenticator?: AuthenticatorInfo;
+  }>{
     return this.signerWorkerManager.deriveNearKeypairAndEncryptFromSerialized({
       credential,
       nearAccountId: toAccountId(nearAccountId),
@@ -341,10 +436,12 @@ export class WebAuthnManager {
     nearAccountId,
     encryptedVrfKeypair,
     credential,
+    scopes,
   }: {
     nearAccountId: AccountId;
     encryptedVrfKeypair: EncryptedVRFKeypair;
     credential: WebAuthnAuthenticationCredential;
+    scopes?: VrfScope[];
   }): Promise<{ success: boolean; error?: string }> {
     try {
       console.debug('WebAuthnManager: Unlocking VRF keypair');
@@ -353,6 +450,7 @@ export class WebAuthnManager {
         credential,
         nearAccountId,
         encryptedVrfKeypair,
+        scopes,
       });
 
       if (!unlockResult.success) {
@@ -362,6 +460,7 @@ export class WebAuthnManager {
 
       // Warm up signer workers after a successful unlock to minimize first-use latency
       try { this.signerWorkerManager.preWarmWorkerPool().catch(() => {}); } catch {}
+      await this.enableChallengePrefetch(nearAccountId);
 
       return { success: true };
 
@@ -382,18 +481,24 @@ export class WebAuthnManager {
     kek_s_b64u,
     ciphertextVrfB64u,
     serverKeyId,
+    scopes,
   }: {
     nearAccountId: AccountId;
     kek_s_b64u: string;
     ciphertextVrfB64u: string;
     serverKeyId: string;
+    scopes?: VrfScope[];
   }): Promise<{ success: boolean; error?: string }> {
     const result = await this.vrfWorkerManager.shamir3PassDecryptVrfKeypair({
       nearAccountId,
       kek_s_b64u,
       ciphertextVrfB64u,
       serverKeyId,
+      scopes,
     });
+    if (result.success) {
+      await this.enableChallengePrefetch(nearAccountId);
+    }
 
     return {
       success: result.success,
@@ -401,6 +506,34 @@ export class WebAuthnManager {
     };
   }
 
+  /**
+   * Start the VRF worker's challenge prefetch and auto-lock for the unlocked account when
+   * configured (vrfWorkerConfigs.prefetchChallenge, autoLockAfterMs), and its issuance
+   * receipts (issuanceReceiptKeyB64u). Best-effort: signing falls back to fresh proofs.
+   */
+  private async enableChallengePrefetch(nearAccountId: AccountId): Promise<void> {
+    if (this.passkeyManagerConfigs.maxSessionDurationMs) {
+      // The VRF worker refuses challenges past the signer's maxSessionDurationMs too
+      await this.vrfWorkerManager.syncSignerSession().catch(error => {
+        console.debug('WebAuthnManager: VRF session policy not updated', error);
+      });
+    }
+    const { prefetchChallenge, autoLockAfterMs, issuanceReceiptKeyB64u } =
+      this.passkeyManagerConfigs.vrfWorkerConfigs ?? {};
+    if (!prefetchChallenge && !autoLockAfterMs && !issuanceReceiptKeyB64u) return;
+    try {
+      await this.vrfWorkerManager.configureSessionOptions({
+        prefetchChallenge: !!prefetchChallenge,
+        userId: nearAccountId,
+        rpId: this.getRpId(),
+        autoLockAfterMs,
+        issuanceReceiptKeyB64u,
+      });
+    } catch (error) {
+      console.debug('WebAuthnManager: VRF challenge prefetch not enabled', error);
+    }
+  }
+
   /**
    * Shamir 3-pass: encrypt the unlocked VRF keypair under the server key
    * Returns a fresh blob to store in IndexedDB for future auto-login.
@@ -590,6 +723,7 @@ export class WebAuthnManager {
     syncedAt: string;
     vrfPublicKey: string;
     deviceNumber?: number;
+    residentKey?: boolean;
   }): Promise<void> {
     const authData = {
       ...authenticatorData,
@@ -660,6 +794,7 @@ export class WebAuthnManager {
         registered: new Date().toISOString(),
         syncedAt: new Date().toISOString(),
         vrfPublicKey: vrfPublicKey,
+        residentKey: credential.clientExtensionResults?.credProps?.rk,
       });
 
       // Store WebAuthn user data with encrypted VRF credentials
@@ -710,17 +845,54 @@ export class WebAuthnManager {
    * @param confirmationConfigOverride: Optional confirmation configuration override
    * @param onEvent: Optional callback for progress updates during signing
    * @param onEvent - Optional callback for progress updates during signing
+   * @param validUntilBlockHeight - Optional last block height the transactions may be broadcast at;
+   *   shown in the confirmation and returned with each result
+   * @param signingPublicKey - Optional key to sign with instead of the passkey's key; its blob must
+   *   be among `keyCandidates`, and its on-chain permission must cover the transactions
+   * @param deduplicate - Drop transactions identical to an earlier one of the batch; the dropped
+   *   indexes are shown in the confirmation and returned with each result
+   * @param previewDigest - Optional digest from summarizeTransactions for the batch the user
+   *   previewed; fails with PayloadChangedSincePreview if the transactions differ from it
+   * @param redactPaths - Optional FunctionCall args values (e.g. "args.email") to show as their
+   *   SHA-256 instead of verbatim in the confirmation and the audit log
+   * @param conditionalBatch - Simulate the transactions in order before signing, and sign each
+   *   only if every earlier one simulated successfully; results carry conditionalEntries
+   * @param autoStorageDeposit - Insert a storage_deposit ahead of ft_transfer and ft_transfer_call
+   *   actions to receivers the token contract has not registered; the confirmation shows the
+   *   inserted actions as added automatically
+   * @param idempotencyKey - Retries with the same key get the first completed result back
+   *   instead of being signed again, also from a later worker
    */
   async signTransactionsWithActions({
     transactions,
     rpcCall,
     confirmationConfigOverride,
     onEvent,
+    rpcOverrides,
+    validUntilBlockHeight,
+    signingPublicKey,
+    keyCandidates,
+    deduplicate,
+    previewDigest,
+    redactPaths,
+    conditionalBatch,
+    autoStorageDeposit,
+    idempotencyKey,
   }: {
     transactions: TransactionInputWasm[],
     rpcCall: RpcCallPayload,
     confirmationConfigOverride?: ConfirmationConfig,
     onEvent?: (update: onProgressEvents) => void,
+    rpcOverrides?: RpcOverrides,
+    validUntilBlockHeight?: number,
+    signingPublicKey?: string,
+    keyCandidates?: KeyBlobCandidate[],
+    deduplicate?: boolean,
+    previewDigest?: string,
+    redactPaths?: string[],
+    conditionalBatch?: ConditionalBatchOptions,
+    autoStorageDeposit?: boolean,
+    idempotencyKey?: string,
   }): Promise<VerifyAndSignTransactionResult[]> {
 
     if (transactions.length === 0) {
@@ -731,9 +903,352 @@ export class WebAuthnManager {
       rpcCall,
       confirmationConfigOverride,
       onEvent,
+      rpcOverrides,
+      validUntilBlockHeight,
+      signingPublicKey,
+      keyCandidates,
+      deduplicate,
+      previewDigest,
+      redactPaths,
+      conditionalBatch,
+      autoStorageDeposit,
+      idempotencyKey,
     });
   }
 
+  /**
+   * Canonical digest of a batch of transactions, to show with a preview of it and pass to
+   * signTransactionsWithActions as `previewDigest`, so the batch signed is the one previewed
+   */
+  async summarizeTransactions(args: {
+    transactions: TransactionInputWasm[],
+    nearAccountId: AccountId,
+    deduplicate?: boolean,
+  }): Promise<TransactionsSummary> {
+    return await this.signerWorkerManager.summarizeTransactions(args);
+  }
+
+  /**
+   * Signs a deploy of a contract above the transaction size limit, through a staging contract
+   * that supports store-then-deploy. The user confirms one summarized deploy; the result holds
+   * the staging calls and the final deploy call in broadcast order, plus the manifest of chunk
+   * hashes and the total hash (the deployed contract's code_hash).
+   *
+   * @param code - Contract wasm bytes
+   * @param chunkSize - Bytes per staging call
+   * @param staging - Staging contract methods (and receiver, defaulting to the signer account)
+   */
+  async deployLargeContract(args: {
+    code: Uint8Array | number[],
+    chunkSize: number,
+    staging: StagingContractInterface,
+    rpcCall: RpcCallPayload,
+    confirmationConfigOverride?: ConfirmationConfig,
+    onEvent?: (update: onProgressEvents) => void,
+    rpcOverrides?: RpcOverrides,
+  }): Promise<{
+    signedTransactions: SignedTransaction[];
+    nearAccountId: AccountId;
+    manifest: DeployManifest;
+    logs?: string[];
+    broadcastRpcUrl?: string;
+  }> {
+    return await this.signerWorkerManager.deployLargeContract(args);
+  }
+
+  /**
+   * Runs the usual transaction confirmation and returns a signing intent instead of signatures,
+   * so the batch can be signed later (e.g. once back online) with executeSigningIntent.
+   * `ttlMs` defaults to 10 minutes and may not exceed configs.maxSigningIntentTtlMs.
+   */
+  async createSigningIntent(args: {
+    transactions: TransactionInputWasm[],
+    rpcCall: RpcCallPayload,
+    ttlMs?: number,
+    confirmationConfigOverride?: ConfirmationConfig,
+    onEvent?: (update: onProgressEvents) => void,
+  }): Promise<SigningIntent> {
+    return await this.signerWorkerManager.createSigningIntent(args);
+  }
+
+  /**
+   * Signs the transactions of a signing intent without confirmation UI, with a fresh nonce and
+   * block hash. `transactions` must be the confirmed ones. Errors start with
+   * 'SigningIntentInvalid', 'SigningIntentExpired', 'SigningIntentReplayed' or
+   * 'SigningIntentPayloadMismatch'.
+   */
+  async executeSigningIntent(args: {
+    intentToken: string,
+    transactions: TransactionInputWasm[],
+    rpcCall: RpcCallPayload,
+    onEvent?: (update: onProgressEvents) => void,
+    rpcOverrides?: RpcOverrides,
+  }): Promise<Array<{
+    signedTransaction: SignedTransaction;
+    nearAccountId: AccountId;
+    logs?: string[];
+    broadcastRpcUrl?: string;
+  }>> {
+    return await this.signerWorkerManager.executeSigningIntent(args);
+  }
+
+  /**
+   * Signs a sign-in result with `sessionPublicKey` (a session key of `nearAccountId`) for an
+   * integration that can't embed the wallet iframe, and returns `callbackUrl` with the payload
+   * and `state` added. The callback must be https on configs.allowedRedirectOrigins. The page
+   * it lands on checks the payload with verify_redirect_payload, and that the session key is
+   * an access key of the account. Errors start with 'RedirectSchemeNotAllowed' or
+   * 'RedirectOriginNotAllowed' for refused callbacks.
+   */
+  async buildRedirectPayload(args: {
+    nearAccountId: AccountId,
+    sessionPublicKey: string,
+    callbackUrl: string,
+    state: string,
+    ttlMs?: number,
+  }): Promise<RedirectPayload> {
+    const { txBlockHash, txBlockHeight } = await this.nonceManager.getNonceBlockHashAndHeight(this.nearClient);
+    const vrfChallenge = await this.generateVrfChallenge({
+      userId: args.nearAccountId,
+      rpId: this.getRpId(),
+      blockHash: txBlockHash,
+      blockHeight: txBlockHeight,
+    });
+    return await this.signerWorkerManager.buildRedirectPayload({ ...args, vrfChallenge });
+  }
+
+  /**
+   * Starts a confirmation of `transactions` on another device signed into the same passkey
+   * (e.g. a phone): returns the request to hand over by QR code or relayer. The approval
+   * comes back from approveRemoteConfirmation on that device and is only valid on this
+   * device (its worker state record), once, until `expiresAtMs` (`ttlMs` defaults to 2 minutes, at most 10).
+   */
+  async createRemoteConfirmation(args: {
+    transactions: TransactionInputWasm[],
+    rpcCall: RpcCallPayload,
+    ttlMs?: number,
+  }): Promise<RemoteConfirmationRequest> {
+    const { txBlockHash, txBlockHeight } = await this.nonceManager.getNonceBlockHashAndHeight(this.nearClient);
+    const vrfChallenge = await this.generateVrfChallenge({
+      userId: args.rpcCall.nearAccountId,
+      rpId: this.getRpId(),
+      blockHash: txBlockHash,
+      blockHeight: txBlockHeight,
+    });
+    return await this.signerWorkerManager.createRemoteConfirmation({ ...args, vrfChallenge });
+  }
+
+  /**
+   * Shows another device's confirmation request in the confirmation UI and returns the sealed
+   * approval to send back. uiMode 'skip' is refused. Errors start with
+   * 'RemoteConfirmationInvalid', 'RemoteConfirmationExpired', 'RemoteConfirmationReplayed' or
+   * 'RemoteConfirmationWrongSession'.
+   */
+  async approveRemoteConfirmation(args: {
+    confirmationRequest: string,
+    nearAccountId: AccountId,
+    confirmationConfigOverride?: ConfirmationConfig,
+    onEvent?: (update: onProgressEvents) => void,
+  }): Promise<{ approval: string; requestId: string }> {
+    return await this.signerWorkerManager.approveRemoteConfirmation(args);
+  }
+
+  /**
+   * Signs the transactions of a remote confirmation without local UI once its approval is
+   * back. `transactions` must be the ones given to createRemoteConfirmation; errors carry the
+   * same codes as approveRemoteConfirmation.
+   */
+  async completeRemoteConfirmation(args: {
+    approval: string,
+    transactions: TransactionInputWasm[],
+    rpcCall: RpcCallPayload,
+    onEvent?: (update: onProgressEvents) => void,
+    rpcOverrides?: RpcOverrides,
+  }): Promise<Array<{
+    signedTransaction: SignedTransaction;
+    nearAccountId: AccountId;
+    logs?: string[];
+    broadcastRpcUrl?: string;
+  }>> {
+    return await this.signerWorkerManager.completeRemoteConfirmation(args);
+  }
+
+  /**
+   * Creates the account through configs.relayer from the signer worker, which posts the
+   * registration args plus the optional SignedDelegateAction, retries only the side-effect-free
+   * reachability probe, and validates the response. Relayer failures come back as
+   * `errorKind` (e.g. 'RelayerAccountExists', 'RelayerQuotaExceeded') rather than throwing.
+   * The relayer URL must be on configs.allowedRpcOrigins.
+   */
+  async submitToRelayer(args: {
+    nearAccountId: string,
+    newPublicKey: string,
+    deviceNumber?: number,
+    vrfChallenge: VRFChallenge,
+    credential: WebAuthnRegistrationCredential | PublicKeyCredential,
+    deterministicVrfPublicKey: string,
+    authenticatorOptions?: AuthenticatorOptions,
+    signedDelegateAction?: string,
+    authToken?: string,
+  }): Promise<RelayerResult> {
+    return await this.signerWorkerManager.submitToRelayer({
+      relayer: this.passkeyManagerConfigs.relayer,
+      ...args,
+    });
+  }
+
+  /**
+   * Registration in two phases that survives a closed tab: prepareRegistration stores the
+   * encrypted key (and a resume token, valid 10 minutes) before anything reaches the network;
+   * completeRegistration then creates the account through the relayer and may be retried
+   * with the same credential, always submitting the stored key.
+   */
+  async prepareRegistration(args: {
+    credential: WebAuthnRegistrationCredential,
+    nearAccountId: AccountId,
+    deviceNumber?: number,
+  }): Promise<PendingRegistration> {
+    return await this.signerWorkerManager.prepareRegistration(args);
+  }
+
+  async completeRegistration(args: {
+    nearAccountId: AccountId,
+    credential: WebAuthnRegistrationCredential,
+    vrfChallenge: VRFChallenge,
+    deterministicVrfPublicKey: string,
+    deviceNumber?: number,
+    authenticatorOptions?: AuthenticatorOptions,
+    signedDelegateAction?: string,
+    authToken?: string,
+  }): Promise<RelayerResult> {
+    return await this.signerWorkerManager.completeRegistration({
+      relayer: this.passkeyManagerConfigs.relayer,
+      ...args,
+    });
+  }
+
+  /**
+   * Exports this browser profile's passkey state (user and device records, authenticators,
+   * encrypted NEAR keys, settings) as one file for importAccountBundle in another profile.
+   * The file is encrypted in the signer worker under a key derived from `passphrase`.
+   */
+  async exportAccountBundle(args: {
+    passphrase: string,
+  }): Promise<{ bundle: Uint8Array; recordCount: number }> {
+    return await this.signerWorkerManager.exportAccountBundle(args);
+  }
+
+  /**
+   * Restores the records of an exportAccountBundle file. Errors start with
+   * 'BundlePassphraseInvalid', 'BundleTruncated', 'BundleVersionUnsupported' or 'BundleInvalid'.
+   */
+  async importAccountBundle(args: {
+    passphrase: string,
+    bundle: Uint8Array | number[],
+  }): Promise<{ recordCount: number; exportedAtMs: number }> {
+    return await this.signerWorkerManager.importAccountBundle(args);
+  }
+
+  /**
+   * Register integrator pre-sign/post-sign hooks (e.g. a compliance module).
+   * preSign may veto a confirmed request before the key is decrypted; a hook that
+   * throws or does not answer within `timeoutMs` denies the request.
+   * Pass undefined to remove the hooks.
+   */
+  setSigningHooks(hooks?: SigningHooks): void {
+    this.signerWorkerManager.setSigningHooks(hooks);
+  }
+
+  /** Frame encoding for signer worker messages ('json' by default) */
+  setSignerWireFormat(wireFormat: SignerWireFormat): void {
+    this.signerWorkerManager.setWireFormat(wireFormat);
+  }
+
+  /** Sealing of secrets sent to the signer worker: 'off' (default), 'seal' or 'require' */
+  setSecretSealing(mode: SecretSealingMode): void {
+    this.signerWorkerManager.setSecretSealing(mode);
+  }
+
+  /**
+   * Non-extractable AES-GCM CryptoKey for the outer wrap of stored key blobs (undefined to clear).
+   * Blobs stored while a key is set can only be decrypted with that key.
+   */
+  setOuterWrapKey(key?: CryptoKey): void {
+    this.signerWorkerManager.setOuterWrapKey(key);
+  }
+
+  /**
+   * Sign counts, last use and last receiver category of each stored key blob, kept across
+   * sessions. Entries whose stored record was edited are listed in `tamperedKeyIds`.
+   */
+  async getKeyUsageStats(): Promise<KeyUsageStats> {
+    return await this.signerWorkerManager.getKeyUsageStats();
+  }
+
+  /**
+   * Features compiled into the signer wasm (e.g. 'nep413', 'relayer'). A build without one
+   * answers its requests with FeatureNotCompiled.
+   */
+  async getWorkerInfo(): Promise<WorkerInfo> {
+    return await this.signerWorkerManager.getWorkerInfo();
+  }
+
+  /** Session epoch and time left before the signer session expires (see maxSessionDurationMs) */
+  async getSessionStatus(): Promise<SessionStatus> {
+    return await this.signerWorkerManager.getSessionStatus();
+  }
+
+  /**
+   * Whether a stored key blob decrypts with the PRF output of an earlier authentication,
+   * without signing anything. Lets a flow fail before prompting, and recovery UI test each
+   * stored blob against the credential just presented.
+   */
+  async validateDecryptionCapability(args: {
+    nearAccountId: AccountId;
+    chacha20PrfOutput: string;
+    encryptedPrivateKeyData: string;
+    encryptedPrivateKeyIv: string;
+  }): Promise<DecryptionCapability> {
+    return await this.signerWorkerManager.validateDecryptionCapability(args);
+  }
+
+  /**
+   * Brings stored key, VRF keypair and session snapshot blobs to the current encryption
+   * formats with the PRF output of one fresh authentication. The caller writes each report's
+   * `blob` back to storage; blobs already current are returned untouched, so it is safe to run
+   * on every login.
+   */
+  async migrateEncryptedBlobs(args: {
+    chacha20PrfOutput: string;
+    blobs: StoredEncryptedBlob[];
+  }): Promise<MigrateEncryptedBlobsResult> {
+    return await this.signerWorkerManager.migrateEncryptedBlobs(args);
+  }
+
+  /**
+   * Accounts the signer worker holds session state for, with their request counters.
+   * Lets a multi-account wallet show which accounts were used this session.
+   */
+  async listActiveAccounts(): Promise<ActiveAccounts> {
+    return await this.signerWorkerManager.listActiveAccounts();
+  }
+
+  /**
+   * Discards one account's signer session state (e.g. on signing out of that account)
+   * without touching the other accounts of the session
+   */
+  async wipeAccountState(args: { accountId: AccountId }): Promise<AccountWipeSummary> {
+    return await this.signerWorkerManager.wipeAccountState(args);
+  }
+
+  /**
+   * Registers JSON Schemas for contract method args; signing requests whose FunctionCall args
+   * fail them are refused before the user is prompted. Throws if a schema does not compile.
+   */
+  async registerArgsSchemas(schemas?: ArgsSchema[]): Promise<void> {
+    await this.signerWorkerManager.registerArgsSchemas(schemas);
+  }
+
   async signNEP413Message(payload: {
     message: string;
     recipient: string;
@@ -770,6 +1285,28 @@ export class WebAuthnManager {
     }
   }
 
+  /**
+   * Signs an EVM personal_sign message (0x-prefixed hex or text) with the account's secp256k1
+   * key blob; the worker shows the decoded message and prompts the passkey itself
+   */
+  async signEvmPersonalMessage(payload: {
+    message: string;
+    accountId: AccountId;
+    confirmationConfig?: ConfirmationConfig;
+  }): Promise<{
+    success: boolean;
+    accountId: string;
+    publicKey: string;
+    evm?: EvmSignature;
+    error?: string;
+  }> {
+    const result = await this.signerWorkerManager.signEvmPersonalMessage(payload);
+    if (!result.success) {
+      console.error('WebAuthnManager: EVM personal_sign error:', result.error);
+    }
+    return result;
+  }
+
   // === COSE OPERATIONS ===
 
   /**
@@ -811,12 +1348,14 @@ export class WebAuthnManager {
     credential,
     vrfChallenge,
     authenticatorOptions,
+    dryRun,
     onEvent,
   }: {
     contractId: string,
     credential: WebAuthnRegistrationCredential,
     vrfChallenge: VRFChallenge,
     authenticatorOptions?: AuthenticatorOptions;
+    dryRun?: boolean;
     onEvent?: (update: onProgressEvents) => void
   }): Promise<{
     success: boolean;
@@ -825,12 +1364,14 @@ export class WebAuthnManager {
     logs?: string[];
     signedTransactionBorsh?: number[];
     error?: string;
+    dryRun?: RegistrationDryRunReport;
   }> {
     return await this.signerWorkerManager.checkCanRegisterUser({
       contractId,
       credential,
       vrfChallenge,
       authenticatorOptions,
+      dryRun,
       onEvent,
       nearRpcUrl: this.passkeyManagerConfigs.nearRpcUrl,
     });
@@ -846,16 +1387,20 @@ export class WebAuthnManager {
    * @param challenge - Random challenge for WebAuthn authentication ceremony
    * @param authenticationCredential - The authentication credential with dual PRF outputs
    * @param accountIdHint - Optional account ID hint for recovery
+   * @param subKeyPaths - SLIP-0010 paths of the account's sub-keys to derive again
    * @returns Public key and encrypted private key for secure storage
    */
   async recoverKeypairFromPasskey(
     authenticationCredential: WebAuthnAuthenticationCredential,
     accountIdHint?: string,
+    subKeyPaths?: string[],
   ): Promise<{
     publicKey: string;
     encryptedPrivateKey: string;
     iv: string;
     accountIdHint?: string;
+    outerWrap?: OuterWrapMode;
+    subKeys: SubKeyEntry[];
     stored?: boolean;
   }> {
     try {
@@ -879,6 +1424,7 @@ export class WebAuthnManager {
       const result = await this.signerWorkerManager.recoverKeypairFromPasskey({
         credential: authenticationCredential,
         accountIdHint,
+        subKeyPaths,
       });
 
        console.debug('WebAuthnManager: Deterministic keypair derivation successful');
@@ -923,7 +1469,8 @@ export class WebAuthnManager {
     receiverId,
     nonce,
     blockHash,
-    actions
+    actions,
+    idempotencyKey
   }: {
     nearPrivateKey: string;
     signerAccountId: string;
@@ -931,6 +1478,7 @@ export class WebAuthnManager {
     nonce: string;
     blockHash: string;
     actions: ActionArgsWasm[];
+    idempotencyKey?: string;
   }): Promise<{
     signedTransaction: SignedTransaction;
     logs?: string[];
@@ -941,7 +1489,8 @@ export class WebAuthnManager {
       receiverId,
       nonce,
       blockHash,
-      actions
+      actions,
+      idempotencyKey
     });
   }
 
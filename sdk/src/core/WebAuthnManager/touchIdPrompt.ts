@@ -1,11 +1,12 @@
 import { ClientAuthenticatorData } from '../IndexedDBManager';
 import { base64UrlDecode } from '../../utils/encoders';
-import { outputAs32Bytes, VRFChallenge } from '../types/vrf-worker';
+import { deriveWebAuthnChallenge, VRFChallenge } from '../types/vrf-worker';
 import { serializeAuthenticationCredentialWithPRF } from './credentialsHelpers';
 import type {
   WebAuthnAuthenticationCredential,
   WebAuthnRegistrationCredential
 } from '../types/webauthn';
+import type { CredentialCreationOptionsJSON, CredentialRequestOptionsJSON } from '../types/signer-worker';
 import { executeWithFallbacks } from './WebAuthnFallbacks';
 // Local rpId policy helpers (moved back from WebAuthnFallbacks)
 function isRegistrableSuffix(host: string, cand: string): boolean {
@@ -29,7 +30,8 @@ export interface RegisterCredentialsArgs {
 
 export interface AuthenticateCredentialsArgs {
   nearAccountId: string,
-  challenge: VRFChallenge,
+  /** A VRF challenge (hashed into the WebAuthn challenge), or the raw challenge bytes to sign */
+  challenge: VRFChallenge | Uint8Array,
   allowCredentials: AllowCredential[],
 }
 
@@ -136,7 +138,7 @@ export class TouchIdPrompt {
   /**
    * Get authentication credentials
    * @param nearAccountId - NEAR account ID to authenticate
-   * @param challenge - VRF challenge bytes
+   * @param challenge - VRF challenge, or raw WebAuthn challenge bytes
    * @param allowCredentials - Array of allowed credentials for authentication
    * @returns WebAuthn credential with only the first PRF output
    */
@@ -146,7 +148,7 @@ export class TouchIdPrompt {
     allowCredentials,
   }: {
     nearAccountId: string
-    challenge: VRFChallenge
+    challenge: VRFChallenge | Uint8Array
     allowCredentials: AllowCredential[]
   }): Promise<WebAuthnAuthenticationCredential> {
     const credentialMaybe = (await this.getAuthenticationCredentialsInternal({
@@ -213,7 +215,7 @@ export class TouchIdPrompt {
     // Single source of truth for rpId: use getRpId().
     const rpId = this.getRpId();
     const publicKey: PublicKeyCredentialCreationOptions = {
-      challenge: outputAs32Bytes(challenge) as BufferSource,
+      challenge: await deriveWebAuthnChallenge(challenge) as BufferSource,
       rp: {
         name: 'WebAuthn VRF Passkey',
         id: rpId
@@ -234,8 +236,10 @@ export class TouchIdPrompt {
             first: generateChaCha20Salt(nearAccountId) as BufferSource,  // ChaCha20Poly1305 encryption keys
             second: generateEd25519Salt(nearAccountId) as BufferSource   // Ed25519 signing keys
           }
-        }
-      },
+        },
+        // Fallback key storage for authenticators without PRF (WorkerPolicy.prfFallbackSchemes)
+        largeBlob: { support: 'preferred' },
+      } as AuthenticationExtensionsClientInputs,
     };
     const result = await executeWithFallbacks('create', publicKey, {
       rpId,
@@ -245,6 +249,70 @@ export class TouchIdPrompt {
     return result as PublicKeyCredential;
   }
 
+  /**
+   * Creates a credential from options built by the signer worker (BuildCredentialCreationOptions),
+   * decoding only its base64url members
+   */
+  async createCredentialFromOptions(options: CredentialCreationOptionsJSON): Promise<PublicKeyCredential> {
+    const publicKey: PublicKeyCredentialCreationOptions = {
+      ...options,
+      challenge: base64UrlDecode(options.challenge) as BufferSource,
+      user: { ...options.user, id: base64UrlDecode(options.user.id) as BufferSource },
+      excludeCredentials: options.excludeCredentials.map((credential) => ({
+        ...credential,
+        id: base64UrlDecode(credential.id) as BufferSource,
+        transports: credential.transports as AuthenticatorTransport[] | undefined,
+      })),
+      extensions: {
+        ...options.extensions,
+        prf: {
+          eval: {
+            first: base64UrlDecode(options.extensions.prf.eval.first) as BufferSource,
+            second: base64UrlDecode(options.extensions.prf.eval.second) as BufferSource,
+          }
+        },
+      } as AuthenticationExtensionsClientInputs,
+    };
+    const result = await executeWithFallbacks('create', publicKey, {
+      rpId: options.rp.id,
+      inIframe: TouchIdPrompt._inIframe(),
+      timeoutMs: options.timeout,
+    });
+    return result as PublicKeyCredential;
+  }
+
+  /**
+   * Gets an assertion with options built by the signer worker (BuildCredentialRequestOptions),
+   * decoding only its base64url members
+   */
+  async getCredentialFromOptions(options: CredentialRequestOptionsJSON): Promise<PublicKeyCredential> {
+    const publicKey: PublicKeyCredentialRequestOptions = {
+      ...options,
+      challenge: base64UrlDecode(options.challenge) as BufferSource,
+      allowCredentials: options.allowCredentials?.map((credential) => ({
+        ...credential,
+        id: base64UrlDecode(credential.id) as BufferSource,
+        transports: credential.transports as AuthenticatorTransport[] | undefined,
+      })),
+      extensions: {
+        ...options.extensions,
+        prf: {
+          eval: {
+            first: base64UrlDecode(options.extensions.prf.eval.first) as BufferSource,
+            second: base64UrlDecode(options.extensions.prf.eval.second) as BufferSource,
+          }
+        },
+      } as AuthenticationExtensionsClientInputs,
+    };
+    const result = await executeWithFallbacks('get', publicKey, {
+      rpId: options.rpId,
+      inIframe: TouchIdPrompt._inIframe(),
+      timeoutMs: options.timeout,
+      permitGetBridgeOnAncestorError: this.safariGetWebauthnRegistrationFallback,
+    });
+    return result as PublicKeyCredential;
+  }
+
   /**
    * Internal method for getting WebAuthn authentication credentials with PRF output
    * @param nearAccountId - NEAR account ID to authenticate
@@ -267,7 +335,9 @@ export class TouchIdPrompt {
     // Single source of truth for rpId: use getRpId().
     const rpId = this.getRpId();
     const publicKey: PublicKeyCredentialRequestOptions = {
-      challenge: outputAs32Bytes(challenge) as BufferSource,
+      challenge: (challenge instanceof Uint8Array
+        ? challenge
+        : await deriveWebAuthnChallenge(challenge)) as BufferSource,
       rpId,
       allowCredentials: allowCredentials.map((credential) => ({
         id: base64UrlDecode(credential.id) as BufferSource,
@@ -283,8 +353,10 @@ export class TouchIdPrompt {
             first: generateChaCha20Salt(nearAccountId) as BufferSource,  // ChaCha20Poly1305 encryption keys
             second: generateEd25519Salt(nearAccountId) as BufferSource   // Ed25519 signing keys
           }
-        }
-      }
+        },
+        // Unlocks largeBlob-wrapped keys on authenticators without PRF
+        largeBlob: { read: true },
+      } as AuthenticationExtensionsClientInputs
     };
     const result = await executeWithFallbacks('get', publicKey, {
       rpId,
@@ -294,6 +366,43 @@ export class TouchIdPrompt {
     });
     return result as PublicKeyCredential;
   }
+
+  /**
+   * Stores the wrapping secret of a largeBlob-wrapped NEAR key on the authenticator.
+   * Requires one more WebAuthn ceremony with the newly registered credential.
+   * @param credentialId - base64url credential id of the registered credential
+   * @param largeBlobSecret - base64url secret from the DeriveNearKeypairAndEncrypt result
+   * The ceremony authorizes nothing server-side, so its challenge is random.
+   */
+  async writeLargeBlob({
+    credentialId,
+    largeBlobSecret,
+  }: {
+    credentialId: string;
+    largeBlobSecret: string;
+  }): Promise<void> {
+    const rpId = this.getRpId();
+    const publicKey: PublicKeyCredentialRequestOptions = {
+      challenge: crypto.getRandomValues(new Uint8Array(32)) as BufferSource,
+      rpId,
+      allowCredentials: [{ id: base64UrlDecode(credentialId) as BufferSource, type: 'public-key' }],
+      userVerification: 'preferred' as UserVerificationRequirement,
+      timeout: 60000,
+      extensions: {
+        largeBlob: { write: base64UrlDecode(largeBlobSecret) as BufferSource },
+      } as AuthenticationExtensionsClientInputs,
+    };
+    const result = await executeWithFallbacks('get', publicKey, {
+      rpId,
+      inIframe: TouchIdPrompt._inIframe(),
+      timeoutMs: publicKey.timeout as number | undefined,
+      permitGetBridgeOnAncestorError: this.safariGetWebauthnRegistrationFallback,
+    }) as PublicKeyCredential;
+    const written = (result.getClientExtensionResults() as { largeBlob?: { written?: boolean } })?.largeBlob?.written;
+    if (!written) {
+      throw new Error('The authenticator did not store the largeBlob key wrapping secret');
+    }
+  }
 }
 
 // Type guard for already-serialized authentication credential
@@ -49,6 +49,8 @@ export function buildConfigsFromEnv(overrides: Partial<PasskeyManagerConfigs> =
     relayer: {
       accountId: overrides.relayer?.accountId ?? PASSKEY_MANAGER_DEFAULT_CONFIGS.relayer.accountId,
       url: overrides.relayer?.url ?? PASSKEY_MANAGER_DEFAULT_CONFIGS.relayer.url,
+      authHeaderName: overrides.relayer?.authHeaderName,
+      responseSchemaVersion: overrides.relayer?.responseSchemaVersion,
     },
     vrfWorkerConfigs: {
       shamir3pass: {
@@ -59,7 +61,9 @@ export function buildConfigsFromEnv(overrides: Partial<PasskeyManagerConfigs> =
         applyServerLockRoute: overrides.vrfWorkerConfigs?.shamir3pass?.applyServerLockRoute
           ?? shamir3passDefaults?.applyServerLockRoute,
         relayServerUrl: overrides.vrfWorkerConfigs?.shamir3pass?.relayServerUrl
-      }
+      },
+      prefetchChallenge: overrides.vrfWorkerConfigs?.prefetchChallenge,
+      expectedWasmSha256B64u: overrides.vrfWorkerConfigs?.expectedWasmSha256B64u,
     },
     ...(overrides.iframeWallet ? { iframeWallet: overrides.iframeWallet } : {}),
   } as PasskeyManagerConfigs;
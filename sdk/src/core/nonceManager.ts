@@ -32,6 +32,9 @@ export class NonceManager {
   private reservedNonces: Set<string> = new Set();
   private lastReservedNonce: string | null = null;
 
+  // Notified of each newly fetched block (e.g. the VRF worker's challenge prefetch); survives clear()
+  private blockListeners: Set<(block: { blockHeight: string; blockHash: string }) => void> = new Set();
+
   // Freshness thresholds (ms)
   // Treat context older than 5s as stale enough to refetch
   private readonly NONCE_FRESHNESS_THRESHOLD = 5 * 1000; // 5 seconds
@@ -87,6 +90,15 @@ export class NonceManager {
     this.clearTransactionContext();
   }
 
+  /**
+   * Subscribe to the block stream: `listener` runs whenever a fetch commits a new block.
+   * Returns the unsubscribe function.
+   */
+  public onBlockUpdate(listener: (block: { blockHeight: string; blockHash: string }) => void): () => void {
+    this.blockListeners.add(listener);
+    return () => { this.blockListeners.delete(listener); };
+  }
+
   /**
    * Clear all data when user logs out
    */
@@ -190,6 +202,7 @@ export class NonceManager {
         let accessKeyInfo = this.transactionContext?.accessKeyInfo;
         let txBlockHeight = this.transactionContext?.txBlockHeight;
         let txBlockHash = this.transactionContext?.txBlockHash;
+        let gasPrice = this.transactionContext?.gasPrice;
 
         const fetchAccessKey = isNonceStale || !accessKeyInfo;
         const fetchBlock = isBlockStale || !txBlockHeight || !txBlockHash;
@@ -262,6 +275,7 @@ export class NonceManager {
           }
           txBlockHeight = String(maybeBlock.header.height);
           txBlockHash = maybeBlock.header.hash;
+          gasPrice = blockGasPrice(maybeBlock) ?? gasPrice;
           try { console.debug('[NonceManager]: fetched block', { txBlockHeight, txBlockHash }); } catch {}
         }
 
@@ -280,6 +294,8 @@ export class NonceManager {
           nextNonce,
           txBlockHeight: txBlockHeight!,
           txBlockHash: txBlockHash!,
+          accessKeyAllowance: functionCallAllowance(accessKeyInfo!),
+          gasPrice,
         };
 
         // Only commit if identity did not change AND this fetch is still the latest.
@@ -292,7 +308,11 @@ export class NonceManager {
           this.transactionContext = transactionContext;
           const now = Date.now();
           if (fetchAccessKey) this.lastNonceUpdate = now;
-          if (fetchBlock) this.lastBlockHeightUpdate = now;
+          if (fetchBlock) {
+            this.lastBlockHeightUpdate = now;
+            const block = { blockHeight: transactionContext.txBlockHeight, blockHash: transactionContext.txBlockHash };
+            this.blockListeners.forEach(listener => { try { listener(block); } catch {} });
+          }
           try { console.debug('[NonceManager]: committed context', {
             nextNonce: transactionContext.nextNonce,
             txBlockHeight: transactionContext.txBlockHeight,
@@ -480,12 +500,14 @@ export class NonceManager {
       // Update cached context with fresh access key info and computed next nonce
       if (this.transactionContext) {
         this.transactionContext.accessKeyInfo = accessKeyInfo;
+        this.transactionContext.accessKeyAllowance = functionCallAllowance(accessKeyInfo);
         this.transactionContext.nextNonce = candidateNext.toString();
       } else {
         // If no context exists (should be rare here), construct a minimal one
         this.transactionContext = {
           nearPublicKeyStr: this.nearPublicKeyStr!,
           accessKeyInfo: accessKeyInfo,
+          accessKeyAllowance: functionCallAllowance(accessKeyInfo),
           nextNonce: candidateNext.toString(),
           // Block values are unknown here; leave stale ones to be refreshed later
           txBlockHeight: '0',
@@ -608,6 +630,26 @@ function isBlockResult(x: unknown): x is BlockResult {
   return isNumber(height) && isString(hash);
 }
 
+/**
+ * Remaining allowance of a function-call access key, which the signer worker checks the
+ * transaction's estimated cost against; undefined for full access keys and unlimited allowances
+ */
+function functionCallAllowance(accessKey: AccessKeyView): string | undefined {
+  const permission: unknown = accessKey.permission;
+  if (!isObject(permission)) return undefined;
+  const functionCall = (permission as { FunctionCall?: unknown }).FunctionCall;
+  if (!isObject(functionCall)) return undefined;
+  const allowance = (functionCall as { allowance?: unknown }).allowance;
+  if (isString(allowance)) return allowance;
+  if (typeof allowance === 'bigint' || isNumber(allowance)) return allowance.toString();
+  return undefined;
+}
+
+function blockGasPrice(block: BlockResult): string | undefined {
+  const gasPrice: unknown = (block.header as { gas_price?: unknown }).gas_price;
+  return isString(gasPrice) ? gasPrice : undefined;
+}
+
 function makePlaceholderAccessKey(): AccessKeyView {
   return {
     nonce: BigInt(0),
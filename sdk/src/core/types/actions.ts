@@ -9,6 +9,96 @@ export interface TransactionInputWasm {
   receiverId: string;
   actions: ActionArgsWasm[],
   nonce?: string; // Optional - computed in confirmation flow if not provided
+  // Set by the signer worker in confirmation payloads: true if the receiver is absent from
+  // the account's recent history, null when the history is unknown. Covered by the intent digest.
+  firstTimeReceiver?: boolean | null;
+  // Set by the signer worker for chunked deploys (DeployLargeContract): the transaction's step
+  // and, for staging calls, its chunk hash. Covered by the intent digest.
+  deployStep?: DeployStep;
+  // Set by the signer worker in signing confirmation payloads: what to render for the
+  // transaction, warnings first. Covered by the intent digest, so renderers cannot omit any.
+  summaryBlocks?: ConfirmationSummaryBlock[];
+  // Set on the first transaction with summaryBlocks: one sentence describing the whole batch
+  // for screen readers, in ConfirmationConfig.locale. Covered by the intent digest.
+  summarySpeech?: string;
+  // Set by the signer worker when the request has redactPaths: the args values shown as
+  // `«redacted (sha256: …)»`, with their full hashes. Covered by the intent digest.
+  redactions?: ArgRedaction[];
+  // Set by the signer worker when args values exceed the render budget: the values shown as
+  // `{ truncated: true, preview, bytes, sha256 }`, with their full hashes. Covered by the
+  // intent digest.
+  truncations?: ArgTruncation[];
+  // Indexes of the earlier transactions of the batch this one depends on. Refused with
+  // InvalidDependencyGraph when cyclic or not earlier; shown in the confirmation (covered by
+  // the intent digest), and with broadcast a failed dependency withholds the rest of the batch.
+  dependsOn?: number[];
+  // Set by the signer worker with autoStorageDeposit: indexes of the storage_deposit actions it
+  // inserted ahead of token transfers, labelled "added automatically". Covered by the intent
+  // digest.
+  autoInsertedActions?: number[];
+}
+
+/** An args value redacted from a confirmation (sha256 over its compact JSON) */
+export interface ArgRedaction {
+  actionIndex: number;
+  path: string;
+  sha256: string;
+}
+
+/** An args value shown truncated in a confirmation (sha256 over its compact JSON) */
+export interface ArgTruncation {
+  actionIndex: number;
+  path: string;
+  bytes: number;
+  sha256: string;
+}
+
+/**
+ * Renderer-agnostic confirmation content generated by the signer worker (amounts in yoctoNEAR).
+ * `ariaLabel` is the block verbalized for screen readers in ConfirmationConfig.locale; a
+ * warning's `ariaLive` is the politeness of the live region announcing it.
+ */
+export type ConfirmationSummaryBlock = (
+  | { kind: 'heading'; text: string }
+  | { kind: 'amountRow'; label: string; yocto: string; formatted: string }
+  | { kind: 'accountRow'; label: string; accountId: string; firstTime: boolean | null }
+  | { kind: 'codeBlock'; json: string }
+  | {
+      kind: 'warning';
+      severity: 'caution' | 'danger';
+      code:
+        | 'FullAccessKey'
+        | 'DeleteAccount'
+        | 'DepositAboveThreshold'
+        | 'FirstTimeReceiver'
+        | 'LowAllowance'
+        | 'ArgsNotValidated'
+        | 'ConditionalBatch'
+        | 'StorageDepositsAdded'
+        | 'UnknownActions';
+      text: string;
+      ariaLive: 'polite' | 'assertive';
+    }
+  | { kind: 'deadlineRow'; label: string; blockHeight: number }
+  | { kind: 'keyRow'; label: string; publicKey: string; fingerprint: string }
+  | {
+      kind: 'rollup';
+      text: string;
+      hiddenActions: number;
+      receiverId: string;
+      /** Every amount the hidden actions move */
+      amounts: { actionIndex: number; label: string; yocto: string; formatted: string }[];
+    }
+) & { ariaLabel: string };
+
+/** Position of a transaction in a chunked deploy (hashes are base58 SHA-256) */
+export interface DeployStep {
+  step: number;
+  stepCount: number;
+  totalBytes: number;
+  totalHash: string;
+  /** null for the final deploy-from-staged call */
+  chunkHash: string | null;
 }
 
 /**
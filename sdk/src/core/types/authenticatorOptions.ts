@@ -47,6 +47,12 @@ export const toEnumUserVerificationPolicy = (userVerification: UserVerificationP
 export interface AuthenticatorOptions {
   userVerification: UserVerificationPolicy;
   originPolicy: OriginPolicyInput;
+  /**
+   * COSE algorithm identifiers to offer at registration, in preference order (a subset of
+   * -8 EdDSA, -7 ES256, -257 RS256; all three when omitted). A credential created with another
+   * algorithm fails with DisallowedAlgorithmNegotiated. Not sent to the contract.
+   */
+  allowedAlgorithms?: number[];
 }
 
 /**
@@ -8,11 +8,13 @@ export type Result<T, E = PasskeyErrorDetails> =
   | { success: false; error: E };
 
 /**
- * WASM Bindgen generates a `free` method on all structs.
- * This type removes the `free` method from the struct.
+ * WASM Bindgen generates a `free` method on all structs, and some structs also expose
+ * constructors/builders (`withTheme`, `validate`, `toJSON`, ...).
+ * This type removes the methods, leaving the data shape: plain objects and class instances
+ * both satisfy it.
  */
 export type StripFree<T> = T extends object
-  ? { [K in keyof T as K extends 'free' ? never : K]: StripFree<T[K]> }
+  ? { [K in keyof T as K extends 'free' ? never : T[K] extends (...args: any[]) => any ? never : K]: StripFree<T[K]> }
   : T;
 
 // Export all types
@@ -1,8 +1,17 @@
 import type { FinalExecutionOutcome, TxExecutionStatus } from "@near-js/types";
-import type { EncryptedVRFKeypair } from './vrf-worker';
+import type { EncryptedVRFKeypair, VrfScope } from './vrf-worker';
 import { AccountId } from "./accountIds";
 import { SignedTransaction } from "../NearClient";
 import type { AuthenticatorOptions } from './authenticatorOptions';
+import type {
+  AuthenticatorInfo,
+  AuthenticatorModel,
+  ConditionalBatchEntry,
+  MessageSizeLimits,
+  PrfFallbackScheme,
+  RpcOverrides,
+  TelemetryPolicy,
+} from './signer-worker';
 import { ClientUserData } from ".";
 import { RecoveryResult } from '../PasskeyManager';
 
@@ -476,6 +485,17 @@ export interface BaseHooksOptions {
 export interface RegistrationHooksOptions {
   onEvent?: EventCallback<RegistrationSSEEvent>;
   onError?: (error: Error) => void;
+  /**
+   * Run every registration check (attestation, challenge binding, origin policy, account
+   * availability, contract view check, relayer reachability) without creating the account
+   * or storing anything. The outcome is returned in RegistrationResult.dryRun.
+   */
+  dryRun?: boolean;
+  /**
+   * Retrying a registration with the same key returns the keypair and registration
+   * transaction the first attempt derived instead of deriving them again
+   */
+  idempotencyKey?: string;
   // Back-compat fields (temporarily supported)
   beforeCall?: BeforeCall;
   afterCall?: AfterCall<any>;
@@ -486,6 +506,9 @@ export interface LoginHooksOptions {
   onError?: (error: Error) => void;
   beforeCall?: BeforeCall;
   afterCall?: AfterCall<any>;
+  // What the unlocked VRF keypair may be used for (all scopes when unset). Without 'rotation'
+  // the serverEncryptedVrfKeypair refresh after login is skipped.
+  vrfScopes?: VrfScope[];
 }
 
 export interface ActionHooksOptions {
@@ -494,6 +517,11 @@ export interface ActionHooksOptions {
   waitUntil?: TxExecutionStatus;
   beforeCall?: BeforeCall;
   afterCall?: AfterCall<any>;
+  // Verification/broadcast RPC endpoints for this request (must be on configs.allowedRpcOrigins)
+  rpcOverrides?: RpcOverrides;
+  // Retries with the same key get the transactions the first attempt signed, so a broadcast
+  // retry never signs the same nonce twice
+  idempotencyKey?: string;
 }
 
 export type ExecutionWaitOption =
@@ -510,6 +538,11 @@ export interface SignAndSendTransactionHooksOptions {
   executionWait?: ExecutionWaitOption;
   beforeCall?: BeforeCall;
   afterCall?: AfterCall<any>;
+  // Verification/broadcast RPC endpoints for this request (must be on configs.allowedRpcOrigins)
+  rpcOverrides?: RpcOverrides;
+  // Retries with the same key get the transactions the first attempt signed, so a broadcast
+  // retry never signs the same nonce twice
+  idempotencyKey?: string;
 }
 
 export interface SignTransactionHooksOptions {
@@ -518,6 +551,10 @@ export interface SignTransactionHooksOptions {
   beforeCall?: BeforeCall;
   afterCall?: AfterCall<any>;
   waitUntil?: TxExecutionStatus;
+  // Verification/broadcast RPC endpoints for this request (must be on configs.allowedRpcOrigins)
+  rpcOverrides?: RpcOverrides;
+  // Retries with the same key get the transactions the first attempt signed
+  idempotencyKey?: string;
 }
 
 export interface SendTransactionHooksOptions {
@@ -526,6 +563,8 @@ export interface SendTransactionHooksOptions {
   beforeCall?: BeforeCall;
   afterCall?: AfterCall<ActionResult>;
   waitUntil?: TxExecutionStatus;
+  // Broadcast through this RPC endpoint instead of the configured client (see broadcastRpcUrl)
+  broadcastRpcUrl?: string;
 }
 
 export interface AccountRecoveryHooksOptions {
@@ -534,6 +573,8 @@ export interface AccountRecoveryHooksOptions {
   waitUntil?: TxExecutionStatus;
   beforeCall?: BeforeCall;
   afterCall?: AfterCall<RecoveryResult>;
+  /** SLIP-0010 paths of the account's sub-keys to derive again and record with its key */
+  subKeyPaths?: string[];
 }
 
 //////////////////////////////////
@@ -551,12 +592,43 @@ export interface LoginState {
 
 
 // Result Types
+/**
+ * Outcome of a dry-run registration. `outcome` names the first failing check so the UI can
+ * guide the user, or is 'WouldSucceed'.
+ */
+export interface RegistrationDryRunReport {
+  outcome:
+    | 'WouldSucceed'
+    | 'InvalidAttestation'
+    | 'DisallowedAlgorithm'
+    | 'InvalidChallenge'
+    | 'OriginNotAllowed'
+    | 'KeyDerivationFailed'
+    | 'AccountExists'
+    | 'ContractRejected'
+    | 'RpcUnavailable'
+    | 'RelayerUnavailable';
+  wouldSucceed: boolean;
+  detail?: string;
+  nearPublicKey?: string;
+  vrfPublicKey: string;
+  credentialPublicKey?: Uint8Array;
+  /** JSON verify_and_register_user args the registration would submit */
+  contractArgs?: string;
+  /** yoctoNEAR, decimal string */
+  estimatedStorageCostYocto?: string;
+}
+
 export interface RegistrationResult {
   success: boolean;
   error?: string;
+  dryRun?: RegistrationDryRunReport;
   clientNearPublicKey?: string | null;
   nearAccountId?: AccountId;
   transactionId?: string | null;
+  // The authenticator model the passkey was created on (e.g. "iCloud Keychain", synced), from
+  // the attestation's AAGUID; recovery guidance differs for synced and device-bound passkeys
+  authenticator?: AuthenticatorInfo;
   vrfRegistration?: {
     success: boolean;
     vrfPublicKey?: string;
@@ -585,6 +657,14 @@ export interface VerifyAndSignTransactionResult {
   signedTransaction: SignedTransaction;
   nearAccountId: string;
   logs?: string[];
+  // rpcOverrides.broadcast as allowed by the signer worker
+  broadcastRpcUrl?: string;
+  // The request's validUntilBlockHeight: do not broadcast once the chain is past it
+  validUntilBlockHeight?: number;
+  // With deduplicate: indexes of the requested transactions dropped as duplicates
+  removedDuplicateIndexes?: number[];
+  // With conditionalBatch: each transaction signed or skipped, with its simulation
+  conditionalEntries?: ConditionalBatchEntry[];
 }
 
 export interface GetRecentLoginsResult {
@@ -627,10 +707,54 @@ export interface PasskeyManagerConfigs {
   // Relay Server is used to create new NEAR accounts
   relayer: {
     accountId: string;
-    url: string
+    url: string;
+    // Header carrying the auth token of worker submissions (submitToRelayer), e.g. 'Authorization'
+    authHeaderName?: string;
+    // Schema version the relayer's responses declare (default 1)
+    responseSchemaVersion?: number;
   }
   // authenticator options for registrations
   authenticatorOptions?: AuthenticatorOptions;
+  // Origins that per-request rpcOverrides may point at (e.g. an integrator's verification proxy).
+  // Overrides are refused when unset.
+  allowedRpcOrigins?: string[];
+  // Origins (https only) that buildRedirectPayload may send users back to with a signed
+  // sign-in payload. Every callback URL is refused when unset.
+  allowedRedirectOrigins?: string[];
+  // Longest lifetime of signing intents (createSigningIntent), enforced by the signer worker
+  // when intents are created and executed. Defaults to 1 hour; at most 24 hours.
+  maxSigningIntentTtlMs?: number;
+  // How long a signer session may decrypt and sign before a fresh passkey assertion. Past it,
+  // requests that do not prompt fail with errorCode 'SessionExpired' and signing intents and
+  // approval tokens issued earlier are refused; the next prompted request starts a new session.
+  // The VRF worker applies it from unlock: past it, it issues no challenges and exports or
+  // restores no session snapshots until the next login.
+  // Sessions do not expire when unset.
+  maxSessionDurationMs?: number;
+  // Total deposit per transaction (yoctoNEAR string) above which signing confirmations carry a
+  // DepositAboveThreshold warning. Defaults to 10 NEAR.
+  depositEscalationYocto?: string;
+  // Allowance (yoctoNEAR string) a function-call signing key may have left after a confirmation's
+  // estimated cost before the confirmation carries a LowAllowance warning. Defaults to 0.05 NEAR.
+  lowAllowanceWarningYocto?: string;
+  // Key wrapping for authenticators without the PRF extension, in order of preference:
+  // 'largeBlob' stores the wrapping secret on the authenticator, 'passphrase' derives it from a
+  // user passphrase (Argon2id). Registrations without PRF are refused when unset.
+  prfFallbackSchemes?: PrfFallbackScheme[];
+  // Opt-in telemetry: signer workers POST sampled per-handler counters and latency histograms
+  // (no account IDs, receivers or amounts) to telemetry.endpoint, which must be on
+  // allowedRpcOrigins. Nothing is sent when unset.
+  telemetry?: TelemetryPolicy;
+  // Largest message frames signer workers parse. Defaults to 1 MiB, 8 MiB for chunked contract
+  // deploys; larger requests fail with errorCode 'MessageTooLarge'.
+  messageSizeLimits?: MessageSizeLimits;
+  // Origin of the wallet iframe that renders confirmations (usually iframeWallet.walletOrigin).
+  // Signer workers then refuse decisions that do not come from a document at this origin with
+  // errorCode 'UntrustedConfirmationOrigin'. Unchecked when unset.
+  trustedConfirmationOrigin?: string;
+  // AAGUIDs to name in registration results and parsed attestations, added to the built-in
+  // table of well-known authenticators (replacing its entries with the same AAGUID)
+  knownAuthenticators?: AuthenticatorModel[];
   // Shamir 3-pass configuration (optional, used for auto-unlocking VRF keypairs)
   vrfWorkerConfigs?: {
     shamir3pass?: {
@@ -639,6 +763,18 @@ export interface PasskeyManagerConfigs {
       applyServerLockRoute?: string; // Apply server lock route
       removeServerLockRoute?: string; // Remove server lock route
     }
+    // Keep a VRF challenge proven for the latest block while unlocked, so signing prompts
+    // skip the proof. Fed by NonceManager's block polling.
+    prefetchChallenge?: boolean;
+    // Lock the VRF session this long after unlock. Counted in wall-clock time, so a tab frozen
+    // in the background (pagehide/pageshow) comes back locked once the time is up.
+    autoLockAfterMs?: number;
+    // 32-byte key (base64url) shared with the integrator's backend over its own channel. VRF
+    // challenges then carry an HMAC receipt (issuanceReceiptB64u) the backend can verify.
+    issuanceReceiptKeyB64u?: string;
+    // Attestation hash (base64url SHA-256) of the VRF worker wasm from the SDK release manifest.
+    // The VRF worker is refused when the served wasm hashes differently.
+    expectedWasmSha256B64u?: string;
   }
 }
 
@@ -44,6 +44,10 @@ export interface TransactionContext {
   nextNonce: string;
   txBlockHeight: string;
   txBlockHash: string;
+  /** Remaining allowance (yoctoNEAR) of a function-call access key; unset for full access keys and unlimited allowances */
+  accessKeyAllowance?: string;
+  /** Gas price (yoctoNEAR per gas unit) from the block header, for the worker's allowance estimate */
+  gasPrice?: string;
 }
 
 export interface BlockInfo {
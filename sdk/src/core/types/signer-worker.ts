@@ -8,11 +8,20 @@ export { WorkerRequestType, WorkerResponseType }; // Export the WASM enums direc
 
 import { StripFree } from "./index.js";
 import type { onProgressEvents } from "./passkeyManager.js";
+import type { ActionArgsWasm, ConfirmationSummaryBlock, TransactionInputWasm } from "./actions.js";
+import type { TransactionContext } from "./rpc.js";
+import type { VRFChallenge, VrfKeypairBlobMeta } from "./vrf-worker.js";
+import type { WebAuthnAuthenticationCredential } from "./webauthn.js";
 
 export type WasmTransaction = wasmModule.WasmTransaction;
 export type WasmSignature = wasmModule.WasmSignature;
-export type TransactionPayload = StripFree<wasmModule.TransactionPayload>;
+export type TransactionPayload = StripFree<wasmModule.TransactionPayload> & {
+  /** Indexes of the earlier transactions of the batch this one depends on (SignTransactionsWithActions) */
+  dependsOn?: number[];
+};
 export type RpcCallPayload = StripFree<wasmModule.RpcCallPayload>;
+/** Per-request verification/broadcast RPC endpoints, pinned to WorkerPolicy.allowedRpcOrigins */
+export type RpcOverrides = StripFree<wasmModule.RpcOverrides>;
 /**
  * RPC call parameters for NEAR operations and VRF generation
  * Used to pass essential parameters for background operations
@@ -27,22 +36,230 @@ export type WasmDeriveNearKeypairAndEncryptRequest = StripFree<wasmModule.Derive
 export type WasmRecoverKeypairRequest = StripFree<wasmModule.RecoverKeypairRequest>;
 export type WasmCheckCanRegisterUserRequest = StripFree<wasmModule.CheckCanRegisterUserRequest>;
 // Override the WASM request type to accept string literals for confirmation config
-export type WasmSignTransactionsWithActionsRequest = Omit<StripFree<wasmModule.SignTransactionsWithActionsRequest>, 'confirmationConfig'> & {
+// (or a wasmModule.ConfirmationConfig instance; it is posted in its toJSON() form)
+/** An encrypted key blob the worker may sign with, tagged with its "ed25519:<base58>" public key */
+export interface KeyBlobCandidate {
+  publicKey: string;
+  encryptedPrivateKeyData: string;
+  encryptedPrivateKeyIv: string;
+}
+export type WasmSignTransactionsWithActionsRequest = Omit<StripFree<wasmModule.SignTransactionsWithActionsRequest>, 'confirmationConfig' | 'workerPolicy' | 'decryption'> & {
+  decryption: StripFree<wasmModule.DecryptionPayload> & { candidates?: KeyBlobCandidate[] };
   confirmationConfig?: {
     uiMode: ConfirmationUIMode;
     behavior: ConfirmationBehavior;
     autoProceedDelay?: number;
     theme?: 'dark' | 'light';
-  };
+    confirmationTimeoutMs?: number;
+    locale?: string;
+  } | wasmModule.ConfirmationConfig;
+  workerPolicy?: WorkerPolicy;
+  /** Last block height the batch may be broadcast at (shown in the confirmation) */
+  validUntilBlockHeight?: number;
+  /** Nonce manager context at request time; with accessKeyAllowance set, the worker checks the batch's estimated cost against it */
+  transactionContext?: Omit<TransactionContext, 'accessKeyInfo'>;
+  /** Signs with the candidate blob tagged with this key, once its on-chain permission covers the batch */
+  signingPublicKey?: string;
+  /** Drop transactions identical to an earlier one of the batch (reported as removedDuplicateIndexes) */
+  deduplicate?: boolean;
+  /** SummarizeTransactions' previewDigest for the batch shown to the user; a changed batch fails with PayloadChangedSincePreview */
+  previewDigest?: string;
+  /** FunctionCall args values ("args.email") shown as their SHA-256 in the confirmation and audit log; receiverId, methodName, deposit and gas fail with CannotRedactCriticalField */
+  redactPaths?: string[];
+  /** Simulate the batch in order before signing; a transaction is signed only if every earlier one simulated successfully. Without an RPC able to simulate the batch fails with SimulationUnavailable, unless fallbackToUnconditional */
+  conditionalBatch?: ConditionalBatchOptions;
+  /** Under WorkerPolicy.requiredApprovals 2: the first approval's token, sent with the second approver's request */
+  approvalToken?: string;
+  /** Insert a storage_deposit ahead of ft_transfer(_call)s to receivers the token contract has not registered; shown in the confirmation as added automatically (a skip uiMode becomes modal) */
+  autoStorageDeposit?: boolean;
+};
+export type WasmDecryptPrivateKeyRequest = StripFree<wasmModule.DecryptPrivateKeyRequest> & {
+  /** The assertion the PRF output came from, made for a challenge from IssueElevationChallenge (its base64url-decoded bytes) */
+  credential?: WebAuthnAuthenticationCredential;
 };
-export type WasmDecryptPrivateKeyRequest = StripFree<wasmModule.DecryptPrivateKeyRequest>;
 export type WasmExtractCosePublicKeyRequest = StripFree<wasmModule.ExtractCoseRequest>;
-export type WasmSignNep413MessageRequest = StripFree<wasmModule.SignNep413Request>;
+export type WasmSignNep413MessageRequest = Omit<StripFree<wasmModule.SignNep413Request>, 'message' | 'recipient' | 'nonce' | 'prfOutput'> & {
+  /** NEP-413 fields, required unless `chain` signs for another chain */
+  message?: string;
+  recipient?: string;
+  nonce?: string;
+  prfOutput?: string;
+  /** Defaults to NEAR; EVM messages are confirmed (and their PRF output collected) by the worker */
+  chain?: SigningChain;
+  confirmationConfig?: ConfirmationConfig;
+};
+/** Chain a message is signed for; evmPersonalSign messages are 0x-prefixed hex or text */
+export type SigningChain = { kind: 'near' } | { kind: 'evmPersonalSign'; message: string };
+/** EIP-191 personal_sign signature: r, s (0x hex), v (27 | 28), 0x `r || s || v` and the signer's address */
+export interface EvmSignature {
+  r: string;
+  s: string;
+  v: number;
+  signature: string;
+  address: string;
+}
+export type WasmSignNep413MessageResult = wasmModule.SignNep413Result & { evm?: EvmSignature };
 export type WasmSignTransactionWithKeyPairRequest = StripFree<wasmModule.SignTransactionWithKeyPairRequest>;
 export type WasmRegistrationCredentialConfirmationRequest = Omit<StripFree<wasmModule.RegistrationCredentialConfirmationRequest>, 'confirmationConfig'> & {
-  confirmationConfig?: ConfirmationConfig;
+  confirmationConfig?: ConfirmationConfig | wasmModule.ConfirmationConfig;
 };
 export type WasmExportNearKeypairUiRequest = StripFree<wasmModule.ExportNearKeypairUiRequest>;
+export type WasmValidateEncryptedBlobsRequest = StripFree<wasmModule.ValidateEncryptedBlobsRequest>;
+export type WasmCreateSessionKeyRequest = StripFree<wasmModule.CreateSessionKeyRequest>;
+export type WasmSignWithSessionKeyRequest = StripFree<wasmModule.SignWithSessionKeyRequest>;
+export type WasmRevokeSessionKeyRequest = StripFree<wasmModule.RevokeSessionKeyRequest>;
+/** CreateSessionKey result: the public key, its expiry and the unsigned AddKey action */
+export type SessionKeyCreated = StripFree<wasmModule.CreateSessionKeyResult>;
+/** RevokeSessionKey result: whether the worker held the key, and the unsigned DeleteKey action */
+export type SessionKeyRevoked = StripFree<wasmModule.RevokeSessionKeyResult>;
+export type WasmComposeMultisigRequest = StripFree<wasmModule.ComposeMultisigRequest>;
+export type WasmComposeCreateSubaccountRequest = StripFree<wasmModule.ComposeCreateSubaccountRequest>;
+export type WasmStartBackgroundRefreshRequest = StripFree<wasmModule.StartBackgroundRefreshRequest>;
+export type WasmStopBackgroundRefreshRequest = StripFree<wasmModule.StopBackgroundRefreshRequest>;
+export type WasmBackgroundRefreshStatusRequest = StripFree<wasmModule.BackgroundRefreshStatusRequest>;
+export type WasmSuspendHintRequest = StripFree<wasmModule.SuspendHintRequest>;
+export type WasmResumeHintRequest = StripFree<wasmModule.ResumeHintRequest>;
+
+/**
+ * Requests for a signer worker the integrator keeps alive itself, calling the wasm module's
+ * handle_signer_message for every request: the background refresh loop and its suspend hints
+ * only live in that worker's memory. The bundled web3authn-signer.worker closes after one
+ * request, so SignerWorkerManager refuses these.
+ */
+export const STANDALONE_WORKER_REQUEST_TYPES = [
+  WorkerRequestType.StartBackgroundRefresh,
+  WorkerRequestType.StopBackgroundRefresh,
+  WorkerRequestType.BackgroundRefreshStatus,
+  WorkerRequestType.SuspendHint,
+  WorkerRequestType.ResumeHint,
+] as const;
+export type StandaloneWorkerRequestType = typeof STANDALONE_WORKER_REQUEST_TYPES[number];
+/** Requests SignerWorkerManager sends, each to a worker of its own */
+export type OneShotWorkerRequestType = Exclude<WorkerRequestType, StandaloneWorkerRequestType>;
+
+export function isStandaloneWorkerRequest(type: WorkerRequestType): type is StandaloneWorkerRequestType {
+  return (STANDALONE_WORKER_REQUEST_TYPES as readonly WorkerRequestType[]).includes(type);
+}
+export type WasmDeriveSubKeyRequest = StripFree<wasmModule.DeriveSubKeyRequest>;
+/** A derived sub-key as the worker returns it (plain JSON, not the wasm-bindgen class) */
+export type SubKeyEntry = { path: string; publicKey: string };
+/** The block a view call reads: a finality (optimistic by default) or a block height or hash */
+export type ViewBlockReference =
+  | { finality: 'optimistic' | 'near-final' | 'final' }
+  | { blockId: number | string };
+export type WasmViewCallRequest = StripFree<wasmModule.ViewCallRequest> & {
+  blockReference?: ViewBlockReference;
+};
+/** The returned bytes parsed as JSON (null when they are not JSON) next to their base64 */
+export type ViewCallResult = StripFree<wasmModule.ViewCallResult> & { resultJson: unknown };
+export type WasmDumpDiagnosticsRequest = StripFree<wasmModule.DumpDiagnosticsRequest>;
+/** Same payload as SignTransactionsWithActions; needs a connected VRF peer port */
+export type WasmSignTransactionsWithFreshChallengeRequest = WasmSignTransactionsWithActionsRequest;
+export type WasmGetSessionStatusRequest = StripFree<wasmModule.GetSessionStatusRequest>;
+export type WasmBuildRedirectPayloadRequest = StripFree<wasmModule.BuildRedirectPayloadRequest>;
+export type WasmIssueElevationChallengeRequest = StripFree<wasmModule.IssueElevationChallengeRequest>;
+export type WasmGetMemoryStatsRequest = StripFree<wasmModule.GetMemoryStatsRequest>;
+export type WasmTrimCachesRequest = StripFree<wasmModule.TrimCachesRequest>;
+export type WasmGetTelemetrySnapshotRequest = StripFree<wasmModule.GetTelemetrySnapshotRequest>;
+export type WasmGetKeyUsageStatsRequest = StripFree<wasmModule.GetKeyUsageStatsRequest>;
+export type WasmGetWorkerInfoRequest = StripFree<wasmModule.GetWorkerInfoRequest>;
+export type WasmValidateDecryptionCapabilityRequest = StripFree<wasmModule.ValidateDecryptionCapabilityRequest>;
+export type WasmListActiveAccountsRequest = StripFree<wasmModule.ListActiveAccountsRequest>;
+export type WasmWipeAccountStateRequest = StripFree<wasmModule.WipeAccountStateRequest>;
+export type WasmValidateArgsSchemasRequest = StripFree<wasmModule.ValidateArgsSchemasRequest> & {
+  argsSchemas: ArgsSchema[];
+};
+export type WasmSummarizeTransactionsRequest = StripFree<wasmModule.SummarizeTransactionsRequest> & {
+  deduplicate?: boolean;
+};
+export type WasmMigrateEncryptedBlobsRequest = StripFree<wasmModule.MigrateEncryptedBlobsRequest> & {
+  blobs: StoredEncryptedBlob[];
+};
+export type WasmBuildCredentialCreationOptionsRequest = Omit<StripFree<wasmModule.BuildCredentialCreationOptionsRequest>, 'registration' | 'vrfChallenge' | 'rpName' | 'userName' | 'userDisplayName' | 'rpcCall'> & {
+  registration: {
+    nearAccountId: string;
+    nonce: string;
+    blockHash: string;
+    deviceNumber?: number;
+    /** Serde (snake_case) form of AuthenticatorOptions */
+    authenticatorOptions?: { user_verification?: string; origin_policy?: unknown };
+  };
+  vrfChallenge: {
+    vrfInput: string;
+    vrfOutput: string;
+    vrfProof: string;
+    vrfPublicKey: string;
+    userId: string;
+    rpId: string;
+    blockHeight: string;
+    blockHash: string;
+  };
+  rpName?: string;
+  userName?: string;
+  userDisplayName?: string;
+  /** The account's credentials to exclude; fetched from the contract with rpcCall when absent */
+  existingCredentials?: { credentialId: string; transports?: string[] }[];
+  rpcCall?: RpcCallPayload;
+};
+export type WasmBuildCredentialRequestOptionsRequest = Omit<StripFree<wasmModule.BuildCredentialRequestOptionsRequest>, 'vrfChallenge' | 'deviceNumber' | 'userVerification' | 'discoverable' | 'rpcCall'> & {
+  vrfChallenge: WasmBuildCredentialCreationOptionsRequest['vrfChallenge'];
+  /** The account's authenticators; fetched from the contract with rpcCall when absent */
+  authenticators?: KnownAuthenticator[];
+  /** Only allow the credentials of this device */
+  deviceNumber?: number;
+  userVerification?: 'required' | 'preferred' | 'discouraged';
+  /** Omit allowCredentials; defaults to true when every authenticator reported rk=true and no device is selected */
+  discoverable?: boolean;
+  rpcCall?: RpcCallPayload;
+};
+export type WasmRecoverPendingOperationsRequest = StripFree<wasmModule.RecoverPendingOperationsRequest> & {
+  /** Entries persisted from OPERATION_JOURNAL_ENTRY messages */
+  entries: JournalEntry[];
+};
+export type WasmGetRecentReceiversRequest = Omit<StripFree<wasmModule.GetRecentReceiversRequest>, 'workerPolicy'> & {
+  workerPolicy?: WorkerPolicy;
+};
+export type WasmBuildAccountDescriptorRequest = StripFree<wasmModule.BuildAccountDescriptorRequest>;
+/** Staging contract methods for a chunked deploy (stage `{ index, data, hash }`, then deploy) */
+export type StagingContractInterface = StripFree<wasmModule.StagingContractInterface>;
+export type WasmDeployLargeContractRequest = Omit<StripFree<wasmModule.DeployLargeContractRequest>, 'code' | 'staging' | 'confirmationConfig' | 'workerPolicy'> & {
+  /** Contract wasm bytes (as for DeployContract actions) */
+  code: number[];
+  staging: StagingContractInterface;
+  confirmationConfig?: WasmSignTransactionsWithActionsRequest['confirmationConfig'];
+  workerPolicy?: WorkerPolicy;
+};
+export type WasmRunSelfTestRequest = StripFree<wasmModule.RunSelfTestRequest>;
+export type WasmExportAccountBundleRequest = StripFree<wasmModule.ExportAccountBundleRequest> & {
+  records: AccountBundleRecords;
+};
+export type WasmImportAccountBundleRequest = Omit<StripFree<wasmModule.ImportAccountBundleRequest>, 'bundle'> & {
+  /** Bundle file bytes, as returned by ExportAccountBundle */
+  bundle: number[];
+};
+export type WasmCreateSigningIntentRequest = Omit<StripFree<wasmModule.CreateSigningIntentRequest>, 'confirmationConfig' | 'workerPolicy'> & {
+  confirmationConfig?: WasmSignTransactionsWithActionsRequest['confirmationConfig'];
+  workerPolicy?: WorkerPolicy;
+};
+export type WasmExecuteSigningIntentRequest = Omit<StripFree<wasmModule.ExecuteSigningIntentRequest>, 'workerPolicy'> & {
+  workerPolicy?: WorkerPolicy;
+};
+export type WasmSubmitToRelayerRequest = Omit<StripFree<wasmModule.SubmitToRelayerRequest>, 'workerPolicy'> & {
+  workerPolicy?: WorkerPolicy;
+};
+export type WasmPrepareRegistrationRequest = StripFree<wasmModule.PrepareRegistrationRequest>;
+export type WasmCompleteRegistrationRequest = Omit<StripFree<wasmModule.CompleteRegistrationRequest>, 'workerPolicy'> & {
+  workerPolicy?: WorkerPolicy;
+};
+export type WasmCreateRemoteConfirmationRequest = Omit<StripFree<wasmModule.CreateRemoteConfirmationRequest>, 'workerPolicy'> & {
+  workerPolicy?: WorkerPolicy;
+};
+export type WasmApproveRemoteConfirmationRequest = Omit<StripFree<wasmModule.ApproveRemoteConfirmationRequest>, 'confirmationConfig'> & {
+  confirmationConfig?: WasmSignTransactionsWithActionsRequest['confirmationConfig'];
+};
+export type WasmCompleteRemoteConfirmationRequest = Omit<StripFree<wasmModule.CompleteRemoteConfirmationRequest>, 'workerPolicy'> & {
+  workerPolicy?: WorkerPolicy;
+};
 
 export type WasmRequestPayload = WasmDeriveNearKeypairAndEncryptRequest
   | WasmRecoverKeypairRequest
@@ -53,15 +270,66 @@ export type WasmRequestPayload = WasmDeriveNearKeypairAndEncryptRequest
   | WasmSignNep413MessageRequest
   | WasmSignTransactionWithKeyPairRequest
   | WasmRegistrationCredentialConfirmationRequest
-  | WasmExportNearKeypairUiRequest;
+  | WasmExportNearKeypairUiRequest
+  | WasmValidateEncryptedBlobsRequest
+  | WasmCreateSessionKeyRequest
+  | WasmSignWithSessionKeyRequest
+  | WasmRevokeSessionKeyRequest
+  | WasmComposeMultisigRequest
+  | WasmGetMemoryStatsRequest
+  | WasmTrimCachesRequest
+  | WasmGetRecentReceiversRequest
+  | WasmBuildAccountDescriptorRequest
+  | WasmDeployLargeContractRequest
+  | WasmRunSelfTestRequest
+  | WasmExportAccountBundleRequest
+  | WasmImportAccountBundleRequest
+  | WasmCreateSigningIntentRequest
+  | WasmExecuteSigningIntentRequest
+  | WasmSubmitToRelayerRequest
+  | WasmGetTelemetrySnapshotRequest
+  | WasmGetKeyUsageStatsRequest
+  | WasmPrepareRegistrationRequest
+  | WasmCompleteRegistrationRequest
+  | WasmCreateRemoteConfirmationRequest
+  | WasmApproveRemoteConfirmationRequest
+  | WasmCompleteRemoteConfirmationRequest
+  | WasmGetWorkerInfoRequest
+  | WasmValidateDecryptionCapabilityRequest
+  | WasmListActiveAccountsRequest
+  | WasmWipeAccountStateRequest
+  | WasmValidateArgsSchemasRequest
+  | WasmSummarizeTransactionsRequest
+  | WasmMigrateEncryptedBlobsRequest
+  | WasmBuildCredentialCreationOptionsRequest
+  | WasmBuildCredentialRequestOptionsRequest
+  | WasmRecoverPendingOperationsRequest
+  | WasmComposeCreateSubaccountRequest
+  | WasmStartBackgroundRefreshRequest
+  | WasmStopBackgroundRefreshRequest
+  | WasmBackgroundRefreshStatusRequest
+  | WasmSuspendHintRequest
+  | WasmResumeHintRequest
+  | WasmDeriveSubKeyRequest
+  | WasmViewCallRequest
+  | WasmDumpDiagnosticsRequest
+  | WasmSignTransactionsWithFreshChallengeRequest
+  | WasmGetSessionStatusRequest
+  | WasmBuildRedirectPayloadRequest
+  | WasmIssueElevationChallengeRequest;
 
 // WASM Worker Response Types
 export type WasmRecoverKeypairResult = InstanceType<typeof wasmModule.RecoverKeypairResult>;
 export type WasmRegistrationCheckResult = InstanceType<typeof wasmModule.RegistrationCheckResult>;
 export type WasmSignedTransaction = InstanceType<typeof wasmModule.WasmSignedTransaction>;
 export type WasmTransactionSignResult = InstanceType<typeof wasmModule.TransactionSignResult>;
+/** A TransactionSignResult with the phase it failed in ('challenge', 'confirmation', ...; null on success) */
+export type SignTransactionsWithFreshChallengeResult = WasmTransactionSignResult & { failedPhase: string | null };
 export type WasmDecryptPrivateKeyResult = InstanceType<typeof wasmModule.DecryptPrivateKeyResult>;
-export type WasmDeriveNearKeypairAndEncryptResult = InstanceType<typeof wasmModule.DeriveNearKeypairAndEncryptResult>;
+export type WasmDeriveNearKeypairAndEncryptResult = InstanceType<typeof wasmModule.DeriveNearKeypairAndEncryptResult> & {
+  /** The authenticator model named by the attestation's AAGUID */
+  authenticator?: AuthenticatorInfo | null;
+};
 // wasm-bindgen generates some classes with private constructors, which breaks
 // `InstanceType<typeof Class>`. Use the class name directly for the instance type.
 export type WasmRegistrationCredentialConfirmationResult = wasmModule.RegistrationCredentialConfirmationResult;
@@ -115,7 +383,7 @@ export interface WorkerRequestTypeMap {
   [WorkerRequestType.SignNep413Message]: {
     type: WorkerRequestType.SignNep413Message;
     request: WasmSignNep413MessageRequest;
-    result: wasmModule.SignNep413Result;
+    result: WasmSignNep413MessageResult;
   };
   [WorkerRequestType.RegistrationCredentialConfirmation]: {
     type: WorkerRequestType.RegistrationCredentialConfirmation;
@@ -127,6 +395,236 @@ export interface WorkerRequestTypeMap {
     request: WasmExportNearKeypairUiRequest;
     result: WasmExportNearKeypairUiResult;
   };
+  [WorkerRequestType.ValidateEncryptedBlobs]: {
+    type: WorkerRequestType.ValidateEncryptedBlobs;
+    request: WasmValidateEncryptedBlobsRequest;
+    result: wasmModule.ValidateEncryptedBlobsResult;
+  };
+  [WorkerRequestType.CreateSessionKey]: {
+    type: WorkerRequestType.CreateSessionKey;
+    request: WasmCreateSessionKeyRequest;
+    result: wasmModule.CreateSessionKeyResult;
+  };
+  [WorkerRequestType.SignWithSessionKey]: {
+    type: WorkerRequestType.SignWithSessionKey;
+    request: WasmSignWithSessionKeyRequest;
+    result: WasmTransactionSignResult;
+  };
+  [WorkerRequestType.RevokeSessionKey]: {
+    type: WorkerRequestType.RevokeSessionKey;
+    request: WasmRevokeSessionKeyRequest;
+    result: wasmModule.RevokeSessionKeyResult;
+  };
+  [WorkerRequestType.ComposeMultisigRequest]: {
+    type: WorkerRequestType.ComposeMultisigRequest;
+    request: WasmComposeMultisigRequest;
+    result: wasmModule.ComposeMultisigResult;
+  };
+  [WorkerRequestType.GetMemoryStats]: {
+    type: WorkerRequestType.GetMemoryStats;
+    request: WasmGetMemoryStatsRequest;
+    result: wasmModule.MemoryStats;
+  };
+  [WorkerRequestType.TrimCaches]: {
+    type: WorkerRequestType.TrimCaches;
+    request: WasmTrimCachesRequest;
+    result: wasmModule.TrimCachesResult;
+  };
+  [WorkerRequestType.GetRecentReceivers]: {
+    type: WorkerRequestType.GetRecentReceivers;
+    request: WasmGetRecentReceiversRequest;
+    result: GetRecentReceiversResult;
+  };
+  [WorkerRequestType.BuildAccountDescriptor]: {
+    type: WorkerRequestType.BuildAccountDescriptor;
+    request: WasmBuildAccountDescriptorRequest;
+    result: BuildAccountDescriptorResult;
+  };
+  [WorkerRequestType.DeployLargeContract]: {
+    type: WorkerRequestType.DeployLargeContract;
+    request: WasmDeployLargeContractRequest;
+    result: DeployLargeContractResult;
+  };
+  [WorkerRequestType.RunSelfTest]: {
+    type: WorkerRequestType.RunSelfTest;
+    request: WasmRunSelfTestRequest;
+    result: SelfTestReport;
+  };
+  [WorkerRequestType.ExportAccountBundle]: {
+    type: WorkerRequestType.ExportAccountBundle;
+    request: WasmExportAccountBundleRequest;
+    result: ExportAccountBundleResult;
+  };
+  [WorkerRequestType.ImportAccountBundle]: {
+    type: WorkerRequestType.ImportAccountBundle;
+    request: WasmImportAccountBundleRequest;
+    result: ImportAccountBundleResult;
+  };
+  [WorkerRequestType.CreateSigningIntent]: {
+    type: WorkerRequestType.CreateSigningIntent;
+    request: WasmCreateSigningIntentRequest;
+    result: wasmModule.CreateSigningIntentResult;
+  };
+  [WorkerRequestType.ExecuteSigningIntent]: {
+    type: WorkerRequestType.ExecuteSigningIntent;
+    request: WasmExecuteSigningIntentRequest;
+    result: WasmTransactionSignResult;
+  };
+  [WorkerRequestType.SubmitToRelayer]: {
+    type: WorkerRequestType.SubmitToRelayer;
+    request: WasmSubmitToRelayerRequest;
+    result: RelayerResult;
+  };
+  [WorkerRequestType.GetTelemetrySnapshot]: {
+    type: WorkerRequestType.GetTelemetrySnapshot;
+    request: WasmGetTelemetrySnapshotRequest;
+    result: wasmModule.TelemetrySnapshot;
+  };
+  [WorkerRequestType.GetKeyUsageStats]: {
+    type: WorkerRequestType.GetKeyUsageStats;
+    request: WasmGetKeyUsageStatsRequest;
+    result: KeyUsageStats;
+  };
+  [WorkerRequestType.PrepareRegistration]: {
+    type: WorkerRequestType.PrepareRegistration;
+    request: WasmPrepareRegistrationRequest;
+    result: PrepareRegistrationResult;
+  };
+  [WorkerRequestType.CompleteRegistration]: {
+    type: WorkerRequestType.CompleteRegistration;
+    request: WasmCompleteRegistrationRequest;
+    result: RelayerResult;
+  };
+  [WorkerRequestType.CreateRemoteConfirmation]: {
+    type: WorkerRequestType.CreateRemoteConfirmation;
+    request: WasmCreateRemoteConfirmationRequest;
+    result: RemoteConfirmationRequest;
+  };
+  [WorkerRequestType.ApproveRemoteConfirmation]: {
+    type: WorkerRequestType.ApproveRemoteConfirmation;
+    request: WasmApproveRemoteConfirmationRequest;
+    result: wasmModule.ApproveRemoteConfirmationResult;
+  };
+  [WorkerRequestType.CompleteRemoteConfirmation]: {
+    type: WorkerRequestType.CompleteRemoteConfirmation;
+    request: WasmCompleteRemoteConfirmationRequest;
+    result: WasmTransactionSignResult;
+  };
+  [WorkerRequestType.GetWorkerInfo]: {
+    type: WorkerRequestType.GetWorkerInfo;
+    request: WasmGetWorkerInfoRequest;
+    result: WorkerInfo;
+  };
+  [WorkerRequestType.ValidateDecryptionCapability]: {
+    type: WorkerRequestType.ValidateDecryptionCapability;
+    request: WasmValidateDecryptionCapabilityRequest;
+    result: DecryptionCapability;
+  };
+  [WorkerRequestType.ListActiveAccounts]: {
+    type: WorkerRequestType.ListActiveAccounts;
+    request: WasmListActiveAccountsRequest;
+    result: ActiveAccounts;
+  };
+  [WorkerRequestType.WipeAccountState]: {
+    type: WorkerRequestType.WipeAccountState;
+    request: WasmWipeAccountStateRequest;
+    result: AccountWipeSummary;
+  };
+  [WorkerRequestType.ValidateArgsSchemas]: {
+    type: WorkerRequestType.ValidateArgsSchemas;
+    request: WasmValidateArgsSchemasRequest;
+    result: ArgsSchemaValidation;
+  };
+  [WorkerRequestType.SummarizeTransactions]: {
+    type: WorkerRequestType.SummarizeTransactions;
+    request: WasmSummarizeTransactionsRequest;
+    result: TransactionsSummary;
+  };
+  [WorkerRequestType.MigrateEncryptedBlobs]: {
+    type: WorkerRequestType.MigrateEncryptedBlobs;
+    request: WasmMigrateEncryptedBlobsRequest;
+    result: MigrateEncryptedBlobsResult;
+  };
+  [WorkerRequestType.BuildCredentialCreationOptions]: {
+    type: WorkerRequestType.BuildCredentialCreationOptions;
+    request: WasmBuildCredentialCreationOptionsRequest;
+    result: BuildCredentialCreationOptionsResult;
+  };
+  [WorkerRequestType.BuildCredentialRequestOptions]: {
+    type: WorkerRequestType.BuildCredentialRequestOptions;
+    request: WasmBuildCredentialRequestOptionsRequest;
+    result: BuildCredentialRequestOptionsResult;
+  };
+  [WorkerRequestType.RecoverPendingOperations]: {
+    type: WorkerRequestType.RecoverPendingOperations;
+    request: WasmRecoverPendingOperationsRequest;
+    result: RecoverPendingOperationsResult;
+  };
+  [WorkerRequestType.ComposeCreateSubaccount]: {
+    type: WorkerRequestType.ComposeCreateSubaccount;
+    request: WasmComposeCreateSubaccountRequest;
+    result: wasmModule.ComposeCreateSubaccountResult;
+  };
+  [WorkerRequestType.StartBackgroundRefresh]: {
+    type: WorkerRequestType.StartBackgroundRefresh;
+    request: WasmStartBackgroundRefreshRequest;
+    result: wasmModule.BackgroundRefreshStatus;
+  };
+  [WorkerRequestType.StopBackgroundRefresh]: {
+    type: WorkerRequestType.StopBackgroundRefresh;
+    request: WasmStopBackgroundRefreshRequest;
+    result: wasmModule.StopBackgroundRefreshResult;
+  };
+  [WorkerRequestType.BackgroundRefreshStatus]: {
+    type: WorkerRequestType.BackgroundRefreshStatus;
+    request: WasmBackgroundRefreshStatusRequest;
+    result: wasmModule.BackgroundRefreshStatus;
+  };
+  [WorkerRequestType.SuspendHint]: {
+    type: WorkerRequestType.SuspendHint;
+    request: WasmSuspendHintRequest;
+    result: wasmModule.SuspendHintResult;
+  };
+  [WorkerRequestType.ResumeHint]: {
+    type: WorkerRequestType.ResumeHint;
+    request: WasmResumeHintRequest;
+    result: wasmModule.SuspendHintResult;
+  };
+  [WorkerRequestType.DeriveSubKey]: {
+    type: WorkerRequestType.DeriveSubKey;
+    request: WasmDeriveSubKeyRequest;
+    result: wasmModule.DeriveSubKeyResult;
+  };
+  [WorkerRequestType.ViewCall]: {
+    type: WorkerRequestType.ViewCall;
+    request: WasmViewCallRequest;
+    result: ViewCallResult;
+  };
+  [WorkerRequestType.DumpDiagnostics]: {
+    type: WorkerRequestType.DumpDiagnostics;
+    request: WasmDumpDiagnosticsRequest;
+    result: wasmModule.DumpDiagnosticsResult;
+  };
+  [WorkerRequestType.SignTransactionsWithFreshChallenge]: {
+    type: WorkerRequestType.SignTransactionsWithFreshChallenge;
+    request: WasmSignTransactionsWithFreshChallengeRequest;
+    result: SignTransactionsWithFreshChallengeResult;
+  };
+  [WorkerRequestType.GetSessionStatus]: {
+    type: WorkerRequestType.GetSessionStatus;
+    request: WasmGetSessionStatusRequest;
+    result: SessionStatus;
+  };
+  [WorkerRequestType.BuildRedirectPayload]: {
+    type: WorkerRequestType.BuildRedirectPayload;
+    request: WasmBuildRedirectPayloadRequest;
+    result: RedirectPayload;
+  };
+  [WorkerRequestType.IssueElevationChallenge]: {
+    type: WorkerRequestType.IssueElevationChallenge;
+    request: WasmIssueElevationChallengeRequest;
+    result: ElevationChallenge;
+  };
 }
 
 /**
@@ -156,6 +654,850 @@ export interface ConfirmationConfig {
   autoProceedDelay?: number;
   /** Theme for the confirmation UI: 'dark' | 'light' */
   theme: 'dark' | 'light';
+  /** How long the user has to confirm, in milliseconds (default 120000); later confirmations are rejected */
+  confirmationTimeoutMs?: number;
+  /** Locale of the screen-reader text (ariaLabel, summarySpeech): 'en' (default) or 'es'; region subtags are ignored */
+  locale?: string;
+}
+
+/**
+ * Encoding of signer worker message frames, fixed per worker by the INITIALIZE handshake.
+ * 'cbor' sends binary payloads (e.g. contract code) as byte strings instead of number arrays.
+ */
+export type SignerWireFormat = 'json' | 'cbor';
+
+/**
+ * Outer wrap of stored key blobs: a non-extractable AES-GCM WebCrypto key encrypts the
+ * PRF-encrypted blob again (see wasm_signer_worker/src/outer_wrap.rs)
+ */
+export type OuterWrapMode = 'aes-gcm-webcrypto';
+
+/**
+ * Worker control messages for the wire format handshake. INITIALIZED carries the self-test
+ * report (`selfTest`) and the base64url X25519 key secrets are sealed to (`sessionPublicKey`).
+ */
+export const SIGNER_WORKER_INITIALIZE = 'INITIALIZE';
+export const SIGNER_WORKER_INITIALIZED = 'INITIALIZED';
+
+/**
+ * Control message carrying the signer end of a MessageChannel to the VRF worker (ports[0]),
+ * posted before the request. The signer worker then requests its VRF challenge over the port.
+ */
+export const SIGNER_WORKER_CONNECT_PEER_PORT = 'CONNECT_PEER_PORT';
+
+/**
+ * Control message carrying the worker end of the confirmation origin bridge (ports[0]), posted
+ * before the request when a trusted confirmation origin is set. Render attestations are only
+ * taken from decisions arriving over it (see confirmationOriginBridge.ts).
+ */
+export const SIGNER_WORKER_CONNECT_CONFIRMATION_PORT = 'CONNECT_CONFIRMATION_PORT';
+
+/** Payload of SIGNER_WORKER_CONNECT_PEER_PORT: the VRF key the challenge must be proven with */
+export interface SignerPeerSession {
+  expectedVrfPublicKey: string;
+  rpId: string;
+}
+
+/** Input item for the signer wasm `verify_signatures_batch` export (all fields base64url) */
+export interface SignatureVerifyItem {
+  /** Ed25519 key (base64url or NEAR "ed25519:<base58>"), or the MAC key for hmacSha256 */
+  publicKey: string;
+  message: string;
+  signature: string;
+  kind: 'ed25519' | 'ed25519Sha256' | 'hmacSha256';
+}
+
+export interface SignatureVerifyResult {
+  index: number;
+  valid: boolean;
+  failure?: 'KeyEncoding' | 'MessageEncoding' | 'SignatureEncoding' | 'SignatureMismatch' | null;
+  detail?: string | null;
+}
+
+/** How a NEAR key blob is wrapped when the authenticator has no PRF (blob versions 2 and 3) */
+export type PrfFallbackScheme = 'largeBlob' | 'passphrase';
+
+/**
+ * Operation that needs a passkey assertion from within WorkerPolicy.elevationMaxAgeMs.
+ * 'exportPrivateKey' and 'addFullAccessKey' (which covers key rotation) always do.
+ */
+export type ElevatedOperation =
+  | 'exportPrivateKey'
+  | 'addFullAccessKey'
+  | 'deleteKey'
+  | 'deleteAccount'
+  | 'deployContract';
+
+/** Integrator policy applied by the signer worker (mirrors Rust WorkerPolicy) */
+export interface WorkerPolicy {
+  /** Ask the pre-sign hook to allow each request before signing */
+  preSignHook?: boolean;
+  /** Notify the post-sign hook of every signed transaction */
+  postSignHook?: boolean;
+  /** How long the worker waits for the pre-sign hook before denying (default 10s) */
+  hookTimeoutMs?: number;
+  /** Average block time for challenge expiry estimates on non-standard networks */
+  averageBlockTimeMs?: number;
+  /** NearBlocks-compatible indexer API base URL for recent-receiver lookups */
+  indexerUrl?: string;
+  /** Fail requests whose payloads contain unknown fields (errorCode 'UnknownField') */
+  strictParsing?: boolean;
+  /** Origins rpcOverrides may point at; other overrides fail with errorCode 'RpcOriginNotAllowed' */
+  allowedRpcOrigins?: string[];
+  /** Longest signing intent lifetime, at creation and execution (default 1h, at most 24h) */
+  maxSigningIntentTtlMs?: number;
+  /** Total deposit per transaction (yoctoNEAR) above which confirmations warn (default 10 NEAR) */
+  depositEscalationYocto?: string;
+  /** Function-call key allowance (yoctoNEAR) left after a confirmation's estimated cost below which it warns (default 0.05 NEAR) */
+  lowAllowanceWarningYocto?: string;
+  /**
+   * Key wrapping allowed for authenticators without PRF, in order of preference.
+   * Without any, such registrations fail with errorCode 'PrfUnsupportedByAuthenticator'.
+   */
+  prfFallbackSchemes?: PrfFallbackScheme[];
+  /** Relayer for SubmitToRelayer; its url must be on allowedRpcOrigins */
+  relayer?: {
+    url: string;
+    /** Header carrying the request's authToken */
+    authHeaderName?: string;
+    /** Schema version relayer responses must declare (default 1, which may omit it) */
+    responseSchemaVersion?: number;
+  };
+  /** Opt-in remote telemetry; without it counters stay in the worker (GetTelemetrySnapshot) */
+  telemetry?: TelemetryPolicy;
+  /** Settings that replace the ones above for requests signed by one account, keyed by account ID */
+  accountOverrides?: Record<string, AccountPolicyOverride>;
+  /**
+   * JSON Schemas FunctionCall args are checked against before the user is prompted; failures
+   * list every violation under errorCode 'ArgsSchemaViolation'
+   */
+  argsSchemas?: ArgsSchema[];
+  /**
+   * Refuse PRF outputs, private keys and passphrases sent in plaintext instead of sealed to the
+   * worker's session key (errorCode 'PlaintextSecretRefused'), until WipeAllState
+   */
+  requireEncryptedSecrets?: boolean;
+  /** Largest message frames the worker parses; larger ones fail with errorCode 'MessageTooLarge' */
+  messageSizeLimits?: MessageSizeLimits;
+  /**
+   * Fail requests that take a deprecated path with errorCode 'DeprecatedUsage' instead of
+   * reporting it in the response's `warnings`
+   */
+  failOnDeprecated?: boolean;
+  /**
+   * Refuse batches that add a full access key with errorCode 'FullAccessAddKeyBlocked'. Without
+   * it, such batches (and DeleteAccount to another beneficiary) are always confirmed with a
+   * click, whatever the confirmationConfig.
+   */
+  blockFullAccessAddKey?: boolean;
+  /**
+   * Compact JSON bytes of a FunctionCall argument shown in confirmations; longer values are
+   * shown truncated, with their SHA-256 (default 2048)
+   */
+  maxArgPreviewBytes?: number;
+  /**
+   * Summary blocks shown in confirmations; past it, the remaining actions are rolled up into
+   * one 'rollup' block listing their amounts (default 200)
+   */
+  maxSummaryBlocks?: number;
+  /**
+   * Operations needing a fresh passkey assertion, besides 'exportPrivateKey' and
+   * 'addFullAccessKey'. Older assertions fail with errorCode 'ElevationRequired'; run a fresh
+   * one and retry.
+   */
+  elevatedOperations?: ElevatedOperation[];
+  /** How recent an elevated operation's passkey assertion must be, in ms (default 60000) */
+  elevationMaxAgeMs?: number;
+  /**
+   * How long a session may use keys before a fresh passkey assertion, in ms (no limit by
+   * default). Kept from the last policy that sets it. Past it, requests that decrypt or sign
+   * without prompting fail with errorCode 'SessionExpired'; a prompted request renews the
+   * session, and intent and approval tokens from the expired session are refused.
+   */
+  maxSessionDurationMs?: number;
+  /**
+   * Distinct passkeys that must approve a batch before it is signed (default 1). With 2, the
+   * first approval fails with errorCode 'AwaitingSecondApproval' and an awaitingSecondApproval
+   * token; the same batch is then sent with that approvalToken and approved with another
+   * passkey within 10 minutes.
+   */
+  requiredApprovals?: 1 | 2;
+  /**
+   * Origin of the wallet iframe that renders confirmations. Read at Initialize only: decisions
+   * without a render attestation from it fail with errorCode 'UntrustedConfirmationOrigin'.
+   */
+  trustedConfirmationOrigin?: string;
+  /**
+   * Origins (https://host[:port]) buildRedirectPayload may redirect to. Other callbacks fail
+   * with errorCode 'RedirectOriginNotAllowed', non-https ones (javascript:, data:, http:) with
+   * 'RedirectSchemeNotAllowed'. Redirect payloads are refused when empty.
+   */
+  allowedRedirectOrigins?: string[];
+}
+
+/** `awaitingSecondApproval` of a SignTransactionsWithActions result (mirrors Rust AwaitingSecondApproval) */
+export interface AwaitingSecondApproval {
+  /** The first approval itself, MACed by the worker: keep it until the second approver's request, which any later worker verifies */
+  approvalToken: string;
+  expiresAtMs: number;
+  /** Fingerprint of the credential that made the first approval */
+  firstApprover: string;
+}
+
+/**
+ * Sealing of secret request fields: 'seal' sends them sealed to the worker's session key,
+ * 'require' also asks the worker to refuse plaintext ones (WorkerPolicy.requireEncryptedSecrets)
+ */
+export type SecretSealingMode = 'off' | 'seal' | 'require';
+
+/**
+ * A JSON Schema for the args of one contract method. Supported (draft-07 subset): type, enum,
+ * const, required, properties, additionalProperties (boolean), items, minimum, maximum,
+ * exclusiveMinimum, exclusiveMaximum, minLength, maxLength, minItems, maxItems and pattern;
+ * other keywords are refused.
+ */
+export interface ArgsSchema {
+  contractId: string;
+  methodName: string;
+  schema: Record<string, unknown> | boolean;
+}
+
+/** The WorkerPolicy settings an account can override; unset ones fall back to the base policy */
+export type AccountPolicyOverride = Pick<
+  WorkerPolicy,
+  | 'preSignHook'
+  | 'postSignHook'
+  | 'hookTimeoutMs'
+  | 'allowedRpcOrigins'
+  | 'maxSigningIntentTtlMs'
+  | 'depositEscalationYocto'
+  | 'lowAllowanceWarningYocto'
+  | 'requiredApprovals'
+  | 'allowedRedirectOrigins'
+>;
+
+/**
+ * Sink for CBOR reports of per-handler request counts, error codes and latency histograms.
+ * Reports never include account IDs, receivers or amounts.
+ */
+export interface TelemetryPolicy {
+  /** URL reports are POSTed to; must be on allowedRpcOrigins */
+  endpoint: string;
+  /** Fraction of requests (0 to 1) counted in reports (default 1) */
+  samplingRate?: number;
+  /** Minutes between reports (default 15, at least 1) */
+  flushIntervalMinutes?: number;
+}
+
+/** Frame size limits in bytes, each at least 4 KiB */
+export interface MessageSizeLimits {
+  /** Request types without a limit below (default 1 MiB) */
+  defaultBytes?: number;
+  /** DeployLargeContract (default 8 MiB) */
+  deployBytes?: number;
+  /** Limits keyed by request type name, e.g. SIGN_TRANSACTIONS_WITH_ACTIONS */
+  byType?: Record<string, number>;
+}
+
+/**
+ * Per-key usage the signer worker hands back as `keyUsageRecord` after signing (mirrors Rust
+ * KeyUsageRecord). Stored as-is and loaded into the next worker; each entry carries a MAC the
+ * worker checks, so edited entries are reported in GetKeyUsageStats `tamperedKeyIds`.
+ */
+export interface KeyUsageRecord {
+  version: number;
+  entries: Array<{
+    keyId: string;
+    signCount: number;
+    lastUsedMs: number;
+    lastReceiverCategory: 'own' | 'implicit' | 'named';
+    /** Truncated SHA-256 of the receiver ID */
+    lastReceiverHash: string;
+    mac: string;
+  }>;
+}
+
+/** GetKeyUsageStats result: usage of each key blob, most recently used first */
+export type KeyUsageStats = StripFree<wasmModule.KeyUsageStats>;
+
+/**
+ * GetWorkerInfo result: the optional features compiled into the signer wasm
+ * ('secp256k1', 'nep413', 'device-linking', 'relayer', 'wasm-threads', 'telemetry').
+ * Requests for a missing feature fail with error code FeatureNotCompiled.
+ */
+export type WorkerInfo = StripFree<wasmModule.WorkerInfo>;
+
+/**
+ * GetSessionStatus result: the session epoch and, under WorkerPolicy.maxSessionDurationMs,
+ * when the session expires and the time left (remainingMs is 0 once expired)
+ */
+export type SessionStatus = StripFree<wasmModule.SessionStatus>;
+
+/**
+ * ValidateDecryptionCapability result: whether a stored key blob decrypts with a PRF output.
+ * `reason` is the error code when it does not ('WrongCredentialForBlob', 'CorruptedCiphertext',
+ * 'DecryptionFailed', 'OuterWrapKeyUnavailable' or 'InvalidPlaintext').
+ */
+export type DecryptionCapability = StripFree<wasmModule.DecryptionCapability>;
+
+/** ListActiveAccounts result: accounts with session state or live session keys, most recently active first */
+export type ActiveAccounts = StripFree<wasmModule.ListActiveAccountsResult>;
+
+/** In-flight limits of the SignerWorkerManager's pending-request registry */
+export interface RequestLimits {
+  /** Requests running at once (at least 1); further requests are queued */
+  maxConcurrent: number;
+  /** Requests waiting for a slot before new ones fail with TooManyPendingRequests */
+  maxQueued: number;
+}
+
+/** Running and queued requests of the SignerWorkerManager, oldest first (mirrors ListPendingRequestsResult) */
+export type PendingRequests = Omit<StripFree<wasmModule.ListPendingRequestsResult>, 'requests'> & {
+  requests: Array<Omit<StripFree<wasmModule.PendingRequestEntry>, 'phase'> & { phase: 'queued' | 'running' }>;
+};
+
+/** What cancelRequest found: only queued requests are cancelled (mirrors CancelRequestResult) */
+export interface CancelledRequest {
+  requestId: string;
+  cancelled: boolean;
+  /** Phase the request was in; absent when it is not pending */
+  phase?: 'queued' | 'running';
+}
+
+/** WipeAccountState result: what was discarded of one account's session state */
+export type AccountWipeSummary = StripFree<wasmModule.WipeAccountStateResult>;
+
+/** ValidateArgsSchemas result: whether every schema compiles, and where the others fail */
+export type ArgsSchemaValidation = StripFree<wasmModule.ValidateArgsSchemasResult>;
+
+/** `conditionalBatch` of a SignTransactionsWithActions request */
+export interface ConditionalBatchOptions {
+  /** Sign every transaction when the RPC cannot simulate, instead of failing the batch */
+  fallbackToUnconditional?: boolean;
+}
+
+/** How a conditional batch's transaction simulated (view calls of its FunctionCalls) */
+export type SimulationOutcome =
+  | { status: 'succeeded'; logs: string[] }
+  | { status: 'failed'; methodName: string; error: string; logs: string[] }
+  | { status: 'unavailable'; error: string };
+
+/** A transaction of a conditional batch; transactions after the first unsuccessful simulation are skipped and not simulated */
+export interface ConditionalBatchEntry {
+  index: number;
+  status: 'signed' | 'skipped';
+  simulation: SimulationOutcome | null;
+}
+
+/** SummarizeTransactions result: the batch's canonical digest (to send back as previewDigest) and the duplicates deduplicate drops */
+export type TransactionsSummary = StripFree<wasmModule.SummarizeTransactionsResult>;
+
+/**
+ * What a stored blob holds: a PasskeyNearKeys record, the encryptedVrfKeypair of a
+ * PasskeyClientDB user, or a VRF worker session snapshot
+ */
+export type StoredBlobKind = 'nearKey' | 'vrfKeypair' | 'sessionSnapshot';
+
+/** A blob for MigrateEncryptedBlobs, as read from storage */
+export interface StoredEncryptedBlob {
+  /** Echoed back in the report, e.g. 'alice.testnet#1' */
+  blobId?: string;
+  kind: StoredBlobKind;
+  blob: Record<string, unknown>;
+  /**
+   * Required to migrate a vrfKeypair blob without `meta`: the metadata to bind into it, from
+   * the user record it is stored in
+   */
+  vrfKeypairMeta?: VrfKeypairBlobMeta;
+}
+
+/** How one blob fared (mirrors Rust BlobMigrationReport) */
+export interface BlobMigrationReport {
+  /** Position of the blob in the request */
+  index: number;
+  blobId?: string | null;
+  kind: StoredBlobKind;
+  status: 'migrated' | 'alreadyCurrent' | 'failed';
+  /** null when the format could not be read */
+  fromVersion?: number | null;
+  /** null when the blob failed */
+  toVersion?: number | null;
+  /** The blob to store: the migrated one, or the original when current or failed */
+  blob: Record<string, unknown>;
+  /** 'WrongCredentialForBlob', 'CorruptedCiphertext', 'DecryptionFailed' or 'OuterWrapKeyUnavailable' */
+  errorCode?: string | null;
+  error?: string | null;
+}
+
+/** MigrateEncryptedBlobs result: a report per blob, failed blobs returned as they were */
+export interface MigrateEncryptedBlobsResult {
+  migrated: number;
+  alreadyCurrent: number;
+  failed: number;
+  reports: BlobMigrationReport[];
+}
+
+/**
+ * PublicKeyCredentialCreationOptionsJSON built by the signer worker (mirrors Rust
+ * CredentialCreationOptions). Binary members are base64url: challenge, user.id,
+ * excludeCredentials[].id and the PRF eval salts.
+ */
+export interface CredentialCreationOptionsJSON {
+  rp: { id: string; name: string };
+  user: { id: string; name: string; displayName: string };
+  challenge: string;
+  /** EdDSA (-8), ES256 (-7), RS256 (-257), in preference order */
+  pubKeyCredParams: { type: 'public-key'; alg: number }[];
+  timeout: number;
+  attestation: 'none';
+  authenticatorSelection: {
+    residentKey: 'required';
+    requireResidentKey: boolean;
+    userVerification: 'required' | 'preferred' | 'discouraged';
+  };
+  excludeCredentials: { type: 'public-key'; id: string; transports?: string[] }[];
+  extensions: {
+    prf: { eval: { first: string; second: string } };
+    largeBlob: { support: 'preferred' };
+    credProps: boolean;
+  };
+}
+
+export interface BuildCredentialCreationOptionsResult {
+  publicKey: CredentialCreationOptionsJSON;
+}
+
+/** An authenticator of the account for BuildCredentialRequestOptions (mirrors Rust KnownAuthenticator) */
+export interface KnownAuthenticator {
+  credentialId: string;
+  transports?: string[];
+  deviceNumber: number;
+  /** credProps.rk reported at registration; omitted when unknown */
+  residentKey?: boolean;
+}
+
+/**
+ * PublicKeyCredentialRequestOptionsJSON built by the signer worker (mirrors Rust
+ * CredentialRequestOptions). Binary members are base64url: challenge,
+ * allowCredentials[].id and the PRF eval salts.
+ */
+export interface CredentialRequestOptionsJSON {
+  challenge: string;
+  rpId: string;
+  /** Absent for discoverable-credential flows */
+  allowCredentials?: { type: 'public-key'; id: string; transports?: string[] }[];
+  userVerification: 'required' | 'preferred' | 'discouraged';
+  timeout: number;
+  extensions: {
+    prf: { eval: { first: string; second: string } };
+    largeBlob: { read: boolean };
+  };
+}
+
+export interface BuildCredentialRequestOptionsResult {
+  publicKey: CredentialRequestOptionsJSON;
+  /** allowCredentials was omitted: check which account's passkey answered */
+  discoverable: boolean;
+}
+
+/** IndexedDB app state key of the stored KeyUsageRecord */
+export const KEY_USAGE_RECORD_APP_STATE_KEY = 'keyUsageRecord';
+
+/**
+ * Sealed state the signer worker hands back as `workerStateRecord` when state that outlives the
+ * request changed (mirrors Rust WorkerStateRecord). Opaque: stored as-is and loaded into the
+ * next worker, which opens it with the state wrap CryptoKey.
+ */
+export interface WorkerStateRecord {
+  version: number;
+  wrappedStateKey: string;
+  sealedState: string;
+}
+
+/** IndexedDB app state key of the stored WorkerStateRecord */
+export const WORKER_STATE_RECORD_APP_STATE_KEY = 'workerStateRecord';
+
+/** IndexedDB app state key of the non-extractable AES-GCM CryptoKey wrapping the state key */
+export const WORKER_STATE_WRAP_KEY_APP_STATE_KEY = 'workerStateWrapKey';
+
+/**
+ * Written by the signer worker right before a broadcast or relayer submission (mirrors Rust
+ * JournalEntry). Identifiers only, no secrets.
+ */
+export interface JournalEntry {
+  requestId: string;
+  step: 'broadcast' | 'relayerSubmission';
+  /** Broadcasts only */
+  txHash?: string;
+  /** Signer of a broadcast, or the account a relayer submission creates */
+  accountId: string;
+  idempotencyKey?: string;
+  createdAtMs: number;
+}
+
+/** IndexedDB app state key of the journal entries awaiting a result */
+export const OPERATION_JOURNAL_APP_STATE_KEY = 'operationJournal';
+
+/** What became of a journaled step (mirrors Rust RecoveredOperation) */
+export interface RecoveredOperation {
+  entry: JournalEntry;
+  /** 'unknown': the RPC could not answer, recover again later */
+  status: 'confirmed' | 'pending' | 'notFound' | 'unknown';
+  /** Confirmed broadcasts: whether the transaction succeeded */
+  succeeded?: boolean;
+  detail?: string;
+}
+
+export interface RecoverPendingOperationsResult {
+  operations: RecoveredOperation[];
+  /** Operations whose status is 'unknown' */
+  unresolved: number;
+}
+
+/** Outcome of SubmitToRelayer (mirrors Rust RelayerResult) */
+export interface RelayerResult {
+  success: boolean;
+  accountCreated: boolean;
+  txHash?: string | null;
+  /** Error code of a failure */
+  errorKind?: 'RelayerAccountExists' | 'RelayerQuotaExceeded' | 'RelayerRejected'
+    | 'RelayerUnavailable' | 'RelayerResponseInvalid' | null;
+  error?: string | null;
+}
+
+/** PrepareRegistration result: the encrypted key to store, and the token CompleteRegistration takes */
+export type PrepareRegistrationResult = Omit<StripFree<wasmModule.PrepareRegistrationResult>, 'keys'> & {
+  keys: WasmDeriveNearKeypairAndEncryptResult;
+};
+
+/** IndexedDB app state key of an account's pending resume token (PrepareRegistration) */
+export const registrationResumeAppStateKey = (nearAccountId: string) => `registrationResume:${nearAccountId}`;
+
+/** Stored between PrepareRegistration and a successful CompleteRegistration */
+export interface PendingRegistration {
+  publicKey: string;
+  resumeToken: string;
+  resumeExpiresAtMs: number;
+}
+
+export interface RecentReceiver {
+  receiverId: string;
+  lastSeenMs: number;
+}
+
+export interface GetRecentReceiversResult {
+  accountId: string;
+  /** Most recently seen first; empty when source is 'unknown' */
+  receivers: RecentReceiver[];
+  /** 'unknown' when no indexer is configured or it could not be reached */
+  source: 'cache' | 'indexer' | 'unknown';
+}
+
+export interface BuildAccountDescriptorResult {
+  /** base64url CBOR blob; check it with verify_account_descriptor(descriptor, nearPublicKey) */
+  descriptor: string;
+  nearPublicKey: string;
+  expiresAtMs: number;
+}
+
+/** Hashes of a chunked deploy (base58 SHA-256, like NEAR's code_hash) */
+export interface DeployManifest {
+  totalBytes: number;
+  chunkSize: number;
+  /** One per staging call, in broadcast order */
+  chunkHashes: string[];
+  /** Hash of the whole code: the deployed contract's code_hash */
+  totalHash: string;
+}
+
+/** Staging calls then the deploy call, signed in broadcast order, with the deploy manifest */
+export type DeployLargeContractResult = WasmTransactionSignResult & {
+  manifest: DeployManifest;
+};
+
+/**
+ * Known-answer test report from RunSelfTest, also sent with INITIALIZED as `selfTest`.
+ * After a failure key-handling requests fail with errorCode 'SelfTestFailed' until the next INITIALIZE.
+ */
+export interface SelfTestReport {
+  passed: boolean;
+  results: { primitive: string; passed: boolean; durationMs: number; error?: string | null }[];
+  totalDurationMs: number;
+  failedPrimitive?: string | null;
+}
+
+/**
+ * IndexedDB records carried by an account bundle, one list per store. Records are plain JSON:
+ * authenticator `credentialPublicKey` bytes travel as number[].
+ */
+export interface AccountBundleRecords {
+  users: Record<string, unknown>[];
+  authenticators: Record<string, unknown>[];
+  nearKeys: Record<string, unknown>[];
+  appState: { key: string; value: unknown }[];
+}
+
+export interface ExportAccountBundleResult {
+  /** Bundle file bytes */
+  bundle: number[];
+  version: number;
+  recordCount: number;
+}
+
+/**
+ * Decrypted and validated bundle records, in the current schema, for the storage layer to write.
+ * Failures carry 'BundlePassphraseInvalid', 'BundleTruncated', 'BundleVersionUnsupported' or
+ * 'BundleInvalid' as the error prefix.
+ */
+export interface ImportAccountBundleResult {
+  records: AccountBundleRecords;
+  version: number;
+  exportedAtMs: number;
+}
+
+/**
+ * A confirmed batch to sign later with executeSigningIntent. The token is only valid in the
+ * signer worker session that issued it, until `expiresAtMs`, and executes once.
+ */
+export type SigningIntent = StripFree<wasmModule.CreateSigningIntentResult>;
+
+/**
+ * A batch to confirm on another device holding the same passkey. `confirmationRequest` goes to
+ * that device (QR code or relayer) for approveRemoteConfirmation; its approval is only readable
+ * by signer workers handed this device's worker state record, until `expiresAtMs`, and signs once.
+ */
+export type RemoteConfirmationRequest = StripFree<wasmModule.CreateRemoteConfirmationResult>;
+
+/**
+ * A signed sign-in redirect: `url` is the callback URL with `w3a_payload=<token>&state=<state>`
+ * added to its query. The token names the account, the session key that signed it and a VRF
+ * challenge; it carries no PRF output or private key.
+ */
+export type RedirectPayload = StripFree<wasmModule.BuildRedirectPayloadResult>;
+
+/**
+ * A challenge for the passkey assertion behind a raw-PRF export: prompt with its base64url-decoded
+ * bytes and send the assertion with DecryptPrivateKeyWithPrf, within the elevation window of `issuedAtMs`
+ */
+export type ElevationChallenge = StripFree<wasmModule.IssueElevationChallengeResult>;
+
+/**
+ * Fields returned by verify_redirect_payload(token, callbackUrl, state) on the receiving side.
+ * The receiver still checks that sessionPublicKey is an access key of nearAccountId.
+ */
+export interface VerifiedRedirectPayload {
+  nearAccountId: string;
+  sessionPublicKey: string;
+  vrfChallenge: VRFChallenge;
+  callbackUrl: string;
+  state: string;
+  issuedAtMs: number;
+  expiresAtMs: number;
+  /** base64url; receivers that remember nonces can refuse replays until expiresAtMs */
+  nonce: string;
+}
+
+/** Fields returned by verify_account_descriptor */
+export interface AccountDescriptor {
+  version: number;
+  accountId: string;
+  contractId: string;
+  vrfPublicKey: string;
+  authenticators: { credentialId: string; deviceNumber: number }[];
+  nearPublicKey: string;
+  issuedAtMs: number;
+  expiresAtMs: number;
+}
+
+/** Input of the signer wasm `build_unsigned_transaction` export (pass as a JSON string) */
+export interface UnsignedTransactionPayload {
+  signerAccountId: string;
+  /** Access key the transaction will be signed with ("ed25519:<base58>" or base64url) */
+  signerPublicKey: string;
+  receiverId: string;
+  nonce: string;
+  /** base58 */
+  blockHash: string;
+  /** JSON string of ActionArgsWasm[] */
+  actions: string;
+}
+
+/** Returned by build_unsigned_transaction: the exact bytes the signing path would sign */
+export interface UnsignedTransactionResult {
+  /** Borsh-serialized Transaction (standard base64) */
+  borshBase64: string;
+  /** Transaction hash (SHA-256 of the borsh bytes), base58 */
+  txHashB58: string;
+  parsedSummary: {
+    signerId: string;
+    publicKey: string;
+    nonce: string;
+    receiverId: string;
+    blockHash: string;
+    actions: ActionArgsWasm[];
+  };
+}
+
+/** One field compared by verify_signed_transaction, e.g. 'receiverId' or 'actions[0].deposit' */
+export interface AuditFieldCheck {
+  field: string;
+  matches: boolean;
+  /** null when the expected summary has no such action */
+  expected: unknown;
+  /** null when the transaction has no such action */
+  actual: unknown;
+}
+
+/**
+ * Returned by the signer wasm `verify_signed_transaction(signedTxBase64, expectedDigest,
+ * expectedSummaryJson)` export: whether a signed transaction is the one its confirmation showed
+ */
+export interface AuditVerificationResult {
+  /** Signature, digest and every compared field check out */
+  valid: boolean;
+  signatureValid: boolean;
+  /** Intent digest recomputed from the transaction */
+  digest: string;
+  digestMatches: boolean;
+  summaryMatches: boolean;
+  fields: AuditFieldCheck[];
+  txHashB58: string;
+  transaction: UnsignedTransactionResult['parsedSummary'];
+}
+
+/**
+ * An action of a type the SDK does not know. Actions are not length-prefixed, so rawBytes
+ * (standard base64, discriminant included) also holds every action after it.
+ */
+export interface UnknownAction {
+  action_type: 'UnknownAction';
+  discriminant: number;
+  rawBytes: string;
+}
+
+/**
+ * Returned by the signer wasm `decode_signed_transaction(signedTxBase64)` export: a signed
+ * transaction from any wallet, rendered like a confirmation. Malformed input throws an error
+ * naming the byte offset where decoding stopped.
+ */
+export interface DecodedTransaction {
+  signerId: string;
+  publicKey: string;
+  nonce: string;
+  receiverId: string;
+  blockHash: string;
+  txHashB58: string;
+  /** The embedded signature verifies against publicKey */
+  signatureValid: boolean;
+  /** Number of actions the transaction declares */
+  actionCount: number;
+  /** An UnknownAction is always last */
+  actions: (ActionArgsWasm | UnknownAction)[];
+  /** The transaction's txSigningRequests entry, as a confirmation would show it */
+  txTree: Omit<TransactionInputWasm, 'actions'> & { actions: DecodedTransaction['actions'] };
+  summaryBlocks: ConfirmationSummaryBlock[];
+}
+
+/** What a QR payload carries (signer wasm `encode_qr_payload(kind, blobB64u, maxPartChars?)`) */
+export type QrPayloadKind = 'deviceLinking' | 'remoteConfirmation';
+
+/**
+ * Returned by the signer wasm `decode_qr_payload(parts)` export, for the parts scanned so far
+ * (any order). Parts of another payload and blobs that fail the checksum throw QrPayloadInvalid.
+ */
+export interface DecodedQrPayload {
+  kind: QrPayloadKind;
+  partCount: number;
+  /** 0-based indexes of the parts still to scan */
+  missingParts: number[];
+  /** The reassembled blob; null until no part is missing */
+  blobB64u: string | null;
+}
+
+/** Public key returned by the signer wasm `cose_key_to_jwk` export (binary members base64url) */
+export interface CredentialPublicKeyJwk {
+  kty: 'OKP' | 'EC' | 'RSA';
+  /** JOSE algorithm name, e.g. "ES256"; absent for unregistered COSE algorithms */
+  alg?: string;
+  crv?: string;
+  x?: string;
+  y?: string;
+  n?: string;
+  e?: string;
+}
+
+/**
+ * Returned by the signer wasm `parse_authenticator_data` export. Parsed by the same code
+ * registration uses, so no worker session is needed and the two always agree.
+ */
+export interface ParsedAuthData {
+  rpIdHashB64u: string;
+  flags: {
+    userPresent: boolean;
+    userVerified: boolean;
+    backupEligible: boolean;
+    backupState: boolean;
+    attestedCredentialData: boolean;
+    extensionData: boolean;
+    /** The flags byte as received */
+    raw: number;
+  };
+  signCount: number;
+  attestedCredential?: {
+    /** Authenticator model as a UUID string (all zeros for "none" attestation) */
+    aaguid: string;
+    /** The provider the AAGUID names, e.g. "iCloud Keychain"; null when unknown */
+    providerName?: string | null;
+    /** Whether the provider syncs its passkeys across devices */
+    isSyncedPasskey?: boolean | null;
+    credentialIdB64u: string;
+    credentialPublicKeyB64u: string;
+    /** COSE algorithm identifier, e.g. -7 */
+    publicKeyAlgorithm?: number | null;
+    /** e.g. "ES256", "EdDSA" */
+    publicKeyAlgorithmName?: string | null;
+    publicKeyJwk: CredentialPublicKeyJwk;
+  } | null;
+  /** CBOR map of extension outputs, base64url */
+  extensionsB64u?: string | null;
+}
+
+/**
+ * The authenticator model a credential was created on, named from its AAGUID by the built-in
+ * table of well-known authenticators and PasskeyManagerConfigs.knownAuthenticators
+ */
+export interface AuthenticatorInfo {
+  /** Lowercase UUID string */
+  aaguid: string;
+  /** null for AAGUIDs neither table knows */
+  providerName?: string | null;
+  isSyncedPasskey?: boolean | null;
+}
+
+/** An entry of PasskeyManagerConfigs.knownAuthenticators */
+export interface AuthenticatorModel {
+  /** UUID string, any case */
+  aaguid: string;
+  providerName: string;
+  /** Whether its passkeys sync across devices (recovery guidance differs) */
+  isSyncedPasskey?: boolean;
+}
+
+/** Returned by the signer wasm `parse_attestation_object` export */
+export interface ParsedAttestation {
+  /** Attestation statement format, e.g. "none" or "packed" */
+  fmt?: string | null;
+  attStmtKeys: string[];
+  authData: ParsedAuthData;
+}
+
+/** Thrown by parse_attestation_object, parse_authenticator_data and cose_key_to_jwk */
+export interface WebAuthnDataError {
+  code: 'WebAuthnDataMalformed';
+  /** Structure `offset` is relative to */
+  section: 'attestationObject' | 'authData' | 'coseKey';
+  offset: number;
+  message: string;
 }
 
 export const DEFAULT_CONFIRMATION_CONFIG: ConfirmationConfig = {
@@ -197,6 +1539,11 @@ export type WasmRequestResult = WasmRecoverKeypairResult
 export interface SignerWorkerMessage<T extends WorkerRequestType, R extends WasmRequestPayload> {
   type: T;
   payload: R;
+  /** Outer-wrap key, structured-cloned to the worker; never forwarded to WASM as data */
+  outerWrapKey?: CryptoKey;
+  /** Wraps the state key of workerStateRecord; never forwarded to WASM as data */
+  workerStateWrapKey?: CryptoKey;
+  workerStateRecord?: WorkerStateRecord;
 }
 
 /**
@@ -270,6 +1617,19 @@ export interface ProgressStepMap {
 export interface BaseWorkerResponse {
   type: WorkerResponseType;
   payload: unknown;
+  /** Deprecated paths the request took; absent when there are none */
+  warnings?: DeprecationWarning[];
+}
+
+/**
+ * A deprecated field, encoding or message the request used. Codes are catalogued in
+ * wasm_shared/error_codes.rs (look them up with the wasm `lookup_deprecation` export).
+ */
+export interface DeprecationWarning {
+  code: 'PlaintextSecret' | 'PlaintextConfirmationSecret' | 'LegacyKeyBlob';
+  message: string;
+  /** Last release that accepts the path */
+  removeAfterVersion: string;
 }
 
 // Map request types to their expected success response payloads (WASM types)
@@ -281,15 +1641,65 @@ export interface RequestResponseMap {
   [WorkerRequestType.SignTransactionsWithActions]: WasmTransactionSignResult;
   [WorkerRequestType.ExtractCosePublicKey]: wasmModule.CoseExtractionResult;
   [WorkerRequestType.SignTransactionWithKeyPair]: WasmTransactionSignResult;
-  [WorkerRequestType.SignNep413Message]: wasmModule.SignNep413Result;
+  [WorkerRequestType.SignNep413Message]: WasmSignNep413MessageResult;
   [WorkerRequestType.RegistrationCredentialConfirmation]: wasmModule.RegistrationCredentialConfirmationResult;
   [WorkerRequestType.ExportNearKeypairUI]: WasmExportNearKeypairUiResult;
+  [WorkerRequestType.ValidateEncryptedBlobs]: wasmModule.ValidateEncryptedBlobsResult;
+  [WorkerRequestType.CreateSessionKey]: wasmModule.CreateSessionKeyResult;
+  [WorkerRequestType.SignWithSessionKey]: WasmTransactionSignResult;
+  [WorkerRequestType.RevokeSessionKey]: wasmModule.RevokeSessionKeyResult;
+  [WorkerRequestType.ComposeMultisigRequest]: wasmModule.ComposeMultisigResult;
+  [WorkerRequestType.GetMemoryStats]: wasmModule.MemoryStats;
+  [WorkerRequestType.TrimCaches]: wasmModule.TrimCachesResult;
+  [WorkerRequestType.GetRecentReceivers]: GetRecentReceiversResult;
+  [WorkerRequestType.BuildAccountDescriptor]: BuildAccountDescriptorResult;
+  [WorkerRequestType.DeployLargeContract]: DeployLargeContractResult;
+  [WorkerRequestType.RunSelfTest]: SelfTestReport;
+  [WorkerRequestType.ExportAccountBundle]: ExportAccountBundleResult;
+  [WorkerRequestType.ImportAccountBundle]: ImportAccountBundleResult;
+  [WorkerRequestType.CreateSigningIntent]: wasmModule.CreateSigningIntentResult;
+  [WorkerRequestType.ExecuteSigningIntent]: WasmTransactionSignResult;
+  [WorkerRequestType.SubmitToRelayer]: RelayerResult;
+  [WorkerRequestType.GetTelemetrySnapshot]: wasmModule.TelemetrySnapshot;
+  [WorkerRequestType.GetKeyUsageStats]: KeyUsageStats;
+  [WorkerRequestType.PrepareRegistration]: PrepareRegistrationResult;
+  [WorkerRequestType.CompleteRegistration]: RelayerResult;
+  [WorkerRequestType.CreateRemoteConfirmation]: RemoteConfirmationRequest;
+  [WorkerRequestType.ApproveRemoteConfirmation]: wasmModule.ApproveRemoteConfirmationResult;
+  [WorkerRequestType.CompleteRemoteConfirmation]: WasmTransactionSignResult;
+  [WorkerRequestType.GetWorkerInfo]: WorkerInfo;
+  [WorkerRequestType.ValidateDecryptionCapability]: DecryptionCapability;
+  [WorkerRequestType.ListActiveAccounts]: ActiveAccounts;
+  [WorkerRequestType.WipeAccountState]: AccountWipeSummary;
+  [WorkerRequestType.ValidateArgsSchemas]: ArgsSchemaValidation;
+  [WorkerRequestType.SummarizeTransactions]: TransactionsSummary;
+  [WorkerRequestType.MigrateEncryptedBlobs]: MigrateEncryptedBlobsResult;
+  [WorkerRequestType.BuildCredentialCreationOptions]: BuildCredentialCreationOptionsResult;
+  [WorkerRequestType.BuildCredentialRequestOptions]: BuildCredentialRequestOptionsResult;
+  [WorkerRequestType.RecoverPendingOperations]: RecoverPendingOperationsResult;
+  [WorkerRequestType.ComposeCreateSubaccount]: wasmModule.ComposeCreateSubaccountResult;
+  [WorkerRequestType.StartBackgroundRefresh]: wasmModule.BackgroundRefreshStatus;
+  [WorkerRequestType.StopBackgroundRefresh]: wasmModule.StopBackgroundRefreshResult;
+  [WorkerRequestType.BackgroundRefreshStatus]: wasmModule.BackgroundRefreshStatus;
+  [WorkerRequestType.SuspendHint]: wasmModule.SuspendHintResult;
+  [WorkerRequestType.ResumeHint]: wasmModule.SuspendHintResult;
+  [WorkerRequestType.DeriveSubKey]: wasmModule.DeriveSubKeyResult;
+  [WorkerRequestType.ViewCall]: ViewCallResult;
+  [WorkerRequestType.DumpDiagnostics]: wasmModule.DumpDiagnosticsResult;
+  [WorkerRequestType.SignTransactionsWithFreshChallenge]: SignTransactionsWithFreshChallengeResult;
+  [WorkerRequestType.GetSessionStatus]: SessionStatus;
+  [WorkerRequestType.BuildRedirectPayload]: RedirectPayload;
+  [WorkerRequestType.IssueElevationChallenge]: ElevationChallenge;
 }
 
 // Generic success response type that uses WASM types
 export interface WorkerSuccessResponse<T extends WorkerRequestType> extends BaseWorkerResponse {
   type: WorkerResponseType;
-  payload: RequestResponseMap[T];
+  /**
+   * `replayed` is set when the request repeated a completed request's `idempotencyKey`
+   * and the stored result was returned instead of re-executing
+   */
+  payload: RequestResponseMap[T] & { replayed?: boolean };
 }
 
 // Generic error response type
@@ -298,10 +1708,20 @@ export interface WorkerErrorResponse extends BaseWorkerResponse {
   payload: {
     error: string;
     errorCode?: WorkerErrorCode;
+    /** Category of errorCode; 'Internal' when the error has no catalogued code */
+    errorCategory?: ErrorCategory;
+    /** Whether sending the same request again can succeed */
+    retriable?: boolean;
     context?: Record<string, unknown>;
   };
 }
 
+/**
+ * Categories of the error code catalog shared by both workers
+ * (wasm_shared/error_codes.rs; look codes up with the wasm `lookup_error_code` export)
+ */
+export type ErrorCategory = 'UserAction' | 'Policy' | 'Crypto' | 'Network' | 'Internal';
+
 export enum WorkerErrorCode {
   WASM_INIT_FAILED = 'WASM_INIT_FAILED',
   INVALID_REQUEST = 'INVALID_REQUEST',
@@ -313,6 +1733,27 @@ export enum WorkerErrorCode {
   UNKNOWN_ERROR = 'UNKNOWN_ERROR',
 }
 
+/**
+ * Codes reported when a stored NEAR key blob fails to decrypt. Blobs encrypted with a
+ * key-check header tell a wrong passkey apart from corrupted data; older blobs only
+ * report DecryptionFailed.
+ */
+export type KeyBlobDecryptErrorCode =
+  | 'WrongCredentialForBlob'
+  | 'CorruptedCiphertext'
+  | 'DecryptionFailed';
+
+const KEY_BLOB_DECRYPT_ERROR_CODES: KeyBlobDecryptErrorCode[] = [
+  'WrongCredentialForBlob',
+  'CorruptedCiphertext',
+  'DecryptionFailed',
+];
+
+/** Extracts the key blob decryption code from a worker error message, if present */
+export function getKeyBlobDecryptErrorCode(message: string): KeyBlobDecryptErrorCode | undefined {
+  return KEY_BLOB_DECRYPT_ERROR_CODES.find(code => message.includes(`${code}:`));
+}
+
 export interface WorkerProgressResponse extends BaseWorkerResponse {
   type: WorkerResponseType;
   payload: onProgressEvents
@@ -336,6 +1777,45 @@ export type TransactionResponse = WorkerResponseForRequest<typeof WorkerRequestT
 export type DecryptionResponse = WorkerResponseForRequest<typeof WorkerRequestType.DecryptPrivateKeyWithPrf>;
 export type CoseExtractionResponse = WorkerResponseForRequest<typeof WorkerRequestType.ExtractCosePublicKey>;
 export type Nep413SigningResponse = WorkerResponseForRequest<typeof WorkerRequestType.SignNep413Message>;
+export type DeployLargeContractResponse = WorkerResponseForRequest<typeof WorkerRequestType.DeployLargeContract>;
+export type ExportAccountBundleResponse = WorkerResponseForRequest<typeof WorkerRequestType.ExportAccountBundle>;
+export type ImportAccountBundleResponse = WorkerResponseForRequest<typeof WorkerRequestType.ImportAccountBundle>;
+export type CreateSessionKeyResponse = WorkerResponseForRequest<typeof WorkerRequestType.CreateSessionKey>;
+export type SignWithSessionKeyResponse = WorkerResponseForRequest<typeof WorkerRequestType.SignWithSessionKey>;
+export type RevokeSessionKeyResponse = WorkerResponseForRequest<typeof WorkerRequestType.RevokeSessionKey>;
+export type CreateSigningIntentResponse = WorkerResponseForRequest<typeof WorkerRequestType.CreateSigningIntent>;
+export type ExecuteSigningIntentResponse = WorkerResponseForRequest<typeof WorkerRequestType.ExecuteSigningIntent>;
+export type SubmitToRelayerResponse = WorkerResponseForRequest<typeof WorkerRequestType.SubmitToRelayer>;
+export type GetTelemetrySnapshotResponse = WorkerResponseForRequest<typeof WorkerRequestType.GetTelemetrySnapshot>;
+export type GetKeyUsageStatsResponse = WorkerResponseForRequest<typeof WorkerRequestType.GetKeyUsageStats>;
+export type PrepareRegistrationResponse = WorkerResponseForRequest<typeof WorkerRequestType.PrepareRegistration>;
+export type CompleteRegistrationResponse = WorkerResponseForRequest<typeof WorkerRequestType.CompleteRegistration>;
+export type CreateRemoteConfirmationResponse = WorkerResponseForRequest<typeof WorkerRequestType.CreateRemoteConfirmation>;
+export type ApproveRemoteConfirmationResponse = WorkerResponseForRequest<typeof WorkerRequestType.ApproveRemoteConfirmation>;
+export type CompleteRemoteConfirmationResponse = WorkerResponseForRequest<typeof WorkerRequestType.CompleteRemoteConfirmation>;
+export type GetWorkerInfoResponse = WorkerResponseForRequest<typeof WorkerRequestType.GetWorkerInfo>;
+export type ValidateDecryptionCapabilityResponse = WorkerResponseForRequest<typeof WorkerRequestType.ValidateDecryptionCapability>;
+export type ListActiveAccountsResponse = WorkerResponseForRequest<typeof WorkerRequestType.ListActiveAccounts>;
+export type WipeAccountStateResponse = WorkerResponseForRequest<typeof WorkerRequestType.WipeAccountState>;
+export type ValidateArgsSchemasResponse = WorkerResponseForRequest<typeof WorkerRequestType.ValidateArgsSchemas>;
+export type SummarizeTransactionsResponse = WorkerResponseForRequest<typeof WorkerRequestType.SummarizeTransactions>;
+export type MigrateEncryptedBlobsResponse = WorkerResponseForRequest<typeof WorkerRequestType.MigrateEncryptedBlobs>;
+export type BuildCredentialCreationOptionsResponse = WorkerResponseForRequest<typeof WorkerRequestType.BuildCredentialCreationOptions>;
+export type BuildCredentialRequestOptionsResponse = WorkerResponseForRequest<typeof WorkerRequestType.BuildCredentialRequestOptions>;
+export type RecoverPendingOperationsResponse = WorkerResponseForRequest<typeof WorkerRequestType.RecoverPendingOperations>;
+export type ComposeCreateSubaccountResponse = WorkerResponseForRequest<typeof WorkerRequestType.ComposeCreateSubaccount>;
+export type StartBackgroundRefreshResponse = WorkerResponseForRequest<typeof WorkerRequestType.StartBackgroundRefresh>;
+export type StopBackgroundRefreshResponse = WorkerResponseForRequest<typeof WorkerRequestType.StopBackgroundRefresh>;
+export type BackgroundRefreshStatusResponse = WorkerResponseForRequest<typeof WorkerRequestType.BackgroundRefreshStatus>;
+export type SuspendHintResponse = WorkerResponseForRequest<typeof WorkerRequestType.SuspendHint>;
+export type ResumeHintResponse = WorkerResponseForRequest<typeof WorkerRequestType.ResumeHint>;
+export type DeriveSubKeyResponse = WorkerResponseForRequest<typeof WorkerRequestType.DeriveSubKey>;
+export type ViewCallResponse = WorkerResponseForRequest<typeof WorkerRequestType.ViewCall>;
+export type DumpDiagnosticsResponse = WorkerResponseForRequest<typeof WorkerRequestType.DumpDiagnostics>;
+export type SignTransactionsWithFreshChallengeResponse = WorkerResponseForRequest<typeof WorkerRequestType.SignTransactionsWithFreshChallenge>;
+export type GetSessionStatusResponse = WorkerResponseForRequest<typeof WorkerRequestType.GetSessionStatus>;
+export type BuildRedirectPayloadResponse = WorkerResponseForRequest<typeof WorkerRequestType.BuildRedirectPayload>;
+export type IssueElevationChallengeResponse = WorkerResponseForRequest<typeof WorkerRequestType.IssueElevationChallenge>;
 
 // === TYPE GUARDS FOR GENERIC RESPONSES ===
 
@@ -413,3 +1893,159 @@ export function isExtractCosePublicKeySuccess(response: CoseExtractionResponse):
 export function isSignNep413MessageSuccess(response: Nep413SigningResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.SignNep413Message> {
   return response.type === WorkerResponseType.SignNep413MessageSuccess;
 }
+
+export function isDeployLargeContractSuccess(response: DeployLargeContractResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.DeployLargeContract> {
+  return response.type === WorkerResponseType.DeployLargeContractSuccess;
+}
+
+export function isExportAccountBundleSuccess(response: ExportAccountBundleResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.ExportAccountBundle> {
+  return response.type === WorkerResponseType.ExportAccountBundleSuccess;
+}
+
+export function isImportAccountBundleSuccess(response: ImportAccountBundleResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.ImportAccountBundle> {
+  return response.type === WorkerResponseType.ImportAccountBundleSuccess;
+}
+
+export function isCreateSessionKeySuccess(response: CreateSessionKeyResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.CreateSessionKey> {
+  return response.type === WorkerResponseType.CreateSessionKeySuccess;
+}
+
+export function isSignWithSessionKeySuccess(response: SignWithSessionKeyResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.SignWithSessionKey> {
+  return response.type === WorkerResponseType.SignWithSessionKeySuccess;
+}
+
+export function isRevokeSessionKeySuccess(response: RevokeSessionKeyResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.RevokeSessionKey> {
+  return response.type === WorkerResponseType.RevokeSessionKeySuccess;
+}
+
+export function isCreateSigningIntentSuccess(response: CreateSigningIntentResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.CreateSigningIntent> {
+  return response.type === WorkerResponseType.CreateSigningIntentSuccess;
+}
+
+export function isExecuteSigningIntentSuccess(response: ExecuteSigningIntentResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.ExecuteSigningIntent> {
+  return response.type === WorkerResponseType.ExecuteSigningIntentSuccess;
+}
+
+export function isSubmitToRelayerSuccess(response: SubmitToRelayerResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.SubmitToRelayer> {
+  return response.type === WorkerResponseType.SubmitToRelayerSuccess;
+}
+
+export function isGetTelemetrySnapshotSuccess(response: GetTelemetrySnapshotResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.GetTelemetrySnapshot> {
+  return response.type === WorkerResponseType.GetTelemetrySnapshotSuccess;
+}
+
+export function isGetKeyUsageStatsSuccess(response: GetKeyUsageStatsResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.GetKeyUsageStats> {
+  return response.type === WorkerResponseType.GetKeyUsageStatsSuccess;
+}
+
+export function isPrepareRegistrationSuccess(response: PrepareRegistrationResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.PrepareRegistration> {
+  return response.type === WorkerResponseType.PrepareRegistrationSuccess;
+}
+
+export function isCompleteRegistrationSuccess(response: CompleteRegistrationResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.CompleteRegistration> {
+  return response.type === WorkerResponseType.CompleteRegistrationSuccess;
+}
+
+export function isCreateRemoteConfirmationSuccess(response: CreateRemoteConfirmationResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.CreateRemoteConfirmation> {
+  return response.type === WorkerResponseType.CreateRemoteConfirmationSuccess;
+}
+
+export function isApproveRemoteConfirmationSuccess(response: ApproveRemoteConfirmationResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.ApproveRemoteConfirmation> {
+  return response.type === WorkerResponseType.ApproveRemoteConfirmationSuccess;
+}
+
+export function isCompleteRemoteConfirmationSuccess(response: CompleteRemoteConfirmationResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.CompleteRemoteConfirmation> {
+  return response.type === WorkerResponseType.CompleteRemoteConfirmationSuccess;
+}
+
+export function isGetWorkerInfoSuccess(response: GetWorkerInfoResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.GetWorkerInfo> {
+  return response.type === WorkerResponseType.GetWorkerInfoSuccess;
+}
+
+export function isValidateDecryptionCapabilitySuccess(response: ValidateDecryptionCapabilityResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.ValidateDecryptionCapability> {
+  return response.type === WorkerResponseType.ValidateDecryptionCapabilitySuccess;
+}
+
+export function isListActiveAccountsSuccess(response: ListActiveAccountsResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.ListActiveAccounts> {
+  return response.type === WorkerResponseType.ListActiveAccountsSuccess;
+}
+
+export function isWipeAccountStateSuccess(response: WipeAccountStateResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.WipeAccountState> {
+  return response.type === WorkerResponseType.WipeAccountStateSuccess;
+}
+
+export function isValidateArgsSchemasSuccess(response: ValidateArgsSchemasResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.ValidateArgsSchemas> {
+  return response.type === WorkerResponseType.ValidateArgsSchemasSuccess;
+}
+
+export function isSummarizeTransactionsSuccess(response: SummarizeTransactionsResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.SummarizeTransactions> {
+  return response.type === WorkerResponseType.SummarizeTransactionsSuccess;
+}
+
+export function isMigrateEncryptedBlobsSuccess(response: MigrateEncryptedBlobsResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.MigrateEncryptedBlobs> {
+  return response.type === WorkerResponseType.MigrateEncryptedBlobsSuccess;
+}
+
+export function isBuildCredentialCreationOptionsSuccess(response: BuildCredentialCreationOptionsResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.BuildCredentialCreationOptions> {
+  return response.type === WorkerResponseType.BuildCredentialCreationOptionsSuccess;
+}
+
+export function isBuildCredentialRequestOptionsSuccess(response: BuildCredentialRequestOptionsResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.BuildCredentialRequestOptions> {
+  return response.type === WorkerResponseType.BuildCredentialRequestOptionsSuccess;
+}
+
+export function isRecoverPendingOperationsSuccess(response: RecoverPendingOperationsResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.RecoverPendingOperations> {
+  return response.type === WorkerResponseType.RecoverPendingOperationsSuccess;
+}
+
+export function isComposeCreateSubaccountSuccess(response: ComposeCreateSubaccountResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.ComposeCreateSubaccount> {
+  return response.type === WorkerResponseType.ComposeCreateSubaccountSuccess;
+}
+
+export function isStartBackgroundRefreshSuccess(response: StartBackgroundRefreshResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.StartBackgroundRefresh> {
+  return response.type === WorkerResponseType.StartBackgroundRefreshSuccess;
+}
+
+export function isStopBackgroundRefreshSuccess(response: StopBackgroundRefreshResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.StopBackgroundRefresh> {
+  return response.type === WorkerResponseType.StopBackgroundRefreshSuccess;
+}
+
+export function isBackgroundRefreshStatusSuccess(response: BackgroundRefreshStatusResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.BackgroundRefreshStatus> {
+  return response.type === WorkerResponseType.BackgroundRefreshStatusSuccess;
+}
+
+export function isSuspendHintSuccess(response: SuspendHintResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.SuspendHint> {
+  return response.type === WorkerResponseType.SuspendHintSuccess;
+}
+
+export function isResumeHintSuccess(response: ResumeHintResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.ResumeHint> {
+  return response.type === WorkerResponseType.ResumeHintSuccess;
+}
+
+export function isDeriveSubKeySuccess(response: DeriveSubKeyResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.DeriveSubKey> {
+  return response.type === WorkerResponseType.DeriveSubKeySuccess;
+}
+
+export function isViewCallSuccess(response: ViewCallResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.ViewCall> {
+  return response.type === WorkerResponseType.ViewCallSuccess;
+}
+
+export function isDumpDiagnosticsSuccess(response: DumpDiagnosticsResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.DumpDiagnostics> {
+  return response.type === WorkerResponseType.DumpDiagnosticsSuccess;
+}
+
+export function isSignTransactionsWithFreshChallengeSuccess(response: SignTransactionsWithFreshChallengeResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.SignTransactionsWithFreshChallenge> {
+  return response.type === WorkerResponseType.SignTransactionsWithFreshChallengeSuccess;
+}
+
+export function isGetSessionStatusSuccess(response: GetSessionStatusResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.GetSessionStatus> {
+  return response.type === WorkerResponseType.GetSessionStatusSuccess;
+}
+
+export function isBuildRedirectPayloadSuccess(response: BuildRedirectPayloadResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.BuildRedirectPayload> {
+  return response.type === WorkerResponseType.BuildRedirectPayloadSuccess;
+}
+
+export function isIssueElevationChallengeSuccess(response: IssueElevationChallengeResponse): response is WorkerSuccessResponse<typeof WorkerRequestType.IssueElevationChallenge> {
+  return response.type === WorkerResponseType.IssueElevationChallengeSuccess;
+}
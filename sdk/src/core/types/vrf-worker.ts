@@ -7,26 +7,62 @@ import { StripFree } from "./index.js";
 
 export type WasmGenerateVrfKeypairBootstrapRequest = StripFree<wasmModule.GenerateVrfKeypairBootstrapRequest>;
 export type WasmGenerateVrfChallengeRequest = StripFree<wasmModule.GenerateVrfChallengeRequest>;
-export type WasmUnlockVrfKeypairRequest = StripFree<wasmModule.UnlockVrfKeypairRequest>;
+export type WasmGenerateVrfChallengesBatchRequest = StripFree<wasmModule.GenerateVrfChallengesBatchRequest>;
+export type WasmUnlockVrfKeypairRequest = StripFree<wasmModule.UnlockVrfKeypairRequest> & {
+  scopes?: VrfScope[];
+};
 export type WasmDeriveVrfKeypairFromPrfRequest = StripFree<wasmModule.DeriveVrfKeypairFromPrfRequest>;
+export type WasmRestoreSessionSnapshotRequest = StripFree<wasmModule.RestoreSessionSnapshotRequest>;
+export type WasmUpdateBlockInfoRequest = StripFree<wasmModule.UpdateBlockInfoRequest>;
+export type WasmConfigureSessionOptionsRequest = StripFree<wasmModule.ConfigureSessionOptionsRequest>;
+export type WasmUpdateSessionPolicyRequest = StripFree<wasmModule.UpdateSessionPolicyRequest>;
 
 export type WasmShamir3PassConfigPRequest = StripFree<wasmModule.Shamir3PassConfigPRequest>;
 export type WasmShamir3PassConfigServerUrlsRequest = StripFree<wasmModule.Shamir3PassConfigServerUrlsRequest>;
 export type WasmShamir3PassClientEncryptCurrentVrfKeypairRequest = StripFree<wasmModule.Shamir3PassClientEncryptCurrentVrfKeypairRequest>;
-export type WasmShamir3PassClientDecryptVrfKeypairRequest = StripFree<wasmModule.Shamir3PassClientDecryptVrfKeypairRequest>;
+export type WasmShamir3PassClientDecryptVrfKeypairRequest = StripFree<wasmModule.Shamir3PassClientDecryptVrfKeypairRequest> & {
+  scopes?: VrfScope[];
+};
 
 export type WasmVrfWorkerRequestType = WasmGenerateVrfKeypairBootstrapRequest
   | WasmGenerateVrfChallengeRequest
+  | WasmGenerateVrfChallengesBatchRequest
   | WasmUnlockVrfKeypairRequest
   | WasmDeriveVrfKeypairFromPrfRequest
+  | WasmRestoreSessionSnapshotRequest
+  | WasmUpdateBlockInfoRequest
+  | WasmConfigureSessionOptionsRequest
+  | WasmUpdateSessionPolicyRequest
   | WasmShamir3PassConfigPRequest
   | WasmShamir3PassConfigServerUrlsRequest
   | WasmShamir3PassClientEncryptCurrentVrfKeypairRequest
-  | WasmShamir3PassClientDecryptVrfKeypairRequest;
+  | WasmShamir3PassClientDecryptVrfKeypairRequest
+  | VrfSnapshotRevocationState;
 
 import { AccountId } from "./accountIds.js";
+import type { ErrorCategory } from "./signer-worker.js";
 import { base64UrlDecode, base64UrlEncode } from "../../utils/encoders.js";
 
+/**
+ * Freshness anchor bound into the VRF input.
+ * NearBlock is the default, checked by the contract against recent blocks;
+ * Generic covers other randomness beacons (e.g. drand), with `label` naming the source.
+ */
+export type VrfAnchor =
+  | { type: 'NearBlock'; blockHeight: string; blockHash: string }
+  | { type: 'Generic'; label: string; valueB64u: string };
+
+/**
+ * What an unlocked VRF keypair may be used for (mirrors Rust VrfScope). An unlock without
+ * scopes grants all of them; granting more than an unlock did takes another unlock.
+ * - challengeGeneration: VRF challenges, including prefetched and peer-port ones
+ * - rotation: re-encrypting the keypair under the current Shamir 3-pass server key
+ * - export: session snapshots
+ * - deviceLinking: re-encrypting the keypair under another credential's PRF output
+ * Operations outside them fail with ScopeNotGranted.
+ */
+export type VrfScope = 'challengeGeneration' | 'rotation' | 'export' | 'deviceLinking';
+
 export interface VRFChallenge {
   vrfInput: string;
   vrfOutput: string;
@@ -34,18 +70,57 @@ export interface VRFChallenge {
   vrfPublicKey: string;
   userId: string;
   rpId: string;
+  /** Empty for generic anchors */
   blockHeight: string;
+  /** Empty for generic anchors */
   blockHash: string;
+  /** Anchor echoed by the VRF worker (absent on challenges predating anchors) */
+  anchor?: VrfAnchor;
+  /**
+   * Issuance receipt for the integrator's backend, when the session was configured with
+   * `issuanceReceiptKeyB64u`; checked with the VRF worker's verify_issuance_receipt
+   */
+  issuanceReceiptB64u?: string;
+  /**
+   * Bytes of the WebAuthn challenge derived from vrfOutput (16 to 64); absent means
+   * DEFAULT_CHALLENGE_LENGTH, the raw output prefix the contract verifies
+   */
+  challengeLength?: number;
 }
 
+/** WebAuthn challenge length the contract verifies (mirrors Rust DEFAULT_CHALLENGE_LENGTH) */
+export const DEFAULT_CHALLENGE_LENGTH = 32;
+export const MIN_CHALLENGE_LENGTH = 16;
+export const MAX_CHALLENGE_LENGTH = 64;
+
 /**
- * Decode VRF output and use first 32 bytes as WebAuthn challenge
+ * WebAuthn challenge of a VRF challenge, derived like Rust `derive_webauthn_challenge` (which
+ * the signer worker checks clientDataJSON against):
+ * - the default length is the raw VRF output prefix
+ * - other lengths up to 32 bytes are a prefix of SHA-256(vrfOutput)
+ * - longer lengths are a prefix of SHA-512(vrfOutput)
  * @param vrfChallenge - VRF challenge object
- * @returns 32-byte Uint8Array
+ * @returns challengeLength bytes
  */
-export function outputAs32Bytes(vrfChallenge: VRFChallenge): Uint8Array {
-  let vrfOutputBytes = base64UrlDecode(vrfChallenge.vrfOutput);
-  return vrfOutputBytes.slice(0, 32);
+export async function deriveWebAuthnChallenge(vrfChallenge: VRFChallenge): Promise<Uint8Array> {
+  const length = vrfChallenge.challengeLength ?? DEFAULT_CHALLENGE_LENGTH;
+  if (!Number.isInteger(length) || length < MIN_CHALLENGE_LENGTH || length > MAX_CHALLENGE_LENGTH) {
+    throw new Error(
+      `challengeLength must be between ${MIN_CHALLENGE_LENGTH} and ${MAX_CHALLENGE_LENGTH} bytes, got ${length}`
+    );
+  }
+  const vrfOutputBytes = base64UrlDecode(vrfChallenge.vrfOutput);
+  if (length === DEFAULT_CHALLENGE_LENGTH) {
+    if (vrfOutputBytes.length < length) {
+      throw new Error(`VRF output too short: ${vrfOutputBytes.length} bytes`);
+    }
+    return vrfOutputBytes.slice(0, length);
+  }
+  const digest = await crypto.subtle.digest(
+    length <= 32 ? 'SHA-256' : 'SHA-512',
+    vrfOutputBytes as BufferSource,
+  );
+  return new Uint8Array(digest).slice(0, length);
 }
 
 /**
@@ -62,6 +137,9 @@ export function validateVRFChallenge(vrfChallengeData: {
   rpId: string;
   blockHeight: string;
   blockHash: string;
+  anchor?: VrfAnchor;
+  issuanceReceiptB64u?: string;
+  challengeLength?: number;
 }): VRFChallenge {
   if (!vrfChallengeData.vrfInput || typeof vrfChallengeData.vrfInput !== 'string') {
     throw new Error('vrfInput must be a non-empty string');
@@ -81,11 +159,22 @@ export function validateVRFChallenge(vrfChallengeData: {
   if (!vrfChallengeData.rpId || typeof vrfChallengeData.rpId !== 'string') {
     throw new Error('rpId must be a non-empty string');
   }
-  if (!vrfChallengeData.blockHeight || typeof vrfChallengeData.blockHeight !== 'string') {
-    throw new Error('blockHeight must be a non-empty string');
+  // Generic anchors carry no NEAR block
+  if (vrfChallengeData.anchor?.type !== 'Generic') {
+    if (!vrfChallengeData.blockHeight || typeof vrfChallengeData.blockHeight !== 'string') {
+      throw new Error('blockHeight must be a non-empty string');
+    }
+    if (!vrfChallengeData.blockHash || typeof vrfChallengeData.blockHash !== 'string') {
+      throw new Error('blockHash must be a non-empty string');
+    }
   }
-  if (!vrfChallengeData.blockHash || typeof vrfChallengeData.blockHash !== 'string') {
-    throw new Error('blockHash must be a non-empty string');
+  const challengeLength = vrfChallengeData.challengeLength;
+  if (challengeLength != null && (
+    !Number.isInteger(challengeLength)
+    || challengeLength < MIN_CHALLENGE_LENGTH
+    || challengeLength > MAX_CHALLENGE_LENGTH
+  )) {
+    throw new Error(`challengeLength must be an integer between ${MIN_CHALLENGE_LENGTH} and ${MAX_CHALLENGE_LENGTH}`);
   }
 
   return {
@@ -97,6 +186,11 @@ export function validateVRFChallenge(vrfChallengeData: {
     rpId: vrfChallengeData.rpId,
     blockHeight: vrfChallengeData.blockHeight,
     blockHash: vrfChallengeData.blockHash,
+    ...(vrfChallengeData.anchor ? { anchor: vrfChallengeData.anchor } : {}),
+    ...(vrfChallengeData.issuanceReceiptB64u
+      ? { issuanceReceiptB64u: vrfChallengeData.issuanceReceiptB64u }
+      : {}),
+    ...(challengeLength != null ? { challengeLength } : {}),
   };
 }
 
@@ -129,6 +223,8 @@ export interface VrfWorkerManagerConfig {
   relayServerUrl?: string;
   applyServerLockRoute?: string;
   removeServerLockRoute?: string;
+  // Release manifest hash of the worker wasm; a worker whose module hashes differently is refused
+  expectedWasmSha256B64u?: string;
 }
 
 // Define interfaces that are missing
@@ -136,18 +232,69 @@ export interface VRFWorkerStatus {
   active: boolean;
   nearAccountId: AccountId | null;
   sessionDuration?: number;
+  /** Granted by the unlock (empty when locked) */
+  scopes?: VrfScope[];
 }
 
 export interface EncryptedVRFKeypair {
   encryptedVrfDataB64u: string;
   chacha20NonceB64u: string;
+  /** Authenticated with the ciphertext; absent on keypairs stored before it (v1) */
+  meta?: VrfKeypairBlobMeta;
+}
+
+/** What an EncryptedVRFKeypair belongs to (mirrors Rust VrfKeypairBlobMeta) */
+export interface VrfKeypairBlobMeta {
+  accountId: AccountId;
+  credentialIdB64u?: string;
+  deviceNumber?: number;
+  createdAtMs: number;
+  derivationVersion: number;
+}
+
+/**
+ * describe_encrypted_blob result. `meta` is what the blob claims: unlocking fails if it
+ * was edited, and refuses a blob whose accountId is not the account unlocking.
+ */
+export interface BlobMetadata {
+  /** 2 with meta, 1 without */
+  version: number;
+  meta?: VrfKeypairBlobMeta;
 }
 
+/**
+ * VRF input parameters. Give either `anchor`, or the flat blockHeight/blockHash
+ * (equivalent to a NearBlock anchor).
+ */
 export interface VRFInputData {
   userId: string;
   rpId: string;
-  blockHeight: string;
-  blockHash: string;
+  blockHeight?: string;
+  blockHash?: string;
+  anchor?: VrfAnchor;
+}
+
+/**
+ * Normalize VRF input data for the VRF worker: an explicit anchor is passed through,
+ * flat block fields are sent as before
+ */
+export function toVrfInputPayload(input: VRFInputData): VRFInputData {
+  if (input.anchor) {
+    return { userId: input.userId, rpId: input.rpId, anchor: input.anchor };
+  }
+  return {
+    userId: input.userId,
+    rpId: input.rpId,
+    blockHeight: String(input.blockHeight),
+    blockHash: input.blockHash,
+  };
+}
+
+/** Whether `input` has everything needed to build a VRF input */
+export function hasCompleteVrfInput(input?: VRFInputData): input is VRFInputData {
+  return !!(input?.userId
+    && input?.rpId
+    && (input.anchor || (input.blockHash && input.blockHeight)));
 }
 
 export interface VRFWorkerMessage<T extends WasmVrfWorkerRequestType> {
@@ -165,6 +312,20 @@ export interface VRFWorkerMessage<T extends WasmVrfWorkerRequestType> {
       | 'SHAMIR3PASS_REMOVE_SERVER_LOCK_KEK' // server only
       | 'SHAMIR3PASS_CONFIG_P'
       | 'SHAMIR3PASS_CONFIG_SERVER_URLS'
+      | 'VALIDATE_ENCRYPTED_BLOBS'
+      | 'EXPORT_SESSION_SNAPSHOT'
+      | 'RESTORE_SESSION_SNAPSHOT'
+      | 'WIPE_ALL_STATE'
+      | 'GENERATE_VRF_CHALLENGES_BATCH'
+      | 'RUN_SELF_TEST'
+      | 'UPDATE_BLOCK_INFO'
+      | 'CONFIGURE_SESSION_OPTIONS'
+      | 'CONNECT_PEER_PORT' // transfers the signer worker's port (event.ports[0])
+      | 'REQUEST_CHALLENGE' // signer worker only, over its port
+      | 'SUSPEND_HINT' // pagehide
+      | 'RESUME_HINT' // pageshow
+      | 'LOAD_SNAPSHOT_REVOCATION_STATE' // right after the worker starts
+      | 'UPDATE_SESSION_POLICY' // the signer session's epoch and maxSessionDurationMs
   id?: string;
   payload?: T;
 }
@@ -174,8 +335,122 @@ export interface VRFWorkerResponse {
   success: boolean;
   data?: any;
   error?: string;
+  /** Catalogued code of `error` (see `lookup_error_code`) */
+  errorCode?: string;
+  /** Set on failures; 'Internal' when there is no code */
+  errorCategory?: ErrorCategory;
+  retriable?: boolean;
+}
+
+/** Build capabilities reported in the PING response */
+export interface VrfWorkerInfo {
+  /** 'wasm-threads' when compiled in; 'parallel-batch-proofs' once the thread pool is running */
+  features: string[];
+  threadCount: number;
+  buildInfo?: VrfWorkerBuildInfo;
+}
+
+/** What the running VRF worker was built from (workerInfo.buildInfo) */
+export interface VrfWorkerBuildInfo {
+  crateVersion: string;
+  /** HEAD at build time, '-dirty' for uncommitted changes */
+  gitCommit: string;
+  profile: string;
+  target: string;
+  cryptoDependencies: Array<{ name: string; version: string; checksum?: string }>;
+  /** Attestation hash of the instantiated module, once the worker has checked it */
+  wasmSha256B64u?: string;
+  attestation?: VrfBuildAttestationReport;
+}
+
+/**
+ * Result of verify_build_attestation. The attestation hash is the SHA-256 of the wasm with its
+ * `w3a_build_attestation` custom sections removed; the release build embeds it in that section.
+ */
+export interface VrfBuildAttestationReport {
+  matches: boolean;
+  actualSha256B64u: string;
+  expectedSha256B64u?: string;
+  embeddedSha256B64u?: string;
+  mismatches: string[];
+}
+
+/** Reported with GENERATE_VRF_CHALLENGE results */
+export interface VrfChallengeTimings {
+  /** Served from the challenge prefetched for a recent block (CONFIGURE_SESSION_OPTIONS) */
+  usedPrefetchedChallenge: boolean;
+  durationMs: number;
+}
+
+/** One GENERATE_VRF_CHALLENGES_BATCH item, in input order */
+export interface VrfChallengeBatchItem {
+  index: number;
+  success: boolean;
+  challenge?: VRFChallenge;
+  error?: string;
+}
+
+export interface VrfChallengeBatchResult {
+  results: VrfChallengeBatchItem[];
+  /** Whether proofs were spread over the thread pool */
+  parallel: boolean;
+  threadCount: number;
 }
 
+/**
+ * Encrypted VRF session exported by EXPORT_SESSION_SNAPSHOT.
+ * Restorable once, within 5 minutes of export, and only until the worker's next WIPE_ALL_STATE.
+ * A restarted worker restores it only after LOAD_SNAPSHOT_REVOCATION_STATE. Not restorable
+ * once its session passes autoLockAfterMs or maxSessionDurationMs, or in a later signer
+ * session epoch (UPDATE_SESSION_POLICY).
+ */
+export interface VrfSessionSnapshot {
+  version: number;
+  snapshotId: string;
+  epoch: number;
+  exportedAtMs: number;
+  nonceB64u: string;
+  ciphertextB64u: string;
+}
+
+export interface VrfSessionSnapshotExport {
+  snapshot: VrfSessionSnapshot;
+  /** One-time snapshot key; keep it in memory only, never persisted next to the snapshot */
+  token: string;
+  expiresAtMs: number;
+  revocationState: VrfSnapshotRevocationState;
+}
+
+/**
+ * Worker epoch and already restored snapshots, returned by EXPORT_SESSION_SNAPSHOT,
+ * RESTORE_SESSION_SNAPSHOT and WIPE_ALL_STATE. A new worker starts from a random epoch that
+ * revokes every snapshot; LOAD_SNAPSHOT_REVOCATION_STATE (once per worker) carries the last
+ * state over, so wiped and restored snapshots stay refused across restarts.
+ */
+export interface VrfSnapshotRevocationState {
+  epoch: number;
+  consumedSnapshots: Array<{ snapshotId: string; exportedAtMs: number }>;
+}
+
+/** What RESUME_HINT found stale after the page was hidden */
+export type VrfResumeInvalidation = 'prefetchedChallenge' | 'blockInfo' | 'sessionLocked';
+
+/** RESUME_HINT result: lets the UI refresh what went stale while the tab was frozen */
+export interface VrfResumedState {
+  /** Wall-clock time since SUSPEND_HINT (0 without one) */
+  suspendedMs: number;
+  invalidated: VrfResumeInvalidation[];
+  /** False once autoLockAfterMs passed, freeze included */
+  sessionActive: boolean;
+}
+
+/** Error codes prefixed to RESTORE_SESSION_SNAPSHOT failures */
+export type VrfSessionSnapshotErrorCode =
+  | 'SessionSnapshotExpired'
+  | 'SessionSnapshotReused'
+  | 'SessionSnapshotRevoked'
+  | 'SessionSnapshotInvalid';
+
 export interface VRFKeypairBootstrapResponse {
   vrfPublicKey: string;
   vrfChallengeData?: VRFChallenge;
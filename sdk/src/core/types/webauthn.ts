@@ -24,6 +24,9 @@ export interface WebAuthnAuthenticationCredential {
     authenticatorData: string; // base64url-encoded
     signature: string; // base64url-encoded
     userHandle: string | undefined; // base64url-encoded or undefined
+    // COSE public key registration stored for the credential (base64url), when known.
+    // Lets the signer worker verify the assertion locally before contract verification.
+    credentialPublicKey?: string;
   };
   // Dual PRF outputs extracted in main thread just before transferring to worker
   clientExtensionResults: AuthenticationExtensionsClientOutputs;
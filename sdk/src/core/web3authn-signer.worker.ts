@@ -38,6 +38,12 @@ import {
   WorkerRequestType,
   WorkerResponseType,
   WasmRequestPayload,
+  SIGNER_WORKER_INITIALIZE,
+  SIGNER_WORKER_INITIALIZED,
+  SIGNER_WORKER_CONNECT_PEER_PORT,
+  SIGNER_WORKER_CONNECT_CONFIRMATION_PORT,
+  type SelfTestReport,
+  type SignerPeerSession,
 } from './types/signer-worker';
 // Import WASM binary directly
 import init, * as wasmModule from '../wasm_signer_worker/pkg/wasm_signer_worker.js';
@@ -56,12 +62,83 @@ import { errorMessage } from '../utils/errors';
 
 // Resolve WASM URL using the centralized resolution strategy
 const wasmUrl = resolveWasmUrl('wasm_signer_worker_bg.wasm', 'Signer Worker');
-const { handle_signer_message } = wasmModule;
-import { awaitSecureConfirmationV2 } from './WebAuthnManager/SignerWorkerManager/confirmTxFlow/awaitSecureConfirmation';
+const {
+  handle_signer_message,
+  handle_signer_message_cbor,
+  initialize_signer_worker,
+  signer_session_public_key,
+  connect_peer_port,
+  load_key_usage_record,
+  load_worker_state,
+  configure_message_size_limits,
+  configure_trusted_confirmation_origin,
+  configure_known_authenticators,
+} = wasmModule;
+import {
+  awaitSecureConfirmationV2,
+  setConfirmationPort,
+} from './WebAuthnManager/SignerWorkerManager/confirmTxFlow/awaitSecureConfirmation';
 import { SecureConfirmMessageType } from './WebAuthnManager/SignerWorkerManager/confirmTxFlow/types';
+import {
+  SigningHookMessageType,
+  isPreSignHookResponse,
+} from './WebAuthnManager/SignerWorkerManager/signingHooks';
+import { decodeCbor } from './WebAuthnManager/SignerWorkerManager/cborWire';
+import { OperationJournalMessageType } from './WebAuthnManager/SignerWorkerManager/operationJournal';
 
 let messageProcessed = false;
 
+// Outer-wrap CryptoKey posted with the request (or the INITIALIZE handshake)
+let outerWrapKey: CryptoKey | undefined;
+// CryptoKey wrapping the state key of the worker state record, posted alongside it
+let workerStateWrapKey: CryptoKey | undefined;
+
+/** Loads the key usage record posted with the request (or the INITIALIZE handshake) */
+function loadKeyUsageRecord(record: unknown): void {
+  if (!record) return;
+  try {
+    load_key_usage_record(JSON.stringify(record));
+  } catch (error: any) {
+    // Stats only: a rejected record starts them over rather than failing the request
+    console.warn('[signer-worker]: Key usage record not loaded:', errorMessage(error));
+  }
+}
+
+/**
+ * Loads the worker state record posted with the request (or the INITIALIZE handshake). A
+ * record that does not open is discarded by Rust; only a missing WebCrypto fails here.
+ */
+async function loadWorkerState(wrapKey: unknown, record: unknown): Promise<void> {
+  workerStateWrapKey = wrapKey instanceof CryptoKey ? wrapKey : undefined;
+  await load_worker_state(record ? JSON.stringify(record) : undefined);
+}
+
+/** Applies the manager's message size limits before the request frame reaches Rust */
+function applyMessageSizeLimits(limits: unknown): void {
+  if (!limits) return;
+  configure_message_size_limits(limits);
+}
+
+/**
+ * Fixes the origin confirmation decisions must attest (WorkerPolicy.trustedConfirmationOrigin);
+ * attestations are only taken from the confirmation port
+ */
+function applyTrustedConfirmationOrigin(origin: unknown): void {
+  if (typeof origin !== 'string' || !origin) return;
+  configure_trusted_confirmation_origin(origin);
+}
+
+/** Adds the manager's AAGUID entries to the worker's table of known authenticators */
+function applyKnownAuthenticators(entries: unknown): void {
+  if (!Array.isArray(entries) || entries.length === 0) return;
+  configure_known_authenticators(entries);
+}
+
+// Port to the VRF worker and the session it was connected for (CONNECT_PEER_PORT)
+let peerPort: MessagePort | undefined;
+let peerSession: SignerPeerSession | undefined;
+let peerRequestCounter = 0;
+
 /**
  * Function called by WASM to send progress messages
  * This is imported into the WASM module as sendProgressMessage
@@ -130,6 +207,114 @@ function sendProgressMessage(
 // Expose the worker bridge for WASM to call (V2 only)
 (globalThis as any).awaitSecureConfirmationV2 = awaitSecureConfirmationV2;
 
+/**
+ * Bridge called by WASM (src/hooks.rs) to ask the main thread's pre-sign hook for a decision.
+ * Resolves with a JSON string; a timeout resolves as a denial (fail closed).
+ */
+function awaitPreSignHook(requestJson: string, timeoutMs: number): Promise<string> {
+  return new Promise((resolve) => {
+    const request = safeJsonParse(requestJson, {});
+    let timeoutId: ReturnType<typeof setTimeout> | undefined;
+    const finish = (response: { allow: boolean; reasonCode?: string }) => {
+      try { if (timeoutId) clearTimeout(timeoutId); } catch {}
+      self.removeEventListener('message', onResponse);
+      resolve(JSON.stringify(response));
+    };
+    const onResponse = (event: MessageEvent) => {
+      if (event?.data?.type !== SigningHookMessageType.PRE_SIGN_HOOK_RESPONSE) return;
+      const data = event.data.data;
+      if (!isPreSignHookResponse(data) || data.requestId !== request.requestId) return;
+      finish({ allow: data.allow, reasonCode: data.reasonCode });
+    };
+    self.addEventListener('message', onResponse);
+    timeoutId = setTimeout(() => finish({ allow: false, reasonCode: 'HookTimeout' }), timeoutMs);
+    try {
+      self.postMessage({ type: SigningHookMessageType.PRE_SIGN_HOOK_REQUEST, data: request });
+    } catch (error: any) {
+      console.error('[signer-worker]: Failed to post pre-sign hook request:', error);
+      finish({ allow: false, reasonCode: 'HookUnavailable' });
+    }
+  });
+}
+
+/**
+ * Bridge called by WASM after each signed transaction (fire-and-forget)
+ */
+function notifyPostSignHook(notificationJson: string): void {
+  try {
+    self.postMessage({
+      type: SigningHookMessageType.POST_SIGN_HOOK_NOTIFICATION,
+      data: safeJsonParse(notificationJson, {}),
+    });
+  } catch (error: any) {
+    console.warn('[signer-worker]: Failed to post post-sign hook notification:', error);
+  }
+}
+
+(globalThis as any).awaitPreSignHook = awaitPreSignHook;
+(globalThis as any).notifyPostSignHook = notifyPostSignHook;
+
+/**
+ * Bridge called by WASM (src/operation_journal.rs) right before a broadcast or relayer
+ * submission; the main thread persists the entry until the request succeeds
+ */
+function recordJournalEntry(entryJson: string): void {
+  try {
+    self.postMessage({
+      type: OperationJournalMessageType.OPERATION_JOURNAL_ENTRY,
+      data: safeJsonParse(entryJson, {}),
+    });
+  } catch (error: any) {
+    console.warn('[signer-worker]: Failed to post journal entry:', error);
+  }
+}
+
+(globalThis as any).recordJournalEntry = recordJournalEntry;
+
+// Bridge called by WASM (src/outer_wrap.rs): undefined when no outer-wrap key was given
+(globalThis as any).getOuterWrapKey = () => outerWrapKey;
+
+// Bridge called by WASM (src/worker_state.rs): undefined when no state wrap key was given
+(globalThis as any).getWorkerStateWrapKey = () => workerStateWrapKey;
+
+/**
+ * Bridge called by WASM (src/peer_channel.rs): sends REQUEST_CHALLENGE to the VRF worker over
+ * the peer port and resolves with its response as JSON. Never rejects: a missing port or a
+ * timeout resolves with a failed response.
+ */
+function requestPeerVrfChallenge(requestJson: string, timeoutMs: number): Promise<string> {
+  const port = peerPort;
+  if (!port) {
+    return Promise.resolve(JSON.stringify({ success: false, error: 'No peer port connected' }));
+  }
+  const id = `peer-challenge-${++peerRequestCounter}`;
+  return new Promise((resolve) => {
+    const finish = (response: unknown) => {
+      clearTimeout(timer);
+      port.removeEventListener('message', onResponse);
+      resolve(JSON.stringify(response));
+    };
+    const onResponse = (event: MessageEvent) => {
+      if (event.data?.id === id) finish(event.data);
+    };
+    const timer = setTimeout(() => {
+      finish({ success: false, error: `No VRF challenge within ${timeoutMs}ms` });
+    }, timeoutMs);
+    port.addEventListener('message', onResponse);
+    port.start();
+    port.postMessage({ type: 'REQUEST_CHALLENGE', id, payload: JSON.parse(requestJson) });
+  });
+}
+
+function closePeerPort(): void {
+  peerPort?.close();
+  peerPort = undefined;
+  peerSession = undefined;
+}
+
+(globalThis as any).requestPeerVrfChallenge = requestPeerVrfChallenge;
+(globalThis as any).closePeerPort = closePeerPort;
+
 /**
  * Initialize WASM module
  */
@@ -181,6 +366,42 @@ function getFailureResponseType(requestType: WorkerRequestType): WorkerResponseT
   }
 }
 
+/**
+ * INITIALIZE handshake: fixes the wire format before the first request frame and runs the
+ * crypto self-test. Replies INITIALIZED with the self-test report and the session public key
+ * secrets are sealed to (or an error when the format is rejected).
+ */
+async function handleInitialize(event: MessageEvent): Promise<void> {
+  const wireFormat = (event.data as any)?.wireFormat ?? 'json';
+  outerWrapKey = (event.data as any)?.outerWrapKey;
+  try {
+    await initializeWasm();
+    const selfTest: SelfTestReport = initialize_signer_worker(wireFormat);
+    loadKeyUsageRecord((event.data as any)?.keyUsageRecord);
+    await loadWorkerState((event.data as any)?.workerStateWrapKey, (event.data as any)?.workerStateRecord);
+    applyMessageSizeLimits((event.data as any)?.messageSizeLimits);
+    applyTrustedConfirmationOrigin((event.data as any)?.trustedConfirmationOrigin);
+    applyKnownAuthenticators((event.data as any)?.knownAuthenticators);
+    if (!selfTest.passed) {
+      console.error('[signer-worker]: Self-test failed, key-handling requests will be refused:', selfTest);
+    }
+    const sessionPublicKey: string = signer_session_public_key();
+    self.postMessage({ type: SIGNER_WORKER_INITIALIZED, wireFormat, selfTest, sessionPublicKey });
+  } catch (error: any) {
+    console.error('[signer-worker]: Initialize failed:', error);
+    self.postMessage({ type: SIGNER_WORKER_INITIALIZED, wireFormat, error: errorMessage(error) });
+  }
+}
+
+/** Request type of a frame, for failure responses (oversized CBOR frames are not decoded) */
+function frameRequestType(data: unknown, error: unknown): unknown {
+  if (data instanceof Uint8Array) {
+    if (errorMessage(error).startsWith('MessageTooLarge')) return undefined;
+    try { return (decodeCbor(data) as any)?.type; } catch { return undefined; }
+  }
+  return (data as any)?.type;
+}
+
 /**
  * Process a WASM worker message (main operation)
  */
@@ -189,8 +410,34 @@ async function processWorkerMessage(event: MessageEvent): Promise<void> {
   try {
     // Initialize WASM
     await initializeWasm();
-    // Convert TypeScript message to JSON and pass to Rust
-    const messageJson = JSON.stringify(event.data);
+    if (peerSession) {
+      connect_peer_port(peerSession.expectedVrfPublicKey, peerSession.rpId);
+    }
+    if (event.data instanceof Uint8Array) {
+      // CBOR frame: Rust decodes the request and encodes the response
+      const responseFrame = await handle_signer_message_cbor(event.data);
+      self.postMessage(responseFrame, [responseFrame.buffer]);
+      self.close();
+      return;
+    }
+    // Convert TypeScript message to JSON and pass to Rust (the CryptoKey stays in JS)
+    const {
+      outerWrapKey: key,
+      keyUsageRecord,
+      workerStateWrapKey: stateWrapKey,
+      workerStateRecord,
+      messageSizeLimits,
+      trustedConfirmationOrigin,
+      knownAuthenticators,
+      ...message
+    } = event.data;
+    outerWrapKey = key;
+    loadKeyUsageRecord(keyUsageRecord);
+    await loadWorkerState(stateWrapKey, workerStateRecord);
+    applyMessageSizeLimits(messageSizeLimits);
+    applyTrustedConfirmationOrigin(trustedConfirmationOrigin);
+    applyKnownAuthenticators(knownAuthenticators);
+    const messageJson = JSON.stringify(message);
     // Call the Rust message handler
     const responseJson = await handle_signer_message(messageJson);
     // Parse response and send back to main thread
@@ -200,15 +447,16 @@ async function processWorkerMessage(event: MessageEvent): Promise<void> {
   } catch (error: any) {
     console.error('[signer-worker]: Message processing failed:', error);
     // Determine the correct failure response type based on the request type
-    const failureType = typeof event.data?.type === 'number'
-      ? getFailureResponseType(event.data.type)
+    const requestType = frameRequestType(event.data, error);
+    const failureType = typeof requestType === 'number'
+      ? getFailureResponseType(requestType)
       : WorkerResponseType.DeriveNearKeypairAndEncryptFailure; // Fallback for invalid requests
 
     self.postMessage({
       type: failureType,
       payload: {
         error: errorMessage(error),
-        context: { type: event.data.type }
+        context: { type: requestType }
       }
     });
     self.close();
@@ -231,6 +479,27 @@ self.onmessage = async (event: MessageEvent<SignerWorkerMessage<WorkerRequestTyp
 
   // Handle different message types explicitly
   switch (true) {
+    case !messageProcessed && eventType === SIGNER_WORKER_INITIALIZE:
+      // Wire format handshake - precedes the request frame
+      await handleInitialize(event);
+      break;
+
+    case !messageProcessed && eventType === SIGNER_WORKER_CONNECT_PEER_PORT:
+      // Port to the VRF worker - precedes the request frame
+      peerPort = event.ports[0];
+      peerSession = (event.data as any)?.payload as SignerPeerSession;
+      break;
+
+    case !messageProcessed && eventType === SIGNER_WORKER_CONNECT_CONFIRMATION_PORT:
+      // Confirmation origin bridge - precedes the request frame
+      setConfirmationPort(event.ports[0]);
+      break;
+
+    case !messageProcessed && (event.data as unknown) instanceof Uint8Array:
+      // CBOR request frame (worker initialized with wireFormat 'cbor')
+      await processWorkerMessage(event);
+      break;
+
     case !messageProcessed && typeof eventType === 'number':
       // Case 1: First message with numeric type - process as normal worker operation
       await processWorkerMessage(event);
@@ -247,6 +516,16 @@ self.onmessage = async (event: MessageEvent<SignerWorkerMessage<WorkerRequestTyp
       // to the existing addEventListener('message', onMainChannelDecision) listener in awaitSecureConfirmationV2
       break;
 
+    case eventType === SecureConfirmMessageType.COUNTDOWN_STARTED:
+    case eventType === SecureConfirmMessageType.CANCEL_CONFIRMATION:
+    case eventType === SecureConfirmMessageType.CONFIRMATION_BEHAVIOR_CHANGED:
+      // Auto-proceed countdown handshake - let it bubble to the awaitSecureConfirmationV2 listener
+      break;
+
+    case eventType === SigningHookMessageType.PRE_SIGN_HOOK_RESPONSE:
+      // Pre-sign hook decision - let it bubble to the awaitPreSignHook listener
+      break;
+
     case messageProcessed:
       // Case 4: Worker already processed initial message and this isn't a confirmation
       console.error('[signer-worker]: Invalid message - worker already processed initial message');
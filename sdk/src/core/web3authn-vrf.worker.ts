@@ -32,6 +32,9 @@ const { handle_message } = vrfWasmModule;
 let wasmReady = false;
 let messageQueue: MessageEvent[] = [];
 
+// Port to the signer worker (CONNECT_PEER_PORT); it may only send REQUEST_CHALLENGE
+let peerPort: MessagePort | null = null;
+
 // === WASM INITIALIZATION ===
 
 /**
@@ -39,9 +42,13 @@ let messageQueue: MessageEvent[] = [];
  */
 async function initializeWasmModule(): Promise<void> {
   try {
-    // Prefer explicit URL init so bundlers resolve the asset from node_modules
+    // Fetch the bytes ourselves (the URL is still resolved so bundlers find the asset in
+    // node_modules): the module that is instantiated is the one whose hash is attested.
     // Use new single-object signature to avoid deprecation warning
-    await init({ module_or_path: wasmUrl as any });
+    const wasmBytes = new Uint8Array(await (await fetch(wasmUrl)).arrayBuffer());
+    await init({ module_or_path: wasmBytes as any });
+    verifyBuildAttestation(wasmBytes);
+    await initThreadPoolIfSupported();
     // Mark WASM as ready and process any queued messages
     wasmReady = true;
     await processQueuedMessages();
@@ -58,6 +65,40 @@ async function initializeWasmModule(): Promise<void> {
   }
 }
 
+/**
+ * Hash the instantiated module against the hash embedded at build time. The report is
+ * returned with PING (workerInfo.buildInfo.attestation), where VrfWorkerManager compares it
+ * against the release manifest and refuses a mismatching worker.
+ */
+function verifyBuildAttestation(wasmBytes: Uint8Array): void {
+  try {
+    const report = vrfWasmModule.verify_build_attestation(undefined, wasmBytes);
+    if (!report?.matches) {
+      console.warn('[vrf-worker] WASM build attestation mismatch:', report?.mismatches);
+    }
+  } catch (error) {
+    console.warn('[vrf-worker] WASM build attestation failed:', error);
+  }
+}
+
+/**
+ * Start the thread pool for parallel batch proofs. Only builds with the `wasm-threads`
+ * feature export initThreadPool, and it needs SharedArrayBuffer (cross-origin isolation);
+ * otherwise batch proofs stay on the serial path.
+ */
+async function initThreadPoolIfSupported(): Promise<void> {
+  const { initThreadPool, mark_thread_pool_ready } = vrfWasmModule as any;
+  if (typeof initThreadPool !== 'function' || !(self as any).crossOriginIsolated) {
+    return;
+  }
+  try {
+    await initThreadPool(navigator.hardwareConcurrency || 2);
+    mark_thread_pool_ready();
+  } catch (error) {
+    console.warn('[vrf-worker] Thread pool unavailable, batch proofs run serially:', error);
+  }
+}
+
 // === MESSAGE HANDLING ===
 
 self.onmessage = async (event: MessageEvent) => {
@@ -92,8 +133,18 @@ async function handleMessage(event: MessageEvent): Promise<void> {
   }
 
   try {
+    if (data.type === 'CONNECT_PEER_PORT') {
+      if (!event.ports[0]) {
+        self.postMessage(createErrorResponse(data.id, 'CONNECT_PEER_PORT requires a transferred MessagePort'));
+        return;
+      }
+      connectPeerPort(event.ports[0]);
+    }
     // Call WASM handle_message with JavaScript object (async)
     const response = await handle_message(data) as VRFWorkerResponse;
+    if (data.type === 'WIPE_ALL_STATE') {
+      closePeerPort();
+    }
     // Send response back to main thread
     self.postMessage(response);
   } catch (error: unknown) {
@@ -104,6 +155,36 @@ async function handleMessage(event: MessageEvent): Promise<void> {
   }
 }
 
+// === PEER PORT ===
+
+/**
+ * Answer the signer worker's REQUEST_CHALLENGE messages directly, without the main thread.
+ * A new port replaces the previous one.
+ */
+function connectPeerPort(port: MessagePort): void {
+  closePeerPort();
+  peerPort = port;
+  port.onmessage = async (event: MessageEvent) => {
+    const data = event.data as VRFWorkerMessage<WasmVrfWorkerRequestType>;
+    if (data?.type !== 'REQUEST_CHALLENGE') {
+      port.postMessage(createErrorResponse(data?.id, `Unsupported peer message: ${data?.type}`));
+      return;
+    }
+    try {
+      port.postMessage(await handle_message(data) as VRFWorkerResponse);
+    } catch (error: unknown) {
+      port.postMessage(createErrorResponse(data.id, error));
+    }
+  };
+}
+
+function closePeerPort(): void {
+  if (!peerPort) return;
+  peerPort.onmessage = null;
+  peerPort.close();
+  peerPort = null;
+}
+
 // === ERROR HANDLING ===
 
 function createErrorResponse(
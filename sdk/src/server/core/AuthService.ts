@@ -1073,7 +1073,7 @@ export class AuthService {
     const signedTxs = response?.payload?.signedTransactions || [];
     if (!signedTxs.length) throw new Error('No signed transaction returned');
     const signed = signedTxs[0];
-    const borshBytes = signed?.borshBytes || signed?.borsh_bytes;
+    const borshBytes = signed?.borshBytes;
     if (!Array.isArray(borshBytes)) throw new Error('Missing borsh bytes');
     return { borshBytes };
   }
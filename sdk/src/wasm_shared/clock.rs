@@ -0,0 +1,122 @@
+// === CLOCK ===
+// Time source of the signer and VRF workers. This file is shared by both crates
+// (`#[path]`-included as `mod clock`). Every TTL, expiry and duration is read through a Clock
+// held in the worker's session state, so tests drive expiry by advancing a ManualClock instead
+// of sleeping.
+
+use std::rc::Rc;
+
+pub trait Clock {
+    /// Wall-clock milliseconds since the Unix epoch. Keeps counting while the page is frozen,
+    /// so TTLs measured with it include time spent in bfcache or a suspended tab.
+    fn now_ms(&self) -> f64;
+
+    /// Milliseconds from an arbitrary origin that never go backwards, for timing operations
+    fn monotonic_ms(&self) -> f64 {
+        self.now_ms()
+    }
+}
+
+/// The real clock: Date.now() and performance.now() in the worker, std::time natively
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[cfg(target_arch = "wasm32")]
+    fn now_ms(&self) -> f64 {
+        js_sys::Date::now()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn now_ms(&self) -> f64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as f64)
+            .unwrap_or(0.0)
+    }
+
+    /// performance.now() on the worker's global scope, Date.now() where it is missing
+    #[cfg(target_arch = "wasm32")]
+    fn monotonic_ms(&self) -> f64 {
+        use wasm_bindgen::{JsCast, JsValue};
+
+        let performance =
+            js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("performance")).ok();
+        performance
+            .filter(|p| p.is_object())
+            .and_then(|p| {
+                let now = js_sys::Reflect::get(&p, &JsValue::from_str("now"))
+                    .ok()?
+                    .dyn_into::<js_sys::Function>()
+                    .ok()?;
+                now.call0(&p).ok()?.as_f64()
+            })
+            .unwrap_or_else(js_sys::Date::now)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn monotonic_ms(&self) -> f64 {
+        use std::sync::OnceLock;
+        use std::time::Instant;
+
+        static ORIGIN: OnceLock<Instant> = OnceLock::new();
+        ORIGIN.get_or_init(Instant::now).elapsed().as_secs_f64() * 1000.0
+    }
+}
+
+/// The clock a worker's session state holds; the SystemClock unless a test installs another
+#[derive(Clone)]
+pub struct SharedClock(Rc<dyn Clock>);
+
+impl SharedClock {
+    pub fn new(clock: impl Clock + 'static) -> Self {
+        Self(Rc::new(clock))
+    }
+}
+
+impl Default for SharedClock {
+    fn default() -> Self {
+        Self::new(SystemClock)
+    }
+}
+
+impl Clock for SharedClock {
+    fn now_ms(&self) -> f64 {
+        self.0.now_ms()
+    }
+
+    fn monotonic_ms(&self) -> f64 {
+        self.0.monotonic_ms()
+    }
+}
+
+/// Test clock that only moves when told to. Clones share the same time, so a test keeps one
+/// to advance after installing another in the worker state.
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct ManualClock {
+    now_ms: Rc<std::cell::Cell<f64>>,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    pub fn new(now_ms: f64) -> Self {
+        Self {
+            now_ms: Rc::new(std::cell::Cell::new(now_ms)),
+        }
+    }
+
+    pub fn advance(&self, ms: f64) {
+        self.now_ms.set(self.now_ms.get() + ms);
+    }
+
+    pub fn set(&self, now_ms: f64) {
+        self.now_ms.set(now_ms);
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now_ms(&self) -> f64 {
+        self.now_ms.get()
+    }
+}
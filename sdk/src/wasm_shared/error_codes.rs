@@ -0,0 +1,1176 @@
+// === ERROR CODE CATALOG ===
+// Every error code the signer and VRF workers return, in one place. This file is shared by
+// both crates (`#[path]`-included as `mod error_codes`); each worker maps its own code enum
+// onto these definitions with an exhaustive match (SignerErrorCode::definition,
+// VrfErrorCode::definition), so a worker cannot return a code that is not catalogued here.
+//
+// Ids are stable: the hundreds digit is the category (1 UserAction, 2 Policy, 3 Crypto,
+// 4 Network, 5 Internal). Ids are never renumbered or reused; a new code takes the next free
+// id of its category. Serialized errors carry `errorCode`, `errorCategory` and `retriable`.
+//
+// Deprecation codes (end of file) are catalogued here too: they name paths that still work
+// but will be removed, and are reported in a response's `warnings` rather than as errors.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ErrorCategory {
+    /// The user cancelled, timed out, or has to act first (log in, use another passkey)
+    UserAction,
+    /// Refused by a configured rule, limit or expiry
+    Policy,
+    /// A key, signature or ciphertext did not verify
+    Crypto,
+    /// NEAR RPC or the relayer could not be reached or answered unexpectedly
+    Network,
+    /// Malformed requests and unexpected failures
+    Internal,
+}
+
+impl ErrorCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCategory::UserAction => "UserAction",
+            ErrorCategory::Policy => "Policy",
+            ErrorCategory::Crypto => "Crypto",
+            ErrorCategory::Network => "Network",
+            ErrorCategory::Internal => "Internal",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ErrorCodeDef {
+    pub code: &'static str,
+    pub id: u32,
+    pub category: ErrorCategory,
+    /// Whether sending the same request again can succeed
+    pub retriable: bool,
+    /// Default English message, for UIs without their own copy
+    pub message: &'static str,
+}
+
+impl ErrorCodeDef {
+    pub fn info(&self) -> ErrorCodeInfo {
+        ErrorCodeInfo {
+            code: self.code.to_string(),
+            id: self.id,
+            category: self.category.as_str().to_string(),
+            retriable: self.retriable,
+            default_message: self.message.to_string(),
+        }
+    }
+}
+
+/// Catalog entry returned to JS by `lookup_error_code`
+#[wasm_bindgen]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorCodeInfo {
+    #[wasm_bindgen(getter_with_clone)]
+    pub code: String,
+    pub id: u32,
+    #[wasm_bindgen(getter_with_clone)]
+    pub category: String,
+    pub retriable: bool,
+    #[wasm_bindgen(getter_with_clone, js_name = "defaultMessage")]
+    pub default_message: String,
+}
+
+/// Catalog entry of an error code (e.g. "DecryptionFailed")
+pub fn find_error_code(code: &str) -> Option<&'static ErrorCodeDef> {
+    CATALOG.iter().find(|def| def.code == code)
+}
+
+/// Catalog entry of the code an error message starts with ("DecryptionFailed: ...")
+pub fn error_code_of_message(message: &str) -> Option<&'static ErrorCodeDef> {
+    let (code, _) = message.split_once(':')?;
+    find_error_code(code)
+}
+
+/// `errorCategory` and `retriable` of a serialized error; errors without a catalogued code
+/// are Internal and not retriable
+pub fn category_and_retriable(def: Option<&ErrorCodeDef>) -> (ErrorCategory, bool) {
+    def.map_or((ErrorCategory::Internal, false), |def| {
+        (def.category, def.retriable)
+    })
+}
+
+/// Catalog entry of `code`, or undefined for a code neither worker returns
+#[wasm_bindgen]
+pub fn lookup_error_code(code: &str) -> Option<ErrorCodeInfo> {
+    find_error_code(code).map(ErrorCodeDef::info)
+}
+
+// === DEFINITIONS ===
+
+// --- Signer worker ---
+
+pub const USER_DECLINED: ErrorCodeDef = ErrorCodeDef {
+    code: "UserDeclined",
+    id: 101,
+    category: ErrorCategory::UserAction,
+    retriable: true,
+    message: "The passkey prompt was cancelled",
+};
+
+pub const CREDENTIAL_TIMEOUT: ErrorCodeDef = ErrorCodeDef {
+    code: "CredentialTimeout",
+    id: 102,
+    category: ErrorCategory::UserAction,
+    retriable: true,
+    message: "The passkey prompt timed out",
+};
+
+pub const CREDENTIAL_COLLECTION_FAILED: ErrorCodeDef = ErrorCodeDef {
+    code: "CredentialCollectionFailed",
+    id: 103,
+    category: ErrorCategory::UserAction,
+    retriable: true,
+    message: "The passkey credential could not be collected",
+};
+
+pub const TOO_MANY_PENDING_REQUESTS: ErrorCodeDef = ErrorCodeDef {
+    code: "TooManyPendingRequests",
+    id: 201,
+    category: ErrorCategory::Policy,
+    retriable: true,
+    message: "Too many requests are pending; try again shortly",
+};
+
+pub const DUPLICATE_REQUEST_ID: ErrorCodeDef = ErrorCodeDef {
+    code: "DuplicateRequestId",
+    id: 501,
+    category: ErrorCategory::Internal,
+    retriable: false,
+    message: "A request with this id is already pending",
+};
+
+pub const REQUEST_CANCELLED: ErrorCodeDef = ErrorCodeDef {
+    code: "RequestCancelled",
+    id: 104,
+    category: ErrorCategory::UserAction,
+    retriable: true,
+    message: "The request was cancelled before it started",
+};
+
+pub const SESSION_KEY_NOT_FOUND: ErrorCodeDef = ErrorCodeDef {
+    code: "SessionKeyNotFound",
+    id: 202,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "No session key with this public key is held",
+};
+
+pub const SESSION_KEY_EXPIRED: ErrorCodeDef = ErrorCodeDef {
+    code: "SessionKeyExpired",
+    id: 203,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The session key has expired",
+};
+
+pub const SESSION_KEY_PERMISSION_DENIED: ErrorCodeDef = ErrorCodeDef {
+    code: "SessionKeyPermissionDenied",
+    id: 204,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The transaction is outside the session key's permissions",
+};
+
+pub const SESSION_KEY_ALLOWANCE_EXCEEDED: ErrorCodeDef = ErrorCodeDef {
+    code: "SessionKeyAllowanceExceeded",
+    id: 205,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The transaction exceeds the session key's remaining allowance",
+};
+
+pub const PRE_SIGN_HOOK_DENIED: ErrorCodeDef = ErrorCodeDef {
+    code: "PreSignHookDenied",
+    id: 206,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The pre-sign hook denied the request",
+};
+
+pub const CHALLENGE_EXPIRED: ErrorCodeDef = ErrorCodeDef {
+    code: "ChallengeExpired",
+    id: 207,
+    category: ErrorCategory::Policy,
+    retriable: true,
+    message: "The VRF challenge is too old for the contract to accept",
+};
+
+pub const WIRE_FORMAT_MISMATCH: ErrorCodeDef = ErrorCodeDef {
+    code: "WireFormatMismatch",
+    id: 502,
+    category: ErrorCategory::Internal,
+    retriable: false,
+    message: "The message encoding differs from the negotiated wire format",
+};
+
+pub const WRONG_CREDENTIAL_FOR_BLOB: ErrorCodeDef = ErrorCodeDef {
+    code: "WrongCredentialForBlob",
+    id: 301,
+    category: ErrorCategory::Crypto,
+    retriable: true,
+    message: "The key was encrypted with a different passkey",
+};
+
+pub const CORRUPTED_CIPHERTEXT: ErrorCodeDef = ErrorCodeDef {
+    code: "CorruptedCiphertext",
+    id: 302,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "The encrypted key is damaged",
+};
+
+pub const DECRYPTION_FAILED: ErrorCodeDef = ErrorCodeDef {
+    code: "DecryptionFailed",
+    id: 303,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "The encrypted key could not be decrypted",
+};
+
+pub const UNKNOWN_FIELD: ErrorCodeDef = ErrorCodeDef {
+    code: "UnknownField",
+    id: 208,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The request has a field the worker does not recognise",
+};
+
+pub const DESCRIPTOR_INVALID: ErrorCodeDef = ErrorCodeDef {
+    code: "DescriptorInvalid",
+    id: 304,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "The account descriptor is malformed or its signature does not verify",
+};
+
+pub const DESCRIPTOR_EXPIRED: ErrorCodeDef = ErrorCodeDef {
+    code: "DescriptorExpired",
+    id: 209,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The account descriptor has expired",
+};
+
+pub const ASSERTION_SIGNATURE_INVALID: ErrorCodeDef = ErrorCodeDef {
+    code: "AssertionSignatureInvalid",
+    id: 305,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "The passkey assertion signature does not verify",
+};
+
+pub const IDEMPOTENCY_KEY_CONFLICT: ErrorCodeDef = ErrorCodeDef {
+    code: "IdempotencyKeyConflict",
+    id: 210,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The idempotency key was already used for a different request",
+};
+
+pub const OUTER_WRAP_KEY_UNAVAILABLE: ErrorCodeDef = ErrorCodeDef {
+    code: "OuterWrapKeyUnavailable",
+    id: 306,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "The key that wraps the stored key is not available on this device",
+};
+
+pub const CONFIRMATION_EXPIRED: ErrorCodeDef = ErrorCodeDef {
+    code: "ConfirmationExpired",
+    id: 105,
+    category: ErrorCategory::UserAction,
+    retriable: true,
+    message: "The confirmation arrived after the confirmation window closed",
+};
+
+pub const INVALID_CONFIG: ErrorCodeDef = ErrorCodeDef {
+    code: "InvalidConfig",
+    id: 503,
+    category: ErrorCategory::Internal,
+    retriable: false,
+    message: "The configuration is invalid",
+};
+
+pub const RPC_ORIGIN_NOT_ALLOWED: ErrorCodeDef = ErrorCodeDef {
+    code: "RpcOriginNotAllowed",
+    id: 211,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The RPC endpoint is not on the allowed origins",
+};
+
+pub const SELF_TEST_FAILED: ErrorCodeDef = ErrorCodeDef {
+    code: "SelfTestFailed",
+    id: 504,
+    category: ErrorCategory::Internal,
+    retriable: false,
+    message: "A startup self-test failed; key handling is disabled",
+};
+
+pub const BUNDLE_PASSPHRASE_INVALID: ErrorCodeDef = ErrorCodeDef {
+    code: "BundlePassphraseInvalid",
+    id: 106,
+    category: ErrorCategory::UserAction,
+    retriable: true,
+    message: "The passphrase does not match the account bundle",
+};
+
+pub const BUNDLE_TRUNCATED: ErrorCodeDef = ErrorCodeDef {
+    code: "BundleTruncated",
+    id: 505,
+    category: ErrorCategory::Internal,
+    retriable: false,
+    message: "The account bundle is incomplete",
+};
+
+pub const BUNDLE_VERSION_UNSUPPORTED: ErrorCodeDef = ErrorCodeDef {
+    code: "BundleVersionUnsupported",
+    id: 212,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The account bundle was written by a newer release",
+};
+
+pub const BUNDLE_INVALID: ErrorCodeDef = ErrorCodeDef {
+    code: "BundleInvalid",
+    id: 307,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "The account bundle is damaged or invalid",
+};
+
+pub const SIGNING_INTENT_INVALID: ErrorCodeDef = ErrorCodeDef {
+    code: "SigningIntentInvalid",
+    id: 308,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "The signing intent is malformed or was not issued by this worker",
+};
+
+pub const SIGNING_INTENT_EXPIRED: ErrorCodeDef = ErrorCodeDef {
+    code: "SigningIntentExpired",
+    id: 213,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The signing intent has expired",
+};
+
+pub const SIGNING_INTENT_REPLAYED: ErrorCodeDef = ErrorCodeDef {
+    code: "SigningIntentReplayed",
+    id: 214,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The signing intent was already executed",
+};
+
+pub const SIGNING_INTENT_PAYLOAD_MISMATCH: ErrorCodeDef = ErrorCodeDef {
+    code: "SigningIntentPayloadMismatch",
+    id: 215,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The transactions differ from the confirmed signing intent",
+};
+
+pub const WEBAUTHN_DATA_MALFORMED: ErrorCodeDef = ErrorCodeDef {
+    code: "WebAuthnDataMalformed",
+    id: 309,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "The WebAuthn data does not parse",
+};
+
+pub const RELAYER_ACCOUNT_EXISTS: ErrorCodeDef = ErrorCodeDef {
+    code: "RelayerAccountExists",
+    id: 216,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The account already exists",
+};
+
+pub const RELAYER_QUOTA_EXCEEDED: ErrorCodeDef = ErrorCodeDef {
+    code: "RelayerQuotaExceeded",
+    id: 401,
+    category: ErrorCategory::Network,
+    retriable: true,
+    message: "The relayer's rate limit was reached; try again later",
+};
+
+pub const RELAYER_REJECTED: ErrorCodeDef = ErrorCodeDef {
+    code: "RelayerRejected",
+    id: 217,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The relayer refused the request",
+};
+
+pub const RELAYER_UNAVAILABLE: ErrorCodeDef = ErrorCodeDef {
+    code: "RelayerUnavailable",
+    id: 402,
+    category: ErrorCategory::Network,
+    retriable: true,
+    message: "The relayer could not be reached",
+};
+
+pub const RELAYER_RESPONSE_INVALID: ErrorCodeDef = ErrorCodeDef {
+    code: "RelayerResponseInvalid",
+    id: 403,
+    category: ErrorCategory::Network,
+    retriable: false,
+    message: "The relayer's response is invalid",
+};
+
+pub const PRF_UNSUPPORTED_BY_AUTHENTICATOR: ErrorCodeDef = ErrorCodeDef {
+    code: "PrfUnsupportedByAuthenticator",
+    id: 218,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The authenticator does not support the PRF extension",
+};
+
+pub const COUNTDOWN_NOT_DISPLAYED: ErrorCodeDef = ErrorCodeDef {
+    code: "CountdownNotDisplayed",
+    id: 107,
+    category: ErrorCategory::UserAction,
+    retriable: true,
+    message: "The auto-proceed countdown was not shown to the user",
+};
+
+pub const RPC_UNREACHABLE: ErrorCodeDef = ErrorCodeDef {
+    code: "RpcUnreachable",
+    id: 404,
+    category: ErrorCategory::Network,
+    retriable: true,
+    message: "No NEAR RPC endpoint could be reached",
+};
+
+pub const RPC_TIMEOUT: ErrorCodeDef = ErrorCodeDef {
+    code: "RpcTimeout",
+    id: 405,
+    category: ErrorCategory::Network,
+    retriable: true,
+    message: "The NEAR RPC request timed out",
+};
+
+pub const INVALID_NONCE: ErrorCodeDef = ErrorCodeDef {
+    code: "InvalidNonce",
+    id: 406,
+    category: ErrorCategory::Network,
+    retriable: true,
+    message: "The transaction nonce is outdated",
+};
+
+pub const RPC_REQUEST_FAILED: ErrorCodeDef = ErrorCodeDef {
+    code: "RpcRequestFailed",
+    id: 407,
+    category: ErrorCategory::Network,
+    retriable: false,
+    message: "The NEAR RPC node returned an error",
+};
+
+pub const RPC_RESPONSE_INVALID: ErrorCodeDef = ErrorCodeDef {
+    code: "RpcResponseInvalid",
+    id: 408,
+    category: ErrorCategory::Network,
+    retriable: false,
+    message: "The NEAR RPC response is invalid",
+};
+
+pub const SIMULATION_UNAVAILABLE: ErrorCodeDef = ErrorCodeDef {
+    code: "SimulationUnavailable",
+    id: 410,
+    category: ErrorCategory::Network,
+    retriable: true,
+    message: "The RPC could not simulate the conditional batch",
+};
+
+pub const PEER_CHALLENGE_UNAVAILABLE: ErrorCodeDef = ErrorCodeDef {
+    code: "PeerChallengeUnavailable",
+    id: 506,
+    category: ErrorCategory::Internal,
+    retriable: true,
+    message: "The VRF worker did not provide a challenge",
+};
+
+pub const PEER_CHALLENGE_REJECTED: ErrorCodeDef = ErrorCodeDef {
+    code: "PeerChallengeRejected",
+    id: 310,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "The VRF challenge was not proven with the session's key",
+};
+
+pub const DEADLINE_EXCEEDED: ErrorCodeDef = ErrorCodeDef {
+    code: "DeadlineExceeded",
+    id: 222,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The chain is past the request's validUntilBlockHeight",
+};
+
+pub const REGISTRATION_RESUME_INVALID: ErrorCodeDef = ErrorCodeDef {
+    code: "RegistrationResumeInvalid",
+    id: 317,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "The registration resume token is malformed or does not verify with this passkey",
+};
+
+pub const REGISTRATION_RESUME_EXPIRED: ErrorCodeDef = ErrorCodeDef {
+    code: "RegistrationResumeExpired",
+    id: 223,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The registration resume token has expired",
+};
+
+pub const REGISTRATION_RESUME_MISMATCH: ErrorCodeDef = ErrorCodeDef {
+    code: "RegistrationResumeMismatch",
+    id: 224,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The registration resume token was issued for another account, passkey or key",
+};
+
+pub const REMOTE_CONFIRMATION_INVALID: ErrorCodeDef = ErrorCodeDef {
+    code: "RemoteConfirmationInvalid",
+    id: 318,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "The remote confirmation request or approval is malformed or does not verify",
+};
+
+pub const REMOTE_CONFIRMATION_EXPIRED: ErrorCodeDef = ErrorCodeDef {
+    code: "RemoteConfirmationExpired",
+    id: 225,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The remote confirmation request has expired",
+};
+
+pub const REMOTE_CONFIRMATION_REPLAYED: ErrorCodeDef = ErrorCodeDef {
+    code: "RemoteConfirmationReplayed",
+    id: 226,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The remote confirmation was already approved or used",
+};
+
+pub const REMOTE_CONFIRMATION_WRONG_SESSION: ErrorCodeDef = ErrorCodeDef {
+    code: "RemoteConfirmationWrongSession",
+    id: 227,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The approval was made for another remote confirmation session or request",
+};
+
+pub const FEATURE_NOT_COMPILED: ErrorCodeDef = ErrorCodeDef {
+    code: "FeatureNotCompiled",
+    id: 513,
+    category: ErrorCategory::Internal,
+    retriable: false,
+    message: "This worker build does not include the feature the request needs",
+};
+
+pub const INSUFFICIENT_ALLOWANCE: ErrorCodeDef = ErrorCodeDef {
+    code: "InsufficientAllowance",
+    id: 228,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The function-call key's remaining allowance does not cover the transaction",
+};
+
+pub const KEY_LACKS_PERMISSION: ErrorCodeDef = ErrorCodeDef {
+    code: "KeyLacksPermission",
+    id: 229,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The selected signing key is not on the account or may not sign these actions",
+};
+
+pub const ARGS_SCHEMA_VIOLATION: ErrorCodeDef = ErrorCodeDef {
+    code: "ArgsSchemaViolation",
+    id: 230,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "Function call arguments do not match the schema registered for the method",
+};
+
+pub const PAYLOAD_CHANGED_SINCE_PREVIEW: ErrorCodeDef = ErrorCodeDef {
+    code: "PayloadChangedSincePreview",
+    id: 231,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The transactions differ from the batch that was previewed",
+};
+
+pub const PLAINTEXT_SECRET_REFUSED: ErrorCodeDef = ErrorCodeDef {
+    code: "PlaintextSecretRefused",
+    id: 232,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "A secret was sent in plaintext while requireEncryptedSecrets is set",
+};
+
+pub const CANNOT_REDACT_CRITICAL_FIELD: ErrorCodeDef = ErrorCodeDef {
+    code: "CannotRedactCriticalField",
+    id: 233,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The receiver, method, deposit and gas of a call cannot be redacted",
+};
+
+pub const MESSAGE_TOO_LARGE: ErrorCodeDef = ErrorCodeDef {
+    code: "MessageTooLarge",
+    id: 234,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The message is larger than the worker accepts for its request type",
+};
+
+pub const DEPRECATED_USAGE: ErrorCodeDef = ErrorCodeDef {
+    code: "DeprecatedUsage",
+    id: 235,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The request uses a deprecated path while failOnDeprecated is set",
+};
+
+pub const FULL_ACCESS_ADD_KEY_BLOCKED: ErrorCodeDef = ErrorCodeDef {
+    code: "FullAccessAddKeyBlocked",
+    id: 236,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The batch adds a full access key while blockFullAccessAddKey is set",
+};
+
+pub const DISALLOWED_ALGORITHM_NEGOTIATED: ErrorCodeDef = ErrorCodeDef {
+    code: "DisallowedAlgorithmNegotiated",
+    id: 237,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The credential was created with an algorithm outside allowedAlgorithms",
+};
+
+pub const NOT_A_CHILD_ACCOUNT: ErrorCodeDef = ErrorCodeDef {
+    code: "NotAChildAccount",
+    id: 238,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The account to create is not a direct subaccount of the signer",
+};
+
+pub const ELEVATION_REQUIRED: ErrorCodeDef = ErrorCodeDef {
+    code: "ElevationRequired",
+    id: 110,
+    category: ErrorCategory::UserAction,
+    retriable: true,
+    message: "The operation needs a fresh passkey assertion",
+};
+
+pub const AWAITING_SECOND_APPROVAL: ErrorCodeDef = ErrorCodeDef {
+    code: "AwaitingSecondApproval",
+    id: 111,
+    category: ErrorCategory::UserAction,
+    retriable: true,
+    message: "The first approval is recorded; a second passkey must approve the transaction",
+};
+
+pub const SESSION_EXPIRED: ErrorCodeDef = ErrorCodeDef {
+    code: "SessionExpired",
+    id: 112,
+    category: ErrorCategory::UserAction,
+    retriable: true,
+    message: "The session is past its maximum duration; a fresh passkey assertion starts a new one",
+};
+
+pub const APPROVAL_TOKEN_INVALID: ErrorCodeDef = ErrorCodeDef {
+    code: "ApprovalTokenInvalid",
+    id: 322,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "The approval token was not issued for this account by this worker",
+};
+
+pub const APPROVAL_TOKEN_EXPIRED: ErrorCodeDef = ErrorCodeDef {
+    code: "ApprovalTokenExpired",
+    id: 239,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The first approval expired before the second one",
+};
+
+pub const APPROVAL_DIGEST_MISMATCH: ErrorCodeDef = ErrorCodeDef {
+    code: "ApprovalDigestMismatch",
+    id: 240,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The second approval is for other transactions than the first",
+};
+
+pub const SECOND_APPROVAL_SAME_CREDENTIAL: ErrorCodeDef = ErrorCodeDef {
+    code: "SecondApprovalSameCredential",
+    id: 241,
+    category: ErrorCategory::Policy,
+    retriable: true,
+    message: "The second approval must come from another passkey",
+};
+
+pub const UNTRUSTED_CONFIRMATION_ORIGIN: ErrorCodeDef = ErrorCodeDef {
+    code: "UntrustedConfirmationOrigin",
+    id: 242,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The confirmation was not shown by the trusted wallet origin",
+};
+
+pub const INVALID_DERIVATION_PATH: ErrorCodeDef = ErrorCodeDef {
+    code: "InvalidDerivationPath",
+    id: 323,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "Sub-key paths are SLIP-0010 ed25519 paths with hardened indexes only",
+};
+
+pub const CONTRACT_PANIC: ErrorCodeDef = ErrorCodeDef {
+    code: "ContractPanic",
+    id: 411,
+    category: ErrorCategory::Network,
+    retriable: false,
+    message: "The contract panicked while running the view call",
+};
+
+pub const INVALID_DEPENDENCY_GRAPH: ErrorCodeDef = ErrorCodeDef {
+    code: "InvalidDependencyGraph",
+    id: 516,
+    category: ErrorCategory::Internal,
+    retriable: false,
+    message: "A transaction's dependsOn names itself, a later transaction or one outside the batch",
+};
+
+pub const QR_PAYLOAD_INVALID: ErrorCodeDef = ErrorCodeDef {
+    code: "QrPayloadInvalid",
+    id: 517,
+    category: ErrorCategory::Internal,
+    retriable: false,
+    message: "The scanned QR parts do not reassemble to a valid payload",
+};
+
+pub const REDIRECT_SCHEME_NOT_ALLOWED: ErrorCodeDef = ErrorCodeDef {
+    code: "RedirectSchemeNotAllowed",
+    id: 243,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "Redirect callback URLs must be https",
+};
+
+pub const REDIRECT_ORIGIN_NOT_ALLOWED: ErrorCodeDef = ErrorCodeDef {
+    code: "RedirectOriginNotAllowed",
+    id: 244,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The redirect callback URL is not on the allowed redirect origins",
+};
+
+pub const REDIRECT_PAYLOAD_EXPIRED: ErrorCodeDef = ErrorCodeDef {
+    code: "RedirectPayloadExpired",
+    id: 245,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The redirect payload has expired",
+};
+
+pub const REDIRECT_PAYLOAD_INVALID: ErrorCodeDef = ErrorCodeDef {
+    code: "RedirectPayloadInvalid",
+    id: 324,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "The redirect payload is malformed, does not verify, or was issued for another callback",
+};
+
+pub const SEALED_SECRET_INVALID: ErrorCodeDef = ErrorCodeDef {
+    code: "SealedSecretInvalid",
+    id: 319,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "A sealed secret is malformed or was not sealed to this session's key",
+};
+
+pub const WRONG_CURVE_FOR_CHAIN: ErrorCodeDef = ErrorCodeDef {
+    code: "WrongCurveForChain",
+    id: 320,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "The stored key is on a different curve than the chain requires",
+};
+
+// --- VRF worker ---
+
+pub const NO_VRF_KEYPAIR: ErrorCodeDef = ErrorCodeDef {
+    code: "NoVrfKeypair",
+    id: 108,
+    category: ErrorCategory::UserAction,
+    retriable: false,
+    message: "No VRF keypair is loaded; log in first",
+};
+
+pub const VRF_NOT_UNLOCKED: ErrorCodeDef = ErrorCodeDef {
+    code: "VrfNotUnlocked",
+    id: 109,
+    category: ErrorCategory::UserAction,
+    retriable: false,
+    message: "The VRF keypair is locked; log in first",
+};
+
+pub const INVALID_PRF_OUTPUT: ErrorCodeDef = ErrorCodeDef {
+    code: "InvalidPrfOutput",
+    id: 311,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "The PRF output is missing or invalid",
+};
+
+pub const HKDF_DERIVATION_FAILED: ErrorCodeDef = ErrorCodeDef {
+    code: "HkdfDerivationFailed",
+    id: 312,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "Key derivation failed",
+};
+
+pub const AES_GCM_FAILED: ErrorCodeDef = ErrorCodeDef {
+    code: "AesGcmFailed",
+    id: 313,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "The VRF keypair could not be encrypted or decrypted",
+};
+
+pub const INVALID_IV_LENGTH: ErrorCodeDef = ErrorCodeDef {
+    code: "InvalidIvLength",
+    id: 314,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "The encryption IV has the wrong length",
+};
+
+pub const SERIALIZATION_FAILED: ErrorCodeDef = ErrorCodeDef {
+    code: "SerializationFailed",
+    id: 507,
+    category: ErrorCategory::Internal,
+    retriable: false,
+    message: "The VRF keypair could not be serialized",
+};
+
+pub const VRF_PUBLIC_KEY_MISMATCH: ErrorCodeDef = ErrorCodeDef {
+    code: "VrfPublicKeyMismatch",
+    id: 315,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "The VRF public key does not match the expected key",
+};
+
+pub const MESSAGE_PARSING_FAILED: ErrorCodeDef = ErrorCodeDef {
+    code: "MessageParsingFailed",
+    id: 508,
+    category: ErrorCategory::Internal,
+    retriable: false,
+    message: "The message does not parse",
+};
+
+pub const MISSING_REQUIRED_DATA: ErrorCodeDef = ErrorCodeDef {
+    code: "MissingRequiredData",
+    id: 509,
+    category: ErrorCategory::Internal,
+    retriable: false,
+    message: "The request is missing required data",
+};
+
+pub const INVALID_MESSAGE_FORMAT: ErrorCodeDef = ErrorCodeDef {
+    code: "InvalidMessageFormat",
+    id: 510,
+    category: ErrorCategory::Internal,
+    retriable: false,
+    message: "The message format is invalid",
+};
+
+pub const BLOCK_HEIGHT_INVALID: ErrorCodeDef = ErrorCodeDef {
+    code: "BlockHeightInvalid",
+    id: 511,
+    category: ErrorCategory::Internal,
+    retriable: false,
+    message: "The block height is invalid",
+};
+
+pub const SESSION_SNAPSHOT_EXPIRED: ErrorCodeDef = ErrorCodeDef {
+    code: "SessionSnapshotExpired",
+    id: 219,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The session snapshot has expired",
+};
+
+pub const SESSION_SNAPSHOT_REUSED: ErrorCodeDef = ErrorCodeDef {
+    code: "SessionSnapshotReused",
+    id: 220,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The session snapshot was already restored",
+};
+
+pub const SESSION_SNAPSHOT_REVOKED: ErrorCodeDef = ErrorCodeDef {
+    code: "SessionSnapshotRevoked",
+    id: 221,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The session snapshot was revoked by a wipe, a worker restart or a new signer session",
+};
+
+pub const SESSION_SNAPSHOT_INVALID: ErrorCodeDef = ErrorCodeDef {
+    code: "SessionSnapshotInvalid",
+    id: 316,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "The session snapshot is invalid",
+};
+
+pub const PEER_PORT_NOT_CONNECTED: ErrorCodeDef = ErrorCodeDef {
+    code: "PeerPortNotConnected",
+    id: 512,
+    category: ErrorCategory::Internal,
+    retriable: true,
+    message: "No signer worker port is connected",
+};
+
+pub const INSECURE_RANDOMNESS: ErrorCodeDef = ErrorCodeDef {
+    code: "InsecureRandomness",
+    id: 514,
+    category: ErrorCategory::Internal,
+    retriable: false,
+    message: "The random number generator failed its health check",
+};
+
+pub const INVALID_BLOCK_HASH: ErrorCodeDef = ErrorCodeDef {
+    code: "InvalidBlockHash",
+    id: 515,
+    category: ErrorCategory::Internal,
+    retriable: false,
+    message: "The block hash is invalid or does not match the block height",
+};
+
+pub const BLOCK_CROSS_CHECK_FAILED: ErrorCodeDef = ErrorCodeDef {
+    code: "BlockCrossCheckFailed",
+    id: 409,
+    category: ErrorCategory::Network,
+    retriable: true,
+    message: "The block could not be looked up to cross-check it",
+};
+
+pub const BLOB_ACCOUNT_MISMATCH: ErrorCodeDef = ErrorCodeDef {
+    code: "BlobAccountMismatch",
+    id: 321,
+    category: ErrorCategory::Crypto,
+    retriable: false,
+    message: "The encrypted VRF keypair's metadata names another account",
+};
+
+pub const SCOPE_NOT_GRANTED: ErrorCodeDef = ErrorCodeDef {
+    code: "ScopeNotGranted",
+    id: 246,
+    category: ErrorCategory::Policy,
+    retriable: false,
+    message: "The VRF keypair was unlocked without the scope this operation needs",
+};
+
+/// Every definition above
+pub const CATALOG: &[ErrorCodeDef] = &[
+    USER_DECLINED,
+    CREDENTIAL_TIMEOUT,
+    CREDENTIAL_COLLECTION_FAILED,
+    TOO_MANY_PENDING_REQUESTS,
+    DUPLICATE_REQUEST_ID,
+    REQUEST_CANCELLED,
+    SESSION_KEY_NOT_FOUND,
+    SESSION_KEY_EXPIRED,
+    SESSION_KEY_PERMISSION_DENIED,
+    SESSION_KEY_ALLOWANCE_EXCEEDED,
+    PRE_SIGN_HOOK_DENIED,
+    CHALLENGE_EXPIRED,
+    WIRE_FORMAT_MISMATCH,
+    WRONG_CREDENTIAL_FOR_BLOB,
+    CORRUPTED_CIPHERTEXT,
+    DECRYPTION_FAILED,
+    UNKNOWN_FIELD,
+    DESCRIPTOR_INVALID,
+    DESCRIPTOR_EXPIRED,
+    ASSERTION_SIGNATURE_INVALID,
+    IDEMPOTENCY_KEY_CONFLICT,
+    OUTER_WRAP_KEY_UNAVAILABLE,
+    CONFIRMATION_EXPIRED,
+    INVALID_CONFIG,
+    RPC_ORIGIN_NOT_ALLOWED,
+    SELF_TEST_FAILED,
+    BUNDLE_PASSPHRASE_INVALID,
+    BUNDLE_TRUNCATED,
+    BUNDLE_VERSION_UNSUPPORTED,
+    BUNDLE_INVALID,
+    SIGNING_INTENT_INVALID,
+    SIGNING_INTENT_EXPIRED,
+    SIGNING_INTENT_REPLAYED,
+    SIGNING_INTENT_PAYLOAD_MISMATCH,
+    WEBAUTHN_DATA_MALFORMED,
+    RELAYER_ACCOUNT_EXISTS,
+    RELAYER_QUOTA_EXCEEDED,
+    RELAYER_REJECTED,
+    RELAYER_UNAVAILABLE,
+    RELAYER_RESPONSE_INVALID,
+    PRF_UNSUPPORTED_BY_AUTHENTICATOR,
+    COUNTDOWN_NOT_DISPLAYED,
+    RPC_UNREACHABLE,
+    RPC_TIMEOUT,
+    INVALID_NONCE,
+    RPC_REQUEST_FAILED,
+    RPC_RESPONSE_INVALID,
+    PEER_CHALLENGE_UNAVAILABLE,
+    PEER_CHALLENGE_REJECTED,
+    DEADLINE_EXCEEDED,
+    REGISTRATION_RESUME_INVALID,
+    REGISTRATION_RESUME_EXPIRED,
+    REGISTRATION_RESUME_MISMATCH,
+    REMOTE_CONFIRMATION_INVALID,
+    REMOTE_CONFIRMATION_EXPIRED,
+    REMOTE_CONFIRMATION_REPLAYED,
+    REMOTE_CONFIRMATION_WRONG_SESSION,
+    FEATURE_NOT_COMPILED,
+    INSUFFICIENT_ALLOWANCE,
+    KEY_LACKS_PERMISSION,
+    ARGS_SCHEMA_VIOLATION,
+    PAYLOAD_CHANGED_SINCE_PREVIEW,
+    PLAINTEXT_SECRET_REFUSED,
+    CANNOT_REDACT_CRITICAL_FIELD,
+    MESSAGE_TOO_LARGE,
+    SEALED_SECRET_INVALID,
+    WRONG_CURVE_FOR_CHAIN,
+    SIMULATION_UNAVAILABLE,
+    DEPRECATED_USAGE,
+    FULL_ACCESS_ADD_KEY_BLOCKED,
+    DISALLOWED_ALGORITHM_NEGOTIATED,
+    NOT_A_CHILD_ACCOUNT,
+    ELEVATION_REQUIRED,
+    AWAITING_SECOND_APPROVAL,
+    APPROVAL_TOKEN_INVALID,
+    APPROVAL_TOKEN_EXPIRED,
+    APPROVAL_DIGEST_MISMATCH,
+    SECOND_APPROVAL_SAME_CREDENTIAL,
+    INVALID_DERIVATION_PATH,
+    CONTRACT_PANIC,
+    UNTRUSTED_CONFIRMATION_ORIGIN,
+    INVALID_DEPENDENCY_GRAPH,
+    QR_PAYLOAD_INVALID,
+    SESSION_EXPIRED,
+    REDIRECT_SCHEME_NOT_ALLOWED,
+    REDIRECT_ORIGIN_NOT_ALLOWED,
+    REDIRECT_PAYLOAD_EXPIRED,
+    REDIRECT_PAYLOAD_INVALID,
+    NO_VRF_KEYPAIR,
+    VRF_NOT_UNLOCKED,
+    INVALID_PRF_OUTPUT,
+    HKDF_DERIVATION_FAILED,
+    AES_GCM_FAILED,
+    INVALID_IV_LENGTH,
+    SERIALIZATION_FAILED,
+    VRF_PUBLIC_KEY_MISMATCH,
+    MESSAGE_PARSING_FAILED,
+    MISSING_REQUIRED_DATA,
+    INVALID_MESSAGE_FORMAT,
+    BLOCK_HEIGHT_INVALID,
+    SESSION_SNAPSHOT_EXPIRED,
+    SESSION_SNAPSHOT_REUSED,
+    SESSION_SNAPSHOT_REVOKED,
+    SESSION_SNAPSHOT_INVALID,
+    PEER_PORT_NOT_CONNECTED,
+    INSECURE_RANDOMNESS,
+    INVALID_BLOCK_HASH,
+    BLOCK_CROSS_CHECK_FAILED,
+    BLOB_ACCOUNT_MISMATCH,
+    SCOPE_NOT_GRANTED,
+];
+
+// === DEPRECATIONS ===
+// Codes are stable like error ids: a code is never reused for another path. Paths are removed
+// in the first release after `remove_after_version`.
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DeprecationDef {
+    pub code: &'static str,
+    /// What was used and what replaces it
+    pub message: &'static str,
+    /// Last release that accepts the path
+    pub remove_after_version: &'static str,
+}
+
+impl DeprecationDef {
+    pub fn warning(&self) -> DeprecationWarning {
+        DeprecationWarning {
+            code: self.code.to_string(),
+            message: self.message.to_string(),
+            remove_after_version: self.remove_after_version.to_string(),
+        }
+    }
+}
+
+/// Entry of a response's `warnings`
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeprecationWarning {
+    #[wasm_bindgen(getter_with_clone)]
+    pub code: String,
+    #[wasm_bindgen(getter_with_clone)]
+    pub message: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "removeAfterVersion")]
+    pub remove_after_version: String,
+}
+
+/// Catalog entry of a deprecation code (e.g. "LegacyKeyBlob")
+pub fn find_deprecation(code: &str) -> Option<&'static DeprecationDef> {
+    DEPRECATIONS.iter().find(|def| def.code == code)
+}
+
+/// Catalog entry of deprecation `code`, or undefined for an unknown code
+#[wasm_bindgen]
+pub fn lookup_deprecation(code: &str) -> Option<DeprecationWarning> {
+    find_deprecation(code).map(DeprecationDef::warning)
+}
+
+pub const PLAINTEXT_SECRET: DeprecationDef = DeprecationDef {
+    code: "PlaintextSecret",
+    message: "A PRF output, private key or passphrase was sent in plaintext; seal it into sealedSecrets",
+    remove_after_version: "0.3.0",
+};
+
+pub const PLAINTEXT_CONFIRMATION_SECRET: DeprecationDef = DeprecationDef {
+    code: "PlaintextConfirmationSecret",
+    message: "The confirmation response carried its PRF output in plaintext; seal it into sealed_secrets",
+    remove_after_version: "0.3.0",
+};
+
+pub const LEGACY_KEY_BLOB: DeprecationDef = DeprecationDef {
+    code: "LegacyKeyBlob",
+    message: "The key blob predates the key-check header; upgrade it with MigrateEncryptedBlobs",
+    remove_after_version: "0.4.0",
+};
+
+/// Every deprecation above
+pub const DEPRECATIONS: &[DeprecationDef] = &[
+    PLAINTEXT_SECRET,
+    PLAINTEXT_CONFIRMATION_SECRET,
+    LEGACY_KEY_BLOB,
+];
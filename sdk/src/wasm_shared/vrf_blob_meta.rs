@@ -0,0 +1,66 @@
+// === VRF KEYPAIR BLOB METADATA ===
+// What an EncryptedVRFKeypair belongs to, stored with the ciphertext instead of loose in the
+// TS records beside it. This file is shared by both crates (`#[path]`-included as
+// `mod vrf_blob_meta`): the VRF worker writes and checks the metadata, and the signer worker's
+// MigrateEncryptedBlobs binds it to blobs written before it existed.
+//
+// The metadata is readable without the PRF output, and bound into the ChaCha20Poly1305
+// associated data, so a blob whose metadata was edited no longer decrypts. Until a blob has
+// been decrypted its metadata is only what the blob claims.
+
+use serde::{Deserialize, Serialize};
+
+/// Domain separator leading the metadata's associated data
+pub const VRF_BLOB_META_AAD_DOMAIN: &[u8] = b"web3_authn_vrf_keypair_blob_meta_v1";
+
+/// `meta` of an EncryptedVRFKeypair
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VrfKeypairBlobMeta {
+    pub account_id: String,
+    /// The passkey whose PRF output encrypts the keypair, when the writer knew it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credential_id_b64u: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_number: Option<u32>,
+    pub created_at_ms: f64,
+    /// How the keypair was derived (the VRF worker's VRF_KEYPAIR_DERIVATION_VERSION)
+    pub derivation_version: u32,
+}
+
+impl VrfKeypairBlobMeta {
+    /// Associated data of the blob's ciphertext: every field, length-prefixed, with absent
+    /// optional fields encoded apart from empty ones
+    pub fn aad(&self) -> Vec<u8> {
+        fn bytes(aad: &mut Vec<u8>, value: &[u8]) {
+            aad.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            aad.extend_from_slice(value);
+        }
+        fn optional(aad: &mut Vec<u8>, value: Option<&[u8]>) {
+            match value {
+                Some(value) => {
+                    aad.push(1);
+                    bytes(aad, value);
+                }
+                None => aad.push(0),
+            }
+        }
+
+        let mut aad = VRF_BLOB_META_AAD_DOMAIN.to_vec();
+        bytes(&mut aad, self.account_id.as_bytes());
+        optional(
+            &mut aad,
+            self.credential_id_b64u.as_deref().map(str::as_bytes),
+        );
+        optional(
+            &mut aad,
+            self.device_number
+                .map(u32::to_le_bytes)
+                .as_ref()
+                .map(|n| &n[..]),
+        );
+        aad.extend_from_slice(&self.created_at_ms.to_le_bytes());
+        aad.extend_from_slice(&self.derivation_version.to_le_bytes());
+        aad
+    }
+}
@@ -0,0 +1,386 @@
+// === ACCOUNT BUNDLES ===
+// A one-file export of an account's IndexedDB state (user records with device metadata and
+// preferences, authenticators, PRF-encrypted NEAR keys, app settings) for moving to a new
+// browser profile. The records are CBOR-encoded and encrypted with ChaCha20Poly1305 under a
+// key derived from a user-chosen passphrase with Argon2id. The file is a CBOR envelope of the
+// header bytes and the ciphertext; the header carries the format version, the Argon2id
+// parameters and salt, the nonce and a key-check value, and is bound to the ciphertext as
+// associated data. The key-check value tells a wrong passphrase apart from a damaged file.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use hmac::Mac;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::config::{
+    ACCOUNT_BUNDLE_ARGON2_ITERATIONS, ACCOUNT_BUNDLE_ARGON2_MEMORY_KIB,
+    ACCOUNT_BUNDLE_ARGON2_PARALLELISM, ACCOUNT_BUNDLE_FORMAT, ACCOUNT_BUNDLE_SALT_SIZE,
+    ACCOUNT_BUNDLE_VERSION, CHACHA20_KEY_SIZE, CHACHA20_NONCE_SIZE, KEY_CHECK_VALUE_SIZE,
+    MAX_ACCOUNT_BUNDLE_ARGON2_ITERATIONS, MAX_ACCOUNT_BUNDLE_ARGON2_MEMORY_KIB,
+    MIN_ACCOUNT_BUNDLE_PASSPHRASE_CHARS,
+};
+use crate::crypto::key_check_mac;
+use crate::error::AccountBundleError;
+use crate::stored_records::StoredRecord;
+use crate::types::worker_messages::expect_object;
+
+/// KDF algorithm name recorded in the header
+const ARGON2ID: &str = "argon2id";
+
+/// Records gathered from (and returned to) the TS storage layer, one list per object store
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountBundleRecords {
+    /// PasskeyClientDB `users` records (registration, device metadata, preferences)
+    #[serde(default)]
+    pub users: Vec<Value>,
+    /// PasskeyClientDB `authenticators` records
+    #[serde(default)]
+    pub authenticators: Vec<Value>,
+    /// PasskeyNearKeys `encryptedKeys` records (NEAR keys still encrypted under the PRF)
+    #[serde(default)]
+    pub near_keys: Vec<Value>,
+    /// PasskeyClientDB `appState` entries (`{ key, value }`)
+    #[serde(default)]
+    pub app_state: Vec<Value>,
+}
+
+/// `records` of a request, from an object only (see `expect_object`)
+pub fn deserialize_records_object<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<AccountBundleRecords, D::Error> {
+    let value = Value::deserialize(deserializer)?;
+    expect_object(&value).map_err(D::Error::custom)?;
+    serde_json::from_value(value).map_err(D::Error::custom)
+}
+
+impl AccountBundleRecords {
+    pub fn record_count(&self) -> usize {
+        self.users.len() + self.authenticators.len() + self.near_keys.len() + self.app_state.len()
+    }
+}
+
+/// Argon2id parameters, stored in the header so older bundles stay importable when the
+/// defaults are retuned
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleKdfParams {
+    pub algorithm: String,
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    #[serde(with = "serde_bytes")]
+    pub salt: Vec<u8>,
+}
+
+impl BundleKdfParams {
+    /// Default parameters with a fresh random salt
+    pub fn generate() -> Result<BundleKdfParams, String> {
+        let mut salt = vec![0u8; ACCOUNT_BUNDLE_SALT_SIZE];
+        getrandom::getrandom(&mut salt).map_err(|e| format!("Failed to generate salt: {}", e))?;
+        Ok(BundleKdfParams {
+            algorithm: ARGON2ID.to_string(),
+            memory_kib: ACCOUNT_BUNDLE_ARGON2_MEMORY_KIB,
+            iterations: ACCOUNT_BUNDLE_ARGON2_ITERATIONS,
+            parallelism: ACCOUNT_BUNDLE_ARGON2_PARALLELISM,
+            salt,
+        })
+    }
+
+    /// Derives the bundle encryption key. Parameters above the MAX_ACCOUNT_BUNDLE_ARGON2_*
+    /// bounds are refused before any memory is allocated.
+    pub fn derive_key(&self, passphrase: &str) -> Result<[u8; CHACHA20_KEY_SIZE], String> {
+        if self.algorithm != ARGON2ID {
+            return Err(format!("Unsupported bundle KDF {:?}", self.algorithm));
+        }
+        if self.memory_kib > MAX_ACCOUNT_BUNDLE_ARGON2_MEMORY_KIB
+            || self.iterations > MAX_ACCOUNT_BUNDLE_ARGON2_ITERATIONS
+        {
+            return Err(format!(
+                "Bundle KDF parameters exceed the accepted bounds (m={} KiB, t={})",
+                self.memory_kib, self.iterations
+            ));
+        }
+        if self.salt.len() < ACCOUNT_BUNDLE_SALT_SIZE {
+            return Err(format!(
+                "Bundle KDF salt must be at least {} bytes",
+                ACCOUNT_BUNDLE_SALT_SIZE
+            ));
+        }
+        let params = Params::new(
+            self.memory_kib,
+            self.iterations,
+            self.parallelism,
+            Some(CHACHA20_KEY_SIZE),
+        )
+        .map_err(|e| format!("Invalid bundle KDF parameters: {}", e))?;
+        let mut key = [0u8; CHACHA20_KEY_SIZE];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &self.salt, &mut key)
+            .map_err(|e| format!("Bundle key derivation failed: {}", e))?;
+        Ok(key)
+    }
+}
+
+/// Read first, and alone, so a newer layout is reported as a version problem
+#[derive(Deserialize)]
+struct BundleHeaderVersion {
+    format: String,
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleHeader {
+    format: String,
+    version: u32,
+    kdf: BundleKdfParams,
+    #[serde(with = "serde_bytes")]
+    nonce: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    key_check: Vec<u8>,
+}
+
+/// The file itself: the header's exact CBOR bytes (the associated data) and the ciphertext
+#[derive(Serialize, Deserialize)]
+struct BundleFile {
+    #[serde(with = "serde_bytes")]
+    header: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    ciphertext: Vec<u8>,
+}
+
+/// Plaintext of the ciphertext
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleContents {
+    exported_at_ms: f64,
+    records: AccountBundleRecords,
+}
+
+/// A decrypted, validated bundle
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenedAccountBundle {
+    pub version: u32,
+    pub exported_at_ms: f64,
+    /// Records in the current storage schema (earlier shapes are migrated)
+    pub records: AccountBundleRecords,
+}
+
+fn key_check_value(key: &[u8], nonce: &[u8]) -> Result<Vec<u8>, String> {
+    let mac = key_check_mac(key, nonce)?.finalize().into_bytes();
+    Ok(mac[..KEY_CHECK_VALUE_SIZE].to_vec())
+}
+
+/// Checks every record's structure and rewrites user and key records in the current schema.
+/// Errors name the offending record, e.g. `nearKeys[2]`.
+pub fn validate_bundle_records(
+    records: AccountBundleRecords,
+) -> Result<AccountBundleRecords, String> {
+    let stored = |list: &str, index: usize, record: Value, want_user: bool| {
+        let label = format!("{}[{}]", list, index);
+        match StoredRecord::from_json(record) {
+            Ok(record @ StoredRecord::User(_)) if want_user => record.to_json(),
+            Ok(record @ StoredRecord::NearKey(_)) if !want_user => record.to_json(),
+            Ok(_) => Err("record belongs to another store".to_string()),
+            Err(e) => Err(e),
+        }
+        .map_err(|e| format!("{}: {}", label, e))
+    };
+    let keyed = |list: &str, index: usize, record: Value, keys: &[&str]| {
+        for key in keys {
+            let present = match (*key, record.get(*key)) {
+                ("deviceNumber", Some(value)) => value.is_u64(),
+                (_, Some(value)) => value.as_str().is_some_and(|s| !s.is_empty()),
+                (_, None) => false,
+            };
+            if !present {
+                return Err(format!("{}[{}]: missing or invalid {}", list, index, key));
+            }
+        }
+        Ok(record)
+    };
+
+    Ok(AccountBundleRecords {
+        users: records
+            .users
+            .into_iter()
+            .enumerate()
+            .map(|(i, r)| stored("users", i, r, true))
+            .collect::<Result<_, _>>()?,
+        authenticators: records
+            .authenticators
+            .into_iter()
+            .enumerate()
+            .map(|(i, r)| {
+                keyed(
+                    "authenticators",
+                    i,
+                    r,
+                    &["nearAccountId", "deviceNumber", "credentialId"],
+                )
+            })
+            .collect::<Result<_, _>>()?,
+        near_keys: records
+            .near_keys
+            .into_iter()
+            .enumerate()
+            .map(|(i, r)| stored("nearKeys", i, r, false))
+            .collect::<Result<_, _>>()?,
+        app_state: records
+            .app_state
+            .into_iter()
+            .enumerate()
+            .map(|(i, r)| keyed("appState", i, r, &["key"]))
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+/// Validates `records` and encrypts them into bundle file bytes under `passphrase`
+pub fn seal_account_bundle(
+    records: AccountBundleRecords,
+    passphrase: &str,
+    kdf: BundleKdfParams,
+    exported_at_ms: f64,
+) -> Result<Vec<u8>, String> {
+    if passphrase.chars().count() < MIN_ACCOUNT_BUNDLE_PASSPHRASE_CHARS {
+        return Err(format!(
+            "Bundle passphrase must be at least {} characters",
+            MIN_ACCOUNT_BUNDLE_PASSPHRASE_CHARS
+        ));
+    }
+    let records = validate_bundle_records(records)?;
+    let key = kdf.derive_key(passphrase)?;
+
+    let mut nonce = vec![0u8; CHACHA20_NONCE_SIZE];
+    getrandom::getrandom(&mut nonce).map_err(|e| format!("Failed to generate nonce: {}", e))?;
+    let header = BundleHeader {
+        format: ACCOUNT_BUNDLE_FORMAT.to_string(),
+        version: ACCOUNT_BUNDLE_VERSION,
+        kdf,
+        key_check: key_check_value(&key, &nonce)?,
+        nonce,
+    };
+    let mut header_cbor = Vec::new();
+    ciborium::into_writer(&header, &mut header_cbor)
+        .map_err(|e| format!("Failed to encode bundle header: {}", e))?;
+
+    let mut contents = Vec::new();
+    ciborium::into_writer(
+        &BundleContents {
+            exported_at_ms,
+            records,
+        },
+        &mut contents,
+    )
+    .map_err(|e| format!("Failed to encode bundle records: {}", e))?;
+    let cipher = ChaCha20Poly1305::new_from_slice(&key)
+        .map_err(|e| format!("Bundle cipher setup failed: {}", e))?;
+    let ciphertext = cipher
+        .encrypt(
+            Nonce::from_slice(&header.nonce),
+            Payload {
+                msg: &contents,
+                aad: &header_cbor,
+            },
+        )
+        .map_err(|e| format!("Bundle encryption failed: {}", e))?;
+
+    let mut file = Vec::new();
+    ciborium::into_writer(
+        &BundleFile {
+            header: header_cbor,
+            ciphertext,
+        },
+        &mut file,
+    )
+    .map_err(|e| format!("Failed to encode bundle: {}", e))?;
+    Ok(file)
+}
+
+/// Decodes one CBOR value that must span all of `bytes`; running out of input is truncation
+fn decode_exact<T: serde::de::DeserializeOwned>(
+    bytes: &[u8],
+    what: &str,
+) -> Result<T, AccountBundleError> {
+    let mut reader = bytes;
+    let value = ciborium::from_reader(&mut reader).map_err(|e| match e {
+        ciborium::de::Error::Io(_) => AccountBundleError::Truncated,
+        other => AccountBundleError::Invalid(format!("Invalid {}: {}", what, other)),
+    })?;
+    if !reader.is_empty() {
+        return Err(AccountBundleError::Invalid(format!(
+            "{} trailing bytes after the {}",
+            reader.len(),
+            what
+        )));
+    }
+    Ok(value)
+}
+
+/// Decrypts bundle file bytes and validates the records inside
+pub fn open_account_bundle(
+    bundle: &[u8],
+    passphrase: &str,
+) -> Result<OpenedAccountBundle, AccountBundleError> {
+    if bundle.is_empty() {
+        return Err(AccountBundleError::Truncated);
+    }
+    let file: BundleFile = decode_exact(bundle, "bundle")?;
+
+    let probe: BundleHeaderVersion = decode_exact(&file.header, "bundle header")?;
+    if probe.format != ACCOUNT_BUNDLE_FORMAT {
+        return Err(AccountBundleError::Invalid(format!(
+            "Not an account bundle (format {:?})",
+            probe.format
+        )));
+    }
+    if probe.version > ACCOUNT_BUNDLE_VERSION {
+        return Err(AccountBundleError::VersionUnsupported {
+            version: probe.version,
+        });
+    }
+    let header: BundleHeader = decode_exact(&file.header, "bundle header")?;
+    if header.nonce.len() != CHACHA20_NONCE_SIZE {
+        return Err(AccountBundleError::Invalid(format!(
+            "Bundle nonce must be {} bytes",
+            CHACHA20_NONCE_SIZE
+        )));
+    }
+
+    let key = header
+        .kdf
+        .derive_key(passphrase)
+        .map_err(AccountBundleError::Invalid)?;
+    if key_check_value(&key, &header.nonce).map_err(AccountBundleError::Invalid)?
+        != header.key_check
+    {
+        return Err(AccountBundleError::PassphraseInvalid);
+    }
+    let cipher = ChaCha20Poly1305::new_from_slice(&key)
+        .map_err(|e| AccountBundleError::Invalid(format!("Bundle cipher setup failed: {}", e)))?;
+    let contents = cipher
+        .decrypt(
+            Nonce::from_slice(&header.nonce),
+            Payload {
+                msg: &file.ciphertext,
+                aad: &file.header,
+            },
+        )
+        .map_err(|_| {
+            AccountBundleError::Invalid(
+                "the passphrase matches but the bundle is damaged".to_string(),
+            )
+        })?;
+
+    let contents: BundleContents = decode_exact(&contents, "bundle records")?;
+    let records = validate_bundle_records(contents.records).map_err(AccountBundleError::Invalid)?;
+    Ok(OpenedAccountBundle {
+        version: header.version,
+        exported_at_ms: contents.exported_at_ms,
+        records,
+    })
+}
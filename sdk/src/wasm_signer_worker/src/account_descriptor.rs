@@ -0,0 +1,170 @@
+// === ACCOUNT DESCRIPTORS ===
+// A public, shareable summary of an account's passkey setup (contract, deterministic VRF
+// public key, registered credential IDs and device numbers), so a second device can learn
+// what it is linking to. The descriptor is CBOR, signed with the account's NEAR key over a
+// domain-separated message, and wrapped with its signature in a CBOR envelope encoded as
+// base64url. It carries no secrets; the signature and the embedded expiry are what make a
+// copied or stale descriptor detectable.
+
+use ed25519_dalek::{Signature, Signer, SigningKey};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ACCOUNT_DESCRIPTOR_SIGNING_DOMAIN, ACCOUNT_DESCRIPTOR_VERSION};
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::AccountDescriptorError;
+use crate::rpc_calls::RegisteredAuthenticator;
+use crate::signature_verify::decode_ed25519_key;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DescriptorAuthenticator {
+    pub credential_id: String,
+    pub device_number: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountDescriptor {
+    pub version: u32,
+    pub account_id: String,
+    pub contract_id: String,
+    /// Deterministic VRF public key (base64url), as registered on-chain
+    pub vrf_public_key: String,
+    pub authenticators: Vec<DescriptorAuthenticator>,
+    /// Key that signed the descriptor (`ed25519:<base58>`)
+    pub near_public_key: String,
+    pub issued_at_ms: f64,
+    pub expires_at_ms: f64,
+}
+
+/// What is actually shared: the descriptor's exact CBOR bytes and the signature over them
+#[derive(Serialize, Deserialize)]
+struct SignedDescriptor {
+    #[serde(with = "serde_bytes")]
+    descriptor: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    signature: Vec<u8>,
+}
+
+/// Assembles a descriptor from the account's on-chain authenticators. Fails when the
+/// account has none, or when `vrf_public_key` is not bound to any of them.
+pub fn build_account_descriptor(
+    account_id: &str,
+    contract_id: &str,
+    vrf_public_key: &str,
+    authenticators: &[RegisteredAuthenticator],
+    signing_key: &SigningKey,
+    issued_at_ms: f64,
+    ttl_ms: u32,
+) -> Result<AccountDescriptor, String> {
+    if authenticators.is_empty() {
+        return Err(format!("No registered authenticators for {}", account_id));
+    }
+    if !authenticators
+        .iter()
+        .any(|a| a.vrf_public_keys.iter().any(|k| k == vrf_public_key))
+    {
+        return Err(format!(
+            "VRF public key is not registered on-chain for {}",
+            account_id
+        ));
+    }
+
+    let mut authenticators: Vec<DescriptorAuthenticator> = authenticators
+        .iter()
+        .map(|a| DescriptorAuthenticator {
+            credential_id: a.credential_id.clone(),
+            device_number: a.device_number,
+        })
+        .collect();
+    authenticators.sort_by_key(|a| a.device_number);
+
+    Ok(AccountDescriptor {
+        version: ACCOUNT_DESCRIPTOR_VERSION,
+        account_id: account_id.to_string(),
+        contract_id: contract_id.to_string(),
+        vrf_public_key: vrf_public_key.to_string(),
+        authenticators,
+        near_public_key: format!(
+            "ed25519:{}",
+            bs58::encode(signing_key.verifying_key().to_bytes()).into_string()
+        ),
+        issued_at_ms,
+        expires_at_ms: issued_at_ms + ttl_ms as f64,
+    })
+}
+
+fn signing_message(descriptor_cbor: &[u8]) -> Vec<u8> {
+    [ACCOUNT_DESCRIPTOR_SIGNING_DOMAIN, descriptor_cbor].concat()
+}
+
+/// Signs the descriptor and returns the shareable base64url blob
+pub fn sign_account_descriptor(
+    descriptor: &AccountDescriptor,
+    signing_key: &SigningKey,
+) -> Result<String, String> {
+    let mut descriptor_cbor = Vec::new();
+    ciborium::into_writer(descriptor, &mut descriptor_cbor)
+        .map_err(|e| format!("Failed to encode account descriptor: {}", e))?;
+    let signature = signing_key.sign(&signing_message(&descriptor_cbor));
+
+    let mut envelope = Vec::new();
+    ciborium::into_writer(
+        &SignedDescriptor {
+            descriptor: descriptor_cbor,
+            signature: signature.to_bytes().to_vec(),
+        },
+        &mut envelope,
+    )
+    .map_err(|e| format!("Failed to encode account descriptor envelope: {}", e))?;
+    Ok(base64_url_encode(&envelope))
+}
+
+/// Checks the blob's signature against `public_key` (`ed25519:<base58>` or base64url) and
+/// its expiry against `now_ms`, returning the parsed descriptor. The embedded
+/// `nearPublicKey` must be the verifying key, so a descriptor cannot claim another signer.
+pub fn verify_account_descriptor(
+    blob_b64u: &str,
+    public_key: &str,
+    now_ms: f64,
+) -> Result<AccountDescriptor, AccountDescriptorError> {
+    let envelope =
+        base64_url_decode(blob_b64u.trim()).map_err(AccountDescriptorError::Malformed)?;
+    let signed: SignedDescriptor = ciborium::from_reader(envelope.as_slice())
+        .map_err(|e| AccountDescriptorError::Malformed(format!("Invalid envelope: {}", e)))?;
+
+    let verifying_key =
+        decode_ed25519_key(public_key).map_err(AccountDescriptorError::InvalidSignature)?;
+    let signature = Signature::from_slice(&signed.signature).map_err(|e| {
+        AccountDescriptorError::InvalidSignature(format!("Invalid signature: {}", e))
+    })?;
+    verifying_key
+        .verify_strict(&signing_message(&signed.descriptor), &signature)
+        .map_err(|_| {
+            AccountDescriptorError::InvalidSignature(
+                "signature does not match the provided public key".to_string(),
+            )
+        })?;
+
+    let descriptor: AccountDescriptor = ciborium::from_reader(signed.descriptor.as_slice())
+        .map_err(|e| AccountDescriptorError::Malformed(format!("Invalid descriptor: {}", e)))?;
+    if descriptor.version != ACCOUNT_DESCRIPTOR_VERSION {
+        return Err(AccountDescriptorError::Malformed(format!(
+            "Unsupported descriptor version {}",
+            descriptor.version
+        )));
+    }
+    let embedded_key = decode_ed25519_key(&descriptor.near_public_key)
+        .map_err(AccountDescriptorError::Malformed)?;
+    if embedded_key != verifying_key {
+        return Err(AccountDescriptorError::InvalidSignature(
+            "descriptor nearPublicKey differs from the verifying key".to_string(),
+        ));
+    }
+    if descriptor.expires_at_ms.is_nan() || descriptor.expires_at_ms <= now_ms {
+        return Err(AccountDescriptorError::Expired {
+            expires_at_ms: descriptor.expires_at_ms,
+        });
+    }
+    Ok(descriptor)
+}
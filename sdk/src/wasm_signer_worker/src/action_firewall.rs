@@ -0,0 +1,110 @@
+// === HIGH-RISK ACTION FIREWALL ===
+// Two actions hand an account over outright: AddKey with full access (whoever holds the key
+// can sign anything for the account) and DeleteAccount sending the remaining balance to an
+// account other than the signer. Whatever the integrator configures, a batch with either:
+//   - is confirmed with a click: its ConfirmationConfig is escalated to behavior requireClick,
+//     a skip uiMode becomes modal, and a request without a config gets the default modal
+//     (see `escalate_confirmation_config`). No confirmationConfig or WorkerPolicy field turns
+//     this off.
+//   - carries a danger warning naming the key (with its fingerprint) or the beneficiary, and
+//     a keyRow with each full access key's fingerprint (see confirmation_blocks.rs). Full
+//     access keys that cannot be fingerprinted are refused before the user is prompted.
+//   - fails with FullAccessAddKeyBlocked before the user is prompted, for full access AddKey,
+//     when `workerPolicy.blockFullAccessAddKey` is set.
+
+use crate::actions::ActionParams;
+use crate::confirmation_blocks::is_full_access_key;
+use crate::error::ActionFirewallError;
+use crate::key_selection::parse_public_key;
+use crate::types::handlers::{
+    ConfirmationBehavior, ConfirmationConfig, ConfirmationUIMode, WorkerPolicy,
+};
+
+/// An action the firewall escalates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HighRiskAction {
+    /// AddKey with a permission other than FunctionCall
+    FullAccessAddKey {
+        receiver_id: String,
+        public_key: String,
+    },
+    /// DeleteAccount whose beneficiary is not the signer
+    DeleteAccountToOther {
+        receiver_id: String,
+        beneficiary_id: String,
+    },
+}
+
+/// The batch's high-risk actions, in order, for a batch signed by `signer_id`
+pub fn high_risk_actions(
+    signer_id: &str,
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+) -> Vec<HighRiskAction> {
+    receivers_and_actions
+        .iter()
+        .flat_map(|(receiver_id, actions)| {
+            actions.iter().filter_map(move |action| match action {
+                ActionParams::AddKey {
+                    public_key,
+                    access_key,
+                } if is_full_access_key(access_key) => Some(HighRiskAction::FullAccessAddKey {
+                    receiver_id: receiver_id.clone(),
+                    public_key: public_key.clone(),
+                }),
+                ActionParams::DeleteAccount { beneficiary_id } if beneficiary_id != signer_id => {
+                    Some(HighRiskAction::DeleteAccountToOther {
+                        receiver_id: receiver_id.clone(),
+                        beneficiary_id: beneficiary_id.clone(),
+                    })
+                }
+                _ => None,
+            })
+        })
+        .collect()
+}
+
+/// Refuses a batch with a full access AddKey that is blocked by `policy` or whose key has no
+/// fingerprint to show
+pub fn check_high_risk_actions(
+    policy: Option<&WorkerPolicy>,
+    signer_id: &str,
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+) -> Result<(), ActionFirewallError> {
+    let block_full_access = policy.is_some_and(|p| p.block_full_access_add_key);
+    for action in high_risk_actions(signer_id, receivers_and_actions) {
+        let HighRiskAction::FullAccessAddKey {
+            receiver_id,
+            public_key,
+        } = action
+        else {
+            continue;
+        };
+        if block_full_access {
+            return Err(ActionFirewallError::FullAccessAddKeyBlocked {
+                receiver_id,
+                public_key,
+            });
+        }
+        parse_public_key(&public_key)
+            .map_err(|reason| ActionFirewallError::KeyNotFingerprintable { public_key, reason })?;
+    }
+    Ok(())
+}
+
+/// The config a batch with `high_risk` actions is confirmed with: `config` unchanged when there
+/// are none, otherwise requireClick in a modal or drawer, whatever `config` asks for
+pub fn escalate_confirmation_config(
+    config: Option<&ConfirmationConfig>,
+    high_risk: &[HighRiskAction],
+) -> Option<ConfirmationConfig> {
+    if high_risk.is_empty() {
+        return config.cloned();
+    }
+    let mut escalated = config.cloned().unwrap_or_default();
+    if escalated.ui_mode == ConfirmationUIMode::Skip {
+        escalated.ui_mode = ConfirmationUIMode::Modal;
+    }
+    escalated.behavior = ConfirmationBehavior::RequireClick;
+    escalated.auto_proceed_delay = None;
+    Some(escalated)
+}
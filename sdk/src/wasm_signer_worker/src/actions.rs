@@ -181,6 +181,35 @@ impl ActionHandler for CreateAccountActionHandler {
     }
 }
 
+pub struct DeployContractActionHandler;
+
+impl ActionHandler for DeployContractActionHandler {
+    fn validate_params(&self, params: &ActionParams) -> Result<(), String> {
+        match params {
+            ActionParams::DeployContract { code } => {
+                if code.is_empty() {
+                    return Err("Contract code cannot be empty".to_string());
+                }
+                Ok(())
+            }
+            _ => Err("Invalid params for DeployContract action".to_string()),
+        }
+    }
+
+    fn build_action(&self, params: &ActionParams) -> Result<Action, String> {
+        match params {
+            ActionParams::DeployContract { code } => {
+                Ok(Action::DeployContract { code: code.clone() })
+            }
+            _ => Err("Invalid params for DeployContract action".to_string()),
+        }
+    }
+
+    fn get_action_type(&self) -> ActionType {
+        ActionType::DeployContract
+    }
+}
+
 pub struct AddKeyActionHandler;
 
 impl ActionHandler for AddKeyActionHandler {
@@ -392,6 +421,7 @@ pub fn get_action_handler(params: &ActionParams) -> Result<Box<dyn ActionHandler
         ActionParams::FunctionCall { .. } => Ok(Box::new(FunctionCallActionHandler)),
         ActionParams::Transfer { .. } => Ok(Box::new(TransferActionHandler)),
         ActionParams::CreateAccount => Ok(Box::new(CreateAccountActionHandler)),
+        ActionParams::DeployContract { .. } => Ok(Box::new(DeployContractActionHandler)),
         ActionParams::AddKey { .. } => Ok(Box::new(AddKeyActionHandler)),
         ActionParams::DeleteKey { .. } => Ok(Box::new(DeleteKeyActionHandler)),
         ActionParams::DeleteAccount { .. } => Ok(Box::new(DeleteAccountActionHandler)),
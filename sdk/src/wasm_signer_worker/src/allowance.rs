@@ -0,0 +1,110 @@
+// === ACCESS KEY ALLOWANCE ===
+// A function-call access key pays for gas from its allowance, and once the allowance runs out
+// the chain rejects the transaction with an RPC error that does not say why. The main thread's
+// nonce fetch records the key's remaining allowance and the block's gas price in the
+// transaction context; with them the worker estimates what a batch can cost (prepaid gas at
+// that gas price, plus attached deposits) and:
+//   - refuses a batch estimated above the allowance with InsufficientAllowance, before the
+//     user is prompted and again (against the context fetched for signing) before the key is
+//     decrypted;
+//   - attaches `payload.allowanceAfterEstimate` to the confirmation, with a LowAllowance
+//     warning block when it falls below the policy threshold.
+// The estimate is an upper bound on what the batch's actions prepay: unused gas is refunded.
+// Full access keys and unlimited allowances carry no `accessKeyAllowance` and are not checked.
+
+use crate::actions::ActionParams;
+use crate::config::{DEFAULT_ALLOWANCE_GAS_PRICE, DEFAULT_LOW_ALLOWANCE_WARNING_YOCTO};
+use crate::error::InsufficientAllowanceError;
+use crate::types::handlers::{TransactionContext, WorkerPolicy};
+
+/// A function-call key's allowance against a batch's estimated cost (yoctoNEAR)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllowanceEstimate {
+    /// Allowance the key has left before the batch
+    pub remaining: u128,
+    /// Estimated cost of the batch
+    pub required: u128,
+    /// Allowance left after the batch below which the confirmation warns
+    pub warn_below: u128,
+}
+
+impl AllowanceEstimate {
+    /// Allowance left once the batch is paid for (None when it does not cover the batch)
+    pub fn allowance_after(&self) -> Option<u128> {
+        self.remaining.checked_sub(self.required)
+    }
+
+    pub fn is_low(&self) -> bool {
+        match self.allowance_after() {
+            Some(after) => after < self.warn_below,
+            None => true,
+        }
+    }
+
+    pub fn check(&self) -> Result<(), InsufficientAllowanceError> {
+        match self.allowance_after() {
+            Some(_) => Ok(()),
+            None => Err(InsufficientAllowanceError {
+                remaining: self.remaining,
+                required: self.required,
+            }),
+        }
+    }
+}
+
+/// Prepaid gas of the FunctionCall actions at `gas_price`, plus the attached deposits.
+/// Unparseable amounts are refused when the actions are built; they are not counted here.
+pub fn estimate_batch_cost(
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+    gas_price: u128,
+) -> u128 {
+    receivers_and_actions
+        .iter()
+        .flat_map(|(_, actions)| actions)
+        .map(|action| match action {
+            ActionParams::FunctionCall { gas, deposit, .. } => {
+                let gas_cost = gas
+                    .parse::<u128>()
+                    .map_or(0, |gas| gas.saturating_mul(gas_price));
+                gas_cost.saturating_add(deposit.parse::<u128>().unwrap_or(0))
+            }
+            ActionParams::Transfer { deposit } => deposit.parse::<u128>().unwrap_or(0),
+            _ => 0,
+        })
+        .fold(0u128, u128::saturating_add)
+}
+
+fn parse_yocto(value: &str, field: &str) -> Result<u128, String> {
+    value
+        .parse::<u128>()
+        .map_err(|e| format!("Invalid {}: {}", field, e))
+}
+
+/// The batch's allowance estimate, when `context` is for a function-call key with a finite
+/// allowance (threshold from `workerPolicy.lowAllowanceWarningYocto`)
+pub fn allowance_estimate(
+    context: Option<&TransactionContext>,
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+    policy: Option<&WorkerPolicy>,
+) -> Result<Option<AllowanceEstimate>, String> {
+    let Some(context) = context else {
+        return Ok(None);
+    };
+    let Some(allowance) = context.access_key_allowance.as_deref() else {
+        return Ok(None);
+    };
+    let remaining = parse_yocto(allowance, "transactionContext.accessKeyAllowance")?;
+    let gas_price = match context.gas_price.as_deref() {
+        Some(price) => parse_yocto(price, "transactionContext.gasPrice")?,
+        None => DEFAULT_ALLOWANCE_GAS_PRICE,
+    };
+    let warn_below = match policy.and_then(|p| p.low_allowance_warning_yocto.as_deref()) {
+        Some(yocto) => parse_yocto(yocto, "workerPolicy.lowAllowanceWarningYocto")?,
+        None => DEFAULT_LOW_ALLOWANCE_WARNING_YOCTO,
+    };
+    Ok(Some(AllowanceEstimate {
+        remaining,
+        required: estimate_batch_cost(receivers_and_actions, gas_price),
+        warn_below,
+    }))
+}
@@ -0,0 +1,490 @@
+// === FUNCTION CALL ARGS SCHEMAS ===
+// Integrators who know a contract's interface register JSON Schemas for its methods in
+// `workerPolicy.argsSchemas` ({ contractId, methodName, schema } entries), and the worker checks
+// the decoded args of every FunctionCall to that contract and method before the user is
+// prompted. A batch with any violation fails with ArgsSchemaViolation listing all of them,
+// each at the JSON pointer of the offending value, continuing into the args as if they were
+// inline (`/txSigningRequests/0/actions/1/args/amount`).
+//
+// The validator is a draft-07 subset: type, enum, const, required, properties,
+// additionalProperties (true or false), items (one schema), minimum, maximum,
+// exclusiveMinimum, exclusiveMaximum, minLength, maxLength, minItems, maxItems and pattern
+// (see schema_pattern.rs). Annotations ($schema, $id, $comment, title, description, default,
+// examples) are ignored; any other keyword is refused, so a schema never silently checks less
+// than it says. Schemas are compiled when a request arrives, and one that does not compile
+// fails the request with InvalidConfig before anything is shown; ValidateArgsSchemas compiles
+// them on their own, so the TS layer reports schema errors when they are registered.
+//
+// Args that are not JSON are not validated; the confirmation carries an ArgsNotValidated
+// warning for them instead (see confirmation_blocks.rs).
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::actions::ActionParams;
+use crate::config::{MAX_ARGS_SCHEMAS, MAX_ARGS_SCHEMA_DEPTH, MAX_REPORTED_ARGS_SCHEMA_VIOLATIONS};
+use crate::error::{ArgsSchemaCompileError, ArgsSchemaError, ArgsSchemaViolation};
+use crate::schema_pattern::{Pattern, PatternMatch};
+use crate::types::handlers::{ArgsSchemaEntry, WorkerPolicy};
+
+/// Keywords that only describe the schema
+const ANNOTATION_KEYWORDS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$comment",
+    "title",
+    "description",
+    "default",
+    "examples",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JsonType {
+    Null,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl JsonType {
+    fn parse(name: &str) -> Option<JsonType> {
+        Some(match name {
+            "null" => JsonType::Null,
+            "boolean" => JsonType::Boolean,
+            "integer" => JsonType::Integer,
+            "number" => JsonType::Number,
+            "string" => JsonType::String,
+            "array" => JsonType::Array,
+            "object" => JsonType::Object,
+            _ => return None,
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            JsonType::Null => "null",
+            JsonType::Boolean => "boolean",
+            JsonType::Integer => "integer",
+            JsonType::Number => "number",
+            JsonType::String => "string",
+            JsonType::Array => "array",
+            JsonType::Object => "object",
+        }
+    }
+
+    /// The narrowest type of `value` (integral numbers are integers)
+    fn of(value: &Value) -> JsonType {
+        match value {
+            Value::Null => JsonType::Null,
+            Value::Bool(_) => JsonType::Boolean,
+            Value::Number(n) if n.as_f64().is_none_or(|f| f.fract() == 0.0) => JsonType::Integer,
+            Value::Number(_) => JsonType::Number,
+            Value::String(_) => JsonType::String,
+            Value::Array(_) => JsonType::Array,
+            Value::Object(_) => JsonType::Object,
+        }
+    }
+
+    fn accepts(self, value: &Value) -> bool {
+        match (self, JsonType::of(value)) {
+            (JsonType::Number, JsonType::Integer) => true,
+            (expected, actual) => expected == actual,
+        }
+    }
+}
+
+/// A compiled schema
+#[derive(Debug, Clone, Default)]
+struct Schema {
+    /// The `false` schema
+    rejects_all: bool,
+    types: Option<Vec<JsonType>>,
+    enum_values: Option<Vec<Value>>,
+    const_value: Option<Value>,
+    required: Vec<String>,
+    properties: Vec<(String, Schema)>,
+    /// `additionalProperties: false`
+    closed: bool,
+    items: Option<Box<Schema>>,
+    minimum: Option<f64>,
+    maximum: Option<f64>,
+    exclusive_minimum: Option<f64>,
+    exclusive_maximum: Option<f64>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    min_items: Option<usize>,
+    max_items: Option<usize>,
+    pattern: Option<Pattern>,
+}
+
+/// Where in a schema compilation failed, and why
+type CompileFailure = (String, String);
+
+fn child_pointer(path: &str, key: &str) -> String {
+    format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"))
+}
+
+fn compile_number(value: &Value, path: &str) -> Result<f64, CompileFailure> {
+    value
+        .as_f64()
+        .ok_or_else(|| (path.to_string(), "must be a number".to_string()))
+}
+
+fn compile_count(value: &Value, path: &str) -> Result<usize, CompileFailure> {
+    value
+        .as_u64()
+        .and_then(|count| usize::try_from(count).ok())
+        .ok_or_else(|| {
+            (
+                path.to_string(),
+                "must be a non-negative integer".to_string(),
+            )
+        })
+}
+
+fn compile(schema: &Value, path: &str, depth: usize) -> Result<Schema, CompileFailure> {
+    if depth > MAX_ARGS_SCHEMA_DEPTH {
+        return Err((
+            path.to_string(),
+            format!("nested deeper than {} levels", MAX_ARGS_SCHEMA_DEPTH),
+        ));
+    }
+    let keywords = match schema {
+        Value::Bool(accepts) => {
+            return Ok(Schema {
+                rejects_all: !accepts,
+                ..Schema::default()
+            })
+        }
+        Value::Object(keywords) => keywords,
+        _ => {
+            return Err((
+                path.to_string(),
+                "a schema must be an object or a boolean".to_string(),
+            ))
+        }
+    };
+
+    let mut compiled = Schema::default();
+    for (keyword, value) in keywords {
+        let at = child_pointer(path, keyword);
+        match keyword.as_str() {
+            "type" => {
+                let names: Vec<&Value> = match value {
+                    Value::Array(names) if !names.is_empty() => names.iter().collect(),
+                    Value::String(_) => vec![value],
+                    _ => return Err((at, "must be a type name or a list of them".to_string())),
+                };
+                let types = names
+                    .into_iter()
+                    .map(|name| name.as_str().and_then(JsonType::parse))
+                    .collect::<Option<Vec<JsonType>>>()
+                    .ok_or_else(|| (at.clone(), format!("unknown type in {}", value)))?;
+                compiled.types = Some(types);
+            }
+            "enum" => match value {
+                Value::Array(values) if !values.is_empty() => {
+                    compiled.enum_values = Some(values.clone())
+                }
+                _ => return Err((at, "must be a non-empty list".to_string())),
+            },
+            "const" => compiled.const_value = Some(value.clone()),
+            "required" => {
+                compiled.required = value
+                    .as_array()
+                    .and_then(|names| {
+                        names
+                            .iter()
+                            .map(|name| name.as_str().map(str::to_string))
+                            .collect::<Option<Vec<String>>>()
+                    })
+                    .ok_or_else(|| (at, "must be a list of property names".to_string()))?;
+            }
+            "properties" => {
+                let properties = value
+                    .as_object()
+                    .ok_or_else(|| (at.clone(), "must be an object of schemas".to_string()))?;
+                for (name, property) in properties {
+                    let property_path = child_pointer(&at, name);
+                    let schema = compile(property, &property_path, depth + 1)?;
+                    compiled.properties.push((name.clone(), schema));
+                }
+            }
+            "additionalProperties" => match value {
+                Value::Bool(allowed) => compiled.closed = !allowed,
+                _ => return Err((at, "only true or false is supported".to_string())),
+            },
+            "items" => match value {
+                Value::Array(_) => return Err((at, "tuple items are not supported".to_string())),
+                _ => compiled.items = Some(Box::new(compile(value, &at, depth + 1)?)),
+            },
+            "minimum" => compiled.minimum = Some(compile_number(value, &at)?),
+            "maximum" => compiled.maximum = Some(compile_number(value, &at)?),
+            "exclusiveMinimum" => compiled.exclusive_minimum = Some(compile_number(value, &at)?),
+            "exclusiveMaximum" => compiled.exclusive_maximum = Some(compile_number(value, &at)?),
+            "minLength" => compiled.min_length = Some(compile_count(value, &at)?),
+            "maxLength" => compiled.max_length = Some(compile_count(value, &at)?),
+            "minItems" => compiled.min_items = Some(compile_count(value, &at)?),
+            "maxItems" => compiled.max_items = Some(compile_count(value, &at)?),
+            "pattern" => {
+                let source = value
+                    .as_str()
+                    .ok_or_else(|| (at.clone(), "must be a string".to_string()))?;
+                compiled.pattern = Some(Pattern::compile(source).map_err(|e| (at, e))?);
+            }
+            annotation if ANNOTATION_KEYWORDS.contains(&annotation) => {}
+            unsupported => {
+                return Err((at, format!("unsupported keyword \"{}\"", unsupported)));
+            }
+        }
+    }
+    Ok(compiled)
+}
+
+impl Schema {
+    /// Adds every violation of `value` (at `path`) to `violations`
+    fn check(
+        &self,
+        value: &Value,
+        path: &str,
+        depth: usize,
+        violations: &mut Vec<ArgsSchemaViolation>,
+    ) {
+        let mut violation = |message: String| {
+            violations.push(ArgsSchemaViolation {
+                path: path.to_string(),
+                message,
+            })
+        };
+        if self.rejects_all {
+            violation("no value is allowed here".to_string());
+            return;
+        }
+        if depth > MAX_ARGS_SCHEMA_DEPTH {
+            violation(format!(
+                "nested deeper than {} levels",
+                MAX_ARGS_SCHEMA_DEPTH
+            ));
+            return;
+        }
+        if let Some(types) = &self.types {
+            if !types.iter().any(|t| t.accepts(value)) {
+                let expected: Vec<&str> = types.iter().map(|t| t.name()).collect();
+                violation(format!(
+                    "expected {}, got {}",
+                    expected.join(" or "),
+                    JsonType::of(value).name()
+                ));
+                return;
+            }
+        }
+        if let Some(values) = &self.enum_values {
+            if !values.contains(value) {
+                violation(format!("must be one of {}", Value::Array(values.clone())));
+            }
+        }
+        if let Some(expected) = &self.const_value {
+            if expected != value {
+                violation(format!("must be {}", expected));
+            }
+        }
+
+        match value {
+            Value::Number(n) => {
+                let n = n.as_f64().unwrap_or(f64::NAN);
+                if let Some(min) = self.minimum.filter(|min| n < *min) {
+                    violation(format!("must be at least {}", min));
+                }
+                if let Some(max) = self.maximum.filter(|max| n > *max) {
+                    violation(format!("must be at most {}", max));
+                }
+                if let Some(min) = self.exclusive_minimum.filter(|min| n <= *min) {
+                    violation(format!("must be greater than {}", min));
+                }
+                if let Some(max) = self.exclusive_maximum.filter(|max| n >= *max) {
+                    violation(format!("must be less than {}", max));
+                }
+            }
+            Value::String(s) => {
+                let length = s.chars().count();
+                if let Some(min) = self.min_length.filter(|min| length < *min) {
+                    violation(format!("must be at least {} characters long", min));
+                }
+                if let Some(max) = self.max_length.filter(|max| length > *max) {
+                    violation(format!("must be at most {} characters long", max));
+                }
+                if let Some(pattern) = &self.pattern {
+                    match pattern.find_in(s) {
+                        PatternMatch::Matched => {}
+                        PatternMatch::NotMatched => {
+                            violation(format!("must match pattern {}", pattern.source()))
+                        }
+                        PatternMatch::LimitExceeded => violation(format!(
+                            "pattern {} is too expensive to evaluate on this value",
+                            pattern.source()
+                        )),
+                    }
+                }
+            }
+            Value::Array(items) => {
+                if let Some(min) = self.min_items.filter(|min| items.len() < *min) {
+                    violation(format!("must have at least {} items", min));
+                }
+                if let Some(max) = self.max_items.filter(|max| items.len() > *max) {
+                    violation(format!("must have at most {} items", max));
+                }
+                if let Some(schema) = &self.items {
+                    for (i, item) in items.iter().enumerate() {
+                        let item_path = format!("{}/{}", path, i);
+                        schema.check(item, &item_path, depth + 1, violations);
+                    }
+                }
+            }
+            Value::Object(fields) => {
+                for name in &self.required {
+                    if !fields.contains_key(name) {
+                        violations.push(ArgsSchemaViolation {
+                            path: child_pointer(path, name),
+                            message: "required property is missing".to_string(),
+                        });
+                    }
+                }
+                for (name, schema) in &self.properties {
+                    if let Some(field) = fields.get(name) {
+                        let field_path = child_pointer(path, name);
+                        schema.check(field, &field_path, depth + 1, violations);
+                    }
+                }
+                if self.closed {
+                    for name in fields.keys() {
+                        if !self.properties.iter().any(|(known, _)| known == name) {
+                            violations.push(ArgsSchemaViolation {
+                                path: child_pointer(path, name),
+                                message: "property is not allowed by the schema".to_string(),
+                            });
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Compiled `workerPolicy.argsSchemas`, keyed by (contractId, methodName)
+#[derive(Debug, Clone, Default)]
+pub struct ArgsSchemas {
+    schemas: HashMap<(String, String), Schema>,
+}
+
+impl ArgsSchemas {
+    /// Compiles every entry, reporting all that do not compile
+    pub fn compile(
+        entries: &[ArgsSchemaEntry],
+    ) -> Result<ArgsSchemas, Vec<ArgsSchemaCompileError>> {
+        let mut schemas = HashMap::new();
+        let mut errors = Vec::new();
+        for entry in entries {
+            let error = |path: String, message: String| ArgsSchemaCompileError {
+                contract_id: entry.contract_id.clone(),
+                method_name: entry.method_name.clone(),
+                path,
+                message,
+            };
+            if entry.contract_id.is_empty() || entry.method_name.is_empty() {
+                errors.push(error(
+                    String::new(),
+                    "contractId and methodName must not be empty".to_string(),
+                ));
+                continue;
+            }
+            let key = (entry.contract_id.clone(), entry.method_name.clone());
+            if schemas.contains_key(&key) {
+                errors.push(error(
+                    String::new(),
+                    "registered more than once".to_string(),
+                ));
+                continue;
+            }
+            match compile(&entry.schema, "", 0) {
+                Ok(schema) => {
+                    schemas.insert(key, schema);
+                }
+                Err((path, message)) => errors.push(error(path, message)),
+            }
+        }
+        if entries.len() > MAX_ARGS_SCHEMAS {
+            errors.push(ArgsSchemaCompileError {
+                contract_id: String::new(),
+                method_name: String::new(),
+                path: String::new(),
+                message: format!(
+                    "{} schemas registered, at most {} are allowed",
+                    entries.len(),
+                    MAX_ARGS_SCHEMAS
+                ),
+            });
+        }
+        if errors.is_empty() {
+            Ok(ArgsSchemas { schemas })
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Checks the args of every FunctionCall of the batch that has a registered schema
+    pub fn check_batch(
+        &self,
+        receivers_and_actions: &[(String, Vec<ActionParams>)],
+    ) -> Result<(), ArgsSchemaError> {
+        let mut violations = Vec::new();
+        for (i, (receiver_id, actions)) in receivers_and_actions.iter().enumerate() {
+            for (j, action) in actions.iter().enumerate() {
+                let ActionParams::FunctionCall {
+                    method_name, args, ..
+                } = action
+                else {
+                    continue;
+                };
+                let Some(schema) = self
+                    .schemas
+                    .get(&(receiver_id.clone(), method_name.clone()))
+                else {
+                    continue;
+                };
+                // Not JSON: left to the ArgsNotValidated warning
+                let Ok(args) = serde_json::from_str::<Value>(args) else {
+                    continue;
+                };
+                let path = format!("/txSigningRequests/{}/actions/{}/args", i, j);
+                schema.check(&args, &path, 0, &mut violations);
+            }
+        }
+        if violations.is_empty() {
+            return Ok(());
+        }
+        let total = violations.len();
+        violations.truncate(MAX_REPORTED_ARGS_SCHEMA_VIOLATIONS);
+        Err(ArgsSchemaError::Violations { violations, total })
+    }
+}
+
+/// Compiles the policy's argsSchemas and checks the batch against them
+pub fn check_batch_args(
+    policy: Option<&WorkerPolicy>,
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+) -> Result<(), ArgsSchemaError> {
+    let Some(entries) = policy
+        .map(|p| p.args_schemas.as_slice())
+        .filter(|entries| !entries.is_empty())
+    else {
+        return Ok(());
+    };
+    ArgsSchemas::compile(entries)
+        .map_err(ArgsSchemaError::InvalidSchemas)?
+        .check_batch(receivers_and_actions)
+}
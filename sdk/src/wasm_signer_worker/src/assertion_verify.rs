@@ -0,0 +1,161 @@
+// === LOCAL ASSERTION VERIFICATION ===
+// Assertions carry no public key, so the TS layer forwards the COSE key registration stored
+// for the credential (`response.credentialPublicKey`, from the IndexedDB authenticator
+// record), and the assertion signature is checked in the worker before any RPC call is spent
+// on contract verification. This is a fast local failure only; the contract still verifies
+// every assertion against its own copy of the key. Without a forwarded key, or for
+// algorithms not listed here, nothing is checked locally.
+
+// `signature::Verifier`, re-exported by ed25519-dalek and shared by the p256 and rsa keys
+use ed25519_dalek::Verifier as _;
+use rsa::pkcs1v15;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::cose::{decode_cose_key, Jwk};
+use crate::encoders::base64_url_decode;
+use crate::error::{SignerErrorCode, WebAuthnDataSection};
+
+/// COSE algorithm identifiers (RFC 9053 / RFC 8812)
+pub const COSE_ALG_EDDSA: i64 = -8;
+pub const COSE_ALG_ES256: i64 = -7;
+pub const COSE_ALG_RS256: i64 = -257;
+
+/// The assertion fields needed for local verification, read from a serialized credential
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct AssertionResponse {
+    #[serde(rename = "clientDataJSON")]
+    client_data_json: String,
+    authenticator_data: String,
+    signature: String,
+    /// base64url COSE key, as registration stored it
+    #[serde(default)]
+    credential_public_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SerializedAssertion {
+    response: AssertionResponse,
+}
+
+/// Outcome of a local check that did not reject the assertion
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalAssertionCheck {
+    Verified,
+    /// No public key was forwarded (behaviour unchanged)
+    NoPublicKey,
+    /// The forwarded key uses an algorithm the worker cannot verify; left to the contract
+    UnsupportedAlgorithm(i64),
+}
+
+/// Verifies the assertion signature over `authenticatorData || SHA-256(clientDataJSON)`
+/// when the serialized credential carries `credentialPublicKey`, with the algorithm the COSE
+/// key declares. Errors are prefixed with `AssertionSignatureInvalid`.
+pub fn verify_assertion_locally(
+    credential: &serde_json::Value,
+) -> Result<LocalAssertionCheck, String> {
+    let Ok(SerializedAssertion { response }) =
+        serde_json::from_value::<SerializedAssertion>(credential.clone())
+    else {
+        return Ok(LocalAssertionCheck::NoPublicKey);
+    };
+    let Some(cose_key_b64u) = &response.credential_public_key else {
+        return Ok(LocalAssertionCheck::NoPublicKey);
+    };
+
+    let invalid =
+        |detail: String| format!("{}: {}", SignerErrorCode::AssertionSignatureInvalid, detail);
+    let cose_key = base64_url_decode(cose_key_b64u)
+        .map_err(|e| invalid(format!("credentialPublicKey: {}", e)))?;
+    let cose_key = decode_cose_key(&cose_key, WebAuthnDataSection::CoseKey, 0)
+        .map_err(|e| invalid(format!("credentialPublicKey: {}", e)))?;
+    // WebAuthn credential keys always name their algorithm (alg, label 3)
+    let algorithm = cose_key
+        .alg
+        .ok_or_else(|| invalid("credentialPublicKey has no algorithm".to_string()))?;
+    if ![COSE_ALG_EDDSA, COSE_ALG_ES256, COSE_ALG_RS256].contains(&algorithm) {
+        return Ok(LocalAssertionCheck::UnsupportedAlgorithm(algorithm));
+    }
+
+    let authenticator_data = base64_url_decode(&response.authenticator_data)
+        .map_err(|e| invalid(format!("authenticatorData: {}", e)))?;
+    let client_data_json = base64_url_decode(&response.client_data_json)
+        .map_err(|e| invalid(format!("clientDataJSON: {}", e)))?;
+    let signature =
+        base64_url_decode(&response.signature).map_err(|e| invalid(format!("signature: {}", e)))?;
+
+    let mut signed_data = authenticator_data;
+    signed_data.extend_from_slice(&Sha256::digest(&client_data_json));
+
+    verify_signature(algorithm, &cose_key.jwk, &signed_data, &signature).map_err(invalid)?;
+    Ok(LocalAssertionCheck::Verified)
+}
+
+/// A base64url JWK member of a decoded COSE key
+fn jwk_member(value: &Option<String>, name: &str) -> Result<Vec<u8>, String> {
+    value
+        .as_deref()
+        .and_then(|v| base64_url_decode(v).ok())
+        .ok_or_else(|| format!("credentialPublicKey has no {}", name))
+}
+
+fn verify_signature(
+    algorithm: i64,
+    key: &Jwk,
+    signed_data: &[u8],
+    signature: &[u8],
+) -> Result<(), String> {
+    let curve = key.crv.as_deref().unwrap_or_default();
+    match algorithm {
+        COSE_ALG_EDDSA => {
+            if curve != "Ed25519" {
+                return Err("credentialPublicKey is not an Ed25519 key".to_string());
+            }
+            let x: [u8; 32] = jwk_member(&key.x, "x")?
+                .try_into()
+                .map_err(|_| "Invalid Ed25519 public key length".to_string())?;
+            let key = ed25519_dalek::VerifyingKey::from_bytes(&x)
+                .map_err(|e| format!("Invalid Ed25519 public key: {}", e))?;
+            let signature = ed25519_dalek::Signature::from_slice(signature)
+                .map_err(|e| format!("Invalid Ed25519 signature: {}", e))?;
+            key.verify(signed_data, &signature)
+                .map_err(|_| "Ed25519 signature does not verify".to_string())
+        }
+        COSE_ALG_ES256 => {
+            if curve != "P-256" {
+                return Err("credentialPublicKey is not a P-256 key".to_string());
+            }
+            // Uncompressed SEC1 point: 0x04 || x || y
+            let point = [
+                &[0x04][..],
+                &jwk_member(&key.x, "x")?,
+                &jwk_member(&key.y, "y")?,
+            ]
+            .concat();
+            let key = p256::ecdsa::VerifyingKey::from_sec1_bytes(&point)
+                .map_err(|e| format!("Invalid P-256 public key: {}", e))?;
+            // WebAuthn ES256 signatures are ASN.1 DER encoded
+            let signature = p256::ecdsa::DerSignature::try_from(signature)
+                .map_err(|e| format!("Invalid ES256 signature: {}", e))?;
+            key.verify(signed_data, &signature)
+                .map_err(|_| "ES256 signature does not verify".to_string())
+        }
+        COSE_ALG_RS256 => {
+            if key.kty != "RSA" {
+                return Err("credentialPublicKey is not an RSA key".to_string());
+            }
+            let key = rsa::RsaPublicKey::new(
+                rsa::BigUint::from_bytes_be(&jwk_member(&key.n, "n")?),
+                rsa::BigUint::from_bytes_be(&jwk_member(&key.e, "e")?),
+            )
+            .map_err(|e| format!("Invalid RSA public key: {}", e))?;
+            let key = pkcs1v15::VerifyingKey::<Sha256>::new(key);
+            let signature = pkcs1v15::Signature::try_from(signature)
+                .map_err(|e| format!("Invalid RS256 signature: {}", e))?;
+            key.verify(signed_data, &signature)
+                .map_err(|_| "RS256 signature does not verify".to_string())
+        }
+        _ => Err(format!("Unsupported COSE algorithm {}", algorithm)),
+    }
+}
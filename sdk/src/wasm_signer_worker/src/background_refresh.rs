@@ -0,0 +1,376 @@
+// === BACKGROUND REFRESH ===
+// An integrator that keeps one signer worker alive for a signing session can have the worker
+// keep its block and access-key nonces warm itself, instead of the TS NonceManager pushing
+// them. StartBackgroundRefresh names the accounts (and the public key whose nonce to track)
+// and an interval, raised to MIN_BACKGROUND_REFRESH_INTERVAL_MS; the worker then refreshes on
+// a setTimeout loop until StopBackgroundRefresh, another StartBackgroundRefresh or
+// WipeAllState. The workers of SignerWorkerManager close after each request, so it refuses
+// these requests (STANDALONE_WORKER_REQUEST_TYPES in signer-worker.ts): they only serve
+// workers the integrator hosts itself.
+//
+// A tick does nothing while the page is suspended (SuspendHint until ResumeHint) or while no
+// keypair is unlocked: no peer session to an unlocked VRF session and no live session key.
+// A failed tick is not retried at the interval: the wait doubles with every consecutive
+// failure, up to MAX_BACKGROUND_REFRESH_BACKOFF_MS, and only the first failure of a streak is
+// logged. The failure count and the last error are reported by BackgroundRefreshStatus,
+// with the cached block and nonces.
+//
+// Each StartBackgroundRefresh starts a new generation; a loop whose generation is no longer
+// current exits at its next tick, and a tick that finishes after a stop discards its results.
+
+use std::cell::RefCell;
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::config::{
+    MAX_BACKGROUND_REFRESH_ACCOUNTS, MAX_BACKGROUND_REFRESH_BACKOFF_MS,
+    MIN_BACKGROUND_REFRESH_INTERVAL_MS,
+};
+use crate::error::RpcErrorKind;
+use crate::peer_channel;
+use crate::rpc_client::{refresh_nonce_and_block, RpcClient};
+use crate::state;
+
+/// An account whose access-key nonce is kept warm
+#[wasm_bindgen]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshAccount {
+    #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
+    pub near_account_id: String,
+    /// Access key whose nonce is tracked ("ed25519:<base58>")
+    #[wasm_bindgen(getter_with_clone, js_name = "publicKey")]
+    pub public_key: String,
+}
+
+/// Why a tick does not refresh
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshPause {
+    /// SuspendHint received, no ResumeHint since
+    Suspended,
+    /// Neither a peer session to an unlocked VRF session nor a live session key
+    NoUnlockedKeypair,
+}
+
+impl RefreshPause {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RefreshPause::Suspended => "suspended",
+            RefreshPause::NoUnlockedKeypair => "noUnlockedKeypair",
+        }
+    }
+}
+
+/// The latest final block fetched
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshedBlock {
+    /// Decimal string, like `txBlockHeight` of a TransactionContext
+    #[wasm_bindgen(getter_with_clone)]
+    pub height: String,
+    #[wasm_bindgen(getter_with_clone)]
+    pub hash: String,
+    #[wasm_bindgen(js_name = "refreshedAtMs")]
+    pub refreshed_at_ms: f64,
+}
+
+/// Nonce for the next transaction of an account's key
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshedNonce {
+    #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
+    pub near_account_id: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "publicKey")]
+    pub public_key: String,
+    /// Decimal string, like `nextNonce` of a TransactionContext
+    #[wasm_bindgen(getter_with_clone, js_name = "nextNonce")]
+    pub next_nonce: String,
+    #[wasm_bindgen(js_name = "refreshedAtMs")]
+    pub refreshed_at_ms: f64,
+}
+
+/// BackgroundRefreshStatus result
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BackgroundRefreshStatus {
+    pub running: bool,
+    /// The interval in use, after clamping (0 when not running)
+    #[wasm_bindgen(js_name = "intervalMs")]
+    pub interval_ms: u32,
+    #[wasm_bindgen(getter_with_clone)]
+    pub accounts: Vec<RefreshAccount>,
+    /// Why ticks of the running refresh are skipped right now ("suspended",
+    /// "noUnlockedKeypair"); None when they refresh
+    #[wasm_bindgen(getter_with_clone)]
+    pub paused: Option<String>,
+    #[wasm_bindgen(js_name = "consecutiveFailures")]
+    pub consecutive_failures: u32,
+    #[wasm_bindgen(js_name = "nextRefreshAtMs")]
+    pub next_refresh_at_ms: Option<f64>,
+    #[wasm_bindgen(js_name = "lastSuccessAtMs")]
+    pub last_success_at_ms: Option<f64>,
+    /// Error code of the last failed tick (e.g. "RpcUnreachable")
+    #[wasm_bindgen(getter_with_clone, js_name = "lastErrorCode")]
+    pub last_error_code: Option<String>,
+    #[wasm_bindgen(getter_with_clone, js_name = "lastError")]
+    pub last_error: Option<String>,
+    #[wasm_bindgen(js_name = "lastErrorAtMs")]
+    pub last_error_at_ms: Option<f64>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub block: Option<RefreshedBlock>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub nonces: Vec<RefreshedNonce>,
+}
+
+struct Schedule {
+    interval_ms: u32,
+    accounts: Vec<RefreshAccount>,
+}
+
+#[derive(Default)]
+struct RefreshState {
+    generation: u32,
+    schedule: Option<Schedule>,
+    suspended: bool,
+    consecutive_failures: u32,
+    next_refresh_at_ms: Option<f64>,
+    last_success_at_ms: Option<f64>,
+    last_error: Option<(RpcErrorKind, f64)>,
+    block: Option<RefreshedBlock>,
+    nonces: Vec<RefreshedNonce>,
+}
+
+thread_local! {
+    static REFRESH: RefCell<RefreshState> = RefCell::new(RefreshState::default());
+}
+
+fn with_refresh<R>(f: impl FnOnce(&mut RefreshState) -> R) -> R {
+    REFRESH.with(|r| f(&mut r.borrow_mut()))
+}
+
+/// `requested_ms` raised to MIN_BACKGROUND_REFRESH_INTERVAL_MS
+pub fn clamp_interval_ms(requested_ms: u32) -> u32 {
+    requested_ms.max(MIN_BACKGROUND_REFRESH_INTERVAL_MS)
+}
+
+/// Wait before the next tick after `consecutive_failures` failed ticks in a row: the interval,
+/// doubled per failure up to MAX_BACKGROUND_REFRESH_BACKOFF_MS (never below the interval)
+pub fn backoff_delay_ms(interval_ms: u32, consecutive_failures: u32) -> u32 {
+    let factor = 1u32 << consecutive_failures.min(16);
+    interval_ms
+        .saturating_mul(factor)
+        .min(MAX_BACKGROUND_REFRESH_BACKOFF_MS)
+        .max(interval_ms)
+}
+
+/// Whether any keypair is unlocked: the peer session of an unlocked VRF session, or a live
+/// session key
+pub fn keypair_unlocked() -> bool {
+    peer_channel::peer_session().is_some() || state::has_live_session_key()
+}
+
+/// Why a tick would not refresh right now, if it would not
+pub fn pause_reason() -> Option<RefreshPause> {
+    if with_refresh(|r| r.suspended) {
+        Some(RefreshPause::Suspended)
+    } else if !keypair_unlocked() {
+        Some(RefreshPause::NoUnlockedKeypair)
+    } else {
+        None
+    }
+}
+
+/// Replaces any running refresh with one of `accounts` every `interval_ms` (clamped). Returns
+/// the new generation, for `run_tick` and `spawn_refresh_loop`.
+pub fn start(
+    near_rpc_url: &str,
+    interval_ms: u32,
+    accounts: Vec<RefreshAccount>,
+) -> Result<u32, String> {
+    if near_rpc_url.trim().is_empty() {
+        return Err("StartBackgroundRefresh requires nearRpcUrl".to_string());
+    }
+    if accounts.is_empty() || accounts.len() > MAX_BACKGROUND_REFRESH_ACCOUNTS {
+        return Err(format!(
+            "StartBackgroundRefresh requires 1 to {} accounts, got {}",
+            MAX_BACKGROUND_REFRESH_ACCOUNTS,
+            accounts.len()
+        ));
+    }
+    if let Some(account) = accounts
+        .iter()
+        .find(|a| a.near_account_id.is_empty() || a.public_key.is_empty())
+    {
+        return Err(format!(
+            "Refresh account needs nearAccountId and publicKey: {:?}",
+            account
+        ));
+    }
+    Ok(with_refresh(|r| {
+        let suspended = r.suspended;
+        let generation = r.generation.wrapping_add(1);
+        *r = RefreshState {
+            generation,
+            schedule: Some(Schedule {
+                interval_ms: clamp_interval_ms(interval_ms),
+                accounts,
+            }),
+            suspended,
+            next_refresh_at_ms: Some(state::now_ms()),
+            ..RefreshState::default()
+        };
+        generation
+    }))
+}
+
+/// Stops the refresh and drops what it cached. Returns whether one was running.
+pub fn stop() -> bool {
+    with_refresh(|r| {
+        let running = r.schedule.is_some();
+        *r = RefreshState {
+            generation: r.generation.wrapping_add(1),
+            suspended: r.suspended,
+            ..RefreshState::default()
+        };
+        running
+    })
+}
+
+/// SuspendHint: ticks pause until `resume`. Returns whether the refresh was already suspended.
+pub fn suspend() -> bool {
+    with_refresh(|r| std::mem::replace(&mut r.suspended, true))
+}
+
+/// ResumeHint. Returns whether the refresh was suspended.
+pub fn resume() -> bool {
+    with_refresh(|r| std::mem::replace(&mut r.suspended, false))
+}
+
+pub fn status() -> BackgroundRefreshStatus {
+    let paused = pause_reason();
+    with_refresh(|r| BackgroundRefreshStatus {
+        running: r.schedule.is_some(),
+        interval_ms: r.schedule.as_ref().map_or(0, |s| s.interval_ms),
+        accounts: r
+            .schedule
+            .as_ref()
+            .map(|s| s.accounts.clone())
+            .unwrap_or_default(),
+        paused: r
+            .schedule
+            .as_ref()
+            .and(paused)
+            .map(|p| p.as_str().to_string()),
+        consecutive_failures: r.consecutive_failures,
+        next_refresh_at_ms: r.next_refresh_at_ms,
+        last_success_at_ms: r.last_success_at_ms,
+        last_error_code: r
+            .last_error
+            .as_ref()
+            .map(|(e, _)| e.code().as_str().to_string()),
+        last_error: r.last_error.as_ref().map(|(e, _)| e.to_string()),
+        last_error_at_ms: r.last_error.as_ref().map(|(_, at)| *at),
+        block: r.block.clone(),
+        nonces: r.nonces.clone(),
+    })
+}
+
+/// One tick of the refresh of `generation`: refreshes the block and every account's nonce
+/// unless paused, records the outcome, and returns the wait before the next tick. None when
+/// `generation` is no longer running, which ends its loop.
+// Only the WASM loop calls it outside tests
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+pub async fn run_tick<R: RpcClient>(rpc: &R, generation: u32) -> Option<u32> {
+    let accounts = with_refresh(|r| match &r.schedule {
+        Some(schedule) if r.generation == generation => Some(schedule.accounts.clone()),
+        _ => None,
+    })?;
+    let outcome = if pause_reason().is_some() {
+        None
+    } else {
+        let mut refreshed = Vec::with_capacity(accounts.len());
+        let mut result = Ok(None);
+        for account in &accounts {
+            match refresh_nonce_and_block(rpc, &account.near_account_id, &account.public_key).await
+            {
+                Ok((next_nonce, block)) => {
+                    refreshed.push((account, next_nonce));
+                    result = Ok(Some(block));
+                }
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        Some(result.map(|block| (block, refreshed)))
+    };
+
+    let now = state::now_ms();
+    with_refresh(|r| {
+        let interval_ms = match &r.schedule {
+            Some(schedule) if r.generation == generation => schedule.interval_ms,
+            // Stopped or replaced while the calls were in flight
+            _ => return None,
+        };
+        match outcome {
+            None => {}
+            Some(Ok((block, refreshed))) => {
+                if r.consecutive_failures > 0 {
+                    info!(
+                        "Background refresh recovered after {} failed ticks",
+                        r.consecutive_failures
+                    );
+                }
+                r.consecutive_failures = 0;
+                r.last_success_at_ms = Some(now);
+                if let Some(block) = block {
+                    r.block = Some(RefreshedBlock {
+                        height: block.height.to_string(),
+                        hash: block.hash,
+                        refreshed_at_ms: now,
+                    });
+                }
+                r.nonces = refreshed
+                    .into_iter()
+                    .map(|(account, next_nonce)| RefreshedNonce {
+                        near_account_id: account.near_account_id.clone(),
+                        public_key: account.public_key.clone(),
+                        next_nonce: next_nonce.to_string(),
+                        refreshed_at_ms: now,
+                    })
+                    .collect();
+            }
+            Some(Err(e)) => {
+                if r.consecutive_failures == 0 {
+                    warn!("Background refresh failed, backing off: {}", e);
+                }
+                r.consecutive_failures = r.consecutive_failures.saturating_add(1);
+                r.last_error = Some((e, now));
+            }
+        }
+        let delay_ms = backoff_delay_ms(interval_ms, r.consecutive_failures);
+        r.next_refresh_at_ms = Some(now + delay_ms as f64);
+        Some(delay_ms)
+    })
+}
+
+/// Runs the ticks of `generation` on setTimeout until it stops
+#[cfg(target_arch = "wasm32")]
+pub fn spawn_refresh_loop(generation: u32, near_rpc_url: String) {
+    wasm_bindgen_futures::spawn_local(async move {
+        let rpc = crate::rpc_client::NearRpcClient::new(&near_rpc_url);
+        while let Some(delay_ms) = run_tick(&rpc, generation).await {
+            crate::rpc_calls::sleep_ms(delay_ms).await;
+        }
+    });
+}
+
+/// Non-WASM fallback (native tests): no timers; tests drive `run_tick` themselves
+#[cfg(not(target_arch = "wasm32"))]
+pub fn spawn_refresh_loop(_generation: u32, _near_rpc_url: String) {}
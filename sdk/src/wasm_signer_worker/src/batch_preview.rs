@@ -0,0 +1,100 @@
+// === BATCH PREVIEW AND DEDUPLICATION ===
+// Dapps sometimes send the same transaction twice in a batch (a double-clicked button), or a
+// batch that no longer matches what they previewed to the user. Before a SignTransactionsWithActions
+// batch is looked at by anything else:
+//   - with `deduplicate`, every transaction identical to an earlier one of the batch is dropped
+//     (the first is kept), and the dropped indexes are reported in the confirmation payload
+//     and the result;
+//   - with `previewDigest`, the batch (once deduplicated) must have that canonical digest, as
+//     returned by SummarizeTransactions for the batch that was previewed, or the request fails
+//     with PayloadChangedSincePreview.
+// Both compare canonical digests: the receiver and parsed actions, keys alphabetized (see
+// compute_intent_digest_from_js_inputs), so how the actions JSON is formatted does not matter but
+// the order of transactions and actions does. Unlike the confirmation's intent digest, the
+// canonical digest carries no receiver annotations or summary blocks, so it changes only with
+// the transactions themselves.
+
+use crate::error::{PayloadChangedSincePreviewError, SignerErrorCode};
+use crate::handlers::confirm_tx_details::compute_intent_digest_from_js_inputs;
+use crate::handlers::handle_sign_transactions_with_actions::{
+    SignTransactionsWithActionsRequest, TransactionPayload,
+};
+use std::collections::HashSet;
+
+/// What preparing a batch did to it, shown in the confirmation payload
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchPreparation {
+    /// With `deduplicate`: indexes (in the batch as sent) of the transactions dropped
+    pub removed_duplicate_indexes: Option<Vec<u32>>,
+    /// With `previewDigest`: the digest the batch was checked to have
+    pub verified_preview_digest: Option<String>,
+}
+
+/// The canonical digest of one transaction (None when its actions do not parse)
+fn transaction_digest(tx: &TransactionPayload) -> Option<String> {
+    let actions = tx.parsed_actions().ok()?;
+    compute_intent_digest_from_js_inputs(&[(tx.receiver_id.clone(), actions)], None).ok()
+}
+
+/// Drops every transaction identical to an earlier one of `txs`, returning the indexes dropped.
+/// Transactions whose actions do not parse are never duplicates; they fail later like any
+/// other.
+pub fn deduplicate_transactions(txs: &mut Vec<TransactionPayload>) -> Vec<u32> {
+    let mut seen = HashSet::new();
+    let mut removed = Vec::new();
+    let mut index = 0u32;
+    txs.retain(|tx| {
+        let keep = match transaction_digest(tx) {
+            Some(digest) => seen.insert(digest),
+            None => true,
+        };
+        if !keep {
+            removed.push(index);
+        }
+        index += 1;
+        keep
+    });
+    removed
+}
+
+/// The canonical digest of a batch (what `previewDigest` is checked against)
+pub fn canonical_batch_digest(txs: &[TransactionPayload]) -> Result<String, String> {
+    let receivers_and_actions = txs
+        .iter()
+        .enumerate()
+        .map(|(i, tx)| {
+            tx.parsed_actions()
+                .map(|actions| (tx.receiver_id.clone(), actions))
+                .map_err(|e| format!("Invalid actions in transaction {}: {}", i, e))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    compute_intent_digest_from_js_inputs(&receivers_and_actions, None)
+}
+
+/// Applies the request's `deduplicate` and `previewDigest` to its batch (None when it sets
+/// neither)
+pub fn prepare_batch(
+    request: &mut SignTransactionsWithActionsRequest,
+) -> Result<Option<BatchPreparation>, (SignerErrorCode, String)> {
+    if !request.deduplicate && request.preview_digest.is_none() {
+        return Ok(None);
+    }
+    let removed_duplicate_indexes = request
+        .deduplicate
+        .then(|| deduplicate_transactions(&mut request.tx_signing_requests));
+    if let Some(preview_digest) = &request.preview_digest {
+        let batch_digest = canonical_batch_digest(&request.tx_signing_requests)
+            .map_err(|e| (SignerErrorCode::InvalidConfig, e))?;
+        if batch_digest != *preview_digest {
+            let err = PayloadChangedSincePreviewError {
+                preview_digest: preview_digest.clone(),
+                batch_digest,
+            };
+            return Err((err.code(), err.to_string()));
+        }
+    }
+    Ok(Some(BatchPreparation {
+        removed_duplicate_indexes,
+        verified_preview_digest: request.preview_digest.clone(),
+    }))
+}
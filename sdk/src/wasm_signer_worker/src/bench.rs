@@ -0,0 +1,375 @@
+// === NATIVE MICRO-BENCHMARKS ===
+// Signing latency regressions only surfaced once users noticed them. Each case below times one
+// hot path of a signing request on the native target, with its inputs prepared outside the
+// timed closure:
+//   cargo test --release bench_signer_hot_paths -- --ignored --nocapture
+// prints the median of each case; tests/perf_budget_tests.rs asserts every case stays under its
+// budget on each `cargo test`. Budgets are about ten times a debug-build median: they catch
+// order-of-magnitude regressions, not noise. A median of `Instant` samples is all that needs,
+// so there is no benchmark-framework dependency. VRF challenge generation lives in the VRF
+// worker and has its own budget test there.
+//
+// RPC goes through MockRpcClient (rpc_client.rs), so the whole build-and-sign path runs
+// without the network.
+
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use crate::actions::ActionParams;
+use crate::confirmation_blocks::SummaryPolicy;
+use crate::cose::describe_attestation_object_b64u;
+use crate::crypto::{decrypt_private_key_with_prf, derive_and_encrypt_keypair_from_dual_prf};
+use crate::encoders::base64_url_encode;
+use crate::error::RpcErrorKind;
+use crate::handlers::confirm_tx_details::{compute_confirmation_intent_digest, ConfirmationResult};
+use crate::handlers::handle_sign_transactions_with_actions::{
+    sign_near_transactions_with_actions_impl, Decryption, TransactionPayload,
+};
+use crate::rpc_calls::{
+    verify_authentication_response_rpc_call, VrfData, VERIFY_AUTHENTICATION_RESPONSE_METHOD,
+};
+use crate::rpc_client::MockRpcClient;
+use crate::tests::block_on;
+use crate::transaction::{build_actions_from_params, build_transaction_with_actions};
+use crate::types::{
+    DualPrfOutputs, WebAuthnAuthenticationCredential, WebAuthnAuthenticationResponse,
+};
+
+pub const BENCH_ACCOUNT_ID: &str = "bench.testnet";
+pub const BENCH_CONTRACT_ID: &str = "web3-authn.testnet";
+const BENCH_PUBLIC_KEY: &str = "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp";
+
+/// MockRpcClient route of the contract's verify_authentication_response view call
+pub fn verification_route() -> String {
+    format!(
+        "query/call_function/{}",
+        VERIFY_AUTHENTICATION_RESPONSE_METHOD
+    )
+}
+
+/// A call_function result whose return value is `contract_response` as JSON bytes
+pub fn call_function_answer(contract_response: &Value) -> Result<Value, RpcErrorKind> {
+    let bytes: Vec<u8> = contract_response.to_string().into_bytes();
+    Ok(json!({ "result": bytes, "logs": ["verified by bench"] }))
+}
+
+/// An RPC whose contract verifies every credential
+pub fn verifying_rpc() -> MockRpcClient {
+    MockRpcClient::new().answer(
+        &verification_route(),
+        call_function_answer(&json!({ "verified": true })),
+    )
+}
+
+/// One benchmarked hot path
+pub struct BenchCase {
+    pub name: &'static str,
+    /// Upper bound on the median of a debug build (see perf_budget_tests.rs)
+    pub budget: Duration,
+    /// Prepares the inputs and returns the closure to time
+    pub prepare: fn() -> Box<dyn FnMut()>,
+}
+
+pub const BENCH_CASES: &[BenchCase] = &[
+    BenchCase {
+        name: "chacha20_decrypt_keypair_blob",
+        budget: Duration::from_millis(4),
+        prepare: prepare_keypair_blob_decrypt,
+    },
+    BenchCase {
+        name: "borsh_serialize_10_action_transaction",
+        budget: Duration::from_micros(100),
+        prepare: prepare_borsh_serialization,
+    },
+    BenchCase {
+        name: "intent_digest_large_tx_tree",
+        budget: Duration::from_millis(400),
+        prepare: prepare_intent_digest,
+    },
+    BenchCase {
+        name: "cose_attestation_parsing",
+        budget: Duration::from_millis(1),
+        prepare: prepare_attestation_parsing,
+    },
+    BenchCase {
+        name: "build_and_sign_batch_of_5",
+        budget: Duration::from_millis(60),
+        prepare: prepare_build_and_sign_batch,
+    },
+];
+
+/// Median wall time of `iterations` runs of `f`, after one warm-up run
+pub fn median_time(iterations: usize, f: &mut dyn FnMut()) -> Duration {
+    f();
+    let mut samples: Vec<Duration> = (0..iterations.max(1))
+        .map(|_| {
+            let started = Instant::now();
+            f();
+            started.elapsed()
+        })
+        .collect();
+    samples.sort();
+    samples[samples.len() / 2]
+}
+
+/// Median of `case` over `iterations` runs
+pub fn run_case(case: &BenchCase, iterations: usize) -> Duration {
+    let mut timed = (case.prepare)();
+    median_time(iterations, &mut *timed)
+}
+
+pub fn bench_prf_outputs() -> DualPrfOutputs {
+    DualPrfOutputs {
+        chacha20_prf_output_base64: base64_url_encode(&[7u8; 32]),
+        ed25519_prf_output_base64: base64_url_encode(&[8u8; 32]),
+    }
+}
+
+/// Ten actions covering every kind a dapp batch typically mixes
+pub fn ten_actions() -> Vec<ActionParams> {
+    let mut actions = vec![
+        ActionParams::CreateAccount,
+        ActionParams::Transfer {
+            deposit: "1000000000000000000000000".to_string(),
+        },
+        ActionParams::AddKey {
+            public_key: BENCH_PUBLIC_KEY.to_string(),
+            access_key: json!({
+                "nonce": 0,
+                "permission": {
+                    "FunctionCall": {
+                        "allowance": "250000000000000000000000",
+                        "receiver_id": BENCH_CONTRACT_ID,
+                        "method_names": ["swap", "deposit"]
+                    }
+                }
+            })
+            .to_string(),
+        },
+        ActionParams::DeleteKey {
+            public_key: BENCH_PUBLIC_KEY.to_string(),
+        },
+    ];
+    actions.extend((0..6).map(|i| ActionParams::FunctionCall {
+        method_name: format!("method_{}", i),
+        args: json!({ "pool_id": i, "amount": "1000000", "memo": "x".repeat(64) }).to_string(),
+        gas: "30000000000000".to_string(),
+        deposit: "1".to_string(),
+    }));
+    actions
+}
+
+fn prepare_keypair_blob_decrypt() -> Box<dyn FnMut()> {
+    let prf = bench_prf_outputs();
+    let (_, blob) = derive_and_encrypt_keypair_from_dual_prf(&prf, BENCH_ACCOUNT_ID).unwrap();
+    Box::new(move || {
+        decrypt_private_key_with_prf(
+            BENCH_ACCOUNT_ID,
+            &prf.chacha20_prf_output_base64,
+            &blob.encrypted_near_key_data_b64u,
+            &blob.chacha20_nonce_b64u,
+        )
+        .unwrap();
+    })
+}
+
+fn prepare_borsh_serialization() -> Box<dyn FnMut()> {
+    let signing_key = ed25519_dalek::SigningKey::from_bytes(&[3u8; 32]);
+    let transaction = build_transaction_with_actions(
+        BENCH_ACCOUNT_ID,
+        BENCH_CONTRACT_ID,
+        42,
+        &[1u8; 32],
+        &signing_key,
+        build_actions_from_params(ten_actions()).unwrap(),
+    )
+    .unwrap();
+    Box::new(move || {
+        borsh::to_vec(&transaction).unwrap();
+    })
+}
+
+/// 50 transactions of 10 actions each, digested with summary blocks (the canonical-JSON path)
+fn prepare_intent_digest() -> Box<dyn FnMut()> {
+    let tree: Vec<(String, Vec<ActionParams>)> = (0..50)
+        .map(|i| (format!("receiver-{}.testnet", i), ten_actions()))
+        .collect();
+    let flags: Vec<Option<bool>> = (0..50).map(|i| Some(i % 3 == 0)).collect();
+    let policy = SummaryPolicy::default();
+    Box::new(move || {
+        compute_confirmation_intent_digest(&tree, &flags, None, Some(&policy)).unwrap();
+    })
+}
+
+/// A packed attestation over an Ed25519 credential key
+pub fn bench_attestation_object_b64u() -> String {
+    use ciborium::value::Value as CborValue;
+
+    fn cbor(value: &CborValue) -> Vec<u8> {
+        let mut out = Vec::new();
+        ciborium::into_writer(value, &mut out).unwrap();
+        out
+    }
+    let cose_key = cbor(&CborValue::Map(vec![
+        (CborValue::Integer(1.into()), CborValue::Integer(1.into())),
+        (
+            CborValue::Integer(3.into()),
+            CborValue::Integer((-8).into()),
+        ),
+        (
+            CborValue::Integer((-1).into()),
+            CborValue::Integer(6.into()),
+        ),
+        (
+            CborValue::Integer((-2).into()),
+            CborValue::Bytes(vec![0x11; 32]),
+        ),
+    ]));
+    let mut auth_data = vec![0x49u8; 32];
+    auth_data.push(0x5D); // UP UV BE BS AT
+    auth_data.extend_from_slice(&7u32.to_be_bytes());
+    auth_data.extend_from_slice(&[0xad; 16]);
+    auth_data.extend_from_slice(&16u16.to_be_bytes());
+    auth_data.extend_from_slice(&[0x42; 16]);
+    auth_data.extend_from_slice(&cose_key);
+    base64_url_encode(&cbor(&CborValue::Map(vec![
+        (
+            CborValue::Text("fmt".to_string()),
+            CborValue::Text("packed".to_string()),
+        ),
+        (
+            CborValue::Text("attStmt".to_string()),
+            CborValue::Map(vec![
+                (
+                    CborValue::Text("alg".to_string()),
+                    CborValue::Integer((-8).into()),
+                ),
+                (
+                    CborValue::Text("sig".to_string()),
+                    CborValue::Bytes(vec![0; 64]),
+                ),
+            ]),
+        ),
+        (
+            CborValue::Text("authData".to_string()),
+            CborValue::Bytes(auth_data),
+        ),
+    ])))
+}
+
+fn prepare_attestation_parsing() -> Box<dyn FnMut()> {
+    let attestation_object = bench_attestation_object_b64u();
+    Box::new(move || {
+        describe_attestation_object_b64u(&attestation_object).unwrap();
+    })
+}
+
+/// Everything a confirmed batch needs after the user approved it
+pub struct ConfirmedBatch {
+    pub rpc: MockRpcClient,
+    pub vrf_data: VrfData,
+    pub credential: WebAuthnAuthenticationCredential,
+    pub tx_requests: Vec<TransactionPayload>,
+    pub decryption: Decryption,
+    pub confirmation: ConfirmationResult,
+}
+
+/// Five 10-action transactions, confirmed, with a contract that verifies the credential
+pub fn confirmed_batch_of_5() -> ConfirmedBatch {
+    let prf = bench_prf_outputs();
+    let (_, blob) = derive_and_encrypt_keypair_from_dual_prf(&prf, BENCH_ACCOUNT_ID).unwrap();
+    let actions = serde_json::to_string(&ten_actions()).unwrap();
+    let tx_requests = (0..5)
+        .map(|i| TransactionPayload {
+            near_account_id: BENCH_ACCOUNT_ID.to_string(),
+            receiver_id: format!("receiver-{}.testnet", i),
+            actions: actions.clone(),
+            depends_on: Vec::new(),
+        })
+        .collect();
+    let confirmation: ConfirmationResult = serde_json::from_value(json!({
+        "confirmed": true,
+        "request_id": "bench",
+        "prf_output": prf.chacha20_prf_output_base64,
+        "transaction_context": {
+            "nearPublicKeyStr": BENCH_PUBLIC_KEY,
+            "nextNonce": "100",
+            "txBlockHeight": "1000",
+            "txBlockHash": bs58::encode([5u8; 32]).into_string()
+        }
+    }))
+    .unwrap();
+    ConfirmedBatch {
+        rpc: verifying_rpc(),
+        vrf_data: VrfData {
+            vrf_input_data: vec![1; 32],
+            vrf_output: vec![2; 64],
+            vrf_proof: vec![3; 80],
+            public_key: vec![4; 32],
+            user_id: BENCH_ACCOUNT_ID.to_string(),
+            rp_id: "example.com".to_string(),
+            block_height: 1000,
+            block_hash: vec![5; 32],
+        },
+        credential: WebAuthnAuthenticationCredential {
+            id: "bench-credential".to_string(),
+            raw_id: "YmVuY2g".to_string(),
+            response: WebAuthnAuthenticationResponse {
+                client_data_json: base64_url_encode(b"{\"type\":\"webauthn.get\"}"),
+                authenticator_data: base64_url_encode(&[0x49; 37]),
+                signature: base64_url_encode(&[0x30; 70]),
+                user_handle: None,
+            },
+            authenticator_attachment: Some("platform".to_string()),
+            auth_type: "public-key".to_string(),
+        },
+        tx_requests,
+        decryption: Decryption::new(
+            prf.chacha20_prf_output_base64.clone(),
+            blob.encrypted_near_key_data_b64u,
+            blob.chacha20_nonce_b64u,
+        ),
+        confirmation,
+    }
+}
+
+/// Contract verification through the batch's RPC, then key decryption and
+/// build-and-sign of every transaction (the handler's steps 3 and 4)
+pub fn verify_and_sign(batch: &ConfirmedBatch) -> Result<Vec<String>, String> {
+    block_on(async {
+        let verification = verify_authentication_response_rpc_call(
+            &batch.rpc,
+            BENCH_CONTRACT_ID,
+            batch.vrf_data.clone(),
+            batch.credential.clone(),
+        )
+        .await?;
+        if !verification.verified {
+            return Err(verification
+                .error
+                .unwrap_or_else(|| "Contract verification failed".to_string()));
+        }
+        let result = sign_near_transactions_with_actions_impl(
+            batch.tx_requests.clone(),
+            &batch.decryption,
+            &batch.confirmation,
+            None::<&MockRpcClient>,
+            None,
+            None,
+            Vec::new(),
+        )
+        .await?;
+        match result.transaction_hashes {
+            Some(hashes) if result.success => Ok(hashes),
+            _ => Err(result.error.unwrap_or_default()),
+        }
+    })
+}
+
+fn prepare_build_and_sign_batch() -> Box<dyn FnMut()> {
+    let batch = confirmed_batch_of_5();
+    Box::new(move || {
+        verify_and_sign(&batch).unwrap();
+    })
+}
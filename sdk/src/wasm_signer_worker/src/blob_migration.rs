@@ -0,0 +1,256 @@
+// === ENCRYPTED BLOB MIGRATION ===
+// Stored blobs keep the format of the release that wrote them. MigrateEncryptedBlobs brings a
+// device's blobs to the current formats with one fresh PRF output, blob by blob:
+//   NEAR keys          PRF blobs without the key-check header are decrypted and encrypted
+//                      again with it, outer-wrapped when the request carries the CryptoKey.
+//                      Outer-wrapped and fallback-scheme blobs were always written with it.
+//   VRF keypairs       keypairs without authenticated metadata are decrypted and encrypted
+//                      again with the caller's `vrfKeypairMeta` bound as associated data
+//                      (wasm_shared/vrf_blob_meta.rs), under the VRF worker's key derivation
+//   Session snapshots  encrypted under their restore token, not the PRF output, and
+//                      short-lived: a current one is left as it is, another is refused
+// A blob in the current format is returned untouched without being decrypted, and a blob that
+// fails keeps its original in the result, so the migration can be retried until every blob
+// reports current.
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use getrandom::getrandom;
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::config::{
+    CHACHA20_KEY_SIZE, CHACHA20_NONCE_SIZE, CHACHA20_POLY1305_TAG_SIZE,
+    NEAR_KEY_BLOB_FORMAT_KEY_CHECK, NEAR_KEY_BLOB_FORMAT_LEGACY, SESSION_SNAPSHOT_VERSION,
+    VRF_KEYPAIR_BLOB_VERSION, VRF_KEYPAIR_BLOB_VERSION_LEGACY, VRF_KEYPAIR_HKDF_INFO,
+};
+use crate::crypto::{
+    decrypt_data_chacha20, derive_chacha20_key_from_prf, encrypt_data_chacha20,
+    split_key_check_header,
+};
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::BlobDecryptError;
+use crate::key_wrapping::{split_key_wrapping_header, KeyWrappingScheme};
+use crate::outer_wrap::{split_outer_wrap, wrap_encrypted_data};
+use crate::stored_records::{StoredEncryptedVrfKeypair, StoredNearKeyRecord};
+use crate::vrf_blob_meta::VrfKeypairBlobMeta;
+
+/// What a stored blob holds, which decides how its format is read
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum StoredBlobKind {
+    /// PasskeyNearKeys `encryptedKeys` record
+    NearKey,
+    /// `encryptedVrfKeypair` of a PasskeyClientDB `users` record
+    VrfKeypair,
+    /// Session snapshot exported by the VRF worker
+    SessionSnapshot,
+}
+
+impl StoredBlobKind {
+    /// The format blobs of this kind are written in today
+    pub fn current_format(self) -> u32 {
+        match self {
+            StoredBlobKind::NearKey => NEAR_KEY_BLOB_FORMAT_KEY_CHECK,
+            StoredBlobKind::VrfKeypair => VRF_KEYPAIR_BLOB_VERSION,
+            StoredBlobKind::SessionSnapshot => SESSION_SNAPSHOT_VERSION,
+        }
+    }
+}
+
+/// The fields of a session snapshot its format is read from
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredSessionSnapshot {
+    version: u32,
+    nonce_b64u: String,
+    ciphertext_b64u: String,
+}
+
+fn parse<T: for<'de> Deserialize<'de>>(blob: &Value, what: &str) -> Result<T, BlobDecryptError> {
+    serde_json::from_value(blob.clone())
+        .map_err(|e| BlobDecryptError::InvalidInput(format!("Invalid {}: {}", what, e)))
+}
+
+fn check_nonce_and_ciphertext(
+    nonce_b64u: &str,
+    ciphertext_b64u: &str,
+) -> Result<(), BlobDecryptError> {
+    let corrupted = |message: String| Err(BlobDecryptError::CorruptedCiphertext(message));
+    match base64_url_decode(nonce_b64u) {
+        Ok(nonce) if nonce.len() == CHACHA20_NONCE_SIZE => {}
+        Ok(nonce) => {
+            return corrupted(format!(
+                "Nonce is {} bytes, expected {}",
+                nonce.len(),
+                CHACHA20_NONCE_SIZE
+            ))
+        }
+        Err(e) => return corrupted(format!("Nonce is not valid base64url: {}", e)),
+    }
+    match base64_url_decode(ciphertext_b64u) {
+        Ok(ciphertext) if ciphertext.len() >= CHACHA20_POLY1305_TAG_SIZE => Ok(()),
+        Ok(ciphertext) => corrupted(format!(
+            "Ciphertext is {} bytes, shorter than the {}-byte authentication tag",
+            ciphertext.len(),
+            CHACHA20_POLY1305_TAG_SIZE
+        )),
+        Err(e) => corrupted(format!("Ciphertext is not valid base64url: {}", e)),
+    }
+}
+
+/// The format of a stored blob, read from its structure without decrypting it
+pub fn blob_format(kind: StoredBlobKind, blob: &Value) -> Result<u32, BlobDecryptError> {
+    match kind {
+        StoredBlobKind::NearKey => {
+            let record: StoredNearKeyRecord = parse(blob, "stored key record")?;
+            check_nonce_and_ciphertext(&record.iv, &record.encrypted_data)?;
+            let data = base64_url_decode(&record.encrypted_data)
+                .map_err(BlobDecryptError::CorruptedCiphertext)?;
+            // The outer wrap hides the key-check header, but came after it
+            if split_outer_wrap(&data).is_some() {
+                return Ok(NEAR_KEY_BLOB_FORMAT_KEY_CHECK);
+            }
+            let (_, _, data) = split_key_wrapping_header(&data);
+            Ok(match split_key_check_header(data) {
+                Some(_) => NEAR_KEY_BLOB_FORMAT_KEY_CHECK,
+                None => NEAR_KEY_BLOB_FORMAT_LEGACY,
+            })
+        }
+        StoredBlobKind::VrfKeypair => {
+            let keypair: StoredEncryptedVrfKeypair = parse(blob, "encrypted VRF keypair")?;
+            check_nonce_and_ciphertext(
+                &keypair.chacha20_nonce_b64u,
+                &keypair.encrypted_vrf_data_b64u,
+            )?;
+            Ok(match keypair.meta {
+                Some(_) => VRF_KEYPAIR_BLOB_VERSION,
+                None => VRF_KEYPAIR_BLOB_VERSION_LEGACY,
+            })
+        }
+        StoredBlobKind::SessionSnapshot => {
+            let snapshot: StoredSessionSnapshot = parse(blob, "session snapshot")?;
+            check_nonce_and_ciphertext(&snapshot.nonce_b64u, &snapshot.ciphertext_b64u)?;
+            Ok(snapshot.version)
+        }
+    }
+}
+
+/// The VRF keypair's ChaCha20Poly1305 cipher, keyed as the VRF worker keys it: HKDF-SHA256 of
+/// the raw PRF output, without salt
+fn vrf_keypair_cipher(chacha20_prf_output: &str) -> Result<ChaCha20Poly1305, BlobDecryptError> {
+    let prf_output = Zeroizing::new(
+        base64_url_decode(chacha20_prf_output).map_err(BlobDecryptError::InvalidInput)?,
+    );
+    let mut key = Zeroizing::new([0u8; CHACHA20_KEY_SIZE]);
+    Hkdf::<Sha256>::new(None, &prf_output)
+        .expand(VRF_KEYPAIR_HKDF_INFO, key.as_mut())
+        .map_err(|_| BlobDecryptError::InvalidInput("VRF keypair key derivation failed".into()))?;
+    Ok(ChaCha20Poly1305::new(chacha20poly1305::Key::from_slice(
+        key.as_ref(),
+    )))
+}
+
+/// Re-encrypts a blob of an earlier `format` in the current one: legacy PRF-scheme NEAR key
+/// blobs, and VRF keypairs without metadata (which take `vrf_keypair_meta`).
+pub async fn reencrypt_blob(
+    kind: StoredBlobKind,
+    format: u32,
+    blob: &Value,
+    chacha20_prf_output: &str,
+    vrf_keypair_meta: Option<&VrfKeypairBlobMeta>,
+) -> Result<Value, BlobDecryptError> {
+    match (kind, format) {
+        (StoredBlobKind::NearKey, NEAR_KEY_BLOB_FORMAT_LEGACY) => {
+            let mut record: StoredNearKeyRecord = parse(blob, "stored key record")?;
+            let data = base64_url_decode(&record.encrypted_data)
+                .map_err(BlobDecryptError::CorruptedCiphertext)?;
+            let (scheme, _, _) = split_key_wrapping_header(&data);
+            if scheme != KeyWrappingScheme::Prf {
+                return Err(BlobDecryptError::InvalidInput(format!(
+                    "Key blob of the {} scheme is not encrypted under the PRF output",
+                    scheme.as_str()
+                )));
+            }
+
+            let key = Zeroizing::new(
+                derive_chacha20_key_from_prf(chacha20_prf_output, &record.near_account_id)
+                    .map_err(|e| BlobDecryptError::InvalidInput(e.to_string()))?,
+            );
+            let private_key = Zeroizing::new(decrypt_data_chacha20(
+                &record.encrypted_data,
+                &record.iv,
+                &key,
+            )?);
+            let encrypted = encrypt_data_chacha20(&private_key, &key)
+                .map_err(BlobDecryptError::InvalidInput)?;
+            let (encrypted_data, outer_wrap) =
+                wrap_encrypted_data(&encrypted.encrypted_near_key_data_b64u)
+                    .await
+                    .map_err(BlobDecryptError::InvalidInput)?;
+
+            record.encrypted_data = encrypted_data;
+            record.iv = encrypted.chacha20_nonce_b64u;
+            record.outer_wrap = outer_wrap;
+            serde_json::to_value(&record).map_err(|e| {
+                BlobDecryptError::InvalidInput(format!("Failed to serialize key record: {}", e))
+            })
+        }
+        (StoredBlobKind::VrfKeypair, VRF_KEYPAIR_BLOB_VERSION_LEGACY) => {
+            let meta = vrf_keypair_meta.ok_or_else(|| {
+                BlobDecryptError::InvalidInput(
+                    "Missing vrfKeypairMeta: a VRF keypair without metadata takes the metadata \
+                     to bind into it"
+                        .to_string(),
+                )
+            })?;
+            let mut keypair: StoredEncryptedVrfKeypair = parse(blob, "encrypted VRF keypair")?;
+            let cipher = vrf_keypair_cipher(chacha20_prf_output)?;
+            // Both decode: blob_format checked them
+            let nonce = base64_url_decode(&keypair.chacha20_nonce_b64u)
+                .map_err(BlobDecryptError::CorruptedCiphertext)?;
+            let ciphertext = base64_url_decode(&keypair.encrypted_vrf_data_b64u)
+                .map_err(BlobDecryptError::CorruptedCiphertext)?;
+            let keypair_data = Zeroizing::new(
+                cipher
+                    .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+                    .map_err(|e| {
+                        BlobDecryptError::DecryptionFailed(format!("Decryption error: {}", e))
+                    })?,
+            );
+
+            let mut nonce = [0u8; CHACHA20_NONCE_SIZE];
+            getrandom(&mut nonce).map_err(|e| {
+                BlobDecryptError::InvalidInput(format!("Failed to generate nonce: {}", e))
+            })?;
+            let ciphertext = cipher
+                .encrypt(
+                    Nonce::from_slice(&nonce),
+                    Payload {
+                        msg: &keypair_data,
+                        aad: &meta.aad(),
+                    },
+                )
+                .map_err(|e| BlobDecryptError::InvalidInput(format!("Encryption error: {}", e)))?;
+
+            keypair.encrypted_vrf_data_b64u = base64_url_encode(&ciphertext);
+            keypair.chacha20_nonce_b64u = base64_url_encode(&nonce);
+            keypair.meta = Some(meta.clone());
+            serde_json::to_value(&keypair).map_err(|e| {
+                BlobDecryptError::InvalidInput(format!("Failed to serialize VRF keypair: {}", e))
+            })
+        }
+        (StoredBlobKind::SessionSnapshot, _) => Err(BlobDecryptError::InvalidInput(format!(
+            "Session snapshot version {} is not supported; snapshots are encrypted under their \
+             restore token, so export a new one instead",
+            format
+        ))),
+        _ => Err(BlobDecryptError::InvalidInput(format!(
+            "No migration from format {} of a {:?} blob",
+            format, kind
+        ))),
+    }
+}
@@ -0,0 +1,214 @@
+// === CHUNKED CONTRACT DEPLOYS ===
+// Contracts above the transaction size limit cannot be deployed with one DeployContract action.
+// Staging contracts that support store-then-deploy take the code in chunks (one FunctionCall per
+// chunk) and then deploy the assembled code in a final call. The plan below splits the wasm into
+// staging calls plus the deploy-from-staged call, in broadcast order, with a manifest of SHA-256
+// hashes: each staging call carries its chunk's hash, and the deploy call carries the total hash,
+// which is the `code_hash` NEAR reports for the deployed contract.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use wasm_bindgen::prelude::*;
+
+use crate::actions::ActionParams;
+use crate::config::{
+    DEFAULT_DEPLOY_FROM_STAGED_GAS, DEFAULT_DEPLOY_STAGE_GAS, MAX_DEPLOY_CHUNKS,
+    MAX_DEPLOY_CHUNK_BYTES,
+};
+use crate::encoders::base64_standard_encode;
+use crate::handlers::TransactionPayload;
+
+const WASM_MAGIC: &[u8] = b"\0asm";
+
+/// Methods of the staging contract.
+/// The stage method takes `{ index, data, hash }` (data in standard base64, hash in base58);
+/// the deploy method takes `{ code_hash, chunk_count, total_bytes }`.
+#[wasm_bindgen]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StagingContractInterface {
+    /// Account of the staging contract (defaults to the signer, i.e. a self-upgrade)
+    #[wasm_bindgen(getter_with_clone, js_name = "receiverId")]
+    #[serde(default)]
+    pub receiver_id: Option<String>,
+    #[wasm_bindgen(getter_with_clone, js_name = "stageMethod")]
+    pub stage_method: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "deployMethod")]
+    pub deploy_method: String,
+    /// Gas per staging call (defaults to DEFAULT_DEPLOY_STAGE_GAS)
+    #[wasm_bindgen(getter_with_clone, js_name = "stageGas")]
+    #[serde(default)]
+    pub stage_gas: Option<String>,
+    /// Deposit per staging call, e.g. to cover the chunk's storage (defaults to 0)
+    #[wasm_bindgen(getter_with_clone, js_name = "stageDeposit")]
+    #[serde(default)]
+    pub stage_deposit: Option<String>,
+    /// Gas for the deploy-from-staged call (defaults to DEFAULT_DEPLOY_FROM_STAGED_GAS)
+    #[wasm_bindgen(getter_with_clone, js_name = "deployGas")]
+    #[serde(default)]
+    pub deploy_gas: Option<String>,
+}
+
+/// Hashes of a chunked deploy, in base58 like NEAR's CryptoHash
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployManifest {
+    #[wasm_bindgen(js_name = "totalBytes")]
+    pub total_bytes: u32,
+    #[wasm_bindgen(js_name = "chunkSize")]
+    pub chunk_size: u32,
+    /// SHA-256 of each chunk, in staging order
+    #[wasm_bindgen(getter_with_clone, js_name = "chunkHashes")]
+    pub chunk_hashes: Vec<String>,
+    /// SHA-256 of the whole code (the deployed contract's code_hash)
+    #[wasm_bindgen(getter_with_clone, js_name = "totalHash")]
+    pub total_hash: String,
+}
+
+/// `deployStep` annotation of a transaction in the confirmation payload
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployStep {
+    /// 1-based position in broadcast order
+    pub step: u32,
+    pub step_count: u32,
+    pub total_bytes: u32,
+    pub total_hash: String,
+    /// Hash of the chunk a staging call carries (None for the deploy call)
+    pub chunk_hash: Option<String>,
+}
+
+impl DeployManifest {
+    /// Staging calls plus the deploy-from-staged call
+    pub fn step_count(&self) -> usize {
+        self.chunk_hashes.len() + 1
+    }
+
+    /// Annotation of the transaction at `index` of the planned batch
+    pub fn deploy_step(&self, index: usize) -> Option<DeployStep> {
+        if index >= self.step_count() {
+            return None;
+        }
+        Some(DeployStep {
+            step: index as u32 + 1,
+            step_count: self.step_count() as u32,
+            total_bytes: self.total_bytes,
+            total_hash: self.total_hash.clone(),
+            chunk_hash: self.chunk_hashes.get(index).cloned(),
+        })
+    }
+
+    /// "Deploy 3.2MB contract in 7 steps"
+    pub fn description(&self) -> String {
+        format!(
+            "Deploy {:.1}MB contract in {} steps",
+            self.total_bytes as f64 / 1_000_000.0,
+            self.step_count()
+        )
+    }
+}
+
+/// Transactions of a chunked deploy, in broadcast order
+#[derive(Debug, Clone)]
+pub struct DeployPlan {
+    pub transactions: Vec<TransactionPayload>,
+    pub manifest: DeployManifest,
+}
+
+pub fn sha256_b58(data: &[u8]) -> String {
+    bs58::encode(Sha256::digest(data)).into_string()
+}
+
+/// Splits `code` into staging calls on `staging` followed by the deploy-from-staged call,
+/// all signed by `signer_account_id`
+pub fn plan_chunked_deploy(
+    signer_account_id: &str,
+    code: &[u8],
+    chunk_size: u32,
+    staging: &StagingContractInterface,
+) -> Result<DeployPlan, String> {
+    if !code.starts_with(WASM_MAGIC) {
+        return Err("Contract code is not a wasm module".to_string());
+    }
+    if code.len() > u32::MAX as usize {
+        return Err("Contract code is too large".to_string());
+    }
+    if chunk_size == 0 || chunk_size > MAX_DEPLOY_CHUNK_BYTES {
+        return Err(format!(
+            "Chunk size must be between 1 and {} bytes, got {}",
+            MAX_DEPLOY_CHUNK_BYTES, chunk_size
+        ));
+    }
+    let chunk_count = code.len().div_ceil(chunk_size as usize);
+    if chunk_count > MAX_DEPLOY_CHUNKS {
+        return Err(format!(
+            "Contract code needs {} chunks of {} bytes, at most {} are allowed",
+            chunk_count, chunk_size, MAX_DEPLOY_CHUNKS
+        ));
+    }
+    if staging.stage_method.is_empty() || staging.deploy_method.is_empty() {
+        return Err("Staging contract interface requires stageMethod and deployMethod".to_string());
+    }
+
+    let receiver_id = staging.receiver_id.as_deref().unwrap_or(signer_account_id);
+    let transaction = |action: ActionParams| -> Result<TransactionPayload, String> {
+        Ok(TransactionPayload {
+            near_account_id: signer_account_id.to_string(),
+            receiver_id: receiver_id.to_string(),
+            actions: serde_json::to_string(&[action])
+                .map_err(|e| format!("Failed to serialize deploy action: {}", e))?,
+            depends_on: Vec::new(),
+        })
+    };
+
+    let mut transactions = Vec::with_capacity(chunk_count + 1);
+    let mut chunk_hashes = Vec::with_capacity(chunk_count);
+    for (index, chunk) in code.chunks(chunk_size as usize).enumerate() {
+        let hash = sha256_b58(chunk);
+        let args = serde_json::json!({
+            "index": index,
+            "data": base64_standard_encode(chunk),
+            "hash": hash,
+        });
+        transactions.push(transaction(ActionParams::FunctionCall {
+            method_name: staging.stage_method.clone(),
+            args: args.to_string(),
+            gas: staging
+                .stage_gas
+                .clone()
+                .unwrap_or_else(|| DEFAULT_DEPLOY_STAGE_GAS.to_string()),
+            deposit: staging
+                .stage_deposit
+                .clone()
+                .unwrap_or_else(|| "0".to_string()),
+        })?);
+        chunk_hashes.push(hash);
+    }
+
+    let manifest = DeployManifest {
+        total_bytes: code.len() as u32,
+        chunk_size,
+        chunk_hashes,
+        total_hash: sha256_b58(code),
+    };
+    let args = serde_json::json!({
+        "code_hash": manifest.total_hash,
+        "chunk_count": chunk_count,
+        "total_bytes": manifest.total_bytes,
+    });
+    transactions.push(transaction(ActionParams::FunctionCall {
+        method_name: staging.deploy_method.clone(),
+        args: args.to_string(),
+        gas: staging
+            .deploy_gas
+            .clone()
+            .unwrap_or_else(|| DEFAULT_DEPLOY_FROM_STAGED_GAS.to_string()),
+        deposit: "0".to_string(),
+    })?);
+
+    Ok(DeployPlan {
+        transactions,
+        manifest,
+    })
+}
@@ -0,0 +1,227 @@
+// === CONDITIONAL BATCHES ===
+// Composable flows (an approval, then a swap that spends it) should not leave the later steps
+// signed when an earlier one would fail. With `conditionalBatch` set, SignTransactionsWithActions
+// simulates the confirmed batch in declared order right before signing, and signs a transaction
+// only when every earlier transaction simulated successfully; the rest of the batch is skipped.
+// The signed transactions are therefore always a prefix of the batch, on consecutive nonces.
+//
+// A simulation runs each FunctionCall of the transaction as a view call (`call_function` at
+// optimistic finality), the same dry-run facility registration dry runs use. View calls see the
+// chain as it is, not as earlier transactions of the batch would leave it, and cannot write
+// state: a call that gets as far as a host function views are refused (ProhibitedInView, e.g.
+// a storage write) ran without panicking up to there, and counts as succeeded. Transactions
+// without FunctionCalls have nothing to simulate and succeed.
+//
+// When the RPC cannot simulate (unreachable, timed out, malformed answer) the batch fails with
+// SimulationUnavailable, unless the caller set `fallbackToUnconditional`: then every transaction
+// is signed, each entry reporting the unavailable simulation. The confirmation shows the whole
+// batch with a ConditionalBatch warning, so the user knows later steps may be skipped.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::actions::ActionParams;
+use crate::encoders::base64_standard_encode;
+use crate::error::{RpcErrorKind, SignerErrorCode};
+use crate::rpc_client::RpcClient;
+
+/// The request's `conditionalBatch` option
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConditionalBatch {
+    /// Sign every transaction when the RPC cannot simulate, instead of failing the batch
+    #[serde(default)]
+    pub fallback_to_unconditional: bool,
+}
+
+/// How one transaction's simulation went
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum SimulationOutcome {
+    #[serde(rename_all = "camelCase")]
+    Succeeded { logs: Vec<String> },
+    /// A FunctionCall panicked or the node refused it
+    #[serde(rename_all = "camelCase")]
+    Failed {
+        method_name: String,
+        error: String,
+        logs: Vec<String>,
+    },
+    /// The RPC could not run the simulation
+    #[serde(rename_all = "camelCase")]
+    Unavailable { error: String },
+}
+
+impl SimulationOutcome {
+    pub fn succeeded(&self) -> bool {
+        matches!(self, SimulationOutcome::Succeeded { .. })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConditionalEntryStatus {
+    Signed,
+    Skipped,
+}
+
+/// One transaction of a conditional batch, in the result's `conditionalEntries`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConditionalBatchEntry {
+    pub index: u32,
+    pub status: ConditionalEntryStatus,
+    /// None for transactions after the first unsuccessful simulation, which are not simulated
+    pub simulation: Option<SimulationOutcome>,
+}
+
+/// Whether a view call stopped at a host function only views are refused
+fn is_prohibited_in_view(error: &str) -> bool {
+    error.contains("ProhibitedInView")
+}
+
+/// Logs of a call_function result
+fn view_logs(result: &Value) -> Vec<String> {
+    result
+        .get("logs")
+        .and_then(|logs| logs.as_array())
+        .map(|logs| {
+            logs.iter()
+                .filter_map(|log| log.as_str().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Simulates the FunctionCalls of one transaction, in order, stopping at the first failure
+pub async fn simulate_transaction<R: RpcClient>(
+    rpc: &R,
+    receiver_id: &str,
+    actions: &[ActionParams],
+) -> SimulationOutcome {
+    let mut logs = Vec::new();
+    for action in actions {
+        let ActionParams::FunctionCall {
+            method_name, args, ..
+        } = action
+        else {
+            continue;
+        };
+        let params = json!({
+            "request_type": "call_function",
+            "account_id": receiver_id,
+            "method_name": method_name,
+            "args_base64": base64_standard_encode(args.as_bytes()),
+            "finality": "optimistic"
+        });
+        let error = match rpc.call("query", params).await {
+            // Older nodes answer a panicking view call with an error string in the result
+            Ok(result) => match result.get("error").and_then(|e| e.as_str()) {
+                None => {
+                    logs.extend(view_logs(&result));
+                    continue;
+                }
+                Some(error) => {
+                    logs.extend(view_logs(&result));
+                    error.to_string()
+                }
+            },
+            Err(RpcErrorKind::Rpc { message, error, .. }) => {
+                if message.is_empty() {
+                    error.to_string()
+                } else {
+                    message
+                }
+            }
+            Err(e) => {
+                return SimulationOutcome::Unavailable {
+                    error: e.to_string(),
+                }
+            }
+        };
+        if is_prohibited_in_view(&error) {
+            logs.push(format!(
+                "{} reached a state change; simulated up to it",
+                method_name
+            ));
+            continue;
+        }
+        return SimulationOutcome::Failed {
+            method_name: method_name.clone(),
+            error,
+            logs,
+        };
+    }
+    SimulationOutcome::Succeeded { logs }
+}
+
+/// Simulates the batch in declared order, up to and including the first transaction that did
+/// not simulate successfully; later transactions are left unsimulated (None)
+pub async fn simulate_batch<R: RpcClient>(
+    rpc: &R,
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+) -> Vec<Option<SimulationOutcome>> {
+    let mut simulations = Vec::with_capacity(receivers_and_actions.len());
+    let mut stopped = false;
+    for (receiver_id, actions) in receivers_and_actions {
+        if stopped {
+            simulations.push(None);
+            continue;
+        }
+        let outcome = simulate_transaction(rpc, receiver_id, actions).await;
+        stopped = !outcome.succeeded();
+        simulations.push(Some(outcome));
+    }
+    simulations
+}
+
+/// Signed/skipped status of each transaction: signed when every earlier simulation succeeded.
+/// An unavailable simulation fails the batch with SimulationUnavailable, or signs every
+/// transaction with `fallback_to_unconditional`.
+pub fn plan_entries(
+    simulations: Vec<Option<SimulationOutcome>>,
+    options: &ConditionalBatch,
+) -> Result<Vec<ConditionalBatchEntry>, (SignerErrorCode, String)> {
+    let unavailable = simulations.iter().enumerate().find_map(|(i, s)| match s {
+        Some(SimulationOutcome::Unavailable { error }) => Some((i, error.clone())),
+        _ => None,
+    });
+    if let Some((index, error)) = &unavailable {
+        if !options.fallback_to_unconditional {
+            return Err((
+                SignerErrorCode::SimulationUnavailable,
+                format!(
+                    "SimulationUnavailable: transaction {} could not be simulated: {}",
+                    index + 1,
+                    error
+                ),
+            ));
+        }
+    }
+    let mut earlier_succeeded = true;
+    Ok(simulations
+        .into_iter()
+        .enumerate()
+        .map(|(index, simulation)| {
+            let status = if earlier_succeeded || unavailable.is_some() {
+                ConditionalEntryStatus::Signed
+            } else {
+                ConditionalEntryStatus::Skipped
+            };
+            earlier_succeeded &= simulation.as_ref().is_some_and(|s| s.succeeded());
+            ConditionalBatchEntry {
+                index: index as u32,
+                status,
+                simulation,
+            }
+        })
+        .collect())
+}
+
+/// Number of transactions signed: the signed entries are a prefix of the batch
+pub fn signed_count(entries: &[ConditionalBatchEntry]) -> usize {
+    entries
+        .iter()
+        .take_while(|entry| entry.status == ConditionalEntryStatus::Signed)
+        .count()
+}
@@ -13,6 +13,49 @@ pub const CHACHA20_NONCE_SIZE: usize = 12;
 /// ChaCha20 key size in bytes (256 bits / 32 bytes)
 pub const CHACHA20_KEY_SIZE: usize = 32;
 
+/// Poly1305 authentication tag size in bytes, appended to every ChaCha20Poly1305 ciphertext
+pub const CHACHA20_POLY1305_TAG_SIZE: usize = 16;
+
+/// Encrypted key blob format: ChaCha20Poly1305 with a 12-byte random nonce.
+/// Blobs stored before versioning was introduced carry no version field and are treated as v1.
+pub const ENCRYPTED_BLOB_VERSION_V1: u32 = 1;
+
+/// Encrypted key blob format for authenticators without PRF: as v1, under a key derived from a
+/// random secret kept in the credential's largeBlob (see key_wrapping.rs)
+pub const ENCRYPTED_BLOB_VERSION_V2_LARGE_BLOB: u32 = 2;
+
+/// Encrypted key blob format for authenticators without PRF: as v1, under a key derived from a
+/// caller-supplied passphrase with Argon2id (see key_wrapping.rs)
+pub const ENCRYPTED_BLOB_VERSION_V3_PASSPHRASE: u32 = 3;
+
+/// Magic prefix of the key-check header on newly encrypted blobs: the header (magic then
+/// key-check value) is prepended to the ciphertext and bound to it as associated data.
+/// Blobs whose ciphertext lacks it predate the header.
+pub const KEY_CHECK_HEADER_MAGIC: &[u8; 4] = b"W3KC";
+
+/// Key-check value size in bytes (HMAC-SHA256 truncated to 64 bits)
+pub const KEY_CHECK_VALUE_SIZE: usize = 8;
+
+/// Full key-check header size in bytes
+pub const KEY_CHECK_HEADER_SIZE: usize = KEY_CHECK_HEADER_MAGIC.len() + KEY_CHECK_VALUE_SIZE;
+
+/// Info string for the HKDF-derived key-check MAC key
+pub const KEY_CHECK_HKDF_INFO: &str = "chacha20poly1305-key-check-v1";
+
+/// Magic prefix of an outer-wrapped blob: the PRF-encrypted bytes (key-check header and
+/// ciphertext) encrypted again with a non-extractable WebCrypto AES-GCM key, as
+/// magic || AES-GCM IV || AES-GCM ciphertext. The magic is the AES-GCM associated data.
+pub const OUTER_WRAP_MAGIC: &[u8; 4] = b"W3OW";
+
+/// AES-GCM IV size in bytes for the outer wrap
+pub const OUTER_WRAP_IV_SIZE: usize = 12;
+
+/// AES-GCM authentication tag size in bytes (WebCrypto default)
+pub const OUTER_WRAP_TAG_SIZE: usize = 16;
+
+/// `outerWrap` value recorded in the blob metadata for wrapped blobs
+pub const OUTER_WRAP_AES_GCM_WEBCRYPTO: &str = "aes-gcm-webcrypto";
+
 /// Ed25519 private key size in bytes
 pub const ED25519_PRIVATE_KEY_SIZE: usize = 32;
 
@@ -22,6 +65,13 @@ pub const CHACHA20_ENCRYPTION_INFO: &str = "chacha20poly1305-encryption-key-v1";
 /// Info string for Ed25519 signing key derivation from dual PRF
 pub const ED25519_HKDF_KEY_INFO: &str = "ed25519-signing-key-dual-prf-v1";
 
+/// Default WebAuthn challenge length in bytes (raw VRF output prefix, as verified by the contract)
+pub const DEFAULT_CHALLENGE_LENGTH: u8 = 32;
+
+/// Accepted WebAuthn challenge length range in bytes (mirrors the VRF worker)
+pub const MIN_CHALLENGE_LENGTH: u8 = 16;
+pub const MAX_CHALLENGE_LENGTH: u8 = 64;
+
 // === GAS CONSTANTS ===
 
 /// Standard gas amount for contract verification calls (30 TGas)
@@ -30,6 +80,538 @@ pub const VERIFY_REGISTRATION_GAS: &str = "30000000000000";
 /// Higher gas amount for device linking registration calls (30 TGas)
 pub const LINK_DEVICE_REGISTRATION_GAS: &str = "30000000000000";
 
+/// Default gas for calls on a multisig contract (100 TGas: add_request_and_confirm
+/// executes the request once enough confirmations are collected)
+pub const MULTISIG_DEFAULT_GAS: &str = "100000000000000";
+
+/// Gas of the storage_deposit calls autoStorageDeposit inserts (30 TGas)
+pub const STORAGE_DEPOSIT_GAS: &str = "30000000000000";
+
+// === CHUNKED DEPLOYS ===
+
+/// Largest staging chunk in bytes: base64-encoded in the staging call's args, a 1 MiB chunk
+/// keeps the transaction under NEAR's 1.5 MiB transaction size limit
+pub const MAX_DEPLOY_CHUNK_BYTES: u32 = 1024 * 1024;
+
+/// Most staging chunks in one chunked deploy (each staging call takes a nonce of the batch)
+pub const MAX_DEPLOY_CHUNKS: usize = 64;
+
+/// Default gas for each staging call (100 TGas)
+pub const DEFAULT_DEPLOY_STAGE_GAS: &str = "100000000000000";
+
+/// Default gas for the deploy-from-staged call (300 TGas: it assembles and deploys the code)
+pub const DEFAULT_DEPLOY_FROM_STAGED_GAS: &str = "300000000000000";
+
+// === WORKER STATE LIMITS ===
+
+/// Default number of requests the worker runs concurrently (further requests are queued)
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 4;
+
+/// Default number of requests allowed to wait for a slot before new ones are rejected
+pub const DEFAULT_MAX_QUEUED_REQUESTS: usize = 16;
+
+/// Default largest message frame in bytes, for request types without a larger limit
+pub const DEFAULT_MAX_MESSAGE_BYTES: u32 = 1024 * 1024;
+
+/// Default largest DeployLargeContract frame in bytes (the contract code travels in the frame)
+pub const DEFAULT_MAX_DEPLOY_MESSAGE_BYTES: u32 = 8 * 1024 * 1024;
+
+/// Smallest message size limit a policy may set, so it cannot lock out the requests that
+/// would raise it again
+pub const MIN_MESSAGE_SIZE_LIMIT_BYTES: u32 = 4 * 1024;
+
+/// Maximum number of request outcomes retained in each account's in-memory audit log
+pub const AUDIT_LOG_MAX_ENTRIES: usize = 256;
+
+/// Maximum number of accounts with session state; beyond it the least recently active
+/// account holding no nonce reservations is dropped
+pub const MAX_ACTIVE_ACCOUNTS: usize = 32;
+
+// === SESSION KEYS ===
+
+/// Default session key lifetime in milliseconds (15 minutes)
+pub const DEFAULT_SESSION_KEY_TTL_MS: u32 = 15 * 60 * 1000;
+
+/// Maximum session key lifetime in milliseconds (24 hours)
+pub const MAX_SESSION_KEY_TTL_MS: u32 = 24 * 60 * 60 * 1000;
+
+/// Maximum number of session keys held in worker memory at once
+pub const MAX_SESSION_KEYS: usize = 16;
+
+/// Gas price (yoctoNEAR per gas unit) used to charge the local allowance when the caller
+/// does not supply one (NEAR's minimum gas price)
+pub const DEFAULT_SESSION_KEY_GAS_PRICE: u128 = 100_000_000;
+
+// === SIGNING HOOKS ===
+
+/// Default time to wait for a PreSignHookResponse before denying the request
+pub const DEFAULT_SIGN_HOOK_TIMEOUT_MS: u32 = 10_000;
+
+// === PEER CHANNEL ===
+
+/// Time to wait for the VRF worker's answer to a REQUEST_CHALLENGE sent over the peer port
+pub const PEER_CHALLENGE_TIMEOUT_MS: u32 = 5_000;
+
+// === CHALLENGE EXPIRY ===
+
+/// Number of blocks after a VRF challenge's blockHeight for which the contract still accepts it
+pub const VRF_CHALLENGE_ACCEPTANCE_WINDOW_BLOCKS: u64 = 100;
+
+/// Average block time on NEAR mainnet, in milliseconds
+pub const NEAR_MAINNET_AVERAGE_BLOCK_TIME_MS: u32 = 1_100;
+
+/// Average block time on NEAR testnet, in milliseconds
+pub const NEAR_TESTNET_AVERAGE_BLOCK_TIME_MS: u32 = 1_200;
+
+/// Average block time assumed for other networks (localnet, sandboxes) unless
+/// WorkerPolicy.averageBlockTimeMs is set
+pub const DEFAULT_AVERAGE_BLOCK_TIME_MS: u32 = 1_000;
+
+// === CONFIRMATION TIMEOUT ===
+
+/// How long a signing confirmation stays valid when ConfirmationConfig.confirmationTimeoutMs is unset
+pub const DEFAULT_CONFIRMATION_TIMEOUT_MS: u32 = 120_000;
+
+/// How long an auto-proceed confirmation waits for the UI's CountdownStarted ack before it
+/// falls back to requireClick
+pub const COUNTDOWN_ACK_TIMEOUT_MS: u32 = 2_000;
+
+// === RECENT RECEIVERS ===
+
+/// Receivers returned by GetRecentReceivers when the request gives no limit
+pub const DEFAULT_RECENT_RECEIVERS_LIMIT: u32 = 20;
+
+/// Outgoing transactions fetched from the indexer (and the largest accepted limit)
+pub const MAX_RECENT_RECEIVERS_LIMIT: u32 = 100;
+
+/// How long an account's fetched receiver history is reused before re-querying the indexer
+pub const RECENT_RECEIVERS_CACHE_TTL_MS: f64 = 10.0 * 60.0 * 1000.0;
+
+// === DIFFERENTIAL CONFIRMATION ===
+
+/// Confirmed transaction batches kept per worker session to diff re-requests against
+pub const RECENT_CONFIRMED_INTENTS_LIMIT: usize = 5;
+
+/// FunctionCall args larger than this are compared as a whole ("args changed")
+pub const MAX_DIFF_ARGS_BYTES: usize = 16 * 1024;
+
+/// Changed argument paths reported per action before falling back to "args changed"
+pub const MAX_DIFF_ARG_PATHS: usize = 32;
+
+// === ARGS SCHEMAS ===
+
+/// Schemas `WorkerPolicy.argsSchemas` may register
+pub const MAX_ARGS_SCHEMAS: usize = 64;
+
+/// Nesting depth of a registered schema (and of the args checked against it) beyond which
+/// the schema is refused, and deeper args are reported as a violation
+pub const MAX_ARGS_SCHEMA_DEPTH: usize = 32;
+
+/// Matching steps one `pattern` may take on one string before it is reported as too
+/// expensive to evaluate (a violation, never a pass)
+pub const ARGS_SCHEMA_PATTERN_STEP_LIMIT: u32 = 1_000_000;
+
+/// Backtracking depth of one `pattern` match, bounding the stack the matcher uses
+pub const ARGS_SCHEMA_PATTERN_RECURSION_LIMIT: u32 = 1_000;
+
+/// Violations listed in an ArgsSchemaViolation error; further ones are counted
+pub const MAX_REPORTED_ARGS_SCHEMA_VIOLATIONS: usize = 32;
+
+// === CONFIRMATION SUMMARY BLOCKS ===
+
+/// yoctoNEAR in one NEAR
+pub const YOCTO_PER_NEAR: u128 = 1_000_000_000_000_000_000_000_000;
+
+/// Total deposit per transaction above which confirmations carry a DepositAboveThreshold
+/// warning, unless `WorkerPolicy.depositEscalationYocto` sets another (10 NEAR)
+pub const DEFAULT_DEPOSIT_ESCALATION_YOCTO: u128 = 10 * YOCTO_PER_NEAR;
+
+/// Allowance a function-call key may have left after the estimated cost of a confirmation
+/// before it carries a LowAllowance warning, unless `WorkerPolicy.lowAllowanceWarningYocto`
+/// sets another (0.05 NEAR)
+pub const DEFAULT_LOW_ALLOWANCE_WARNING_YOCTO: u128 = YOCTO_PER_NEAR / 20;
+
+/// Compact JSON bytes of one FunctionCall argument a confirmation shows before truncating it,
+/// unless `WorkerPolicy.maxArgPreviewBytes` sets another (see render_budget.rs)
+pub const DEFAULT_MAX_ARG_PREVIEW_BYTES: usize = 2 * 1024;
+
+/// Summary blocks a confirmation shows before rolling the remaining actions up, unless
+/// `WorkerPolicy.maxSummaryBlocks` sets another (see render_budget.rs)
+pub const DEFAULT_MAX_SUMMARY_BLOCKS: usize = 200;
+
+/// Gas price (yoctoNEAR per gas unit) for allowance estimates when the transaction context
+/// has no block gas price (NEAR's minimum gas price)
+pub const DEFAULT_ALLOWANCE_GAS_PRICE: u128 = 100_000_000;
+
+// === ACCOUNT DESCRIPTORS ===
+
+/// Descriptor format version embedded in (and required of) every descriptor
+pub const ACCOUNT_DESCRIPTOR_VERSION: u32 = 1;
+
+/// Domain separation prefix for the NEAR-key signature over a descriptor's CBOR bytes
+pub const ACCOUNT_DESCRIPTOR_SIGNING_DOMAIN: &[u8] = b"web3authn:account-descriptor:v1";
+
+/// Descriptor lifetime when BuildAccountDescriptor gives no ttlMs
+pub const DEFAULT_ACCOUNT_DESCRIPTOR_TTL_MS: u32 = 24 * 60 * 60 * 1000;
+
+/// Longest accepted descriptor lifetime (7 days)
+pub const MAX_ACCOUNT_DESCRIPTOR_TTL_MS: u32 = 7 * 24 * 60 * 60 * 1000;
+
+// === ACCOUNT BUNDLES ===
+
+/// Format tag at the start of every account bundle header
+pub const ACCOUNT_BUNDLE_FORMAT: &str = "web3authn-account-bundle";
+
+/// Bundle version written by ExportAccountBundle; ImportAccountBundle rejects newer ones
+pub const ACCOUNT_BUNDLE_VERSION: u32 = 1;
+
+/// Argon2id memory cost for new bundles, in KiB (19 MiB, the OWASP minimum for Argon2id).
+/// Tuned with the `bench_account_bundle_kdf` test to stay under ~1s in WASM on a mid-range phone.
+pub const ACCOUNT_BUNDLE_ARGON2_MEMORY_KIB: u32 = 19 * 1024;
+
+/// Argon2id passes over memory for new bundles
+pub const ACCOUNT_BUNDLE_ARGON2_ITERATIONS: u32 = 2;
+
+/// Argon2id lanes for new bundles (the worker derives on a single thread)
+pub const ACCOUNT_BUNDLE_ARGON2_PARALLELISM: u32 = 1;
+
+/// Largest Argon2id memory cost accepted from a bundle header (64 MiB), so a crafted bundle
+/// cannot make the worker allocate without bound
+pub const MAX_ACCOUNT_BUNDLE_ARGON2_MEMORY_KIB: u32 = 64 * 1024;
+
+/// Largest Argon2id pass count accepted from a bundle header
+pub const MAX_ACCOUNT_BUNDLE_ARGON2_ITERATIONS: u32 = 10;
+
+/// Argon2id salt size in bytes
+pub const ACCOUNT_BUNDLE_SALT_SIZE: usize = 16;
+
+/// Shortest passphrase ExportAccountBundle accepts, in characters
+pub const MIN_ACCOUNT_BUNDLE_PASSPHRASE_CHARS: usize = 8;
+
+// === PRF FALLBACK KEY WRAPPING ===
+
+/// Magic prefix of the key-wrapping header on v2 and v3 blobs: magic, the blob version byte
+/// and, for v3, the Argon2id salt, ahead of the key-check header
+pub const KEY_WRAPPING_HEADER_MAGIC: &[u8; 4] = b"W3KW";
+
+/// Size of the random secret a v2 blob's key is derived from, stored in the largeBlob
+pub const LARGE_BLOB_SECRET_SIZE: usize = 32;
+
+/// Argon2id salt size in bytes for v3 blobs
+pub const PASSPHRASE_WRAP_SALT_SIZE: usize = 16;
+
+/// Argon2id cost for v3 blobs (the account bundle defaults). The header does not record them:
+/// retuning needs a new blob version.
+pub const PASSPHRASE_WRAP_ARGON2_MEMORY_KIB: u32 = 19 * 1024;
+pub const PASSPHRASE_WRAP_ARGON2_ITERATIONS: u32 = 2;
+pub const PASSPHRASE_WRAP_ARGON2_PARALLELISM: u32 = 1;
+
+/// Shortest passphrase the v3 scheme accepts, in characters
+pub const MIN_PASSPHRASE_WRAP_CHARS: usize = 8;
+
+// === SIGNING INTENTS ===
+
+/// Signing intent format version embedded in (and required of) every intent token
+pub const SIGNING_INTENT_VERSION: u32 = 1;
+
+/// Domain separation prefix for the worker's HMAC over an intent's CBOR bytes
+pub const SIGNING_INTENT_MAC_DOMAIN: &[u8] = b"web3authn:signing-intent:v1";
+
+/// Intent lifetime when CreateSigningIntent gives no ttlMs (10 minutes)
+pub const DEFAULT_SIGNING_INTENT_TTL_MS: u32 = 10 * 60 * 1000;
+
+/// Longest intent lifetime when WorkerPolicy.maxSigningIntentTtlMs is unset (1 hour)
+pub const DEFAULT_MAX_SIGNING_INTENT_TTL_MS: u32 = 60 * 60 * 1000;
+
+/// Longest intent lifetime any WorkerPolicy may allow (24 hours)
+pub const MAX_SIGNING_INTENT_TTL_MS: u32 = 24 * 60 * 60 * 1000;
+
+// === RESUMABLE REGISTRATION ===
+
+/// Resume token format version embedded in (and required of) every PrepareRegistration token
+pub const REGISTRATION_RESUME_VERSION: u32 = 1;
+
+/// HKDF info of the resume token MAC key, derived from the registration's ChaCha20 PRF output
+pub const REGISTRATION_RESUME_HKDF_INFO: &str = "web3authn-registration-resume-v1";
+
+/// How long CompleteRegistration accepts a resume token (10 minutes)
+pub const REGISTRATION_RESUME_TTL_MS: u32 = 10 * 60 * 1000;
+
+// === REMOTE CONFIRMATION ===
+
+#[cfg(feature = "device-linking")]
+/// Remote confirmation format version embedded in (and required of) requests and approvals
+pub const REMOTE_CONFIRMATION_VERSION: u32 = 1;
+
+#[cfg(feature = "device-linking")]
+/// Domain separation prefix for the session key's signature over a request's CBOR bytes
+pub const REMOTE_CONFIRMATION_SIGNATURE_DOMAIN: &[u8] = b"web3authn:remote-confirmation:v1";
+
+#[cfg(feature = "device-linking")]
+/// HKDF info of the approval key, derived from the X25519 shared secret
+pub const REMOTE_CONFIRMATION_HKDF_INFO: &str = "web3authn-remote-confirmation-approval-v1";
+
+#[cfg(feature = "device-linking")]
+/// Request lifetime when CreateRemoteConfirmation gives no ttlMs (2 minutes, about the VRF
+/// challenge acceptance window)
+pub const DEFAULT_REMOTE_CONFIRMATION_TTL_MS: u32 = 2 * 60 * 1000;
+
+#[cfg(feature = "device-linking")]
+/// Longest request lifetime CreateRemoteConfirmation accepts (10 minutes)
+pub const MAX_REMOTE_CONFIRMATION_TTL_MS: u32 = 10 * 60 * 1000;
+
+// === QR PAYLOADS ===
+
+#[cfg(feature = "device-linking")]
+/// QR payload format version, the first byte of every part
+pub const QR_PAYLOAD_VERSION: u8 = 1;
+
+#[cfg(feature = "device-linking")]
+/// Prefix of every QR part, ahead of its base45 body
+pub const QR_PAYLOAD_PREFIX: &str = "W3A:";
+
+#[cfg(feature = "device-linking")]
+/// Longest QR part, in QR alphanumeric characters (about 1.2KB, which scans reliably at medium
+/// error correction)
+pub const QR_PART_MAX_CHARS: usize = 1200;
+
+#[cfg(feature = "device-linking")]
+/// Shortest part budget QrPayloadEncoder accepts: the prefix and header with some data
+pub const QR_PART_MIN_CHARS: usize = 64;
+
+#[cfg(feature = "device-linking")]
+/// Largest blob a QR payload carries, before compression and after decompression
+pub const MAX_QR_PAYLOAD_BYTES: usize = 64 * 1024;
+
+// === REDIRECT PAYLOADS ===
+
+/// Redirect payload format version embedded in (and required of) every payload token
+pub const REDIRECT_PAYLOAD_VERSION: u32 = 1;
+
+/// Domain separation prefix for the session key's signature over a payload's CBOR bytes
+pub const REDIRECT_PAYLOAD_SIGNATURE_DOMAIN: &[u8] = b"web3authn:redirect-payload:v1";
+
+/// Payload lifetime when BuildRedirectPayload gives no ttlMs (5 minutes)
+pub const DEFAULT_REDIRECT_PAYLOAD_TTL_MS: u32 = 5 * 60 * 1000;
+
+/// Longest payload lifetime BuildRedirectPayload accepts (15 minutes)
+pub const MAX_REDIRECT_PAYLOAD_TTL_MS: u32 = 15 * 60 * 1000;
+
+/// Query parameters the token and state are appended to the callback URL as
+pub const REDIRECT_PAYLOAD_PARAM: &str = "w3a_payload";
+pub const REDIRECT_STATE_PARAM: &str = "state";
+
+/// Longest composed redirect URL, in characters (what browsers, proxies and deep-link
+/// handlers pass through reliably)
+pub const MAX_REDIRECT_URL_CHARS: usize = 2048;
+
+/// Longest `state` a redirect payload carries, in characters
+pub const MAX_REDIRECT_STATE_CHARS: usize = 128;
+
+// === SEALED SECRETS ===
+
+/// Sealed secret format version, the first byte of every box
+pub const SEALED_SECRET_VERSION: u8 = 1;
+
+/// HKDF info prefix of a sealed secret's key; the ephemeral and session public keys follow
+pub const SEALED_SECRET_HKDF_INFO: &str = "web3authn-sealed-secret-v1";
+
+/// Payload field holding the request's sealed secrets, keyed by the JSON pointer they open to
+pub const SEALED_SECRETS_FIELD: &str = "sealedSecrets";
+
+// === RELAYER ===
+
+#[cfg(feature = "relayer")]
+/// Relayer endpoint of sponsored account creation, relative to WorkerPolicy.relayer.url
+pub const RELAYER_CREATE_ACCOUNT_PATH: &str = "/create_account_and_register_user";
+
+#[cfg(feature = "relayer")]
+/// Reachability probe run before submitting, relative to WorkerPolicy.relayer.url
+pub const RELAYER_HEALTH_PATH: &str = "/healthz";
+
+#[cfg(feature = "relayer")]
+/// Relayer response schema expected when WorkerPolicy.relayer.responseSchemaVersion is unset.
+/// Version 1 responses need not declare `schemaVersion`.
+pub const RELAYER_RESPONSE_SCHEMA_VERSION: u32 = 1;
+
+// === RETRY POLICY ===
+
+#[cfg(feature = "relayer")]
+/// Attempts of an idempotent network phase, including the first
+pub const RETRY_MAX_ATTEMPTS: u32 = 3;
+
+#[cfg(feature = "relayer")]
+/// Delay before the first retry; doubled before each following one
+pub const RETRY_BASE_DELAY_MS: u32 = 500;
+
+/// Re-signs of one broadcast transaction after InvalidNonce (each with the access key's
+/// current nonce); a second InvalidNonce means another signer is using the key
+pub const INVALID_NONCE_MAX_RESIGNS: u32 = 1;
+
+// === IDEMPOTENCY KEYS ===
+
+/// Completed requests remembered by idempotency key for the worker's lifetime
+pub const IDEMPOTENCY_CACHE_LIMIT: usize = 64;
+
+// === TELEMETRY ===
+
+#[cfg(feature = "telemetry")]
+/// Shortest interval between two telemetry reports WorkerPolicy.telemetry may ask for
+pub const MIN_TELEMETRY_FLUSH_INTERVAL_MINUTES: u32 = 1;
+
+#[cfg(feature = "telemetry")]
+/// Interval between telemetry reports when WorkerPolicy.telemetry.flushIntervalMinutes is unset
+pub const DEFAULT_TELEMETRY_FLUSH_INTERVAL_MINUTES: u32 = 15;
+
+#[cfg(feature = "telemetry")]
+/// Upper bounds (inclusive) of the latency histogram buckets; slower requests fall in one
+/// overflow bucket after the last
+pub const TELEMETRY_LATENCY_BUCKETS_MS: [u32; 10] =
+    [10, 25, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000];
+
+#[cfg(feature = "telemetry")]
+/// Version of the CBOR TelemetryReport layout
+pub const TELEMETRY_REPORT_VERSION: u32 = 1;
+
+// === KEY USAGE STATS ===
+
+/// Version of the exported KeyUsageRecord layout
+pub const KEY_USAGE_RECORD_VERSION: u32 = 1;
+
+/// HKDF info deriving a key's usage-entry MAC key from its passkey's PRF output
+pub const KEY_USAGE_MAC_HKDF_INFO: &str = "web3authn-key-usage-mac-v1";
+
+/// Bytes of SHA-256(receiver ID) kept as an entry's last receiver
+pub const KEY_USAGE_RECEIVER_HASH_SIZE: usize = 8;
+
+/// Entries of a KeyUsageRecord; past it the least recently used key is dropped
+pub const MAX_KEY_USAGE_ENTRIES: usize = 64;
+
+// === PERSISTED WORKER STATE ===
+
+/// Version of the exported WorkerStateRecord layout
+pub const WORKER_STATE_RECORD_VERSION: u32 = 1;
+
+/// Associated data of the sealed state (the wrapped state key is appended)
+pub const WORKER_STATE_AAD_DOMAIN: &str = "web3authn-worker-state-v1";
+
+/// AES-GCM IV size in bytes for the wrapped state key
+pub const WORKER_STATE_KEY_IV_SIZE: usize = 12;
+
+// === OPERATION JOURNAL ===
+
+/// Journal entries one RecoverPendingOperations request may reconcile
+pub const MAX_RECOVERABLE_OPERATIONS: usize = 64;
+
+// === ELEVATED OPERATIONS ===
+
+/// How recent an elevated operation's passkey assertion must be, unless WorkerPolicy sets
+/// elevationMaxAgeMs
+pub const DEFAULT_ELEVATION_MAX_AGE_MS: u32 = 60_000;
+
+/// Domain separation prefix for the worker's HMAC over an elevation challenge's CBOR bytes
+pub const ELEVATION_CHALLENGE_MAC_DOMAIN: &[u8] = b"web3authn:elevation-challenge:v1";
+
+// === DUAL CONTROL ===
+
+/// How long a first approval waits for the second one (10 minutes)
+pub const DUAL_CONTROL_APPROVAL_TTL_MS: u32 = 10 * 60 * 1000;
+
+/// Domain separation prefix for the worker's HMAC over a first approval's CBOR bytes
+pub const DUAL_CONTROL_APPROVAL_MAC_DOMAIN: &[u8] = b"web3authn:dual-control-approval:v1";
+
+// === SUB-KEY DERIVATION ===
+
+/// HKDF info deriving the SLIP-0010 seed of an account's sub-keys from the Ed25519 PRF output
+/// (with the account's `near_key_salt_for_account` salt, like the account key)
+pub const SUB_KEY_SEED_HKDF_INFO: &str = "near-sub-key-slip10-seed-v1";
+
+/// Bytes of that seed (SLIP-0010 accepts 16 to 64)
+pub const SUB_KEY_SEED_SIZE: usize = 64;
+
+/// HMAC key of the SLIP-0010 ed25519 master key
+pub const SLIP10_ED25519_CURVE_KEY: &[u8] = b"ed25519 seed";
+
+/// Deepest sub-key path accepted (the BIP-44 style "m/44'/397'/0'/0'/k'" is 5)
+pub const MAX_DERIVATION_DEPTH: usize = 10;
+
+/// Sub-key paths one registration or recovery may derive
+pub const MAX_SUB_KEY_PATHS: usize = 32;
+
+// === BACKGROUND REFRESH ===
+
+/// Shortest interval StartBackgroundRefresh accepts; shorter ones are raised to it so a
+/// misconfigured page cannot drain its RPC quota
+pub const MIN_BACKGROUND_REFRESH_INTERVAL_MS: u32 = 2_000;
+
+/// Longest wait between refreshes while failures back off (5 minutes)
+pub const MAX_BACKGROUND_REFRESH_BACKOFF_MS: u32 = 5 * 60 * 1000;
+
+/// Accounts one background refresh may keep warm
+pub const MAX_BACKGROUND_REFRESH_ACCOUNTS: usize = 16;
+
+// === SUBACCOUNTS ===
+
+/// NEAR account IDs are 2 to 64 characters long
+pub const MIN_ACCOUNT_ID_LEN: usize = 2;
+pub const MAX_ACCOUNT_ID_LEN: usize = 64;
+
+// === STORAGE COST ESTIMATION ===
+
+/// NEAR storage staking price: 10^19 yoctoNEAR (0.00001 NEAR) per byte
+pub const STORAGE_PRICE_PER_BYTE_YOCTO: u128 = 10_000_000_000_000_000_000;
+
+/// Fixed per-authenticator record overhead on the contract (keys, account ID, metadata),
+/// added to the credential ID, COSE key and VRF key bytes when estimating registration storage
+pub const REGISTRATION_STORAGE_OVERHEAD_BYTES: u64 = 256;
+
+// === BLOB MIGRATION ===
+
+/// Formats MigrateEncryptedBlobs reports for NEAR key blobs, apart from the record's `version`
+/// (the key-wrapping scheme): ciphertext without the key-check header, then with it (current)
+pub const NEAR_KEY_BLOB_FORMAT_LEGACY: u32 = 1;
+pub const NEAR_KEY_BLOB_FORMAT_KEY_CHECK: u32 = 2;
+
+/// EncryptedVRFKeypair formats, the VRF worker's ENCRYPTED_BLOB_VERSION_V1 and _V2: without
+/// metadata, then with `meta` bound as associated data (current)
+pub const VRF_KEYPAIR_BLOB_VERSION_LEGACY: u32 = 1;
+pub const VRF_KEYPAIR_BLOB_VERSION: u32 = 2;
+
+/// HKDF info of the VRF keypair's ChaCha20 key, the VRF worker's HKDF_CHACHA20_KEY_INFO
+pub const VRF_KEYPAIR_HKDF_INFO: &[u8] = b"vrf-chacha20-key";
+
+/// Session snapshot format, the VRF worker's SESSION_SNAPSHOT_VERSION
+pub const SESSION_SNAPSHOT_VERSION: u32 = 1;
+
+// === MEMORY ===
+
+/// WebAssembly linear memory page size, in bytes
+pub const WASM_PAGE_SIZE_BYTES: usize = 65_536;
+
+/// Transient buffers larger than this are not kept in the arena once released,
+/// so one big DeployContract doesn't pin its buffer for the rest of the worker's life
+pub const TRANSIENT_ARENA_RETAIN_MAX_BYTES: usize = 64 * 1024;
+
+// === DIAGNOSTICS ===
+
+/// Version of the DumpDiagnostics document
+pub const DIAGNOSTICS_SCHEMA_VERSION: u32 = 1;
+
+/// Policy lists longer than this are reported as their length and SHA-256
+pub const DIAGNOSTICS_MAX_LIST_ITEMS: usize = 16;
+
+/// Audit entries (across accounts) included in a diagnostics dump
+pub const DIAGNOSTICS_LOG_ENTRIES: usize = 50;
+
+// === RESPONSE SCHEMA ===
+
+/// Version of the JS-visible shape of handler results, reported by GetWorkerInfo.
+/// v2 made every result camelCase plain objects:
+/// - progress data `transaction_count` became `transactionCount`;
+/// - wasm exports return plain objects where they returned Maps (tagged enums such as
+///   `parsedSummary.actions` of build_unsigned_transaction, JSON values), and null for None.
+pub const RESPONSE_SCHEMA_VERSION: u32 = 2;
+
 // === ERROR MESSAGES ===
 
 /// Error message for empty PRF output
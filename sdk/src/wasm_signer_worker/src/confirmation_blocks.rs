@@ -0,0 +1,930 @@
+// === CONFIRMATION SUMMARY BLOCKS ===
+// Integrators render confirmations with their own components, but all of them must show the
+// same verified content. Each transaction of a confirmation payload carries `summaryBlocks`:
+// an ordered list of renderer-agnostic blocks (headings, amount and account rows, code, and
+// warnings) generated here from the receivers and actions. The blocks are part of the intent
+// digest, and the warnings for risky actions are generated by the worker, so a renderer cannot
+// drop one and still produce a digest the worker accepts.
+//
+// Blocks (tagged by `kind`), each with the `ariaLabel` a screen reader announces for it (see
+// confirmation_speech.rs):
+//   { kind: "heading", text }
+//   { kind: "amountRow", label, yocto, formatted }
+//   { kind: "accountRow", label, accountId, firstTime }   // firstTime: null when unknown
+//   { kind: "codeBlock", json }
+//   { kind: "warning", severity: "caution" | "danger", code, text, ariaLive }
+//   { kind: "deadlineRow", label, blockHeight }           // requests with validUntilBlockHeight
+//   { kind: "keyRow", label, publicKey, fingerprint }     // signingPublicKey, full access AddKey
+//   { kind: "rollup", text, hiddenActions, receiverId, amounts }  // past maxSummaryBlocks
+// A transaction's warnings come first, then its receiver row and deadline row, then each
+// action in order (a full access AddKey shows the new key's row, with its fingerprint, after
+// its heading; see action_firewall.rs), except that a subaccount creation (CreateAccount,
+// Transfer, full access AddKey) is one "Create account X with Y NEAR" heading (see
+// subaccount.rs). Blocks about the whole batch (the signing key row, then the LowAllowance
+// warning, then the ConditionalBatch, StorageDepositsAdded and UnknownActions warnings) lead the first transaction's blocks, and the first transaction carries the
+// `summarySpeech` sentence describing the whole batch. Past the policy's maxSummaryBlocks, the
+// remaining actions of each transaction are shown as one rollup block listing the amounts
+// they move; warnings, receiver and deadline rows and batch blocks are never rolled up (see
+// render_budget.rs).
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::actions::ActionParams;
+use crate::allowance::AllowanceEstimate;
+use crate::config::{DEFAULT_DEPOSIT_ESCALATION_YOCTO, YOCTO_PER_NEAR};
+use crate::confirmation_speech::{Phrase, SpeechLocale, SpokenTransaction};
+use crate::key_selection::{key_fingerprint, parse_public_key};
+use crate::redaction::RedactPath;
+use crate::render_budget::RenderBudget;
+use crate::subaccount::as_create_subaccount;
+use crate::types::handlers::WorkerPolicy;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum WarningSeverity {
+    /// Worth a second look (a new receiver, a large deposit)
+    Caution,
+    /// Hands over or destroys the account
+    Danger,
+}
+
+/// aria-live politeness of a warning's live region
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AriaLive {
+    Polite,
+    Assertive,
+}
+
+impl WarningSeverity {
+    /// Danger warnings interrupt the screen reader; cautions wait for it
+    pub fn aria_live(self) -> AriaLive {
+        match self {
+            WarningSeverity::Caution => AriaLive::Polite,
+            WarningSeverity::Danger => AriaLive::Assertive,
+        }
+    }
+}
+
+/// Labels of the amount and account rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowLabel {
+    Amount,
+    Deposit,
+    Stake,
+    Receiver,
+    Beneficiary,
+    #[cfg(feature = "secp256k1")]
+    Account,
+}
+
+impl RowLabel {
+    /// The label shown (the spoken label is localized)
+    pub fn text(self) -> &'static str {
+        match self {
+            RowLabel::Amount => "Amount",
+            RowLabel::Deposit => "Deposit",
+            RowLabel::Stake => "Stake",
+            RowLabel::Receiver => "Receiver",
+            RowLabel::Beneficiary => "Beneficiary",
+            #[cfg(feature = "secp256k1")]
+            RowLabel::Account => "Account",
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryWarningCode {
+    FullAccessKey,
+    DeleteAccount,
+    DepositAboveThreshold,
+    FirstTimeReceiver,
+    LowAllowance,
+    ArgsNotValidated,
+    ConditionalBatch,
+    StorageDepositsAdded,
+    UnknownActions,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ConfirmationSummaryBlock {
+    #[serde(rename_all = "camelCase")]
+    Heading {
+        text: String,
+        aria_label: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    AmountRow {
+        label: String,
+        yocto: String,
+        formatted: String,
+        aria_label: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    AccountRow {
+        label: String,
+        account_id: String,
+        first_time: Option<bool>,
+        aria_label: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    CodeBlock {
+        json: String,
+        aria_label: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Warning {
+        severity: WarningSeverity,
+        code: SummaryWarningCode,
+        text: String,
+        aria_label: String,
+        aria_live: AriaLive,
+    },
+    #[serde(rename_all = "camelCase")]
+    DeadlineRow {
+        label: String,
+        block_height: u64,
+        aria_label: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    KeyRow {
+        label: String,
+        public_key: String,
+        fingerprint: String,
+        aria_label: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Rollup {
+        text: String,
+        hidden_actions: usize,
+        receiver_id: String,
+        /// Amount rows of the hidden actions, in order
+        amounts: Vec<RolledUpAmount>,
+        aria_label: String,
+    },
+}
+
+/// An amount moved by an action hidden in a rollup block
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RolledUpAmount {
+    pub action_index: usize,
+    pub label: String,
+    pub yocto: String,
+    pub formatted: String,
+}
+
+/// Worker policy and request inputs to the summary blocks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SummaryPolicy {
+    /// Transactions depositing more than this in total get a DepositAboveThreshold warning
+    pub deposit_escalation_yocto: u128,
+    /// The request's validUntilBlockHeight, shown as a deadline row
+    pub valid_until_block_height: Option<u64>,
+    /// The function-call signing key's allowance against the batch (see allowance.rs)
+    pub allowance: Option<AllowanceEstimate>,
+    /// The request's signingPublicKey (see key_selection.rs), shown with its fingerprint
+    pub signing_key: Option<[u8; 32]>,
+    /// (contractId, methodName) of `workerPolicy.argsSchemas`: FunctionCalls to them whose
+    /// args are not JSON get an ArgsNotValidated warning
+    pub args_schema_methods: Vec<(String, String)>,
+    /// The request's redactPaths: args values shown as their hash (see redaction.rs)
+    pub redact_paths: Vec<RedactPath>,
+    /// ConfirmationConfig.locale, for the blocks' ariaLabel and the summarySpeech
+    pub locale: SpeechLocale,
+    /// The request's conditionalBatch: later transactions may be skipped (see
+    /// conditional_batch.rs)
+    pub conditional: bool,
+    /// Each transaction's `dependsOn` (see tx_dependencies.rs), empty when none has one
+    pub depends_on: Vec<Vec<u32>>,
+    /// Each transaction's actions inserted by autoStorageDeposit (see storage_deposit.rs),
+    /// empty when none was
+    pub auto_inserted_actions: Vec<Vec<u32>>,
+    /// Actions of a decoded transaction that could not be decoded (see signed_tx_decoder.rs)
+    pub unknown_actions: usize,
+    /// Argument preview and block limits (see render_budget.rs)
+    pub render_budget: RenderBudget,
+}
+
+impl Default for SummaryPolicy {
+    fn default() -> Self {
+        SummaryPolicy {
+            deposit_escalation_yocto: DEFAULT_DEPOSIT_ESCALATION_YOCTO,
+            valid_until_block_height: None,
+            allowance: None,
+            signing_key: None,
+            args_schema_methods: Vec::new(),
+            redact_paths: Vec::new(),
+            locale: SpeechLocale::default(),
+            conditional: false,
+            depends_on: Vec::new(),
+            auto_inserted_actions: Vec::new(),
+            unknown_actions: 0,
+            render_budget: RenderBudget::default(),
+        }
+    }
+}
+
+impl SummaryPolicy {
+    /// From `workerPolicy.depositEscalationYocto` (defaults to DEFAULT_DEPOSIT_ESCALATION_YOCTO),
+    /// `workerPolicy.argsSchemas` and the policy's render budget
+    pub fn from_worker_policy(policy: Option<&WorkerPolicy>) -> Result<Self, String> {
+        let deposit_escalation_yocto = match policy
+            .and_then(|p| p.deposit_escalation_yocto.as_deref())
+        {
+            None => DEFAULT_DEPOSIT_ESCALATION_YOCTO,
+            Some(yocto) => yocto
+                .parse::<u128>()
+                .map_err(|e| format!("Invalid workerPolicy.depositEscalationYocto: {}", e))?,
+        };
+        let args_schema_methods = policy
+            .map(|p| {
+                p.args_schemas
+                    .iter()
+                    .map(|entry| (entry.contract_id.clone(), entry.method_name.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(SummaryPolicy {
+            deposit_escalation_yocto,
+            args_schema_methods,
+            render_budget: RenderBudget::from_worker_policy(policy)?,
+            ..SummaryPolicy::default()
+        })
+    }
+
+    /// Adds the request's signing deadline
+    pub fn with_deadline(self, valid_until_block_height: Option<u64>) -> Self {
+        SummaryPolicy {
+            valid_until_block_height,
+            ..self
+        }
+    }
+
+    /// Adds the signing key's allowance estimate
+    pub fn with_allowance(self, allowance: Option<AllowanceEstimate>) -> Self {
+        SummaryPolicy { allowance, ..self }
+    }
+
+    /// Adds the key selected to sign
+    pub fn with_signing_key(self, signing_key: Option<[u8; 32]>) -> Self {
+        SummaryPolicy {
+            signing_key,
+            ..self
+        }
+    }
+
+    /// Adds the args values to redact
+    pub fn with_redactions(self, redact_paths: Vec<RedactPath>) -> Self {
+        SummaryPolicy {
+            redact_paths,
+            ..self
+        }
+    }
+
+    /// Adds the locale the blocks are spoken in
+    pub fn with_locale(self, locale: SpeechLocale) -> Self {
+        SummaryPolicy { locale, ..self }
+    }
+
+    /// Marks the batch as conditional
+    pub fn with_conditional(self, conditional: bool) -> Self {
+        SummaryPolicy {
+            conditional,
+            ..self
+        }
+    }
+
+    /// Adds each transaction's `dependsOn`
+    pub fn with_dependencies(self, depends_on: Vec<Vec<u32>>) -> Self {
+        SummaryPolicy { depends_on, ..self }
+    }
+
+    /// Adds each transaction's automatically inserted actions
+    pub fn with_auto_inserted_actions(self, auto_inserted_actions: Vec<Vec<u32>>) -> Self {
+        SummaryPolicy {
+            auto_inserted_actions,
+            ..self
+        }
+    }
+
+    /// Adds the number of actions that could not be decoded
+    pub fn with_unknown_actions(self, unknown_actions: usize) -> Self {
+        SummaryPolicy {
+            unknown_actions,
+            ..self
+        }
+    }
+}
+
+/// `yocto` in NEAR, without trailing zeros (e.g. "1.5 NEAR")
+pub fn format_yocto_near(yocto: u128) -> String {
+    let whole = yocto / YOCTO_PER_NEAR;
+    let fraction = yocto % YOCTO_PER_NEAR;
+    if fraction == 0 {
+        return format!("{} NEAR", whole);
+    }
+    let fraction = format!("{:024}", fraction);
+    format!("{}.{} NEAR", whole, fraction.trim_end_matches('0'))
+}
+
+fn amount_row(label: RowLabel, yocto: &str, locale: SpeechLocale) -> ConfirmationSummaryBlock {
+    let amount = yocto.parse::<u128>().ok();
+    let formatted = match amount {
+        Some(amount) => format_yocto_near(amount),
+        None => format!("{} yoctoNEAR (invalid amount)", yocto),
+    };
+    ConfirmationSummaryBlock::AmountRow {
+        label: label.text().to_string(),
+        yocto: yocto.to_string(),
+        formatted,
+        aria_label: locale.say(&Phrase::Amount {
+            label,
+            yocto: amount,
+        }),
+    }
+}
+
+pub fn account_row(
+    label: RowLabel,
+    account_id: &str,
+    first_time: Option<bool>,
+    locale: SpeechLocale,
+) -> ConfirmationSummaryBlock {
+    ConfirmationSummaryBlock::AccountRow {
+        label: label.text().to_string(),
+        account_id: account_id.to_string(),
+        first_time,
+        aria_label: locale.say(&Phrase::Account {
+            label,
+            account_id,
+            first_time,
+        }),
+    }
+}
+
+fn heading(
+    action: &ActionParams,
+    text: impl Into<String>,
+    locale: SpeechLocale,
+) -> ConfirmationSummaryBlock {
+    ConfirmationSummaryBlock::Heading {
+        text: text.into(),
+        aria_label: locale.say(&Phrase::Heading(action)),
+    }
+}
+
+pub fn code_block(json: Value, aria_label: String) -> ConfirmationSummaryBlock {
+    ConfirmationSummaryBlock::CodeBlock {
+        json: json.to_string(),
+        aria_label,
+    }
+}
+
+fn warning(
+    severity: WarningSeverity,
+    code: SummaryWarningCode,
+    text: String,
+    phrase: Phrase,
+    locale: SpeechLocale,
+) -> ConfirmationSummaryBlock {
+    ConfirmationSummaryBlock::Warning {
+        severity,
+        code,
+        text,
+        aria_label: locale.warning(severity, &phrase),
+        aria_live: severity.aria_live(),
+    }
+}
+
+/// Whether an AddKey `access_key` JSON grants full access. Unparseable keys count as full
+/// access: the warning is better shown needlessly than missed.
+pub fn is_full_access_key(access_key: &str) -> bool {
+    match serde_json::from_str::<Value>(access_key) {
+        Ok(access_key) => {
+            let permission = &access_key["permission"];
+            !permission["FunctionCall"].is_object()
+        }
+        Err(_) => true,
+    }
+}
+
+/// key_fingerprint of an "ed25519:<base58>" public key; None when it does not parse
+fn public_key_fingerprint(public_key: &str) -> Option<String> {
+    parse_public_key(public_key)
+        .ok()
+        .map(|bytes| key_fingerprint(&bytes))
+}
+
+/// Deposit an action attaches (Stake locks rather than sends, and is not counted)
+fn action_deposit(action: &ActionParams) -> Option<&str> {
+    match action {
+        ActionParams::FunctionCall { deposit, .. } | ActionParams::Transfer { deposit } => {
+            Some(deposit)
+        }
+        _ => None,
+    }
+}
+
+fn action_blocks(action: &ActionParams, locale: SpeechLocale) -> Vec<ConfirmationSummaryBlock> {
+    match action {
+        ActionParams::CreateAccount => vec![heading(action, "Create account", locale)],
+        ActionParams::DeployContract { code } => vec![
+            heading(action, "Deploy contract", locale),
+            code_block(
+                serde_json::json!({ "codeBytes": code.len() }),
+                locale.say(&Phrase::ContractCode { bytes: code.len() }),
+            ),
+        ],
+        ActionParams::FunctionCall {
+            method_name,
+            args,
+            gas,
+            deposit,
+        } => {
+            let args = serde_json::from_str::<Value>(args).unwrap_or_else(|_| args.as_str().into());
+            vec![
+                heading(action, format!("Call {}", method_name), locale),
+                code_block(
+                    serde_json::json!({ "args": args, "gas": gas }),
+                    locale.say(&Phrase::CallArgs { gas }),
+                ),
+                amount_row(RowLabel::Deposit, deposit, locale),
+            ]
+        }
+        ActionParams::Transfer { deposit } => vec![
+            heading(action, "Transfer", locale),
+            amount_row(RowLabel::Amount, deposit, locale),
+        ],
+        ActionParams::Stake { stake, public_key } => vec![
+            heading(action, "Stake", locale),
+            amount_row(RowLabel::Stake, stake, locale),
+            code_block(
+                serde_json::json!({ "publicKey": public_key }),
+                locale.say(&Phrase::ValidatorKey { public_key }),
+            ),
+        ],
+        ActionParams::AddKey {
+            public_key,
+            access_key: access_key_json,
+        } => {
+            let access_key = serde_json::from_str::<Value>(access_key_json)
+                .unwrap_or_else(|_| access_key_json.as_str().into());
+            let full_access = !access_key["permission"]["FunctionCall"].is_object();
+            let kind = if full_access {
+                "Add full access key"
+            } else {
+                "Add function call key"
+            };
+            let mut blocks = vec![heading(action, kind, locale)];
+            // The firewall refuses full access keys without a fingerprint (see
+            // action_firewall.rs)
+            if let Some(fingerprint) = public_key_fingerprint(public_key).filter(|_| full_access) {
+                blocks.push(ConfirmationSummaryBlock::KeyRow {
+                    label: "New full access key".to_string(),
+                    public_key: public_key.clone(),
+                    aria_label: locale.say(&Phrase::FullAccessKey {
+                        public_key,
+                        fingerprint: &fingerprint,
+                    }),
+                    fingerprint,
+                });
+            }
+            blocks.push(code_block(
+                serde_json::json!({ "publicKey": public_key, "accessKey": access_key }),
+                locale.say(&Phrase::AccessKey {
+                    public_key,
+                    access_key: access_key_json,
+                }),
+            ));
+            blocks
+        }
+        ActionParams::DeleteKey { public_key } => vec![
+            heading(action, "Delete key", locale),
+            code_block(
+                serde_json::json!({ "publicKey": public_key }),
+                locale.say(&Phrase::PublicKey { public_key }),
+            ),
+        ],
+        ActionParams::DeleteAccount { beneficiary_id } => vec![
+            heading(action, "Delete account", locale),
+            account_row(RowLabel::Beneficiary, beneficiary_id, None, locale),
+        ],
+    }
+}
+
+/// Blocks of each action of a transaction; a subaccount creation's blocks all belong to its
+/// first action
+fn transaction_action_blocks(
+    receiver_id: &str,
+    actions: &[ActionParams],
+    locale: SpeechLocale,
+) -> Vec<Vec<ConfirmationSummaryBlock>> {
+    if let Some(initial_balance) = as_create_subaccount(actions) {
+        let heading = ConfirmationSummaryBlock::Heading {
+            text: format!(
+                "Create account {} with {}",
+                receiver_id,
+                format_yocto_near(initial_balance)
+            ),
+            aria_label: locale.say(&Phrase::CreateSubaccount {
+                account_id: receiver_id,
+                initial_balance,
+            }),
+        };
+        return vec![vec![heading], Vec::new(), Vec::new()];
+    }
+    actions
+        .iter()
+        .map(|action| action_blocks(action, locale))
+        .collect()
+}
+
+/// Warnings for one transaction, in a fixed order
+fn transaction_warnings(
+    receiver_id: &str,
+    actions: &[ActionParams],
+    first_time_receiver: Option<bool>,
+    policy: &SummaryPolicy,
+) -> Vec<ConfirmationSummaryBlock> {
+    let locale = policy.locale;
+    let mut warnings = Vec::new();
+    for action in actions {
+        match action {
+            ActionParams::AddKey {
+                public_key,
+                access_key,
+            } if is_full_access_key(access_key) => {
+                let fingerprint = public_key_fingerprint(public_key);
+                warnings.push(warning(
+                    WarningSeverity::Danger,
+                    SummaryWarningCode::FullAccessKey,
+                    format!(
+                        "Adds full access key {}{} to {}: its holder can sign anything for the \
+                         account",
+                        public_key,
+                        fingerprint
+                            .as_deref()
+                            .map(|f| format!(" (fingerprint {})", f))
+                            .unwrap_or_default(),
+                        receiver_id
+                    ),
+                    Phrase::FullAccessKeyWarning {
+                        public_key,
+                        fingerprint: fingerprint.as_deref(),
+                        receiver_id,
+                    },
+                    locale,
+                ))
+            }
+            ActionParams::DeleteAccount { beneficiary_id } => warnings.push(warning(
+                WarningSeverity::Danger,
+                SummaryWarningCode::DeleteAccount,
+                format!(
+                    "Deletes {} and sends its remaining balance to {}",
+                    receiver_id, beneficiary_id
+                ),
+                Phrase::DeleteAccountWarning {
+                    receiver_id,
+                    beneficiary_id,
+                },
+                locale,
+            )),
+            // Args without a registered schema are not checked, so only these are flagged
+            ActionParams::FunctionCall {
+                method_name, args, ..
+            } if policy
+                .args_schema_methods
+                .iter()
+                .any(|(contract, method)| contract == receiver_id && method == method_name)
+                && serde_json::from_str::<Value>(args).is_err() =>
+            {
+                warnings.push(warning(
+                    WarningSeverity::Caution,
+                    SummaryWarningCode::ArgsNotValidated,
+                    format!(
+                        "The args of {} on {} are not JSON and were not checked against its schema",
+                        method_name, receiver_id
+                    ),
+                    Phrase::ArgsNotValidatedWarning {
+                        method_name,
+                        receiver_id,
+                    },
+                    locale,
+                ))
+            }
+            _ => {}
+        }
+    }
+
+    // Unparseable deposits are refused when the actions are built; they are not counted here
+    let total_deposit = actions
+        .iter()
+        .filter_map(action_deposit)
+        .filter_map(|deposit| deposit.parse::<u128>().ok())
+        .fold(0u128, u128::saturating_add);
+    if total_deposit > policy.deposit_escalation_yocto {
+        warnings.push(warning(
+            WarningSeverity::Caution,
+            SummaryWarningCode::DepositAboveThreshold,
+            format!(
+                "Deposits {} to {}, above the {} review threshold",
+                format_yocto_near(total_deposit),
+                receiver_id,
+                format_yocto_near(policy.deposit_escalation_yocto)
+            ),
+            Phrase::DepositWarning {
+                total: total_deposit,
+                receiver_id,
+                threshold: policy.deposit_escalation_yocto,
+            },
+            locale,
+        ));
+    }
+
+    if first_time_receiver == Some(true) {
+        warnings.push(warning(
+            WarningSeverity::Caution,
+            SummaryWarningCode::FirstTimeReceiver,
+            format!(
+                "This account has not sent a transaction to {} before",
+                receiver_id
+            ),
+            Phrase::FirstTimeReceiverWarning { receiver_id },
+            locale,
+        ));
+    }
+    warnings
+}
+
+/// Blocks about the batch as a whole, shown ahead of the first transaction's blocks
+pub fn batch_blocks(policy: &SummaryPolicy) -> Vec<ConfirmationSummaryBlock> {
+    let mut blocks = Vec::new();
+    if let Some(signing_key) = &policy.signing_key {
+        let public_key = format!("ed25519:{}", bs58::encode(signing_key).into_string());
+        let fingerprint = key_fingerprint(signing_key);
+        let aria_label = policy.locale.say(&Phrase::SigningKey {
+            public_key: &public_key,
+            fingerprint: &fingerprint,
+        });
+        blocks.push(ConfirmationSummaryBlock::KeyRow {
+            label: "Signing key".to_string(),
+            public_key,
+            fingerprint,
+            aria_label,
+        });
+    }
+    match policy.allowance {
+        Some(allowance) if allowance.is_low() => blocks.push(warning(
+            WarningSeverity::Caution,
+            SummaryWarningCode::LowAllowance,
+            format!(
+                "The signing key will have about {} of allowance left, below {}: top it up or \
+                 sign with another key soon",
+                format_yocto_near(allowance.allowance_after().unwrap_or(0)),
+                format_yocto_near(allowance.warn_below)
+            ),
+            Phrase::LowAllowanceWarning {
+                left: allowance.allowance_after().unwrap_or(0),
+                warn_below: allowance.warn_below,
+            },
+            policy.locale,
+        )),
+        _ => {}
+    }
+    if policy.conditional {
+        blocks.push(warning(
+            WarningSeverity::Caution,
+            SummaryWarningCode::ConditionalBatch,
+            "Later steps may be skipped: each transaction is only signed if every transaction \
+             before it simulates successfully"
+                .to_string(),
+            Phrase::ConditionalBatchWarning,
+            policy.locale,
+        ));
+    }
+    let inserted = policy.auto_inserted_actions.iter().map(Vec::len).sum::<usize>();
+    if inserted > 0 {
+        blocks.push(warning(
+            WarningSeverity::Caution,
+            SummaryWarningCode::StorageDepositsAdded,
+            format!(
+                "{} added automatically to register receivers with their token contracts; the \
+                 deposits are included in the totals",
+                match inserted {
+                    1 => "1 storage_deposit was".to_string(),
+                    n => format!("{} storage_deposits were", n),
+                }
+            ),
+            Phrase::StorageDepositsAddedWarning { actions: inserted },
+            policy.locale,
+        ));
+    }
+    if policy.unknown_actions > 0 {
+        blocks.push(warning(
+            WarningSeverity::Danger,
+            SummaryWarningCode::UnknownActions,
+            format!(
+                "{} not be decoded and what {} is not shown",
+                match policy.unknown_actions {
+                    1 => "1 action could".to_string(),
+                    n => format!("{} actions could", n),
+                },
+                if policy.unknown_actions == 1 {
+                    "it does"
+                } else {
+                    "they do"
+                }
+            ),
+            Phrase::UnknownActionsWarning {
+                actions: policy.unknown_actions,
+            },
+            policy.locale,
+        ));
+    }
+    blocks
+}
+
+/// A transaction's blocks ahead of its actions: warnings, receiver and deadline
+fn transaction_header_blocks(
+    receiver_id: &str,
+    actions: &[ActionParams],
+    first_time_receiver: Option<bool>,
+    policy: &SummaryPolicy,
+) -> Vec<ConfirmationSummaryBlock> {
+    let mut blocks = transaction_warnings(receiver_id, actions, first_time_receiver, policy);
+    blocks.push(account_row(
+        RowLabel::Receiver,
+        receiver_id,
+        first_time_receiver,
+        policy.locale,
+    ));
+    if let Some(block_height) = policy.valid_until_block_height {
+        blocks.push(ConfirmationSummaryBlock::DeadlineRow {
+            label: "Valid until block".to_string(),
+            block_height,
+            aria_label: policy.locale.say(&Phrase::Deadline { block_height }),
+        });
+    }
+    blocks
+}
+
+/// Summary blocks of one transaction: its warnings, receiver, deadline and actions
+pub fn transaction_summary_blocks(
+    receiver_id: &str,
+    actions: &[ActionParams],
+    first_time_receiver: Option<bool>,
+    policy: &SummaryPolicy,
+) -> Vec<ConfirmationSummaryBlock> {
+    let mut blocks = transaction_header_blocks(receiver_id, actions, first_time_receiver, policy);
+    blocks.extend(
+        transaction_action_blocks(receiver_id, actions, policy.locale)
+            .into_iter()
+            .flatten(),
+    );
+    blocks
+}
+
+/// One block standing for the `hidden` actions (indexes into `actions`) of a transaction
+fn rollup_block(
+    receiver_id: &str,
+    actions: &[ActionParams],
+    hidden: &[usize],
+    locale: SpeechLocale,
+) -> ConfirmationSummaryBlock {
+    let amounts: Vec<RolledUpAmount> = hidden
+        .iter()
+        .flat_map(|&action_index| {
+            action_blocks(&actions[action_index], locale)
+                .into_iter()
+                .filter_map(move |block| match block {
+                    ConfirmationSummaryBlock::AmountRow {
+                        label,
+                        yocto,
+                        formatted,
+                        ..
+                    } => Some(RolledUpAmount {
+                        action_index,
+                        label,
+                        yocto,
+                        formatted,
+                    }),
+                    _ => None,
+                })
+        })
+        .collect();
+    let total = amounts
+        .iter()
+        .filter_map(|amount| amount.yocto.parse::<u128>().ok())
+        .fold(0u128, u128::saturating_add);
+    ConfirmationSummaryBlock::Rollup {
+        text: match hidden.len() {
+            1 => "1 more action".to_string(),
+            n => format!("{} more actions", n),
+        },
+        hidden_actions: hidden.len(),
+        receiver_id: receiver_id.to_string(),
+        aria_label: locale.say(&Phrase::RolledUpActions {
+            actions: hidden.len(),
+            receiver_id,
+            total,
+        }),
+        amounts,
+    }
+}
+
+/// Summary blocks of each transaction of a batch within `policy.render_budget`: the batch
+/// blocks lead the first transaction's, and once the action blocks no longer fit, each
+/// transaction's remaining actions are rolled up. Header blocks always count against the
+/// budget first, so the risky actions' warnings are never what is left out.
+pub fn batch_summary_blocks(
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+    first_time_receivers: &[Option<bool>],
+    policy: &SummaryPolicy,
+) -> Vec<Vec<ConfirmationSummaryBlock>> {
+    let full: Vec<Vec<ConfirmationSummaryBlock>> = receivers_and_actions
+        .iter()
+        .enumerate()
+        .map(|(i, (receiver_id, actions))| {
+            let mut blocks = if i == 0 { batch_blocks(policy) } else { Vec::new() };
+            blocks.extend(transaction_summary_blocks(
+                receiver_id,
+                actions,
+                first_time_receivers.get(i).copied().flatten(),
+                policy,
+            ));
+            blocks
+        })
+        .collect();
+    if full.iter().map(Vec::len).sum::<usize>() <= policy.render_budget.max_summary_blocks {
+        return full;
+    }
+
+    let headers: Vec<Vec<ConfirmationSummaryBlock>> = receivers_and_actions
+        .iter()
+        .enumerate()
+        .map(|(i, (receiver_id, actions))| {
+            let mut blocks = if i == 0 { batch_blocks(policy) } else { Vec::new() };
+            blocks.extend(transaction_header_blocks(
+                receiver_id,
+                actions,
+                first_time_receivers.get(i).copied().flatten(),
+                policy,
+            ));
+            blocks
+        })
+        .collect();
+    let mut remaining = policy
+        .render_budget
+        .max_summary_blocks
+        .saturating_sub(headers.iter().map(Vec::len).sum());
+    let mut rolling_up = false;
+    headers
+        .into_iter()
+        .zip(receivers_and_actions)
+        .map(|(mut blocks, (receiver_id, actions))| {
+            let mut hidden = Vec::new();
+            let action_blocks = transaction_action_blocks(receiver_id, actions, policy.locale);
+            for (action_index, shown) in action_blocks.into_iter().enumerate() {
+                // Later actions are hidden too, so what is shown stays a prefix of the batch
+                rolling_up = rolling_up || shown.len() > remaining;
+                if rolling_up {
+                    hidden.push(action_index);
+                } else {
+                    remaining -= shown.len();
+                    blocks.extend(shown);
+                }
+            }
+            if !hidden.is_empty() {
+                blocks.push(rollup_block(receiver_id, actions, &hidden, policy.locale));
+            }
+            blocks
+        })
+        .collect()
+}
+
+/// The `summarySpeech` of a batch: one sentence over its transactions, counting the warnings
+/// among the batch's blocks
+pub fn summary_speech(
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+    first_time_receivers: &[Option<bool>],
+    blocks: &[ConfirmationSummaryBlock],
+    locale: SpeechLocale,
+) -> String {
+    let transactions: Vec<SpokenTransaction> = receivers_and_actions
+        .iter()
+        .enumerate()
+        .map(|(i, (receiver_id, actions))| SpokenTransaction {
+            receiver_id,
+            actions,
+            first_time: first_time_receivers.get(i).copied().flatten(),
+        })
+        .collect();
+    let warnings = blocks
+        .iter()
+        .filter(|block| matches!(block, ConfirmationSummaryBlock::Warning { .. }))
+        .count();
+    locale.summary(&transactions, warnings)
+}
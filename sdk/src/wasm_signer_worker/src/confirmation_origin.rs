@@ -0,0 +1,84 @@
+// === CONFIRMATION ORIGIN ATTESTATION ===
+// The intent digest proves the confirmation UI was given what the worker signs, not where it
+// was shown: a page running the SDK can draw a look-alike overlay and answer the prompt itself.
+// With WorkerPolicy.trustedConfirmationOrigin (the wallet iframe's origin, fixed at Initialize
+// by `configure_trusted_confirmation_origin`), every decision must prove it came from there:
+//   1. Each confirmation prompt carries a random `renderNonce`, issued with the request's
+//      confirmation nonce.
+//   2. The decision echoes it, and the confirmation origin bridge on the main thread
+//      (confirmationOriginBridge.ts) adds the origin as the browser reports it: the
+//      MessageEvent.origin of a decision the wallet iframe posted, or the location of the
+//      document that rendered the prompt. It attests `renderAttestation { renderNonce, origin }`
+//      for the trusted origin only, and posts it over a MessagePort of the worker's own.
+//   3. The worker's confirmation bridge (awaitSecureConfirmationV2) forwards the attestation
+//      verbatim when the decision came over that port, and drops it otherwise.
+// The worker refuses a decision without an attestation, with another prompt's nonce or naming
+// another origin with UntrustedConfirmationOrigin. Without a trusted origin, attestations are
+// neither required nor checked.
+
+use serde::Deserialize;
+
+use crate::encoders::base64_url_encode;
+use crate::error::{ConfigError, UntrustedConfirmationOriginError};
+use crate::rpc_endpoints::rpc_origin;
+use crate::state;
+
+/// Proof that the decision came from the main thread that rendered the prompt
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderAttestation {
+    /// The prompt's renderNonce, echoed
+    pub render_nonce: String,
+    /// Origin of the document that rendered the prompt
+    pub origin: String,
+}
+
+/// Random nonce for a confirmation prompt (16 bytes, base64url)
+pub fn new_render_nonce() -> String {
+    let mut nonce = [0u8; 16];
+    // Confirmation request IDs tolerate a failed draw the same way (generate_request_id)
+    let _ = getrandom::getrandom(&mut nonce);
+    base64_url_encode(&nonce)
+}
+
+/// Sets the origin decisions must attest, normalized as `scheme://host[:port]`. Once set it
+/// cannot be changed (a later request's sidecar may only repeat it).
+pub fn configure(origin: &str) -> Result<String, ConfigError> {
+    let normalized = rpc_origin(origin).ok_or_else(|| ConfigError::InvalidValue {
+        field: "trustedConfirmationOrigin",
+        reason: format!("\"{}\" is not an http(s) origin", origin),
+    })?;
+    match state::trusted_confirmation_origin() {
+        Some(current) if current != normalized => Err(ConfigError::InvalidCombination {
+            field: "trustedConfirmationOrigin",
+            reason: format!("already set to {}", current),
+        }),
+        _ => {
+            state::set_trusted_confirmation_origin(Some(normalized.clone()));
+            Ok(normalized)
+        }
+    }
+}
+
+/// Checks a decision's attestation against the prompt's render nonce and the trusted origin;
+/// anything passes when no origin is trusted
+pub fn verify_render_attestation(
+    attestation: Option<&RenderAttestation>,
+    render_nonce: &str,
+    trusted_origin: Option<&str>,
+) -> Result<(), UntrustedConfirmationOriginError> {
+    let Some(trusted) = trusted_origin else {
+        return Ok(());
+    };
+    let attestation = attestation.ok_or(UntrustedConfirmationOriginError::MissingAttestation)?;
+    if attestation.render_nonce != render_nonce {
+        return Err(UntrustedConfirmationOriginError::RenderNonceMismatch);
+    }
+    if rpc_origin(&attestation.origin).as_deref() != Some(trusted) {
+        return Err(UntrustedConfirmationOriginError::OriginMismatch {
+            attested: attestation.origin.clone(),
+            trusted: trusted.to_string(),
+        });
+    }
+    Ok(())
+}
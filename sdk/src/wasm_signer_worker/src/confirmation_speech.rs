@@ -0,0 +1,1085 @@
+// === CONFIRMATION SPEECH ===
+// Screen readers announce a confirmation from the `ariaLabel` of each summary block and the
+// `summarySpeech` of the request, so these say in full what the blocks show: amounts in words
+// ("twelve point five NEAR"), account IDs with their punctuation spoken ("alice dot near"),
+// keys by their last characters. They are generated here from the values the visual blocks
+// are built from (see confirmation_blocks.rs), in the locale of ConfirmationConfig.locale, and
+// are digested with the blocks.
+//
+// Supported locales: "en" (default) and "es"; region subtags select their language ("es-MX").
+
+use crate::actions::ActionParams;
+use crate::config::YOCTO_PER_NEAR;
+use crate::confirmation_blocks::{is_full_access_key, RowLabel, WarningSeverity};
+use crate::types::handlers::ConfirmationConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpeechLocale {
+    #[default]
+    En,
+    Es,
+}
+
+/// What a block says, in terms of the values the block shows
+pub enum Phrase<'a> {
+    Heading(&'a ActionParams),
+    Amount {
+        label: RowLabel,
+        /// None when the amount does not parse
+        yocto: Option<u128>,
+    },
+    Account {
+        label: RowLabel,
+        account_id: &'a str,
+        first_time: Option<bool>,
+    },
+    Deadline {
+        block_height: u64,
+    },
+    SigningKey {
+        public_key: &'a str,
+        fingerprint: &'a str,
+    },
+    FullAccessKey {
+        public_key: &'a str,
+        fingerprint: &'a str,
+    },
+    ContractCode {
+        bytes: usize,
+    },
+    CallArgs {
+        gas: &'a str,
+    },
+    ValidatorKey {
+        public_key: &'a str,
+    },
+    AccessKey {
+        public_key: &'a str,
+        /// The AddKey action's access key JSON
+        access_key: &'a str,
+    },
+    PublicKey {
+        public_key: &'a str,
+    },
+    FullAccessKeyWarning {
+        public_key: &'a str,
+        /// None when the key does not parse
+        fingerprint: Option<&'a str>,
+        receiver_id: &'a str,
+    },
+    DeleteAccountWarning {
+        receiver_id: &'a str,
+        beneficiary_id: &'a str,
+    },
+    DepositWarning {
+        total: u128,
+        receiver_id: &'a str,
+        threshold: u128,
+    },
+    FirstTimeReceiverWarning {
+        receiver_id: &'a str,
+    },
+    LowAllowanceWarning {
+        left: u128,
+        warn_below: u128,
+    },
+    ArgsNotValidatedWarning {
+        method_name: &'a str,
+        receiver_id: &'a str,
+    },
+    ConditionalBatchWarning,
+    /// Storage deposits inserted by autoStorageDeposit (see storage_deposit.rs)
+    StorageDepositsAddedWarning {
+        actions: usize,
+    },
+    /// Actions of a decoded transaction that could not be decoded (see signed_tx_decoder.rs)
+    UnknownActionsWarning {
+        actions: usize,
+    },
+    /// A rollup block (see render_budget.rs)
+    RolledUpActions {
+        actions: usize,
+        receiver_id: &'a str,
+        /// Total of the hidden actions' amounts
+        total: u128,
+    },
+    /// A subaccount creation's heading (see subaccount.rs)
+    CreateSubaccount {
+        account_id: &'a str,
+        initial_balance: u128,
+    },
+    #[cfg(feature = "secp256k1")]
+    EvmHeading,
+    #[cfg(feature = "secp256k1")]
+    EvmMessage {
+        text: &'a str,
+    },
+}
+
+/// One transaction of a request, as the summary speech describes it
+pub struct SpokenTransaction<'a> {
+    pub receiver_id: &'a str,
+    pub actions: &'a [ActionParams],
+    pub first_time: Option<bool>,
+}
+
+impl SpeechLocale {
+    pub const SUPPORTED: [&'static str; 2] = ["en", "es"];
+
+    /// Locale of a BCP 47 tag, by its language subtag
+    pub fn parse(tag: &str) -> Option<SpeechLocale> {
+        let language = tag.split(['-', '_']).next().unwrap_or_default();
+        match language.to_ascii_lowercase().as_str() {
+            "en" => Some(SpeechLocale::En),
+            "es" => Some(SpeechLocale::Es),
+            _ => None,
+        }
+    }
+
+    /// ConfirmationConfig.locale; absent or unsupported locales speak English
+    pub fn from_config(config: Option<&ConfirmationConfig>) -> SpeechLocale {
+        config
+            .and_then(|config| config.locale.as_deref())
+            .and_then(SpeechLocale::parse)
+            .unwrap_or_default()
+    }
+
+    pub fn say(&self, phrase: &Phrase) -> String {
+        match self {
+            SpeechLocale::En => en::say(phrase),
+            SpeechLocale::Es => es::say(phrase),
+        }
+    }
+
+    /// A warning's label: its severity, then the warning
+    pub fn warning(&self, severity: WarningSeverity, phrase: &Phrase) -> String {
+        let severity = match (self, severity) {
+            (SpeechLocale::En, WarningSeverity::Caution) => "Caution",
+            (SpeechLocale::En, WarningSeverity::Danger) => "Danger",
+            (SpeechLocale::Es, WarningSeverity::Caution) => "Atención",
+            (SpeechLocale::Es, WarningSeverity::Danger) => "Peligro",
+        };
+        format!("{}: {}", severity, self.say(phrase))
+    }
+
+    /// One sentence describing the whole request: each transaction's actions in order, then
+    /// how many warnings the blocks show
+    pub fn summary(&self, transactions: &[SpokenTransaction], warnings: usize) -> String {
+        let sentence = match self {
+            SpeechLocale::En => en::summary(transactions, warnings),
+            SpeechLocale::Es => es::summary(transactions, warnings),
+        };
+        let mut chars = sentence.chars();
+        match chars.next() {
+            Some(first) => format!("{}{}.", first.to_uppercase(), chars.as_str()),
+            None => sentence,
+        }
+    }
+
+    #[cfg(feature = "secp256k1")]
+    /// A sentence for a request without transaction blocks (e.g. an EVM message)
+    pub fn evm_summary(&self, account_id: &str) -> String {
+        match self {
+            SpeechLocale::En => format!("Sign an EVM message as {}.", en::account(account_id)),
+            SpeechLocale::Es => format!("Firmar un mensaje EVM como {}.", es::account(account_id)),
+        }
+    }
+}
+
+/// Characters spoken one by one ("3 f 2 a")
+fn spell(text: &str) -> String {
+    text.chars().map(String::from).collect::<Vec<_>>().join(" ")
+}
+
+/// Whole NEAR and the fraction's digits, from yoctoNEAR
+fn near_parts(yocto: u128) -> (u128, Vec<usize>) {
+    let fraction = format!("{:024}", yocto % YOCTO_PER_NEAR);
+    let digits = fraction
+        .trim_end_matches('0')
+        .bytes()
+        .map(|digit| usize::from(digit - b'0'))
+        .collect();
+    (yocto / YOCTO_PER_NEAR, digits)
+}
+
+/// The hex characters of an implicit account (64 hex, or 0x and 40 hex for EVM accounts)
+fn implicit_account_hex(account_id: &str) -> Option<&str> {
+    let hex = match account_id.strip_prefix("0x") {
+        Some(hex) if hex.len() == 40 => hex,
+        _ if account_id.len() == 64 => account_id,
+        _ => return None,
+    };
+    hex.bytes().all(|b| b.is_ascii_hexdigit()).then_some(hex)
+}
+
+/// An identifier with its separators spoken
+fn identifier(id: &str, dot: &str, dash: &str, underscore: &str) -> String {
+    let mut spoken = String::new();
+    for c in id.chars() {
+        match c {
+            '.' => spoken.extend([" ", dot, " "]),
+            '-' => spoken.extend([" ", dash, " "]),
+            '_' => spoken.extend([" ", underscore, " "]),
+            c => spoken.push(c),
+        }
+    }
+    spoken.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The key type and last characters of a `<type>:<base58>` public key
+fn key_parts(public_key: &str) -> (&str, String) {
+    let (key_type, key) = public_key.split_once(':').unwrap_or(("", public_key));
+    let tail = key.len().saturating_sub(4);
+    let tail = key.get(tail..).unwrap_or(key);
+    (key_type, spell(tail))
+}
+
+/// Colon-separated fingerprint groups, spelled and separated by commas
+fn spell_fingerprint(fingerprint: &str) -> String {
+    fingerprint
+        .split(':')
+        .map(spell)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The receiver a function call access key may call (None for full access keys)
+fn access_key_receiver(access_key: &str) -> Option<String> {
+    if is_full_access_key(access_key) {
+        return None;
+    }
+    let access_key: serde_json::Value = serde_json::from_str(access_key).ok()?;
+    access_key["permission"]["FunctionCall"]["receiver_id"]
+        .as_str()
+        .map(str::to_string)
+}
+
+mod en {
+    use super::*;
+
+    const ONES: [&str; 20] = [
+        "zero",
+        "one",
+        "two",
+        "three",
+        "four",
+        "five",
+        "six",
+        "seven",
+        "eight",
+        "nine",
+        "ten",
+        "eleven",
+        "twelve",
+        "thirteen",
+        "fourteen",
+        "fifteen",
+        "sixteen",
+        "seventeen",
+        "eighteen",
+        "nineteen",
+    ];
+    const TENS: [&str; 10] = [
+        "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+    ];
+    // Short scale, enough for u128
+    const SCALES: [&str; 13] = [
+        "",
+        "thousand",
+        "million",
+        "billion",
+        "trillion",
+        "quadrillion",
+        "quintillion",
+        "sextillion",
+        "septillion",
+        "octillion",
+        "nonillion",
+        "decillion",
+        "undecillion",
+    ];
+
+    fn below_thousand(n: u128) -> String {
+        let (hundreds, rest) = ((n / 100) as usize, (n % 100) as usize);
+        let mut words = Vec::new();
+        if hundreds > 0 {
+            words.push(format!("{} hundred", ONES[hundreds]));
+        }
+        match rest {
+            0 => {}
+            1..=19 => words.push(ONES[rest].to_string()),
+            _ if rest.is_multiple_of(10) => words.push(TENS[rest / 10].to_string()),
+            _ => words.push(format!("{}-{}", TENS[rest / 10], ONES[rest % 10])),
+        }
+        words.join(" ")
+    }
+
+    pub fn number(n: u128) -> String {
+        if n == 0 {
+            return ONES[0].to_string();
+        }
+        let mut groups = Vec::new();
+        let mut rest = n;
+        let mut scale = 0;
+        while rest > 0 {
+            let group = rest % 1000;
+            if group > 0 {
+                let group = below_thousand(group);
+                groups.push(match scale {
+                    0 => group,
+                    _ => format!("{} {}", group, SCALES[scale]),
+                });
+            }
+            rest /= 1000;
+            scale += 1;
+        }
+        groups.reverse();
+        groups.join(" ")
+    }
+
+    fn count(n: u128, singular: &str, plural: &str) -> String {
+        match n {
+            1 => format!("one {}", singular),
+            _ => format!("{} {}", number(n), plural),
+        }
+    }
+
+    pub fn amount(yocto: u128) -> String {
+        let (whole, digits) = near_parts(yocto);
+        if digits.is_empty() {
+            return format!("{} NEAR", number(whole));
+        }
+        let digits: Vec<&str> = digits.iter().map(|digit| ONES[*digit]).collect();
+        format!("{} point {} NEAR", number(whole), digits.join(" "))
+    }
+
+    pub fn account(account_id: &str) -> String {
+        match implicit_account_hex(account_id) {
+            Some(hex) => format!(
+                "implicit account starting {}, ending {}",
+                spell(&hex[..4]),
+                spell(&hex[hex.len() - 4..])
+            ),
+            None => identifier(account_id, "dot", "dash", "underscore"),
+        }
+    }
+
+    fn public_key(public_key: &str) -> String {
+        match key_parts(public_key) {
+            ("", tail) => format!("key ending in {}", tail),
+            (key_type, tail) => format!("{} key ending in {}", key_type, tail),
+        }
+    }
+
+    fn amount_or_invalid(yocto: Option<u128>) -> String {
+        yocto.map_or_else(|| "an invalid amount".to_string(), amount)
+    }
+
+    fn gas(gas: &str) -> String {
+        match gas.parse::<u128>() {
+            Ok(gas) => format!("{} gas", number(gas)),
+            Err(_) => format!("{} gas", gas),
+        }
+    }
+
+    fn label(label: RowLabel) -> &'static str {
+        label.text()
+    }
+
+    pub fn say(phrase: &Phrase) -> String {
+        match phrase {
+            Phrase::Heading(action) => match action {
+                ActionParams::CreateAccount => "Create account".to_string(),
+                ActionParams::DeployContract { .. } => "Deploy contract".to_string(),
+                ActionParams::FunctionCall { method_name, .. } => {
+                    format!(
+                        "Call method {}",
+                        identifier(method_name, "dot", "dash", "underscore")
+                    )
+                }
+                ActionParams::Transfer { .. } => "Transfer".to_string(),
+                ActionParams::Stake { .. } => "Stake".to_string(),
+                ActionParams::AddKey { access_key, .. } if is_full_access_key(access_key) => {
+                    "Add full access key".to_string()
+                }
+                ActionParams::AddKey { .. } => "Add function call key".to_string(),
+                ActionParams::DeleteKey { .. } => "Delete key".to_string(),
+                ActionParams::DeleteAccount { .. } => "Delete account".to_string(),
+            },
+            Phrase::Amount { label: row, yocto } => match yocto {
+                Some(yocto) => format!("{}: {}", label(*row), amount(*yocto)),
+                None => format!("{}: invalid amount", label(*row)),
+            },
+            Phrase::Account {
+                label: row,
+                account_id,
+                first_time,
+            } => match first_time {
+                Some(true) => format!(
+                    "{}: {}, first time recipient",
+                    label(*row),
+                    account(account_id)
+                ),
+                _ => format!("{}: {}", label(*row), account(account_id)),
+            },
+            Phrase::Deadline { block_height } => {
+                format!("Valid until block {}", number(u128::from(*block_height)))
+            }
+            Phrase::SigningKey {
+                public_key: key,
+                fingerprint,
+            } => format!(
+                "Signing {}, fingerprint {}",
+                public_key(key),
+                spell_fingerprint(fingerprint)
+            ),
+            Phrase::FullAccessKey {
+                public_key: key,
+                fingerprint,
+            } => format!(
+                "New full access {}, fingerprint {}",
+                public_key(key),
+                spell_fingerprint(fingerprint)
+            ),
+            Phrase::ContractCode { bytes } => {
+                format!("Contract code, {}", count(*bytes as u128, "byte", "bytes"))
+            }
+            Phrase::CallArgs { gas: call_gas } => {
+                format!("Call arguments shown as code, {}", gas(call_gas))
+            }
+            Phrase::ValidatorKey { public_key: key } => format!("Validator {}", public_key(key)),
+            Phrase::AccessKey {
+                public_key: key,
+                access_key,
+            } => match access_key_receiver(access_key) {
+                Some(receiver_id) => {
+                    format!("{}, may call {}", public_key(key), account(&receiver_id))
+                }
+                None if is_full_access_key(access_key) => {
+                    format!("{}, full access", public_key(key))
+                }
+                None => format!("{}, function call access", public_key(key)),
+            },
+            Phrase::PublicKey { public_key: key } => public_key(key),
+            Phrase::FullAccessKeyWarning {
+                public_key: key,
+                fingerprint,
+                receiver_id,
+            } => format!(
+                "adds full access {}{} to {}; its holder can sign anything for the account",
+                public_key(key),
+                fingerprint
+                    .map(|f| format!(", fingerprint {},", spell_fingerprint(f)))
+                    .unwrap_or_default(),
+                account(receiver_id)
+            ),
+            Phrase::DeleteAccountWarning {
+                receiver_id,
+                beneficiary_id,
+            } => format!(
+                "deletes {} and sends its remaining balance to {}",
+                account(receiver_id),
+                account(beneficiary_id)
+            ),
+            Phrase::DepositWarning {
+                total,
+                receiver_id,
+                threshold,
+            } => format!(
+                "deposits {} to {}, above the {} review threshold",
+                amount(*total),
+                account(receiver_id),
+                amount(*threshold)
+            ),
+            Phrase::FirstTimeReceiverWarning { receiver_id } => format!(
+                "this account has not sent a transaction to {} before",
+                account(receiver_id)
+            ),
+            Phrase::LowAllowanceWarning { left, warn_below } => format!(
+                "the signing key will have about {} of allowance left, below {}; top it up or \
+                 sign with another key soon",
+                amount(*left),
+                amount(*warn_below)
+            ),
+            Phrase::ArgsNotValidatedWarning {
+                method_name,
+                receiver_id,
+            } => format!(
+                "the arguments of {} on {} are not JSON and were not checked against its schema",
+                identifier(method_name, "dot", "dash", "underscore"),
+                account(receiver_id)
+            ),
+            Phrase::ConditionalBatchWarning => "later steps may be skipped; each transaction is \
+                only signed if every transaction before it simulates successfully"
+                .to_string(),
+            Phrase::StorageDepositsAddedWarning { actions } => format!(
+                "{} added automatically to register receivers with their token contracts",
+                match actions {
+                    1 => "a storage deposit was".to_string(),
+                    n => format!("{} storage deposits were", number(*n as u128)),
+                }
+            ),
+            Phrase::UnknownActionsWarning { actions } => format!(
+                "{} not be decoded, and what {} is not shown",
+                match actions {
+                    1 => "an action could".to_string(),
+                    n => format!("{} actions could", number(*n as u128)),
+                },
+                if *actions == 1 { "it does" } else { "they do" }
+            ),
+            Phrase::RolledUpActions {
+                actions,
+                receiver_id,
+                total,
+            } => format!(
+                "{} for {} not shown{}",
+                count(*actions as u128, "more action", "more actions"),
+                account(receiver_id),
+                if *total > 0 {
+                    format!(", moving {} in total", amount(*total))
+                } else {
+                    String::new()
+                }
+            ),
+            Phrase::CreateSubaccount {
+                account_id,
+                initial_balance,
+            } => format!(
+                "Create account {} with {}",
+                account(account_id),
+                amount(*initial_balance)
+            ),
+            #[cfg(feature = "secp256k1")]
+            Phrase::EvmHeading => "Sign EVM message".to_string(),
+            #[cfg(feature = "secp256k1")]
+            Phrase::EvmMessage { text } => format!("Message: {}", text),
+        }
+    }
+
+    fn action(action: &ActionParams, receiver_id: &str) -> String {
+        let receiver = account(receiver_id);
+        match action {
+            ActionParams::CreateAccount => format!("create account {}", receiver),
+            ActionParams::DeployContract { code } => format!(
+                "deploy {} of contract code to {}",
+                count(code.len() as u128, "byte", "bytes"),
+                receiver
+            ),
+            ActionParams::FunctionCall {
+                method_name,
+                deposit,
+                ..
+            } => {
+                let method = identifier(method_name, "dot", "dash", "underscore");
+                match deposit.parse::<u128>() {
+                    Ok(0) => format!("call {} on {}", method, receiver),
+                    deposit => format!(
+                        "call {} on {} attaching {}",
+                        method,
+                        receiver,
+                        amount_or_invalid(deposit.ok())
+                    ),
+                }
+            }
+            ActionParams::Transfer { deposit } => format!(
+                "send {} to {}",
+                amount_or_invalid(deposit.parse().ok()),
+                receiver
+            ),
+            ActionParams::Stake { stake, .. } => {
+                format!("stake {}", amount_or_invalid(stake.parse().ok()))
+            }
+            ActionParams::AddKey { access_key, .. } if is_full_access_key(access_key) => {
+                format!("add a full access key to {}", receiver)
+            }
+            ActionParams::AddKey { .. } => format!("add a function call key to {}", receiver),
+            ActionParams::DeleteKey { .. } => format!("delete a key of {}", receiver),
+            ActionParams::DeleteAccount { beneficiary_id } => format!(
+                "delete account {} and send its balance to {}",
+                receiver,
+                account(beneficiary_id)
+            ),
+        }
+    }
+
+    pub fn summary(transactions: &[SpokenTransaction], warnings: usize) -> String {
+        let mut sentence = transactions
+            .iter()
+            .map(|tx| {
+                let mut clause = match tx.actions {
+                    [] => format!("send an empty transaction to {}", account(tx.receiver_id)),
+                    actions => actions
+                        .iter()
+                        .map(|a| action(a, tx.receiver_id))
+                        .collect::<Vec<_>>()
+                        .join(" and "),
+                };
+                if tx.first_time == Some(true) {
+                    clause.push_str(", first time recipient");
+                }
+                clause
+            })
+            .collect::<Vec<_>>()
+            .join("; then ");
+        if warnings > 0 {
+            sentence.push_str(&format!(
+                ", with {}",
+                count(warnings as u128, "warning", "warnings")
+            ));
+        }
+        sentence
+    }
+}
+
+mod es {
+    use super::*;
+
+    const ONES: [&str; 30] = [
+        "cero",
+        "uno",
+        "dos",
+        "tres",
+        "cuatro",
+        "cinco",
+        "seis",
+        "siete",
+        "ocho",
+        "nueve",
+        "diez",
+        "once",
+        "doce",
+        "trece",
+        "catorce",
+        "quince",
+        "dieciséis",
+        "diecisiete",
+        "dieciocho",
+        "diecinueve",
+        "veinte",
+        "veintiuno",
+        "veintidós",
+        "veintitrés",
+        "veinticuatro",
+        "veinticinco",
+        "veintiséis",
+        "veintisiete",
+        "veintiocho",
+        "veintinueve",
+    ];
+    const TENS: [&str; 10] = [
+        "",
+        "",
+        "",
+        "treinta",
+        "cuarenta",
+        "cincuenta",
+        "sesenta",
+        "setenta",
+        "ochenta",
+        "noventa",
+    ];
+    const HUNDREDS: [&str; 10] = [
+        "",
+        "ciento",
+        "doscientos",
+        "trescientos",
+        "cuatrocientos",
+        "quinientos",
+        "seiscientos",
+        "setecientos",
+        "ochocientos",
+        "novecientos",
+    ];
+    // Long scale (groups of a million), enough for u128
+    const SCALES: [(&str, &str); 7] = [
+        ("", ""),
+        ("millón", "millones"),
+        ("billón", "billones"),
+        ("trillón", "trillones"),
+        ("cuatrillón", "cuatrillones"),
+        ("quintillón", "quintillones"),
+        ("sextillón", "sextillones"),
+    ];
+
+    /// `apocope`: the number precedes a noun ("un millón", "veintiún mil")
+    fn below_hundred(n: usize, apocope: bool) -> String {
+        match n {
+            1 if apocope => "un".to_string(),
+            21 if apocope => "veintiún".to_string(),
+            0..=29 => ONES[n].to_string(),
+            _ if n.is_multiple_of(10) => TENS[n / 10].to_string(),
+            _ => format!("{} y {}", TENS[n / 10], below_hundred(n % 10, apocope)),
+        }
+    }
+
+    fn below_thousand(n: usize, apocope: bool) -> String {
+        let (hundreds, rest) = (n / 100, n % 100);
+        match (hundreds, rest) {
+            (1, 0) => "cien".to_string(),
+            (0, _) => below_hundred(rest, apocope),
+            (_, 0) => HUNDREDS[hundreds].to_string(),
+            _ => format!("{} {}", HUNDREDS[hundreds], below_hundred(rest, apocope)),
+        }
+    }
+
+    fn below_million(n: usize, apocope: bool) -> String {
+        let (thousands, rest) = (n / 1000, n % 1000);
+        let mut words = Vec::new();
+        match thousands {
+            0 => {}
+            1 => words.push("mil".to_string()),
+            _ => words.push(format!("{} mil", below_thousand(thousands, true))),
+        }
+        if rest > 0 {
+            words.push(below_thousand(rest, apocope));
+        }
+        words.join(" ")
+    }
+
+    fn number_before(n: u128, apocope: bool) -> String {
+        if n == 0 {
+            return ONES[0].to_string();
+        }
+        let mut groups = Vec::new();
+        let mut rest = n;
+        let mut scale = 0;
+        while rest > 0 {
+            let group = (rest % 1_000_000) as usize;
+            if group > 0 {
+                groups.push(match (scale, group) {
+                    (0, _) => below_million(group, apocope),
+                    (_, 1) => format!("un {}", SCALES[scale].0),
+                    _ => format!("{} {}", below_million(group, true), SCALES[scale].1),
+                });
+            }
+            rest /= 1_000_000;
+            scale += 1;
+        }
+        groups.reverse();
+        groups.join(" ")
+    }
+
+    pub fn number(n: u128) -> String {
+        number_before(n, false)
+    }
+
+    fn count(n: u128, singular: &str, plural: &str) -> String {
+        match n {
+            1 => format!("un {}", singular),
+            _ => format!("{} {}", number_before(n, true), plural),
+        }
+    }
+
+    pub fn amount(yocto: u128) -> String {
+        let (whole, digits) = near_parts(yocto);
+        if digits.is_empty() {
+            return format!("{} NEAR", number_before(whole, true));
+        }
+        let digits: Vec<&str> = digits.iter().map(|digit| ONES[*digit]).collect();
+        format!("{} coma {} NEAR", number(whole), digits.join(" "))
+    }
+
+    pub fn account(account_id: &str) -> String {
+        match implicit_account_hex(account_id) {
+            Some(hex) => format!(
+                "cuenta implícita que empieza por {} y termina en {}",
+                spell(&hex[..4]),
+                spell(&hex[hex.len() - 4..])
+            ),
+            None => identifier(account_id, "punto", "guion", "guion bajo"),
+        }
+    }
+
+    fn public_key(public_key: &str) -> String {
+        match key_parts(public_key) {
+            ("", tail) => format!("clave que termina en {}", tail),
+            (key_type, tail) => format!("clave {} que termina en {}", key_type, tail),
+        }
+    }
+
+    fn amount_or_invalid(yocto: Option<u128>) -> String {
+        yocto.map_or_else(|| "un importe no válido".to_string(), amount)
+    }
+
+    fn gas(gas: &str) -> String {
+        match gas.parse::<u128>() {
+            Ok(gas) => format!("{} de gas", number(gas)),
+            Err(_) => format!("{} de gas", gas),
+        }
+    }
+
+    fn label(label: RowLabel) -> &'static str {
+        match label {
+            RowLabel::Amount => "Importe",
+            RowLabel::Deposit => "Depósito",
+            RowLabel::Stake => "Stake",
+            RowLabel::Receiver => "Destinatario",
+            RowLabel::Beneficiary => "Beneficiario",
+            #[cfg(feature = "secp256k1")]
+            RowLabel::Account => "Cuenta",
+        }
+    }
+
+    fn method(method_name: &str) -> String {
+        identifier(method_name, "punto", "guion", "guion bajo")
+    }
+
+    pub fn say(phrase: &Phrase) -> String {
+        match phrase {
+            Phrase::Heading(action) => match action {
+                ActionParams::CreateAccount => "Crear cuenta".to_string(),
+                ActionParams::DeployContract { .. } => "Desplegar contrato".to_string(),
+                ActionParams::FunctionCall { method_name, .. } => {
+                    format!("Llamar al método {}", method(method_name))
+                }
+                ActionParams::Transfer { .. } => "Transferencia".to_string(),
+                ActionParams::Stake { .. } => "Stake".to_string(),
+                ActionParams::AddKey { access_key, .. } if is_full_access_key(access_key) => {
+                    "Añadir clave de acceso total".to_string()
+                }
+                ActionParams::AddKey { .. } => "Añadir clave de llamadas a función".to_string(),
+                ActionParams::DeleteKey { .. } => "Eliminar clave".to_string(),
+                ActionParams::DeleteAccount { .. } => "Eliminar cuenta".to_string(),
+            },
+            Phrase::Amount { label: row, yocto } => match yocto {
+                Some(yocto) => format!("{}: {}", label(*row), amount(*yocto)),
+                None => format!("{}: importe no válido", label(*row)),
+            },
+            Phrase::Account {
+                label: row,
+                account_id,
+                first_time,
+            } => match first_time {
+                Some(true) => format!(
+                    "{}: {}, destinatario nuevo",
+                    label(*row),
+                    account(account_id)
+                ),
+                _ => format!("{}: {}", label(*row), account(account_id)),
+            },
+            Phrase::Deadline { block_height } => format!(
+                "Válido hasta el bloque {}",
+                number(u128::from(*block_height))
+            ),
+            Phrase::SigningKey {
+                public_key: key,
+                fingerprint,
+            } => format!(
+                "Firma con la {}, huella {}",
+                public_key(key),
+                spell_fingerprint(fingerprint)
+            ),
+            Phrase::FullAccessKey {
+                public_key: key,
+                fingerprint,
+            } => format!(
+                "Nueva {} con acceso total, huella {}",
+                public_key(key),
+                spell_fingerprint(fingerprint)
+            ),
+            Phrase::ContractCode { bytes } => format!(
+                "Código del contrato, {}",
+                count(*bytes as u128, "byte", "bytes")
+            ),
+            Phrase::CallArgs { gas: call_gas } => format!(
+                "Argumentos de la llamada mostrados como código, {}",
+                gas(call_gas)
+            ),
+            Phrase::ValidatorKey { public_key: key } => {
+                format!("Validador con la {}", public_key(key))
+            }
+            Phrase::AccessKey {
+                public_key: key,
+                access_key,
+            } => match access_key_receiver(access_key) {
+                Some(receiver_id) => format!(
+                    "{}, puede llamar a {}",
+                    public_key(key),
+                    account(&receiver_id)
+                ),
+                None if is_full_access_key(access_key) => {
+                    format!("{}, acceso total", public_key(key))
+                }
+                None => format!("{}, acceso de llamadas a función", public_key(key)),
+            },
+            Phrase::PublicKey { public_key: key } => public_key(key),
+            Phrase::FullAccessKeyWarning {
+                public_key: key,
+                fingerprint,
+                receiver_id,
+            } => format!(
+                "añade a {} la {}{} con acceso total; quien la tenga puede firmar cualquier \
+                 cosa por la cuenta",
+                account(receiver_id),
+                public_key(key),
+                fingerprint
+                    .map(|f| format!(", huella {},", spell_fingerprint(f)))
+                    .unwrap_or_default()
+            ),
+            Phrase::DeleteAccountWarning {
+                receiver_id,
+                beneficiary_id,
+            } => format!(
+                "elimina {} y envía su saldo restante a {}",
+                account(receiver_id),
+                account(beneficiary_id)
+            ),
+            Phrase::DepositWarning {
+                total,
+                receiver_id,
+                threshold,
+            } => format!(
+                "deposita {} en {}, por encima del umbral de revisión de {}",
+                amount(*total),
+                account(receiver_id),
+                amount(*threshold)
+            ),
+            Phrase::FirstTimeReceiverWarning { receiver_id } => format!(
+                "esta cuenta no ha enviado antes ninguna transacción a {}",
+                account(receiver_id)
+            ),
+            Phrase::LowAllowanceWarning { left, warn_below } => format!(
+                "a la clave de firma le quedarán unos {} de asignación, menos de {}; recárguela \
+                 o firme pronto con otra clave",
+                amount(*left),
+                amount(*warn_below)
+            ),
+            Phrase::ArgsNotValidatedWarning {
+                method_name,
+                receiver_id,
+            } => format!(
+                "los argumentos de {} en {} no son JSON y no se comprobaron con su esquema",
+                method(method_name),
+                account(receiver_id)
+            ),
+            Phrase::ConditionalBatchWarning => "puede que se omitan pasos posteriores; cada \
+                transacción solo se firma si todas las anteriores se simulan correctamente"
+                .to_string(),
+            Phrase::StorageDepositsAddedWarning { actions } => format!(
+                "{} automáticamente para registrar a los destinatarios en sus contratos de tokens",
+                match actions {
+                    1 => "se añadió un depósito de almacenamiento".to_string(),
+                    n => format!(
+                        "se añadieron {} depósitos de almacenamiento",
+                        number_before(*n as u128, true)
+                    ),
+                }
+            ),
+            Phrase::UnknownActionsWarning { actions } => format!(
+                "{}, y no se muestra lo que {}",
+                match actions {
+                    1 => "no se pudo decodificar una acción".to_string(),
+                    n => format!(
+                        "no se pudieron decodificar {} acciones",
+                        number_before(*n as u128, false)
+                    ),
+                },
+                if *actions == 1 { "hace" } else { "hacen" }
+            ),
+            Phrase::RolledUpActions {
+                actions,
+                receiver_id,
+                total,
+            } => format!(
+                "{} para {}, sin mostrar{}",
+                match actions {
+                    1 => "una acción más".to_string(),
+                    n => format!("{} acciones más", number_before(*n as u128, true)),
+                },
+                account(receiver_id),
+                if *total > 0 {
+                    format!(", que mueven {} en total", amount(*total))
+                } else {
+                    String::new()
+                }
+            ),
+            Phrase::CreateSubaccount {
+                account_id,
+                initial_balance,
+            } => format!(
+                "Crear la cuenta {} con {}",
+                account(account_id),
+                amount(*initial_balance)
+            ),
+            #[cfg(feature = "secp256k1")]
+            Phrase::EvmHeading => "Firmar mensaje EVM".to_string(),
+            #[cfg(feature = "secp256k1")]
+            Phrase::EvmMessage { text } => format!("Mensaje: {}", text),
+        }
+    }
+
+    fn action(action: &ActionParams, receiver_id: &str) -> String {
+        let receiver = account(receiver_id);
+        match action {
+            ActionParams::CreateAccount => format!("crear la cuenta {}", receiver),
+            ActionParams::DeployContract { code } => format!(
+                "desplegar {} de código de contrato en {}",
+                count(code.len() as u128, "byte", "bytes"),
+                receiver
+            ),
+            ActionParams::FunctionCall {
+                method_name,
+                deposit,
+                ..
+            } => match deposit.parse::<u128>() {
+                Ok(0) => format!("llamar a {} en {}", method(method_name), receiver),
+                deposit => format!(
+                    "llamar a {} en {} adjuntando {}",
+                    method(method_name),
+                    receiver,
+                    amount_or_invalid(deposit.ok())
+                ),
+            },
+            ActionParams::Transfer { deposit } => format!(
+                "enviar {} a {}",
+                amount_or_invalid(deposit.parse().ok()),
+                receiver
+            ),
+            ActionParams::Stake { stake, .. } => {
+                format!("hacer stake de {}", amount_or_invalid(stake.parse().ok()))
+            }
+            ActionParams::AddKey { access_key, .. } if is_full_access_key(access_key) => {
+                format!("añadir una clave de acceso total a {}", receiver)
+            }
+            ActionParams::AddKey { .. } => {
+                format!("añadir una clave de llamadas a función a {}", receiver)
+            }
+            ActionParams::DeleteKey { .. } => format!("eliminar una clave de {}", receiver),
+            ActionParams::DeleteAccount { beneficiary_id } => format!(
+                "eliminar la cuenta {} y enviar su saldo a {}",
+                receiver,
+                account(beneficiary_id)
+            ),
+        }
+    }
+
+    pub fn summary(transactions: &[SpokenTransaction], warnings: usize) -> String {
+        let mut sentence = transactions
+            .iter()
+            .map(|tx| {
+                let mut clause = match tx.actions {
+                    [] => format!("enviar una transacción vacía a {}", account(tx.receiver_id)),
+                    actions => actions
+                        .iter()
+                        .map(|a| action(a, tx.receiver_id))
+                        .collect::<Vec<_>>()
+                        .join(" y "),
+                };
+                if tx.first_time == Some(true) {
+                    clause.push_str(", destinatario nuevo");
+                }
+                clause
+            })
+            .collect::<Vec<_>>()
+            .join("; después, ");
+        match warnings {
+            0 => {}
+            1 => sentence.push_str(", con una advertencia"),
+            _ => sentence.push_str(&format!(
+                ", con {} advertencias",
+                number_before(warnings as u128, true)
+            )),
+        }
+        sentence
+    }
+}
@@ -0,0 +1,126 @@
+// === CONTRACT REGISTRATION ARGUMENTS ===
+// Typed arguments for the web3-authn contract's registration methods
+// (`verify_and_register_user`, `link_device_register_user`), one struct per deployed
+// interface version. Building the args through these structs instead of ad-hoc JSON keeps
+// field names checked at compile time.
+//
+// V1: original interface (single device, no authenticator options)
+// V2: adds `device_number` and `authenticator_options` (multi-device support)
+
+use serde::Serialize;
+
+use crate::rpc_calls::VrfData;
+use crate::types::handlers::AuthenticatorOptions;
+use crate::types::WebAuthnRegistrationCredential;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractInterfaceVersion {
+    V1 = 1,
+    V2 = 2,
+}
+
+impl ContractInterfaceVersion {
+    pub const LATEST: ContractInterfaceVersion = ContractInterfaceVersion::V2;
+
+    pub fn from_u32(version: u32) -> Result<Self, String> {
+        match version {
+            1 => Ok(ContractInterfaceVersion::V1),
+            2 => Ok(ContractInterfaceVersion::V2),
+            other => Err(format!("Unsupported contract interface version: {}", other)),
+        }
+    }
+
+    /// Explicit version if given, otherwise the latest
+    pub fn resolve(version: Option<u32>) -> Result<Self, String> {
+        version.map_or(Ok(Self::LATEST), Self::from_u32)
+    }
+}
+
+/// `verify_and_register_user` arguments, interface V1
+#[derive(Serialize, Debug, Clone)]
+pub struct ContractRegistrationArgsV1 {
+    pub vrf_data: VrfData,
+    pub webauthn_registration: WebAuthnRegistrationCredential,
+    pub deterministic_vrf_public_key: Option<Vec<u8>>,
+}
+
+/// `verify_and_register_user` arguments, interface V2
+#[derive(Serialize, Debug, Clone)]
+pub struct ContractRegistrationArgsV2 {
+    pub vrf_data: VrfData,
+    pub webauthn_registration: WebAuthnRegistrationCredential,
+    pub deterministic_vrf_public_key: Option<Vec<u8>>,
+    /// Defaults to 1 on the contract side
+    pub device_number: Option<u8>,
+    pub authenticator_options: Option<AuthenticatorOptions>,
+}
+
+/// `link_device_register_user` arguments, interface V1
+#[derive(Serialize, Debug, Clone)]
+pub struct ContractLinkDeviceArgsV1 {
+    pub vrf_data: VrfData,
+    pub webauthn_registration: WebAuthnRegistrationCredential,
+    pub deterministic_vrf_public_key: Vec<u8>,
+}
+
+/// `link_device_register_user` arguments, interface V2
+#[derive(Serialize, Debug, Clone)]
+pub struct ContractLinkDeviceArgsV2 {
+    pub vrf_data: VrfData,
+    pub webauthn_registration: WebAuthnRegistrationCredential,
+    pub deterministic_vrf_public_key: Vec<u8>,
+    pub authenticator_options: Option<AuthenticatorOptions>,
+}
+
+fn to_args_json<T: Serialize>(args: &T) -> Result<String, String> {
+    serde_json::to_string(args).map_err(|e| format!("Failed to serialize contract args: {}", e))
+}
+
+/// Serialized `verify_and_register_user` args for `version`.
+/// Fields the version doesn't know about are dropped.
+pub fn registration_args_json(
+    version: ContractInterfaceVersion,
+    vrf_data: VrfData,
+    webauthn_registration: WebAuthnRegistrationCredential,
+    deterministic_vrf_public_key: Option<Vec<u8>>,
+    device_number: Option<u8>,
+    authenticator_options: Option<AuthenticatorOptions>,
+) -> Result<String, String> {
+    match version {
+        ContractInterfaceVersion::V1 => to_args_json(&ContractRegistrationArgsV1 {
+            vrf_data,
+            webauthn_registration,
+            deterministic_vrf_public_key,
+        }),
+        ContractInterfaceVersion::V2 => to_args_json(&ContractRegistrationArgsV2 {
+            vrf_data,
+            webauthn_registration,
+            deterministic_vrf_public_key,
+            device_number,
+            authenticator_options: authenticator_options.map(AuthenticatorOptions::for_contract),
+        }),
+    }
+}
+
+/// Serialized `link_device_register_user` args for `version`
+pub fn link_device_args_json(
+    version: ContractInterfaceVersion,
+    vrf_data: VrfData,
+    webauthn_registration: WebAuthnRegistrationCredential,
+    deterministic_vrf_public_key: Vec<u8>,
+    authenticator_options: Option<AuthenticatorOptions>,
+) -> Result<String, String> {
+    match version {
+        ContractInterfaceVersion::V1 => to_args_json(&ContractLinkDeviceArgsV1 {
+            vrf_data,
+            webauthn_registration,
+            deterministic_vrf_public_key,
+        }),
+        ContractInterfaceVersion::V2 => to_args_json(&ContractLinkDeviceArgsV2 {
+            vrf_data,
+            webauthn_registration,
+            deterministic_vrf_public_key,
+            authenticator_options: authenticator_options.map(AuthenticatorOptions::for_contract),
+        }),
+    }
+}
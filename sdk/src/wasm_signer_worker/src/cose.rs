@@ -1,68 +1,270 @@
 use ciborium::Value as CborValue;
 use log::debug;
+use serde::Serialize;
 
-use crate::encoders::base64_url_decode;
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::{WebAuthnDataError, WebAuthnDataSection};
+use crate::known_authenticators;
 
-/// Parse WebAuthn attestation object to extract authData
-pub fn parse_attestation_object(attestation_object_bytes: &[u8]) -> Result<Vec<u8>, String> {
-    let cbor_value: CborValue = ciborium::from_reader(attestation_object_bytes)
-        .map_err(|e| format!("Failed to parse CBOR: {}", e))?;
-
-    if let CborValue::Map(map) = cbor_value {
-        // Extract authData (required)
-        for (key, value) in map.iter() {
-            if let CborValue::Text(key_str) = key {
-                if key_str == "authData" {
-                    if let CborValue::Bytes(auth_data_bytes) = value {
-                        return Ok(auth_data_bytes.clone());
-                    }
-                }
-            }
-        }
-        Err("authData not found in attestation object".to_string())
-    } else {
-        Err("Attestation object is not a CBOR map".to_string())
-    }
+// === ATTESTATION AND AUTHENTICATOR DATA PARSING ===
+// One parser for attestation objects, authenticator data and COSE keys. Registration uses it
+// to extract the credential public key, and the parse_attestation_object /
+// parse_authenticator_data / cose_key_to_jwk wasm exports describe the same structures for
+// the TS layer, so the two can never disagree about what a credential contains.
+
+const RP_ID_HASH_LEN: usize = 32;
+const AUTH_DATA_MIN_LEN: usize = 37; // rpIdHash(32) + flags(1) + signCount(4)
+const AAGUID_LEN: usize = 16;
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_BACKUP_ELIGIBLE: u8 = 0x08;
+const FLAG_BACKUP_STATE: u8 = 0x10;
+const FLAG_ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+const FLAG_EXTENSION_DATA: u8 = 0x80;
+
+/// Decoded attestation object
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttestationObject {
+    pub fmt: Option<String>,
+    pub att_stmt: Vec<(CborValue, CborValue)>,
+    pub auth_data: Vec<u8>,
 }
 
-/// Parse authenticator data to extract COSE public key
-pub fn parse_authenticator_data(auth_data_bytes: &[u8]) -> Result<Vec<u8>, String> {
-    if auth_data_bytes.len() < 37 {
-        return Err("Authenticator data too short".to_string());
-    }
+/// Decoded authenticator data
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthenticatorData {
+    pub rp_id_hash: [u8; RP_ID_HASH_LEN],
+    pub flags: u8,
+    pub sign_count: u32,
+    pub attested_credential: Option<AttestedCredentialData>,
+    /// CBOR map of extension outputs (ED flag)
+    pub extensions: Option<Vec<u8>>,
+}
 
-    let flags = auth_data_bytes[32];
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttestedCredentialData {
+    pub aaguid: [u8; AAGUID_LEN],
+    pub credential_id: Vec<u8>,
+    /// Exact CBOR bytes of the COSE key
+    pub credential_public_key: Vec<u8>,
+    /// Where the COSE key starts within the authenticator data
+    pub credential_public_key_offset: usize,
+}
 
-    // Check if attested credential data is present (AT flag = bit 6)
-    if (flags & 0x40) == 0 {
-        return Err("No attested credential data present".to_string());
-    }
+/// Reads one CBOR item from the start of `bytes`; returns it with its encoded length
+fn read_cbor_item(
+    bytes: &[u8],
+    section: WebAuthnDataSection,
+    base: usize,
+) -> Result<(CborValue, usize), WebAuthnDataError> {
+    let mut reader = bytes;
+    let value: CborValue = ciborium::from_reader(&mut reader).map_err(|e| {
+        let (offset, detail) = match e {
+            ciborium::de::Error::Syntax(offset) => (offset, "syntax error".to_string()),
+            ciborium::de::Error::Semantic(offset, msg) => (offset.unwrap_or(0), msg),
+            ciborium::de::Error::Io(_) => (bytes.len(), "unexpected end of input".to_string()),
+            ciborium::de::Error::RecursionLimitExceeded => (0, "nested too deeply".to_string()),
+        };
+        WebAuthnDataError::new(
+            section,
+            base + offset,
+            format!("Failed to parse CBOR: {}", detail),
+        )
+    })?;
+    Ok((value, bytes.len() - reader.len()))
+}
 
-    let mut offset = 37; // Skip rpIdHash(32) + flags(1) + counter(4)
+fn map_get<'a>(map: &'a [(CborValue, CborValue)], key: &CborValue) -> Option<&'a CborValue> {
+    map.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+}
 
-    // Skip AAGUID (16 bytes)
-    if auth_data_bytes.len() < offset + 16 {
-        return Err("Authenticator data too short for AAGUID".to_string());
+/// Decodes a WebAuthn attestation object
+pub fn decode_attestation_object(bytes: &[u8]) -> Result<AttestationObject, WebAuthnDataError> {
+    let section = WebAuthnDataSection::AttestationObject;
+    let (value, len) = read_cbor_item(bytes, section, 0)?;
+    if len != bytes.len() {
+        return Err(WebAuthnDataError::new(
+            section,
+            len,
+            "Trailing bytes after attestation object",
+        ));
     }
-    offset += 16;
+    let CborValue::Map(map) = value else {
+        return Err(WebAuthnDataError::new(
+            section,
+            0,
+            "Attestation object is not a CBOR map",
+        ));
+    };
+
+    let auth_data = match map_get(&map, &CborValue::Text("authData".to_string())) {
+        Some(CborValue::Bytes(auth_data)) => auth_data.clone(),
+        Some(_) => {
+            return Err(WebAuthnDataError::new(
+                section,
+                0,
+                "authData in attestation object is not a byte string",
+            ))
+        }
+        None => {
+            return Err(WebAuthnDataError::new(
+                section,
+                0,
+                "authData not found in attestation object",
+            ))
+        }
+    };
+    let fmt = match map_get(&map, &CborValue::Text("fmt".to_string())) {
+        Some(CborValue::Text(fmt)) => Some(fmt.clone()),
+        Some(_) => {
+            return Err(WebAuthnDataError::new(
+                section,
+                0,
+                "fmt in attestation object is not a text string",
+            ))
+        }
+        None => None,
+    };
+    let att_stmt = match map_get(&map, &CborValue::Text("attStmt".to_string())) {
+        Some(CborValue::Map(att_stmt)) => att_stmt.clone(),
+        Some(_) => {
+            return Err(WebAuthnDataError::new(
+                section,
+                0,
+                "attStmt in attestation object is not a CBOR map",
+            ))
+        }
+        None => Vec::new(),
+    };
+
+    Ok(AttestationObject {
+        fmt,
+        att_stmt,
+        auth_data,
+    })
+}
 
-    // Get credential ID length (2 bytes, big-endian)
-    if auth_data_bytes.len() < offset + 2 {
-        return Err("Authenticator data too short for credential ID length".to_string());
+/// Decodes authenticator data, including the attested credential data (AT flag) and the
+/// extension outputs (ED flag). Nothing may follow the last section the flags announce.
+pub fn decode_authenticator_data(bytes: &[u8]) -> Result<AuthenticatorData, WebAuthnDataError> {
+    let section = WebAuthnDataSection::AuthData;
+    if bytes.len() < AUTH_DATA_MIN_LEN {
+        return Err(WebAuthnDataError::new(
+            section,
+            bytes.len(),
+            "Authenticator data too short",
+        ));
     }
-    let cred_id_length =
-        u16::from_be_bytes([auth_data_bytes[offset], auth_data_bytes[offset + 1]]) as usize;
-    offset += 2;
+    let mut rp_id_hash = [0u8; RP_ID_HASH_LEN];
+    rp_id_hash.copy_from_slice(&bytes[..RP_ID_HASH_LEN]);
+    let flags = bytes[RP_ID_HASH_LEN];
+    let sign_count = u32::from_be_bytes([bytes[33], bytes[34], bytes[35], bytes[36]]);
+    let mut offset = AUTH_DATA_MIN_LEN;
+
+    let attested_credential = if flags & FLAG_ATTESTED_CREDENTIAL_DATA != 0 {
+        if bytes.len() < offset + AAGUID_LEN {
+            return Err(WebAuthnDataError::new(
+                section,
+                bytes.len(),
+                "Authenticator data too short for AAGUID",
+            ));
+        }
+        let mut aaguid = [0u8; AAGUID_LEN];
+        aaguid.copy_from_slice(&bytes[offset..offset + AAGUID_LEN]);
+        offset += AAGUID_LEN;
+
+        if bytes.len() < offset + 2 {
+            return Err(WebAuthnDataError::new(
+                section,
+                bytes.len(),
+                "Authenticator data too short for credential ID length",
+            ));
+        }
+        let cred_id_length = u16::from_be_bytes([bytes[offset], bytes[offset + 1]]) as usize;
+        offset += 2;
+
+        if bytes.len() < offset + cred_id_length {
+            return Err(WebAuthnDataError::new(
+                section,
+                bytes.len(),
+                format!(
+                    "Authenticator data too short for credential ID ({} bytes declared at byte {})",
+                    cred_id_length,
+                    offset - 2
+                ),
+            ));
+        }
+        let credential_id = bytes[offset..offset + cred_id_length].to_vec();
+        offset += cred_id_length;
+
+        let key_offset = offset;
+        let (key, key_len) = read_cbor_item(&bytes[key_offset..], section, key_offset)?;
+        if !matches!(key, CborValue::Map(_)) {
+            return Err(WebAuthnDataError::new(
+                section,
+                key_offset,
+                "Credential public key is not a CBOR map",
+            ));
+        }
+        offset += key_len;
+        Some(AttestedCredentialData {
+            aaguid,
+            credential_id,
+            credential_public_key: bytes[key_offset..offset].to_vec(),
+            credential_public_key_offset: key_offset,
+        })
+    } else {
+        None
+    };
+
+    let extensions = if flags & FLAG_EXTENSION_DATA != 0 {
+        let extensions_offset = offset;
+        let (value, len) = read_cbor_item(&bytes[offset..], section, offset)?;
+        if !matches!(value, CborValue::Map(_)) {
+            return Err(WebAuthnDataError::new(
+                section,
+                extensions_offset,
+                "Extension outputs are not a CBOR map",
+            ));
+        }
+        offset += len;
+        Some(bytes[extensions_offset..offset].to_vec())
+    } else {
+        None
+    };
 
-    // Skip credential ID
-    if auth_data_bytes.len() < offset + cred_id_length {
-        return Err("Authenticator data too short for credential ID".to_string());
+    if offset != bytes.len() {
+        return Err(WebAuthnDataError::new(
+            section,
+            offset,
+            "Trailing bytes after authenticator data",
+        ));
     }
-    offset += cred_id_length;
 
-    // The rest is the credential public key (COSE format)
-    let credential_public_key = auth_data_bytes[offset..].to_vec();
-    Ok(credential_public_key)
+    Ok(AuthenticatorData {
+        rp_id_hash,
+        flags,
+        sign_count,
+        attested_credential,
+        extensions,
+    })
+}
+
+/// Parse WebAuthn attestation object to extract authData
+pub fn parse_attestation_object(attestation_object_bytes: &[u8]) -> Result<Vec<u8>, String> {
+    decode_attestation_object(attestation_object_bytes)
+        .map(|attestation| attestation.auth_data)
+        .map_err(|e| e.to_string())
+}
+
+/// Parse authenticator data to extract COSE public key
+pub fn parse_authenticator_data(auth_data_bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let auth_data = decode_authenticator_data(auth_data_bytes).map_err(|e| e.to_string())?;
+    auth_data
+        .attested_credential
+        .map(|credential| credential.credential_public_key)
+        .ok_or_else(|| "No attested credential data present".to_string())
 }
 
 /// Extract COSE public key from WebAuthn attestation object
@@ -87,3 +289,324 @@ pub fn extract_cose_public_key_from_attestation(
     );
     Ok(cose_public_key_bytes)
 }
+
+// === COSE KEYS ===
+
+/// Public key as a JWK (RFC 7517), binary members base64url-encoded
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Jwk {
+    /// "OKP", "EC" or "RSA"
+    pub kty: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alg: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crv: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub e: Option<String>,
+}
+
+/// JOSE name of a COSE algorithm identifier (IANA COSE Algorithms registry)
+pub fn cose_algorithm_name(alg: i64) -> Option<&'static str> {
+    match alg {
+        -7 => Some("ES256"),
+        -8 => Some("EdDSA"),
+        -35 => Some("ES384"),
+        -36 => Some("ES512"),
+        -37 => Some("PS256"),
+        -38 => Some("PS384"),
+        -39 => Some("PS512"),
+        #[cfg(feature = "secp256k1")]
+        -47 => Some("ES256K"),
+        -257 => Some("RS256"),
+        -258 => Some("RS384"),
+        -259 => Some("RS512"),
+        _ => None,
+    }
+}
+
+/// (JWK name, coordinate length) of a COSE EC2 or OKP curve
+fn cose_curve(kty: i64, crv: i64) -> Option<(&'static str, usize)> {
+    match (kty, crv) {
+        (1, 4) => Some(("X25519", 32)),
+        (1, 5) => Some(("X448", 56)),
+        (1, 6) => Some(("Ed25519", 32)),
+        (1, 7) => Some(("Ed448", 57)),
+        (2, 1) => Some(("P-256", 32)),
+        (2, 2) => Some(("P-384", 48)),
+        (2, 3) => Some(("P-521", 66)),
+        #[cfg(feature = "secp256k1")]
+        (2, 8) => Some(("secp256k1", 32)),
+        _ => None,
+    }
+}
+
+/// A decoded COSE key with its algorithm
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoseKey {
+    /// COSE algorithm identifier (label 3), if present
+    pub alg: Option<i64>,
+    pub jwk: Jwk,
+}
+
+/// Decodes a COSE_Key; `base` is where `bytes` start within `section`, for error offsets
+pub fn decode_cose_key(
+    bytes: &[u8],
+    section: WebAuthnDataSection,
+    base: usize,
+) -> Result<CoseKey, WebAuthnDataError> {
+    let (value, len) = read_cbor_item(bytes, section, base)?;
+    if len != bytes.len() {
+        return Err(WebAuthnDataError::new(
+            section,
+            base + len,
+            "Trailing bytes after COSE key",
+        ));
+    }
+    let err = |message: String| WebAuthnDataError::new(section, base, message);
+    let CborValue::Map(map) = value else {
+        return Err(err("COSE key is not a CBOR map".to_string()));
+    };
+
+    let int_param = |label: i64, name: &str| -> Result<Option<i64>, WebAuthnDataError> {
+        match map_get(&map, &CborValue::Integer(label.into())) {
+            None => Ok(None),
+            Some(CborValue::Integer(v)) => i64::try_from(i128::from(*v))
+                .map(Some)
+                .map_err(|_| err(format!("COSE key {} is out of range", name))),
+            Some(_) => Err(err(format!("COSE key {} is not an integer", name))),
+        }
+    };
+    let bytes_param = |label: i64, name: &str| -> Result<Vec<u8>, WebAuthnDataError> {
+        match map_get(&map, &CborValue::Integer(label.into())) {
+            Some(CborValue::Bytes(v)) => Ok(v.clone()),
+            Some(_) => Err(err(format!("COSE key {} is not a byte string", name))),
+            None => Err(err(format!("COSE key is missing {}", name))),
+        }
+    };
+
+    let kty = int_param(1, "kty")?.ok_or_else(|| err("COSE key is missing kty".to_string()))?;
+    let alg = int_param(3, "alg")?;
+    let alg_name = alg.and_then(cose_algorithm_name).map(str::to_string);
+
+    let jwk = match kty {
+        // OKP and EC2
+        1 | 2 => {
+            let crv =
+                int_param(-1, "crv")?.ok_or_else(|| err("COSE key is missing crv".to_string()))?;
+            let (crv_name, coordinate_len) = cose_curve(kty, crv)
+                .ok_or_else(|| err(format!("Unsupported COSE curve {} for kty {}", crv, kty)))?;
+            let x = bytes_param(-2, "x")?;
+            if x.len() != coordinate_len {
+                return Err(err(format!(
+                    "COSE key x is {} bytes, expected {} for {}",
+                    x.len(),
+                    coordinate_len,
+                    crv_name
+                )));
+            }
+            let y = if kty == 2 {
+                let y = bytes_param(-3, "y")?;
+                if y.len() != coordinate_len {
+                    return Err(err(format!(
+                        "COSE key y is {} bytes, expected {} for {}",
+                        y.len(),
+                        coordinate_len,
+                        crv_name
+                    )));
+                }
+                Some(base64_url_encode(&y))
+            } else {
+                None
+            };
+            Jwk {
+                kty: if kty == 1 { "OKP" } else { "EC" }.to_string(),
+                alg: alg_name,
+                crv: Some(crv_name.to_string()),
+                x: Some(base64_url_encode(&x)),
+                y,
+                n: None,
+                e: None,
+            }
+        }
+        // RSA
+        3 => Jwk {
+            kty: "RSA".to_string(),
+            alg: alg_name,
+            crv: None,
+            x: None,
+            y: None,
+            n: Some(base64_url_encode(&bytes_param(-1, "n")?)),
+            e: Some(base64_url_encode(&bytes_param(-2, "e")?)),
+        },
+        _ => return Err(err(format!("Unsupported COSE key type {}", kty))),
+    };
+
+    Ok(CoseKey { alg, jwk })
+}
+
+// === DESCRIPTIONS FOR THE TS LAYER ===
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthDataFlags {
+    pub user_present: bool,
+    pub user_verified: bool,
+    pub backup_eligible: bool,
+    pub backup_state: bool,
+    pub attested_credential_data: bool,
+    pub extension_data: bool,
+    /// The flags byte as received
+    pub raw: u8,
+}
+
+impl From<u8> for AuthDataFlags {
+    fn from(flags: u8) -> Self {
+        Self {
+            user_present: flags & FLAG_USER_PRESENT != 0,
+            user_verified: flags & FLAG_USER_VERIFIED != 0,
+            backup_eligible: flags & FLAG_BACKUP_ELIGIBLE != 0,
+            backup_state: flags & FLAG_BACKUP_STATE != 0,
+            attested_credential_data: flags & FLAG_ATTESTED_CREDENTIAL_DATA != 0,
+            extension_data: flags & FLAG_EXTENSION_DATA != 0,
+            raw: flags,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedAttestedCredential {
+    /// Authenticator model, as a lowercase UUID string (all zeros for "none" attestation)
+    pub aaguid: String,
+    /// The provider the AAGUID names, e.g. "iCloud Keychain" (see known_authenticators.rs)
+    pub provider_name: Option<String>,
+    /// Whether the provider syncs its passkeys across devices
+    pub is_synced_passkey: Option<bool>,
+    pub credential_id_b64u: String,
+    /// COSE key bytes, as registration stores them
+    pub credential_public_key_b64u: String,
+    /// COSE algorithm identifier, e.g. -7
+    pub public_key_algorithm: Option<i64>,
+    /// JOSE name of the algorithm, e.g. "ES256"; absent for unregistered identifiers
+    pub public_key_algorithm_name: Option<String>,
+    pub public_key_jwk: Jwk,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedAuthData {
+    pub rp_id_hash_b64u: String,
+    pub flags: AuthDataFlags,
+    pub sign_count: u32,
+    pub attested_credential: Option<ParsedAttestedCredential>,
+    /// CBOR map of extension outputs
+    pub extensions_b64u: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedAttestation {
+    /// Attestation statement format, e.g. "none" or "packed"
+    pub fmt: Option<String>,
+    /// Text keys of the attestation statement, e.g. ["alg", "sig", "x5c"]
+    pub att_stmt_keys: Vec<String>,
+    pub auth_data: ParsedAuthData,
+}
+
+/// Formats an AAGUID as a UUID string (8-4-4-4-12 hex digits)
+pub fn format_aaguid(aaguid: &[u8; AAGUID_LEN]) -> String {
+    let hex: String = aaguid.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+fn decode_b64u(input: &str, section: WebAuthnDataSection) -> Result<Vec<u8>, WebAuthnDataError> {
+    base64_url_decode(input.trim())
+        .map_err(|e| WebAuthnDataError::new(section, 0, format!("Invalid base64url: {}", e)))
+}
+
+/// Describes decoded authenticator data
+pub fn describe_authenticator_data(
+    auth_data: &AuthenticatorData,
+) -> Result<ParsedAuthData, WebAuthnDataError> {
+    let attested_credential = match &auth_data.attested_credential {
+        Some(credential) => {
+            let key = decode_cose_key(
+                &credential.credential_public_key,
+                WebAuthnDataSection::AuthData,
+                credential.credential_public_key_offset,
+            )?;
+            let authenticator = known_authenticators::lookup(&credential.aaguid);
+            Some(ParsedAttestedCredential {
+                aaguid: authenticator.aaguid,
+                provider_name: authenticator.provider_name,
+                is_synced_passkey: authenticator.is_synced_passkey,
+                credential_id_b64u: base64_url_encode(&credential.credential_id),
+                credential_public_key_b64u: base64_url_encode(&credential.credential_public_key),
+                public_key_algorithm: key.alg,
+                public_key_algorithm_name: key
+                    .alg
+                    .and_then(cose_algorithm_name)
+                    .map(str::to_string),
+                public_key_jwk: key.jwk,
+            })
+        }
+        None => None,
+    };
+    Ok(ParsedAuthData {
+        rp_id_hash_b64u: base64_url_encode(&auth_data.rp_id_hash),
+        flags: auth_data.flags.into(),
+        sign_count: auth_data.sign_count,
+        attested_credential,
+        extensions_b64u: auth_data.extensions.as_deref().map(base64_url_encode),
+    })
+}
+
+/// parse_attestation_object export: describes a base64url attestation object
+pub fn describe_attestation_object_b64u(
+    attestation_object_b64u: &str,
+) -> Result<ParsedAttestation, WebAuthnDataError> {
+    let bytes = decode_b64u(
+        attestation_object_b64u,
+        WebAuthnDataSection::AttestationObject,
+    )?;
+    let attestation = decode_attestation_object(&bytes)?;
+    let auth_data = decode_authenticator_data(&attestation.auth_data)?;
+    Ok(ParsedAttestation {
+        fmt: attestation.fmt,
+        att_stmt_keys: attestation
+            .att_stmt
+            .iter()
+            .filter_map(|(k, _)| k.as_text().map(str::to_string))
+            .collect(),
+        auth_data: describe_authenticator_data(&auth_data)?,
+    })
+}
+
+/// parse_authenticator_data export: describes base64url authenticator data (from an
+/// attestation or an assertion)
+pub fn describe_authenticator_data_b64u(
+    auth_data_b64u: &str,
+) -> Result<ParsedAuthData, WebAuthnDataError> {
+    let bytes = decode_b64u(auth_data_b64u, WebAuthnDataSection::AuthData)?;
+    describe_authenticator_data(&decode_authenticator_data(&bytes)?)
+}
+
+/// cose_key_to_jwk export: converts a base64url COSE key to a JWK
+pub fn cose_key_b64u_to_jwk(cose_key_b64u: &str) -> Result<Jwk, WebAuthnDataError> {
+    let bytes = decode_b64u(cose_key_b64u, WebAuthnDataSection::CoseKey)?;
+    Ok(decode_cose_key(&bytes, WebAuthnDataSection::CoseKey, 0)?.jwk)
+}
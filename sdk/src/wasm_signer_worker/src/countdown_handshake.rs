@@ -0,0 +1,114 @@
+// === AUTO-PROCEED COUNTDOWN HANDSHAKE ===
+// In autoProceed mode nothing proved that the UI displayed the confirmation before proceeding:
+// a page that skipped rendering got the same signature. The worker's confirmation bridge
+// (awaitSecureConfirmationV2) now runs a handshake with the UI:
+//   1. Once the countdown is on screen, the UI acks the prompt with
+//      COUNTDOWN_STARTED { requestId, displayedDigest }.
+//   2. The ack starts the bridge's own autoProceedDelay timer. A decision that arrives before
+//      the timer elapses is held until it does; CANCEL_CONFIRMATION { requestId } before the
+//      decision is released declines the request.
+//   3. Without an ack within COUNTDOWN_ACK_TIMEOUT_MS, the bridge tells the UI to switch to
+//      requireClick (CONFIRMATION_BEHAVIOR_CHANGED), and the request proceeds as requireClick.
+//      The UI sends the same message when it downgrades on its own (e.g. without a user
+//      activation), so a non-compliant page can at most turn autoProceed into requireClick.
+// The bridge reports what it saw, timed on the worker's clock, as `countdown` on the
+// confirmation response; the worker verifies the report before anything is signed.
+
+use serde::Deserialize;
+
+use crate::config::COUNTDOWN_ACK_TIMEOUT_MS;
+use crate::error::SignerErrorCode;
+use crate::types::handlers::{ConfirmationBehavior, ConfirmationConfig, ConfirmationUIMode};
+
+/// Handshake events the confirmation bridge saw, in worker time (ms since the epoch)
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct CountdownReport {
+    /// When the prompt was posted to the UI
+    pub prompted_at_ms: f64,
+    /// When the first CountdownStarted ack arrived (the start of the bridge's timer)
+    pub started_at_ms: Option<f64>,
+    /// Intent digest the UI says it displayed
+    pub displayed_digest: Option<String>,
+    /// When a CancelConfirmation arrived
+    pub cancelled_at_ms: Option<f64>,
+    /// The request fell back to requireClick (no ack in time, or the UI downgraded)
+    #[serde(default)]
+    pub require_click: bool,
+    /// When the bridge released the decision to the worker
+    pub released_at_ms: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountdownOutcome {
+    /// The countdown was displayed for the whole auto-proceed delay
+    Elapsed,
+    /// The request proceeded as requireClick
+    RequireClick,
+}
+
+/// Auto-proceed delay of a normalized config that shows a countdown (modal or drawer with
+/// behavior autoProceed); None for requireClick and skip
+pub fn countdown_delay_ms(config: Option<&ConfirmationConfig>) -> Option<u32> {
+    config
+        .filter(|c| {
+            c.ui_mode != ConfirmationUIMode::Skip && c.behavior == ConfirmationBehavior::AutoProceed
+        })
+        .map(|c| c.auto_proceed_delay.unwrap_or(0))
+}
+
+fn not_displayed(reason: String) -> (SignerErrorCode, String) {
+    (
+        SignerErrorCode::CountdownNotDisplayed,
+        format!("Auto-proceed countdown not verified: {}", reason),
+    )
+}
+
+/// Checks the bridge's report for an auto-proceed confirmation of `intent_digest`
+pub fn verify_countdown_handshake(
+    report: Option<&CountdownReport>,
+    intent_digest: &str,
+    auto_proceed_delay_ms: u32,
+) -> Result<CountdownOutcome, (SignerErrorCode, String)> {
+    let report = report.ok_or_else(|| {
+        not_displayed("the confirmation response carries no countdown report".to_string())
+    })?;
+    if report.cancelled_at_ms.is_some() {
+        return Err((
+            SignerErrorCode::UserDeclined,
+            "Confirmation cancelled during the auto-proceed countdown".to_string(),
+        ));
+    }
+    if report.require_click {
+        return Ok(CountdownOutcome::RequireClick);
+    }
+
+    let started_at_ms = report
+        .started_at_ms
+        .ok_or_else(|| not_displayed("the UI never acked CountdownStarted".to_string()))?;
+    let ack_delay_ms = started_at_ms - report.prompted_at_ms;
+    if ack_delay_ms > COUNTDOWN_ACK_TIMEOUT_MS as f64 {
+        return Err(not_displayed(format!(
+            "CountdownStarted arrived {}ms after the prompt, past the {}ms deadline",
+            ack_delay_ms.round(),
+            COUNTDOWN_ACK_TIMEOUT_MS
+        )));
+    }
+    match report.displayed_digest.as_deref() {
+        Some(displayed) if displayed == intent_digest => {}
+        displayed => {
+            return Err(not_displayed(format!(
+                "the UI displayed digest {} instead of {}",
+                displayed.unwrap_or("(none)"),
+                intent_digest
+            )))
+        }
+    }
+    let remaining_ms = started_at_ms + auto_proceed_delay_ms as f64 - report.released_at_ms;
+    if remaining_ms > 0.0 {
+        return Err(not_displayed(format!(
+            "the decision was released {}ms before the countdown elapsed",
+            remaining_ms.round()
+        )));
+    }
+    Ok(CountdownOutcome::Elapsed)
+}
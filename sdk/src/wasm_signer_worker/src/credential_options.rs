@@ -0,0 +1,391 @@
+// === WEBAUTHN CREDENTIAL OPTIONS ===
+// The options passed to navigator.credentials.create and .get, built in the worker so
+// registration, link-device and signing flows cannot drift apart. They are the WebAuthn JSON
+// forms (PublicKeyCredentialCreationOptionsJSON, PublicKeyCredentialRequestOptionsJSON): binary
+// members (challenge, user.id, credential ids, PRF salts) are base64url strings, which the TS
+// layer decodes without reassembling anything.
+
+use serde::{Deserialize, Serialize};
+
+use crate::cose::{decode_authenticator_data, decode_cose_key, parse_attestation_object};
+use crate::crypto::derive_webauthn_challenge;
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::{AlgorithmNegotiationError, WebAuthnDataSection};
+use crate::types::handlers::{AuthenticatorOptions, UserVerificationPolicy};
+use crate::types::VrfChallenge;
+
+/// COSE algorithm identifiers offered at registration, in preference order:
+/// EdDSA (-8), ES256 (-7), RS256 (-257)
+pub const PUB_KEY_CRED_ALGORITHMS: [i32; 3] = [-8, -7, -257];
+
+/// Relying party name shown by authenticators when the request gives none
+pub const DEFAULT_RP_NAME: &str = "WebAuthn VRF Passkey";
+
+/// How long navigator.credentials.create may take
+pub const CREDENTIAL_CREATION_TIMEOUT_MS: u32 = 60_000;
+
+/// How long navigator.credentials.get may take
+pub const CREDENTIAL_REQUEST_TIMEOUT_MS: u32 = 60_000;
+
+/// PRF salt prefixes: the first output keys ChaCha20Poly1305, the second derives Ed25519 keys
+const CHACHA20_PRF_SALT_PREFIX: &str = "chacha20-salt:";
+const ED25519_PRF_SALT_PREFIX: &str = "ed25519-salt:";
+const PRF_SALT_LEN: usize = 32;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RelyingPartyEntity {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UserEntity {
+    /// base64url of the UTF-8 user name
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PubKeyCredParam {
+    #[serde(rename = "type")]
+    pub credential_type: String,
+    pub alg: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticatorSelection {
+    pub resident_key: String,
+    pub require_resident_key: bool,
+    pub user_verification: UserVerificationPolicy,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CredentialDescriptor {
+    #[serde(rename = "type")]
+    pub credential_type: String,
+    /// base64url credential id
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transports: Vec<String>,
+}
+
+impl CredentialDescriptor {
+    /// A public-key credential descriptor; errors when `id` is not base64url
+    pub fn public_key(id: &str, transports: Vec<String>) -> Result<Self, String> {
+        match base64_url_decode(id) {
+            Ok(bytes) if !bytes.is_empty() => Ok(CredentialDescriptor {
+                credential_type: "public-key".to_string(),
+                id: id.to_string(),
+                transports,
+            }),
+            _ => Err(format!("Credential id is not base64url: {}", id)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PrfValues {
+    /// base64url salt
+    pub first: String,
+    /// base64url salt
+    pub second: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PrfInputs {
+    pub eval: PrfValues,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LargeBlobCreationInputs {
+    pub support: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CreationExtensions {
+    pub prf: PrfInputs,
+    /// Fallback key storage for authenticators without PRF (WorkerPolicy.prfFallbackSchemes)
+    pub large_blob: LargeBlobCreationInputs,
+    /// Reports whether the credential is discoverable, stored with the authenticator
+    pub cred_props: bool,
+}
+
+/// PublicKeyCredentialCreationOptionsJSON
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialCreationOptions {
+    pub rp: RelyingPartyEntity,
+    pub user: UserEntity,
+    /// base64url WebAuthn challenge derived from the VRF output
+    pub challenge: String,
+    pub pub_key_cred_params: Vec<PubKeyCredParam>,
+    pub timeout: u32,
+    pub attestation: String,
+    pub authenticator_selection: AuthenticatorSelection,
+    pub exclude_credentials: Vec<CredentialDescriptor>,
+    pub extensions: CreationExtensions,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LargeBlobRequestInputs {
+    pub read: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertionExtensions {
+    pub prf: PrfInputs,
+    /// Unlocks largeBlob-wrapped keys on authenticators without PRF
+    pub large_blob: LargeBlobRequestInputs,
+}
+
+/// PublicKeyCredentialRequestOptionsJSON
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialRequestOptions {
+    /// base64url WebAuthn challenge derived from the VRF output
+    pub challenge: String,
+    pub rp_id: String,
+    /// Absent for discoverable-credential flows, where the authenticator offers its passkeys
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_credentials: Option<Vec<CredentialDescriptor>>,
+    pub user_verification: UserVerificationPolicy,
+    pub timeout: u32,
+    pub extensions: AssertionExtensions,
+}
+
+/// Who the credential is created for
+#[derive(Debug, Clone, PartialEq)]
+pub struct CredentialUser {
+    pub near_account_id: String,
+    pub device_number: Option<u8>,
+    /// Overrides the device-specific user name
+    pub name: Option<String>,
+    /// Overrides the friendly display name
+    pub display_name: Option<String>,
+}
+
+impl CredentialUser {
+    /// Device 1 registers as the account id itself; later devices as "account (N)", so synced
+    /// passkeys of different devices do not overwrite each other
+    pub fn device_specific_name(&self) -> String {
+        match self.device_number {
+            None | Some(1) => self.near_account_id.clone(),
+            Some(n) => format!("{} ({})", self.near_account_id, n),
+        }
+    }
+
+    /// The account's first label, with the device for devices after the first
+    pub fn friendly_display_name(&self) -> String {
+        let base = self.near_account_id.split('.').next().unwrap_or_default();
+        match self.device_number {
+            None | Some(1) => base.to_string(),
+            Some(n) => format!("{} (device {})", base, n),
+        }
+    }
+}
+
+/// The 32-byte PRF eval salts (first, second) for an account: the prefixed account id,
+/// truncated or zero-padded. The same on every device, so every passkey of the account
+/// derives the same keys.
+pub fn prf_eval_salts(near_account_id: &str) -> ([u8; PRF_SALT_LEN], [u8; PRF_SALT_LEN]) {
+    let salt = |prefix: &str| {
+        let mut salt = [0u8; PRF_SALT_LEN];
+        let bytes = format!("{}{}", prefix, near_account_id).into_bytes();
+        let len = bytes.len().min(PRF_SALT_LEN);
+        salt[..len].copy_from_slice(&bytes[..len]);
+        salt
+    };
+    (
+        salt(CHACHA20_PRF_SALT_PREFIX),
+        salt(ED25519_PRF_SALT_PREFIX),
+    )
+}
+
+pub fn prf_inputs(near_account_id: &str) -> PrfInputs {
+    let (first, second) = prf_eval_salts(near_account_id);
+    PrfInputs {
+        eval: PrfValues {
+            first: base64_url_encode(&first),
+            second: base64_url_encode(&second),
+        },
+    }
+}
+
+/// The WebAuthn challenge bytes of a VRF challenge for `rp_id` (base64url)
+pub fn webauthn_challenge_for(vrf_challenge: &VrfChallenge, rp_id: &str) -> Result<String, String> {
+    if vrf_challenge.rp_id != rp_id {
+        return Err(format!(
+            "VRF challenge is for rpId {}, not {}",
+            vrf_challenge.rp_id, rp_id
+        ));
+    }
+    let vrf_output = base64_url_decode(&vrf_challenge.vrf_output)
+        .map_err(|e| format!("Invalid VRF output: {}", e))?;
+    let challenge = derive_webauthn_challenge(&vrf_output, vrf_challenge.challenge_length)?;
+    Ok(base64_url_encode(&challenge))
+}
+
+/// The algorithms offered at registration, in preference order: `allowedAlgorithms`, or
+/// PUB_KEY_CRED_ALGORITHMS when unset
+pub fn offered_algorithms(authenticator_options: Option<&AuthenticatorOptions>) -> &[i32] {
+    authenticator_options
+        .and_then(|options| options.allowed_algorithms.as_deref())
+        .unwrap_or(&PUB_KEY_CRED_ALGORITHMS)
+}
+
+/// The COSE algorithm the authenticator picked for the credential in `attestation_object_b64u`,
+/// refused when it is not one of the offered algorithms. An attestation without a readable
+/// algorithm is refused only when `allowedAlgorithms` is set (otherwise `None`: the flows that
+/// parse the credential key report malformed attestations themselves).
+pub fn negotiated_algorithm(
+    attestation_object_b64u: &str,
+    authenticator_options: Option<&AuthenticatorOptions>,
+) -> Result<Option<i32>, AlgorithmNegotiationError> {
+    let restricted = authenticator_options.is_some_and(|o| o.allowed_algorithms.is_some());
+    let alg = match credential_algorithm(attestation_object_b64u) {
+        Ok(alg) => alg,
+        Err(reason) if restricted => return Err(AlgorithmNegotiationError::Unreadable { reason }),
+        Err(_) => return Ok(None),
+    };
+    let allowed = offered_algorithms(authenticator_options);
+    match i32::try_from(alg) {
+        Ok(alg) if allowed.contains(&alg) => Ok(Some(alg)),
+        _ => Err(AlgorithmNegotiationError::Disallowed {
+            alg,
+            allowed: allowed.to_vec(),
+        }),
+    }
+}
+
+fn credential_algorithm(attestation_object_b64u: &str) -> Result<i64, String> {
+    let attestation_object = base64_url_decode(attestation_object_b64u)
+        .map_err(|e| format!("Failed to decode attestation object: {}", e))?;
+    let auth_data =
+        decode_authenticator_data(&parse_attestation_object(&attestation_object)?)
+            .map_err(|e| e.to_string())?;
+    let credential = auth_data
+        .attested_credential
+        .ok_or_else(|| "No attested credential data present".to_string())?;
+    decode_cose_key(
+        &credential.credential_public_key,
+        WebAuthnDataSection::AuthData,
+        credential.credential_public_key_offset,
+    )
+    .map_err(|e| e.to_string())?
+    .alg
+    .ok_or_else(|| "COSE key has no alg".to_string())
+}
+
+/// Creation options for `user` on `rp_id`, with the challenge from `vrf_challenge` and the
+/// account's existing credentials excluded (so an authenticator is not registered twice)
+pub fn build_creation_options(
+    rp_id: &str,
+    rp_name: Option<&str>,
+    user: &CredentialUser,
+    vrf_challenge: &VrfChallenge,
+    authenticator_options: &AuthenticatorOptions,
+    exclude_credentials: Vec<CredentialDescriptor>,
+) -> Result<CredentialCreationOptions, String> {
+    if rp_id.is_empty() {
+        return Err("Missing required field: rpId".to_string());
+    }
+    if user.near_account_id.is_empty() {
+        return Err("Missing required field: nearAccountId".to_string());
+    }
+    if vrf_challenge.user_id != user.near_account_id {
+        return Err(format!(
+            "VRF challenge is for user {}, not {}",
+            vrf_challenge.user_id, user.near_account_id
+        ));
+    }
+    let challenge = webauthn_challenge_for(vrf_challenge, rp_id)?;
+    let name = user
+        .name
+        .clone()
+        .unwrap_or_else(|| user.device_specific_name());
+
+    Ok(CredentialCreationOptions {
+        rp: RelyingPartyEntity {
+            id: rp_id.to_string(),
+            name: rp_name.unwrap_or(DEFAULT_RP_NAME).to_string(),
+        },
+        user: UserEntity {
+            id: base64_url_encode(name.as_bytes()),
+            display_name: user
+                .display_name
+                .clone()
+                .unwrap_or_else(|| user.friendly_display_name()),
+            name,
+        },
+        challenge,
+        pub_key_cred_params: offered_algorithms(Some(authenticator_options))
+            .iter()
+            .map(|alg| PubKeyCredParam {
+                credential_type: "public-key".to_string(),
+                alg: *alg,
+            })
+            .collect(),
+        timeout: CREDENTIAL_CREATION_TIMEOUT_MS,
+        attestation: "none".to_string(),
+        authenticator_selection: AuthenticatorSelection {
+            resident_key: "required".to_string(),
+            require_resident_key: true,
+            user_verification: authenticator_options
+                .user_verification
+                .clone()
+                .unwrap_or(UserVerificationPolicy::Preferred),
+        },
+        exclude_credentials,
+        extensions: CreationExtensions {
+            prf: prf_inputs(&user.near_account_id),
+            large_blob: LargeBlobCreationInputs {
+                support: "preferred".to_string(),
+            },
+            cred_props: true,
+        },
+    })
+}
+
+/// Request options for an assertion by `near_account_id` on `rp_id`, with the challenge from
+/// `vrf_challenge`. `allow_credentials` of None leaves the choice of passkey to the
+/// authenticator (discoverable credentials); Some must list at least one credential.
+pub fn build_request_options(
+    rp_id: &str,
+    near_account_id: &str,
+    vrf_challenge: &VrfChallenge,
+    user_verification: UserVerificationPolicy,
+    allow_credentials: Option<Vec<CredentialDescriptor>>,
+) -> Result<CredentialRequestOptions, String> {
+    if rp_id.is_empty() {
+        return Err("Missing required field: rpId".to_string());
+    }
+    if vrf_challenge.user_id != near_account_id {
+        return Err(format!(
+            "VRF challenge is for user {}, not {}",
+            vrf_challenge.user_id, near_account_id
+        ));
+    }
+    if allow_credentials
+        .as_ref()
+        .is_some_and(|list| list.is_empty())
+    {
+        return Err(format!("No credentials to allow for {}", near_account_id));
+    }
+
+    Ok(CredentialRequestOptions {
+        challenge: webauthn_challenge_for(vrf_challenge, rp_id)?,
+        rp_id: rp_id.to_string(),
+        allow_credentials,
+        user_verification,
+        timeout: CREDENTIAL_REQUEST_TIMEOUT_MS,
+        extensions: AssertionExtensions {
+            prf: prf_inputs(near_account_id),
+            large_blob: LargeBlobRequestInputs { read: true },
+        },
+    })
+}
@@ -1,19 +1,24 @@
 use bs58;
-use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
 use chacha20poly1305::{ChaCha20Poly1305, Nonce};
 use getrandom::getrandom;
 use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
 use log::{debug, info};
-use sha2::Sha256;
+use sha2::{Digest, Sha256, Sha512};
+use zeroize::Zeroizing;
 
 use crate::config::{
     chacha_salt_for_account, near_key_salt_for_account, CHACHA20_ENCRYPTION_INFO,
-    CHACHA20_KEY_SIZE, CHACHA20_NONCE_SIZE, ED25519_HKDF_KEY_INFO, ED25519_PRIVATE_KEY_SIZE,
-    ERROR_EMPTY_PRF_OUTPUT, ERROR_INVALID_KEY_SIZE,
+    CHACHA20_KEY_SIZE, CHACHA20_NONCE_SIZE, CHACHA20_POLY1305_TAG_SIZE, DEFAULT_CHALLENGE_LENGTH,
+    ED25519_HKDF_KEY_INFO, ED25519_PRIVATE_KEY_SIZE, ERROR_EMPTY_PRF_OUTPUT,
+    ERROR_INVALID_KEY_SIZE, KEY_CHECK_HEADER_MAGIC, KEY_CHECK_HEADER_SIZE, KEY_CHECK_HKDF_INFO,
+    KEY_CHECK_VALUE_SIZE, MAX_CHALLENGE_LENGTH, MIN_CHALLENGE_LENGTH,
 };
 use crate::encoders::{base64_url_decode, base64_url_encode};
-use crate::error::KdfError;
-use crate::types::EncryptedDataChaCha20Response;
+use crate::error::{BlobDecryptError, KdfError};
+use crate::error_codes::LEGACY_KEY_BLOB;
+use crate::types::{EncryptedDataChaCha20Response, VrfChallenge};
 
 // === UTILITY FUNCTIONS ===
 
@@ -55,7 +60,38 @@ pub(crate) fn derive_chacha20_key_from_prf(
 
 // === CHACHA20POLY1305 ENCRYPTION/DECRYPTION ===
 
-/// Encrypt data using ChaCha20Poly1305
+/// Key-check value for a blob: HMAC-SHA256 under an HKDF-derived MAC key, over the magic and
+/// nonce, truncated to 8 bytes. Not a hash of the encryption key: testing a guessed PRF output
+/// against it costs the same HKDF work as testing it against the Poly1305 tag, which the blob
+/// already carries, and the nonce makes it per-blob.
+pub(crate) fn key_check_mac(key_bytes: &[u8], nonce_bytes: &[u8]) -> Result<Hmac<Sha256>, String> {
+    let hk = Hkdf::<Sha256>::new(None, key_bytes);
+    let mut mac_key = [0u8; 32];
+    hk.expand(KEY_CHECK_HKDF_INFO.as_bytes(), &mut mac_key)
+        .map_err(|_| "Key-check key derivation failed".to_string())?;
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&mac_key)
+        .map_err(|e| format!("Key-check MAC setup failed: {}", e))?;
+    mac.update(KEY_CHECK_HEADER_MAGIC);
+    mac.update(nonce_bytes);
+    Ok(mac)
+}
+
+/// Splits encrypted blob bytes into the key-check value and the ciphertext proper.
+/// Returns None for blobs written before the key-check header was introduced.
+pub(crate) fn split_key_check_header(encrypted_data: &[u8]) -> Option<(&[u8], &[u8])> {
+    if encrypted_data.len() < KEY_CHECK_HEADER_SIZE
+        || !encrypted_data.starts_with(KEY_CHECK_HEADER_MAGIC)
+    {
+        return None;
+    }
+    Some((
+        &encrypted_data[KEY_CHECK_HEADER_MAGIC.len()..KEY_CHECK_HEADER_SIZE],
+        &encrypted_data[KEY_CHECK_HEADER_SIZE..],
+    ))
+}
+
+/// Encrypt data using ChaCha20Poly1305.
+/// The ciphertext is prefixed with a key-check header, authenticated as associated data.
 pub(crate) fn encrypt_data_chacha20(
     plain_text_data_str: &str,
     key_bytes: &[u8],
@@ -71,47 +107,105 @@ pub(crate) fn encrypt_data_chacha20(
     getrandom(&mut nonce_bytes).map_err(|e| format!("Failed to generate nonce: {}", e))?;
     let nonce = Nonce::from_slice(&nonce_bytes);
 
+    let key_check = key_check_mac(key_bytes, &nonce_bytes)?
+        .finalize()
+        .into_bytes();
+    let mut encrypted_data = Vec::with_capacity(
+        KEY_CHECK_HEADER_SIZE + plain_text_data_str.len() + CHACHA20_POLY1305_TAG_SIZE,
+    );
+    encrypted_data.extend_from_slice(KEY_CHECK_HEADER_MAGIC);
+    encrypted_data.extend_from_slice(&key_check[..KEY_CHECK_VALUE_SIZE]);
+
     let ciphertext = cipher
-        .encrypt(nonce, plain_text_data_str.as_bytes())
+        .encrypt(
+            nonce,
+            Payload {
+                msg: plain_text_data_str.as_bytes(),
+                aad: &encrypted_data,
+            },
+        )
         .map_err(|e| format!("Encryption error: {}", e))?;
+    encrypted_data.extend_from_slice(&ciphertext);
 
     Ok(EncryptedDataChaCha20Response {
-        encrypted_near_key_data_b64u: base64_url_encode(&ciphertext),
+        encrypted_near_key_data_b64u: base64_url_encode(&encrypted_data),
         chacha20_nonce_b64u: base64_url_encode(&nonce_bytes),
     })
 }
 
-/// Decrypt data using ChaCha20Poly1305
+/// Decrypt data using ChaCha20Poly1305.
+/// For blobs with a key-check header, a failure is classified by comparing the key-check value
+/// derived from `key_bytes` with the stored one; legacy blobs report `DecryptionFailed`.
 pub(crate) fn decrypt_data_chacha20(
     encrypted_data_b64u: &str,
     chacha20_nonce_b64u: &str,
     key_bytes: &[u8],
-) -> Result<String, String> {
+) -> Result<String, BlobDecryptError> {
     if key_bytes.len() != CHACHA20_KEY_SIZE {
-        return Err(ERROR_INVALID_KEY_SIZE.to_string());
+        return Err(BlobDecryptError::InvalidInput(
+            ERROR_INVALID_KEY_SIZE.to_string(),
+        ));
     }
 
     let key = chacha20poly1305::Key::from_slice(key_bytes);
     let cipher = ChaCha20Poly1305::new(key);
 
-    let nonce_bytes = base64_url_decode(chacha20_nonce_b64u)
-        .map_err(|e| format!("Base64 decode error for ChaCha20 nonce: {}", e))?;
+    let nonce_bytes = base64_url_decode(chacha20_nonce_b64u).map_err(|e| {
+        BlobDecryptError::CorruptedCiphertext(format!(
+            "Base64 decode error for ChaCha20 nonce: {}",
+            e
+        ))
+    })?;
     if nonce_bytes.len() != CHACHA20_NONCE_SIZE {
-        return Err(format!(
+        return Err(BlobDecryptError::CorruptedCiphertext(format!(
             "Decryption ChaCha20 nonce must be {} bytes.",
             CHACHA20_NONCE_SIZE
-        ));
+        )));
     }
     let nonce = Nonce::from_slice(&nonce_bytes);
 
-    let encrypted_data = base64_url_decode(encrypted_data_b64u)
-        .map_err(|e| format!("Base64 decode error for encrypted data: {}", e))?;
-
-    let decrypted_bytes = cipher
-        .decrypt(nonce, encrypted_data.as_slice())
-        .map_err(|e| format!("Decryption error: {}", e))?;
+    let encrypted_data = base64_url_decode(encrypted_data_b64u).map_err(|e| {
+        BlobDecryptError::CorruptedCiphertext(format!(
+            "Base64 decode error for encrypted data: {}",
+            e
+        ))
+    })?;
+
+    let decrypted_bytes = match split_key_check_header(&encrypted_data) {
+        None => cipher
+            .decrypt(nonce, encrypted_data.as_slice())
+            .map_err(|e| BlobDecryptError::DecryptionFailed(format!("Decryption error: {}", e)))?,
+        Some((stored_key_check, ciphertext)) => {
+            let payload = Payload {
+                msg: ciphertext,
+                aad: &encrypted_data[..KEY_CHECK_HEADER_SIZE],
+            };
+            match cipher.decrypt(nonce, payload) {
+                Ok(decrypted) => decrypted,
+                // A legacy ciphertext can start with the magic bytes by chance
+                Err(_) => match cipher.decrypt(nonce, encrypted_data.as_slice()) {
+                    Ok(decrypted) => decrypted,
+                    Err(e) => {
+                        let key_matches = key_check_mac(key_bytes, &nonce_bytes)
+                            .map_err(BlobDecryptError::InvalidInput)?
+                            .verify_truncated_left(stored_key_check)
+                            .is_ok();
+                        return Err(if key_matches {
+                            BlobDecryptError::CorruptedCiphertext(format!(
+                                "Decryption error: {}",
+                                e
+                            ))
+                        } else {
+                            BlobDecryptError::WrongCredentialForBlob
+                        });
+                    }
+                },
+            }
+        }
+    };
 
-    String::from_utf8(decrypted_bytes).map_err(|e| format!("UTF-8 decoding error: {}", e))
+    String::from_utf8(decrypted_bytes)
+        .map_err(|e| BlobDecryptError::InvalidInput(format!("UTF-8 decoding error: {}", e)))
 }
 
 // === KEY GENERATION ===
@@ -169,6 +263,23 @@ pub(crate) fn derive_ed25519_key_from_prf_output(
     Ok((near_private_key, near_public_key))
 }
 
+/// Random Ed25519 keypair in NEAR format, for registrations without PRF output to derive from
+pub(crate) fn generate_near_keypair() -> Result<(String, String), KdfError> {
+    let mut seed = [0u8; ED25519_PRIVATE_KEY_SIZE];
+    getrandom(&mut seed)
+        .map_err(|e| KdfError::InvalidInput(format!("Failed to generate key: {}", e)))?;
+    let signing_key = ed25519_dalek::SigningKey::from_bytes(&seed);
+    let public_key_bytes = signing_key.verifying_key().to_bytes();
+    let near_private_key_bytes = [seed, public_key_bytes].concat();
+    Ok((
+        format!(
+            "ed25519:{}",
+            bs58::encode(&near_private_key_bytes).into_string()
+        ),
+        format!("ed25519:{}", bs58::encode(public_key_bytes).into_string()),
+    ))
+}
+
 /// Dual PRF workflow
 /// Derives both ChaCha20 and Ed25519 keys from separate PRF outputs and encrypts the Ed25519 key
 pub(crate) fn derive_and_encrypt_keypair_from_dual_prf(
@@ -198,37 +309,78 @@ pub(crate) fn derive_and_encrypt_keypair_from_dual_prf(
     Ok((near_public_key, encrypted_response))
 }
 
+/// Curve of a decrypted private key string: `secp256k1:` keys (NEAR's prefix), otherwise
+/// Ed25519 (with or without the `ed25519:` prefix)
+pub(crate) fn private_key_curve(private_key: &str) -> &'static str {
+    if private_key.starts_with("secp256k1:") {
+        "secp256k1"
+    } else {
+        "ed25519"
+    }
+}
+
+/// Private key string of a blob, before it is parsed for its curve
+fn decrypt_private_key_plaintext_with_prf(
+    near_account_id: &str,
+    chacha20_prf_output: &str,
+    encrypted_private_key_data: &str,
+    encrypted_private_key_iv: &str,
+) -> Result<Zeroizing<String>, BlobDecryptError> {
+    let (chacha20_key, encrypted_private_key_data) = crate::key_wrapping::unwrap_blob_key(
+        near_account_id,
+        chacha20_prf_output,
+        encrypted_private_key_data,
+    )?;
+    let chacha20_key = Zeroizing::new(chacha20_key);
+    // Blobs written before the key-check header still decrypt, with a deprecation warning
+    if base64_url_decode(&encrypted_private_key_data)
+        .is_ok_and(|data| split_key_check_header(&data).is_none())
+    {
+        crate::deprecations::note(&LEGACY_KEY_BLOB).map_err(BlobDecryptError::Deprecated)?;
+    }
+    Ok(Zeroizing::new(decrypt_data_chacha20(
+        &encrypted_private_key_data,
+        encrypted_private_key_iv,
+        &chacha20_key,
+    )?))
+}
+
 /// Decrypt private key from stored data and return as SigningKey
-/// Now uses account-specific HKDF for secure key derivation
+/// Now uses account-specific HKDF for secure key derivation.
+/// For blobs of a PRF fallback scheme, `chacha20_prf_output` carries that scheme's secret
+/// instead (see key_wrapping.rs); the scheme is read from the blob.
 pub fn decrypt_private_key_with_prf(
     near_account_id: &str,
     chacha20_prf_output: &str,
     encrypted_private_key_data: &str,
     encrypted_private_key_iv: &str,
-) -> Result<ed25519_dalek::SigningKey, String> {
+) -> Result<ed25519_dalek::SigningKey, BlobDecryptError> {
     info!("Decrypting private key with PRF using account-specific HKDF");
 
-    let chacha20_key = derive_chacha20_key_from_prf(chacha20_prf_output, near_account_id)
-        .map_err(|e| format!("Account-specific key derivation failed: {}", e))?;
-
-    // 2. Decrypt private key using ChaCha20Poly1305
-    let decrypted_private_key_str = decrypt_data_chacha20(
+    // 1-2. Derive the blob key and decrypt private key using ChaCha20Poly1305
+    let decrypted_private_key_str = decrypt_private_key_plaintext_with_prf(
+        near_account_id,
+        chacha20_prf_output,
         encrypted_private_key_data,
         encrypted_private_key_iv,
-        &chacha20_key,
     )?;
+    let curve = private_key_curve(&decrypted_private_key_str);
+    if curve != "ed25519" {
+        return Err(BlobDecryptError::WrongCurve {
+            expected: "ed25519",
+            found: curve,
+        });
+    }
 
     // 3. Parse private key (remove ed25519: prefix if present)
-    let private_key_b58 = if decrypted_private_key_str.starts_with("ed25519:") {
-        &decrypted_private_key_str[8..]
-    } else {
-        &decrypted_private_key_str
-    };
+    let private_key_b58 = decrypted_private_key_str
+        .strip_prefix("ed25519:")
+        .unwrap_or(&decrypted_private_key_str);
 
     // 4. Decode private key from base58
-    let private_key_bytes = bs58::decode(private_key_b58)
-        .into_vec()
-        .map_err(|e| format!("Failed to decode private key: {}", e))?;
+    let private_key_bytes = bs58::decode(private_key_b58).into_vec().map_err(|e| {
+        BlobDecryptError::InvalidInput(format!("Failed to decode private key: {}", e))
+    })?;
 
     // 5. Handle both 32-byte (seed only) and 64-byte (seed + public key) formats
     let seed_bytes = if private_key_bytes.len() == 32 {
@@ -240,10 +392,10 @@ pub fn decrypt_private_key_with_prf(
         debug!("Using 64-byte private key format (seed + public key)");
         private_key_bytes[0..32].to_vec()
     } else {
-        return Err(format!(
+        return Err(BlobDecryptError::InvalidInput(format!(
             "Invalid private key length: {} (expected 32 or 64)",
             private_key_bytes.len()
-        ));
+        )));
     };
 
     // 6. Create SigningKey from the 32-byte seed
@@ -255,6 +407,131 @@ pub fn decrypt_private_key_with_prf(
     Ok(signing_key)
 }
 
+/// `decrypt_private_key_with_prf` for blobs holding a secp256k1 key (`secp256k1:` and the
+/// base58 32-byte secret, as NEAR formats them); Ed25519 blobs fail with WrongCurve
+#[cfg(feature = "secp256k1")]
+pub fn decrypt_secp256k1_private_key_with_prf(
+    near_account_id: &str,
+    chacha20_prf_output: &str,
+    encrypted_private_key_data: &str,
+    encrypted_private_key_iv: &str,
+) -> Result<k256::ecdsa::SigningKey, BlobDecryptError> {
+    let plaintext = decrypt_private_key_plaintext_with_prf(
+        near_account_id,
+        chacha20_prf_output,
+        encrypted_private_key_data,
+        encrypted_private_key_iv,
+    )?;
+    let Some(secret_b58) = plaintext.strip_prefix("secp256k1:") else {
+        return Err(BlobDecryptError::WrongCurve {
+            expected: "secp256k1",
+            found: private_key_curve(&plaintext),
+        });
+    };
+    let secret = Zeroizing::new(bs58::decode(secret_b58).into_vec().map_err(|e| {
+        BlobDecryptError::InvalidInput(format!("Failed to decode private key: {}", e))
+    })?);
+    k256::ecdsa::SigningKey::from_slice(&secret).map_err(|_| {
+        BlobDecryptError::InvalidInput(format!(
+            "Invalid secp256k1 private key ({} bytes)",
+            secret.len()
+        ))
+    })
+}
+
+/// `decrypt_secp256k1_private_key_with_prf` for blob data as stored (see
+/// `decrypt_stored_private_key_with_prf`)
+#[cfg(feature = "secp256k1")]
+pub async fn decrypt_stored_secp256k1_private_key_with_prf(
+    near_account_id: &str,
+    chacha20_prf_output: &str,
+    encrypted_private_key_data: &str,
+    encrypted_private_key_iv: &str,
+) -> Result<k256::ecdsa::SigningKey, BlobDecryptError> {
+    let encrypted_private_key_data =
+        crate::outer_wrap::unwrap_encrypted_data(encrypted_private_key_data).await?;
+    decrypt_secp256k1_private_key_with_prf(
+        near_account_id,
+        chacha20_prf_output,
+        &encrypted_private_key_data,
+        encrypted_private_key_iv,
+    )
+}
+
+/// `decrypt_private_key_with_prf` for blob data as stored, which may carry an outer
+/// WebCrypto wrap (see outer_wrap.rs); the wrap is removed before the PRF decryption
+pub async fn decrypt_stored_private_key_with_prf(
+    near_account_id: &str,
+    chacha20_prf_output: &str,
+    encrypted_private_key_data: &str,
+    encrypted_private_key_iv: &str,
+) -> Result<ed25519_dalek::SigningKey, BlobDecryptError> {
+    decrypt_stored_private_key_with_identity(
+        near_account_id,
+        chacha20_prf_output,
+        encrypted_private_key_data,
+        encrypted_private_key_iv,
+    )
+    .await
+    .map(|(signing_key, _)| signing_key)
+}
+
+/// `decrypt_stored_private_key_with_prf`, also returning the blob's AAD identity
+/// (key_usage.rs; None for blobs without a key-check header)
+pub async fn decrypt_stored_private_key_with_identity(
+    near_account_id: &str,
+    chacha20_prf_output: &str,
+    encrypted_private_key_data: &str,
+    encrypted_private_key_iv: &str,
+) -> Result<(ed25519_dalek::SigningKey, Option<String>), BlobDecryptError> {
+    let encrypted_private_key_data =
+        crate::outer_wrap::unwrap_encrypted_data(encrypted_private_key_data).await?;
+    let signing_key = decrypt_private_key_with_prf(
+        near_account_id,
+        chacha20_prf_output,
+        &encrypted_private_key_data,
+        encrypted_private_key_iv,
+    )?;
+    let key_id = base64_url_decode(&encrypted_private_key_data)
+        .ok()
+        .and_then(|data| crate::key_usage::key_usage_id(&data));
+    Ok((signing_key, key_id))
+}
+
+/// Dry run of `decrypt_stored_private_key_with_prf`: decrypts the blob and checks that the
+/// plaintext is an Ed25519 (or secp256k1) private key. The derived key and the plaintext are
+/// wiped before returning; nothing of the key is kept or returned.
+pub async fn check_stored_private_key_decrypts(
+    near_account_id: &str,
+    chacha20_prf_output: &str,
+    encrypted_private_key_data: &str,
+    encrypted_private_key_iv: &str,
+) -> Result<(), BlobDecryptError> {
+    let encrypted_private_key_data =
+        crate::outer_wrap::unwrap_encrypted_data(encrypted_private_key_data).await?;
+    let plaintext = decrypt_private_key_plaintext_with_prf(
+        near_account_id,
+        chacha20_prf_output,
+        &encrypted_private_key_data,
+        encrypted_private_key_iv,
+    )?;
+
+    let private_key_b58 = plaintext
+        .strip_prefix("ed25519:")
+        .or_else(|| plaintext.strip_prefix("secp256k1:"))
+        .unwrap_or(&plaintext);
+    let private_key_bytes = Zeroizing::new(bs58::decode(private_key_b58).into_vec().map_err(
+        |e| BlobDecryptError::InvalidInput(format!("Failed to decode private key: {}", e)),
+    )?);
+    if private_key_bytes.len() != 32 && private_key_bytes.len() != 64 {
+        return Err(BlobDecryptError::InvalidInput(format!(
+            "Invalid private key length: {} (expected 32 or 64)",
+            private_key_bytes.len()
+        )));
+    }
+    Ok(())
+}
+
 /// Encrypt private key with PRF output for storage
 /// Returns both encrypted data and IV separately for IndexedDB storage
 pub fn encrypt_private_key_with_prf(
@@ -278,3 +555,65 @@ pub fn encrypt_private_key_with_prf(
     info!("Private key encrypted successfully");
     Ok(encrypted_result)
 }
+
+// === VRF CHALLENGE BINDING ===
+
+/// Derives the WebAuthn challenge from the VRF output (mirrors the VRF worker):
+/// the default length is the raw VRF output prefix, other lengths are a prefix of
+/// SHA-256 (up to 32 bytes) or SHA-512 (longer) over the VRF output.
+pub fn derive_webauthn_challenge(
+    vrf_output: &[u8],
+    challenge_length: u8,
+) -> Result<Vec<u8>, String> {
+    if !(MIN_CHALLENGE_LENGTH..=MAX_CHALLENGE_LENGTH).contains(&challenge_length) {
+        return Err(format!(
+            "challengeLength must be between {} and {} bytes, got {}",
+            MIN_CHALLENGE_LENGTH, MAX_CHALLENGE_LENGTH, challenge_length
+        ));
+    }
+    let length = challenge_length as usize;
+
+    if challenge_length == DEFAULT_CHALLENGE_LENGTH {
+        return vrf_output
+            .get(..length)
+            .map(|prefix| prefix.to_vec())
+            .ok_or_else(|| format!("VRF output too short: {} bytes", vrf_output.len()));
+    }
+
+    let digest = if length <= 32 {
+        Sha256::digest(vrf_output).to_vec()
+    } else {
+        Sha512::digest(vrf_output).to_vec()
+    };
+    Ok(digest[..length].to_vec())
+}
+
+/// Checks that the credential's clientDataJSON (base64url) signed the challenge
+/// derived from `vrf_challenge`, honoring its `challenge_length`
+pub fn verify_vrf_challenge_binding(
+    vrf_challenge: &VrfChallenge,
+    client_data_json_b64u: &str,
+) -> Result<(), String> {
+    let vrf_output = base64_url_decode(&vrf_challenge.vrf_output)
+        .map_err(|e| format!("Invalid VRF output: {}", e))?;
+    let expected = derive_webauthn_challenge(&vrf_output, vrf_challenge.challenge_length)?;
+
+    let client_data_bytes = base64_url_decode(client_data_json_b64u)
+        .map_err(|e| format!("Invalid clientDataJSON encoding: {}", e))?;
+    let client_data: serde_json::Value = serde_json::from_slice(&client_data_bytes)
+        .map_err(|e| format!("Invalid clientDataJSON: {}", e))?;
+    let challenge_b64u = client_data
+        .get("challenge")
+        .and_then(|c| c.as_str())
+        .ok_or_else(|| "clientDataJSON is missing the challenge".to_string())?;
+    let challenge = base64_url_decode(challenge_b64u)
+        .map_err(|e| format!("Invalid clientDataJSON challenge: {}", e))?;
+
+    if challenge != expected {
+        return Err(format!(
+            "WebAuthn challenge does not match the VRF output ({}-byte challenge)",
+            vrf_challenge.challenge_length
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,419 @@
+// === DEFLATE ===
+// Raw DEFLATE (RFC 1951) for QR payloads (qr_payload.rs). Compression is deterministic: greedy
+// LZ77 over a hash chain of bounded length, emitted as one fixed-Huffman block, or as stored
+// blocks when that would be larger. The same input therefore always gives the same bytes, in
+// every build, which keeps QR parts reproducible. Inflation accepts any raw DEFLATE stream
+// (stored, fixed and dynamic blocks) up to an output limit, so a hostile stream cannot expand
+// without bound.
+
+/// Longest match distance (the DEFLATE window)
+const WINDOW_SIZE: usize = 32 * 1024;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+/// Candidates tried per position; bounds compression time on repetitive input
+const MAX_CHAIN: usize = 64;
+const HASH_BITS: u32 = 15;
+/// Largest stored block
+const MAX_STORED_BLOCK: usize = 65_535;
+
+/// Base length and extra bits of length codes 257..=285
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+/// Base distance and extra bits of distance codes 0..=29
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order code length code lengths are sent in (dynamic blocks)
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+// --- Compression ---
+
+/// Writes bits least significant first, as DEFLATE packs them
+struct BitWriter {
+    out: Vec<u8>,
+    bits: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            out: Vec::new(),
+            bits: 0,
+            count: 0,
+        }
+    }
+
+    fn write(&mut self, value: u32, count: u32) {
+        self.bits |= (value as u64) << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.out.push(self.bits as u8);
+            self.bits >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Huffman codes are defined most significant bit first
+    fn write_code(&mut self, code: u32, length: u32) {
+        self.write(code.reverse_bits() >> (32 - length), length);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.out.push(self.bits as u8);
+        }
+        self.out
+    }
+}
+
+/// Fixed Huffman code of a literal/length symbol (RFC 1951 3.2.6)
+fn write_fixed_literal(writer: &mut BitWriter, symbol: u16) {
+    let symbol = symbol as u32;
+    match symbol {
+        0..=143 => writer.write_code(0x30 + symbol, 8),
+        144..=255 => writer.write_code(0x190 + symbol - 144, 9),
+        256..=279 => writer.write_code(symbol - 256, 7),
+        _ => writer.write_code(0xC0 + symbol - 280, 8),
+    }
+}
+
+fn write_match(writer: &mut BitWriter, length: usize, distance: usize) {
+    let code = LENGTH_BASE
+        .iter()
+        .rposition(|&base| base as usize <= length)
+        .unwrap_or(0);
+    write_fixed_literal(writer, 257 + code as u16);
+    writer.write(
+        (length - LENGTH_BASE[code] as usize) as u32,
+        LENGTH_EXTRA[code] as u32,
+    );
+    let code = DIST_BASE
+        .iter()
+        .rposition(|&base| base as usize <= distance)
+        .unwrap_or(0);
+    writer.write_code(code as u32, 5);
+    writer.write(
+        (distance - DIST_BASE[code] as usize) as u32,
+        DIST_EXTRA[code] as u32,
+    );
+}
+
+fn hash3(data: &[u8], pos: usize) -> usize {
+    let value = (data[pos] as u32) << 16 | (data[pos + 1] as u32) << 8 | data[pos + 2] as u32;
+    (value.wrapping_mul(0x9E37_79B1) >> (32 - HASH_BITS)) as usize
+}
+
+/// Links `pos` into the hash chain of its first 3 bytes
+fn insert(data: &[u8], head: &mut [usize], prev: &mut [usize], pos: usize) {
+    if pos + MIN_MATCH <= data.len() {
+        let hash = hash3(data, pos);
+        prev[pos] = head[hash];
+        head[hash] = pos;
+    }
+}
+
+/// One fixed-Huffman block of greedy LZ77 matches
+fn deflate_fixed(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::new();
+    writer.write(1, 1); // BFINAL
+    writer.write(1, 2); // BTYPE 01: fixed Huffman
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; data.len()];
+    let mut pos = 0;
+    while pos < data.len() {
+        let mut best = (0, 0);
+        if pos + MIN_MATCH <= data.len() {
+            let max_length = MAX_MATCH.min(data.len() - pos);
+            let mut candidate = head[hash3(data, pos)];
+            let mut tried = 0;
+            while candidate != usize::MAX && pos - candidate <= WINDOW_SIZE && tried < MAX_CHAIN {
+                let length = data[candidate..]
+                    .iter()
+                    .zip(&data[pos..pos + max_length])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if length > best.0 {
+                    best = (length, pos - candidate);
+                    if length == max_length {
+                        break;
+                    }
+                }
+                candidate = prev[candidate];
+                tried += 1;
+            }
+        }
+        if best.0 >= MIN_MATCH {
+            write_match(&mut writer, best.0, best.1);
+            for p in pos..pos + best.0 {
+                insert(data, &mut head, &mut prev, p);
+            }
+            pos += best.0;
+        } else {
+            write_fixed_literal(&mut writer, data[pos] as u16);
+            insert(data, &mut head, &mut prev, pos);
+            pos += 1;
+        }
+    }
+    write_fixed_literal(&mut writer, 256);
+    writer.finish()
+}
+
+/// Stored (uncompressed) blocks
+fn deflate_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + 5 * (data.len() / MAX_STORED_BLOCK + 1));
+    let mut blocks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        return vec![0x01, 0x00, 0x00, 0xFF, 0xFF];
+    }
+    while let Some(block) = blocks.next() {
+        out.push(if blocks.peek().is_none() { 0x01 } else { 0x00 });
+        let length = block.len() as u16;
+        out.extend_from_slice(&length.to_le_bytes());
+        out.extend_from_slice(&(!length).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out
+}
+
+/// Compresses `data` to a raw DEFLATE stream, deterministically
+pub fn deflate(data: &[u8]) -> Vec<u8> {
+    let fixed = deflate_fixed(data);
+    let stored_length = data.len() + 5 * data.len().div_ceil(MAX_STORED_BLOCK).max(1);
+    if fixed.len() <= stored_length {
+        fixed
+    } else {
+        deflate_stored(data)
+    }
+}
+
+// --- Decompression ---
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bits: u32,
+    count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            bits: 0,
+            count: 0,
+        }
+    }
+
+    fn read(&mut self, count: u32) -> Result<u32, String> {
+        while self.count < count {
+            let byte = *self.data.get(self.pos).ok_or("DEFLATE stream ends early")?;
+            self.pos += 1;
+            self.bits |= (byte as u32) << self.count;
+            self.count += 8;
+        }
+        let value = self.bits & ((1u64 << count) - 1) as u32;
+        self.bits >>= count;
+        self.count -= count;
+        Ok(value)
+    }
+
+    /// Drops the bits left of the current byte (stored blocks start on a byte boundary)
+    fn align(&mut self) {
+        self.bits = 0;
+        self.count = 0;
+    }
+}
+
+/// A canonical Huffman code: symbols counted per code length, in code order
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, String> {
+        let mut counts = [0u16; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        let mut left: i32 = 1;
+        for &count in &counts[1..] {
+            left = (left << 1) - count as i32;
+            if left < 0 {
+                return Err("DEFLATE stream has an over-subscribed Huffman code".to_string());
+            }
+        }
+        let mut offsets = [0u16; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0u16; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+        Ok(Self { counts, symbols })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, String> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= reader.read(1)? as i32;
+            let count = count as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("DEFLATE stream has an invalid Huffman code".to_string())
+    }
+}
+
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    // Both are complete codes
+    (
+        Huffman::new(&lengths).expect("fixed literal code"),
+        Huffman::new(&[5u8; 30]).expect("fixed distance code"),
+    )
+}
+
+fn dynamic_codes(reader: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    let literal_count = reader.read(5)? as usize + 257;
+    let distance_count = reader.read(5)? as usize + 1;
+    let code_length_count = reader.read(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return Err("DEFLATE stream has too many codes".to_string());
+    }
+    let mut code_lengths = [0u8; 19];
+    for &symbol in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[symbol] = reader.read(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&code_lengths)?;
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (value, repeat) = match code_length_code.decode(reader)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths
+                    .last()
+                    .ok_or("DEFLATE stream repeats a missing code length")?;
+                (previous, 3 + reader.read(2)? as usize)
+            }
+            17 => (0, 3 + reader.read(3)? as usize),
+            _ => (0, 11 + reader.read(7)? as usize),
+        };
+        if lengths.len() + repeat > literal_count + distance_count {
+            return Err("DEFLATE stream has too many code lengths".to_string());
+        }
+        lengths.extend(std::iter::repeat_n(value, repeat));
+    }
+    if lengths[256] == 0 {
+        return Err("DEFLATE stream has no end-of-block code".to_string());
+    }
+    Ok((
+        Huffman::new(&lengths[..literal_count])?,
+        Huffman::new(&lengths[literal_count..])?,
+    ))
+}
+
+fn inflate_block(
+    reader: &mut BitReader,
+    out: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+    max_output: usize,
+) -> Result<(), String> {
+    loop {
+        let symbol = literals.decode(reader)? as usize;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            257..=285 => {
+                let code = symbol - 257;
+                let length =
+                    LENGTH_BASE[code] as usize + reader.read(LENGTH_EXTRA[code] as u32)? as usize;
+                let code = distances.decode(reader)? as usize;
+                if code >= DIST_BASE.len() {
+                    return Err("DEFLATE stream has an invalid distance code".to_string());
+                }
+                let distance =
+                    DIST_BASE[code] as usize + reader.read(DIST_EXTRA[code] as u32)? as usize;
+                if distance > out.len() {
+                    return Err("DEFLATE stream refers before its start".to_string());
+                }
+                for _ in 0..length {
+                    out.push(out[out.len() - distance]);
+                }
+            }
+            _ => return Err("DEFLATE stream has an invalid length code".to_string()),
+        }
+        if out.len() > max_output {
+            return Err(format!("DEFLATE stream inflates past {} bytes", max_output));
+        }
+    }
+}
+
+/// Decompresses a raw DEFLATE stream, refusing output longer than `max_output`
+pub fn inflate(data: &[u8], max_output: usize) -> Result<Vec<u8>, String> {
+    let mut reader = BitReader::new(data);
+    let mut out = Vec::new();
+    loop {
+        let last = reader.read(1)? == 1;
+        match reader.read(2)? {
+            0 => {
+                reader.align();
+                let header = data
+                    .get(reader.pos..reader.pos + 4)
+                    .ok_or("DEFLATE stream ends early")?;
+                let length = u16::from_le_bytes([header[0], header[1]]);
+                if length != !u16::from_le_bytes([header[2], header[3]]) {
+                    return Err("DEFLATE stored block length does not check".to_string());
+                }
+                let start = reader.pos + 4;
+                let block = data
+                    .get(start..start + length as usize)
+                    .ok_or("DEFLATE stream ends early")?;
+                if out.len() + block.len() > max_output {
+                    return Err(format!("DEFLATE stream inflates past {} bytes", max_output));
+                }
+                out.extend_from_slice(block);
+                reader.pos = start + length as usize;
+            }
+            1 => {
+                let (literals, distances) = fixed_codes();
+                inflate_block(&mut reader, &mut out, &literals, &distances, max_output)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(&mut reader)?;
+                inflate_block(&mut reader, &mut out, &literals, &distances, max_output)?;
+            }
+            _ => return Err("DEFLATE stream has an invalid block type".to_string()),
+        }
+        if last {
+            return Ok(out);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+// === DEPRECATION WARNINGS ===
+// Superseded paths keep working for a while, but a request that takes one reports it in the
+// response envelope's `warnings` ({ code, message, removeAfterVersion }), so integrators find
+// out before the path is removed. The codes are catalogued in wasm_shared/error_codes.rs:
+//   PlaintextSecret              a request's secret field sent in plaintext, not sealed
+//   PlaintextConfirmationSecret  the confirmation response's PRF outputs sent in plaintext
+//   LegacyKeyBlob                a NEAR key blob without the key-check header decrypted
+// Each is noted where the path is taken, at most once per request. With
+// `WorkerPolicy.failOnDeprecated` noting fails instead, with DeprecatedUsage, before the
+// deprecated path is used. Both secret paths stay quiet once requireEncryptedSecrets is in
+// force: plaintext is then refused with PlaintextSecretRefused.
+
+use serde_json::Value;
+use std::cell::RefCell;
+
+use crate::error::DeprecationError;
+use crate::error_codes::{DeprecationDef, DeprecationWarning};
+
+#[derive(Default)]
+struct RequestDeprecations {
+    fail_on_deprecated: bool,
+    noted: Vec<&'static DeprecationDef>,
+}
+
+thread_local! {
+    static DEPRECATIONS: RefCell<RequestDeprecations> = RefCell::new(RequestDeprecations::default());
+}
+
+/// Whether the payload's WorkerPolicy turns deprecation warnings into errors
+pub fn fail_on_deprecated(payload: &Value) -> bool {
+    payload.pointer("/workerPolicy/failOnDeprecated") == Some(&Value::Bool(true))
+}
+
+/// Dispatch step: starts the request's warnings, under the payload's failOnDeprecated
+pub fn begin_request(payload: &Value) {
+    DEPRECATIONS.with(|d| {
+        *d.borrow_mut() = RequestDeprecations {
+            fail_on_deprecated: fail_on_deprecated(payload),
+            noted: Vec::new(),
+        }
+    });
+}
+
+/// Records that the current request took the deprecated path `def`; an error under
+/// failOnDeprecated, which the caller returns before taking the path
+pub fn note(def: &'static DeprecationDef) -> Result<(), DeprecationError> {
+    DEPRECATIONS.with(|d| {
+        let mut d = d.borrow_mut();
+        if d.fail_on_deprecated {
+            return Err(DeprecationError {
+                deprecation: def.code,
+                message: def.message,
+            });
+        }
+        if !d.noted.iter().any(|noted| noted.code == def.code) {
+            d.noted.push(def);
+        }
+        Ok(())
+    })
+}
+
+/// The current request's warnings, in the order they were noted; clears them
+pub fn take_warnings() -> Vec<DeprecationWarning> {
+    DEPRECATIONS.with(|d| {
+        std::mem::take(&mut d.borrow_mut().noted)
+            .into_iter()
+            .map(DeprecationDef::warning)
+            .collect()
+    })
+}
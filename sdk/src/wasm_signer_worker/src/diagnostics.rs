@@ -0,0 +1,466 @@
+// === DIAGNOSTICS ===
+// DumpDiagnostics answers "what is this worker configured with and holding right now" as one
+// pretty-printed JSON document that support can ask for and diff against another dump.
+//
+// Redaction is by construction: the document is built from the structs below, and none of
+// them has a field for a secret, PRF output, credential ID or full public key. Keys appear as
+// fingerprints (first 8 bytes of a SHA-256, as in confirmations), URLs as their origin, audit
+// details only as whether there was one, and policy lists longer than
+// DIAGNOSTICS_MAX_LIST_ITEMS as their length and SHA-256.
+//
+// The document is deterministic for a given state: every list is sorted, maps are BTreeMaps,
+// and nothing derived from the current time (ages, memory use) is included, so two dumps
+// differ only where the state does. The one exception is the session's remainingMs, which
+// support asks about often enough to be worth the diff noise.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::config::{
+    DIAGNOSTICS_LOG_ENTRIES, DIAGNOSTICS_MAX_LIST_ITEMS, DIAGNOSTICS_SCHEMA_VERSION,
+    ENCRYPTED_BLOB_VERSION_V1,
+};
+use crate::encoders::base64_url_decode;
+use crate::features::{WorkerInfo, FEATURES};
+use crate::handlers::handle_validate_encrypted_blobs::EncryptedKeyBlob;
+use crate::key_selection::{key_fingerprint, parse_public_key};
+use crate::key_usage;
+use crate::outer_wrap::split_outer_wrap;
+use crate::redaction::value_sha256;
+use crate::rpc_endpoints::rpc_origin;
+use crate::session_duration::{self, SessionStatus};
+use crate::state::{self, RequestPhase};
+use crate::types::handlers::WorkerPolicy;
+
+/// The DumpDiagnostics document
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostics {
+    pub schema_version: u32,
+    pub build: WorkerInfo,
+    pub compiled_features: Vec<FeatureDiagnostics>,
+    pub policy: PolicyDiagnostics,
+    pub accounts: Vec<AccountDiagnostics>,
+    pub key_usage: KeyUsageDiagnostics,
+    pub caches: CacheDiagnostics,
+    pub request_limiter: RequestLimiterDiagnostics,
+    pub session: SessionStatus,
+    pub pending_requests: Vec<PendingRequestDiagnostics>,
+    pub recent_log: Vec<LogEntryDiagnostics>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeatureDiagnostics {
+    pub name: String,
+    pub compiled: bool,
+}
+
+/// A policy list: its items, or its length and SHA-256 once it is longer than
+/// DIAGNOSTICS_MAX_LIST_ITEMS
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ListDiagnostics {
+    Items(Vec<String>),
+    Digest { count: usize, sha256: String },
+}
+
+impl ListDiagnostics {
+    /// Sorts the items, so the same list in another order reports the same
+    pub fn new(mut items: Vec<String>) -> Self {
+        items.sort();
+        if items.len() <= DIAGNOSTICS_MAX_LIST_ITEMS {
+            return ListDiagnostics::Items(items);
+        }
+        ListDiagnostics::Digest {
+            count: items.len(),
+            sha256: value_sha256(&json!(items)),
+        }
+    }
+}
+
+/// The effective WorkerPolicy: the request's policy, with the settings the worker latches
+/// (encrypted secrets, message size limits, session duration, confirmation origin) read from its
+/// state
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyDiagnostics {
+    pub pre_sign_hook: bool,
+    pub post_sign_hook: bool,
+    pub hook_timeout_ms: Option<u32>,
+    pub average_block_time_ms: Option<u32>,
+    /// Origin of the indexer URL
+    pub indexer_origin: Option<String>,
+    pub strict_parsing: bool,
+    pub allowed_rpc_origins: ListDiagnostics,
+    pub max_signing_intent_ttl_ms: Option<u32>,
+    /// Origin of the relayer URL
+    pub relayer_origin: Option<String>,
+    pub deposit_escalation_yocto: Option<String>,
+    pub low_allowance_warning_yocto: Option<String>,
+    pub prf_fallback_schemes: Vec<String>,
+    /// Origin of the telemetry endpoint
+    pub telemetry_origin: Option<String>,
+    /// Accounts with a policy override
+    pub account_overrides: ListDiagnostics,
+    /// Methods with an args schema, as "contract.method"
+    pub args_schemas: ListDiagnostics,
+    pub require_encrypted_secrets: bool,
+    pub message_size_limits: MessageSizeLimitsDiagnostics,
+    pub fail_on_deprecated: bool,
+    pub block_full_access_add_key: bool,
+    pub max_arg_preview_bytes: Option<u32>,
+    pub max_summary_blocks: Option<u32>,
+    pub elevated_operations: Vec<String>,
+    pub elevation_max_age_ms: Option<u32>,
+    pub max_session_duration_ms: Option<u32>,
+    pub required_approvals: Option<u8>,
+    pub trusted_confirmation_origin: Option<String>,
+    pub allowed_redirect_origins: ListDiagnostics,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageSizeLimitsDiagnostics {
+    pub default_bytes: Option<u32>,
+    pub deploy_bytes: Option<u32>,
+    pub by_type: BTreeMap<String, u32>,
+}
+
+/// An account the worker holds state for, or that a key blob is bound to
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountDiagnostics {
+    pub account_id: String,
+    pub signed_requests: u32,
+    pub failed_requests: u32,
+    pub nonce_reservations: usize,
+    pub audit_entries: usize,
+    pub last_active_ms: f64,
+    pub session_keys: Vec<SessionKeyDiagnostics>,
+    pub key_blobs: Vec<KeyBlobDiagnostics>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionKeyDiagnostics {
+    /// Fingerprint of the session key's public key
+    pub fingerprint: String,
+    pub receiver_id: String,
+    pub expires_at_ms: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyBlobDiagnostics {
+    pub blob_id: Option<String>,
+    pub version: u32,
+    pub outer_wrap: Option<String>,
+    /// Fingerprint of the blob's key-check value (matches `keyUsage`); None when the blob is
+    /// outer-wrapped or has no key check
+    pub key_fingerprint: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyUsageDiagnostics {
+    pub keys: Vec<KeyUsageEntryDiagnostics>,
+    pub tampered_keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyUsageEntryDiagnostics {
+    /// Fingerprint of the key-check value
+    pub key_fingerprint: String,
+    pub sign_count: u32,
+    pub last_used_ms: f64,
+    pub last_receiver_category: String,
+    pub verified: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheDiagnostics {
+    pub completed_requests: usize,
+    pub confirmation_nonces: usize,
+    pub recent_receiver_caches: usize,
+    pub confirmed_intents: usize,
+    pub consumed_approvals: usize,
+    pub consumed_signing_intents: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestLimiterDiagnostics {
+    pub max_concurrent: usize,
+    pub max_queued: usize,
+    pub running: usize,
+    pub queued: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingRequestDiagnostics {
+    pub request_id: String,
+    pub operation: String,
+    pub phase: String,
+    pub admitted_at_ms: f64,
+}
+
+/// An audit entry without its detail text or RPC endpoints
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntryDiagnostics {
+    pub timestamp_ms: f64,
+    pub account_id: String,
+    pub request_id: String,
+    pub operation: String,
+    pub outcome: String,
+    pub has_detail: bool,
+}
+
+/// Short form of a key ID: the first 8 bytes of its SHA-256, in hex pairs of bytes
+fn id_fingerprint(id: &str) -> String {
+    Sha256::digest(id.as_bytes())[..8]
+        .chunks(2)
+        .map(|pair| format!("{:02x}{:02x}", pair[0], pair[1]))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn origin_of(url: Option<&str>) -> Option<String> {
+    url.map(|url| rpc_origin(url).unwrap_or_else(|| "(invalid)".to_string()))
+}
+
+fn policy_diagnostics(policy: &WorkerPolicy) -> PolicyDiagnostics {
+    let limits = state::message_size_limits();
+    let mut elevated_operations: Vec<String> = policy
+        .elevated_operations
+        .iter()
+        .map(|op| json!(op).as_str().unwrap_or_default().to_string())
+        .collect();
+    elevated_operations.sort();
+    elevated_operations.dedup();
+    let mut prf_fallback_schemes = policy.prf_fallback_schemes.clone();
+    prf_fallback_schemes.sort();
+    PolicyDiagnostics {
+        pre_sign_hook: policy.pre_sign_hook,
+        post_sign_hook: policy.post_sign_hook,
+        hook_timeout_ms: policy.hook_timeout_ms,
+        average_block_time_ms: policy.average_block_time_ms,
+        indexer_origin: origin_of(policy.indexer_url.as_deref()),
+        strict_parsing: policy.strict_parsing,
+        allowed_rpc_origins: ListDiagnostics::new(policy.allowed_rpc_origins.clone()),
+        max_signing_intent_ttl_ms: policy.max_signing_intent_ttl_ms,
+        relayer_origin: origin_of(policy.relayer.as_ref().map(|r| r.url.as_str())),
+        deposit_escalation_yocto: policy.deposit_escalation_yocto.clone(),
+        low_allowance_warning_yocto: policy.low_allowance_warning_yocto.clone(),
+        prf_fallback_schemes,
+        telemetry_origin: origin_of(policy.telemetry.as_ref().map(|t| t.endpoint.as_str())),
+        account_overrides: ListDiagnostics::new(
+            policy
+                .account_overrides
+                .keys()
+                .map(|id| id.as_str().to_string())
+                .collect(),
+        ),
+        args_schemas: ListDiagnostics::new(
+            policy
+                .args_schemas
+                .iter()
+                .map(|s| format!("{}.{}", s.contract_id, s.method_name))
+                .collect(),
+        ),
+        require_encrypted_secrets: state::require_encrypted_secrets(),
+        message_size_limits: MessageSizeLimitsDiagnostics {
+            default_bytes: limits.default_bytes,
+            deploy_bytes: limits.deploy_bytes,
+            by_type: limits.by_type.into_iter().collect(),
+        },
+        fail_on_deprecated: policy.fail_on_deprecated,
+        block_full_access_add_key: policy.block_full_access_add_key,
+        max_arg_preview_bytes: policy.max_arg_preview_bytes,
+        max_summary_blocks: policy.max_summary_blocks,
+        elevated_operations,
+        elevation_max_age_ms: policy.elevation_max_age_ms,
+        max_session_duration_ms: state::session().max_duration_ms,
+        required_approvals: policy.required_approvals,
+        trusted_confirmation_origin: state::trusted_confirmation_origin(),
+        allowed_redirect_origins: ListDiagnostics::new(policy.allowed_redirect_origins.clone()),
+    }
+}
+
+fn key_blob_diagnostics(blob: &EncryptedKeyBlob) -> KeyBlobDiagnostics {
+    // The key check is only readable from an unwrapped blob
+    let key_fingerprint = base64_url_decode(&blob.encrypted_private_key_data)
+        .ok()
+        .filter(|data| blob.outer_wrap.is_none() && split_outer_wrap(data).is_none())
+        .and_then(|data| key_usage::key_usage_id(&data))
+        .map(|key_id| id_fingerprint(&key_id));
+    KeyBlobDiagnostics {
+        blob_id: blob.blob_id.clone(),
+        version: blob.version.unwrap_or(ENCRYPTED_BLOB_VERSION_V1),
+        outer_wrap: blob.outer_wrap.clone(),
+        key_fingerprint,
+    }
+}
+
+fn account_entry<'a>(
+    accounts: &'a mut BTreeMap<String, AccountDiagnostics>,
+    account_id: &str,
+) -> &'a mut AccountDiagnostics {
+    accounts
+        .entry(account_id.to_string())
+        .or_insert_with(|| AccountDiagnostics {
+            account_id: account_id.to_string(),
+            signed_requests: 0,
+            failed_requests: 0,
+            nonce_reservations: 0,
+            audit_entries: 0,
+            last_active_ms: 0.0,
+            session_keys: Vec::new(),
+            key_blobs: Vec::new(),
+        })
+}
+
+fn account_diagnostics(key_blobs: &[EncryptedKeyBlob]) -> Vec<AccountDiagnostics> {
+    let mut accounts: BTreeMap<String, AccountDiagnostics> = BTreeMap::new();
+    for info in state::list_active_accounts() {
+        let account = account_entry(&mut accounts, info.account_id.as_str());
+        account.signed_requests = info.signed_requests;
+        account.failed_requests = info.failed_requests;
+        account.nonce_reservations = info.nonce_reservations;
+        account.audit_entries = info.audit_entries;
+        account.last_active_ms = info.last_active_ms;
+    }
+    for key in state::session_key_infos() {
+        let fingerprint = parse_public_key(&key.public_key)
+            .map(|bytes| key_fingerprint(&bytes))
+            .unwrap_or_else(|_| id_fingerprint(&key.public_key));
+        account_entry(&mut accounts, &key.near_account_id)
+            .session_keys
+            .push(SessionKeyDiagnostics {
+                fingerprint,
+                receiver_id: key.receiver_id,
+                expires_at_ms: key.expires_at_ms,
+            });
+    }
+    for blob in key_blobs {
+        let account_id = blob.near_account_id.as_deref().unwrap_or("(unbound)");
+        account_entry(&mut accounts, account_id)
+            .key_blobs
+            .push(key_blob_diagnostics(blob));
+    }
+    accounts
+        .into_values()
+        .map(|mut account| {
+            account.session_keys.sort_by(|a, b| {
+                a.fingerprint
+                    .cmp(&b.fingerprint)
+                    .then_with(|| a.receiver_id.cmp(&b.receiver_id))
+            });
+            account.key_blobs.sort_by(|a, b| {
+                (&a.blob_id, &a.key_fingerprint).cmp(&(&b.blob_id, &b.key_fingerprint))
+            });
+            account
+        })
+        .collect()
+}
+
+fn key_usage_diagnostics() -> KeyUsageDiagnostics {
+    let stats = key_usage::stats();
+    let mut keys: Vec<KeyUsageEntryDiagnostics> = stats
+        .keys
+        .iter()
+        .map(|stat| KeyUsageEntryDiagnostics {
+            key_fingerprint: id_fingerprint(&stat.key_id),
+            sign_count: stat.sign_count,
+            last_used_ms: stat.last_used_ms,
+            last_receiver_category: stat.last_receiver_category.clone(),
+            verified: stat.verified,
+        })
+        .collect();
+    keys.sort_by(|a, b| a.key_fingerprint.cmp(&b.key_fingerprint));
+    let mut tampered_keys: Vec<String> = stats
+        .tampered_key_ids
+        .iter()
+        .map(|key_id| id_fingerprint(key_id))
+        .collect();
+    tampered_keys.sort();
+    KeyUsageDiagnostics {
+        keys,
+        tampered_keys,
+    }
+}
+
+/// Builds the document from the worker's state, `policy` (the effective WorkerPolicy as the
+/// caller sends it with requests) and `key_blobs` (the stored blobs, for versions and
+/// fingerprints; they are not decrypted)
+pub fn collect(policy: &WorkerPolicy, key_blobs: &[EncryptedKeyBlob]) -> Diagnostics {
+    let caches = state::cache_counts();
+    let limits = state::request_limits();
+    let mut pending_requests: Vec<PendingRequestDiagnostics> = state::list_pending_requests()
+        .into_iter()
+        .map(|r| PendingRequestDiagnostics {
+            request_id: r.request_id,
+            operation: r.operation,
+            phase: r.phase.as_str().to_string(),
+            admitted_at_ms: r.admitted_at_ms,
+        })
+        .collect();
+    pending_requests.sort_by(|a, b| {
+        a.admitted_at_ms
+            .total_cmp(&b.admitted_at_ms)
+            .then_with(|| a.request_id.cmp(&b.request_id))
+    });
+    let count_phase = |phase: RequestPhase| {
+        pending_requests
+            .iter()
+            .filter(|r| r.phase == phase.as_str())
+            .count()
+    };
+    Diagnostics {
+        schema_version: DIAGNOSTICS_SCHEMA_VERSION,
+        build: WorkerInfo::current(),
+        compiled_features: FEATURES
+            .iter()
+            .map(|(name, compiled)| FeatureDiagnostics {
+                name: name.to_string(),
+                compiled: *compiled,
+            })
+            .collect(),
+        policy: policy_diagnostics(policy),
+        accounts: account_diagnostics(key_blobs),
+        key_usage: key_usage_diagnostics(),
+        caches: CacheDiagnostics {
+            completed_requests: caches.completed_requests,
+            confirmation_nonces: state::state_counts().confirmation_nonces,
+            recent_receiver_caches: caches.recent_receiver_caches,
+            confirmed_intents: caches.confirmed_intents,
+            consumed_approvals: caches.consumed_approvals,
+            consumed_signing_intents: caches.consumed_signing_intents,
+        },
+        request_limiter: RequestLimiterDiagnostics {
+            max_concurrent: limits.max_concurrent,
+            max_queued: limits.max_queued,
+            running: count_phase(RequestPhase::Running),
+            queued: count_phase(RequestPhase::Queued),
+        },
+        session: session_duration::status(state::now_ms()),
+        pending_requests,
+        recent_log: state::recent_audit_entries(DIAGNOSTICS_LOG_ENTRIES)
+            .into_iter()
+            .map(|(account_id, entry)| LogEntryDiagnostics {
+                timestamp_ms: entry.timestamp_ms,
+                account_id: account_id.as_str().to_string(),
+                request_id: entry.request_id,
+                operation: entry.operation,
+                outcome: entry.outcome,
+                has_detail: entry.detail.is_some(),
+            })
+            .collect(),
+    }
+}
@@ -0,0 +1,227 @@
+// === DUAL CONTROL ===
+// Two people sharing an account, each with their own passkey, can require both to approve a
+// transaction before the worker signs it, without a multisig contract. Under
+// WorkerPolicy.requiredApprovals 2 (settable per account), SignTransactionsWithActions runs the
+// confirmation and contract verification as usual, then, instead of signing, keeps a pending
+// approval of the batch and answers AwaitingSecondApproval with an approval token. The second
+// approver sends the same batch with that token: its credential must be verified the same way,
+// come from another credential ID, and approve the same digest within
+// DUAL_CONTROL_APPROVAL_TTL_MS of the first approval. Only then is the key decrypted.
+//
+// The pending approval is the token itself: the first approval's CBOR bytes, MACed under the
+// worker's persisted MAC key (state.rs), which the TS layer holds and sends back with the
+// second approval, so whichever later worker receives it can verify it. A token is bound to the
+// account and session epoch (see session_duration.rs) it was issued in, and its approval ID is
+// consumed by the second approval that signs; consumed IDs travel in the worker state record
+// until the token expires. A second approval with the wrong credential or batch leaves the
+// first one pending. The audit entry of the signing records both credentials' fingerprints.
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::actions::ActionParams;
+use crate::config::{DUAL_CONTROL_APPROVAL_MAC_DOMAIN, DUAL_CONTROL_APPROVAL_TTL_MS};
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::{ConfigError, DualControlError};
+use crate::handlers::confirm_tx_details::compute_intent_digest_from_js_inputs;
+use crate::session_duration;
+use crate::state;
+use crate::types::handlers::WorkerPolicy;
+use crate::types::AccountId;
+
+/// A first approval waiting for the second one, as its approval token records it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingApproval {
+    pub near_account_id: String,
+    /// Random ID, consumed by the second approval that signs
+    pub approval_id: String,
+    /// Digest of the approved receivers and actions (`approval_digest`)
+    pub tx_digest: String,
+    /// rawId of the first approver's credential
+    pub credential_id: String,
+    pub fingerprint: String,
+    /// Request ID of the first approval's confirmation
+    pub request_id: String,
+    pub expires_at_ms: f64,
+    /// Session epoch of the first approval
+    pub session_epoch: u32,
+}
+
+/// `awaitingSecondApproval` of a TransactionSignResult
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AwaitingSecondApproval {
+    pub approval_token: String,
+    pub expires_at_ms: f64,
+    /// Fingerprint of the credential that made the first approval
+    pub first_approver: String,
+}
+
+/// Approvals a batch of the policy's account needs: 1 (the default) or 2
+pub fn required_approvals(policy: Option<&WorkerPolicy>) -> Result<u8, ConfigError> {
+    match policy.and_then(|p| p.required_approvals).unwrap_or(1) {
+        approvals @ (1 | 2) => Ok(approvals),
+        approvals => Err(ConfigError::InvalidValue {
+            field: "requiredApprovals",
+            reason: format!("must be 1 or 2, got {}", approvals),
+        }),
+    }
+}
+
+/// The digest both approvals must be made for (the receivers and actions, without the
+/// confirmation's annotations, which can change between the two prompts)
+pub fn approval_digest(
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+) -> Result<String, String> {
+    compute_intent_digest_from_js_inputs(receivers_and_actions, None)
+}
+
+/// Short form of a credential ID for the audit log: the first 8 bytes of the SHA-256 of its
+/// base64url form, in hex pairs of bytes (e.g. "3f2a:91c0:07be:5d44")
+pub fn credential_fingerprint(credential_id: &str) -> String {
+    Sha256::digest(credential_id.as_bytes())[..8]
+        .chunks(2)
+        .map(|pair| format!("{:02x}{:02x}", pair[0], pair[1]))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// The approval's exact CBOR bytes and the worker's MAC over them
+#[derive(Serialize, Deserialize)]
+struct SignedApproval {
+    #[serde(with = "serde_bytes")]
+    approval: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    mac: Vec<u8>,
+}
+
+fn approval_mac(key: &[u8; 32], approval_cbor: &[u8]) -> Hmac<Sha256> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts any key size");
+    mac.update(DUAL_CONTROL_APPROVAL_MAC_DOMAIN);
+    mac.update(approval_cbor);
+    mac
+}
+
+fn generate_approval_id() -> Result<String, String> {
+    let mut id = [0u8; 16];
+    getrandom::getrandom(&mut id).map_err(|e| format!("Failed to generate approval ID: {}", e))?;
+    Ok(base64_url_encode(&id))
+}
+
+/// MACs the approval under the worker's MAC key and returns the base64url approval token
+fn seal_approval(approval: &PendingApproval) -> Result<String, String> {
+    let mut approval_cbor = Vec::new();
+    ciborium::into_writer(approval, &mut approval_cbor)
+        .map_err(|e| format!("Failed to encode approval: {}", e))?;
+    let mac = approval_mac(&state::signing_intent_key()?, &approval_cbor)
+        .finalize()
+        .into_bytes()
+        .to_vec();
+
+    let mut envelope = Vec::new();
+    ciborium::into_writer(
+        &SignedApproval {
+            approval: approval_cbor,
+            mac,
+        },
+        &mut envelope,
+    )
+    .map_err(|e| format!("Failed to encode approval token: {}", e))?;
+    Ok(base64_url_encode(&envelope))
+}
+
+/// The approval an approval token records, if its MAC verifies under the worker's MAC key and
+/// it was issued for `account`
+fn open_approval(account: &AccountId, approval_token: &str) -> Option<PendingApproval> {
+    let envelope = base64_url_decode(approval_token).ok()?;
+    let signed: SignedApproval = ciborium::from_reader(envelope.as_slice()).ok()?;
+    approval_mac(&state::signing_intent_key().ok()?, &signed.approval)
+        .verify_slice(&signed.mac)
+        .ok()?;
+    let approval: PendingApproval = ciborium::from_reader(signed.approval.as_slice()).ok()?;
+    (approval.near_account_id == account.0).then_some(approval)
+}
+
+/// Records the first approval of `tx_digest`, made with `credential_id` in the confirmation
+/// `request_id`, in the token the second approval must carry
+pub fn record_first_approval(
+    account: &AccountId,
+    request_id: &str,
+    tx_digest: &str,
+    credential_id: &str,
+) -> Result<AwaitingSecondApproval, String> {
+    let approval = PendingApproval {
+        near_account_id: account.0.clone(),
+        approval_id: generate_approval_id()?,
+        tx_digest: tx_digest.to_string(),
+        credential_id: credential_id.to_string(),
+        fingerprint: credential_fingerprint(credential_id),
+        request_id: request_id.to_string(),
+        expires_at_ms: state::now_ms() + DUAL_CONTROL_APPROVAL_TTL_MS as f64,
+        session_epoch: state::session_epoch(),
+    };
+    Ok(AwaitingSecondApproval {
+        approval_token: seal_approval(&approval)?,
+        expires_at_ms: approval.expires_at_ms,
+        first_approver: approval.fingerprint,
+    })
+}
+
+/// The account's pending approval in `approval_token`, if it is live, not yet consumed and for
+/// `tx_digest`. Checked before the second approver is prompted; nothing is consumed.
+pub fn check_pending_approval(
+    account: &AccountId,
+    approval_token: &str,
+    tx_digest: &str,
+    now_ms: f64,
+) -> Result<PendingApproval, DualControlError> {
+    let approval = open_approval(account, approval_token)
+        .filter(|a| !state::is_approval_consumed(&a.approval_id))
+        .ok_or(DualControlError::TokenInvalid)?;
+    if approval.expires_at_ms <= now_ms {
+        return Err(DualControlError::TokenExpired {
+            expires_at_ms: approval.expires_at_ms,
+        });
+    }
+    session_duration::check_epoch("approval token", approval.session_epoch)
+        .map_err(DualControlError::Session)?;
+    if approval.tx_digest != tx_digest {
+        return Err(DualControlError::DigestMismatch {
+            approved: approval.tx_digest,
+            requested: tx_digest.to_string(),
+        });
+    }
+    Ok(approval)
+}
+
+/// Completes the pending approval in `approval_token` with a second approval of
+/// `tx_digest` made with `credential_id`, consuming it. Returns the first approval.
+pub fn complete_approval(
+    account: &AccountId,
+    approval_token: &str,
+    tx_digest: &str,
+    credential_id: &str,
+    now_ms: f64,
+) -> Result<PendingApproval, DualControlError> {
+    let approval = check_pending_approval(account, approval_token, tx_digest, now_ms)?;
+    if approval.credential_id == credential_id {
+        return Err(DualControlError::SameCredential {
+            fingerprint: approval.fingerprint,
+        });
+    }
+    if !state::consume_approval(&approval.approval_id, approval.expires_at_ms) {
+        return Err(DualControlError::TokenInvalid);
+    }
+    Ok(approval)
+}
+
+/// Audit detail recording both approvers, first approver first
+pub fn approvals_detail(first: &PendingApproval, second_credential_id: &str) -> String {
+    format!(
+        "approvals: {}, {}",
+        first.fingerprint,
+        credential_fingerprint(second_credential_id)
+    )
+}
@@ -0,0 +1,256 @@
+// === ELEVATED OPERATIONS ===
+// Some operations hand the account over: exporting its private key, or adding a full access
+// key (which is also how a key is rotated). They need a passkey assertion made moments ago,
+// not a PRF output collected for some earlier request. ExportPrivateKey and AddFullAccessKey
+// are always elevated; WorkerPolicy.elevatedOperations adds to them, and
+// WorkerPolicy.elevationMaxAgeMs sets how recent the assertion must be (default
+// DEFAULT_ELEVATION_MAX_AGE_MS). Older evidence fails with ElevationRequired, and the TS layer
+// runs a fresh assertion.
+//
+// Freshness is measured on the worker's clock. A confirmation response carries
+// `credential_collected_at_ms`, the main thread's time of the assertion, which the worker only
+// accepts between the moment it issued the confirmation nonce the response answers and the
+// moment the response arrived (see `collection_time`). A credential collected before the
+// prompt, such as an earlier assertion replayed, counts as no assertion; one without a
+// timestamp counts as collected when the prompt was issued.
+//
+// DecryptPrivateKeyWithPrf receives its PRF output directly instead of through a confirmation,
+// so the request carries the assertion itself, made for an elevation challenge the worker
+// issued (IssueElevationChallenge). The challenge is a token MACed under the worker's persisted
+// MAC key (state.rs) recording the account and when it was issued; its base64url form is the
+// assertion's WebAuthn challenge, so the assertion was made after that moment, which counts as
+// its collection time. Each challenge is accepted once, in whichever later worker receives it.
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::actions::ActionParams;
+use crate::config::{DEFAULT_ELEVATION_MAX_AGE_MS, ELEVATION_CHALLENGE_MAC_DOMAIN};
+use crate::confirmation_blocks::is_full_access_key;
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::ElevationError;
+use crate::types::handlers::WorkerPolicy;
+
+/// An operation that may need a fresh passkey assertion
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ElevatedOperation {
+    /// Revealing a private key (ExportNearKeypairUI, DecryptPrivateKeyWithPrf)
+    ExportPrivateKey,
+    /// AddKey with a permission other than FunctionCall
+    AddFullAccessKey,
+    DeleteKey,
+    DeleteAccount,
+    DeployContract,
+}
+
+/// Operations elevated whatever the policy says
+pub const DEFAULT_ELEVATED_OPERATIONS: [ElevatedOperation; 2] = [
+    ElevatedOperation::ExportPrivateKey,
+    ElevatedOperation::AddFullAccessKey,
+];
+
+impl ElevatedOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ElevatedOperation::ExportPrivateKey => "exportPrivateKey",
+            ElevatedOperation::AddFullAccessKey => "addFullAccessKey",
+            ElevatedOperation::DeleteKey => "deleteKey",
+            ElevatedOperation::DeleteAccount => "deleteAccount",
+            ElevatedOperation::DeployContract => "deployContract",
+        }
+    }
+
+    /// The operation an action performs, if it is one that can be elevated
+    pub fn of_action(action: &ActionParams) -> Option<ElevatedOperation> {
+        match action {
+            ActionParams::AddKey { access_key, .. } if is_full_access_key(access_key) => {
+                Some(ElevatedOperation::AddFullAccessKey)
+            }
+            ActionParams::DeleteKey { .. } => Some(ElevatedOperation::DeleteKey),
+            ActionParams::DeleteAccount { .. } => Some(ElevatedOperation::DeleteAccount),
+            ActionParams::DeployContract { .. } => Some(ElevatedOperation::DeployContract),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `operation` needs a fresh assertion under `policy`
+pub fn is_elevated(policy: Option<&WorkerPolicy>, operation: ElevatedOperation) -> bool {
+    DEFAULT_ELEVATED_OPERATIONS.contains(&operation)
+        || policy.is_some_and(|p| p.elevated_operations.contains(&operation))
+}
+
+pub fn max_age_ms(policy: Option<&WorkerPolicy>) -> u32 {
+    policy
+        .and_then(|p| p.elevation_max_age_ms)
+        .unwrap_or(DEFAULT_ELEVATION_MAX_AGE_MS)
+}
+
+/// The elevated operations a batch performs under `policy`, in order of first appearance
+pub fn batch_operations(
+    policy: Option<&WorkerPolicy>,
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+) -> Vec<ElevatedOperation> {
+    let mut operations = Vec::new();
+    for action in receivers_and_actions
+        .iter()
+        .flat_map(|(_, actions)| actions)
+    {
+        match ElevatedOperation::of_action(action) {
+            Some(op) if is_elevated(policy, op) && !operations.contains(&op) => operations.push(op),
+            _ => {}
+        }
+    }
+    operations
+}
+
+/// When a credential delivered in answer to a confirmation nonce issued at `issued_at_ms` was
+/// collected, as far as the worker can vouch for it. `claimed_ms` is the main thread's
+/// timestamp: later than the response's arrival it is capped there, earlier than the nonce it
+/// is refused (None).
+pub fn collection_time(
+    claimed_ms: Option<f64>,
+    issued_at_ms: f64,
+    received_at_ms: f64,
+) -> Option<f64> {
+    match claimed_ms {
+        None => Some(issued_at_ms),
+        Some(claimed) if !claimed.is_finite() || claimed < issued_at_ms => None,
+        Some(claimed) => Some(claimed.min(received_at_ms)),
+    }
+}
+
+/// Refuses `operation` when it is elevated and the assertion collected at `collected_at_ms`
+/// (None: no assertion the worker can vouch for) is older than the policy's window
+pub fn check_elevation(
+    policy: Option<&WorkerPolicy>,
+    operation: ElevatedOperation,
+    collected_at_ms: Option<f64>,
+    now_ms: f64,
+) -> Result<(), ElevationError> {
+    if !is_elevated(policy, operation) {
+        return Ok(());
+    }
+    let max_age_ms = max_age_ms(policy);
+    let age_ms = collected_at_ms.map(|at| (now_ms - at).max(0.0));
+    match age_ms {
+        Some(age_ms) if age_ms <= max_age_ms as f64 => Ok(()),
+        _ => Err(ElevationError {
+            operation: operation.as_str(),
+            max_age_ms,
+            age_ms,
+        }),
+    }
+}
+
+/// `check_elevation` for every elevated operation of a batch
+pub fn check_batch_elevation(
+    policy: Option<&WorkerPolicy>,
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+    collected_at_ms: Option<f64>,
+    now_ms: f64,
+) -> Result<(), ElevationError> {
+    batch_operations(policy, receivers_and_actions)
+        .into_iter()
+        .try_for_each(|op| check_elevation(policy, op, collected_at_ms, now_ms))
+}
+
+/// What an elevation challenge records: an assertion made for it was made after `issued_at_ms`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ElevationChallenge {
+    pub near_account_id: String,
+    /// Random ID, consumed by the request the assertion is presented with
+    pub challenge_id: String,
+    pub issued_at_ms: f64,
+}
+
+/// The challenge's exact CBOR bytes and the worker's MAC over them
+#[derive(Serialize, Deserialize)]
+struct SignedChallenge {
+    #[serde(with = "serde_bytes")]
+    challenge: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    mac: Vec<u8>,
+}
+
+/// The client data of a serialized assertion
+#[derive(Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    ceremony: String,
+    challenge: String,
+}
+
+fn challenge_mac(key: &[u8; 32], challenge_cbor: &[u8]) -> Hmac<Sha256> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts any key size");
+    mac.update(ELEVATION_CHALLENGE_MAC_DOMAIN);
+    mac.update(challenge_cbor);
+    mac
+}
+
+/// MACs the challenge under `key` and returns the base64url token, whose decoded bytes are
+/// the WebAuthn challenge of the assertion
+pub fn issue_elevation_challenge(
+    challenge: &ElevationChallenge,
+    key: &[u8; 32],
+) -> Result<String, String> {
+    let mut challenge_cbor = Vec::new();
+    ciborium::into_writer(challenge, &mut challenge_cbor)
+        .map_err(|e| format!("Failed to encode elevation challenge: {}", e))?;
+    let mac = challenge_mac(key, &challenge_cbor)
+        .finalize()
+        .into_bytes()
+        .to_vec();
+
+    let mut envelope = Vec::new();
+    ciborium::into_writer(
+        &SignedChallenge {
+            challenge: challenge_cbor,
+            mac,
+        },
+        &mut envelope,
+    )
+    .map_err(|e| format!("Failed to encode elevation challenge envelope: {}", e))?;
+    Ok(base64_url_encode(&envelope))
+}
+
+/// The elevation challenge a serialized assertion (`response.clientDataJSON`) was made for,
+/// once its MAC verifies under `key` and it was issued for `near_account_id`. Replay is
+/// checked by the caller (state::consume_elevation_challenge).
+pub fn assertion_challenge(
+    credential: &serde_json::Value,
+    key: &[u8; 32],
+    near_account_id: &str,
+) -> Result<ElevationChallenge, String> {
+    let client_data_json = credential
+        .pointer("/response/clientDataJSON")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Assertion has no response.clientDataJSON".to_string())?;
+    let client_data: ClientData = serde_json::from_slice(&base64_url_decode(client_data_json)?)
+        .map_err(|e| format!("Invalid clientDataJSON: {}", e))?;
+    if client_data.ceremony != "webauthn.get" {
+        return Err(format!("Not an assertion: {}", client_data.ceremony));
+    }
+
+    let envelope = base64_url_decode(&client_data.challenge)?;
+    let signed: SignedChallenge = ciborium::from_reader(envelope.as_slice())
+        .map_err(|_| "The assertion was not made for an elevation challenge".to_string())?;
+    challenge_mac(key, &signed.challenge)
+        .verify_slice(&signed.mac)
+        .map_err(|_| {
+            "Elevation challenge MAC does not verify (tampered, or issued under a wiped key)"
+                .to_string()
+        })?;
+    let challenge: ElevationChallenge = ciborium::from_reader(signed.challenge.as_slice())
+        .map_err(|e| format!("Invalid elevation challenge: {}", e))?;
+    if challenge.near_account_id != near_account_id {
+        return Err(format!(
+            "Elevation challenge was issued for {}, not {}",
+            challenge.near_account_id, near_account_id
+        ));
+    }
+    Ok(challenge)
+}
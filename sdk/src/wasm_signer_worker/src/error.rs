@@ -1,6 +1,10 @@
+use serde::Serialize;
 use std::fmt;
 use wasm_bindgen::JsValue;
 
+use crate::error_codes::{self, ErrorCodeDef};
+use crate::types::wasm_to_json::to_js_value;
+
 // Parse payload error with message name context
 #[derive(Debug)]
 pub struct ParsePayloadError {
@@ -39,6 +43,402 @@ impl From<ParsePayloadError> for JsValue {
     }
 }
 
+/// Stable error codes returned alongside error messages, so the TS layer can tell
+/// user-driven outcomes apart from infrastructure failures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignerErrorCode {
+    /// User dismissed or cancelled the passkey prompt
+    UserDeclined,
+    /// Passkey prompt timed out before the user responded
+    CredentialTimeout,
+    /// Credential collection failed for another reason (e.g. InvalidStateError)
+    CredentialCollectionFailed,
+    /// Registry is full: max concurrent requests running and max queued waiting
+    TooManyPendingRequests,
+    /// A request with the same requestId is already pending
+    DuplicateRequestId,
+    /// Request was cancelled (CancelRequest/WipeAllState) before it started
+    RequestCancelled,
+    /// No session key with this public key is held by the worker
+    SessionKeyNotFound,
+    /// Session key outlived its TTL and was wiped
+    SessionKeyExpired,
+    /// Transaction falls outside the session key's receiver/method permissions
+    SessionKeyPermissionDenied,
+    /// Transaction would exceed the session key's remaining allowance
+    SessionKeyAllowanceExceeded,
+    /// The integrator's pre-sign hook denied the request (or did not answer in time)
+    PreSignHookDenied,
+    /// The VRF challenge is older than the contract's acceptance window
+    ChallengeExpired,
+    /// The message frame's encoding differs from the wire format chosen at Initialize
+    WireFormatMismatch,
+    /// The PRF output does not belong to the credential the key blob was encrypted with
+    WrongCredentialForBlob,
+    /// The PRF output matches the key blob, but its ciphertext is damaged
+    CorruptedCiphertext,
+    /// Decryption failed and the cause cannot be told apart (blobs without a key-check header)
+    DecryptionFailed,
+    /// Strict parsing is on and the payload has a field the worker does not recognise
+    UnknownField,
+    /// Account descriptor is malformed, or its signature does not verify against the given key
+    DescriptorInvalid,
+    /// Account descriptor is past its embedded expiry
+    DescriptorExpired,
+    /// The assertion signature does not verify against the public key forwarded with the credential
+    AssertionSignatureInvalid,
+    /// The idempotency key was already used for a request with a different payload
+    IdempotencyKeyConflict,
+    /// The key blob is wrapped by a WebCrypto key that is not available in this environment
+    OuterWrapKeyUnavailable,
+    /// The user confirmed after the confirmation window (ConfirmationConfig.confirmationTimeoutMs) closed
+    ConfirmationExpired,
+    /// A config or payload built through the wasm-bindgen constructors failed `validate()`
+    InvalidConfig,
+    /// An `rpcOverrides` endpoint is not on one of `WorkerPolicy.allowedRpcOrigins`
+    RpcOriginNotAllowed,
+    /// A startup known-answer test failed; key-handling requests are refused until re-initialized
+    SelfTestFailed,
+    /// The passphrase does not match the one the account bundle was exported with
+    BundlePassphraseInvalid,
+    /// The account bundle ends before its declared length (an incomplete download or copy)
+    BundleTruncated,
+    /// The account bundle was written by a newer release (version above ACCOUNT_BUNDLE_VERSION)
+    BundleVersionUnsupported,
+    /// The account bundle is not a bundle, is damaged, or holds a record that fails validation
+    BundleInvalid,
+    /// The signing intent token is malformed or its MAC does not verify (tampered, or issued
+    /// by a previous worker session)
+    SigningIntentInvalid,
+    /// The signing intent is past its expiry, or older than the WorkerPolicy TTL ceiling
+    SigningIntentExpired,
+    /// The signing intent was already executed
+    SigningIntentReplayed,
+    /// The transactions supplied to ExecuteSigningIntent differ from the confirmed ones
+    SigningIntentPayloadMismatch,
+    /// An attestation object, authenticator data or COSE key does not parse
+    WebAuthnDataMalformed,
+    /// The relayer reports that the account to create already exists
+    RelayerAccountExists,
+    /// The relayer refused the request for a quota or rate limit
+    RelayerQuotaExceeded,
+    /// The relayer refused the request for another reason (invalid registration, auth)
+    RelayerRejected,
+    /// The relayer could not be reached, or failed with a server error
+    RelayerUnavailable,
+    /// The relayer's response does not match the expected schema
+    RelayerResponseInvalid,
+    /// The authenticator returned no PRF output and WorkerPolicy allows no fallback key wrapping
+    PrfUnsupportedByAuthenticator,
+    /// An auto-proceed confirmation arrived without a countdown the worker saw start and run out
+    /// (no CountdownStarted ack, an ack for another digest, or a decision before the timer elapsed)
+    CountdownNotDisplayed,
+    /// No NEAR RPC endpoint answered (network error, CORS, server error on every endpoint)
+    RpcUnreachable,
+    /// The NEAR RPC node timed out; a broadcast transaction may still be included
+    RpcTimeout,
+    /// The transaction nonce is not above the access key's nonce
+    InvalidNonce,
+    /// The NEAR RPC node answered with an error (e.g. UNKNOWN_BLOCK, INVALID_TRANSACTION)
+    RpcRequestFailed,
+    /// The NEAR RPC response has neither a result nor an error, or a result of the wrong shape
+    RpcResponseInvalid,
+    /// The VRF worker connected over the peer port did not answer REQUEST_CHALLENGE
+    PeerChallengeUnavailable,
+    /// A challenge from the peer port was proven with a key other than the session's, or the
+    /// confirmation came back with a different challenge than the one the worker requested
+    PeerChallengeRejected,
+    /// The chain reached a block past the request's validUntilBlockHeight before broadcast
+    DeadlineExceeded,
+    /// The CompleteRegistration resume token is malformed or its MAC does not verify under the
+    /// supplied PRF output
+    RegistrationResumeInvalid,
+    /// The resume token is older than its 10 minute lifetime
+    RegistrationResumeExpired,
+    /// The resume token names another account or credential, or the PRF output derives another key
+    RegistrationResumeMismatch,
+    /// A remote confirmation request or approval is malformed, its signature or encryption does
+    /// not verify, or the digest of its transactions differs from the one it names
+    RemoteConfirmationInvalid,
+    /// The remote confirmation request is past its expiry
+    RemoteConfirmationExpired,
+    /// The remote confirmation request was already approved on this device, or its approval
+    /// was already used to sign
+    RemoteConfirmationReplayed,
+    /// The approval answers a request this worker has no session for, was sealed to another
+    /// session key, or approves other transactions than the session's
+    RemoteConfirmationWrongSession,
+    /// The request type needs a cargo feature this build was compiled without
+    FeatureNotCompiled,
+    /// The signing function-call key's remaining allowance is below the transaction's
+    /// estimated cost
+    InsufficientAllowance,
+    /// The request's signingPublicKey is not an access key of the account, or its function-call
+    /// permission does not cover the batch
+    KeyLacksPermission,
+    /// FunctionCall args fail the JSON Schema registered for their contract and method
+    ArgsSchemaViolation,
+    /// The batch's canonical digest differs from the previewDigest it was previewed with
+    PayloadChangedSincePreview,
+    /// A secret field arrived in plaintext while the session requires sealed secrets
+    PlaintextSecretRefused,
+    /// A sealed secret is malformed, names an unknown field, or does not open with the
+    /// session key (sealed before a rotation, or to another worker)
+    SealedSecretInvalid,
+    /// The stored key's curve does not sign for the requested chain (e.g. EVM signing with an
+    /// Ed25519 key)
+    WrongCurveForChain,
+    /// A redactPaths entry names the receiver, method, deposit or gas of a call
+    CannotRedactCriticalField,
+    /// The message frame is above the size limit of its request type, or declares more bytes
+    /// than it holds
+    MessageTooLarge,
+    /// A conditional batch could not be simulated and fallbackToUnconditional is not set
+    SimulationUnavailable,
+    /// The request took a deprecated path while its WorkerPolicy sets failOnDeprecated
+    DeprecatedUsage,
+    /// The batch adds a full access key while WorkerPolicy sets blockFullAccessAddKey
+    FullAccessAddKeyBlocked,
+    /// The credential was created with a COSE algorithm outside allowedAlgorithms
+    DisallowedAlgorithmNegotiated,
+    /// ComposeCreateSubaccount was asked for an account that is not a direct subaccount of
+    /// the signer
+    NotAChildAccount,
+    /// An elevated operation's passkey assertion is older than the elevation window, or was not
+    /// collected through a worker confirmation
+    ElevationRequired,
+    /// Dual control: the first approval is recorded, the response carries its approval token
+    AwaitingSecondApproval,
+    /// The approval token is unknown to this worker session or belongs to another account
+    ApprovalTokenInvalid,
+    /// The first approval expired (DUAL_CONTROL_APPROVAL_TTL_MS) before the second one
+    ApprovalTokenExpired,
+    /// The second approval's transactions do not hash to the first approval's digest
+    ApprovalDigestMismatch,
+    /// The second approval was made with the first approval's credential
+    SecondApprovalSameCredential,
+    /// A sub-key path is malformed, unhardened or too deep
+    InvalidDerivationPath,
+    /// A view call's contract panicked (or otherwise failed to execute)
+    ContractPanic,
+    /// A confirmation decision lacks a render attestation from the trusted confirmation origin
+    UntrustedConfirmationOrigin,
+    /// A transaction's `dependsOn` forms a cycle or names a later transaction
+    InvalidDependencyGraph,
+    /// QR parts that are not ours, do not fit together, or reassemble to a corrupted payload
+    QrPayloadInvalid,
+    /// The session is past WorkerPolicy.maxSessionDurationMs, or a token was issued in an
+    /// earlier session epoch
+    SessionExpired,
+    /// A redirect callback URL that is not https (javascript:, data:, http: ...)
+    RedirectSchemeNotAllowed,
+    /// A redirect callback URL whose origin is not on WorkerPolicy.allowedRedirectOrigins
+    RedirectOriginNotAllowed,
+    /// A redirect payload past its expiry
+    RedirectPayloadExpired,
+    /// A redirect payload that is malformed, does not verify, or names another callback
+    RedirectPayloadInvalid,
+}
+
+impl SignerErrorCode {
+    /// Every code, for catalog tests
+    #[cfg(test)]
+    pub const ALL: [SignerErrorCode; 88] = [
+        SignerErrorCode::UserDeclined,
+        SignerErrorCode::CredentialTimeout,
+        SignerErrorCode::CredentialCollectionFailed,
+        SignerErrorCode::TooManyPendingRequests,
+        SignerErrorCode::DuplicateRequestId,
+        SignerErrorCode::RequestCancelled,
+        SignerErrorCode::SessionKeyNotFound,
+        SignerErrorCode::SessionKeyExpired,
+        SignerErrorCode::SessionKeyPermissionDenied,
+        SignerErrorCode::SessionKeyAllowanceExceeded,
+        SignerErrorCode::PreSignHookDenied,
+        SignerErrorCode::ChallengeExpired,
+        SignerErrorCode::WireFormatMismatch,
+        SignerErrorCode::WrongCredentialForBlob,
+        SignerErrorCode::CorruptedCiphertext,
+        SignerErrorCode::DecryptionFailed,
+        SignerErrorCode::UnknownField,
+        SignerErrorCode::DescriptorInvalid,
+        SignerErrorCode::DescriptorExpired,
+        SignerErrorCode::AssertionSignatureInvalid,
+        SignerErrorCode::IdempotencyKeyConflict,
+        SignerErrorCode::OuterWrapKeyUnavailable,
+        SignerErrorCode::ConfirmationExpired,
+        SignerErrorCode::InvalidConfig,
+        SignerErrorCode::RpcOriginNotAllowed,
+        SignerErrorCode::SelfTestFailed,
+        SignerErrorCode::BundlePassphraseInvalid,
+        SignerErrorCode::BundleTruncated,
+        SignerErrorCode::BundleVersionUnsupported,
+        SignerErrorCode::BundleInvalid,
+        SignerErrorCode::SigningIntentInvalid,
+        SignerErrorCode::SigningIntentExpired,
+        SignerErrorCode::SigningIntentReplayed,
+        SignerErrorCode::SigningIntentPayloadMismatch,
+        SignerErrorCode::WebAuthnDataMalformed,
+        SignerErrorCode::RelayerAccountExists,
+        SignerErrorCode::RelayerQuotaExceeded,
+        SignerErrorCode::RelayerRejected,
+        SignerErrorCode::RelayerUnavailable,
+        SignerErrorCode::RelayerResponseInvalid,
+        SignerErrorCode::PrfUnsupportedByAuthenticator,
+        SignerErrorCode::CountdownNotDisplayed,
+        SignerErrorCode::RpcUnreachable,
+        SignerErrorCode::RpcTimeout,
+        SignerErrorCode::InvalidNonce,
+        SignerErrorCode::RpcRequestFailed,
+        SignerErrorCode::RpcResponseInvalid,
+        SignerErrorCode::PeerChallengeUnavailable,
+        SignerErrorCode::PeerChallengeRejected,
+        SignerErrorCode::DeadlineExceeded,
+        SignerErrorCode::RegistrationResumeInvalid,
+        SignerErrorCode::RegistrationResumeExpired,
+        SignerErrorCode::RegistrationResumeMismatch,
+        SignerErrorCode::RemoteConfirmationInvalid,
+        SignerErrorCode::RemoteConfirmationExpired,
+        SignerErrorCode::RemoteConfirmationReplayed,
+        SignerErrorCode::RemoteConfirmationWrongSession,
+        SignerErrorCode::FeatureNotCompiled,
+        SignerErrorCode::InsufficientAllowance,
+        SignerErrorCode::KeyLacksPermission,
+        SignerErrorCode::ArgsSchemaViolation,
+        SignerErrorCode::PayloadChangedSincePreview,
+        SignerErrorCode::PlaintextSecretRefused,
+        SignerErrorCode::SealedSecretInvalid,
+        SignerErrorCode::WrongCurveForChain,
+        SignerErrorCode::CannotRedactCriticalField,
+        SignerErrorCode::MessageTooLarge,
+        SignerErrorCode::SimulationUnavailable,
+        SignerErrorCode::DeprecatedUsage,
+        SignerErrorCode::FullAccessAddKeyBlocked,
+        SignerErrorCode::DisallowedAlgorithmNegotiated,
+        SignerErrorCode::NotAChildAccount,
+        SignerErrorCode::ElevationRequired,
+        SignerErrorCode::AwaitingSecondApproval,
+        SignerErrorCode::ApprovalTokenInvalid,
+        SignerErrorCode::ApprovalTokenExpired,
+        SignerErrorCode::ApprovalDigestMismatch,
+        SignerErrorCode::SecondApprovalSameCredential,
+        SignerErrorCode::InvalidDerivationPath,
+        SignerErrorCode::ContractPanic,
+        SignerErrorCode::UntrustedConfirmationOrigin,
+        SignerErrorCode::InvalidDependencyGraph,
+        SignerErrorCode::QrPayloadInvalid,
+        SignerErrorCode::SessionExpired,
+        SignerErrorCode::RedirectSchemeNotAllowed,
+        SignerErrorCode::RedirectOriginNotAllowed,
+        SignerErrorCode::RedirectPayloadExpired,
+        SignerErrorCode::RedirectPayloadInvalid,
+    ];
+
+    /// Catalog entry (src/wasm_shared/error_codes.rs); new codes need one there
+    pub fn definition(&self) -> &'static ErrorCodeDef {
+        match self {
+            SignerErrorCode::UserDeclined => &error_codes::USER_DECLINED,
+            SignerErrorCode::CredentialTimeout => &error_codes::CREDENTIAL_TIMEOUT,
+            SignerErrorCode::CredentialCollectionFailed => &error_codes::CREDENTIAL_COLLECTION_FAILED,
+            SignerErrorCode::TooManyPendingRequests => &error_codes::TOO_MANY_PENDING_REQUESTS,
+            SignerErrorCode::DuplicateRequestId => &error_codes::DUPLICATE_REQUEST_ID,
+            SignerErrorCode::RequestCancelled => &error_codes::REQUEST_CANCELLED,
+            SignerErrorCode::SessionKeyNotFound => &error_codes::SESSION_KEY_NOT_FOUND,
+            SignerErrorCode::SessionKeyExpired => &error_codes::SESSION_KEY_EXPIRED,
+            SignerErrorCode::SessionKeyPermissionDenied => &error_codes::SESSION_KEY_PERMISSION_DENIED,
+            SignerErrorCode::SessionKeyAllowanceExceeded => &error_codes::SESSION_KEY_ALLOWANCE_EXCEEDED,
+            SignerErrorCode::PreSignHookDenied => &error_codes::PRE_SIGN_HOOK_DENIED,
+            SignerErrorCode::ChallengeExpired => &error_codes::CHALLENGE_EXPIRED,
+            SignerErrorCode::WireFormatMismatch => &error_codes::WIRE_FORMAT_MISMATCH,
+            SignerErrorCode::WrongCredentialForBlob => &error_codes::WRONG_CREDENTIAL_FOR_BLOB,
+            SignerErrorCode::CorruptedCiphertext => &error_codes::CORRUPTED_CIPHERTEXT,
+            SignerErrorCode::DecryptionFailed => &error_codes::DECRYPTION_FAILED,
+            SignerErrorCode::UnknownField => &error_codes::UNKNOWN_FIELD,
+            SignerErrorCode::DescriptorInvalid => &error_codes::DESCRIPTOR_INVALID,
+            SignerErrorCode::DescriptorExpired => &error_codes::DESCRIPTOR_EXPIRED,
+            SignerErrorCode::AssertionSignatureInvalid => &error_codes::ASSERTION_SIGNATURE_INVALID,
+            SignerErrorCode::IdempotencyKeyConflict => &error_codes::IDEMPOTENCY_KEY_CONFLICT,
+            SignerErrorCode::OuterWrapKeyUnavailable => &error_codes::OUTER_WRAP_KEY_UNAVAILABLE,
+            SignerErrorCode::ConfirmationExpired => &error_codes::CONFIRMATION_EXPIRED,
+            SignerErrorCode::InvalidConfig => &error_codes::INVALID_CONFIG,
+            SignerErrorCode::RpcOriginNotAllowed => &error_codes::RPC_ORIGIN_NOT_ALLOWED,
+            SignerErrorCode::SelfTestFailed => &error_codes::SELF_TEST_FAILED,
+            SignerErrorCode::BundlePassphraseInvalid => &error_codes::BUNDLE_PASSPHRASE_INVALID,
+            SignerErrorCode::BundleTruncated => &error_codes::BUNDLE_TRUNCATED,
+            SignerErrorCode::BundleVersionUnsupported => &error_codes::BUNDLE_VERSION_UNSUPPORTED,
+            SignerErrorCode::BundleInvalid => &error_codes::BUNDLE_INVALID,
+            SignerErrorCode::SigningIntentInvalid => &error_codes::SIGNING_INTENT_INVALID,
+            SignerErrorCode::SigningIntentExpired => &error_codes::SIGNING_INTENT_EXPIRED,
+            SignerErrorCode::SigningIntentReplayed => &error_codes::SIGNING_INTENT_REPLAYED,
+            SignerErrorCode::SigningIntentPayloadMismatch => &error_codes::SIGNING_INTENT_PAYLOAD_MISMATCH,
+            SignerErrorCode::WebAuthnDataMalformed => &error_codes::WEBAUTHN_DATA_MALFORMED,
+            SignerErrorCode::RelayerAccountExists => &error_codes::RELAYER_ACCOUNT_EXISTS,
+            SignerErrorCode::RelayerQuotaExceeded => &error_codes::RELAYER_QUOTA_EXCEEDED,
+            SignerErrorCode::RelayerRejected => &error_codes::RELAYER_REJECTED,
+            SignerErrorCode::RelayerUnavailable => &error_codes::RELAYER_UNAVAILABLE,
+            SignerErrorCode::RelayerResponseInvalid => &error_codes::RELAYER_RESPONSE_INVALID,
+            SignerErrorCode::PrfUnsupportedByAuthenticator => &error_codes::PRF_UNSUPPORTED_BY_AUTHENTICATOR,
+            SignerErrorCode::CountdownNotDisplayed => &error_codes::COUNTDOWN_NOT_DISPLAYED,
+            SignerErrorCode::RpcUnreachable => &error_codes::RPC_UNREACHABLE,
+            SignerErrorCode::RpcTimeout => &error_codes::RPC_TIMEOUT,
+            SignerErrorCode::InvalidNonce => &error_codes::INVALID_NONCE,
+            SignerErrorCode::RpcRequestFailed => &error_codes::RPC_REQUEST_FAILED,
+            SignerErrorCode::RpcResponseInvalid => &error_codes::RPC_RESPONSE_INVALID,
+            SignerErrorCode::PeerChallengeUnavailable => &error_codes::PEER_CHALLENGE_UNAVAILABLE,
+            SignerErrorCode::PeerChallengeRejected => &error_codes::PEER_CHALLENGE_REJECTED,
+            SignerErrorCode::DeadlineExceeded => &error_codes::DEADLINE_EXCEEDED,
+            SignerErrorCode::RegistrationResumeInvalid => &error_codes::REGISTRATION_RESUME_INVALID,
+            SignerErrorCode::RegistrationResumeExpired => &error_codes::REGISTRATION_RESUME_EXPIRED,
+            SignerErrorCode::RegistrationResumeMismatch => &error_codes::REGISTRATION_RESUME_MISMATCH,
+            SignerErrorCode::RemoteConfirmationInvalid => &error_codes::REMOTE_CONFIRMATION_INVALID,
+            SignerErrorCode::RemoteConfirmationExpired => &error_codes::REMOTE_CONFIRMATION_EXPIRED,
+            SignerErrorCode::RemoteConfirmationReplayed => &error_codes::REMOTE_CONFIRMATION_REPLAYED,
+            SignerErrorCode::RemoteConfirmationWrongSession => &error_codes::REMOTE_CONFIRMATION_WRONG_SESSION,
+            SignerErrorCode::FeatureNotCompiled => &error_codes::FEATURE_NOT_COMPILED,
+            SignerErrorCode::InsufficientAllowance => &error_codes::INSUFFICIENT_ALLOWANCE,
+            SignerErrorCode::KeyLacksPermission => &error_codes::KEY_LACKS_PERMISSION,
+            SignerErrorCode::ArgsSchemaViolation => &error_codes::ARGS_SCHEMA_VIOLATION,
+            SignerErrorCode::PayloadChangedSincePreview => &error_codes::PAYLOAD_CHANGED_SINCE_PREVIEW,
+            SignerErrorCode::PlaintextSecretRefused => &error_codes::PLAINTEXT_SECRET_REFUSED,
+            SignerErrorCode::SealedSecretInvalid => &error_codes::SEALED_SECRET_INVALID,
+            SignerErrorCode::WrongCurveForChain => &error_codes::WRONG_CURVE_FOR_CHAIN,
+            SignerErrorCode::CannotRedactCriticalField => &error_codes::CANNOT_REDACT_CRITICAL_FIELD,
+            SignerErrorCode::MessageTooLarge => &error_codes::MESSAGE_TOO_LARGE,
+            SignerErrorCode::SimulationUnavailable => &error_codes::SIMULATION_UNAVAILABLE,
+            SignerErrorCode::DeprecatedUsage => &error_codes::DEPRECATED_USAGE,
+            SignerErrorCode::FullAccessAddKeyBlocked => &error_codes::FULL_ACCESS_ADD_KEY_BLOCKED,
+            SignerErrorCode::DisallowedAlgorithmNegotiated => &error_codes::DISALLOWED_ALGORITHM_NEGOTIATED,
+            SignerErrorCode::NotAChildAccount => &error_codes::NOT_A_CHILD_ACCOUNT,
+            SignerErrorCode::ElevationRequired => &error_codes::ELEVATION_REQUIRED,
+            SignerErrorCode::AwaitingSecondApproval => &error_codes::AWAITING_SECOND_APPROVAL,
+            SignerErrorCode::ApprovalTokenInvalid => &error_codes::APPROVAL_TOKEN_INVALID,
+            SignerErrorCode::ApprovalTokenExpired => &error_codes::APPROVAL_TOKEN_EXPIRED,
+            SignerErrorCode::ApprovalDigestMismatch => &error_codes::APPROVAL_DIGEST_MISMATCH,
+            SignerErrorCode::SecondApprovalSameCredential => &error_codes::SECOND_APPROVAL_SAME_CREDENTIAL,
+            SignerErrorCode::InvalidDerivationPath => &error_codes::INVALID_DERIVATION_PATH,
+            SignerErrorCode::ContractPanic => &error_codes::CONTRACT_PANIC,
+            SignerErrorCode::UntrustedConfirmationOrigin => &error_codes::UNTRUSTED_CONFIRMATION_ORIGIN,
+            SignerErrorCode::InvalidDependencyGraph => &error_codes::INVALID_DEPENDENCY_GRAPH,
+            SignerErrorCode::QrPayloadInvalid => &error_codes::QR_PAYLOAD_INVALID,
+            SignerErrorCode::SessionExpired => &error_codes::SESSION_EXPIRED,
+            SignerErrorCode::RedirectSchemeNotAllowed => &error_codes::REDIRECT_SCHEME_NOT_ALLOWED,
+            SignerErrorCode::RedirectOriginNotAllowed => &error_codes::REDIRECT_ORIGIN_NOT_ALLOWED,
+            SignerErrorCode::RedirectPayloadExpired => &error_codes::REDIRECT_PAYLOAD_EXPIRED,
+            SignerErrorCode::RedirectPayloadInvalid => &error_codes::REDIRECT_PAYLOAD_INVALID,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.definition().code
+    }
+}
+
+impl fmt::Display for SignerErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 // Custom error type for KDF operations
 #[derive(Debug)]
 pub enum KdfError {
@@ -78,3 +478,1526 @@ impl From<String> for KdfError {
         KdfError::Base64DecodeError(err)
     }
 }
+
+/// Failure decrypting a PRF-encrypted key blob.
+/// Blobs written with a key-check header distinguish a wrong credential from damaged data;
+/// legacy blobs can only report `DecryptionFailed`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlobDecryptError {
+    /// The key-check value derived from the presented PRF output differs from the stored one
+    WrongCredentialForBlob,
+    /// The key matches (or the blob is structurally broken) but the ciphertext does not authenticate
+    CorruptedCiphertext(String),
+    /// Legacy blob without a key-check header failed to decrypt
+    DecryptionFailed(String),
+    /// Bad input unrelated to the blob itself (PRF output encoding, key size, plaintext format)
+    InvalidInput(String),
+    /// The blob has an outer WebCrypto wrap, and the CryptoKey that made it was not provided
+    /// (e.g. a new browser profile) or is a different key
+    OuterWrapKeyUnavailable(String),
+    /// The blob decrypts to a key on another curve than the caller signs with
+    WrongCurve {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// The blob is in a deprecated format and the request sets failOnDeprecated
+    Deprecated(DeprecationError),
+}
+
+impl BlobDecryptError {
+    /// Error code to surface to the TS layer (None for plain input errors)
+    pub fn code(&self) -> Option<SignerErrorCode> {
+        match self {
+            BlobDecryptError::WrongCredentialForBlob => {
+                Some(SignerErrorCode::WrongCredentialForBlob)
+            }
+            BlobDecryptError::CorruptedCiphertext(_) => Some(SignerErrorCode::CorruptedCiphertext),
+            BlobDecryptError::DecryptionFailed(_) => Some(SignerErrorCode::DecryptionFailed),
+            BlobDecryptError::InvalidInput(_) => None,
+            BlobDecryptError::OuterWrapKeyUnavailable(_) => {
+                Some(SignerErrorCode::OuterWrapKeyUnavailable)
+            }
+            BlobDecryptError::WrongCurve { .. } => Some(SignerErrorCode::WrongCurveForChain),
+            BlobDecryptError::Deprecated(e) => Some(e.code()),
+        }
+    }
+}
+
+impl fmt::Display for BlobDecryptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BlobDecryptError::WrongCredentialForBlob => write!(
+                f,
+                "{}: key blob was encrypted with a different passkey",
+                SignerErrorCode::WrongCredentialForBlob
+            ),
+            BlobDecryptError::CorruptedCiphertext(e) => {
+                write!(f, "{}: {}", SignerErrorCode::CorruptedCiphertext, e)
+            }
+            BlobDecryptError::DecryptionFailed(e) => {
+                write!(f, "{}: {}", SignerErrorCode::DecryptionFailed, e)
+            }
+            BlobDecryptError::InvalidInput(e) => write!(f, "{}", e),
+            BlobDecryptError::OuterWrapKeyUnavailable(e) => write!(
+                f,
+                "{}: {}; link this device again from a device that has access",
+                SignerErrorCode::OuterWrapKeyUnavailable,
+                e
+            ),
+            BlobDecryptError::WrongCurve { expected, found } => write!(
+                f,
+                "{}: the stored key is {}, this chain signs with {}",
+                SignerErrorCode::WrongCurveForChain,
+                found,
+                expected
+            ),
+            BlobDecryptError::Deprecated(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// A high-risk action refused before the user is prompted (see action_firewall.rs)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionFirewallError {
+    /// A full access AddKey while WorkerPolicy sets blockFullAccessAddKey
+    FullAccessAddKeyBlocked {
+        receiver_id: String,
+        public_key: String,
+    },
+    /// A full access key the confirmation cannot show a fingerprint for
+    KeyNotFingerprintable { public_key: String, reason: String },
+}
+
+impl ActionFirewallError {
+    /// Error code to surface to the TS layer (None for malformed keys)
+    pub fn code(&self) -> Option<SignerErrorCode> {
+        match self {
+            ActionFirewallError::FullAccessAddKeyBlocked { .. } => {
+                Some(SignerErrorCode::FullAccessAddKeyBlocked)
+            }
+            ActionFirewallError::KeyNotFingerprintable { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for ActionFirewallError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ActionFirewallError::FullAccessAddKeyBlocked {
+                receiver_id,
+                public_key,
+            } => write!(
+                f,
+                "{}: adding full access key {} to {} is blocked by workerPolicy.blockFullAccessAddKey",
+                SignerErrorCode::FullAccessAddKeyBlocked,
+                public_key,
+                receiver_id
+            ),
+            ActionFirewallError::KeyNotFingerprintable { public_key, reason } => write!(
+                f,
+                "Full access key {} cannot be shown with a fingerprint: {}",
+                public_key, reason
+            ),
+        }
+    }
+}
+
+/// A registration credential whose algorithm cannot be accepted (see credential_options.rs)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlgorithmNegotiationError {
+    /// The credential public key's COSE algorithm is not one of the algorithms offered
+    Disallowed { alg: i64, allowed: Vec<i32> },
+    /// allowedAlgorithms is set but the attestation has no readable credential algorithm
+    Unreadable { reason: String },
+}
+
+impl AlgorithmNegotiationError {
+    /// Error code to surface to the TS layer (None for unreadable attestations)
+    pub fn code(&self) -> Option<SignerErrorCode> {
+        match self {
+            AlgorithmNegotiationError::Disallowed { .. } => {
+                Some(SignerErrorCode::DisallowedAlgorithmNegotiated)
+            }
+            AlgorithmNegotiationError::Unreadable { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for AlgorithmNegotiationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AlgorithmNegotiationError::Disallowed { alg, allowed } => write!(
+                f,
+                "{}: the credential was created with COSE algorithm {}, allowed: {:?}",
+                SignerErrorCode::DisallowedAlgorithmNegotiated,
+                alg,
+                allowed
+            ),
+            AlgorithmNegotiationError::Unreadable { reason } => write!(
+                f,
+                "Cannot check the credential algorithm against allowedAlgorithms: {}",
+                reason
+            ),
+        }
+    }
+}
+
+/// A subaccount ComposeCreateSubaccount refuses to compose (see subaccount.rs)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubaccountError {
+    /// The account would not be a direct subaccount of the signer: the signer is not the
+    /// parent, or the prefix holds a dot
+    NotAChildAccount {
+        account_id: String,
+        signer_id: String,
+    },
+    /// An input that does not form a valid account ID, amount or public key
+    Invalid { field: &'static str, reason: String },
+}
+
+impl SubaccountError {
+    /// Error code to surface to the TS layer (None for malformed inputs)
+    pub fn code(&self) -> Option<SignerErrorCode> {
+        match self {
+            SubaccountError::NotAChildAccount { .. } => Some(SignerErrorCode::NotAChildAccount),
+            SubaccountError::Invalid { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for SubaccountError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SubaccountError::NotAChildAccount {
+                account_id,
+                signer_id,
+            } => write!(
+                f,
+                "{}: {} is not a direct subaccount of {}, which signs its creation",
+                SignerErrorCode::NotAChildAccount,
+                account_id,
+                signer_id
+            ),
+            SubaccountError::Invalid { field, reason } => write!(f, "Invalid {}: {}", field, reason),
+        }
+    }
+}
+
+/// An elevated operation refused for want of a fresh passkey assertion (see elevation.rs)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElevationError {
+    pub operation: &'static str,
+    /// The elevation window the assertion had to fall in
+    pub max_age_ms: u32,
+    /// Age of the latest assertion (None: no assertion the worker can vouch for)
+    pub age_ms: Option<f64>,
+}
+
+impl ElevationError {
+    pub fn code(&self) -> SignerErrorCode {
+        SignerErrorCode::ElevationRequired
+    }
+}
+
+impl fmt::Display for ElevationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} needs a passkey assertion from the last {} ms (maxAgeMs); ",
+            SignerErrorCode::ElevationRequired,
+            self.operation,
+            self.max_age_ms
+        )?;
+        match self.age_ms {
+            Some(age_ms) => write!(f, "the latest is {} ms old", age_ms.round()),
+            None => f.write_str("none was collected through a worker confirmation"),
+        }
+    }
+}
+
+/// Refusal of a second approval under WorkerPolicy.requiredApprovals 2 (see dual_control.rs)
+#[derive(Debug, Clone, PartialEq)]
+pub enum DualControlError {
+    /// No pending approval of the account has this token
+    TokenInvalid,
+    /// The first approval expired at `expires_at_ms`
+    TokenExpired { expires_at_ms: f64 },
+    /// The batch's digest is not the one the first approval was made for
+    DigestMismatch { approved: String, requested: String },
+    /// Both approvals come from the credential with this fingerprint
+    SameCredential { fingerprint: String },
+    /// The first approval was made in an earlier session epoch
+    Session(SessionError),
+}
+
+impl DualControlError {
+    pub fn code(&self) -> SignerErrorCode {
+        match self {
+            DualControlError::TokenInvalid => SignerErrorCode::ApprovalTokenInvalid,
+            DualControlError::TokenExpired { .. } => SignerErrorCode::ApprovalTokenExpired,
+            DualControlError::DigestMismatch { .. } => SignerErrorCode::ApprovalDigestMismatch,
+            DualControlError::SameCredential { .. } => {
+                SignerErrorCode::SecondApprovalSameCredential
+            }
+            DualControlError::Session(e) => e.code(),
+        }
+    }
+}
+
+impl fmt::Display for DualControlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DualControlError::TokenInvalid => write!(
+                f,
+                "{}: no first approval of this account has this approval token",
+                self.code()
+            ),
+            DualControlError::TokenExpired { expires_at_ms } => write!(
+                f,
+                "{}: the first approval expired at {}",
+                self.code(),
+                expires_at_ms
+            ),
+            DualControlError::DigestMismatch {
+                approved,
+                requested,
+            } => write!(
+                f,
+                "{}: the first approval was for digest {}, this batch is {}",
+                self.code(),
+                approved,
+                requested
+            ),
+            DualControlError::SameCredential { fingerprint } => write!(
+                f,
+                "{}: credential {} already made the first approval",
+                self.code(),
+                fingerprint
+            ),
+            DualControlError::Session(e) => e.fmt(f),
+        }
+    }
+}
+
+/// Refusal under WorkerPolicy.maxSessionDurationMs (see session_duration.rs)
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionError {
+    /// The session that started at `started_at_ms` expired at `expired_at_ms`; only a
+    /// credential collected since then starts a new one
+    Expired {
+        started_at_ms: f64,
+        expired_at_ms: f64,
+    },
+    /// A signing intent or approval token from an earlier session epoch
+    StaleEpoch {
+        token: &'static str,
+        issued_in: u32,
+        current: u32,
+    },
+}
+
+impl SessionError {
+    pub fn code(&self) -> SignerErrorCode {
+        SignerErrorCode::SessionExpired
+    }
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SessionError::Expired {
+                started_at_ms,
+                expired_at_ms,
+            } => write!(
+                f,
+                "{}: the session started at {} expired at {}; a fresh passkey assertion starts a new one",
+                self.code(),
+                started_at_ms,
+                expired_at_ms
+            ),
+            SessionError::StaleEpoch {
+                token,
+                issued_in,
+                current,
+            } => write!(
+                f,
+                "{}: the {} was issued in session epoch {}, which ended (now epoch {})",
+                self.code(),
+                token,
+                issued_in,
+                current
+            ),
+        }
+    }
+}
+
+/// A sub-key derivation path that is not a hardened SLIP-0010 ed25519 path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivationPathError {
+    pub path: String,
+    pub reason: String,
+}
+
+impl DerivationPathError {
+    pub fn code(&self) -> SignerErrorCode {
+        SignerErrorCode::InvalidDerivationPath
+    }
+}
+
+impl fmt::Display for DerivationPathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: \"{}\" {}",
+            self.code(),
+            self.path,
+            self.reason
+        )
+    }
+}
+
+/// A confirmation decision refused under WorkerPolicy.trustedConfirmationOrigin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UntrustedConfirmationOriginError {
+    /// The bridge forwarded no render attestation
+    MissingAttestation,
+    /// The attestation echoes another prompt's render nonce
+    RenderNonceMismatch,
+    /// The attestation names an origin other than the trusted one
+    OriginMismatch { attested: String, trusted: String },
+}
+
+impl UntrustedConfirmationOriginError {
+    pub fn code(&self) -> SignerErrorCode {
+        SignerErrorCode::UntrustedConfirmationOrigin
+    }
+}
+
+impl fmt::Display for UntrustedConfirmationOriginError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UntrustedConfirmationOriginError::MissingAttestation => write!(
+                f,
+                "{}: the confirmation decision carries no render attestation",
+                self.code()
+            ),
+            UntrustedConfirmationOriginError::RenderNonceMismatch => write!(
+                f,
+                "{}: the render attestation is not for this confirmation",
+                self.code()
+            ),
+            UntrustedConfirmationOriginError::OriginMismatch { attested, trusted } => write!(
+                f,
+                "{}: the confirmation was rendered by {}, not {}",
+                self.code(),
+                attested,
+                trusted
+            ),
+        }
+    }
+}
+
+/// A batch's `dependsOn` annotations refused (see tx_dependencies.rs). Indexes are 0-based,
+/// in the batch as sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidDependencyGraphError {
+    /// Following `dependsOn` from `cycle[0]` leads back to it
+    Cycle { cycle: Vec<u32> },
+    /// Transaction `index` depends on `depends_on`, which comes after it or is not in the batch
+    ForwardReference { index: u32, depends_on: u32 },
+    /// `dependsOn` indexes refer to the batch as sent, which `deduplicate` would change
+    WithDeduplicate,
+}
+
+impl InvalidDependencyGraphError {
+    pub fn code(&self) -> SignerErrorCode {
+        SignerErrorCode::InvalidDependencyGraph
+    }
+}
+
+impl fmt::Display for InvalidDependencyGraphError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvalidDependencyGraphError::Cycle { cycle } => write!(
+                f,
+                "{}: transactions {} depend on each other in a cycle",
+                self.code(),
+                cycle
+                    .iter()
+                    .chain(cycle.first())
+                    .map(|index| index.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" -> ")
+            ),
+            InvalidDependencyGraphError::ForwardReference { index, depends_on } => write!(
+                f,
+                "{}: transaction {} depends on transaction {}, which does not come before it",
+                self.code(),
+                index,
+                depends_on
+            ),
+            InvalidDependencyGraphError::WithDeduplicate => write!(
+                f,
+                "{}: dependsOn cannot be combined with deduplicate",
+                self.code()
+            ),
+        }
+    }
+}
+
+/// QR parts refused by QrPayloadEncoder / QrPayloadDecoder (see qr_payload.rs)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QrPayloadError {
+    /// Not a QR part (prefix, base45, header), an unsupported version or kind, or a blob too
+    /// large to carry
+    Malformed(String),
+    /// Parts of different payloads, or two different parts with the same index
+    Mismatched(String),
+    /// Every part is in but the reassembled payload does not match its checksum
+    ChecksumMismatch,
+    /// `finish` before every part was added
+    Incomplete { missing_parts: Vec<u32> },
+}
+
+impl QrPayloadError {
+    pub fn code(&self) -> SignerErrorCode {
+        SignerErrorCode::QrPayloadInvalid
+    }
+}
+
+impl fmt::Display for QrPayloadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QrPayloadError::Malformed(e) | QrPayloadError::Mismatched(e) => {
+                write!(f, "{}: {}", self.code(), e)
+            }
+            QrPayloadError::ChecksumMismatch => write!(
+                f,
+                "{}: the reassembled payload does not match its checksum",
+                self.code()
+            ),
+            QrPayloadError::Incomplete { missing_parts } => write!(
+                f,
+                "{}: parts {} are missing",
+                self.code(),
+                missing_parts
+                    .iter()
+                    .map(|index| index.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+impl From<QrPayloadError> for JsValue {
+    fn from(err: QrPayloadError) -> Self {
+        JsValue::from_str(&err.to_string())
+    }
+}
+
+/// A deprecated path refused under WorkerPolicy.failOnDeprecated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeprecationError {
+    /// Deprecation code (src/wasm_shared/error_codes.rs)
+    pub deprecation: &'static str,
+    pub message: &'static str,
+}
+
+impl DeprecationError {
+    pub fn code(&self) -> SignerErrorCode {
+        SignerErrorCode::DeprecatedUsage
+    }
+}
+
+impl fmt::Display for DeprecationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}: {}",
+            SignerErrorCode::DeprecatedUsage,
+            self.deprecation,
+            self.message
+        )
+    }
+}
+
+/// Strict-mode payload rejection (WorkerPolicy.strictParsing)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StrictParseError {
+    /// `path` is the JSON pointer of the unrecognised field
+    UnknownField { path: String },
+}
+
+impl StrictParseError {
+    pub fn code(&self) -> SignerErrorCode {
+        match self {
+            StrictParseError::UnknownField { .. } => SignerErrorCode::UnknownField,
+        }
+    }
+}
+
+impl fmt::Display for StrictParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StrictParseError::UnknownField { path } => {
+                write!(
+                    f,
+                    "{}: unknown field at {}",
+                    SignerErrorCode::UnknownField,
+                    path
+                )
+            }
+        }
+    }
+}
+
+/// Failure verifying a shared account descriptor
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccountDescriptorError {
+    /// Not base64url CBOR of a signed descriptor, or an unsupported version
+    Malformed(String),
+    /// The signature does not verify, or was made by a key other than the one provided
+    InvalidSignature(String),
+    /// `expires_at_ms` is not after the verifier's clock
+    Expired { expires_at_ms: f64 },
+}
+
+impl AccountDescriptorError {
+    pub fn code(&self) -> SignerErrorCode {
+        match self {
+            AccountDescriptorError::Malformed(_) | AccountDescriptorError::InvalidSignature(_) => {
+                SignerErrorCode::DescriptorInvalid
+            }
+            AccountDescriptorError::Expired { .. } => SignerErrorCode::DescriptorExpired,
+        }
+    }
+}
+
+impl fmt::Display for AccountDescriptorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AccountDescriptorError::Malformed(e) | AccountDescriptorError::InvalidSignature(e) => {
+                write!(f, "{}: {}", self.code(), e)
+            }
+            AccountDescriptorError::Expired { expires_at_ms } => write!(
+                f,
+                "{}: account descriptor expired at {}",
+                self.code(),
+                expires_at_ms
+            ),
+        }
+    }
+}
+
+/// Rejection of a request carrying an idempotency key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdempotencyError {
+    /// The key completed a request whose payload digest differs from this one
+    KeyConflict { key: String },
+}
+
+impl IdempotencyError {
+    pub fn code(&self) -> SignerErrorCode {
+        match self {
+            IdempotencyError::KeyConflict { .. } => SignerErrorCode::IdempotencyKeyConflict,
+        }
+    }
+}
+
+impl fmt::Display for IdempotencyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IdempotencyError::KeyConflict { key } => write!(
+                f,
+                "{}: idempotency key {} was used for a different payload",
+                SignerErrorCode::IdempotencyKeyConflict,
+                key
+            ),
+        }
+    }
+}
+
+/// `validate()` failure for the config and payload classes exposed to JS
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// `field` holds a value it can never take
+    InvalidValue { field: &'static str, reason: String },
+    /// `field` does not fit the rest of the config (e.g. autoProceed without a delay)
+    InvalidCombination { field: &'static str, reason: String },
+}
+
+impl ConfigError {
+    pub fn code(&self) -> SignerErrorCode {
+        SignerErrorCode::InvalidConfig
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::InvalidValue { field, reason }
+            | ConfigError::InvalidCombination { field, reason } => {
+                write!(f, "{}: {}: {}", self.code(), field, reason)
+            }
+        }
+    }
+}
+
+impl From<ConfigError> for JsValue {
+    fn from(err: ConfigError) -> Self {
+        JsValue::from_str(&err.to_string())
+    }
+}
+
+/// Refusal of an `rpcOverrides` endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RpcOriginError {
+    /// `url` is not an http(s) URL on one of the allowed origins
+    NotAllowed { endpoint: &'static str, url: String },
+}
+
+impl RpcOriginError {
+    pub fn code(&self) -> SignerErrorCode {
+        match self {
+            RpcOriginError::NotAllowed { .. } => SignerErrorCode::RpcOriginNotAllowed,
+        }
+    }
+}
+
+impl fmt::Display for RpcOriginError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RpcOriginError::NotAllowed { endpoint, url } => write!(
+                f,
+                "{}: {} endpoint {} is not on an allowed RPC origin",
+                self.code(),
+                endpoint,
+                url
+            ),
+        }
+    }
+}
+
+/// Refusal of a request whose validUntilBlockHeight has passed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeadlineError {
+    /// The chain is at `current`, past `valid_until`
+    DeadlineExceeded { valid_until: u64, current: u64 },
+}
+
+impl DeadlineError {
+    pub fn code(&self) -> SignerErrorCode {
+        match self {
+            DeadlineError::DeadlineExceeded { .. } => SignerErrorCode::DeadlineExceeded,
+        }
+    }
+}
+
+impl fmt::Display for DeadlineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeadlineError::DeadlineExceeded {
+                valid_until,
+                current,
+            } => write!(
+                f,
+                "{}: valid until block {}, the chain is at block {}",
+                self.code(),
+                valid_until,
+                current
+            ),
+        }
+    }
+}
+
+/// Failure of a NEAR JSON-RPC call (see rpc_client.rs)
+#[derive(Debug, Clone, PartialEq)]
+pub enum RpcErrorKind {
+    /// No endpoint answered; the last endpoint's failure
+    Unreachable(String),
+    /// HTTP 408/504 or a TIMEOUT_ERROR cause
+    Timeout(String),
+    /// InvalidTxError::InvalidNonce: the access key is already at `ak_nonce`
+    InvalidNonce { tx_nonce: u64, ak_nonce: u64 },
+    /// Any other error the node answered with. `name` is its cause (e.g. UNKNOWN_ACCOUNT);
+    /// `error` is the JSON-RPC error object as received.
+    Rpc {
+        name: String,
+        message: String,
+        error: serde_json::Value,
+    },
+    /// A response without result or error, or a result of the wrong shape
+    InvalidResponse(String),
+}
+
+impl RpcErrorKind {
+    pub fn code(&self) -> SignerErrorCode {
+        match self {
+            RpcErrorKind::Unreachable(_) => SignerErrorCode::RpcUnreachable,
+            RpcErrorKind::Timeout(_) => SignerErrorCode::RpcTimeout,
+            RpcErrorKind::InvalidNonce { .. } => SignerErrorCode::InvalidNonce,
+            RpcErrorKind::Rpc { .. } => SignerErrorCode::RpcRequestFailed,
+            RpcErrorKind::InvalidResponse(_) => SignerErrorCode::RpcResponseInvalid,
+        }
+    }
+
+    /// Whether the next endpoint may answer where this one did not
+    pub fn is_failover(&self) -> bool {
+        matches!(self, RpcErrorKind::Unreachable(_) | RpcErrorKind::Timeout(_))
+    }
+}
+
+impl fmt::Display for RpcErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RpcErrorKind::Unreachable(e)
+            | RpcErrorKind::Timeout(e)
+            | RpcErrorKind::InvalidResponse(e) => write!(f, "{}: {}", self.code(), e),
+            RpcErrorKind::InvalidNonce { tx_nonce, ak_nonce } => write!(
+                f,
+                "{}: transaction nonce {} is not above the access key nonce {}",
+                self.code(),
+                tx_nonce,
+                ak_nonce
+            ),
+            RpcErrorKind::Rpc { name, message, .. } => {
+                write!(f, "{}: {} ({})", self.code(), message, name)
+            }
+        }
+    }
+}
+
+impl From<RpcErrorKind> for String {
+    fn from(err: RpcErrorKind) -> Self {
+        err.to_string()
+    }
+}
+
+/// Failure of a ViewCall (see view_call.rs)
+#[derive(Debug, Clone, PartialEq)]
+pub enum ViewCallError {
+    /// The contract panicked; `message` is the panic message extracted from the node's error
+    ContractPanic { message: String },
+    /// Any other RPC failure (unknown account, no contract code, unreachable node, ...)
+    Rpc(RpcErrorKind),
+}
+
+impl ViewCallError {
+    pub fn code(&self) -> SignerErrorCode {
+        match self {
+            ViewCallError::ContractPanic { .. } => SignerErrorCode::ContractPanic,
+            ViewCallError::Rpc(e) => e.code(),
+        }
+    }
+}
+
+impl fmt::Display for ViewCallError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ViewCallError::ContractPanic { message } => write!(f, "{}: {}", self.code(), message),
+            ViewCallError::Rpc(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<RpcErrorKind> for ViewCallError {
+    fn from(err: RpcErrorKind) -> Self {
+        ViewCallError::Rpc(err)
+    }
+}
+
+/// Refusal of a key-handling request after a failed self-test
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelfTestError {
+    /// The known-answer test of `primitive` (e.g. "sha256") did not match its spec vector
+    Failed { primitive: String },
+}
+
+impl SelfTestError {
+    pub fn code(&self) -> SignerErrorCode {
+        match self {
+            SelfTestError::Failed { .. } => SignerErrorCode::SelfTestFailed,
+        }
+    }
+}
+
+impl fmt::Display for SelfTestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SelfTestError::Failed { primitive } => write!(
+                f,
+                "{}: {} known-answer test failed; key-handling requests are refused until the worker is re-initialized",
+                self.code(),
+                primitive
+            ),
+        }
+    }
+}
+
+/// Failure reading an account bundle in ImportAccountBundle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountBundleError {
+    /// The key-check value derived from the passphrase differs from the header's
+    PassphraseInvalid,
+    /// The bundle bytes end inside the header or ciphertext
+    Truncated,
+    /// The header declares a version this release cannot read
+    VersionUnsupported { version: u32 },
+    /// Not a bundle, unreadable header parameters, damaged ciphertext or an invalid record
+    Invalid(String),
+}
+
+impl AccountBundleError {
+    pub fn code(&self) -> SignerErrorCode {
+        match self {
+            AccountBundleError::PassphraseInvalid => SignerErrorCode::BundlePassphraseInvalid,
+            AccountBundleError::Truncated => SignerErrorCode::BundleTruncated,
+            AccountBundleError::VersionUnsupported { .. } => {
+                SignerErrorCode::BundleVersionUnsupported
+            }
+            AccountBundleError::Invalid(_) => SignerErrorCode::BundleInvalid,
+        }
+    }
+}
+
+impl fmt::Display for AccountBundleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AccountBundleError::PassphraseInvalid => write!(
+                f,
+                "{}: the passphrase does not match this account bundle",
+                self.code()
+            ),
+            AccountBundleError::Truncated => {
+                write!(f, "{}: the account bundle is incomplete", self.code())
+            }
+            AccountBundleError::VersionUnsupported { version } => write!(
+                f,
+                "{}: account bundle version {} is newer than this release supports ({})",
+                self.code(),
+                version,
+                crate::config::ACCOUNT_BUNDLE_VERSION
+            ),
+            AccountBundleError::Invalid(e) => write!(f, "{}: {}", self.code(), e),
+        }
+    }
+}
+
+/// Failure choosing or applying a key-wrapping scheme at registration (see key_wrapping.rs).
+/// PrfUnsupported messages start with the PrfUnsupportedByAuthenticator code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyWrappingError {
+    /// No PRF output, and the requested fallback is not allowed by WorkerPolicy
+    PrfUnsupported(String),
+    /// Bad fallback input (unknown scheme name, missing or short passphrase)
+    InvalidInput(String),
+}
+
+impl fmt::Display for KeyWrappingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeyWrappingError::PrfUnsupported(e) => write!(
+                f,
+                "{}: the authenticator does not support the PRF extension; {}",
+                SignerErrorCode::PrfUnsupportedByAuthenticator,
+                e
+            ),
+            KeyWrappingError::InvalidInput(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Rejection of a signing intent token in ExecuteSigningIntent
+#[derive(Debug, Clone, PartialEq)]
+pub enum SigningIntentError {
+    /// Not base64url CBOR of a signed intent, an unsupported version, or a MAC mismatch
+    Invalid(String),
+    /// The intent's effective expiry is not after the worker's clock
+    Expired { expires_at_ms: f64 },
+    /// The intent was already consumed by an execution
+    Replayed { intent_id: String },
+    /// The supplied transactions do not hash to the intent's digest
+    PayloadMismatch(String),
+    /// The intent was issued in an earlier session epoch
+    Session(SessionError),
+}
+
+impl SigningIntentError {
+    pub fn code(&self) -> SignerErrorCode {
+        match self {
+            SigningIntentError::Invalid(_) => SignerErrorCode::SigningIntentInvalid,
+            SigningIntentError::Expired { .. } => SignerErrorCode::SigningIntentExpired,
+            SigningIntentError::Replayed { .. } => SignerErrorCode::SigningIntentReplayed,
+            SigningIntentError::PayloadMismatch(_) => SignerErrorCode::SigningIntentPayloadMismatch,
+            SigningIntentError::Session(e) => e.code(),
+        }
+    }
+}
+
+impl fmt::Display for SigningIntentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SigningIntentError::Invalid(e) | SigningIntentError::PayloadMismatch(e) => {
+                write!(f, "{}: {}", self.code(), e)
+            }
+            SigningIntentError::Expired { expires_at_ms } => write!(
+                f,
+                "{}: signing intent expired at {}",
+                self.code(),
+                expires_at_ms
+            ),
+            SigningIntentError::Replayed { intent_id } => write!(
+                f,
+                "{}: signing intent {} was already executed",
+                self.code(),
+                intent_id
+            ),
+            SigningIntentError::Session(e) => e.fmt(f),
+        }
+    }
+}
+
+/// Refusal of a redirect callback in BuildRedirectPayload, or rejection of a redirect payload
+/// by verify_redirect_payload (see redirect_payload.rs)
+#[derive(Debug, Clone, PartialEq)]
+pub enum RedirectPayloadError {
+    /// The callback URL is not https
+    SchemeNotAllowed { scheme: String },
+    /// The callback URL's origin is not allowed, or the URL has none (credentials, no host)
+    OriginNotAllowed { url: String },
+    /// Not base64url CBOR of a signed payload, an unsupported version, a bad signature, or a
+    /// payload issued for another callback or state
+    Invalid(String),
+    /// The payload's expiry is not after the verifier's clock
+    Expired { expires_at_ms: f64 },
+}
+
+impl RedirectPayloadError {
+    pub fn code(&self) -> SignerErrorCode {
+        match self {
+            RedirectPayloadError::SchemeNotAllowed { .. } => {
+                SignerErrorCode::RedirectSchemeNotAllowed
+            }
+            RedirectPayloadError::OriginNotAllowed { .. } => {
+                SignerErrorCode::RedirectOriginNotAllowed
+            }
+            RedirectPayloadError::Invalid(_) => SignerErrorCode::RedirectPayloadInvalid,
+            RedirectPayloadError::Expired { .. } => SignerErrorCode::RedirectPayloadExpired,
+        }
+    }
+}
+
+impl fmt::Display for RedirectPayloadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RedirectPayloadError::SchemeNotAllowed { scheme } if scheme.is_empty() => write!(
+                f,
+                "{}: redirect callbacks must be absolute https URLs",
+                self.code()
+            ),
+            RedirectPayloadError::SchemeNotAllowed { scheme } => write!(
+                f,
+                "{}: redirect callbacks must be https, not {}:",
+                self.code(),
+                scheme
+            ),
+            RedirectPayloadError::OriginNotAllowed { url } => write!(
+                f,
+                "{}: {} is not on workerPolicy.allowedRedirectOrigins",
+                self.code(),
+                url
+            ),
+            RedirectPayloadError::Invalid(e) => write!(f, "{}: {}", self.code(), e),
+            RedirectPayloadError::Expired { expires_at_ms } => write!(
+                f,
+                "{}: redirect payload expired at {}",
+                self.code(),
+                expires_at_ms
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "relayer")]
+/// Rejection of a registration resume token in CompleteRegistration
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegistrationResumeError {
+    /// Not base64url CBOR of a signed resume state, an unsupported version, or a MAC mismatch
+    Invalid(String),
+    /// The token's expiry is not after the worker's clock
+    Expired { expires_at_ms: f64 },
+    /// Another account or credential, or PRF output that derives another key
+    Mismatch(String),
+}
+
+#[cfg(feature = "relayer")]
+impl RegistrationResumeError {
+    pub fn code(&self) -> SignerErrorCode {
+        match self {
+            RegistrationResumeError::Invalid(_) => SignerErrorCode::RegistrationResumeInvalid,
+            RegistrationResumeError::Expired { .. } => SignerErrorCode::RegistrationResumeExpired,
+            RegistrationResumeError::Mismatch(_) => SignerErrorCode::RegistrationResumeMismatch,
+        }
+    }
+}
+
+#[cfg(feature = "relayer")]
+impl fmt::Display for RegistrationResumeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegistrationResumeError::Invalid(e) | RegistrationResumeError::Mismatch(e) => {
+                write!(f, "{}: {}", self.code(), e)
+            }
+            RegistrationResumeError::Expired { expires_at_ms } => write!(
+                f,
+                "{}: resume token expired at {}",
+                self.code(),
+                expires_at_ms
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "device-linking")]
+/// Rejection of a remote confirmation request (ApproveRemoteConfirmation) or of its approval
+/// (CompleteRemoteConfirmation)
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemoteConfirmationError {
+    /// Not base64url CBOR of a signed request or sealed approval, an unsupported version, a bad
+    /// signature, an approval that does not decrypt, or transactions that hash to another digest
+    Invalid(String),
+    /// The request's expiry is not after the worker's clock
+    Expired { expires_at_ms: f64 },
+    /// The request was already approved here, or its approval already used
+    Replayed { request_id: String },
+    /// No session for the request, another session key, or another request or digest
+    WrongSession(String),
+}
+
+#[cfg(feature = "device-linking")]
+impl RemoteConfirmationError {
+    pub fn code(&self) -> SignerErrorCode {
+        match self {
+            RemoteConfirmationError::Invalid(_) => SignerErrorCode::RemoteConfirmationInvalid,
+            RemoteConfirmationError::Expired { .. } => SignerErrorCode::RemoteConfirmationExpired,
+            RemoteConfirmationError::Replayed { .. } => SignerErrorCode::RemoteConfirmationReplayed,
+            RemoteConfirmationError::WrongSession(_) => {
+                SignerErrorCode::RemoteConfirmationWrongSession
+            }
+        }
+    }
+}
+
+#[cfg(feature = "device-linking")]
+impl fmt::Display for RemoteConfirmationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RemoteConfirmationError::Invalid(e) | RemoteConfirmationError::WrongSession(e) => {
+                write!(f, "{}: {}", self.code(), e)
+            }
+            RemoteConfirmationError::Expired { expires_at_ms } => write!(
+                f,
+                "{}: remote confirmation expired at {}",
+                self.code(),
+                expires_at_ms
+            ),
+            RemoteConfirmationError::Replayed { request_id } => write!(
+                f,
+                "{}: remote confirmation {} was already used",
+                self.code(),
+                request_id
+            ),
+        }
+    }
+}
+
+/// A request for a handler left out of the build (see features.rs)
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureNotCompiledError {
+    pub feature: &'static str,
+}
+
+impl FeatureNotCompiledError {
+    pub fn code(&self) -> SignerErrorCode {
+        SignerErrorCode::FeatureNotCompiled
+    }
+}
+
+impl fmt::Display for FeatureNotCompiledError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: the signer worker was built without the `{}` feature",
+            self.code(),
+            self.feature
+        )
+    }
+}
+
+/// A function-call key whose remaining allowance does not cover the estimated cost of the
+/// transactions (see allowance.rs); amounts in yoctoNEAR
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InsufficientAllowanceError {
+    pub remaining: u128,
+    pub required: u128,
+}
+
+impl InsufficientAllowanceError {
+    pub fn code(&self) -> SignerErrorCode {
+        SignerErrorCode::InsufficientAllowance
+    }
+}
+
+impl fmt::Display for InsufficientAllowanceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: the access key has {} yoctoNEAR of allowance left, the transactions need up to {}",
+            self.code(),
+            self.remaining,
+            self.required
+        )
+    }
+}
+
+/// A signingPublicKey that cannot sign the batch (see key_selection.rs)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyLacksPermissionError {
+    pub public_key: String,
+    /// What the key is missing (e.g. "it is not an access key of alice.testnet")
+    pub reason: String,
+}
+
+impl KeyLacksPermissionError {
+    pub fn code(&self) -> SignerErrorCode {
+        SignerErrorCode::KeyLacksPermission
+    }
+}
+
+impl fmt::Display for KeyLacksPermissionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} cannot sign this batch: {}",
+            self.code(),
+            self.public_key,
+            self.reason
+        )
+    }
+}
+
+/// One value of a FunctionCall's args that fails its registered schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgsSchemaViolation {
+    /// JSON pointer into the request, continuing into the args as if they were inline
+    /// (e.g. "/txSigningRequests/0/actions/1/args/amount")
+    pub path: String,
+    pub message: String,
+}
+
+/// A registered args schema that does not compile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgsSchemaCompileError {
+    pub contract_id: String,
+    pub method_name: String,
+    /// JSON pointer into the schema ("" for the schema itself)
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ArgsSchemaCompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} schema at \"{}\": {}",
+            self.contract_id, self.method_name, self.path, self.message
+        )
+    }
+}
+
+/// Why a batch fails `workerPolicy.argsSchemas` (see args_schema.rs)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgsSchemaError {
+    /// A registered schema does not compile
+    InvalidSchemas(Vec<ArgsSchemaCompileError>),
+    /// FunctionCall args fail their schemas: the first MAX_REPORTED_ARGS_SCHEMA_VIOLATIONS
+    /// violations, and how many there are in all
+    Violations {
+        violations: Vec<ArgsSchemaViolation>,
+        total: usize,
+    },
+}
+
+impl ArgsSchemaError {
+    pub fn code(&self) -> SignerErrorCode {
+        match self {
+            ArgsSchemaError::InvalidSchemas(_) => SignerErrorCode::InvalidConfig,
+            ArgsSchemaError::Violations { .. } => SignerErrorCode::ArgsSchemaViolation,
+        }
+    }
+}
+
+impl fmt::Display for ArgsSchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArgsSchemaError::InvalidSchemas(errors) => {
+                let listed: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                write!(
+                    f,
+                    "{}: Invalid workerPolicy.argsSchemas: {}",
+                    self.code(),
+                    listed.join("; ")
+                )
+            }
+            ArgsSchemaError::Violations { violations, total } => {
+                let listed: Vec<String> = violations
+                    .iter()
+                    .map(|v| format!("{}: {}", v.path, v.message))
+                    .collect();
+                write!(
+                    f,
+                    "{}: {} schema violation(s) in FunctionCall args: {}",
+                    self.code(),
+                    total,
+                    listed.join("; ")
+                )?;
+                if *total > violations.len() {
+                    write!(f, "; and {} more", total - violations.len())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A batch whose canonical digest is not the previewDigest it was sent with (see
+/// batch_preview.rs)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayloadChangedSincePreviewError {
+    /// The request's previewDigest
+    pub preview_digest: String,
+    /// The digest of the batch as sent (after deduplication, with `deduplicate`)
+    pub batch_digest: String,
+}
+
+impl PayloadChangedSincePreviewError {
+    pub fn code(&self) -> SignerErrorCode {
+        SignerErrorCode::PayloadChangedSincePreview
+    }
+}
+
+impl fmt::Display for PayloadChangedSincePreviewError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: the batch's digest is {}, not the previewed {}",
+            self.code(),
+            self.batch_digest,
+            self.preview_digest
+        )
+    }
+}
+
+/// Rejection of a request's secret fields (see sealed_secrets.rs)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SealedSecretError {
+    /// The field is set in plaintext while requireEncryptedSecrets is in force
+    PlaintextRefused { path: String },
+    /// The sealed box is malformed, targets a field that is not a secret of the request, or
+    /// does not open with the current session key
+    Invalid { path: String, reason: String },
+    /// The field is set in plaintext and the request sets failOnDeprecated
+    Deprecated(DeprecationError),
+}
+
+impl SealedSecretError {
+    pub fn code(&self) -> SignerErrorCode {
+        match self {
+            SealedSecretError::PlaintextRefused { .. } => SignerErrorCode::PlaintextSecretRefused,
+            SealedSecretError::Invalid { .. } => SignerErrorCode::SealedSecretInvalid,
+            SealedSecretError::Deprecated(e) => e.code(),
+        }
+    }
+}
+
+impl fmt::Display for SealedSecretError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SealedSecretError::PlaintextRefused { path } => write!(
+                f,
+                "{}: `{}` must be sent in sealedSecrets, not in plaintext",
+                self.code(),
+                path
+            ),
+            SealedSecretError::Invalid { path, reason } => {
+                write!(f, "{}: `{}`: {}", self.code(), path, reason)
+            }
+            SealedSecretError::Deprecated(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// A `redactPaths` entry a signing request is refused for (see redaction.rs)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedactionError {
+    /// Names a field every confirmation shows: the receiver, method, deposit or gas
+    CriticalField { path: String },
+    /// Not a path to a value in FunctionCall args
+    InvalidPath { path: String, reason: String },
+}
+
+impl RedactionError {
+    pub fn code(&self) -> SignerErrorCode {
+        match self {
+            RedactionError::CriticalField { .. } => SignerErrorCode::CannotRedactCriticalField,
+            RedactionError::InvalidPath { .. } => SignerErrorCode::InvalidConfig,
+        }
+    }
+}
+
+impl fmt::Display for RedactionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RedactionError::CriticalField { path } => write!(
+                f,
+                "{}: `{}` is always shown in the confirmation; only args can be redacted",
+                self.code(),
+                path
+            ),
+            RedactionError::InvalidPath { path, reason } => write!(
+                f,
+                "{}: Invalid redactPaths entry `{}`: {}",
+                self.code(),
+                path,
+                reason
+            ),
+        }
+    }
+}
+
+/// Refusal of a message frame before it is parsed (see message_limits.rs)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageSizeError {
+    /// The frame is `received` bytes, above the `limit` for its request type
+    MessageTooLarge { limit: u32, received: usize },
+    /// A CBOR header at byte `offset` declares `declared` bytes or items, more than the
+    /// `available` bytes after it
+    DeclaredLengthExceedsFrame {
+        offset: usize,
+        declared: u64,
+        available: usize,
+    },
+}
+
+impl MessageSizeError {
+    pub fn code(&self) -> SignerErrorCode {
+        SignerErrorCode::MessageTooLarge
+    }
+}
+
+impl fmt::Display for MessageSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MessageSizeError::MessageTooLarge { limit, received } => write!(
+                f,
+                "{}: message is {} bytes, the limit is {} bytes",
+                self.code(),
+                received,
+                limit
+            ),
+            MessageSizeError::DeclaredLengthExceedsFrame {
+                offset,
+                declared,
+                available,
+            } => write!(
+                f,
+                "{}: CBOR header at byte {} declares {} bytes or items, the frame has {} left",
+                self.code(),
+                offset,
+                declared,
+                available
+            ),
+        }
+    }
+}
+
+/// Structure a WebAuthnDataError offset is relative to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WebAuthnDataSection {
+    AttestationObject,
+    AuthData,
+    CoseKey,
+}
+
+impl WebAuthnDataSection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebAuthnDataSection::AttestationObject => "attestationObject",
+            WebAuthnDataSection::AuthData => "authData",
+            WebAuthnDataSection::CoseKey => "coseKey",
+        }
+    }
+}
+
+/// Malformed WebAuthn structure, with the byte offset (within `section`) where parsing failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebAuthnDataError {
+    pub section: WebAuthnDataSection,
+    pub offset: usize,
+    pub message: String,
+}
+
+impl WebAuthnDataError {
+    pub fn new(section: WebAuthnDataSection, offset: usize, message: impl Into<String>) -> Self {
+        Self {
+            section,
+            offset,
+            message: message.into(),
+        }
+    }
+
+    pub fn code(&self) -> SignerErrorCode {
+        SignerErrorCode::WebAuthnDataMalformed
+    }
+}
+
+impl fmt::Display for WebAuthnDataError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} (byte {} of {})",
+            self.code(),
+            self.message,
+            self.offset,
+            self.section.as_str()
+        )
+    }
+}
+
+/// Thrown to JS as `{ code, section, offset, message }`
+impl From<WebAuthnDataError> for JsValue {
+    fn from(err: WebAuthnDataError) -> Self {
+        #[derive(Serialize)]
+        struct Thrown<'a> {
+            code: &'static str,
+            section: WebAuthnDataSection,
+            offset: usize,
+            message: &'a str,
+        }
+        to_js_value(&Thrown {
+            code: err.code().as_str(),
+            section: err.section,
+            offset: err.offset,
+            message: &err.message,
+        })
+        .unwrap_or_else(|_| JsValue::from_str(&err.to_string()))
+    }
+}
+
+impl From<BlobDecryptError> for String {
+    fn from(err: BlobDecryptError) -> Self {
+        err.to_string()
+    }
+}
@@ -0,0 +1,133 @@
+// === EVM PERSONAL_SIGN ===
+// Integrators that derive a secp256k1 key for their EVM accounts from the same passkey sign
+// `personal_sign` (EIP-191, version 0x45) messages with this worker instead of a second signer.
+// The message is hashed as
+//
+//   keccak256("\x19Ethereum Signed Message:\n" || decimal byte length || message)
+//
+// and signed with a recoverable ECDSA signature, returned as r, s and v (27 + recovery id) and
+// as the 65-byte `r || s || v` hex wallets return. s is always in the lower half of the order.
+//
+// Messages are given as to `personal_sign`: 0x-prefixed hex of the bytes, or text signed as its
+// UTF-8 bytes. The confirmation shows them decoded: the text, or the hex when the bytes are not
+// UTF-8.
+
+use k256::ecdsa::{SigningKey, VerifyingKey};
+use serde::Serialize;
+use sha3::{Digest, Keccak256};
+
+pub const PERSONAL_SIGN_PREFIX: &str = "\x19Ethereum Signed Message:\n";
+
+/// Recoverable signature of a personal_sign message, with the signer's address
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EvmSignature {
+    /// 0x-prefixed 32-byte hex
+    pub r: String,
+    /// 0x-prefixed 32-byte hex
+    pub s: String,
+    /// 27 or 28
+    pub v: u8,
+    /// 0x-prefixed `r || s || v`
+    pub signature: String,
+    /// EIP-55 checksummed address of the signing key
+    pub address: String,
+}
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+pub fn hex_encode(bytes: &[u8]) -> String {
+    let digits: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("0x{}", digits)
+}
+
+fn hex_decode(digits: &str) -> Result<Vec<u8>, String> {
+    if !digits.len().is_multiple_of(2) {
+        return Err(format!("odd number of hex digits ({})", digits.len()));
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(digits.get(i..i + 2).unwrap_or_default(), 16)
+                .map_err(|_| format!("invalid hex at offset {}", i))
+        })
+        .collect()
+}
+
+/// Bytes of a personal_sign message: 0x-prefixed hex decoded, anything else as UTF-8
+pub fn message_bytes(message: &str) -> Result<Vec<u8>, String> {
+    match message.strip_prefix("0x") {
+        Some(digits) => hex_decode(digits).map_err(|e| format!("Invalid hex message: {}", e)),
+        None => Ok(message.as_bytes().to_vec()),
+    }
+}
+
+/// The message as shown for confirmation: its text, or its hex when it is not UTF-8
+pub fn display_message(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => hex_encode(bytes),
+    }
+}
+
+/// EIP-191 hash of a personal_sign message
+pub fn personal_sign_hash(message: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(PERSONAL_SIGN_PREFIX.as_bytes());
+    hasher.update(message.len().to_string().as_bytes());
+    hasher.update(message);
+    hasher.finalize().into()
+}
+
+/// EIP-55 checksummed address: the last 20 bytes of keccak256 over the uncompressed key
+pub fn evm_address(verifying_key: &VerifyingKey) -> String {
+    let point = verifying_key.to_encoded_point(false);
+    let hash = keccak256(&point.as_bytes()[1..]);
+    let lower = hex_encode(&hash[12..]);
+    let checksum = keccak256(&lower.as_bytes()[2..]);
+    let checksummed: String = lower[2..]
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (checksum[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0x0f;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+    format!("0x{}", checksummed)
+}
+
+/// NEAR-format public key of a secp256k1 key: `secp256k1:` and the base58 of the 64-byte
+/// uncompressed point without its 0x04 tag
+pub fn near_public_key(verifying_key: &VerifyingKey) -> String {
+    let point = verifying_key.to_encoded_point(false);
+    format!(
+        "secp256k1:{}",
+        bs58::encode(&point.as_bytes()[1..]).into_string()
+    )
+}
+
+/// Signs a personal_sign message (its bytes, see `message_bytes`)
+pub fn sign_personal_message(
+    signing_key: &SigningKey,
+    message: &[u8],
+) -> Result<EvmSignature, String> {
+    let hash = personal_sign_hash(message);
+    let (signature, recovery_id) = signing_key
+        .sign_prehash_recoverable(&hash)
+        .map_err(|e| format!("secp256k1 signing failed: {}", e))?;
+    let (r, s) = signature.split_bytes();
+    let v = 27 + recovery_id.to_byte();
+    Ok(EvmSignature {
+        r: hex_encode(&r),
+        s: hex_encode(&s),
+        v,
+        signature: hex_encode(&[&r[..], &s[..], &[v]].concat()),
+        address: evm_address(signing_key.verifying_key()),
+    })
+}
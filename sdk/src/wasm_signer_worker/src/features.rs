@@ -0,0 +1,90 @@
+// === COMPILED FEATURES ===
+// Integrators who never sign NEP-413 messages, link devices, use the relayer or report telemetry
+// can leave that code out of the wasm: each group is a cargo feature (all on by default, see
+// Cargo.toml). The request types stay in the wire protocol either way. A request for a group
+// the build left out is answered with FeatureNotCompiled before its payload is parsed, and
+// GetWorkerInfo reports the compiled set, so the TS layer can tell a smaller build from a
+// broken one.
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::config::RESPONSE_SCHEMA_VERSION;
+use crate::error::FeatureNotCompiledError;
+use crate::types::worker_messages::WorkerRequestType;
+
+/// Every optional feature and whether this build has it, in Cargo.toml order
+pub const FEATURES: [(&str, bool); 6] = [
+    ("secp256k1", cfg!(feature = "secp256k1")),
+    ("nep413", cfg!(feature = "nep413")),
+    ("device-linking", cfg!(feature = "device-linking")),
+    ("relayer", cfg!(feature = "relayer")),
+    ("wasm-threads", cfg!(feature = "wasm-threads")),
+    ("telemetry", cfg!(feature = "telemetry")),
+];
+
+/// Build capabilities, returned by GetWorkerInfo
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkerInfo {
+    /// The compiled-in features, e.g. "nep413", "relayer"
+    #[wasm_bindgen(getter_with_clone)]
+    pub features: Vec<String>,
+    /// Crate version of the signer worker
+    #[wasm_bindgen(getter_with_clone)]
+    pub version: String,
+    /// RESPONSE_SCHEMA_VERSION of this build
+    #[wasm_bindgen(js_name = "responseSchemaVersion")]
+    pub response_schema_version: u32,
+}
+
+impl WorkerInfo {
+    pub fn current() -> Self {
+        WorkerInfo {
+            features: compiled_features(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            response_schema_version: RESPONSE_SCHEMA_VERSION,
+        }
+    }
+}
+
+pub fn compiled_features() -> Vec<String> {
+    FEATURES
+        .iter()
+        .filter(|(_, compiled)| *compiled)
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
+
+/// The feature whose handler serves `request_type`, for request types in an optional group
+pub fn required_feature(request_type: WorkerRequestType) -> Option<&'static str> {
+    match request_type {
+        WorkerRequestType::SignNep413Message => Some("nep413"),
+        WorkerRequestType::SignTransactionWithKeyPair
+        | WorkerRequestType::CreateRemoteConfirmation
+        | WorkerRequestType::ApproveRemoteConfirmation
+        | WorkerRequestType::CompleteRemoteConfirmation => Some("device-linking"),
+        WorkerRequestType::SubmitToRelayer | WorkerRequestType::CompleteRegistration => {
+            Some("relayer")
+        }
+        WorkerRequestType::GetTelemetrySnapshot => Some("telemetry"),
+        _ => None,
+    }
+}
+
+pub fn is_compiled(feature: &str) -> bool {
+    FEATURES
+        .iter()
+        .any(|(name, compiled)| *name == feature && *compiled)
+}
+
+/// Refuses a request whose handler was not compiled in
+pub fn check_feature_compiled(
+    request_type: WorkerRequestType,
+) -> Result<(), FeatureNotCompiledError> {
+    match required_feature(request_type) {
+        Some(feature) if !is_compiled(feature) => Err(FeatureNotCompiledError { feature }),
+        _ => Ok(()),
+    }
+}
@@ -14,11 +14,45 @@ use super::handle_sign_transactions_with_actions::{TransactionPayload, SignTrans
 use crate::types::handlers::{
     ConfirmationConfig,
     ConfirmationUIMode,
-    ConfirmationBehavior
+    ConfirmationBehavior,
+    TransactionContext,
+    WorkerPolicy,
+};
+use crate::config::{
+    DEFAULT_AVERAGE_BLOCK_TIME_MS, DEFAULT_CONFIRMATION_TIMEOUT_MS,
+    NEAR_MAINNET_AVERAGE_BLOCK_TIME_MS, NEAR_TESTNET_AVERAGE_BLOCK_TIME_MS,
+    VRF_CHALLENGE_ACCEPTANCE_WINDOW_BLOCKS,
 };
 use crate::encoders::base64_url_encode;
+use crate::action_firewall;
 use crate::actions::ActionParams;
+use crate::batch_preview::BatchPreparation;
+use crate::chunked_deploy::DeployManifest;
+use crate::allowance::{self, AllowanceEstimate};
+use crate::confirmation_blocks::{
+    batch_summary_blocks, summary_speech, SummaryPolicy,
+};
+use crate::confirmation_origin::{self, RenderAttestation};
+use crate::confirmation_speech::SpeechLocale;
+use crate::countdown_handshake::{countdown_delay_ms, verify_countdown_handshake, CountdownReport};
+use crate::elevation;
+use crate::error::SignerErrorCode;
+use crate::key_selection;
+use crate::redaction;
+use crate::render_budget;
+#[cfg(feature = "device-linking")]
+use crate::remote_confirmation::RemoteConfirmationRequest;
+#[cfg(feature = "device-linking")]
+use crate::types::handlers::RpcCallPayload;
+use crate::types::AccountId;
+use crate::sealed_secrets;
+use crate::session_duration;
+use crate::state;
+use crate::storage_deposit;
+use crate::tx_dependencies;
+use crate::tx_diff;
 use serde_json::Value;
+use std::collections::HashMap;
 
 // External JS function for secure confirmation (V2 typed API)
 //
@@ -51,15 +85,233 @@ pub struct ConfirmationResult {
     pub intent_digest: Option<String>,
     pub credential: Option<serde_json::Value>, // Serialized WebAuthn credential (JSON)
     pub prf_output: Option<String>, // Base64url-encoded PRF output for decryption
+    pub prf_supported: Option<bool>, // false when the credential's clientExtensionResults lack PRF results
     pub vrf_challenge: Option<crate::types::VrfChallenge>, // VRF challenge generated in main thread
     pub transaction_context: Option<crate::types::handlers::TransactionContext>, // NEAR data from main thread
+    pub reserved_nonces: Option<Vec<String>>, // NEAR nonces the main thread reserved for this request
+    pub collection_error: Option<CollectionError>, // Set when WebAuthn credential collection failed
     pub error: Option<String>, // Error message if confirmation failed
+    pub error_code: Option<String>, // SignerErrorCode name when the main thread refused to prompt (e.g. "ChallengeExpired")
+    pub countdown: Option<CountdownReport>, // Auto-proceed handshake seen by the confirmation bridge
+    /// The prompt's render nonce and origin, forwarded by the bridge from the trusted origin
+    /// only (see confirmation_origin.rs)
+    pub render_attestation: Option<RenderAttestation>,
+    /// Sealed `prf_output` and credential PRF results, keyed by JSON pointer (see sealed_secrets.rs)
+    pub sealed_secrets: Option<HashMap<String, String>>,
+    /// When the main thread collected the credential; replaced by the time the worker vouches
+    /// for when the response is accepted, None when it vouches for none (see elevation.rs)
+    pub credential_collected_at_ms: Option<f64>,
+    /// Deadline for the confirmation, set by the worker when it asks (never read from the response)
+    #[serde(skip)]
+    pub confirmation_expires_at_ms: Option<f64>,
+}
+
+/// WebAuthn credential collection failure reported by the main thread
+/// (`name` is the DOMException name from navigator.credentials.get())
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionError {
+    pub name: String,
+    pub message: String,
+}
+
+impl CollectionError {
+    pub fn error_code(&self) -> SignerErrorCode {
+        match self.name.as_str() {
+            "NotAllowedError" | "AbortError" => SignerErrorCode::UserDeclined,
+            "TimeoutError" => SignerErrorCode::CredentialTimeout,
+            _ => SignerErrorCode::CredentialCollectionFailed,
+        }
+    }
+}
+
+/// Cleans up after a failed credential collection: releases the request's reserved nonces,
+/// clears its confirmation nonce and records the outcome in the audit log.
+/// Returns the error code to surface to the TS layer.
+pub fn handle_collection_error(
+    account: &AccountId,
+    request_id: &str,
+    operation: &str,
+    collection_error: &CollectionError,
+    logs: &mut Vec<String>,
+) -> SignerErrorCode {
+    let error_code = collection_error.error_code();
+    let released = state::release_nonces(account, request_id);
+    state::clear_confirmation_nonce(request_id);
+    state::record_audit(
+        account,
+        request_id,
+        operation,
+        error_code.as_str(),
+        Some(format!("{}: {}", collection_error.name, collection_error.message)),
+    );
+    logs.push(format!(
+        "Credential collection failed ({}): {}; released {} reserved nonce(s)",
+        collection_error.name,
+        collection_error.message,
+        released.len()
+    ));
+    error_code
+}
+
+/// Challenge acceptance parameters sent with the confirmation request, so the UI shows
+/// (and refuses on) the same expiry estimate the worker enforces after confirmation
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChallengeExpiryPolicy {
+    pub average_block_time_ms: u32,
+    pub acceptance_window_blocks: u64,
+}
+
+/// How old a VRF challenge is and roughly how long the contract will still accept it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChallengeExpiryEstimate {
+    pub challenge_age_blocks: u64,
+    pub estimated_expiry_seconds: u64,
+}
+
+/// Average block time for the network behind `near_rpc_url`
+pub fn average_block_time_ms_for_network(near_rpc_url: &str) -> u32 {
+    if near_rpc_url.contains("testnet") {
+        NEAR_TESTNET_AVERAGE_BLOCK_TIME_MS
+    } else if near_rpc_url.contains("mainnet") {
+        NEAR_MAINNET_AVERAGE_BLOCK_TIME_MS
+    } else {
+        DEFAULT_AVERAGE_BLOCK_TIME_MS
+    }
+}
+
+/// Expiry parameters for a request: WorkerPolicy.averageBlockTimeMs overrides the network default
+pub fn challenge_expiry_policy(
+    worker_policy: Option<&WorkerPolicy>,
+    near_rpc_url: &str,
+) -> ChallengeExpiryPolicy {
+    ChallengeExpiryPolicy {
+        average_block_time_ms: worker_policy
+            .and_then(|p| p.average_block_time_ms)
+            .unwrap_or_else(|| average_block_time_ms_for_network(near_rpc_url)),
+        acceptance_window_blocks: VRF_CHALLENGE_ACCEPTANCE_WINDOW_BLOCKS,
+    }
+}
+
+/// Estimates the remaining lifetime of a challenge issued at `challenge_block_height`, given the
+/// current chain height. Fails when the challenge is already past the acceptance window.
+pub fn estimate_challenge_expiry(
+    challenge_block_height: u64,
+    current_block_height: u64,
+    policy: &ChallengeExpiryPolicy,
+) -> Result<ChallengeExpiryEstimate, String> {
+    let challenge_age_blocks = current_block_height.saturating_sub(challenge_block_height);
+    if challenge_age_blocks > policy.acceptance_window_blocks {
+        return Err(format!(
+            "Challenge expired: issued at block {}, chain is at {} ({} blocks old, contract accepts {})",
+            challenge_block_height,
+            current_block_height,
+            challenge_age_blocks,
+            policy.acceptance_window_blocks
+        ));
+    }
+    let remaining_blocks = policy.acceptance_window_blocks - challenge_age_blocks;
+    Ok(ChallengeExpiryEstimate {
+        challenge_age_blocks,
+        estimated_expiry_seconds: remaining_blocks * policy.average_block_time_ms as u64 / 1000,
+    })
+}
+
+/// Checks the confirmed VRF challenge against the chain height the main thread fetched for signing.
+/// Returns None for generic-anchored challenges: their freshness isn't measured in NEAR blocks.
+pub fn check_challenge_expiry(
+    vrf_challenge: &crate::types::VrfChallenge,
+    transaction_context: &TransactionContext,
+    policy: &ChallengeExpiryPolicy,
+) -> Result<Option<ChallengeExpiryEstimate>, String> {
+    if !vrf_challenge.is_near_anchored() {
+        return Ok(None);
+    }
+    let (challenge_block_height, current_block_height) =
+        challenge_block_heights(vrf_challenge, transaction_context)?;
+    estimate_challenge_expiry(challenge_block_height, current_block_height, policy).map(Some)
+}
+
+/// Challenge anchor height and the signing height
+fn challenge_block_heights(
+    vrf_challenge: &crate::types::VrfChallenge,
+    transaction_context: &TransactionContext,
+) -> Result<(u64, u64), String> {
+    let challenge_block_height = vrf_challenge
+        .block_height
+        .parse::<u64>()
+        .map_err(|e| format!("Invalid VRF challenge block height: {}", e))?;
+    let current_block_height = transaction_context
+        .tx_block_height
+        .parse::<u64>()
+        .map_err(|e| format!("Invalid transaction block height: {}", e))?;
+    Ok((challenge_block_height, current_block_height))
+}
+
+/// Confirmation window for a request: ConfirmationConfig.confirmationTimeoutMs or the default
+pub fn confirmation_timeout_ms(config: Option<&ConfirmationConfig>) -> u32 {
+    config
+        .and_then(|c| c.confirmation_timeout_ms)
+        .unwrap_or(DEFAULT_CONFIRMATION_TIMEOUT_MS)
+}
+
+/// Checks a confirmed request against its confirmation deadline and its challenge's acceptance
+/// window. When both have passed, the one that passed first is reported, with its own error code.
+pub fn check_request_expiry(
+    confirmation_expires_at_ms: Option<f64>,
+    confirmed_at_ms: f64,
+    challenge: Option<(&crate::types::VrfChallenge, &TransactionContext)>,
+    policy: &ChallengeExpiryPolicy,
+) -> Result<Option<ChallengeExpiryEstimate>, (SignerErrorCode, String)> {
+    let challenge_check = match challenge {
+        Some((vrf_challenge, transaction_context)) => {
+            check_challenge_expiry(vrf_challenge, transaction_context, policy)
+        }
+        None => Ok(None),
+    };
+    let confirmation_expired_at_ms =
+        confirmation_expires_at_ms.filter(|expires_at_ms| confirmed_at_ms > *expires_at_ms);
+    let confirmation_expired = |expires_at_ms: f64| {
+        (
+            SignerErrorCode::ConfirmationExpired,
+            format!(
+                "Confirmation expired: confirmed {}ms after the confirmation window closed",
+                (confirmed_at_ms - expires_at_ms).round()
+            ),
+        )
+    };
+
+    match (challenge_check, confirmation_expired_at_ms) {
+        (Ok(estimate), None) => Ok(estimate),
+        (Ok(_), Some(expires_at_ms)) => Err(confirmation_expired(expires_at_ms)),
+        (Err(challenge_error), None) => Err((SignerErrorCode::ChallengeExpired, challenge_error)),
+        (Err(challenge_error), Some(expires_at_ms)) => {
+            // Date the challenge's expiry from how many blocks past the window the chain is
+            let challenge_expired_at_ms = challenge
+                .and_then(|(vrf_challenge, transaction_context)| {
+                    challenge_block_heights(vrf_challenge, transaction_context).ok()
+                })
+                .map(|(challenge_block_height, current_block_height)| {
+                    let overdue_blocks = current_block_height
+                        .saturating_sub(challenge_block_height)
+                        .saturating_sub(policy.acceptance_window_blocks);
+                    confirmed_at_ms - (overdue_blocks * policy.average_block_time_ms as u64) as f64
+                });
+            match challenge_expired_at_ms {
+                Some(challenge_expired_at_ms) if challenge_expired_at_ms < expires_at_ms => {
+                    Err((SignerErrorCode::ChallengeExpired, challenge_error))
+                }
+                _ => Err(confirmation_expired(expires_at_ms)),
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ConfirmationSummaryAction {
     pub to: String,
-    #[serde(rename = "totalAmount")]
     pub total_amount: String,
 }
 
@@ -74,6 +326,8 @@ pub fn validate_and_normalize_confirmation_config(
     config: &ConfirmationConfig,
 ) -> ConfirmationConfig {
     let mut normalized = config.clone();
+    let timeout_ms = confirmation_timeout_ms(Some(config));
+    normalized.confirmation_timeout_ms = Some(timeout_ms);
 
     match config.ui_mode {
         ConfirmationUIMode::Skip => {
@@ -91,9 +345,9 @@ pub fn validate_and_normalize_confirmation_config(
                     }
                 },
                 ConfirmationBehavior::AutoProceed => {
-                    if config.auto_proceed_delay.is_none() {
-                        normalized.auto_proceed_delay = Some(2000);
-                    }
+                    // Auto-proceeding after the window closes would only produce ConfirmationExpired
+                    normalized.auto_proceed_delay =
+                        Some(config.auto_proceed_delay.unwrap_or(2000).min(timeout_ms));
                 }
             }
         }
@@ -104,7 +358,9 @@ pub fn validate_and_normalize_confirmation_config(
 
 /// Generates a unique request ID for confirmation requests using timestamp and random value
 pub fn generate_request_id() -> String {
-    format!("{}-{}", js_sys::Date::now(), js_sys::Math::random())
+    let mut random = [0u8; 8];
+    let _ = getrandom::getrandom(&mut random);
+    format!("{}-{}", state::now_ms(), u64::from_le_bytes(random))
 }
 
 /// Creates a transaction summary for user confirmation based on all transactions
@@ -221,19 +477,137 @@ pub fn create_transaction_summary_from_parsed(
 /// as we insert keys in a stable order). The UI should either mirror this order
 /// when building its digest input or alphabetize keys consistently before hashing
 /// to avoid ordering-related drift.
+///
+/// With `first_time_receivers`, each object also carries its `firstTimeReceiver` flag (null
+/// when the receiver history is unknown), so the annotation shown is covered by the digest.
 pub fn compute_intent_digest_from_js_inputs(
-    receivers_and_actions: &[(String, Vec<ActionParams>)]
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+    first_time_receivers: Option<&[Option<bool>]>,
 ) -> Result<String, String> {
+    digest_tx_signing_requests_json(tx_signing_requests_json(receivers_and_actions, first_time_receivers))
+}
 
-    // Build the exact JSON structure passed to the main thread
-    let js_array: Vec<serde_json::Value> = receivers_and_actions.iter()
-        .map(|(receiver_id, actions)| {
-            serde_json::json!({
+/// The txSigningRequests array sent to the main thread, optionally annotated with
+/// `firstTimeReceiver` per transaction
+pub fn tx_signing_requests_json(
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+    first_time_receivers: Option<&[Option<bool>]>,
+) -> Vec<serde_json::Value> {
+    receivers_and_actions.iter().enumerate()
+        .map(|(i, (receiver_id, actions))| {
+            let mut tx = serde_json::json!({
                 "receiverId": receiver_id,
                 "actions": actions
-            })
+            });
+            if let Some(flags) = first_time_receivers {
+                tx["firstTimeReceiver"] = serde_json::json!(flags.get(i).copied().flatten());
+            }
+            tx
+        })
+        .collect()
+}
+
+/// The txSigningRequests array of a signing request's confirmation: annotated with
+/// `firstTimeReceiver`, for chunked deploys each transaction's `deployStep` and, with a
+/// summary policy, each transaction's `summaryBlocks` (see confirmation_blocks.rs) and the
+/// first transaction's `summarySpeech` (see confirmation_speech.rs), and each transaction's
+/// `dependsOn` (see tx_dependencies.rs) and `autoInsertedActions` (see storage_deposit.rs). With
+/// the policy's redact paths the args values are shown redacted, each transaction listing its
+/// `redactions` (see redaction.rs). With a summary policy, args values over its render budget
+/// are shown truncated, each transaction listing its `truncations`, and the summary blocks are
+/// kept within the budget (see render_budget.rs).
+pub fn confirmation_tx_signing_requests_json(
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+    first_time_receivers: &[Option<bool>],
+    deploy_manifest: Option<&DeployManifest>,
+    summary_policy: Option<&SummaryPolicy>,
+) -> Vec<serde_json::Value> {
+    let redact_paths = summary_policy.map(|p| p.redact_paths.as_slice()).unwrap_or_default();
+    let mut redactions = Vec::new();
+    let mut truncations = Vec::new();
+    let shown: Vec<(String, Vec<ActionParams>)> = receivers_and_actions.iter()
+        .map(|(receiver_id, actions)| {
+            let (mut shown_actions, tx_redactions) = redaction::redact_actions(actions, redact_paths);
+            redactions.push(tx_redactions);
+            if let Some(policy) = summary_policy {
+                let (truncated, tx_truncations) =
+                    render_budget::truncate_actions(&shown_actions, &policy.render_budget);
+                shown_actions = truncated;
+                truncations.push(tx_truncations);
+            }
+            (receiver_id.clone(), shown_actions)
         })
         .collect();
+    let receivers_and_actions = shown.as_slice();
+    let mut txs = tx_signing_requests_json(receivers_and_actions, Some(first_time_receivers));
+    if !redact_paths.is_empty() {
+        for (tx, tx_redactions) in txs.iter_mut().zip(&redactions) {
+            tx["redactions"] = serde_json::json!(tx_redactions);
+        }
+    }
+    for (tx, tx_truncations) in txs.iter_mut().zip(&truncations) {
+        if !tx_truncations.is_empty() {
+            tx["truncations"] = serde_json::json!(tx_truncations);
+        }
+    }
+    if let Some(manifest) = deploy_manifest {
+        for (i, tx) in txs.iter_mut().enumerate() {
+            tx["deployStep"] = serde_json::json!(manifest.deploy_step(i));
+        }
+    }
+    if let Some(policy) = summary_policy {
+        for (tx, depends_on) in txs.iter_mut().zip(&policy.depends_on) {
+            if !depends_on.is_empty() {
+                tx["dependsOn"] = serde_json::json!(depends_on);
+            }
+        }
+        for (tx, inserted) in txs.iter_mut().zip(&policy.auto_inserted_actions) {
+            if !inserted.is_empty() {
+                tx["autoInsertedActions"] = serde_json::json!(inserted);
+            }
+        }
+        let mut batch = Vec::new();
+        let tx_blocks = batch_summary_blocks(receivers_and_actions, first_time_receivers, policy);
+        for (tx, blocks) in txs.iter_mut().zip(tx_blocks) {
+            tx["summaryBlocks"] = serde_json::json!(blocks);
+            batch.extend(blocks);
+        }
+        if let Some(first) = txs.first_mut() {
+            first["summarySpeech"] = serde_json::json!(summary_speech(
+                receivers_and_actions,
+                first_time_receivers,
+                &batch,
+                policy.locale,
+            ));
+        }
+    }
+    txs
+}
+
+/// Intent digest over `confirmation_tx_signing_requests_json`, so the deploy steps and
+/// summary blocks shown are covered like the receiver annotations
+pub fn compute_confirmation_intent_digest(
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+    first_time_receivers: &[Option<bool>],
+    deploy_manifest: Option<&DeployManifest>,
+    summary_policy: Option<&SummaryPolicy>,
+) -> Result<String, String> {
+    if deploy_manifest.is_none() && summary_policy.is_none() {
+        return compute_intent_digest_from_js_inputs(
+            receivers_and_actions,
+            Some(first_time_receivers),
+        );
+    }
+    digest_tx_signing_requests_json(confirmation_tx_signing_requests_json(
+        receivers_and_actions,
+        first_time_receivers,
+        deploy_manifest,
+        summary_policy,
+    ))
+}
+
+/// Digest of a txSigningRequests array as sent to the main thread (keys alphabetized)
+pub fn digest_tx_signing_requests_json(js_array: Vec<serde_json::Value>) -> Result<String, String> {
 
     // alphabetize keys recursively to ensure deterministic JSON so that digest hashes
     // match the ones calcuated in the JS main thread (which also alphabetize JSON keys)
@@ -266,17 +640,74 @@ pub fn compute_intent_digest_from_js_inputs(
     Ok(base64_url_encode(&hash))
 }
 
+/// Adds `payload.diffFromPrevious` when the request repeats a recently confirmed call.
+/// Informational only: the intent digest is computed without it.
+fn attach_diff_from_previous(
+    request_obj: &mut Value,
+    near_account_id: &str,
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+) {
+    // An empty account ID has no confirmed history
+    let Ok(account) = AccountId::new(near_account_id.to_string()) else {
+        return;
+    };
+    let diffs = tx_diff::diff_from_previous(
+        receivers_and_actions,
+        &state::recent_confirmed_intents(&account),
+    );
+    if !diffs.is_empty() {
+        request_obj["payload"]["diffFromPrevious"] = serde_json::json!(diffs);
+    }
+}
+
+/// Adds `payload.allowanceAfterEstimate` for function-call signing keys (see allowance.rs).
+/// Informational like diffFromPrevious; the LowAllowance warning block is the digested part.
+fn attach_allowance_after_estimate(request_obj: &mut Value, allowance: Option<&AllowanceEstimate>) {
+    if let Some(after) = allowance.and_then(AllowanceEstimate::allowance_after) {
+        request_obj["payload"]["allowanceAfterEstimate"] = serde_json::json!(after.to_string());
+    }
+}
+
+/// Adds `payload.removedDuplicateIndexes` (with `deduplicate`) and `payload.verifiedPreviewDigest`
+/// (with `previewDigest`), so the user knows the batch shown is deduplicated and the one
+/// previewed (see batch_preview.rs). The txSigningRequests shown, and digested, are that batch.
+fn attach_batch_preparation(request_obj: &mut Value, preparation: Option<&BatchPreparation>) {
+    let Some(preparation) = preparation else {
+        return;
+    };
+    if let Some(removed) = &preparation.removed_duplicate_indexes {
+        request_obj["payload"]["removedDuplicateIndexes"] = serde_json::json!(removed);
+    }
+    if let Some(digest) = &preparation.verified_preview_digest {
+        request_obj["payload"]["verifiedPreviewDigest"] = serde_json::json!(digest);
+    }
+}
+
+/// Adds `payload.vrfChallenge` when the worker got the challenge over the peer port; the main
+/// thread then prompts with it instead of generating one
+fn attach_peer_challenge(request_obj: &mut Value, peer_challenge: Option<&crate::types::VrfChallenge>) {
+    if let Some(challenge) = peer_challenge {
+        request_obj["payload"]["vrfChallenge"] = serde_json::json!(challenge);
+    }
+}
+
 /// Requests user confirmation for transaction signing with comprehensive error handling
+/// (`first_time_receivers` annotates each transaction in the payload and its digest;
+/// `peer_challenge` is the VRF challenge from the peer port, if connected)
 pub async fn request_user_confirmation(
     tx_batch_request: &SignTransactionsWithActionsRequest,
+    first_time_receivers: &[Option<bool>],
+    peer_challenge: Option<&crate::types::VrfChallenge>,
     logs: &mut Vec<String>,
 ) -> Result<ConfirmationResult, String> {
-    request_user_confirmation_with_config(tx_batch_request, logs).await
+    request_user_confirmation_with_config(tx_batch_request, first_time_receivers, peer_challenge, logs).await
 }
 
 /// Requests user confirmation with configurable options
 pub async fn request_user_confirmation_with_config(
     tx_batch_request: &SignTransactionsWithActionsRequest,
+    first_time_receivers: &[Option<bool>],
+    peer_challenge: Option<&crate::types::VrfChallenge>,
     logs: &mut Vec<String>,
 ) -> Result<ConfirmationResult, String> {
     // Validate input
@@ -285,6 +716,11 @@ pub async fn request_user_confirmation_with_config(
     }
 
     let first_request = &tx_batch_request.tx_signing_requests[0];
+    let deploy_manifest = tx_batch_request.deploy_manifest.as_ref();
+    let expiry_policy = challenge_expiry_policy(
+        tx_batch_request.worker_policy.as_ref(),
+        &tx_batch_request.rpc_call.near_rpc_url,
+    );
 
     // Pre-parse actions once for summary and UI payloads
     let parsed_receivers_and_actions: Vec<(String, Vec<ActionParams>)> = tx_batch_request
@@ -295,9 +731,53 @@ pub async fn request_user_confirmation_with_config(
             (tx.receiver_id.clone(), actions)
         })
         .collect();
+    let allowance = allowance::allowance_estimate(
+        tx_batch_request.transaction_context.as_ref(),
+        &parsed_receivers_and_actions,
+        tx_batch_request.worker_policy.as_ref(),
+    )?;
+    let signing_key = tx_batch_request
+        .signing_public_key
+        .as_deref()
+        .map(key_selection::parse_public_key)
+        .transpose()?;
+    let summary_policy = SummaryPolicy::from_worker_policy(tx_batch_request.worker_policy.as_ref())?
+        .with_deadline(tx_batch_request.valid_until_block_height)
+        .with_allowance(allowance)
+        .with_signing_key(signing_key)
+        .with_redactions(
+            redaction::parse_redact_paths(&tx_batch_request.redact_paths).map_err(|e| e.to_string())?,
+        )
+        .with_locale(SpeechLocale::from_config(tx_batch_request.confirmation_config.as_ref()))
+        .with_conditional(tx_batch_request.conditional_batch.is_some())
+        .with_dependencies(tx_dependencies::depends_on(&tx_batch_request.tx_signing_requests))
+        .with_auto_inserted_actions(tx_batch_request.auto_inserted_actions.clone());
+
+    // Full access AddKey and DeleteAccount to another account are confirmed with a click,
+    // whatever the request's config says (see action_firewall.rs)
+    let high_risk = action_firewall::high_risk_actions(
+        &first_request.near_account_id,
+        &parsed_receivers_and_actions,
+    );
+    if !high_risk.is_empty() {
+        logs.push(format!(
+            "Escalating confirmation to requireClick for {} high-risk actions",
+            high_risk.len()
+        ));
+    }
+    let confirmation_config = action_firewall::escalate_confirmation_config(
+        tx_batch_request.confirmation_config.as_ref(),
+        &high_risk,
+    );
+    // Automatically inserted actions are always shown (see storage_deposit.rs)
+    let confirmation_config = storage_deposit::show_insertions(
+        confirmation_config,
+        &tx_batch_request.auto_inserted_actions,
+        logs,
+    );
 
     // Check if UI mode is Skip - still collect credentials and PRF output via the bridge (no additional UI shown)
-    if let Some(confirmation_config) = &tx_batch_request.confirmation_config {
+    if let Some(confirmation_config) = &confirmation_config {
 
         let should_skip_ui_confirm = confirmation_config.ui_mode == ConfirmationUIMode::Skip;
 
@@ -308,7 +788,7 @@ pub async fn request_user_confirmation_with_config(
             // but we don't show any UI. The main thread should handle this.
             // For now, we'll still call the JS bridge but with a flag to indicate no UI
             // Compute digest over the same structure we pass to the main thread/UI
-            let intent_digest = compute_intent_digest_from_js_inputs(&parsed_receivers_and_actions)
+            let intent_digest = compute_confirmation_intent_digest(&parsed_receivers_and_actions, first_time_receivers, deploy_manifest, Some(&summary_policy))
                 .map_err(|e| format!("Failed to compute intent digest: {}", e))?;
 
             let request_id = generate_request_id();
@@ -319,6 +799,8 @@ pub async fn request_user_confirmation_with_config(
 
             let summary = create_transaction_summary_from_parsed(&parsed_receivers_and_actions)
                 .map_err(|e| format!("Failed to create transaction summary: {}", e))?;
+            let confirmation_expires_at_ms =
+                state::now_ms() + confirmation_timeout_ms(Some(&normalized_config)) as f64;
 
             let confirmation_data = serde_json::json!({
                 "intentDigest": intent_digest,
@@ -329,15 +811,10 @@ pub async fn request_user_confirmation_with_config(
             });
 
             // Convert actions: str to serde_json::Value first before serializing (to avoid double-encoding strings)
-            let tx_signing_requests_json = parsed_receivers_and_actions.iter().map(|(receiver_id, actions)| {
-                serde_json::json!({
-                    "receiverId": receiver_id,
-                    "actions": actions
-                })
-            }).collect::<Vec<serde_json::Value>>();
+            let tx_signing_requests_json = confirmation_tx_signing_requests_json(&parsed_receivers_and_actions, first_time_receivers, deploy_manifest, Some(&summary_policy));
 
             // Build V2 secure confirm request
-            let request_obj = serde_json::json!({
+            let mut request_obj = serde_json::json!({
                 "schemaVersion": 2,
                 "requestId": request_id,
                 "type": "signTransaction",
@@ -346,13 +823,20 @@ pub async fn request_user_confirmation_with_config(
                     "txSigningRequests": tx_signing_requests_json,
                     "intentDigest": intent_digest,
                     "rpcCall": tx_batch_request.rpc_call,
+                    "expiryPolicy": expiry_policy,
+                    "confirmationExpiresAtMs": confirmation_expires_at_ms,
                 },
                 "confirmationConfig": normalized_config,
             });
+            attach_diff_from_previous(&mut request_obj, near_account_id, &parsed_receivers_and_actions);
+            attach_allowance_after_estimate(&mut request_obj, allowance.as_ref());
+            attach_batch_preparation(&mut request_obj, tx_batch_request.batch_preparation.as_ref());
+            attach_peer_challenge(&mut request_obj, peer_challenge);
 
             // Serialize to JSON string for robust cross-boundary cloning into TS
             // Using the same strategy as the normal confirmation flow to avoid
             // wasm-bindgen object shape issues in the TS validator.
+            register_confirmation_prompt(&mut request_obj, &request_id);
             let request_json_str = serde_json::to_string(&request_obj)
                 .map_err(|e| format!("Failed to serialize V2 confirm request to string: {}", e))?;
             web_sys::console::log_1(&format!("[Rust] V2 confirm request (tx:skip) JSON length: {}", request_json_str.len()).into());
@@ -360,7 +844,13 @@ pub async fn request_user_confirmation_with_config(
 
             let confirm_result = await_secure_confirmation_v2(request_js).await;
 
-            let result = parse_confirmation_result(confirm_result)?;
+            let mut result = parse_confirmation_result(confirm_result, &request_id)?;
+            result.confirmation_expires_at_ms = Some(confirmation_expires_at_ms);
+
+            // Credential collection failures are handled (and cleaned up) by the caller
+            if result.collection_error.is_some() {
+                return Ok(result);
+            }
 
             // For skip override, we assume the user implicitly confirms
             // but we still need the credentials and PRF output
@@ -385,7 +875,7 @@ pub async fn request_user_confirmation_with_config(
         .map_err(|e| format!("Failed to create transaction summary: {}", e))?;
 
     // Compute digest over the same structure we pass to the main thread/UI
-    let intent_digest = compute_intent_digest_from_js_inputs(&parsed_receivers_and_actions)
+    let intent_digest = compute_confirmation_intent_digest(&parsed_receivers_and_actions, first_time_receivers, deploy_manifest, Some(&summary_policy))
         .map_err(|e| format!("Failed to compute intent digest: {}", e))?;
 
     let request_id = generate_request_id();
@@ -402,11 +892,11 @@ pub async fn request_user_confirmation_with_config(
 
     // Create enhanced confirmation data with account info and configuration
     // Validate and normalize confirmation config according to documented rules
-    let normalized_config = if let Some(confirmation_config) = &tx_batch_request.confirmation_config {
-        Some(validate_and_normalize_confirmation_config(confirmation_config))
-    } else {
-        None
-    };
+    let normalized_config = confirmation_config
+        .as_ref()
+        .map(validate_and_normalize_confirmation_config);
+    let confirmation_expires_at_ms = state::now_ms()
+        + confirmation_timeout_ms(confirmation_config.as_ref()) as f64;
 
     let confirmation_data = serde_json::json!({
         "intentDigest": intent_digest,
@@ -417,15 +907,10 @@ pub async fn request_user_confirmation_with_config(
     });
 
     // Convert actions to JSON values the UI expects
-    let tx_signing_requests_json = parsed_receivers_and_actions.iter().map(|(receiver_id, actions)| {
-        serde_json::json!({
-            "receiverId": receiver_id,
-            "actions": actions
-        })
-    }).collect::<Vec<serde_json::Value>>();
+    let tx_signing_requests_json = confirmation_tx_signing_requests_json(&parsed_receivers_and_actions, first_time_receivers, deploy_manifest, Some(&summary_policy));
 
     // Build V2 secure confirm request
-    let request_obj = serde_json::json!({
+    let mut request_obj = serde_json::json!({
         "schemaVersion": 2,
         "requestId": request_id,
         "type": "signTransaction",
@@ -434,11 +919,18 @@ pub async fn request_user_confirmation_with_config(
             "txSigningRequests": tx_signing_requests_json,
             "intentDigest": intent_digest,
             "rpcCall": tx_batch_request.rpc_call,
+            "expiryPolicy": expiry_policy,
+            "confirmationExpiresAtMs": confirmation_expires_at_ms,
         },
         "confirmationConfig": normalized_config,
     });
+    attach_diff_from_previous(&mut request_obj, near_account_id, &parsed_receivers_and_actions);
+    attach_allowance_after_estimate(&mut request_obj, allowance.as_ref());
+    attach_batch_preparation(&mut request_obj, tx_batch_request.batch_preparation.as_ref());
+    attach_peer_challenge(&mut request_obj, peer_challenge);
 
     // Serialize to JSON string for robust cross-boundary cloning into TS
+    register_confirmation_prompt(&mut request_obj, &request_id);
     let request_json_str = serde_json::to_string(&request_obj)
         .map_err(|e| format!("Failed to serialize V2 confirm request to string: {}", e))?;
     web_sys::console::log_1(&format!("[Rust] V2 confirm request (tx) JSON length: {}", request_json_str.len()).into());
@@ -448,7 +940,9 @@ pub async fn request_user_confirmation_with_config(
     let confirm_result = await_secure_confirmation_v2(request_js).await;
 
     // Parse confirmation result
-    let result = parse_confirmation_result(confirm_result)?;
+    let mut result = parse_confirmation_result(confirm_result, &request_id)?;
+    result.confirmation_expires_at_ms = Some(confirmation_expires_at_ms);
+    enforce_countdown_handshake(&mut result, normalized_config.as_ref(), &intent_digest, logs);
 
     Ok(result)
 }
@@ -485,6 +979,8 @@ pub async fn request_registration_credential_confirmation(
             behavior: ConfirmationBehavior::RequireClick,
             auto_proceed_delay: None,
             theme: None,
+            confirmation_timeout_ms: None,
+            locale: None,
         },
     };
 
@@ -505,7 +1001,7 @@ pub async fn request_registration_credential_confirmation(
     });
 
     // Build V2 secure confirm request (registration/link-device)
-    let request_obj = serde_json::json!({
+    let mut request_obj = serde_json::json!({
         "schemaVersion": 2,
         "requestId": request_id,
         "type": "linkDevice",
@@ -527,6 +1023,7 @@ pub async fn request_registration_credential_confirmation(
     });
 
     // Serialize to JSON string for robust cross-boundary cloning into TS
+    register_confirmation_prompt(&mut request_obj, &request_id);
     let request_json_str = serde_json::to_string(&request_obj)
         .map_err(|e| format!("Failed to serialize V2 confirm request to string: {}", e))?;
     web_sys::console::log_1(&format!("[Rust] V2 confirm registration request JSON length: {}", request_json_str.len()).into());
@@ -534,16 +1031,266 @@ pub async fn request_registration_credential_confirmation(
 
     let confirm_result = await_secure_confirmation_v2(request_js).await;
 
-    parse_confirmation_result(confirm_result)
+    let mut result = parse_confirmation_result(confirm_result, &request_id);
+    if let Ok(result) = result.as_mut() {
+        let mut logs = Vec::new();
+        enforce_countdown_handshake(result, Some(&normalized_config), &intent_digest, &mut logs);
+    }
+    // Registration flows hold no nonce reservations; the confirmation is consumed here
+    state::clear_confirmation_nonce(&request_id);
+    result
+}
+
+/// V2 confirm request for an EVM personal_sign message: the decoded message is shown, and the
+/// intent digest is base64url of its EIP-191 hash
+#[cfg(feature = "secp256k1")]
+pub fn evm_personal_sign_confirm_request(
+    request_id: &str,
+    near_account_id: &str,
+    message: &[u8],
+    normalized_config: Option<&ConfirmationConfig>,
+    confirmation_expires_at_ms: f64,
+) -> Value {
+    use crate::confirmation_blocks::{account_row, code_block, ConfirmationSummaryBlock, RowLabel};
+    use crate::confirmation_speech::Phrase;
+    use crate::evm;
+
+    let decoded = evm::display_message(message);
+    let intent_digest = base64_url_encode(&evm::personal_sign_hash(message));
+    let locale = SpeechLocale::from_config(normalized_config);
+    let summary_blocks = vec![
+        ConfirmationSummaryBlock::Heading {
+            text: "Sign EVM Message".to_string(),
+            aria_label: locale.say(&Phrase::EvmHeading),
+        },
+        account_row(RowLabel::Account, near_account_id, None, locale),
+        code_block(
+            Value::String(decoded.clone()),
+            locale.say(&Phrase::EvmMessage { text: &decoded }),
+        ),
+    ];
+    serde_json::json!({
+        "schemaVersion": 2,
+        "requestId": request_id,
+        "type": "evmPersonalSign",
+        "summary": {
+            "operation": "Sign EVM Message",
+            "accountId": near_account_id,
+            "message": decoded,
+        },
+        "payload": {
+            "nearAccountId": near_account_id,
+            "message": decoded,
+            "messageHex": evm::hex_encode(message),
+            "intentDigest": intent_digest,
+            "summaryBlocks": summary_blocks,
+            "summarySpeech": locale.evm_summary(near_account_id),
+            "confirmationExpiresAtMs": confirmation_expires_at_ms,
+        },
+        "confirmationConfig": normalized_config,
+        "intentDigest": intent_digest,
+    })
+}
+
+/// Requests user confirmation of an EVM personal_sign message; the confirmed result carries the
+/// PRF output that decrypts the account's secp256k1 key
+#[cfg(feature = "secp256k1")]
+pub async fn request_evm_personal_sign_confirmation(
+    near_account_id: &str,
+    message: &[u8],
+    confirmation_config: Option<&ConfirmationConfig>,
+) -> Result<ConfirmationResult, String> {
+    let normalized_config = confirmation_config.map(validate_and_normalize_confirmation_config);
+    let request_id = generate_request_id();
+    let confirmation_expires_at_ms =
+        state::now_ms() + confirmation_timeout_ms(normalized_config.as_ref()) as f64;
+    let mut request_obj = evm_personal_sign_confirm_request(
+        &request_id,
+        near_account_id,
+        message,
+        normalized_config.as_ref(),
+        confirmation_expires_at_ms,
+    );
+    let intent_digest = request_obj["intentDigest"].as_str().unwrap_or_default().to_string();
+
+    register_confirmation_prompt(&mut request_obj, &request_id);
+    let request_json_str = serde_json::to_string(&request_obj)
+        .map_err(|e| format!("Failed to serialize V2 confirm request to string: {}", e))?;
+    web_sys::console::log_1(&format!("[Rust] V2 confirm request (evmPersonalSign) JSON length: {}", request_json_str.len()).into());
+    let request_js = JsValue::from_str(&request_json_str);
+
+    let confirm_result = await_secure_confirmation_v2(request_js).await;
+
+    let mut result = parse_confirmation_result(confirm_result, &request_id);
+    if let Ok(result) = result.as_mut() {
+        result.confirmation_expires_at_ms = Some(confirmation_expires_at_ms);
+        let mut logs = Vec::new();
+        enforce_countdown_handshake(result, normalized_config.as_ref(), &intent_digest, &mut logs);
+    }
+    // Message signing holds no nonce reservations; the confirmation is consumed here
+    state::clear_confirmation_nonce(&request_id);
+    result
+}
+
+#[cfg(feature = "device-linking")]
+/// Requests user confirmation of another device's transactions (ApproveRemoteConfirmation).
+/// The main thread shows them like a local signing request and prompts the passkey with the
+/// request's VRF challenge (`payload.vrfChallenge`, used as-is); `rpc_call` is this device's.
+pub async fn request_remote_user_confirmation(
+    remote_request: &RemoteConfirmationRequest,
+    rpc_call: &RpcCallPayload,
+    confirmation_config: Option<&ConfirmationConfig>,
+    logs: &mut Vec<String>,
+) -> Result<ConfirmationResult, String> {
+    // The summary only; what is shown (and digested) is the request's txSigningRequests
+    let parsed_receivers_and_actions: Vec<(String, Vec<ActionParams>)> = remote_request
+        .tx_signing_requests
+        .iter()
+        .map(|tx| {
+            let receiver_id = tx["receiverId"].as_str().unwrap_or_default().to_string();
+            let actions = serde_json::from_value(tx["actions"].clone()).unwrap_or_default();
+            (receiver_id, actions)
+        })
+        .collect();
+    let summary = create_transaction_summary_from_parsed(&parsed_receivers_and_actions)
+        .map_err(|e| format!("Failed to create transaction summary: {}", e))?;
+    let high_risk = action_firewall::high_risk_actions(
+        &remote_request.near_account_id,
+        &parsed_receivers_and_actions,
+    );
+    let normalized_config =
+        action_firewall::escalate_confirmation_config(confirmation_config, &high_risk)
+            .as_ref()
+            .map(validate_and_normalize_confirmation_config);
+    let request_id = generate_request_id();
+    logs.push(format!(
+        "Prompting confirmation of remote request {} ({} transactions)",
+        remote_request.request_id,
+        remote_request.tx_signing_requests.len()
+    ));
+
+    let mut request_obj = serde_json::json!({
+        "schemaVersion": 2,
+        "requestId": request_id,
+        "type": "signTransaction",
+        "summary": summary,
+        "payload": {
+            "txSigningRequests": remote_request.tx_signing_requests,
+            "intentDigest": remote_request.intent_digest,
+            "rpcCall": rpc_call,
+            "expiryPolicy": challenge_expiry_policy(None, &rpc_call.near_rpc_url),
+            "confirmationExpiresAtMs": remote_request.expires_at_ms,
+        },
+        "confirmationConfig": normalized_config,
+    });
+    attach_peer_challenge(&mut request_obj, Some(&remote_request.vrf_challenge));
+
+    register_confirmation_prompt(&mut request_obj, &request_id);
+    let request_json_str = serde_json::to_string(&request_obj)
+        .map_err(|e| format!("Failed to serialize V2 confirm request to string: {}", e))?;
+    web_sys::console::log_1(&format!("[Rust] V2 confirm request (remote) JSON length: {}", request_json_str.len()).into());
+    let request_js = JsValue::from_str(&request_json_str);
+
+    let confirm_result = await_secure_confirmation_v2(request_js).await;
+
+    let mut result = parse_confirmation_result(confirm_result, &request_id)?;
+    result.confirmation_expires_at_ms = Some(remote_request.expires_at_ms);
+    enforce_countdown_handshake(&mut result, normalized_config.as_ref(), &remote_request.intent_digest, logs);
+    Ok(result)
 }
 
 /// Creates a summary for registration confirmation
 // legacy registration summary function removed with deprecated testnet flow
 
-/// Parses the confirmation result from JavaScript bridge
-fn parse_confirmation_result(confirm_result: JsValue) -> Result<ConfirmationResult, String> {
-    serde_wasm_bindgen::from_value::<ConfirmationResult>(confirm_result)
+/// Parses the confirmation result from JavaScript bridge.
+/// The response must answer the outstanding confirmation `request_id`, and its sealed secrets
+/// are opened; on error the confirmation nonce is cleared so nothing is left dangling.
+pub(crate) fn parse_confirmation_result(
+    confirm_result: JsValue,
+    request_id: &str,
+) -> Result<ConfirmationResult, String> {
+    let result = serde_wasm_bindgen::from_value::<ConfirmationResult>(confirm_result)
         .map_err(|e| format!("Failed to parse confirmation result: {}", e))
+        .and_then(|result| check_confirmation_response(result, request_id))
+        .and_then(|mut result| {
+            sealed_secrets::open_confirmation_secrets(&mut result).map_err(|e| e.to_string())?;
+            Ok(result)
+        });
+    if result.is_err() {
+        state::clear_confirmation_nonce(request_id);
+    }
+    result
+}
+
+/// Declines a confirmed auto-proceed result whose countdown handshake does not verify, with the
+/// handshake's error code (see countdown_handshake.rs)
+pub fn enforce_countdown_handshake(
+    result: &mut ConfirmationResult,
+    normalized_config: Option<&ConfirmationConfig>,
+    intent_digest: &str,
+    logs: &mut Vec<String>,
+) {
+    let Some(delay_ms) = countdown_delay_ms(normalized_config) else {
+        return;
+    };
+    if !result.confirmed {
+        return;
+    }
+    match verify_countdown_handshake(result.countdown.as_ref(), intent_digest, delay_ms) {
+        Ok(outcome) => logs.push(format!("Auto-proceed countdown handshake: {:?}", outcome)),
+        Err((code, error_msg)) => {
+            logs.push(error_msg.clone());
+            result.confirmed = false;
+            result.error_code = Some(code.as_str().to_string());
+            result.error = Some(error_msg);
+        }
+    }
+}
+
+/// Marks the confirmation as outstanding and gives its prompt the render nonce the decision
+/// must attest (see confirmation_origin.rs)
+pub(crate) fn register_confirmation_prompt(request_obj: &mut serde_json::Value, request_id: &str) {
+    request_obj["renderNonce"] = serde_json::json!(state::register_confirmation_nonce(request_id));
+}
+
+/// Rejects confirmation responses that do not match the outstanding confirmation nonce, or
+/// that lack a render attestation from the trusted confirmation origin, and bounds the
+/// credential's collection time by the nonce's issue time and the response's arrival. A
+/// confirmation made once the session expired renews it with that credential, or is rejected
+/// (see session_duration.rs).
+pub(crate) fn check_confirmation_response(
+    mut result: ConfirmationResult,
+    request_id: &str,
+) -> Result<ConfirmationResult, String> {
+    let issued_at_ms = state::confirmation_nonce_issued_at(request_id)
+        .filter(|_| result.request_id == request_id)
+        .ok_or_else(|| {
+            format!(
+                "Confirmation response for unknown request: {}",
+                result.request_id
+            )
+        })?;
+    confirmation_origin::verify_render_attestation(
+        result.render_attestation.as_ref(),
+        &state::confirmation_render_nonce(request_id).unwrap_or_default(),
+        state::trusted_confirmation_origin().as_deref(),
+    )
+    .map_err(|e| e.to_string())?;
+    let has_credential = result.credential.is_some() || result.prf_output.is_some();
+    result.credential_collected_at_ms = if has_credential {
+        elevation::collection_time(
+            result.credential_collected_at_ms,
+            issued_at_ms,
+            state::now_ms(),
+        )
+    } else {
+        None
+    };
+    if result.confirmed {
+        session_duration::renew_with_credential(result.credential_collected_at_ms)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(result)
 }
 
 
@@ -560,9 +1307,269 @@ mod tests {
 
     #[test]
     fn test_compute_intent_digest_empty() {
-        let empty_requests: Vec<TransactionPayload> = vec![];
-        let result = compute_intent_digest(&empty_requests);
+        let empty_requests: Vec<(String, Vec<ActionParams>)> = vec![];
+        let result = compute_intent_digest_from_js_inputs(&empty_requests, None);
         assert!(result.is_ok());
         assert!(!result.unwrap().is_empty());
     }
+
+    fn declined_confirmation(request_id: &str, name: &str) -> ConfirmationResult {
+        serde_json::from_value(serde_json::json!({
+            "request_id": request_id,
+            "confirmed": false,
+            "reserved_nonces": ["101", "102"],
+            "collection_error": { "name": name, "message": "The operation was not allowed" },
+            "error": "The operation was not allowed"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_collection_error_codes() {
+        let code = |name: &str| {
+            declined_confirmation("req-codes", name)
+                .collection_error
+                .unwrap()
+                .error_code()
+        };
+        assert_eq!(code("NotAllowedError"), SignerErrorCode::UserDeclined);
+        assert_eq!(code("AbortError"), SignerErrorCode::UserDeclined);
+        assert_eq!(code("TimeoutError"), SignerErrorCode::CredentialTimeout);
+        assert_eq!(code("InvalidStateError"), SignerErrorCode::CredentialCollectionFailed);
+    }
+
+    #[test]
+    fn test_handle_collection_error_releases_request_state() {
+        let request_id = "req-declined";
+        let result = declined_confirmation(request_id, "NotAllowedError");
+        state::register_confirmation_nonce(request_id);
+        let account = AccountId("alice.testnet".to_string());
+        state::reserve_nonces(&account, request_id, result.reserved_nonces.clone().unwrap());
+
+        let mut logs = Vec::new();
+        let code = handle_collection_error(
+            &account,
+            request_id,
+            "signTransactionsWithActions",
+            result.collection_error.as_ref().unwrap(),
+            &mut logs,
+        );
+
+        assert_eq!(code, SignerErrorCode::UserDeclined);
+        assert!(state::reserved_nonces(&account, request_id).is_empty());
+        assert!(!state::has_confirmation_nonce(request_id));
+        let audit = state::audit_entries_for(request_id);
+        assert_eq!(audit.len(), 1);
+        assert_eq!(audit[0].outcome, "UserDeclined");
+        assert!(logs.iter().any(|l| l.contains("released 2 reserved nonce(s)")));
+    }
+
+    #[test]
+    fn test_challenge_expiry_estimates() {
+        let policy = challenge_expiry_policy(None, "https://rpc.testnet.near.org");
+        assert_eq!(policy.average_block_time_ms, NEAR_TESTNET_AVERAGE_BLOCK_TIME_MS);
+        assert_eq!(policy.acceptance_window_blocks, VRF_CHALLENGE_ACCEPTANCE_WINDOW_BLOCKS);
+
+        let fresh = estimate_challenge_expiry(1_000, 1_000, &policy).unwrap();
+        assert_eq!(fresh.challenge_age_blocks, 0);
+        assert_eq!(fresh.estimated_expiry_seconds, 120); // 100 blocks * 1.2s
+
+        let aging = estimate_challenge_expiry(1_000, 1_070, &policy).unwrap();
+        assert_eq!(aging.challenge_age_blocks, 70);
+        assert_eq!(aging.estimated_expiry_seconds, 36);
+
+        // Last accepted block, then expired
+        assert_eq!(estimate_challenge_expiry(1_000, 1_100, &policy).unwrap().estimated_expiry_seconds, 0);
+        let err = estimate_challenge_expiry(1_000, 1_101, &policy).unwrap_err();
+        assert!(err.contains("101 blocks old"));
+        assert!(err.contains("accepts 100"));
+    }
+
+    #[test]
+    fn test_challenge_expiry_skips_generic_anchors() {
+        let policy = challenge_expiry_policy(None, "https://rpc.testnet.near.org");
+        let context = TransactionContext {
+            near_public_key_str: "ed25519:key".to_string(),
+            next_nonce: "1".to_string(),
+            tx_block_height: "5000".to_string(),
+            tx_block_hash: "hash".to_string(),
+            access_key_allowance: None,
+            gas_price: None,
+        };
+        let challenge = |anchor: serde_json::Value| -> crate::types::VrfChallenge {
+            let mut json = serde_json::json!({
+                "vrfInput": "aW5wdXQ",
+                "vrfOutput": "b3V0cHV0",
+                "vrfProof": "cHJvb2Y",
+                "vrfPublicKey": "cGs",
+                "userId": "alice.testnet",
+                "rpId": "example.localhost",
+                "blockHeight": "1000",
+                "blockHash": "aGFzaA"
+            });
+            if !anchor.is_null() {
+                json["anchor"] = anchor;
+            }
+            serde_json::from_value(json).unwrap()
+        };
+
+        // Challenges without an anchor, or with a NEAR anchor, get the block-age check
+        let legacy = challenge(serde_json::Value::Null);
+        assert!(check_challenge_expiry(&legacy, &context, &policy).is_err());
+        let near = challenge(serde_json::json!({
+            "type": "NearBlock", "blockHeight": "1000", "blockHash": "11111111111111111111111111111111"
+        }));
+        assert!(check_challenge_expiry(&near, &context, &policy).is_err());
+
+        // Generic anchors have no NEAR block height to compare against
+        let mut generic = challenge(serde_json::json!({
+            "type": "Generic", "label": "drand:quicknet:1000", "valueB64u": "AQID"
+        }));
+        generic.block_height = String::new();
+        generic.block_hash = String::new();
+        assert_eq!(check_challenge_expiry(&generic, &context, &policy), Ok(None));
+    }
+
+    #[test]
+    fn test_confirmation_timeout_normalization() {
+        let config = ConfirmationConfig {
+            ui_mode: ConfirmationUIMode::Modal,
+            behavior: ConfirmationBehavior::AutoProceed,
+            auto_proceed_delay: Some(5_000),
+            ..ConfirmationConfig::default()
+        };
+        assert_eq!(confirmation_timeout_ms(None), DEFAULT_CONFIRMATION_TIMEOUT_MS);
+        let normalized = validate_and_normalize_confirmation_config(&config);
+        assert_eq!(normalized.confirmation_timeout_ms, Some(DEFAULT_CONFIRMATION_TIMEOUT_MS));
+        assert_eq!(normalized.auto_proceed_delay, Some(5_000));
+
+        // The auto-proceed timer never outlasts the confirmation window
+        let short = ConfirmationConfig {
+            confirmation_timeout_ms: Some(3_000),
+            ..config
+        };
+        let normalized = validate_and_normalize_confirmation_config(&short);
+        assert_eq!(normalized.confirmation_timeout_ms, Some(3_000));
+        assert_eq!(normalized.auto_proceed_delay, Some(3_000));
+    }
+
+    #[test]
+    fn test_request_expiry_reports_whichever_expired_first() {
+        // Testnet: 1.2s blocks, 100-block window
+        let policy = challenge_expiry_policy(None, "https://rpc.testnet.near.org");
+        let context = |tx_block_height: &str| TransactionContext {
+            near_public_key_str: "ed25519:key".to_string(),
+            next_nonce: "1".to_string(),
+            tx_block_height: tx_block_height.to_string(),
+            tx_block_hash: "hash".to_string(),
+            access_key_allowance: None,
+            gas_price: None,
+        };
+        let vrf_challenge: crate::types::VrfChallenge = serde_json::from_value(serde_json::json!({
+            "vrfInput": "aW5wdXQ",
+            "vrfOutput": "b3V0cHV0",
+            "vrfProof": "cHJvb2Y",
+            "vrfPublicKey": "cGs",
+            "userId": "alice.testnet",
+            "rpId": "example.localhost",
+            "blockHeight": "1000",
+            "blockHash": "aGFzaA"
+        }))
+        .unwrap();
+        let fresh = context("1050");
+        let stale = context("1110"); // 10 blocks (~12s) past the window
+        let confirmed_at_ms = 1_000_000.0;
+        let code = |expires_at_ms: Option<f64>, ctx: &TransactionContext| {
+            check_request_expiry(expires_at_ms, confirmed_at_ms, Some((&vrf_challenge, ctx)), &policy)
+                .map_err(|(code, _)| code)
+        };
+
+        // Only one of the two expired
+        assert!(code(Some(confirmed_at_ms + 1.0), &fresh).unwrap().is_some());
+        assert_eq!(code(Some(confirmed_at_ms - 1.0), &fresh), Err(SignerErrorCode::ConfirmationExpired));
+        assert_eq!(code(Some(confirmed_at_ms + 1.0), &stale), Err(SignerErrorCode::ChallengeExpired));
+        assert_eq!(code(None, &stale), Err(SignerErrorCode::ChallengeExpired));
+
+        // Both expired: the challenge passed its window ~12s before confirming
+        let challenge_first = code(Some(confirmed_at_ms - 5_000.0), &stale);
+        assert_eq!(challenge_first, Err(SignerErrorCode::ChallengeExpired));
+        let confirmation_first = code(Some(confirmed_at_ms - 20_000.0), &stale);
+        assert_eq!(confirmation_first, Err(SignerErrorCode::ConfirmationExpired));
+
+        let (_, message) =
+            check_request_expiry(Some(confirmed_at_ms - 1.0), confirmed_at_ms, None, &policy)
+                .unwrap_err();
+        assert!(message.starts_with("Confirmation expired"), "{}", message);
+    }
+
+    #[test]
+    fn test_challenge_expiry_policy_override() {
+        let worker_policy = WorkerPolicy {
+            average_block_time_ms: Some(500),
+            ..WorkerPolicy::default()
+        };
+        let policy = challenge_expiry_policy(Some(&worker_policy), "https://rpc.mainnet.near.org");
+        assert_eq!(policy.average_block_time_ms, 500);
+        assert_eq!(
+            challenge_expiry_policy(None, "https://rpc.mainnet.near.org").average_block_time_ms,
+            NEAR_MAINNET_AVERAGE_BLOCK_TIME_MS
+        );
+        assert_eq!(
+            challenge_expiry_policy(None, "http://localhost:3030").average_block_time_ms,
+            DEFAULT_AVERAGE_BLOCK_TIME_MS
+        );
+    }
+
+    #[test]
+    fn test_confirmation_response_must_match_outstanding_request() {
+        let result = declined_confirmation("req-other", "TimeoutError");
+        assert!(check_confirmation_response(result.clone(), "req-expected").is_err());
+        // Matching ID but no outstanding confirmation nonce
+        assert!(check_confirmation_response(result.clone(), "req-other").is_err());
+
+        state::register_confirmation_nonce("req-other");
+        assert!(check_confirmation_response(result, "req-other").is_ok());
+        {
+            let account = AccountId("alice.testnet".to_string());
+            let _guard = state::PendingRequestGuard::new(&account, "req-other");
+        }
+        assert!(!state::has_confirmation_nonce("req-other"));
+    }
+
+    #[test]
+    fn test_confirmation_response_bounds_the_credential_collection_time() {
+        let clock = crate::clock::ManualClock::new(10_000.0);
+        state::set_clock(crate::clock::SharedClock::new(clock.clone()));
+        let request_id = "req-elevated";
+        let response = |collected_at_ms: Option<f64>| -> ConfirmationResult {
+            serde_json::from_value(serde_json::json!({
+                "request_id": request_id,
+                "confirmed": true,
+                "prf_output": "BwcH",
+                "credential_collected_at_ms": collected_at_ms
+            }))
+            .unwrap()
+        };
+        let checked = |result: ConfirmationResult| {
+            check_confirmation_response(result, request_id)
+                .unwrap()
+                .credential_collected_at_ms
+        };
+
+        state::register_confirmation_nonce(request_id);
+        clock.advance(5_000.0);
+        assert_eq!(checked(response(Some(12_000.0))), Some(12_000.0));
+        // Without a timestamp: when the prompt was issued
+        assert_eq!(checked(response(None)), Some(10_000.0));
+        // Capped at the response's arrival
+        assert_eq!(checked(response(Some(99_000.0))), Some(15_000.0));
+        // An assertion collected before the prompt is a replay
+        assert_eq!(checked(response(Some(9_000.0))), None);
+        // Nothing collected, nothing to vouch for
+        assert_eq!(
+            checked(declined_confirmation(request_id, "NotAllowedError")),
+            None
+        );
+        state::clear_confirmation_nonce(request_id);
+    }
 }
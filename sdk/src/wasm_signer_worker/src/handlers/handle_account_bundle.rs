@@ -0,0 +1,114 @@
+// ******************************************************************************
+// *                                                                            *
+// *              HANDLERS: EXPORT / IMPORT ACCOUNT BUNDLE                      *
+// *                                                                            *
+// ******************************************************************************
+use crate::account_bundle::{
+    open_account_bundle, seal_account_bundle, AccountBundleRecords, BundleKdfParams,
+};
+use crate::config::ACCOUNT_BUNDLE_VERSION;
+use crate::state;
+use log::info;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportAccountBundleRequest {
+    /// User-chosen passphrase (at least MIN_ACCOUNT_BUNDLE_PASSPHRASE_CHARS characters)
+    #[wasm_bindgen(getter_with_clone)]
+    pub passphrase: String,
+    /// Records read from IndexedDB by the storage layer
+    #[wasm_bindgen(skip)]
+    #[serde(deserialize_with = "crate::account_bundle::deserialize_records_object")]
+    pub records: AccountBundleRecords,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportAccountBundleResult {
+    /// Bundle file bytes
+    pub bundle: Vec<u8>,
+    pub version: u32,
+    pub record_count: u32,
+}
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportAccountBundleRequest {
+    #[wasm_bindgen(getter_with_clone)]
+    pub passphrase: String,
+    /// Bundle file bytes, as produced by ExportAccountBundle
+    #[wasm_bindgen(getter_with_clone)]
+    pub bundle: Vec<u8>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportAccountBundleResult {
+    /// Validated records for the storage layer to persist, in the current schema
+    pub records: AccountBundleRecords,
+    pub version: u32,
+    pub exported_at_ms: f64,
+}
+
+/// **Handles:** `WorkerRequestType::ExportAccountBundle`
+/// Validates the records and encrypts them into a bundle file under a key derived from the
+/// passphrase with Argon2id (see account_bundle.rs).
+///
+/// # Arguments
+/// * `request` - The passphrase and the records of every store to export
+///
+/// # Returns
+/// * `ExportAccountBundleResult` - The bundle bytes, its version and the number of records
+pub async fn handle_export_account_bundle(
+    request: ExportAccountBundleRequest,
+) -> Result<ExportAccountBundleResult, String> {
+    let record_count = request.records.record_count() as u32;
+    let bundle = seal_account_bundle(
+        request.records,
+        &request.passphrase,
+        BundleKdfParams::generate()?,
+        state::now_ms(),
+    )?;
+
+    info!(
+        "RUST: Exported account bundle ({} records, {} bytes)",
+        record_count,
+        bundle.len()
+    );
+    Ok(ExportAccountBundleResult {
+        bundle,
+        version: ACCOUNT_BUNDLE_VERSION,
+        record_count,
+    })
+}
+
+/// **Handles:** `WorkerRequestType::ImportAccountBundle`
+/// Decrypts a bundle and validates each record's structure. Nothing is persisted here: the
+/// storage layer writes the returned records.
+///
+/// # Arguments
+/// * `request` - The passphrase and the bundle bytes
+///
+/// # Returns
+/// * `ImportAccountBundleResult` - The records, the bundle version and its export time
+pub async fn handle_import_account_bundle(
+    request: ImportAccountBundleRequest,
+) -> Result<ImportAccountBundleResult, String> {
+    let opened =
+        open_account_bundle(&request.bundle, &request.passphrase).map_err(|e| e.to_string())?;
+
+    info!(
+        "RUST: Imported account bundle v{} ({} records)",
+        opened.version,
+        opened.records.record_count()
+    );
+    Ok(ImportAccountBundleResult {
+        records: opened.records,
+        version: opened.version,
+        exported_at_ms: opened.exported_at_ms,
+    })
+}
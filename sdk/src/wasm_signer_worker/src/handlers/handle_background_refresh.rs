@@ -0,0 +1,139 @@
+// ******************************************************************************
+// *                                                                            *
+// *                      HANDLER: BACKGROUND REFRESH                           *
+// *                                                                            *
+// ******************************************************************************
+use crate::background_refresh::{self, BackgroundRefreshStatus, RefreshAccount};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StartBackgroundRefreshRequest {
+    /// NEAR RPC URL (comma-separated endpoints are tried in order)
+    #[wasm_bindgen(getter_with_clone, js_name = "nearRpcUrl")]
+    pub near_rpc_url: String,
+    /// Wait between refreshes; raised to MIN_BACKGROUND_REFRESH_INTERVAL_MS (2s)
+    #[wasm_bindgen(js_name = "intervalMs")]
+    pub interval_ms: u32,
+    #[wasm_bindgen(getter_with_clone)]
+    pub accounts: Vec<RefreshAccount>,
+}
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StopBackgroundRefreshRequest {}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StopBackgroundRefreshResult {
+    /// Whether a refresh was running
+    #[wasm_bindgen(js_name = "wasRunning")]
+    pub was_running: bool,
+}
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BackgroundRefreshStatusRequest {}
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SuspendHintRequest {}
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumeHintRequest {}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SuspendHintResult {
+    /// Whether the worker was suspended before this hint
+    #[wasm_bindgen(js_name = "wasSuspended")]
+    pub was_suspended: bool,
+}
+
+/// **Handles:** `WorkerRequestType::StartBackgroundRefresh`
+/// Starts refreshing the latest final block and the listed accounts' access-key nonces on a
+/// setTimeout loop, replacing any refresh already running. The first refresh runs right away.
+///
+/// # Arguments
+/// * `request` - RPC URL, interval and accounts
+///
+/// # Returns
+/// * `BackgroundRefreshStatus` - The refresh as started (interval after clamping)
+pub async fn handle_start_background_refresh(
+    request: StartBackgroundRefreshRequest,
+) -> Result<BackgroundRefreshStatus, String> {
+    let generation =
+        background_refresh::start(&request.near_rpc_url, request.interval_ms, request.accounts)?;
+    background_refresh::spawn_refresh_loop(generation, request.near_rpc_url);
+    Ok(background_refresh::status())
+}
+
+/// **Handles:** `WorkerRequestType::StopBackgroundRefresh`
+/// Stops the background refresh and drops the block and nonces it cached.
+///
+/// # Arguments
+/// * `_request` - Empty request
+///
+/// # Returns
+/// * `StopBackgroundRefreshResult` - Whether a refresh was running
+pub async fn handle_stop_background_refresh(
+    _request: StopBackgroundRefreshRequest,
+) -> Result<StopBackgroundRefreshResult, String> {
+    Ok(StopBackgroundRefreshResult {
+        was_running: background_refresh::stop(),
+    })
+}
+
+/// **Handles:** `WorkerRequestType::BackgroundRefreshStatus`
+/// Reports the background refresh: whether it runs or is paused, its failures and backoff,
+/// and the cached block and nonces.
+///
+/// # Arguments
+/// * `_request` - Empty request
+///
+/// # Returns
+/// * `BackgroundRefreshStatus` - State of the refresh
+pub async fn handle_background_refresh_status(
+    _request: BackgroundRefreshStatusRequest,
+) -> Result<BackgroundRefreshStatus, String> {
+    Ok(background_refresh::status())
+}
+
+/// **Handles:** `WorkerRequestType::SuspendHint`
+/// Sent on pagehide: the background refresh skips its ticks until ResumeHint.
+///
+/// # Arguments
+/// * `_request` - Empty request
+///
+/// # Returns
+/// * `SuspendHintResult` - Whether the worker was already suspended
+pub async fn handle_suspend_hint(
+    _request: SuspendHintRequest,
+) -> Result<SuspendHintResult, String> {
+    Ok(SuspendHintResult {
+        was_suspended: background_refresh::suspend(),
+    })
+}
+
+/// **Handles:** `WorkerRequestType::ResumeHint`
+/// Sent on pageshow: the background refresh resumes at its next tick.
+///
+/// # Arguments
+/// * `_request` - Empty request
+///
+/// # Returns
+/// * `SuspendHintResult` - Whether the worker was suspended
+pub async fn handle_resume_hint(_request: ResumeHintRequest) -> Result<SuspendHintResult, String> {
+    Ok(SuspendHintResult {
+        was_suspended: background_refresh::resume(),
+    })
+}
@@ -0,0 +1,136 @@
+// ******************************************************************************
+// *                                                                            *
+// *                    HANDLER: BUILD ACCOUNT DESCRIPTOR                       *
+// *                                                                            *
+// ******************************************************************************
+use crate::account_descriptor::{build_account_descriptor, sign_account_descriptor};
+use crate::config::{DEFAULT_ACCOUNT_DESCRIPTOR_TTL_MS, MAX_ACCOUNT_DESCRIPTOR_TTL_MS};
+use crate::handlers::confirm_tx_details::{generate_request_id, ConfirmationResult};
+use crate::rpc_calls::get_authenticators_by_user_rpc_call;
+use crate::rpc_client::NearRpcClient;
+use crate::state;
+use crate::types::handlers::{DecryptionPayload, RpcCallPayload};
+use log::info;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsValue;
+
+// Bridge to TS awaitSecureConfirmationV2 (defined globally in the worker wrapper)
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = awaitSecureConfirmationV2)]
+    async fn await_secure_confirmation_v2(request: JsValue) -> JsValue;
+}
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildAccountDescriptorRequest {
+    #[wasm_bindgen(getter_with_clone, js_name = "rpcCall")]
+    pub rpc_call: RpcCallPayload,
+    /// Deterministic VRF public key (base64url); must be registered on-chain
+    #[wasm_bindgen(getter_with_clone, js_name = "vrfPublicKey")]
+    pub vrf_public_key: String,
+    #[wasm_bindgen(getter_with_clone)]
+    pub decryption: DecryptionPayload,
+    /// Descriptor lifetime (defaults to DEFAULT_ACCOUNT_DESCRIPTOR_TTL_MS, capped at MAX)
+    #[wasm_bindgen(js_name = "ttlMs")]
+    #[serde(default)]
+    pub ttl_ms: Option<u32>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildAccountDescriptorResult {
+    /// base64url CBOR of the signed descriptor, for VerifyAccountDescriptor on the other device
+    pub descriptor: String,
+    pub near_public_key: String,
+    pub expires_at_ms: f64,
+}
+
+/// **Handles:** `WorkerRequestType::BuildAccountDescriptor`
+/// Assembles the account's public descriptor from its on-chain authenticators and signs it
+/// with the account's NEAR key, decrypted with a freshly collected PRF output.
+///
+/// # Arguments
+/// * `request` - RPC details, the VRF public key to embed, the encrypted key and a TTL
+///
+/// # Returns
+/// * `BuildAccountDescriptorResult` - The signed descriptor blob, signer key and expiry
+pub async fn handle_build_account_descriptor(
+    request: BuildAccountDescriptorRequest,
+) -> Result<BuildAccountDescriptorResult, String> {
+    let account_id = request.rpc_call.near_account_id.clone();
+    if account_id.is_empty() || request.vrf_public_key.is_empty() {
+        return Err("Missing required field: nearAccountId or vrfPublicKey".to_string());
+    }
+    let ttl_ms = request
+        .ttl_ms
+        .unwrap_or(DEFAULT_ACCOUNT_DESCRIPTOR_TTL_MS)
+        .min(MAX_ACCOUNT_DESCRIPTOR_TTL_MS);
+
+    let rpc = NearRpcClient::new(&request.rpc_call.near_rpc_url);
+    let authenticators =
+        get_authenticators_by_user_rpc_call(&rpc, &request.rpc_call.contract_id, &account_id)
+            .await?;
+
+    // Collect a fresh PRF output (UI skipped by main thread)
+    let confirm_request = serde_json::json!({
+        "schemaVersion": 2,
+        "requestId": generate_request_id(),
+        "type": "decryptPrivateKeyWithPrf",
+        "summary": {
+            "operation": "Share Account Descriptor",
+            "accountId": account_id,
+        },
+        "payload": {
+            "nearAccountId": account_id,
+        },
+        "confirmationConfig": { "uiMode": "skip" }
+    });
+    let confirm_request = serde_json::to_string(&confirm_request)
+        .map_err(|e| format!("Serialize V2 request failed: {}", e))?;
+    let response = await_secure_confirmation_v2(JsValue::from_str(&confirm_request)).await;
+    let confirmation: ConfirmationResult = serde_wasm_bindgen::from_value(response)
+        .map_err(|e| format!("Failed to parse V2 decryptPrivateKeyWithPrf result: {}", e))?;
+    if !confirmation.confirmed {
+        return Err(confirmation
+            .error
+            .unwrap_or_else(|| "User cancelled".to_string()));
+    }
+    let prf = confirmation
+        .prf_output
+        .ok_or_else(|| "Missing PRF output from confirmation".to_string())?;
+
+    let signing_key = crate::crypto::decrypt_stored_private_key_with_prf(
+        &account_id,
+        &prf,
+        &request.decryption.encrypted_private_key_data,
+        &request.decryption.encrypted_private_key_iv,
+    )
+    .await
+    .map_err(|e| format!("Decryption failed: {}", e))?;
+
+    let descriptor = build_account_descriptor(
+        &account_id,
+        &request.rpc_call.contract_id,
+        &request.vrf_public_key,
+        &authenticators,
+        &signing_key,
+        state::now_ms(),
+        ttl_ms,
+    )?;
+    let blob = sign_account_descriptor(&descriptor, &signing_key)?;
+
+    info!(
+        "RUST: Built account descriptor for {} ({} authenticators)",
+        account_id,
+        descriptor.authenticators.len()
+    );
+
+    Ok(BuildAccountDescriptorResult {
+        descriptor: blob,
+        near_public_key: descriptor.near_public_key,
+        expires_at_ms: descriptor.expires_at_ms,
+    })
+}
@@ -0,0 +1,335 @@
+// ******************************************************************************
+// *                                                                            *
+// *                      HANDLER: BUILD CREDENTIAL OPTIONS                     *
+// *                                                                            *
+// ******************************************************************************
+use crate::credential_options::{
+    build_creation_options, build_request_options, CredentialCreationOptions, CredentialDescriptor,
+    CredentialRequestOptions, CredentialUser,
+};
+use crate::rpc_calls::get_authenticators_by_user_rpc_call;
+use crate::rpc_client::{NearRpcClient, RpcClient};
+use crate::types::handlers::{RegistrationPayload, RpcCallPayload, UserVerificationPolicy};
+use crate::types::VrfChallenge;
+use log::info;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// A credential the account already has, as listed by the contract
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExistingCredential {
+    /// base64url credential id
+    pub credential_id: String,
+    #[serde(default)]
+    pub transports: Vec<String>,
+}
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildCredentialCreationOptionsRequest {
+    /// Account and device being registered; its authenticatorOptions set user verification
+    #[wasm_bindgen(getter_with_clone)]
+    pub registration: RegistrationPayload,
+    #[wasm_bindgen(getter_with_clone, js_name = "rpId")]
+    pub rp_id: String,
+    /// Relying party name (defaults to DEFAULT_RP_NAME)
+    #[wasm_bindgen(getter_with_clone, js_name = "rpName")]
+    #[serde(default)]
+    pub rp_name: Option<String>,
+    /// User name (defaults to the device-specific account name)
+    #[wasm_bindgen(getter_with_clone, js_name = "userName")]
+    #[serde(default)]
+    pub user_name: Option<String>,
+    /// Display name (defaults to the account's first label and device)
+    #[wasm_bindgen(getter_with_clone, js_name = "userDisplayName")]
+    #[serde(default)]
+    pub user_display_name: Option<String>,
+    /// VRF challenge the WebAuthn challenge is derived from
+    #[wasm_bindgen(getter_with_clone, js_name = "vrfChallenge")]
+    pub vrf_challenge: VrfChallenge,
+    /// The account's credentials, excluded from registering again; fetched from the contract
+    /// with `rpcCall` when absent
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub existing_credentials: Option<Vec<ExistingCredential>>,
+    #[wasm_bindgen(getter_with_clone, js_name = "rpcCall")]
+    #[serde(default)]
+    pub rpc_call: Option<RpcCallPayload>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildCredentialCreationOptionsResult {
+    /// For navigator.credentials.create({ publicKey }), once its base64url members are decoded
+    pub public_key: CredentialCreationOptions,
+}
+
+/// An authenticator of the account, as stored client-side or listed by the contract
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KnownAuthenticator {
+    /// base64url credential id
+    pub credential_id: String,
+    #[serde(default)]
+    pub transports: Vec<String>,
+    pub device_number: u32,
+    /// credProps.rk reported at registration; absent when unknown (e.g. fetched from the
+    /// contract, which does not store it)
+    #[serde(default)]
+    pub resident_key: Option<bool>,
+}
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildCredentialRequestOptionsRequest {
+    #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
+    pub near_account_id: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "rpId")]
+    pub rp_id: String,
+    /// VRF challenge the WebAuthn challenge is derived from
+    #[wasm_bindgen(getter_with_clone, js_name = "vrfChallenge")]
+    pub vrf_challenge: VrfChallenge,
+    /// The account's authenticators; fetched from the contract with `rpcCall` when absent
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub authenticators: Option<Vec<KnownAuthenticator>>,
+    /// Only allow the credentials of this device
+    #[wasm_bindgen(js_name = "deviceNumber")]
+    #[serde(default)]
+    pub device_number: Option<u32>,
+    /// Defaults to preferred
+    #[wasm_bindgen(getter_with_clone, js_name = "userVerification")]
+    #[serde(default)]
+    pub user_verification: Option<UserVerificationPolicy>,
+    /// Omit allowCredentials. Defaults to true when every allowed authenticator is known to be
+    /// discoverable (resident key) and no device is selected.
+    #[wasm_bindgen(js_name = "discoverable")]
+    #[serde(default)]
+    pub discoverable: Option<bool>,
+    #[wasm_bindgen(getter_with_clone, js_name = "rpcCall")]
+    #[serde(default)]
+    pub rpc_call: Option<RpcCallPayload>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildCredentialRequestOptionsResult {
+    /// For navigator.credentials.get({ publicKey }), once its base64url members are decoded
+    pub public_key: CredentialRequestOptions,
+    /// Whether allowCredentials was omitted, so the authenticator may offer any passkey for
+    /// the rpId and the caller must check which account answered
+    pub discoverable: bool,
+}
+
+/// The account's authenticators registered on the contract
+pub async fn registered_authenticators<R: RpcClient>(
+    rpc: &R,
+    contract_id: &str,
+    account_id: &str,
+) -> Result<Vec<KnownAuthenticator>, String> {
+    Ok(
+        get_authenticators_by_user_rpc_call(rpc, contract_id, account_id)
+            .await?
+            .into_iter()
+            .map(|authenticator| KnownAuthenticator {
+                credential_id: authenticator.credential_id,
+                transports: authenticator.transports.unwrap_or_default(),
+                device_number: authenticator.device_number,
+                resident_key: None,
+            })
+            .collect(),
+    )
+}
+
+/// The account's credentials registered on the contract
+pub async fn registered_credentials<R: RpcClient>(
+    rpc: &R,
+    contract_id: &str,
+    account_id: &str,
+) -> Result<Vec<ExistingCredential>, String> {
+    Ok(registered_authenticators(rpc, contract_id, account_id)
+        .await?
+        .into_iter()
+        .map(|authenticator| ExistingCredential {
+            credential_id: authenticator.credential_id,
+            transports: authenticator.transports,
+        })
+        .collect())
+}
+
+/// The request's options, excluding `existing` credentials
+pub fn creation_options_for(
+    request: &BuildCredentialCreationOptionsRequest,
+    existing: &[ExistingCredential],
+) -> Result<BuildCredentialCreationOptionsResult, String> {
+    let exclude_credentials = existing
+        .iter()
+        .map(|credential| {
+            CredentialDescriptor::public_key(
+                &credential.credential_id,
+                credential.transports.clone(),
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let authenticator_options = request
+        .registration
+        .authenticator_options
+        .clone()
+        .unwrap_or_default();
+    authenticator_options
+        .validate()
+        .map_err(|e| e.to_string())?;
+    let user = CredentialUser {
+        near_account_id: request.registration.near_account_id.clone(),
+        device_number: request.registration.device_number,
+        name: request.user_name.clone(),
+        display_name: request.user_display_name.clone(),
+    };
+
+    let public_key = build_creation_options(
+        &request.rp_id,
+        request.rp_name.as_deref(),
+        &user,
+        &request.vrf_challenge,
+        &authenticator_options,
+        exclude_credentials,
+    )?;
+    Ok(BuildCredentialCreationOptionsResult { public_key })
+}
+
+/// **Handles:** `WorkerRequestType::BuildCredentialCreationOptions`
+/// Builds the registration's navigator.credentials.create options, so every flow registers
+/// with the same algorithms, authenticator selection and PRF salts (see credential_options.rs).
+///
+/// # Arguments
+/// * `request` - The registration, rpId, user display info, VRF challenge and the account's
+///   existing credentials (or the RPC details to fetch them)
+///
+/// # Returns
+/// * `BuildCredentialCreationOptionsResult` - The creation options in WebAuthn JSON form
+pub async fn handle_build_credential_creation_options(
+    request: BuildCredentialCreationOptionsRequest,
+) -> Result<BuildCredentialCreationOptionsResult, String> {
+    let existing = match (&request.existing_credentials, &request.rpc_call) {
+        (Some(existing), _) => existing.clone(),
+        (None, Some(rpc_call)) => {
+            let rpc = NearRpcClient::new(&rpc_call.near_rpc_url);
+            registered_credentials(
+                &rpc,
+                &rpc_call.contract_id,
+                &request.registration.near_account_id,
+            )
+            .await?
+        }
+        (None, None) => Vec::new(),
+    };
+    let result = creation_options_for(&request, &existing)?;
+
+    info!(
+        "RUST: Built credential creation options for {} ({} excluded credentials)",
+        request.registration.near_account_id,
+        existing.len()
+    );
+    Ok(result)
+}
+
+/// The request's options over `authenticators`
+pub fn request_options_for(
+    request: &BuildCredentialRequestOptionsRequest,
+    authenticators: &[KnownAuthenticator],
+) -> Result<BuildCredentialRequestOptionsResult, String> {
+    let allowed: Vec<&KnownAuthenticator> = authenticators
+        .iter()
+        .filter(|authenticator| {
+            request
+                .device_number
+                .is_none_or(|device| authenticator.device_number == device)
+        })
+        .collect();
+    if let (Some(device), true) = (request.device_number, allowed.is_empty()) {
+        return Err(format!(
+            "No credential registered for device {} of {}",
+            device, request.near_account_id
+        ));
+    }
+    // A device filter asks for that device's passkey, which a discoverable prompt cannot ensure
+    let discoverable = request.discoverable.unwrap_or_else(|| {
+        request.device_number.is_none()
+            && !allowed.is_empty()
+            && allowed
+                .iter()
+                .all(|authenticator| authenticator.resident_key == Some(true))
+    });
+
+    let allow_credentials = if discoverable {
+        None
+    } else {
+        Some(
+            allowed
+                .iter()
+                .map(|authenticator| {
+                    CredentialDescriptor::public_key(
+                        &authenticator.credential_id,
+                        authenticator.transports.clone(),
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        )
+    };
+    let public_key = build_request_options(
+        &request.rp_id,
+        &request.near_account_id,
+        &request.vrf_challenge,
+        request
+            .user_verification
+            .clone()
+            .unwrap_or(UserVerificationPolicy::Preferred),
+        allow_credentials,
+    )?;
+    Ok(BuildCredentialRequestOptionsResult {
+        public_key,
+        discoverable,
+    })
+}
+
+/// **Handles:** `WorkerRequestType::BuildCredentialRequestOptions`
+/// Builds the navigator.credentials.get options of an assertion, with allowCredentials taken
+/// from the account's authenticators (optionally one device's) or omitted for discoverable
+/// credentials.
+///
+/// # Arguments
+/// * `request` - The account, rpId, VRF challenge, user verification, device filter and the
+///   account's authenticators (or the RPC details to fetch them)
+///
+/// # Returns
+/// * `BuildCredentialRequestOptionsResult` - The request options in WebAuthn JSON form
+pub async fn handle_build_credential_request_options(
+    request: BuildCredentialRequestOptionsRequest,
+) -> Result<BuildCredentialRequestOptionsResult, String> {
+    let authenticators = match (&request.authenticators, &request.rpc_call) {
+        (Some(authenticators), _) => authenticators.clone(),
+        (None, Some(rpc_call)) => {
+            let rpc = NearRpcClient::new(&rpc_call.near_rpc_url);
+            registered_authenticators(&rpc, &rpc_call.contract_id, &request.near_account_id).await?
+        }
+        (None, None) => {
+            return Err(
+                "Missing required field: authenticators (or rpcCall to fetch them)".to_string(),
+            )
+        }
+    };
+    let result = request_options_for(&request, &authenticators)?;
+
+    info!(
+        "RUST: Built credential request options for {} ({})",
+        request.near_account_id,
+        match &result.public_key.allow_credentials {
+            Some(allowed) => format!("{} allowed credentials", allowed.len()),
+            None => "discoverable".to_string(),
+        }
+    );
+    Ok(result)
+}
@@ -0,0 +1,130 @@
+// ******************************************************************************
+// *                                                                            *
+// *                      HANDLER: BUILD REDIRECT PAYLOAD                       *
+// *                                                                            *
+// ******************************************************************************
+use crate::config::{DEFAULT_REDIRECT_PAYLOAD_TTL_MS, MAX_REDIRECT_PAYLOAD_TTL_MS};
+use crate::error::SignerErrorCode;
+use crate::redirect_payload::{check_callback_url, issue_redirect_payload};
+use crate::state;
+use crate::types::handlers::WorkerPolicy;
+use crate::types::{AccountId, VrfChallenge};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildRedirectPayloadRequest {
+    /// Account signing in; the session key must belong to it
+    #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
+    pub near_account_id: String,
+    /// Session key (CreateSessionKey, possibly in an earlier worker whose record was loaded)
+    /// that signs the payload
+    #[wasm_bindgen(getter_with_clone, js_name = "sessionPublicKey")]
+    pub session_public_key: String,
+    /// Challenge from the VRF worker, issued for nearAccountId
+    #[wasm_bindgen(getter_with_clone, js_name = "vrfChallenge")]
+    pub vrf_challenge: VrfChallenge,
+    /// https URL on one of workerPolicy.allowedRedirectOrigins
+    #[wasm_bindgen(getter_with_clone, js_name = "callbackUrl")]
+    pub callback_url: String,
+    /// Caller's CSRF state, echoed in the URL (letters, digits and -._~)
+    #[wasm_bindgen(getter_with_clone)]
+    pub state: String,
+    /// Payload lifetime (defaults to DEFAULT_REDIRECT_PAYLOAD_TTL_MS, and never outlives the
+    /// session key)
+    #[wasm_bindgen(js_name = "ttlMs")]
+    #[serde(default)]
+    pub ttl_ms: Option<u32>,
+    #[wasm_bindgen(getter_with_clone, js_name = "workerPolicy")]
+    #[serde(default)]
+    pub worker_policy: Option<WorkerPolicy>,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildRedirectPayloadResult {
+    /// base64url signed payload, also carried by `url`
+    #[wasm_bindgen(getter_with_clone)]
+    pub token: String,
+    /// callbackUrl with the token and state added to its query
+    #[wasm_bindgen(getter_with_clone)]
+    pub url: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "sessionPublicKey")]
+    pub session_public_key: String,
+    #[wasm_bindgen(js_name = "expiresAtMs")]
+    pub expires_at_ms: f64,
+}
+
+/// **Handles:** `WorkerRequestType::BuildRedirectPayload`
+/// Checks the callback URL against the policy's redirect origins and returns a payload naming
+/// the account, the session key and the VRF challenge, signed with the session key, with the
+/// redirect URL carrying it (see redirect_payload.rs). No PRF output or private key is read.
+///
+/// # Arguments
+/// * `request` - The account, session key, VRF challenge, callback URL and state
+///
+/// # Returns
+/// * `BuildRedirectPayloadResult` - The token, the composed URL and the payload's expiry
+pub async fn handle_build_redirect_payload(
+    request: BuildRedirectPayloadRequest,
+) -> Result<BuildRedirectPayloadResult, String> {
+    let account = AccountId::new(request.near_account_id.clone())?;
+    let worker_policy = request
+        .worker_policy
+        .map(|policy| policy.for_account(&account));
+    check_callback_url(&request.callback_url, worker_policy.as_ref()).map_err(|e| e.to_string())?;
+    let ttl_ms = match request.ttl_ms {
+        None => DEFAULT_REDIRECT_PAYLOAD_TTL_MS,
+        Some(ttl_ms) if ttl_ms == 0 || ttl_ms > MAX_REDIRECT_PAYLOAD_TTL_MS => {
+            return Err(format!(
+                "ttlMs must be between 1 and {}",
+                MAX_REDIRECT_PAYLOAD_TTL_MS
+            ));
+        }
+        Some(ttl_ms) => ttl_ms,
+    };
+
+    let (signing_key, near_account_id, key_expires_at_ms) =
+        state::with_session_key(&request.session_public_key, |key| {
+            (
+                key.signing_key.clone(),
+                key.near_account_id.clone(),
+                key.expires_at_ms,
+            )
+        })
+        .map_err(|code| {
+            format!(
+                "{}: session key {} is not available",
+                code, request.session_public_key
+            )
+        })?;
+    if near_account_id != request.near_account_id {
+        return Err(format!(
+            "{}: session key belongs to {}, not {}",
+            SignerErrorCode::SessionKeyPermissionDenied,
+            near_account_id,
+            request.near_account_id
+        ));
+    }
+
+    let now_ms = state::now_ms();
+    let expires_at_ms = (now_ms + ttl_ms as f64).min(key_expires_at_ms);
+    let issued = issue_redirect_payload(
+        &signing_key,
+        &request.near_account_id,
+        &request.vrf_challenge,
+        &request.callback_url,
+        &request.state,
+        now_ms,
+        expires_at_ms,
+    )?;
+    Ok(BuildRedirectPayloadResult {
+        token: issued.token,
+        url: issued.url,
+        session_public_key: request.session_public_key,
+        expires_at_ms: issued.expires_at_ms,
+    })
+}
@@ -0,0 +1,51 @@
+// ******************************************************************************
+// *                                                                            *
+// *                          HANDLER: CANCEL REQUEST                           *
+// *                                                                            *
+// ******************************************************************************
+use crate::state::{self, RequestPhase};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelRequestRequest {
+    #[wasm_bindgen(getter_with_clone, js_name = "requestId")]
+    pub request_id: String,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelRequestResult {
+    #[wasm_bindgen(getter_with_clone, js_name = "requestId")]
+    pub request_id: String,
+    pub cancelled: bool,
+    /// Phase the request was in when cancellation was requested (None if unknown)
+    #[wasm_bindgen(getter_with_clone)]
+    pub phase: Option<String>,
+}
+
+/// **Handles:** `WorkerRequestType::CancelRequest`
+/// Cancels a queued request before it starts; its own response is a `RequestCancelled` failure.
+/// Running requests are not interrupted (`cancelled: false`, `phase: "running"`). Only
+/// requests queued in this worker can be cancelled; SignerWorkerManager cancels the pool's
+/// queued requests itself (cancelRequest).
+///
+/// # Arguments
+/// * `request` - ID of the request to cancel
+///
+/// # Returns
+/// * `CancelRequestResult` - Whether the request was cancelled and the phase it was in
+pub async fn handle_cancel_request(
+    request: CancelRequestRequest,
+) -> Result<CancelRequestResult, String> {
+    let phase = state::cancel_request(&request.request_id);
+
+    Ok(CancelRequestResult {
+        request_id: request.request_id,
+        cancelled: phase == Some(RequestPhase::Queued),
+        phase: phase.map(|p| p.as_str().to_string()),
+    })
+}
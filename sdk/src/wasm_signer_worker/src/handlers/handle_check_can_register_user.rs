@@ -3,11 +3,19 @@
 // *                     HANDLER: CHECK CAN REGISTER USER                     *
 // *                                                                            *
 // ******************************************************************************
-use crate::rpc_calls::{check_can_register_user_rpc_call, VrfData};
+use crate::config::{REGISTRATION_STORAGE_OVERHEAD_BYTES, STORAGE_PRICE_PER_BYTE_YOCTO};
+use crate::contract_args::{registration_args_json, ContractInterfaceVersion};
+use crate::cose::extract_cose_public_key_from_attestation;
+use crate::credential_options::negotiated_algorithm;
+use crate::encoders::base64_url_decode;
+use crate::rpc_calls::{check_can_register_user_rpc_call, view_account_exists_rpc_call, VrfData};
+use crate::rpc_client::{NearRpcClient, RpcClient};
+use crate::types::handlers::OriginPolicyInput;
 use crate::types::wasm_to_json::WasmSignedTransaction;
 use crate::types::{
     AuthenticatorOptions, SerializedRegistrationCredential, VrfChallenge,
     WebAuthnRegistrationCredential, WebAuthnRegistrationCredentialStruct,
+    WebAuthnRegistrationResponse,
 };
 use serde::{Deserialize, Serialize};
 use serde_json;
@@ -60,6 +68,11 @@ pub struct CheckCanRegisterUserRequest {
     pub near_rpc_url: String,
     #[wasm_bindgen(getter_with_clone, js_name = "authenticatorOptions")]
     pub authenticator_options: Option<AuthenticatorOptions>,
+    /// Run every local and view-only check and report the outcome instead of failing;
+    /// nothing is signed or stored
+    #[wasm_bindgen(js_name = "dryRun")]
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 #[wasm_bindgen]
@@ -96,6 +109,9 @@ pub struct RegistrationCheckResult {
     pub signed_transaction: Option<WasmSignedTransaction>,
     #[wasm_bindgen(getter_with_clone)]
     pub error: Option<String>,
+    /// Present for dry-run checks only
+    #[wasm_bindgen(getter_with_clone, js_name = "dryRun")]
+    pub dry_run: Option<RegistrationDryRunReport>,
 }
 
 #[wasm_bindgen]
@@ -114,10 +130,165 @@ impl RegistrationCheckResult {
             logs,
             signed_transaction,
             error,
+            dry_run: None,
         }
     }
 }
 
+// === DRY RUN TYPES ===
+
+/// Outcome of a dry-run registration: the first check that failed, or `WouldSucceed`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistrationDryRunOutcome {
+    WouldSucceed,
+    InvalidAttestation,
+    DisallowedAlgorithm,
+    InvalidChallenge,
+    OriginNotAllowed,
+    KeyDerivationFailed,
+    AccountExists,
+    ContractRejected,
+    RpcUnavailable,
+}
+
+impl RegistrationDryRunOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RegistrationDryRunOutcome::WouldSucceed => "WouldSucceed",
+            RegistrationDryRunOutcome::InvalidAttestation => "InvalidAttestation",
+            RegistrationDryRunOutcome::DisallowedAlgorithm => "DisallowedAlgorithm",
+            RegistrationDryRunOutcome::InvalidChallenge => "InvalidChallenge",
+            RegistrationDryRunOutcome::OriginNotAllowed => "OriginNotAllowed",
+            RegistrationDryRunOutcome::KeyDerivationFailed => "KeyDerivationFailed",
+            RegistrationDryRunOutcome::AccountExists => "AccountExists",
+            RegistrationDryRunOutcome::ContractRejected => "ContractRejected",
+            RegistrationDryRunOutcome::RpcUnavailable => "RpcUnavailable",
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrationDryRunReport {
+    /// RegistrationDryRunOutcome as a string
+    #[wasm_bindgen(getter_with_clone)]
+    pub outcome: String,
+    #[wasm_bindgen(js_name = "wouldSucceed")]
+    pub would_succeed: bool,
+    /// Why the failing check failed
+    #[wasm_bindgen(getter_with_clone)]
+    pub detail: Option<String>,
+    #[wasm_bindgen(getter_with_clone, js_name = "nearPublicKey")]
+    pub near_public_key: Option<String>,
+    #[wasm_bindgen(getter_with_clone, js_name = "vrfPublicKey")]
+    pub vrf_public_key: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "credentialPublicKey")]
+    pub credential_public_key: Option<Vec<u8>>,
+    /// JSON `verify_and_register_user` args the registration would submit
+    #[wasm_bindgen(getter_with_clone, js_name = "contractArgs")]
+    pub contract_args: Option<String>,
+    /// U128 as a decimal string
+    #[wasm_bindgen(getter_with_clone, js_name = "estimatedStorageCostYocto")]
+    pub estimated_storage_cost_yocto: Option<String>,
+}
+
+impl RegistrationDryRunReport {
+    fn new(vrf_public_key: String) -> Self {
+        RegistrationDryRunReport {
+            outcome: RegistrationDryRunOutcome::WouldSucceed.as_str().to_string(),
+            would_succeed: true,
+            detail: None,
+            near_public_key: None,
+            vrf_public_key,
+            credential_public_key: None,
+            contract_args: None,
+            estimated_storage_cost_yocto: None,
+        }
+    }
+
+    fn fail(mut self, outcome: RegistrationDryRunOutcome, detail: String) -> Self {
+        self.outcome = outcome.as_str().to_string();
+        self.would_succeed = false;
+        self.detail = Some(detail);
+        self
+    }
+}
+
+#[derive(Deserialize)]
+struct RegistrationClientData {
+    #[serde(rename = "type")]
+    ceremony_type: String,
+    challenge: String,
+    origin: String,
+}
+
+/// Checks that clientDataJSON is a `webauthn.create` ceremony over the VRF challenge
+/// (first 32 bytes of the VRF output). Returns the ceremony's origin.
+pub fn check_registration_challenge_binding(
+    client_data_json_b64u: &str,
+    vrf_output_b64u: &str,
+) -> Result<String, String> {
+    let client_data_bytes = base64_url_decode(client_data_json_b64u)
+        .map_err(|e| format!("Failed to decode clientDataJSON: {}", e))?;
+    let client_data: RegistrationClientData = serde_json::from_slice(&client_data_bytes)
+        .map_err(|e| format!("Failed to parse clientDataJSON: {}", e))?;
+    if client_data.ceremony_type != "webauthn.create" {
+        return Err(format!(
+            "Expected a webauthn.create ceremony, got {}",
+            client_data.ceremony_type
+        ));
+    }
+
+    let vrf_output = base64_url_decode(vrf_output_b64u)
+        .map_err(|e| format!("Failed to decode VRF output: {}", e))?;
+    let challenge = base64_url_decode(&client_data.challenge)
+        .map_err(|e| format!("Failed to decode credential challenge: {}", e))?;
+    if vrf_output.len() < 32 || challenge != vrf_output[..32] {
+        return Err("Credential challenge does not match the VRF challenge".to_string());
+    }
+    Ok(client_data.origin)
+}
+
+fn origin_host(origin: &str) -> &str {
+    let without_scheme = origin.split("://").nth(1).unwrap_or(origin);
+    without_scheme
+        .split([':', '/'])
+        .next()
+        .unwrap_or(without_scheme)
+}
+
+/// Applies the registration origin policy (default: rpId and all its subdomains)
+pub fn origin_allowed(origin: &str, rp_id: &str, policy: Option<&OriginPolicyInput>) -> bool {
+    let host = origin_host(origin);
+    if host == rp_id {
+        return true;
+    }
+    match policy {
+        Some(OriginPolicyInput {
+            single: Some(true), ..
+        }) => false,
+        Some(OriginPolicyInput {
+            multiple: Some(allowed),
+            ..
+        }) => allowed.iter().any(|entry| origin_host(entry) == host),
+        _ => host.ends_with(&format!(".{}", rp_id)),
+    }
+}
+
+/// Storage staking the contract needs for one new authenticator record, in yoctoNEAR
+pub fn estimate_registration_storage_cost(
+    credential_id_len: usize,
+    credential_public_key_len: usize,
+    vrf_public_key_count: usize,
+) -> u128 {
+    let bytes = REGISTRATION_STORAGE_OVERHEAD_BYTES
+        + credential_id_len as u64
+        + credential_public_key_len as u64
+        + 32 * vrf_public_key_count as u64;
+    bytes as u128 * STORAGE_PRICE_PER_BYTE_YOCTO
+}
+
 /// **Handles:** `WorkerRequestType::CheckCanRegisterUser`
 /// This handler performs preliminary validation before full registration. It verifies the VRF challenge,
 /// validates the WebAuthn registration credential, and checks contract-specific registration requirements
@@ -131,6 +302,28 @@ impl RegistrationCheckResult {
 pub async fn handle_check_can_register_user(
     request: CheckCanRegisterUserRequest,
 ) -> Result<RegistrationCheckResult, String> {
+    let rpc = NearRpcClient::new(&request.near_rpc_url);
+    check_can_register_user_with(&rpc, request).await
+}
+
+/// handle_check_can_register_user over any RpcClient
+pub(crate) async fn check_can_register_user_with<R: RpcClient>(
+    rpc: &R,
+    request: CheckCanRegisterUserRequest,
+) -> Result<RegistrationCheckResult, String> {
+    if request.dry_run {
+        let report = registration_dry_run(rpc, request).await;
+        let mut result = RegistrationCheckResult::new(
+            report.would_succeed,
+            None,
+            vec![],
+            None,
+            report.detail.clone(),
+        );
+        result.dry_run = Some(report);
+        return Ok(result);
+    }
+
     // Use VrfChallenge directly instead of converting
     let vrf_challenge = &request.vrf_challenge;
 
@@ -159,10 +352,10 @@ pub async fn handle_check_can_register_user(
 
     // Call the http module function
     let registration_result = check_can_register_user_rpc_call(
+        rpc,
         &check_request.contract_id,
         vrf_data,
         webauthn_registration,
-        &check_request.near_rpc_url,
         request.authenticator_options,
     )
     .await
@@ -204,3 +397,155 @@ pub async fn handle_check_can_register_user(
         registration_result.error,
     ))
 }
+
+/// Runs every registration check, in the order a real registration would hit them, and
+/// stops at the first failure. Only view RPC calls are made: no transaction is signed, no
+/// nonce is used and nothing is stored, so the device number stays available.
+async fn registration_dry_run<R: RpcClient>(
+    rpc: &R,
+    request: CheckCanRegisterUserRequest,
+) -> RegistrationDryRunReport {
+    use RegistrationDryRunOutcome::*;
+
+    let vrf_challenge = &request.vrf_challenge;
+    let credential = &request.credential;
+    let mut report = RegistrationDryRunReport::new(vrf_challenge.vrf_public_key.clone());
+
+    // Step 1: Attestation parsing
+    let credential_public_key =
+        match extract_cose_public_key_from_attestation(&credential.response.attestation_object) {
+            Ok(key) => key,
+            Err(e) => return report.fail(InvalidAttestation, e),
+        };
+    report.credential_public_key = Some(credential_public_key.clone());
+    if let Err(e) = negotiated_algorithm(
+        &credential.response.attestation_object,
+        request.authenticator_options.as_ref(),
+    ) {
+        return report.fail(DisallowedAlgorithm, e.to_string());
+    }
+
+    // Step 2: Challenge binding
+    let origin = match check_registration_challenge_binding(
+        &credential.response.client_data_json,
+        &vrf_challenge.vrf_output,
+    ) {
+        Ok(origin) => origin,
+        Err(e) => return report.fail(InvalidChallenge, e),
+    };
+    let vrf_data = match VrfData::try_from(vrf_challenge) {
+        Ok(vrf_data) => vrf_data,
+        Err(e) => {
+            return report.fail(
+                InvalidChallenge,
+                format!("Failed to convert VRF challenge: {:?}", e),
+            )
+        }
+    };
+
+    // Step 3: Origin policy
+    let origin_policy = request
+        .authenticator_options
+        .as_ref()
+        .and_then(|options| options.origin_policy.as_ref());
+    if !origin_allowed(&origin, &vrf_challenge.rp_id, origin_policy) {
+        return report.fail(
+            OriginNotAllowed,
+            format!(
+                "Origin {} is not allowed for rpId {}",
+                origin, vrf_challenge.rp_id
+            ),
+        );
+    }
+
+    // Step 4: NEAR key derivation (the key is derived and dropped, never stored)
+    let ed25519_prf_output = match credential
+        .client_extension_results
+        .prf
+        .results
+        .second
+        .as_deref()
+    {
+        Some(prf) => prf,
+        None => {
+            return report.fail(
+                KeyDerivationFailed,
+                "Missing ed25519 PRF output".to_string(),
+            )
+        }
+    };
+    match crate::crypto::derive_ed25519_key_from_prf_output(
+        ed25519_prf_output,
+        &vrf_challenge.user_id,
+    ) {
+        Ok((_private_key, public_key)) => report.near_public_key = Some(public_key),
+        Err(e) => return report.fail(KeyDerivationFailed, format!("{:?}", e)),
+    }
+
+    // Step 5: Would-be contract arguments and storage cost
+    let webauthn_registration = WebAuthnRegistrationCredential {
+        id: credential.id.clone(),
+        raw_id: credential.raw_id.clone(),
+        response: WebAuthnRegistrationResponse {
+            client_data_json: credential.response.client_data_json.clone(),
+            attestation_object: credential.response.attestation_object.clone(),
+            transports: Some(credential.response.transports.clone()),
+        },
+        authenticator_attachment: credential.authenticator_attachment.clone(),
+        reg_type: credential.credential_type.clone(),
+    };
+    report.contract_args = registration_args_json(
+        ContractInterfaceVersion::LATEST,
+        vrf_data.clone(),
+        webauthn_registration.clone(),
+        None,
+        None,
+        request.authenticator_options.clone(),
+    )
+    .ok();
+    let credential_id_len = base64_url_decode(&credential.raw_id)
+        .map(|id| id.len())
+        .unwrap_or(0);
+    report.estimated_storage_cost_yocto = Some(
+        estimate_registration_storage_cost(credential_id_len, credential_public_key.len(), 1)
+            .to_string(),
+    );
+
+    // Step 6: Account availability
+    match view_account_exists_rpc_call(rpc, &vrf_challenge.user_id).await {
+        Ok(false) => {}
+        Ok(true) => {
+            return report.fail(
+                AccountExists,
+                format!("Account {} already exists", vrf_challenge.user_id),
+            )
+        }
+        Err(e) => return report.fail(RpcUnavailable, e),
+    }
+
+    // Step 7: Contract verification (view function)
+    match check_can_register_user_rpc_call(
+        rpc,
+        &request.contract_id,
+        vrf_data,
+        webauthn_registration,
+        request.authenticator_options,
+    )
+    .await
+    {
+        Ok(result) if !result.success => report.fail(
+            RpcUnavailable,
+            result
+                .error
+                .unwrap_or_else(|| "Unknown RPC error".to_string()),
+        ),
+        Ok(result) if !result.verified => report.fail(
+            ContractRejected,
+            result
+                .error
+                .unwrap_or_else(|| "Contract rejected the registration".to_string()),
+        ),
+        Ok(_) => report,
+        Err(e) => report.fail(RpcUnavailable, e),
+    }
+}
@@ -0,0 +1,69 @@
+// ******************************************************************************
+// *                                                                            *
+// *                   HANDLER: COMPOSE CREATE SUBACCOUNT                       *
+// *                                                                            *
+// ******************************************************************************
+use crate::subaccount::{compose_create_subaccount, AccountSuffixPolicy};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ComposeCreateSubaccountRequest {
+    /// Account that will sign the transaction; must be the parent
+    #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
+    pub near_account_id: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "parentAccountId")]
+    pub parent_account_id: String,
+    /// First part of the new account ID (e.g. "alice" for alice.myapp.near)
+    #[wasm_bindgen(getter_with_clone, js_name = "childPrefix")]
+    pub child_prefix: String,
+    /// yoctoNEAR transferred to the new account
+    #[wasm_bindgen(getter_with_clone, js_name = "initialBalance")]
+    pub initial_balance: String,
+    /// The new account's full access key ("ed25519:<base58>")
+    #[wasm_bindgen(getter_with_clone, js_name = "publicKey")]
+    pub public_key: String,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ComposeCreateSubaccountResult {
+    /// The new account, receiver of the transaction
+    #[wasm_bindgen(getter_with_clone, js_name = "receiverId")]
+    pub receiver_id: String,
+    /// JSON string of the CreateAccount, Transfer and AddKey ActionParams
+    #[wasm_bindgen(getter_with_clone)]
+    pub actions: String,
+}
+
+/// **Handles:** `WorkerRequestType::ComposeCreateSubaccount`
+/// Builds the transaction creating `<childPrefix>.<parentAccountId>` with an initial balance
+/// and a full access key. The result is signed like any other transaction, by the parent.
+///
+/// # Arguments
+/// * `request` - Signer, parent, child prefix, initial balance and the new account's key
+///
+/// # Returns
+/// * `ComposeCreateSubaccountResult` - Receiver and actions of the transaction
+pub async fn handle_compose_create_subaccount(
+    request: ComposeCreateSubaccountRequest,
+) -> Result<ComposeCreateSubaccountResult, String> {
+    let policy = AccountSuffixPolicy::new(&request.parent_account_id).map_err(|e| e.to_string())?;
+    let (receiver_id, actions) = compose_create_subaccount(
+        &request.near_account_id,
+        &policy,
+        &request.child_prefix,
+        &request.initial_balance,
+        &request.public_key,
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(ComposeCreateSubaccountResult {
+        receiver_id,
+        actions: serde_json::to_string(&actions)
+            .map_err(|e| format!("Failed to serialize subaccount actions: {}", e))?,
+    })
+}
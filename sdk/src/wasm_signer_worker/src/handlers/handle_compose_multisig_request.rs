@@ -0,0 +1,94 @@
+// ******************************************************************************
+// *                                                                            *
+// *                   HANDLER: COMPOSE MULTISIG REQUEST                        *
+// *                                                                            *
+// ******************************************************************************
+use crate::actions::ActionParams;
+use crate::config::MULTISIG_DEFAULT_GAS;
+use crate::multisig::{compose_add_request_and_confirm, compose_confirm, compose_delete_request};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ComposeMultisigRequest {
+    /// "addRequestAndConfirm" | "confirm" | "deleteRequest"
+    #[wasm_bindgen(getter_with_clone)]
+    pub operation: String,
+    /// Target of the inner actions (addRequestAndConfirm only)
+    #[wasm_bindgen(getter_with_clone, js_name = "receiverId")]
+    #[serde(default)]
+    pub receiver_id: Option<String>,
+    /// JSON string of ActionParams[] to wrap (addRequestAndConfirm only)
+    #[wasm_bindgen(getter_with_clone)]
+    #[serde(default)]
+    pub actions: Option<String>,
+    /// Multisig request ID (confirm / deleteRequest only)
+    #[wasm_bindgen(js_name = "requestId")]
+    #[serde(default)]
+    pub request_id: Option<u32>,
+    /// Gas for the multisig call (defaults to MULTISIG_DEFAULT_GAS)
+    #[wasm_bindgen(getter_with_clone)]
+    #[serde(default)]
+    pub gas: Option<String>,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ComposeMultisigResult {
+    #[wasm_bindgen(getter_with_clone, js_name = "methodName")]
+    pub method_name: String,
+    /// FunctionCall action (JSON ActionParams) to send to the multisig contract
+    #[wasm_bindgen(getter_with_clone)]
+    pub action: String,
+}
+
+/// **Handles:** `WorkerRequestType::ComposeMultisigRequest`
+/// Builds the FunctionCall action for a standard multisig contract, mapping inner actions to
+/// the contract's request schema. The returned action is signed like any other, with the
+/// multisig account as receiver.
+///
+/// # Arguments
+/// * `request` - Operation plus receiver/actions or request ID
+///
+/// # Returns
+/// * `ComposeMultisigResult` - Method name and the composed FunctionCall action
+pub async fn handle_compose_multisig_request(
+    request: ComposeMultisigRequest,
+) -> Result<ComposeMultisigResult, String> {
+    let gas = request.gas.as_deref().unwrap_or(MULTISIG_DEFAULT_GAS);
+    let request_id = || {
+        request
+            .request_id
+            .ok_or_else(|| format!("{} requires requestId", request.operation))
+    };
+
+    let action = match request.operation.as_str() {
+        "addRequestAndConfirm" => {
+            let receiver_id = request
+                .receiver_id
+                .as_deref()
+                .ok_or("addRequestAndConfirm requires receiverId")?;
+            let actions: Vec<ActionParams> =
+                serde_json::from_str(request.actions.as_deref().unwrap_or("[]"))
+                    .map_err(|e| format!("Failed to parse actions: {}", e))?;
+            compose_add_request_and_confirm(receiver_id, &actions, gas)?
+        }
+        "confirm" => compose_confirm(request_id()?, gas),
+        "deleteRequest" => compose_delete_request(request_id()?, gas),
+        other => return Err(format!("Unknown multisig operation: {}", other)),
+    };
+
+    let method_name = match &action {
+        ActionParams::FunctionCall { method_name, .. } => method_name.clone(),
+        _ => unreachable!("multisig helpers always compose a FunctionCall"),
+    };
+
+    Ok(ComposeMultisigResult {
+        method_name,
+        action: serde_json::to_string(&action)
+            .map_err(|e| format!("Failed to serialize multisig action: {}", e))?,
+    })
+}
@@ -3,9 +3,15 @@
 // *                  HANDLER: DECRYPT PRIVATE KEY WITH PRF                   *
 // *                                                                            *
 // ******************************************************************************
-use crate::handlers::confirm_tx_details::{generate_request_id, ConfirmationResult};
+use crate::elevation::{self, ElevatedOperation};
+use crate::handlers::confirm_tx_details::{
+    generate_request_id, parse_confirmation_result, register_confirmation_prompt,
+};
+use crate::state;
+use crate::types::handlers::WorkerPolicy;
+use crate::types::AccountId;
 use bs58;
-use log::info;
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsValue;
@@ -29,6 +35,15 @@ pub struct DecryptPrivateKeyRequest {
     pub encrypted_private_key_data: String,
     #[wasm_bindgen(getter_with_clone, js_name = "encryptedPrivateKeyIv")]
     pub encrypted_private_key_iv: String,
+    /// Elevation window and elevated operations (see elevation.rs)
+    #[wasm_bindgen(getter_with_clone, js_name = "workerPolicy")]
+    #[serde(default)]
+    pub worker_policy: Option<WorkerPolicy>,
+    /// The serialized passkey assertion the PRF output came from, made for a challenge from
+    /// IssueElevationChallenge
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub credential: Option<serde_json::Value>,
 }
 
 #[wasm_bindgen]
@@ -45,6 +60,8 @@ impl DecryptPrivateKeyRequest {
             chacha20_prf_output,
             encrypted_private_key_data,
             encrypted_private_key_iv,
+            worker_policy: None,
+            credential: None,
         }
     }
 }
@@ -74,6 +91,9 @@ impl DecryptPrivateKeyResult {
 /// This handler takes encrypted private key data and an AES PRF output to decrypt and return
 /// the private key in NEAR-compatible format. Used when applications need direct access to
 /// the private key for signing operations outside of the worker context.
+/// The PRF output arrives without a confirmation, so the request carries the assertion it
+/// came from, made for an elevation challenge still within the elevation window
+/// (ElevationRequired otherwise).
 ///
 /// # Arguments
 /// * `request` - Contains account ID, PRF output, and encrypted private key data with IV
@@ -83,13 +103,31 @@ impl DecryptPrivateKeyResult {
 pub async fn handle_decrypt_private_key_with_prf(
     request: DecryptPrivateKeyRequest,
 ) -> Result<DecryptPrivateKeyResult, String> {
+    let account = AccountId::new(request.near_account_id.clone())?;
+    let policy = request
+        .worker_policy
+        .as_ref()
+        .map(|policy| policy.for_account(&account));
+    let collected_at_ms = request
+        .credential
+        .as_ref()
+        .and_then(|credential| elevation_challenge_time(credential, &account, policy.as_ref()));
+    elevation::check_elevation(
+        policy.as_ref(),
+        ElevatedOperation::ExportPrivateKey,
+        collected_at_ms,
+        state::now_ms(),
+    )
+    .map_err(|e| e.to_string())?;
+
     // Use the core function to decrypt and get SigningKey
-    let signing_key = crate::crypto::decrypt_private_key_with_prf(
+    let signing_key = crate::crypto::decrypt_stored_private_key_with_prf(
         &request.near_account_id,
         &request.chacha20_prf_output,
         &request.encrypted_private_key_data,
         &request.encrypted_private_key_iv,
     )
+    .await
     .map_err(|e| format!("Decryption failed: {}", e))?;
 
     // Convert SigningKey to NEAR format (64 bytes: 32-byte seed + 32-byte public key)
@@ -113,6 +151,29 @@ pub async fn handle_decrypt_private_key_with_prf(
     Ok(result)
 }
 
+/// When the assertion's elevation challenge was issued, if it verifies for the account and was
+/// not accepted before; the challenge is consumed until its elevation window ends
+fn elevation_challenge_time(
+    credential: &serde_json::Value,
+    account: &AccountId,
+    policy: Option<&WorkerPolicy>,
+) -> Option<f64> {
+    let key = state::signing_intent_key().ok()?;
+    let challenge = match elevation::assertion_challenge(credential, &key, &account.0) {
+        Ok(challenge) => challenge,
+        Err(e) => {
+            warn!("RUST: Export assertion not accepted: {}", e);
+            return None;
+        }
+    };
+    let window_ends_ms = challenge.issued_at_ms + elevation::max_age_ms(policy) as f64;
+    if !state::consume_elevation_challenge(&challenge.challenge_id, window_ends_ms) {
+        warn!("RUST: Export assertion not accepted: elevation challenge already used");
+        return None;
+    }
+    Some(challenge.issued_at_ms)
+}
+
 // ===== Two‑phase export with UI (worker‑driven) =====
 
 #[wasm_bindgen]
@@ -131,6 +192,10 @@ pub struct ExportNearKeypairUiRequest {
     pub variant: Option<String>,
     #[wasm_bindgen(getter_with_clone, js_name = "theme")]
     pub theme: Option<String>,
+    /// Elevation window and elevated operations (see elevation.rs)
+    #[wasm_bindgen(getter_with_clone, js_name = "workerPolicy")]
+    #[serde(default)]
+    pub worker_policy: Option<WorkerPolicy>,
 }
 
 #[wasm_bindgen]
@@ -151,7 +216,8 @@ impl ExportNearKeypairUiResult {
 }
 
 /// Orchestrates two-phase export:
-/// 1) awaitSecureConfirmationV2(decryptPrivateKeyWithPrf) to collect PRF (no UI)
+/// 1) awaitSecureConfirmationV2(decryptPrivateKeyWithPrf) to collect PRF (no UI); the credential
+///    must be collected within the elevation window (ElevationRequired otherwise)
 /// 2) Decrypt in-worker using encrypted data provided in the request
 /// 3) awaitSecureConfirmationV2(showSecurePrivateKeyUi) to render the viewer with the decrypted key
 pub async fn handle_export_near_keypair_ui(
@@ -159,11 +225,17 @@ pub async fn handle_export_near_keypair_ui(
 ) -> Result<ExportNearKeypairUiResult, String> {
     let account_id = request.near_account_id.clone();
     let public_key = request.public_key.clone();
+    let account = AccountId::new(account_id.clone())?;
+    let policy = request
+        .worker_policy
+        .as_ref()
+        .map(|policy| policy.for_account(&account));
 
     // Phase 1: collect PRF (UI skipped by main thread)
-    let req1 = serde_json::json!({
+    let request_id = generate_request_id();
+    let mut req1 = serde_json::json!({
         "schemaVersion": 2,
-        "requestId": generate_request_id(),
+        "requestId": request_id,
         "type": "decryptPrivateKeyWithPrf",
         "summary": {
             "operation": "Export Private Key",
@@ -178,26 +250,36 @@ pub async fn handle_export_near_keypair_ui(
         // main thread clamps to uiMode: 'skip' for this type; include explicit hint
         "confirmationConfig": { "uiMode": "skip" }
     });
+    // The response must answer this prompt, which also bounds when its credential was collected
+    register_confirmation_prompt(&mut req1, &request_id);
     let req1_str =
         serde_json::to_string(&req1).map_err(|e| format!("Serialize V2 request failed: {}", e))?;
     let js_req1 = JsValue::from_str(&req1_str);
     let resp1 = await_secure_confirmation_v2(js_req1).await;
-    let conf1: ConfirmationResult = serde_wasm_bindgen::from_value(resp1)
-        .map_err(|e| format!("Failed to parse V2 decryptPrivateKeyWithPrf result: {}", e))?;
+    let conf1 = parse_confirmation_result(resp1, &request_id)?;
+    state::clear_confirmation_nonce(&request_id);
     if !conf1.confirmed {
         return Err(conf1.error.unwrap_or_else(|| "User cancelled".to_string()));
     }
+    elevation::check_elevation(
+        policy.as_ref(),
+        ElevatedOperation::ExportPrivateKey,
+        conf1.credential_collected_at_ms,
+        state::now_ms(),
+    )
+    .map_err(|e| e.to_string())?;
     let prf = conf1
         .prf_output
         .ok_or_else(|| "Missing PRF output from confirmation".to_string())?;
 
     // Decrypt using PRF output and encrypted material
-    let signing_key = crate::crypto::decrypt_private_key_with_prf(
+    let signing_key = crate::crypto::decrypt_stored_private_key_with_prf(
         &request.near_account_id,
         &prf,
         &request.encrypted_private_key_data,
         &request.encrypted_private_key_iv,
     )
+    .await
     .map_err(|e| format!("Decryption failed: {}", e))?;
 
     // Convert to NEAR ed25519:<b58(64)>
@@ -0,0 +1,104 @@
+// ******************************************************************************
+// *                                                                            *
+// *                     HANDLER: DEPLOY LARGE CONTRACT                         *
+// *                                                                            *
+// ******************************************************************************
+use crate::chunked_deploy::{plan_chunked_deploy, DeployManifest, StagingContractInterface};
+use crate::handlers::handle_sign_transactions_with_actions::{
+    handle_sign_transactions_with_actions, SignTransactionsWithActionsRequest,
+    TransactionSignResult,
+};
+use crate::types::handlers::{ConfirmationConfig, RpcCallPayload, RpcOverrides, WorkerPolicy};
+use crate::types::DecryptionPayload;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployLargeContractRequest {
+    /// Signer (`nearAccountId`) and contract verification
+    #[wasm_bindgen(getter_with_clone, js_name = "rpcCall")]
+    pub rpc_call: RpcCallPayload,
+    #[wasm_bindgen(getter_with_clone)]
+    pub decryption: DecryptionPayload,
+    /// Contract wasm bytes
+    #[wasm_bindgen(getter_with_clone)]
+    pub code: Vec<u8>,
+    /// Bytes per staging call (at most MAX_DEPLOY_CHUNK_BYTES)
+    #[wasm_bindgen(js_name = "chunkSize")]
+    pub chunk_size: u32,
+    #[wasm_bindgen(getter_with_clone)]
+    pub staging: StagingContractInterface,
+    #[wasm_bindgen(getter_with_clone, js_name = "confirmationConfig")]
+    #[serde(default)]
+    pub confirmation_config: Option<ConfirmationConfig>,
+    #[wasm_bindgen(getter_with_clone, js_name = "workerPolicy")]
+    #[serde(default)]
+    pub worker_policy: Option<WorkerPolicy>,
+    #[wasm_bindgen(getter_with_clone, js_name = "rpcOverrides")]
+    #[serde(default)]
+    pub rpc_overrides: Option<RpcOverrides>,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployLargeContractResult {
+    /// Signed staging calls followed by the deploy call, in broadcast order
+    #[wasm_bindgen(getter_with_clone, js_name = "signResult")]
+    #[serde(flatten)]
+    pub sign_result: TransactionSignResult,
+    #[wasm_bindgen(getter_with_clone)]
+    pub manifest: DeployManifest,
+}
+
+/// **Handles:** `WorkerRequestType::DeployLargeContract`
+/// Splits the contract into staging calls plus the deploy-from-staged call (see
+/// chunked_deploy.rs) and signs them as one batch: one confirmation, sequential nonces.
+/// The staging calls must land in order before the deploy call is broadcast.
+///
+/// # Arguments
+/// * `request` - Contract code, chunk size and staging contract methods, plus the signing inputs
+///
+/// # Returns
+/// * `DeployLargeContractResult` - Signed transactions in broadcast order and the deploy manifest
+pub async fn handle_deploy_large_contract(
+    request: DeployLargeContractRequest,
+) -> Result<DeployLargeContractResult, String> {
+    let plan = plan_chunked_deploy(
+        &request.rpc_call.near_account_id,
+        &request.code,
+        request.chunk_size,
+        &request.staging,
+    )?;
+
+    let sign_result = handle_sign_transactions_with_actions(SignTransactionsWithActionsRequest {
+        rpc_call: request.rpc_call,
+        decryption: request.decryption,
+        tx_signing_requests: plan.transactions,
+        confirmation_config: request.confirmation_config,
+        worker_policy: request.worker_policy,
+        idempotency_key: None,
+        rpc_overrides: request.rpc_overrides,
+        broadcast: false,
+        valid_until_block_height: None,
+        transaction_context: None,
+        signing_public_key: None,
+        deduplicate: false,
+        preview_digest: None,
+        redact_paths: Vec::new(),
+        conditional_batch: None,
+        approval_token: None,
+        auto_storage_deposit: false,
+        batch_preparation: None,
+        deploy_manifest: Some(plan.manifest.clone()),
+        auto_inserted_actions: Vec::new(),
+    })
+    .await?;
+
+    Ok(DeployLargeContractResult {
+        sign_result,
+        manifest: plan.manifest,
+    })
+}
@@ -10,8 +10,15 @@ use serde::{Deserialize, Serialize};
 use serde_json;
 use wasm_bindgen::prelude::*;
 
+use crate::contract_args::ContractInterfaceVersion;
+use crate::credential_options::negotiated_algorithm;
 use crate::encoders::base64_url_decode;
-use crate::rpc_calls::VrfData;
+use crate::key_wrapping::{select_fallback_scheme, wrap_near_private_key, KeyWrappingScheme};
+use crate::known_authenticators::{authenticator_of_attestation, AuthenticatorInfo};
+use crate::rpc_calls::{probe_contract_interface, VrfData};
+use crate::rpc_client::NearRpcClient;
+use crate::sub_keys::{derive_sub_key_entries, SubKeyEntry};
+use crate::types::handlers::WorkerPolicy;
 use crate::types::wasm_to_json::WasmSignedTransaction;
 use crate::types::{
     AuthenticatorOptions, SerializedRegistrationCredential, VrfChallenge,
@@ -22,8 +29,22 @@ use crate::types::{
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct DeriveNearKeypairAndEncryptRequest {
+    /// Absent when the authenticator does not support the PRF extension
     #[wasm_bindgen(getter_with_clone, js_name = "dualPrfOutputs")]
-    pub dual_prf_outputs: DualPrfOutputsStruct,
+    #[serde(default)]
+    pub dual_prf_outputs: Option<DualPrfOutputsStruct>,
+    /// false when the credential's clientExtensionResults lack PRF results
+    #[wasm_bindgen(getter_with_clone, js_name = "prfSupported")]
+    #[serde(default)]
+    pub prf_supported: Option<bool>,
+    /// Key wrapping to use without PRF output, within `workerPolicy.prfFallbackSchemes`
+    #[wasm_bindgen(getter_with_clone, js_name = "prfFallback")]
+    #[serde(default)]
+    pub prf_fallback: Option<PrfFallbackOptions>,
+    /// `prfFallbackSchemes` allows registering without PRF output
+    #[wasm_bindgen(getter_with_clone, js_name = "workerPolicy")]
+    #[serde(default)]
+    pub worker_policy: Option<WorkerPolicy>,
     #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
     pub near_account_id: String,
     #[wasm_bindgen(getter_with_clone)]
@@ -32,6 +53,15 @@ pub struct DeriveNearKeypairAndEncryptRequest {
     pub registration_transaction: Option<LinkDeviceRegistrationTransaction>,
     #[wasm_bindgen(getter_with_clone, js_name = "authenticatorOptions")]
     pub authenticator_options: Option<AuthenticatorOptions>,
+    /// Repeats with the same key replay the first completed result (see idempotency.rs)
+    #[wasm_bindgen(getter_with_clone, js_name = "idempotencyKey")]
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+    /// SLIP-0010 paths of sub-keys to derive alongside the account key (see sub_keys.rs);
+    /// needs PRF output
+    #[wasm_bindgen(getter_with_clone, js_name = "subKeyPaths")]
+    #[serde(default)]
+    pub sub_key_paths: Option<Vec<String>>,
 }
 
 #[wasm_bindgen]
@@ -44,6 +74,20 @@ pub struct DualPrfOutputsStruct {
     pub ed25519_prf_output: String,
 }
 
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PrfFallbackOptions {
+    /// "largeBlob" or "passphrase" (defaults to the policy's first)
+    #[wasm_bindgen(getter_with_clone)]
+    #[serde(default)]
+    pub scheme: Option<String>,
+    /// For the passphrase scheme
+    #[wasm_bindgen(getter_with_clone)]
+    #[serde(default)]
+    pub passphrase: Option<String>,
+}
+
 #[wasm_bindgen]
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -58,6 +102,14 @@ pub struct LinkDeviceRegistrationTransaction {
     pub block_hash: String,
     #[wasm_bindgen(getter_with_clone, js_name = "deterministicVrfPublicKey")]
     pub deterministic_vrf_public_key: String,
+    /// Contract registration interface version; probed via `nearRpcUrl` when omitted,
+    /// otherwise the latest
+    #[wasm_bindgen(js_name = "contractInterfaceVersion")]
+    #[serde(default)]
+    pub contract_interface_version: Option<u32>,
+    #[wasm_bindgen(getter_with_clone, js_name = "nearRpcUrl")]
+    #[serde(default)]
+    pub near_rpc_url: Option<String>,
 }
 
 #[wasm_bindgen]
@@ -74,7 +126,34 @@ pub struct DeriveNearKeypairAndEncryptResult {
     pub iv: String,
     pub stored: bool,
     #[wasm_bindgen(getter_with_clone, js_name = "signedTransaction")]
+    #[serde(default)]
     pub signed_transaction: Option<WasmSignedTransaction>,
+    /// "aes-gcm-webcrypto" when `encryptedData` carries the outer WebCrypto wrap
+    #[wasm_bindgen(getter_with_clone, js_name = "outerWrap")]
+    #[serde(default)]
+    pub outer_wrap: Option<String>,
+    /// Encrypted blob version, which records the key-wrapping scheme; stored with the blob
+    pub version: u32,
+    /// "prf", or the fallback scheme the key was wrapped with
+    #[wasm_bindgen(getter_with_clone, js_name = "keyWrapping")]
+    pub key_wrapping: String,
+    /// largeBlob scheme only: the secret to write to the credential's largeBlob
+    #[wasm_bindgen(getter_with_clone, js_name = "largeBlobSecret")]
+    #[serde(default)]
+    pub large_blob_secret: Option<String>,
+    /// COSE algorithm of the credential key (e.g. -8 for EdDSA), when the attestation has one
+    #[wasm_bindgen(js_name = "negotiatedAlgorithm")]
+    #[serde(default)]
+    pub negotiated_algorithm: Option<i32>,
+    /// The `subKeyPaths` derived, in canonical form, with their public keys
+    #[wasm_bindgen(getter_with_clone, js_name = "subKeys")]
+    #[serde(default)]
+    pub sub_keys: Vec<SubKeyEntry>,
+    /// The authenticator model named by the attestation's AAGUID (see known_authenticators.rs),
+    /// when the attestation has an attested credential
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub authenticator: Option<AuthenticatorInfo>,
 }
 
 #[wasm_bindgen]
@@ -87,6 +166,7 @@ impl DeriveNearKeypairAndEncryptResult {
         iv: String,
         stored: bool,
         signed_transaction: Option<WasmSignedTransaction>,
+        outer_wrap: Option<String>,
     ) -> DeriveNearKeypairAndEncryptResult {
         DeriveNearKeypairAndEncryptResult {
             near_account_id,
@@ -95,6 +175,13 @@ impl DeriveNearKeypairAndEncryptResult {
             iv,
             stored,
             signed_transaction,
+            outer_wrap,
+            version: KeyWrappingScheme::Prf.blob_version(),
+            key_wrapping: KeyWrappingScheme::Prf.as_str().to_string(),
+            large_blob_secret: None,
+            negotiated_algorithm: None,
+            sub_keys: Vec::new(),
+            authenticator: None,
         }
     }
 }
@@ -105,6 +192,16 @@ impl DeriveNearKeypairAndEncryptResult {
 /// 2. Encrypts the private key using AES-GCM with AES PRF output
 /// 3. Optionally signs a device registration transaction for linking devices
 ///
+/// Without PRF output, the keypair is generated and encrypted under the fallback scheme
+/// WorkerPolicy allows (see key_wrapping.rs), or the request fails with
+/// PrfUnsupportedByAuthenticator.
+///
+/// Sub-keys listed in `subKeyPaths` are derived from the Ed25519 PRF output and returned with
+/// their public keys; listing any without PRF output fails the request.
+///
+/// A credential created with an algorithm outside `authenticatorOptions.allowedAlgorithms`
+/// fails with DisallowedAlgorithmNegotiated before any key is derived.
+///
 /// # Arguments
 /// * `request` - Contains dual PRF outputs, account ID, WebAuthn credential, and optional registration transaction
 ///
@@ -114,29 +211,94 @@ pub async fn handle_derive_near_keypair_and_encrypt(
     request: DeriveNearKeypairAndEncryptRequest,
 ) -> Result<DeriveNearKeypairAndEncryptResult, String> {
     info!("RUST: WASM binding - starting structured dual PRF keypair derivation with optional transaction signing");
-    // Convert wasm-bindgen types to internal types
-    let internal_dual_prf_outputs = crate::types::DualPrfOutputs {
-        chacha20_prf_output_base64: request.dual_prf_outputs.chacha20_prf_output,
-        ed25519_prf_output_base64: request.dual_prf_outputs.ed25519_prf_output,
+    let negotiated_algorithm = negotiated_algorithm(
+        &request.credential.response.attestation_object,
+        request.authenticator_options.as_ref(),
+    )
+    .map_err(|e| e.to_string())?;
+    let authenticator =
+        authenticator_of_attestation(&request.credential.response.attestation_object);
+    let prf_outputs = request.dual_prf_outputs.clone().filter(|outputs| {
+        request.prf_supported != Some(false)
+            && !outputs.chacha20_prf_output.is_empty()
+            && !outputs.ed25519_prf_output.is_empty()
+    });
+    let sub_keys = match (&prf_outputs, request.sub_key_paths.as_deref()) {
+        (_, None | Some([])) => Vec::new(),
+        (Some(prf_outputs), Some(paths)) => derive_sub_key_entries(
+            &prf_outputs.ed25519_prf_output,
+            &request.near_account_id,
+            paths,
+        )?,
+        (None, Some(_)) => {
+            return Err("subKeyPaths need PRF output to derive sub-keys from".to_string())
+        }
     };
 
-    // Call the dual PRF derivation function (same as JSON version)
-    let (public_key, encrypted_result) = crate::crypto::derive_and_encrypt_keypair_from_dual_prf(
-        &internal_dual_prf_outputs,
-        &request.near_account_id,
-    )
-    .map_err(|e| format!("Failed to derive and encrypt keypair: {}", e))?;
+    let (near_private_key, public_key, encrypted_result, scheme, large_blob_secret) =
+        match prf_outputs {
+            Some(prf_outputs) => {
+                // Convert wasm-bindgen types to internal types
+                let internal_dual_prf_outputs = crate::types::DualPrfOutputs {
+                    chacha20_prf_output_base64: prf_outputs.chacha20_prf_output,
+                    ed25519_prf_output_base64: prf_outputs.ed25519_prf_output,
+                };
+
+                // Call the dual PRF derivation function (same as JSON version)
+                let (public_key, encrypted_result) =
+                    crate::crypto::derive_and_encrypt_keypair_from_dual_prf(
+                        &internal_dual_prf_outputs,
+                        &request.near_account_id,
+                    )
+                    .map_err(|e| format!("Failed to derive and encrypt keypair: {}", e))?;
+
+                // Re-derive the private key from the same PRF output for signing (before it's encrypted)
+                let (near_private_key, _near_public_key) =
+                    crate::crypto::derive_ed25519_key_from_prf_output(
+                        &internal_dual_prf_outputs.ed25519_prf_output_base64,
+                        &request.near_account_id,
+                    )
+                    .map_err(|e| format!("Failed to re-derive keypair for signing: {}", e))?;
+                (
+                    near_private_key,
+                    public_key,
+                    encrypted_result,
+                    KeyWrappingScheme::Prf,
+                    None,
+                )
+            }
+            None => {
+                let fallback = request.prf_fallback.as_ref();
+                let scheme = select_fallback_scheme(
+                    fallback.and_then(|f| f.scheme.as_deref()),
+                    request.worker_policy.as_ref(),
+                )
+                .map_err(|e| e.to_string())?;
+                info!(
+                    "RUST: No PRF output from the authenticator, wrapping a generated key with {}",
+                    scheme.as_str()
+                );
+                let (near_private_key, public_key) = crate::crypto::generate_near_keypair()
+                    .map_err(|e| format!("Failed to generate keypair: {}", e))?;
+                let wrapped = wrap_near_private_key(
+                    &near_private_key,
+                    &request.near_account_id,
+                    scheme,
+                    fallback.and_then(|f| f.passphrase.as_deref()),
+                )
+                .map_err(|e| e.to_string())?;
+                (
+                    near_private_key,
+                    public_key,
+                    wrapped.encrypted,
+                    scheme,
+                    wrapped.large_blob_secret,
+                )
+            }
+        };
 
     // Handle optional transaction signing if registration transaction is provided
     let signed_transaction_wasm = if let Some(registration_tx) = &request.registration_transaction {
-        // Re-derive the private key from the same PRF output for signing (before it's encrypted)
-        let (near_private_key, _near_public_key) =
-            crate::crypto::derive_ed25519_key_from_prf_output(
-                &internal_dual_prf_outputs.ed25519_prf_output_base64,
-                &request.near_account_id,
-            )
-            .map_err(|e| format!("Failed to re-derive keypair for signing: {}", e))?;
-
         // Parse nonce
         let parsed_nonce = registration_tx
             .nonce
@@ -169,6 +331,17 @@ pub async fn handle_derive_near_keypair_and_encrypt(
             base64_url_decode(&registration_tx.deterministic_vrf_public_key)
                 .map_err(|e| format!("Failed to decode deterministic VRF public key: {}", e))?;
 
+        let interface_version = match (
+            registration_tx.contract_interface_version,
+            &registration_tx.near_rpc_url,
+        ) {
+            (None, Some(rpc_url)) => {
+                let rpc = NearRpcClient::new(rpc_url);
+                probe_contract_interface(&rpc, &registration_tx.contract_id).await
+            }
+            (version, _) => ContractInterfaceVersion::resolve(version)?,
+        };
+
         match crate::transaction::sign_link_device_registration_tx(
             &registration_tx.contract_id,
             vrf_data,
@@ -181,6 +354,7 @@ pub async fn handle_derive_near_keypair_and_encrypt(
                 .into_vec()
                 .map_err(|e| format!("Invalid block hash: {}", e))?,
             request.authenticator_options, // Pass authenticator options
+            interface_version,
         )
         .await
         {
@@ -212,13 +386,28 @@ pub async fn handle_derive_near_keypair_and_encrypt(
     // Convert signed transaction to WASM wrapper if present
     let signed_transaction_struct = signed_transaction_wasm;
 
+    // Outer WebCrypto wrap, when the request came with a CryptoKey
+    let (encrypted_data, outer_wrap) =
+        crate::outer_wrap::wrap_encrypted_data(&encrypted_result.encrypted_near_key_data_b64u)
+            .await
+            .map_err(|e| format!("Failed to outer-wrap encrypted key: {}", e))?;
+
     // Return structured result with optional signed transaction
-    Ok(DeriveNearKeypairAndEncryptResult::new(
-        request.near_account_id,
-        public_key,
-        encrypted_result.encrypted_near_key_data_b64u,
-        encrypted_result.chacha20_nonce_b64u,
-        true, // stored = true since we're storing in WASM
-        signed_transaction_struct,
-    ))
+    Ok(DeriveNearKeypairAndEncryptResult {
+        version: scheme.blob_version(),
+        key_wrapping: scheme.as_str().to_string(),
+        large_blob_secret,
+        negotiated_algorithm,
+        sub_keys,
+        authenticator,
+        ..DeriveNearKeypairAndEncryptResult::new(
+            request.near_account_id,
+            public_key,
+            encrypted_data,
+            encrypted_result.chacha20_nonce_b64u,
+            true, // stored = true since we're storing in WASM
+            signed_transaction_struct,
+            outer_wrap,
+        )
+    })
 }
@@ -0,0 +1,104 @@
+// ******************************************************************************
+// *                                                                            *
+// *                          HANDLER: DERIVE SUB-KEY                           *
+// *                                                                            *
+// ******************************************************************************
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::handlers::handle_derive_near_keypair_and_encrypt::DualPrfOutputsStruct;
+use crate::sub_keys::{derive_sub_key, DerivationPath};
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeriveSubKeyRequest {
+    #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
+    pub near_account_id: String,
+    /// PRF outputs of an assertion with the account's passkey; the ChaCha20 output is only
+    /// used with `encrypt`
+    #[wasm_bindgen(getter_with_clone, js_name = "dualPrfOutputs")]
+    pub dual_prf_outputs: DualPrfOutputsStruct,
+    /// SLIP-0010 path, hardened indexes only (e.g. "m/44'/397'/0'/0'/1'")
+    #[wasm_bindgen(getter_with_clone)]
+    pub path: String,
+    /// Also return the private key, encrypted like the account key
+    #[serde(default)]
+    pub encrypt: bool,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeriveSubKeyResult {
+    #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
+    pub near_account_id: String,
+    /// The path in canonical form (`'` for hardened indexes)
+    #[wasm_bindgen(getter_with_clone)]
+    pub path: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "publicKey")]
+    pub public_key: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "encryptedData")]
+    pub encrypted_data: Option<String>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub iv: Option<String>,
+    /// "aes-gcm-webcrypto" when `encryptedData` carries the outer WebCrypto wrap
+    #[wasm_bindgen(getter_with_clone, js_name = "outerWrap")]
+    pub outer_wrap: Option<String>,
+}
+
+/// **Handles:** `WorkerRequestType::DeriveSubKey`
+/// Derives the account's sub-key at a SLIP-0010 path from the Ed25519 PRF output (see
+/// sub_keys.rs). The private key never leaves the worker in the clear: it is returned only
+/// with `encrypt`, encrypted under the ChaCha20 PRF output like the account key.
+///
+/// # Arguments
+/// * `request` - Account, PRF outputs, path and whether to encrypt the private key
+///
+/// # Returns
+/// * `DeriveSubKeyResult` - Canonical path, public key and the optional encrypted key
+pub async fn handle_derive_sub_key(
+    request: DeriveSubKeyRequest,
+) -> Result<DeriveSubKeyResult, String> {
+    let path = DerivationPath::parse(&request.path).map_err(|e| e.to_string())?;
+    let (private_key, public_key) = derive_sub_key(
+        &request.dual_prf_outputs.ed25519_prf_output,
+        &request.near_account_id,
+        &path,
+    )
+    .map_err(|e| format!("Failed to derive sub-key: {}", e))?;
+    info!(
+        "RUST: Derived sub-key {} for {}",
+        path, request.near_account_id
+    );
+
+    let (encrypted_data, iv, outer_wrap) = if request.encrypt {
+        let encrypted = crate::crypto::encrypt_private_key_with_prf(
+            &private_key,
+            &request.dual_prf_outputs.chacha20_prf_output,
+            &request.near_account_id,
+        )?;
+        let (encrypted_data, outer_wrap) =
+            crate::outer_wrap::wrap_encrypted_data(&encrypted.encrypted_near_key_data_b64u)
+                .await
+                .map_err(|e| format!("Failed to outer-wrap encrypted key: {}", e))?;
+        (
+            Some(encrypted_data),
+            Some(encrypted.chacha20_nonce_b64u),
+            outer_wrap,
+        )
+    } else {
+        (None, None, None)
+    };
+
+    Ok(DeriveSubKeyResult {
+        near_account_id: request.near_account_id,
+        path: path.to_string(),
+        public_key,
+        encrypted_data,
+        iv,
+        outer_wrap,
+    })
+}
@@ -0,0 +1,61 @@
+// ******************************************************************************
+// *                                                                            *
+// *                        HANDLER: DUMP DIAGNOSTICS                           *
+// *                                                                            *
+// ******************************************************************************
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use wasm_bindgen::prelude::*;
+
+use crate::diagnostics;
+use crate::handlers::handle_validate_encrypted_blobs::EncryptedKeyBlob;
+use crate::types::handlers::WorkerPolicy;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DumpDiagnosticsRequest {
+    /// The WorkerPolicy the caller sends with its requests
+    #[wasm_bindgen(getter_with_clone, js_name = "workerPolicy")]
+    #[serde(default)]
+    pub worker_policy: Option<WorkerPolicy>,
+    /// Stored key blobs, reported by version and key fingerprint; never decrypted
+    #[wasm_bindgen(getter_with_clone, js_name = "keyBlobs")]
+    #[serde(default)]
+    pub key_blobs: Vec<EncryptedKeyBlob>,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DumpDiagnosticsResult {
+    /// The diagnostics document, pretty-printed JSON
+    #[wasm_bindgen(getter_with_clone)]
+    pub document: String,
+    /// Hex SHA-256 of `document`; equal digests mean nothing changed between two dumps
+    #[wasm_bindgen(getter_with_clone)]
+    pub digest: String,
+}
+
+/// **Handles:** `WorkerRequestType::DumpDiagnostics`
+/// Exports the worker's effective configuration and session state as a redacted,
+/// deterministic JSON document (see diagnostics.rs).
+///
+/// # Arguments
+/// * `request` - The caller's WorkerPolicy and stored key blobs
+///
+/// # Returns
+/// * `DumpDiagnosticsResult` - The document and its SHA-256
+pub async fn handle_dump_diagnostics(
+    request: DumpDiagnosticsRequest,
+) -> Result<DumpDiagnosticsResult, String> {
+    let policy = request.worker_policy.unwrap_or_default();
+    let document = serde_json::to_string_pretty(&diagnostics::collect(&policy, &request.key_blobs))
+        .map_err(|e| format!("Failed to serialize diagnostics: {}", e))?;
+    let digest = Sha256::digest(document.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Ok(DumpDiagnosticsResult { document, digest })
+}
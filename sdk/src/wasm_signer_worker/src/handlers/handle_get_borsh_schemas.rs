@@ -0,0 +1,113 @@
+// ******************************************************************************
+// *                                                                            *
+// *                       HANDLER: GET BORSH SCHEMAS                           *
+// *                                                                            *
+// ******************************************************************************
+use crate::types::near::{DelegateAction, Nep413Payload, SignedTransaction, Transaction};
+use borsh::schema::{BorshSchemaContainer, Definition, Fields};
+use borsh::BorshSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetBorshSchemasRequest {}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetBorshSchemasResult {
+    /// JSON-encoded schema container for `Transaction` (includes every `Action` variant)
+    #[wasm_bindgen(getter_with_clone, js_name = "transaction")]
+    pub transaction: String,
+    /// JSON-encoded schema container for `SignedTransaction`
+    #[wasm_bindgen(getter_with_clone, js_name = "signedTransaction")]
+    pub signed_transaction: String,
+    /// JSON-encoded schema container for `DelegateAction`
+    #[wasm_bindgen(getter_with_clone, js_name = "delegateAction")]
+    pub delegate_action: String,
+    /// JSON-encoded schema container for the NEP-413 payload (signed after the 2^31 + 413 u32 prefix)
+    #[wasm_bindgen(getter_with_clone, js_name = "nep413Payload")]
+    pub nep413_payload: String,
+}
+
+/// **Handles:** `WorkerRequestType::GetBorshSchemas`
+/// Returns the borsh schemas of the exact types this worker serializes before signing,
+/// so auditors (and other-language decoders) can verify the signed byte layout.
+///
+/// # Arguments
+/// * `_request` - Empty request
+///
+/// # Returns
+/// * `GetBorshSchemasResult` - JSON-encoded schema containers, one per signed type
+pub async fn handle_get_borsh_schemas(
+    _request: GetBorshSchemasRequest,
+) -> Result<GetBorshSchemasResult, String> {
+    Ok(GetBorshSchemasResult {
+        transaction: borsh_schema_json::<Transaction>().to_string(),
+        signed_transaction: borsh_schema_json::<SignedTransaction>().to_string(),
+        delegate_action: borsh_schema_json::<DelegateAction>().to_string(),
+        nep413_payload: borsh_schema_json::<Nep413Payload>().to_string(),
+    })
+}
+
+/// Converts the `BorshSchemaContainer` of `T` into JSON:
+/// `{ "declaration": "<root>", "definitions": { "<declaration>": { "kind": ..., ... } } }`
+pub fn borsh_schema_json<T: BorshSchema + ?Sized>() -> Value {
+    let container = BorshSchemaContainer::for_type::<T>();
+    let definitions: serde_json::Map<String, Value> = container
+        .definitions()
+        .map(|(declaration, definition)| (declaration.clone(), definition_to_json(definition)))
+        .collect();
+
+    json!({
+        "declaration": container.declaration(),
+        "definitions": definitions,
+    })
+}
+
+fn definition_to_json(definition: &Definition) -> Value {
+    match definition {
+        Definition::Primitive(size) => json!({ "kind": "primitive", "size": size }),
+        Definition::Sequence {
+            length_width,
+            length_range,
+            elements,
+        } => json!({
+            "kind": "sequence",
+            "lengthWidth": length_width,
+            "lengthRange": [length_range.start(), length_range.end()],
+            "elements": elements,
+        }),
+        Definition::Tuple { elements } => json!({ "kind": "tuple", "elements": elements }),
+        Definition::Enum {
+            tag_width,
+            variants,
+        } => json!({
+            "kind": "enum",
+            "tagWidth": tag_width,
+            "variants": variants
+                .iter()
+                .map(|(discriminant, name, declaration)| {
+                    json!({ "discriminant": discriminant, "name": name, "type": declaration })
+                })
+                .collect::<Vec<_>>(),
+        }),
+        Definition::Struct { fields } => {
+            let fields = match fields {
+                Fields::NamedFields(named) => named
+                    .iter()
+                    .map(|(name, declaration)| json!({ "name": name, "type": declaration }))
+                    .collect(),
+                Fields::UnnamedFields(unnamed) => unnamed
+                    .iter()
+                    .map(|declaration| json!({ "type": declaration }))
+                    .collect(),
+                Fields::Empty => Vec::new(),
+            };
+            json!({ "kind": "struct", "fields": fields })
+        }
+    }
+}
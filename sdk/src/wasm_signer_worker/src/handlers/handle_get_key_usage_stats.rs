@@ -0,0 +1,28 @@
+// ******************************************************************************
+// *                                                                            *
+// *                      HANDLER: GET KEY USAGE STATS                          *
+// *                                                                            *
+// ******************************************************************************
+use crate::key_usage::{self, KeyUsageStats};
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetKeyUsageStatsRequest {}
+
+/// **Handles:** `WorkerRequestType::GetKeyUsageStats`
+/// Reports the usage of each key blob: the record handed over at Initialize merged with what
+/// this worker signed since.
+///
+/// # Arguments
+/// * `_request` - Empty request
+///
+/// # Returns
+/// * `KeyUsageStats` - Per-key sign counts and last use, most recently used first
+pub async fn handle_get_key_usage_stats(
+    _request: GetKeyUsageStatsRequest,
+) -> Result<KeyUsageStats, String> {
+    Ok(key_usage::stats())
+}
@@ -0,0 +1,71 @@
+// ******************************************************************************
+// *                                                                            *
+// *                      HANDLER: GET RECENT RECEIVERS                         *
+// *                                                                            *
+// ******************************************************************************
+use crate::config::{DEFAULT_RECENT_RECEIVERS_LIMIT, MAX_RECENT_RECEIVERS_LIMIT};
+use crate::recent_receivers::lookup_receiver_history;
+use crate::state::RecentReceiver;
+use crate::types::handlers::WorkerPolicy;
+use crate::types::AccountId;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRecentReceiversRequest {
+    #[wasm_bindgen(getter_with_clone, js_name = "accountId")]
+    pub account_id: String,
+    /// Maximum receivers to return (defaults to DEFAULT_RECENT_RECEIVERS_LIMIT)
+    #[serde(default)]
+    pub limit: Option<u32>,
+    /// Supplies the indexer URL
+    #[wasm_bindgen(getter_with_clone, js_name = "workerPolicy")]
+    #[serde(default)]
+    pub worker_policy: Option<WorkerPolicy>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRecentReceiversResult {
+    pub account_id: String,
+    /// Most recently seen first; empty when the source is "unknown"
+    pub receivers: Vec<RecentReceiver>,
+    /// "cache", "indexer" or "unknown" (no indexer configured, or it was unreachable)
+    pub source: String,
+}
+
+/// **Handles:** `WorkerRequestType::GetRecentReceivers`
+/// Lists the distinct receivers of the account's recent outgoing transactions, for account
+/// ID autocomplete. Indexer failures are reported as source "unknown", not as errors.
+///
+/// # Arguments
+/// * `request` - Account ID, limit and the policy carrying the indexer URL
+///
+/// # Returns
+/// * `GetRecentReceiversResult` - Receivers with last-seen timestamps and where they came from
+pub async fn handle_get_recent_receivers(
+    request: GetRecentReceiversRequest,
+) -> Result<GetRecentReceiversResult, String> {
+    let account = AccountId::new(request.account_id.clone())
+        .map_err(|_| "Missing required field: accountId".to_string())?;
+    let limit = request
+        .limit
+        .unwrap_or(DEFAULT_RECENT_RECEIVERS_LIMIT)
+        .min(MAX_RECENT_RECEIVERS_LIMIT) as usize;
+    let indexer_url = request
+        .worker_policy
+        .as_ref()
+        .and_then(|p| p.indexer_url.as_deref());
+
+    let history = lookup_receiver_history(&account, indexer_url).await;
+    let mut receivers = history.receivers.unwrap_or_default();
+    receivers.truncate(limit);
+
+    Ok(GetRecentReceiversResult {
+        account_id: request.account_id,
+        receivers,
+        source: history.source.as_str().to_string(),
+    })
+}
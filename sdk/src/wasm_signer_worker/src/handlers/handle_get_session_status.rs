@@ -0,0 +1,30 @@
+// ******************************************************************************
+// *                                                                            *
+// *                       HANDLER: GET SESSION STATUS                          *
+// *                                                                            *
+// ******************************************************************************
+use crate::session_duration::{self, SessionStatus};
+use crate::state;
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetSessionStatusRequest {}
+
+/// **Handles:** `WorkerRequestType::GetSessionStatus`
+/// Reports the session epoch and, under a maxSessionDurationMs policy, when the session expires
+/// and how long is left (see session_duration.rs), so callers can prompt before key-using
+/// requests start failing with SessionExpired.
+///
+/// # Arguments
+/// * `_request` - Empty request
+///
+/// # Returns
+/// * `SessionStatus` - The session's start, epoch, expiry and remaining time
+pub async fn handle_get_session_status(
+    _request: GetSessionStatusRequest,
+) -> Result<SessionStatus, String> {
+    Ok(session_duration::status(state::now_ms()))
+}
@@ -0,0 +1,28 @@
+// ******************************************************************************
+// *                                                                            *
+// *                     HANDLER: GET TELEMETRY SNAPSHOT                        *
+// *                                                                            *
+// ******************************************************************************
+use crate::telemetry::{self, TelemetrySnapshot};
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTelemetrySnapshotRequest {}
+
+/// **Handles:** `WorkerRequestType::GetTelemetrySnapshot`
+/// Reports the per-handler request counters and latency histograms kept in memory, whether
+/// or not WorkerPolicy.telemetry reports them remotely.
+///
+/// # Arguments
+/// * `_request` - Empty request
+///
+/// # Returns
+/// * `TelemetrySnapshot` - Counters of every tracked request since the worker started
+pub async fn handle_get_telemetry_snapshot(
+    _request: GetTelemetrySnapshotRequest,
+) -> Result<TelemetrySnapshot, String> {
+    Ok(telemetry::snapshot())
+}
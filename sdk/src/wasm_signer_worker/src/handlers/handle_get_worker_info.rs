@@ -0,0 +1,26 @@
+// ******************************************************************************
+// *                                                                            *
+// *                        HANDLER: GET WORKER INFO                            *
+// *                                                                            *
+// ******************************************************************************
+use crate::features::WorkerInfo;
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetWorkerInfoRequest {}
+
+/// **Handles:** `WorkerRequestType::GetWorkerInfo`
+/// Reports which optional features this build was compiled with (see features.rs), so callers
+/// can hide what the worker would answer with FeatureNotCompiled.
+///
+/// # Arguments
+/// * `_request` - Empty request
+///
+/// # Returns
+/// * `WorkerInfo` - The compiled features and the worker version
+pub async fn handle_get_worker_info(_request: GetWorkerInfoRequest) -> Result<WorkerInfo, String> {
+    Ok(WorkerInfo::current())
+}
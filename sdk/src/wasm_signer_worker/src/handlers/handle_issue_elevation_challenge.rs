@@ -0,0 +1,58 @@
+// ******************************************************************************
+// *                                                                            *
+// *                   HANDLER: ISSUE ELEVATION CHALLENGE                       *
+// *                                                                            *
+// ******************************************************************************
+use crate::elevation::{issue_elevation_challenge, ElevationChallenge};
+use crate::encoders::base64_url_encode;
+use crate::state;
+use crate::types::AccountId;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueElevationChallengeRequest {
+    #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
+    pub near_account_id: String,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueElevationChallengeResult {
+    /// base64url token; its decoded bytes are the WebAuthn challenge of the assertion
+    #[wasm_bindgen(getter_with_clone)]
+    pub challenge: String,
+    #[wasm_bindgen(js_name = "issuedAtMs")]
+    pub issued_at_ms: f64,
+}
+
+/// **Handles:** `WorkerRequestType::IssueElevationChallenge`
+/// Issues the challenge a raw-PRF export's passkey assertion is made for (see elevation.rs).
+/// DecryptPrivateKeyWithPrf accepts the assertion, once, while the challenge is within the
+/// elevation window.
+///
+/// # Arguments
+/// * `request` - The account the assertion will be made for
+///
+/// # Returns
+/// * `IssueElevationChallengeResult` - The challenge and when it was issued (worker clock)
+pub async fn handle_issue_elevation_challenge(
+    request: IssueElevationChallengeRequest,
+) -> Result<IssueElevationChallengeResult, String> {
+    let account = AccountId::new(request.near_account_id)?;
+    let mut id = [0u8; 16];
+    getrandom::getrandom(&mut id)
+        .map_err(|e| format!("Failed to generate elevation challenge ID: {}", e))?;
+    let challenge = ElevationChallenge {
+        near_account_id: account.0,
+        challenge_id: base64_url_encode(&id),
+        issued_at_ms: state::now_ms(),
+    };
+    Ok(IssueElevationChallengeResult {
+        challenge: issue_elevation_challenge(&challenge, &state::signing_intent_key()?)?,
+        issued_at_ms: challenge.issued_at_ms,
+    })
+}
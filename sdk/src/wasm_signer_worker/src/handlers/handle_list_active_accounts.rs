@@ -0,0 +1,72 @@
+// ******************************************************************************
+// *                                                                            *
+// *                     HANDLER: LIST ACTIVE ACCOUNTS                          *
+// *                                                                            *
+// ******************************************************************************
+use crate::state;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ListActiveAccountsRequest {}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveAccountEntry {
+    #[wasm_bindgen(getter_with_clone, js_name = "accountId")]
+    pub account_id: String,
+    /// Requests of this session that signed
+    #[wasm_bindgen(js_name = "signedRequests")]
+    pub signed_requests: u32,
+    /// Requests of this session that were rejected, refused or failed
+    #[wasm_bindgen(js_name = "failedRequests")]
+    pub failed_requests: u32,
+    #[wasm_bindgen(js_name = "nonceReservations")]
+    pub nonce_reservations: u32,
+    #[wasm_bindgen(js_name = "auditEntries")]
+    pub audit_entries: u32,
+    #[wasm_bindgen(js_name = "sessionKeys")]
+    pub session_keys: u32,
+    /// When the account's state last changed (0 for accounts holding only session keys)
+    #[wasm_bindgen(js_name = "lastActiveMs")]
+    pub last_active_ms: f64,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ListActiveAccountsResult {
+    #[wasm_bindgen(getter_with_clone)]
+    pub accounts: Vec<ActiveAccountEntry>,
+}
+
+/// **Handles:** `WorkerRequestType::ListActiveAccounts`
+/// Lists the accounts the worker holds session state for (nonce reservations, audit log,
+/// request counters, cached receivers) or live session keys, most recently active first.
+///
+/// # Arguments
+/// * `_request` - Empty request
+///
+/// # Returns
+/// * `ListActiveAccountsResult` - Per-account counters
+pub async fn handle_list_active_accounts(
+    _request: ListActiveAccountsRequest,
+) -> Result<ListActiveAccountsResult, String> {
+    let accounts = state::list_active_accounts()
+        .into_iter()
+        .map(|info| ActiveAccountEntry {
+            account_id: info.account_id.to_string(),
+            signed_requests: info.signed_requests,
+            failed_requests: info.failed_requests,
+            nonce_reservations: info.nonce_reservations as u32,
+            audit_entries: info.audit_entries as u32,
+            session_keys: info.session_keys as u32,
+            last_active_ms: info.last_active_ms,
+        })
+        .collect();
+
+    Ok(ListActiveAccountsResult { accounts })
+}
@@ -0,0 +1,74 @@
+// ******************************************************************************
+// *                                                                            *
+// *                     HANDLER: LIST PENDING REQUESTS                         *
+// *                                                                            *
+// ******************************************************************************
+use crate::state;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ListPendingRequestsRequest {}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingRequestEntry {
+    #[wasm_bindgen(getter_with_clone, js_name = "requestId")]
+    pub request_id: String,
+    /// Request type name, e.g. "SIGN_TRANSACTIONS_WITH_ACTIONS"
+    #[wasm_bindgen(getter_with_clone)]
+    pub operation: String,
+    /// "queued" | "running"
+    #[wasm_bindgen(getter_with_clone)]
+    pub phase: String,
+    /// Milliseconds since the request was admitted
+    #[wasm_bindgen(js_name = "ageMs")]
+    pub age_ms: f64,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ListPendingRequestsResult {
+    #[wasm_bindgen(getter_with_clone)]
+    pub requests: Vec<PendingRequestEntry>,
+    #[wasm_bindgen(js_name = "maxConcurrent")]
+    pub max_concurrent: u32,
+    #[wasm_bindgen(js_name = "maxQueued")]
+    pub max_queued: u32,
+}
+
+/// **Handles:** `WorkerRequestType::ListPendingRequests`
+/// Lists the requests currently in the pending-request registry (running and queued),
+/// oldest first, together with the configured limits. The registry only sees this worker's
+/// requests: SignerWorkerManager runs one request per worker and keeps the pool's registry
+/// itself (listPendingRequests).
+///
+/// # Arguments
+/// * `_request` - Empty request
+///
+/// # Returns
+/// * `ListPendingRequestsResult` - Request IDs, phases and ages
+pub async fn handle_list_pending_requests(
+    _request: ListPendingRequestsRequest,
+) -> Result<ListPendingRequestsResult, String> {
+    let limits = state::request_limits();
+    let requests = state::list_pending_requests()
+        .into_iter()
+        .map(|info| PendingRequestEntry {
+            request_id: info.request_id,
+            operation: info.operation,
+            phase: info.phase.as_str().to_string(),
+            age_ms: info.age_ms,
+        })
+        .collect();
+
+    Ok(ListPendingRequestsResult {
+        requests,
+        max_concurrent: limits.max_concurrent as u32,
+        max_queued: limits.max_queued as u32,
+    })
+}
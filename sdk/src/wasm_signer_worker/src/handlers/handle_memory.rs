@@ -0,0 +1,129 @@
+// ******************************************************************************
+// *                                                                            *
+// *                   HANDLER: GET MEMORY STATS / TRIM CACHES                  *
+// *                                                                            *
+// ******************************************************************************
+use crate::memory;
+use crate::state;
+use log::info;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetMemoryStatsRequest {}
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TrimCachesRequest {}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryStats {
+    /// WASM linear memory size in 64KiB pages (absent outside WASM). Never shrinks.
+    #[wasm_bindgen(js_name = "memoryPages")]
+    pub memory_pages: Option<u32>,
+    #[wasm_bindgen(js_name = "memoryBytes")]
+    pub memory_bytes: Option<f64>,
+    /// Heap bytes currently allocated
+    #[wasm_bindgen(js_name = "liveHeapBytes")]
+    pub live_heap_bytes: f64,
+    /// Highest live heap size since the worker started (or the last TrimCaches)
+    #[wasm_bindgen(js_name = "peakHeapBytes")]
+    pub peak_heap_bytes: f64,
+    /// Capacity held by idle transient arena buffers
+    #[wasm_bindgen(js_name = "transientBufferBytes")]
+    pub transient_buffer_bytes: f64,
+    #[wasm_bindgen(js_name = "pendingRequests")]
+    pub pending_requests: u32,
+    #[wasm_bindgen(js_name = "confirmationNonces")]
+    pub confirmation_nonces: u32,
+    #[wasm_bindgen(js_name = "nonceReservations")]
+    pub nonce_reservations: u32,
+    #[wasm_bindgen(js_name = "auditEntries")]
+    pub audit_entries: u32,
+    #[wasm_bindgen(js_name = "sessionKeys")]
+    pub session_keys: u32,
+}
+
+impl MemoryStats {
+    pub fn snapshot() -> Self {
+        let counts = state::state_counts();
+        Self {
+            memory_pages: memory::memory_pages().map(|p| p as u32),
+            memory_bytes: memory::memory_bytes().map(|b| b as f64),
+            live_heap_bytes: memory::live_heap_bytes() as f64,
+            peak_heap_bytes: memory::peak_heap_bytes() as f64,
+            transient_buffer_bytes: memory::transient_buffer_bytes() as f64,
+            pending_requests: counts.pending_requests as u32,
+            confirmation_nonces: counts.confirmation_nonces as u32,
+            nonce_reservations: counts.nonce_reservations as u32,
+            audit_entries: counts.audit_entries as u32,
+            session_keys: counts.session_keys as u32,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TrimCachesResult {
+    /// Heap bytes returned to the allocator (live heap before minus after)
+    #[wasm_bindgen(js_name = "freedHeapBytes")]
+    pub freed_heap_bytes: f64,
+    #[wasm_bindgen(js_name = "droppedTransientBufferBytes")]
+    pub dropped_transient_buffer_bytes: f64,
+    #[wasm_bindgen(js_name = "droppedExpiredSessionKeys")]
+    pub dropped_expired_session_keys: u32,
+    /// Stats after trimming; the peak is reset to the trimmed live size
+    #[wasm_bindgen(getter_with_clone)]
+    pub stats: MemoryStats,
+}
+
+/// **Handles:** `WorkerRequestType::GetMemoryStats`
+/// Reports WASM memory size, tracked heap usage and counts of live cached objects.
+///
+/// # Arguments
+/// * `_request` - Empty request
+///
+/// # Returns
+/// * `MemoryStats` - Current memory usage snapshot
+pub async fn handle_get_memory_stats(
+    _request: GetMemoryStatsRequest,
+) -> Result<MemoryStats, String> {
+    Ok(MemoryStats::snapshot())
+}
+
+/// **Handles:** `WorkerRequestType::TrimCaches`
+/// Drops everything droppable: all transient arena buffers, expired session keys and
+/// spare collection capacity. Live requests, reservations, session keys and the audit
+/// log are kept. WASM memory pages stay allocated, but freed heap is reused by later
+/// operations instead of growing memory further.
+///
+/// # Arguments
+/// * `_request` - Empty request
+///
+/// # Returns
+/// * `TrimCachesResult` - What was freed and the resulting stats
+pub async fn handle_trim_caches(_request: TrimCachesRequest) -> Result<TrimCachesResult, String> {
+    let live_before = memory::live_heap_bytes();
+    let dropped_transient_buffer_bytes = memory::release_transient_buffers(0);
+    let dropped_expired_session_keys = state::trim_state();
+    memory::reset_peak_heap_bytes();
+    let freed_heap_bytes = live_before.saturating_sub(memory::live_heap_bytes());
+
+    info!(
+        "RUST: Trimmed caches: {} heap bytes freed, {} transient buffer bytes dropped",
+        freed_heap_bytes, dropped_transient_buffer_bytes
+    );
+
+    Ok(TrimCachesResult {
+        freed_heap_bytes: freed_heap_bytes as f64,
+        dropped_transient_buffer_bytes: dropped_transient_buffer_bytes as f64,
+        dropped_expired_session_keys: dropped_expired_session_keys as u32,
+        stats: MemoryStats::snapshot(),
+    })
+}
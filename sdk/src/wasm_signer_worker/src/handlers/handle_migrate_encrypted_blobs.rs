@@ -0,0 +1,170 @@
+// ******************************************************************************
+// *                                                                            *
+// *                   HANDLER: MIGRATE ENCRYPTED BLOBS                         *
+// *                                                                            *
+// ******************************************************************************
+use crate::blob_migration::{blob_format, reencrypt_blob, StoredBlobKind};
+use crate::encoders::base64_url_decode;
+use crate::error::BlobDecryptError;
+use crate::vrf_blob_meta::VrfKeypairBlobMeta;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+/// A blob as stored, with what it holds
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredEncryptedBlob {
+    /// Caller-chosen identifier echoed back in the report (e.g. "alice.testnet#1")
+    #[serde(default)]
+    pub blob_id: Option<String>,
+    pub kind: StoredBlobKind,
+    /// The record (NEAR key), encryptedVrfKeypair (VRF keypair) or snapshot, as stored
+    pub blob: Value,
+    /// Metadata to bind into a VRF keypair written without it, from the records kept beside
+    /// it; required to migrate such a keypair, ignored otherwise
+    #[serde(default)]
+    pub vrf_keypair_meta: Option<VrfKeypairBlobMeta>,
+}
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrateEncryptedBlobsRequest {
+    /// PRF output of a fresh authentication (prf.results.first)
+    #[wasm_bindgen(getter_with_clone, js_name = "chacha20PrfOutput")]
+    pub chacha20_prf_output: String,
+    #[wasm_bindgen(skip)]
+    pub blobs: Vec<StoredEncryptedBlob>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BlobMigrationStatus {
+    Migrated,
+    /// Already in the current format, returned untouched
+    AlreadyCurrent,
+    /// Returned as it was; see the error
+    Failed,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BlobMigrationReport {
+    /// Position of the blob in the request
+    pub index: u32,
+    pub blob_id: Option<String>,
+    pub kind: StoredBlobKind,
+    pub status: BlobMigrationStatus,
+    /// None when the format could not be read
+    pub from_version: Option<u32>,
+    /// None when the blob failed
+    pub to_version: Option<u32>,
+    /// The blob to store: the migrated one, or the original when current or failed
+    pub blob: Value,
+    /// WrongCredentialForBlob, CorruptedCiphertext, DecryptionFailed or
+    /// OuterWrapKeyUnavailable; None for other failures
+    pub error_code: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrateEncryptedBlobsResult {
+    pub migrated: u32,
+    pub already_current: u32,
+    pub failed: u32,
+    pub reports: Vec<BlobMigrationReport>,
+}
+
+async fn migrate_blob(
+    index: usize,
+    stored: StoredEncryptedBlob,
+    chacha20_prf_output: &str,
+) -> BlobMigrationReport {
+    let current = stored.kind.current_format();
+    let from_version = blob_format(stored.kind, &stored.blob);
+    let outcome = match &from_version {
+        Ok(format) if *format == current => Ok(None),
+        Ok(format) => reencrypt_blob(
+            stored.kind,
+            *format,
+            &stored.blob,
+            chacha20_prf_output,
+            stored.vrf_keypair_meta.as_ref(),
+        )
+        .await
+        .map(Some),
+        Err(e) => Err(e.clone()),
+    };
+
+    let report = |status, blob, error: Option<BlobDecryptError>| BlobMigrationReport {
+        index: index as u32,
+        blob_id: stored.blob_id.clone(),
+        kind: stored.kind,
+        status,
+        from_version: from_version.as_ref().ok().copied(),
+        to_version: (status != BlobMigrationStatus::Failed).then_some(current),
+        blob,
+        error_code: error
+            .as_ref()
+            .and_then(|e| e.code())
+            .map(|code| code.as_str().to_string()),
+        error: error.map(|e| e.to_string()),
+    };
+    match outcome {
+        Ok(Some(migrated)) => report(BlobMigrationStatus::Migrated, migrated, None),
+        Ok(None) => report(
+            BlobMigrationStatus::AlreadyCurrent,
+            stored.blob.clone(),
+            None,
+        ),
+        Err(e) => report(BlobMigrationStatus::Failed, stored.blob.clone(), Some(e)),
+    }
+}
+
+/// **Handles:** `WorkerRequestType::MigrateEncryptedBlobs`
+/// Re-encrypts stored blobs written in earlier formats with the current defaults, so a device
+/// converges on one format whatever release wrote its records (see blob_migration.rs). Each
+/// blob succeeds or fails on its own, and a failed one comes back as it was: the storage layer
+/// writes every report's `blob` and can retry the failures later.
+///
+/// # Arguments
+/// * `request` - A fresh PRF output and the stored blobs
+///
+/// # Returns
+/// * `MigrateEncryptedBlobsResult` - Per-blob report with the blob to store
+pub async fn handle_migrate_encrypted_blobs(
+    request: MigrateEncryptedBlobsRequest,
+) -> Result<MigrateEncryptedBlobsResult, String> {
+    match base64_url_decode(&request.chacha20_prf_output) {
+        Ok(prf_output) if !prf_output.is_empty() => {}
+        _ => return Err("Missing or invalid PRF output".to_string()),
+    }
+
+    let mut reports = Vec::with_capacity(request.blobs.len());
+    for (index, stored) in request.blobs.into_iter().enumerate() {
+        reports.push(migrate_blob(index, stored, &request.chacha20_prf_output).await);
+    }
+    let count = |status| reports.iter().filter(|r| r.status == status).count() as u32;
+    let result = MigrateEncryptedBlobsResult {
+        migrated: count(BlobMigrationStatus::Migrated),
+        already_current: count(BlobMigrationStatus::AlreadyCurrent),
+        failed: count(BlobMigrationStatus::Failed),
+        reports,
+    };
+
+    if result.failed > 0 {
+        warn!(
+            "RUST: Migrated {} encrypted blobs, {} already current, {} failed",
+            result.migrated, result.already_current, result.failed
+        );
+    } else {
+        info!(
+            "RUST: Migrated {} encrypted blobs, {} already current",
+            result.migrated, result.already_current
+        );
+    }
+    Ok(result)
+}
@@ -4,6 +4,7 @@
 // *                                                                            *
 // ******************************************************************************
 
+use crate::sub_keys::{derive_sub_key_entries, SubKeyEntry};
 use crate::types::SerializedCredential;
 use log::info;
 use serde::{Deserialize, Serialize};
@@ -17,6 +18,10 @@ pub struct RecoverKeypairRequest {
     pub credential: SerializedCredential,
     #[wasm_bindgen(getter_with_clone, js_name = "accountIdHint")]
     pub account_id_hint: Option<String>,
+    /// SLIP-0010 paths of the account's sub-keys to derive again (see sub_keys.rs)
+    #[wasm_bindgen(getter_with_clone, js_name = "subKeyPaths")]
+    #[serde(default)]
+    pub sub_key_paths: Option<Vec<String>>,
 }
 
 #[wasm_bindgen]
@@ -31,6 +36,12 @@ pub struct RecoverKeypairResult {
     pub iv: String,
     #[wasm_bindgen(getter_with_clone, js_name = "accountIdHint")]
     pub account_id_hint: Option<String>,
+    /// "aes-gcm-webcrypto" when `encryptedData` carries the outer WebCrypto wrap
+    #[wasm_bindgen(getter_with_clone, js_name = "outerWrap")]
+    pub outer_wrap: Option<String>,
+    /// The `subKeyPaths` derived, in canonical form, with their public keys
+    #[wasm_bindgen(getter_with_clone, js_name = "subKeys")]
+    pub sub_keys: Vec<SubKeyEntry>,
 }
 
 #[wasm_bindgen]
@@ -41,12 +52,15 @@ impl RecoverKeypairResult {
         encrypted_data: String,
         iv: String,
         account_id_hint: Option<String>,
+        outer_wrap: Option<String>,
     ) -> RecoverKeypairResult {
         RecoverKeypairResult {
             public_key,
             encrypted_data,
             iv,
             account_id_hint,
+            outer_wrap,
+            sub_keys: Vec::new(),
         }
     }
 }
@@ -56,7 +70,7 @@ impl RecoverKeypairResult {
 ///
 /// This handler is used when a user wants to recover access to their account using an existing passkey.
 /// It extracts PRF outputs from the authentication response and regenerates the same keypair that was
-/// originally created during registration.
+/// originally created during registration, along with the sub-keys at `subKeyPaths`.
 ///
 /// # Arguments
 /// * `request` - Contains authentication credential with PRF outputs and optional account ID hint
@@ -99,18 +113,33 @@ pub async fn handle_recover_keypair_from_passkey(
         crate::crypto::derive_ed25519_key_from_prf_output(&ed25519_prf_output, account_id)
             .map_err(|e| format!("Failed to derive Ed25519 key from PRF: {}", e))?;
 
+    let sub_keys = derive_sub_key_entries(
+        &ed25519_prf_output,
+        account_id,
+        request.sub_key_paths.as_deref().unwrap_or_default(),
+    )?;
+
     // Encrypt the private key with the AES PRF output (correct usage)
     let encryption_result =
         crate::crypto::encrypt_private_key_with_prf(&private_key, &chacha20_prf_output, account_id)
             .map_err(|e| format!("Failed to encrypt private key with AES PRF: {}", e))?;
 
+    let (encrypted_data, outer_wrap) =
+        crate::outer_wrap::wrap_encrypted_data(&encryption_result.encrypted_near_key_data_b64u)
+            .await
+            .map_err(|e| format!("Failed to outer-wrap encrypted key: {}", e))?;
+
     info!("RUST: Successfully derived NEAR keypair from Ed25519 PRF and encrypted with AES PRF");
     info!("RUST: PRF-based keypair recovery from authentication credential successful");
 
-    Ok(RecoverKeypairResult::new(
-        public_key,
-        encryption_result.encrypted_near_key_data_b64u,
-        encryption_result.chacha20_nonce_b64u, // IV
-        Some(account_id.to_string()),
-    ))
+    Ok(RecoverKeypairResult {
+        sub_keys,
+        ..RecoverKeypairResult::new(
+            public_key,
+            encrypted_data,
+            encryption_result.chacha20_nonce_b64u, // IV
+            Some(account_id.to_string()),
+            outer_wrap,
+        )
+    })
 }
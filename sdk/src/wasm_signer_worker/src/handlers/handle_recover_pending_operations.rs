@@ -0,0 +1,60 @@
+// ******************************************************************************
+// *                                                                            *
+// *                  HANDLER: RECOVER PENDING OPERATIONS                       *
+// *                                                                            *
+// ******************************************************************************
+use crate::config::MAX_RECOVERABLE_OPERATIONS;
+use crate::operation_journal::{
+    recover_pending_operations, JournalEntry, RecoveredOperation, RecoveryStatus,
+};
+use crate::rpc_client::NearRpcClient;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoverPendingOperationsRequest {
+    #[wasm_bindgen(getter_with_clone, js_name = "nearRpcUrl")]
+    pub near_rpc_url: String,
+    /// Journal entries persisted before the worker went away (see operation_journal.rs)
+    #[wasm_bindgen(skip)]
+    pub entries: Vec<JournalEntry>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoverPendingOperationsResult {
+    /// One per entry, in request order
+    pub operations: Vec<RecoveredOperation>,
+    /// Entries whose status is Unknown; recover them again later
+    pub unresolved: u32,
+}
+
+/// **Handles:** `WorkerRequestType::RecoverPendingOperations`
+/// Reports what became of each journaled broadcast or relayer submission: confirmed, still
+/// pending, not found, or unknown when the RPC could not answer. Makes view calls only, so the
+/// TS layer can call it again until nothing is unresolved.
+pub async fn handle_recover_pending_operations(
+    request: RecoverPendingOperationsRequest,
+) -> Result<RecoverPendingOperationsResult, String> {
+    if request.near_rpc_url.is_empty() {
+        return Err("Missing required field: nearRpcUrl".to_string());
+    }
+    if request.entries.len() > MAX_RECOVERABLE_OPERATIONS {
+        return Err(format!(
+            "At most {} journal entries can be recovered per request, got {}",
+            MAX_RECOVERABLE_OPERATIONS,
+            request.entries.len()
+        ));
+    }
+    let rpc = NearRpcClient::new(&request.near_rpc_url);
+    let operations = recover_pending_operations(&rpc, request.entries).await;
+    Ok(RecoverPendingOperationsResult {
+        unresolved: operations
+            .iter()
+            .filter(|operation| operation.status == RecoveryStatus::Unknown)
+            .count() as u32,
+        operations,
+    })
+}
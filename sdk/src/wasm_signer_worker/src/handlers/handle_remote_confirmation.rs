@@ -0,0 +1,568 @@
+// ******************************************************************************
+// *                                                                            *
+// *          HANDLERS: CREATE / APPROVE / COMPLETE REMOTE CONFIRMATION         *
+// *                                                                            *
+// ******************************************************************************
+use crate::action_firewall::check_high_risk_actions;
+use crate::actions::ActionParams;
+use crate::args_schema::check_batch_args;
+use crate::config::{
+    DEFAULT_REMOTE_CONFIRMATION_TTL_MS, MAX_REMOTE_CONFIRMATION_TTL_MS, REMOTE_CONFIRMATION_VERSION,
+};
+use crate::confirmation_blocks::SummaryPolicy;
+use crate::encoders::base64_url_encode;
+use crate::error::RemoteConfirmationError;
+use crate::handlers::confirm_tx_details::{
+    compute_confirmation_intent_digest, confirmation_tx_signing_requests_json, generate_request_id,
+    handle_collection_error, request_remote_user_confirmation, ConfirmationResult,
+};
+use crate::handlers::handle_sign_transactions_with_actions::{
+    annotate_first_time_receivers, sign_transactions_with_actions_confirmed_by, PresetConfirmation,
+    SignTransactionsWithActionsRequest, TransactionPayload, TransactionSignResult,
+};
+use crate::remote_confirmation::{
+    generate_session_key, issue_remote_request, open_approval, open_remote_request,
+    parse_sealed_approval, seal_approval, RemoteApproval, RemoteConfirmationRequest, RemoteSession,
+};
+use crate::state::{self, PendingRequestGuard};
+use crate::types::handlers::{
+    ConfirmationConfig, ConfirmationUIMode, RpcCallPayload, RpcOverrides, TransactionContext,
+    WorkerPolicy,
+};
+use crate::types::{AccountId, DecryptionPayload, VrfChallenge};
+use log::info;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateRemoteConfirmationRequest {
+    #[wasm_bindgen(getter_with_clone, js_name = "rpcCall")]
+    pub rpc_call: RpcCallPayload,
+    /// Transactions to have another device confirm (one signer account)
+    #[wasm_bindgen(getter_with_clone, js_name = "txSigningRequests")]
+    pub tx_signing_requests: Vec<TransactionPayload>,
+    /// Challenge for the approving credential to sign, from this device's VRF worker
+    #[wasm_bindgen(getter_with_clone, js_name = "vrfChallenge")]
+    pub vrf_challenge: VrfChallenge,
+    /// Summary block thresholds, as for SignTransactionsWithActions
+    #[wasm_bindgen(getter_with_clone, js_name = "workerPolicy")]
+    #[serde(default)]
+    pub worker_policy: Option<WorkerPolicy>,
+    /// Request lifetime (defaults to DEFAULT_REMOTE_CONFIRMATION_TTL_MS)
+    #[wasm_bindgen(js_name = "ttlMs")]
+    #[serde(default)]
+    pub ttl_ms: Option<u32>,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateRemoteConfirmationResult {
+    /// Opaque base64url request for the approving device (QR code or relayer)
+    #[wasm_bindgen(getter_with_clone, js_name = "confirmationRequest")]
+    pub confirmation_request: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "requestId")]
+    pub request_id: String,
+    /// base64url ed25519 session key the approval is sealed to
+    #[wasm_bindgen(getter_with_clone, js_name = "sessionPublicKey")]
+    pub session_public_key: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "intentDigest")]
+    pub intent_digest: String,
+    #[wasm_bindgen(js_name = "expiresAtMs")]
+    pub expires_at_ms: f64,
+}
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ApproveRemoteConfirmationRequest {
+    /// From CreateRemoteConfirmation on the originating device
+    #[wasm_bindgen(getter_with_clone, js_name = "confirmationRequest")]
+    pub confirmation_request: String,
+    /// This device's RPC settings; nearAccountId must be the request's account
+    #[wasm_bindgen(getter_with_clone, js_name = "rpcCall")]
+    pub rpc_call: RpcCallPayload,
+    /// uiMode "skip" is refused: the point is to show the transactions here
+    #[wasm_bindgen(getter_with_clone, js_name = "confirmationConfig")]
+    #[serde(default)]
+    pub confirmation_config: Option<ConfirmationConfig>,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ApproveRemoteConfirmationResult {
+    /// Opaque base64url approval for CompleteRemoteConfirmation, readable only by a worker
+    /// holding the originating device's session
+    #[wasm_bindgen(getter_with_clone)]
+    pub approval: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "requestId")]
+    pub request_id: String,
+}
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CompleteRemoteConfirmationRequest {
+    /// From ApproveRemoteConfirmation on the approving device
+    #[wasm_bindgen(getter_with_clone)]
+    pub approval: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "rpcCall")]
+    pub rpc_call: RpcCallPayload,
+    #[wasm_bindgen(getter_with_clone)]
+    pub decryption: DecryptionPayload,
+    /// The transactions given to CreateRemoteConfirmation, unchanged
+    #[wasm_bindgen(getter_with_clone, js_name = "txSigningRequests")]
+    pub tx_signing_requests: Vec<TransactionPayload>,
+    /// Nonce and block hash to sign with (fetched by the main thread, as in local confirmations)
+    #[wasm_bindgen(getter_with_clone, js_name = "transactionContext")]
+    pub transaction_context: TransactionContext,
+    #[wasm_bindgen(getter_with_clone, js_name = "workerPolicy")]
+    #[serde(default)]
+    pub worker_policy: Option<WorkerPolicy>,
+    #[wasm_bindgen(getter_with_clone, js_name = "rpcOverrides")]
+    #[serde(default)]
+    pub rpc_overrides: Option<RpcOverrides>,
+}
+
+/// The signer account, confirmation txSigningRequests and digest of a batch, as the signing
+/// flow computes them (summary blocks from the account's policy, no deadline or deploy
+/// manifest)
+fn remote_confirmation_content(
+    tx_signing_requests: &[TransactionPayload],
+    first_time_receivers: &[Option<bool>],
+    worker_policy: Option<&WorkerPolicy>,
+) -> Result<(String, Vec<serde_json::Value>, String), String> {
+    let near_account_id = match tx_signing_requests.first() {
+        Some(tx) => tx.near_account_id.clone(),
+        None => return Err("No transactions provided".to_string()),
+    };
+    if tx_signing_requests
+        .iter()
+        .any(|tx| tx.near_account_id != near_account_id)
+    {
+        return Err(
+            "All transactions of a remote confirmation must share one nearAccountId".to_string(),
+        );
+    }
+    let receivers_and_actions = tx_signing_requests
+        .iter()
+        .map(|tx| {
+            let actions: Vec<ActionParams> = tx
+                .parsed_actions()
+                .map_err(|e| format!("Failed to parse actions: {}", e))?;
+            Ok((tx.receiver_id.clone(), actions))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let account = AccountId::new(near_account_id.clone())?;
+    let account_policy = worker_policy
+        .map(|policy| policy.for_account(&account))
+        .unwrap_or_default();
+    check_batch_args(Some(&account_policy), &receivers_and_actions).map_err(|e| e.to_string())?;
+    check_high_risk_actions(Some(&account_policy), &near_account_id, &receivers_and_actions)
+        .map_err(|e| e.to_string())?;
+    let summary_policy = SummaryPolicy::from_worker_policy(Some(&account_policy))?;
+    let txs_json = confirmation_tx_signing_requests_json(
+        &receivers_and_actions,
+        first_time_receivers,
+        None,
+        Some(&summary_policy),
+    );
+    let digest = compute_confirmation_intent_digest(
+        &receivers_and_actions,
+        first_time_receivers,
+        None,
+        Some(&summary_policy),
+    )?;
+    Ok((near_account_id, txs_json, digest))
+}
+
+/// **Handles:** `WorkerRequestType::CreateRemoteConfirmation`
+/// Starts a confirmation on another device: computes what the signing flow would show for the
+/// batch and returns it as a request signed with a new session key (see remote_confirmation.rs).
+/// The session stays in worker memory until the request expires. Nothing is decrypted or signed.
+///
+/// # Arguments
+/// * `request` - The transactions, the VRF challenge to approve with and the request lifetime
+///
+/// # Returns
+/// * `CreateRemoteConfirmationResult` - The request to carry over, its ID, session key and expiry
+pub async fn handle_create_remote_confirmation(
+    request: CreateRemoteConfirmationRequest,
+) -> Result<CreateRemoteConfirmationResult, String> {
+    let ttl_ms = match request.ttl_ms {
+        None => DEFAULT_REMOTE_CONFIRMATION_TTL_MS,
+        Some(ttl_ms) if ttl_ms == 0 || ttl_ms > MAX_REMOTE_CONFIRMATION_TTL_MS => {
+            return Err(format!(
+                "ttlMs must be between 1 and {}",
+                MAX_REMOTE_CONFIRMATION_TTL_MS
+            ));
+        }
+        Some(ttl_ms) => ttl_ms,
+    };
+
+    // Receiver annotations read neither the decryption payload nor the confirmation config
+    let annotate_request = SignTransactionsWithActionsRequest {
+        rpc_call: request.rpc_call,
+        decryption: DecryptionPayload::new(String::new(), String::new()),
+        tx_signing_requests: request.tx_signing_requests,
+        confirmation_config: None,
+        worker_policy: request.worker_policy,
+        idempotency_key: None,
+        rpc_overrides: None,
+        broadcast: false,
+        valid_until_block_height: None,
+        transaction_context: None,
+        signing_public_key: None,
+        deduplicate: false,
+        preview_digest: None,
+        redact_paths: Vec::new(),
+        conditional_batch: None,
+        approval_token: None,
+        auto_storage_deposit: false,
+        batch_preparation: None,
+        deploy_manifest: None,
+        auto_inserted_actions: Vec::new(),
+    };
+    let first_time_receivers = annotate_first_time_receivers(&annotate_request).await;
+    let (near_account_id, tx_signing_requests, intent_digest) = remote_confirmation_content(
+        &annotate_request.tx_signing_requests,
+        &first_time_receivers,
+        annotate_request.worker_policy.as_ref(),
+    )?;
+    if request.vrf_challenge.user_id != near_account_id {
+        return Err(format!(
+            "vrfChallenge was issued for {}, not {}",
+            request.vrf_challenge.user_id, near_account_id
+        ));
+    }
+
+    let session_key = generate_session_key()?;
+    let session_public_key = session_key.verifying_key().to_bytes().to_vec();
+    let request_id = generate_request_id();
+    let now = state::now_ms();
+    let remote_request = RemoteConfirmationRequest {
+        version: REMOTE_CONFIRMATION_VERSION,
+        request_id: request_id.clone(),
+        near_account_id: near_account_id.clone(),
+        intent_digest: intent_digest.clone(),
+        tx_signing_requests,
+        vrf_challenge: request.vrf_challenge.clone(),
+        issued_at_ms: now,
+        expires_at_ms: now + ttl_ms as f64,
+        session_public_key: session_public_key.clone(),
+    };
+    let confirmation_request = issue_remote_request(&remote_request, &session_key)?;
+    state::store_remote_session(
+        &request_id,
+        RemoteSession {
+            session_key,
+            near_account_id: near_account_id.clone(),
+            intent_digest: intent_digest.clone(),
+            first_time_receivers,
+            vrf_challenge: request.vrf_challenge,
+            expires_at_ms: remote_request.expires_at_ms,
+        },
+    );
+    state::record_audit(
+        &AccountId::new(near_account_id.clone())?,
+        &request_id,
+        "createRemoteConfirmation",
+        "Created",
+        None,
+    );
+
+    info!(
+        "RUST: Created remote confirmation {} for {} ({} transactions, expires in {}ms)",
+        request_id,
+        near_account_id,
+        annotate_request.tx_signing_requests.len(),
+        ttl_ms
+    );
+    Ok(CreateRemoteConfirmationResult {
+        confirmation_request,
+        request_id,
+        session_public_key: base64_url_encode(&session_public_key),
+        intent_digest,
+        expires_at_ms: remote_request.expires_at_ms,
+    })
+}
+
+/// Checks a confirmed result of the approving device's UI and turns it into the approval:
+/// the credential must come back over the request's VRF challenge, with its PRF output, before
+/// the request expires
+pub fn remote_approval_from_confirmation(
+    remote_request: &RemoteConfirmationRequest,
+    c: &ConfirmationResult,
+    now_ms: f64,
+) -> Result<RemoteApproval, RemoteConfirmationError> {
+    if remote_request.expires_at_ms <= now_ms {
+        return Err(RemoteConfirmationError::Expired {
+            expires_at_ms: remote_request.expires_at_ms,
+        });
+    }
+    let same_challenge = c.vrf_challenge.as_ref().is_some_and(|decided| {
+        decided.vrf_output == remote_request.vrf_challenge.vrf_output
+            && decided.vrf_proof == remote_request.vrf_challenge.vrf_proof
+            && decided.vrf_public_key == remote_request.vrf_challenge.vrf_public_key
+    });
+    if !same_challenge {
+        return Err(RemoteConfirmationError::Invalid(
+            "confirmation returned a different VRF challenge than the request's".to_string(),
+        ));
+    }
+    match (&c.credential, &c.prf_output) {
+        (Some(credential), Some(prf_output)) => Ok(RemoteApproval {
+            version: REMOTE_CONFIRMATION_VERSION,
+            request_id: remote_request.request_id.clone(),
+            intent_digest: remote_request.intent_digest.clone(),
+            credential: credential.clone(),
+            prf_output: prf_output.clone(),
+        }),
+        _ => Err(RemoteConfirmationError::Invalid(
+            "confirmation returned no credential or PRF output".to_string(),
+        )),
+    }
+}
+
+/// **Handles:** `WorkerRequestType::ApproveRemoteConfirmation`
+/// Runs on the approving device: verifies the request, shows its transactions in the normal
+/// confirmation UI, collects the passkey assertion over its VRF challenge with the PRF output,
+/// and seals both to the request's session key. Each request is approved once on a device; a
+/// declined or failed approval can be retried.
+///
+/// # Arguments
+/// * `request` - The originating device's request and this device's RPC and UI settings
+///
+/// # Returns
+/// * `ApproveRemoteConfirmationResult` - The sealed approval to carry back
+pub async fn handle_approve_remote_confirmation(
+    request: ApproveRemoteConfirmationRequest,
+) -> Result<ApproveRemoteConfirmationResult, String> {
+    if request
+        .confirmation_config
+        .as_ref()
+        .is_some_and(|config| config.ui_mode == ConfirmationUIMode::Skip)
+    {
+        return Err("ApproveRemoteConfirmation always shows the confirmation UI".to_string());
+    }
+    let remote_request = open_remote_request(&request.confirmation_request, state::now_ms())
+        .map_err(|e| e.to_string())?;
+    if remote_request.near_account_id != request.rpc_call.near_account_id {
+        return Err(RemoteConfirmationError::WrongSession(format!(
+            "request is for {}, this device confirms for {}",
+            remote_request.near_account_id, request.rpc_call.near_account_id
+        ))
+        .to_string());
+    }
+    if !state::note_remote_approval(&remote_request.request_id, remote_request.expires_at_ms) {
+        return Err(RemoteConfirmationError::Replayed {
+            request_id: remote_request.request_id,
+        }
+        .to_string());
+    }
+
+    let approval = approve_remote_request(&request, &remote_request).await;
+    if approval.is_err() {
+        state::release_remote_approval(&remote_request.request_id);
+    }
+    let approval = approval?;
+    info!(
+        "RUST: Approved remote confirmation {} for {}",
+        remote_request.request_id, remote_request.near_account_id
+    );
+    Ok(ApproveRemoteConfirmationResult {
+        approval,
+        request_id: remote_request.request_id,
+    })
+}
+
+async fn approve_remote_request(
+    request: &ApproveRemoteConfirmationRequest,
+    remote_request: &RemoteConfirmationRequest,
+) -> Result<String, String> {
+    let mut logs: Vec<String> = Vec::new();
+    let c = request_remote_user_confirmation(
+        remote_request,
+        &request.rpc_call,
+        request.confirmation_config.as_ref(),
+        &mut logs,
+    )
+    .await
+    .map_err(|e| format!("Confirmation request failed: {}", e))?;
+
+    let account = AccountId::new(remote_request.near_account_id.clone())?;
+    let request_id = c.request_id.clone();
+    let _request_guard = PendingRequestGuard::new(&account, &request_id);
+    if let Some(collection_error) = &c.collection_error {
+        let error_code = handle_collection_error(
+            &account,
+            &request_id,
+            "approveRemoteConfirmation",
+            collection_error,
+            &mut logs,
+        );
+        return Err(format!(
+            "{}: Credential collection failed: {}",
+            error_code, collection_error.message
+        ));
+    }
+    if !c.confirmed {
+        let outcome = c.error_code.as_deref().unwrap_or("Rejected");
+        state::record_audit(
+            &account,
+            &request_id,
+            "approveRemoteConfirmation",
+            outcome,
+            c.error.clone(),
+        );
+        return Err(match (&c.error_code, &c.error) {
+            (Some(code), Some(error)) => format!("{}: {}", code, error),
+            (Some(code), None) => format!("{}: confirmation refused", code),
+            (None, _) => "Remote confirmation rejected by user".to_string(),
+        });
+    }
+
+    let sealed = remote_approval_from_confirmation(remote_request, &c, state::now_ms())
+        .and_then(|approval| seal_approval(&approval, &remote_request.session_public_key));
+    let outcome = match &sealed {
+        Ok(_) => "Approved",
+        Err(e) => e.code().as_str(),
+    };
+    state::record_audit(
+        &account,
+        &request_id,
+        "approveRemoteConfirmation",
+        outcome,
+        Some(remote_request.request_id.clone()),
+    );
+    sealed.map_err(|e| e.to_string())
+}
+
+/// Opens the approval with the session it answers and checks the supplied transactions are
+/// the session's, then marks the approval used; the returned error carries the code for an
+/// invalid, expired, replayed or wrong-session approval
+pub fn accept_remote_approval(
+    request: &CompleteRemoteConfirmationRequest,
+    now_ms: f64,
+) -> Result<(String, RemoteSession, RemoteApproval), RemoteConfirmationError> {
+    let sealed = parse_sealed_approval(&request.approval)?;
+    let session = state::remote_session(&sealed.request_id).ok_or_else(|| {
+        RemoteConfirmationError::WrongSession(format!(
+            "no remote confirmation session for request {}",
+            sealed.request_id
+        ))
+    })?;
+    let approval = open_approval(&sealed, &session, now_ms)?;
+
+    let (near_account_id, _, intent_digest) = remote_confirmation_content(
+        &request.tx_signing_requests,
+        &session.first_time_receivers,
+        request.worker_policy.as_ref(),
+    )
+    .map_err(RemoteConfirmationError::WrongSession)?;
+    if near_account_id != session.near_account_id || intent_digest != session.intent_digest {
+        return Err(RemoteConfirmationError::WrongSession(
+            "transactions differ from the remotely confirmed ones".to_string(),
+        ));
+    }
+    if !state::consume_remote_session(&sealed.request_id, session.expires_at_ms) {
+        return Err(RemoteConfirmationError::Replayed {
+            request_id: sealed.request_id,
+        });
+    }
+    Ok((sealed.request_id, session, approval))
+}
+
+/// **Handles:** `WorkerRequestType::CompleteRemoteConfirmation`
+/// Signs a batch approved on another device. The approval is opened with the session's key and
+/// checked against the supplied transactions first; then the batch goes through the
+/// SignTransactionsWithActions flow with the approval in place of the local confirmation
+/// (contract verification, PRF decryption, signing). An approval is used once: it is released
+/// again only when nothing was signed, so a failed attempt can be retried until expiry.
+///
+/// # Arguments
+/// * `request` - The sealed approval, the transactions and the signing inputs
+///
+/// # Returns
+/// * `TransactionSignResult` - Signed transactions, or a coded failure
+pub async fn handle_complete_remote_confirmation(
+    request: CompleteRemoteConfirmationRequest,
+) -> Result<TransactionSignResult, String> {
+    let (request_id, session, approval) = match accept_remote_approval(&request, state::now_ms()) {
+        Ok(accepted) => accepted,
+        Err(e) => {
+            info!("RUST: Refused remote approval: {}", e);
+            return Ok(TransactionSignResult::failed_with_code(
+                vec![e.to_string()],
+                e.to_string(),
+                e.code(),
+            ));
+        }
+    };
+    info!(
+        "RUST: Completing remote confirmation {} for {}",
+        request_id, session.near_account_id
+    );
+
+    let preset = PresetConfirmation {
+        confirmation: ConfirmationResult {
+            confirmed: true,
+            request_id: request_id.clone(),
+            intent_digest: Some(approval.intent_digest),
+            credential: Some(approval.credential),
+            prf_output: Some(approval.prf_output),
+            prf_supported: Some(true),
+            vrf_challenge: Some(session.vrf_challenge),
+            transaction_context: Some(request.transaction_context),
+            reserved_nonces: None,
+            collection_error: None,
+            error: None,
+            error_code: None,
+            countdown: None,
+            render_attestation: None,
+            sealed_secrets: None,
+            // Collected on the approving device, at a time this worker cannot vouch for
+            credential_collected_at_ms: None,
+            confirmation_expires_at_ms: Some(session.expires_at_ms),
+        },
+        first_time_receivers: session.first_time_receivers,
+    };
+    let result = sign_transactions_with_actions_confirmed_by(
+        SignTransactionsWithActionsRequest {
+            rpc_call: request.rpc_call,
+            decryption: request.decryption,
+            tx_signing_requests: request.tx_signing_requests,
+            confirmation_config: None,
+            worker_policy: request.worker_policy,
+            idempotency_key: None,
+            rpc_overrides: request.rpc_overrides,
+            broadcast: false,
+            valid_until_block_height: None,
+            transaction_context: None,
+            signing_public_key: None,
+            deduplicate: false,
+            preview_digest: None,
+            redact_paths: Vec::new(),
+            conditional_batch: None,
+            approval_token: None,
+            auto_storage_deposit: false,
+            batch_preparation: None,
+            deploy_manifest: None,
+            auto_inserted_actions: Vec::new(),
+        },
+        Some(preset),
+    )
+    .await;
+
+    let signed_any = matches!(
+        &result,
+        Ok(r) if r.signed_transactions.as_ref().is_some_and(|txs| !txs.is_empty())
+    );
+    if !signed_any {
+        state::release_remote_session(&request_id);
+    }
+    result
+}
@@ -1,9 +1,5 @@
-use crate::types::wasm_to_json::ToJson;
 use serde::{Deserialize, Serialize};
-use serde_json;
-use serde_wasm_bindgen;
 use wasm_bindgen::prelude::*;
-use wasm_bindgen::JsValue;
 
 use super::confirm_tx_details::request_registration_credential_confirmation;
 use crate::types::handlers::{TransactionContext, ConfirmationConfig};
@@ -28,7 +24,8 @@ pub struct RegistrationCredentialConfirmationRequest {
 }
 
 #[wasm_bindgen]
-#[derive(Debug, Clone)]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct RegistrationCredentialConfirmationResult {
     #[wasm_bindgen(getter_with_clone, js_name = "confirmed")]
     pub confirmed: bool,
@@ -36,10 +33,14 @@ pub struct RegistrationCredentialConfirmationResult {
     pub request_id: String,
     #[wasm_bindgen(getter_with_clone, js_name = "intentDigest")]
     pub intent_digest: String,
-    #[wasm_bindgen(getter_with_clone, js_name = "credential")]
-    pub credential: JsValue,
+    /// Serialized WebAuthn registration credential
+    #[wasm_bindgen(skip)]
+    pub credential: Option<serde_json::Value>,
     #[wasm_bindgen(getter_with_clone, js_name = "prfOutput")]
     pub prf_output: Option<String>,
+    /// false when the authenticator returned no PRF results (see key_wrapping.rs)
+    #[wasm_bindgen(getter_with_clone, js_name = "prfSupported")]
+    pub prf_supported: Option<bool>,
     #[wasm_bindgen(getter_with_clone, js_name = "vrfChallenge")]
     pub vrf_challenge: Option<VrfChallenge>,
     #[wasm_bindgen(getter_with_clone, js_name = "transactionContext")]
@@ -54,12 +55,9 @@ impl RegistrationCredentialConfirmationResult {
             confirmed: c.confirmed,
             request_id: c.request_id,
             intent_digest: c.intent_digest.unwrap_or_default(),
-            credential: c
-                .credential
-                .as_ref()
-                .and_then(|v| serde_wasm_bindgen::to_value(v).ok())
-                .unwrap_or(JsValue::UNDEFINED),
+            credential: c.credential,
             prf_output: c.prf_output,
+            prf_supported: c.prf_supported,
             vrf_challenge: c.vrf_challenge,
             transaction_context: c.transaction_context,
             error: c.error,
@@ -67,31 +65,6 @@ impl RegistrationCredentialConfirmationResult {
     }
 }
 
-impl ToJson for RegistrationCredentialConfirmationResult {
-    fn to_json(&self) -> Result<serde_json::Value, String> {
-        let credential_json: serde_json::Value =
-            if self.credential.is_undefined() || self.credential.is_null() {
-                serde_json::Value::Null
-            } else {
-                serde_wasm_bindgen::from_value(self.credential.clone())
-                    .unwrap_or(serde_json::Value::Null)
-            };
-
-        let json = serde_json::json!({
-            "confirmed": self.confirmed,
-            "requestId": self.request_id,
-            "intentDigest": self.intent_digest,
-            "credential": credential_json,
-            "prfOutput": self.prf_output,
-            "vrfChallenge": self.vrf_challenge,
-            "transactionContext": self.transaction_context,
-            "error": self.error,
-        });
-
-        Ok(json)
-    }
-}
-
 /// Handles Link Device user confirmation by delegating to the JS main thread
 /// secure confirmation flow. Presents a modal in the wallet iframe, collects
 /// WebAuthn registration credential and PRF output, then returns artifacts.
@@ -0,0 +1,194 @@
+// ******************************************************************************
+// *                                                                            *
+// *            HANDLERS: PREPARE / COMPLETE RESUMABLE REGISTRATION             *
+// *                                                                            *
+// ******************************************************************************
+use crate::handlers::handle_derive_near_keypair_and_encrypt::{
+    handle_derive_near_keypair_and_encrypt, DeriveNearKeypairAndEncryptRequest,
+    DeriveNearKeypairAndEncryptResult, DualPrfOutputsStruct,
+};
+use crate::key_wrapping::KeyWrappingScheme;
+use crate::registration_resume::{issue_resume_token, resume_mac_key, RegistrationResumeState};
+use crate::state;
+use crate::types::SerializedRegistrationCredential;
+// CompleteRegistration submits through the relayer
+#[cfg(feature = "relayer")]
+use crate::{
+    crypto::derive_ed25519_key_from_prf_output,
+    error::RegistrationResumeError,
+    handlers::handle_submit_to_relayer::{handle_submit_to_relayer, SubmitToRelayerRequest},
+    registration_resume::verify_resume_token,
+    relayer::RelayerResult,
+    types::handlers::WorkerPolicy,
+    types::{AuthenticatorOptions, VrfChallenge},
+};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PrepareRegistrationRequest {
+    /// Required: the resume token is MAC'd under a key derived from chacha20PrfOutput
+    #[wasm_bindgen(getter_with_clone, js_name = "dualPrfOutputs")]
+    pub dual_prf_outputs: DualPrfOutputsStruct,
+    #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
+    pub near_account_id: String,
+    #[wasm_bindgen(getter_with_clone)]
+    pub credential: SerializedRegistrationCredential,
+    /// Sub-keys to derive alongside the account key, as in DeriveNearKeypairAndEncrypt
+    #[wasm_bindgen(getter_with_clone, js_name = "subKeyPaths")]
+    #[serde(default)]
+    pub sub_key_paths: Option<Vec<String>>,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PrepareRegistrationResult {
+    /// The encrypted key, to store before CompleteRegistration
+    #[wasm_bindgen(getter_with_clone)]
+    pub keys: DeriveNearKeypairAndEncryptResult,
+    #[wasm_bindgen(getter_with_clone, js_name = "resumeToken")]
+    pub resume_token: String,
+    #[wasm_bindgen(js_name = "resumeExpiresAtMs")]
+    pub resume_expires_at_ms: f64,
+}
+
+#[cfg(feature = "relayer")]
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CompleteRegistrationRequest {
+    /// From PrepareRegistration; the same token may be submitted again until it expires
+    #[wasm_bindgen(getter_with_clone, js_name = "resumeToken")]
+    pub resume_token: String,
+    /// The registration credential's PRF outputs: verify the token and re-derive its key
+    #[wasm_bindgen(getter_with_clone, js_name = "dualPrfOutputs")]
+    pub dual_prf_outputs: DualPrfOutputsStruct,
+    #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
+    pub near_account_id: String,
+    #[wasm_bindgen(js_name = "deviceNumber")]
+    #[serde(default)]
+    pub device_number: Option<u8>,
+    #[wasm_bindgen(getter_with_clone, js_name = "vrfChallenge")]
+    pub vrf_challenge: VrfChallenge,
+    #[wasm_bindgen(getter_with_clone)]
+    pub credential: SerializedRegistrationCredential,
+    /// base64url
+    #[wasm_bindgen(getter_with_clone, js_name = "deterministicVrfPublicKey")]
+    pub deterministic_vrf_public_key: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "authenticatorOptions")]
+    #[serde(default)]
+    pub authenticator_options: Option<AuthenticatorOptions>,
+    /// Base64 borsh SignedDelegateAction, forwarded as is
+    #[wasm_bindgen(getter_with_clone, js_name = "signedDelegateAction")]
+    #[serde(default)]
+    pub signed_delegate_action: Option<String>,
+    /// Sent in `relayer.authHeaderName`
+    #[wasm_bindgen(getter_with_clone, js_name = "authToken")]
+    #[serde(default)]
+    pub auth_token: Option<String>,
+    /// `relayer` configures the endpoint, `allowedRpcOrigins` pins it
+    #[wasm_bindgen(getter_with_clone, js_name = "workerPolicy")]
+    #[serde(default)]
+    pub worker_policy: Option<WorkerPolicy>,
+}
+
+/// **Handles:** `WorkerRequestType::PrepareRegistration`
+/// Phase 1 of a resumable registration: derives and encrypts the NEAR keypair like
+/// DeriveNearKeypairAndEncrypt (PRF only, no registration transaction) and issues the resume
+/// token. Makes no network call.
+pub async fn handle_prepare_registration(
+    request: PrepareRegistrationRequest,
+) -> Result<PrepareRegistrationResult, String> {
+    let mac_key = resume_mac_key(&request.dual_prf_outputs.chacha20_prf_output)?;
+    let keys = handle_derive_near_keypair_and_encrypt(DeriveNearKeypairAndEncryptRequest {
+        dual_prf_outputs: Some(request.dual_prf_outputs),
+        prf_supported: Some(true),
+        prf_fallback: None,
+        worker_policy: None,
+        near_account_id: request.near_account_id.clone(),
+        credential: request.credential.clone(),
+        registration_transaction: None,
+        authenticator_options: None,
+        idempotency_key: None,
+        sub_key_paths: request.sub_key_paths,
+    })
+    .await?;
+    if keys.key_wrapping != KeyWrappingScheme::Prf.as_str() {
+        return Err("Resumable registration requires PRF output".to_string());
+    }
+
+    let state = RegistrationResumeState::new(
+        &request.near_account_id,
+        &request.credential.raw_id,
+        keys.version,
+        &keys.public_key,
+        state::now_ms(),
+    );
+    Ok(PrepareRegistrationResult {
+        resume_token: issue_resume_token(&state, &mac_key)?,
+        resume_expires_at_ms: state.expires_at_ms,
+        keys,
+    })
+}
+
+#[cfg(feature = "relayer")]
+/// Relayer submission of a CompleteRegistration request: the token must verify under the PRF
+/// output, name the request's account and credential, and carry the key that output derives
+pub fn resumed_relayer_request(
+    request: &CompleteRegistrationRequest,
+    now_ms: f64,
+) -> Result<SubmitToRelayerRequest, RegistrationResumeError> {
+    let mac_key = resume_mac_key(&request.dual_prf_outputs.chacha20_prf_output)
+        .map_err(RegistrationResumeError::Invalid)?;
+    let resume_state = verify_resume_token(
+        &request.resume_token,
+        &mac_key,
+        &request.near_account_id,
+        &request.credential.raw_id,
+        now_ms,
+    )?;
+    if resume_state.derivation_version != KeyWrappingScheme::Prf.blob_version() {
+        return Err(RegistrationResumeError::Invalid(format!(
+            "Unsupported key derivation version {}",
+            resume_state.derivation_version
+        )));
+    }
+    let (_, derived_public_key) = derive_ed25519_key_from_prf_output(
+        &request.dual_prf_outputs.ed25519_prf_output,
+        &request.near_account_id,
+    )
+    .map_err(|e| RegistrationResumeError::Invalid(e.to_string()))?;
+    if derived_public_key != resume_state.public_key {
+        return Err(RegistrationResumeError::Mismatch(
+            "the PRF output derives another key than the prepared one".to_string(),
+        ));
+    }
+
+    Ok(SubmitToRelayerRequest {
+        near_account_id: resume_state.near_account_id,
+        new_public_key: resume_state.public_key,
+        device_number: request.device_number,
+        vrf_challenge: request.vrf_challenge.clone(),
+        credential: request.credential.clone(),
+        deterministic_vrf_public_key: request.deterministic_vrf_public_key.clone(),
+        authenticator_options: request.authenticator_options.clone(),
+        signed_delegate_action: request.signed_delegate_action.clone(),
+        auth_token: request.auth_token.clone(),
+        worker_policy: request.worker_policy.clone(),
+    })
+}
+
+#[cfg(feature = "relayer")]
+/// **Handles:** `WorkerRequestType::CompleteRegistration`
+/// Phase 2: submits the prepared key to the relayer (see SubmitToRelayer). A relayer that
+/// created the account for an earlier attempt answers a retry with RelayerAccountExists.
+pub async fn handle_complete_registration(
+    request: CompleteRegistrationRequest,
+) -> Result<RelayerResult, String> {
+    let submission =
+        resumed_relayer_request(&request, state::now_ms()).map_err(|e| e.to_string())?;
+    handle_submit_to_relayer(submission).await
+}
@@ -0,0 +1,26 @@
+// ******************************************************************************
+// *                                                                            *
+// *                          HANDLER: RUN SELF TEST                            *
+// *                                                                            *
+// ******************************************************************************
+use crate::self_test::{self, SelfTestReport};
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RunSelfTestRequest {}
+
+/// **Handles:** `WorkerRequestType::RunSelfTest`
+/// Re-runs the known-answer tests the Initialize handshake runs (see self_test.rs).
+/// A failure puts the worker into the refusing state; a pass does not leave it.
+///
+/// # Arguments
+/// * `_request` - Empty request
+///
+/// # Returns
+/// * `SelfTestReport` - Per-primitive pass/fail with timings
+pub async fn handle_run_self_test(_request: RunSelfTestRequest) -> Result<SelfTestReport, String> {
+    Ok(self_test::run_self_test())
+}
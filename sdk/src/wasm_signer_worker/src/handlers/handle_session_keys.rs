@@ -0,0 +1,402 @@
+// ******************************************************************************
+// *                                                                            *
+// *             HANDLERS: CREATE / SIGN WITH / REVOKE SESSION KEY              *
+// *                                                                            *
+// ******************************************************************************
+use crate::actions::ActionParams;
+use crate::config::{
+    DEFAULT_SESSION_KEY_GAS_PRICE, DEFAULT_SESSION_KEY_TTL_MS, MAX_SESSION_KEY_TTL_MS,
+};
+use crate::error::SignerErrorCode;
+use crate::handlers::handle_sign_transactions_with_actions::TransactionSignResult;
+use crate::state::{self, SessionKey};
+use crate::transaction::{
+    build_actions_from_params, build_transaction_with_actions, calculate_transaction_hash,
+    sign_transaction,
+};
+use crate::types::wasm_to_json::WasmSignedTransaction;
+use crate::types::{Balance, Gas};
+use bs58;
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSessionKeyRequest {
+    /// Account the AddKey action will be sent from (the session key's signer)
+    #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
+    pub near_account_id: String,
+    /// The only contract the session key may call
+    #[wasm_bindgen(getter_with_clone, js_name = "receiverId")]
+    pub receiver_id: String,
+    /// Allowed methods (empty: any method on receiverId)
+    #[wasm_bindgen(getter_with_clone, js_name = "methodNames")]
+    #[serde(default)]
+    pub method_names: Vec<String>,
+    /// Allowance in yoctoNEAR
+    #[wasm_bindgen(getter_with_clone)]
+    pub allowance: String,
+    /// Lifetime of the key (defaults to DEFAULT_SESSION_KEY_TTL_MS)
+    #[wasm_bindgen(js_name = "ttlMs")]
+    #[serde(default)]
+    pub ttl_ms: Option<u32>,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSessionKeyResult {
+    #[wasm_bindgen(getter_with_clone, js_name = "publicKey")]
+    pub public_key: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
+    pub near_account_id: String,
+    #[wasm_bindgen(js_name = "expiresAtMs")]
+    pub expires_at_ms: f64,
+    /// Unsigned AddKey action (JSON ActionParams) to be signed with the account's main key
+    #[wasm_bindgen(getter_with_clone, js_name = "addKeyAction")]
+    pub add_key_action: String,
+}
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SignWithSessionKeyRequest {
+    #[wasm_bindgen(getter_with_clone, js_name = "sessionPublicKey")]
+    pub session_public_key: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "signerAccountId")]
+    pub signer_account_id: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "receiverId")]
+    pub receiver_id: String,
+    /// Next nonce of the session access key
+    #[wasm_bindgen(getter_with_clone)]
+    pub nonce: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "blockHash")]
+    pub block_hash: String,
+    #[wasm_bindgen(getter_with_clone)]
+    pub actions: String, // JSON string of ActionParams[]
+    /// Gas price in yoctoNEAR used to charge the allowance (defaults to NEAR's minimum)
+    #[wasm_bindgen(getter_with_clone, js_name = "gasPrice")]
+    #[serde(default)]
+    pub gas_price: Option<String>,
+    /// Repeats with the same key replay the first completed result (see idempotency.rs)
+    #[wasm_bindgen(getter_with_clone, js_name = "idempotencyKey")]
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+}
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RevokeSessionKeyRequest {
+    #[wasm_bindgen(getter_with_clone, js_name = "sessionPublicKey")]
+    pub session_public_key: String,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RevokeSessionKeyResult {
+    #[wasm_bindgen(getter_with_clone, js_name = "publicKey")]
+    pub public_key: String,
+    /// False if the worker no longer held the key (unknown or expired)
+    pub revoked: bool,
+    /// Unsigned DeleteKey action (JSON ActionParams) removing the key on-chain
+    #[wasm_bindgen(getter_with_clone, js_name = "deleteKeyAction")]
+    pub delete_key_action: String,
+}
+
+fn parse_balance(value: &str, field: &str) -> Result<Balance, String> {
+    value
+        .parse::<Balance>()
+        .map_err(|_| format!("Invalid {}: {}", field, value))
+}
+
+/// Checks a transaction against the session key's permissions and returns the gas fees
+/// it would charge to the allowance. Mirrors the on-chain function-call access key rules:
+/// only FunctionCall actions to receiver_id, allowed methods, and no attached deposit.
+pub fn check_session_key_permissions(
+    key: &SessionKey,
+    signer_account_id: &str,
+    receiver_id: &str,
+    actions: &[ActionParams],
+    gas_price: Balance,
+) -> Result<Balance, (SignerErrorCode, String)> {
+    let denied = |msg: String| (SignerErrorCode::SessionKeyPermissionDenied, msg);
+
+    if signer_account_id != key.near_account_id {
+        return Err(denied(format!(
+            "Session key belongs to {}, not {}",
+            key.near_account_id, signer_account_id
+        )));
+    }
+    if receiver_id != key.receiver_id {
+        return Err(denied(format!(
+            "Session key may only call {}, not {}",
+            key.receiver_id, receiver_id
+        )));
+    }
+    if actions.is_empty() {
+        return Err(denied("No actions to sign".to_string()));
+    }
+
+    let mut cost: Balance = 0;
+    for (i, action) in actions.iter().enumerate() {
+        let ActionParams::FunctionCall {
+            method_name,
+            gas,
+            deposit,
+            ..
+        } = action
+        else {
+            return Err(denied(format!(
+                "Action {}: session keys may only sign FunctionCall actions",
+                i
+            )));
+        };
+        if !key.method_names.is_empty() && !key.method_names.contains(method_name) {
+            return Err(denied(format!(
+                "Action {}: method {} is not allowed for this session key",
+                i, method_name
+            )));
+        }
+        if parse_balance(deposit, "deposit").map_err(denied)? != 0 {
+            return Err(denied(format!(
+                "Action {}: session keys cannot attach a deposit",
+                i
+            )));
+        }
+        let gas: Gas = gas
+            .parse()
+            .map_err(|_| denied(format!("Action {}: invalid gas amount", i)))?;
+        cost = (gas as Balance)
+            .checked_mul(gas_price)
+            .and_then(|fee| cost.checked_add(fee))
+            .ok_or_else(|| denied("Gas fee overflow".to_string()))?;
+    }
+
+    let remaining = key.allowance.saturating_sub(key.spent);
+    if cost > remaining {
+        return Err((
+            SignerErrorCode::SessionKeyAllowanceExceeded,
+            format!(
+                "Transaction needs up to {} yoctoNEAR of gas fees, {} remaining",
+                cost, remaining
+            ),
+        ));
+    }
+    Ok(cost)
+}
+
+/// **Handles:** `WorkerRequestType::CreateSessionKey`
+/// Generates a fresh ed25519 keypair and keeps the secret key in the worker state until it
+/// expires or is revoked; it leaves the worker only inside the sealed worker state record the
+/// response carries (see worker_state.rs). Returns the public key and the unsigned AddKey
+/// action registering it as a function-call access key.
+///
+/// # Arguments
+/// * `request` - Account, receiver, allowed methods, allowance and TTL
+///
+/// # Returns
+/// * `CreateSessionKeyResult` - Public key, expiry, and AddKey action
+pub async fn handle_create_session_key(
+    request: CreateSessionKeyRequest,
+) -> Result<CreateSessionKeyResult, String> {
+    if request.receiver_id.is_empty() {
+        return Err("receiverId cannot be empty".to_string());
+    }
+    let allowance = parse_balance(&request.allowance, "allowance")?;
+    if allowance == 0 {
+        return Err("allowance must be greater than zero".to_string());
+    }
+    let ttl_ms = request.ttl_ms.unwrap_or(DEFAULT_SESSION_KEY_TTL_MS);
+    if ttl_ms == 0 || ttl_ms > MAX_SESSION_KEY_TTL_MS {
+        return Err(format!(
+            "ttlMs must be between 1 and {}",
+            MAX_SESSION_KEY_TTL_MS
+        ));
+    }
+
+    let mut seed = [0u8; 32];
+    getrandom::getrandom(&mut seed)
+        .map_err(|e| format!("Failed to generate session key: {}", e))?;
+    let signing_key = ed25519_dalek::SigningKey::from_bytes(&seed);
+    let public_key = format!(
+        "ed25519:{}",
+        bs58::encode(signing_key.verifying_key().to_bytes()).into_string()
+    );
+
+    let access_key = serde_json::json!({
+        "nonce": 0,
+        "permission": {
+            "FunctionCall": {
+                "allowance": allowance.to_string(),
+                "receiver_id": request.receiver_id,
+                "method_names": request.method_names,
+            }
+        }
+    });
+    let add_key_action = serde_json::to_string(&ActionParams::AddKey {
+        public_key: public_key.clone(),
+        access_key: access_key.to_string(),
+    })
+    .map_err(|e| format!("Failed to serialize AddKey action: {}", e))?;
+
+    let expires_at_ms = state::now_ms() + ttl_ms as f64;
+    state::store_session_key(
+        &public_key,
+        SessionKey {
+            signing_key,
+            near_account_id: request.near_account_id.clone(),
+            receiver_id: request.receiver_id,
+            method_names: request.method_names,
+            allowance,
+            spent: 0,
+            expires_at_ms,
+        },
+    )?;
+
+    info!(
+        "RUST: Created session key {} for {} (ttl {} ms)",
+        public_key, request.near_account_id, ttl_ms
+    );
+
+    Ok(CreateSessionKeyResult {
+        public_key,
+        near_account_id: request.near_account_id,
+        expires_at_ms,
+        add_key_action,
+    })
+}
+
+/// **Handles:** `WorkerRequestType::SignWithSessionKey`
+/// Signs a transaction with a session key without any confirmation UI. Receiver, methods and
+/// allowance are enforced locally before signing; violations return a failed result with an
+/// error code instead of a signature.
+///
+/// # Arguments
+/// * `request` - Session public key, transaction details and action parameters
+///
+/// # Returns
+/// * `TransactionSignResult` - Signed transaction and hash, or a coded failure
+pub async fn handle_sign_with_session_key(
+    request: SignWithSessionKeyRequest,
+) -> Result<TransactionSignResult, String> {
+    let mut logs: Vec<String> = Vec::new();
+
+    let action_params: Vec<ActionParams> = serde_json::from_str(&request.actions)
+        .map_err(|e| format!("Failed to parse actions: {}", e))?;
+    let gas_price = match &request.gas_price {
+        Some(price) => parse_balance(price, "gasPrice")?,
+        None => DEFAULT_SESSION_KEY_GAS_PRICE,
+    };
+    let nonce: u64 = request
+        .nonce
+        .parse()
+        .map_err(|e| format!("Invalid nonce: {}", e))?;
+    let block_hash = bs58::decode(&request.block_hash)
+        .into_vec()
+        .map_err(|e| format!("Invalid block hash: {}", e))?;
+
+    // Check permissions and charge the allowance in one step, so concurrent requests
+    // cannot both spend the same remaining allowance
+    let checked = state::with_session_key(&request.session_public_key, |key| {
+        let cost = check_session_key_permissions(
+            key,
+            &request.signer_account_id,
+            &request.receiver_id,
+            &action_params,
+            gas_price,
+        )?;
+        key.spent += cost;
+        Ok((key.signing_key.clone(), cost, key.allowance - key.spent))
+    });
+    let (signing_key, cost, remaining) = match checked {
+        Ok(Ok(checked)) => checked,
+        Ok(Err((code, msg))) => {
+            return Ok(TransactionSignResult::failed_with_code(logs, msg, code));
+        }
+        Err(code) => {
+            return Ok(TransactionSignResult::failed_with_code(
+                logs,
+                format!(
+                    "Session key {} is not available",
+                    request.session_public_key
+                ),
+                code,
+            ));
+        }
+    };
+    logs.push(format!(
+        "Session key permissions checked: {} yoctoNEAR charged, {} remaining",
+        cost, remaining
+    ));
+
+    let signed = build_actions_from_params(action_params)
+        .and_then(|actions| {
+            build_transaction_with_actions(
+                &request.signer_account_id,
+                &request.receiver_id,
+                nonce,
+                &block_hash,
+                &signing_key,
+                actions,
+            )
+        })
+        .and_then(|transaction| sign_transaction(transaction, &signing_key));
+    let signed_tx_bytes = match signed {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            // Nothing was signed: refund the charge
+            let _ = state::with_session_key(&request.session_public_key, |key| {
+                key.spent = key.spent.saturating_sub(cost);
+            });
+            return Err(format!("Failed to sign with session key: {}", e));
+        }
+    };
+
+    let transaction_hash = calculate_transaction_hash(&signed_tx_bytes);
+    let signed_tx = crate::types::SignedTransaction::from_borsh_bytes(&signed_tx_bytes)
+        .map_err(|e| format!("Failed to deserialize SignedTransaction: {}", e))?;
+    logs.push("Transaction signed with session key".to_string());
+
+    Ok(TransactionSignResult::new(
+        true,
+        Some(vec![transaction_hash]),
+        Some(vec![WasmSignedTransaction::from(&signed_tx)]),
+        logs,
+        None,
+    ))
+}
+
+/// **Handles:** `WorkerRequestType::RevokeSessionKey`
+/// Wipes the session key from worker memory and returns the DeleteKey action that removes it
+/// on-chain (returned even if the worker no longer held the key).
+///
+/// # Arguments
+/// * `request` - Session public key to revoke
+///
+/// # Returns
+/// * `RevokeSessionKeyResult` - Whether the key was held, and the DeleteKey action
+pub async fn handle_revoke_session_key(
+    request: RevokeSessionKeyRequest,
+) -> Result<RevokeSessionKeyResult, String> {
+    let revoked = state::remove_session_key(&request.session_public_key);
+    let delete_key_action = serde_json::to_string(&ActionParams::DeleteKey {
+        public_key: request.session_public_key.clone(),
+    })
+    .map_err(|e| format!("Failed to serialize DeleteKey action: {}", e))?;
+
+    info!(
+        "RUST: Revoked session key {} (held: {})",
+        request.session_public_key, revoked
+    );
+
+    Ok(RevokeSessionKeyResult {
+        public_key: request.session_public_key,
+        revoked,
+        delete_key_action,
+    })
+}
@@ -4,20 +4,40 @@
 // *                                                                            *
 // ******************************************************************************
 use crate::encoders::base64_standard_encode;
+use crate::error::SignerErrorCode;
+#[cfg(feature = "secp256k1")]
+use crate::evm::{self, EvmSignature};
+use crate::types::handlers::ConfirmationConfig;
+use crate::types::near::Nep413Payload;
 use log::info;
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
+/// Chain a message is signed for (`chain.kind`)
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum SigningChain {
+    /// NEP-413 message, signed with the account's Ed25519 key
+    #[default]
+    Near,
+    /// EIP-191 personal_sign message (0x-prefixed hex or text, see evm.rs), signed with the
+    /// account's secp256k1 key once the user confirms it
+    EvmPersonalSign { message: String },
+}
+
 #[wasm_bindgen]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct SignNep413Request {
     #[wasm_bindgen(getter_with_clone)]
-    pub message: String, // Message to sign
+    #[serde(default)]
+    pub message: Option<String>, // Message to sign (NEAR)
     #[wasm_bindgen(getter_with_clone)]
-    pub recipient: String, // Recipient identifier
+    #[serde(default)]
+    pub recipient: Option<String>, // Recipient identifier (NEAR)
     #[wasm_bindgen(getter_with_clone)]
-    pub nonce: String, // Base64-encoded 32-byte nonce
+    #[serde(default)]
+    pub nonce: Option<String>, // Base64-encoded 32-byte nonce (NEAR)
     #[wasm_bindgen(getter_with_clone)]
     pub state: Option<String>, // Optional state
     #[wasm_bindgen(getter_with_clone, js_name = "accountId")]
@@ -26,8 +46,18 @@ pub struct SignNep413Request {
     pub encrypted_private_key_data: String,
     #[wasm_bindgen(getter_with_clone, js_name = "encryptedPrivateKeyIv")]
     pub encrypted_private_key_iv: String,
+    /// Collected by the caller for NEAR; EVM messages are confirmed by the worker, and the
+    /// confirmation returns it
     #[wasm_bindgen(getter_with_clone, js_name = "prfOutput")]
-    pub prf_output: String,
+    #[serde(default)]
+    pub prf_output: Option<String>,
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub chain: SigningChain,
+    /// Confirmation UI for EVM messages (the user's preferences when unset)
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub confirmation_config: Option<ConfirmationConfig>,
 }
 
 #[wasm_bindgen]
@@ -37,11 +67,16 @@ pub struct SignNep413Result {
     #[wasm_bindgen(getter_with_clone, js_name = "accountId")]
     pub account_id: String,
     #[wasm_bindgen(getter_with_clone, js_name = "publicKey")]
-    pub public_key: String, // Base58-encoded public key
+    pub public_key: String, // Base58-encoded public key, with its curve prefix
     #[wasm_bindgen(getter_with_clone)]
-    pub signature: String, // Base64-encoded signature
+    pub signature: String, // Base64-encoded signature (NEAR), 0x-prefixed r || s || v (EVM)
     #[wasm_bindgen(getter_with_clone)]
     pub state: Option<String>,
+    /// EVM personal_sign: r, s, v and the signer's address
+    #[cfg(feature = "secp256k1")]
+    #[wasm_bindgen(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evm: Option<EvmSignature>,
 }
 
 #[wasm_bindgen]
@@ -58,6 +93,8 @@ impl SignNep413Result {
             public_key,
             signature,
             state,
+            #[cfg(feature = "secp256k1")]
+            evm: None,
         }
     }
 }
@@ -66,6 +103,7 @@ impl SignNep413Result {
 /// This handler implements NEP-413 message signing, which allows signing arbitrary off-chain messages
 /// that cannot represent valid NEAR transactions. It follows the NEP-413 specification for message
 /// structure, serialization, hashing, and signing.
+/// With `chain: evmPersonalSign` it signs an EIP-191 personal_sign message instead (see evm.rs).
 ///
 /// # Arguments
 /// * `request` - Contains message data, recipient, nonce, optional state, and decryption parameters
@@ -75,10 +113,30 @@ impl SignNep413Result {
 pub async fn handle_sign_nep413_message(
     request: SignNep413Request,
 ) -> Result<SignNep413Result, String> {
+    match request.chain.clone() {
+        SigningChain::Near => sign_near_message(request).await,
+        #[cfg(feature = "secp256k1")]
+        SigningChain::EvmPersonalSign { message } => {
+            sign_evm_personal_message(request, &message).await
+        }
+        #[cfg(not(feature = "secp256k1"))]
+        SigningChain::EvmPersonalSign { .. } => Err(crate::error::FeatureNotCompiledError {
+            feature: "secp256k1",
+        }
+        .to_string()),
+    }
+}
+
+async fn sign_near_message(request: SignNep413Request) -> Result<SignNep413Result, String> {
     info!("RUST: Starting NEP-413 message signing");
+    let missing = |field: &str| format!("`{}` is required for NEP-413 signing", field);
+    let message = request.message.ok_or_else(|| missing("message"))?;
+    let recipient = request.recipient.ok_or_else(|| missing("recipient"))?;
+    let nonce = request.nonce.ok_or_else(|| missing("nonce"))?;
+    let prf_output = request.prf_output.ok_or_else(|| missing("prfOutput"))?;
 
     // Decode and validate nonce is exactly 32 bytes
-    let nonce_bytes = crate::encoders::base64_standard_decode(&request.nonce)
+    let nonce_bytes = crate::encoders::base64_standard_decode(&nonce)
         .map_err(|e| format!("Failed to decode nonce from base64: {}", e))?;
 
     if nonce_bytes.len() != 32 {
@@ -89,30 +147,26 @@ pub async fn handle_sign_nep413_message(
     }
 
     // Decrypt private key using PRF output
-    let signing_key = crate::crypto::decrypt_private_key_with_prf(
+    let signing_key = crate::crypto::decrypt_stored_private_key_with_prf(
         &request.account_id,
-        &request.prf_output,
+        &prf_output,
         &request.encrypted_private_key_data,
         &request.encrypted_private_key_iv,
     )
-    .map_err(|e| format!("Failed to decrypt private key: {}", e))?;
-
-    // Create NEP-413 payload structure for Borsh serialization
-    #[derive(borsh::BorshSerialize)]
-    struct Nep413Payload {
-        message: String,
-        recipient: String,
-        nonce: [u8; 32],
-        state: Option<String>,
-    }
+    .await
+    .map_err(|e| match e.code() {
+        // Keep the code leading so the dispatch surfaces it
+        Some(SignerErrorCode::WrongCurveForChain) => e.to_string(),
+        _ => format!("Failed to decrypt private key: {}", e),
+    })?;
 
     let nonce_array: [u8; 32] = nonce_bytes
         .try_into()
         .map_err(|_| "Failed to convert nonce to 32-byte array")?;
 
     let payload = Nep413Payload {
-        message: request.message,
-        recipient: request.recipient,
+        message,
+        recipient,
         nonce: nonce_array,
         state: request.state.clone(),
     };
@@ -165,3 +219,74 @@ pub async fn handle_sign_nep413_message(
         request.state,
     ))
 }
+
+/// EVM personal_sign: shows the decoded message for confirmation, then signs it with the
+/// secp256k1 key the confirmation's PRF output decrypts
+#[cfg(feature = "secp256k1")]
+async fn sign_evm_personal_message(
+    request: SignNep413Request,
+    message: &str,
+) -> Result<SignNep413Result, String> {
+    use crate::handlers::confirm_tx_details::request_evm_personal_sign_confirmation;
+
+    info!("RUST: Starting EVM personal_sign message signing");
+    let message = evm::message_bytes(message)?;
+    let confirmation = request_evm_personal_sign_confirmation(
+        &request.account_id,
+        &message,
+        request.confirmation_config.as_ref(),
+    )
+    .await?;
+    if let Some(collection_error) = &confirmation.collection_error {
+        return Err(format!(
+            "{}: Credential collection failed: {}",
+            collection_error.error_code(),
+            collection_error.message
+        ));
+    }
+    if !confirmation.confirmed {
+        return Err(format!(
+            "{}: {}",
+            confirmation
+                .error_code
+                .as_deref()
+                .unwrap_or(SignerErrorCode::UserDeclined.as_str()),
+            confirmation
+                .error
+                .as_deref()
+                .unwrap_or("User declined to sign the message")
+        ));
+    }
+    if confirmation
+        .confirmation_expires_at_ms
+        .is_some_and(|expires_at_ms| crate::state::now_ms() > expires_at_ms)
+    {
+        return Err(format!(
+            "{}: confirmed after the confirmation window closed",
+            SignerErrorCode::ConfirmationExpired
+        ));
+    }
+    let prf_output = confirmation
+        .prf_output
+        .as_deref()
+        .ok_or("PRF output missing from the confirmation")?;
+
+    let signing_key = crate::crypto::decrypt_stored_secp256k1_private_key_with_prf(
+        &request.account_id,
+        prf_output,
+        &request.encrypted_private_key_data,
+        &request.encrypted_private_key_iv,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    let signature = evm::sign_personal_message(&signing_key, &message)?;
+
+    info!("RUST: EVM personal_sign message signed successfully");
+    Ok(SignNep413Result {
+        account_id: request.account_id,
+        public_key: evm::near_public_key(signing_key.verifying_key()),
+        signature: signature.signature.clone(),
+        state: None,
+        evm: Some(signature),
+    })
+}
@@ -32,6 +32,10 @@ pub struct SignTransactionWithKeyPairRequest {
     pub block_hash: String,
     #[wasm_bindgen(getter_with_clone)]
     pub actions: String, // JSON string of ActionParams[]
+    /// Repeats with the same key replay the first completed result (see idempotency.rs)
+    #[wasm_bindgen(getter_with_clone, js_name = "idempotencyKey")]
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
 }
 
 /// Signs a transaction using a provided private key without requiring WebAuthn authentication.
@@ -4,21 +4,59 @@
 // *                                                                            *
 // ******************************************************************************
 
+use crate::action_firewall::check_high_risk_actions;
 use crate::actions::ActionParams;
-use crate::handlers::confirm_tx_details::{request_user_confirmation, ConfirmationResult};
+use crate::allowance;
+use crate::args_schema::check_batch_args;
+use crate::assertion_verify::{verify_assertion_locally, LocalAssertionCheck};
+use crate::batch_preview::{self, BatchPreparation};
+use crate::chunked_deploy::DeployManifest;
+use crate::conditional_batch::{self, ConditionalBatch, ConditionalBatchEntry};
+use crate::config::INVALID_NONCE_MAX_RESIGNS;
+use crate::confirmation_blocks::SummaryPolicy;
+use crate::confirmation_speech::SpeechLocale;
+use crate::crypto::verify_vrf_challenge_binding;
+use crate::dual_control::{self, AwaitingSecondApproval};
+use crate::elevation;
+use crate::error::{DeadlineError, RpcErrorKind, SignerErrorCode};
+use crate::handlers::confirm_tx_details::{
+    challenge_expiry_policy, check_request_expiry, compute_confirmation_intent_digest,
+    create_transaction_summary_from_parsed, handle_collection_error, request_user_confirmation,
+    ConfirmationResult,
+};
+use crate::hooks::{self, PostSignHookNotification, PreSignHookRequest};
+use crate::key_selection::{self, OnChainAccessKey};
+use crate::key_usage;
+use crate::operation_journal::{emit_journal_entry, JournalEntry};
+use crate::peer_channel;
+use crate::recent_receivers::{first_time_receiver_flags, lookup_receiver_history};
+use crate::redaction;
 use crate::rpc_calls::{verify_authentication_response_rpc_call, VrfData};
+use crate::rpc_client::{
+    broadcast_tx_commit, latest_block_height, refresh_nonce_and_block, view_access_key,
+    NearRpcClient, RpcClient,
+};
+use crate::rpc_endpoints::resolve_rpc_endpoints;
+use crate::sign_phases::{PhaseTracker, SignPhase};
+use crate::state::{self, PendingRequestGuard};
+use crate::storage_deposit;
 use crate::transaction::{
     build_actions_from_params, build_transaction_with_actions, calculate_transaction_hash,
     sign_transaction,
 };
+use crate::tx_dependencies;
+use crate::tx_diff;
 use crate::types::{
-    handlers::{ConfirmationConfig, RpcCallPayload, TransactionContext},
+    handlers::{
+        ConfirmationConfig, KeyBlobCandidate, RpcCallPayload, RpcOverrides, TransactionContext,
+        WorkerPolicy,
+    },
     progress::{
         send_completion_message, send_error_message, send_progress_message, ProgressMessageType,
         ProgressStep,
     },
     wasm_to_json::WasmSignedTransaction,
-    DecryptionPayload, SignedTransaction, WebAuthnAuthenticationCredential,
+    AccountId, DecryptionPayload, SignedTransaction, WebAuthnAuthenticationCredential,
     WebAuthnAuthenticationCredentialStruct,
 };
 use bs58;
@@ -40,6 +78,89 @@ pub struct SignTransactionsWithActionsRequest {
     /// Unified confirmation configuration for controlling the confirmation flow
     #[wasm_bindgen(getter_with_clone, js_name = "confirmationConfig")]
     pub confirmation_config: Option<ConfirmationConfig>,
+    /// Integrator policy (pre-sign/post-sign hooks)
+    #[wasm_bindgen(getter_with_clone, js_name = "workerPolicy")]
+    #[serde(default)]
+    pub worker_policy: Option<WorkerPolicy>,
+    /// Repeats with the same key replay the first completed result (see idempotency.rs)
+    #[wasm_bindgen(getter_with_clone, js_name = "idempotencyKey")]
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+    /// Verification/broadcast endpoints replacing rpcCall.nearRpcUrl
+    /// (pinned to workerPolicy.allowedRpcOrigins)
+    #[wasm_bindgen(getter_with_clone, js_name = "rpcOverrides")]
+    #[serde(default)]
+    pub rpc_overrides: Option<RpcOverrides>,
+    /// Broadcast each signed transaction from the worker (broadcast_tx_commit, through
+    /// rpcOverrides.broadcast or the verification endpoint). An InvalidNonce rejection is
+    /// re-signed on a fresh nonce and block hash instead of failing the batch.
+    #[wasm_bindgen(getter_with_clone)]
+    #[serde(default)]
+    pub broadcast: bool,
+    /// Last block height the batch may be sent at. Shown in the confirmation summary (and so
+    /// covered by the intent digest); with `broadcast` the worker checks the chain height right
+    /// before each send, otherwise the result carries it for the main thread to enforce.
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub valid_until_block_height: Option<u64>,
+    /// The main thread's nonce and block context when the request was made. With a
+    /// function-call signing key (`accessKeyAllowance` set), the confirmation shows the
+    /// allowance left after the estimated cost, and a batch the allowance cannot cover is
+    /// refused before the user is prompted (see allowance.rs).
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub transaction_context: Option<TransactionContext>,
+    /// Signs with the `decryption.candidates` blob tagged with this key instead of the main
+    /// blob, once the key's on-chain permission is checked to cover the batch (see
+    /// key_selection.rs)
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub signing_public_key: Option<String>,
+    /// Drop every transaction identical to an earlier one of the batch before it is confirmed
+    /// (see batch_preview.rs)
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub deduplicate: bool,
+    /// Canonical digest SummarizeTransactions returned for the previewed batch; a batch with
+    /// another digest fails with PayloadChangedSincePreview
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub preview_digest: Option<String>,
+    /// FunctionCall args values ("args.email", "args.profile.notes.0") shown as their
+    /// SHA-256 instead of verbatim in the confirmation and the audit log (see redaction.rs)
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub redact_paths: Vec<String>,
+    /// Simulate the batch in order right before signing, and sign a transaction only when
+    /// every earlier one simulated successfully (see conditional_batch.rs)
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub conditional_batch: Option<ConditionalBatch>,
+    /// Under WorkerPolicy.requiredApprovals 2: the token of the first approval this request
+    /// approves a second time (see dual_control.rs)
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub approval_token: Option<String>,
+    /// Insert a storage_deposit ahead of ft_transfer and ft_transfer_call actions to receivers
+    /// their token contract has not registered, shown in the confirmation as added
+    /// automatically (see storage_deposit.rs)
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub auto_storage_deposit: bool,
+    /// Set once `deduplicate` and `previewDigest` are applied, for the confirmation payload
+    #[wasm_bindgen(skip)]
+    #[serde(skip)]
+    pub batch_preparation: Option<BatchPreparation>,
+    /// Set by DeployLargeContract: the batch is a chunked deploy, confirmed as one summarized
+    /// deploy with each transaction's step and chunk hash
+    #[wasm_bindgen(skip)]
+    #[serde(skip)]
+    pub deploy_manifest: Option<DeployManifest>,
+    /// Set once `autoStorageDeposit` inserted actions: each transaction's inserted action
+    /// indexes, for the confirmation payload
+    #[wasm_bindgen(skip)]
+    #[serde(skip)]
+    pub auto_inserted_actions: Vec<Vec<u32>>,
 }
 
 #[wasm_bindgen]
@@ -54,6 +175,11 @@ pub struct TransactionPayload {
     // WASM does not support complex Enums, so it's passed in as a JSON string
     #[wasm_bindgen(getter_with_clone, js_name = "actions")]
     pub actions: String,
+    /// Indexes of the earlier transactions of the batch this one depends on (see
+    /// tx_dependencies.rs)
+    #[wasm_bindgen(skip)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<u32>,
 }
 
 impl TransactionPayload {
@@ -82,6 +208,39 @@ pub struct TransactionSignResult {
     pub logs: Vec<String>,
     #[wasm_bindgen(getter_with_clone)]
     pub error: Option<String>,
+    /// Machine-readable error code (e.g. "UserDeclined", "CredentialTimeout")
+    #[wasm_bindgen(getter_with_clone, js_name = "errorCode")]
+    pub error_code: Option<String>,
+    /// Catalog category of `error_code` (e.g. "UserAction", "Network")
+    #[wasm_bindgen(getter_with_clone, js_name = "errorCategory")]
+    pub error_category: Option<String>,
+    /// Whether sending the request again can succeed (set with `error_code`)
+    #[wasm_bindgen(getter_with_clone)]
+    pub retriable: Option<bool>,
+    /// rpcOverrides.broadcast, once allowed: where the main thread should broadcast
+    #[wasm_bindgen(getter_with_clone, js_name = "broadcastRpcUrl")]
+    pub broadcast_rpc_url: Option<String>,
+    /// Final execution outcome per broadcast transaction (requests with `broadcast`)
+    #[wasm_bindgen(skip)]
+    pub broadcast_outcomes: Option<Vec<serde_json::Value>>,
+    /// The request's validUntilBlockHeight, when the main thread broadcasts the transactions
+    #[wasm_bindgen(skip)]
+    pub valid_until_block_height: Option<u64>,
+    /// With `deduplicate`: indexes (in the batch as sent) of the transactions dropped as
+    /// duplicates
+    #[wasm_bindgen(skip)]
+    pub removed_duplicate_indexes: Option<Vec<u32>>,
+    /// With `conditionalBatch`: each transaction signed or skipped, with its simulation
+    #[wasm_bindgen(skip)]
+    pub conditional_entries: Option<Vec<ConditionalBatchEntry>>,
+    /// With `broadcast` and `dependsOn`: indexes of the transactions signed but not broadcast
+    /// after a transaction others depend on failed on chain (see tx_dependencies.rs)
+    #[wasm_bindgen(skip)]
+    pub withheld_transactions: Option<Vec<u32>>,
+    /// Under WorkerPolicy.requiredApprovals 2, with errorCode AwaitingSecondApproval: the
+    /// token the second approval must be requested with
+    #[wasm_bindgen(skip)]
+    pub awaiting_second_approval: Option<AwaitingSecondApproval>,
 }
 
 #[wasm_bindgen]
@@ -100,6 +259,16 @@ impl TransactionSignResult {
             signed_transactions,
             logs,
             error,
+            error_code: None,
+            error_category: None,
+            retriable: None,
+            broadcast_rpc_url: None,
+            broadcast_outcomes: None,
+            valid_until_block_height: None,
+            removed_duplicate_indexes: None,
+            conditional_entries: None,
+            withheld_transactions: None,
+            awaiting_second_approval: None,
         }
     }
 
@@ -115,6 +284,22 @@ impl TransactionSignResult {
     }
 }
 
+impl TransactionSignResult {
+    /// Failed result carrying an error code the UI can act on (e.g. offer "try again")
+    pub fn failed_with_code(
+        logs: Vec<String>,
+        error_msg: String,
+        error_code: SignerErrorCode,
+    ) -> TransactionSignResult {
+        TransactionSignResult {
+            error_code: Some(error_code.as_str().to_string()),
+            error_category: Some(error_code.definition().category.as_str().to_string()),
+            retriable: Some(error_code.definition().retriable),
+            ..TransactionSignResult::failed(logs, error_msg)
+        }
+    }
+}
+
 #[wasm_bindgen]
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -159,6 +344,11 @@ pub struct Decryption {
     pub encrypted_private_key_data: String,
     #[wasm_bindgen(getter_with_clone)]
     pub encrypted_private_key_iv: String,
+    /// Public key the blob is tagged with (a signingPublicKey candidate); a blob that decrypts
+    /// to another key is refused
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub expected_public_key: Option<String>,
 }
 
 #[wasm_bindgen]
@@ -173,6 +363,7 @@ impl Decryption {
             chacha20_prf_output,
             encrypted_private_key_data,
             encrypted_private_key_iv,
+            expected_public_key: None,
         }
     }
 }
@@ -193,11 +384,129 @@ impl Decryption {
 /// * `TransactionSignResult` - Contains success status, transaction hashes, signed transactions, and detailed logs
 pub async fn handle_sign_transactions_with_actions(
     tx_batch_request: SignTransactionsWithActionsRequest,
+) -> Result<TransactionSignResult, String> {
+    sign_transactions_with_actions_confirmed_by(tx_batch_request, None).await
+}
+
+/// Checks the batch's estimated cost against the function-call key allowance in `context`
+/// (see allowance.rs). Contexts of full access keys and unlimited allowances pass.
+fn check_allowance(
+    context: Option<&TransactionContext>,
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+    worker_policy: Option<&WorkerPolicy>,
+    logs: &mut Vec<String>,
+) -> Result<(), (SignerErrorCode, String)> {
+    let estimate = allowance::allowance_estimate(context, receivers_and_actions, worker_policy)
+        .map_err(|e| (SignerErrorCode::InvalidConfig, e))?;
+    if let Some(estimate) = estimate {
+        logs.push(format!(
+            "Access key allowance: {} yoctoNEAR left, batch estimated at up to {}",
+            estimate.remaining, estimate.required
+        ));
+        estimate
+            .check()
+            .map_err(|e| (e.code(), e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// The request's signingPublicKey with its candidate blob
+pub(crate) struct SelectedKey {
+    pub public_key: String,
+    pub key_bytes: [u8; 32],
+    pub candidate: KeyBlobCandidate,
+}
+
+/// Picks the `decryption.candidates` blob of the request's signingPublicKey and reads the key
+/// from the chain, checked to cover the batch (see key_selection.rs)
+pub(crate) async fn select_signing_key<R: RpcClient>(
+    rpc: &R,
+    tx_batch_request: &SignTransactionsWithActionsRequest,
+    public_key: &str,
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+) -> Result<(SelectedKey, OnChainAccessKey), (SignerErrorCode, String)> {
+    let key_bytes = key_selection::parse_public_key(public_key).map_err(|e| {
+        (
+            SignerErrorCode::InvalidConfig,
+            format!("Invalid signingPublicKey: {}", e),
+        )
+    })?;
+    let candidate = key_selection::select_candidate(&tx_batch_request.decryption, &key_bytes)
+        .ok_or_else(|| {
+            (
+                SignerErrorCode::InvalidConfig,
+                format!("No decryption candidate holds signingPublicKey {}", public_key),
+            )
+        })?
+        .clone();
+    let account_id = &tx_batch_request.tx_signing_requests[0].near_account_id;
+    let key = fetch_selected_key(rpc, account_id, public_key, receivers_and_actions).await?;
+    let selected = SelectedKey {
+        public_key: public_key.to_string(),
+        key_bytes,
+        candidate,
+    };
+    Ok((selected, key))
+}
+
+/// `public_key`'s access key on `account_id`, checked to cover the batch
+pub(crate) async fn fetch_selected_key<R: RpcClient>(
+    rpc: &R,
+    account_id: &str,
+    public_key: &str,
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+) -> Result<OnChainAccessKey, (SignerErrorCode, String)> {
+    let result = view_access_key(rpc, account_id, public_key)
+        .await
+        .map_err(|e| {
+            (
+                e.code(),
+                format!("Failed to fetch access key {}: {}", public_key, e),
+            )
+        })?;
+    let key = result
+        .map(|result| key_selection::parse_access_key(&result))
+        .transpose()
+        .map_err(|e| (SignerErrorCode::RpcResponseInvalid, e))?;
+    key_selection::check_key_permission(account_id, public_key, key, receivers_and_actions)
+        .map_err(|e| (e.code(), e.to_string()))
+}
+
+/// A confirmation made outside this worker's confirmation UI (CompleteRemoteConfirmation), with
+/// the first-time-receiver flags its digest was computed with
+pub(crate) struct PresetConfirmation {
+    pub confirmation: ConfirmationResult,
+    pub first_time_receivers: Vec<Option<bool>>,
+}
+
+/// The SignTransactionsWithActions flow, confirmed through the confirmation UI or, with
+/// `preset`, by a confirmation made elsewhere (no UI, no peer challenge). Everything after the
+/// confirmation (expiry, hooks, verification, signing) is shared.
+pub(crate) async fn sign_transactions_with_actions_confirmed_by(
+    tx_batch_request: SignTransactionsWithActionsRequest,
+    preset: Option<PresetConfirmation>,
+) -> Result<TransactionSignResult, String> {
+    sign_transactions_with_actions_tracked(tx_batch_request, preset, &PhaseTracker::default())
+        .await
+}
+
+/// `sign_transactions_with_actions_confirmed_by`, marking each phase on `phases` as the flow
+/// enters it (see sign_phases.rs)
+pub(crate) async fn sign_transactions_with_actions_tracked(
+    mut tx_batch_request: SignTransactionsWithActionsRequest,
+    preset: Option<PresetConfirmation>,
+    phases: &PhaseTracker,
 ) -> Result<TransactionSignResult, String> {
     // Validate input
     if tx_batch_request.tx_signing_requests.is_empty() {
         return Err("No transactions provided".to_string());
     }
+    // Session state (nonce reservations, audit log, receiver history) is kept per account,
+    // so a batch is signed by exactly one
+    let account = batch_account_id(&tx_batch_request)?;
+    tx_batch_request.worker_policy = tx_batch_request
+        .worker_policy
+        .map(|policy| policy.for_account(&account));
 
     let mut logs: Vec<String> = Vec::new();
     logs.push(format!(
@@ -205,18 +514,225 @@ pub async fn handle_sign_transactions_with_actions(
         tx_batch_request.tx_signing_requests.len()
     ));
 
+    // dependsOn indexes the batch as sent, so its graph is checked before duplicates are dropped
+    if let Err(e) = tx_dependencies::check_dependencies(&tx_batch_request) {
+        let error_msg = e.to_string();
+        logs.push(error_msg.clone());
+        return Ok(TransactionSignResult::failed_with_code(logs, error_msg, e.code()));
+    }
+
+    // Duplicates are dropped, and a batch changed since its preview is refused, before anything
+    // else looks at the batch
+    match batch_preview::prepare_batch(&mut tx_batch_request) {
+        Ok(preparation) => {
+            let removed = preparation
+                .as_ref()
+                .and_then(|p| p.removed_duplicate_indexes.as_ref());
+            if let Some(removed) = removed.filter(|removed| !removed.is_empty()) {
+                logs.push(format!("Dropped duplicate transactions at indexes {:?}", removed));
+            }
+            tx_batch_request.batch_preparation = preparation;
+        }
+        Err((code, error_msg)) => {
+            logs.push(error_msg.clone());
+            return Ok(TransactionSignResult::failed_with_code(logs, error_msg, code));
+        }
+    }
+
+    // Overrides off the allowed origins fail before the user is prompted
+    let rpc_endpoints = match resolve_rpc_endpoints(
+        &tx_batch_request.rpc_call,
+        tx_batch_request.rpc_overrides.as_ref(),
+        tx_batch_request.worker_policy.as_ref(),
+    ) {
+        Ok(endpoints) => endpoints,
+        Err(e) => {
+            logs.push(e.to_string());
+            return Ok(TransactionSignResult::failed_with_code(
+                logs,
+                e.to_string(),
+                e.code(),
+            ));
+        }
+    };
+
+    let verification_rpc = NearRpcClient::new(&rpc_endpoints.verification);
+
+    // Storage deposits are inserted before anything else reads the actions, so the checks,
+    // deposit totals and digest below all include them (see storage_deposit.rs). A
+    // confirmation made elsewhere was computed over the batch as sent.
+    if tx_batch_request.auto_storage_deposit && preset.is_none() {
+        match storage_deposit::insert_storage_deposits(
+            &verification_rpc,
+            &mut tx_batch_request.tx_signing_requests,
+        )
+        .await
+        {
+            Ok(insertions) => {
+                for insertion in &insertions {
+                    logs.push(format!(
+                        "Transaction {}: added storage_deposit of {} yocto on {} for {}",
+                        insertion.tx_index + 1,
+                        insertion.deposit_yocto,
+                        insertion.contract_id,
+                        insertion.account_id
+                    ));
+                }
+                tx_batch_request.auto_inserted_actions = storage_deposit::auto_inserted_actions(
+                    &insertions,
+                    tx_batch_request.tx_signing_requests.len(),
+                );
+            }
+            Err(e) => {
+                let error_msg = format!("Storage registration check failed: {}", e);
+                logs.push(error_msg.clone());
+                return Ok(TransactionSignResult::failed_with_code(logs, error_msg, e.code()));
+            }
+        }
+    }
+
+    // A batch the function-call signing key's allowance cannot cover fails before the user is
+    // prompted, instead of being rejected by the chain. Actions the worker cannot sign (say, a
+    // DelegateAction) are refused before the user is prompted, not after.
+    let mut parsed_receivers_and_actions: Vec<(String, Vec<ActionParams>)> = Vec::new();
+    for (index, tx) in tx_batch_request.tx_signing_requests.iter().enumerate() {
+        match tx.parsed_actions() {
+            Ok(actions) => parsed_receivers_and_actions.push((tx.receiver_id.clone(), actions)),
+            Err(e) => {
+                let error_msg =
+                    format!("Transaction {}: Failed to parse actions: {}", index + 1, e);
+                logs.push(error_msg.clone());
+                return Ok(TransactionSignResult::failed_with_code(
+                    logs,
+                    error_msg,
+                    SignerErrorCode::InvalidConfig,
+                ));
+            }
+        }
+    }
+
+    // FunctionCall args that fail their registered schema are refused before the user is
+    // prompted, with every violation listed
+    if let Err(e) = check_batch_args(
+        tx_batch_request.worker_policy.as_ref(),
+        &parsed_receivers_and_actions,
+    ) {
+        let error_msg = e.to_string();
+        logs.push(error_msg.clone());
+        return Ok(TransactionSignResult::failed_with_code(logs, error_msg, e.code()));
+    }
+
+    // Full access keys blocked by policy, or without a fingerprint to show, are refused before
+    // the user is prompted (see action_firewall.rs)
+    if let Err(e) = check_high_risk_actions(
+        tx_batch_request.worker_policy.as_ref(),
+        account.as_str(),
+        &parsed_receivers_and_actions,
+    ) {
+        let error_msg = e.to_string();
+        logs.push(error_msg.clone());
+        return Ok(match e.code() {
+            Some(code) => TransactionSignResult::failed_with_code(logs, error_msg, code),
+            None => TransactionSignResult::failed(logs, error_msg),
+        });
+    }
+
+    // Under dual control, a second approval whose token is unknown, expired or for another
+    // batch fails before the second approver is prompted (see dual_control.rs)
+    let required_approvals =
+        match dual_control::required_approvals(tx_batch_request.worker_policy.as_ref()) {
+            Ok(required_approvals) => required_approvals,
+            Err(e) => {
+                let error_msg = e.to_string();
+                logs.push(error_msg.clone());
+                return Ok(TransactionSignResult::failed_with_code(logs, error_msg, e.code()));
+            }
+        };
+    let approval_digest = match required_approvals {
+        1 => None,
+        _ => Some(dual_control::approval_digest(&parsed_receivers_and_actions)?),
+    };
+    if let (Some(digest), Some(token)) = (&approval_digest, &tx_batch_request.approval_token) {
+        let now = state::now_ms();
+        if let Err(e) = dual_control::check_pending_approval(&account, token, digest, now) {
+            let error_msg = e.to_string();
+            logs.push(error_msg.clone());
+            return Ok(TransactionSignResult::failed_with_code(logs, error_msg, e.code()));
+        }
+    }
+
+    // Only args can be redacted: the receiver, method, deposit and gas are always shown
+    let redact_paths = match redaction::parse_redact_paths(&tx_batch_request.redact_paths) {
+        Ok(paths) => paths,
+        Err(e) => {
+            let error_msg = e.to_string();
+            logs.push(error_msg.clone());
+            return Ok(TransactionSignResult::failed_with_code(logs, error_msg, e.code()));
+        }
+    };
+
+    // A selected signing key must be on the account, with a permission covering the batch,
+    // before the user is prompted; the summary and allowance checks then apply to that key
+    let selected_key = match tx_batch_request.signing_public_key.clone() {
+        None => None,
+        Some(public_key) => match select_signing_key(
+            &verification_rpc,
+            &tx_batch_request,
+            &public_key,
+            &parsed_receivers_and_actions,
+        )
+        .await
+        {
+            Ok((selected, key)) => {
+                logs.push(format!(
+                    "Signing with {} ({})",
+                    selected.public_key,
+                    key_selection::key_fingerprint(&selected.key_bytes)
+                ));
+                tx_batch_request.transaction_context = Some(key_selection::signing_context(
+                    tx_batch_request.transaction_context.as_ref(),
+                    &selected.public_key,
+                    &key,
+                ));
+                Some(selected)
+            }
+            Err((code, error_msg)) => {
+                logs.push(error_msg.clone());
+                return Ok(TransactionSignResult::failed_with_code(logs, error_msg, code));
+            }
+        },
+    };
+
+    if let Err((code, error_msg)) = check_allowance(
+        tx_batch_request.transaction_context.as_ref(),
+        &parsed_receivers_and_actions,
+        tx_batch_request.worker_policy.as_ref(),
+        &mut logs,
+    ) {
+        logs.push(error_msg.clone());
+        return Ok(TransactionSignResult::failed_with_code(logs, error_msg, code));
+    }
+
     // Step 1: Request user confirmation and credential collection
     let mut confirmation_result_opt: Option<ConfirmationResult> = None;
 
-    // Log transaction details for validation
-    for (i, tx) in tx_batch_request.tx_signing_requests.iter().enumerate() {
+    // Log transaction details for validation (staging calls would log the whole contract)
+    if let Some(manifest) = &tx_batch_request.deploy_manifest {
         logs.push(format!(
-            "Transaction {}: {} -> {} ({} actions)",
-            i + 1,
-            tx.near_account_id,
-            tx.receiver_id,
-            tx.actions
+            "{} (code hash {})",
+            manifest.description(),
+            manifest.total_hash
         ));
+    } else {
+        for (i, tx) in tx_batch_request.tx_signing_requests.iter().enumerate() {
+            logs.push(format!(
+                "Transaction {}: {} -> {} ({} actions)",
+                i + 1,
+                tx.near_account_id,
+                tx.receiver_id,
+                tx.actions
+            ));
+        }
     }
     send_progress_message(
         ProgressMessageType::ExecuteActionsProgress,
@@ -226,7 +742,7 @@ pub async fn handle_sign_transactions_with_actions(
             &serde_json::json!({
                 "step": 1,
                 "total": 4,
-                "transaction_count": tx_batch_request.tx_signing_requests.len()
+                "transactionCount": tx_batch_request.tx_signing_requests.len()
             })
             .to_string(),
         ),
@@ -239,11 +755,101 @@ pub async fn handle_sign_transactions_with_actions(
         confirmation_config
     ));
 
-    let c = request_user_confirmation(&tx_batch_request, &mut logs)
+    // Flag receivers absent from the account's recent history; an unavailable indexer leaves
+    // the flags unknown instead of blocking the request
+    let first_time_receivers = match &preset {
+        Some(preset) => preset.first_time_receivers.clone(),
+        None => annotate_first_time_receivers(&tx_batch_request).await,
+    };
+
+    // With a peer port to the VRF worker, the challenge comes from there (checked against the
+    // session's VRF key) rather than from the main thread
+    phases.enter(SignPhase::Challenge);
+    let peer_challenge = match preset {
+        Some(_) => None,
+        None => match peer_channel::request_peer_challenge(
+            &tx_batch_request.tx_signing_requests[0].near_account_id,
+        )
+        .await
+        {
+            Ok(challenge) => challenge,
+            Err((code, error_msg)) => {
+                logs.push(error_msg.clone());
+                return Ok(TransactionSignResult::failed_with_code(logs, error_msg, code));
+            }
+        },
+    };
+    if peer_challenge.is_some() {
+        logs.push("VRF challenge received over the peer port".to_string());
+    }
+
+    phases.enter(SignPhase::Confirmation);
+    let c = match preset {
+        Some(preset) => {
+            logs.push("Using a confirmation made on another device".to_string());
+            preset.confirmation
+        }
+        None => request_user_confirmation(
+            &tx_batch_request,
+            &first_time_receivers,
+            peer_challenge.as_ref(),
+            &mut logs,
+        )
         .await
-        .map_err(|e| format!("Confirmation request failed: {}", e))?;
+        .map_err(|e| format!("Confirmation request failed: {}", e))?,
+    };
+
+    // Release this request's confirmation nonce and nonce reservations on every exit path
+    let request_id = c.request_id.clone();
+    let _request_guard = PendingRequestGuard::new(&account, &request_id);
+    if let Some(reserved_nonces) = c.reserved_nonces.clone() {
+        state::reserve_nonces(&account, &request_id, reserved_nonces);
+    }
+
+    if let Some(collection_error) = &c.collection_error {
+        phases.enter(SignPhase::CredentialCollection);
+        let error_code = handle_collection_error(
+            &account,
+            &request_id,
+            "signTransactionsWithActions",
+            collection_error,
+            &mut logs,
+        );
+        let error_msg = format!("Credential collection failed: {}", collection_error.message);
+        return Ok(TransactionSignResult::failed_with_code(
+            logs, error_msg, error_code,
+        ));
+    }
+
+    // The main thread refuses to prompt for a challenge already past the acceptance window,
+    // and drops confirmations that arrive after the confirmation window closed; auto-proceed
+    // confirmations without a verified countdown are declined by the worker
+    let refusal_code = [
+        SignerErrorCode::ChallengeExpired,
+        SignerErrorCode::ConfirmationExpired,
+        SignerErrorCode::CountdownNotDisplayed,
+        SignerErrorCode::UserDeclined,
+    ]
+    .into_iter()
+    .find(|code| c.error_code.as_deref() == Some(code.as_str()));
+    if let (false, Some(code)) = (c.confirmed, refusal_code) {
+        let error_msg = c
+            .error
+            .clone()
+            .unwrap_or_else(|| format!("{} before confirmation", code.as_str()));
+        state::record_audit(
+            &account,
+            &request_id,
+            "signTransactionsWithActions",
+            code.as_str(),
+            Some(error_msg.clone()),
+        );
+        logs.push(error_msg.clone());
+        return Ok(TransactionSignResult::failed_with_code(logs, error_msg, code));
+    }
 
     if !c.confirmed {
+        state::record_audit(&account, &request_id, "signTransactionsWithActions", "Rejected", None);
         return Ok(TransactionSignResult::failed(
             logs,
             "Transaction rejected by user".to_string(),
@@ -253,15 +859,195 @@ pub async fn handle_sign_transactions_with_actions(
         "User confirmation received with digest: {}",
         c.intent_digest.clone().unwrap_or_default()
     ));
+    if let Some(requested) = &peer_challenge {
+        if let Err((code, error_msg)) =
+            peer_channel::check_decision_challenge(requested, c.vrf_challenge.as_ref())
+        {
+            state::record_audit(
+                &account,
+                &request_id,
+                "signTransactionsWithActions",
+                code.as_str(),
+                Some(error_msg.clone()),
+            );
+            logs.push(error_msg.clone());
+            return Ok(TransactionSignResult::failed_with_code(logs, error_msg, code));
+        }
+    }
 
     // Log validation success
     if tx_batch_request.confirmation_config.is_some() {
         logs.push("[WASM] User has confirmed transaction details".to_string());
     }
 
+    // Re-check the confirmation window and challenge freshness against the chain height fetched
+    // for signing; the contract would reject an expired challenge anyway, this fails before the
+    // key is decrypted (the request guard releases the reserved nonces)
+    let expiry_policy = challenge_expiry_policy(
+        tx_batch_request.worker_policy.as_ref(),
+        &tx_batch_request.rpc_call.near_rpc_url,
+    );
+    let challenge = c.vrf_challenge.as_ref().zip(c.transaction_context.as_ref());
+    match check_request_expiry(
+        c.confirmation_expires_at_ms,
+        state::now_ms(),
+        challenge,
+        &expiry_policy,
+    ) {
+        Ok(Some(estimate)) => logs.push(format!(
+            "Challenge is {} blocks old, expires in ~{}s",
+            estimate.challenge_age_blocks, estimate.estimated_expiry_seconds
+        )),
+        Ok(None) if challenge.is_some() => logs.push(
+            "Challenge has a generic anchor; NEAR block expiry check skipped".to_string(),
+        ),
+        Ok(None) => {}
+        Err((code, error_msg)) => {
+            state::record_audit(
+                &account,
+                &request_id,
+                "signTransactionsWithActions",
+                code.as_str(),
+                Some(error_msg.clone()),
+            );
+            logs.push(error_msg.clone());
+            return Ok(TransactionSignResult::failed_with_code(logs, error_msg, code));
+        }
+    }
+
+    // Elevated operations (full access AddKey, and those the policy adds) need a credential
+    // collected within the elevation window, not one from an earlier prompt (see elevation.rs)
+    if let Err(e) = elevation::check_batch_elevation(
+        tx_batch_request.worker_policy.as_ref(),
+        &parsed_receivers_and_actions,
+        c.credential_collected_at_ms,
+        state::now_ms(),
+    ) {
+        let error_msg = e.to_string();
+        state::record_audit(
+            &account,
+            &request_id,
+            "signTransactionsWithActions",
+            e.code().as_str(),
+            Some(error_msg.clone()),
+        );
+        logs.push(error_msg.clone());
+        return Ok(TransactionSignResult::failed_with_code(logs, error_msg, e.code()));
+    }
+
+    // The selected key may have been deleted, or used, while the user confirmed: read it again
+    // and sign on its nonce and allowance (the block reference stays the one fetched for signing)
+    let c = match &selected_key {
+        None => c,
+        Some(selected) => match fetch_selected_key(
+            &verification_rpc,
+            &tx_batch_request.tx_signing_requests[0].near_account_id,
+            &selected.public_key,
+            &parsed_receivers_and_actions,
+        )
+        .await
+        {
+            Ok(key) => ConfirmationResult {
+                transaction_context: Some(key_selection::signing_context(
+                    c.transaction_context.as_ref(),
+                    &selected.public_key,
+                    &key,
+                )),
+                ..c
+            },
+            Err((code, error_msg)) => {
+                state::record_audit(
+                    &account,
+                    &request_id,
+                    "signTransactionsWithActions",
+                    code.as_str(),
+                    Some(error_msg.clone()),
+                );
+                logs.push(error_msg.clone());
+                return Ok(TransactionSignResult::failed_with_code(logs, error_msg, code));
+            }
+        },
+    };
+
+    // The allowance may have been spent by another request while the user confirmed: check it
+    // again against the context fetched for signing
+    if let Err((code, error_msg)) = check_allowance(
+        c.transaction_context.as_ref(),
+        &parsed_receivers_and_actions,
+        tx_batch_request.worker_policy.as_ref(),
+        &mut logs,
+    ) {
+        state::record_audit(
+            &account,
+            &request_id,
+            "signTransactionsWithActions",
+            code.as_str(),
+            Some(error_msg.clone()),
+        );
+        logs.push(error_msg.clone());
+        return Ok(TransactionSignResult::failed_with_code(logs, error_msg, code));
+    }
+
+    // The pre-sign hook runs after the user confirms (so it sees the confirmed intent) and
+    // before the key is decrypted; a denial exits through the request guard like a rejection
+    let worker_policy = tx_batch_request.worker_policy.clone().unwrap_or_default();
+    // The summary blocks were generated from the allowance in the request's context
+    let summary_allowance = allowance::allowance_estimate(
+        tx_batch_request.transaction_context.as_ref(),
+        &parsed_receivers_and_actions,
+        Some(&worker_policy),
+    )?;
+    let summary_policy = SummaryPolicy::from_worker_policy(Some(&worker_policy))?
+        .with_deadline(tx_batch_request.valid_until_block_height)
+        .with_allowance(summary_allowance)
+        .with_signing_key(selected_key.as_ref().map(|selected| selected.key_bytes))
+        .with_redactions(redact_paths.clone())
+        .with_locale(SpeechLocale::from_config(tx_batch_request.confirmation_config.as_ref()))
+        .with_conditional(tx_batch_request.conditional_batch.is_some())
+        .with_dependencies(tx_dependencies::depends_on(&tx_batch_request.tx_signing_requests))
+        .with_auto_inserted_actions(tx_batch_request.auto_inserted_actions.clone());
+    let intent_digest = compute_confirmation_intent_digest(
+        &parsed_receivers_and_actions,
+        &first_time_receivers,
+        tx_batch_request.deploy_manifest.as_ref(),
+        Some(&summary_policy),
+    )
+    .map_err(|e| format!("Failed to compute intent digest: {}", e))?;
+    tx_diff::record_confirmed_intent(
+        &account,
+        &intent_digest,
+        &parsed_receivers_and_actions,
+    );
+
+    if worker_policy.pre_sign_hook {
+        let hook_request = PreSignHookRequest {
+            request_id: request_id.clone(),
+            tx_summary: create_transaction_summary_from_parsed(&parsed_receivers_and_actions)?,
+            digest: intent_digest.clone(),
+        };
+        let response = hooks::request_pre_sign_decision(
+            &hook_request,
+            hooks::hook_timeout_ms(&worker_policy),
+        )
+        .await;
+        if let Err((error_code, error_msg)) = hooks::enforce_pre_sign_decision(
+            &account,
+            &request_id,
+            "signTransactionsWithActions",
+            response,
+        ) {
+            logs.push(error_msg.clone());
+            return Ok(TransactionSignResult::failed_with_code(
+                logs, error_msg, error_code,
+            ));
+        }
+        logs.push("Pre-sign hook allowed the request".to_string());
+    }
+
     confirmation_result_opt = Some(c);
 
     // Step 2: Extract credentials for verification
+    phases.enter(SignPhase::CredentialCollection);
     logs.push("Extracting credentials for contract verification...".to_string());
     send_progress_message(
         ProgressMessageType::ExecuteActionsProgress,
@@ -285,6 +1071,27 @@ pub async fn handle_sign_transactions_with_actions(
         .and_then(|r| r.credential.clone())
         .ok_or_else(|| "Missing authentication credential from confirmation".to_string())?;
 
+    // With a forwarded credential public key, a bad assertion fails here instead of
+    // costing a contract verification RPC call
+    match verify_assertion_locally(&credential_json_value) {
+        Ok(LocalAssertionCheck::Verified) => {
+            logs.push("Assertion signature verified locally".to_string())
+        }
+        Ok(LocalAssertionCheck::UnsupportedAlgorithm(algorithm)) => logs.push(format!(
+            "Assertion algorithm {} not verifiable locally; left to the contract",
+            algorithm
+        )),
+        Ok(LocalAssertionCheck::NoPublicKey) => {}
+        Err(error_msg) => {
+            logs.push(error_msg.clone());
+            return Ok(TransactionSignResult::failed_with_code(
+                logs,
+                error_msg,
+                SignerErrorCode::AssertionSignatureInvalid,
+            ));
+        }
+    }
+
     // If credential is serde_json::Value, convert; else assume structured already
     let credential = if let Ok(c) = serde_json::from_value::<WebAuthnAuthenticationCredentialStruct>(
         credential_json_value.clone(),
@@ -306,7 +1113,15 @@ pub async fn handle_sign_transactions_with_actions(
         )
     };
 
+    // The credential must have signed the challenge derived from this VRF output
+    if let Err(e) = verify_vrf_challenge_binding(&vrf_challenge, &credential.client_data_json) {
+        let error_msg = format!("VRF challenge binding failed: {}", e);
+        logs.push(error_msg.clone());
+        return Ok(TransactionSignResult::failed(logs, error_msg));
+    }
+
     // Step 3: Contract verification using confirmed credentials (if preConfirm) or provided ones
+    phases.enter(SignPhase::Verification);
     logs.push(format!(
         "Starting contract verification for {}",
         tx_batch_request.rpc_call.contract_id
@@ -326,9 +1141,10 @@ pub async fn handle_sign_transactions_with_actions(
     let webauthn_auth = WebAuthnAuthenticationCredential::from(&credential);
 
     // Perform contract verification once for the entire batch
+    logs.push(format!("Verifying through {}", rpc_endpoints.verification));
     let verification_result = match verify_authentication_response_rpc_call(
+        &verification_rpc,
         &tx_batch_request.rpc_call.contract_id,
-        &tx_batch_request.rpc_call.near_rpc_url,
         vrf_data,
         webauthn_auth,
     )
@@ -367,6 +1183,14 @@ pub async fn handle_sign_transactions_with_actions(
                 &e.to_string(),
             );
 
+            state::record_audit_with_rpc_endpoints(
+                &account,
+                &request_id,
+                "signTransactionsWithActions",
+                "VerificationFailed",
+                Some(error_msg.clone()),
+                Some(rpc_endpoints),
+            );
             return Ok(TransactionSignResult::failed(logs, error_msg));
         }
     };
@@ -384,10 +1208,112 @@ pub async fn handle_sign_transactions_with_actions(
             "verification failed",
         );
 
+        state::record_audit_with_rpc_endpoints(
+            &account,
+            &request_id,
+            "signTransactionsWithActions",
+            "VerificationFailed",
+            Some(error_msg.clone()),
+            Some(rpc_endpoints),
+        );
         return Ok(TransactionSignResult::failed(logs, error_msg));
     }
 
     logs.push("Contract verification successful".to_string());
+    phases.enter(SignPhase::Signing);
+
+    // Dual control: a first approval is kept for another passkey instead of signing, and a
+    // second approval must come from a credential other than the first one's
+    let approvals_detail = match (&approval_digest, &tx_batch_request.approval_token) {
+        (None, _) => None,
+        (Some(digest), None) => {
+            let awaiting = dual_control::record_first_approval(
+                &account,
+                &request_id,
+                digest,
+                &credential.raw_id,
+            )?;
+            let code = SignerErrorCode::AwaitingSecondApproval;
+            let error_msg = format!(
+                "{}: first approval by {} recorded; another passkey must approve before {}",
+                code, awaiting.first_approver, awaiting.expires_at_ms
+            );
+            state::record_audit_with_rpc_endpoints(
+                &account,
+                &request_id,
+                "signTransactionsWithActions",
+                code.as_str(),
+                Some(format!("approvals: {}", awaiting.first_approver)),
+                Some(rpc_endpoints),
+            );
+            logs.push(error_msg.clone());
+            return Ok(TransactionSignResult {
+                awaiting_second_approval: Some(awaiting),
+                ..TransactionSignResult::failed_with_code(logs, error_msg, code)
+            });
+        }
+        (Some(digest), Some(token)) => match dual_control::complete_approval(
+            &account,
+            token,
+            digest,
+            &credential.raw_id,
+            state::now_ms(),
+        ) {
+            Ok(first) => {
+                let detail = dual_control::approvals_detail(&first, &credential.raw_id);
+                logs.push(format!("Second approval completes the first ({})", detail));
+                Some(detail)
+            }
+            Err(e) => {
+                let error_msg = e.to_string();
+                state::record_audit_with_rpc_endpoints(
+                    &account,
+                    &request_id,
+                    "signTransactionsWithActions",
+                    e.code().as_str(),
+                    Some(error_msg.clone()),
+                    Some(rpc_endpoints),
+                );
+                logs.push(error_msg.clone());
+                return Ok(TransactionSignResult::failed_with_code(logs, error_msg, e.code()));
+            }
+        },
+    };
+
+    // A conditional batch is simulated against the chain as it is now, right before signing,
+    // and cut to the transactions whose earlier steps all simulated successfully
+    let conditional_entries = match &tx_batch_request.conditional_batch {
+        None => None,
+        Some(options) => {
+            let simulations =
+                conditional_batch::simulate_batch(&verification_rpc, &parsed_receivers_and_actions)
+                    .await;
+            match conditional_batch::plan_entries(simulations, options) {
+                Ok(entries) => {
+                    let signed = conditional_batch::signed_count(&entries);
+                    logs.push(format!(
+                        "Conditional batch: signing {} of {} transactions",
+                        signed,
+                        entries.len()
+                    ));
+                    tx_batch_request.tx_signing_requests.truncate(signed);
+                    parsed_receivers_and_actions.truncate(signed);
+                    Some(entries)
+                }
+                Err((code, error_msg)) => {
+                    state::record_audit(
+                        &account,
+                        &request_id,
+                        "signTransactionsWithActions",
+                        code.as_str(),
+                        Some(error_msg.clone()),
+                    );
+                    logs.push(error_msg.clone());
+                    return Ok(TransactionSignResult::failed_with_code(logs, error_msg, code));
+                }
+            }
+        }
+    };
 
     // Step 4: Batch transaction signing (confirmation and verification completed)
     logs.push(format!(
@@ -400,7 +1326,7 @@ pub async fn handle_sign_transactions_with_actions(
         ProgressMessageType::ExecuteActionsProgress,
         ProgressStep::TransactionSigningProgress,
         "Decrypting private key and signing transactions...",
-        Some(&serde_json::json!({"step": 4, "total": 4, "transaction_count": tx_batch_request.tx_signing_requests.len()}).to_string())
+        Some(&serde_json::json!({"step": 4, "total": 4, "transactionCount": tx_batch_request.tx_signing_requests.len()}).to_string())
     );
 
     // Get PRF output from confirmation result (mandatory now)
@@ -409,27 +1335,94 @@ pub async fn handle_sign_transactions_with_actions(
         .and_then(|r| r.prf_output.clone())
         .ok_or_else(|| "Missing PRF output from confirmation".to_string())?;
 
-    let decryption = Decryption::new(
-        chacha20_prf_output,
-        tx_batch_request
-            .decryption
-            .encrypted_private_key_data
-            .clone(),
-        tx_batch_request.decryption.encrypted_private_key_iv.clone(),
-    );
+    let decryption = match selected_key {
+        Some(selected) => Decryption {
+            expected_public_key: Some(selected.public_key),
+            ..Decryption::new(
+                chacha20_prf_output,
+                selected.candidate.encrypted_private_key_data,
+                selected.candidate.encrypted_private_key_iv,
+            )
+        },
+        None => Decryption::new(
+            chacha20_prf_output,
+            tx_batch_request
+                .decryption
+                .encrypted_private_key_data
+                .clone(),
+            tx_batch_request.decryption.encrypted_private_key_iv.clone(),
+        ),
+    };
 
     // Process all transactions using the shared verification and decryption
     let tx_count = tx_batch_request.tx_signing_requests.len();
     let confirmation_result = confirmation_result_opt
         .as_ref()
         .ok_or_else(|| "Confirmation result not available".to_string())?;
-    let result = sign_near_transactions_with_actions_impl(
+    let broadcast_rpc = tx_batch_request.broadcast.then(|| {
+        NearRpcClient::new(
+            rpc_endpoints
+                .broadcast
+                .as_deref()
+                .unwrap_or(&rpc_endpoints.verification),
+        )
+    });
+    let mut result = sign_near_transactions_with_actions_impl(
         tx_batch_request.tx_signing_requests,
         &decryption,
         confirmation_result,
+        broadcast_rpc.as_ref(),
+        tx_batch_request.valid_until_block_height,
+        tx_batch_request.idempotency_key.as_deref(),
         logs,
     )
     .await?;
+    if result.success && broadcast_rpc.is_none() {
+        result.broadcast_rpc_url = rpc_endpoints.broadcast.clone();
+        result.valid_until_block_height = tx_batch_request.valid_until_block_height;
+    }
+    if result.success {
+        result.removed_duplicate_indexes = tx_batch_request
+            .batch_preparation
+            .and_then(|p| p.removed_duplicate_indexes);
+    }
+    result.conditional_entries = conditional_entries;
+
+    state::record_audit_with_rpc_endpoints(
+        &account,
+        &request_id,
+        "signTransactionsWithActions",
+        if result.success { "Signed" } else { "Failed" },
+        match &result.error {
+            Some(error) => Some(error.clone()),
+            // The full hashes let support check a redacted value later, and both approvers'
+            // fingerprints who approved under dual control
+            None => [
+                approvals_detail,
+                redaction::audit_detail(&parsed_receivers_and_actions, &redact_paths),
+            ]
+            .into_iter()
+            .flatten()
+            .reduce(|approvals, redactions| format!("{}; {}", approvals, redactions)),
+        },
+        Some(rpc_endpoints),
+    );
+
+    if result.success {
+        for (receiver_id, _) in &parsed_receivers_and_actions {
+            state::note_recent_receiver(&account, receiver_id);
+        }
+    }
+
+    if worker_policy.post_sign_hook && result.success {
+        for tx_hash in result.transaction_hashes.iter().flatten() {
+            hooks::send_post_sign_notification(&PostSignHookNotification {
+                request_id: request_id.clone(),
+                tx_hash: tx_hash.clone(),
+                digest: intent_digest.clone(),
+            });
+        }
+    }
 
     // Send completion progress message
     send_completion_message(
@@ -441,7 +1434,7 @@ pub async fn handle_sign_transactions_with_actions(
                 "step": 4,
                 "total": 4,
                 "success": result.success,
-                "transaction_count": tx_count,
+                "transactionCount": tx_count,
                 "logs": result.logs
             })
             .to_string(),
@@ -451,6 +1444,50 @@ pub async fn handle_sign_transactions_with_actions(
     Ok(result)
 }
 
+/// The account signing every transaction of the batch; a batch mixing signers is refused
+pub(crate) fn batch_account_id(
+    tx_batch_request: &SignTransactionsWithActionsRequest,
+) -> Result<AccountId, String> {
+    let near_account_id = &tx_batch_request
+        .tx_signing_requests
+        .first()
+        .ok_or_else(|| "No transactions provided".to_string())?
+        .near_account_id;
+    if let Some(tx) = tx_batch_request
+        .tx_signing_requests
+        .iter()
+        .find(|tx| tx.near_account_id != *near_account_id)
+    {
+        return Err(format!(
+            "All transactions of a batch must be signed by one account ({} and {})",
+            near_account_id, tx.near_account_id
+        ));
+    }
+    AccountId::new(near_account_id.clone())
+        .map_err(|e| format!("Invalid signer account: {}", e))
+}
+
+/// `firstTimeReceiver` flag per transaction of the batch (all signed by the same account)
+pub(crate) async fn annotate_first_time_receivers(
+    tx_batch_request: &SignTransactionsWithActionsRequest,
+) -> Vec<Option<bool>> {
+    let near_account_id = &tx_batch_request.tx_signing_requests[0].near_account_id;
+    let Ok(account) = batch_account_id(tx_batch_request) else {
+        return vec![None; tx_batch_request.tx_signing_requests.len()];
+    };
+    let indexer_url = tx_batch_request
+        .worker_policy
+        .as_ref()
+        .and_then(|p| p.indexer_url.as_deref());
+    let history = lookup_receiver_history(&account, indexer_url).await;
+    let receiver_ids: Vec<&str> = tx_batch_request
+        .tx_signing_requests
+        .iter()
+        .map(|tx| tx.receiver_id.as_str())
+        .collect();
+    first_time_receiver_flags(near_account_id, &history, &receiver_ids)
+}
+
 /// Internal implementation for batch transaction signing after verification is complete.
 /// This function handles the actual signing logic for multiple transactions using a shared
 /// decrypted private key. It processes each transaction individually, provides detailed logging
@@ -459,14 +1496,21 @@ pub async fn handle_sign_transactions_with_actions(
 /// # Arguments
 /// * `tx_requests` - Array of transaction payloads to sign
 /// * `decryption` - Shared decryption parameters for private key access
+/// * `broadcast_rpc` - Broadcasts each transaction once signed; None leaves broadcasting
+///   to the main thread
+/// * `valid_until_block_height` - Refuses to broadcast once the chain is past this height
+/// * `idempotency_key` - The request's idempotency key, recorded in broadcast journal entries
 /// * `logs` - Existing log entries to append to
 ///
 /// # Returns
 /// * `TransactionSignResult` - Contains batch signing results with individual transaction details
-async fn sign_near_transactions_with_actions_impl(
+pub(crate) async fn sign_near_transactions_with_actions_impl<R: RpcClient>(
     tx_requests: Vec<TransactionPayload>,
     decryption: &Decryption,
     confirmation_result: &ConfirmationResult,
+    broadcast_rpc: Option<&R>,
+    valid_until_block_height: Option<u64>,
+    idempotency_key: Option<&str>,
     mut logs: Vec<String>,
 ) -> Result<TransactionSignResult, String> {
     if tx_requests.is_empty() {
@@ -487,16 +1531,42 @@ async fn sign_near_transactions_with_actions_impl(
     }
 
     logs.push(format!("Processing {} transactions", tx_requests.len()));
-    let signing_key = crate::crypto::decrypt_private_key_with_prf(
+    let (signing_key, key_usage_id) = match crate::crypto::decrypt_stored_private_key_with_identity(
         &first_transaction.near_account_id,
         &decryption.chacha20_prf_output,
         &decryption.encrypted_private_key_data,
         &decryption.encrypted_private_key_iv,
     )
-    .map_err(|e| format!("Decryption failed: {}", e))?;
+    .await
+    {
+        Ok(decrypted) => decrypted,
+        // Wrong passkey vs damaged blob lets the UI choose between "use another passkey"
+        // and device-linking recovery
+        Err(e) => {
+            let error_msg = format!("Decryption failed: {}", e);
+            logs.push(error_msg.clone());
+            return Ok(match e.code() {
+                Some(code) => TransactionSignResult::failed_with_code(logs, error_msg, code),
+                None => TransactionSignResult::failed(logs, error_msg),
+            });
+        }
+    };
 
     logs.push("Private key decrypted successfully".to_string());
 
+    if let Some(expected) = &decryption.expected_public_key {
+        let decrypted = signing_key.verifying_key().to_bytes();
+        if key_selection::parse_public_key(expected).ok() != Some(decrypted) {
+            let error_msg = format!(
+                "The blob tagged {} decrypted to another key (ed25519:{})",
+                expected,
+                bs58::encode(decrypted).into_string()
+            );
+            logs.push(error_msg.clone());
+            return Ok(TransactionSignResult::failed(logs, error_msg));
+        }
+    }
+
     // Process NEAR data from confirmation result
     let transaction_context = confirmation_result
         .transaction_context
@@ -508,10 +1578,17 @@ async fn sign_near_transactions_with_actions_impl(
         .next_nonce
         .parse()
         .map_err(|e| format!("Invalid nonce: {}", e))?;
+    // Replaced, like the nonce, when an InvalidNonce rejection forces a re-sign
+    let mut block_hash = bs58::decode(&transaction_context.tx_block_hash)
+        .into_vec()
+        .map_err(|e| format!("Invalid block hash: {}", e))?;
 
     // Process each transaction
     let mut signed_transactions_wasm = Vec::new();
     let mut transaction_hashes = Vec::new();
+    let mut broadcast_outcomes = Vec::new();
+    // A transaction others depend on that failed on chain: the rest are signed, not broadcast
+    let mut failed_dependency: Option<usize> = None;
 
     for (index, tx_data) in tx_requests.iter().enumerate() {
         logs.push(format!(
@@ -554,50 +1631,152 @@ async fn sign_near_transactions_with_actions_impl(
             }
         };
 
-        // Build and sign transaction
-        let transaction = match build_transaction_with_actions(
-            &tx_data.near_account_id,
-            &tx_data.receiver_id,
-            current_nonce,
-            &bs58::decode(&transaction_context.tx_block_hash)
-                .into_vec()
-                .map_err(|e| format!("Invalid block hash: {}", e))?,
-            &signing_key,
-            actions,
-        ) {
-            Ok(tx) => {
-                logs.push(format!(
-                    "Transaction {}: Built successfully (nonce used: {})",
-                    index + 1,
-                    current_nonce
-                ));
-                tx
-            }
-            Err(e) => {
-                let error_msg = format!(
-                    "Transaction {}: Failed to build transaction: {}",
-                    index + 1,
-                    e
-                );
-                logs.push(error_msg.clone());
-                return Ok(TransactionSignResult::failed(logs, error_msg));
-            }
-        };
-
-        let signed_tx_bytes = match sign_transaction(transaction, &signing_key) {
-            Ok(bytes) => {
-                logs.push(format!("Transaction {}: Signed successfully", index + 1));
-                bytes
-            }
-            Err(e) => {
-                let error_msg = format!(
-                    "Transaction {}: Failed to sign transaction: {}",
-                    index + 1,
-                    e
-                );
-                logs.push(error_msg.clone());
-                return Ok(TransactionSignResult::failed(logs, error_msg));
+        // Build, sign and (with a broadcast client) submit the transaction. An InvalidNonce
+        // rejection rebuilds it on a fresh nonce and block hash, up to
+        // INVALID_NONCE_MAX_RESIGNS times; later transactions continue from the new nonce.
+        let mut resigns = 0;
+        let signed_tx_bytes = loop {
+            let transaction = match build_transaction_with_actions(
+                &tx_data.near_account_id,
+                &tx_data.receiver_id,
+                current_nonce,
+                &block_hash,
+                &signing_key,
+                actions.clone(),
+            ) {
+                Ok(tx) => {
+                    logs.push(format!(
+                        "Transaction {}: Built successfully (nonce used: {})",
+                        index + 1,
+                        current_nonce
+                    ));
+                    tx
+                }
+                Err(e) => {
+                    let error_msg = format!(
+                        "Transaction {}: Failed to build transaction: {}",
+                        index + 1,
+                        e
+                    );
+                    logs.push(error_msg.clone());
+                    return Ok(TransactionSignResult::failed(logs, error_msg));
+                }
+            };
+
+            let signed_tx_bytes = match sign_transaction(transaction, &signing_key) {
+                Ok(bytes) => {
+                    logs.push(format!("Transaction {}: Signed successfully", index + 1));
+                    bytes
+                }
+                Err(e) => {
+                    let error_msg = format!(
+                        "Transaction {}: Failed to sign transaction: {}",
+                        index + 1,
+                        e
+                    );
+                    logs.push(error_msg.clone());
+                    return Ok(TransactionSignResult::failed(logs, error_msg));
+                }
+            };
+
+            let rpc = match broadcast_rpc.filter(|_| failed_dependency.is_none()) {
+                Some(rpc) => rpc,
+                None => break signed_tx_bytes,
+            };
+            if let Some(valid_until) = valid_until_block_height {
+                let deadline_check = match latest_block_height(rpc).await {
+                    Ok(current) => check_deadline(valid_until, current)
+                        .map_err(|e| (e.code(), e.to_string())),
+                    Err(e) => Err((
+                        e.code(),
+                        format!("Failed to fetch the chain height for the deadline: {}", e),
+                    )),
+                };
+                if let Err((code, error)) = deadline_check {
+                    let error_msg = format!("Transaction {}: {}", index + 1, error);
+                    logs.push(error_msg.clone());
+                    // Earlier transactions of the batch are on chain, as for a failed broadcast
+                    return Ok(TransactionSignResult {
+                        transaction_hashes: Some(transaction_hashes),
+                        signed_transactions: Some(signed_transactions_wasm),
+                        broadcast_outcomes: Some(broadcast_outcomes),
+                        ..TransactionSignResult::failed_with_code(logs, error_msg, code)
+                    });
+                }
             }
+            // Journaled first: the transaction may land even if the worker dies before answering
+            emit_journal_entry(&JournalEntry::broadcast(
+                &confirmation_result.request_id,
+                &calculate_transaction_hash(&signed_tx_bytes),
+                &tx_data.near_account_id,
+                idempotency_key,
+            ));
+            let broadcast_error = match broadcast_tx_commit(rpc, &signed_tx_bytes).await {
+                Ok(outcome) => {
+                    logs.push(format!("Transaction {}: Broadcast", index + 1));
+                    if tx_dependencies::outcome_failed(&outcome)
+                        && !tx_dependencies::dependents(&tx_requests, index).is_empty()
+                    {
+                        logs.push(format!(
+                            "Transaction {}: Failed on chain, withholding the rest of the batch",
+                            index + 1
+                        ));
+                        failed_dependency = Some(index);
+                    }
+                    broadcast_outcomes.push(outcome);
+                    break signed_tx_bytes;
+                }
+                Err(RpcErrorKind::InvalidNonce { tx_nonce, ak_nonce })
+                    if resigns < INVALID_NONCE_MAX_RESIGNS =>
+                {
+                    resigns += 1;
+                    logs.push(format!(
+                        "Transaction {}: InvalidNonce (tx nonce {}, access key nonce {}), re-signing",
+                        index + 1,
+                        tx_nonce,
+                        ak_nonce
+                    ));
+                    let public_key = format!(
+                        "ed25519:{}",
+                        bs58::encode(signing_key.verifying_key().as_bytes()).into_string()
+                    );
+                    match refresh_nonce_and_block(rpc, &tx_data.near_account_id, &public_key)
+                        .await
+                    {
+                        Ok((nonce, block)) => {
+                            current_nonce = nonce;
+                            block_hash = bs58::decode(&block.hash)
+                                .into_vec()
+                                .map_err(|e| format!("Invalid block hash: {}", e))?;
+                            logs.push(format!(
+                                "Transaction {}: Rebuilding on block {}",
+                                index + 1,
+                                block.height
+                            ));
+                            continue;
+                        }
+                        Err(e) => e,
+                    }
+                }
+                Err(e) => e,
+            };
+            let error_msg = format!(
+                "Transaction {}: Broadcast failed: {}",
+                index + 1,
+                broadcast_error
+            );
+            logs.push(error_msg.clone());
+            // Earlier transactions of the batch are on chain: report them with the failure
+            return Ok(TransactionSignResult {
+                transaction_hashes: Some(transaction_hashes),
+                signed_transactions: Some(signed_transactions_wasm),
+                broadcast_outcomes: Some(broadcast_outcomes),
+                ..TransactionSignResult::failed_with_code(
+                    logs,
+                    error_msg,
+                    broadcast_error.code(),
+                )
+            });
         };
 
         // Calculate transaction hash from signed transaction bytes (before moving the bytes)
@@ -632,13 +1811,54 @@ async fn sign_near_transactions_with_actions_impl(
         "All {} transactions signed successfully",
         signed_transactions_wasm.len()
     ));
+    if let (Some(key_id), Some(last_tx)) = (&key_usage_id, tx_requests.last()) {
+        // Usage stats are informational: failing to record them never fails the batch
+        if let Err(e) = key_usage::record_signing(
+            key_id,
+            &decryption.chacha20_prf_output,
+            signed_transactions_wasm.len() as u32,
+            &last_tx.receiver_id,
+            &last_tx.near_account_id,
+            state::now_ms(),
+        ) {
+            logs.push(format!("Key usage not recorded: {}", e));
+        }
+    }
     info!("RUST: Batch signing completed successfully");
 
-    Ok(TransactionSignResult::new(
+    let mut result = TransactionSignResult::new(
         true,
         Some(transaction_hashes),
         Some(signed_transactions_wasm),
         logs,
         None,
-    ))
+    );
+    if broadcast_rpc.is_some() {
+        result.broadcast_outcomes = Some(broadcast_outcomes);
+    }
+    if let Some(failed) = failed_dependency {
+        let withheld: Vec<u32> = (failed as u32 + 1..tx_requests.len() as u32).collect();
+        let error_msg = format!(
+            "Transaction {}: Failed on chain, later transactions depend on it; withheld the \
+             transactions at indexes {:?}",
+            failed + 1,
+            withheld
+        );
+        result.logs.push(error_msg.clone());
+        result.success = false;
+        result.error = Some(error_msg);
+        result.withheld_transactions = Some(withheld);
+    }
+    Ok(result)
+}
+
+/// Refuses a send at chain height `current` for a request valid until `valid_until`
+pub(crate) fn check_deadline(valid_until: u64, current: u64) -> Result<(), DeadlineError> {
+    if current > valid_until {
+        return Err(DeadlineError::DeadlineExceeded {
+            valid_until,
+            current,
+        });
+    }
+    Ok(())
 }
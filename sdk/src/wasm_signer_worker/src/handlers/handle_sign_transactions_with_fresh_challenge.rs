@@ -0,0 +1,94 @@
+// ******************************************************************************
+// *                                                                            *
+// *            HANDLER: SIGN TRANSACTIONS WITH FRESH CHALLENGE                 *
+// *                                                                            *
+// ******************************************************************************
+
+use log::info;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::error::SignerErrorCode;
+use crate::handlers::handle_sign_transactions_with_actions::{
+    sign_transactions_with_actions_tracked, SignTransactionsWithActionsRequest,
+    TransactionSignResult,
+};
+use crate::peer_channel;
+use crate::sign_phases::{PhaseTracker, SignPhase};
+use crate::types::progress::{send_progress_message, ProgressMessageType, ProgressStep};
+
+/// The SignTransactionsWithActions payload
+pub type SignTransactionsWithFreshChallengeRequest = SignTransactionsWithActionsRequest;
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SignTransactionsWithFreshChallengeResult {
+    /// The result SignTransactionsWithActions would have returned
+    #[wasm_bindgen(getter_with_clone, js_name = "signResult")]
+    #[serde(flatten)]
+    pub sign_result: TransactionSignResult,
+    /// Phase the request failed in ("challenge", "confirmation", "credentialCollection",
+    /// "verification", ...); None when it succeeded
+    #[wasm_bindgen(getter_with_clone, js_name = "failedPhase")]
+    pub failed_phase: Option<String>,
+}
+
+/// **Handles:** `WorkerRequestType::SignTransactionsWithFreshChallenge`
+/// Runs the SignTransactionsWithActions flow in one round trip: the VRF challenge comes from
+/// the VRF worker over the peer port, then the confirmation and credential collection,
+/// contract verification and signing follow as usual (see sign_phases.rs). Refused before
+/// anything else without a connected peer port; SignTransactionsWithActions keeps the
+/// main-thread challenge path for callers orchestrating the steps themselves.
+///
+/// # Arguments
+/// * `request` - The SignTransactionsWithActions payload
+///
+/// # Returns
+/// * `SignTransactionsWithFreshChallengeResult` - The signing result, with the phase that failed
+pub async fn handle_sign_transactions_with_fresh_challenge(
+    request: SignTransactionsWithFreshChallengeRequest,
+) -> Result<SignTransactionsWithFreshChallengeResult, String> {
+    let failed = |phase: SignPhase, sign_result: TransactionSignResult| {
+        info!("RUST: Fresh-challenge signing failed in {}", phase.as_str());
+        SignTransactionsWithFreshChallengeResult {
+            sign_result,
+            failed_phase: Some(phase.as_str().to_string()),
+        }
+    };
+
+    if peer_channel::peer_session().is_none() {
+        let error_msg = "SignTransactionsWithFreshChallenge needs a VRF peer port \
+                         (CONNECT_PEER_PORT); use SignTransactionsWithActions without one"
+            .to_string();
+        return Ok(failed(
+            SignPhase::Challenge,
+            TransactionSignResult::failed_with_code(
+                vec![error_msg.clone()],
+                error_msg,
+                SignerErrorCode::PeerChallengeUnavailable,
+            ),
+        ));
+    }
+    send_progress_message(
+        ProgressMessageType::ExecuteActionsProgress,
+        ProgressStep::Preparation,
+        "Requesting a fresh VRF challenge from the VRF worker...",
+        None,
+    );
+
+    let phases = PhaseTracker::default();
+    match sign_transactions_with_actions_tracked(request, None, &phases).await {
+        Ok(sign_result) if sign_result.success => Ok(SignTransactionsWithFreshChallengeResult {
+            sign_result,
+            failed_phase: None,
+        }),
+        Ok(sign_result) => Ok(failed(phases.current(), sign_result)),
+        // Errors the granular message reports as a failure response are reported with their
+        // phase, like any other failure
+        Err(error_msg) => Ok(failed(
+            phases.current(),
+            TransactionSignResult::failed(vec![error_msg.clone()], error_msg),
+        )),
+    }
+}
@@ -0,0 +1,351 @@
+// ******************************************************************************
+// *                                                                            *
+// *              HANDLERS: CREATE / EXECUTE SIGNING INTENT                     *
+// *                                                                            *
+// ******************************************************************************
+use crate::action_firewall::check_high_risk_actions;
+use crate::actions::ActionParams;
+use crate::args_schema::check_batch_args;
+use crate::config::{DEFAULT_SIGNING_INTENT_TTL_MS, SIGNING_INTENT_VERSION};
+use crate::encoders::base64_url_encode;
+use crate::error::SigningIntentError;
+use crate::handlers::confirm_tx_details::{
+    challenge_expiry_policy, check_request_expiry, handle_collection_error,
+    request_user_confirmation,
+};
+use crate::handlers::handle_sign_transactions_with_actions::{
+    annotate_first_time_receivers, handle_sign_transactions_with_actions,
+    SignTransactionsWithActionsRequest, TransactionPayload, TransactionSignResult,
+};
+use crate::session_duration;
+use crate::signing_intent::{
+    issue_signing_intent, signing_intent_payload, signing_intent_ttl_ceiling_ms,
+    verify_signing_intent, SigningIntent,
+};
+use crate::state::{self, PendingRequestGuard};
+use crate::types::handlers::{
+    ConfirmationBehavior, ConfirmationConfig, ConfirmationUIMode, RpcCallPayload, RpcOverrides,
+    WorkerPolicy,
+};
+use crate::types::{AccountId, DecryptionPayload};
+use log::info;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSigningIntentRequest {
+    #[wasm_bindgen(getter_with_clone, js_name = "rpcCall")]
+    pub rpc_call: RpcCallPayload,
+    /// Transactions to confirm now and sign later (one signer account)
+    #[wasm_bindgen(getter_with_clone, js_name = "txSigningRequests")]
+    pub tx_signing_requests: Vec<TransactionPayload>,
+    #[wasm_bindgen(getter_with_clone, js_name = "confirmationConfig")]
+    #[serde(default)]
+    pub confirmation_config: Option<ConfirmationConfig>,
+    /// `maxSigningIntentTtlMs` caps ttlMs
+    #[wasm_bindgen(getter_with_clone, js_name = "workerPolicy")]
+    #[serde(default)]
+    pub worker_policy: Option<WorkerPolicy>,
+    /// Intent lifetime (defaults to DEFAULT_SIGNING_INTENT_TTL_MS, within the policy ceiling)
+    #[wasm_bindgen(js_name = "ttlMs")]
+    #[serde(default)]
+    pub ttl_ms: Option<u32>,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSigningIntentResult {
+    /// Opaque token for ExecuteSigningIntent, valid until expiry or the next WipeAllState
+    #[wasm_bindgen(getter_with_clone, js_name = "intentToken")]
+    pub intent_token: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "intentId")]
+    pub intent_id: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
+    pub near_account_id: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "intentDigest")]
+    pub intent_digest: String,
+    #[wasm_bindgen(js_name = "txCount")]
+    pub tx_count: u32,
+    #[wasm_bindgen(js_name = "expiresAtMs")]
+    pub expires_at_ms: f64,
+}
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecuteSigningIntentRequest {
+    /// Token returned by CreateSigningIntent
+    #[wasm_bindgen(getter_with_clone, js_name = "intentToken")]
+    pub intent_token: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "rpcCall")]
+    pub rpc_call: RpcCallPayload,
+    #[wasm_bindgen(getter_with_clone)]
+    pub decryption: DecryptionPayload,
+    /// The confirmed transactions, unchanged
+    #[wasm_bindgen(getter_with_clone, js_name = "txSigningRequests")]
+    pub tx_signing_requests: Vec<TransactionPayload>,
+    /// Policy at execution time; its TTL ceiling also applies to outstanding intents
+    #[wasm_bindgen(getter_with_clone, js_name = "workerPolicy")]
+    #[serde(default)]
+    pub worker_policy: Option<WorkerPolicy>,
+    #[wasm_bindgen(getter_with_clone, js_name = "rpcOverrides")]
+    #[serde(default)]
+    pub rpc_overrides: Option<RpcOverrides>,
+}
+
+fn generate_intent_id() -> Result<String, String> {
+    let mut id = [0u8; 16];
+    getrandom::getrandom(&mut id)
+        .map_err(|e| format!("Failed to generate signing intent ID: {}", e))?;
+    Ok(base64_url_encode(&id))
+}
+
+/// **Handles:** `WorkerRequestType::CreateSigningIntent`
+/// Runs the normal transaction confirmation and, once the user confirms, returns a token
+/// binding the confirmed batch's digest, signer and expiry under the worker's persisted MAC
+/// key (see signing_intent.rs). Nothing is decrypted or signed, and the nonces reserved for
+/// the confirmation are released.
+///
+/// # Arguments
+/// * `request` - The transactions and confirmation inputs, plus the intent lifetime
+///
+/// # Returns
+/// * `CreateSigningIntentResult` - The intent token, its ID, digest and expiry
+pub async fn handle_create_signing_intent(
+    request: CreateSigningIntentRequest,
+) -> Result<CreateSigningIntentResult, String> {
+    let (near_account_id, intent_digest) = signing_intent_payload(&request.tx_signing_requests)?;
+    let account = AccountId::new(near_account_id.clone())?;
+    let worker_policy = request
+        .worker_policy
+        .map(|policy| policy.for_account(&account));
+    let ttl_ceiling_ms = signing_intent_ttl_ceiling_ms(worker_policy.as_ref());
+    let ttl_ms = match request.ttl_ms {
+        None => DEFAULT_SIGNING_INTENT_TTL_MS.min(ttl_ceiling_ms),
+        Some(ttl_ms) if ttl_ms == 0 || ttl_ms > ttl_ceiling_ms => {
+            return Err(format!(
+                "ttlMs must be between 1 and {} (workerPolicy.maxSigningIntentTtlMs)",
+                ttl_ceiling_ms
+            ));
+        }
+        Some(ttl_ms) => ttl_ms,
+    };
+    let receivers_and_actions: Vec<(String, Vec<ActionParams>)> = request
+        .tx_signing_requests
+        .iter()
+        .map(|tx| (tx.receiver_id.clone(), tx.parsed_actions().unwrap_or_default()))
+        .collect();
+    check_batch_args(worker_policy.as_ref(), &receivers_and_actions).map_err(|e| e.to_string())?;
+    check_high_risk_actions(worker_policy.as_ref(), &near_account_id, &receivers_and_actions)
+        .map_err(|e| e.to_string())?;
+    let key = state::signing_intent_key()?;
+
+    // The confirmation flow reads neither the decryption payload nor RPC overrides
+    let confirm_request = SignTransactionsWithActionsRequest {
+        rpc_call: request.rpc_call,
+        decryption: DecryptionPayload::new(String::new(), String::new()),
+        tx_signing_requests: request.tx_signing_requests,
+        confirmation_config: request.confirmation_config,
+        worker_policy,
+        idempotency_key: None,
+        rpc_overrides: None,
+        broadcast: false,
+        valid_until_block_height: None,
+        transaction_context: None,
+        signing_public_key: None,
+        deduplicate: false,
+        preview_digest: None,
+        redact_paths: Vec::new(),
+        conditional_batch: None,
+        approval_token: None,
+        auto_storage_deposit: false,
+        batch_preparation: None,
+        deploy_manifest: None,
+        auto_inserted_actions: Vec::new(),
+    };
+    let mut logs: Vec<String> = Vec::new();
+    let first_time_receivers = annotate_first_time_receivers(&confirm_request).await;
+    let c = request_user_confirmation(&confirm_request, &first_time_receivers, None, &mut logs)
+        .await
+        .map_err(|e| format!("Confirmation request failed: {}", e))?;
+
+    let request_id = c.request_id.clone();
+    let _request_guard = PendingRequestGuard::new(&account, &request_id);
+    if let Some(collection_error) = &c.collection_error {
+        let error_code = handle_collection_error(
+            &account,
+            &request_id,
+            "createSigningIntent",
+            collection_error,
+            &mut logs,
+        );
+        return Err(format!(
+            "{}: Credential collection failed: {}",
+            error_code, collection_error.message
+        ));
+    }
+    if !c.confirmed {
+        let outcome = c.error_code.as_deref().unwrap_or("Rejected");
+        state::record_audit(&account, &request_id, "createSigningIntent", outcome, c.error.clone());
+        return Err(match (&c.error_code, &c.error) {
+            (Some(code), Some(error)) => format!("{}: {}", code, error),
+            (Some(code), None) => format!("{}: confirmation refused", code),
+            (None, _) => "Signing intent rejected by user".to_string(),
+        });
+    }
+
+    // The challenge is not reused at execution, only the confirmation window applies
+    let expiry_policy = challenge_expiry_policy(
+        confirm_request.worker_policy.as_ref(),
+        &confirm_request.rpc_call.near_rpc_url,
+    );
+    let now = state::now_ms();
+    if let Err((code, error_msg)) =
+        check_request_expiry(c.confirmation_expires_at_ms, now, None, &expiry_policy)
+    {
+        state::record_audit(
+            &account,
+            &request_id,
+            "createSigningIntent",
+            code.as_str(),
+            Some(error_msg.clone()),
+        );
+        return Err(format!("{}: {}", code, error_msg));
+    }
+
+    let intent = SigningIntent {
+        version: SIGNING_INTENT_VERSION,
+        intent_id: generate_intent_id()?,
+        near_account_id,
+        intent_digest,
+        tx_count: confirm_request.tx_signing_requests.len() as u32,
+        confirmation_request_id: request_id.clone(),
+        issued_at_ms: now,
+        expires_at_ms: now + ttl_ms as f64,
+        session_epoch: state::session_epoch(),
+    };
+    let intent_token = issue_signing_intent(&intent, &key)?;
+    state::record_audit(
+        &account,
+        &request_id,
+        "createSigningIntent",
+        "Created",
+        Some(intent.intent_id.clone()),
+    );
+
+    info!(
+        "RUST: Created signing intent {} for {} ({} transactions, expires in {}ms)",
+        intent.intent_id, intent.near_account_id, intent.tx_count, ttl_ms
+    );
+    Ok(CreateSigningIntentResult {
+        intent_token,
+        intent_id: intent.intent_id,
+        near_account_id: intent.near_account_id,
+        intent_digest: intent.intent_digest,
+        tx_count: intent.tx_count,
+        expires_at_ms: intent.expires_at_ms,
+    })
+}
+
+/// Verifies the token against the supplied transactions and consumes the intent; the
+/// returned error carries the code for an invalid, expired, replayed or mismatched intent
+fn consume_verified_intent(
+    request: &ExecuteSigningIntentRequest,
+) -> Result<SigningIntent, SigningIntentError> {
+    let (near_account_id, intent_digest) = signing_intent_payload(&request.tx_signing_requests)
+        .map_err(SigningIntentError::PayloadMismatch)?;
+    let account =
+        AccountId::new(near_account_id.clone()).map_err(SigningIntentError::PayloadMismatch)?;
+    let worker_policy = request
+        .worker_policy
+        .as_ref()
+        .map(|policy| policy.for_account(&account));
+    let key = state::signing_intent_key().map_err(SigningIntentError::Invalid)?;
+    let intent = verify_signing_intent(
+        &request.intent_token,
+        &key,
+        &near_account_id,
+        &intent_digest,
+        state::now_ms(),
+        signing_intent_ttl_ceiling_ms(worker_policy.as_ref()),
+    )?;
+    session_duration::check_epoch("signing intent", intent.session_epoch)
+        .map_err(SigningIntentError::Session)?;
+    if !state::consume_signing_intent(&intent.intent_id, intent.expires_at_ms) {
+        return Err(SigningIntentError::Replayed {
+            intent_id: intent.intent_id,
+        });
+    }
+    Ok(intent)
+}
+
+/// **Handles:** `WorkerRequestType::ExecuteSigningIntent`
+/// Signs a batch confirmed earlier with CreateSigningIntent, without confirmation UI. The
+/// token's MAC, expiry and digest are checked against the supplied transactions first; then
+/// the batch goes through the SignTransactionsWithActions flow with the UI skipped, which
+/// fetches a fresh nonce and block hash and collects the PRF output. An intent executes once:
+/// it is released again only when nothing was signed, so a failed attempt can be retried.
+///
+/// # Arguments
+/// * `request` - The intent token, the confirmed transactions and the signing inputs
+///
+/// # Returns
+/// * `TransactionSignResult` - Signed transactions, or a coded failure
+pub async fn handle_execute_signing_intent(
+    request: ExecuteSigningIntentRequest,
+) -> Result<TransactionSignResult, String> {
+    let intent = match consume_verified_intent(&request) {
+        Ok(intent) => intent,
+        Err(e) => {
+            info!("RUST: Refused signing intent: {}", e);
+            return Ok(TransactionSignResult::failed_with_code(
+                vec![e.to_string()],
+                e.to_string(),
+                e.code(),
+            ));
+        }
+    };
+    info!(
+        "RUST: Executing signing intent {} for {}",
+        intent.intent_id, intent.near_account_id
+    );
+
+    let result = handle_sign_transactions_with_actions(SignTransactionsWithActionsRequest {
+        rpc_call: request.rpc_call,
+        decryption: request.decryption,
+        tx_signing_requests: request.tx_signing_requests,
+        confirmation_config: Some(ConfirmationConfig::new(
+            ConfirmationUIMode::Skip,
+            ConfirmationBehavior::AutoProceed,
+        )),
+        worker_policy: request.worker_policy,
+        idempotency_key: None,
+        rpc_overrides: request.rpc_overrides,
+        broadcast: false,
+        valid_until_block_height: None,
+        transaction_context: None,
+        signing_public_key: None,
+        deduplicate: false,
+        preview_digest: None,
+        redact_paths: Vec::new(),
+        conditional_batch: None,
+        approval_token: None,
+        auto_storage_deposit: false,
+        batch_preparation: None,
+        deploy_manifest: None,
+        auto_inserted_actions: Vec::new(),
+    })
+    .await;
+
+    let signed_any = matches!(
+        &result,
+        Ok(r) if r.signed_transactions.as_ref().is_some_and(|txs| !txs.is_empty())
+    );
+    if !signed_any {
+        state::release_signing_intent(&intent.intent_id);
+    }
+    result
+}
@@ -0,0 +1,121 @@
+// ******************************************************************************
+// *                                                                            *
+// *                       HANDLER: SUBMIT TO RELAYER                           *
+// *                                                                            *
+// ******************************************************************************
+use crate::contract_args::{registration_args_json, ContractInterfaceVersion};
+use crate::encoders::base64_url_decode;
+use crate::handlers::confirm_tx_details::generate_request_id;
+use crate::operation_journal::{emit_journal_entry, JournalEntry};
+use crate::relayer::{
+    build_relayer_request_body, pinned_relayer, submit_to_relayer, RelayerResult,
+};
+use crate::rpc_calls::{http_json_request, sleep_ms, VrfData};
+use crate::types::handlers::WorkerPolicy;
+use crate::types::{
+    AuthenticatorOptions, SerializedRegistrationCredential, VrfChallenge,
+    WebAuthnRegistrationCredential, WebAuthnRegistrationResponse,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmitToRelayerRequest {
+    /// Account the relayer creates
+    #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
+    pub near_account_id: String,
+    /// Its full-access key ("ed25519:...")
+    #[wasm_bindgen(getter_with_clone, js_name = "newPublicKey")]
+    pub new_public_key: String,
+    #[wasm_bindgen(js_name = "deviceNumber")]
+    #[serde(default)]
+    pub device_number: Option<u8>,
+    #[wasm_bindgen(getter_with_clone, js_name = "vrfChallenge")]
+    pub vrf_challenge: VrfChallenge,
+    #[wasm_bindgen(getter_with_clone)]
+    pub credential: SerializedRegistrationCredential,
+    /// base64url
+    #[wasm_bindgen(getter_with_clone, js_name = "deterministicVrfPublicKey")]
+    pub deterministic_vrf_public_key: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "authenticatorOptions")]
+    #[serde(default)]
+    pub authenticator_options: Option<AuthenticatorOptions>,
+    /// Base64 borsh SignedDelegateAction, forwarded as is
+    #[wasm_bindgen(getter_with_clone, js_name = "signedDelegateAction")]
+    #[serde(default)]
+    pub signed_delegate_action: Option<String>,
+    /// Sent in `relayer.authHeaderName`
+    #[wasm_bindgen(getter_with_clone, js_name = "authToken")]
+    #[serde(default)]
+    pub auth_token: Option<String>,
+    /// `relayer` configures the endpoint, `allowedRpcOrigins` pins it
+    #[wasm_bindgen(getter_with_clone, js_name = "workerPolicy")]
+    #[serde(default)]
+    pub worker_policy: Option<WorkerPolicy>,
+}
+
+/// Relayer request body for `request`
+pub fn relayer_request_body(request: &SubmitToRelayerRequest) -> Result<Value, String> {
+    let vrf_data = VrfData::try_from(&request.vrf_challenge)
+        .map_err(|e| format!("Failed to convert VRF challenge: {:?}", e))?;
+    // PRF outputs stay in the worker
+    let webauthn_registration = WebAuthnRegistrationCredential {
+        id: request.credential.id.clone(),
+        raw_id: request.credential.raw_id.clone(),
+        response: WebAuthnRegistrationResponse {
+            client_data_json: request.credential.response.client_data_json.clone(),
+            attestation_object: request.credential.response.attestation_object.clone(),
+            transports: Some(request.credential.response.transports.clone()),
+        },
+        authenticator_attachment: request.credential.authenticator_attachment.clone(),
+        reg_type: request.credential.credential_type.clone(),
+    };
+    let deterministic_vrf_public_key = base64_url_decode(&request.deterministic_vrf_public_key)
+        .map_err(|e| format!("Failed to decode deterministic VRF public key: {}", e))?;
+
+    let registration_args = registration_args_json(
+        ContractInterfaceVersion::LATEST,
+        vrf_data,
+        webauthn_registration,
+        Some(deterministic_vrf_public_key),
+        request.device_number,
+        request.authenticator_options.clone(),
+    )?;
+    build_relayer_request_body(
+        &registration_args,
+        &request.near_account_id,
+        &request.new_public_key,
+        request.signed_delegate_action.as_deref(),
+    )
+}
+
+/// Posts the registration to the policy's relayer. Relayer failures are reported in the
+/// result's errorKind; a missing or unpinned relayer, or invalid input, is an error.
+pub async fn handle_submit_to_relayer(
+    request: SubmitToRelayerRequest,
+) -> Result<RelayerResult, String> {
+    let relayer = pinned_relayer(request.worker_policy.as_ref())?;
+    let body = relayer_request_body(&request)?;
+    // The relayer may create the account even if this worker never sees its answer
+    emit_journal_entry(&JournalEntry::relayer_submission(
+        &generate_request_id(),
+        &request.near_account_id,
+    ));
+    Ok(submit_to_relayer(
+        &relayer,
+        request.auth_token.as_deref(),
+        body,
+        |method, url, body, headers| async move {
+            let headers: Vec<(&str, &str)> = headers
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect();
+            http_json_request(method, &url, body.as_ref(), &headers).await
+        },
+        sleep_ms,
+    )
+    .await)
+}
@@ -0,0 +1,73 @@
+// ******************************************************************************
+// *                                                                            *
+// *                      HANDLER: SUMMARIZE TRANSACTIONS                       *
+// *                                                                            *
+// ******************************************************************************
+use crate::batch_preview::{canonical_batch_digest, deduplicate_transactions};
+use crate::handlers::handle_sign_transactions_with_actions::TransactionPayload;
+use log::info;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SummarizeTransactionsRequest {
+    /// The batch as it will be sent to SignTransactionsWithActions
+    #[wasm_bindgen(getter_with_clone, js_name = "txSigningRequests")]
+    pub tx_signing_requests: Vec<TransactionPayload>,
+    /// Summarize the batch as SignTransactionsWithActions will with `deduplicate`
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub deduplicate: bool,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SummarizeTransactionsResult {
+    /// Canonical digest of the batch, to send back as `previewDigest`
+    #[wasm_bindgen(getter_with_clone, js_name = "previewDigest")]
+    pub preview_digest: String,
+    /// Transactions in the batch, once deduplicated
+    #[wasm_bindgen(js_name = "transactionCount")]
+    pub transaction_count: u32,
+    /// Indexes of the transactions `deduplicate` drops (empty without it)
+    #[wasm_bindgen(getter_with_clone, js_name = "removedDuplicateIndexes")]
+    pub removed_duplicate_indexes: Vec<u32>,
+}
+
+/// **Handles:** `WorkerRequestType::SummarizeTransactions`
+/// Computes the canonical digest of a batch without signing anything, so it can be shown to
+/// the user and later sent as the batch's `previewDigest` (see batch_preview.rs).
+///
+/// # Arguments
+/// * `request` - The batch, and whether it will be deduplicated
+///
+/// # Returns
+/// * `SummarizeTransactionsResult` - The batch's digest, and the duplicates dropped
+pub async fn handle_summarize_transactions(
+    request: SummarizeTransactionsRequest,
+) -> Result<SummarizeTransactionsResult, String> {
+    let mut txs = request.tx_signing_requests;
+    if txs.is_empty() {
+        return Err("No transactions provided".to_string());
+    }
+    let removed_duplicate_indexes = if request.deduplicate {
+        deduplicate_transactions(&mut txs)
+    } else {
+        Vec::new()
+    };
+    let preview_digest = canonical_batch_digest(&txs)?;
+    info!(
+        "RUST: Summarized {} transaction(s), {} duplicate(s) dropped",
+        txs.len(),
+        removed_duplicate_indexes.len()
+    );
+
+    Ok(SummarizeTransactionsResult {
+        preview_digest,
+        transaction_count: txs.len() as u32,
+        removed_duplicate_indexes,
+    })
+}
@@ -0,0 +1,88 @@
+// ******************************************************************************
+// *                                                                            *
+// *                       HANDLER: VALIDATE ARGS SCHEMAS                       *
+// *                                                                            *
+// ******************************************************************************
+use crate::args_schema::ArgsSchemas;
+use crate::types::handlers::ArgsSchemaEntry;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidateArgsSchemasRequest {
+    /// Entries as they would be sent in `workerPolicy.argsSchemas`
+    #[wasm_bindgen(skip)]
+    pub args_schemas: Vec<ArgsSchemaEntry>,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArgsSchemaErrorEntry {
+    #[wasm_bindgen(getter_with_clone, js_name = "contractId")]
+    pub contract_id: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "methodName")]
+    pub method_name: String,
+    /// JSON pointer into the schema ("" for the schema itself)
+    #[wasm_bindgen(getter_with_clone)]
+    pub path: String,
+    #[wasm_bindgen(getter_with_clone)]
+    pub message: String,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidateArgsSchemasResult {
+    pub valid: bool,
+    #[wasm_bindgen(js_name = "schemaCount")]
+    pub schema_count: u32,
+    #[wasm_bindgen(getter_with_clone)]
+    pub errors: Vec<ArgsSchemaErrorEntry>,
+}
+
+/// **Handles:** `WorkerRequestType::ValidateArgsSchemas`
+/// Compiles args schemas without signing anything, so a schema that does not compile is
+/// reported when the integrator registers it instead of failing a later signing request.
+///
+/// # Arguments
+/// * `request` - The schemas to compile
+///
+/// # Returns
+/// * `ValidateArgsSchemasResult` - Whether every schema compiles, and the errors of those
+///   that do not
+pub async fn handle_validate_args_schemas(
+    request: ValidateArgsSchemasRequest,
+) -> Result<ValidateArgsSchemasResult, String> {
+    let schema_count = request.args_schemas.len() as u32;
+    let errors: Vec<ArgsSchemaErrorEntry> = match ArgsSchemas::compile(&request.args_schemas) {
+        Ok(_) => Vec::new(),
+        Err(errors) => errors
+            .into_iter()
+            .map(|e| ArgsSchemaErrorEntry {
+                contract_id: e.contract_id,
+                method_name: e.method_name,
+                path: e.path,
+                message: e.message,
+            })
+            .collect(),
+    };
+    if errors.is_empty() {
+        info!("RUST: {} args schema(s) compile", schema_count);
+    } else {
+        warn!(
+            "RUST: {} of {} args schema entries do not compile",
+            errors.len(),
+            schema_count
+        );
+    }
+
+    Ok(ValidateArgsSchemasResult {
+        valid: errors.is_empty(),
+        schema_count,
+        errors,
+    })
+}
@@ -0,0 +1,103 @@
+// ******************************************************************************
+// *                                                                            *
+// *                HANDLER: VALIDATE DECRYPTION CAPABILITY                     *
+// *                                                                            *
+// ******************************************************************************
+use crate::crypto::check_stored_private_key_decrypts;
+use crate::encoders::base64_url_decode;
+use crate::error::BlobDecryptError;
+use crate::types::handlers::DecryptionPayload;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidateDecryptionCapabilityRequest {
+    #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
+    pub near_account_id: String,
+    /// PRF output of an authentication earlier in the session (prf.results.first)
+    #[wasm_bindgen(getter_with_clone, js_name = "chacha20PrfOutput")]
+    pub chacha20_prf_output: String,
+    /// The blob to test; candidates are ignored
+    #[wasm_bindgen(getter_with_clone)]
+    pub decryption: DecryptionPayload,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DecryptionCapability {
+    #[wasm_bindgen(js_name = "canDecrypt")]
+    pub can_decrypt: bool,
+    /// Why the blob does not decrypt (None when it does): WrongCredentialForBlob,
+    /// CorruptedCiphertext, DecryptionFailed (legacy blob without key-check value: either one),
+    /// OuterWrapKeyUnavailable or InvalidPlaintext
+    #[wasm_bindgen(getter_with_clone)]
+    pub reason: Option<String>,
+}
+
+impl DecryptionCapability {
+    fn from_outcome(outcome: Result<(), BlobDecryptError>) -> Self {
+        match outcome {
+            Ok(()) => DecryptionCapability {
+                can_decrypt: true,
+                reason: None,
+            },
+            Err(e) => DecryptionCapability {
+                can_decrypt: false,
+                reason: Some(
+                    e.code()
+                        .map(|code| code.definition().code)
+                        .unwrap_or("InvalidPlaintext")
+                        .to_string(),
+                ),
+            },
+        }
+    }
+}
+
+/// **Handles:** `WorkerRequestType::ValidateDecryptionCapability`
+/// Dry-run decryption of a stored key blob with a PRF output the caller already holds, so a
+/// flow can find out before prompting that the blob will not decrypt with this credential
+/// (and the recovery UI can test each stored blob against the presented one). The plaintext
+/// is wiped as soon as it has been checked; the key is never cached or returned.
+///
+/// # Arguments
+/// * `request` - Account ID, PRF output and the blob to test
+///
+/// # Returns
+/// * `DecryptionCapability` - Whether the blob decrypts, and why not
+pub async fn handle_validate_decryption_capability(
+    request: ValidateDecryptionCapabilityRequest,
+) -> Result<DecryptionCapability, String> {
+    if request.near_account_id.is_empty() {
+        return Err("Missing NEAR account ID".to_string());
+    }
+    match base64_url_decode(&request.chacha20_prf_output) {
+        Ok(prf_output) if !prf_output.is_empty() => {}
+        _ => return Err("Missing or invalid PRF output".to_string()),
+    }
+
+    let capability = DecryptionCapability::from_outcome(
+        check_stored_private_key_decrypts(
+            &request.near_account_id,
+            &request.chacha20_prf_output,
+            &request.decryption.encrypted_private_key_data,
+            &request.decryption.encrypted_private_key_iv,
+        )
+        .await,
+    );
+    match &capability.reason {
+        None => info!(
+            "RUST: Key blob of {} decrypts with the presented credential",
+            request.near_account_id
+        ),
+        Some(reason) => warn!(
+            "RUST: Key blob of {} does not decrypt: {}",
+            request.near_account_id, reason
+        ),
+    }
+    Ok(capability)
+}
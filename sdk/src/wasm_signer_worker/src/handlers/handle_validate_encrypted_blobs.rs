@@ -0,0 +1,280 @@
+// ******************************************************************************
+// *                                                                            *
+// *                    HANDLER: VALIDATE ENCRYPTED BLOBS                       *
+// *                                                                            *
+// ******************************************************************************
+use crate::config::{
+    CHACHA20_NONCE_SIZE, CHACHA20_POLY1305_TAG_SIZE, ENCRYPTED_BLOB_VERSION_V1,
+    ENCRYPTED_BLOB_VERSION_V2_LARGE_BLOB, ENCRYPTED_BLOB_VERSION_V3_PASSPHRASE,
+    OUTER_WRAP_AES_GCM_WEBCRYPTO, OUTER_WRAP_TAG_SIZE,
+};
+use crate::crypto::split_key_check_header;
+use crate::encoders::base64_url_decode;
+use crate::key_wrapping::split_key_wrapping_header;
+use crate::outer_wrap::split_outer_wrap;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// An encrypted NEAR key blob as stored in IndexedDB (PasskeyNearKeys `encryptedKeys` store)
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedKeyBlob {
+    /// Caller-chosen identifier echoed back in the report (e.g. "alice.testnet#1")
+    #[wasm_bindgen(getter_with_clone, js_name = "blobId")]
+    #[serde(default)]
+    pub blob_id: Option<String>,
+    #[wasm_bindgen(getter_with_clone, js_name = "encryptedPrivateKeyData")]
+    pub encrypted_private_key_data: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "encryptedPrivateKeyIv")]
+    pub encrypted_private_key_iv: String,
+    /// Encryption format version (absent on blobs stored before versioning: treated as v1)
+    #[serde(default)]
+    pub version: Option<u32>,
+    /// Account the blob is bound to (the record's nearAccountId)
+    #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
+    #[serde(default)]
+    pub near_account_id: Option<String>,
+    /// Outer wrap recorded with the blob ("aes-gcm-webcrypto"), absent for unwrapped blobs
+    #[wasm_bindgen(getter_with_clone, js_name = "outerWrap")]
+    #[serde(default)]
+    pub outer_wrap: Option<String>,
+}
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidateEncryptedBlobsRequest {
+    /// Account the blobs are expected to belong to
+    #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
+    pub near_account_id: String,
+    #[wasm_bindgen(getter_with_clone)]
+    pub blobs: Vec<EncryptedKeyBlob>,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BlobProblem {
+    /// Stable problem code, e.g. "NonceLengthMismatch"
+    #[wasm_bindgen(getter_with_clone)]
+    pub code: String,
+    #[wasm_bindgen(getter_with_clone)]
+    pub message: String,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BlobValidationReport {
+    /// Position of the blob in the request
+    pub index: u32,
+    #[wasm_bindgen(getter_with_clone, js_name = "blobId")]
+    pub blob_id: Option<String>,
+    pub valid: bool,
+    #[wasm_bindgen(getter_with_clone)]
+    pub problems: Vec<BlobProblem>,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidateEncryptedBlobsResult {
+    #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
+    pub near_account_id: String,
+    #[wasm_bindgen(js_name = "allValid")]
+    pub all_valid: bool,
+    #[wasm_bindgen(getter_with_clone)]
+    pub reports: Vec<BlobValidationReport>,
+}
+
+impl BlobProblem {
+    fn new(code: &str, message: String) -> Self {
+        BlobProblem {
+            code: code.to_string(),
+            message,
+        }
+    }
+}
+
+/// Expected nonce length for a known blob version (None for unknown versions)
+fn nonce_size_for_version(version: u32) -> Option<usize> {
+    match version {
+        ENCRYPTED_BLOB_VERSION_V1
+        | ENCRYPTED_BLOB_VERSION_V2_LARGE_BLOB
+        | ENCRYPTED_BLOB_VERSION_V3_PASSPHRASE => Some(CHACHA20_NONCE_SIZE),
+        _ => None,
+    }
+}
+
+/// Structural checks on a single blob. Never decrypts: no PRF output is needed.
+pub fn validate_encrypted_key_blob(
+    blob: &EncryptedKeyBlob,
+    near_account_id: &str,
+) -> Vec<BlobProblem> {
+    let mut problems = Vec::new();
+
+    let version = blob.version.unwrap_or(ENCRYPTED_BLOB_VERSION_V1);
+    let expected_nonce_size = nonce_size_for_version(version);
+    if expected_nonce_size.is_none() {
+        problems.push(BlobProblem::new(
+            "UnknownVersion",
+            format!("Unknown encryption version {}", version),
+        ));
+    }
+
+    if let Some(outer_wrap) = &blob.outer_wrap {
+        if outer_wrap != OUTER_WRAP_AES_GCM_WEBCRYPTO {
+            problems.push(BlobProblem::new(
+                "UnknownOuterWrap",
+                format!("Unknown outer wrap {}", outer_wrap),
+            ));
+        }
+    }
+
+    // The key-wrapping and key-check headers, when present, precede the ciphertext proper.
+    // Under an outer wrap neither is readable, so only the inner length (wrap ciphertext minus
+    // its tag) is checked
+    let ciphertext_len = |data: &[u8]| match split_outer_wrap(data) {
+        Some((_, wrapped)) => wrapped.len() - OUTER_WRAP_TAG_SIZE,
+        None => {
+            let (_, _, data) = split_key_wrapping_header(data);
+            match split_key_check_header(data) {
+                Some((_, ciphertext)) => ciphertext.len(),
+                None => data.len(),
+            }
+        }
+    };
+    if let Ok(data) = base64_url_decode(&blob.encrypted_private_key_data) {
+        let (scheme, _, _) = split_key_wrapping_header(&data);
+        if split_outer_wrap(&data).is_none()
+            && expected_nonce_size.is_some()
+            && scheme.blob_version() != version
+        {
+            problems.push(BlobProblem::new(
+                "KeyWrappingMismatch",
+                format!(
+                    "Ciphertext is wrapped with the {} scheme (version {}) but the blob records version {}",
+                    scheme.as_str(),
+                    scheme.blob_version(),
+                    version
+                ),
+            ));
+        }
+        let wrapped = split_outer_wrap(&data).is_some();
+        if wrapped != blob.outer_wrap.is_some() {
+            problems.push(BlobProblem::new(
+                "OuterWrapMismatch",
+                if wrapped {
+                    "Ciphertext is outer-wrapped but the blob records no outer wrap".to_string()
+                } else {
+                    "Blob records an outer wrap but the ciphertext is not wrapped".to_string()
+                },
+            ));
+        }
+    }
+    match base64_url_decode(&blob.encrypted_private_key_data) {
+        Ok(data) if ciphertext_len(&data) < CHACHA20_POLY1305_TAG_SIZE => {
+            problems.push(BlobProblem::new(
+                "CiphertextTooShort",
+                format!(
+                    "Ciphertext is {} bytes, shorter than the {}-byte authentication tag",
+                    ciphertext_len(&data),
+                    CHACHA20_POLY1305_TAG_SIZE
+                ),
+            ));
+        }
+        Ok(_) => {}
+        Err(e) => problems.push(BlobProblem::new(
+            "InvalidCiphertextEncoding",
+            format!("Ciphertext is not valid base64url: {}", e),
+        )),
+    }
+
+    match base64_url_decode(&blob.encrypted_private_key_iv) {
+        Ok(nonce) => {
+            if let Some(expected) = expected_nonce_size {
+                if nonce.len() != expected {
+                    problems.push(BlobProblem::new(
+                        "NonceLengthMismatch",
+                        format!(
+                            "Nonce is {} bytes, version {} requires {}",
+                            nonce.len(),
+                            version,
+                            expected
+                        ),
+                    ));
+                }
+            }
+        }
+        Err(e) => problems.push(BlobProblem::new(
+            "InvalidNonceEncoding",
+            format!("Nonce is not valid base64url: {}", e),
+        )),
+    }
+
+    if let Some(bound_account_id) = &blob.near_account_id {
+        if bound_account_id != near_account_id {
+            problems.push(BlobProblem::new(
+                "AccountIdMismatch",
+                format!(
+                    "Blob is bound to {} but was supplied for {}",
+                    bound_account_id, near_account_id
+                ),
+            ));
+        }
+    }
+
+    problems
+}
+
+/// **Handles:** `WorkerRequestType::ValidateEncryptedBlobs`
+/// Structural self-check of stored NEAR key blobs, so corrupted records can be detected
+/// (and re-registration or device-linking recovery offered) before a signing attempt fails.
+///
+/// # Arguments
+/// * `request` - Expected account ID and the blobs to check
+///
+/// # Returns
+/// * `ValidateEncryptedBlobsResult` - Per-blob report listing every problem found
+pub async fn handle_validate_encrypted_blobs(
+    request: ValidateEncryptedBlobsRequest,
+) -> Result<ValidateEncryptedBlobsResult, String> {
+    let reports: Vec<BlobValidationReport> = request
+        .blobs
+        .iter()
+        .enumerate()
+        .map(|(index, blob)| {
+            let problems = validate_encrypted_key_blob(blob, &request.near_account_id);
+            BlobValidationReport {
+                index: index as u32,
+                blob_id: blob.blob_id.clone(),
+                valid: problems.is_empty(),
+                problems,
+            }
+        })
+        .collect();
+
+    let invalid = reports.iter().filter(|r| !r.valid).count();
+    if invalid > 0 {
+        warn!(
+            "RUST: {} of {} encrypted key blobs failed validation for {}",
+            invalid,
+            reports.len(),
+            request.near_account_id
+        );
+    } else {
+        info!(
+            "RUST: {} encrypted key blobs validated for {}",
+            reports.len(),
+            request.near_account_id
+        );
+    }
+
+    Ok(ValidateEncryptedBlobsResult {
+        near_account_id: request.near_account_id,
+        all_valid: invalid == 0,
+        reports,
+    })
+}
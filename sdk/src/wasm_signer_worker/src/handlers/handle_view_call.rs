@@ -0,0 +1,57 @@
+// ******************************************************************************
+// *                                                                            *
+// *                              HANDLER: VIEW CALL                            *
+// *                                                                            *
+// ******************************************************************************
+
+use log::info;
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+use crate::rpc_client::NearRpcClient;
+use crate::view_call::{view_call, view_call_params, ViewBlockReference, ViewCallResult};
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewCallRequest {
+    /// NEAR RPC URL (comma-separated endpoints are tried in order)
+    #[wasm_bindgen(getter_with_clone, js_name = "nearRpcUrl")]
+    pub near_rpc_url: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "contractId")]
+    pub contract_id: String,
+    #[wasm_bindgen(getter_with_clone)]
+    pub method: String,
+    /// Arguments as a JSON string; `{}` when omitted
+    #[wasm_bindgen(getter_with_clone, js_name = "argsJson")]
+    #[serde(default)]
+    pub args_json: Option<String>,
+    /// `{ finality }` or `{ blockId }`; optimistic finality when omitted
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub block_reference: ViewBlockReference,
+}
+
+/// **Handles:** `WorkerRequestType::ViewCall`
+/// Calls a contract's view method through the worker's RPC client (see view_call.rs). No key
+/// is used and nothing is confirmed.
+///
+/// # Arguments
+/// * `request` - RPC URL, contract, method, JSON arguments and the block to read
+///
+/// # Returns
+/// * `ViewCallResult` - Returned bytes (base64, and parsed when JSON), logs and block
+pub async fn handle_view_call(request: ViewCallRequest) -> Result<ViewCallResult, String> {
+    let args = request.args_json.as_deref().unwrap_or("{}");
+    serde_json::from_str::<serde_json::Value>(args)
+        .map_err(|e| format!("argsJson is not valid JSON: {}", e))?;
+    let params = view_call_params(
+        &request.contract_id,
+        &request.method,
+        args.as_bytes(),
+        &request.block_reference,
+    )?;
+    info!("RUST: View call {}.{}", request.contract_id, request.method);
+    let rpc = NearRpcClient::new(&request.near_rpc_url);
+    view_call(&rpc, params).await.map_err(|e| e.to_string())
+}
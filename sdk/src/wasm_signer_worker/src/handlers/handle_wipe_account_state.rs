@@ -0,0 +1,60 @@
+// ******************************************************************************
+// *                                                                            *
+// *                        HANDLER: WIPE ACCOUNT STATE                         *
+// *                                                                            *
+// ******************************************************************************
+use crate::state;
+use crate::types::AccountId;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WipeAccountStateRequest {
+    #[wasm_bindgen(getter_with_clone, js_name = "accountId")]
+    pub account_id: String,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WipeAccountStateResult {
+    #[wasm_bindgen(getter_with_clone, js_name = "accountId")]
+    pub account_id: String,
+    /// Whether the worker held any state for the account
+    pub found: bool,
+    #[wasm_bindgen(js_name = "releasedNonceReservations")]
+    pub released_nonce_reservations: u32,
+    #[wasm_bindgen(js_name = "clearedAuditEntries")]
+    pub cleared_audit_entries: u32,
+    #[wasm_bindgen(js_name = "clearedSessionKeys")]
+    pub cleared_session_keys: u32,
+}
+
+/// **Handles:** `WorkerRequestType::WipeAccountState`
+/// Discards one account's session state (nonce reservations, audit log, request counters,
+/// cached receivers, confirmed batches) and its session keys, e.g. when a wallet removes the
+/// account. Other accounts, and worker-wide state such as the pending-request registry, are
+/// untouched; WipeAllState clears everything.
+///
+/// # Arguments
+/// * `request` - The account to wipe
+///
+/// # Returns
+/// * `WipeAccountStateResult` - Counts of what was discarded
+pub async fn handle_wipe_account_state(
+    request: WipeAccountStateRequest,
+) -> Result<WipeAccountStateResult, String> {
+    let account = AccountId::new(request.account_id)
+        .map_err(|_| "Missing required field: accountId".to_string())?;
+    let summary = state::wipe_account_state(&account);
+
+    Ok(WipeAccountStateResult {
+        account_id: account.to_string(),
+        found: summary.found,
+        released_nonce_reservations: summary.released_nonce_reservations as u32,
+        cleared_audit_entries: summary.cleared_audit_entries as u32,
+        cleared_session_keys: summary.cleared_session_keys as u32,
+    })
+}
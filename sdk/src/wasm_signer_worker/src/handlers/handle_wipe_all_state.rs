@@ -0,0 +1,63 @@
+// ******************************************************************************
+// *                                                                            *
+// *                          HANDLER: WIPE ALL STATE                           *
+// *                                                                            *
+// ******************************************************************************
+use crate::state;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WipeAllStateRequest {}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WipeAllStateResult {
+    /// Queued requests cancelled before they started
+    #[wasm_bindgen(js_name = "cancelledQueuedRequests")]
+    pub cancelled_queued_requests: u32,
+    /// Requests still running (they finish, but find no state left to release)
+    #[wasm_bindgen(js_name = "runningRequests")]
+    pub running_requests: u32,
+    #[wasm_bindgen(js_name = "clearedConfirmations")]
+    pub cleared_confirmations: u32,
+    #[wasm_bindgen(js_name = "releasedNonceReservations")]
+    pub released_nonce_reservations: u32,
+    #[wasm_bindgen(js_name = "clearedSessionKeys")]
+    pub cleared_session_keys: u32,
+    /// Whether the peer port to the VRF worker was connected and is now closed
+    #[wasm_bindgen(js_name = "closedPeerPort")]
+    pub closed_peer_port: bool,
+    /// Whether a background refresh was running and is now stopped
+    #[wasm_bindgen(js_name = "stoppedBackgroundRefresh")]
+    pub stopped_background_refresh: bool,
+}
+
+/// **Handles:** `WorkerRequestType::WipeAllState`
+/// Discards all in-memory request state: cancels queued requests and clears
+/// confirmation nonces, nonce reservations, session keys and the audit log, closes the
+/// peer port to the VRF worker and stops the background refresh.
+///
+/// # Arguments
+/// * `_request` - Empty request
+///
+/// # Returns
+/// * `WipeAllStateResult` - Counts of what was discarded
+pub async fn handle_wipe_all_state(
+    _request: WipeAllStateRequest,
+) -> Result<WipeAllStateResult, String> {
+    let summary = state::wipe_all_state();
+
+    Ok(WipeAllStateResult {
+        cancelled_queued_requests: summary.cancelled_queued_requests as u32,
+        running_requests: summary.running_requests as u32,
+        cleared_confirmations: summary.cleared_confirmations as u32,
+        released_nonce_reservations: summary.released_nonce_reservations as u32,
+        cleared_session_keys: summary.cleared_session_keys as u32,
+        closed_peer_port: summary.closed_peer_port,
+        stopped_background_refresh: summary.stopped_background_refresh,
+    })
+}
@@ -1,44 +1,194 @@
 pub mod confirm_tx_details;
+pub mod handle_account_bundle;
+pub mod handle_background_refresh;
+pub mod handle_build_account_descriptor;
+pub mod handle_build_redirect_payload;
+pub mod handle_build_credential_options;
+pub mod handle_cancel_request;
 pub mod handle_check_can_register_user;
+pub mod handle_compose_create_subaccount;
+pub mod handle_compose_multisig_request;
 pub mod handle_decrypt_private_key_with_prf;
+pub mod handle_deploy_large_contract;
 pub mod handle_derive_near_keypair_and_encrypt;
+pub mod handle_derive_sub_key;
+pub mod handle_dump_diagnostics;
 pub mod handle_extract_cose_public_key;
+pub mod handle_get_borsh_schemas;
+pub mod handle_get_key_usage_stats;
+pub mod handle_get_recent_receivers;
+pub mod handle_get_session_status;
+#[cfg(feature = "telemetry")]
+pub mod handle_get_telemetry_snapshot;
+pub mod handle_get_worker_info;
+pub mod handle_issue_elevation_challenge;
+pub mod handle_list_active_accounts;
+pub mod handle_list_pending_requests;
+pub mod handle_memory;
+pub mod handle_migrate_encrypted_blobs;
 pub mod handle_recover_keypair_from_passkey;
+pub mod handle_recover_pending_operations;
+#[cfg(feature = "device-linking")]
+pub mod handle_remote_confirmation;
 pub mod handle_request_registration_credential_confirmation;
+pub mod handle_resumable_registration;
+pub mod handle_run_self_test;
+pub mod handle_session_keys;
+#[cfg(feature = "nep413")]
 pub mod handle_sign_nep413_message;
+#[cfg(feature = "device-linking")]
 pub mod handle_sign_transaction_with_keypair;
 pub mod handle_sign_transactions_with_actions;
+pub mod handle_sign_transactions_with_fresh_challenge;
+pub mod handle_signing_intent;
+#[cfg(feature = "relayer")]
+pub mod handle_submit_to_relayer;
+pub mod handle_summarize_transactions;
+pub mod handle_validate_args_schemas;
+pub mod handle_validate_decryption_capability;
+pub mod handle_validate_encrypted_blobs;
+pub mod handle_view_call;
+pub mod handle_wipe_account_state;
+pub mod handle_wipe_all_state;
 
 // Handler functions
+pub use handle_account_bundle::{handle_export_account_bundle, handle_import_account_bundle};
+pub use handle_background_refresh::{
+    handle_background_refresh_status, handle_resume_hint, handle_start_background_refresh,
+    handle_stop_background_refresh, handle_suspend_hint,
+};
+pub use handle_build_account_descriptor::handle_build_account_descriptor;
+pub use handle_build_redirect_payload::handle_build_redirect_payload;
+pub use handle_build_credential_options::{
+    handle_build_credential_creation_options, handle_build_credential_request_options,
+};
+pub use handle_cancel_request::handle_cancel_request;
 pub use handle_check_can_register_user::handle_check_can_register_user;
+pub use handle_compose_create_subaccount::handle_compose_create_subaccount;
+pub use handle_compose_multisig_request::handle_compose_multisig_request;
 pub use handle_decrypt_private_key_with_prf::handle_decrypt_private_key_with_prf;
 pub use handle_decrypt_private_key_with_prf::handle_export_near_keypair_ui;
+pub use handle_deploy_large_contract::handle_deploy_large_contract;
 pub use handle_derive_near_keypair_and_encrypt::handle_derive_near_keypair_and_encrypt;
+pub use handle_derive_sub_key::handle_derive_sub_key;
+pub use handle_dump_diagnostics::handle_dump_diagnostics;
 pub use handle_extract_cose_public_key::handle_extract_cose_public_key;
+pub use handle_get_borsh_schemas::handle_get_borsh_schemas;
+pub use handle_get_key_usage_stats::handle_get_key_usage_stats;
+pub use handle_get_recent_receivers::handle_get_recent_receivers;
+#[cfg(feature = "telemetry")]
+pub use handle_get_telemetry_snapshot::handle_get_telemetry_snapshot;
+pub use handle_get_session_status::handle_get_session_status;
+pub use handle_get_worker_info::handle_get_worker_info;
+pub use handle_issue_elevation_challenge::handle_issue_elevation_challenge;
+pub use handle_list_active_accounts::handle_list_active_accounts;
+pub use handle_list_pending_requests::handle_list_pending_requests;
+pub use handle_memory::{handle_get_memory_stats, handle_trim_caches};
+pub use handle_migrate_encrypted_blobs::handle_migrate_encrypted_blobs;
 pub use handle_recover_keypair_from_passkey::handle_recover_keypair_from_passkey;
+pub use handle_recover_pending_operations::handle_recover_pending_operations;
+#[cfg(feature = "device-linking")]
+pub use handle_remote_confirmation::{
+    handle_approve_remote_confirmation, handle_complete_remote_confirmation,
+    handle_create_remote_confirmation,
+};
 pub use handle_request_registration_credential_confirmation::handle_request_registration_credential_confirmation;
+#[cfg(feature = "relayer")]
+pub use handle_resumable_registration::handle_complete_registration;
+pub use handle_resumable_registration::handle_prepare_registration;
+pub use handle_run_self_test::handle_run_self_test;
+pub use handle_session_keys::{
+    handle_create_session_key, handle_revoke_session_key, handle_sign_with_session_key,
+};
+#[cfg(feature = "nep413")]
 pub use handle_sign_nep413_message::handle_sign_nep413_message;
+#[cfg(feature = "device-linking")]
 pub use handle_sign_transaction_with_keypair::handle_sign_transaction_with_keypair;
 pub use handle_sign_transactions_with_actions::handle_sign_transactions_with_actions;
+pub use handle_sign_transactions_with_fresh_challenge::handle_sign_transactions_with_fresh_challenge;
+pub use handle_signing_intent::{handle_create_signing_intent, handle_execute_signing_intent};
+#[cfg(feature = "relayer")]
+pub use handle_submit_to_relayer::handle_submit_to_relayer;
+pub use handle_summarize_transactions::handle_summarize_transactions;
+pub use handle_validate_args_schemas::handle_validate_args_schemas;
+pub use handle_validate_decryption_capability::handle_validate_decryption_capability;
+pub use handle_validate_encrypted_blobs::handle_validate_encrypted_blobs;
+pub use handle_view_call::handle_view_call;
+pub use handle_wipe_account_state::handle_wipe_account_state;
+pub use handle_wipe_all_state::handle_wipe_all_state;
 
 // Request/Result types
+pub use handle_account_bundle::{ExportAccountBundleRequest, ImportAccountBundleRequest};
+pub use handle_background_refresh::{
+    BackgroundRefreshStatusRequest, ResumeHintRequest, StartBackgroundRefreshRequest,
+    StopBackgroundRefreshRequest, SuspendHintRequest,
+};
+pub use handle_build_account_descriptor::BuildAccountDescriptorRequest;
+pub use handle_build_redirect_payload::BuildRedirectPayloadRequest;
+pub use handle_build_credential_options::{
+    BuildCredentialCreationOptionsRequest, BuildCredentialRequestOptionsRequest,
+};
+pub use handle_cancel_request::CancelRequestRequest;
 pub use handle_check_can_register_user::{
     CheckCanRegisterUserRequest, RegistrationCheckRequest, RegistrationCheckResult,
     RegistrationInfoStruct,
 };
+pub use handle_compose_create_subaccount::ComposeCreateSubaccountRequest;
+pub use handle_compose_multisig_request::ComposeMultisigRequest;
 pub use handle_decrypt_private_key_with_prf::{
     ExportNearKeypairUiRequest, ExportNearKeypairUiResult,
 };
+pub use handle_deploy_large_contract::DeployLargeContractRequest;
+pub use handle_derive_sub_key::DeriveSubKeyRequest;
+pub use handle_dump_diagnostics::DumpDiagnosticsRequest;
 pub use handle_extract_cose_public_key::{CoseExtractionResult, ExtractCoseRequest};
+pub use handle_get_borsh_schemas::GetBorshSchemasRequest;
+pub use handle_get_key_usage_stats::GetKeyUsageStatsRequest;
+pub use handle_get_recent_receivers::GetRecentReceiversRequest;
+#[cfg(feature = "telemetry")]
+pub use handle_get_telemetry_snapshot::GetTelemetrySnapshotRequest;
+pub use handle_get_session_status::GetSessionStatusRequest;
+pub use handle_get_worker_info::GetWorkerInfoRequest;
+pub use handle_issue_elevation_challenge::IssueElevationChallengeRequest;
+pub use handle_list_active_accounts::ListActiveAccountsRequest;
+pub use handle_list_pending_requests::ListPendingRequestsRequest;
+pub use handle_memory::{GetMemoryStatsRequest, TrimCachesRequest};
+pub use handle_migrate_encrypted_blobs::MigrateEncryptedBlobsRequest;
 pub use handle_recover_keypair_from_passkey::{RecoverKeypairRequest, RecoverKeypairResult};
+pub use handle_recover_pending_operations::RecoverPendingOperationsRequest;
+#[cfg(feature = "device-linking")]
+pub use handle_remote_confirmation::{
+    ApproveRemoteConfirmationRequest, CompleteRemoteConfirmationRequest,
+    CreateRemoteConfirmationRequest,
+};
 pub use handle_request_registration_credential_confirmation::{
     RegistrationCredentialConfirmationRequest, RegistrationCredentialConfirmationResult,
 };
+#[cfg(feature = "relayer")]
+pub use handle_resumable_registration::CompleteRegistrationRequest;
+pub use handle_resumable_registration::PrepareRegistrationRequest;
+pub use handle_run_self_test::RunSelfTestRequest;
+pub use handle_session_keys::{
+    CreateSessionKeyRequest, RevokeSessionKeyRequest, SignWithSessionKeyRequest,
+};
+#[cfg(feature = "nep413")]
 pub use handle_sign_nep413_message::{SignNep413Request, SignNep413Result};
+#[cfg(feature = "device-linking")]
 pub use handle_sign_transaction_with_keypair::SignTransactionWithKeyPairRequest;
 pub use handle_sign_transactions_with_actions::{
     KeyActionResult, SignTransactionsWithActionsRequest, TransactionPayload,
 };
+pub use handle_sign_transactions_with_fresh_challenge::SignTransactionsWithFreshChallengeRequest;
+pub use handle_signing_intent::{CreateSigningIntentRequest, ExecuteSigningIntentRequest};
+#[cfg(feature = "relayer")]
+pub use handle_submit_to_relayer::SubmitToRelayerRequest;
+pub use handle_summarize_transactions::SummarizeTransactionsRequest;
+pub use handle_validate_args_schemas::ValidateArgsSchemasRequest;
+pub use handle_validate_decryption_capability::ValidateDecryptionCapabilityRequest;
+pub use handle_validate_encrypted_blobs::ValidateEncryptedBlobsRequest;
+pub use handle_view_call::ViewCallRequest;
+pub use handle_wipe_account_state::WipeAccountStateRequest;
+pub use handle_wipe_all_state::WipeAllStateRequest;
 
 // Transaction confirmation utilities
 pub use confirm_tx_details::{
@@ -0,0 +1,136 @@
+// === SIGNING HOOKS ===
+// Integrator hooks that run on the main thread, independent of the dapp page
+// (e.g. a compliance module). With `WorkerPolicy.preSignHook` the worker asks the hook to
+// allow a request after the user confirms it and before the private key is decrypted; with
+// `WorkerPolicy.postSignHook` it reports every signed transaction. The pre-sign hook fails
+// closed: a timeout, a malformed response or a missing bridge all deny the request.
+
+use serde::{Deserialize, Serialize};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::JsValue;
+
+use crate::config::DEFAULT_SIGN_HOOK_TIMEOUT_MS;
+use crate::error::SignerErrorCode;
+use crate::state;
+use crate::types::handlers::WorkerPolicy;
+use crate::types::AccountId;
+
+/// Reason code recorded when no usable response came back from the main thread
+pub const HOOK_REASON_UNAVAILABLE: &str = "HookUnavailable";
+/// Reason code recorded when the hook denied without giving one
+pub const HOOK_REASON_DENIED: &str = "Denied";
+
+// Bridges implemented in web3authn-signer.worker.ts. As with awaitSecureConfirmationV2,
+// requests cross the boundary as JSON strings.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = awaitPreSignHook)]
+    async fn await_pre_sign_hook(request_json: JsValue, timeout_ms: u32) -> JsValue;
+
+    #[wasm_bindgen(js_name = notifyPostSignHook)]
+    fn notify_post_sign_hook(notification_json: JsValue);
+}
+
+/// Sent to the main thread before signing
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreSignHookRequest {
+    pub request_id: String,
+    /// Same summary the confirmation UI displays
+    pub tx_summary: serde_json::Value,
+    /// Intent digest the user confirmed
+    pub digest: String,
+}
+
+/// The hook's decision
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreSignHookResponse {
+    pub allow: bool,
+    #[serde(default)]
+    pub reason_code: Option<String>,
+}
+
+/// Sent to the main thread once per signed transaction
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostSignHookNotification {
+    pub request_id: String,
+    pub tx_hash: String,
+    pub digest: String,
+}
+
+pub fn hook_timeout_ms(policy: &WorkerPolicy) -> u32 {
+    policy.hook_timeout_ms.unwrap_or(DEFAULT_SIGN_HOOK_TIMEOUT_MS)
+}
+
+/// Turns the hook's (possibly missing) response into a decision: Ok to proceed, or the
+/// reason code of the denial. Anything but an explicit `allow: true` denies.
+pub fn pre_sign_decision(response: Option<PreSignHookResponse>) -> Result<(), String> {
+    match response {
+        Some(PreSignHookResponse { allow: true, .. }) => Ok(()),
+        Some(PreSignHookResponse { reason_code, .. }) => {
+            Err(reason_code.unwrap_or_else(|| HOOK_REASON_DENIED.to_string()))
+        }
+        None => Err(HOOK_REASON_UNAVAILABLE.to_string()),
+    }
+}
+
+/// Applies the hook's decision for `request_id`, recording denials in the audit log.
+/// Returns the error code and message to surface when the request is denied.
+pub fn enforce_pre_sign_decision(
+    account: &AccountId,
+    request_id: &str,
+    operation: &str,
+    response: Option<PreSignHookResponse>,
+) -> Result<(), (SignerErrorCode, String)> {
+    pre_sign_decision(response).map_err(|reason_code| {
+        state::record_audit(
+            account,
+            request_id,
+            operation,
+            SignerErrorCode::PreSignHookDenied.as_str(),
+            Some(reason_code.clone()),
+        );
+        (
+            SignerErrorCode::PreSignHookDenied,
+            format!("Pre-sign hook denied the request ({})", reason_code),
+        )
+    })
+}
+
+/// Asks the main thread's pre-sign hook for a decision. The bridge resolves with
+/// `{ allow: false, reasonCode: "HookTimeout" }` once the timeout elapses.
+#[cfg(target_arch = "wasm32")]
+pub async fn request_pre_sign_decision(
+    request: &PreSignHookRequest,
+    timeout_ms: u32,
+) -> Option<PreSignHookResponse> {
+    let request_json = serde_json::to_string(request).ok()?;
+    let response = await_pre_sign_hook(JsValue::from_str(&request_json), timeout_ms).await;
+    let response_json = response.as_string()?;
+    serde_json::from_str(&response_json).ok()
+}
+
+/// Non-WASM fallback (native tests): there is no main thread to ask, so the hook denies
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn request_pre_sign_decision(
+    _request: &PreSignHookRequest,
+    _timeout_ms: u32,
+) -> Option<PreSignHookResponse> {
+    None
+}
+
+/// Fire-and-forget notification; failures never affect the signing result
+#[cfg(target_arch = "wasm32")]
+pub fn send_post_sign_notification(notification: &PostSignHookNotification) {
+    if let Ok(json) = serde_json::to_string(notification) {
+        notify_post_sign_hook(JsValue::from_str(&json));
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn send_post_sign_notification(_notification: &PostSignHookNotification) {}
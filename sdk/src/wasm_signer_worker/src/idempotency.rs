@@ -0,0 +1,89 @@
+// === IDEMPOTENCY KEYS ===
+// On flaky networks the TS layer retries registration and signing requests, which can
+// register twice on-chain or sign the same nonce twice. Requests of the types below may
+// carry an `idempotencyKey`: once such a request completes, a repeat with the same key
+// gets the stored response back (marked `replayed: true`) without re-executing. The key is
+// bound to a digest of the request type and payload, so reusing it for anything else is
+// refused with IdempotencyKeyConflict. Every request runs in a new worker, so entries travel
+// in the sealed worker state record (see worker_state.rs) and a retry in the next worker is
+// still replayed; they are bounded by IDEMPOTENCY_CACHE_LIMIT and cleared by WipeAllState.
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::encoders::base64_url_encode;
+use crate::error::IdempotencyError;
+use crate::state::{self, CompletedRequest};
+use crate::types::worker_messages::WorkerRequestType;
+
+/// Request types that accept an idempotency key: registration and broadcastable signing
+fn accepts_idempotency_key(request_type: WorkerRequestType) -> bool {
+    matches!(
+        request_type,
+        WorkerRequestType::DeriveNearKeypairAndEncrypt
+            | WorkerRequestType::SignTransactionsWithActions
+            | WorkerRequestType::SignTransactionsWithFreshChallenge
+            | WorkerRequestType::SignTransactionWithKeyPair
+            | WorkerRequestType::SignWithSessionKey
+    )
+}
+
+/// The payload's idempotency key, when its request type accepts one
+pub fn idempotency_key(request_type: WorkerRequestType, payload: &Value) -> Option<&str> {
+    if !accepts_idempotency_key(request_type) {
+        return None;
+    }
+    payload
+        .get("idempotencyKey")
+        .and_then(Value::as_str)
+        .filter(|key| !key.is_empty())
+}
+
+/// SHA-256 (base64url) over the request type name and the payload's JSON (object keys sorted)
+pub fn payload_digest(request_type: WorkerRequestType, payload: &Value) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(request_type.name().as_bytes());
+    hasher.update([0u8]);
+    hasher.update(payload.to_string().as_bytes());
+    base64_url_encode(&hasher.finalize())
+}
+
+/// The stored response to replay for a repeated key, marked `replayed: true`. None when the
+/// request carries no key or the key is new.
+pub fn replayed_response(
+    request_type: WorkerRequestType,
+    payload: &Value,
+) -> Result<Option<Value>, IdempotencyError> {
+    let Some(key) = idempotency_key(request_type, payload) else {
+        return Ok(None);
+    };
+    let Some(completed) = state::completed_request(key) else {
+        return Ok(None);
+    };
+    if completed.payload_digest != payload_digest(request_type, payload) {
+        return Err(IdempotencyError::KeyConflict {
+            key: key.to_string(),
+        });
+    }
+    let mut response = completed.response;
+    if let Some(object) = response.as_object_mut() {
+        object.insert("replayed".to_string(), Value::Bool(true));
+    }
+    Ok(Some(response))
+}
+
+/// Stores a successful response under the request's key. Responses reporting
+/// `success: false` are not stored, so a failed attempt can be retried with the same key.
+pub fn record_response(request_type: WorkerRequestType, payload: &Value, response: &Value) {
+    let Some(key) = idempotency_key(request_type, payload) else {
+        return;
+    };
+    if response.get("success") == Some(&Value::Bool(false)) {
+        return;
+    }
+    state::record_completed_request(CompletedRequest {
+        idempotency_key: key.to_string(),
+        payload_digest: payload_digest(request_type, payload),
+        response: response.clone(),
+    });
+}
@@ -0,0 +1,89 @@
+// === KNOWN-ANSWER TEST VECTORS ===
+// Vectors for the startup self-test (self_test.rs), copied from the reference specs cited on
+// each block. Any change here must be re-checked against the cited section, not against the
+// output of this crate: the point of a known answer is that it does not come from the code
+// under test. Hex strings are lowercase without separators.
+
+// --- SHA-256: FIPS 180-4, examples from NIST CSRC "SHA256.pdf" (one-block, two-block) and the
+// empty message ---
+
+/// (message, digest)
+pub const SHA256_VECTORS: &[(&[u8], &str)] = &[
+    (
+        b"abc",
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+    ),
+    (
+        b"",
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    ),
+    (
+        b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+        "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+    ),
+];
+
+// --- ChaCha20-Poly1305 AEAD: RFC 8439 §2.8.2 ---
+
+pub const CHACHA20_POLY1305_KEY: &str =
+    "808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f";
+pub const CHACHA20_POLY1305_NONCE: &str = "070000004041424344454647";
+pub const CHACHA20_POLY1305_AAD: &str = "50515253c0c1c2c3c4c5c6c7";
+pub const CHACHA20_POLY1305_PLAINTEXT: &[u8] = b"Ladies and Gentlemen of the class of '99: \
+If I could offer you only one tip for the future, sunscreen would be it.";
+/// Ciphertext followed by the 16-byte tag (1ae10b594f09e26a7e902ecbd0600691)
+pub const CHACHA20_POLY1305_SEALED: &str = concat!(
+    "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6",
+    "3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36",
+    "92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc",
+    "3ff4def08e4b7a9de576d26586cec64b6116",
+    "1ae10b594f09e26a7e902ecbd0600691",
+);
+
+// --- HKDF-SHA256: RFC 5869 Appendix A.1 (Test Case 1) ---
+
+pub const HKDF_IKM: &str = "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b";
+pub const HKDF_SALT: &str = "000102030405060708090a0b0c";
+pub const HKDF_INFO: &str = "f0f1f2f3f4f5f6f7f8f9";
+/// L = 42
+pub const HKDF_OKM: &str =
+    "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865";
+
+// --- Ed25519: RFC 8032 §7.1, TEST 1 (empty message) ---
+
+pub const ED25519_SECRET_KEY: &str =
+    "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+pub const ED25519_PUBLIC_KEY: &str =
+    "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+pub const ED25519_MESSAGE: &[u8] = b"";
+pub const ED25519_SIGNATURE: &str = concat!(
+    "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e06522490155",
+    "5fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+);
+
+// --- base64url without padding: RFC 4648 §10 (test vectors) and §5 (URL-safe alphabet) ---
+
+/// (data, encoding); §10 vectors with the padding removed, plus bytes hitting '-' and '_'
+pub const BASE64URL_VECTORS: &[(&[u8], &str)] = &[
+    (b"", ""),
+    (b"f", "Zg"),
+    (b"fo", "Zm8"),
+    (b"foo", "Zm9v"),
+    (b"foob", "Zm9vYg"),
+    (b"fooba", "Zm9vYmE"),
+    (b"foobar", "Zm9vYmFy"),
+    (&[0xfb, 0xff], "-_8"),
+];
+
+// --- Borsh: borsh specification (borsh.io), integers little-endian, strings and vectors with a
+// u32 length prefix, Option as a 0/1 tag ---
+
+pub const BORSH_U32: (u32, &str) = (1, "01000000");
+pub const BORSH_U128: (u128, &str) = (
+    1_000_000_000_000_000_000_000_000,
+    "000000a1edccce1bc2d3000000000000",
+);
+pub const BORSH_STRING: (&str, &str) = ("abc", "03000000616263");
+pub const BORSH_OPTION_SOME_U8: (u8, &str) = (7, "0107");
+pub const BORSH_OPTION_NONE: &str = "00";
+pub const BORSH_VEC_U16: (&[u16], &str) = (&[1, 2], "0200000001000200");
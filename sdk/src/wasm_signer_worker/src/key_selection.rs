@@ -0,0 +1,209 @@
+// === SIGNING KEY SELECTION ===
+// The worker can hold more than one key blob for an account (the passkey's key plus, e.g., a
+// staking key). A request picks the key that signs with `signingPublicKey`: the handler takes
+// the blob of `decryption.candidates` tagged with that key, reads the key from the chain
+// (view_access_key) and checks locally that its permission covers the batch:
+//   - a full access key signs anything;
+//   - a function-call key signs only FunctionCall actions without deposit, to its receiver,
+//     calling one of its methods (any method when its list is empty).
+// A key that is not on the account, or whose permission falls short, fails with
+// KeyLacksPermission: before the user is prompted, and again against a fresh read once they
+// confirmed. Signing then uses the selected key's nonce and allowance, the confirmation shows
+// the key and its fingerprint, and a blob that decrypts to another key than its tag is refused.
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::actions::ActionParams;
+use crate::error::KeyLacksPermissionError;
+use crate::types::handlers::{DecryptionPayload, KeyBlobCandidate, TransactionContext};
+
+/// Permission of an access key, as `view_access_key` reports it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessKeyPermission {
+    FullAccess,
+    FunctionCall {
+        receiver_id: String,
+        /// Empty: any method of `receiver_id`
+        method_names: Vec<String>,
+        /// Remaining allowance (yoctoNEAR); None when unlimited
+        allowance: Option<String>,
+    },
+}
+
+/// An access key read from the chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OnChainAccessKey {
+    pub nonce: u64,
+    pub permission: AccessKeyPermission,
+}
+
+/// Parses a `view_access_key` result
+pub fn parse_access_key(result: &Value) -> Result<OnChainAccessKey, String> {
+    let nonce = result
+        .get("nonce")
+        .and_then(|n| n.as_u64())
+        .ok_or_else(|| format!("view_access_key result without nonce: {}", result))?;
+    let permission = &result["permission"];
+    let permission = if permission == "FullAccess" || permission["FullAccess"].is_object() {
+        AccessKeyPermission::FullAccess
+    } else if let Some(function_call) = permission["FunctionCall"].as_object() {
+        let receiver_id = function_call
+            .get("receiver_id")
+            .and_then(|r| r.as_str())
+            .ok_or("Missing receiver_id in FunctionCall permission")?
+            .to_string();
+        let method_names = function_call
+            .get("method_names")
+            .and_then(|m| m.as_array())
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(|name| name.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let allowance = function_call
+            .get("allowance")
+            .and_then(|a| a.as_str())
+            .map(str::to_string);
+        AccessKeyPermission::FunctionCall {
+            receiver_id,
+            method_names,
+            allowance,
+        }
+    } else {
+        return Err(format!("Invalid access key permission: {}", permission));
+    };
+    Ok(OnChainAccessKey { nonce, permission })
+}
+
+/// The 32 key bytes of an "ed25519:<base58>" public key
+pub fn parse_public_key(public_key: &str) -> Result<[u8; 32], String> {
+    let b58 = public_key
+        .strip_prefix("ed25519:")
+        .ok_or_else(|| format!("{} is not an ed25519:<base58> public key", public_key))?;
+    let bytes = bs58::decode(b58)
+        .into_vec()
+        .map_err(|e| format!("Invalid base58 public key {}: {}", public_key, e))?;
+    bytes
+        .try_into()
+        .map_err(|b: Vec<u8>| format!("Ed25519 public key must be 32 bytes, got {}", b.len()))
+}
+
+/// Short form of a public key for the confirmation: the first 8 bytes of its SHA-256, in hex
+/// pairs of bytes (e.g. "3f2a:91c0:07be:5d44")
+pub fn key_fingerprint(public_key: &[u8; 32]) -> String {
+    Sha256::digest(public_key)[..8]
+        .chunks(2)
+        .map(|pair| format!("{:02x}{:02x}", pair[0], pair[1]))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// The candidate blob tagged with `public_key`. Tags are compared as keys, not as strings;
+/// candidates with an unparseable tag never match.
+pub fn select_candidate<'a>(
+    decryption: &'a DecryptionPayload,
+    public_key: &[u8; 32],
+) -> Option<&'a KeyBlobCandidate> {
+    decryption
+        .candidates
+        .iter()
+        .find(|candidate| parse_public_key(&candidate.public_key).ok().as_ref() == Some(public_key))
+}
+
+/// `key` (None: not on the account), once checked to be able to sign every transaction of the
+/// batch
+pub fn check_key_permission(
+    account_id: &str,
+    public_key: &str,
+    key: Option<OnChainAccessKey>,
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+) -> Result<OnChainAccessKey, KeyLacksPermissionError> {
+    let lacks = |reason: String| KeyLacksPermissionError {
+        public_key: public_key.to_string(),
+        reason,
+    };
+    let key = key.ok_or_else(|| lacks(format!("it is not an access key of {}", account_id)))?;
+    let (receiver_id, method_names) = match &key.permission {
+        AccessKeyPermission::FullAccess => return Ok(key),
+        AccessKeyPermission::FunctionCall {
+            receiver_id,
+            method_names,
+            ..
+        } => (receiver_id, method_names),
+    };
+
+    for (i, (tx_receiver_id, actions)) in receivers_and_actions.iter().enumerate() {
+        if tx_receiver_id != receiver_id {
+            return Err(lacks(format!(
+                "transaction {} is to {}, the function-call key may only call {}",
+                i + 1,
+                tx_receiver_id,
+                receiver_id
+            )));
+        }
+        for action in actions {
+            let ActionParams::FunctionCall {
+                method_name,
+                deposit,
+                ..
+            } = action
+            else {
+                return Err(lacks(format!(
+                    "transaction {} has an action other than a function call",
+                    i + 1
+                )));
+            };
+            if deposit.parse::<u128>().ok() != Some(0) {
+                return Err(lacks(format!(
+                    "transaction {} attaches a deposit to {}, which a function-call key cannot",
+                    i + 1,
+                    method_name
+                )));
+            }
+            if !method_names.is_empty() && !method_names.contains(method_name) {
+                return Err(lacks(format!(
+                    "transaction {} calls {}, the function-call key may only call {}",
+                    i + 1,
+                    method_name,
+                    method_names.join(", ")
+                )));
+            }
+        }
+    }
+    Ok(key)
+}
+
+/// `base` with the selected key's public key, next nonce and allowance; the block reference
+/// and gas price are kept. Without a base the block fields are empty: before the prompt only
+/// the allowance is read from the context.
+pub fn signing_context(
+    base: Option<&TransactionContext>,
+    public_key: &str,
+    key: &OnChainAccessKey,
+) -> TransactionContext {
+    let access_key_allowance = match &key.permission {
+        AccessKeyPermission::FunctionCall { allowance, .. } => allowance.clone(),
+        AccessKeyPermission::FullAccess => None,
+    };
+    let (tx_block_height, tx_block_hash, gas_price) = match base {
+        Some(base) => (
+            base.tx_block_height.clone(),
+            base.tx_block_hash.clone(),
+            base.gas_price.clone(),
+        ),
+        None => (String::new(), String::new(), None),
+    };
+    TransactionContext {
+        access_key_allowance,
+        gas_price,
+        ..TransactionContext::new(
+            public_key.to_string(),
+            key.nonce.saturating_add(1).to_string(),
+            tx_block_height,
+            tx_block_hash,
+        )
+    }
+}
@@ -0,0 +1,320 @@
+// === KEY USAGE STATS ===
+// Per key blob usage, so users with several devices can tell which key they actually sign
+// with. Entries are keyed by the blob's AAD identity (the key-check value its ciphertext
+// authenticates, see crypto.rs) and count signed transactions, when the key last signed and
+// what kind of receiver it last signed for. Receivers are only kept as a category and a
+// truncated SHA-256; amounts are never recorded.
+// Workers do not outlive their request, so the TS layer stores the exported KeyUsageRecord
+// and hands it back with the next Initialize; responses of requests that signed carry the
+// updated record. Each entry carries an HMAC-SHA256 under a key derived from the PRF output
+// of the key's passkey, the only secret that is the same in every session: the entry is
+// verified the next time its key signs, and one that fails is dropped and reported as
+// tampered instead of being counted on.
+
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use wasm_bindgen::prelude::*;
+
+use crate::config::{
+    KEY_USAGE_MAC_HKDF_INFO, KEY_USAGE_RECEIVER_HASH_SIZE, KEY_USAGE_RECORD_VERSION,
+    MAX_KEY_USAGE_ENTRIES,
+};
+use crate::crypto::split_key_check_header;
+use crate::encoders::{base64_url_decode, base64_url_encode};
+
+/// What kind of account a key last signed for
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReceiverCategory {
+    /// The signer account itself (e.g. key management)
+    Own,
+    /// A 64-hex implicit account or an 0x-prefixed Ethereum implicit account
+    Implicit,
+    /// Any other named account
+    Named,
+}
+
+impl ReceiverCategory {
+    pub fn of(receiver_id: &str, signer_id: &str) -> Self {
+        let is_hex = |s: &str| {
+            s.bytes()
+                .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+        };
+        if receiver_id == signer_id {
+            ReceiverCategory::Own
+        } else if (receiver_id.len() == 64 && is_hex(receiver_id))
+            || receiver_id
+                .strip_prefix("0x")
+                .is_some_and(|rest| rest.len() == 40 && is_hex(rest))
+        {
+            ReceiverCategory::Implicit
+        } else {
+            ReceiverCategory::Named
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReceiverCategory::Own => "own",
+            ReceiverCategory::Implicit => "implicit",
+            ReceiverCategory::Named => "named",
+        }
+    }
+}
+
+/// Usage of one key blob, as stored by the TS layer
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct KeyUsageEntry {
+    /// base64url key-check value of the blob (`key_usage_id`)
+    pub key_id: String,
+    /// Transactions signed with the key
+    pub sign_count: u32,
+    pub last_used_ms: f64,
+    pub last_receiver_category: ReceiverCategory,
+    /// base64url of the first KEY_USAGE_RECEIVER_HASH_SIZE bytes of SHA-256(receiver ID)
+    pub last_receiver_hash: String,
+    /// base64url HMAC-SHA256 over the fields above
+    pub mac: String,
+}
+
+/// Exported usage of every key blob the worker has seen sign
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct KeyUsageRecord {
+    pub version: u32,
+    pub entries: Vec<KeyUsageEntry>,
+}
+
+#[derive(Default)]
+struct KeyUsageState {
+    entries: BTreeMap<String, KeyUsageEntry>,
+    /// Keys whose entry was checked (or written) with their MAC key by this worker
+    verified: BTreeSet<String>,
+    /// Keys whose stored entry failed its MAC check
+    tampered: BTreeSet<String>,
+    /// Whether an entry changed since the record was loaded or last exported
+    changed: bool,
+}
+
+thread_local! {
+    static KEY_USAGE: RefCell<KeyUsageState> = RefCell::new(KeyUsageState::default());
+}
+
+/// AAD identity of decoded (and outer-unwrapped) blob bytes: the base64url key-check value.
+/// None for blobs written before the key-check header, which are not tracked.
+pub fn key_usage_id(encrypted_data: &[u8]) -> Option<String> {
+    split_key_check_header(encrypted_data).map(|(key_check, _)| base64_url_encode(key_check))
+}
+
+/// Truncated SHA-256 of a receiver ID, so the record never holds the ID itself
+pub fn receiver_hash(receiver_id: &str) -> String {
+    let hash = Sha256::digest(receiver_id.as_bytes());
+    base64_url_encode(&hash[..KEY_USAGE_RECEIVER_HASH_SIZE])
+}
+
+/// Entry MAC key of the key blob unlocked with `prf_output` (base64url)
+fn entry_mac_key(prf_output: &str) -> Result<[u8; 32], String> {
+    let prf_bytes = base64_url_decode(prf_output)
+        .map_err(|e| format!("Invalid PRF output for key usage: {}", e))?;
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(None, &prf_bytes)
+        .expand(KEY_USAGE_MAC_HKDF_INFO.as_bytes(), &mut key)
+        .map_err(|_| "Key usage MAC key derivation failed".to_string())?;
+    Ok(key)
+}
+
+fn entry_mac(key: &[u8; 32], entry: &KeyUsageEntry) -> Hmac<Sha256> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts any key size");
+    let fields = serde_json::json!([
+        entry.key_id,
+        entry.sign_count,
+        entry.last_used_ms,
+        entry.last_receiver_category.as_str(),
+        entry.last_receiver_hash,
+    ]);
+    mac.update(fields.to_string().as_bytes());
+    mac
+}
+
+fn entry_mac_is_valid(key: &[u8; 32], entry: &KeyUsageEntry) -> bool {
+    base64_url_decode(&entry.mac)
+        .map(|mac| entry_mac(key, entry).verify_slice(&mac).is_ok())
+        .unwrap_or(false)
+}
+
+/// Replaces the worker's entries with a record the TS layer stored. The MACs are checked
+/// when each key next signs. Returns the number of entries loaded.
+pub fn load_record(record: KeyUsageRecord) -> Result<usize, String> {
+    if record.version != KEY_USAGE_RECORD_VERSION {
+        return Err(format!(
+            "Unsupported key usage record version {} (expected {})",
+            record.version, KEY_USAGE_RECORD_VERSION
+        ));
+    }
+    if record.entries.len() > MAX_KEY_USAGE_ENTRIES {
+        return Err(format!(
+            "Key usage record has {} entries, at most {} are kept",
+            record.entries.len(),
+            MAX_KEY_USAGE_ENTRIES
+        ));
+    }
+    let mut entries = BTreeMap::new();
+    for entry in record.entries {
+        if let Some(duplicate) = entries.insert(entry.key_id.clone(), entry) {
+            return Err(format!(
+                "Key usage record lists key {} twice",
+                duplicate.key_id
+            ));
+        }
+    }
+    let loaded = entries.len();
+    KEY_USAGE.with(|k| {
+        *k.borrow_mut() = KeyUsageState {
+            entries,
+            ..KeyUsageState::default()
+        }
+    });
+    Ok(loaded)
+}
+
+pub fn load_record_json(record_json: &str) -> Result<usize, String> {
+    let record: KeyUsageRecord = serde_json::from_str(record_json)
+        .map_err(|e| format!("Invalid key usage record: {}", e))?;
+    load_record(record)
+}
+
+/// Counts `sign_count` transactions signed with the key blob `key_id`, unlocked with
+/// `prf_output`, the last of them for `receiver_id`
+pub fn record_signing(
+    key_id: &str,
+    prf_output: &str,
+    sign_count: u32,
+    receiver_id: &str,
+    signer_id: &str,
+    now_ms: f64,
+) -> Result<(), String> {
+    let mac_key = entry_mac_key(prf_output)?;
+    KEY_USAGE.with(|k| {
+        let mut k = k.borrow_mut();
+        let stored_is_valid = match k.entries.get(key_id) {
+            Some(stored) if !k.verified.contains(key_id) => entry_mac_is_valid(&mac_key, stored),
+            _ => true,
+        };
+        if !stored_is_valid {
+            warn!(
+                "RUST: Key usage entry of {} failed its MAC check, starting over",
+                key_id
+            );
+            k.entries.remove(key_id);
+            k.tampered.insert(key_id.to_string());
+        }
+        let previous_count = k.entries.get(key_id).map_or(0, |e| e.sign_count);
+        if !k.entries.contains_key(key_id) && k.entries.len() >= MAX_KEY_USAGE_ENTRIES {
+            let least_recent = k
+                .entries
+                .values()
+                .min_by(|a, b| a.last_used_ms.total_cmp(&b.last_used_ms))
+                .map(|e| e.key_id.clone());
+            if let Some(least_recent) = least_recent {
+                k.entries.remove(&least_recent);
+                k.verified.remove(&least_recent);
+            }
+        }
+        let mut entry = KeyUsageEntry {
+            key_id: key_id.to_string(),
+            sign_count: previous_count.saturating_add(sign_count),
+            last_used_ms: now_ms,
+            last_receiver_category: ReceiverCategory::of(receiver_id, signer_id),
+            last_receiver_hash: receiver_hash(receiver_id),
+            mac: String::new(),
+        };
+        entry.mac = base64_url_encode(&entry_mac(&mac_key, &entry).finalize().into_bytes());
+        k.entries.insert(key_id.to_string(), entry);
+        k.verified.insert(key_id.to_string());
+        k.changed = true;
+    });
+    Ok(())
+}
+
+pub fn export_record() -> KeyUsageRecord {
+    KEY_USAGE.with(|k| KeyUsageRecord {
+        version: KEY_USAGE_RECORD_VERSION,
+        entries: k.borrow().entries.values().cloned().collect(),
+    })
+}
+
+/// The record to hand back to the TS layer, when an entry changed since the last one
+pub fn take_changed_record() -> Option<KeyUsageRecord> {
+    let changed = KEY_USAGE.with(|k| std::mem::take(&mut k.borrow_mut().changed));
+    changed.then(export_record)
+}
+
+// === STATS VIEW ===
+
+/// Usage of one key blob
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyUsageStat {
+    #[wasm_bindgen(getter_with_clone, js_name = "keyId")]
+    pub key_id: String,
+    #[wasm_bindgen(js_name = "signCount")]
+    pub sign_count: u32,
+    #[wasm_bindgen(js_name = "lastUsedMs")]
+    pub last_used_ms: f64,
+    /// "own" | "implicit" | "named"
+    #[wasm_bindgen(getter_with_clone, js_name = "lastReceiverCategory")]
+    pub last_receiver_category: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "lastReceiverHash")]
+    pub last_receiver_hash: String,
+    /// Whether this worker checked the entry's MAC; entries from the stored record are
+    /// checked the next time their key signs
+    pub verified: bool,
+}
+
+/// Every key's usage, most recently used first
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyUsageStats {
+    #[wasm_bindgen(getter_with_clone)]
+    pub keys: Vec<KeyUsageStat>,
+    /// Keys whose stored entry failed its MAC check and was started over
+    #[wasm_bindgen(getter_with_clone, js_name = "tamperedKeyIds")]
+    pub tampered_key_ids: Vec<String>,
+}
+
+pub fn stats() -> KeyUsageStats {
+    KEY_USAGE.with(|k| {
+        let k = k.borrow();
+        let mut keys: Vec<KeyUsageStat> = k
+            .entries
+            .values()
+            .map(|entry| KeyUsageStat {
+                key_id: entry.key_id.clone(),
+                sign_count: entry.sign_count,
+                last_used_ms: entry.last_used_ms,
+                last_receiver_category: entry.last_receiver_category.as_str().to_string(),
+                last_receiver_hash: entry.last_receiver_hash.clone(),
+                verified: k.verified.contains(&entry.key_id),
+            })
+            .collect();
+        keys.sort_by(|a, b| b.last_used_ms.total_cmp(&a.last_used_ms));
+        KeyUsageStats {
+            keys,
+            tampered_key_ids: k.tampered.iter().cloned().collect(),
+        }
+    })
+}
+
+/// Forgets every entry (native tests share the worker's thread-local state)
+#[cfg(test)]
+pub fn reset() {
+    KEY_USAGE.with(|k| *k.borrow_mut() = KeyUsageState::default());
+}
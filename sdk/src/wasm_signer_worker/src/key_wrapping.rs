@@ -0,0 +1,275 @@
+// === KEY WRAPPING SCHEMES ===
+// The NEAR key blob is normally encrypted under a key derived from the passkey's PRF output
+// (blob version 1). Some authenticators (certain security keys, older Android) do not support
+// the PRF extension; when WorkerPolicy.prfFallbackSchemes allows it, they register with a
+// fallback scheme instead:
+//   v2 "largeBlob":  a random secret, written to the credential's largeBlob by the main thread
+//   v3 "passphrase": Argon2id over a caller-supplied passphrase, with a per-blob salt
+// Either secret takes the PRF output's place in the account-specific HKDF. With no PRF output
+// to derive the NEAR key from, fallback registrations generate it at random.
+//
+// Fallback blobs start with a key-wrapping header (magic, version byte and, for v3, the salt)
+// ahead of the key-check header, so decryption dispatches on the recorded scheme whichever
+// handler it runs in; the record's `version` repeats it for structural validation. The header
+// is not associated data: altering it only derives a different key. Blobs are stored per
+// device, so the devices of one account can each use a different scheme.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+
+use crate::config::{
+    CHACHA20_KEY_SIZE, ENCRYPTED_BLOB_VERSION_V1, ENCRYPTED_BLOB_VERSION_V2_LARGE_BLOB,
+    ENCRYPTED_BLOB_VERSION_V3_PASSPHRASE, KEY_WRAPPING_HEADER_MAGIC, LARGE_BLOB_SECRET_SIZE,
+    MIN_PASSPHRASE_WRAP_CHARS, PASSPHRASE_WRAP_ARGON2_ITERATIONS,
+    PASSPHRASE_WRAP_ARGON2_MEMORY_KIB, PASSPHRASE_WRAP_ARGON2_PARALLELISM,
+    PASSPHRASE_WRAP_SALT_SIZE,
+};
+use crate::crypto::{derive_chacha20_key_from_prf, encrypt_data_chacha20};
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::{BlobDecryptError, KeyWrappingError};
+use crate::types::handlers::WorkerPolicy;
+use crate::types::EncryptedDataChaCha20Response;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyWrappingScheme {
+    Prf,
+    LargeBlob,
+    Passphrase,
+}
+
+impl KeyWrappingScheme {
+    pub fn blob_version(self) -> u32 {
+        match self {
+            KeyWrappingScheme::Prf => ENCRYPTED_BLOB_VERSION_V1,
+            KeyWrappingScheme::LargeBlob => ENCRYPTED_BLOB_VERSION_V2_LARGE_BLOB,
+            KeyWrappingScheme::Passphrase => ENCRYPTED_BLOB_VERSION_V3_PASSPHRASE,
+        }
+    }
+
+    pub fn from_blob_version(version: u32) -> Option<Self> {
+        match version {
+            ENCRYPTED_BLOB_VERSION_V1 => Some(KeyWrappingScheme::Prf),
+            ENCRYPTED_BLOB_VERSION_V2_LARGE_BLOB => Some(KeyWrappingScheme::LargeBlob),
+            ENCRYPTED_BLOB_VERSION_V3_PASSPHRASE => Some(KeyWrappingScheme::Passphrase),
+            _ => None,
+        }
+    }
+
+    /// Name used in WorkerPolicy.prfFallbackSchemes and registration results
+    pub fn as_str(self) -> &'static str {
+        match self {
+            KeyWrappingScheme::Prf => "prf",
+            KeyWrappingScheme::LargeBlob => "largeBlob",
+            KeyWrappingScheme::Passphrase => "passphrase",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        [
+            KeyWrappingScheme::Prf,
+            KeyWrappingScheme::LargeBlob,
+            KeyWrappingScheme::Passphrase,
+        ]
+        .into_iter()
+        .find(|scheme| scheme.as_str() == name)
+    }
+
+    fn header_size(self) -> usize {
+        match self {
+            KeyWrappingScheme::Prf => 0,
+            KeyWrappingScheme::LargeBlob => KEY_WRAPPING_HEADER_MAGIC.len() + 1,
+            KeyWrappingScheme::Passphrase => {
+                KEY_WRAPPING_HEADER_MAGIC.len() + 1 + PASSPHRASE_WRAP_SALT_SIZE
+            }
+        }
+    }
+}
+
+/// A NEAR key encrypted under a fallback scheme
+#[derive(Debug, Clone)]
+pub struct WrappedNearKey {
+    pub encrypted: EncryptedDataChaCha20Response,
+    /// v2 only: the secret (base64url) the main thread writes to the credential's largeBlob
+    pub large_blob_secret: Option<String>,
+}
+
+/// The fallback scheme for a registration without PRF output: `requested` when the policy
+/// allows it, else the policy's first. Fails with PrfUnsupported when the policy allows none.
+pub fn select_fallback_scheme(
+    requested: Option<&str>,
+    policy: Option<&WorkerPolicy>,
+) -> Result<KeyWrappingScheme, KeyWrappingError> {
+    let mut allowed = Vec::new();
+    for name in policy
+        .map(|p| p.prf_fallback_schemes.as_slice())
+        .unwrap_or(&[])
+    {
+        match KeyWrappingScheme::parse(name) {
+            Some(KeyWrappingScheme::Prf) | None => {
+                return Err(KeyWrappingError::InvalidInput(format!(
+                    "Unknown workerPolicy.prfFallbackSchemes entry {:?} (expected \"largeBlob\" or \"passphrase\")",
+                    name
+                )))
+            }
+            Some(scheme) => allowed.push(scheme),
+        }
+    }
+    match requested {
+        None => allowed.first().copied().ok_or_else(|| {
+            KeyWrappingError::PrfUnsupported(
+                "workerPolicy.prfFallbackSchemes allows no fallback".to_string(),
+            )
+        }),
+        Some(name) => {
+            let scheme = KeyWrappingScheme::parse(name).ok_or_else(|| {
+                KeyWrappingError::InvalidInput(format!("Unknown key-wrapping scheme {:?}", name))
+            })?;
+            if allowed.contains(&scheme) {
+                Ok(scheme)
+            } else {
+                Err(KeyWrappingError::PrfUnsupported(format!(
+                    "workerPolicy.prfFallbackSchemes does not allow {:?}",
+                    name
+                )))
+            }
+        }
+    }
+}
+
+/// Argon2id output for a v3 blob, used as input keying material like a PRF output
+fn passphrase_key_material(
+    passphrase: &str,
+    salt: &[u8],
+) -> Result<[u8; CHACHA20_KEY_SIZE], String> {
+    let params = Params::new(
+        PASSPHRASE_WRAP_ARGON2_MEMORY_KIB,
+        PASSPHRASE_WRAP_ARGON2_ITERATIONS,
+        PASSPHRASE_WRAP_ARGON2_PARALLELISM,
+        Some(CHACHA20_KEY_SIZE),
+    )
+    .map_err(|e| format!("Invalid passphrase KDF parameters: {}", e))?;
+    let mut material = [0u8; CHACHA20_KEY_SIZE];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut material)
+        .map_err(|e| format!("Passphrase key derivation failed: {}", e))?;
+    Ok(material)
+}
+
+/// Encrypts `near_private_key` under a fallback scheme, prefixed with its key-wrapping header
+pub fn wrap_near_private_key(
+    near_private_key: &str,
+    near_account_id: &str,
+    scheme: KeyWrappingScheme,
+    passphrase: Option<&str>,
+) -> Result<WrappedNearKey, KeyWrappingError> {
+    let random = |size: usize| {
+        let mut bytes = vec![0u8; size];
+        getrandom::getrandom(&mut bytes)
+            .map(|_| bytes)
+            .map_err(|e| {
+                KeyWrappingError::InvalidInput(format!("Failed to generate secret: {}", e))
+            })
+    };
+    let mut header = KEY_WRAPPING_HEADER_MAGIC.to_vec();
+    header.push(scheme.blob_version() as u8);
+
+    let (key_material, large_blob_secret) = match scheme {
+        KeyWrappingScheme::Prf => {
+            return Err(KeyWrappingError::InvalidInput(
+                "PRF blobs are encrypted by the dual PRF workflow".to_string(),
+            ))
+        }
+        KeyWrappingScheme::LargeBlob => {
+            let secret = base64_url_encode(&random(LARGE_BLOB_SECRET_SIZE)?);
+            (secret.clone(), Some(secret))
+        }
+        KeyWrappingScheme::Passphrase => {
+            let passphrase = passphrase.unwrap_or_default();
+            if passphrase.chars().count() < MIN_PASSPHRASE_WRAP_CHARS {
+                return Err(KeyWrappingError::InvalidInput(format!(
+                    "The passphrase scheme needs a passphrase of at least {} characters",
+                    MIN_PASSPHRASE_WRAP_CHARS
+                )));
+            }
+            let salt = random(PASSPHRASE_WRAP_SALT_SIZE)?;
+            let material = passphrase_key_material(passphrase, &salt)
+                .map_err(KeyWrappingError::InvalidInput)?;
+            header.extend_from_slice(&salt);
+            (base64_url_encode(&material), None)
+        }
+    };
+
+    let chacha20_key = derive_chacha20_key_from_prf(&key_material, near_account_id)
+        .map_err(|e| KeyWrappingError::InvalidInput(e.to_string()))?;
+    let encrypted = encrypt_data_chacha20(near_private_key, &chacha20_key)
+        .map_err(KeyWrappingError::InvalidInput)?;
+    let inner = base64_url_decode(&encrypted.encrypted_near_key_data_b64u)
+        .map_err(KeyWrappingError::InvalidInput)?;
+    Ok(WrappedNearKey {
+        encrypted: EncryptedDataChaCha20Response {
+            encrypted_near_key_data_b64u: base64_url_encode(&[header, inner].concat()),
+            chacha20_nonce_b64u: encrypted.chacha20_nonce_b64u,
+        },
+        large_blob_secret,
+    })
+}
+
+/// Splits blob bytes into their scheme, the v3 salt (empty otherwise) and the v1-format rest.
+/// Blobs without a key-wrapping header are PRF blobs, returned whole.
+pub(crate) fn split_key_wrapping_header(
+    encrypted_data: &[u8],
+) -> (KeyWrappingScheme, &[u8], &[u8]) {
+    let magic_len = KEY_WRAPPING_HEADER_MAGIC.len();
+    let scheme = encrypted_data
+        .strip_prefix(KEY_WRAPPING_HEADER_MAGIC)
+        .and_then(|rest| rest.first())
+        .and_then(|version| KeyWrappingScheme::from_blob_version(*version as u32))
+        .filter(|scheme| {
+            *scheme != KeyWrappingScheme::Prf && encrypted_data.len() >= scheme.header_size()
+        });
+    match scheme {
+        None => (KeyWrappingScheme::Prf, &[], encrypted_data),
+        Some(scheme) => (
+            scheme,
+            &encrypted_data[magic_len + 1..scheme.header_size()],
+            &encrypted_data[scheme.header_size()..],
+        ),
+    }
+}
+
+/// The ChaCha20 key and v1-format data (base64url) of a stored blob. `unlock_secret` is what
+/// the blob's scheme derives its key from: the PRF output, the largeBlob secret or the
+/// passphrase.
+pub(crate) fn unwrap_blob_key(
+    near_account_id: &str,
+    unlock_secret: &str,
+    encrypted_data_b64u: &str,
+) -> Result<(Vec<u8>, String), BlobDecryptError> {
+    let encrypted_data = base64_url_decode(encrypted_data_b64u).map_err(|e| {
+        BlobDecryptError::CorruptedCiphertext(format!(
+            "Base64 decode error for encrypted data: {}",
+            e
+        ))
+    })?;
+    let (scheme, salt, inner) = split_key_wrapping_header(&encrypted_data);
+    let key_material = match scheme {
+        KeyWrappingScheme::Prf => {
+            return derive_chacha20_key_from_prf(unlock_secret, near_account_id)
+                .map(|key| (key, encrypted_data_b64u.to_string()))
+                .map_err(|e| {
+                    BlobDecryptError::InvalidInput(format!(
+                        "Account-specific key derivation failed: {}",
+                        e
+                    ))
+                })
+        }
+        KeyWrappingScheme::LargeBlob => unlock_secret.to_string(),
+        KeyWrappingScheme::Passphrase => base64_url_encode(
+            &passphrase_key_material(unlock_secret, salt)
+                .map_err(BlobDecryptError::InvalidInput)?,
+        ),
+    };
+    let key = derive_chacha20_key_from_prf(&key_material, near_account_id).map_err(|e| {
+        BlobDecryptError::InvalidInput(format!("{} key derivation failed: {}", scheme.as_str(), e))
+    })?;
+    Ok((key, base64_url_encode(inner)))
+}
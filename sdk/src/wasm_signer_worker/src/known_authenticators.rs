@@ -0,0 +1,180 @@
+// === KNOWN AUTHENTICATORS ===
+// Recovery guidance differs between a passkey synced by a password manager and one bound to a
+// security key, so registration names the authenticator model from the AAGUID in the
+// attestation's authData: DeriveNearKeypairAndEncrypt results carry `authenticator`, and
+// parse_attestation_object / parse_authenticator_data add `providerName` and
+// `isSyncedPasskey` to the attested credential. Names come from the table below, compiled in;
+// `configure_known_authenticators` (at Initialize, PasskeyManagerConfigs.knownAuthenticators)
+// adds entries and replaces built-in ones with the same AAGUID. An unknown AAGUID (including
+// the all-zero one of "none" attestations that hide the model) is reported as is, with no
+// provider.
+
+use serde::{Deserialize, Serialize};
+
+use crate::cose::{decode_attestation_object, decode_authenticator_data, format_aaguid};
+use crate::encoders::base64_url_decode;
+use crate::error::ConfigError;
+use crate::state;
+
+/// Well-known AAGUIDs: (aaguid, provider name, synced passkey)
+const BUILT_IN_AUTHENTICATORS: &[(&str, &str, bool)] = &[
+    (
+        "fbfc3007-154e-4ecc-8c0b-6e020557d7bd",
+        "iCloud Keychain",
+        true,
+    ),
+    (
+        "dd4ec289-e01d-41c9-bb89-70fa845d4bf2",
+        "iCloud Keychain (Managed)",
+        true,
+    ),
+    (
+        "ea9b8d66-4d01-1d21-3ce4-b6b48cb575d4",
+        "Google Password Manager",
+        true,
+    ),
+    (
+        "adce0002-35bc-c60a-648b-0b25f1f05503",
+        "Chrome on Mac",
+        false,
+    ),
+    (
+        "08987058-cadc-4b81-b6e1-30de50dcbe96",
+        "Windows Hello",
+        false,
+    ),
+    (
+        "9ddd1817-af5a-4672-a2b9-3e3dd95000a9",
+        "Windows Hello",
+        false,
+    ),
+    (
+        "6028b017-b1d4-4c02-b4b3-afcdafc96bb2",
+        "Windows Hello",
+        false,
+    ),
+    ("53414d53-554e-4700-0000-000000000000", "Samsung Pass", true),
+    ("bada5566-a7aa-401f-bd96-45619a55120d", "1Password", true),
+    ("d548826e-79b4-db40-a3d8-11116f7e8349", "Bitwarden", true),
+    ("531126d6-e717-415c-9320-3d9aa6981239", "Dashlane", true),
+    (
+        "cb69481e-8ff7-4039-93ec-0a2729a154a8",
+        "YubiKey 5 Series",
+        false,
+    ),
+    (
+        "ee882879-721c-4913-9775-3dfcce97072a",
+        "YubiKey 5 Series",
+        false,
+    ),
+    (
+        "fa2b99dc-9e39-4257-8f92-4a30d23c4118",
+        "YubiKey 5 Series with NFC",
+        false,
+    ),
+    (
+        "b92c3f9a-c014-4056-887f-140a2501163b",
+        "Security Key by Yubico",
+        false,
+    ),
+    (
+        "149a2021-8ef6-4133-96b8-81f8d5b7f1f5",
+        "Security Key by Yubico with NFC",
+        false,
+    ),
+];
+
+/// An entry of PasskeyManagerConfigs.knownAuthenticators
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticatorModel {
+    /// UUID string, any case
+    pub aaguid: String,
+    pub provider_name: String,
+    /// Whether its passkeys sync across devices; unknown when absent
+    #[serde(default)]
+    pub is_synced_passkey: Option<bool>,
+}
+
+/// The authenticator model a credential was created on
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticatorInfo {
+    /// Lowercase UUID string
+    pub aaguid: String,
+    /// Absent for AAGUIDs neither the built-in table nor the configured entries know
+    pub provider_name: Option<String>,
+    pub is_synced_passkey: Option<bool>,
+}
+
+/// Lowercase form of a UUID string (8-4-4-4-12 hex digits)
+fn normalize_aaguid(aaguid: &str) -> Option<String> {
+    let normalized = aaguid.trim().to_ascii_lowercase();
+    let well_formed = normalized.len() == 36
+        && normalized.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        });
+    well_formed.then_some(normalized)
+}
+
+/// Replaces the configured entries; each must name a UUID and a provider
+pub fn configure(entries: Vec<AuthenticatorModel>) -> Result<(), ConfigError> {
+    let entries = entries
+        .into_iter()
+        .map(|entry| {
+            let aaguid =
+                normalize_aaguid(&entry.aaguid).ok_or_else(|| ConfigError::InvalidValue {
+                    field: "knownAuthenticators",
+                    reason: format!("\"{}\" is not a UUID", entry.aaguid),
+                })?;
+            if entry.provider_name.trim().is_empty() {
+                return Err(ConfigError::InvalidValue {
+                    field: "knownAuthenticators",
+                    reason: format!("{} has no providerName", aaguid),
+                });
+            }
+            Ok(AuthenticatorModel { aaguid, ..entry })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    state::set_known_authenticators(entries);
+    Ok(())
+}
+
+/// Looks up an AAGUID: configured entries first, then the built-in table
+pub fn lookup(aaguid: &[u8; 16]) -> AuthenticatorInfo {
+    let aaguid = format_aaguid(aaguid);
+    let configured = state::known_authenticators()
+        .into_iter()
+        .find(|entry| entry.aaguid == aaguid)
+        .map(|entry| (entry.provider_name, entry.is_synced_passkey));
+    let known = configured.or_else(|| {
+        BUILT_IN_AUTHENTICATORS
+            .iter()
+            .find(|(id, _, _)| *id == aaguid)
+            .map(|(_, name, synced)| (name.to_string(), Some(*synced)))
+    });
+    match known {
+        Some((provider_name, is_synced_passkey)) => AuthenticatorInfo {
+            aaguid,
+            provider_name: Some(provider_name),
+            is_synced_passkey,
+        },
+        None => AuthenticatorInfo {
+            aaguid,
+            provider_name: None,
+            is_synced_passkey: None,
+        },
+    }
+}
+
+/// The authenticator of a base64url attestation object; None when it has no attested
+/// credential or cannot be read (registration reports the unreadable attestation itself)
+pub fn authenticator_of_attestation(attestation_object_b64u: &str) -> Option<AuthenticatorInfo> {
+    let bytes = base64_url_decode(attestation_object_b64u.trim()).ok()?;
+    let attestation = decode_attestation_object(&bytes).ok()?;
+    let auth_data = decode_authenticator_data(&attestation.auth_data).ok()?;
+    auth_data
+        .attested_credential
+        .map(|credential| lookup(&credential.aaguid))
+}
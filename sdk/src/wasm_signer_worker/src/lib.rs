@@ -1,15 +1,98 @@
+mod account_bundle;
+mod account_descriptor;
+mod action_firewall;
 mod actions;
+mod allowance;
+mod args_schema;
+mod assertion_verify;
+mod background_refresh;
+mod batch_preview;
+#[cfg(test)]
+mod bench;
+mod blob_migration;
+mod chunked_deploy;
+#[path = "../../wasm_shared/clock.rs"]
+mod clock;
+mod conditional_batch;
 mod config;
+mod confirmation_blocks;
+mod confirmation_origin;
+mod confirmation_speech;
+mod contract_args;
 mod cose;
+mod countdown_handshake;
+mod credential_options;
 mod crypto;
+#[cfg(feature = "device-linking")]
+mod deflate;
+mod deprecations;
+mod diagnostics;
+mod dual_control;
+mod elevation;
 mod encoders;
 mod error;
+#[path = "../../wasm_shared/error_codes.rs"]
+mod error_codes;
+#[cfg(feature = "secp256k1")]
+mod evm;
+mod features;
 mod handlers;
+mod hooks;
+mod idempotency;
+mod kat_vectors;
+mod key_selection;
+mod key_usage;
+mod key_wrapping;
+mod known_authenticators;
+mod memory;
+mod message_limits;
+mod multisig;
+mod operation_journal;
+mod outer_wrap;
+mod peer_channel;
+#[cfg(feature = "device-linking")]
+mod qr_payload;
+mod recent_receivers;
+mod redaction;
+mod redirect_payload;
+mod registration_resume;
+#[cfg(feature = "relayer")]
+mod relayer;
+#[cfg(feature = "device-linking")]
+mod remote_confirmation;
+mod render_budget;
 mod rpc_calls;
+mod rpc_client;
+mod rpc_endpoints;
+mod schema_pattern;
+mod sealed_secrets;
+mod self_test;
+mod session_duration;
+mod sign_phases;
+mod signature_verify;
+mod signed_tx_decoder;
+mod signing_intent;
+mod state;
+mod storage_deposit;
+mod stored_records;
+mod strict_parsing;
+mod sub_keys;
+mod subaccount;
+#[cfg(feature = "telemetry")]
+mod telemetry;
 #[cfg(test)]
 mod tests;
 mod transaction;
+mod tx_audit;
+mod tx_dependencies;
+mod tx_diff;
 mod types;
+mod unsigned_transaction;
+mod view_call;
+#[path = "../../wasm_shared/vrf_blob_meta.rs"]
+mod vrf_blob_meta;
+mod wire_format;
+mod worker_state;
 
 use serde_json;
 use wasm_bindgen::prelude::*;
@@ -19,6 +102,10 @@ use crate::types::worker_messages::{
 };
 use crate::types::*;
 
+/// Tracks live/peak heap bytes for GetMemoryStats
+#[global_allocator]
+static GLOBAL_ALLOCATOR: memory::TrackingAllocator = memory::TrackingAllocator;
+
 /////////////////////////////
 pub use handlers::handle_decrypt_private_key_with_prf::{
     handle_decrypt_private_key_with_prf, DecryptPrivateKeyRequest, DecryptPrivateKeyResult,
@@ -43,15 +130,22 @@ pub use handlers::{
     RegistrationCheckRequest,
     RegistrationCheckResult,
     RegistrationInfoStruct,
-    // Sign Nep413 Message
-    SignNep413Request,
-    SignNep413Result,
-    // Sign Transaction With Key Pair
-    SignTransactionWithKeyPairRequest,
     // Execute Actions
     SignTransactionsWithActionsRequest,
     TransactionPayload,
 };
+// Sign Nep413 Message
+#[cfg(feature = "nep413")]
+pub use handlers::{SignNep413Request, SignNep413Result};
+// Sign Transaction With Key Pair
+#[cfg(feature = "device-linking")]
+pub use handlers::SignTransactionWithKeyPairRequest;
+
+// Signed transaction audit and decoding, for server-side tooling linking the crate natively
+pub use signed_tx_decoder::{
+    inspect_signed_transaction, DecodedAction, DecodedTransaction, UnknownAction,
+};
+pub use tx_audit::{audit_signed_transaction, AuditFieldCheck, AuditVerificationResult};
 
 // Re-export NEAR types for TypeScript usage
 pub use types::near::{PublicKey, Signature, SignedTransaction, Transaction};
@@ -94,6 +188,7 @@ pub fn error(s: &str) {
 
 #[wasm_bindgen]
 pub fn init_worker() {
+    #[cfg(feature = "console_error_panic_hook")]
     console_error_panic_hook::set_once();
     // Initialize WASM logger for better debugging
     wasm_logger::init(wasm_logger::Config::default());
@@ -159,21 +254,363 @@ pub fn send_progress_message(message_type: u32, step: u32, message: &str, _data:
     }
 }
 
+// === REQUEST REGISTRY CONFIGURATION ===
+
+/// Configure the pending-request registry limits
+/// (defaults: 4 concurrent requests, 16 queued)
+#[wasm_bindgen]
+pub fn configure_request_limits(max_concurrent: u32, max_queued: u32) -> Result<(), JsValue> {
+    state::set_request_limits(state::RequestLimits {
+        max_concurrent: max_concurrent as usize,
+        max_queued: max_queued as usize,
+    })
+    .map_err(|e| JsValue::from_str(&e))
+}
+
+/// Configure the message size limits (WorkerPolicy.messageSizeLimits) before the first frame
+/// (defaults: 1 MiB, 8 MiB for DeployLargeContract); larger frames fail with MessageTooLarge
+#[wasm_bindgen]
+pub fn configure_message_size_limits(limits: JsValue) -> Result<(), JsValue> {
+    let limits: types::handlers::MessageSizeLimits = serde_wasm_bindgen::from_value(limits)
+        .map_err(|e| JsValue::from_str(&format!("Invalid messageSizeLimits: {}", e)))?;
+    message_limits::configure(limits).map_err(JsValue::from)
+}
+
+/// Configure the AAGUIDs registration names (PasskeyManagerConfigs.knownAuthenticators) at
+/// Initialize: `entries` is an array of `{ aaguid, providerName, isSyncedPasskey? }` that adds to
+/// the built-in table and replaces its entries with the same AAGUID (see known_authenticators.rs)
+#[wasm_bindgen]
+pub fn configure_known_authenticators(entries: JsValue) -> Result<(), JsValue> {
+    let entries: Vec<known_authenticators::AuthenticatorModel> =
+        serde_wasm_bindgen::from_value(entries)
+            .map_err(|e| JsValue::from_str(&format!("Invalid knownAuthenticators: {}", e)))?;
+    known_authenticators::configure(entries).map_err(JsValue::from)
+}
+
+/// Configure the origin confirmation decisions must attest (WorkerPolicy.trustedConfirmationOrigin)
+/// at Initialize; decisions without a render attestation from it fail with
+/// UntrustedConfirmationOrigin. Returns the normalized origin the bridge compares senders with.
+#[wasm_bindgen]
+pub fn configure_trusted_confirmation_origin(origin: &str) -> Result<String, JsValue> {
+    confirmation_origin::configure(origin).map_err(JsValue::from)
+}
+
 // === MESSAGE HANDLER FUNCTIONS ===
 
+/// Initialize handshake, sent before the first request frame.
+/// `wire_format` is "json" (default) or "cbor"; frames in the other format are then
+/// rejected with WireFormatMismatch.
+/// Also runs the crypto self-test and returns its report; after a failure key-handling
+/// requests are refused with SelfTestFailed until the next Initialize.
+/// Rotates the sealed secrets key: read the new one with `signer_session_public_key`.
+#[wasm_bindgen]
+pub fn initialize_signer_worker(wire_format: &str) -> Result<JsValue, JsValue> {
+    wire_format::initialize(wire_format).map_err(|e| JsValue::from_str(&e))?;
+    state::rotate_sealed_secrets_key();
+    state::clear_self_test_failure();
+    let report = self_test::run_self_test();
+    to_js_value(&report)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize self-test report: {}", e)))
+}
+
+/// base64url X25519 public key that secrets are sealed to for this session (see
+/// sealed_secrets.rs), posted by the Initialize handshake as `sessionPublicKey`
+#[wasm_bindgen]
+pub fn signer_session_public_key() -> Result<String, JsValue> {
+    sealed_secrets::session_public_key().map_err(|e| JsValue::from_str(&e))
+}
+
+/// Connects the peer session for the VRF worker port posted with CONNECT_PEER_PORT (the port
+/// itself stays in JS). Signing requests then take their VRF challenge from the VRF worker and
+/// refuse one proven with a key other than `expected_vrf_public_key`.
+#[wasm_bindgen]
+pub fn connect_peer_port(expected_vrf_public_key: String, rp_id: String) -> Result<(), JsValue> {
+    peer_channel::connect_peer_session(peer_channel::PeerSession {
+        expected_vrf_public_key,
+        rp_id,
+    })
+    .map_err(|e| JsValue::from_str(&e))
+}
+
+/// Loads the key usage record the TS layer stored from an earlier response's `keyUsageRecord`.
+/// Call it on Initialize, before the request that signs; GetKeyUsageStats then reports it.
+#[wasm_bindgen]
+pub fn load_key_usage_record(record_json: &str) -> Result<(), JsValue> {
+    key_usage::load_record_json(record_json)
+        .map(|_| ())
+        .map_err(|e| JsValue::from_str(&e))
+}
+
+/// Loads the worker state record the TS layer stored from an earlier response's
+/// `workerStateRecord` (null on first use), with the state wrap CryptoKey available through
+/// `getWorkerStateWrapKey`. Call it before the request; a record that does not open is
+/// discarded (see worker_state.rs). Resolves to whether this worker's state will be persisted.
+#[wasm_bindgen]
+pub async fn load_worker_state(record_json: Option<String>) -> Result<bool, JsValue> {
+    worker_state::load_record(record_json.as_deref())
+        .await
+        .map_err(|e| JsValue::from_str(&e))
+}
+
+/// Verifies several signatures in one call: `items` is an array of
+/// `{ publicKey, message, signature, kind }` (base64url; kind "ed25519" | "ed25519Sha256" | "hmacSha256").
+/// Returns one `{ index, valid, failure, detail }` per item. Single-kind Ed25519 inputs use
+/// batch verification; mixed kinds are verified individually.
+#[wasm_bindgen]
+pub fn verify_signatures_batch(items: JsValue) -> Result<JsValue, JsValue> {
+    let items: Vec<signature_verify::SignatureVerifyItem> = serde_wasm_bindgen::from_value(items)
+        .map_err(|e| JsValue::from_str(&format!("Invalid verify items: {}", e)))?;
+    let results = signature_verify::verify_signatures(&items);
+    to_js_value(&results)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize verify results: {}", e)))
+}
+
+/// Verifies an account descriptor blob from BuildAccountDescriptor against `public_key`
+/// (`ed25519:<base58>` or base64url) and enforces its embedded expiry.
+/// Returns the parsed descriptor fields; errors start with DescriptorInvalid or DescriptorExpired.
+#[wasm_bindgen]
+pub fn verify_account_descriptor(descriptor: &str, public_key: &str) -> Result<JsValue, JsValue> {
+    let descriptor =
+        account_descriptor::verify_account_descriptor(descriptor, public_key, state::now_ms())
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    to_js_value(&descriptor)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize account descriptor: {}", e)))
+}
+
+/// Builds the transaction described by `payload_json` (`{ signerAccountId, signerPublicKey,
+/// receiverId, nonce, blockHash, actions }`) exactly as the signing path would, without signing.
+/// Returns `{ borshBase64, txHashB58, parsedSummary }`; no key material is involved.
+#[wasm_bindgen]
+pub fn build_unsigned_transaction(payload_json: &str) -> Result<JsValue, JsValue> {
+    let request: unsigned_transaction::UnsignedTransactionRequest =
+        serde_json::from_str(payload_json).map_err(|e| {
+            JsValue::from_str(&format!("Invalid unsigned transaction payload: {}", e))
+        })?;
+    let unsigned = unsigned_transaction::build_unsigned_transaction(&request)
+        .map_err(|e| JsValue::from_str(&e))?;
+    to_js_value(&unsigned)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize unsigned transaction: {}", e)))
+}
+
+/// Checks a signed transaction (standard base64 borsh) against the intent digest of its
+/// confirmation and the summary shown (JSON, a txSigningRequests entry or parsedSummary):
+/// recomputes the digest from the transaction, verifies its signature and compares the summary
+/// field by field. Returns `{ valid, signatureValid, digest, digestMatches, summaryMatches,
+/// fields, txHashB58, transaction }`; needs no key material or worker session.
+#[wasm_bindgen]
+pub fn verify_signed_transaction(
+    signed_tx_base64: &str,
+    expected_digest: &str,
+    expected_summary_json: &str,
+) -> Result<JsValue, JsValue> {
+    let result =
+        tx_audit::audit_signed_transaction(signed_tx_base64, expected_digest, expected_summary_json)
+            .map_err(|e| JsValue::from_str(&e))?;
+    to_js_value(&result)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize audit result: {}", e)))
+}
+
+/// Decodes a signed transaction (standard base64 borsh) from any wallet and renders it like a
+/// confirmation. Returns `{ signerId, publicKey, nonce, receiverId, blockHash, txHashB58,
+/// signatureValid, actionCount, actions, txTree, summaryBlocks }`; actions of unknown types
+/// decode as `{ action_type: "UnknownAction", discriminant, rawBytes }`. Malformed input throws
+/// an error naming the byte offset. Needs no key material or worker session.
+#[wasm_bindgen]
+pub fn decode_signed_transaction(signed_tx_base64: &str) -> Result<JsValue, JsValue> {
+    let decoded = signed_tx_decoder::inspect_signed_transaction(signed_tx_base64)
+        .map_err(|e| JsValue::from_str(&e))?;
+    to_js_value(&decoded)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize decoded transaction: {}", e)))
+}
+
+/// Encodes a device-linking or remote-confirmation blob (base64url) for QR codes: compressed,
+/// base45 for QR alphanumeric mode, and split into as few parts of at most `max_part_chars`
+/// characters (default 1200) as it needs. `kind` is "deviceLinking" | "remoteConfirmation".
+/// Returns the part strings in index order; errors start with QrPayloadInvalid.
+#[cfg(feature = "device-linking")]
+#[wasm_bindgen]
+pub fn encode_qr_payload(
+    kind: &str,
+    blob_b64u: &str,
+    max_part_chars: Option<u32>,
+) -> Result<JsValue, JsValue> {
+    let kind = qr_payload::QrPayloadKind::parse(kind)
+        .ok_or_else(|| JsValue::from_str(&format!("Unknown QR payload kind: {}", kind)))?;
+    let blob = encoders::base64_url_decode(blob_b64u)
+        .map_err(|e| JsValue::from_str(&format!("Invalid QR payload blob: {}", e)))?;
+    let encoder = match max_part_chars {
+        Some(max_part_chars) => qr_payload::QrPayloadEncoder::new(max_part_chars as usize)?,
+        None => qr_payload::QrPayloadEncoder::default(),
+    };
+    to_js_value(&encoder.encode(kind, &blob)?)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize QR parts: {}", e)))
+}
+
+/// Reassembles the QR parts scanned so far, in any order. Returns `{ kind, partCount,
+/// missingParts, blobB64u }`, the blob null until no part is missing; parts of another payload
+/// and blobs that fail the checksum throw QrPayloadInvalid.
+#[cfg(feature = "device-linking")]
+#[wasm_bindgen]
+pub fn decode_qr_payload(parts: JsValue) -> Result<JsValue, JsValue> {
+    let parts: Vec<String> = serde_wasm_bindgen::from_value(parts)
+        .map_err(|e| JsValue::from_str(&format!("Invalid QR parts: {}", e)))?;
+    let decoded = qr_payload::decode_qr_parts(&parts)?;
+    to_js_value(&decoded)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize QR payload: {}", e)))
+}
+
+/// The receiving side of a BuildRedirectPayload redirect: checks the token's session key
+/// signature, its expiry, and that it was issued for `callback_url` (the URL before the token
+/// and state were appended) and `state`. Returns `{ nearAccountId, sessionPublicKey,
+/// vrfChallenge, callbackUrl, state, issuedAtMs, expiresAtMs, nonce }`; errors start with
+/// RedirectPayloadInvalid or RedirectPayloadExpired. Whether sessionPublicKey is an access key
+/// of the account is for the caller to check on chain.
+#[wasm_bindgen]
+pub fn verify_redirect_payload(
+    token: &str,
+    callback_url: &str,
+    state: &str,
+) -> Result<JsValue, JsValue> {
+    let verified =
+        redirect_payload::verify_redirect_payload(token, callback_url, state, state::now_ms())
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    to_js_value(&verified)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize redirect payload: {}", e)))
+}
+
+/// Upgrades an IndexedDB record (PasskeyClientDB user or PasskeyNearKeys key record) written by
+/// an earlier release to the current schema. Current records are returned as the same string,
+/// so the storage layer can rewrite only the records that differ.
+#[wasm_bindgen(js_name = migrateStoredRecord)]
+pub fn migrate_stored_record(record_json: &str) -> Result<String, JsValue> {
+    let record: serde_json::Value = serde_json::from_str(record_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid stored record JSON: {}", e)))?;
+    let migrated = stored_records::migrate_stored_record(record.clone())
+        .map_err(|e| JsValue::from_str(&e))?;
+    if migrated == record {
+        return Ok(record_json.to_string());
+    }
+    Ok(migrated.to_string())
+}
+
+/// Describes a base64url WebAuthn attestation object: format, attestation statement keys and
+/// the authenticator data (see parse_authenticator_data). Uses the parser registration uses
+/// to extract the credential public key; needs no worker session. The AAGUID's provider comes
+/// from the built-in table and this module's configure_known_authenticators entries.
+/// Malformed input throws `{ code: "WebAuthnDataMalformed", section, offset, message }`.
+#[wasm_bindgen]
+pub fn parse_attestation_object(attestation_object_b64u: &str) -> Result<JsValue, JsValue> {
+    let parsed = cose::describe_attestation_object_b64u(attestation_object_b64u)?;
+    to_js_value(&parsed)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize attestation: {}", e)))
+}
+
+/// Describes base64url authenticator data: rpIdHash, flags, signCount and, when present, the
+/// attested credential (AAGUID as a UUID string with the provider it names, credential ID,
+/// public key as a JWK with its algorithm name). Throws like parse_attestation_object.
+#[wasm_bindgen]
+pub fn parse_authenticator_data(auth_data_b64u: &str) -> Result<JsValue, JsValue> {
+    let parsed = cose::describe_authenticator_data_b64u(auth_data_b64u)?;
+    to_js_value(&parsed)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize authenticator data: {}", e)))
+}
+
+/// Converts a base64url COSE key (OKP, EC2 or RSA) to a JWK. Throws like
+/// parse_attestation_object.
+#[wasm_bindgen]
+pub fn cose_key_to_jwk(cose_key_b64u: &str) -> Result<JsValue, JsValue> {
+    let jwk = cose::cose_key_b64u_to_jwk(cose_key_b64u)?;
+    to_js_value(&jwk)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize JWK: {}", e)))
+}
+
 /// Unified message handler for all signer worker operations
 /// This replaces the TypeScript-based message dispatching with a Rust-based approach
 /// for better type safety and performance
 #[wasm_bindgen]
 pub async fn handle_signer_message(message_json: &str) -> Result<String, JsValue> {
     init_worker();
+    dispatch_json_frame(message_json)
+        .await
+        .map_err(|e| JsValue::from_str(&e))
+}
 
-    // Parse the JSON message
-    let msg: SignerWorkerMessage = serde_json::from_str(message_json)
-        .map_err(|e| JsValue::from_str(&format!("Failed to parse message: {:?}", e)))?;
+/// CBOR counterpart of `handle_signer_message` for workers initialized with wireFormat "cbor"
+#[wasm_bindgen]
+pub async fn handle_signer_message_cbor(frame: Vec<u8>) -> Result<Vec<u8>, JsValue> {
+    init_worker();
+    dispatch_cbor_frame(&frame)
+        .await
+        .map_err(|e| JsValue::from_str(&e))
+}
 
-    // Convert numeric enum to WorkerRequestType using From trait
-    let request_type = WorkerRequestType::from(msg.msg_type);
+/// `handle_signer_message` without the wasm glue (callable in native tests). Every frame that
+/// decodes to a known request type gets a response, failures included; only frames that do not
+/// decode are errors.
+pub(crate) async fn dispatch_json_frame(message_json: &str) -> Result<String, String> {
+    let msg = wire_format::decode_json_frame(state::wire_format(), message_json)?;
+    let response = dispatch_signer_message(msg).await?;
+    wire_format::encode_json_response(&response)
+}
+
+/// `handle_signer_message_cbor` without the wasm glue
+pub(crate) async fn dispatch_cbor_frame(frame: &[u8]) -> Result<Vec<u8>, String> {
+    let msg = wire_format::decode_cbor_frame(state::wire_format(), frame)?;
+    let response = dispatch_signer_message(msg).await?;
+    wire_format::encode_cbor_response(&response)
+}
+
+/// Routes a decoded message to its handler and counts it for telemetry; shared by both wire
+/// formats. Messages of an unknown type are refused, as they have no failure response type.
+async fn dispatch_signer_message(msg: SignerWorkerMessage) -> Result<SignerWorkerResponse, String> {
+    let request_type = WorkerRequestType::from_u32(msg.msg_type)
+        .ok_or_else(|| format!("Unknown message type: {}", msg.msg_type))?;
+    // Latency covers queueing, confirmation and the handler
+    #[cfg(feature = "telemetry")]
+    let started_at_ms = state::monotonic_ms();
+    #[cfg(feature = "telemetry")]
+    telemetry::configure_from_payload(&msg.payload, state::now_ms());
+
+    let mut response = route_signer_message(request_type, msg).await;
+    // Requests that signed hand the updated usage record back for the TS layer to store
+    if let Some(record) = key_usage::take_changed_record() {
+        if let (Some(payload), Ok(record)) =
+            (response.payload.as_object_mut(), serde_json::to_value(record))
+        {
+            payload.insert("keyUsageRecord".to_string(), record);
+        }
+    }
+    // As do requests that changed the persisted worker state, failed ones included
+    if let Some(record) = worker_state::take_changed_record() {
+        if let (Some(payload), Ok(record)) =
+            (response.payload.as_object_mut(), serde_json::to_value(record))
+        {
+            payload.insert("workerStateRecord".to_string(), record);
+        }
+    }
+
+    #[cfg(feature = "telemetry")]
+    if request_type.is_tracked() {
+        let outcome = telemetry::outcome_of(
+            worker_response_type_name(WorkerResponseType::from(response.response_type))
+                .ends_with("_FAILURE"),
+            &response.payload,
+        );
+        telemetry::record_request(
+            request_type.name(),
+            state::monotonic_ms() - started_at_ms,
+            outcome,
+            telemetry::random_sample(),
+        );
+        telemetry::flush_if_due(state::now_ms()).await;
+    }
+    Ok(response)
+}
+
+async fn route_signer_message(
+    request_type: WorkerRequestType,
+    mut msg: SignerWorkerMessage,
+) -> SignerWorkerResponse {
 
     // Debug logging to understand what's happening
     log(&format!(
@@ -186,60 +623,125 @@ pub async fn handle_signer_message(message_json: &str) -> Result<String, JsValue
         request_type
     ));
 
-    // Route message to appropriate handler
-    let response_payload = match request_type {
-        WorkerRequestType::DeriveNearKeypairAndEncrypt => {
-            let request = msg.parse_payload::<DeriveNearKeypairAndEncryptRequest>(request_type)?;
-            let result = handlers::handle_derive_near_keypair_and_encrypt(request).await?;
-            result.to_json()
+    // Admit the request into the pending-request registry; queued requests wait for a slot.
+    // The slot is released when `_request_slot` drops at the end of this function.
+    let request_id = msg
+        .request_id
+        .clone()
+        .unwrap_or_else(handlers::generate_request_id);
+    let mut error_code: Option<error::SignerErrorCode> = None;
+    let _request_slot = if request_type.is_tracked() {
+        match state::admit_request(&request_id, request_type.name()) {
+            Ok(slot) => {
+                if let Err(code) = slot.started().await {
+                    error_code = Some(code);
+                }
+                Some(slot)
+            }
+            Err(code) => {
+                error_code = Some(code);
+                None
+            }
         }
-        WorkerRequestType::RecoverKeypairFromPasskey => {
-            let request = msg.parse_payload::<RecoverKeypairRequest>(request_type)?;
-            let result = handlers::handle_recover_keypair_from_passkey(request).await?;
-            result.to_json()
+    } else {
+        None
+    };
+
+    // Deprecation warnings: noted by the steps and handlers below, refused under failOnDeprecated
+    deprecations::begin_request(&msg.payload);
+
+    // Sealed secrets: opened into the payload before it is parsed, plaintext refused under
+    // requireEncryptedSecrets
+    let mut rejection: Option<String> = None;
+    if error_code.is_none() {
+        if let Err(e) = sealed_secrets::open_request_secrets(request_type, &mut msg.payload) {
+            error_code = Some(e.code());
+            rejection = Some(e.to_string());
         }
-        WorkerRequestType::CheckCanRegisterUser => {
-            let request = msg.parse_payload::<CheckCanRegisterUserRequest>(request_type)?;
-            let result = handlers::handle_check_can_register_user(request).await?;
-            result.to_json()
+    }
+
+    // Strict parsing: the raw payload is checked for unknown fields before the typed parse
+    if error_code.is_none() {
+        if let Err(e) = strict_parsing::check_unknown_fields(request_type, &msg.payload) {
+            error_code = Some(e.code());
+            rejection = Some(e.to_string());
         }
-        WorkerRequestType::DecryptPrivateKeyWithPrf => {
-            let request = msg.parse_payload::<DecryptPrivateKeyRequest>(request_type)?;
-            let result = handlers::handle_decrypt_private_key_with_prf(request).await?;
-            result.to_json()
+    }
+
+    // Message size limits: a policy's messageSizeLimits apply from the next frame on
+    if error_code.is_none() {
+        if let Err(e) = message_limits::apply_policy_limits(&msg.payload) {
+            error_code = Some(e.code());
+            rejection = Some(e.to_string());
         }
-        WorkerRequestType::SignTransactionsWithActions => {
-            let request = msg.parse_payload::<SignTransactionsWithActionsRequest>(request_type)?;
-            let result = handlers::handle_sign_transactions_with_actions(request).await?;
-            result.to_json()
+    }
+
+    // Compiled features: request types whose handler this build left out are refused
+    if error_code.is_none() {
+        if let Err(e) = features::check_feature_compiled(request_type) {
+            error_code = Some(e.code());
+            rejection = Some(e.to_string());
         }
-        WorkerRequestType::ExtractCosePublicKey => {
-            let request = msg.parse_payload::<ExtractCoseRequest>(request_type)?;
-            let result = handlers::handle_extract_cose_public_key(request).await?;
-            result.to_json()
+    }
+
+    // Self-test: after a failed known-answer test, key-handling requests are refused
+    if error_code.is_none() {
+        if let Err(e) = self_test::check_key_handling_allowed(request_type) {
+            error_code = Some(e.code());
+            rejection = Some(e.to_string());
         }
-        WorkerRequestType::SignTransactionWithKeyPair => {
-            let request = msg.parse_payload::<SignTransactionWithKeyPairRequest>(request_type)?;
-            let result = handlers::handle_sign_transaction_with_keypair(request).await?;
-            result.to_json()
+    }
+
+    // Session duration: a policy's maxSessionDurationMs applies from this request on; once the
+    // session expired, key-using requests that cannot collect a fresh credential are refused
+    if error_code.is_none() {
+        if let Err(e) = session_duration::apply_policy_duration(&msg.payload) {
+            error_code = Some(e.code());
+            rejection = Some(e.to_string());
         }
-        WorkerRequestType::SignNep413Message => {
-            let request = msg.parse_payload::<SignNep413Request>(request_type)?;
-            let result = handlers::handle_sign_nep413_message(request).await?;
-            result.to_json()
+    }
+    if error_code.is_none() {
+        if let Err(e) = session_duration::check_session_allowed(request_type) {
+            error_code = Some(e.code());
+            rejection = Some(e.to_string());
         }
-        WorkerRequestType::RegistrationCredentialConfirmation => {
-            let request = msg.parse_payload::<handlers::RegistrationCredentialConfirmationRequest>(request_type)?;
-            let result = handlers::handle_request_registration_credential_confirmation(request).await?;
-            result.to_json()
+    }
+
+    // Idempotency keys: a repeated key replays the stored response instead of re-executing
+    let mut replayed_response: Option<serde_json::Value> = None;
+    if error_code.is_none() {
+        match idempotency::replayed_response(request_type, &msg.payload) {
+            Ok(response) => replayed_response = response,
+            Err(e) => {
+                error_code = Some(e.code());
+                rejection = Some(e.to_string());
+            }
         }
-        WorkerRequestType::ExportNearKeypairUI => {
-            let request = msg.parse_payload::<handlers::ExportNearKeypairUiRequest>(request_type)?;
-            let result = handlers::handle_export_near_keypair_ui(request).await?;
-            result.to_json()
+    }
+
+    // Route message to appropriate handler
+    let response_payload = if let Some(code) = error_code {
+        Err(rejection
+            .unwrap_or_else(|| format!("Request {} was not started: {}", request_id, code)))
+    } else if let Some(response) = replayed_response {
+        log(&format!(
+            "WASM Worker: Replaying completed {} for its idempotency key",
+            request_type.name()
+        ));
+        Ok(response)
+    } else {
+        let response_payload = run_handler(request_type, &msg).await;
+        if let Ok(response) = &response_payload {
+            idempotency::record_response(request_type, &msg.payload, response);
+            session_duration::note_completed(request_type, response);
         }
+        response_payload
     };
 
+    // Free this request's large transient buffers so repeated big operations
+    // don't ratchet memory upward
+    memory::release_transient_buffers(config::TRANSIENT_ARENA_RETAIN_MAX_BYTES);
+
     // Handle the result and determine response type
     let (response_type, response_payload) = match response_payload {
         Ok(message) => {
@@ -275,6 +777,62 @@ pub async fn handle_signer_message(message_json: &str) -> Result<String, JsValue
                 WorkerRequestType::ExportNearKeypairUI => {
                     WorkerResponseType::ExportNearKeypairUiSuccess
                 }
+                WorkerRequestType::GetBorshSchemas => WorkerResponseType::GetBorshSchemasSuccess,
+                WorkerRequestType::ListPendingRequests => WorkerResponseType::ListPendingRequestsSuccess,
+                WorkerRequestType::CancelRequest => WorkerResponseType::CancelRequestSuccess,
+                WorkerRequestType::WipeAllState => WorkerResponseType::WipeAllStateSuccess,
+                WorkerRequestType::ValidateEncryptedBlobs => WorkerResponseType::ValidateEncryptedBlobsSuccess,
+                WorkerRequestType::CreateSessionKey => WorkerResponseType::CreateSessionKeySuccess,
+                WorkerRequestType::SignWithSessionKey => WorkerResponseType::SignWithSessionKeySuccess,
+                WorkerRequestType::RevokeSessionKey => WorkerResponseType::RevokeSessionKeySuccess,
+                WorkerRequestType::ComposeMultisigRequest => WorkerResponseType::ComposeMultisigRequestSuccess,
+                WorkerRequestType::GetMemoryStats => WorkerResponseType::GetMemoryStatsSuccess,
+                WorkerRequestType::TrimCaches => WorkerResponseType::TrimCachesSuccess,
+                WorkerRequestType::GetRecentReceivers => WorkerResponseType::GetRecentReceiversSuccess,
+                WorkerRequestType::BuildAccountDescriptor => WorkerResponseType::BuildAccountDescriptorSuccess,
+                WorkerRequestType::DeployLargeContract => WorkerResponseType::DeployLargeContractSuccess,
+                WorkerRequestType::RunSelfTest => WorkerResponseType::RunSelfTestSuccess,
+                WorkerRequestType::ExportAccountBundle => WorkerResponseType::ExportAccountBundleSuccess,
+                WorkerRequestType::ImportAccountBundle => WorkerResponseType::ImportAccountBundleSuccess,
+                WorkerRequestType::CreateSigningIntent => WorkerResponseType::CreateSigningIntentSuccess,
+                WorkerRequestType::ExecuteSigningIntent => WorkerResponseType::ExecuteSigningIntentSuccess,
+                WorkerRequestType::SubmitToRelayer => WorkerResponseType::SubmitToRelayerSuccess,
+                WorkerRequestType::GetTelemetrySnapshot => WorkerResponseType::GetTelemetrySnapshotSuccess,
+                WorkerRequestType::GetKeyUsageStats => WorkerResponseType::GetKeyUsageStatsSuccess,
+                WorkerRequestType::PrepareRegistration => WorkerResponseType::PrepareRegistrationSuccess,
+                WorkerRequestType::CompleteRegistration => WorkerResponseType::CompleteRegistrationSuccess,
+                WorkerRequestType::CreateRemoteConfirmation => WorkerResponseType::CreateRemoteConfirmationSuccess,
+                WorkerRequestType::ApproveRemoteConfirmation => WorkerResponseType::ApproveRemoteConfirmationSuccess,
+                WorkerRequestType::CompleteRemoteConfirmation => WorkerResponseType::CompleteRemoteConfirmationSuccess,
+                WorkerRequestType::GetWorkerInfo => WorkerResponseType::GetWorkerInfoSuccess,
+                WorkerRequestType::ValidateDecryptionCapability => WorkerResponseType::ValidateDecryptionCapabilitySuccess,
+                WorkerRequestType::ListActiveAccounts => WorkerResponseType::ListActiveAccountsSuccess,
+                WorkerRequestType::WipeAccountState => WorkerResponseType::WipeAccountStateSuccess,
+                WorkerRequestType::ValidateArgsSchemas => WorkerResponseType::ValidateArgsSchemasSuccess,
+                WorkerRequestType::SummarizeTransactions => WorkerResponseType::SummarizeTransactionsSuccess,
+                WorkerRequestType::MigrateEncryptedBlobs => WorkerResponseType::MigrateEncryptedBlobsSuccess,
+                WorkerRequestType::BuildCredentialCreationOptions => WorkerResponseType::BuildCredentialCreationOptionsSuccess,
+                WorkerRequestType::BuildCredentialRequestOptions => WorkerResponseType::BuildCredentialRequestOptionsSuccess,
+                WorkerRequestType::RecoverPendingOperations => WorkerResponseType::RecoverPendingOperationsSuccess,
+                WorkerRequestType::ComposeCreateSubaccount => WorkerResponseType::ComposeCreateSubaccountSuccess,
+                WorkerRequestType::StartBackgroundRefresh => WorkerResponseType::StartBackgroundRefreshSuccess,
+                WorkerRequestType::StopBackgroundRefresh => WorkerResponseType::StopBackgroundRefreshSuccess,
+                WorkerRequestType::BackgroundRefreshStatus => WorkerResponseType::BackgroundRefreshStatusSuccess,
+                WorkerRequestType::SuspendHint => WorkerResponseType::SuspendHintSuccess,
+                WorkerRequestType::ResumeHint => WorkerResponseType::ResumeHintSuccess,
+                WorkerRequestType::DeriveSubKey => WorkerResponseType::DeriveSubKeySuccess,
+                WorkerRequestType::ViewCall => WorkerResponseType::ViewCallSuccess,
+                WorkerRequestType::DumpDiagnostics => WorkerResponseType::DumpDiagnosticsSuccess,
+                WorkerRequestType::SignTransactionsWithFreshChallenge => {
+                    WorkerResponseType::SignTransactionsWithFreshChallengeSuccess
+                }
+                WorkerRequestType::GetSessionStatus => WorkerResponseType::GetSessionStatusSuccess,
+                WorkerRequestType::BuildRedirectPayload => {
+                    WorkerResponseType::BuildRedirectPayloadSuccess
+                }
+                WorkerRequestType::IssueElevationChallenge => {
+                    WorkerResponseType::IssueElevationChallengeSuccess
+                }
             };
             (success_response_type, message)
         }
@@ -311,10 +869,73 @@ pub async fn handle_signer_message(message_json: &str) -> Result<String, JsValue
                 WorkerRequestType::ExportNearKeypairUI => {
                     WorkerResponseType::ExportNearKeypairUiFailure
                 }
+                WorkerRequestType::GetBorshSchemas => WorkerResponseType::GetBorshSchemasFailure,
+                WorkerRequestType::ListPendingRequests => WorkerResponseType::ListPendingRequestsFailure,
+                WorkerRequestType::CancelRequest => WorkerResponseType::CancelRequestFailure,
+                WorkerRequestType::WipeAllState => WorkerResponseType::WipeAllStateFailure,
+                WorkerRequestType::ValidateEncryptedBlobs => WorkerResponseType::ValidateEncryptedBlobsFailure,
+                WorkerRequestType::CreateSessionKey => WorkerResponseType::CreateSessionKeyFailure,
+                WorkerRequestType::SignWithSessionKey => WorkerResponseType::SignWithSessionKeyFailure,
+                WorkerRequestType::RevokeSessionKey => WorkerResponseType::RevokeSessionKeyFailure,
+                WorkerRequestType::ComposeMultisigRequest => WorkerResponseType::ComposeMultisigRequestFailure,
+                WorkerRequestType::GetMemoryStats => WorkerResponseType::GetMemoryStatsFailure,
+                WorkerRequestType::TrimCaches => WorkerResponseType::TrimCachesFailure,
+                WorkerRequestType::GetRecentReceivers => WorkerResponseType::GetRecentReceiversFailure,
+                WorkerRequestType::BuildAccountDescriptor => WorkerResponseType::BuildAccountDescriptorFailure,
+                WorkerRequestType::DeployLargeContract => WorkerResponseType::DeployLargeContractFailure,
+                WorkerRequestType::RunSelfTest => WorkerResponseType::RunSelfTestFailure,
+                WorkerRequestType::ExportAccountBundle => WorkerResponseType::ExportAccountBundleFailure,
+                WorkerRequestType::ImportAccountBundle => WorkerResponseType::ImportAccountBundleFailure,
+                WorkerRequestType::CreateSigningIntent => WorkerResponseType::CreateSigningIntentFailure,
+                WorkerRequestType::ExecuteSigningIntent => WorkerResponseType::ExecuteSigningIntentFailure,
+                WorkerRequestType::SubmitToRelayer => WorkerResponseType::SubmitToRelayerFailure,
+                WorkerRequestType::GetTelemetrySnapshot => WorkerResponseType::GetTelemetrySnapshotFailure,
+                WorkerRequestType::GetKeyUsageStats => WorkerResponseType::GetKeyUsageStatsFailure,
+                WorkerRequestType::PrepareRegistration => WorkerResponseType::PrepareRegistrationFailure,
+                WorkerRequestType::CompleteRegistration => WorkerResponseType::CompleteRegistrationFailure,
+                WorkerRequestType::CreateRemoteConfirmation => WorkerResponseType::CreateRemoteConfirmationFailure,
+                WorkerRequestType::ApproveRemoteConfirmation => WorkerResponseType::ApproveRemoteConfirmationFailure,
+                WorkerRequestType::CompleteRemoteConfirmation => WorkerResponseType::CompleteRemoteConfirmationFailure,
+                WorkerRequestType::GetWorkerInfo => WorkerResponseType::GetWorkerInfoFailure,
+                WorkerRequestType::ValidateDecryptionCapability => WorkerResponseType::ValidateDecryptionCapabilityFailure,
+                WorkerRequestType::ListActiveAccounts => WorkerResponseType::ListActiveAccountsFailure,
+                WorkerRequestType::WipeAccountState => WorkerResponseType::WipeAccountStateFailure,
+                WorkerRequestType::ValidateArgsSchemas => WorkerResponseType::ValidateArgsSchemasFailure,
+                WorkerRequestType::SummarizeTransactions => WorkerResponseType::SummarizeTransactionsFailure,
+                WorkerRequestType::MigrateEncryptedBlobs => WorkerResponseType::MigrateEncryptedBlobsFailure,
+                WorkerRequestType::BuildCredentialCreationOptions => WorkerResponseType::BuildCredentialCreationOptionsFailure,
+                WorkerRequestType::BuildCredentialRequestOptions => WorkerResponseType::BuildCredentialRequestOptionsFailure,
+                WorkerRequestType::RecoverPendingOperations => WorkerResponseType::RecoverPendingOperationsFailure,
+                WorkerRequestType::ComposeCreateSubaccount => WorkerResponseType::ComposeCreateSubaccountFailure,
+                WorkerRequestType::StartBackgroundRefresh => WorkerResponseType::StartBackgroundRefreshFailure,
+                WorkerRequestType::StopBackgroundRefresh => WorkerResponseType::StopBackgroundRefreshFailure,
+                WorkerRequestType::BackgroundRefreshStatus => WorkerResponseType::BackgroundRefreshStatusFailure,
+                WorkerRequestType::SuspendHint => WorkerResponseType::SuspendHintFailure,
+                WorkerRequestType::ResumeHint => WorkerResponseType::ResumeHintFailure,
+                WorkerRequestType::DeriveSubKey => WorkerResponseType::DeriveSubKeyFailure,
+                WorkerRequestType::ViewCall => WorkerResponseType::ViewCallFailure,
+                WorkerRequestType::DumpDiagnostics => WorkerResponseType::DumpDiagnosticsFailure,
+                WorkerRequestType::SignTransactionsWithFreshChallenge => {
+                    WorkerResponseType::SignTransactionsWithFreshChallengeFailure
+                }
+                WorkerRequestType::GetSessionStatus => WorkerResponseType::GetSessionStatusFailure,
+                WorkerRequestType::BuildRedirectPayload => {
+                    WorkerResponseType::BuildRedirectPayloadFailure
+                }
+                WorkerRequestType::IssueElevationChallenge => {
+                    WorkerResponseType::IssueElevationChallengeFailure
+                }
             };
+            let definition = error_code
+                .map(|code| code.definition())
+                .or_else(|| error_codes::error_code_of_message(&error));
+            let (category, retriable) = error_codes::category_and_retriable(definition);
             let error_payload = serde_json::json!({
                 "error": error,
-                "context": { "type": msg.msg_type }
+                "errorCode": definition.map(|def| def.code),
+                "errorCategory": category.as_str(),
+                "retriable": retriable,
+                "context": { "type": msg.msg_type, "requestId": request_id }
             });
             (failure_response_type, error_payload)
         }
@@ -329,14 +950,339 @@ pub async fn handle_signer_message(message_json: &str) -> Result<String, JsValue
     ));
 
     // Create the final response
-    let response = SignerWorkerResponse {
+    SignerWorkerResponse {
         response_type: u32::from(response_type),
         payload: response_payload,
-    };
+        warnings: deprecations::take_warnings(),
+    }
+}
 
-    // Return JSON string
-    serde_json::to_string(&response)
-        .map_err(|e| JsValue::from_str(&format!("Failed to serialize response: {:?}", e)))
+/// Parses the payload into the request of `request_type` and runs its handler. Payloads that
+/// do not parse fail like any other request, with a failure response.
+async fn run_handler(
+    request_type: WorkerRequestType,
+    msg: &SignerWorkerMessage,
+) -> Result<serde_json::Value, String> {
+    match request_type {
+        WorkerRequestType::DeriveNearKeypairAndEncrypt => {
+            let request = msg.parse_payload::<DeriveNearKeypairAndEncryptRequest>(request_type)?;
+            let result = handlers::handle_derive_near_keypair_and_encrypt(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::RecoverKeypairFromPasskey => {
+            let request = msg.parse_payload::<RecoverKeypairRequest>(request_type)?;
+            let result = handlers::handle_recover_keypair_from_passkey(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::CheckCanRegisterUser => {
+            let request = msg.parse_payload::<CheckCanRegisterUserRequest>(request_type)?;
+            let result = handlers::handle_check_can_register_user(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::DecryptPrivateKeyWithPrf => {
+            let request = msg.parse_payload::<DecryptPrivateKeyRequest>(request_type)?;
+            let result = handlers::handle_decrypt_private_key_with_prf(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::SignTransactionsWithActions => {
+            let request = msg.parse_payload::<SignTransactionsWithActionsRequest>(request_type)?;
+            let result = handlers::handle_sign_transactions_with_actions(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::ExtractCosePublicKey => {
+            let request = msg.parse_payload::<ExtractCoseRequest>(request_type)?;
+            let result = handlers::handle_extract_cose_public_key(request).await?;
+            result.to_json()
+        }
+        #[cfg(feature = "device-linking")]
+        WorkerRequestType::SignTransactionWithKeyPair => {
+            let request = msg.parse_payload::<SignTransactionWithKeyPairRequest>(request_type)?;
+            let result = handlers::handle_sign_transaction_with_keypair(request).await?;
+            result.to_json()
+        }
+        #[cfg(feature = "nep413")]
+        WorkerRequestType::SignNep413Message => {
+            let request = msg.parse_payload::<SignNep413Request>(request_type)?;
+            let result = handlers::handle_sign_nep413_message(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::RegistrationCredentialConfirmation => {
+            let request = msg.parse_payload::<handlers::RegistrationCredentialConfirmationRequest>(request_type)?;
+            let result = handlers::handle_request_registration_credential_confirmation(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::ExportNearKeypairUI => {
+            let request = msg.parse_payload::<handlers::ExportNearKeypairUiRequest>(request_type)?;
+            let result = handlers::handle_export_near_keypair_ui(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::GetBorshSchemas => {
+            let request = msg.parse_payload::<handlers::GetBorshSchemasRequest>(request_type)?;
+            let result = handlers::handle_get_borsh_schemas(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::ListPendingRequests => {
+            let request = msg.parse_payload::<handlers::ListPendingRequestsRequest>(request_type)?;
+            let result = handlers::handle_list_pending_requests(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::CancelRequest => {
+            let request = msg.parse_payload::<handlers::CancelRequestRequest>(request_type)?;
+            let result = handlers::handle_cancel_request(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::WipeAllState => {
+            let request = msg.parse_payload::<handlers::WipeAllStateRequest>(request_type)?;
+            let result = handlers::handle_wipe_all_state(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::ValidateEncryptedBlobs => {
+            let request = msg.parse_payload::<handlers::ValidateEncryptedBlobsRequest>(request_type)?;
+            let result = handlers::handle_validate_encrypted_blobs(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::CreateSessionKey => {
+            let request = msg.parse_payload::<handlers::CreateSessionKeyRequest>(request_type)?;
+            let result = handlers::handle_create_session_key(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::SignWithSessionKey => {
+            let request = msg.parse_payload::<handlers::SignWithSessionKeyRequest>(request_type)?;
+            let result = handlers::handle_sign_with_session_key(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::RevokeSessionKey => {
+            let request = msg.parse_payload::<handlers::RevokeSessionKeyRequest>(request_type)?;
+            let result = handlers::handle_revoke_session_key(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::ComposeMultisigRequest => {
+            let request = msg.parse_payload::<handlers::ComposeMultisigRequest>(request_type)?;
+            let result = handlers::handle_compose_multisig_request(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::GetMemoryStats => {
+            let request = msg.parse_payload::<handlers::GetMemoryStatsRequest>(request_type)?;
+            let result = handlers::handle_get_memory_stats(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::TrimCaches => {
+            let request = msg.parse_payload::<handlers::TrimCachesRequest>(request_type)?;
+            let result = handlers::handle_trim_caches(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::GetRecentReceivers => {
+            let request = msg.parse_payload::<handlers::GetRecentReceiversRequest>(request_type)?;
+            let result = handlers::handle_get_recent_receivers(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::BuildAccountDescriptor => {
+            let request = msg.parse_payload::<handlers::BuildAccountDescriptorRequest>(request_type)?;
+            let result = handlers::handle_build_account_descriptor(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::DeployLargeContract => {
+            let request = msg.parse_payload::<handlers::DeployLargeContractRequest>(request_type)?;
+            let result = handlers::handle_deploy_large_contract(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::RunSelfTest => {
+            let request = msg.parse_payload::<handlers::RunSelfTestRequest>(request_type)?;
+            let result = handlers::handle_run_self_test(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::ExportAccountBundle => {
+            let request = msg.parse_payload::<handlers::ExportAccountBundleRequest>(request_type)?;
+            let result = handlers::handle_export_account_bundle(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::ImportAccountBundle => {
+            let request = msg.parse_payload::<handlers::ImportAccountBundleRequest>(request_type)?;
+            let result = handlers::handle_import_account_bundle(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::CreateSigningIntent => {
+            let request = msg.parse_payload::<handlers::CreateSigningIntentRequest>(request_type)?;
+            let result = handlers::handle_create_signing_intent(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::ExecuteSigningIntent => {
+            let request = msg.parse_payload::<handlers::ExecuteSigningIntentRequest>(request_type)?;
+            let result = handlers::handle_execute_signing_intent(request).await?;
+            result.to_json()
+        }
+        #[cfg(feature = "relayer")]
+        WorkerRequestType::SubmitToRelayer => {
+            let request = msg.parse_payload::<handlers::SubmitToRelayerRequest>(request_type)?;
+            let result = handlers::handle_submit_to_relayer(request).await?;
+            result.to_json()
+        }
+        #[cfg(feature = "telemetry")]
+        WorkerRequestType::GetTelemetrySnapshot => {
+            let request = msg.parse_payload::<handlers::GetTelemetrySnapshotRequest>(request_type)?;
+            let result = handlers::handle_get_telemetry_snapshot(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::GetKeyUsageStats => {
+            let request = msg.parse_payload::<handlers::GetKeyUsageStatsRequest>(request_type)?;
+            let result = handlers::handle_get_key_usage_stats(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::PrepareRegistration => {
+            let request = msg.parse_payload::<handlers::PrepareRegistrationRequest>(request_type)?;
+            let result = handlers::handle_prepare_registration(request).await?;
+            result.to_json()
+        }
+        #[cfg(feature = "relayer")]
+        WorkerRequestType::CompleteRegistration => {
+            let request = msg.parse_payload::<handlers::CompleteRegistrationRequest>(request_type)?;
+            let result = handlers::handle_complete_registration(request).await?;
+            result.to_json()
+        }
+        #[cfg(feature = "device-linking")]
+        WorkerRequestType::CreateRemoteConfirmation => {
+            let request = msg.parse_payload::<handlers::CreateRemoteConfirmationRequest>(request_type)?;
+            let result = handlers::handle_create_remote_confirmation(request).await?;
+            result.to_json()
+        }
+        #[cfg(feature = "device-linking")]
+        WorkerRequestType::ApproveRemoteConfirmation => {
+            let request = msg.parse_payload::<handlers::ApproveRemoteConfirmationRequest>(request_type)?;
+            let result = handlers::handle_approve_remote_confirmation(request).await?;
+            result.to_json()
+        }
+        #[cfg(feature = "device-linking")]
+        WorkerRequestType::CompleteRemoteConfirmation => {
+            let request = msg.parse_payload::<handlers::CompleteRemoteConfirmationRequest>(request_type)?;
+            let result = handlers::handle_complete_remote_confirmation(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::GetWorkerInfo => {
+            let request = msg.parse_payload::<handlers::GetWorkerInfoRequest>(request_type)?;
+            let result = handlers::handle_get_worker_info(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::ValidateDecryptionCapability => {
+            let request = msg.parse_payload::<handlers::ValidateDecryptionCapabilityRequest>(request_type)?;
+            let result = handlers::handle_validate_decryption_capability(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::ListActiveAccounts => {
+            let request = msg.parse_payload::<handlers::ListActiveAccountsRequest>(request_type)?;
+            let result = handlers::handle_list_active_accounts(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::WipeAccountState => {
+            let request = msg.parse_payload::<handlers::WipeAccountStateRequest>(request_type)?;
+            let result = handlers::handle_wipe_account_state(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::ValidateArgsSchemas => {
+            let request = msg.parse_payload::<handlers::ValidateArgsSchemasRequest>(request_type)?;
+            let result = handlers::handle_validate_args_schemas(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::SummarizeTransactions => {
+            let request = msg.parse_payload::<handlers::SummarizeTransactionsRequest>(request_type)?;
+            let result = handlers::handle_summarize_transactions(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::MigrateEncryptedBlobs => {
+            let request = msg.parse_payload::<handlers::MigrateEncryptedBlobsRequest>(request_type)?;
+            let result = handlers::handle_migrate_encrypted_blobs(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::BuildCredentialCreationOptions => {
+            let request = msg.parse_payload::<handlers::BuildCredentialCreationOptionsRequest>(request_type)?;
+            let result = handlers::handle_build_credential_creation_options(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::BuildCredentialRequestOptions => {
+            let request = msg.parse_payload::<handlers::BuildCredentialRequestOptionsRequest>(request_type)?;
+            let result = handlers::handle_build_credential_request_options(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::RecoverPendingOperations => {
+            let request = msg.parse_payload::<handlers::RecoverPendingOperationsRequest>(request_type)?;
+            let result = handlers::handle_recover_pending_operations(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::ComposeCreateSubaccount => {
+            let request = msg.parse_payload::<handlers::ComposeCreateSubaccountRequest>(request_type)?;
+            let result = handlers::handle_compose_create_subaccount(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::StartBackgroundRefresh => {
+            let request = msg.parse_payload::<handlers::StartBackgroundRefreshRequest>(request_type)?;
+            let result = handlers::handle_start_background_refresh(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::StopBackgroundRefresh => {
+            let request = msg.parse_payload::<handlers::StopBackgroundRefreshRequest>(request_type)?;
+            let result = handlers::handle_stop_background_refresh(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::BackgroundRefreshStatus => {
+            let request = msg.parse_payload::<handlers::BackgroundRefreshStatusRequest>(request_type)?;
+            let result = handlers::handle_background_refresh_status(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::SuspendHint => {
+            let request = msg.parse_payload::<handlers::SuspendHintRequest>(request_type)?;
+            let result = handlers::handle_suspend_hint(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::ResumeHint => {
+            let request = msg.parse_payload::<handlers::ResumeHintRequest>(request_type)?;
+            let result = handlers::handle_resume_hint(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::DeriveSubKey => {
+            let request = msg.parse_payload::<handlers::DeriveSubKeyRequest>(request_type)?;
+            let result = handlers::handle_derive_sub_key(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::ViewCall => {
+            let request = msg.parse_payload::<handlers::ViewCallRequest>(request_type)?;
+            let result = handlers::handle_view_call(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::DumpDiagnostics => {
+            let request = msg.parse_payload::<handlers::DumpDiagnosticsRequest>(request_type)?;
+            let result = handlers::handle_dump_diagnostics(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::SignTransactionsWithFreshChallenge => {
+            let request = msg
+                .parse_payload::<handlers::SignTransactionsWithFreshChallengeRequest>(request_type)?;
+            let result = handlers::handle_sign_transactions_with_fresh_challenge(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::GetSessionStatus => {
+            let request = msg.parse_payload::<handlers::GetSessionStatusRequest>(request_type)?;
+            let result = handlers::handle_get_session_status(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::BuildRedirectPayload => {
+            let request = msg.parse_payload::<handlers::BuildRedirectPayloadRequest>(request_type)?;
+            let result = handlers::handle_build_redirect_payload(request).await?;
+            result.to_json()
+        }
+        WorkerRequestType::IssueElevationChallenge => {
+            let request =
+                msg.parse_payload::<handlers::IssueElevationChallengeRequest>(request_type)?;
+            let result = handlers::handle_issue_elevation_challenge(request).await?;
+            result.to_json()
+        }
+        // Refused by check_feature_compiled above; kept so the match stays exhaustive
+        #[cfg(not(all(
+            feature = "nep413",
+            feature = "device-linking",
+            feature = "relayer",
+            feature = "telemetry"
+        )))]
+        _ => Err(format!("{} is not compiled into this worker", request_type.name())),
+    }
 }
 
 // === DEBUGGING HELPERS ===
@@ -358,6 +1304,58 @@ pub fn worker_request_type_name(request_type: WorkerRequestType) -> &'static str
             "REGISTRATION_CREDENTIAL_CONFIRMATION"
         }
         WorkerRequestType::ExportNearKeypairUI => "EXPORT_NEAR_KEYPAIR_UI",
+        WorkerRequestType::GetBorshSchemas => "GET_BORSH_SCHEMAS",
+        WorkerRequestType::ListPendingRequests => "LIST_PENDING_REQUESTS",
+        WorkerRequestType::CancelRequest => "CANCEL_REQUEST",
+        WorkerRequestType::WipeAllState => "WIPE_ALL_STATE",
+        WorkerRequestType::ValidateEncryptedBlobs => "VALIDATE_ENCRYPTED_BLOBS",
+        WorkerRequestType::CreateSessionKey => "CREATE_SESSION_KEY",
+        WorkerRequestType::SignWithSessionKey => "SIGN_WITH_SESSION_KEY",
+        WorkerRequestType::RevokeSessionKey => "REVOKE_SESSION_KEY",
+        WorkerRequestType::ComposeMultisigRequest => "COMPOSE_MULTISIG_REQUEST",
+        WorkerRequestType::GetMemoryStats => "GET_MEMORY_STATS",
+        WorkerRequestType::TrimCaches => "TRIM_CACHES",
+        WorkerRequestType::GetRecentReceivers => "GET_RECENT_RECEIVERS",
+        WorkerRequestType::BuildAccountDescriptor => "BUILD_ACCOUNT_DESCRIPTOR",
+        WorkerRequestType::DeployLargeContract => "DEPLOY_LARGE_CONTRACT",
+        WorkerRequestType::RunSelfTest => "RUN_SELF_TEST",
+        WorkerRequestType::ExportAccountBundle => "EXPORT_ACCOUNT_BUNDLE",
+        WorkerRequestType::ImportAccountBundle => "IMPORT_ACCOUNT_BUNDLE",
+        WorkerRequestType::CreateSigningIntent => "CREATE_SIGNING_INTENT",
+        WorkerRequestType::ExecuteSigningIntent => "EXECUTE_SIGNING_INTENT",
+        WorkerRequestType::SubmitToRelayer => "SUBMIT_TO_RELAYER",
+        WorkerRequestType::GetTelemetrySnapshot => "GET_TELEMETRY_SNAPSHOT",
+        WorkerRequestType::GetKeyUsageStats => "GET_KEY_USAGE_STATS",
+        WorkerRequestType::PrepareRegistration => "PREPARE_REGISTRATION",
+        WorkerRequestType::CompleteRegistration => "COMPLETE_REGISTRATION",
+        WorkerRequestType::CreateRemoteConfirmation => "CREATE_REMOTE_CONFIRMATION",
+        WorkerRequestType::ApproveRemoteConfirmation => "APPROVE_REMOTE_CONFIRMATION",
+        WorkerRequestType::CompleteRemoteConfirmation => "COMPLETE_REMOTE_CONFIRMATION",
+        WorkerRequestType::GetWorkerInfo => "GET_WORKER_INFO",
+        WorkerRequestType::ValidateDecryptionCapability => "VALIDATE_DECRYPTION_CAPABILITY",
+        WorkerRequestType::ListActiveAccounts => "LIST_ACTIVE_ACCOUNTS",
+        WorkerRequestType::WipeAccountState => "WIPE_ACCOUNT_STATE",
+        WorkerRequestType::ValidateArgsSchemas => "VALIDATE_ARGS_SCHEMAS",
+        WorkerRequestType::SummarizeTransactions => "SUMMARIZE_TRANSACTIONS",
+        WorkerRequestType::MigrateEncryptedBlobs => "MIGRATE_ENCRYPTED_BLOBS",
+        WorkerRequestType::BuildCredentialCreationOptions => "BUILD_CREDENTIAL_CREATION_OPTIONS",
+        WorkerRequestType::BuildCredentialRequestOptions => "BUILD_CREDENTIAL_REQUEST_OPTIONS",
+        WorkerRequestType::RecoverPendingOperations => "RECOVER_PENDING_OPERATIONS",
+        WorkerRequestType::ComposeCreateSubaccount => "COMPOSE_CREATE_SUBACCOUNT",
+        WorkerRequestType::StartBackgroundRefresh => "START_BACKGROUND_REFRESH",
+        WorkerRequestType::StopBackgroundRefresh => "STOP_BACKGROUND_REFRESH",
+        WorkerRequestType::BackgroundRefreshStatus => "BACKGROUND_REFRESH_STATUS",
+        WorkerRequestType::SuspendHint => "SUSPEND_HINT",
+        WorkerRequestType::ResumeHint => "RESUME_HINT",
+        WorkerRequestType::DeriveSubKey => "DERIVE_SUB_KEY",
+        WorkerRequestType::ViewCall => "VIEW_CALL",
+        WorkerRequestType::DumpDiagnostics => "DUMP_DIAGNOSTICS",
+        WorkerRequestType::SignTransactionsWithFreshChallenge => {
+            "SIGN_TRANSACTIONS_WITH_FRESH_CHALLENGE"
+        }
+        WorkerRequestType::GetSessionStatus => "GET_SESSION_STATUS",
+        WorkerRequestType::BuildRedirectPayload => "BUILD_REDIRECT_PAYLOAD",
+        WorkerRequestType::IssueElevationChallenge => "ISSUE_ELEVATION_CHALLENGE",
     }
 }
 
@@ -417,5 +1415,109 @@ pub fn worker_response_type_name(response_type: WorkerResponseType) -> &'static
         WorkerResponseType::RegistrationComplete => "REGISTRATION_COMPLETE",
         WorkerResponseType::ExecuteActionsProgress => "EXECUTE_ACTIONS_PROGRESS",
         WorkerResponseType::ExecuteActionsComplete => "EXECUTE_ACTIONS_COMPLETE",
+        WorkerResponseType::GetBorshSchemasSuccess => "GET_BORSH_SCHEMAS_SUCCESS",
+        WorkerResponseType::GetBorshSchemasFailure => "GET_BORSH_SCHEMAS_FAILURE",
+        WorkerResponseType::ListPendingRequestsSuccess => "LIST_PENDING_REQUESTS_SUCCESS",
+        WorkerResponseType::ListPendingRequestsFailure => "LIST_PENDING_REQUESTS_FAILURE",
+        WorkerResponseType::CancelRequestSuccess => "CANCEL_REQUEST_SUCCESS",
+        WorkerResponseType::CancelRequestFailure => "CANCEL_REQUEST_FAILURE",
+        WorkerResponseType::WipeAllStateSuccess => "WIPE_ALL_STATE_SUCCESS",
+        WorkerResponseType::WipeAllStateFailure => "WIPE_ALL_STATE_FAILURE",
+        WorkerResponseType::ValidateEncryptedBlobsSuccess => "VALIDATE_ENCRYPTED_BLOBS_SUCCESS",
+        WorkerResponseType::ValidateEncryptedBlobsFailure => "VALIDATE_ENCRYPTED_BLOBS_FAILURE",
+        WorkerResponseType::CreateSessionKeySuccess => "CREATE_SESSION_KEY_SUCCESS",
+        WorkerResponseType::CreateSessionKeyFailure => "CREATE_SESSION_KEY_FAILURE",
+        WorkerResponseType::SignWithSessionKeySuccess => "SIGN_WITH_SESSION_KEY_SUCCESS",
+        WorkerResponseType::SignWithSessionKeyFailure => "SIGN_WITH_SESSION_KEY_FAILURE",
+        WorkerResponseType::RevokeSessionKeySuccess => "REVOKE_SESSION_KEY_SUCCESS",
+        WorkerResponseType::RevokeSessionKeyFailure => "REVOKE_SESSION_KEY_FAILURE",
+        WorkerResponseType::ComposeMultisigRequestSuccess => "COMPOSE_MULTISIG_REQUEST_SUCCESS",
+        WorkerResponseType::ComposeMultisigRequestFailure => "COMPOSE_MULTISIG_REQUEST_FAILURE",
+        WorkerResponseType::GetMemoryStatsSuccess => "GET_MEMORY_STATS_SUCCESS",
+        WorkerResponseType::GetMemoryStatsFailure => "GET_MEMORY_STATS_FAILURE",
+        WorkerResponseType::TrimCachesSuccess => "TRIM_CACHES_SUCCESS",
+        WorkerResponseType::TrimCachesFailure => "TRIM_CACHES_FAILURE",
+        WorkerResponseType::GetRecentReceiversSuccess => "GET_RECENT_RECEIVERS_SUCCESS",
+        WorkerResponseType::GetRecentReceiversFailure => "GET_RECENT_RECEIVERS_FAILURE",
+        WorkerResponseType::BuildAccountDescriptorSuccess => "BUILD_ACCOUNT_DESCRIPTOR_SUCCESS",
+        WorkerResponseType::BuildAccountDescriptorFailure => "BUILD_ACCOUNT_DESCRIPTOR_FAILURE",
+        WorkerResponseType::DeployLargeContractSuccess => "DEPLOY_LARGE_CONTRACT_SUCCESS",
+        WorkerResponseType::DeployLargeContractFailure => "DEPLOY_LARGE_CONTRACT_FAILURE",
+        WorkerResponseType::RunSelfTestSuccess => "RUN_SELF_TEST_SUCCESS",
+        WorkerResponseType::RunSelfTestFailure => "RUN_SELF_TEST_FAILURE",
+        WorkerResponseType::ExportAccountBundleSuccess => "EXPORT_ACCOUNT_BUNDLE_SUCCESS",
+        WorkerResponseType::ExportAccountBundleFailure => "EXPORT_ACCOUNT_BUNDLE_FAILURE",
+        WorkerResponseType::ImportAccountBundleSuccess => "IMPORT_ACCOUNT_BUNDLE_SUCCESS",
+        WorkerResponseType::ImportAccountBundleFailure => "IMPORT_ACCOUNT_BUNDLE_FAILURE",
+        WorkerResponseType::CreateSigningIntentSuccess => "CREATE_SIGNING_INTENT_SUCCESS",
+        WorkerResponseType::CreateSigningIntentFailure => "CREATE_SIGNING_INTENT_FAILURE",
+        WorkerResponseType::ExecuteSigningIntentSuccess => "EXECUTE_SIGNING_INTENT_SUCCESS",
+        WorkerResponseType::ExecuteSigningIntentFailure => "EXECUTE_SIGNING_INTENT_FAILURE",
+        WorkerResponseType::SubmitToRelayerSuccess => "SUBMIT_TO_RELAYER_SUCCESS",
+        WorkerResponseType::SubmitToRelayerFailure => "SUBMIT_TO_RELAYER_FAILURE",
+        WorkerResponseType::GetTelemetrySnapshotSuccess => "GET_TELEMETRY_SNAPSHOT_SUCCESS",
+        WorkerResponseType::GetTelemetrySnapshotFailure => "GET_TELEMETRY_SNAPSHOT_FAILURE",
+        WorkerResponseType::GetKeyUsageStatsSuccess => "GET_KEY_USAGE_STATS_SUCCESS",
+        WorkerResponseType::GetKeyUsageStatsFailure => "GET_KEY_USAGE_STATS_FAILURE",
+        WorkerResponseType::PrepareRegistrationSuccess => "PREPARE_REGISTRATION_SUCCESS",
+        WorkerResponseType::PrepareRegistrationFailure => "PREPARE_REGISTRATION_FAILURE",
+        WorkerResponseType::CompleteRegistrationSuccess => "COMPLETE_REGISTRATION_SUCCESS",
+        WorkerResponseType::CompleteRegistrationFailure => "COMPLETE_REGISTRATION_FAILURE",
+        WorkerResponseType::CreateRemoteConfirmationSuccess => "CREATE_REMOTE_CONFIRMATION_SUCCESS",
+        WorkerResponseType::CreateRemoteConfirmationFailure => "CREATE_REMOTE_CONFIRMATION_FAILURE",
+        WorkerResponseType::ApproveRemoteConfirmationSuccess => "APPROVE_REMOTE_CONFIRMATION_SUCCESS",
+        WorkerResponseType::ApproveRemoteConfirmationFailure => "APPROVE_REMOTE_CONFIRMATION_FAILURE",
+        WorkerResponseType::CompleteRemoteConfirmationSuccess => "COMPLETE_REMOTE_CONFIRMATION_SUCCESS",
+        WorkerResponseType::CompleteRemoteConfirmationFailure => "COMPLETE_REMOTE_CONFIRMATION_FAILURE",
+        WorkerResponseType::GetWorkerInfoSuccess => "GET_WORKER_INFO_SUCCESS",
+        WorkerResponseType::GetWorkerInfoFailure => "GET_WORKER_INFO_FAILURE",
+        WorkerResponseType::ValidateDecryptionCapabilitySuccess => "VALIDATE_DECRYPTION_CAPABILITY_SUCCESS",
+        WorkerResponseType::ValidateDecryptionCapabilityFailure => "VALIDATE_DECRYPTION_CAPABILITY_FAILURE",
+        WorkerResponseType::ListActiveAccountsSuccess => "LIST_ACTIVE_ACCOUNTS_SUCCESS",
+        WorkerResponseType::ListActiveAccountsFailure => "LIST_ACTIVE_ACCOUNTS_FAILURE",
+        WorkerResponseType::WipeAccountStateSuccess => "WIPE_ACCOUNT_STATE_SUCCESS",
+        WorkerResponseType::WipeAccountStateFailure => "WIPE_ACCOUNT_STATE_FAILURE",
+        WorkerResponseType::ValidateArgsSchemasSuccess => "VALIDATE_ARGS_SCHEMAS_SUCCESS",
+        WorkerResponseType::ValidateArgsSchemasFailure => "VALIDATE_ARGS_SCHEMAS_FAILURE",
+        WorkerResponseType::SummarizeTransactionsSuccess => "SUMMARIZE_TRANSACTIONS_SUCCESS",
+        WorkerResponseType::SummarizeTransactionsFailure => "SUMMARIZE_TRANSACTIONS_FAILURE",
+        WorkerResponseType::MigrateEncryptedBlobsSuccess => "MIGRATE_ENCRYPTED_BLOBS_SUCCESS",
+        WorkerResponseType::MigrateEncryptedBlobsFailure => "MIGRATE_ENCRYPTED_BLOBS_FAILURE",
+        WorkerResponseType::BuildCredentialCreationOptionsSuccess => "BUILD_CREDENTIAL_CREATION_OPTIONS_SUCCESS",
+        WorkerResponseType::BuildCredentialCreationOptionsFailure => "BUILD_CREDENTIAL_CREATION_OPTIONS_FAILURE",
+        WorkerResponseType::BuildCredentialRequestOptionsSuccess => "BUILD_CREDENTIAL_REQUEST_OPTIONS_SUCCESS",
+        WorkerResponseType::BuildCredentialRequestOptionsFailure => "BUILD_CREDENTIAL_REQUEST_OPTIONS_FAILURE",
+        WorkerResponseType::RecoverPendingOperationsSuccess => "RECOVER_PENDING_OPERATIONS_SUCCESS",
+        WorkerResponseType::RecoverPendingOperationsFailure => "RECOVER_PENDING_OPERATIONS_FAILURE",
+        WorkerResponseType::ComposeCreateSubaccountSuccess => "COMPOSE_CREATE_SUBACCOUNT_SUCCESS",
+        WorkerResponseType::ComposeCreateSubaccountFailure => "COMPOSE_CREATE_SUBACCOUNT_FAILURE",
+        WorkerResponseType::StartBackgroundRefreshSuccess => "START_BACKGROUND_REFRESH_SUCCESS",
+        WorkerResponseType::StartBackgroundRefreshFailure => "START_BACKGROUND_REFRESH_FAILURE",
+        WorkerResponseType::StopBackgroundRefreshSuccess => "STOP_BACKGROUND_REFRESH_SUCCESS",
+        WorkerResponseType::StopBackgroundRefreshFailure => "STOP_BACKGROUND_REFRESH_FAILURE",
+        WorkerResponseType::BackgroundRefreshStatusSuccess => "BACKGROUND_REFRESH_STATUS_SUCCESS",
+        WorkerResponseType::BackgroundRefreshStatusFailure => "BACKGROUND_REFRESH_STATUS_FAILURE",
+        WorkerResponseType::SuspendHintSuccess => "SUSPEND_HINT_SUCCESS",
+        WorkerResponseType::SuspendHintFailure => "SUSPEND_HINT_FAILURE",
+        WorkerResponseType::ResumeHintSuccess => "RESUME_HINT_SUCCESS",
+        WorkerResponseType::ResumeHintFailure => "RESUME_HINT_FAILURE",
+        WorkerResponseType::DeriveSubKeySuccess => "DERIVE_SUB_KEY_SUCCESS",
+        WorkerResponseType::DeriveSubKeyFailure => "DERIVE_SUB_KEY_FAILURE",
+        WorkerResponseType::ViewCallSuccess => "VIEW_CALL_SUCCESS",
+        WorkerResponseType::ViewCallFailure => "VIEW_CALL_FAILURE",
+        WorkerResponseType::DumpDiagnosticsSuccess => "DUMP_DIAGNOSTICS_SUCCESS",
+        WorkerResponseType::DumpDiagnosticsFailure => "DUMP_DIAGNOSTICS_FAILURE",
+        WorkerResponseType::SignTransactionsWithFreshChallengeSuccess => {
+            "SIGN_TRANSACTIONS_WITH_FRESH_CHALLENGE_SUCCESS"
+        }
+        WorkerResponseType::SignTransactionsWithFreshChallengeFailure => {
+            "SIGN_TRANSACTIONS_WITH_FRESH_CHALLENGE_FAILURE"
+        }
+        WorkerResponseType::GetSessionStatusSuccess => "GET_SESSION_STATUS_SUCCESS",
+        WorkerResponseType::GetSessionStatusFailure => "GET_SESSION_STATUS_FAILURE",
+        WorkerResponseType::BuildRedirectPayloadSuccess => "BUILD_REDIRECT_PAYLOAD_SUCCESS",
+        WorkerResponseType::BuildRedirectPayloadFailure => "BUILD_REDIRECT_PAYLOAD_FAILURE",
+        WorkerResponseType::IssueElevationChallengeSuccess => "ISSUE_ELEVATION_CHALLENGE_SUCCESS",
+        WorkerResponseType::IssueElevationChallengeFailure => "ISSUE_ELEVATION_CHALLENGE_FAILURE",
     }
 }
@@ -0,0 +1,140 @@
+// === WORKER MEMORY ACCOUNTING ===
+// Heap tracking for GetMemoryStats and an arena for large transient buffers.
+// WASM linear memory never shrinks once grown, so the goal is to keep the peak from
+// ratcheting upward across repeated big operations (e.g. several 3MB DeployContract
+// signatures): transient buffers are reused within a request and released when its
+// handler returns, instead of each operation allocating afresh on top of retained ones.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::{Cell, RefCell};
+
+use crate::config::WASM_PAGE_SIZE_BYTES;
+
+/// System allocator wrapper counting live and peak heap bytes.
+/// Counters are per thread: the worker is single-threaded, and native tests each get
+/// their own view.
+pub struct TrackingAllocator;
+
+thread_local! {
+    static LIVE_HEAP_BYTES: Cell<usize> = const { Cell::new(0) };
+    static PEAK_HEAP_BYTES: Cell<usize> = const { Cell::new(0) };
+}
+
+fn record_alloc(size: usize) {
+    let _ = LIVE_HEAP_BYTES.try_with(|live| {
+        let now = live.get().saturating_add(size);
+        live.set(now);
+        let _ = PEAK_HEAP_BYTES.try_with(|peak| peak.set(peak.get().max(now)));
+    });
+}
+
+fn record_dealloc(size: usize) {
+    // Saturating: a block freed on a different thread than it was allocated on
+    // (native only) must not wrap the counter
+    let _ = LIVE_HEAP_BYTES.try_with(|live| live.set(live.get().saturating_sub(size)));
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record_dealloc(layout.size());
+            record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+pub fn live_heap_bytes() -> usize {
+    LIVE_HEAP_BYTES.with(|live| live.get())
+}
+
+pub fn peak_heap_bytes() -> usize {
+    PEAK_HEAP_BYTES.with(|peak| peak.get())
+}
+
+/// Restarts peak tracking from the current live size
+pub fn reset_peak_heap_bytes() {
+    let live = live_heap_bytes();
+    PEAK_HEAP_BYTES.with(|peak| peak.set(live));
+}
+
+/// Current size of WASM linear memory in pages (None outside WASM)
+#[cfg(target_arch = "wasm32")]
+pub fn memory_pages() -> Option<usize> {
+    Some(core::arch::wasm32::memory_size::<0>())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn memory_pages() -> Option<usize> {
+    None
+}
+
+pub fn memory_bytes() -> Option<usize> {
+    memory_pages().map(|pages| pages * WASM_PAGE_SIZE_BYTES)
+}
+
+// === TRANSIENT BUFFER ARENA ===
+
+thread_local! {
+    static TRANSIENT_BUFFERS: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runs `f` with an empty scratch buffer from the arena, returning the buffer afterwards.
+/// Buffers stay in the arena until `release_transient_buffers` runs at the end of the request.
+pub fn with_transient_buffer<R>(f: impl FnOnce(&mut Vec<u8>) -> R) -> R {
+    let mut buffer = TRANSIENT_BUFFERS
+        .with(|buffers| buffers.borrow_mut().pop())
+        .unwrap_or_default();
+    buffer.clear();
+    let result = f(&mut buffer);
+    TRANSIENT_BUFFERS.with(|buffers| buffers.borrow_mut().push(buffer));
+    result
+}
+
+/// Frees arena buffers with more than `retain_max_bytes` of capacity (all of them for 0).
+/// Buffers in use by a running request are not in the arena and are unaffected.
+/// Returns the number of bytes freed.
+pub fn release_transient_buffers(retain_max_bytes: usize) -> usize {
+    TRANSIENT_BUFFERS.with(|buffers| {
+        let mut buffers = buffers.borrow_mut();
+        let mut freed = 0;
+        buffers.retain(|buffer| {
+            let keep = retain_max_bytes > 0 && buffer.capacity() <= retain_max_bytes;
+            if !keep {
+                freed += buffer.capacity();
+            }
+            keep
+        });
+        if buffers.is_empty() {
+            buffers.shrink_to_fit();
+        }
+        freed
+    })
+}
+
+/// Capacity currently held by idle arena buffers
+pub fn transient_buffer_bytes() -> usize {
+    TRANSIENT_BUFFERS.with(|buffers| buffers.borrow().iter().map(|b| b.capacity()).sum())
+}
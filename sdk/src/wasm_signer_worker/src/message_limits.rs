@@ -0,0 +1,229 @@
+// === MESSAGE SIZE LIMITS ===
+// A page can post the worker a frame of any size, and parsing a 200 MB frame into a
+// SignerWorkerMessage stalls the worker or runs it out of memory. Frames are therefore measured
+// before they are parsed: a frame above the largest limit of any request type is refused
+// without reading it at all, and otherwise only its `type` is read (serde skips the payload
+// without building it) to pick the limit of its request type. CBOR frames are also walked
+// header by header first, so a byte string or array header declaring more than the frame holds
+// is refused before the decoder reserves memory for it.
+//
+// Limits come from WorkerPolicy.messageSizeLimits: the TS layer sets them with
+// `configure_message_size_limits` before the first frame, and a request whose policy carries
+// them replaces them for the frames after it.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::config::{
+    DEFAULT_MAX_DEPLOY_MESSAGE_BYTES, DEFAULT_MAX_MESSAGE_BYTES, MIN_MESSAGE_SIZE_LIMIT_BYTES,
+};
+use crate::error::{ConfigError, MessageSizeError};
+use crate::state;
+use crate::types::handlers::MessageSizeLimits;
+use crate::types::worker_messages::WorkerRequestType;
+
+/// The fields of a frame read before its size is checked; everything else is skipped
+#[derive(Deserialize)]
+struct FrameHeader {
+    #[serde(rename = "type")]
+    msg_type: u32,
+}
+
+impl MessageSizeLimits {
+    /// Largest frame accepted for `request_type`
+    pub fn limit_for(&self, request_type: WorkerRequestType) -> u32 {
+        if let Some(limit) = self.by_type.get(request_type.name()) {
+            return *limit;
+        }
+        match request_type {
+            WorkerRequestType::DeployLargeContract => self
+                .deploy_bytes
+                .unwrap_or(DEFAULT_MAX_DEPLOY_MESSAGE_BYTES),
+            _ => self.default_bytes.unwrap_or(DEFAULT_MAX_MESSAGE_BYTES),
+        }
+    }
+
+    /// Largest frame any request type accepts; frames above it are refused unread
+    pub fn ceiling(&self) -> u32 {
+        let default_bytes = self.default_bytes.unwrap_or(DEFAULT_MAX_MESSAGE_BYTES);
+        let deploy_bytes = self
+            .deploy_bytes
+            .unwrap_or(DEFAULT_MAX_DEPLOY_MESSAGE_BYTES);
+        self.by_type
+            .values()
+            .copied()
+            .chain([default_bytes, deploy_bytes])
+            .max()
+            .unwrap_or(default_bytes)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let too_small = |field: &'static str, limit: u32| ConfigError::InvalidValue {
+            field,
+            reason: format!(
+                "{} bytes is below the minimum of {} bytes",
+                limit, MIN_MESSAGE_SIZE_LIMIT_BYTES
+            ),
+        };
+        for (field, limit) in [
+            ("messageSizeLimits.defaultBytes", self.default_bytes),
+            ("messageSizeLimits.deployBytes", self.deploy_bytes),
+        ] {
+            if let Some(limit) = limit.filter(|limit| *limit < MIN_MESSAGE_SIZE_LIMIT_BYTES) {
+                return Err(too_small(field, limit));
+            }
+        }
+        for (name, limit) in &self.by_type {
+            if (0..)
+                .map_while(WorkerRequestType::from_u32)
+                .all(|request_type| request_type.name() != name)
+            {
+                return Err(ConfigError::InvalidValue {
+                    field: "messageSizeLimits.byType",
+                    reason: format!("unknown request type {}", name),
+                });
+            }
+            if *limit < MIN_MESSAGE_SIZE_LIMIT_BYTES {
+                return Err(too_small("messageSizeLimits.byType", *limit));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Limit for a frame whose header names `msg_type`; unknown types get the default limit and
+/// are refused by the full parse
+fn limit_for_type(limits: &MessageSizeLimits, msg_type: u32) -> u32 {
+    match WorkerRequestType::from_u32(msg_type) {
+        Some(request_type) => limits.limit_for(request_type),
+        None => limits.default_bytes.unwrap_or(DEFAULT_MAX_MESSAGE_BYTES),
+    }
+}
+
+fn check_len(limit: u32, received: usize) -> Result<(), MessageSizeError> {
+    if received > limit as usize {
+        return Err(MessageSizeError::MessageTooLarge { limit, received });
+    }
+    Ok(())
+}
+
+/// Refuses a JSON frame above the limit of its request type. Frames whose header does not
+/// parse are left to the full parse to report.
+pub fn check_json_frame(message_json: &str) -> Result<(), MessageSizeError> {
+    let limits = state::message_size_limits();
+    check_len(limits.ceiling(), message_json.len())?;
+    match serde_json::from_str::<FrameHeader>(message_json) {
+        Ok(header) => check_len(limit_for_type(&limits, header.msg_type), message_json.len()),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Refuses a CBOR frame above the limit of its request type, or with a header declaring more
+/// than the frame holds
+pub fn check_cbor_frame(frame: &[u8]) -> Result<(), MessageSizeError> {
+    let limits = state::message_size_limits();
+    check_len(limits.ceiling(), frame.len())?;
+    check_cbor_declared_lengths(frame)?;
+    match ciborium::from_reader::<FrameHeader, _>(frame) {
+        Ok(header) => check_len(limit_for_type(&limits, header.msg_type), frame.len()),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Walks the CBOR item at the start of `frame`, checking that every byte and text string
+/// length fits in the bytes after its header, and that every array and map declares no more
+/// items than there are bytes left (each item takes at least one). Malformed or truncated
+/// headers end the walk: the decoder reports them.
+fn check_cbor_declared_lengths(frame: &[u8]) -> Result<(), MessageSizeError> {
+    // Items left in each open container; None for indefinite-length ones, closed by a break
+    let mut open: Vec<Option<u64>> = vec![Some(1)];
+    let mut offset = 0usize;
+    while let Some(top) = open.last_mut() {
+        if *top == Some(0) {
+            open.pop();
+            continue;
+        }
+        let header_offset = offset;
+        let Some(&initial) = frame.get(offset) else {
+            return Ok(());
+        };
+        offset += 1;
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        if initial == 0xff {
+            if top.is_none() {
+                open.pop();
+                continue;
+            }
+            return Ok(());
+        }
+        if let Some(items) = top {
+            *items -= 1;
+        }
+        let argument = match info {
+            0..=23 => Some(u64::from(info)),
+            24..=27 => {
+                let size = 1usize << (info - 24);
+                let Some(bytes) = frame.get(offset..offset + size) else {
+                    return Ok(());
+                };
+                offset += size;
+                Some(bytes.iter().fold(0u64, |n, b| (n << 8) | u64::from(*b)))
+            }
+            31 => None,
+            _ => return Ok(()),
+        };
+        let available = frame.len() - offset;
+        let exceeds = |declared: u64| MessageSizeError::DeclaredLengthExceedsFrame {
+            offset: header_offset,
+            declared,
+            available,
+        };
+        match (major, argument) {
+            // Byte and text strings: definite ones are skipped, indefinite ones hold chunks
+            (2 | 3, Some(len)) => {
+                if len > available as u64 {
+                    return Err(exceeds(len));
+                }
+                offset += len as usize;
+            }
+            (4, Some(count)) => {
+                if count > available as u64 {
+                    return Err(exceeds(count));
+                }
+                open.push(Some(count));
+            }
+            (5, Some(pairs)) => {
+                let items = pairs.saturating_mul(2);
+                if items > available as u64 {
+                    return Err(exceeds(items));
+                }
+                open.push(Some(items));
+            }
+            (2..=5, None) => open.push(None),
+            // A tag is followed by the item it tags
+            (6, _) => open.push(Some(1)),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Dispatch step: a request whose policy sets messageSizeLimits applies them to the frames
+/// after it
+pub fn apply_policy_limits(payload: &Value) -> Result<(), ConfigError> {
+    let Some(limits) = payload.pointer("/workerPolicy/messageSizeLimits") else {
+        return Ok(());
+    };
+    let limits: MessageSizeLimits =
+        serde_json::from_value(limits.clone()).map_err(|e| ConfigError::InvalidValue {
+            field: "messageSizeLimits",
+            reason: e.to_string(),
+        })?;
+    configure(limits)
+}
+
+pub fn configure(limits: MessageSizeLimits) -> Result<(), ConfigError> {
+    limits.validate()?;
+    state::set_message_size_limits(limits);
+    Ok(())
+}
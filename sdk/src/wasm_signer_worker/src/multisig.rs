@@ -0,0 +1,198 @@
+// === MULTISIG REQUEST COMPOSITION ===
+// Wraps intended actions into calls on the standard NEAR multisig contract
+// (near/core-contracts `multisig`): `add_request_and_confirm`, `confirm` and `delete_request`.
+// The contract takes actions in its own JSON schema, not the NEAR action encoding.
+
+use serde::{Deserialize, Serialize};
+
+use crate::actions::{get_action_handler, ActionParams};
+use crate::encoders::base64_standard_encode;
+
+pub const MULTISIG_ADD_REQUEST_AND_CONFIRM_METHOD: &str = "add_request_and_confirm";
+pub const MULTISIG_CONFIRM_METHOD: &str = "confirm";
+pub const MULTISIG_DELETE_REQUEST_METHOD: &str = "delete_request";
+
+/// `FunctionCallPermission` as accepted by the multisig contract's AddKey action
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MultisigFunctionCallPermission {
+    /// U128 as a decimal string
+    pub allowance: Option<String>,
+    pub receiver_id: String,
+    pub method_names: Vec<String>,
+}
+
+/// `MultiSigRequestAction` of the multisig contract.
+/// Stake and DeleteAccount have no counterpart and are rejected.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum MultisigRequestAction {
+    Transfer {
+        amount: String,
+    },
+    CreateAccount,
+    DeployContract {
+        /// Base64VecU8 (standard base64)
+        code: String,
+    },
+    AddKey {
+        public_key: String,
+        /// None: full access key
+        #[serde(skip_serializing_if = "Option::is_none")]
+        permission: Option<MultisigFunctionCallPermission>,
+    },
+    DeleteKey {
+        public_key: String,
+    },
+    FunctionCall {
+        method_name: String,
+        /// Base64VecU8 (standard base64) of the JSON args
+        args: String,
+        deposit: String,
+        gas: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MultisigRequest {
+    pub receiver_id: String,
+    pub actions: Vec<MultisigRequestAction>,
+}
+
+/// Maps an action to the multisig contract's action schema, validating it first
+pub fn to_multisig_action(params: &ActionParams) -> Result<MultisigRequestAction, String> {
+    let action = match params {
+        ActionParams::CreateAccount => MultisigRequestAction::CreateAccount,
+        ActionParams::DeployContract { code } => MultisigRequestAction::DeployContract {
+            code: base64_standard_encode(code),
+        },
+        ActionParams::FunctionCall {
+            method_name,
+            args,
+            gas,
+            deposit,
+        } => MultisigRequestAction::FunctionCall {
+            method_name: method_name.clone(),
+            args: base64_standard_encode(args.as_bytes()),
+            deposit: deposit.clone(),
+            gas: gas.clone(),
+        },
+        ActionParams::Transfer { deposit } => MultisigRequestAction::Transfer {
+            amount: deposit.clone(),
+        },
+        ActionParams::AddKey {
+            public_key,
+            access_key,
+        } => {
+            let access_key: serde_json::Value = serde_json::from_str(access_key)
+                .map_err(|e| format!("Failed to parse access key JSON: {}", e))?;
+            let permission = &access_key["permission"];
+            let permission = if permission["FullAccess"].is_object() || permission == "FullAccess" {
+                None
+            } else if let Some(function_call) = permission["FunctionCall"].as_object() {
+                Some(MultisigFunctionCallPermission {
+                    allowance: function_call
+                        .get("allowance")
+                        .and_then(|a| a.as_str())
+                        .map(|a| a.to_string()),
+                    receiver_id: function_call
+                        .get("receiver_id")
+                        .and_then(|r| r.as_str())
+                        .ok_or("Missing receiver_id in FunctionCall permission")?
+                        .to_string(),
+                    method_names: function_call
+                        .get("method_names")
+                        .and_then(|m| m.as_array())
+                        .map(|arr| {
+                            arr.iter()
+                                .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                })
+            } else {
+                return Err("Invalid access key permission format".to_string());
+            };
+            MultisigRequestAction::AddKey {
+                public_key: public_key.clone(),
+                permission,
+            }
+        }
+        ActionParams::DeleteKey { public_key } => MultisigRequestAction::DeleteKey {
+            public_key: public_key.clone(),
+        },
+        ActionParams::Stake { .. } => {
+            return Err("Stake actions are not supported by the multisig contract".to_string())
+        }
+        ActionParams::DeleteAccount { .. } => {
+            return Err(
+                "DeleteAccount actions are not supported by the multisig contract".to_string(),
+            )
+        }
+    };
+
+    get_action_handler(params)?.validate_params(params)?;
+    Ok(action)
+}
+
+/// Builds the multisig request for `receiver_id`; fails on the first unsupported action
+pub fn build_multisig_request(
+    receiver_id: &str,
+    actions: &[ActionParams],
+) -> Result<MultisigRequest, String> {
+    if receiver_id.is_empty() {
+        return Err("Multisig request receiverId cannot be empty".to_string());
+    }
+    if actions.is_empty() {
+        return Err("Multisig request must contain at least one action".to_string());
+    }
+    let actions = actions
+        .iter()
+        .enumerate()
+        .map(|(i, action)| to_multisig_action(action).map_err(|e| format!("Action {}: {}", i, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(MultisigRequest {
+        receiver_id: receiver_id.to_string(),
+        actions,
+    })
+}
+
+fn multisig_function_call(method_name: &str, args: serde_json::Value, gas: &str) -> ActionParams {
+    ActionParams::FunctionCall {
+        method_name: method_name.to_string(),
+        args: args.to_string(),
+        gas: gas.to_string(),
+        deposit: "0".to_string(),
+    }
+}
+
+/// `add_request_and_confirm({ request })` call on the multisig contract
+pub fn compose_add_request_and_confirm(
+    receiver_id: &str,
+    actions: &[ActionParams],
+    gas: &str,
+) -> Result<ActionParams, String> {
+    let request = build_multisig_request(receiver_id, actions)?;
+    Ok(multisig_function_call(
+        MULTISIG_ADD_REQUEST_AND_CONFIRM_METHOD,
+        serde_json::json!({ "request": request }),
+        gas,
+    ))
+}
+
+/// `confirm({ request_id })` call on the multisig contract
+pub fn compose_confirm(request_id: u32, gas: &str) -> ActionParams {
+    multisig_function_call(
+        MULTISIG_CONFIRM_METHOD,
+        serde_json::json!({ "request_id": request_id }),
+        gas,
+    )
+}
+
+/// `delete_request({ request_id })` call on the multisig contract
+pub fn compose_delete_request(request_id: u32, gas: &str) -> ActionParams {
+    multisig_function_call(
+        MULTISIG_DELETE_REQUEST_METHOD,
+        serde_json::json!({ "request_id": request_id }),
+        gas,
+    )
+}
@@ -0,0 +1,232 @@
+// === OPERATION JOURNAL ===
+// A worker killed between a broadcast and its result leaves the TS layer unable to tell
+// whether the transaction went out. Right before each irreversible step the worker emits a
+// JournalEntry through the recordJournalEntry bridge; the TS layer persists it until the
+// request succeeds. After a restart, RecoverPendingOperations takes the persisted
+// entries and reconciles each one:
+//   - broadcast: the status of `txHash` sent by `accountId` (the `tx` RPC method)
+//   - relayerSubmission: whether `accountId`, the account the relayer creates, exists (the
+//     relayer's transaction hash is only known once it answers)
+// Entries hold identifiers only (request ID, transaction hash, account ID, idempotency key),
+// never key material, PRF outputs or signed transaction bytes. Recovery makes view calls
+// only, so it is safe to repeat until every entry is resolved.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+use crate::error::RpcErrorKind;
+use crate::rpc_calls::view_account_exists_rpc_call;
+use crate::rpc_client::RpcClient;
+use crate::state;
+
+// Bridge implemented in web3authn-signer.worker.ts (posted to the main thread as
+// OPERATION_JOURNAL_ENTRY)
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = recordJournalEntry)]
+    fn record_journal_entry(entry_json: JsValue);
+}
+
+/// The irreversible step an entry was written before
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JournalStep {
+    Broadcast,
+    RelayerSubmission,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+    pub request_id: String,
+    pub step: JournalStep,
+    /// Broadcasts only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<String>,
+    /// Signer of a broadcast, or the account a relayer submission creates
+    pub account_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+    pub created_at_ms: f64,
+}
+
+impl JournalEntry {
+    pub fn broadcast(
+        request_id: &str,
+        tx_hash: &str,
+        account_id: &str,
+        idempotency_key: Option<&str>,
+    ) -> Self {
+        JournalEntry {
+            request_id: request_id.to_string(),
+            step: JournalStep::Broadcast,
+            tx_hash: Some(tx_hash.to_string()),
+            account_id: account_id.to_string(),
+            idempotency_key: idempotency_key.map(str::to_string),
+            created_at_ms: state::now_ms(),
+        }
+    }
+
+    pub fn relayer_submission(request_id: &str, account_id: &str) -> Self {
+        JournalEntry {
+            request_id: request_id.to_string(),
+            step: JournalStep::RelayerSubmission,
+            tx_hash: None,
+            account_id: account_id.to_string(),
+            idempotency_key: None,
+            created_at_ms: state::now_ms(),
+        }
+    }
+}
+
+/// Hands `entry` to the TS layer before the step runs (fire-and-forget)
+#[cfg(target_arch = "wasm32")]
+pub fn emit_journal_entry(entry: &JournalEntry) {
+    if let Ok(json) = serde_json::to_string(entry) {
+        record_journal_entry(JsValue::from_str(&json));
+    }
+}
+
+#[cfg(all(not(target_arch = "wasm32"), test))]
+thread_local! {
+    static EMITTED: std::cell::RefCell<Vec<JournalEntry>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// Native tests: entries are kept for `take_emitted_entries`
+#[cfg(not(target_arch = "wasm32"))]
+pub fn emit_journal_entry(_entry: &JournalEntry) {
+    #[cfg(test)]
+    EMITTED.with(|emitted| emitted.borrow_mut().push(_entry.clone()));
+}
+
+/// Entries emitted on this thread since the last call
+#[cfg(all(not(target_arch = "wasm32"), test))]
+pub fn take_emitted_entries() -> Vec<JournalEntry> {
+    EMITTED.with(|emitted| std::mem::take(&mut *emitted.borrow_mut()))
+}
+
+// === RECOVERY ===
+
+/// What became of a journaled step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RecoveryStatus {
+    /// The transaction executed, or the relayer's account exists
+    Confirmed,
+    /// The transaction is on chain but its outcome is not final yet
+    Pending,
+    /// The node does not know the transaction, or the account does not exist: the step
+    /// never took effect (a relayer may still be working on it)
+    NotFound,
+    /// The RPC could not answer; recover again later
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveredOperation {
+    pub entry: JournalEntry,
+    pub status: RecoveryStatus,
+    /// Confirmed broadcasts: whether the transaction succeeded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub succeeded: Option<bool>,
+    /// Why the status is Unknown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl RecoveredOperation {
+    fn new(entry: JournalEntry, status: RecoveryStatus) -> Self {
+        RecoveredOperation {
+            entry,
+            status,
+            succeeded: None,
+            detail: None,
+        }
+    }
+
+    fn unknown(entry: JournalEntry, detail: String) -> Self {
+        RecoveredOperation {
+            detail: Some(detail),
+            ..RecoveredOperation::new(entry, RecoveryStatus::Unknown)
+        }
+    }
+}
+
+/// `tx` params: the transaction's current status, without waiting
+pub fn tx_status_params(tx_hash: &str, sender_account_id: &str) -> Value {
+    json!({
+        "tx_hash": tx_hash,
+        "sender_account_id": sender_account_id,
+        "wait_until": "NONE"
+    })
+}
+
+/// Status and success of a `tx` result. Executed transactions are confirmed; included or
+/// not-yet-executed ones are pending.
+pub fn parse_tx_status(result: &Value) -> (RecoveryStatus, Option<bool>) {
+    let outcome = result.get("status");
+    let succeeded = match outcome {
+        Some(status) if status.get("SuccessValue").is_some() => Some(true),
+        Some(status) if status.get("Failure").is_some() => Some(false),
+        _ => None,
+    };
+    let executed = match result.get("final_execution_status").and_then(Value::as_str) {
+        Some(status) => matches!(status, "EXECUTED_OPTIMISTIC" | "EXECUTED" | "FINAL"),
+        // Nodes predating final_execution_status only answer once the outcome is known
+        None => succeeded.is_some(),
+    };
+    if executed {
+        (RecoveryStatus::Confirmed, succeeded)
+    } else {
+        (RecoveryStatus::Pending, None)
+    }
+}
+
+async fn recover_operation<R: RpcClient>(rpc: &R, entry: JournalEntry) -> RecoveredOperation {
+    match entry.step {
+        JournalStep::Broadcast => {
+            let Some(tx_hash) = entry.tx_hash.clone() else {
+                return RecoveredOperation::unknown(entry, "Missing txHash".to_string());
+            };
+            match rpc
+                .call("tx", tx_status_params(&tx_hash, &entry.account_id))
+                .await
+            {
+                Ok(result) => {
+                    let (status, succeeded) = parse_tx_status(&result);
+                    RecoveredOperation {
+                        succeeded,
+                        ..RecoveredOperation::new(entry, status)
+                    }
+                }
+                Err(RpcErrorKind::Rpc { name, .. }) if name == "UNKNOWN_TRANSACTION" => {
+                    RecoveredOperation::new(entry, RecoveryStatus::NotFound)
+                }
+                Err(e) => RecoveredOperation::unknown(entry, e.to_string()),
+            }
+        }
+        JournalStep::RelayerSubmission => {
+            match view_account_exists_rpc_call(rpc, &entry.account_id).await {
+                Ok(true) => RecoveredOperation::new(entry, RecoveryStatus::Confirmed),
+                Ok(false) => RecoveredOperation::new(entry, RecoveryStatus::NotFound),
+                Err(e) => RecoveredOperation::unknown(entry, e),
+            }
+        }
+    }
+}
+
+/// Reconciles `entries` against the chain, in order
+pub async fn recover_pending_operations<R: RpcClient>(
+    rpc: &R,
+    entries: Vec<JournalEntry>,
+) -> Vec<RecoveredOperation> {
+    let mut operations = Vec::with_capacity(entries.len());
+    for entry in entries {
+        operations.push(recover_operation(rpc, entry).await);
+    }
+    operations
+}
@@ -0,0 +1,147 @@
+// === OUTER WRAP ===
+// Optional defense in depth for stored key blobs: besides the PRF-derived ChaCha20Poly1305
+// encryption, the blob bytes are encrypted again with a non-extractable AES-GCM CryptoKey
+// that the integrator keeps in WebCrypto, so a copy of IndexedDB is not enough even if a PRF
+// output leaks. The CryptoKey cannot cross the JSON/CBOR frame, so the worker wrapper keeps
+// the one posted with the request and hands it over through `getOuterWrapKey`.
+// Wrapped blobs are self-describing (OUTER_WRAP_MAGIC prefix): decryption removes the wrap
+// when present and leaves other blobs untouched.
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::JsCast;
+
+use crate::config::{
+    OUTER_WRAP_AES_GCM_WEBCRYPTO, OUTER_WRAP_IV_SIZE, OUTER_WRAP_MAGIC, OUTER_WRAP_TAG_SIZE,
+};
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::BlobDecryptError;
+
+// Bridge implemented in web3authn-signer.worker.ts: the CryptoKey posted with the request
+// (or the Initialize handshake), undefined when none was given
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = getOuterWrapKey)]
+    fn get_outer_wrap_key() -> JsValue;
+}
+
+/// Splits outer-wrapped blob bytes into the AES-GCM IV and ciphertext.
+/// Returns None for blobs without the wrap.
+pub(crate) fn split_outer_wrap(encrypted_data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let header_size = OUTER_WRAP_MAGIC.len() + OUTER_WRAP_IV_SIZE;
+    if encrypted_data.len() < header_size + OUTER_WRAP_TAG_SIZE
+        || !encrypted_data.starts_with(OUTER_WRAP_MAGIC)
+    {
+        return None;
+    }
+    Some((
+        &encrypted_data[OUTER_WRAP_MAGIC.len()..header_size],
+        &encrypted_data[header_size..],
+    ))
+}
+
+pub(crate) fn frame_outer_wrap(iv: &[u8], ciphertext: &[u8]) -> Vec<u8> {
+    [&OUTER_WRAP_MAGIC[..], iv, ciphertext].concat()
+}
+
+/// AES-GCM with a WebCrypto key handed over by a JS bridge; Ok(None) when `key` is not a
+/// CryptoKey (none was provided)
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn aes_gcm_with_crypto_key(
+    key: JsValue,
+    encrypt: bool,
+    iv: &[u8],
+    data: &[u8],
+    aad: &[u8],
+) -> Result<Option<Vec<u8>>, String> {
+    let Ok(key) = key.dyn_into::<web_sys::CryptoKey>() else {
+        return Ok(None);
+    };
+    let crypto = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("crypto"))
+        .ok()
+        .and_then(|c| c.dyn_into::<web_sys::Crypto>().ok())
+        .ok_or_else(|| "WebCrypto is not available".to_string())?;
+    let subtle = crypto.subtle();
+
+    let params = web_sys::AesGcmParams::new("AES-GCM", &js_sys::Uint8Array::from(iv));
+    params.set_additional_data(&js_sys::Uint8Array::from(aad));
+    let promise = if encrypt {
+        subtle.encrypt_with_object_and_u8_array(&params, &key, data)
+    } else {
+        subtle.decrypt_with_object_and_u8_array(&params, &key, data)
+    }
+    .map_err(|e| format!("SubtleCrypto call failed: {:?}", e))?;
+    let result = wasm_bindgen_futures::JsFuture::from(promise)
+        .await
+        .map_err(|e| format!("AES-GCM operation failed: {:?}", e))?;
+    Ok(Some(js_sys::Uint8Array::new(&result).to_vec()))
+}
+
+/// AES-GCM with the request's outer-wrap CryptoKey; Ok(None) when no key was provided
+#[cfg(target_arch = "wasm32")]
+async fn aes_gcm_with_outer_wrap_key(
+    encrypt: bool,
+    iv: &[u8],
+    data: &[u8],
+) -> Result<Option<Vec<u8>>, String> {
+    aes_gcm_with_crypto_key(
+        get_outer_wrap_key(),
+        encrypt,
+        iv,
+        data,
+        &OUTER_WRAP_MAGIC[..],
+    )
+    .await
+}
+
+/// Non-WASM fallback (native tests): WebCrypto keys never exist here
+#[cfg(not(target_arch = "wasm32"))]
+async fn aes_gcm_with_outer_wrap_key(
+    _encrypt: bool,
+    _iv: &[u8],
+    _data: &[u8],
+) -> Result<Option<Vec<u8>>, String> {
+    Ok(None)
+}
+
+/// Wraps newly encrypted blob data when the request carried an outer-wrap CryptoKey.
+/// Returns the blob data to store and its `outerWrap` metadata (None when left unwrapped).
+pub async fn wrap_encrypted_data(
+    encrypted_data_b64u: &str,
+) -> Result<(String, Option<String>), String> {
+    let data = base64_url_decode(encrypted_data_b64u)
+        .map_err(|e| format!("Base64 decode error for encrypted data: {}", e))?;
+    let mut iv = [0u8; OUTER_WRAP_IV_SIZE];
+    getrandom::getrandom(&mut iv).map_err(|e| format!("Failed to generate IV: {}", e))?;
+    match aes_gcm_with_outer_wrap_key(true, &iv, &data).await? {
+        Some(ciphertext) => Ok((
+            base64_url_encode(&frame_outer_wrap(&iv, &ciphertext)),
+            Some(OUTER_WRAP_AES_GCM_WEBCRYPTO.to_string()),
+        )),
+        None => Ok((encrypted_data_b64u.to_string(), None)),
+    }
+}
+
+/// Removes the outer wrap from stored blob data, if it has one. A wrapped blob without the
+/// CryptoKey that made it fails with `OuterWrapKeyUnavailable`.
+pub async fn unwrap_encrypted_data(encrypted_data_b64u: &str) -> Result<String, BlobDecryptError> {
+    // Encoding errors are left to the PRF decryption to report
+    let Ok(data) = base64_url_decode(encrypted_data_b64u) else {
+        return Ok(encrypted_data_b64u.to_string());
+    };
+    let Some((iv, ciphertext)) = split_outer_wrap(&data) else {
+        return Ok(encrypted_data_b64u.to_string());
+    };
+    match aes_gcm_with_outer_wrap_key(false, iv, ciphertext).await {
+        Ok(Some(inner)) => Ok(base64_url_encode(&inner)),
+        Ok(None) => Err(BlobDecryptError::OuterWrapKeyUnavailable(
+            "key blob is outer-wrapped but no outer-wrap CryptoKey was provided".to_string(),
+        )),
+        Err(e) => Err(BlobDecryptError::OuterWrapKeyUnavailable(format!(
+            "the provided CryptoKey does not unwrap this key blob ({})",
+            e
+        ))),
+    }
+}
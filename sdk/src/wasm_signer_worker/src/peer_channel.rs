@@ -0,0 +1,214 @@
+// === PEER CHANNEL ===
+// Optional MessageChannel between this worker and the VRF worker. The main thread posts one
+// end to each worker with CONNECT_PEER_PORT before the request; while connected, signing
+// requests take their VRF challenge straight from the VRF worker (REQUEST_CHALLENGE over the
+// port) and the confirmation flow on the main thread uses it instead of generating its own.
+// The session names the VRF public key expected for the account: a challenge proven with any
+// other key is refused before it reaches the confirmation UI. Without a connected port,
+// requests keep the main-thread path. WipeAllState ends the session and closes the port.
+// As with the other bridges, requests cross the boundary as JSON strings.
+
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::JsValue;
+
+use crate::config::PEER_CHALLENGE_TIMEOUT_MS;
+use crate::encoders::base64_url_decode;
+use crate::error::SignerErrorCode;
+use crate::types::{VrfAnchor, VrfChallenge};
+
+// Bridges implemented in web3authn-signer.worker.ts, over the port it received
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = requestPeerVrfChallenge)]
+    async fn request_peer_vrf_challenge(request_json: JsValue, timeout_ms: u32) -> JsValue;
+
+    #[wasm_bindgen(js_name = closePeerPort)]
+    fn close_peer_port();
+}
+
+/// What the main thread connected the port for
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerSession {
+    /// base64url VRF public key of the account's unlocked VRF session
+    pub expected_vrf_public_key: String,
+    pub rp_id: String,
+}
+
+thread_local! {
+    static PEER_SESSION: RefCell<Option<PeerSession>> = const { RefCell::new(None) };
+}
+
+/// Starts the peer session, replacing any previous one
+pub fn connect_peer_session(session: PeerSession) -> Result<(), String> {
+    if session.expected_vrf_public_key.is_empty() || session.rp_id.is_empty() {
+        return Err("CONNECT_PEER_PORT requires expectedVrfPublicKey and rpId".to_string());
+    }
+    base64_url_decode(&session.expected_vrf_public_key)
+        .map_err(|e| format!("expectedVrfPublicKey is not base64url: {}", e))?;
+    PEER_SESSION.with(|s| *s.borrow_mut() = Some(session));
+    Ok(())
+}
+
+pub fn peer_session() -> Option<PeerSession> {
+    PEER_SESSION.with(|s| s.borrow().clone())
+}
+
+/// Ends the peer session and closes the port. Returns whether one was connected.
+pub fn disconnect_peer_session() -> bool {
+    let connected = PEER_SESSION.with(|s| s.borrow_mut().take()).is_some();
+    if connected {
+        close_port();
+    }
+    connected
+}
+
+/// REQUEST_CHALLENGE payload; without an anchor the VRF worker uses the latest block the
+/// main thread pushed to it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerChallengeRequest {
+    pub user_id: String,
+    pub rp_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<VrfAnchor>,
+}
+
+/// The VRF worker's response (VrfWorkerResponse)
+#[derive(Debug, Deserialize)]
+struct PeerChallengeResponse {
+    #[serde(default)]
+    success: bool,
+    data: Option<VrfChallenge>,
+    error: Option<String>,
+}
+
+/// Refuses a challenge that was not proven with the session's VRF key, or not for the
+/// requested user and rpId
+pub fn verify_peer_challenge(
+    challenge: &VrfChallenge,
+    request: &PeerChallengeRequest,
+    session: &PeerSession,
+) -> Result<(), (SignerErrorCode, String)> {
+    if challenge.vrf_public_key != session.expected_vrf_public_key {
+        return Err((
+            SignerErrorCode::PeerChallengeRejected,
+            format!(
+                "Peer VRF challenge was proven with {}, expected the session's key {}",
+                challenge.vrf_public_key, session.expected_vrf_public_key
+            ),
+        ));
+    }
+    if challenge.user_id != request.user_id || challenge.rp_id != request.rp_id {
+        return Err((
+            SignerErrorCode::PeerChallengeRejected,
+            format!(
+                "Peer VRF challenge is for {} on {}, requested {} on {}",
+                challenge.user_id, challenge.rp_id, request.user_id, request.rp_id
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// The verified challenge from the VRF worker's response to `request`
+pub fn parse_peer_challenge_response(
+    response_json: &str,
+    request: &PeerChallengeRequest,
+    session: &PeerSession,
+) -> Result<VrfChallenge, (SignerErrorCode, String)> {
+    let unavailable = |detail: String| {
+        (
+            SignerErrorCode::PeerChallengeUnavailable,
+            format!("Peer VRF challenge failed: {}", detail),
+        )
+    };
+    let response: PeerChallengeResponse = serde_json::from_str(response_json)
+        .map_err(|e| unavailable(format!("malformed response: {}", e)))?;
+    if !response.success {
+        return Err(unavailable(
+            response
+                .error
+                .unwrap_or_else(|| "no error given".to_string()),
+        ));
+    }
+    let challenge = response
+        .data
+        .ok_or_else(|| unavailable("response without a challenge".to_string()))?;
+    verify_peer_challenge(&challenge, request, session)?;
+    Ok(challenge)
+}
+
+/// The confirmation must come back with the challenge the worker requested, since that is
+/// the one the WebAuthn assertion signs
+pub fn check_decision_challenge(
+    requested: &VrfChallenge,
+    decided: Option<&VrfChallenge>,
+) -> Result<(), (SignerErrorCode, String)> {
+    match decided {
+        Some(decided)
+            if decided.vrf_output == requested.vrf_output
+                && decided.vrf_proof == requested.vrf_proof
+                && decided.vrf_public_key == requested.vrf_public_key =>
+        {
+            Ok(())
+        }
+        _ => Err((
+            SignerErrorCode::PeerChallengeRejected,
+            "Confirmation returned a different VRF challenge than the peer port provided"
+                .to_string(),
+        )),
+    }
+}
+
+/// The VRF challenge for a signing request by `user_id`, from the VRF worker over the peer
+/// port. Ok(None) when no port is connected: the main thread generates the challenge.
+pub async fn request_peer_challenge(
+    user_id: &str,
+) -> Result<Option<VrfChallenge>, (SignerErrorCode, String)> {
+    let Some(session) = peer_session() else {
+        return Ok(None);
+    };
+    let request = PeerChallengeRequest {
+        user_id: user_id.to_string(),
+        rp_id: session.rp_id.clone(),
+        anchor: None,
+    };
+    let response_json = send_peer_request(&request, PEER_CHALLENGE_TIMEOUT_MS)
+        .await
+        .ok_or_else(|| {
+            (
+                SignerErrorCode::PeerChallengeUnavailable,
+                "Peer VRF challenge failed: no answer over the peer port".to_string(),
+            )
+        })?;
+    parse_peer_challenge_response(&response_json, &request, &session).map(Some)
+}
+
+/// Sends REQUEST_CHALLENGE over the port; the bridge resolves with an error response once
+/// `timeout_ms` elapses
+#[cfg(target_arch = "wasm32")]
+async fn send_peer_request(request: &PeerChallengeRequest, timeout_ms: u32) -> Option<String> {
+    let request_json = serde_json::to_string(request).ok()?;
+    request_peer_vrf_challenge(JsValue::from_str(&request_json), timeout_ms)
+        .await
+        .as_string()
+}
+
+/// Non-WASM fallback (native tests): there is no port to send over
+#[cfg(not(target_arch = "wasm32"))]
+async fn send_peer_request(_request: &PeerChallengeRequest, _timeout_ms: u32) -> Option<String> {
+    None
+}
+
+#[cfg(target_arch = "wasm32")]
+fn close_port() {
+    close_peer_port();
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn close_port() {}
@@ -0,0 +1,335 @@
+// === QR PAYLOADS ===
+// Device linking and remote confirmation hand a blob to the other device through QR codes, and
+// a code holds about 1.2KB before scanning gets unreliable at medium error correction. The blobs
+// vary in size and some overflow, so QrPayloadEncoder compresses the blob (raw DEFLATE,
+// deterministic; deflate.rs), splits it into as few parts as fit the character budget, and
+// base45-encodes each part (RFC 9285) so it scans in QR alphanumeric mode.
+//
+// A part is `W3A:` followed by the base45 of:
+//   version (1) | kind (1) | index (1) | part count (1) | checksum (4) | compressed chunk
+// The checksum is the first 4 bytes of SHA-256 over the uncompressed blob; it ties the parts of
+// one payload together and checks the reassembled blob. QrPayloadDecoder takes parts in any
+// order (rescanning a part is harmless), reports the indexes still missing, and once every part
+// is in returns the blob, refusing parts of another payload and payloads that do not check.
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+use crate::config::{
+    MAX_QR_PAYLOAD_BYTES, QR_PART_MAX_CHARS, QR_PART_MIN_CHARS, QR_PAYLOAD_PREFIX,
+    QR_PAYLOAD_VERSION,
+};
+use crate::deflate::{deflate, inflate};
+use crate::encoders::base64_url_encode;
+use crate::error::QrPayloadError;
+
+const BASE45_ALPHABET: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+/// version, kind, index, part count, checksum
+const HEADER_LEN: usize = 8;
+/// Part indexes and counts are one byte
+const MAX_PARTS: usize = u8::MAX as usize;
+
+/// What a QR payload carries
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum QrPayloadKind {
+    DeviceLinking,
+    RemoteConfirmation,
+}
+
+impl QrPayloadKind {
+    /// "deviceLinking" | "remoteConfirmation"
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "deviceLinking" => Some(QrPayloadKind::DeviceLinking),
+            "remoteConfirmation" => Some(QrPayloadKind::RemoteConfirmation),
+            _ => None,
+        }
+    }
+
+    fn tag(self) -> u8 {
+        match self {
+            QrPayloadKind::DeviceLinking => 1,
+            QrPayloadKind::RemoteConfirmation => 2,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            1 => Some(QrPayloadKind::DeviceLinking),
+            2 => Some(QrPayloadKind::RemoteConfirmation),
+            _ => None,
+        }
+    }
+}
+
+pub fn base45_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(2) * 3);
+    for pair in data.chunks(2) {
+        let (mut value, digits) = match pair {
+            [a, b] => ((*a as usize) << 8 | *b as usize, 3),
+            _ => (pair[0] as usize, 2),
+        };
+        for _ in 0..digits {
+            out.push(BASE45_ALPHABET[value % 45] as char);
+            value /= 45;
+        }
+    }
+    out
+}
+
+pub fn base45_decode(input: &str) -> Result<Vec<u8>, String> {
+    let digits = input
+        .bytes()
+        .map(|c| {
+            BASE45_ALPHABET
+                .iter()
+                .position(|&a| a == c)
+                .ok_or_else(|| format!("'{}' is not a base45 character", c as char))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if digits.len() % 3 == 1 {
+        return Err("base45 input has a dangling character".to_string());
+    }
+    let mut out = Vec::with_capacity(digits.len() / 3 * 2 + 1);
+    for group in digits.chunks(3) {
+        let value = group.iter().rev().fold(0usize, |acc, &d| acc * 45 + d);
+        if group.len() == 3 {
+            if value > 0xFFFF {
+                return Err("base45 group is out of range".to_string());
+            }
+            out.extend_from_slice(&(value as u16).to_be_bytes());
+        } else {
+            if value > 0xFF {
+                return Err("base45 group is out of range".to_string());
+            }
+            out.push(value as u8);
+        }
+    }
+    Ok(out)
+}
+
+/// Ties the parts of one payload together and checks the reassembled blob
+fn checksum(blob: &[u8]) -> [u8; 4] {
+    let digest = Sha256::digest(blob);
+    [digest[0], digest[1], digest[2], digest[3]]
+}
+
+/// Splits blobs into QR parts of at most `max_part_chars` characters
+#[derive(Debug, Clone)]
+pub struct QrPayloadEncoder {
+    max_part_chars: usize,
+}
+
+impl Default for QrPayloadEncoder {
+    fn default() -> Self {
+        Self {
+            max_part_chars: QR_PART_MAX_CHARS,
+        }
+    }
+}
+
+impl QrPayloadEncoder {
+    pub fn new(max_part_chars: usize) -> Result<Self, QrPayloadError> {
+        if max_part_chars < QR_PART_MIN_CHARS {
+            return Err(QrPayloadError::Malformed(format!(
+                "a part budget of {} characters is below the minimum of {}",
+                max_part_chars, QR_PART_MIN_CHARS
+            )));
+        }
+        Ok(Self { max_part_chars })
+    }
+
+    /// Compressed bytes each part carries after its header
+    fn chunk_capacity(&self) -> usize {
+        let body_chars = self.max_part_chars - QR_PAYLOAD_PREFIX.len();
+        body_chars / 3 * 2 + usize::from(body_chars % 3 == 2) - HEADER_LEN
+    }
+
+    /// The parts carrying `blob`, in index order
+    pub fn encode(&self, kind: QrPayloadKind, blob: &[u8]) -> Result<Vec<String>, QrPayloadError> {
+        if blob.len() > MAX_QR_PAYLOAD_BYTES {
+            return Err(QrPayloadError::Malformed(format!(
+                "a {} byte blob is over the {} byte limit",
+                blob.len(),
+                MAX_QR_PAYLOAD_BYTES
+            )));
+        }
+        let compressed = deflate(blob);
+        let part_count = compressed.len().div_ceil(self.chunk_capacity());
+        if part_count > MAX_PARTS {
+            return Err(QrPayloadError::Malformed(format!(
+                "the blob needs {} parts, more than {}",
+                part_count, MAX_PARTS
+            )));
+        }
+        // Even chunks, so every code of the payload is about the same size
+        let chunks: Vec<&[u8]> = compressed
+            .chunks(compressed.len().div_ceil(part_count))
+            .collect();
+        let checksum = checksum(blob);
+        Ok(chunks
+            .iter()
+            .enumerate()
+            .map(|(index, chunk)| {
+                let mut part = vec![
+                    QR_PAYLOAD_VERSION,
+                    kind.tag(),
+                    index as u8,
+                    chunks.len() as u8,
+                ];
+                part.extend_from_slice(&checksum);
+                part.extend_from_slice(chunk);
+                format!("{}{}", QR_PAYLOAD_PREFIX, base45_encode(&part))
+            })
+            .collect())
+    }
+}
+
+/// What every part of one payload repeats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PayloadHeader {
+    kind: QrPayloadKind,
+    part_count: u8,
+    checksum: [u8; 4],
+}
+
+/// Reassembles the parts of one payload, added in any order
+#[derive(Debug, Default)]
+pub struct QrPayloadDecoder {
+    header: Option<PayloadHeader>,
+    chunks: BTreeMap<u8, Vec<u8>>,
+}
+
+impl QrPayloadDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a scanned part; adding the same part again is a no-op
+    pub fn add_part(&mut self, part: &str) -> Result<(), QrPayloadError> {
+        let body = part.trim().strip_prefix(QR_PAYLOAD_PREFIX).ok_or_else(|| {
+            QrPayloadError::Malformed(format!("part does not start with {}", QR_PAYLOAD_PREFIX))
+        })?;
+        let bytes = base45_decode(body).map_err(QrPayloadError::Malformed)?;
+        if bytes.len() <= HEADER_LEN {
+            return Err(QrPayloadError::Malformed(
+                "part is shorter than its header".to_string(),
+            ));
+        }
+        if bytes[0] != QR_PAYLOAD_VERSION {
+            return Err(QrPayloadError::Malformed(format!(
+                "unsupported QR payload version {}",
+                bytes[0]
+            )));
+        }
+        let kind = QrPayloadKind::from_tag(bytes[1]).ok_or_else(|| {
+            QrPayloadError::Malformed(format!("unknown QR payload kind {}", bytes[1]))
+        })?;
+        let (index, part_count) = (bytes[2], bytes[3]);
+        if index >= part_count {
+            return Err(QrPayloadError::Malformed(format!(
+                "part {} of a {} part payload",
+                index, part_count
+            )));
+        }
+        let header = PayloadHeader {
+            kind,
+            part_count,
+            checksum: [bytes[4], bytes[5], bytes[6], bytes[7]],
+        };
+        if self.header.is_some_and(|known| known != header) {
+            return Err(QrPayloadError::Mismatched(format!(
+                "part {} belongs to another payload",
+                index
+            )));
+        }
+        let chunk = &bytes[HEADER_LEN..];
+        match self.chunks.get(&index) {
+            Some(known) if known.as_slice() != chunk => {
+                return Err(QrPayloadError::Mismatched(format!(
+                    "two different parts are numbered {}",
+                    index
+                )));
+            }
+            Some(_) => {}
+            None => {
+                self.chunks.insert(index, chunk.to_vec());
+            }
+        }
+        self.header = Some(header);
+        Ok(())
+    }
+
+    pub fn kind(&self) -> Option<QrPayloadKind> {
+        self.header.map(|header| header.kind)
+    }
+
+    /// Parts in the payload; None before the first part
+    pub fn part_count(&self) -> Option<u32> {
+        self.header.map(|header| header.part_count as u32)
+    }
+
+    /// Indexes of the parts not added yet (empty before the first part)
+    pub fn missing_parts(&self) -> Vec<u32> {
+        let part_count = self.header.map_or(0, |header| header.part_count);
+        (0..part_count)
+            .filter(|index| !self.chunks.contains_key(index))
+            .map(u32::from)
+            .collect()
+    }
+
+    /// The blob, once every part is in and it matches the checksum
+    pub fn finish(&self) -> Result<Vec<u8>, QrPayloadError> {
+        let header = self
+            .header
+            .ok_or_else(|| QrPayloadError::Malformed("no parts were added".to_string()))?;
+        let missing_parts = self.missing_parts();
+        if !missing_parts.is_empty() {
+            return Err(QrPayloadError::Incomplete { missing_parts });
+        }
+        let compressed = self.chunks.values().flatten().copied().collect::<Vec<_>>();
+        let blob = inflate(&compressed, MAX_QR_PAYLOAD_BYTES)
+            .map_err(|_| QrPayloadError::ChecksumMismatch)?;
+        if checksum(&blob) != header.checksum {
+            return Err(QrPayloadError::ChecksumMismatch);
+        }
+        Ok(blob)
+    }
+}
+
+/// Returned by decode_qr_payload
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedQrPayload {
+    pub kind: QrPayloadKind,
+    pub part_count: u32,
+    /// Indexes still to scan; the blob is null until this is empty
+    pub missing_parts: Vec<u32>,
+    pub blob_b64u: Option<String>,
+}
+
+/// Decodes the parts scanned so far, in any order
+pub fn decode_qr_parts(parts: &[String]) -> Result<DecodedQrPayload, QrPayloadError> {
+    let mut decoder = QrPayloadDecoder::new();
+    for part in parts {
+        decoder.add_part(part)?;
+    }
+    let (kind, part_count) = match (decoder.kind(), decoder.part_count()) {
+        (Some(kind), Some(part_count)) => (kind, part_count),
+        _ => return Err(QrPayloadError::Malformed("no parts were given".to_string())),
+    };
+    let missing_parts = decoder.missing_parts();
+    let blob_b64u = if missing_parts.is_empty() {
+        Some(base64_url_encode(&decoder.finish()?))
+    } else {
+        None
+    };
+    Ok(DecodedQrPayload {
+        kind,
+        part_count,
+        missing_parts,
+        blob_b64u,
+    })
+}
@@ -0,0 +1,165 @@
+// === RECENT RECEIVERS ===
+// Receiver IDs the account recently sent transactions to, used for account ID autocomplete
+// and to flag first-time receivers in the signing confirmation. NEAR RPC cannot list an
+// account's transactions, so history comes from a NearBlocks-compatible indexer
+// (`WorkerPolicy.indexerUrl`) and is cached per session in worker state. Lookups never
+// fail the caller: without an indexer, or when it is unreachable, the history is "unknown".
+
+use log::warn;
+use serde::Deserialize;
+
+use crate::config::MAX_RECENT_RECEIVERS_LIMIT;
+use crate::rpc_calls::fetch_json;
+use crate::state::{self, RecentReceiver};
+use crate::types::AccountId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReceiversSource {
+    /// Served from this session's cache
+    Cache,
+    /// Fetched from the indexer just now
+    Indexer,
+    /// No indexer configured, or the indexer could not be reached
+    Unknown,
+}
+
+impl ReceiversSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReceiversSource::Cache => "cache",
+            ReceiversSource::Indexer => "indexer",
+            ReceiversSource::Unknown => "unknown",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceiverHistory {
+    /// Most recent first; None when the source is Unknown
+    pub receivers: Option<Vec<RecentReceiver>>,
+    pub source: ReceiversSource,
+}
+
+#[derive(Deserialize)]
+struct IndexerTxnsResponse {
+    #[serde(default)]
+    txns: Vec<IndexerTxn>,
+}
+
+#[derive(Deserialize)]
+struct IndexerTxn {
+    #[serde(default)]
+    predecessor_account_id: Option<String>,
+    #[serde(default)]
+    signer_account_id: Option<String>,
+    receiver_account_id: String,
+    /// Nanoseconds since the epoch, as a decimal string (or number)
+    block_timestamp: serde_json::Value,
+}
+
+fn block_timestamp_ms(value: &serde_json::Value) -> Option<f64> {
+    let nanos: u128 = match value {
+        serde_json::Value::String(s) => s.parse().ok()?,
+        serde_json::Value::Number(n) => n.as_u64()? as u128,
+        _ => return None,
+    };
+    Some((nanos / 1_000_000) as f64)
+}
+
+/// Indexer endpoint listing the account's latest transactions
+pub fn indexer_txns_url(indexer_url: &str, near_account_id: &str) -> String {
+    format!(
+        "{}/account/{}/txns?per_page={}",
+        indexer_url.trim_end_matches('/'),
+        near_account_id,
+        MAX_RECENT_RECEIVERS_LIMIT
+    )
+}
+
+/// Extracts the distinct receivers of the account's outgoing transactions from an indexer
+/// `txns` response, most recently seen first. Self-transactions are skipped.
+pub fn parse_indexer_receivers(
+    near_account_id: &str,
+    response: &serde_json::Value,
+) -> Result<Vec<RecentReceiver>, String> {
+    let parsed: IndexerTxnsResponse = serde_json::from_value(response.clone())
+        .map_err(|e| format!("Unexpected indexer response: {}", e))?;
+
+    let mut receivers: Vec<RecentReceiver> = Vec::new();
+    for txn in parsed.txns {
+        let outgoing = txn.signer_account_id.as_deref() == Some(near_account_id)
+            || txn.predecessor_account_id.as_deref() == Some(near_account_id);
+        if !outgoing || txn.receiver_account_id == near_account_id {
+            continue;
+        }
+        let last_seen_ms = block_timestamp_ms(&txn.block_timestamp).unwrap_or(0.0);
+        match receivers
+            .iter_mut()
+            .find(|r| r.receiver_id == txn.receiver_account_id)
+        {
+            Some(existing) => existing.last_seen_ms = existing.last_seen_ms.max(last_seen_ms),
+            None => receivers.push(RecentReceiver {
+                receiver_id: txn.receiver_account_id,
+                last_seen_ms,
+            }),
+        }
+    }
+    receivers.sort_by(|a, b| b.last_seen_ms.total_cmp(&a.last_seen_ms));
+    Ok(receivers)
+}
+
+/// The account's receiver history, from the session cache or the indexer
+pub async fn lookup_receiver_history(
+    account: &AccountId,
+    indexer_url: Option<&str>,
+) -> ReceiverHistory {
+    if let Some(receivers) = state::cached_recent_receivers(account) {
+        return ReceiverHistory {
+            receivers: Some(receivers),
+            source: ReceiversSource::Cache,
+        };
+    }
+    let unknown = ReceiverHistory {
+        receivers: None,
+        source: ReceiversSource::Unknown,
+    };
+    let Some(indexer_url) = indexer_url.filter(|url| !url.trim().is_empty()) else {
+        return unknown;
+    };
+
+    let fetched = fetch_json(&indexer_txns_url(indexer_url, account.as_str()))
+        .await
+        .and_then(|response| parse_indexer_receivers(account.as_str(), &response));
+    match fetched {
+        Ok(receivers) => {
+            state::cache_recent_receivers(account, receivers.clone());
+            ReceiverHistory {
+                receivers: Some(receivers),
+                source: ReceiversSource::Indexer,
+            }
+        }
+        Err(e) => {
+            warn!("RUST: Recent receivers lookup failed: {}", e);
+            unknown
+        }
+    }
+}
+
+/// Per transaction: Some(true) if the receiver is absent from the account's recent history,
+/// Some(false) if present (or the account itself), None when the history is unknown
+pub fn first_time_receiver_flags(
+    near_account_id: &str,
+    history: &ReceiverHistory,
+    receiver_ids: &[&str],
+) -> Vec<Option<bool>> {
+    receiver_ids
+        .iter()
+        .map(|receiver_id| {
+            let receivers = history.receivers.as_ref()?;
+            Some(
+                *receiver_id != near_account_id
+                    && !receivers.iter().any(|r| r.receiver_id == *receiver_id),
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,171 @@
+// === ARGUMENT REDACTION ===
+// Some contract calls carry PII or secrets in their args (encrypted blobs, email hashes) that
+// should not be shown verbatim in the confirmation or kept in the audit log. A signing request's
+// `redactPaths` names args values ("args.email", "args.profile.notes.0": object keys and array
+// indexes after `args.`), and in every FunctionCall whose JSON args have a value there, the
+// TxTree and the summary blocks show
+//
+//   «redacted (sha256: 1a2b3c4d…)»
+//
+// instead. The SHA-256 is over the value's compact JSON. Each transaction of the confirmation
+// payload lists its `redactions` (action index, path, full hash), and the intent digest covers
+// them, so it still commits to the values signed; the signed request's audit entry keeps the
+// full hashes for support to check a value later. Only args can be redacted: the receiver,
+// method, deposit and gas are always shown.
+
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::actions::ActionParams;
+use crate::error::RedactionError;
+
+/// FunctionCall fields outside its args, which every confirmation shows
+const CRITICAL_FIELDS: [&str; 4] = ["receiverId", "methodName", "deposit", "gas"];
+
+/// A parsed `redactPaths` entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedactPath {
+    /// As given, e.g. "args.email"
+    pub path: String,
+    /// Keys (or array indexes) below `args`
+    segments: Vec<String>,
+}
+
+/// One value redacted from a transaction's confirmation
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Redaction {
+    pub action_index: usize,
+    pub path: String,
+    /// Hex SHA-256 of the value's compact JSON
+    pub sha256: String,
+}
+
+/// Parses a request's `redactPaths`, refusing paths outside FunctionCall args
+pub fn parse_redact_paths(paths: &[String]) -> Result<Vec<RedactPath>, RedactionError> {
+    paths
+        .iter()
+        .map(|path| {
+            let invalid = |reason: &str| RedactionError::InvalidPath {
+                path: path.clone(),
+                reason: reason.to_string(),
+            };
+            let mut segments = path.split('.');
+            match segments.next() {
+                Some("args") => {}
+                Some(root) if CRITICAL_FIELDS.contains(&root) => {
+                    return Err(RedactionError::CriticalField { path: path.clone() })
+                }
+                _ => return Err(invalid("paths start with `args.`")),
+            }
+            let segments: Vec<String> = segments.map(str::to_string).collect();
+            if segments.is_empty() {
+                return Err(invalid("names no argument"));
+            }
+            if segments.iter().any(String::is_empty) {
+                return Err(invalid("empty path segment"));
+            }
+            Ok(RedactPath {
+                path: path.clone(),
+                segments,
+            })
+        })
+        .collect()
+}
+
+/// Hex SHA-256 of a value's compact JSON
+pub fn value_sha256(value: &Value) -> String {
+    let digest = Sha256::digest(value.to_string().as_bytes());
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// What a redacted value is shown as
+pub fn redaction_marker(sha256: &str) -> String {
+    format!("«redacted (sha256: {}…)»", &sha256[..8.min(sha256.len())])
+}
+
+fn value_at<'a>(value: &'a mut Value, segments: &[String]) -> Option<&'a mut Value> {
+    segments
+        .iter()
+        .try_fold(value, |current, segment| match current {
+            Value::Object(map) => map.get_mut(segment),
+            Value::Array(items) => segment
+                .parse::<usize>()
+                .ok()
+                .and_then(move |i| items.get_mut(i)),
+            _ => None,
+        })
+}
+
+/// The actions as shown: FunctionCall args values at `paths` replaced with their marker.
+/// Args that are not JSON, or have no value at a path, are shown unchanged.
+pub fn redact_actions(
+    actions: &[ActionParams],
+    paths: &[RedactPath],
+) -> (Vec<ActionParams>, Vec<Redaction>) {
+    let mut redactions = Vec::new();
+    if paths.is_empty() {
+        return (actions.to_vec(), redactions);
+    }
+    let shown = actions
+        .iter()
+        .enumerate()
+        .map(|(action_index, action)| {
+            let ActionParams::FunctionCall {
+                method_name,
+                args,
+                gas,
+                deposit,
+            } = action
+            else {
+                return action.clone();
+            };
+            let Ok(mut parsed) = serde_json::from_str::<Value>(args) else {
+                return action.clone();
+            };
+            let before = redactions.len();
+            for path in paths {
+                if let Some(value) = value_at(&mut parsed, &path.segments) {
+                    let sha256 = value_sha256(value);
+                    *value = Value::String(redaction_marker(&sha256));
+                    redactions.push(Redaction {
+                        action_index,
+                        path: path.path.clone(),
+                        sha256,
+                    });
+                }
+            }
+            if redactions.len() == before {
+                return action.clone();
+            }
+            ActionParams::FunctionCall {
+                method_name: method_name.clone(),
+                args: parsed.to_string(),
+                gas: gas.clone(),
+                deposit: deposit.clone(),
+            }
+        })
+        .collect();
+    (shown, redactions)
+}
+
+/// Audit detail of a signed batch: its redactions with their full hashes, None without any
+pub fn audit_detail(
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+    paths: &[RedactPath],
+) -> Option<String> {
+    let listed: Vec<String> = receivers_and_actions
+        .iter()
+        .enumerate()
+        .flat_map(|(tx_index, (_, actions))| {
+            redact_actions(actions, paths).1.into_iter().map(move |r| {
+                format!(
+                    "tx {} action {} {} sha256:{}",
+                    tx_index, r.action_index, r.path, r.sha256
+                )
+            })
+        })
+        .collect();
+    (!listed.is_empty()).then(|| format!("Redacted {}", listed.join("; ")))
+}
@@ -0,0 +1,363 @@
+// === REDIRECT PAYLOADS ===
+// Sign-in style wallet redirects for integrations without the wallet iframe: BuildRedirectPayload
+// returns a token naming the account, a session key (CreateSessionKey) and a VRF challenge,
+// and the callback URL with the token and the caller's `state` appended. The receiving side
+// checks it with verify_redirect_payload, a pure function exported to JS.
+//
+// The token is the payload's CBOR bytes with the session key's ed25519 signature over them,
+// wrapped in a CBOR envelope encoded as base64url, like remote confirmation requests. A
+// signature rather than a worker MAC, so the receiver verifies without a shared secret; the
+// session key being an access key of the account is what the receiver checks on chain
+// (view_access_key), as verify_redirect_payload cannot. The payload holds public data only:
+// no PRF output, and no private key or anything derived from one besides the signature.
+//
+// The callback URL must be https, free of credentials, whitespace and backslashes (which
+// browsers read as '/'), and on an origin of WorkerPolicy.allowedRedirectOrigins, so a page
+// cannot turn the worker into an open redirector: javascript:, data: and http: callbacks fail
+// with RedirectSchemeNotAllowed, others with RedirectOriginNotAllowed. The payload binds the
+// exact callback URL and state, so a token captured from one redirect does not verify for
+// another, and the composed URL must fit MAX_REDIRECT_URL_CHARS.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{
+    MAX_REDIRECT_PAYLOAD_TTL_MS, MAX_REDIRECT_STATE_CHARS, MAX_REDIRECT_URL_CHARS,
+    REDIRECT_PAYLOAD_PARAM, REDIRECT_PAYLOAD_SIGNATURE_DOMAIN, REDIRECT_PAYLOAD_VERSION,
+    REDIRECT_STATE_PARAM,
+};
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::RedirectPayloadError;
+use crate::rpc_endpoints::rpc_origin;
+use crate::types::handlers::WorkerPolicy;
+use crate::types::{VrfAnchor, VrfChallenge};
+
+/// A VRF challenge with its base64url fields as bytes, to keep the token short
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct CompactVrfChallenge {
+    #[serde(with = "serde_bytes")]
+    vrf_input: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    vrf_output: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    vrf_proof: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    vrf_public_key: Vec<u8>,
+    user_id: String,
+    rp_id: String,
+    block_height: String,
+    #[serde(with = "serde_bytes")]
+    block_hash: Vec<u8>,
+    /// Only generic anchors; None is NEAR-anchored, from the block height and hash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    generic_anchor: Option<VrfAnchor>,
+    challenge_length: u8,
+}
+
+impl CompactVrfChallenge {
+    fn new(challenge: &VrfChallenge) -> Result<Self, String> {
+        let bytes = |field: &str, value: &str| {
+            base64_url_decode(value).map_err(|e| format!("Invalid vrfChallenge.{}: {}", field, e))
+        };
+        Ok(CompactVrfChallenge {
+            vrf_input: bytes("vrfInput", &challenge.vrf_input)?,
+            vrf_output: bytes("vrfOutput", &challenge.vrf_output)?,
+            vrf_proof: bytes("vrfProof", &challenge.vrf_proof)?,
+            vrf_public_key: bytes("vrfPublicKey", &challenge.vrf_public_key)?,
+            user_id: challenge.user_id.clone(),
+            rp_id: challenge.rp_id.clone(),
+            block_height: challenge.block_height.clone(),
+            block_hash: bytes("blockHash", &challenge.block_hash)?,
+            generic_anchor: challenge
+                .anchor
+                .clone()
+                .filter(|anchor| matches!(anchor, VrfAnchor::Generic { .. })),
+            challenge_length: challenge.challenge_length,
+        })
+    }
+
+    fn to_challenge(&self) -> VrfChallenge {
+        VrfChallenge {
+            vrf_input: base64_url_encode(&self.vrf_input),
+            vrf_output: base64_url_encode(&self.vrf_output),
+            vrf_proof: base64_url_encode(&self.vrf_proof),
+            vrf_public_key: base64_url_encode(&self.vrf_public_key),
+            user_id: self.user_id.clone(),
+            rp_id: self.rp_id.clone(),
+            block_height: self.block_height.clone(),
+            block_hash: base64_url_encode(&self.block_hash),
+            anchor: self.generic_anchor.clone(),
+            challenge_length: self.challenge_length,
+        }
+    }
+}
+
+/// What the token asserts
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct RedirectPayload {
+    version: u32,
+    near_account_id: String,
+    /// ed25519 session key that signed the payload
+    #[serde(with = "serde_bytes")]
+    session_public_key: Vec<u8>,
+    vrf_challenge: CompactVrfChallenge,
+    /// The callback URL as requested, before the token and state were appended
+    callback_url: String,
+    state: String,
+    issued_at_ms: f64,
+    expires_at_ms: f64,
+    #[serde(with = "serde_bytes")]
+    nonce: Vec<u8>,
+}
+
+/// What is handed to the caller: the payload's exact CBOR bytes and the session key's
+/// signature over them
+#[derive(Serialize, Deserialize)]
+struct SignedRedirectPayload {
+    #[serde(with = "serde_bytes")]
+    payload: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    signature: Vec<u8>,
+}
+
+/// A payload verify_redirect_payload accepted
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiedRedirectPayload {
+    pub near_account_id: String,
+    /// "ed25519:..."; the receiver checks it is an access key of `near_account_id`
+    pub session_public_key: String,
+    pub vrf_challenge: VrfChallenge,
+    pub callback_url: String,
+    pub state: String,
+    pub issued_at_ms: f64,
+    pub expires_at_ms: f64,
+    /// base64url; receivers that keep seen nonces can refuse replays within the expiry
+    pub nonce: String,
+}
+
+/// A signed redirect: the token and the callback URL carrying it
+#[derive(Debug, Clone, PartialEq)]
+pub struct RedirectPayloadToken {
+    pub token: String,
+    pub url: String,
+    pub expires_at_ms: f64,
+}
+
+/// Refuses callback URLs that are not https on an allowed origin. Returns the origin.
+pub fn check_callback_url(
+    callback_url: &str,
+    worker_policy: Option<&WorkerPolicy>,
+) -> Result<String, RedirectPayloadError> {
+    let not_allowed = || RedirectPayloadError::OriginNotAllowed {
+        url: callback_url.to_string(),
+    };
+    let scheme = callback_url
+        .split_once(':')
+        .map(|(scheme, _)| scheme.to_ascii_lowercase())
+        .filter(|scheme| {
+            scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+        })
+        .unwrap_or_default();
+    if scheme != "https" {
+        return Err(RedirectPayloadError::SchemeNotAllowed { scheme });
+    }
+    if callback_url
+        .chars()
+        .any(|c| !c.is_ascii_graphic() || c == '\\')
+    {
+        return Err(not_allowed());
+    }
+    let origin = rpc_origin(callback_url).ok_or_else(not_allowed)?;
+    let allowed = worker_policy.is_some_and(|p| {
+        p.allowed_redirect_origins
+            .iter()
+            .any(|allowed| rpc_origin(allowed).as_ref() == Some(&origin))
+    });
+    if !allowed {
+        return Err(not_allowed());
+    }
+    Ok(origin)
+}
+
+/// `state` is echoed in the URL as is, so it is limited to URL-safe characters
+pub fn check_state(state: &str) -> Result<(), String> {
+    if state.is_empty() || state.len() > MAX_REDIRECT_STATE_CHARS {
+        return Err(format!(
+            "state must be 1 to {} characters",
+            MAX_REDIRECT_STATE_CHARS
+        ));
+    }
+    if !state
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || b"-._~".contains(&b))
+    {
+        return Err("state may only contain letters, digits and -._~".to_string());
+    }
+    Ok(())
+}
+
+/// The callback URL with the token and state added to its query, ahead of any fragment
+pub fn compose_redirect_url(callback_url: &str, token: &str, state: &str) -> String {
+    let (base, fragment) = match callback_url.split_once('#') {
+        Some((base, fragment)) => (base, Some(fragment)),
+        None => (callback_url, None),
+    };
+    let separator = match base.split_once('?') {
+        None => "?",
+        Some((_, query)) if query.is_empty() || query.ends_with('&') => "",
+        Some(_) => "&",
+    };
+    let mut url = format!(
+        "{}{}{}={}&{}={}",
+        base, separator, REDIRECT_PAYLOAD_PARAM, token, REDIRECT_STATE_PARAM, state
+    );
+    if let Some(fragment) = fragment {
+        url.push('#');
+        url.push_str(fragment);
+    }
+    url
+}
+
+fn signed_message(payload_cbor: &[u8]) -> Vec<u8> {
+    [REDIRECT_PAYLOAD_SIGNATURE_DOMAIN, payload_cbor].concat()
+}
+
+/// Signs a redirect payload for `callback_url` (already checked with check_callback_url) with
+/// `session_key` and composes the redirect URL
+pub fn issue_redirect_payload(
+    session_key: &SigningKey,
+    near_account_id: &str,
+    vrf_challenge: &VrfChallenge,
+    callback_url: &str,
+    state: &str,
+    issued_at_ms: f64,
+    expires_at_ms: f64,
+) -> Result<RedirectPayloadToken, String> {
+    check_state(state)?;
+    if vrf_challenge.user_id != near_account_id {
+        return Err(format!(
+            "VRF challenge was issued for {}, not {}",
+            vrf_challenge.user_id, near_account_id
+        ));
+    }
+    let mut nonce = vec![0u8; 16];
+    getrandom::getrandom(&mut nonce)
+        .map_err(|e| format!("Failed to generate redirect payload nonce: {}", e))?;
+    let payload = RedirectPayload {
+        version: REDIRECT_PAYLOAD_VERSION,
+        near_account_id: near_account_id.to_string(),
+        session_public_key: session_key.verifying_key().to_bytes().to_vec(),
+        vrf_challenge: CompactVrfChallenge::new(vrf_challenge)?,
+        callback_url: callback_url.to_string(),
+        state: state.to_string(),
+        issued_at_ms,
+        expires_at_ms,
+        nonce,
+    };
+
+    let mut payload_cbor = Vec::new();
+    ciborium::into_writer(&payload, &mut payload_cbor)
+        .map_err(|e| format!("Failed to encode redirect payload: {}", e))?;
+    let signature = session_key.sign(&signed_message(&payload_cbor));
+    let mut envelope = Vec::new();
+    ciborium::into_writer(
+        &SignedRedirectPayload {
+            payload: payload_cbor,
+            signature: signature.to_bytes().to_vec(),
+        },
+        &mut envelope,
+    )
+    .map_err(|e| format!("Failed to encode redirect payload envelope: {}", e))?;
+    let token = base64_url_encode(&envelope);
+
+    let url = compose_redirect_url(callback_url, &token, state);
+    if url.len() > MAX_REDIRECT_URL_CHARS {
+        return Err(format!(
+            "Redirect URL is {} characters, over the {} character budget",
+            url.len(),
+            MAX_REDIRECT_URL_CHARS
+        ));
+    }
+    Ok(RedirectPayloadToken {
+        token,
+        url,
+        expires_at_ms,
+    })
+}
+
+/// The receiving side's check: the token's signature under the session key it names, its
+/// version, its expiry against `now_ms` (capped at MAX_REDIRECT_PAYLOAD_TTL_MS after issue),
+/// and that it was issued for `callback_url` and `state`. Whether the session key is an access
+/// key of the account, and replay within the expiry, are for the receiver to check.
+pub fn verify_redirect_payload(
+    token: &str,
+    callback_url: &str,
+    state: &str,
+    now_ms: f64,
+) -> Result<VerifiedRedirectPayload, RedirectPayloadError> {
+    let envelope = base64_url_decode(token.trim()).map_err(RedirectPayloadError::Invalid)?;
+    let signed: SignedRedirectPayload = ciborium::from_reader(envelope.as_slice())
+        .map_err(|e| RedirectPayloadError::Invalid(format!("Invalid envelope: {}", e)))?;
+    let payload: RedirectPayload = ciborium::from_reader(signed.payload.as_slice())
+        .map_err(|e| RedirectPayloadError::Invalid(format!("Invalid payload: {}", e)))?;
+    if payload.version != REDIRECT_PAYLOAD_VERSION {
+        return Err(RedirectPayloadError::Invalid(format!(
+            "Unsupported redirect payload version {}",
+            payload.version
+        )));
+    }
+    let session_public_key: [u8; 32] = payload
+        .session_public_key
+        .as_slice()
+        .try_into()
+        .map_err(|_| RedirectPayloadError::Invalid("session key must be 32 bytes".to_string()))?;
+    let signature = Signature::from_slice(&signed.signature)
+        .map_err(|e| RedirectPayloadError::Invalid(format!("Invalid signature: {}", e)))?;
+    VerifyingKey::from_bytes(&session_public_key)
+        .map_err(|e| RedirectPayloadError::Invalid(format!("Invalid session key: {}", e)))?
+        .verify_strict(&signed_message(&signed.payload), &signature)
+        .map_err(|_| {
+            RedirectPayloadError::Invalid(
+                "signature does not verify under the session key".to_string(),
+            )
+        })?;
+
+    let expires_at_ms = payload
+        .expires_at_ms
+        .min(payload.issued_at_ms + MAX_REDIRECT_PAYLOAD_TTL_MS as f64);
+    if expires_at_ms.is_nan() || expires_at_ms <= now_ms {
+        return Err(RedirectPayloadError::Expired { expires_at_ms });
+    }
+    if payload.callback_url != callback_url {
+        return Err(RedirectPayloadError::Invalid(format!(
+            "payload was issued for {}, not {}",
+            payload.callback_url, callback_url
+        )));
+    }
+    if payload.state != state {
+        return Err(RedirectPayloadError::Invalid(
+            "payload was issued for another state".to_string(),
+        ));
+    }
+    if payload.vrf_challenge.user_id != payload.near_account_id {
+        return Err(RedirectPayloadError::Invalid(format!(
+            "VRF challenge was issued for {}, not {}",
+            payload.vrf_challenge.user_id, payload.near_account_id
+        )));
+    }
+    Ok(VerifiedRedirectPayload {
+        near_account_id: payload.near_account_id,
+        session_public_key: format!("ed25519:{}", bs58::encode(session_public_key).into_string()),
+        vrf_challenge: payload.vrf_challenge.to_challenge(),
+        callback_url: payload.callback_url,
+        state: payload.state,
+        issued_at_ms: payload.issued_at_ms,
+        expires_at_ms,
+        nonce: base64_url_encode(&payload.nonce),
+    })
+}
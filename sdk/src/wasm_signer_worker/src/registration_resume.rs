@@ -0,0 +1,165 @@
+// === RESUMABLE REGISTRATION ===
+// Registration in two phases, so a tab closed between account creation and local storage
+// cannot leave an on-chain account without its encrypted key. PrepareRegistration derives and
+// encrypts the keys without any network call and returns them with a resume token; the caller
+// stores the blob, then runs CompleteRegistration with the token to submit the registration to
+// the relayer. A retried (or resumed) CompleteRegistration submits the key named in the token
+// and refuses PRF output that derives any other, so what reaches the chain is always the key
+// that was stored.
+//
+// The token records the derivation inputs (account, credential, derivation version) and the
+// derived public key as CBOR, with an HMAC-SHA256 wrapped in a CBOR envelope encoded as
+// base64url, like signing intents. Unlike those, it must verify in a later worker, so the MAC
+// key is derived from the registration's ChaCha20 PRF output rather than the worker session:
+// only the passkey that was registered can resume. Registrations without PRF output (fallback
+// key wrapping) generate a random key and cannot be resumed. Tokens expire after 10 minutes and
+// name a single account.
+
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::config::{
+    REGISTRATION_RESUME_HKDF_INFO, REGISTRATION_RESUME_TTL_MS, REGISTRATION_RESUME_VERSION,
+};
+use crate::encoders::{base64_url_decode, base64_url_encode};
+#[cfg(feature = "relayer")]
+use crate::error::RegistrationResumeError;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrationResumeState {
+    pub version: u32,
+    pub near_account_id: String,
+    /// rawId of the registration credential
+    pub credential_raw_id: String,
+    /// Blob version of the key derivation (KeyWrappingScheme::Prf)
+    pub derivation_version: u32,
+    /// The derived key ("ed25519:..."), submitted by CompleteRegistration
+    pub public_key: String,
+    pub issued_at_ms: f64,
+    pub expires_at_ms: f64,
+}
+
+impl RegistrationResumeState {
+    pub fn new(
+        near_account_id: &str,
+        credential_raw_id: &str,
+        derivation_version: u32,
+        public_key: &str,
+        now_ms: f64,
+    ) -> Self {
+        RegistrationResumeState {
+            version: REGISTRATION_RESUME_VERSION,
+            near_account_id: near_account_id.to_string(),
+            credential_raw_id: credential_raw_id.to_string(),
+            derivation_version,
+            public_key: public_key.to_string(),
+            issued_at_ms: now_ms,
+            expires_at_ms: now_ms + REGISTRATION_RESUME_TTL_MS as f64,
+        }
+    }
+}
+
+/// What is handed to the caller: the state's exact CBOR bytes and the MAC over them
+#[derive(Serialize, Deserialize)]
+struct SignedResumeState {
+    #[serde(with = "serde_bytes")]
+    state: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    mac: Vec<u8>,
+}
+
+/// Resume token MAC key of the registration whose ChaCha20 PRF output is `prf_output` (base64url)
+pub fn resume_mac_key(prf_output: &str) -> Result<[u8; 32], String> {
+    let prf_bytes = base64_url_decode(prf_output)
+        .map_err(|e| format!("Invalid PRF output for the resume token: {}", e))?;
+    if prf_bytes.is_empty() {
+        return Err("Empty PRF output for the resume token".to_string());
+    }
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(None, &prf_bytes)
+        .expand(REGISTRATION_RESUME_HKDF_INFO.as_bytes(), &mut key)
+        .map_err(|_| "Resume token key derivation failed".to_string())?;
+    Ok(key)
+}
+
+fn state_mac(key: &[u8; 32], state_cbor: &[u8]) -> Hmac<Sha256> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts any key size");
+    mac.update(state_cbor);
+    mac
+}
+
+/// MACs the state under `key` and returns the base64url token
+pub fn issue_resume_token(
+    state: &RegistrationResumeState,
+    key: &[u8; 32],
+) -> Result<String, String> {
+    let mut state_cbor = Vec::new();
+    ciborium::into_writer(state, &mut state_cbor)
+        .map_err(|e| format!("Failed to encode resume state: {}", e))?;
+    let mac = state_mac(key, &state_cbor).finalize().into_bytes().to_vec();
+
+    let mut envelope = Vec::new();
+    ciborium::into_writer(
+        &SignedResumeState {
+            state: state_cbor,
+            mac,
+        },
+        &mut envelope,
+    )
+    .map_err(|e| format!("Failed to encode resume token envelope: {}", e))?;
+    Ok(base64_url_encode(&envelope))
+}
+
+#[cfg(feature = "relayer")]
+/// Checks the token's MAC under `key`, its expiry against `now_ms`, and that it names
+/// `near_account_id` and `credential_raw_id`. The caller checks the re-derived key against
+/// `public_key`.
+pub fn verify_resume_token(
+    token: &str,
+    key: &[u8; 32],
+    near_account_id: &str,
+    credential_raw_id: &str,
+    now_ms: f64,
+) -> Result<RegistrationResumeState, RegistrationResumeError> {
+    let envelope = base64_url_decode(token.trim()).map_err(RegistrationResumeError::Invalid)?;
+    let signed: SignedResumeState = ciborium::from_reader(envelope.as_slice())
+        .map_err(|e| RegistrationResumeError::Invalid(format!("Invalid envelope: {}", e)))?;
+    state_mac(key, &signed.state)
+        .verify_slice(&signed.mac)
+        .map_err(|_| {
+            RegistrationResumeError::Invalid(
+                "MAC does not verify (tampered, or issued for another passkey)".to_string(),
+            )
+        })?;
+
+    let state: RegistrationResumeState = ciborium::from_reader(signed.state.as_slice())
+        .map_err(|e| RegistrationResumeError::Invalid(format!("Invalid resume state: {}", e)))?;
+    if state.version != REGISTRATION_RESUME_VERSION {
+        return Err(RegistrationResumeError::Invalid(format!(
+            "Unsupported resume token version {}",
+            state.version
+        )));
+    }
+    // The embedded expiry is MAC'd, but the lifetime is capped here as well
+    let expires_at_ms = state
+        .expires_at_ms
+        .min(state.issued_at_ms + REGISTRATION_RESUME_TTL_MS as f64);
+    if expires_at_ms.is_nan() || expires_at_ms <= now_ms {
+        return Err(RegistrationResumeError::Expired { expires_at_ms });
+    }
+    if state.near_account_id != near_account_id {
+        return Err(RegistrationResumeError::Mismatch(format!(
+            "token was issued for {}, not {}",
+            state.near_account_id, near_account_id
+        )));
+    }
+    if state.credential_raw_id != credential_raw_id {
+        return Err(RegistrationResumeError::Mismatch(
+            "token was issued for another credential".to_string(),
+        ));
+    }
+    Ok(state)
+}
@@ -0,0 +1,354 @@
+// === RELAYER SUBMISSION ===
+// Sponsored onboarding: a relayer creates the account and registers the passkey on the user's
+// behalf. SubmitToRelayer POSTs the contract registration args, the new account and its public
+// key and, for delegate-action onboarding, the SignedDelegateAction to `WorkerPolicy.relayer`,
+// whose URL is pinned to `allowedRpcOrigins` like RPC overrides.
+//
+// Submission runs in two phases. The preflight (GET `{url}/healthz`) has no side effects and
+// follows the standard retry policy. The submission creates the account and is never repeated:
+// a relayer that created the account but whose response was lost would answer a retry with
+// "account exists".
+//
+// Relayer responses (schema version 1):
+//   { schemaVersion?: 1, success: bool, transactionHash?: string, errorKind?: string,
+//     error?: string, message?: string }
+// `errorKind` is one of "accountExists", "quotaExceeded", "rejected", "unavailable"; without it
+// the kind is inferred from the HTTP status and message.
+
+use std::future::Future;
+
+use serde::Serialize;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::config::{
+    RELAYER_CREATE_ACCOUNT_PATH, RELAYER_HEALTH_PATH, RELAYER_RESPONSE_SCHEMA_VERSION,
+    RETRY_BASE_DELAY_MS, RETRY_MAX_ATTEMPTS,
+};
+use crate::error::{RpcOriginError, SignerErrorCode};
+use crate::rpc_calls::HttpJsonResponse;
+use crate::rpc_endpoints::rpc_origin;
+use crate::types::handlers::{RelayerPolicy, WorkerPolicy};
+
+/// Outcome of a relayer submission
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayerResult {
+    pub success: bool,
+    #[wasm_bindgen(js_name = "accountCreated")]
+    pub account_created: bool,
+    #[wasm_bindgen(getter_with_clone, js_name = "txHash")]
+    pub tx_hash: Option<String>,
+    /// Relayer* error code of a failure (e.g. "RelayerAccountExists")
+    #[wasm_bindgen(getter_with_clone, js_name = "errorKind")]
+    pub error_kind: Option<String>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub error: Option<String>,
+}
+
+impl RelayerResult {
+    pub fn created(tx_hash: String) -> Self {
+        RelayerResult {
+            success: true,
+            account_created: true,
+            tx_hash: Some(tx_hash),
+            error_kind: None,
+            error: None,
+        }
+    }
+
+    pub fn failed(code: SignerErrorCode, message: impl AsRef<str>) -> Self {
+        RelayerResult {
+            success: false,
+            account_created: false,
+            tx_hash: None,
+            error_kind: Some(code.as_str().to_string()),
+            error: Some(format!("{}: {}", code.as_str(), message.as_ref())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelayerPhase {
+    /// Reachability probe before anything is submitted
+    Preflight,
+    /// Account creation
+    Submit,
+}
+
+impl RelayerPhase {
+    /// Whether repeating the phase has no further effect
+    pub fn is_idempotent(&self) -> bool {
+        matches!(self, RelayerPhase::Preflight)
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        if self.is_idempotent() {
+            RETRY_MAX_ATTEMPTS
+        } else {
+            1
+        }
+    }
+}
+
+/// Delay before the retry following `failed_attempts` failures
+pub fn retry_delay_ms(failed_attempts: u32) -> u32 {
+    let doublings = failed_attempts.saturating_sub(1).min(16);
+    RETRY_BASE_DELAY_MS.saturating_mul(1 << doublings)
+}
+
+/// No response, a request timeout, or a server error
+pub fn is_retryable(outcome: &Result<HttpJsonResponse, String>) -> bool {
+    match outcome {
+        Err(_) => true,
+        Ok(response) => response.status == 408 || response.status >= 500,
+    }
+}
+
+/// Runs `attempt` up to the phase's attempt budget, sleeping through `sleep` between
+/// retryable outcomes. Returns the last outcome and the number of attempts made.
+pub async fn run_phase<A, AF, S, SF>(
+    phase: RelayerPhase,
+    mut attempt: A,
+    mut sleep: S,
+) -> (Result<HttpJsonResponse, String>, u32)
+where
+    A: FnMut() -> AF,
+    AF: Future<Output = Result<HttpJsonResponse, String>>,
+    S: FnMut(u32) -> SF,
+    SF: Future<Output = ()>,
+{
+    let max_attempts = phase.max_attempts();
+    let mut attempts = 0;
+    loop {
+        let outcome = attempt().await;
+        attempts += 1;
+        if attempts >= max_attempts || !is_retryable(&outcome) {
+            return (outcome, attempts);
+        }
+        sleep(retry_delay_ms(attempts)).await;
+    }
+}
+
+/// The policy's relayer, once its URL is on an allowed origin
+pub fn pinned_relayer(policy: Option<&WorkerPolicy>) -> Result<RelayerPolicy, String> {
+    let relayer = policy
+        .and_then(|p| p.relayer.clone())
+        .ok_or_else(|| "SubmitToRelayer requires workerPolicy.relayer".to_string())?;
+    let allowed = policy.is_some_and(|p| {
+        let origin = rpc_origin(&relayer.url);
+        origin.is_some()
+            && p.allowed_rpc_origins
+                .iter()
+                .any(|allowed| rpc_origin(allowed) == origin)
+    });
+    if !allowed {
+        return Err(RpcOriginError::NotAllowed {
+            endpoint: "relayer",
+            url: relayer.url,
+        }
+        .to_string());
+    }
+    Ok(relayer)
+}
+
+fn relayer_url(relayer: &RelayerPolicy, path: &str) -> String {
+    format!("{}{}", relayer.url.trim_end_matches('/'), path)
+}
+
+/// Request body: the contract registration args (`registration_args_json`) with the new
+/// account, its public key and the optional SignedDelegateAction (base64 borsh) added
+pub fn build_relayer_request_body(
+    registration_args_json: &str,
+    new_account_id: &str,
+    new_public_key: &str,
+    signed_delegate_action: Option<&str>,
+) -> Result<Value, String> {
+    let mut body: Value = serde_json::from_str(registration_args_json)
+        .map_err(|e| format!("Invalid registration args: {}", e))?;
+    let fields = body
+        .as_object_mut()
+        .ok_or_else(|| "Registration args must be a JSON object".to_string())?;
+    fields.insert("new_account_id".to_string(), new_account_id.into());
+    fields.insert("new_public_key".to_string(), new_public_key.into());
+    if let Some(signed_delegate_action) = signed_delegate_action {
+        fields.insert(
+            "signed_delegate_action".to_string(),
+            signed_delegate_action.into(),
+        );
+    }
+    Ok(body)
+}
+
+fn declared_error_kind(kind: &str) -> Option<SignerErrorCode> {
+    match kind {
+        "accountExists" => Some(SignerErrorCode::RelayerAccountExists),
+        "quotaExceeded" => Some(SignerErrorCode::RelayerQuotaExceeded),
+        "rejected" => Some(SignerErrorCode::RelayerRejected),
+        "unavailable" => Some(SignerErrorCode::RelayerUnavailable),
+        _ => None,
+    }
+}
+
+fn inferred_error_kind(status: u16, message: &str) -> SignerErrorCode {
+    let message = message.to_ascii_lowercase();
+    if status == 409 || message.contains("already exists") {
+        SignerErrorCode::RelayerAccountExists
+    } else if status == 429 || message.contains("quota") || message.contains("rate limit") {
+        SignerErrorCode::RelayerQuotaExceeded
+    } else if status >= 500 {
+        SignerErrorCode::RelayerUnavailable
+    } else {
+        SignerErrorCode::RelayerRejected
+    }
+}
+
+fn is_transaction_hash(hash: &str) -> bool {
+    bs58::decode(hash)
+        .into_vec()
+        .is_ok_and(|bytes| bytes.len() == 32)
+}
+
+/// Relayer response as a RelayerResult, checked against the expected schema version
+pub fn parse_relayer_response(
+    response: &HttpJsonResponse,
+    expected_schema_version: u32,
+) -> RelayerResult {
+    let invalid =
+        |message: String| RelayerResult::failed(SignerErrorCode::RelayerResponseInvalid, message);
+    let body = match &response.body {
+        Some(Value::Object(body)) => body,
+        _ if (200..300).contains(&response.status) => {
+            return invalid(format!(
+                "HTTP {} response is not a JSON object",
+                response.status
+            ))
+        }
+        _ => {
+            let code = inferred_error_kind(response.status, "");
+            return RelayerResult::failed(code, format!("HTTP {}", response.status));
+        }
+    };
+
+    match body.get("schemaVersion") {
+        None if expected_schema_version == 1 => {}
+        None => {
+            return invalid(format!(
+                "response does not declare schemaVersion {}",
+                expected_schema_version
+            ))
+        }
+        Some(version) if version.as_u64() == Some(expected_schema_version as u64) => {}
+        Some(version) => {
+            return invalid(format!(
+                "schemaVersion {} is not the expected {}",
+                version, expected_schema_version
+            ))
+        }
+    }
+
+    let success = body.get("success").and_then(Value::as_bool);
+    if success == Some(true) && (200..300).contains(&response.status) {
+        let tx_hash = body
+            .get("transactionHash")
+            .or_else(|| body.get("txHash"))
+            .and_then(Value::as_str);
+        return match tx_hash {
+            Some(hash) if is_transaction_hash(hash) => RelayerResult::created(hash.to_string()),
+            Some(hash) => invalid(format!("transactionHash {} is not a NEAR hash", hash)),
+            None => invalid("successful response has no transactionHash".to_string()),
+        };
+    }
+    if success.is_none() && (200..300).contains(&response.status) {
+        return invalid("response has no boolean success field".to_string());
+    }
+
+    let message = ["error", "message"]
+        .iter()
+        .find_map(|field| body.get(*field).and_then(Value::as_str))
+        .unwrap_or_default();
+    let code = body
+        .get("errorKind")
+        .and_then(Value::as_str)
+        .and_then(declared_error_kind)
+        .unwrap_or_else(|| inferred_error_kind(response.status, message));
+    let message = if message.is_empty() {
+        format!("HTTP {}", response.status)
+    } else {
+        message.to_string()
+    };
+    RelayerResult::failed(code, message)
+}
+
+/// Preflight then submission of `body` through `http(method, url, body, headers)`.
+/// `auth_token` is sent in the relayer's `authHeaderName` header, if it names one.
+pub async fn submit_to_relayer<H, HF, S, SF>(
+    relayer: &RelayerPolicy,
+    auth_token: Option<&str>,
+    body: Value,
+    mut http: H,
+    mut sleep: S,
+) -> RelayerResult
+where
+    H: FnMut(&'static str, String, Option<Value>, Vec<(String, String)>) -> HF,
+    HF: Future<Output = Result<HttpJsonResponse, String>>,
+    S: FnMut(u32) -> SF,
+    SF: Future<Output = ()>,
+{
+    let health_url = relayer_url(relayer, RELAYER_HEALTH_PATH);
+    let (preflight, attempts) = run_phase(
+        RelayerPhase::Preflight,
+        || http("GET", health_url.clone(), None, Vec::new()),
+        &mut sleep,
+    )
+    .await;
+    // Any answer short of a server error shows the relayer is reachable
+    if is_retryable(&preflight) {
+        let reason = match preflight {
+            Ok(response) => format!("HTTP {}", response.status),
+            Err(e) => e,
+        };
+        return RelayerResult::failed(
+            SignerErrorCode::RelayerUnavailable,
+            format!(
+                "relayer unreachable after {} attempts ({}); nothing was submitted",
+                attempts, reason
+            ),
+        );
+    }
+
+    let headers = match (&relayer.auth_header_name, auth_token) {
+        (Some(name), Some(token)) => vec![(name.clone(), token.to_string())],
+        _ => Vec::new(),
+    };
+    let submit_url = relayer_url(relayer, RELAYER_CREATE_ACCOUNT_PATH);
+    let (submission, _) = run_phase(
+        RelayerPhase::Submit,
+        || {
+            http(
+                "POST",
+                submit_url.clone(),
+                Some(body.clone()),
+                headers.clone(),
+            )
+        },
+        &mut sleep,
+    )
+    .await;
+    match submission {
+        Ok(response) => parse_relayer_response(
+            &response,
+            relayer
+                .response_schema_version
+                .unwrap_or(RELAYER_RESPONSE_SCHEMA_VERSION),
+        ),
+        Err(e) => RelayerResult::failed(
+            SignerErrorCode::RelayerUnavailable,
+            format!(
+                "no response to the submission ({}); the account may have been created",
+                e
+            ),
+        ),
+    }
+}
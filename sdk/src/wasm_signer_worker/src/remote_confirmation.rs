@@ -0,0 +1,382 @@
+// === REMOTE CONFIRMATION ===
+// Confirm on another device: a desktop without a usable authenticator has a linked phone
+// approve the transactions. CreateRemoteConfirmation computes the confirmation content and
+// digest as the signing flow would and returns a compact request for the TS layer to carry
+// over (QR code or relayer). ApproveRemoteConfirmation, on the phone, shows it through the
+// normal confirmation UI, collects the passkey assertion over the request's VRF challenge and
+// its PRF output, and seals both into an approval. CompleteRemoteConfirmation, back on the
+// originating device, opens the approval and signs through the SignTransactionsWithActions
+// flow as if the user had confirmed locally.
+//
+// The request is CBOR signed with an ed25519 key generated per request; its public key is the
+// session key, held with the request's digest and VRF challenge in worker state (state.rs).
+// Sessions and used approvals travel in the sealed worker state record (worker_state.rs), so
+// the worker completing a request need not be the one that created it.
+// The phone checks the signature, the expiry, and that the transactions hash to the digest it
+// names, so what is shown is what gets approved. The approval is encrypted to the session key:
+// X25519 between a fresh key on the phone and the Montgomery form of the session key, HKDF-SHA256,
+// then ChaCha20-Poly1305 with the request ID and both public keys as associated data. Only a
+// worker holding the session can open it, and it signs only the batch the session names. Each
+// request is approved at most once per device and each approval used once.
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Nonce,
+};
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::config::{
+    MAX_REMOTE_CONFIRMATION_TTL_MS, REMOTE_CONFIRMATION_HKDF_INFO,
+    REMOTE_CONFIRMATION_SIGNATURE_DOMAIN, REMOTE_CONFIRMATION_VERSION,
+};
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::RemoteConfirmationError;
+use crate::handlers::confirm_tx_details::digest_tx_signing_requests_json;
+use crate::types::VrfChallenge;
+
+/// What the approving device is asked to confirm
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteConfirmationRequest {
+    pub version: u32,
+    pub request_id: String,
+    pub near_account_id: String,
+    /// Digest of `tx_signing_requests` (compute_confirmation_intent_digest)
+    pub intent_digest: String,
+    /// The confirmation's txSigningRequests: receivers, actions, firstTimeReceiver, summaryBlocks
+    pub tx_signing_requests: Vec<serde_json::Value>,
+    /// Challenge the approving credential signs
+    pub vrf_challenge: VrfChallenge,
+    pub issued_at_ms: f64,
+    pub expires_at_ms: f64,
+    /// ed25519 session key: verifies this request, and approvals are sealed to its X25519 form
+    #[serde(with = "serde_bytes")]
+    pub session_public_key: Vec<u8>,
+}
+
+/// What is handed to the caller: the request's exact CBOR bytes and the session key's
+/// signature over them
+#[derive(Serialize, Deserialize)]
+struct SignedRemoteRequest {
+    #[serde(with = "serde_bytes")]
+    request: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    signature: Vec<u8>,
+}
+
+/// The originating device's side of a request, kept until it expires
+#[derive(Debug, Clone)]
+pub struct RemoteSession {
+    pub session_key: SigningKey,
+    pub near_account_id: String,
+    pub intent_digest: String,
+    /// The flags the digest was computed with, reused when signing
+    pub first_time_receivers: Vec<Option<bool>>,
+    pub vrf_challenge: VrfChallenge,
+    pub expires_at_ms: f64,
+}
+
+/// The approving device's answer, encrypted inside a SealedApproval
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteApproval {
+    pub version: u32,
+    pub request_id: String,
+    pub intent_digest: String,
+    /// Serialized WebAuthn authentication credential over the request's VRF challenge
+    pub credential: serde_json::Value,
+    /// base64url ChaCha20 PRF output of that credential
+    pub prf_output: String,
+}
+
+/// An approval as carried back to the originating device: the request and keys it is bound to
+/// in the clear, the RemoteApproval encrypted
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SealedApproval {
+    pub version: u32,
+    pub request_id: String,
+    #[serde(with = "serde_bytes")]
+    pub session_public_key: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    pub ephemeral_public_key: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    nonce: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    ciphertext: Vec<u8>,
+}
+
+/// A fresh session key for one request
+pub fn generate_session_key() -> Result<SigningKey, String> {
+    let mut seed = [0u8; 32];
+    getrandom::getrandom(&mut seed)
+        .map_err(|e| format!("Failed to generate remote confirmation session key: {}", e))?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+fn signed_message(request_cbor: &[u8]) -> Vec<u8> {
+    [REMOTE_CONFIRMATION_SIGNATURE_DOMAIN, request_cbor].concat()
+}
+
+/// Signs the request with `session_key` and returns the base64url blob
+pub fn issue_remote_request(
+    request: &RemoteConfirmationRequest,
+    session_key: &SigningKey,
+) -> Result<String, String> {
+    let mut request_cbor = Vec::new();
+    ciborium::into_writer(request, &mut request_cbor)
+        .map_err(|e| format!("Failed to encode remote confirmation request: {}", e))?;
+    let signature = session_key.sign(&signed_message(&request_cbor));
+
+    let mut envelope = Vec::new();
+    ciborium::into_writer(
+        &SignedRemoteRequest {
+            request: request_cbor,
+            signature: signature.to_bytes().to_vec(),
+        },
+        &mut envelope,
+    )
+    .map_err(|e| format!("Failed to encode remote confirmation envelope: {}", e))?;
+    Ok(base64_url_encode(&envelope))
+}
+
+fn session_verifying_key(bytes: &[u8]) -> Result<VerifyingKey, RemoteConfirmationError> {
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_| {
+        RemoteConfirmationError::Invalid("session key must be 32 bytes".to_string())
+    })?;
+    VerifyingKey::from_bytes(&bytes)
+        .map_err(|e| RemoteConfirmationError::Invalid(format!("Invalid session key: {}", e)))
+}
+
+/// Checks the blob's signature under the session key it carries, its version, its expiry
+/// against `now_ms` (capped at MAX_REMOTE_CONFIRMATION_TTL_MS after issue), and that its
+/// transactions hash to its digest and its challenge names its account. Replay is checked by
+/// the caller (state::note_remote_approval).
+pub fn open_remote_request(
+    blob: &str,
+    now_ms: f64,
+) -> Result<RemoteConfirmationRequest, RemoteConfirmationError> {
+    let envelope = base64_url_decode(blob.trim()).map_err(RemoteConfirmationError::Invalid)?;
+    let signed: SignedRemoteRequest = ciborium::from_reader(envelope.as_slice())
+        .map_err(|e| RemoteConfirmationError::Invalid(format!("Invalid envelope: {}", e)))?;
+    let request: RemoteConfirmationRequest = ciborium::from_reader(signed.request.as_slice())
+        .map_err(|e| RemoteConfirmationError::Invalid(format!("Invalid request: {}", e)))?;
+    if request.version != REMOTE_CONFIRMATION_VERSION {
+        return Err(RemoteConfirmationError::Invalid(format!(
+            "Unsupported remote confirmation version {}",
+            request.version
+        )));
+    }
+    let signature = Signature::from_slice(&signed.signature)
+        .map_err(|e| RemoteConfirmationError::Invalid(format!("Invalid signature: {}", e)))?;
+    session_verifying_key(&request.session_public_key)?
+        .verify_strict(&signed_message(&signed.request), &signature)
+        .map_err(|_| {
+            RemoteConfirmationError::Invalid(
+                "signature does not verify under the session key".to_string(),
+            )
+        })?;
+
+    let expires_at_ms = request
+        .expires_at_ms
+        .min(request.issued_at_ms + MAX_REMOTE_CONFIRMATION_TTL_MS as f64);
+    if expires_at_ms.is_nan() || expires_at_ms <= now_ms {
+        return Err(RemoteConfirmationError::Expired { expires_at_ms });
+    }
+    let digest = digest_tx_signing_requests_json(request.tx_signing_requests.clone())
+        .map_err(RemoteConfirmationError::Invalid)?;
+    if digest != request.intent_digest {
+        return Err(RemoteConfirmationError::Invalid(
+            "transactions do not hash to the request's intent digest".to_string(),
+        ));
+    }
+    if request.vrf_challenge.user_id != request.near_account_id {
+        return Err(RemoteConfirmationError::Invalid(format!(
+            "VRF challenge was issued for {}, not {}",
+            request.vrf_challenge.user_id, request.near_account_id
+        )));
+    }
+    Ok(request)
+}
+
+/// X25519 shared secret to ChaCha20-Poly1305 key; both public keys go into the HKDF info
+fn approval_cipher(
+    shared_secret: &MontgomeryPoint,
+    session_public_key: &[u8],
+    ephemeral_public_key: &[u8],
+) -> Result<ChaCha20Poly1305, RemoteConfirmationError> {
+    // A low-order point yields the identity: refuse rather than encrypt under a known key
+    if shared_secret.as_bytes() == &[0u8; 32] {
+        return Err(RemoteConfirmationError::Invalid(
+            "key agreement produced a low-order point".to_string(),
+        ));
+    }
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(None, shared_secret.as_bytes())
+        .expand_multi_info(
+            &[
+                REMOTE_CONFIRMATION_HKDF_INFO.as_bytes(),
+                session_public_key,
+                ephemeral_public_key,
+            ],
+            &mut key,
+        )
+        .map_err(|_| {
+            RemoteConfirmationError::Invalid("Approval key derivation failed".to_string())
+        })?;
+    ChaCha20Poly1305::new_from_slice(&key)
+        .map_err(|e| RemoteConfirmationError::Invalid(format!("Invalid approval key: {}", e)))
+}
+
+fn approval_aad(
+    request_id: &str,
+    session_public_key: &[u8],
+    ephemeral_public_key: &[u8],
+) -> Vec<u8> {
+    [
+        session_public_key,
+        ephemeral_public_key,
+        request_id.as_bytes(),
+    ]
+    .concat()
+}
+
+/// Encrypts the approval to the request's session key and returns the base64url token
+pub fn seal_approval(
+    approval: &RemoteApproval,
+    session_public_key: &[u8],
+) -> Result<String, RemoteConfirmationError> {
+    let session_montgomery = session_verifying_key(session_public_key)?.to_montgomery();
+    let mut ephemeral_secret = [0u8; 32];
+    let mut nonce = [0u8; 12];
+    getrandom::getrandom(&mut ephemeral_secret)
+        .and_then(|_| getrandom::getrandom(&mut nonce))
+        .map_err(|e| {
+            RemoteConfirmationError::Invalid(format!("Failed to generate approval keys: {}", e))
+        })?;
+    let ephemeral_public_key = MontgomeryPoint::mul_base_clamped(ephemeral_secret).to_bytes();
+    let shared_secret = session_montgomery.mul_clamped(ephemeral_secret);
+
+    let mut plaintext = Vec::new();
+    ciborium::into_writer(approval, &mut plaintext).map_err(|e| {
+        RemoteConfirmationError::Invalid(format!("Failed to encode approval: {}", e))
+    })?;
+    let cipher = approval_cipher(&shared_secret, session_public_key, &ephemeral_public_key)?;
+    let ciphertext = cipher
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &plaintext,
+                aad: &approval_aad(
+                    &approval.request_id,
+                    session_public_key,
+                    &ephemeral_public_key,
+                ),
+            },
+        )
+        .map_err(|e| {
+            RemoteConfirmationError::Invalid(format!("Failed to encrypt approval: {}", e))
+        })?;
+
+    let mut envelope = Vec::new();
+    ciborium::into_writer(
+        &SealedApproval {
+            version: REMOTE_CONFIRMATION_VERSION,
+            request_id: approval.request_id.clone(),
+            session_public_key: session_public_key.to_vec(),
+            ephemeral_public_key: ephemeral_public_key.to_vec(),
+            nonce: nonce.to_vec(),
+            ciphertext,
+        },
+        &mut envelope,
+    )
+    .map_err(|e| {
+        RemoteConfirmationError::Invalid(format!("Failed to encode approval envelope: {}", e))
+    })?;
+    Ok(base64_url_encode(&envelope))
+}
+
+/// Reads an approval's envelope, to find the session it answers
+pub fn parse_sealed_approval(token: &str) -> Result<SealedApproval, RemoteConfirmationError> {
+    let envelope = base64_url_decode(token.trim()).map_err(RemoteConfirmationError::Invalid)?;
+    let sealed: SealedApproval = ciborium::from_reader(envelope.as_slice()).map_err(|e| {
+        RemoteConfirmationError::Invalid(format!("Invalid approval envelope: {}", e))
+    })?;
+    if sealed.version != REMOTE_CONFIRMATION_VERSION {
+        return Err(RemoteConfirmationError::Invalid(format!(
+            "Unsupported remote confirmation version {}",
+            sealed.version
+        )));
+    }
+    Ok(sealed)
+}
+
+/// Decrypts the approval with `session`, the one stored under its request ID. Checks the
+/// session's expiry against `now_ms`, that the approval was sealed to the session key, and that
+/// it approves the session's digest. Replay is checked by the caller
+/// (state::consume_remote_session).
+pub fn open_approval(
+    sealed: &SealedApproval,
+    session: &RemoteSession,
+    now_ms: f64,
+) -> Result<RemoteApproval, RemoteConfirmationError> {
+    if session.expires_at_ms.is_nan() || session.expires_at_ms <= now_ms {
+        return Err(RemoteConfirmationError::Expired {
+            expires_at_ms: session.expires_at_ms,
+        });
+    }
+    let session_public_key = session.session_key.verifying_key().to_bytes();
+    if sealed.session_public_key != session_public_key {
+        return Err(RemoteConfirmationError::WrongSession(
+            "approval was sealed to another session key".to_string(),
+        ));
+    }
+    let ephemeral_public_key: [u8; 32] = sealed
+        .ephemeral_public_key
+        .as_slice()
+        .try_into()
+        .map_err(|_| {
+            RemoteConfirmationError::Invalid("ephemeral key must be 32 bytes".to_string())
+        })?;
+    if sealed.nonce.len() != 12 {
+        return Err(RemoteConfirmationError::Invalid(
+            "approval nonce must be 12 bytes".to_string(),
+        ));
+    }
+    let shared_secret =
+        MontgomeryPoint(ephemeral_public_key).mul_clamped(session.session_key.to_scalar_bytes());
+    let cipher = approval_cipher(&shared_secret, &session_public_key, &ephemeral_public_key)?;
+    let plaintext = cipher
+        .decrypt(
+            Nonce::from_slice(&sealed.nonce),
+            Payload {
+                msg: &sealed.ciphertext,
+                aad: &approval_aad(
+                    &sealed.request_id,
+                    &session_public_key,
+                    &ephemeral_public_key,
+                ),
+            },
+        )
+        .map_err(|_| {
+            RemoteConfirmationError::Invalid("approval does not decrypt (tampered)".to_string())
+        })?;
+
+    let approval: RemoteApproval = ciborium::from_reader(plaintext.as_slice())
+        .map_err(|e| RemoteConfirmationError::Invalid(format!("Invalid approval: {}", e)))?;
+    if approval.version != REMOTE_CONFIRMATION_VERSION || approval.request_id != sealed.request_id {
+        return Err(RemoteConfirmationError::Invalid(
+            "approval does not match its envelope".to_string(),
+        ));
+    }
+    if approval.intent_digest != session.intent_digest {
+        return Err(RemoteConfirmationError::WrongSession(
+            "approval is for other transactions than the session's".to_string(),
+        ));
+    }
+    Ok(approval)
+}
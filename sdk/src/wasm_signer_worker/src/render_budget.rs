@@ -0,0 +1,208 @@
+// === RENDERING BUDGETS ===
+// A dapp can stuff megabytes of args into a FunctionCall, or thousands of actions into a
+// batch, to make the confirmation unreadable while hiding a harmful action at the end. The
+// worker bounds what confirmations show:
+//   - Argument previews: each top-level value of a FunctionCall's JSON args (or the whole args
+//     when they are not a JSON object, or have a key that long) whose compact JSON is longer
+//     than maxArgPreviewBytes is shown as
+//
+//       { "truncated": true, "preview": "<first bytes>", "bytes": 5242880, "sha256": "<hex>" }
+//
+//     Args that are not JSON keep their first bytes, followed by `«truncated: N bytes,
+//     sha256: 1a2b3c4d…»`. Each transaction of the confirmation payload lists its
+//     `truncations` (action index, path, size, full hash), and the intent digest covers them,
+//     so it still commits to the values signed.
+//   - Block count: past maxSummaryBlocks, the remaining actions of each transaction are
+//     rolled up into one `rollup` block ("N more actions") that still names the receiver and
+//     lists every amount those actions move (see confirmation_blocks.rs).
+// Only args previews and action blocks are budgeted: receivers, deposits and the warnings
+// for risky actions are always shown in full.
+
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::actions::ActionParams;
+use crate::config::{DEFAULT_MAX_ARG_PREVIEW_BYTES, DEFAULT_MAX_SUMMARY_BLOCKS};
+use crate::redaction::value_sha256;
+use crate::types::handlers::WorkerPolicy;
+
+/// Limits on what a confirmation shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderBudget {
+    pub max_arg_preview_bytes: usize,
+    pub max_summary_blocks: usize,
+}
+
+impl Default for RenderBudget {
+    fn default() -> Self {
+        RenderBudget {
+            max_arg_preview_bytes: DEFAULT_MAX_ARG_PREVIEW_BYTES,
+            max_summary_blocks: DEFAULT_MAX_SUMMARY_BLOCKS,
+        }
+    }
+}
+
+impl RenderBudget {
+    /// From `workerPolicy.maxArgPreviewBytes` and `workerPolicy.maxSummaryBlocks`; zero is
+    /// refused
+    pub fn from_worker_policy(policy: Option<&WorkerPolicy>) -> Result<Self, String> {
+        let limit = |value: Option<u32>, default: usize, field: &str| match value {
+            None => Ok(default),
+            Some(0) => Err(format!("Invalid workerPolicy.{}: must be positive", field)),
+            Some(value) => Ok(value as usize),
+        };
+        Ok(RenderBudget {
+            max_arg_preview_bytes: limit(
+                policy.and_then(|p| p.max_arg_preview_bytes),
+                DEFAULT_MAX_ARG_PREVIEW_BYTES,
+                "maxArgPreviewBytes",
+            )?,
+            max_summary_blocks: limit(
+                policy.and_then(|p| p.max_summary_blocks),
+                DEFAULT_MAX_SUMMARY_BLOCKS,
+                "maxSummaryBlocks",
+            )?,
+        })
+    }
+}
+
+/// One args value shortened in a transaction's confirmation
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArgTruncation {
+    pub action_index: usize,
+    /// "args" or "args.<key>"
+    pub path: String,
+    /// Length of the full value's compact JSON (of the args string for non-JSON args)
+    pub bytes: usize,
+    /// Hex SHA-256 of the same bytes
+    pub sha256: String,
+}
+
+/// The longest prefix of `text` within `max_bytes` that ends on a char boundary
+fn preview(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// `value` as shown: itself within the budget, otherwise its truncation marker
+fn budgeted_value(
+    value: &Value,
+    path: String,
+    action_index: usize,
+    max_bytes: usize,
+    truncations: &mut Vec<ArgTruncation>,
+) -> Option<Value> {
+    let json = value.to_string();
+    if json.len() <= max_bytes {
+        return None;
+    }
+    let sha256 = value_sha256(value);
+    truncations.push(ArgTruncation {
+        action_index,
+        path,
+        bytes: json.len(),
+        sha256: sha256.clone(),
+    });
+    Some(serde_json::json!({
+        "truncated": true,
+        "preview": preview(&json, max_bytes),
+        "bytes": json.len(),
+        "sha256": sha256,
+    }))
+}
+
+/// Args as shown for one FunctionCall, or None when they fit the budget
+fn budgeted_args(
+    args: &str,
+    action_index: usize,
+    max_bytes: usize,
+    truncations: &mut Vec<ArgTruncation>,
+) -> Option<String> {
+    match serde_json::from_str::<Value>(args) {
+        // Keys are shown whole, so args with an oversized key are truncated as a whole
+        Ok(Value::Object(mut map)) if map.keys().all(|key| key.len() <= max_bytes) => {
+            let before = truncations.len();
+            for (key, value) in map.iter_mut() {
+                let path = format!("args.{}", key);
+                if let Some(shown) =
+                    budgeted_value(value, path, action_index, max_bytes, truncations)
+                {
+                    *value = shown;
+                }
+            }
+            (truncations.len() > before).then(|| Value::Object(map).to_string())
+        }
+        Ok(value) => budgeted_value(
+            &value,
+            "args".to_string(),
+            action_index,
+            max_bytes,
+            truncations,
+        )
+        .map(|shown| shown.to_string()),
+        // Kept as text (not JSON), so an ArgsNotValidated warning still applies
+        Err(_) if args.len() > max_bytes => {
+            let sha256: String = Sha256::digest(args.as_bytes())
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+            truncations.push(ArgTruncation {
+                action_index,
+                path: "args".to_string(),
+                bytes: args.len(),
+                sha256: sha256.clone(),
+            });
+            Some(format!(
+                "{}«truncated: {} bytes, sha256: {}…»",
+                preview(args, max_bytes),
+                args.len(),
+                &sha256[..8]
+            ))
+        }
+        Err(_) => None,
+    }
+}
+
+/// The actions as shown: FunctionCall args values over `max_arg_preview_bytes` replaced
+/// with their truncation markers
+pub fn truncate_actions(
+    actions: &[ActionParams],
+    budget: &RenderBudget,
+) -> (Vec<ActionParams>, Vec<ArgTruncation>) {
+    let mut truncations = Vec::new();
+    let shown = actions
+        .iter()
+        .enumerate()
+        .map(|(action_index, action)| match action {
+            ActionParams::FunctionCall {
+                method_name,
+                args,
+                gas,
+                deposit,
+            } => match budgeted_args(
+                args,
+                action_index,
+                budget.max_arg_preview_bytes,
+                &mut truncations,
+            ) {
+                Some(args) => ActionParams::FunctionCall {
+                    method_name: method_name.clone(),
+                    args,
+                    gas: gas.clone(),
+                    deposit: deposit.clone(),
+                },
+                None => action.clone(),
+            },
+            _ => action.clone(),
+        })
+        .collect();
+    (shown, truncations)
+}
@@ -1,11 +1,14 @@
 use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::JsFuture;
 use web_sys::{Headers, Request, RequestInit, RequestMode, Response};
 
-use crate::encoders::{base64_standard_encode, base64_url_decode};
+use crate::contract_args::ContractInterfaceVersion;
+use crate::encoders::base64_url_decode;
+use crate::error::RpcErrorKind;
+use crate::rpc_client::{call_function_params, RpcClient};
 use crate::types::VrfChallenge;
 use crate::types::{
     WebAuthnAuthenticationCredential, WebAuthnAuthenticationResponse,
@@ -16,6 +19,8 @@ pub const VERIFY_AUTHENTICATION_RESPONSE_METHOD: &str = "verify_authentication_r
 pub const CHECK_CAN_REGISTER_USER_METHOD: &str = "check_can_register_user";
 pub const VERIFY_AND_REGISTER_USER_METHOD: &str = "verify_and_register_user";
 pub const LINK_DEVICE_REGISTER_USER_METHOD: &str = "link_device_register_user";
+pub const CONTRACT_INTERFACE_VERSION_METHOD: &str = "get_contract_interface_version";
+pub const GET_AUTHENTICATORS_BY_USER_METHOD: &str = "get_authenticators_by_user";
 
 /// Contract verification result
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -74,6 +79,21 @@ pub struct RegistrationInfo {
     pub credential_public_key: Vec<u8>,
 }
 
+/// One entry of `get_authenticators_by_user` (the fields the worker uses)
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct RegisteredAuthenticator {
+    /// Filled from the entry's key, not the stored value
+    #[serde(skip)]
+    pub credential_id: String,
+    pub device_number: u32,
+    /// VRF public keys bound to this authenticator (base64url)
+    #[serde(default)]
+    pub vrf_public_keys: Vec<String>,
+    /// Transports the authenticator reported at registration (e.g. "internal", "hybrid")
+    #[serde(default)]
+    pub transports: Option<Vec<String>>,
+}
+
 /// VRF challenge data for contract verification
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct VrfData {
@@ -91,6 +111,12 @@ impl TryFrom<&VrfChallenge> for VrfData {
     type Error = wasm_bindgen::JsValue;
 
     fn try_from(vrf_challenge: &VrfChallenge) -> Result<Self, Self::Error> {
+        // The contract only verifies inputs bound to one of its recent blocks
+        if !vrf_challenge.is_near_anchored() {
+            return Err(wasm_bindgen::JsValue::from_str(
+                "Generic-anchored VRF challenges cannot be verified by the NEAR contract",
+            ));
+        }
         Ok(VrfData {
             vrf_input_data: base64_url_decode(&vrf_challenge.vrf_input).map_err(|e| {
                 wasm_bindgen::JsValue::from_str(&format!("Failed to decode VRF input: {}", e))
@@ -117,9 +143,9 @@ impl TryFrom<&VrfChallenge> for VrfData {
 }
 
 /// Perform contract verification via NEAR RPC directly from WASM
-pub async fn verify_authentication_response_rpc_call(
+pub async fn verify_authentication_response_rpc_call<R: RpcClient>(
+    rpc: &R,
     contract_id: &str,
-    rpc_url: &str,
     vrf_data: VrfData,
     webauthn_authentication_credential: WebAuthnAuthenticationCredential,
 ) -> Result<ContractVerificationResult, String> {
@@ -133,26 +159,16 @@ pub async fn verify_authentication_response_rpc_call(
         "webauthn_authentication": webauthn_authentication_credential
     });
 
-    // Build RPC request body
-    let rpc_body = serde_json::json!({
-        "jsonrpc": "2.0",
-        "id": "verify_from_wasm",
-        "method": "query",
-        "params": {
-            "request_type": "call_function",
-            "account_id": contract_id,
-            "method_name": VERIFY_AUTHENTICATION_RESPONSE_METHOD,
-            "args_base64": base64_standard_encode(contract_args.to_string().as_bytes()),
-            // Use 'final' to align with block hash/height fetched for VRF challenge
-            // which the SDK queries using finality: 'final'. This prevents
-            // large height deltas that can trigger StaleChallenge.
-            "finality": "final"
-        }
-    });
-
-    info!("RUST: Making RPC call to: {}", rpc_url);
-    // Execute the request using shared helper
-    let result = execute_rpc_request(rpc_url, &rpc_body).await?;
+    // Use 'final' to align with block hash/height fetched for VRF challenge
+    // which the SDK queries using finality: 'final'. This prevents
+    // large height deltas that can trigger StaleChallenge.
+    let params = call_function_params(
+        contract_id,
+        VERIFY_AUTHENTICATION_RESPONSE_METHOD,
+        &contract_args,
+        "final",
+    );
+    let result = query_envelope(rpc, params).await?;
 
     // Parse RPC response
     if let Some(error) = result.get("error") {
@@ -243,11 +259,11 @@ pub async fn verify_authentication_response_rpc_call(
 
 /// Check if user can register (VIEW FUNCTION - uses query RPC)
 /// This function validates VRF + WebAuthn but does NOT store any data
-pub async fn check_can_register_user_rpc_call(
+pub async fn check_can_register_user_rpc_call<R: RpcClient>(
+    rpc: &R,
     contract_id: &str,
     vrf_data: VrfData,
     webauthn_registration_credential: WebAuthnRegistrationCredential,
-    rpc_url: &str,
     authenticator_options: Option<crate::types::handlers::AuthenticatorOptions>,
 ) -> Result<ContractRegistrationResult, String> {
     info!("RUST: Checking if user can register (view function)");
@@ -257,6 +273,7 @@ pub async fn check_can_register_user_rpc_call(
         "vrf_data": vrf_data,
         "webauthn_registration": webauthn_registration_credential,
         "authenticator_options": authenticator_options
+            .map(crate::types::handlers::AuthenticatorOptions::for_contract)
     });
 
     // Add logging to see what's being sent to the contract
@@ -266,33 +283,210 @@ pub async fn check_can_register_user_rpc_call(
             .unwrap_or_else(|_| "Failed to serialize".to_string())
     );
 
-    // Build RPC request body for VIEW function
-    let rpc_body = serde_json::json!({
-        "jsonrpc": "2.0",
-        "id": "check_register_from_wasm",
-        "method": "query",
-        "params": {
-            "request_type": "call_function",
-            "account_id": contract_id,
-            "method_name": CHECK_CAN_REGISTER_USER_METHOD,
-            "args_base64": base64_standard_encode(contract_args.to_string().as_bytes()),
-            "finality": "optimistic"
-        }
+    let params = call_function_params(
+        contract_id,
+        CHECK_CAN_REGISTER_USER_METHOD,
+        &contract_args,
+        "optimistic",
+    );
+    let response_result = query_envelope(rpc, params).await?;
+
+    // Parse the response for view function
+    parse_check_can_register_response(response_result)
+}
+
+/// Detect the contract's registration interface version (VIEW FUNCTION - uses query RPC).
+/// Contracts deployed before `get_contract_interface_version` existed are V1. Falls back to
+/// the latest version when the probe itself fails (network error, unexpected response).
+pub async fn probe_contract_interface<R: RpcClient>(
+    rpc: &R,
+    contract_id: &str,
+) -> ContractInterfaceVersion {
+    let params = call_function_params(
+        contract_id,
+        CONTRACT_INTERFACE_VERSION_METHOD,
+        &json!({}),
+        "optimistic",
+    );
+    let version = match query_envelope(rpc, params).await {
+        Ok(response) => parse_contract_interface_version_response(&response),
+        Err(e) => Err(e),
+    };
+    version.unwrap_or_else(|e| {
+        warn!(
+            "RUST: Contract interface probe failed ({}), assuming {:?}",
+            e,
+            ContractInterfaceVersion::LATEST
+        );
+        ContractInterfaceVersion::LATEST
+    })
+}
+
+/// List the account's registered authenticators (VIEW FUNCTION - uses query RPC)
+pub async fn get_authenticators_by_user_rpc_call<R: RpcClient>(
+    rpc: &R,
+    contract_id: &str,
+    account_id: &str,
+) -> Result<Vec<RegisteredAuthenticator>, String> {
+    let params = call_function_params(
+        contract_id,
+        GET_AUTHENTICATORS_BY_USER_METHOD,
+        &json!({ "user_id": account_id }),
+        "optimistic",
+    );
+    let response = query_envelope(rpc, params).await?;
+    parse_authenticators_by_user_response(&response)
+}
+
+/// Check whether `account_id` exists on-chain (VIEW - uses query RPC `view_account`)
+pub async fn view_account_exists_rpc_call<R: RpcClient>(
+    rpc: &R,
+    account_id: &str,
+) -> Result<bool, String> {
+    let params = json!({
+        "request_type": "view_account",
+        "account_id": account_id,
+        "finality": "final"
     });
+    let response = query_envelope(rpc, params).await?;
+    parse_view_account_response(&response)
+}
 
-    info!("RUST: Making registration check RPC call to: {}", rpc_url);
+/// Runs a `query` and rebuilds the JSON-RPC envelope (`{ result }` or `{ error }`) the
+/// parsers below read. Node errors stay in the envelope; no response or a timeout is an error.
+async fn query_envelope<R: RpcClient>(rpc: &R, params: Value) -> Result<Value, String> {
+    match rpc.call("query", params).await {
+        Ok(result) => Ok(json!({ "result": result })),
+        Err(RpcErrorKind::Rpc { error, .. }) => Ok(json!({ "error": error })),
+        Err(e) => Err(e.to_string()),
+    }
+}
 
-    // Execute the request (reuse the same HTTP logic)
-    let response_result = execute_rpc_request(rpc_url, &rpc_body).await?;
+/// GET a JSON document (e.g. from an indexer), trying comma-separated URLs in order
+pub async fn fetch_json(url: &str) -> Result<serde_json::Value, String> {
+    execute_json_request(url, None).await
+}
 
-    // Parse the response for view function
-    parse_check_can_register_response(response_result)
+/// Status and JSON body of an HTTP response; `body` is None when it is not JSON
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpJsonResponse {
+    pub status: u16,
+    pub body: Option<Value>,
+}
+
+/// Sends one request to `url` (no endpoint fallback) and returns the response whatever its
+/// status. Errors mean no response was received.
+pub async fn http_json_request(
+    method: &str,
+    url: &str,
+    body: Option<&Value>,
+    extra_headers: &[(&str, &str)],
+) -> Result<HttpJsonResponse, String> {
+    let headers = Headers::new().map_err(|e| format!("Failed to create headers: {:?}", e))?;
+    headers
+        .set("Accept", "application/json")
+        .map_err(|e| format!("Failed to set Accept header: {:?}", e))?;
+    let opts = RequestInit::new();
+    opts.set_mode(RequestMode::Cors);
+    opts.set_method(method);
+    if let Some(body) = body {
+        headers
+            .set("Content-Type", "application/json")
+            .map_err(|e| format!("Failed to set Content-Type header: {:?}", e))?;
+        opts.set_body(&JsValue::from_str(&body.to_string()));
+    }
+    for (name, value) in extra_headers {
+        headers
+            .set(name, value)
+            .map_err(|e| format!("Failed to set {} header: {:?}", name, e))?;
+    }
+    opts.set_headers(&headers);
+
+    let request = Request::new_with_str_and_init(url, &opts)
+        .map_err(|e| format!("Failed to create request: {:?}", e))?;
+    let global = js_sys::global();
+    let fetch_fn = js_sys::Reflect::get(&global, &JsValue::from_str("fetch"))
+        .map_err(|_| "fetch function not available".to_string())?
+        .dyn_into::<js_sys::Function>()
+        .map_err(|_| "fetch is not a function".to_string())?;
+    let fetch_promise = fetch_fn
+        .call1(&global, &request)
+        .map_err(|e| format!("fetch call failed: {:?}", e))?
+        .dyn_into::<js_sys::Promise>()
+        .map_err(|_| "fetch did not return a Promise".to_string())?;
+    let resp: Response = JsFuture::from(fetch_promise)
+        .await
+        .map_err(|e| format!("Fetch request to {} failed: {:?}", url, e))?
+        .dyn_into()
+        .map_err(|e| format!("Failed to cast response: {:?}", e))?;
+
+    let text = match resp.text() {
+        Ok(promise) => JsFuture::from(promise)
+            .await
+            .ok()
+            .and_then(|v| v.as_string()),
+        Err(_) => None,
+    };
+    Ok(HttpJsonResponse {
+        status: resp.status(),
+        body: text.and_then(|t| serde_json::from_str(&t).ok()),
+    })
+}
+
+#[cfg(feature = "telemetry")]
+/// POSTs raw `body` bytes with `content_type` to `url` (no endpoint fallback) and returns the
+/// response status. Errors mean no response was received.
+pub async fn http_post_bytes(url: &str, content_type: &str, body: &[u8]) -> Result<u16, String> {
+    let headers = Headers::new().map_err(|e| format!("Failed to create headers: {:?}", e))?;
+    headers
+        .set("Content-Type", content_type)
+        .map_err(|e| format!("Failed to set Content-Type header: {:?}", e))?;
+    let opts = RequestInit::new();
+    opts.set_mode(RequestMode::Cors);
+    opts.set_method("POST");
+    opts.set_headers(&headers);
+    opts.set_body(&js_sys::Uint8Array::from(body));
+
+    let request = Request::new_with_str_and_init(url, &opts)
+        .map_err(|e| format!("Failed to create request: {:?}", e))?;
+    let global = js_sys::global();
+    let fetch_fn = js_sys::Reflect::get(&global, &JsValue::from_str("fetch"))
+        .map_err(|_| "fetch function not available".to_string())?
+        .dyn_into::<js_sys::Function>()
+        .map_err(|_| "fetch is not a function".to_string())?;
+    let fetch_promise = fetch_fn
+        .call1(&global, &request)
+        .map_err(|e| format!("fetch call failed: {:?}", e))?
+        .dyn_into::<js_sys::Promise>()
+        .map_err(|_| "fetch did not return a Promise".to_string())?;
+    let resp: Response = JsFuture::from(fetch_promise)
+        .await
+        .map_err(|e| format!("Fetch request to {} failed: {:?}", url, e))?
+        .dyn_into()
+        .map_err(|e| format!("Failed to cast response: {:?}", e))?;
+    Ok(resp.status())
 }
 
-/// Shared HTTP request execution logic
-async fn execute_rpc_request(
+#[cfg(any(feature = "relayer", target_arch = "wasm32"))]
+/// Resolves after `ms` milliseconds (setTimeout on the worker's global scope)
+pub async fn sleep_ms(ms: u32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let global = js_sys::global();
+        if let Ok(set_timeout) = js_sys::Reflect::get(&global, &JsValue::from_str("setTimeout"))
+            .and_then(|f| f.dyn_into::<js_sys::Function>())
+        {
+            let _ = set_timeout.call2(&global, &resolve, &JsValue::from(ms));
+        } else {
+            let _ = resolve.call0(&JsValue::NULL);
+        }
+    });
+    let _ = JsFuture::from(promise).await;
+}
+
+/// POSTs `body` as JSON, or GETs when there is no body
+async fn execute_json_request(
     rpc_url: &str,
-    rpc_body: &serde_json::Value,
+    body: Option<&serde_json::Value>,
 ) -> Result<serde_json::Value, String> {
     // Split comma/whitespace-separated endpoints and try each in order
     let endpoints: Vec<String> = rpc_url
@@ -308,16 +502,24 @@ async fn execute_rpc_request(
 
     // Create headers once
     let headers = Headers::new().map_err(|e| format!("Failed to create headers: {:?}", e))?;
-    headers
-        .set("Content-Type", "application/json")
-        .map_err(|e| format!("Failed to set Content-Type header: {:?}", e))?;
-
-    // Create base options
     let opts = RequestInit::new();
-    opts.set_method("POST");
     opts.set_mode(RequestMode::Cors);
+    match body {
+        Some(body) => {
+            headers
+                .set("Content-Type", "application/json")
+                .map_err(|e| format!("Failed to set Content-Type header: {:?}", e))?;
+            opts.set_method("POST");
+            opts.set_body(&JsValue::from_str(&body.to_string()));
+        }
+        None => {
+            headers
+                .set("Accept", "application/json")
+                .map_err(|e| format!("Failed to set Accept header: {:?}", e))?;
+            opts.set_method("GET");
+        }
+    }
     opts.set_headers(&headers);
-    opts.set_body(&JsValue::from_str(&rpc_body.to_string()));
 
     // Get global scope (works in both Window and Worker contexts)
     let global = js_sys::global();
@@ -570,3 +772,83 @@ pub fn parse_check_can_register_response(
         pre_signed_delete_transaction: None, // View functions don't have transactions
     })
 }
+
+/// Parse the `get_contract_interface_version` view response.
+/// A missing method (MethodNotFound) means the contract predates versioning: V1.
+pub fn parse_contract_interface_version_response(
+    result: &serde_json::Value,
+) -> Result<ContractInterfaceVersion, String> {
+    let error_text = result
+        .get("error")
+        .or_else(|| result.get("result").and_then(|r| r.get("error")))
+        .map(|e| e.to_string());
+    if let Some(error_text) = error_text {
+        if error_text.contains("MethodNotFound") {
+            return Ok(ContractInterfaceVersion::V1);
+        }
+        return Err(format!("Contract interface probe error: {}", error_text));
+    }
+
+    let result_bytes: Vec<u8> = result
+        .get("result")
+        .and_then(|r| r.get("result"))
+        .and_then(|r| r.as_array())
+        .ok_or("Missing or invalid result.result array")?
+        .iter()
+        .map(|v| v.as_u64().unwrap_or(0) as u8)
+        .collect();
+    let version: u32 = serde_json::from_slice(&result_bytes)
+        .map_err(|e| format!("Failed to parse contract interface version: {}", e))?;
+    ContractInterfaceVersion::from_u32(version)
+}
+
+/// Parse a `get_authenticators_by_user` response: `[[credential_id, authenticator], ...]`
+pub fn parse_authenticators_by_user_response(
+    result: &serde_json::Value,
+) -> Result<Vec<RegisteredAuthenticator>, String> {
+    if let Some(error) = result
+        .get("error")
+        .or_else(|| result.get("result").and_then(|r| r.get("error")))
+    {
+        return Err(format!("get_authenticators_by_user failed: {}", error));
+    }
+
+    let result_bytes: Vec<u8> = result
+        .get("result")
+        .and_then(|r| r.get("result"))
+        .and_then(|r| r.as_array())
+        .ok_or("Missing or invalid result.result array")?
+        .iter()
+        .map(|v| v.as_u64().unwrap_or(0) as u8)
+        .collect();
+    let entries: Vec<(String, RegisteredAuthenticator)> = serde_json::from_slice(&result_bytes)
+        .map_err(|e| format!("Failed to parse authenticators: {}", e))?;
+    Ok(entries
+        .into_iter()
+        .map(|(credential_id, authenticator)| RegisteredAuthenticator {
+            credential_id,
+            ..authenticator
+        })
+        .collect())
+}
+
+/// Parse a `view_account` response: true if the account exists, false for UNKNOWN_ACCOUNT
+pub fn parse_view_account_response(result: &serde_json::Value) -> Result<bool, String> {
+    let error = match result.get("error") {
+        Some(error) => error,
+        None => {
+            return result
+                .get("result")
+                .map(|_| true)
+                .ok_or_else(|| "Missing result in view_account response".to_string())
+        }
+    };
+    let cause = error
+        .get("cause")
+        .and_then(|c| c.get("name"))
+        .and_then(|n| n.as_str());
+    if cause == Some("UNKNOWN_ACCOUNT") || error.to_string().contains("does not exist") {
+        return Ok(false);
+    }
+    Err(format!("view_account failed: {}", error))
+}
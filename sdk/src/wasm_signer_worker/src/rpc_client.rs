@@ -0,0 +1,376 @@
+// === NEAR RPC CLIENT ===
+// Handlers reach NEAR through `RpcClient` instead of calling fetch themselves: NearRpcClient in
+// the worker, MockRpcClient in native tests and benchmarks. NearRpcClient speaks JSON-RPC over a
+// JsonTransport (FetchTransport in the worker), trying the comma-separated endpoints of its URL
+// in order, and maps what the node answered to RpcErrorKind:
+//   - no response, HTTP 5xx                      -> Unreachable, next endpoint
+//   - HTTP 408/504, cause TIMEOUT_ERROR          -> Timeout, next endpoint
+//   - InvalidTxError::InvalidNonce               -> InvalidNonce { tx_nonce, ak_nonce }
+//   - any other error object                     -> Rpc { name: cause name, .. }
+// The NEAR RPC has no JSON-RPC batch support, so call_batch sends its calls one after another.
+
+use log::warn;
+use serde_json::{json, Value};
+
+use crate::encoders::base64_standard_encode;
+use crate::error::RpcErrorKind;
+use crate::rpc_calls::{http_json_request, HttpJsonResponse};
+
+/// One HTTP request to a single endpoint: fetch in the worker, scripted in native tests
+pub trait JsonTransport {
+    /// POSTs `body` as JSON to `url`, or GETs it when there is no body. Errors mean no
+    /// response was received.
+    async fn request(&self, url: &str, body: Option<&Value>) -> Result<HttpJsonResponse, String>;
+}
+
+impl<T: JsonTransport> JsonTransport for &T {
+    async fn request(&self, url: &str, body: Option<&Value>) -> Result<HttpJsonResponse, String> {
+        (**self).request(url, body).await
+    }
+}
+
+/// The worker's transport (fetch on the worker's global scope)
+pub struct FetchTransport;
+
+impl JsonTransport for FetchTransport {
+    async fn request(&self, url: &str, body: Option<&Value>) -> Result<HttpJsonResponse, String> {
+        let method = if body.is_some() { "POST" } else { "GET" };
+        http_json_request(method, url, body, &[]).await
+    }
+}
+
+/// NEAR JSON-RPC calls
+pub trait RpcClient {
+    /// The `result` of calling `method` with `params`
+    async fn call(&self, method: &str, params: Value) -> Result<Value, RpcErrorKind>;
+
+    /// Results of several calls, in order; each call fails on its own
+    async fn call_batch(&self, calls: Vec<(&str, Value)>) -> Vec<Result<Value, RpcErrorKind>> {
+        let mut results = Vec::with_capacity(calls.len());
+        for (method, params) in calls {
+            results.push(self.call(method, params).await);
+        }
+        results
+    }
+}
+
+/// JSON-RPC client over the endpoints of a (comma-separated) NEAR RPC URL
+pub struct NearRpcClient<T: JsonTransport = FetchTransport> {
+    transport: T,
+    endpoints: Vec<String>,
+}
+
+impl NearRpcClient<FetchTransport> {
+    pub fn new(rpc_url: &str) -> Self {
+        NearRpcClient::with_transport(FetchTransport, rpc_url)
+    }
+}
+
+impl<T: JsonTransport> NearRpcClient<T> {
+    pub fn with_transport(transport: T, rpc_url: &str) -> Self {
+        let endpoints = rpc_url
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .collect();
+        NearRpcClient {
+            transport,
+            endpoints,
+        }
+    }
+}
+
+impl<T: JsonTransport> RpcClient for NearRpcClient<T> {
+    async fn call(&self, method: &str, params: Value) -> Result<Value, RpcErrorKind> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": format!("{}_from_wasm", method),
+            "method": method,
+            "params": params
+        });
+        let mut last_error = RpcErrorKind::Unreachable("NEAR RPC URL cannot be empty".to_string());
+        for endpoint in &self.endpoints {
+            let outcome = match self.transport.request(endpoint, Some(&body)).await {
+                Ok(response) => parse_rpc_response(endpoint, response),
+                Err(e) => Err(RpcErrorKind::Unreachable(format!("{}: {}", endpoint, e))),
+            };
+            match outcome {
+                Err(e) if e.is_failover() => {
+                    warn!("RUST: {} failed on {}: {}", method, endpoint, e);
+                    last_error = e;
+                }
+                outcome => return outcome,
+            }
+        }
+        Err(last_error)
+    }
+}
+
+/// The `result` of one endpoint's JSON-RPC response
+pub fn parse_rpc_response(
+    endpoint: &str,
+    response: HttpJsonResponse,
+) -> Result<Value, RpcErrorKind> {
+    if response.status == 408 || response.status == 504 {
+        return Err(RpcErrorKind::Timeout(format!(
+            "{} answered HTTP {}",
+            endpoint, response.status
+        )));
+    }
+    if response.status >= 500 {
+        return Err(RpcErrorKind::Unreachable(format!(
+            "{} answered HTTP {}",
+            endpoint, response.status
+        )));
+    }
+    let body = response.body.ok_or_else(|| {
+        RpcErrorKind::InvalidResponse(format!(
+            "{} answered HTTP {} without a JSON body",
+            endpoint, response.status
+        ))
+    })?;
+    if let Some(error) = body.get("error") {
+        return Err(rpc_error_kind(error));
+    }
+    body.get("result").cloned().ok_or_else(|| {
+        RpcErrorKind::InvalidResponse(format!("{} answered without result or error", endpoint))
+    })
+}
+
+/// Classifies a JSON-RPC error object
+pub fn rpc_error_kind(error: &Value) -> RpcErrorKind {
+    let name = error
+        .get("cause")
+        .and_then(|c| c.get("name"))
+        .or_else(|| error.get("name"))
+        .and_then(|n| n.as_str())
+        .unwrap_or("UNKNOWN_ERROR")
+        .to_string();
+    let message = error
+        .get("data")
+        .and_then(|d| d.as_str())
+        .or_else(|| error.get("message").and_then(|m| m.as_str()))
+        .unwrap_or(&name)
+        .to_string();
+    if name == "TIMEOUT_ERROR" {
+        return RpcErrorKind::Timeout(message);
+    }
+    if let Some((tx_nonce, ak_nonce)) = find_invalid_nonce(error) {
+        return RpcErrorKind::InvalidNonce { tx_nonce, ak_nonce };
+    }
+    RpcErrorKind::Rpc {
+        name,
+        message,
+        error: error.clone(),
+    }
+}
+
+/// `{ InvalidNonce: { tx_nonce, ak_nonce } }` anywhere in the error (`data` and `cause.info`
+/// both carry it, depending on the node version)
+fn find_invalid_nonce(value: &Value) -> Option<(u64, u64)> {
+    match value {
+        Value::Object(map) => {
+            if let Some(nonce) = map.get("InvalidNonce") {
+                let tx_nonce = nonce.get("tx_nonce").and_then(|n| n.as_u64());
+                let ak_nonce = nonce.get("ak_nonce").and_then(|n| n.as_u64());
+                if let (Some(tx_nonce), Some(ak_nonce)) = (tx_nonce, ak_nonce) {
+                    return Some((tx_nonce, ak_nonce));
+                }
+            }
+            map.values().find_map(find_invalid_nonce)
+        }
+        Value::Array(items) => items.iter().find_map(find_invalid_nonce),
+        _ => None,
+    }
+}
+
+// === TYPED CALLS ===
+
+/// `query` call_function params for a view call of `method_name` with JSON `args`
+pub fn call_function_params(
+    contract_id: &str,
+    method_name: &str,
+    args: &Value,
+    finality: &str,
+) -> Value {
+    json!({
+        "request_type": "call_function",
+        "account_id": contract_id,
+        "method_name": method_name,
+        "args_base64": base64_standard_encode(args.to_string().as_bytes()),
+        "finality": finality
+    })
+}
+
+fn view_access_key_params(account_id: &str, public_key: &str) -> Value {
+    json!({
+        "request_type": "view_access_key",
+        "account_id": account_id,
+        "public_key": public_key,
+        "finality": "final"
+    })
+}
+
+fn parse_access_key_nonce(result: &Value) -> Result<u64, RpcErrorKind> {
+    result.get("nonce").and_then(|n| n.as_u64()).ok_or_else(|| {
+        RpcErrorKind::InvalidResponse(format!("view_access_key result without nonce: {}", result))
+    })
+}
+
+/// Height and base58 hash of a block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockReference {
+    pub height: u64,
+    pub hash: String,
+}
+
+fn parse_block_reference(result: &Value) -> Result<BlockReference, RpcErrorKind> {
+    let header = result.get("header");
+    let height = header
+        .and_then(|h| h.get("height"))
+        .and_then(|h| h.as_u64());
+    let hash = header.and_then(|h| h.get("hash")).and_then(|h| h.as_str());
+    match (height, hash) {
+        (Some(height), Some(hash)) => Ok(BlockReference {
+            height,
+            hash: hash.to_string(),
+        }),
+        _ => Err(RpcErrorKind::InvalidResponse(
+            "block result without header height and hash".to_string(),
+        )),
+    }
+}
+
+/// Nonce for the next transaction of `public_key` and a final block hash to build it on,
+/// fetched together
+pub async fn refresh_nonce_and_block<R: RpcClient>(
+    rpc: &R,
+    account_id: &str,
+    public_key: &str,
+) -> Result<(u64, BlockReference), RpcErrorKind> {
+    let mut results = rpc
+        .call_batch(vec![
+            ("query", view_access_key_params(account_id, public_key)),
+            ("block", json!({ "finality": "final" })),
+        ])
+        .await
+        .into_iter();
+    let nonce = parse_access_key_nonce(&results.next().unwrap_or_else(missing_result)?)?;
+    let block = parse_block_reference(&results.next().unwrap_or_else(missing_result)?)?;
+    Ok((nonce.saturating_add(1), block))
+}
+
+/// The `view_access_key` result of `public_key` on `account_id`, None when the account has no
+/// such key (an UNKNOWN_ACCESS_KEY error, or from older nodes a result with an `error` string)
+pub async fn view_access_key<R: RpcClient>(
+    rpc: &R,
+    account_id: &str,
+    public_key: &str,
+) -> Result<Option<Value>, RpcErrorKind> {
+    match rpc
+        .call("query", view_access_key_params(account_id, public_key))
+        .await
+    {
+        Ok(result) if result.get("error").is_some() => Ok(None),
+        Ok(result) => Ok(Some(result)),
+        Err(RpcErrorKind::Rpc { name, .. }) if name == "UNKNOWN_ACCESS_KEY" => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Height of the latest (optimistic) block, for deadline checks right before a broadcast
+pub async fn latest_block_height<R: RpcClient>(rpc: &R) -> Result<u64, RpcErrorKind> {
+    let result = rpc
+        .call("block", json!({ "finality": "optimistic" }))
+        .await?;
+    Ok(parse_block_reference(&result)?.height)
+}
+
+fn missing_result() -> Result<Value, RpcErrorKind> {
+    Err(RpcErrorKind::InvalidResponse(
+        "batch returned fewer results than calls".to_string(),
+    ))
+}
+
+/// Broadcasts borsh SignedTransaction bytes and waits for the execution outcome
+pub async fn broadcast_tx_commit<R: RpcClient>(
+    rpc: &R,
+    signed_tx_bytes: &[u8],
+) -> Result<Value, RpcErrorKind> {
+    rpc.call(
+        "broadcast_tx_commit",
+        json!([base64_standard_encode(signed_tx_bytes)]),
+    )
+    .await
+}
+
+// === MOCK ===
+
+/// In-memory RpcClient for native tests and benchmarks. Answers are queued per route
+/// (see `route_of`); the last answer of a route repeats. Every call is recorded.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MockRpcClient {
+    answers: std::cell::RefCell<
+        std::collections::HashMap<String, std::collections::VecDeque<Result<Value, RpcErrorKind>>>,
+    >,
+    pub calls: std::cell::RefCell<Vec<(String, Value)>>,
+}
+
+#[cfg(test)]
+impl MockRpcClient {
+    pub fn new() -> Self {
+        MockRpcClient::default()
+    }
+
+    /// Queues `outcome` for calls on `route`
+    pub fn answer(self, route: &str, outcome: Result<Value, RpcErrorKind>) -> Self {
+        self.answers
+            .borrow_mut()
+            .entry(route.to_string())
+            .or_default()
+            .push_back(outcome);
+        self
+    }
+
+    /// `method`, then `/request_type` and `/method_name` when the params have them
+    /// (e.g. "query/call_function/verify_authentication_response", "block")
+    pub fn route_of(method: &str, params: &Value) -> String {
+        let mut route = method.to_string();
+        for key in ["request_type", "method_name"] {
+            if let Some(part) = params.get(key).and_then(|v| v.as_str()) {
+                route.push('/');
+                route.push_str(part);
+            }
+        }
+        route
+    }
+
+    /// Params of the calls made on `route`, in order
+    pub fn calls_to(&self, route: &str) -> Vec<Value> {
+        self.calls
+            .borrow()
+            .iter()
+            .filter(|(r, _)| r == route)
+            .map(|(_, params)| params.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+impl RpcClient for MockRpcClient {
+    async fn call(&self, method: &str, params: Value) -> Result<Value, RpcErrorKind> {
+        let route = MockRpcClient::route_of(method, &params);
+        self.calls.borrow_mut().push((route.clone(), params));
+        let mut answers = self.answers.borrow_mut();
+        match answers.get_mut(&route) {
+            Some(queue) if queue.len() > 1 => queue.pop_front().unwrap(),
+            Some(queue) => queue.front().cloned().unwrap(),
+            None => Err(RpcErrorKind::Unreachable(format!(
+                "no mock answer for {}",
+                route
+            ))),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+// === RPC ENDPOINT OVERRIDES ===
+// A signing request may route contract verification, and the main thread's broadcast, through
+// endpoints other than `rpcCall.nearRpcUrl` (e.g. an integrator's proxy for verification and a
+// public RPC for broadcasts). The page supplies these endpoints, so each override is pinned to
+// an origin from `WorkerPolicy.allowedRpcOrigins`: a compromised page cannot point verification
+// at an endpoint that answers "verified" for any assertion.
+
+use serde::Serialize;
+
+use crate::error::RpcOriginError;
+use crate::types::{RpcCallPayload, RpcOverrides, WorkerPolicy};
+
+/// Endpoints a signing request used, as recorded in its audit entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcEndpoints {
+    pub verification: String,
+    /// None: the main thread broadcasts through its configured client
+    pub broadcast: Option<String>,
+}
+
+/// `scheme://host[:port]` of an http(s) URL, lowercased and without the scheme's default port.
+/// URLs carrying credentials (`https://allowed.example@evil.example`) have no origin here.
+pub fn rpc_origin(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let scheme = scheme.to_ascii_lowercase();
+    let default_port = match scheme.as_str() {
+        "https" => "443",
+        "http" => "80",
+        _ => return None,
+    };
+    let authority = rest
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if authority.contains('@') {
+        return None;
+    }
+    // The last ':' starts the port unless it is inside an IPv6 literal (`[::1]`)
+    let (host, port) = match authority.rfind(':') {
+        Some(i) if !authority[i..].contains(']') => (&authority[..i], Some(&authority[i + 1..])),
+        _ => (authority.as_str(), None),
+    };
+    if host.is_empty() {
+        return None;
+    }
+    match port {
+        None => Some(format!("{}://{}", scheme, host)),
+        Some(port) if port == default_port => Some(format!("{}://{}", scheme, host)),
+        Some(port) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => {
+            Some(format!("{}://{}:{}", scheme, host, port))
+        }
+        Some(_) => None,
+    }
+}
+
+/// Endpoints for a signing request. Each override must be on an allowed origin; without a
+/// verification override, verification uses `rpc_call.near_rpc_url`.
+pub fn resolve_rpc_endpoints(
+    rpc_call: &RpcCallPayload,
+    overrides: Option<&RpcOverrides>,
+    policy: Option<&WorkerPolicy>,
+) -> Result<RpcEndpoints, RpcOriginError> {
+    let allowed_origins: Vec<String> = policy
+        .map(|p| {
+            p.allowed_rpc_origins
+                .iter()
+                .filter_map(|origin| rpc_origin(origin))
+                .collect()
+        })
+        .unwrap_or_default();
+    let pinned = |endpoint: &'static str, url: Option<&String>| match url {
+        None => Ok(None),
+        Some(url) if rpc_origin(url).is_some_and(|o| allowed_origins.contains(&o)) => {
+            Ok(Some(url.clone()))
+        }
+        Some(url) => Err(RpcOriginError::NotAllowed {
+            endpoint,
+            url: url.clone(),
+        }),
+    };
+
+    let verification = pinned(
+        "verification",
+        overrides.and_then(|o| o.verification.as_ref()),
+    )?;
+    let broadcast = pinned("broadcast", overrides.and_then(|o| o.broadcast.as_ref()))?;
+    Ok(RpcEndpoints {
+        verification: verification.unwrap_or_else(|| rpc_call.near_rpc_url.clone()),
+        broadcast,
+    })
+}
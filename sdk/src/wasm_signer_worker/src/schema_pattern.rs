@@ -0,0 +1,510 @@
+// === ARGS SCHEMA PATTERNS ===
+// The `pattern` keyword of args schemas (args_schema.rs), matched without a regex crate by a
+// small backtracking matcher over the ECMA-262 subset contract args patterns use: literals,
+// `.`, `^`, `$`, classes (`[a-z_]`, `[^0-9]`), the escapes `\d \w \s` (and `\D \W \S` outside
+// classes), `\n \t \r \f \v \0 \xHH \uHHHH` and escaped punctuation, groups (`(..)`, `(?:..)`)
+// with alternation, and the quantifiers `* + ? {n} {n,} {n,m}` (a lazy `?` suffix is accepted
+// and changes nothing, since only whether the string matches is asked). Backreferences,
+// lookaround, named groups and word boundaries are refused when the schema is compiled.
+//
+// As in JSON Schema, a pattern matches anywhere in the string unless anchored. Matching is
+// bounded by ARGS_SCHEMA_PATTERN_STEP_LIMIT steps and ARGS_SCHEMA_PATTERN_RECURSION_LIMIT
+// backtracking depth; a match that runs out of either is reported as such, never as a pass.
+
+use std::cell::Cell;
+
+use crate::config::{
+    ARGS_SCHEMA_PATTERN_RECURSION_LIMIT, ARGS_SCHEMA_PATTERN_STEP_LIMIT, MAX_ARGS_SCHEMA_DEPTH,
+};
+
+const DIGIT: &[(char, char)] = &[('0', '9')];
+const WORD: &[(char, char)] = &[('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')];
+const SPACE: &[(char, char)] = &[
+    ('\t', '\r'),
+    (' ', ' '),
+    ('\u{a0}', '\u{a0}'),
+    ('\u{1680}', '\u{1680}'),
+    ('\u{2000}', '\u{200a}'),
+    ('\u{2028}', '\u{2029}'),
+    ('\u{202f}', '\u{202f}'),
+    ('\u{205f}', '\u{205f}'),
+    ('\u{3000}', '\u{3000}'),
+    ('\u{feff}', '\u{feff}'),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Char(char),
+    /// `.`: any character but a line terminator
+    Any,
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+    Start,
+    End,
+    Group(Vec<Vec<Node>>),
+    Repeat {
+        node: Box<Node>,
+        min: u32,
+        max: Option<u32>,
+    },
+}
+
+impl Node {
+    /// Whether the node consumes exactly one character
+    fn is_single_char(&self) -> bool {
+        matches!(self, Node::Char(_) | Node::Any | Node::Class { .. })
+    }
+
+    fn matches_char(&self, c: char) -> bool {
+        match self {
+            Node::Char(expected) => *expected == c,
+            Node::Any => !matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}'),
+            Node::Class { ranges, negated } => {
+                ranges.iter().any(|(lo, hi)| *lo <= c && c <= *hi) != *negated
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A compiled `pattern`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    source: String,
+    root: Vec<Node>,
+    /// Every alternative starts with `^`: only a match at the start needs trying
+    anchored: bool,
+}
+
+/// Outcome of matching a pattern against a string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternMatch {
+    Matched,
+    NotMatched,
+    /// The step or recursion limit ran out before the match was decided
+    LimitExceeded,
+}
+
+impl Pattern {
+    /// Compiles an ECMA-262 `pattern` of the supported subset
+    pub fn compile(source: &str) -> Result<Pattern, String> {
+        let mut parser = Parser {
+            chars: source.chars().collect(),
+            pos: 0,
+        };
+        let alternatives = parser.alternatives(0)?;
+        if parser.pos < parser.chars.len() {
+            return Err(format!("unmatched ')' at offset {}", parser.pos));
+        }
+        let anchored = alternatives
+            .iter()
+            .all(|alternative| alternative.first() == Some(&Node::Start));
+        Ok(Pattern {
+            source: source.to_string(),
+            root: vec![Node::Group(alternatives)],
+            anchored,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether the pattern matches anywhere in `text`
+    pub fn find_in(&self, text: &str) -> PatternMatch {
+        let input: Vec<char> = text.chars().collect();
+        let matcher = Matcher {
+            input: &input,
+            steps: Cell::new(0),
+            depth: Cell::new(0),
+            exceeded: Cell::new(false),
+        };
+        let last_start = if self.anchored { 0 } else { input.len() };
+        for start in 0..=last_start {
+            if matcher.seq(&self.root, start, &mut |_| true) {
+                return PatternMatch::Matched;
+            }
+            if matcher.exceeded.get() {
+                return PatternMatch::LimitExceeded;
+            }
+        }
+        PatternMatch::NotMatched
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        if c.is_some() {
+            self.pos += 1;
+        }
+        c
+    }
+
+    /// Alternatives up to the end of the pattern or the `)` closing the current group
+    fn alternatives(&mut self, depth: usize) -> Result<Vec<Vec<Node>>, String> {
+        let mut alternatives = vec![Vec::new()];
+        while let Some(c) = self.peek() {
+            match c {
+                ')' => break,
+                '|' => {
+                    self.pos += 1;
+                    alternatives.push(Vec::new());
+                }
+                _ => {
+                    let atom = self.atom(depth)?;
+                    let node = self.quantified(atom)?;
+                    if let Some(alternative) = alternatives.last_mut() {
+                        alternative.push(node);
+                    }
+                }
+            }
+        }
+        Ok(alternatives)
+    }
+
+    fn atom(&mut self, depth: usize) -> Result<Node, String> {
+        let offset = self.pos;
+        let c = self.next().ok_or("unexpected end of pattern")?;
+        Ok(match c {
+            '.' => Node::Any,
+            '^' => Node::Start,
+            '$' => Node::End,
+            '(' => {
+                if self.peek() == Some('?') {
+                    self.pos += 1;
+                    if self.next() != Some(':') {
+                        return Err(format!(
+                            "lookaround and named groups are not supported (offset {})",
+                            offset
+                        ));
+                    }
+                }
+                if depth >= MAX_ARGS_SCHEMA_DEPTH {
+                    return Err(format!(
+                        "groups nested deeper than {} levels",
+                        MAX_ARGS_SCHEMA_DEPTH
+                    ));
+                }
+                let alternatives = self.alternatives(depth + 1)?;
+                if self.next() != Some(')') {
+                    return Err(format!("unclosed group at offset {}", offset));
+                }
+                Node::Group(alternatives)
+            }
+            '[' => self.class(offset)?,
+            '\\' => self.escape(offset)?,
+            '*' | '+' | '?' => return Err(format!("nothing to repeat at offset {}", offset)),
+            '{' => {
+                self.pos = offset;
+                if self.braces().is_some() {
+                    return Err(format!("nothing to repeat at offset {}", offset));
+                }
+                self.pos = offset + 1;
+                Node::Char('{')
+            }
+            other => Node::Char(other),
+        })
+    }
+
+    fn quantified(&mut self, atom: Node) -> Result<Node, String> {
+        let offset = self.pos;
+        let (min, max) = match self.peek() {
+            Some('*') => {
+                self.pos += 1;
+                (0, None)
+            }
+            Some('+') => {
+                self.pos += 1;
+                (1, None)
+            }
+            Some('?') => {
+                self.pos += 1;
+                (0, Some(1))
+            }
+            Some('{') => match self.braces() {
+                Some(bounds) => bounds,
+                None => {
+                    self.pos = offset;
+                    return Ok(atom);
+                }
+            },
+            _ => return Ok(atom),
+        };
+        if matches!(atom, Node::Start | Node::End) {
+            return Err(format!("nothing to repeat at offset {}", offset));
+        }
+        if max.is_some_and(|max| max < min) {
+            return Err(format!(
+                "quantifier range out of order at offset {}",
+                offset
+            ));
+        }
+        // Lazy: same outcome for a yes/no match
+        if self.peek() == Some('?') {
+            self.pos += 1;
+        }
+        Ok(Node::Repeat {
+            node: Box::new(atom),
+            min,
+            max,
+        })
+    }
+
+    /// `{n}`, `{n,}` or `{n,m}` at the current position; None (position unspecified) otherwise
+    fn braces(&mut self) -> Option<(u32, Option<u32>)> {
+        if self.next() != Some('{') {
+            return None;
+        }
+        let min = self.number()?;
+        let max = match self.next()? {
+            '}' => return Some((min, Some(min))),
+            ',' if self.peek() == Some('}') => None,
+            ',' => Some(self.number()?),
+            _ => return None,
+        };
+        (self.next()? == '}').then_some((min, max))
+    }
+
+    fn number(&mut self) -> Option<u32> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        digits.parse().ok()
+    }
+
+    fn class(&mut self, offset: usize) -> Result<Node, String> {
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.pos += 1;
+        }
+        let mut ranges = Vec::new();
+        loop {
+            let c = self
+                .next()
+                .ok_or_else(|| format!("unclosed character class at offset {}", offset))?;
+            if c == ']' {
+                break;
+            }
+            let lo = match self.class_member(c)? {
+                ClassMember::Char(lo) => lo,
+                ClassMember::Set(set) => {
+                    ranges.extend_from_slice(set);
+                    continue;
+                }
+            };
+            let is_range = self.peek() == Some('-')
+                && self
+                    .chars
+                    .get(self.pos + 1)
+                    .is_some_and(|next| *next != ']');
+            if !is_range {
+                ranges.push((lo, lo));
+                continue;
+            }
+            self.pos += 1;
+            let c = self
+                .next()
+                .ok_or_else(|| format!("unclosed character class at offset {}", offset))?;
+            let hi = match self.class_member(c)? {
+                ClassMember::Char(hi) => hi,
+                ClassMember::Set(_) => {
+                    return Err(format!("class range with a shorthand at offset {}", offset))
+                }
+            };
+            if hi < lo {
+                return Err(format!("class range out of order at offset {}", offset));
+            }
+            ranges.push((lo, hi));
+        }
+        Ok(Node::Class { ranges, negated })
+    }
+
+    fn class_member(&mut self, c: char) -> Result<ClassMember, String> {
+        if c != '\\' {
+            return Ok(ClassMember::Char(c));
+        }
+        let offset = self.pos - 1;
+        match self.escape(offset)? {
+            Node::Char(c) => Ok(ClassMember::Char(c)),
+            Node::Class {
+                ranges,
+                negated: false,
+            } => Ok(ClassMember::Set(shorthand_set(&ranges))),
+            _ => Err(format!(
+                "negated shorthands are not supported in a class (offset {})",
+                offset
+            )),
+        }
+    }
+
+    /// The escape after a `\` at `offset`
+    fn escape(&mut self, offset: usize) -> Result<Node, String> {
+        let c = self
+            .next()
+            .ok_or("pattern ends with a lone backslash".to_string())?;
+        let class = |ranges: &[(char, char)], negated| Node::Class {
+            ranges: ranges.to_vec(),
+            negated,
+        };
+        Ok(match c {
+            'd' => class(DIGIT, false),
+            'D' => class(DIGIT, true),
+            'w' => class(WORD, false),
+            'W' => class(WORD, true),
+            's' => class(SPACE, false),
+            'S' => class(SPACE, true),
+            'n' => Node::Char('\n'),
+            't' => Node::Char('\t'),
+            'r' => Node::Char('\r'),
+            'f' => Node::Char('\u{c}'),
+            'v' => Node::Char('\u{b}'),
+            '0' if !self.peek().is_some_and(|next| next.is_ascii_digit()) => Node::Char('\0'),
+            'x' => Node::Char(self.hex(2, offset)?),
+            'u' => Node::Char(self.hex(4, offset)?),
+            '1'..='9' => {
+                return Err(format!(
+                    "backreferences are not supported (offset {})",
+                    offset
+                ))
+            }
+            'b' | 'B' => {
+                return Err(format!(
+                    "word boundaries are not supported (offset {})",
+                    offset
+                ))
+            }
+            other if other.is_ascii_alphanumeric() => {
+                return Err(format!(
+                    "unsupported escape \\{} at offset {}",
+                    other, offset
+                ))
+            }
+            other => Node::Char(other),
+        })
+    }
+
+    fn hex(&mut self, digits: usize, offset: usize) -> Result<char, String> {
+        let end = self.pos + digits;
+        let code = self
+            .chars
+            .get(self.pos..end)
+            .map(|hex| hex.iter().collect::<String>())
+            .and_then(|hex| u32::from_str_radix(&hex, 16).ok())
+            .and_then(char::from_u32)
+            .ok_or_else(|| format!("invalid hex escape at offset {}", offset))?;
+        self.pos = end;
+        Ok(code)
+    }
+}
+
+enum ClassMember {
+    Char(char),
+    Set(&'static [(char, char)]),
+}
+
+fn shorthand_set(ranges: &[(char, char)]) -> &'static [(char, char)] {
+    [DIGIT, WORD, SPACE]
+        .into_iter()
+        .find(|set| *set == ranges)
+        .unwrap_or(&[])
+}
+
+struct Matcher<'a> {
+    input: &'a [char],
+    steps: Cell<u32>,
+    depth: Cell<u32>,
+    exceeded: Cell<bool>,
+}
+
+impl Matcher<'_> {
+    /// Matches `nodes` at `pos`, then hands the end position to `then`
+    fn seq(&self, nodes: &[Node], pos: usize, then: &mut dyn FnMut(usize) -> bool) -> bool {
+        self.steps.set(self.steps.get().saturating_add(1));
+        self.depth.set(self.depth.get() + 1);
+        if self.steps.get() > ARGS_SCHEMA_PATTERN_STEP_LIMIT
+            || self.depth.get() > ARGS_SCHEMA_PATTERN_RECURSION_LIMIT
+        {
+            self.exceeded.set(true);
+        }
+        let matched = !self.exceeded.get() && self.seq_within_limits(nodes, pos, then);
+        self.depth.set(self.depth.get() - 1);
+        matched
+    }
+
+    fn seq_within_limits(
+        &self,
+        nodes: &[Node],
+        pos: usize,
+        then: &mut dyn FnMut(usize) -> bool,
+    ) -> bool {
+        let Some((node, rest)) = nodes.split_first() else {
+            return then(pos);
+        };
+        match node {
+            Node::Start => pos == 0 && self.seq(rest, pos, then),
+            Node::End => pos == self.input.len() && self.seq(rest, pos, then),
+            Node::Group(alternatives) => alternatives.iter().any(|alternative| {
+                self.seq(alternative, pos, &mut |end| self.seq(rest, end, &mut *then))
+            }),
+            Node::Repeat { node, min, max } if node.is_single_char() => {
+                // Greedy run of the character, then give characters back one at a time
+                let limit = max.map_or(usize::MAX, |max| max as usize);
+                let mut run = 0;
+                while run < limit
+                    && self
+                        .input
+                        .get(pos + run)
+                        .is_some_and(|c| node.matches_char(*c))
+                {
+                    run += 1;
+                }
+                (*min as usize..=run)
+                    .rev()
+                    .any(|count| self.seq(rest, pos + count, &mut *then))
+            }
+            Node::Repeat { node, min, max } => self.repeat(node, (*min, *max), 0, pos, rest, then),
+            single => match self.input.get(pos) {
+                Some(c) if single.matches_char(*c) => self.seq(rest, pos + 1, then),
+                _ => false,
+            },
+        }
+    }
+
+    /// `node` repeated within `bounds` (min, max) after `count` iterations ending at `pos`
+    fn repeat(
+        &self,
+        node: &Node,
+        bounds: (u32, Option<u32>),
+        count: u32,
+        pos: usize,
+        rest: &[Node],
+        then: &mut dyn FnMut(usize) -> bool,
+    ) -> bool {
+        let (min, max) = bounds;
+        let may_repeat = max.is_none_or(|max| count < max);
+        if may_repeat
+            && self.seq(std::slice::from_ref(node), pos, &mut |end| {
+                // An empty iteration past the minimum would loop forever
+                (end != pos || count < min)
+                    && self.repeat(node, bounds, count + 1, end, rest, &mut *then)
+            })
+        {
+            return true;
+        }
+        count >= min && self.seq(rest, pos, then)
+    }
+}
@@ -0,0 +1,309 @@
+// === SEALED SECRETS ===
+// PRF outputs, imported private keys and passphrases cross from the main thread in postMessage,
+// where any debugger hook on the page can read them. The worker instead holds an X25519 key per
+// session (state.rs), rotated by Initialize and WipeAllState; the Initialize handshake returns
+// its public key, and the TS layer seals each secret to it in a crypto_box-style sealed box:
+//
+//   base64url(version || ephemeral X25519 public key (32) || ChaCha20-Poly1305 ciphertext)
+//
+// The cipher key is HKDF-SHA256 over the X25519 shared secret, with both public keys in the
+// info. Every box has a fresh ephemeral key, hence a fresh cipher key, so the nonce is fixed at
+// zero. The associated data is the JSON pointer of the field the box opens to, so a box
+// cannot be moved to another field.
+//
+// Requests carry their boxes in `sealedSecrets` ({ pointer: box }); they are opened into the
+// payload at dispatch, before strict and typed parsing, and only into the request type's
+// secret fields. Confirmation responses carry `sealed_secrets` for the PRF output and the
+// credential's PRF results. Plaintext secrets keep working until a request's WorkerPolicy sets
+// requireEncryptedSecrets, after which they are refused for the rest of the session.
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Nonce,
+};
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use hkdf::Hkdf;
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::HashMap;
+use zeroize::Zeroizing;
+
+use crate::config::{SEALED_SECRETS_FIELD, SEALED_SECRET_HKDF_INFO, SEALED_SECRET_VERSION};
+use crate::deprecations;
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::SealedSecretError;
+use crate::error_codes::{PLAINTEXT_CONFIRMATION_SECRET, PLAINTEXT_SECRET};
+use crate::handlers::confirm_tx_details::ConfirmationResult;
+use crate::state;
+use crate::types::worker_messages::WorkerRequestType;
+
+const CREDENTIAL_PRF_RESULTS: [&str; 2] = [
+    "/credential/clientExtensionResults/prf/results/first",
+    "/credential/clientExtensionResults/prf/results/second",
+];
+
+/// Confirmation response fields that may be sealed; credential pointers are relative to the
+/// response, as in requests
+const CONFIRMATION_SECRET_FIELDS: [&str; 3] = [
+    "/prf_output",
+    CREDENTIAL_PRF_RESULTS[0],
+    CREDENTIAL_PRF_RESULTS[1],
+];
+
+/// JSON pointers of a request type's secret fields, the only ones a sealed box may open to
+pub fn secret_fields(request_type: WorkerRequestType) -> &'static [&'static str] {
+    match request_type {
+        WorkerRequestType::DecryptPrivateKeyWithPrf
+        | WorkerRequestType::ValidateDecryptionCapability
+        | WorkerRequestType::MigrateEncryptedBlobs => &["/chacha20PrfOutput"],
+        WorkerRequestType::SignNep413Message => &["/prfOutput"],
+        WorkerRequestType::SignTransactionWithKeyPair => &["/nearPrivateKey"],
+        WorkerRequestType::ExportAccountBundle | WorkerRequestType::ImportAccountBundle => {
+            &["/passphrase"]
+        }
+        WorkerRequestType::DeriveNearKeypairAndEncrypt => &[
+            "/dualPrfOutputs/chacha20PrfOutput",
+            "/dualPrfOutputs/ed25519PrfOutput",
+            "/prfFallback/passphrase",
+            CREDENTIAL_PRF_RESULTS[0],
+            CREDENTIAL_PRF_RESULTS[1],
+        ],
+        WorkerRequestType::PrepareRegistration | WorkerRequestType::CompleteRegistration => &[
+            "/dualPrfOutputs/chacha20PrfOutput",
+            "/dualPrfOutputs/ed25519PrfOutput",
+            CREDENTIAL_PRF_RESULTS[0],
+            CREDENTIAL_PRF_RESULTS[1],
+        ],
+        WorkerRequestType::DeriveSubKey => &[
+            "/dualPrfOutputs/chacha20PrfOutput",
+            "/dualPrfOutputs/ed25519PrfOutput",
+        ],
+        WorkerRequestType::RecoverKeypairFromPasskey
+        | WorkerRequestType::CheckCanRegisterUser
+        | WorkerRequestType::SubmitToRelayer => &CREDENTIAL_PRF_RESULTS,
+        _ => &[],
+    }
+}
+
+/// base64url X25519 public key of the current session, returned by the Initialize handshake
+pub fn session_public_key() -> Result<String, String> {
+    let secret = Zeroizing::new(state::sealed_secrets_key()?);
+    Ok(base64_url_encode(
+        MontgomeryPoint::mul_base_clamped(*secret).as_bytes(),
+    ))
+}
+
+/// X25519 shared secret to ChaCha20-Poly1305 key; both public keys go into the HKDF info
+fn sealed_secret_cipher(
+    shared_secret: &MontgomeryPoint,
+    ephemeral_public_key: &[u8; 32],
+    session_public_key: &[u8; 32],
+) -> Result<ChaCha20Poly1305, String> {
+    // A low-order ephemeral key yields the identity: refuse rather than open under a known key
+    if shared_secret.as_bytes() == &[0u8; 32] {
+        return Err("key agreement produced a low-order point".to_string());
+    }
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(None, shared_secret.as_bytes())
+        .expand_multi_info(
+            &[
+                SEALED_SECRET_HKDF_INFO.as_bytes(),
+                ephemeral_public_key,
+                session_public_key,
+            ],
+            key.as_mut(),
+        )
+        .map_err(|_| "key derivation failed".to_string())?;
+    ChaCha20Poly1305::new_from_slice(key.as_ref()).map_err(|e| format!("invalid key: {}", e))
+}
+
+/// Opens a box sealed to the session key `secret` for the field at `path`
+pub fn open_sealed_secret(
+    secret: &[u8; 32],
+    path: &str,
+    sealed: &str,
+) -> Result<Zeroizing<String>, SealedSecretError> {
+    let invalid = |reason: String| SealedSecretError::Invalid {
+        path: path.to_string(),
+        reason,
+    };
+    let bytes = base64_url_decode(sealed.trim()).map_err(invalid)?;
+    if bytes.len() < 1 + 32 + 16 {
+        return Err(invalid(format!("sealed box is {} bytes", bytes.len())));
+    }
+    if bytes[0] != SEALED_SECRET_VERSION {
+        return Err(invalid(format!(
+            "unsupported sealed box version {}",
+            bytes[0]
+        )));
+    }
+    let mut ephemeral_public_key = [0u8; 32];
+    ephemeral_public_key.copy_from_slice(&bytes[1..33]);
+    let session_public_key = MontgomeryPoint::mul_base_clamped(*secret).to_bytes();
+    let shared_secret = MontgomeryPoint(ephemeral_public_key).mul_clamped(*secret);
+    let cipher = sealed_secret_cipher(&shared_secret, &ephemeral_public_key, &session_public_key)
+        .map_err(invalid)?;
+    let plaintext = cipher
+        .decrypt(
+            Nonce::from_slice(&[0u8; 12]),
+            Payload {
+                msg: &bytes[33..],
+                aad: path.as_bytes(),
+            },
+        )
+        .map(Zeroizing::new)
+        .map_err(|_| {
+            invalid("does not open with this session's key (sealed before a rotation?)".to_string())
+        })?;
+    String::from_utf8(plaintext.to_vec())
+        .map(Zeroizing::new)
+        .map_err(|_| invalid("secret is not UTF-8".to_string()))
+}
+
+/// Seals `plaintext` for the field at `path` to a session public key, as the TS layer does
+#[cfg(test)]
+pub fn seal_secret(
+    session_public_key: &[u8; 32],
+    path: &str,
+    plaintext: &str,
+) -> Result<String, String> {
+    let mut ephemeral_secret = [0u8; 32];
+    getrandom::getrandom(&mut ephemeral_secret).map_err(|e| e.to_string())?;
+    let ephemeral_public_key = MontgomeryPoint::mul_base_clamped(ephemeral_secret).to_bytes();
+    let shared_secret = MontgomeryPoint(*session_public_key).mul_clamped(ephemeral_secret);
+    let ciphertext =
+        sealed_secret_cipher(&shared_secret, &ephemeral_public_key, session_public_key)?
+            .encrypt(
+                Nonce::from_slice(&[0u8; 12]),
+                Payload {
+                    msg: plaintext.as_bytes(),
+                    aad: path.as_bytes(),
+                },
+            )
+            .map_err(|e| e.to_string())?;
+    Ok(base64_url_encode(
+        &[
+            &[SEALED_SECRET_VERSION][..],
+            &ephemeral_public_key,
+            &ciphertext,
+        ]
+        .concat(),
+    ))
+}
+
+/// Whether the payload's WorkerPolicy asks for sealed secrets
+pub fn encryption_required(payload: &Value) -> bool {
+    payload.pointer("/workerPolicy/requireEncryptedSecrets") == Some(&Value::Bool(true))
+}
+
+/// Sets `value` at `pointer`, creating the objects on the way
+fn set_pointer(target: &mut Value, pointer: &str, value: Value) {
+    let mut current = target;
+    for token in pointer.split('/').skip(1) {
+        if !current.is_object() {
+            *current = Value::Object(Default::default());
+        }
+        current = match current {
+            Value::Object(object) => object.entry(token.to_string()).or_insert(Value::Null),
+            _ => return,
+        };
+    }
+    *current = value;
+}
+
+/// Refuses secret fields set in plaintext
+fn check_no_plaintext(value: &Value, fields: &[&str]) -> Result<(), SealedSecretError> {
+    match fields
+        .iter()
+        .find(|path| value.pointer(path).is_some_and(|v| !v.is_null()))
+    {
+        Some(path) => Err(SealedSecretError::PlaintextRefused {
+            path: path.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Opens `sealed` ({ pointer: box }) into `target`, each box only into one of `fields`
+fn open_into(
+    target: &mut Value,
+    sealed: &HashMap<String, String>,
+    fields: &[&str],
+) -> Result<(), SealedSecretError> {
+    if sealed.is_empty() {
+        return Ok(());
+    }
+    let secret = Zeroizing::new(state::sealed_secrets_key().map_err(|reason| {
+        SealedSecretError::Invalid {
+            path: SEALED_SECRETS_FIELD.to_string(),
+            reason,
+        }
+    })?);
+    for (path, sealed_box) in sealed {
+        if !fields.contains(&path.as_str()) {
+            return Err(SealedSecretError::Invalid {
+                path: path.clone(),
+                reason: "not a secret field of this request".to_string(),
+            });
+        }
+        let plaintext = open_sealed_secret(&secret, path, sealed_box)?;
+        set_pointer(target, path, Value::String(plaintext.to_string()));
+    }
+    Ok(())
+}
+
+/// Dispatch step: records a requireEncryptedSecrets policy, refuses plaintext secrets once it
+/// is in force, and replaces `sealedSecrets` with the opened secrets
+pub fn open_request_secrets(
+    request_type: WorkerRequestType,
+    payload: &mut Value,
+) -> Result<(), SealedSecretError> {
+    if encryption_required(payload) {
+        state::set_require_encrypted_secrets();
+    }
+    let fields = secret_fields(request_type);
+    if state::require_encrypted_secrets() {
+        check_no_plaintext(payload, fields)?;
+    } else if check_no_plaintext(payload, fields).is_err() {
+        deprecations::note(&PLAINTEXT_SECRET).map_err(SealedSecretError::Deprecated)?;
+    }
+    let Some(sealed) = payload
+        .as_object_mut()
+        .and_then(|object| object.remove(SEALED_SECRETS_FIELD))
+    else {
+        return Ok(());
+    };
+    let sealed: HashMap<String, String> =
+        serde_json::from_value(sealed).map_err(|e| SealedSecretError::Invalid {
+            path: SEALED_SECRETS_FIELD.to_string(),
+            reason: format!("expected an object of sealed boxes: {}", e),
+        })?;
+    open_into(payload, &sealed, fields)
+}
+
+/// Opens the confirmation response's `sealed_secrets` into its PRF output and credential,
+/// refusing plaintext ones once requireEncryptedSecrets is in force
+pub fn open_confirmation_secrets(result: &mut ConfirmationResult) -> Result<(), SealedSecretError> {
+    let sealed = result.sealed_secrets.take().unwrap_or_default();
+    let plaintext = serde_json::json!({
+        "prf_output": result.prf_output,
+        "credential": result.credential,
+    });
+    if state::require_encrypted_secrets() {
+        check_no_plaintext(&plaintext, &CONFIRMATION_SECRET_FIELDS)?;
+    } else if check_no_plaintext(&plaintext, &CONFIRMATION_SECRET_FIELDS).is_err() {
+        deprecations::note(&PLAINTEXT_CONFIRMATION_SECRET)
+            .map_err(SealedSecretError::Deprecated)?;
+    }
+    if sealed.is_empty() {
+        return Ok(());
+    }
+    let mut opened = serde_json::json!({
+        "prf_output": result.prf_output.take(),
+        "credential": result.credential.take(),
+    });
+    open_into(&mut opened, &sealed, &CONFIRMATION_SECRET_FIELDS)?;
+    result.prf_output = opened["prf_output"].as_str().map(str::to_string);
+    result.credential = Some(opened["credential"].take()).filter(|c| !c.is_null());
+    Ok(())
+}
@@ -0,0 +1,266 @@
+// === STARTUP SELF-TEST ===
+// Some browser/WASM combinations miscompile SIMD paths, and a silently wrong hash or cipher
+// would be catastrophic for key handling. The Initialize handshake (and RunSelfTest) runs a
+// known-answer test per primitive against the spec vectors in kat_vectors.rs. Any failure
+// puts the worker into a refusing state: key-handling requests fail with SelfTestFailed until
+// the worker is re-initialized. VRF prove/verify lives in the VRF worker and is tested there.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use ed25519_dalek::{Signature, Signer, SigningKey};
+use hkdf::Hkdf;
+use log::{info, warn};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use wasm_bindgen::prelude::*;
+
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::SelfTestError;
+use crate::kat_vectors::*;
+use crate::state;
+use crate::types::worker_messages::WorkerRequestType;
+
+/// A named known-answer test; `Err` describes the mismatch
+pub type KnownAnswerTest = (&'static str, fn() -> Result<(), String>);
+
+/// Primitives checked at startup, in run order
+pub const KNOWN_ANSWER_TESTS: &[KnownAnswerTest] = &[
+    ("sha256", check_sha256),
+    ("chacha20poly1305", check_chacha20_poly1305),
+    ("hkdfSha256", check_hkdf_sha256),
+    ("ed25519", check_ed25519),
+    ("base64url", check_base64url),
+    ("borsh", check_borsh),
+];
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestResult {
+    #[wasm_bindgen(getter_with_clone)]
+    pub primitive: String,
+    pub passed: bool,
+    #[wasm_bindgen(js_name = "durationMs")]
+    pub duration_ms: f64,
+    /// What did not match the spec vector (absent when passed)
+    #[wasm_bindgen(getter_with_clone)]
+    pub error: Option<String>,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestReport {
+    /// False while the worker refuses key handling, even if every primitive of this run passed
+    pub passed: bool,
+    #[wasm_bindgen(getter_with_clone)]
+    pub results: Vec<SelfTestResult>,
+    #[wasm_bindgen(js_name = "totalDurationMs")]
+    pub total_duration_ms: f64,
+    /// Primitive the worker refuses key handling for (the first failure since Initialize)
+    #[wasm_bindgen(getter_with_clone, js_name = "failedPrimitive")]
+    pub failed_primitive: Option<String>,
+}
+
+/// Runs `tests` in order without touching worker state
+pub fn run_known_answer_tests(tests: &[KnownAnswerTest]) -> SelfTestReport {
+    let started = state::monotonic_ms();
+    let results: Vec<SelfTestResult> = tests
+        .iter()
+        .map(|(primitive, test)| {
+            let test_started = state::monotonic_ms();
+            let outcome = test();
+            SelfTestResult {
+                primitive: primitive.to_string(),
+                passed: outcome.is_ok(),
+                duration_ms: state::monotonic_ms() - test_started,
+                error: outcome.err(),
+            }
+        })
+        .collect();
+    SelfTestReport {
+        passed: results.iter().all(|r| r.passed),
+        failed_primitive: results
+            .iter()
+            .find(|r| !r.passed)
+            .map(|r| r.primitive.clone()),
+        results,
+        total_duration_ms: state::monotonic_ms() - started,
+    }
+}
+
+/// Runs KNOWN_ANSWER_TESTS and enters the refusing state on any failure.
+/// A passing run does not leave the refusing state; re-initializing does.
+pub fn run_self_test() -> SelfTestReport {
+    let mut report = run_known_answer_tests(KNOWN_ANSWER_TESTS);
+    for result in report.results.iter().filter(|r| !r.passed) {
+        warn!(
+            "RUST: Self-test failed for {}: {}",
+            result.primitive,
+            result.error.as_deref().unwrap_or("")
+        );
+        state::record_self_test_failure(&result.primitive);
+    }
+    report.failed_primitive = state::self_test_failure();
+    report.passed = report.failed_primitive.is_none();
+    info!(
+        "RUST: Self-test {} in {}ms",
+        if report.passed { "passed" } else { "failed" },
+        report.total_duration_ms
+    );
+    report
+}
+
+/// Refuses key-handling requests while the worker is in the refusing state
+pub fn check_key_handling_allowed(request_type: WorkerRequestType) -> Result<(), SelfTestError> {
+    if !request_type.handles_keys() {
+        return Ok(());
+    }
+    match state::self_test_failure() {
+        Some(primitive) => Err(SelfTestError::Failed { primitive }),
+        None => Ok(()),
+    }
+}
+
+// === KNOWN-ANSWER TESTS ===
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).expect("kat_vectors holds valid hex"))
+        .collect()
+}
+
+fn check_sha256() -> Result<(), String> {
+    for (message, digest) in SHA256_VECTORS {
+        if Sha256::digest(message).as_slice() != hex(digest) {
+            return Err(format!("digest of a {}-byte message", message.len()));
+        }
+    }
+    Ok(())
+}
+
+fn check_chacha20_poly1305() -> Result<(), String> {
+    let cipher = ChaCha20Poly1305::new_from_slice(&hex(CHACHA20_POLY1305_KEY))
+        .map_err(|e| format!("key setup: {}", e))?;
+    let nonce_bytes = hex(CHACHA20_POLY1305_NONCE);
+    let nonce = Nonce::from_slice(&nonce_bytes);
+    let aad = hex(CHACHA20_POLY1305_AAD);
+    let sealed = hex(CHACHA20_POLY1305_SEALED);
+
+    let encrypted = cipher
+        .encrypt(
+            nonce,
+            Payload {
+                msg: CHACHA20_POLY1305_PLAINTEXT,
+                aad: &aad,
+            },
+        )
+        .map_err(|e| format!("encrypt: {}", e))?;
+    if encrypted != sealed {
+        return Err("ciphertext or tag".to_string());
+    }
+    let decrypted = cipher
+        .decrypt(
+            nonce,
+            Payload {
+                msg: &sealed,
+                aad: &aad,
+            },
+        )
+        .map_err(|e| format!("decrypt: {}", e))?;
+    if decrypted != CHACHA20_POLY1305_PLAINTEXT {
+        return Err("decrypted plaintext".to_string());
+    }
+
+    let mut tampered = sealed;
+    let last = tampered.len() - 1;
+    tampered[last] ^= 1;
+    let forged = Payload {
+        msg: &tampered,
+        aad: &aad,
+    };
+    if cipher.decrypt(nonce, forged).is_ok() {
+        return Err("a tampered tag was accepted".to_string());
+    }
+    Ok(())
+}
+
+fn check_hkdf_sha256() -> Result<(), String> {
+    let hk = Hkdf::<Sha256>::new(Some(&hex(HKDF_SALT)), &hex(HKDF_IKM));
+    let expected = hex(HKDF_OKM);
+    let mut okm = vec![0u8; expected.len()];
+    hk.expand(&hex(HKDF_INFO), &mut okm)
+        .map_err(|e| format!("expand: {}", e))?;
+    if okm != expected {
+        return Err("output keying material".to_string());
+    }
+    Ok(())
+}
+
+fn check_ed25519() -> Result<(), String> {
+    let secret: [u8; 32] = hex(ED25519_SECRET_KEY)
+        .try_into()
+        .map_err(|_| "secret key length".to_string())?;
+    let signing_key = SigningKey::from_bytes(&secret);
+    let verifying_key = signing_key.verifying_key();
+    if verifying_key.to_bytes().as_slice() != hex(ED25519_PUBLIC_KEY) {
+        return Err("public key derivation".to_string());
+    }
+
+    let signature = signing_key.sign(ED25519_MESSAGE);
+    if signature.to_bytes().as_slice() != hex(ED25519_SIGNATURE) {
+        return Err("signature".to_string());
+    }
+    let expected = Signature::from_slice(&hex(ED25519_SIGNATURE))
+        .map_err(|e| format!("signature parse: {}", e))?;
+    verifying_key
+        .verify_strict(ED25519_MESSAGE, &expected)
+        .map_err(|e| format!("verify: {}", e))?;
+    if verifying_key.verify_strict(b"tampered", &expected).is_ok() {
+        return Err("a signature over another message was accepted".to_string());
+    }
+    Ok(())
+}
+
+fn check_base64url() -> Result<(), String> {
+    for (data, encoded) in BASE64URL_VECTORS {
+        if base64_url_encode(data) != *encoded {
+            return Err(format!("encoding of {:?}", encoded));
+        }
+        if base64_url_decode(encoded)?.as_slice() != *data {
+            return Err(format!("decoding of {:?}", encoded));
+        }
+    }
+    Ok(())
+}
+
+/// Encodes `value`, compares with the spec bytes, and decodes it back
+fn check_borsh_value<T>(label: &str, value: &T, expected: &str) -> Result<(), String>
+where
+    T: BorshSerialize + BorshDeserialize + PartialEq,
+{
+    let bytes = borsh::to_vec(value).map_err(|e| format!("{} encode: {}", label, e))?;
+    if bytes != hex(expected) {
+        return Err(format!("{} encoding", label));
+    }
+    let decoded = T::try_from_slice(&bytes).map_err(|e| format!("{} decode: {}", label, e))?;
+    if &decoded != value {
+        return Err(format!("{} round trip", label));
+    }
+    Ok(())
+}
+
+fn check_borsh() -> Result<(), String> {
+    check_borsh_value("u32", &BORSH_U32.0, BORSH_U32.1)?;
+    check_borsh_value("u128", &BORSH_U128.0, BORSH_U128.1)?;
+    check_borsh_value("string", &BORSH_STRING.0.to_string(), BORSH_STRING.1)?;
+    check_borsh_value(
+        "Some(u8)",
+        &Some(BORSH_OPTION_SOME_U8.0),
+        BORSH_OPTION_SOME_U8.1,
+    )?;
+    check_borsh_value("None", &None::<u8>, BORSH_OPTION_NONE)?;
+    check_borsh_value("Vec<u16>", &BORSH_VEC_U16.0.to_vec(), BORSH_VEC_U16.1)
+}
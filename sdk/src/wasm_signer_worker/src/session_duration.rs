@@ -0,0 +1,158 @@
+// === SESSION DURATION ===
+// WorkerPolicy.maxSessionDurationMs bounds how long key-using requests may go on without a
+// fresh passkey assertion. The session starts when the first key-using request succeeds and
+// expires once maxSessionDurationMs has passed on the worker's clock, at the boundary itself
+// (as the VRF worker's auto-lock does). From then on key-using requests fail with
+// SessionExpired, except those that prompt through the confirmation bridge
+// (WorkerRequestType::collects_credential). Their confirmation is where the worker vouches for
+// a credential's collection time (see elevation.rs): a credential collected at or after the
+// expiry starts a new session, and any other confirmation is refused there.
+//
+// Every new session gets the next session epoch. Signing intent tokens and dual-control
+// approvals record the epoch they were issued in and are refused in a later one, so nothing
+// confirmed in an expired session outlives it. WipeAllState also ends the session and moves to
+// the next epoch. Like messageSizeLimits, the duration is kept from the last policy that set
+// it; it applies from that request on. The session's start, epoch and duration travel in the
+// sealed worker state record (see worker_state.rs), so a session started in one worker expires
+// in the workers after it. The main thread pushes the epoch and duration to the
+// VRF worker (UPDATE_SESSION_POLICY), which refuses challenges past the duration and session
+// snapshots from an earlier epoch.
+
+use log::info;
+use serde::Serialize;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::error::{ConfigError, SessionError};
+use crate::state::{self, SessionRecord};
+use crate::types::worker_messages::WorkerRequestType;
+
+/// Returned by GetSessionStatus and included in DumpDiagnostics
+#[wasm_bindgen]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionStatus {
+    pub epoch: u32,
+    /// None until the first key-using request succeeds
+    #[wasm_bindgen(js_name = "startedAtMs")]
+    pub started_at_ms: Option<f64>,
+    /// None: sessions do not expire
+    #[wasm_bindgen(js_name = "maxSessionDurationMs")]
+    pub max_session_duration_ms: Option<u32>,
+    #[wasm_bindgen(js_name = "expiresAtMs")]
+    pub expires_at_ms: Option<f64>,
+    /// Time left before key-using requests are refused; 0 once expired
+    #[wasm_bindgen(js_name = "remainingMs")]
+    pub remaining_ms: Option<f64>,
+    pub expired: bool,
+}
+
+fn expires_at_ms(session: &SessionRecord) -> Option<f64> {
+    Some(session.started_at_ms? + session.max_duration_ms? as f64)
+}
+
+/// The expiry error, if the session expired at or before `now_ms`
+fn expired_at(now_ms: f64) -> Option<SessionError> {
+    let session = state::session();
+    match (session.started_at_ms, expires_at_ms(&session)) {
+        (Some(started_at_ms), Some(expired_at_ms)) if now_ms >= expired_at_ms => {
+            Some(SessionError::Expired {
+                started_at_ms,
+                expired_at_ms,
+            })
+        }
+        _ => None,
+    }
+}
+
+pub fn status(now_ms: f64) -> SessionStatus {
+    let session = state::session();
+    let expires_at_ms = expires_at_ms(&session);
+    SessionStatus {
+        epoch: session.epoch,
+        started_at_ms: session.started_at_ms,
+        max_session_duration_ms: session.max_duration_ms,
+        expires_at_ms,
+        remaining_ms: expires_at_ms.map(|expires_at_ms| (expires_at_ms - now_ms).max(0.0)),
+        expired: expires_at_ms.is_some_and(|expires_at_ms| now_ms >= expires_at_ms),
+    }
+}
+
+/// Dispatch step: a request whose policy sets maxSessionDurationMs applies it from this
+/// request on
+pub fn apply_policy_duration(payload: &Value) -> Result<(), ConfigError> {
+    let Some(duration) = payload
+        .pointer("/workerPolicy/maxSessionDurationMs")
+        .filter(|duration| !duration.is_null())
+    else {
+        return Ok(());
+    };
+    let max_duration_ms = duration
+        .as_u64()
+        .and_then(|ms| u32::try_from(ms).ok())
+        .filter(|ms| *ms > 0)
+        .ok_or_else(|| ConfigError::InvalidValue {
+            field: "maxSessionDurationMs",
+            reason: format!(
+                "must be a positive number of milliseconds, got {}",
+                duration
+            ),
+        })?;
+    state::set_max_session_duration(max_duration_ms);
+    Ok(())
+}
+
+/// Dispatch step: once the session expired, key-using requests are refused, except those
+/// whose confirmation can renew it
+pub fn check_session_allowed(request_type: WorkerRequestType) -> Result<(), SessionError> {
+    if !request_type.handles_keys() || request_type.collects_credential() {
+        return Ok(());
+    }
+    match expired_at(state::now_ms()) {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Dispatch step after the handler: the first key-using request that succeeds starts the
+/// session (results reporting `success: false` are not successes)
+pub fn note_completed(request_type: WorkerRequestType, response: &Value) {
+    if request_type.handles_keys() && response.get("success") != Some(&Value::Bool(false)) {
+        state::start_session(state::now_ms());
+    }
+}
+
+/// For a confirmed confirmation: an expired session is renewed by a credential collected at
+/// or after its expiry (`collected_at_ms`, as vouched for by elevation::collection_time), and
+/// the confirmation is refused otherwise
+pub fn renew_with_credential(collected_at_ms: Option<f64>) -> Result<(), SessionError> {
+    let Some(expired) = expired_at(state::now_ms()) else {
+        return Ok(());
+    };
+    match (&expired, collected_at_ms) {
+        (SessionError::Expired { expired_at_ms, .. }, Some(collected_at_ms))
+            if collected_at_ms >= *expired_at_ms =>
+        {
+            let epoch = state::renew_session(collected_at_ms);
+            info!(
+                "RUST: Session renewed by a fresh credential (epoch {})",
+                epoch
+            );
+            Ok(())
+        }
+        _ => Err(expired),
+    }
+}
+
+/// Refuses a `token` issued in session epoch `issued_in` once the session has moved on
+pub fn check_epoch(token: &'static str, issued_in: u32) -> Result<(), SessionError> {
+    let current = state::session_epoch();
+    if issued_in == current {
+        return Ok(());
+    }
+    Err(SessionError::StaleEpoch {
+        token,
+        issued_in,
+        current,
+    })
+}
@@ -0,0 +1,59 @@
+// === SIGNING PHASES ===
+// SignTransactionsWithActions runs a batch through the same phases every time: checks on the
+// batch, the VRF challenge, the user's confirmation, the credential it collected, the
+// contract's verification of that credential, and signing. SignTransactionsWithFreshChallenge
+// runs that same flow in one round trip (the challenge comes over the peer port, see
+// peer_channel.rs) and reports the phase a failure happened in, so a caller that no longer
+// sees the individual hops can still tell a declined prompt from a failed verification.
+//
+// The flow marks each phase on a PhaseTracker as it enters it; callers that do not report
+// phases pass a tracker nobody reads.
+
+use std::cell::Cell;
+
+/// A phase of the signing flow, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SignPhase {
+    /// Batch, policy and key checks before the user is prompted
+    #[default]
+    Preparation,
+    /// The VRF challenge, from the VRF worker over the peer port
+    Challenge,
+    /// The confirmation prompt and its decision
+    Confirmation,
+    /// The credential and PRF output collected with the confirmation
+    CredentialCollection,
+    /// The contract's verification of the credential
+    Verification,
+    /// Decryption of the key, signing and (with `broadcast`) broadcasting
+    Signing,
+}
+
+impl SignPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignPhase::Preparation => "preparation",
+            SignPhase::Challenge => "challenge",
+            SignPhase::Confirmation => "confirmation",
+            SignPhase::CredentialCollection => "credentialCollection",
+            SignPhase::Verification => "verification",
+            SignPhase::Signing => "signing",
+        }
+    }
+}
+
+/// The phase a signing flow is in
+#[derive(Debug, Default)]
+pub struct PhaseTracker {
+    current: Cell<SignPhase>,
+}
+
+impl PhaseTracker {
+    pub fn enter(&self, phase: SignPhase) {
+        self.current.set(phase);
+    }
+
+    pub fn current(&self) -> SignPhase {
+        self.current.get()
+    }
+}
@@ -0,0 +1,215 @@
+// === BATCH SIGNATURE VERIFICATION ===
+// Verifies several signatures in one call, e.g. the transfer payload HMACs, assertion
+// signatures and contract attestations checked while linking a device. Inputs of a single
+// Ed25519 kind go through ed25519-dalek batch verification; mixed-kind inputs (and HMACs)
+// are verified one by one. Each item gets its own result, distinguishing undecodable
+// keys/signatures from signatures that don't verify.
+
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::encoders::base64_url_decode;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SignatureKind {
+    /// Ed25519 over the message bytes
+    Ed25519,
+    /// Ed25519 over sha256(message), as NEAR transaction signatures are
+    Ed25519Sha256,
+    /// HMAC-SHA256 tag; `publicKey` carries the (shared) MAC key
+    HmacSha256,
+}
+
+impl SignatureKind {
+    fn is_ed25519(&self) -> bool {
+        matches!(self, SignatureKind::Ed25519 | SignatureKind::Ed25519Sha256)
+    }
+}
+
+/// One signature to check. Key, message and signature are base64url; Ed25519 keys may
+/// also be given in NEAR "ed25519:<base58>" format.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureVerifyItem {
+    pub public_key: String,
+    pub message: String,
+    pub signature: String,
+    pub kind: SignatureKind,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyFailure {
+    KeyEncoding,
+    MessageEncoding,
+    SignatureEncoding,
+    /// Well-formed inputs, but the signature does not verify
+    SignatureMismatch,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyResult {
+    pub index: u32,
+    pub valid: bool,
+    pub failure: Option<VerifyFailure>,
+    pub detail: Option<String>,
+}
+
+impl VerifyResult {
+    fn valid(index: usize) -> Self {
+        Self {
+            index: index as u32,
+            valid: true,
+            failure: None,
+            detail: None,
+        }
+    }
+
+    fn invalid(index: usize, failure: VerifyFailure, detail: String) -> Self {
+        Self {
+            index: index as u32,
+            valid: false,
+            failure: Some(failure),
+            detail: Some(detail),
+        }
+    }
+}
+
+enum DecodedItem {
+    Ed25519 {
+        key: Box<VerifyingKey>,
+        message: Vec<u8>,
+        signature: Signature,
+    },
+    Hmac {
+        key: Vec<u8>,
+        message: Vec<u8>,
+        tag: Vec<u8>,
+    },
+}
+
+pub(crate) fn decode_ed25519_key(public_key: &str) -> Result<VerifyingKey, String> {
+    let bytes = match public_key.strip_prefix("ed25519:") {
+        Some(b58) => bs58::decode(b58)
+            .into_vec()
+            .map_err(|e| format!("Invalid base58 public key: {}", e))?,
+        None => base64_url_decode(public_key)?,
+    };
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|b: Vec<u8>| format!("Ed25519 public key must be 32 bytes, got {}", b.len()))?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| format!("Invalid Ed25519 public key: {}", e))
+}
+
+fn decode_item(item: &SignatureVerifyItem) -> Result<DecodedItem, (VerifyFailure, String)> {
+    let message =
+        base64_url_decode(&item.message).map_err(|e| (VerifyFailure::MessageEncoding, e))?;
+    let signature =
+        base64_url_decode(&item.signature).map_err(|e| (VerifyFailure::SignatureEncoding, e))?;
+
+    match item.kind {
+        SignatureKind::Ed25519 | SignatureKind::Ed25519Sha256 => {
+            let key = decode_ed25519_key(&item.public_key)
+                .map_err(|e| (VerifyFailure::KeyEncoding, e))?;
+            let signature = Signature::from_slice(&signature).map_err(|_| {
+                (
+                    VerifyFailure::SignatureEncoding,
+                    format!(
+                        "Ed25519 signature must be 64 bytes, got {}",
+                        signature.len()
+                    ),
+                )
+            })?;
+            let message = if item.kind == SignatureKind::Ed25519Sha256 {
+                Sha256::digest(&message).to_vec()
+            } else {
+                message
+            };
+            Ok(DecodedItem::Ed25519 {
+                key: Box::new(key),
+                message,
+                signature,
+            })
+        }
+        SignatureKind::HmacSha256 => {
+            let key =
+                base64_url_decode(&item.public_key).map_err(|e| (VerifyFailure::KeyEncoding, e))?;
+            Ok(DecodedItem::Hmac {
+                key,
+                message,
+                tag: signature,
+            })
+        }
+    }
+}
+
+fn verify_decoded(index: usize, item: &DecodedItem) -> VerifyResult {
+    let verified = match item {
+        DecodedItem::Ed25519 {
+            key,
+            message,
+            signature,
+        } => key.verify(message, signature).is_ok(),
+        DecodedItem::Hmac { key, message, tag } => {
+            let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key)
+                .expect("HMAC accepts keys of any length");
+            mac.update(message);
+            mac.verify_slice(tag).is_ok()
+        }
+    };
+    if verified {
+        VerifyResult::valid(index)
+    } else {
+        VerifyResult::invalid(
+            index,
+            VerifyFailure::SignatureMismatch,
+            "Signature does not verify".to_string(),
+        )
+    }
+}
+
+/// Verifies `items`, returning one result per item in input order.
+/// When every decodable item is of the same Ed25519 kind they are checked with a single
+/// batch verification; if the batch fails, items are re-checked individually to find
+/// which ones are bad.
+pub fn verify_signatures(items: &[SignatureVerifyItem]) -> Vec<VerifyResult> {
+    let decoded: Vec<Result<DecodedItem, (VerifyFailure, String)>> =
+        items.iter().map(decode_item).collect();
+
+    let first_kind = items.first().map(|item| item.kind);
+    let single_ed25519_kind = first_kind
+        .is_some_and(|kind| kind.is_ed25519() && items.iter().all(|item| item.kind == kind));
+
+    let mut messages: Vec<&[u8]> = Vec::new();
+    let mut signatures: Vec<Signature> = Vec::new();
+    let mut keys: Vec<VerifyingKey> = Vec::new();
+    for item in decoded.iter().flatten() {
+        if let DecodedItem::Ed25519 {
+            key,
+            message,
+            signature,
+        } = item
+        {
+            messages.push(message);
+            signatures.push(*signature);
+            keys.push(**key);
+        }
+    }
+
+    let batch_verified = single_ed25519_kind
+        && messages.len() > 1
+        && ed25519_dalek::verify_batch(&messages, &signatures, &keys).is_ok();
+
+    decoded
+        .iter()
+        .enumerate()
+        .map(|(i, item)| match item {
+            Err((failure, detail)) => VerifyResult::invalid(i, *failure, detail.clone()),
+            Ok(_) if batch_verified => VerifyResult::valid(i),
+            Ok(item) => verify_decoded(i, item),
+        })
+        .collect()
+}
@@ -0,0 +1,233 @@
+// === SIGNED TRANSACTION DECODER ===
+// Explains a signed transaction from any wallet (e.g. pasted from an explorer) with the
+// pipeline confirmations are rendered with: the transaction is decoded field by field, its
+// signature verified against the embedded public key, and its actions shown as the
+// txSigningRequests entry and summary blocks of a one-transaction confirmation
+// (confirm_tx_details.rs, confirmation_blocks.rs). No key material or worker session is
+// involved.
+//
+// Borsh actions are not length-prefixed, so an action whose discriminant this SDK does not
+// know (a newer protocol version's) cannot be skipped: it decodes into one UnknownAction
+// holding the bytes from its discriminant up to the signature, which also hold any actions
+// after it, and an UnknownActions warning leads the summary blocks. The signature is then
+// read from the end of the transaction (ed25519 only). Malformed input fails with the byte
+// offset where decoding stopped.
+
+use borsh::BorshDeserialize;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::actions::ActionParams;
+use crate::confirmation_blocks::{batch_summary_blocks, ConfirmationSummaryBlock, SummaryPolicy};
+use crate::encoders::{base64_standard_decode, base64_standard_encode};
+use crate::handlers::confirm_tx_details::confirmation_tx_signing_requests_json;
+use crate::tx_audit::{action_params_of, public_key_string};
+use crate::types::{Action, PublicKey};
+
+/// Highest Action discriminant this SDK decodes (DeleteAccount)
+const LAST_KNOWN_ACTION: u8 = 7;
+/// Key type byte and signature of an ed25519 signature
+const ED25519_SIGNATURE_LEN: usize = 1 + 64;
+
+/// An action whose discriminant this SDK does not know
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "action_type", rename_all = "camelCase")]
+pub struct UnknownAction {
+    pub discriminant: u8,
+    /// Standard base64 of the action's bytes, discriminant included, and of those of any
+    /// actions after it
+    pub raw_bytes: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum DecodedAction {
+    Known(ActionParams),
+    Unknown(UnknownAction),
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedTransaction {
+    pub signer_id: String,
+    pub public_key: String,
+    pub nonce: String,
+    pub receiver_id: String,
+    /// Base58
+    pub block_hash: String,
+    /// SHA-256 of the borsh-serialized transaction, base58
+    pub tx_hash_b58: String,
+    /// Ed25519 signature over the transaction hash verifies against the embedded public key
+    pub signature_valid: bool,
+    /// Number of actions the transaction declares
+    pub action_count: u32,
+    /// Actions in request form; an UnknownAction is always last
+    pub actions: Vec<DecodedAction>,
+    /// The transaction's txSigningRequests entry, as a confirmation would show it, with any
+    /// UnknownAction appended to its actions
+    pub tx_tree: Value,
+    pub summary_blocks: Vec<ConfirmationSummaryBlock>,
+}
+
+/// Borsh reader that knows its offset, for errors naming it
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn error(&self, offset: usize, what: &str, detail: impl std::fmt::Display) -> String {
+        format!(
+            "Malformed transaction at byte {}: {}: {}",
+            offset, what, detail
+        )
+    }
+
+    /// Deserializes a `T`; on failure the offset is where decoding stopped: the end of
+    /// truncated input, or just past the bytes that made no sense (an enum tag, a string)
+    fn read<T: BorshDeserialize>(&mut self, what: &str) -> Result<T, String> {
+        let mut rest = &self.bytes[self.offset..];
+        let before = rest.len();
+        let result = T::deserialize(&mut rest);
+        let offset = self.offset + (before - rest.len());
+        match result {
+            Ok(value) => {
+                self.offset = offset;
+                Ok(value)
+            }
+            Err(e) => Err(self.error(offset, what, e)),
+        }
+    }
+
+    fn peek(&self, what: &str) -> Result<u8, String> {
+        self.bytes
+            .get(self.offset)
+            .copied()
+            .ok_or_else(|| self.error(self.offset, what, "unexpected end of input"))
+    }
+
+    fn read_public_key(&mut self, what: &str) -> Result<PublicKey, String> {
+        let key_type = self.peek(what)?;
+        if key_type != 0 {
+            return Err(self.error(
+                self.offset,
+                what,
+                format!("key type {} is not supported, only ed25519 (0)", key_type),
+            ));
+        }
+        self.read(what)
+    }
+}
+
+/// Decodes `signed_tx_base64` (standard base64 borsh SignedTransaction, from any wallet) and
+/// renders it like a confirmation. A bad signature is reported in the result; only inputs that
+/// do not decode are errors.
+pub fn inspect_signed_transaction(signed_tx_base64: &str) -> Result<DecodedTransaction, String> {
+    let bytes = base64_standard_decode(signed_tx_base64.trim())
+        .map_err(|e| format!("Invalid signed transaction: {}", e))?;
+    let mut reader = Reader {
+        bytes: &bytes,
+        offset: 0,
+    };
+
+    let signer_id: String = reader.read("signerId")?;
+    let public_key = reader.read_public_key("publicKey")?;
+    let nonce: u64 = reader.read("nonce")?;
+    let receiver_id: String = reader.read("receiverId")?;
+    let block_hash: [u8; 32] = reader.read("blockHash")?;
+    let action_count: u32 = reader.read("actions length")?;
+
+    let mut known = Vec::new();
+    let mut unknown = None;
+    for index in 0..action_count {
+        let what = format!("actions[{}]", index);
+        let discriminant = reader.peek(&what)?;
+        if discriminant <= LAST_KNOWN_ACTION {
+            known.push(reader.read::<Action>(&what)?);
+            continue;
+        }
+        // The rest, up to the signature, cannot be told apart
+        let signature_start = bytes.len().saturating_sub(ED25519_SIGNATURE_LEN);
+        if signature_start <= reader.offset {
+            return Err(reader.error(
+                reader.offset,
+                &what,
+                format!(
+                    "unknown action type {} with no room for a signature after it",
+                    discriminant
+                ),
+            ));
+        }
+        unknown = Some(UnknownAction {
+            discriminant,
+            raw_bytes: base64_standard_encode(&bytes[reader.offset..signature_start]),
+        });
+        reader.offset = signature_start;
+        break;
+    }
+    let signed_len = reader.offset;
+
+    let signature_type = reader.peek("signature")?;
+    if signature_type != 0 {
+        return Err(reader.error(
+            reader.offset,
+            "signature",
+            format!(
+                "key type {} is not supported, only ed25519 (0)",
+                signature_type
+            ),
+        ));
+    }
+    let signature: crate::types::Signature = reader.read("signature")?;
+    if reader.offset != bytes.len() {
+        return Err(reader.error(
+            reader.offset,
+            "signature",
+            format!("{} trailing bytes", bytes.len() - reader.offset),
+        ));
+    }
+
+    // Hashed as received: the bytes of an unknown action cannot be serialized again
+    let tx_hash = Sha256::digest(&bytes[..signed_len]);
+    let signature_valid = VerifyingKey::from_bytes(&public_key.key_data).is_ok_and(|key| {
+        key.verify(&tx_hash, &Signature::from_bytes(&signature.signature_data))
+            .is_ok()
+    });
+
+    let params: Vec<ActionParams> = known.iter().map(action_params_of).collect();
+    let unknown_actions = match &unknown {
+        Some(_) => (action_count as usize) - known.len(),
+        None => 0,
+    };
+    let policy = SummaryPolicy::default()
+        .with_signing_key(Some(public_key.key_data))
+        .with_unknown_actions(unknown_actions);
+    let batch = [(receiver_id.clone(), params.clone())];
+    let mut tx_tree =
+        confirmation_tx_signing_requests_json(&batch, &[None], None, Some(&policy)).remove(0);
+    let summary_blocks = batch_summary_blocks(&batch, &[None], &policy).remove(0);
+
+    let mut actions: Vec<DecodedAction> = params.into_iter().map(DecodedAction::Known).collect();
+    if let Some(unknown) = unknown {
+        if let Some(shown) = tx_tree["actions"].as_array_mut() {
+            shown.push(serde_json::json!(unknown));
+        }
+        actions.push(DecodedAction::Unknown(unknown));
+    }
+
+    Ok(DecodedTransaction {
+        signer_id,
+        public_key: public_key_string(&public_key),
+        nonce: nonce.to_string(),
+        receiver_id,
+        block_hash: bs58::encode(block_hash).into_string(),
+        tx_hash_b58: bs58::encode(tx_hash).into_string(),
+        signature_valid,
+        action_count,
+        actions,
+        tx_tree,
+        summary_blocks,
+    })
+}
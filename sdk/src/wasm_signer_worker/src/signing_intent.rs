@@ -0,0 +1,172 @@
+// === SIGNING INTENTS ===
+// Queue-and-sign-later: CreateSigningIntent runs the normal confirmation and returns a token
+// recording what the user confirmed; ExecuteSigningIntent signs that batch later (say, once
+// connectivity returns) with a fresh nonce and block hash and no confirmation UI. The token is
+// the intent's CBOR bytes with an HMAC-SHA256 under a key the worker generates once and keeps
+// in the sealed worker state record (state.rs, worker_state.rs), wrapped in a CBOR envelope
+// encoded as base64url. Tokens verify in any later worker handed that record; WipeAllState, or
+// a record that does not open, replaces the key and invalidates every token. It
+// also records the session epoch it was issued in, and stops executing once the session is
+// renewed after maxSessionDurationMs (see session_duration.rs).
+// Execution recomputes the digest from the supplied transactions, so the caller cannot swap
+// the batch, and each intent executes at most once.
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::actions::ActionParams;
+use crate::config::{
+    DEFAULT_MAX_SIGNING_INTENT_TTL_MS, MAX_SIGNING_INTENT_TTL_MS, SIGNING_INTENT_MAC_DOMAIN,
+    SIGNING_INTENT_VERSION,
+};
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::SigningIntentError;
+use crate::handlers::confirm_tx_details::compute_intent_digest_from_js_inputs;
+use crate::handlers::TransactionPayload;
+use crate::types::handlers::WorkerPolicy;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningIntent {
+    pub version: u32,
+    /// Random ID, consumed on execution
+    pub intent_id: String,
+    pub near_account_id: String,
+    /// Digest of the confirmed receivers and actions (compute_intent_digest_from_js_inputs)
+    pub intent_digest: String,
+    pub tx_count: u32,
+    /// Request ID of the confirmation the user approved
+    pub confirmation_request_id: String,
+    pub issued_at_ms: f64,
+    pub expires_at_ms: f64,
+    /// Session epoch the intent was issued in
+    pub session_epoch: u32,
+}
+
+/// What is handed to the caller: the intent's exact CBOR bytes and the worker's MAC over them
+#[derive(Serialize, Deserialize)]
+struct SignedIntent {
+    #[serde(with = "serde_bytes")]
+    intent: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    mac: Vec<u8>,
+}
+
+/// Longest intent lifetime the policy allows
+pub fn signing_intent_ttl_ceiling_ms(worker_policy: Option<&WorkerPolicy>) -> u32 {
+    worker_policy
+        .and_then(|p| p.max_signing_intent_ttl_ms)
+        .unwrap_or(DEFAULT_MAX_SIGNING_INTENT_TTL_MS)
+        .min(MAX_SIGNING_INTENT_TTL_MS)
+}
+
+/// The signer account and intent digest of a batch. Every transaction must be signed by the
+/// same account.
+pub fn signing_intent_payload(
+    tx_signing_requests: &[TransactionPayload],
+) -> Result<(String, String), String> {
+    let near_account_id = match tx_signing_requests.first() {
+        Some(tx) => tx.near_account_id.clone(),
+        None => return Err("No transactions provided".to_string()),
+    };
+    if tx_signing_requests
+        .iter()
+        .any(|tx| tx.near_account_id != near_account_id)
+    {
+        return Err(
+            "All transactions of a signing intent must share one nearAccountId".to_string(),
+        );
+    }
+    let receivers_and_actions = tx_signing_requests
+        .iter()
+        .map(|tx| {
+            let actions: Vec<ActionParams> = tx
+                .parsed_actions()
+                .map_err(|e| format!("Failed to parse actions: {}", e))?;
+            Ok((tx.receiver_id.clone(), actions))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let digest = compute_intent_digest_from_js_inputs(&receivers_and_actions, None)?;
+    Ok((near_account_id, digest))
+}
+
+fn intent_mac(key: &[u8; 32], intent_cbor: &[u8]) -> Hmac<Sha256> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts any key size");
+    mac.update(SIGNING_INTENT_MAC_DOMAIN);
+    mac.update(intent_cbor);
+    mac
+}
+
+/// MACs the intent under `key` and returns the base64url token
+pub fn issue_signing_intent(intent: &SigningIntent, key: &[u8; 32]) -> Result<String, String> {
+    let mut intent_cbor = Vec::new();
+    ciborium::into_writer(intent, &mut intent_cbor)
+        .map_err(|e| format!("Failed to encode signing intent: {}", e))?;
+    let mac = intent_mac(key, &intent_cbor)
+        .finalize()
+        .into_bytes()
+        .to_vec();
+
+    let mut envelope = Vec::new();
+    ciborium::into_writer(
+        &SignedIntent {
+            intent: intent_cbor,
+            mac,
+        },
+        &mut envelope,
+    )
+    .map_err(|e| format!("Failed to encode signing intent envelope: {}", e))?;
+    Ok(base64_url_encode(&envelope))
+}
+
+/// Checks the token's MAC under `key`, its expiry against `now_ms` (the earlier of the
+/// embedded expiry and `issued + ttl_ceiling_ms`, so a stricter policy also shortens
+/// outstanding intents), and that `near_account_id` and `intent_digest` are the confirmed
+/// ones. Replay is checked by the caller (state::consume_signing_intent).
+pub fn verify_signing_intent(
+    token: &str,
+    key: &[u8; 32],
+    near_account_id: &str,
+    intent_digest: &str,
+    now_ms: f64,
+    ttl_ceiling_ms: u32,
+) -> Result<SigningIntent, SigningIntentError> {
+    let envelope = base64_url_decode(token.trim()).map_err(SigningIntentError::Invalid)?;
+    let signed: SignedIntent = ciborium::from_reader(envelope.as_slice())
+        .map_err(|e| SigningIntentError::Invalid(format!("Invalid envelope: {}", e)))?;
+    intent_mac(key, &signed.intent)
+        .verify_slice(&signed.mac)
+        .map_err(|_| {
+            SigningIntentError::Invalid(
+                "MAC does not verify (tampered, or issued under a wiped key)".to_string(),
+            )
+        })?;
+
+    let intent: SigningIntent = ciborium::from_reader(signed.intent.as_slice())
+        .map_err(|e| SigningIntentError::Invalid(format!("Invalid intent: {}", e)))?;
+    if intent.version != SIGNING_INTENT_VERSION {
+        return Err(SigningIntentError::Invalid(format!(
+            "Unsupported signing intent version {}",
+            intent.version
+        )));
+    }
+    let expires_at_ms = intent
+        .expires_at_ms
+        .min(intent.issued_at_ms + ttl_ceiling_ms as f64);
+    if expires_at_ms.is_nan() || expires_at_ms <= now_ms {
+        return Err(SigningIntentError::Expired { expires_at_ms });
+    }
+    if intent.near_account_id != near_account_id {
+        return Err(SigningIntentError::PayloadMismatch(format!(
+            "intent was confirmed for {}, not {}",
+            intent.near_account_id, near_account_id
+        )));
+    }
+    if intent.intent_digest != intent_digest {
+        return Err(SigningIntentError::PayloadMismatch(
+            "transactions differ from the confirmed ones".to_string(),
+        ));
+    }
+    Ok(intent)
+}
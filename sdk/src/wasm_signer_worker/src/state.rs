@@ -0,0 +1,1631 @@
+// === WORKER REQUEST STATE ===
+// Per-request bookkeeping held in worker memory for the lifetime of a signing request:
+// the pending-request registry, outstanding confirmation nonces, NEAR nonces reserved by the
+// main thread, and an audit log of request outcomes. Session keys also live here: their
+// secret keys leave worker memory only in the sealed worker state record (see
+// worker_state.rs), as does the per-session recent-receivers cache.
+// Recently confirmed transaction batches are kept too, to diff dapp re-requests against,
+// and the responses of completed requests that carried an idempotency key; both travel in
+// the sealed record as well, so they reach the next worker. Signing intents and elevation
+// challenges are MACed under a key that travels in the record, alongside the IDs of executed
+// intents and accepted challenges.
+// Remote confirmation sessions keep their per-request session key here until they expire,
+// and travel in the record with the used approvals.
+// Confirmation nonces carry the time they were issued, which bounds how fresh the credential
+// answering them can be (see elevation.rs).
+// First approvals of dual-control batches are MACed under the same key and held by the TS
+// layer; the IDs of completed ones travel in the record (see dual_control.rs). The session
+// bounded by maxSessionDurationMs is kept with its start and epoch, and travels in the record
+// (see session_duration.rs).
+// Everything tied to a NEAR account (nonce reservations, the audit log, request counters,
+// recent receivers and confirmed batches) lives in that account's AccountState, reached only
+// through its AccountId, so one account's requests cannot read or release another's.
+// Time is read through the state's Clock, so tests can replace it with a ManualClock.
+// WASM workers are single-threaded, so state lives in a thread_local.
+
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+use zeroize::Zeroize;
+
+use crate::actions::ActionParams;
+use crate::background_refresh;
+use crate::clock::{Clock, SharedClock};
+use crate::config::{
+    AUDIT_LOG_MAX_ENTRIES, DEFAULT_MAX_CONCURRENT_REQUESTS, DEFAULT_MAX_QUEUED_REQUESTS,
+    IDEMPOTENCY_CACHE_LIMIT, MAX_ACTIVE_ACCOUNTS, MAX_SESSION_KEYS, RECENT_CONFIRMED_INTENTS_LIMIT,
+    RECENT_RECEIVERS_CACHE_TTL_MS,
+};
+use crate::confirmation_origin;
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::SignerErrorCode;
+use crate::known_authenticators::AuthenticatorModel;
+use crate::peer_channel;
+#[cfg(feature = "device-linking")]
+use crate::remote_confirmation::RemoteSession;
+use crate::rpc_endpoints::RpcEndpoints;
+use crate::types::handlers::MessageSizeLimits;
+use crate::types::{AccountId, Balance};
+use crate::wire_format::WireFormat;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub timestamp_ms: f64,
+    pub request_id: String,
+    pub operation: String,
+    pub outcome: String,
+    pub detail: Option<String>,
+    /// RPC endpoints the request used (signing requests that got past confirmation)
+    pub rpc_endpoints: Option<RpcEndpoints>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestPhase {
+    /// Admitted but waiting for a free slot
+    Queued,
+    /// Holds one of the max-concurrent slots
+    Running,
+}
+
+impl RequestPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RequestPhase::Queued => "queued",
+            RequestPhase::Running => "running",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestLimits {
+    pub max_concurrent: usize,
+    pub max_queued: usize,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            max_concurrent: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            max_queued: DEFAULT_MAX_QUEUED_REQUESTS,
+        }
+    }
+}
+
+/// Snapshot of a registry entry (for ListPendingRequests)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingRequestInfo {
+    pub request_id: String,
+    pub operation: String,
+    pub phase: RequestPhase,
+    pub age_ms: f64,
+    pub admitted_at_ms: f64,
+}
+
+struct PendingRequest {
+    request_id: String,
+    operation: String,
+    phase: RequestPhase,
+    created_ms: f64,
+    /// Waker of the queued handler waiting for a slot
+    waker: Option<Waker>,
+}
+
+/// Counts of what WipeAllState discarded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WipeSummary {
+    pub cancelled_queued_requests: usize,
+    pub running_requests: usize,
+    pub cleared_confirmations: usize,
+    pub released_nonce_reservations: usize,
+    pub cleared_session_keys: usize,
+    /// Whether a peer port to the VRF worker was connected (and is now closed)
+    pub closed_peer_port: bool,
+    /// Whether a background refresh was running (and is now stopped)
+    pub stopped_background_refresh: bool,
+}
+
+/// A restricted function-call key generated in the worker for a game/app session
+pub struct SessionKey {
+    pub signing_key: ed25519_dalek::SigningKey,
+    pub near_account_id: String,
+    pub receiver_id: String,
+    /// Allowed methods (empty: any method on receiver_id)
+    pub method_names: Vec<String>,
+    /// Allowance in yoctoNEAR, as registered on-chain with the AddKey action
+    pub allowance: Balance,
+    /// Gas fees charged locally against the allowance so far
+    pub spent: Balance,
+    pub expires_at_ms: f64,
+}
+
+/// A receiver the account recently sent a transaction to
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentReceiver {
+    pub receiver_id: String,
+    pub last_seen_ms: f64,
+}
+
+/// An account's receiver history as fetched from the indexer (most recent first)
+struct CachedReceivers {
+    receivers: Vec<RecentReceiver>,
+    fetched_ms: f64,
+}
+
+/// A transaction batch the user confirmed, kept to diff later re-requests against
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmedIntent {
+    pub intent_digest: String,
+    /// Receivers with their FunctionCall actions (the only actions diffed)
+    pub receivers_and_actions: Vec<(String, Vec<ActionParams>)>,
+}
+
+/// A completed request's response, kept under its idempotency key
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletedRequest {
+    pub idempotency_key: String,
+    /// Digest of the request type and payload the key was first used with
+    pub payload_digest: String,
+    pub response: serde_json::Value,
+}
+
+/// Session state of one NEAR account
+#[derive(Default)]
+struct AccountState {
+    /// NEAR nonces reserved by the main thread, keyed by request ID
+    nonce_reservations: HashMap<String, Vec<String>>,
+    /// Most recent request outcomes (oldest first), bounded by AUDIT_LOG_MAX_ENTRIES
+    audit_log: VecDeque<AuditEntry>,
+    /// Requests recorded as "Signed"
+    signed_requests: u32,
+    /// Requests recorded with any outcome but "Signed", "Created" or "AwaitingSecondApproval"
+    failed_requests: u32,
+    /// Receiver history, bounded by RECENT_RECEIVERS_CACHE_TTL_MS
+    recent_receivers: Option<CachedReceivers>,
+    /// Confirmed batches (oldest first), bounded by RECENT_CONFIRMED_INTENTS_LIMIT
+    confirmed_intents: VecDeque<ConfirmedIntent>,
+    last_active_ms: f64,
+}
+
+/// The session WorkerPolicy.maxSessionDurationMs bounds (see session_duration.rs); persisted
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRecord {
+    /// The first key-using request that succeeded, or the credential collection that renewed
+    /// the session; None until then and after WipeAllState
+    pub started_at_ms: Option<f64>,
+    /// Incremented by every renewal and by WipeAllState
+    pub epoch: u32,
+    /// From the last policy that set maxSessionDurationMs
+    pub max_duration_ms: Option<u32>,
+}
+
+#[derive(Default)]
+struct SignerState {
+    /// Admitted requests in admission order (running and queued)
+    pending_requests: Vec<PendingRequest>,
+    request_limits: RequestLimits,
+    /// Frame size limits from configure_message_size_limits or the last policy that set them
+    message_size_limits: MessageSizeLimits,
+    /// Envelope encoding fixed by the Initialize handshake
+    wire_format: WireFormat,
+    /// First primitive whose known-answer test failed since the last Initialize
+    self_test_failure: Option<String>,
+    /// Request IDs of confirmation prompts awaiting a response from the main thread, with the
+    /// time each was issued and its render nonce
+    confirmation_nonces: HashMap<String, ConfirmationNonce>,
+    /// Origin confirmation decisions must attest, fixed at Initialize (see
+    /// confirmation_origin.rs)
+    trusted_confirmation_origin: Option<String>,
+    /// Entries from configure_known_authenticators (see known_authenticators.rs)
+    known_authenticators: Vec<AuthenticatorModel>,
+    /// The session maxSessionDurationMs bounds
+    session: SessionRecord,
+    /// Per-account state, bounded by MAX_ACTIVE_ACCOUNTS
+    accounts: HashMap<AccountId, AccountState>,
+    /// Session keys keyed by their "ed25519:..." public key
+    session_keys: HashMap<String, SessionKey>,
+    /// Completed idempotent requests (oldest first), bounded by IDEMPOTENCY_CACHE_LIMIT
+    completed_requests: VecDeque<CompletedRequest>,
+    /// MAC key for signing intent tokens, elevation challenges and approval tokens, generated
+    /// on first use; persisted
+    signing_intent_key: Option<[u8; 32]>,
+    /// Executed (or executing) signing intents, keyed by intent ID, with their expiry
+    consumed_signing_intents: HashMap<String, f64>,
+    /// Accepted elevation challenges, keyed by challenge ID, with the end of their window
+    consumed_elevation_challenges: HashMap<String, f64>,
+    /// First approvals completed by a second one, keyed by approval ID, with their expiry
+    consumed_approvals: HashMap<String, f64>,
+    /// X25519 secret that secrets are sealed to, generated on first use and rotated by
+    /// Initialize and WipeAllState
+    sealed_secrets_key: Option<[u8; 32]>,
+    /// Set by the first request whose policy has requireEncryptedSecrets; plaintext secrets
+    /// are refused from then until WipeAllState
+    require_encrypted_secrets: bool,
+    #[cfg(feature = "device-linking")]
+    /// Remote confirmations created on this device, keyed by request ID; persisted
+    remote_sessions: HashMap<String, RemoteSession>,
+    #[cfg(feature = "device-linking")]
+    /// Remote confirmations whose approval was used to sign, with their expiry
+    consumed_remote_sessions: HashMap<String, f64>,
+    #[cfg(feature = "device-linking")]
+    /// Other devices' remote confirmations approved here, with their expiry
+    approved_remote_requests: HashMap<String, f64>,
+    /// Source of every time read; kept across WipeAllState
+    clock: SharedClock,
+}
+
+thread_local! {
+    static SIGNER_STATE: RefCell<SignerState> = RefCell::new(SignerState::default());
+}
+
+fn with_state<R>(f: impl FnOnce(&mut SignerState) -> R) -> R {
+    SIGNER_STATE.with(|state| f(&mut state.borrow_mut()))
+}
+
+/// Wall-clock time from the state's clock, for TTLs and expiry
+pub(crate) fn now_ms() -> f64 {
+    with_state(|s| s.clock.now_ms())
+}
+
+/// Monotonic time from the state's clock, for measuring durations
+pub(crate) fn monotonic_ms() -> f64 {
+    with_state(|s| s.clock.monotonic_ms())
+}
+
+/// Replaces the worker's clock (a ManualClock in tests)
+#[cfg(test)]
+pub(crate) fn set_clock(clock: SharedClock) {
+    with_state(|s| s.clock = clock);
+}
+
+// === ACCOUNTS ===
+
+impl SignerState {
+    /// The account's state, created on first use. At MAX_ACTIVE_ACCOUNTS the least recently
+    /// active account holding no nonce reservations is dropped to make room.
+    fn account_mut(&mut self, account: &AccountId) -> &mut AccountState {
+        if !self.accounts.contains_key(account) && self.accounts.len() >= MAX_ACTIVE_ACCOUNTS {
+            let idle = self
+                .accounts
+                .iter()
+                .filter(|(_, a)| a.nonce_reservations.is_empty())
+                .min_by(|(_, a), (_, b)| a.last_active_ms.total_cmp(&b.last_active_ms))
+                .map(|(id, _)| id.clone());
+            if let Some(idle) = idle {
+                self.accounts.remove(&idle);
+            }
+        }
+        let now = self.clock.now_ms();
+        let state = self.accounts.entry(account.clone()).or_default();
+        state.last_active_ms = now;
+        state
+    }
+
+    fn account(&self, account: &AccountId) -> Option<&AccountState> {
+        self.accounts.get(account)
+    }
+}
+
+/// An account with session state (for ListActiveAccounts)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveAccountInfo {
+    pub account_id: AccountId,
+    pub signed_requests: u32,
+    pub failed_requests: u32,
+    pub nonce_reservations: usize,
+    pub audit_entries: usize,
+    pub session_keys: usize,
+    pub last_active_ms: f64,
+}
+
+/// Counts of what WipeAccountState discarded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountWipeSummary {
+    /// Whether the worker held any state for the account
+    pub found: bool,
+    pub released_nonce_reservations: usize,
+    pub cleared_audit_entries: usize,
+    pub cleared_session_keys: usize,
+}
+
+/// Accounts with session state or live session keys, most recently active first
+pub fn list_active_accounts() -> Vec<ActiveAccountInfo> {
+    let now = now_ms();
+    with_state(|s| {
+        let mut accounts: Vec<ActiveAccountInfo> = s
+            .accounts
+            .iter()
+            .map(|(account_id, a)| ActiveAccountInfo {
+                account_id: account_id.clone(),
+                signed_requests: a.signed_requests,
+                failed_requests: a.failed_requests,
+                nonce_reservations: a.nonce_reservations.len(),
+                audit_entries: a.audit_log.len(),
+                session_keys: 0,
+                last_active_ms: a.last_active_ms,
+            })
+            .collect();
+        for key in s.session_keys.values().filter(|k| k.expires_at_ms > now) {
+            match accounts
+                .iter_mut()
+                .find(|a| a.account_id.as_str() == key.near_account_id)
+            {
+                Some(account) => account.session_keys += 1,
+                None => accounts.push(ActiveAccountInfo {
+                    account_id: AccountId(key.near_account_id.clone()),
+                    signed_requests: 0,
+                    failed_requests: 0,
+                    nonce_reservations: 0,
+                    audit_entries: 0,
+                    session_keys: 1,
+                    last_active_ms: 0.0,
+                }),
+            }
+        }
+        accounts.sort_by(|a, b| b.last_active_ms.total_cmp(&a.last_active_ms));
+        accounts
+    })
+}
+
+/// Discards one account's state and session keys; other accounts are untouched. The
+/// account's running requests finish, but find no reservations left to release.
+pub fn wipe_account_state(account: &AccountId) -> AccountWipeSummary {
+    with_state(|s| {
+        let removed = s.accounts.remove(account);
+        let session_keys_before = s.session_keys.len();
+        s.session_keys
+            .retain(|_, k| k.near_account_id != account.as_str());
+        let cleared_session_keys = session_keys_before - s.session_keys.len();
+        AccountWipeSummary {
+            found: removed.is_some() || cleared_session_keys > 0,
+            released_nonce_reservations: removed
+                .as_ref()
+                .map_or(0, |a| a.nonce_reservations.len()),
+            cleared_audit_entries: removed.as_ref().map_or(0, |a| a.audit_log.len()),
+            cleared_session_keys,
+        }
+    })
+}
+
+// === PENDING-REQUEST REGISTRY ===
+
+impl SignerState {
+    fn count_phase(&self, phase: RequestPhase) -> usize {
+        self.pending_requests
+            .iter()
+            .filter(|r| r.phase == phase)
+            .count()
+    }
+
+    /// Moves queued requests into free slots (oldest first); returns wakers to wake
+    /// once the state borrow is released
+    fn promote_queued(&mut self) -> Vec<Waker> {
+        let mut free_slots = self
+            .request_limits
+            .max_concurrent
+            .saturating_sub(self.count_phase(RequestPhase::Running));
+        let mut wakers = Vec::new();
+        for request in self.pending_requests.iter_mut() {
+            if free_slots == 0 {
+                break;
+            }
+            if request.phase == RequestPhase::Queued {
+                request.phase = RequestPhase::Running;
+                wakers.extend(request.waker.take());
+                free_slots -= 1;
+            }
+        }
+        wakers
+    }
+}
+
+fn wake_all(wakers: Vec<Waker>) {
+    for waker in wakers {
+        waker.wake();
+    }
+}
+
+pub fn request_limits() -> RequestLimits {
+    with_state(|s| s.request_limits)
+}
+
+/// Updates the registry limits; raising max_concurrent starts queued requests immediately
+pub fn set_request_limits(limits: RequestLimits) -> Result<(), String> {
+    if limits.max_concurrent == 0 {
+        return Err("maxConcurrent must be at least 1".to_string());
+    }
+    let wakers = with_state(|s| {
+        s.request_limits = limits;
+        s.promote_queued()
+    });
+    wake_all(wakers);
+    Ok(())
+}
+
+pub fn message_size_limits() -> MessageSizeLimits {
+    with_state(|s| s.message_size_limits.clone())
+}
+
+/// Applies to the frames after the current one; callers validate the limits first
+pub fn set_message_size_limits(limits: MessageSizeLimits) {
+    with_state(|s| s.message_size_limits = limits);
+}
+
+pub fn wire_format() -> WireFormat {
+    with_state(|s| s.wire_format)
+}
+
+pub fn set_wire_format(format: WireFormat) {
+    with_state(|s| s.wire_format = format);
+}
+
+pub fn self_test_failure() -> Option<String> {
+    with_state(|s| s.self_test_failure.clone())
+}
+
+/// Puts the worker into the refusing state; the first failed primitive is kept
+pub fn record_self_test_failure(primitive: &str) {
+    with_state(|s| {
+        s.self_test_failure
+            .get_or_insert_with(|| primitive.to_string());
+    });
+}
+
+/// Leaves the refusing state; only the Initialize handshake calls this, before re-running the self-test
+pub fn clear_self_test_failure() {
+    with_state(|s| s.self_test_failure = None);
+}
+
+/// Admits a request into the registry: it runs immediately if a slot is free, otherwise it is
+/// queued. Rejected with `TooManyPendingRequests` when the queue is full.
+pub fn admit_request(request_id: &str, operation: &str) -> Result<RequestSlot, SignerErrorCode> {
+    with_state(|s| {
+        if s.pending_requests
+            .iter()
+            .any(|r| r.request_id == request_id)
+        {
+            return Err(SignerErrorCode::DuplicateRequestId);
+        }
+        let phase = if s.count_phase(RequestPhase::Running) < s.request_limits.max_concurrent {
+            RequestPhase::Running
+        } else if s.count_phase(RequestPhase::Queued) < s.request_limits.max_queued {
+            RequestPhase::Queued
+        } else {
+            return Err(SignerErrorCode::TooManyPendingRequests);
+        };
+        s.pending_requests.push(PendingRequest {
+            request_id: request_id.to_string(),
+            operation: operation.to_string(),
+            phase,
+            created_ms: s.clock.now_ms(),
+            waker: None,
+        });
+        Ok(RequestSlot {
+            request_id: request_id.to_string(),
+        })
+    })
+}
+
+pub fn list_pending_requests() -> Vec<PendingRequestInfo> {
+    let now = now_ms();
+    with_state(|s| {
+        s.pending_requests
+            .iter()
+            .map(|r| PendingRequestInfo {
+                request_id: r.request_id.clone(),
+                operation: r.operation.clone(),
+                phase: r.phase,
+                age_ms: (now - r.created_ms).max(0.0),
+                admitted_at_ms: r.created_ms,
+            })
+            .collect()
+    })
+}
+
+/// Cancels a queued request (its handler returns `RequestCancelled` without running).
+/// Running requests cannot be interrupted. Returns the phase the request was in, if known.
+pub fn cancel_request(request_id: &str) -> Option<RequestPhase> {
+    let (phase, wakers) = with_state(|s| {
+        let index = s
+            .pending_requests
+            .iter()
+            .position(|r| r.request_id == request_id)?;
+        let phase = s.pending_requests[index].phase;
+        let mut wakers = Vec::new();
+        if phase == RequestPhase::Queued {
+            let mut removed = s.pending_requests.remove(index);
+            wakers.extend(removed.waker.take());
+        }
+        Some((phase, wakers))
+    })?;
+    wake_all(wakers);
+    Some(phase)
+}
+
+/// Discards all request bookkeeping: queued requests are cancelled, confirmation nonces,
+/// session keys and every account's state (nonce reservations, cached receivers, confirmed
+/// intents and the audit log) are cleared. Running requests keep their slot until
+/// they complete, but no longer find any state to release. The signing intent key is discarded
+/// too, so every outstanding intent token stops verifying, as are remote confirmation sessions,
+/// the peer port to the VRF worker is closed and the background refresh stops. The sealed
+/// secrets key rotates, so boxes sealed before the wipe no longer open, and
+/// requireEncryptedSecrets is lifted. The session ends and the next one gets a new epoch.
+pub fn wipe_all_state() -> WipeSummary {
+    let (summary, wakers) = with_state(|s| {
+        let mut wakers = Vec::new();
+        let mut cancelled_queued_requests = 0;
+        s.pending_requests.retain_mut(|r| {
+            if r.phase == RequestPhase::Queued {
+                wakers.extend(r.waker.take());
+                cancelled_queued_requests += 1;
+                false
+            } else {
+                true
+            }
+        });
+        let summary = WipeSummary {
+            cancelled_queued_requests,
+            running_requests: s.pending_requests.len(),
+            cleared_confirmations: s.confirmation_nonces.len(),
+            released_nonce_reservations: s
+                .accounts
+                .values()
+                .map(|a| a.nonce_reservations.len())
+                .sum(),
+            cleared_session_keys: s.session_keys.len(),
+            closed_peer_port: false,
+            stopped_background_refresh: false,
+        };
+        s.confirmation_nonces.clear();
+        s.accounts.clear();
+        s.session_keys.clear();
+        s.completed_requests.clear();
+        s.signing_intent_key = None;
+        s.consumed_signing_intents.clear();
+        s.consumed_elevation_challenges.clear();
+        s.consumed_approvals.clear();
+        // Zeroizing an Option also sets it to None
+        s.sealed_secrets_key.zeroize();
+        s.require_encrypted_secrets = false;
+        s.session.started_at_ms = None;
+        s.session.epoch = s.session.epoch.wrapping_add(1);
+        #[cfg(feature = "device-linking")]
+        {
+            s.remote_sessions.clear();
+            s.consumed_remote_sessions.clear();
+            s.approved_remote_requests.clear();
+        }
+        (summary, wakers)
+    });
+    wake_all(wakers);
+    WipeSummary {
+        closed_peer_port: peer_channel::disconnect_peer_session(),
+        stopped_background_refresh: background_refresh::stop(),
+        ..summary
+    }
+}
+
+/// A request's place in the registry; dropping it frees the slot and starts the next queued request
+pub struct RequestSlot {
+    request_id: String,
+}
+
+impl RequestSlot {
+    /// Resolves once the request holds a running slot, or with `RequestCancelled`
+    /// if it was cancelled (or wiped) while queued
+    pub fn started(&self) -> SlotWait {
+        SlotWait {
+            request_id: self.request_id.clone(),
+        }
+    }
+}
+
+impl Drop for RequestSlot {
+    fn drop(&mut self) {
+        let wakers = with_state(|s| {
+            s.pending_requests
+                .retain(|r| r.request_id != self.request_id);
+            s.promote_queued()
+        });
+        wake_all(wakers);
+    }
+}
+
+pub struct SlotWait {
+    request_id: String,
+}
+
+impl Future for SlotWait {
+    type Output = Result<(), SignerErrorCode>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        with_state(|s| {
+            match s
+                .pending_requests
+                .iter_mut()
+                .find(|r| r.request_id == self.request_id)
+            {
+                None => Poll::Ready(Err(SignerErrorCode::RequestCancelled)),
+                Some(request) if request.phase == RequestPhase::Running => Poll::Ready(Ok(())),
+                Some(request) => {
+                    request.waker = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        })
+    }
+}
+
+// === CONFIRMATION NONCES ===
+
+/// An outstanding confirmation prompt
+struct ConfirmationNonce {
+    issued_at_ms: f64,
+    render_nonce: String,
+}
+
+/// Marks a confirmation request as outstanding before prompting the main thread; returns the
+/// prompt's render nonce
+pub fn register_confirmation_nonce(request_id: &str) -> String {
+    let render_nonce = confirmation_origin::new_render_nonce();
+    with_state(|s| {
+        let issued_at_ms = s.clock.now_ms();
+        s.confirmation_nonces.insert(
+            request_id.to_string(),
+            ConfirmationNonce {
+                issued_at_ms,
+                render_nonce: render_nonce.clone(),
+            },
+        )
+    });
+    render_nonce
+}
+
+#[cfg(test)]
+pub fn has_confirmation_nonce(request_id: &str) -> bool {
+    with_state(|s| s.confirmation_nonces.contains_key(request_id))
+}
+
+/// When an outstanding confirmation was issued
+pub fn confirmation_nonce_issued_at(request_id: &str) -> Option<f64> {
+    with_state(|s| s.confirmation_nonces.get(request_id).map(|n| n.issued_at_ms))
+}
+
+/// Render nonce of an outstanding confirmation
+pub fn confirmation_render_nonce(request_id: &str) -> Option<String> {
+    with_state(|s| {
+        s.confirmation_nonces
+            .get(request_id)
+            .map(|n| n.render_nonce.clone())
+    })
+}
+
+pub fn trusted_confirmation_origin() -> Option<String> {
+    with_state(|s| s.trusted_confirmation_origin.clone())
+}
+
+/// Callers normalize the origin first; WipeAllState keeps it
+pub fn set_trusted_confirmation_origin(origin: Option<String>) {
+    with_state(|s| s.trusted_confirmation_origin = origin);
+}
+
+pub fn known_authenticators() -> Vec<AuthenticatorModel> {
+    with_state(|s| s.known_authenticators.clone())
+}
+
+/// Callers normalize the AAGUIDs first; WipeAllState keeps them
+pub fn set_known_authenticators(entries: Vec<AuthenticatorModel>) {
+    with_state(|s| s.known_authenticators = entries);
+}
+
+/// Clears an outstanding confirmation; returns false if it was not registered
+pub fn clear_confirmation_nonce(request_id: &str) -> bool {
+    with_state(|s| s.confirmation_nonces.remove(request_id).is_some())
+}
+
+// === SESSION DURATION ===
+
+pub fn session() -> SessionRecord {
+    with_state(|s| s.session)
+}
+
+pub fn session_epoch() -> u32 {
+    with_state(|s| s.session.epoch)
+}
+
+/// Applies to the requests after the current one; callers validate the duration first
+pub fn set_max_session_duration(max_duration_ms: u32) {
+    with_state(|s| s.session.max_duration_ms = Some(max_duration_ms));
+}
+
+/// Starts the session at `started_at_ms` unless one is already running
+pub fn start_session(started_at_ms: f64) {
+    with_state(|s| {
+        s.session.started_at_ms.get_or_insert(started_at_ms);
+    });
+}
+
+/// Starts a new session at `started_at_ms` in the next epoch; returns the new epoch
+pub fn renew_session(started_at_ms: f64) -> u32 {
+    with_state(|s| {
+        s.session.started_at_ms = Some(started_at_ms);
+        s.session.epoch = s.session.epoch.wrapping_add(1);
+        s.session.epoch
+    })
+}
+
+// === NONCE RESERVATIONS ===
+
+/// Records the NEAR nonces the main thread reserved for `account`'s request `request_id`
+pub fn reserve_nonces(account: &AccountId, request_id: &str, nonces: Vec<String>) {
+    if nonces.is_empty() {
+        return;
+    }
+    with_state(|s| {
+        s.account_mut(account)
+            .nonce_reservations
+            .insert(request_id.to_string(), nonces)
+    });
+}
+
+/// Releases (forgets) the nonces reserved for `account`'s request `request_id`, returning them
+pub fn release_nonces(account: &AccountId, request_id: &str) -> Vec<String> {
+    with_state(|s| {
+        s.accounts
+            .get_mut(account)
+            .and_then(|a| a.nonce_reservations.remove(request_id))
+            .unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+pub fn reserved_nonces(account: &AccountId, request_id: &str) -> Vec<String> {
+    with_state(|s| {
+        s.account(account)
+            .and_then(|a| a.nonce_reservations.get(request_id))
+            .cloned()
+            .unwrap_or_default()
+    })
+}
+
+// === AUDIT LOG ===
+
+pub fn record_audit(
+    account: &AccountId,
+    request_id: &str,
+    operation: &str,
+    outcome: &str,
+    detail: Option<String>,
+) {
+    record_audit_with_rpc_endpoints(account, request_id, operation, outcome, detail, None);
+}
+
+/// Appends to `account`'s audit log and counts the outcome: "Signed" as signed, "Created" and
+/// "AwaitingSecondApproval" (nothing signed yet) as neither, anything else (a rejection, refusal
+/// or error) as failed
+pub fn record_audit_with_rpc_endpoints(
+    account: &AccountId,
+    request_id: &str,
+    operation: &str,
+    outcome: &str,
+    detail: Option<String>,
+    rpc_endpoints: Option<RpcEndpoints>,
+) {
+    let entry = AuditEntry {
+        timestamp_ms: now_ms(),
+        request_id: request_id.to_string(),
+        operation: operation.to_string(),
+        outcome: outcome.to_string(),
+        detail,
+        rpc_endpoints,
+    };
+    with_state(|s| {
+        let account = s.account_mut(account);
+        match outcome {
+            "Signed" => account.signed_requests += 1,
+            "Created" | "AwaitingSecondApproval" => {}
+            _ => account.failed_requests += 1,
+        }
+        if account.audit_log.len() >= AUDIT_LOG_MAX_ENTRIES {
+            account.audit_log.pop_front();
+        }
+        account.audit_log.push_back(entry);
+    });
+}
+
+#[cfg(test)]
+pub fn audit_entries_for(request_id: &str) -> Vec<AuditEntry> {
+    with_state(|s| {
+        s.accounts
+            .values()
+            .flat_map(|a| a.audit_log.iter())
+            .filter(|e| e.request_id == request_id)
+            .cloned()
+            .collect()
+    })
+}
+
+#[cfg(test)]
+pub fn account_audit_entries(account: &AccountId) -> Vec<AuditEntry> {
+    with_state(|s| {
+        s.account(account)
+            .map(|a| a.audit_log.iter().cloned().collect())
+            .unwrap_or_default()
+    })
+}
+
+// === SESSION KEYS ===
+
+/// Stores a session key, first dropping any that have expired
+pub fn store_session_key(public_key: &str, key: SessionKey) -> Result<(), String> {
+    let now = now_ms();
+    with_state(|s| {
+        s.session_keys.retain(|_, k| k.expires_at_ms > now);
+        if s.session_keys.len() >= MAX_SESSION_KEYS {
+            return Err(format!(
+                "Too many active session keys (max {})",
+                MAX_SESSION_KEYS
+            ));
+        }
+        s.session_keys.insert(public_key.to_string(), key);
+        Ok(())
+    })
+}
+
+/// Runs `f` against a live session key. Expired keys are wiped and reported as `SessionKeyExpired`.
+pub fn with_session_key<R>(
+    public_key: &str,
+    f: impl FnOnce(&mut SessionKey) -> R,
+) -> Result<R, SignerErrorCode> {
+    let now = now_ms();
+    with_state(|s| {
+        let expired = match s.session_keys.get(public_key) {
+            None => return Err(SignerErrorCode::SessionKeyNotFound),
+            Some(key) => key.expires_at_ms <= now,
+        };
+        if expired {
+            s.session_keys.remove(public_key);
+            return Err(SignerErrorCode::SessionKeyExpired);
+        }
+        Ok(f(s.session_keys.get_mut(public_key).unwrap()))
+    })
+}
+
+/// Whether any session key is live
+pub fn has_live_session_key() -> bool {
+    let now = now_ms();
+    with_state(|s| s.session_keys.values().any(|k| k.expires_at_ms > now))
+}
+
+/// Wipes a session key; returns false if it was unknown (or already expired and swept)
+pub fn remove_session_key(public_key: &str) -> bool {
+    with_state(|s| s.session_keys.remove(public_key).is_some())
+}
+
+// === PERSISTED STATE ===
+
+/// A live session key as it travels in the sealed worker state record
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedSessionKey {
+    pub public_key: String,
+    /// base64url ed25519 seed
+    pub secret_key: String,
+    pub near_account_id: String,
+    pub receiver_id: String,
+    pub method_names: Vec<String>,
+    /// yoctoNEAR, as decimal strings
+    pub allowance: String,
+    pub spent: String,
+    pub expires_at_ms: f64,
+}
+
+impl Drop for PersistedSessionKey {
+    fn drop(&mut self) {
+        self.secret_key.zeroize();
+    }
+}
+
+/// An account's confirmed batches (oldest first) as they travel in the record, so diffs
+/// cover batches confirmed in earlier workers
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedConfirmedIntents {
+    pub near_account_id: String,
+    pub intents: Vec<ConfirmedIntent>,
+}
+
+/// A 32-byte key as it travels in the record (base64url), zeroized on drop
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(transparent)]
+pub struct PersistedKey(pub String);
+
+impl Drop for PersistedKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl PersistedKey {
+    fn encode(key: &[u8; 32]) -> Self {
+        PersistedKey(base64_url_encode(key))
+    }
+
+    fn decode(&self, what: &str) -> Result<[u8; 32], String> {
+        base64_url_decode(&self.0)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| format!("Invalid {} in the worker state", what))
+    }
+}
+
+/// A consumed one-time ID and the expiry after which it can no longer be presented
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedConsumedId {
+    pub id: String,
+    pub expires_at_ms: f64,
+}
+
+/// Unexpired entries of a consumed-ID map, sorted by ID
+fn persisted_consumed_ids(consumed: &HashMap<String, f64>, now: f64) -> Vec<PersistedConsumedId> {
+    let mut ids: Vec<PersistedConsumedId> = consumed
+        .iter()
+        .filter(|(_, expiry)| **expiry > now)
+        .map(|(id, expiry)| PersistedConsumedId {
+            id: id.clone(),
+            expires_at_ms: *expiry,
+        })
+        .collect();
+    ids.sort_by(|a, b| a.id.cmp(&b.id));
+    ids
+}
+
+#[cfg(feature = "device-linking")]
+/// A remote confirmation's session as it travels in the record, so the approval can be
+/// completed in a later worker
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedRemoteSession {
+    pub request_id: String,
+    /// ed25519 seed of the session key
+    pub session_key: PersistedKey,
+    pub near_account_id: String,
+    pub intent_digest: String,
+    pub first_time_receivers: Vec<Option<bool>>,
+    pub vrf_challenge: crate::types::VrfChallenge,
+    pub expires_at_ms: f64,
+}
+
+fn consumed_id_map(ids: &[PersistedConsumedId]) -> HashMap<String, f64> {
+    ids.iter()
+        .map(|entry| (entry.id.clone(), entry.expires_at_ms))
+        .collect()
+}
+
+/// The state that outlives the worker in the TS-held record (see worker_state.rs). Lists are
+/// sorted, so the same state always serializes to the same snapshot.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedState {
+    #[serde(default)]
+    pub session_keys: Vec<PersistedSessionKey>,
+    #[serde(default)]
+    pub confirmed_intents: Vec<PersistedConfirmedIntents>,
+    /// Oldest first
+    #[serde(default)]
+    pub completed_requests: Vec<CompletedRequest>,
+    /// MAC key of signing intent tokens, so tokens verify in later workers
+    #[serde(default)]
+    pub signing_intent_key: Option<PersistedKey>,
+    /// Executed signing intents, so replays are refused in later workers
+    #[serde(default)]
+    pub consumed_signing_intents: Vec<PersistedConsumedId>,
+    /// Accepted elevation challenges, likewise
+    #[serde(default)]
+    pub consumed_elevation_challenges: Vec<PersistedConsumedId>,
+    /// Completed dual-control approvals, likewise
+    #[serde(default)]
+    pub consumed_approvals: Vec<PersistedConsumedId>,
+    /// Start and epoch of the session, so it expires in later workers and tokens of an
+    /// earlier epoch stay refused
+    #[serde(default)]
+    pub session: SessionRecord,
+    #[cfg(feature = "device-linking")]
+    /// Remote confirmations created here, completed by a later worker
+    #[serde(default)]
+    pub remote_sessions: Vec<PersistedRemoteSession>,
+    #[cfg(feature = "device-linking")]
+    /// Approvals already used to sign
+    #[serde(default)]
+    pub consumed_remote_sessions: Vec<PersistedConsumedId>,
+    #[cfg(feature = "device-linking")]
+    /// Other devices' requests approved here
+    #[serde(default)]
+    pub approved_remote_requests: Vec<PersistedConsumedId>,
+}
+
+#[cfg(feature = "device-linking")]
+fn persisted_remote_sessions(
+    sessions: &HashMap<String, RemoteSession>,
+    now: f64,
+) -> Vec<PersistedRemoteSession> {
+    let mut persisted: Vec<PersistedRemoteSession> = sessions
+        .iter()
+        .filter(|(_, r)| r.expires_at_ms > now)
+        .map(|(request_id, r)| PersistedRemoteSession {
+            request_id: request_id.clone(),
+            session_key: PersistedKey::encode(&r.session_key.to_bytes()),
+            near_account_id: r.near_account_id.clone(),
+            intent_digest: r.intent_digest.clone(),
+            first_time_receivers: r.first_time_receivers.clone(),
+            vrf_challenge: r.vrf_challenge.clone(),
+            expires_at_ms: r.expires_at_ms,
+        })
+        .collect();
+    persisted.sort_by(|a, b| a.request_id.cmp(&b.request_id));
+    persisted
+}
+
+/// Snapshot of the persisted state; expired session keys and one-time IDs are left out
+pub fn persisted_state() -> PersistedState {
+    let now = now_ms();
+    with_state(|s| {
+        let mut session_keys: Vec<PersistedSessionKey> = s
+            .session_keys
+            .iter()
+            .filter(|(_, k)| k.expires_at_ms > now)
+            .map(|(public_key, k)| PersistedSessionKey {
+                public_key: public_key.clone(),
+                secret_key: base64_url_encode(&k.signing_key.to_bytes()),
+                near_account_id: k.near_account_id.clone(),
+                receiver_id: k.receiver_id.clone(),
+                method_names: k.method_names.clone(),
+                allowance: k.allowance.to_string(),
+                spent: k.spent.to_string(),
+                expires_at_ms: k.expires_at_ms,
+            })
+            .collect();
+        session_keys.sort_by(|a, b| a.public_key.cmp(&b.public_key));
+        let mut confirmed_intents: Vec<PersistedConfirmedIntents> = s
+            .accounts
+            .iter()
+            .filter(|(_, a)| !a.confirmed_intents.is_empty())
+            .map(|(account, a)| PersistedConfirmedIntents {
+                near_account_id: account.0.clone(),
+                intents: a.confirmed_intents.iter().cloned().collect(),
+            })
+            .collect();
+        confirmed_intents.sort_by(|a, b| a.near_account_id.cmp(&b.near_account_id));
+        PersistedState {
+            session_keys,
+            confirmed_intents,
+            completed_requests: s.completed_requests.iter().cloned().collect(),
+            signing_intent_key: s.signing_intent_key.as_ref().map(PersistedKey::encode),
+            consumed_signing_intents: persisted_consumed_ids(&s.consumed_signing_intents, now),
+            consumed_elevation_challenges: persisted_consumed_ids(
+                &s.consumed_elevation_challenges,
+                now,
+            ),
+            consumed_approvals: persisted_consumed_ids(&s.consumed_approvals, now),
+            session: s.session,
+            #[cfg(feature = "device-linking")]
+            remote_sessions: persisted_remote_sessions(&s.remote_sessions, now),
+            #[cfg(feature = "device-linking")]
+            consumed_remote_sessions: persisted_consumed_ids(&s.consumed_remote_sessions, now),
+            #[cfg(feature = "device-linking")]
+            approved_remote_requests: persisted_consumed_ids(&s.approved_remote_requests, now),
+        }
+    })
+}
+
+/// Replaces the persisted parts of the state with a snapshot from the record
+pub fn restore_persisted_state(persisted: PersistedState) -> Result<(), String> {
+    let mut session_keys = HashMap::new();
+    for key in &persisted.session_keys {
+        let seed: [u8; 32] = base64_url_decode(&key.secret_key)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| format!("Invalid secret key of session key {}", key.public_key))?;
+        let parse = |value: &str| {
+            value
+                .parse::<Balance>()
+                .map_err(|_| format!("Invalid amount of session key {}", key.public_key))
+        };
+        session_keys.insert(
+            key.public_key.clone(),
+            SessionKey {
+                signing_key: ed25519_dalek::SigningKey::from_bytes(&seed),
+                near_account_id: key.near_account_id.clone(),
+                receiver_id: key.receiver_id.clone(),
+                method_names: key.method_names.clone(),
+                allowance: parse(&key.allowance)?,
+                spent: parse(&key.spent)?,
+                expires_at_ms: key.expires_at_ms,
+            },
+        );
+    }
+    let signing_intent_key = persisted
+        .signing_intent_key
+        .as_ref()
+        .map(|key| key.decode("signing intent key"))
+        .transpose()?;
+    #[cfg(feature = "device-linking")]
+    let mut remote_sessions = HashMap::new();
+    #[cfg(feature = "device-linking")]
+    for r in &persisted.remote_sessions {
+        let seed = r.session_key.decode("remote confirmation session key")?;
+        remote_sessions.insert(
+            r.request_id.clone(),
+            RemoteSession {
+                session_key: ed25519_dalek::SigningKey::from_bytes(&seed),
+                near_account_id: r.near_account_id.clone(),
+                intent_digest: r.intent_digest.clone(),
+                first_time_receivers: r.first_time_receivers.clone(),
+                vrf_challenge: r.vrf_challenge.clone(),
+                expires_at_ms: r.expires_at_ms,
+            },
+        );
+    }
+    with_state(|s| {
+        s.session_keys = session_keys;
+        s.signing_intent_key.zeroize();
+        s.signing_intent_key = signing_intent_key;
+        s.consumed_signing_intents = consumed_id_map(&persisted.consumed_signing_intents);
+        s.consumed_elevation_challenges = consumed_id_map(&persisted.consumed_elevation_challenges);
+        s.consumed_approvals = consumed_id_map(&persisted.consumed_approvals);
+        s.session = persisted.session;
+        #[cfg(feature = "device-linking")]
+        {
+            s.remote_sessions = remote_sessions;
+            s.consumed_remote_sessions = consumed_id_map(&persisted.consumed_remote_sessions);
+            s.approved_remote_requests = consumed_id_map(&persisted.approved_remote_requests);
+        }
+        for account in s.accounts.values_mut() {
+            account.confirmed_intents.clear();
+        }
+        for entry in persisted.confirmed_intents {
+            let skip = entry
+                .intents
+                .len()
+                .saturating_sub(RECENT_CONFIRMED_INTENTS_LIMIT);
+            let account = AccountId(entry.near_account_id);
+            s.account_mut(&account).confirmed_intents =
+                entry.intents.into_iter().skip(skip).collect();
+        }
+        let skip = persisted
+            .completed_requests
+            .len()
+            .saturating_sub(IDEMPOTENCY_CACHE_LIMIT);
+        s.completed_requests = persisted
+            .completed_requests
+            .into_iter()
+            .skip(skip)
+            .collect();
+    });
+    Ok(())
+}
+
+// === RECENT RECEIVERS ===
+
+/// Caches `receivers` (most recent first) as the account's history
+pub fn cache_recent_receivers(account: &AccountId, receivers: Vec<RecentReceiver>) {
+    let fetched_ms = now_ms();
+    with_state(|s| {
+        s.account_mut(account).recent_receivers = Some(CachedReceivers {
+            receivers,
+            fetched_ms,
+        });
+    })
+}
+
+/// The account's cached history, unless missing or older than RECENT_RECEIVERS_CACHE_TTL_MS
+pub fn cached_recent_receivers(account: &AccountId) -> Option<Vec<RecentReceiver>> {
+    let now = now_ms();
+    with_state(|s| {
+        s.account(account)
+            .and_then(|a| a.recent_receivers.as_ref())
+            .filter(|c| now - c.fetched_ms < RECENT_RECEIVERS_CACHE_TTL_MS)
+            .map(|c| c.receivers.clone())
+    })
+}
+
+/// Moves `receiver_id` to the front of a cached history after the worker signed a
+/// transaction to it, so it is no longer first-time within the session
+pub fn note_recent_receiver(account: &AccountId, receiver_id: &str) {
+    let now = now_ms();
+    with_state(|s| {
+        if let Some(cached) = s
+            .accounts
+            .get_mut(account)
+            .and_then(|a| a.recent_receivers.as_mut())
+        {
+            cached.receivers.retain(|r| r.receiver_id != receiver_id);
+            cached.receivers.insert(
+                0,
+                RecentReceiver {
+                    receiver_id: receiver_id.to_string(),
+                    last_seen_ms: now,
+                },
+            );
+        }
+    })
+}
+
+// === CONFIRMED INTENTS ===
+
+/// Remembers a batch `account` confirmed, evicting its oldest beyond
+/// RECENT_CONFIRMED_INTENTS_LIMIT
+pub fn record_confirmed_intent(account: &AccountId, intent: ConfirmedIntent) {
+    with_state(|s| {
+        let confirmed = &mut s.account_mut(account).confirmed_intents;
+        confirmed.push_back(intent);
+        while confirmed.len() > RECENT_CONFIRMED_INTENTS_LIMIT {
+            confirmed.pop_front();
+        }
+    })
+}
+
+/// The account's confirmed batches, most recent first
+pub fn recent_confirmed_intents(account: &AccountId) -> Vec<ConfirmedIntent> {
+    with_state(|s| {
+        s.account(account)
+            .map(|a| a.confirmed_intents.iter().rev().cloned().collect())
+            .unwrap_or_default()
+    })
+}
+
+// === IDEMPOTENCY KEYS ===
+
+/// Remembers a completed request, evicting the oldest beyond IDEMPOTENCY_CACHE_LIMIT
+pub fn record_completed_request(entry: CompletedRequest) {
+    with_state(|s| {
+        s.completed_requests
+            .retain(|c| c.idempotency_key != entry.idempotency_key);
+        s.completed_requests.push_back(entry);
+        while s.completed_requests.len() > IDEMPOTENCY_CACHE_LIMIT {
+            s.completed_requests.pop_front();
+        }
+    })
+}
+
+pub fn completed_request(idempotency_key: &str) -> Option<CompletedRequest> {
+    with_state(|s| {
+        s.completed_requests
+            .iter()
+            .find(|c| c.idempotency_key == idempotency_key)
+            .cloned()
+    })
+}
+
+// === SIGNING INTENTS ===
+
+/// The MAC key of signing intents and elevation challenges (each under its own domain),
+/// generated on first use and kept in the worker state record
+pub fn signing_intent_key() -> Result<[u8; 32], String> {
+    if let Some(key) = with_state(|s| s.signing_intent_key) {
+        return Ok(key);
+    }
+    let mut key = [0u8; 32];
+    getrandom::getrandom(&mut key)
+        .map_err(|e| format!("Failed to generate signing intent key: {}", e))?;
+    Ok(with_state(|s| *s.signing_intent_key.get_or_insert(key)))
+}
+
+/// X25519 secret of the session's sealed secrets, generated on first use
+pub fn sealed_secrets_key() -> Result<[u8; 32], String> {
+    if let Some(key) = with_state(|s| s.sealed_secrets_key) {
+        return Ok(key);
+    }
+    let mut key = [0u8; 32];
+    getrandom::getrandom(&mut key)
+        .map_err(|e| format!("Failed to generate sealed secrets key: {}", e))?;
+    Ok(with_state(|s| *s.sealed_secrets_key.get_or_insert(key)))
+}
+
+/// Discards the sealed secrets key; the next use generates a new one
+pub fn rotate_sealed_secrets_key() {
+    with_state(|s| s.sealed_secrets_key.zeroize());
+}
+
+pub fn require_encrypted_secrets() -> bool {
+    with_state(|s| s.require_encrypted_secrets)
+}
+
+/// Refuses plaintext secrets until the next WipeAllState; requests cannot lift it
+pub fn set_require_encrypted_secrets() {
+    with_state(|s| s.require_encrypted_secrets = true);
+}
+
+/// Marks an intent as executed; false if it already was. Entries past their expiry are
+/// dropped first: an expired intent fails its expiry check before reaching this one.
+pub fn consume_signing_intent(intent_id: &str, expires_at_ms: f64) -> bool {
+    let now = now_ms();
+    with_state(|s| {
+        s.consumed_signing_intents.retain(|_, expiry| *expiry > now);
+        if s.consumed_signing_intents.contains_key(intent_id) {
+            return false;
+        }
+        s.consumed_signing_intents
+            .insert(intent_id.to_string(), expires_at_ms);
+        true
+    })
+}
+
+/// Makes a consumed intent executable again (its execution signed nothing)
+pub fn release_signing_intent(intent_id: &str) {
+    with_state(|s| {
+        s.consumed_signing_intents.remove(intent_id);
+    })
+}
+
+// === ELEVATION CHALLENGES ===
+
+/// Marks an elevation challenge as accepted until `window_ends_ms`; false if it already was
+pub fn consume_elevation_challenge(challenge_id: &str, window_ends_ms: f64) -> bool {
+    let now = now_ms();
+    with_state(|s| {
+        s.consumed_elevation_challenges
+            .retain(|_, expiry| *expiry > now);
+        if s.consumed_elevation_challenges.contains_key(challenge_id) {
+            return false;
+        }
+        s.consumed_elevation_challenges
+            .insert(challenge_id.to_string(), window_ends_ms);
+        true
+    })
+}
+
+// === DUAL CONTROL ===
+
+pub fn is_approval_consumed(approval_id: &str) -> bool {
+    with_state(|s| s.consumed_approvals.contains_key(approval_id))
+}
+
+/// Marks a first approval as completed until its token expires; false if it already was
+pub fn consume_approval(approval_id: &str, expires_at_ms: f64) -> bool {
+    let now = now_ms();
+    with_state(|s| {
+        s.consumed_approvals.retain(|_, expiry| *expiry > now);
+        if s.consumed_approvals.contains_key(approval_id) {
+            return false;
+        }
+        s.consumed_approvals
+            .insert(approval_id.to_string(), expires_at_ms);
+        true
+    })
+}
+
+// === REMOTE CONFIRMATION ===
+
+#[cfg(feature = "device-linking")]
+/// Keeps a created remote confirmation's session until it expires
+pub fn store_remote_session(request_id: &str, session: RemoteSession) {
+    let now = now_ms();
+    with_state(|s| {
+        s.remote_sessions.retain(|_, r| r.expires_at_ms > now);
+        s.remote_sessions.insert(request_id.to_string(), session);
+    })
+}
+
+#[cfg(feature = "device-linking")]
+pub fn remote_session(request_id: &str) -> Option<RemoteSession> {
+    with_state(|s| s.remote_sessions.get(request_id).cloned())
+}
+
+#[cfg(feature = "device-linking")]
+/// Marks a session's approval as used; false if it already was
+pub fn consume_remote_session(request_id: &str, expires_at_ms: f64) -> bool {
+    let now = now_ms();
+    with_state(|s| {
+        s.consumed_remote_sessions.retain(|_, expiry| *expiry > now);
+        if s.consumed_remote_sessions.contains_key(request_id) {
+            return false;
+        }
+        s.consumed_remote_sessions
+            .insert(request_id.to_string(), expires_at_ms);
+        true
+    })
+}
+
+#[cfg(feature = "device-linking")]
+/// Makes a used approval usable again (signing with it signed nothing)
+pub fn release_remote_session(request_id: &str) {
+    with_state(|s| {
+        s.consumed_remote_sessions.remove(request_id);
+    })
+}
+
+#[cfg(feature = "device-linking")]
+/// Records that this device is approving another device's request; false if it already did
+pub fn note_remote_approval(request_id: &str, expires_at_ms: f64) -> bool {
+    let now = now_ms();
+    with_state(|s| {
+        s.approved_remote_requests.retain(|_, expiry| *expiry > now);
+        if s.approved_remote_requests.contains_key(request_id) {
+            return false;
+        }
+        s.approved_remote_requests
+            .insert(request_id.to_string(), expires_at_ms);
+        true
+    })
+}
+
+#[cfg(feature = "device-linking")]
+/// Forgets an approval that was declined or failed before it was sealed
+pub fn release_remote_approval(request_id: &str) {
+    with_state(|s| {
+        s.approved_remote_requests.remove(request_id);
+    })
+}
+
+// === MEMORY ===
+
+/// Live objects held in worker state (for GetMemoryStats)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateCounts {
+    pub pending_requests: usize,
+    pub confirmation_nonces: usize,
+    pub nonce_reservations: usize,
+    pub audit_entries: usize,
+    pub session_keys: usize,
+}
+
+pub fn state_counts() -> StateCounts {
+    with_state(|s| StateCounts {
+        pending_requests: s.pending_requests.len(),
+        confirmation_nonces: s.confirmation_nonces.len(),
+        nonce_reservations: s
+            .accounts
+            .values()
+            .map(|a| a.nonce_reservations.len())
+            .sum(),
+        audit_entries: s.accounts.values().map(|a| a.audit_log.len()).sum(),
+        session_keys: s.session_keys.len(),
+    })
+}
+
+// === DIAGNOSTICS ===
+
+/// A session key without its signing key (for DumpDiagnostics)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionKeyInfo {
+    pub public_key: String,
+    pub near_account_id: String,
+    pub receiver_id: String,
+    pub expires_at_ms: f64,
+}
+
+pub fn session_key_infos() -> Vec<SessionKeyInfo> {
+    with_state(|s| {
+        s.session_keys
+            .iter()
+            .map(|(public_key, k)| SessionKeyInfo {
+                public_key: public_key.clone(),
+                near_account_id: k.near_account_id.clone(),
+                receiver_id: k.receiver_id.clone(),
+                expires_at_ms: k.expires_at_ms,
+            })
+            .collect()
+    })
+}
+
+/// The latest `limit` audit entries across accounts, oldest first (ties in account order)
+pub fn recent_audit_entries(limit: usize) -> Vec<(AccountId, AuditEntry)> {
+    with_state(|s| {
+        let mut entries: Vec<(AccountId, AuditEntry)> = s
+            .accounts
+            .iter()
+            .flat_map(|(id, a)| a.audit_log.iter().map(move |e| (id.clone(), e.clone())))
+            .collect();
+        entries.sort_by(|(a_id, a), (b_id, b)| {
+            a.timestamp_ms
+                .total_cmp(&b.timestamp_ms)
+                .then_with(|| a_id.as_str().cmp(b_id.as_str()))
+        });
+        let skip = entries.len().saturating_sub(limit);
+        entries.split_off(skip)
+    })
+}
+
+/// Entries held by the worker's bounded caches (for DumpDiagnostics)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheCounts {
+    pub completed_requests: usize,
+    pub recent_receiver_caches: usize,
+    pub confirmed_intents: usize,
+    pub consumed_approvals: usize,
+    pub consumed_signing_intents: usize,
+}
+
+pub fn cache_counts() -> CacheCounts {
+    with_state(|s| CacheCounts {
+        completed_requests: s.completed_requests.len(),
+        recent_receiver_caches: s
+            .accounts
+            .values()
+            .filter(|a| a.recent_receivers.is_some())
+            .count(),
+        confirmed_intents: s.accounts.values().map(|a| a.confirmed_intents.len()).sum(),
+        consumed_approvals: s.consumed_approvals.len(),
+        consumed_signing_intents: s.consumed_signing_intents.len(),
+    })
+}
+
+/// Drops expired session keys, receiver histories, executed intents, accepted elevation
+/// challenges, completed approvals and remote confirmations, and returns spare collection capacity to the
+/// allocator. Live requests, reservations and the audit log are kept. Returns the number of
+/// expired session keys dropped.
+pub fn trim_state() -> usize {
+    let now = now_ms();
+    with_state(|s| {
+        let before = s.session_keys.len();
+        s.session_keys.retain(|_, k| k.expires_at_ms > now);
+        for account in s.accounts.values_mut() {
+            if account
+                .recent_receivers
+                .as_ref()
+                .is_some_and(|c| now - c.fetched_ms >= RECENT_RECEIVERS_CACHE_TTL_MS)
+            {
+                account.recent_receivers = None;
+            }
+            account.confirmed_intents.shrink_to_fit();
+            account.nonce_reservations.shrink_to_fit();
+            account.audit_log.shrink_to_fit();
+        }
+        s.accounts.shrink_to_fit();
+        s.consumed_signing_intents.retain(|_, expiry| *expiry > now);
+        s.consumed_signing_intents.shrink_to_fit();
+        s.consumed_elevation_challenges
+            .retain(|_, expiry| *expiry > now);
+        s.consumed_elevation_challenges.shrink_to_fit();
+        s.consumed_approvals.retain(|_, expiry| *expiry > now);
+        s.consumed_approvals.shrink_to_fit();
+        #[cfg(feature = "device-linking")]
+        {
+            s.remote_sessions.retain(|_, r| r.expires_at_ms > now);
+            s.remote_sessions.shrink_to_fit();
+            s.consumed_remote_sessions.retain(|_, expiry| *expiry > now);
+            s.consumed_remote_sessions.shrink_to_fit();
+            s.approved_remote_requests.retain(|_, expiry| *expiry > now);
+            s.approved_remote_requests.shrink_to_fit();
+        }
+        s.completed_requests.shrink_to_fit();
+        s.pending_requests.shrink_to_fit();
+        s.confirmation_nonces.shrink_to_fit();
+        s.session_keys.shrink_to_fit();
+        before - s.session_keys.len()
+    })
+}
+
+// === REQUEST GUARD ===
+
+/// Releases a request's confirmation nonce and nonce reservations when dropped,
+/// so every exit path of a signing handler leaves no dangling state.
+pub struct PendingRequestGuard {
+    account: AccountId,
+    request_id: String,
+}
+
+impl PendingRequestGuard {
+    pub fn new(account: &AccountId, request_id: &str) -> Self {
+        Self {
+            account: account.clone(),
+            request_id: request_id.to_string(),
+        }
+    }
+}
+
+impl Drop for PendingRequestGuard {
+    fn drop(&mut self) {
+        clear_confirmation_nonce(&self.request_id);
+        release_nonces(&self.account, &self.request_id);
+    }
+}
@@ -0,0 +1,254 @@
+// === STORAGE DEPOSITS ===
+// An ft_transfer or ft_transfer_call to an account the token contract has not registered fails
+// on chain ("account not registered"), so wallets put a storage_deposit for the receiver ahead
+// of the transfer. With `autoStorageDeposit`, SignTransactionsWithActions does it before the
+// user is prompted: for each (token contract, receiver) of the batch's transfers it views
+// `storage_balance_of({ account_id })` on the contract, and when that is null (unregistered)
+// inserts right before the first transfer to the receiver
+//   FunctionCall storage_deposit({ account_id, registration_only: true })
+// with the contract's `storage_balance_bounds().min` as deposit and STORAGE_DEPOSIT_GAS.
+//
+// The inserted actions are part of the batch from then on: the allowance check, the deposit
+// totals, the intent digest and the signed transactions all include them. The confirmation
+// lists each transaction's `autoInsertedActions` (action indexes, covered by the digest),
+// which the TxTree labels "added automatically", a StorageDepositsAdded warning leads the
+// summary blocks, and a request asking for uiMode "skip" is shown as a modal instead: an
+// insertion is never signed without being shown.
+//
+// Contracts that do not implement the storage standard (the view panics, or answers something
+// other than JSON) are left untouched, and so is a receiver the batch already registers with a
+// storage_deposit of its own. Any other view failure fails the request before the user is
+// prompted. Remote confirmations and signing intents never insert: their digest was computed
+// elsewhere, over the batch as sent.
+
+use std::collections::BTreeMap;
+
+use serde_json::{json, Value};
+
+use crate::actions::ActionParams;
+use crate::config::STORAGE_DEPOSIT_GAS;
+use crate::error::{RpcErrorKind, ViewCallError};
+use crate::handlers::handle_sign_transactions_with_actions::TransactionPayload;
+use crate::rpc_client::RpcClient;
+use crate::types::handlers::{ConfirmationConfig, ConfirmationUIMode};
+use crate::view_call::{view_call, view_call_params, ViewBlockReference};
+
+const TRANSFER_METHODS: [&str; 2] = ["ft_transfer", "ft_transfer_call"];
+
+/// A storage_deposit inserted into the batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageDepositInsertion {
+    pub tx_index: u32,
+    /// Index of the inserted action in its transaction
+    pub action_index: u32,
+    pub contract_id: String,
+    pub account_id: String,
+    pub deposit_yocto: u128,
+}
+
+/// What a token contract knows of an account's storage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Registration {
+    /// Registered, or registered by an earlier action of the batch
+    Registered,
+    Unregistered {
+        min_yocto: u128,
+    },
+    /// The contract does not implement the storage standard
+    Unsupported,
+}
+
+/// The `account_id` of a storage_deposit call on a contract (the signer when it names none)
+fn registered_account(action: &ActionParams, signer_id: &str) -> Option<String> {
+    match action {
+        ActionParams::FunctionCall {
+            method_name, args, ..
+        } if method_name == "storage_deposit" => {
+            let args: Value = serde_json::from_str(args).unwrap_or(Value::Null);
+            Some(match args.get("account_id").and_then(|a| a.as_str()) {
+                Some(account_id) => account_id.to_string(),
+                None => signer_id.to_string(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The `receiver_id` of an ft_transfer or ft_transfer_call
+fn transfer_receiver(action: &ActionParams) -> Option<String> {
+    match action {
+        ActionParams::FunctionCall {
+            method_name, args, ..
+        } if TRANSFER_METHODS.contains(&method_name.as_str()) => {
+            let args: Value = serde_json::from_str(args).ok()?;
+            args.get("receiver_id")
+                .and_then(|r| r.as_str())
+                .map(|r| r.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// The JSON a view method returns; None when it returns something else
+async fn view_json<R: RpcClient>(
+    rpc: &R,
+    contract_id: &str,
+    method_name: &str,
+    args: Value,
+) -> Result<Option<Value>, ViewCallError> {
+    let params = view_call_params(
+        contract_id,
+        method_name,
+        args.to_string().as_bytes(),
+        &ViewBlockReference::default(),
+    )
+    .map_err(|e| ViewCallError::Rpc(RpcErrorKind::InvalidResponse(e)))?;
+    Ok(view_call(rpc, params).await?.result_json)
+}
+
+async fn lookup_registration<R: RpcClient>(
+    rpc: &R,
+    contract_id: &str,
+    account_id: &str,
+) -> Result<Registration, ViewCallError> {
+    let balance = match view_json(
+        rpc,
+        contract_id,
+        "storage_balance_of",
+        json!({ "account_id": account_id }),
+    )
+    .await
+    {
+        Err(ViewCallError::ContractPanic { .. }) | Ok(None) => {
+            return Ok(Registration::Unsupported)
+        }
+        Err(e) => return Err(e),
+        Ok(Some(balance)) => balance,
+    };
+    if !balance.is_null() {
+        return Ok(Registration::Registered);
+    }
+    let bounds = match view_json(rpc, contract_id, "storage_balance_bounds", json!({})).await {
+        Err(ViewCallError::ContractPanic { .. }) => return Ok(Registration::Unsupported),
+        Err(e) => return Err(e),
+        Ok(bounds) => bounds,
+    };
+    let min_yocto = bounds
+        .as_ref()
+        .and_then(|b| b.get("min"))
+        .and_then(|m| m.as_str())
+        .and_then(|m| m.parse::<u128>().ok());
+    Ok(match min_yocto {
+        Some(min_yocto) => Registration::Unregistered { min_yocto },
+        None => Registration::Unsupported,
+    })
+}
+
+fn storage_deposit_action(account_id: &str, deposit_yocto: u128) -> ActionParams {
+    ActionParams::FunctionCall {
+        method_name: "storage_deposit".to_string(),
+        args: json!({ "account_id": account_id, "registration_only": true }).to_string(),
+        gas: STORAGE_DEPOSIT_GAS.to_string(),
+        deposit: deposit_yocto.to_string(),
+    }
+}
+
+/// Inserts a storage_deposit ahead of the first transfer to each receiver its token contract
+/// has not registered, in place. Transactions whose actions do not parse are left for the
+/// signing flow to refuse.
+pub async fn insert_storage_deposits<R: RpcClient>(
+    rpc: &R,
+    txs: &mut [TransactionPayload],
+) -> Result<Vec<StorageDepositInsertion>, ViewCallError> {
+    let mut known: BTreeMap<(String, String), Registration> = BTreeMap::new();
+    let mut insertions = Vec::new();
+    for (tx_index, tx) in txs.iter_mut().enumerate() {
+        let Ok(actions) = tx.parsed_actions() else {
+            continue;
+        };
+        let mut with_deposits = Vec::with_capacity(actions.len());
+        for action in actions {
+            if let Some(account_id) = registered_account(&action, &tx.near_account_id) {
+                known.insert(
+                    (tx.receiver_id.clone(), account_id),
+                    Registration::Registered,
+                );
+            }
+            if let Some(account_id) = transfer_receiver(&action) {
+                let key = (tx.receiver_id.clone(), account_id.clone());
+                let registration = match known.get(&key) {
+                    Some(registration) => *registration,
+                    None => lookup_registration(rpc, &tx.receiver_id, &account_id).await?,
+                };
+                if let Registration::Unregistered { min_yocto } = registration {
+                    insertions.push(StorageDepositInsertion {
+                        tx_index: tx_index as u32,
+                        action_index: with_deposits.len() as u32,
+                        contract_id: tx.receiver_id.clone(),
+                        account_id: account_id.clone(),
+                        deposit_yocto: min_yocto,
+                    });
+                    with_deposits.push(storage_deposit_action(&account_id, min_yocto));
+                }
+                known.insert(
+                    key,
+                    match registration {
+                        Registration::Unsupported => Registration::Unsupported,
+                        _ => Registration::Registered,
+                    },
+                );
+            }
+            with_deposits.push(action);
+        }
+        if insertions
+            .last()
+            .is_some_and(|i| i.tx_index == tx_index as u32)
+        {
+            match serde_json::to_string(&with_deposits) {
+                Ok(actions) => tx.actions = actions,
+                Err(_) => insertions.retain(|i| i.tx_index != tx_index as u32),
+            }
+        }
+    }
+    Ok(insertions)
+}
+
+/// Each transaction's inserted action indexes, for the confirmation (see SummaryPolicy); empty
+/// when nothing was inserted
+pub fn auto_inserted_actions(
+    insertions: &[StorageDepositInsertion],
+    tx_count: usize,
+) -> Vec<Vec<u32>> {
+    if insertions.is_empty() {
+        return Vec::new();
+    }
+    let mut per_tx = vec![Vec::new(); tx_count];
+    for insertion in insertions {
+        if let Some(indexes) = per_tx.get_mut(insertion.tx_index as usize) {
+            indexes.push(insertion.action_index);
+        }
+    }
+    per_tx
+}
+
+/// The confirmation config of a batch with inserted actions: a skip uiMode becomes modal, so
+/// the insertion is shown (a request without a config gets the default modal already)
+pub fn show_insertions(
+    config: Option<ConfirmationConfig>,
+    auto_inserted_actions: &[Vec<u32>],
+    logs: &mut Vec<String>,
+) -> Option<ConfirmationConfig> {
+    match config {
+        Some(mut config)
+            if config.ui_mode == ConfirmationUIMode::Skip
+                && auto_inserted_actions
+                    .iter()
+                    .any(|inserted| !inserted.is_empty()) =>
+        {
+            logs.push("Showing the confirmation for automatically inserted actions".to_string());
+            config.ui_mode = ConfirmationUIMode::Modal;
+            Some(config)
+        }
+        config => config,
+    }
+}
@@ -0,0 +1,103 @@
+// === STORED RECORD MIGRATION ===
+// IndexedDB records written by earlier releases, and the upgrade to the current schema.
+// PasskeyClientDB `users` records carry the encrypted VRF keypair, whose fields were written in
+// snake_case before the camelCase migration; PasskeyNearKeys `encryptedKeys` records predate the
+// per-device key path and may lack `deviceNumber`. The structs below accept every historical
+// shape (aliases for renamed fields, defaults for added ones) and serialize the current one;
+// fields they do not model are carried through untouched.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::vrf_blob_meta::VrfKeypairBlobMeta;
+
+/// Device number of records written before multi-device support: the registering device
+const LEGACY_DEVICE_NUMBER: u32 = 1;
+
+fn legacy_device_number() -> u32 {
+    LEGACY_DEVICE_NUMBER
+}
+
+/// `encryptedVrfKeypair` of a user record (EncryptedVRFKeypair in the VRF worker)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredEncryptedVrfKeypair {
+    #[serde(alias = "encrypted_vrf_data_b64u")]
+    pub encrypted_vrf_data_b64u: String,
+    #[serde(alias = "chacha20_nonce_b64u")]
+    pub chacha20_nonce_b64u: String,
+    /// Authenticated metadata, absent on keypairs written before it (see blob_migration.rs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<VrfKeypairBlobMeta>,
+}
+
+/// PasskeyClientDB `users` record
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredUserRecord {
+    pub near_account_id: String,
+    #[serde(default = "legacy_device_number")]
+    pub device_number: u32,
+    #[serde(alias = "encrypted_vrf_keypair")]
+    pub encrypted_vrf_keypair: StoredEncryptedVrfKeypair,
+    #[serde(flatten)]
+    pub other_fields: Map<String, Value>,
+}
+
+/// PasskeyNearKeys `encryptedKeys` record
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredNearKeyRecord {
+    pub near_account_id: String,
+    #[serde(default = "legacy_device_number")]
+    pub device_number: u32,
+    pub encrypted_data: String,
+    pub iv: String,
+    /// Absent on records stored before the outer wrap existed (unwrapped)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outer_wrap: Option<String>,
+    #[serde(flatten)]
+    pub other_fields: Map<String, Value>,
+}
+
+/// A stored record, told apart by the fields only its store has
+#[derive(Debug, Clone, PartialEq)]
+pub enum StoredRecord {
+    User(StoredUserRecord),
+    NearKey(StoredNearKeyRecord),
+}
+
+impl StoredRecord {
+    pub fn from_json(record: Value) -> Result<StoredRecord, String> {
+        let has_field = |name: &str| record.get(name).is_some();
+        if has_field("encryptedVrfKeypair") || has_field("encrypted_vrf_keypair") {
+            serde_json::from_value(record)
+                .map(StoredRecord::User)
+                .map_err(|e| format!("Invalid stored user record: {}", e))
+        } else if has_field("encryptedData") {
+            serde_json::from_value(record)
+                .map(StoredRecord::NearKey)
+                .map_err(|e| format!("Invalid stored key record: {}", e))
+        } else {
+            Err(
+                "Unrecognized stored record: expected a user record (encryptedVrfKeypair) \
+                 or a key record (encryptedData)"
+                    .to_string(),
+            )
+        }
+    }
+
+    pub fn to_json(&self) -> Result<Value, String> {
+        match self {
+            StoredRecord::User(record) => serde_json::to_value(record),
+            StoredRecord::NearKey(record) => serde_json::to_value(record),
+        }
+        .map_err(|e| format!("Failed to serialize stored record: {}", e))
+    }
+}
+
+/// Rewrites a record from any earlier release in the current schema.
+/// Current records come back unchanged, so the result can be compared to decide on a rewrite.
+pub fn migrate_stored_record(record: Value) -> Result<Value, String> {
+    StoredRecord::from_json(record)?.to_json()
+}
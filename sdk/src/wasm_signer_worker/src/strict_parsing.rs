@@ -0,0 +1,400 @@
+// === STRICT PARSING ===
+// With `WorkerPolicy.strictParsing`, security-relevant payloads are refused when they carry a
+// field the worker does not recognise, so a typo (say `confirmationConifg`) cannot silently
+// drop a control. serde's `deny_unknown_fields` cannot be used directly: the payload types
+// stay lenient by default, and the actions inside transaction payloads are an internally
+// tagged enum carried as a JSON string, which `deny_unknown_fields` does not compose with.
+// Instead the raw payload is checked against the field lists below before the typed parse.
+
+use serde_json::Value;
+
+use crate::error::StrictParseError;
+use crate::types::worker_messages::WorkerRequestType;
+
+/// Expected shape of a field's value
+#[derive(Clone, Copy)]
+enum Field {
+    /// Not checked further
+    Any,
+    Object(Fields),
+    List(Fields),
+    /// Object keyed by caller-chosen names (e.g. account IDs), each value an Object
+    Map(Fields),
+    /// JSON string holding a list of ActionParams (tagged by `action_type`)
+    ActionsJson,
+}
+
+type Fields = &'static [(&'static str, Field)];
+
+/// RpcCallPayload (the successor of VerificationPayload)
+const RPC_CALL_FIELDS: Fields = &[
+    ("contractId", Field::Any),
+    ("nearRpcUrl", Field::Any),
+    ("nearAccountId", Field::Any),
+];
+
+const RPC_OVERRIDES_FIELDS: Fields = &[("verification", Field::Any), ("broadcast", Field::Any)];
+
+const KEY_BLOB_CANDIDATE_FIELDS: Fields = &[
+    ("publicKey", Field::Any),
+    ("encryptedPrivateKeyData", Field::Any),
+    ("encryptedPrivateKeyIv", Field::Any),
+];
+
+const DECRYPTION_FIELDS: Fields = &[
+    ("encryptedPrivateKeyData", Field::Any),
+    ("encryptedPrivateKeyIv", Field::Any),
+    ("candidates", Field::List(KEY_BLOB_CANDIDATE_FIELDS)),
+];
+
+const CONFIRMATION_CONFIG_FIELDS: Fields = &[
+    ("uiMode", Field::Any),
+    ("behavior", Field::Any),
+    ("autoProceedDelay", Field::Any),
+    ("confirmationTimeoutMs", Field::Any),
+    ("theme", Field::Any),
+    ("locale", Field::Any),
+];
+
+const RELAYER_POLICY_FIELDS: Fields = &[
+    ("url", Field::Any),
+    ("authHeaderName", Field::Any),
+    ("responseSchemaVersion", Field::Any),
+];
+
+const TELEMETRY_POLICY_FIELDS: Fields = &[
+    ("endpoint", Field::Any),
+    ("samplingRate", Field::Any),
+    ("flushIntervalMinutes", Field::Any),
+];
+
+const MESSAGE_SIZE_LIMITS_FIELDS: Fields = &[
+    ("defaultBytes", Field::Any),
+    ("deployBytes", Field::Any),
+    ("byType", Field::Any),
+];
+
+const ACCOUNT_POLICY_OVERRIDE_FIELDS: Fields = &[
+    ("preSignHook", Field::Any),
+    ("postSignHook", Field::Any),
+    ("hookTimeoutMs", Field::Any),
+    ("allowedRpcOrigins", Field::Any),
+    ("maxSigningIntentTtlMs", Field::Any),
+    ("depositEscalationYocto", Field::Any),
+    ("lowAllowanceWarningYocto", Field::Any),
+    ("allowedRedirectOrigins", Field::Any),
+];
+
+const ARGS_SCHEMA_FIELDS: Fields = &[
+    ("contractId", Field::Any),
+    ("methodName", Field::Any),
+    ("schema", Field::Any),
+];
+
+const WORKER_POLICY_FIELDS: Fields = &[
+    ("preSignHook", Field::Any),
+    ("postSignHook", Field::Any),
+    ("hookTimeoutMs", Field::Any),
+    ("averageBlockTimeMs", Field::Any),
+    ("indexerUrl", Field::Any),
+    ("strictParsing", Field::Any),
+    ("allowedRpcOrigins", Field::Any),
+    ("maxSigningIntentTtlMs", Field::Any),
+    ("relayer", Field::Object(RELAYER_POLICY_FIELDS)),
+    ("depositEscalationYocto", Field::Any),
+    ("lowAllowanceWarningYocto", Field::Any),
+    ("prfFallbackSchemes", Field::Any),
+    ("telemetry", Field::Object(TELEMETRY_POLICY_FIELDS)),
+    ("accountOverrides", Field::Map(ACCOUNT_POLICY_OVERRIDE_FIELDS)),
+    ("argsSchemas", Field::List(ARGS_SCHEMA_FIELDS)),
+    ("requireEncryptedSecrets", Field::Any),
+    ("messageSizeLimits", Field::Object(MESSAGE_SIZE_LIMITS_FIELDS)),
+    ("failOnDeprecated", Field::Any),
+    ("blockFullAccessAddKey", Field::Any),
+    ("maxArgPreviewBytes", Field::Any),
+    ("maxSummaryBlocks", Field::Any),
+    ("maxSessionDurationMs", Field::Any),
+    ("allowedRedirectOrigins", Field::Any),
+];
+
+const TRANSACTION_FIELDS: Fields = &[
+    ("nearAccountId", Field::Any),
+    ("receiverId", Field::Any),
+    ("actions", Field::ActionsJson),
+];
+
+/// SignTransactionsWithActions transactions may also name their dependencies (see
+/// tx_dependencies.rs)
+const SIGNED_TRANSACTION_FIELDS: Fields = &[
+    ("nearAccountId", Field::Any),
+    ("receiverId", Field::Any),
+    ("actions", Field::ActionsJson),
+    ("dependsOn", Field::Any),
+];
+
+const CONDITIONAL_BATCH_FIELDS: Fields = &[("fallbackToUnconditional", Field::Any)];
+
+const SIGN_TRANSACTIONS_WITH_ACTIONS_FIELDS: Fields = &[
+    ("rpcCall", Field::Object(RPC_CALL_FIELDS)),
+    ("decryption", Field::Object(DECRYPTION_FIELDS)),
+    ("txSigningRequests", Field::List(SIGNED_TRANSACTION_FIELDS)),
+    (
+        "confirmationConfig",
+        Field::Object(CONFIRMATION_CONFIG_FIELDS),
+    ),
+    ("workerPolicy", Field::Object(WORKER_POLICY_FIELDS)),
+    ("idempotencyKey", Field::Any),
+    ("rpcOverrides", Field::Object(RPC_OVERRIDES_FIELDS)),
+    ("broadcast", Field::Any),
+    ("validUntilBlockHeight", Field::Any),
+    ("transactionContext", Field::Any),
+    ("signingPublicKey", Field::Any),
+    ("deduplicate", Field::Any),
+    ("previewDigest", Field::Any),
+    ("redactPaths", Field::Any),
+    ("conditionalBatch", Field::Object(CONDITIONAL_BATCH_FIELDS)),
+    ("autoStorageDeposit", Field::Any),
+];
+
+const STAGING_CONTRACT_FIELDS: Fields = &[
+    ("receiverId", Field::Any),
+    ("stageMethod", Field::Any),
+    ("deployMethod", Field::Any),
+    ("stageGas", Field::Any),
+    ("stageDeposit", Field::Any),
+    ("deployGas", Field::Any),
+];
+
+const DEPLOY_LARGE_CONTRACT_FIELDS: Fields = &[
+    ("rpcCall", Field::Object(RPC_CALL_FIELDS)),
+    ("decryption", Field::Object(DECRYPTION_FIELDS)),
+    ("code", Field::Any),
+    ("chunkSize", Field::Any),
+    ("staging", Field::Object(STAGING_CONTRACT_FIELDS)),
+    (
+        "confirmationConfig",
+        Field::Object(CONFIRMATION_CONFIG_FIELDS),
+    ),
+    ("workerPolicy", Field::Object(WORKER_POLICY_FIELDS)),
+    ("rpcOverrides", Field::Object(RPC_OVERRIDES_FIELDS)),
+];
+
+const CREATE_SIGNING_INTENT_FIELDS: Fields = &[
+    ("rpcCall", Field::Object(RPC_CALL_FIELDS)),
+    ("txSigningRequests", Field::List(TRANSACTION_FIELDS)),
+    (
+        "confirmationConfig",
+        Field::Object(CONFIRMATION_CONFIG_FIELDS),
+    ),
+    ("workerPolicy", Field::Object(WORKER_POLICY_FIELDS)),
+    ("ttlMs", Field::Any),
+];
+
+const EXECUTE_SIGNING_INTENT_FIELDS: Fields = &[
+    ("intentToken", Field::Any),
+    ("rpcCall", Field::Object(RPC_CALL_FIELDS)),
+    ("decryption", Field::Object(DECRYPTION_FIELDS)),
+    ("txSigningRequests", Field::List(TRANSACTION_FIELDS)),
+    ("workerPolicy", Field::Object(WORKER_POLICY_FIELDS)),
+    ("rpcOverrides", Field::Object(RPC_OVERRIDES_FIELDS)),
+];
+
+const CREATE_REMOTE_CONFIRMATION_FIELDS: Fields = &[
+    ("rpcCall", Field::Object(RPC_CALL_FIELDS)),
+    ("txSigningRequests", Field::List(TRANSACTION_FIELDS)),
+    ("vrfChallenge", Field::Any),
+    ("workerPolicy", Field::Object(WORKER_POLICY_FIELDS)),
+    ("ttlMs", Field::Any),
+];
+
+const COMPLETE_REMOTE_CONFIRMATION_FIELDS: Fields = &[
+    ("approval", Field::Any),
+    ("rpcCall", Field::Object(RPC_CALL_FIELDS)),
+    ("decryption", Field::Object(DECRYPTION_FIELDS)),
+    ("txSigningRequests", Field::List(TRANSACTION_FIELDS)),
+    ("transactionContext", Field::Any),
+    ("workerPolicy", Field::Object(WORKER_POLICY_FIELDS)),
+    ("rpcOverrides", Field::Object(RPC_OVERRIDES_FIELDS)),
+];
+
+const SUBMIT_TO_RELAYER_FIELDS: Fields = &[
+    ("nearAccountId", Field::Any),
+    ("newPublicKey", Field::Any),
+    ("deviceNumber", Field::Any),
+    ("vrfChallenge", Field::Any),
+    ("credential", Field::Any),
+    ("deterministicVrfPublicKey", Field::Any),
+    ("authenticatorOptions", Field::Any),
+    ("signedDelegateAction", Field::Any),
+    ("authToken", Field::Any),
+    ("workerPolicy", Field::Object(WORKER_POLICY_FIELDS)),
+];
+
+const COMPLETE_REGISTRATION_FIELDS: Fields = &[
+    ("resumeToken", Field::Any),
+    ("dualPrfOutputs", Field::Any),
+    ("nearAccountId", Field::Any),
+    ("deviceNumber", Field::Any),
+    ("vrfChallenge", Field::Any),
+    ("credential", Field::Any),
+    ("deterministicVrfPublicKey", Field::Any),
+    ("authenticatorOptions", Field::Any),
+    ("signedDelegateAction", Field::Any),
+    ("authToken", Field::Any),
+    ("workerPolicy", Field::Object(WORKER_POLICY_FIELDS)),
+];
+
+const BUILD_REDIRECT_PAYLOAD_FIELDS: Fields = &[
+    ("nearAccountId", Field::Any),
+    ("sessionPublicKey", Field::Any),
+    ("vrfChallenge", Field::Any),
+    ("callbackUrl", Field::Any),
+    ("state", Field::Any),
+    ("ttlMs", Field::Any),
+    ("workerPolicy", Field::Object(WORKER_POLICY_FIELDS)),
+];
+
+const GET_RECENT_RECEIVERS_FIELDS: Fields = &[
+    ("accountId", Field::Any),
+    ("limit", Field::Any),
+    ("workerPolicy", Field::Object(WORKER_POLICY_FIELDS)),
+];
+
+/// Fields of each ActionParams variant, besides the `action_type` tag
+fn action_fields(action_type: &str) -> Option<&'static [&'static str]> {
+    Some(match action_type {
+        "CreateAccount" => &[],
+        "DeployContract" => &["code"],
+        "FunctionCall" => &["method_name", "args", "gas", "deposit"],
+        "Transfer" => &["deposit"],
+        "Stake" => &["stake", "public_key"],
+        "AddKey" => &["public_key", "access_key"],
+        "DeleteKey" => &["public_key"],
+        "DeleteAccount" => &["beneficiary_id"],
+        _ => return None,
+    })
+}
+
+/// Request types carrying a WorkerPolicy, i.e. those that can opt into strict parsing
+fn request_fields(request_type: WorkerRequestType) -> Option<Fields> {
+    match request_type {
+        WorkerRequestType::SignTransactionsWithActions
+        | WorkerRequestType::SignTransactionsWithFreshChallenge => {
+            Some(SIGN_TRANSACTIONS_WITH_ACTIONS_FIELDS)
+        }
+        WorkerRequestType::GetRecentReceivers => Some(GET_RECENT_RECEIVERS_FIELDS),
+        WorkerRequestType::DeployLargeContract => Some(DEPLOY_LARGE_CONTRACT_FIELDS),
+        WorkerRequestType::CreateSigningIntent => Some(CREATE_SIGNING_INTENT_FIELDS),
+        WorkerRequestType::ExecuteSigningIntent => Some(EXECUTE_SIGNING_INTENT_FIELDS),
+        WorkerRequestType::CreateRemoteConfirmation => Some(CREATE_REMOTE_CONFIRMATION_FIELDS),
+        WorkerRequestType::CompleteRemoteConfirmation => Some(COMPLETE_REMOTE_CONFIRMATION_FIELDS),
+        WorkerRequestType::SubmitToRelayer => Some(SUBMIT_TO_RELAYER_FIELDS),
+        WorkerRequestType::CompleteRegistration => Some(COMPLETE_REGISTRATION_FIELDS),
+        WorkerRequestType::BuildRedirectPayload => Some(BUILD_REDIRECT_PAYLOAD_FIELDS),
+        _ => None,
+    }
+}
+
+/// First pass: whether the payload's WorkerPolicy asks for strict parsing
+pub fn strict_parsing_requested(payload: &Value) -> bool {
+    payload.pointer("/workerPolicy/strictParsing") == Some(&Value::Bool(true))
+}
+
+/// Rejects the first field not recognised by the request's security-relevant types, when
+/// the payload opted into strict parsing. Values of the wrong type are left to the typed
+/// parse to report. Paths into the actions JSON string continue as if it were inline JSON
+/// (e.g. `/txSigningRequests/0/actions/1/method_nme`).
+pub fn check_unknown_fields(
+    request_type: WorkerRequestType,
+    payload: &Value,
+) -> Result<(), StrictParseError> {
+    match request_fields(request_type) {
+        Some(fields) if strict_parsing_requested(payload) => check_object(payload, "", fields),
+        _ => Ok(()),
+    }
+}
+
+fn check_object(value: &Value, path: &str, fields: Fields) -> Result<(), StrictParseError> {
+    let Some(object) = value.as_object() else {
+        return Ok(());
+    };
+    for (key, child) in object {
+        let child_path = format!("{}/{}", path, escape_pointer_token(key));
+        let field = fields
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, field)| *field)
+            .ok_or_else(|| unknown_field(&child_path))?;
+        check_field(child, &child_path, field)?;
+    }
+    Ok(())
+}
+
+fn check_field(value: &Value, path: &str, field: Field) -> Result<(), StrictParseError> {
+    match field {
+        Field::Any => Ok(()),
+        Field::Object(fields) => check_object(value, path, fields),
+        Field::List(fields) => match value.as_array() {
+            Some(items) => items
+                .iter()
+                .enumerate()
+                .try_for_each(|(i, item)| check_object(item, &format!("{}/{}", path, i), fields)),
+            None => Ok(()),
+        },
+        Field::Map(fields) => match value.as_object() {
+            Some(entries) => entries.iter().try_for_each(|(key, entry)| {
+                check_object(entry, &format!("{}/{}", path, escape_pointer_token(key)), fields)
+            }),
+            None => Ok(()),
+        },
+        Field::ActionsJson => {
+            let Some(actions) = value
+                .as_str()
+                .and_then(|s| serde_json::from_str::<Value>(s).ok())
+            else {
+                return Ok(());
+            };
+            let Some(actions) = actions.as_array() else {
+                return Ok(());
+            };
+            actions
+                .iter()
+                .enumerate()
+                .try_for_each(|(i, action)| check_action(action, &format!("{}/{}", path, i)))
+        }
+    }
+}
+
+fn check_action(action: &Value, path: &str) -> Result<(), StrictParseError> {
+    let Some(object) = action.as_object() else {
+        return Ok(());
+    };
+    let Some(fields) = object
+        .get("action_type")
+        .and_then(Value::as_str)
+        .and_then(action_fields)
+    else {
+        return Ok(());
+    };
+    for key in object.keys() {
+        if key != "action_type" && !fields.contains(&key.as_str()) {
+            return Err(unknown_field(&format!(
+                "{}/{}",
+                path,
+                escape_pointer_token(key)
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn unknown_field(path: &str) -> StrictParseError {
+    StrictParseError::UnknownField {
+        path: path.to_string(),
+    }
+}
+
+/// RFC 6901 escaping of a JSON pointer reference token
+fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
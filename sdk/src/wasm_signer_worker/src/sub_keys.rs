@@ -0,0 +1,242 @@
+// === SUB-KEYS ===
+// Besides its account key, an account can have sub-keys (a per-app key, a trading key, a
+// cold key) derived deterministically from the same passkey PRF output, so they come back
+// with the passkey like the account key does. The account key keeps its HKDF derivation
+// (crypto::derive_ed25519_key_from_prf_output) and has no path: nothing changes for existing
+// accounts. Sub-keys follow SLIP-0010 for ed25519 from a 64-byte seed, HKDF-derived from the
+// Ed25519 PRF output with the account's near-key salt and SUB_KEY_SEED_HKDF_INFO, so the seed
+// is per account and unrelated to the account key's material.
+//
+// SLIP-0010 only defines hardened derivation for ed25519: every index of a path must be
+// hardened, written `'`, `h` or `H` ("m/44'/397'/0'/0'/1'"), and below 2^31.
+//
+// DeriveSubKey returns a sub-key's public key and, on request, its private key encrypted like
+// the account key (the blob decrypts with the account's ChaCha20 PRF output). Registration
+// and recovery derive the paths listed in `subKeyPaths` and return them with their public
+// keys, which the TS layer records with the account's key.
+
+use std::fmt;
+
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Sha256, Sha512};
+use wasm_bindgen::prelude::*;
+use zeroize::Zeroizing;
+
+use crate::config::{
+    near_key_salt_for_account, ERROR_EMPTY_PRF_OUTPUT, MAX_DERIVATION_DEPTH, MAX_SUB_KEY_PATHS,
+    SLIP10_ED25519_CURVE_KEY, SUB_KEY_SEED_HKDF_INFO, SUB_KEY_SEED_SIZE,
+};
+use crate::encoders::base64_url_decode;
+use crate::error::{DerivationPathError, KdfError};
+
+/// Added to an index to harden it
+pub const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// A validated sub-key path: hardened indexes only, at most MAX_DERIVATION_DEPTH deep
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivationPath {
+    /// Indexes before hardening
+    indexes: Vec<u32>,
+}
+
+impl DerivationPath {
+    pub fn parse(path: &str) -> Result<Self, DerivationPathError> {
+        let invalid = |reason: String| DerivationPathError {
+            path: path.to_string(),
+            reason,
+        };
+        let mut segments = path.split('/');
+        if segments.next() != Some("m") {
+            return Err(invalid("must start with \"m\"".to_string()));
+        }
+        let indexes = segments
+            .map(|segment| {
+                let digits = segment
+                    .strip_suffix(['\'', 'h', 'H'])
+                    .ok_or_else(|| invalid(format!("index \"{}\" is not hardened", segment)))?;
+                if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(invalid(format!("index \"{}\" is not a number", segment)));
+                }
+                digits
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|index| *index < HARDENED_OFFSET)
+                    .ok_or_else(|| invalid(format!("index \"{}\" is 2^31 or more", segment)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if indexes.is_empty() {
+            return Err(invalid(
+                "derives the master key; give at least one index".to_string(),
+            ));
+        }
+        if indexes.len() > MAX_DERIVATION_DEPTH {
+            return Err(invalid(format!(
+                "is {} deep, more than {}",
+                indexes.len(),
+                MAX_DERIVATION_DEPTH
+            )));
+        }
+        Ok(DerivationPath { indexes })
+    }
+
+    pub fn indexes(&self) -> &[u32] {
+        &self.indexes
+    }
+}
+
+/// Canonical form, with `'` for hardened indexes
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "m")?;
+        for index in &self.indexes {
+            write!(f, "/{}'", index)?;
+        }
+        Ok(())
+    }
+}
+
+/// A SLIP-0010 node: private key and chain code
+pub struct ExtendedKey {
+    pub key: Zeroizing<[u8; 32]>,
+    pub chain_code: Zeroizing<[u8; 32]>,
+}
+
+impl ExtendedKey {
+    fn from_hmac(key: &[u8], data: &[&[u8]]) -> ExtendedKey {
+        let mut mac =
+            <Hmac<Sha512> as Mac>::new_from_slice(key).expect("HMAC accepts keys of any size");
+        for part in data {
+            mac.update(part);
+        }
+        let output = Zeroizing::new(<[u8; 64]>::from(mac.finalize().into_bytes()));
+        let mut extended = ExtendedKey {
+            key: Zeroizing::new([0u8; 32]),
+            chain_code: Zeroizing::new([0u8; 32]),
+        };
+        extended.key.copy_from_slice(&output[..32]);
+        extended.chain_code.copy_from_slice(&output[32..]);
+        extended
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        ed25519_dalek::SigningKey::from_bytes(&self.key)
+            .verifying_key()
+            .to_bytes()
+    }
+}
+
+/// SLIP-0010 master key of `seed` for ed25519
+pub fn slip10_master_key(seed: &[u8]) -> ExtendedKey {
+    ExtendedKey::from_hmac(SLIP10_ED25519_CURVE_KEY, &[seed])
+}
+
+/// Hardened child `index` (before hardening) of `parent`
+pub fn slip10_derive_child(parent: &ExtendedKey, index: u32) -> ExtendedKey {
+    let hardened = (index | HARDENED_OFFSET).to_be_bytes();
+    ExtendedKey::from_hmac(
+        parent.chain_code.as_slice(),
+        &[&[0u8], parent.key.as_slice(), &hardened],
+    )
+}
+
+/// Key at `path` below the master key of `seed`
+pub fn slip10_derive_path(seed: &[u8], path: &DerivationPath) -> ExtendedKey {
+    path.indexes()
+        .iter()
+        .fold(slip10_master_key(seed), |parent, index| {
+            slip10_derive_child(&parent, *index)
+        })
+}
+
+/// The account's sub-key seed, from the Ed25519 PRF output (prf.results.second)
+pub fn sub_key_seed(
+    ed25519_prf_output_base64: &str,
+    account_id: &str,
+) -> Result<Zeroizing<[u8; SUB_KEY_SEED_SIZE]>, KdfError> {
+    let prf_output = Zeroizing::new(base64_url_decode(ed25519_prf_output_base64)?);
+    if prf_output.is_empty() {
+        return Err(KdfError::InvalidInput(ERROR_EMPTY_PRF_OUTPUT.to_string()));
+    }
+    let salt = near_key_salt_for_account(account_id);
+    let hk = Hkdf::<Sha256>::new(Some(salt.as_bytes()), &prf_output);
+    let mut seed = Zeroizing::new([0u8; SUB_KEY_SEED_SIZE]);
+    hk.expand(SUB_KEY_SEED_HKDF_INFO.as_bytes(), seed.as_mut_slice())
+        .map_err(|_| KdfError::HkdfError)?;
+    Ok(seed)
+}
+
+/// NEAR private and public key ("ed25519:<base58>") of the account's sub-key at `path`
+pub fn derive_sub_key(
+    ed25519_prf_output_base64: &str,
+    account_id: &str,
+    path: &DerivationPath,
+) -> Result<(Zeroizing<String>, String), KdfError> {
+    let seed = sub_key_seed(ed25519_prf_output_base64, account_id)?;
+    let node = slip10_derive_path(seed.as_slice(), path);
+    let public_key = node.public_key();
+    let near_private_key_bytes = Zeroizing::new([node.key.as_slice(), &public_key].concat());
+    Ok((
+        Zeroizing::new(format!(
+            "ed25519:{}",
+            bs58::encode(near_private_key_bytes.as_slice()).into_string()
+        )),
+        format!("ed25519:{}", bs58::encode(public_key).into_string()),
+    ))
+}
+
+/// A derived sub-key, as recorded with the account's key
+#[wasm_bindgen]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SubKeyEntry {
+    /// Canonical path ("m/44'/397'/0'/0'/1'")
+    #[wasm_bindgen(getter_with_clone)]
+    pub path: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "publicKey")]
+    pub public_key: String,
+}
+
+/// Validates the `subKeyPaths` of a registration or recovery: at most MAX_SUB_KEY_PATHS,
+/// each valid and listed once (compared in canonical form)
+pub fn parse_sub_key_paths(paths: &[String]) -> Result<Vec<DerivationPath>, DerivationPathError> {
+    if paths.len() > MAX_SUB_KEY_PATHS {
+        return Err(DerivationPathError {
+            path: paths[MAX_SUB_KEY_PATHS].clone(),
+            reason: format!("is past the {} sub-key paths allowed", MAX_SUB_KEY_PATHS),
+        });
+    }
+    let mut parsed: Vec<DerivationPath> = Vec::with_capacity(paths.len());
+    for path in paths {
+        let derivation_path = DerivationPath::parse(path)?;
+        if parsed.contains(&derivation_path) {
+            return Err(DerivationPathError {
+                path: path.clone(),
+                reason: "is listed twice".to_string(),
+            });
+        }
+        parsed.push(derivation_path);
+    }
+    Ok(parsed)
+}
+
+/// Public keys of the account's sub-keys at `paths`, for a registration or recovery
+pub fn derive_sub_key_entries(
+    ed25519_prf_output_base64: &str,
+    account_id: &str,
+    paths: &[String],
+) -> Result<Vec<SubKeyEntry>, String> {
+    parse_sub_key_paths(paths)
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|path| {
+            let (_, public_key) = derive_sub_key(ed25519_prf_output_base64, account_id, path)
+                .map_err(|e| format!("Failed to derive sub-key {}: {}", path, e))?;
+            Ok(SubKeyEntry {
+                path: path.to_string(),
+                public_key,
+            })
+        })
+        .collect()
+}
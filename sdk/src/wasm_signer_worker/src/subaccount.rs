@@ -0,0 +1,166 @@
+// === SUBACCOUNT CREATION ===
+// Apps that give their users subaccounts (alice.myapp.near) sign the same transaction every
+// time, from the parent account:
+//
+//   receiver <prefix>.<parent>: CreateAccount, Transfer(initial balance), AddKey(full access)
+//
+// AccountSuffixPolicy holds the parent and checks that a name is one of its direct children
+// (the only accounts it can create). compose_create_subaccount validates the inputs against it,
+// refuses with NotAChildAccount when the signer is not the parent, and returns the transaction
+// for the normal confirm-and-sign path. Confirmations show a transaction of this shape as one
+// "Create account X with Y NEAR" heading in place of the three actions' blocks (see
+// confirmation_blocks.rs); the FullAccessKey warning for the new key still leads it.
+
+use crate::actions::ActionParams;
+use crate::config::{MAX_ACCOUNT_ID_LEN, MIN_ACCOUNT_ID_LEN};
+use crate::confirmation_blocks::is_full_access_key;
+use crate::error::SubaccountError;
+use crate::key_selection::parse_public_key;
+
+/// The parent account subaccounts are created under
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountSuffixPolicy {
+    pub parent_id: String,
+}
+
+impl AccountSuffixPolicy {
+    pub fn new(parent_id: &str) -> Result<Self, SubaccountError> {
+        validate_account_id(parent_id).map_err(|reason| SubaccountError::Invalid {
+            field: "parentAccountId",
+            reason,
+        })?;
+        Ok(AccountSuffixPolicy {
+            parent_id: parent_id.to_string(),
+        })
+    }
+
+    /// Whether `account_id` is `<one part>.<parent>`
+    pub fn is_child(&self, account_id: &str) -> bool {
+        account_id
+            .strip_suffix(&self.parent_id)
+            .and_then(|prefix| prefix.strip_suffix('.'))
+            .is_some_and(|prefix| validate_part(prefix).is_ok())
+    }
+
+    /// `<prefix>.<parent>`, refused unless it is a valid direct child
+    pub fn child_account_id(&self, prefix: &str) -> Result<String, SubaccountError> {
+        let account_id = format!("{}.{}", prefix, self.parent_id);
+        // A dot would make a grandchild, which only the intermediate account can create
+        if prefix.contains('.') {
+            return Err(SubaccountError::NotAChildAccount {
+                account_id,
+                signer_id: self.parent_id.clone(),
+            });
+        }
+        let invalid = |reason: String| SubaccountError::Invalid {
+            field: "childPrefix",
+            reason,
+        };
+        validate_part(prefix).map_err(invalid)?;
+        if account_id.len() > MAX_ACCOUNT_ID_LEN {
+            return Err(invalid(format!(
+                "{} is {} characters long, above {}",
+                account_id,
+                account_id.len(),
+                MAX_ACCOUNT_ID_LEN
+            )));
+        }
+        Ok(account_id)
+    }
+}
+
+/// One dot-separated part of an account ID: lowercase letters and digits, with single `-` or
+/// `_` separators between them
+fn validate_part(part: &str) -> Result<(), String> {
+    if part.is_empty() {
+        return Err("empty account ID part".to_string());
+    }
+    let mut previous_separator = true;
+    for c in part.chars() {
+        let separator = c == '-' || c == '_';
+        if !separator && !c.is_ascii_lowercase() && !c.is_ascii_digit() {
+            return Err(format!(
+                "{:?} is not allowed in {} (only a-z, 0-9, - and _)",
+                c, part
+            ));
+        }
+        if separator && previous_separator {
+            return Err(format!(
+                "{} has a separator at its start or next to another",
+                part
+            ));
+        }
+        previous_separator = separator;
+    }
+    if previous_separator {
+        return Err(format!("{} ends with a separator", part));
+    }
+    Ok(())
+}
+
+/// NEAR account ID rules: 2 to 64 characters, dot-separated valid parts
+pub fn validate_account_id(account_id: &str) -> Result<(), String> {
+    if !(MIN_ACCOUNT_ID_LEN..=MAX_ACCOUNT_ID_LEN).contains(&account_id.len()) {
+        return Err(format!(
+            "{} must be {} to {} characters long",
+            account_id, MIN_ACCOUNT_ID_LEN, MAX_ACCOUNT_ID_LEN
+        ));
+    }
+    account_id.split('.').try_for_each(validate_part)
+}
+
+/// The canonical subaccount creation transaction: (receiver, actions)
+pub fn compose_create_subaccount(
+    signer_id: &str,
+    policy: &AccountSuffixPolicy,
+    prefix: &str,
+    initial_balance_yocto: &str,
+    public_key: &str,
+) -> Result<(String, Vec<ActionParams>), SubaccountError> {
+    let account_id = policy.child_account_id(prefix)?;
+    // Only the parent can create its children
+    let signer = AccountSuffixPolicy {
+        parent_id: signer_id.to_string(),
+    };
+    if !signer.is_child(&account_id) {
+        return Err(SubaccountError::NotAChildAccount {
+            account_id,
+            signer_id: signer_id.to_string(),
+        });
+    }
+    initial_balance_yocto
+        .parse::<u128>()
+        .map_err(|e| SubaccountError::Invalid {
+            field: "initialBalance",
+            reason: format!("{}: {}", initial_balance_yocto, e),
+        })?;
+    parse_public_key(public_key).map_err(|reason| SubaccountError::Invalid {
+        field: "publicKey",
+        reason,
+    })?;
+    Ok((
+        account_id,
+        vec![
+            ActionParams::CreateAccount,
+            ActionParams::Transfer {
+                deposit: initial_balance_yocto.to_string(),
+            },
+            ActionParams::AddKey {
+                public_key: public_key.to_string(),
+                access_key: serde_json::json!({ "nonce": 0, "permission": { "FullAccess": {} } })
+                    .to_string(),
+            },
+        ],
+    ))
+}
+
+/// Initial balance of a transaction with the canonical subaccount creation actions
+pub fn as_create_subaccount(actions: &[ActionParams]) -> Option<u128> {
+    match actions {
+        [ActionParams::CreateAccount, ActionParams::Transfer { deposit }, ActionParams::AddKey {
+            access_key,
+            ..
+        }] if is_full_access_key(access_key) => deposit.parse().ok(),
+        _ => None,
+    }
+}
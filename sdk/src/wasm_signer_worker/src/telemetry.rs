@@ -0,0 +1,444 @@
+// === TELEMETRY ===
+// Per-handler request counters and latency histograms. Every tracked request is counted in
+// memory and reported by GetTelemetrySnapshot. Remote reporting is opt-in: with
+// WorkerPolicy.telemetry, a sampled share of requests is also counted towards a
+// TelemetryReport, POSTed as CBOR to the policy's endpoint at most once per flush interval
+// of the worker's lifetime. The endpoint must be on allowedRpcOrigins.
+// Counters are keyed by request type name and catalogued error code, both `&'static str`:
+// nothing taken from a request (account IDs, receivers, amounts) can be recorded, and the
+// report type has no field that could carry it.
+
+use log::{info, warn};
+use serde::Serialize;
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use wasm_bindgen::prelude::*;
+
+use crate::config::{
+    DEFAULT_TELEMETRY_FLUSH_INTERVAL_MINUTES, MIN_TELEMETRY_FLUSH_INTERVAL_MINUTES,
+    TELEMETRY_LATENCY_BUCKETS_MS, TELEMETRY_REPORT_VERSION,
+};
+use crate::error::RpcOriginError;
+use crate::error_codes::find_error_code;
+use crate::rpc_calls::http_post_bytes;
+use crate::rpc_endpoints::rpc_origin;
+use crate::types::WorkerPolicy;
+
+/// Histogram buckets: one per TELEMETRY_LATENCY_BUCKETS_MS bound, then the overflow bucket
+pub const LATENCY_BUCKETS: usize = TELEMETRY_LATENCY_BUCKETS_MS.len() + 1;
+
+/// How a tracked request ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestOutcome {
+    Success,
+    /// With the catalogued error code, when the failure had one
+    Failure(Option<&'static str>),
+}
+
+/// Outcome of a dispatched request from its response: a failure response type, or a result
+/// reporting `success: false`
+pub fn outcome_of(failure_response: bool, payload: &Value) -> RequestOutcome {
+    if failure_response || payload.get("success") == Some(&Value::Bool(false)) {
+        RequestOutcome::Failure(
+            payload
+                .get("errorCode")
+                .and_then(|code| code.as_str())
+                .and_then(find_error_code)
+                .map(|def| def.code),
+        )
+    } else {
+        RequestOutcome::Success
+    }
+}
+
+/// Counters of one handler
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandlerCounters {
+    pub requests: u32,
+    pub successes: u32,
+    pub failures: u32,
+    /// Failures per error code; failures without a catalogued code only count in `failures`
+    pub failure_codes: BTreeMap<&'static str, u32>,
+    /// Requests per latency bucket
+    pub latency_buckets: [u32; LATENCY_BUCKETS],
+}
+
+impl HandlerCounters {
+    pub fn record(&mut self, latency_ms: f64, outcome: RequestOutcome) {
+        self.requests = self.requests.saturating_add(1);
+        match outcome {
+            RequestOutcome::Success => self.successes = self.successes.saturating_add(1),
+            RequestOutcome::Failure(code) => {
+                self.failures = self.failures.saturating_add(1);
+                if let Some(code) = code {
+                    let count = self.failure_codes.entry(code).or_default();
+                    *count = count.saturating_add(1);
+                }
+            }
+        }
+        let bucket = latency_bucket(latency_ms);
+        self.latency_buckets[bucket] = self.latency_buckets[bucket].saturating_add(1);
+    }
+
+    /// Upper bound of the bucket holding the `percentile` (0 to 100) request. None without
+    /// requests, or when that bucket is the overflow bucket.
+    pub fn latency_percentile_ms(&self, percentile: f64) -> Option<u32> {
+        let total: u64 = self.latency_buckets.iter().map(|&n| n as u64).sum();
+        if total == 0 {
+            return None;
+        }
+        let rank = ((percentile.clamp(0.0, 100.0) / 100.0) * total as f64)
+            .ceil()
+            .max(1.0) as u64;
+        let mut seen = 0u64;
+        for (bucket, &count) in self.latency_buckets.iter().enumerate() {
+            seen += count as u64;
+            if seen >= rank {
+                return TELEMETRY_LATENCY_BUCKETS_MS.get(bucket).copied();
+            }
+        }
+        None
+    }
+}
+
+/// Histogram bucket of a request that took `latency_ms`
+pub fn latency_bucket(latency_ms: f64) -> usize {
+    TELEMETRY_LATENCY_BUCKETS_MS
+        .iter()
+        .position(|&bound| latency_ms <= bound as f64)
+        .unwrap_or(TELEMETRY_LATENCY_BUCKETS_MS.len())
+}
+
+/// What a flush sends
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryReport {
+    /// TELEMETRY_REPORT_VERSION
+    pub version: u32,
+    /// Share of requests counted in `handlers`
+    pub sampling_rate: f64,
+    pub window_start_ms: f64,
+    pub window_end_ms: f64,
+    /// Counters of the sampled requests of the window, by request type name
+    pub handlers: BTreeMap<&'static str, HandlerCounters>,
+}
+
+impl TelemetryReport {
+    pub fn to_cbor(&self) -> Result<Vec<u8>, String> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(self, &mut bytes)
+            .map_err(|e| format!("Failed to encode telemetry report: {}", e))?;
+        Ok(bytes)
+    }
+}
+
+/// WorkerPolicy.telemetry after validation
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelemetrySink {
+    pub endpoint: String,
+    pub sampling_rate: f64,
+    pub flush_interval_ms: f64,
+}
+
+/// The policy's telemetry sink; Ok(None) when the policy does not opt in
+pub fn telemetry_sink(policy: &WorkerPolicy) -> Result<Option<TelemetrySink>, String> {
+    let Some(telemetry) = &policy.telemetry else {
+        return Ok(None);
+    };
+    let origin = rpc_origin(&telemetry.endpoint);
+    let allowed = origin.is_some()
+        && policy
+            .allowed_rpc_origins
+            .iter()
+            .any(|allowed| rpc_origin(allowed) == origin);
+    if !allowed {
+        return Err(RpcOriginError::NotAllowed {
+            endpoint: "telemetry",
+            url: telemetry.endpoint.clone(),
+        }
+        .to_string());
+    }
+    let sampling_rate = telemetry.sampling_rate.unwrap_or(1.0);
+    if !(0.0..=1.0).contains(&sampling_rate) {
+        return Err(format!(
+            "telemetry.samplingRate must be between 0 and 1, got {}",
+            sampling_rate
+        ));
+    }
+    let flush_interval_minutes = telemetry
+        .flush_interval_minutes
+        .unwrap_or(DEFAULT_TELEMETRY_FLUSH_INTERVAL_MINUTES);
+    if flush_interval_minutes < MIN_TELEMETRY_FLUSH_INTERVAL_MINUTES {
+        return Err(format!(
+            "telemetry.flushIntervalMinutes must be at least {}, got {}",
+            MIN_TELEMETRY_FLUSH_INTERVAL_MINUTES, flush_interval_minutes
+        ));
+    }
+    Ok(Some(TelemetrySink {
+        endpoint: telemetry.endpoint.clone(),
+        sampling_rate,
+        flush_interval_ms: flush_interval_minutes as f64 * 60_000.0,
+    }))
+}
+
+/// The remote sink and the sampled counters of its current window
+struct RemoteTelemetry {
+    sink: TelemetrySink,
+    window_start_ms: f64,
+    handlers: BTreeMap<&'static str, HandlerCounters>,
+}
+
+struct TelemetryState {
+    handlers: BTreeMap<&'static str, HandlerCounters>,
+    remote: Option<RemoteTelemetry>,
+    reports_sent: u32,
+}
+
+thread_local! {
+    static TELEMETRY: RefCell<TelemetryState> = const {
+        RefCell::new(TelemetryState {
+            handlers: BTreeMap::new(),
+            remote: None,
+            reports_sent: 0,
+        })
+    };
+}
+
+/// Applies the telemetry sink of a request's WorkerPolicy. Requests without one leave the
+/// sink as it is; an invalid sink turns remote reporting off (the request itself goes on).
+/// Changing the sink starts a new window.
+pub fn configure(policy: &WorkerPolicy, now_ms: f64) {
+    if policy.telemetry.is_none() {
+        return;
+    }
+    let sink = match telemetry_sink(policy) {
+        Ok(sink) => sink,
+        Err(e) => {
+            warn!("RUST: Remote telemetry disabled: {}", e);
+            None
+        }
+    };
+    TELEMETRY.with(|t| {
+        let mut t = t.borrow_mut();
+        let unchanged =
+            matches!((&t.remote, &sink), (Some(remote), Some(sink)) if remote.sink == *sink);
+        if !unchanged {
+            t.remote = sink.map(|sink| RemoteTelemetry {
+                sink,
+                window_start_ms: now_ms,
+                handlers: BTreeMap::new(),
+            });
+        }
+    });
+}
+
+/// Applies the telemetry sink of a raw request payload's `workerPolicy`, if any
+pub fn configure_from_payload(payload: &Value, now_ms: f64) {
+    let Some(policy) = payload.get("workerPolicy") else {
+        return;
+    };
+    if let Ok(policy) = serde_json::from_value::<WorkerPolicy>(policy.clone()) {
+        configure(&policy, now_ms);
+    }
+}
+
+/// Counts a finished request. `sample` (uniform in [0, 1)) decides whether it also counts
+/// towards the remote report.
+pub fn record_request(
+    handler: &'static str,
+    latency_ms: f64,
+    outcome: RequestOutcome,
+    sample: f64,
+) {
+    TELEMETRY.with(|t| {
+        let mut t = t.borrow_mut();
+        t.handlers
+            .entry(handler)
+            .or_default()
+            .record(latency_ms, outcome);
+        if let Some(remote) = t.remote.as_mut() {
+            if sample < remote.sink.sampling_rate {
+                remote
+                    .handlers
+                    .entry(handler)
+                    .or_default()
+                    .record(latency_ms, outcome);
+            }
+        }
+    });
+}
+
+/// Uniform sample in [0, 1) for `record_request`
+pub fn random_sample() -> f64 {
+    let mut bytes = [0u8; 4];
+    match getrandom::getrandom(&mut bytes) {
+        Ok(()) => u32::from_le_bytes(bytes) as f64 / (u32::MAX as f64 + 1.0),
+        // Without randomness, count the request: sampling only ever reduces what is sent
+        Err(_) => 0.0,
+    }
+}
+
+/// Takes the report of the current window once the flush interval has passed, starting the
+/// next window. None before then, without a sink, or when no sampled request was counted.
+pub fn take_due_report(now_ms: f64) -> Option<(String, TelemetryReport)> {
+    TELEMETRY.with(|t| {
+        let mut t = t.borrow_mut();
+        let remote = t.remote.as_mut()?;
+        if now_ms - remote.window_start_ms < remote.sink.flush_interval_ms {
+            return None;
+        }
+        let window_start_ms = std::mem::replace(&mut remote.window_start_ms, now_ms);
+        let handlers = std::mem::take(&mut remote.handlers);
+        if handlers.is_empty() {
+            return None;
+        }
+        Some((
+            remote.sink.endpoint.clone(),
+            TelemetryReport {
+                version: TELEMETRY_REPORT_VERSION,
+                sampling_rate: remote.sink.sampling_rate,
+                window_start_ms,
+                window_end_ms: now_ms,
+                handlers,
+            },
+        ))
+    })
+}
+
+/// Sends the due report, if any. Telemetry is best-effort: a report that fails to send is
+/// dropped, never retried or surfaced to the request.
+pub async fn flush_if_due(now_ms: f64) {
+    let Some((endpoint, report)) = take_due_report(now_ms) else {
+        return;
+    };
+    let sent = match report.to_cbor() {
+        Ok(bytes) => http_post_bytes(&endpoint, "application/cbor", &bytes).await,
+        Err(e) => Err(e),
+    };
+    match sent {
+        Ok(status) if (200..300).contains(&status) => {
+            TELEMETRY.with(|t| {
+                let mut t = t.borrow_mut();
+                t.reports_sent = t.reports_sent.saturating_add(1);
+            });
+            info!("RUST: Telemetry report sent to {}", endpoint);
+        }
+        Ok(status) => warn!(
+            "RUST: Telemetry endpoint answered HTTP {}, report dropped",
+            status
+        ),
+        Err(e) => warn!("RUST: Telemetry report dropped: {}", e),
+    }
+}
+
+// === SNAPSHOT ===
+
+/// Failures of a handler with one error code
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailureCodeCount {
+    #[wasm_bindgen(getter_with_clone)]
+    pub code: String,
+    pub count: u32,
+}
+
+/// In-memory counters of one handler
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandlerTelemetry {
+    /// Request type name (e.g. "SIGN_TRANSACTIONS_WITH_ACTIONS")
+    #[wasm_bindgen(getter_with_clone)]
+    pub handler: String,
+    pub requests: u32,
+    pub successes: u32,
+    pub failures: u32,
+    #[wasm_bindgen(getter_with_clone, js_name = "failureCodes")]
+    pub failure_codes: Vec<FailureCodeCount>,
+    /// Requests per latency bucket, bounded by `latencyBucketBoundsMs` of the snapshot; the
+    /// last bucket counts slower requests
+    #[wasm_bindgen(getter_with_clone, js_name = "latencyBuckets")]
+    pub latency_buckets: Vec<u32>,
+    /// Upper bound of the bucket holding the median request
+    #[wasm_bindgen(js_name = "p50LatencyMs")]
+    pub p50_latency_ms: Option<u32>,
+    /// Upper bound of the bucket holding the 95th percentile request
+    #[wasm_bindgen(js_name = "p95LatencyMs")]
+    pub p95_latency_ms: Option<u32>,
+}
+
+impl HandlerTelemetry {
+    fn from_counters(handler: &str, counters: &HandlerCounters) -> Self {
+        HandlerTelemetry {
+            handler: handler.to_string(),
+            requests: counters.requests,
+            successes: counters.successes,
+            failures: counters.failures,
+            failure_codes: counters
+                .failure_codes
+                .iter()
+                .map(|(code, &count)| FailureCodeCount {
+                    code: code.to_string(),
+                    count,
+                })
+                .collect(),
+            latency_buckets: counters.latency_buckets.to_vec(),
+            p50_latency_ms: counters.latency_percentile_ms(50.0),
+            p95_latency_ms: counters.latency_percentile_ms(95.0),
+        }
+    }
+}
+
+/// Everything counted since the worker started
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetrySnapshot {
+    #[wasm_bindgen(getter_with_clone)]
+    pub handlers: Vec<HandlerTelemetry>,
+    /// TELEMETRY_LATENCY_BUCKETS_MS
+    #[wasm_bindgen(getter_with_clone, js_name = "latencyBucketBoundsMs")]
+    pub latency_bucket_bounds_ms: Vec<u32>,
+    /// Remote sink in use; None when remote reporting is off
+    #[wasm_bindgen(getter_with_clone, js_name = "remoteEndpoint")]
+    pub remote_endpoint: Option<String>,
+    #[wasm_bindgen(js_name = "samplingRate")]
+    pub sampling_rate: Option<f64>,
+    /// Start of the window the next report covers
+    #[wasm_bindgen(js_name = "windowStartMs")]
+    pub window_start_ms: Option<f64>,
+    #[wasm_bindgen(js_name = "reportsSent")]
+    pub reports_sent: u32,
+}
+
+pub fn snapshot() -> TelemetrySnapshot {
+    TELEMETRY.with(|t| {
+        let t = t.borrow();
+        TelemetrySnapshot {
+            handlers: t
+                .handlers
+                .iter()
+                .map(|(handler, counters)| HandlerTelemetry::from_counters(handler, counters))
+                .collect(),
+            latency_bucket_bounds_ms: TELEMETRY_LATENCY_BUCKETS_MS.to_vec(),
+            remote_endpoint: t.remote.as_ref().map(|r| r.sink.endpoint.clone()),
+            sampling_rate: t.remote.as_ref().map(|r| r.sink.sampling_rate),
+            window_start_ms: t.remote.as_ref().map(|r| r.window_start_ms),
+            reports_sent: t.reports_sent,
+        }
+    })
+}
+
+/// Clears all counters and the remote sink (native tests share the thread's state)
+#[cfg(test)]
+pub fn reset() {
+    TELEMETRY.with(|t| {
+        let mut t = t.borrow_mut();
+        t.handlers.clear();
+        t.remote = None;
+        t.reports_sent = 0;
+    });
+}
@@ -0,0 +1,304 @@
+use crate::account_bundle::{
+    open_account_bundle, seal_account_bundle, validate_bundle_records, AccountBundleRecords,
+    BundleKdfParams,
+};
+use crate::config::{ACCOUNT_BUNDLE_VERSION, MAX_ACCOUNT_BUNDLE_ARGON2_MEMORY_KIB};
+use crate::error::{AccountBundleError, SignerErrorCode};
+use crate::handlers::{handle_import_account_bundle, ImportAccountBundleRequest};
+use crate::tests::block_on;
+use ciborium::Value as CborValue;
+use serde_json::json;
+
+const PASSPHRASE: &str = "correct horse battery staple";
+
+/// Argon2id parameters cheap enough for debug-build tests
+fn test_kdf() -> BundleKdfParams {
+    BundleKdfParams {
+        algorithm: "argon2id".to_string(),
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+        salt: vec![7; 16],
+    }
+}
+
+fn sample_records() -> AccountBundleRecords {
+    AccountBundleRecords {
+        users: vec![json!({
+            "nearAccountId": "alice.testnet",
+            "deviceNumber": 2,
+            "clientNearPublicKey": "ed25519:abc",
+            "preferences": { "useRelayer": false, "useNetwork": "testnet" },
+            "encryptedVrfKeypair": { "encryptedVrfDataB64u": "AAAA", "chacha20NonceB64u": "BBBB" }
+        })],
+        authenticators: vec![json!({
+            "nearAccountId": "alice.testnet",
+            "deviceNumber": 2,
+            "credentialId": "cred-1",
+            "credentialPublicKey": [1, 2, 3],
+            "registered": "2025-01-01T00:00:00.000Z"
+        })],
+        near_keys: vec![json!({
+            "nearAccountId": "alice.testnet",
+            "deviceNumber": 2,
+            "encryptedData": "CCCC",
+            "iv": "DDDD",
+            "timestamp": 1_700_000_000_000u64
+        })],
+        app_state: vec![
+            json!({ "key": "lastUserAccountId", "value": { "accountId": "alice.testnet", "deviceNumber": 2 } }),
+        ],
+    }
+}
+
+fn seal(records: AccountBundleRecords) -> Vec<u8> {
+    seal_account_bundle(records, PASSPHRASE, test_kdf(), 1_700_000_000_000.0).unwrap()
+}
+
+/// Re-encodes the bundle with `edit` applied to its decoded header
+fn with_header(bundle: &[u8], edit: impl FnOnce(&mut Vec<(CborValue, CborValue)>)) -> Vec<u8> {
+    let mut file: CborValue = ciborium::from_reader(bundle).unwrap();
+    let fields = file.as_map_mut().unwrap();
+    let header_bytes = fields[0].1.as_bytes().unwrap().clone();
+    let mut header: CborValue = ciborium::from_reader(header_bytes.as_slice()).unwrap();
+    edit(header.as_map_mut().unwrap());
+    let mut encoded = Vec::new();
+    ciborium::into_writer(&header, &mut encoded).unwrap();
+    fields[0].1 = CborValue::Bytes(encoded);
+    let mut out = Vec::new();
+    ciborium::into_writer(&file, &mut out).unwrap();
+    out
+}
+
+fn set_field(fields: &mut [(CborValue, CborValue)], name: &str, value: CborValue) {
+    let field = fields
+        .iter_mut()
+        .find(|(k, _)| k.as_text() == Some(name))
+        .unwrap();
+    field.1 = value;
+}
+
+#[test]
+fn test_bundle_round_trips_records() {
+    let bundle = seal(sample_records());
+    let opened = open_account_bundle(&bundle, PASSPHRASE).unwrap();
+    assert_eq!(opened.version, ACCOUNT_BUNDLE_VERSION);
+    assert_eq!(opened.exported_at_ms, 1_700_000_000_000.0);
+    assert_eq!(opened.records, sample_records());
+    assert_eq!(opened.records.record_count(), 4);
+
+    // Records from earlier releases come back in the current schema
+    let mut legacy = sample_records();
+    legacy.users[0] = json!({
+        "nearAccountId": "alice.testnet",
+        "encrypted_vrf_keypair": { "encrypted_vrf_data_b64u": "AAAA", "chacha20_nonce_b64u": "BBBB" }
+    });
+    let opened = open_account_bundle(&seal(legacy), PASSPHRASE).unwrap();
+    assert_eq!(
+        opened.records.users[0],
+        json!({
+            "nearAccountId": "alice.testnet",
+            "deviceNumber": 1,
+            "encryptedVrfKeypair": { "encryptedVrfDataB64u": "AAAA", "chacha20NonceB64u": "BBBB" }
+        })
+    );
+
+    // The salt and nonce are fresh per export
+    let other = seal_account_bundle(
+        sample_records(),
+        PASSPHRASE,
+        BundleKdfParams {
+            salt: vec![8; 16],
+            ..test_kdf()
+        },
+        0.0,
+    )
+    .unwrap();
+    assert_ne!(bundle, other);
+}
+
+#[test]
+fn test_bundle_failures_have_distinct_codes() {
+    let bundle = seal(sample_records());
+
+    let err = open_account_bundle(&bundle, "correct horse battery stapler").unwrap_err();
+    assert_eq!(err, AccountBundleError::PassphraseInvalid);
+    assert_eq!(err.code(), SignerErrorCode::BundlePassphraseInvalid);
+
+    for len in [0, 1, 10, bundle.len() / 2, bundle.len() - 1] {
+        let err = open_account_bundle(&bundle[..len], PASSPHRASE).unwrap_err();
+        assert_eq!(err, AccountBundleError::Truncated, "cut at {}", len);
+    }
+
+    let future = with_header(&bundle, |fields| {
+        set_field(
+            fields,
+            "version",
+            CborValue::from(ACCOUNT_BUNDLE_VERSION + 1),
+        );
+        // A newer layout may drop fields this release needs
+        fields.retain(|(k, _)| k.as_text() != Some("keyCheck"));
+    });
+    let err = open_account_bundle(&future, PASSPHRASE).unwrap_err();
+    assert_eq!(
+        err,
+        AccountBundleError::VersionUnsupported {
+            version: ACCOUNT_BUNDLE_VERSION + 1
+        }
+    );
+    assert!(
+        err.to_string().starts_with("BundleVersionUnsupported: "),
+        "{}",
+        err
+    );
+
+    // The right passphrase over a damaged ciphertext is not reported as a wrong passphrase
+    let mut damaged = bundle.clone();
+    let last = damaged.len() - 1;
+    damaged[last] ^= 1;
+    let err = open_account_bundle(&damaged, PASSPHRASE).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::BundleInvalid);
+
+    let mut trailing = bundle.clone();
+    trailing.push(0);
+    let err = open_account_bundle(&trailing, PASSPHRASE).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::BundleInvalid);
+}
+
+#[test]
+fn test_bundle_header_is_authenticated_and_bounded() {
+    let bundle = seal(sample_records());
+
+    // Weakened parameters derive a different key, so the key check fails
+    let weakened = with_header(&bundle, |fields| {
+        let kdf = fields
+            .iter_mut()
+            .find(|(k, _)| k.as_text() == Some("kdf"))
+            .unwrap();
+        set_field(
+            kdf.1.as_map_mut().unwrap(),
+            "memoryKib",
+            CborValue::from(32),
+        );
+    });
+    assert_eq!(
+        open_account_bundle(&weakened, PASSPHRASE).unwrap_err(),
+        AccountBundleError::PassphraseInvalid
+    );
+
+    // Parameters above the bounds are refused before deriving
+    let huge = with_header(&bundle, |fields| {
+        let kdf = fields
+            .iter_mut()
+            .find(|(k, _)| k.as_text() == Some("kdf"))
+            .unwrap();
+        set_field(
+            kdf.1.as_map_mut().unwrap(),
+            "memoryKib",
+            CborValue::from(MAX_ACCOUNT_BUNDLE_ARGON2_MEMORY_KIB + 1),
+        );
+    });
+    let err = open_account_bundle(&huge, PASSPHRASE).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::BundleInvalid);
+    assert!(
+        err.to_string().contains("exceed the accepted bounds"),
+        "{}",
+        err
+    );
+
+    let foreign = with_header(&bundle, |fields| {
+        set_field(fields, "format", CborValue::from("other-format"));
+    });
+    let err = open_account_bundle(&foreign, PASSPHRASE).unwrap_err();
+    assert!(err.to_string().contains("Not an account bundle"), "{}", err);
+}
+
+#[test]
+fn test_bundle_records_are_validated() {
+    let mut records = sample_records();
+    records
+        .near_keys
+        .push(json!({ "nearAccountId": "alice.testnet", "iv": "DDDD" }));
+    let err = validate_bundle_records(records).unwrap_err();
+    assert!(err.starts_with("nearKeys[1]: "), "{}", err);
+
+    // A key record in the users list is rejected
+    let mut records = sample_records();
+    records.users.push(records.near_keys[0].clone());
+    let err = validate_bundle_records(records).unwrap_err();
+    assert!(err.starts_with("users[1]: "), "{}", err);
+
+    let mut records = sample_records();
+    records.authenticators[0]["deviceNumber"] = json!("2");
+    let err = validate_bundle_records(records).unwrap_err();
+    assert_eq!(err, "authenticators[0]: missing or invalid deviceNumber");
+
+    let mut records = sample_records();
+    records.app_state.push(json!({ "value": true }));
+    let err = seal_account_bundle(records, PASSPHRASE, test_kdf(), 0.0).unwrap_err();
+    assert_eq!(err, "appState[1]: missing or invalid key");
+
+    let err = seal_account_bundle(sample_records(), "short", test_kdf(), 0.0).unwrap_err();
+    assert!(
+        err.starts_with("Bundle passphrase must be at least"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_import_handler_reports_error_codes() {
+    let import = |bundle: &[u8], passphrase: &str| {
+        block_on(handle_import_account_bundle(ImportAccountBundleRequest {
+            passphrase: passphrase.to_string(),
+            bundle: bundle.to_vec(),
+        }))
+    };
+    let bundle = seal(sample_records());
+
+    let result = import(&bundle, PASSPHRASE).unwrap();
+    let serialized = serde_json::to_value(&result).unwrap();
+    assert_eq!(serialized["version"], json!(ACCOUNT_BUNDLE_VERSION));
+    assert_eq!(serialized["exportedAtMs"], json!(1_700_000_000_000.0));
+    assert_eq!(
+        serialized["records"]["nearKeys"][0]["encryptedData"],
+        json!("CCCC")
+    );
+
+    let err = import(&bundle, "wrong passphrase").unwrap_err();
+    assert!(err.starts_with("BundlePassphraseInvalid: "), "{}", err);
+    let err = import(&bundle[..20], PASSPHRASE).unwrap_err();
+    assert!(err.starts_with("BundleTruncated: "), "{}", err);
+    // A complete CBOR value (the integer 1) that is not a bundle
+    let err = import(&[0x01], PASSPHRASE).unwrap_err();
+    assert!(err.starts_with("BundleInvalid: "), "{}", err);
+}
+
+/// Native time for one key derivation with the default parameters. Argon2id in WASM on a
+/// mid-range phone runs about 12x slower than natively on a desktop CPU (no SIMD, slower
+/// cores), so a 75ms native budget keeps import and export under ~1s there.
+/// Run with `cargo test --release -- --ignored bench_`.
+#[test]
+#[ignore = "benchmark"]
+fn bench_account_bundle_kdf() {
+    use std::time::Instant;
+
+    let kdf = BundleKdfParams::generate().unwrap();
+    let mut timings = Vec::new();
+    for _ in 0..5 {
+        let started = Instant::now();
+        kdf.derive_key(PASSPHRASE).unwrap();
+        timings.push(started.elapsed());
+    }
+    timings.sort();
+    let median = timings[timings.len() / 2];
+    println!(
+        "argon2id m={} KiB t={} p={}: median {:?}",
+        kdf.memory_kib, kdf.iterations, kdf.parallelism, median
+    );
+    assert!(
+        median.as_millis() < 75,
+        "key derivation took {:?}; retune ACCOUNT_BUNDLE_ARGON2_*",
+        median
+    );
+}
@@ -0,0 +1,164 @@
+use crate::account_descriptor::*;
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::{AccountDescriptorError, SignerErrorCode};
+use crate::rpc_calls::{parse_authenticators_by_user_response, RegisteredAuthenticator};
+use ed25519_dalek::SigningKey;
+use serde_json::json;
+
+const ACCOUNT_ID: &str = "alice.testnet";
+const CONTRACT_ID: &str = "w3a-v1.testnet";
+const VRF_PUBLIC_KEY: &str = "dnJmLXB1YmxpYy1rZXk";
+const ISSUED_AT_MS: f64 = 1_700_000_000_000.0;
+const TTL_MS: u32 = 60_000;
+
+fn signing_key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+fn near_public_key(key: &SigningKey) -> String {
+    format!(
+        "ed25519:{}",
+        bs58::encode(key.verifying_key().to_bytes()).into_string()
+    )
+}
+
+fn authenticators() -> Vec<RegisteredAuthenticator> {
+    vec![
+        RegisteredAuthenticator {
+            credential_id: "cred-2".to_string(),
+            device_number: 2,
+            vrf_public_keys: vec!["b3RoZXI".to_string()],
+            transports: None,
+        },
+        RegisteredAuthenticator {
+            credential_id: "cred-1".to_string(),
+            device_number: 1,
+            vrf_public_keys: vec![VRF_PUBLIC_KEY.to_string()],
+            transports: None,
+        },
+    ]
+}
+
+fn signed_blob(key: &SigningKey) -> String {
+    let descriptor = build_account_descriptor(
+        ACCOUNT_ID,
+        CONTRACT_ID,
+        VRF_PUBLIC_KEY,
+        &authenticators(),
+        key,
+        ISSUED_AT_MS,
+        TTL_MS,
+    )
+    .unwrap();
+    sign_account_descriptor(&descriptor, key).unwrap()
+}
+
+#[test]
+fn test_parse_authenticators_by_user_response() {
+    let entries = json!([
+        ["cred-1", { "credential_public_key": [1, 2], "transports": ["internal"],
+                     "registered": "2024-01-01", "vrf_public_keys": [VRF_PUBLIC_KEY],
+                     "device_number": 1 }],
+        ["cred-2", { "credential_public_key": [3], "registered": "2024-02-01",
+                     "device_number": 2 }]
+    ]);
+    let bytes: Vec<u8> = entries.to_string().into_bytes();
+    let response = json!({ "result": { "result": bytes } });
+    let parsed = parse_authenticators_by_user_response(&response).unwrap();
+    assert_eq!(parsed.len(), 2);
+    assert_eq!(parsed[0].credential_id, "cred-1");
+    assert_eq!(parsed[0].vrf_public_keys, vec![VRF_PUBLIC_KEY.to_string()]);
+    assert_eq!(parsed[1].device_number, 2);
+    assert!(parsed[1].vrf_public_keys.is_empty());
+
+    let error = json!({ "result": { "error": "wasm execution failed" } });
+    assert!(parse_authenticators_by_user_response(&error).is_err());
+}
+
+#[test]
+fn test_descriptor_round_trips_and_enforces_expiry() {
+    let key = signing_key(7);
+    let blob = signed_blob(&key);
+
+    // Verifies with either public key encoding
+    let base64url_key = base64_url_encode(&key.verifying_key().to_bytes());
+    for public_key in [near_public_key(&key), base64url_key] {
+        let descriptor = verify_account_descriptor(&blob, &public_key, ISSUED_AT_MS + 1.0).unwrap();
+        assert_eq!(descriptor.account_id, ACCOUNT_ID);
+        assert_eq!(descriptor.contract_id, CONTRACT_ID);
+        assert_eq!(descriptor.vrf_public_key, VRF_PUBLIC_KEY);
+        assert_eq!(descriptor.near_public_key, near_public_key(&key));
+        assert_eq!(descriptor.expires_at_ms, ISSUED_AT_MS + TTL_MS as f64);
+        let devices: Vec<(&str, u32)> = descriptor
+            .authenticators
+            .iter()
+            .map(|a| (a.credential_id.as_str(), a.device_number))
+            .collect();
+        assert_eq!(devices, vec![("cred-1", 1), ("cred-2", 2)]);
+    }
+
+    let expired =
+        verify_account_descriptor(&blob, &near_public_key(&key), ISSUED_AT_MS + TTL_MS as f64)
+            .unwrap_err();
+    assert_eq!(
+        expired,
+        AccountDescriptorError::Expired {
+            expires_at_ms: ISSUED_AT_MS + TTL_MS as f64
+        }
+    );
+    assert_eq!(expired.code(), SignerErrorCode::DescriptorExpired);
+    assert!(expired.to_string().starts_with("DescriptorExpired: "));
+}
+
+#[test]
+fn test_descriptor_rejects_wrong_key_and_tampering() {
+    let key = signing_key(7);
+    let blob = signed_blob(&key);
+
+    let wrong_key =
+        verify_account_descriptor(&blob, &near_public_key(&signing_key(8)), 0.0).unwrap_err();
+    assert!(matches!(
+        wrong_key,
+        AccountDescriptorError::InvalidSignature(_)
+    ));
+    assert_eq!(wrong_key.code(), SignerErrorCode::DescriptorInvalid);
+
+    // Flip a byte inside the signed descriptor (after the envelope's map/key headers)
+    let mut bytes = base64_url_decode(&blob).unwrap();
+    bytes[20] ^= 0x01;
+    let tampered =
+        verify_account_descriptor(&base64_url_encode(&bytes), &near_public_key(&key), 0.0)
+            .unwrap_err();
+    assert_eq!(tampered.code(), SignerErrorCode::DescriptorInvalid);
+
+    let garbage = verify_account_descriptor("not*base64", &near_public_key(&key), 0.0).unwrap_err();
+    assert!(matches!(garbage, AccountDescriptorError::Malformed(_)));
+}
+
+#[test]
+fn test_build_requires_the_vrf_key_on_chain() {
+    let key = signing_key(7);
+    let err = build_account_descriptor(
+        ACCOUNT_ID,
+        CONTRACT_ID,
+        "dW5yZWdpc3RlcmVk",
+        &authenticators(),
+        &key,
+        ISSUED_AT_MS,
+        TTL_MS,
+    )
+    .unwrap_err();
+    assert!(err.contains("not registered"));
+
+    let err = build_account_descriptor(
+        ACCOUNT_ID,
+        CONTRACT_ID,
+        VRF_PUBLIC_KEY,
+        &[],
+        &key,
+        ISSUED_AT_MS,
+        TTL_MS,
+    )
+    .unwrap_err();
+    assert!(err.contains("No registered authenticators"));
+}
@@ -0,0 +1,218 @@
+use crate::config::MAX_ACTIVE_ACCOUNTS;
+use crate::error::StrictParseError;
+use crate::handlers::handle_list_active_accounts::{
+    handle_list_active_accounts, ListActiveAccountsRequest,
+};
+use crate::handlers::handle_sign_transactions_with_actions::batch_account_id;
+use crate::handlers::handle_wipe_account_state::{
+    handle_wipe_account_state, WipeAccountStateRequest,
+};
+use crate::handlers::SignTransactionsWithActionsRequest;
+use crate::state::{self, SessionKey};
+use crate::strict_parsing::check_unknown_fields;
+use crate::tests::block_on;
+use crate::types::handlers::WorkerPolicy;
+use crate::types::worker_messages::WorkerRequestType;
+use crate::types::AccountId;
+use serde_json::json;
+
+// Each test runs on its own thread, so the thread_local state starts empty.
+
+fn alice() -> AccountId {
+    AccountId("alice.testnet".to_string())
+}
+
+fn bob() -> AccountId {
+    AccountId("bob.testnet".to_string())
+}
+
+fn session_key(near_account_id: &str) -> SessionKey {
+    SessionKey {
+        signing_key: ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]),
+        near_account_id: near_account_id.to_string(),
+        receiver_id: "app.testnet".to_string(),
+        method_names: vec![],
+        allowance: 1,
+        spent: 0,
+        expires_at_ms: state::now_ms() + 60_000.0,
+    }
+}
+
+#[test]
+fn test_interleaved_accounts_keep_separate_state() {
+    state::reserve_nonces(&alice(), "req-a1", vec!["11".to_string(), "12".to_string()]);
+    state::reserve_nonces(&bob(), "req-b1", vec!["11".to_string()]);
+    state::record_audit(&alice(), "req-a1", "signTransactionsWithActions", "Signed", None);
+    state::record_audit(&bob(), "req-b1", "signTransactionsWithActions", "UserDeclined", None);
+    state::reserve_nonces(&alice(), "req-a2", vec!["13".to_string()]);
+    state::record_audit(&alice(), "req-a2", "signTransactionsWithActions", "Signed", None);
+
+    // Same request ID under another account finds nothing
+    assert!(state::reserved_nonces(&bob(), "req-a1").is_empty());
+    assert!(state::release_nonces(&bob(), "req-a1").is_empty());
+    assert_eq!(state::reserved_nonces(&alice(), "req-a1"), vec!["11", "12"]);
+
+    assert_eq!(state::release_nonces(&bob(), "req-b1"), vec!["11"]);
+    assert_eq!(state::reserved_nonces(&alice(), "req-a2"), vec!["13"]);
+
+    let alice_audit = state::account_audit_entries(&alice());
+    assert_eq!(alice_audit.len(), 2);
+    assert!(alice_audit.iter().all(|e| e.request_id.starts_with("req-a")));
+    assert_eq!(state::account_audit_entries(&bob()).len(), 1);
+
+    let result = block_on(handle_list_active_accounts(ListActiveAccountsRequest {})).unwrap();
+    let entry = |id: &str| {
+        result
+            .accounts
+            .iter()
+            .find(|a| a.account_id == id)
+            .unwrap()
+            .clone()
+    };
+    assert_eq!(result.accounts.len(), 2);
+    let alice_entry = entry("alice.testnet");
+    assert_eq!(
+        (alice_entry.signed_requests, alice_entry.failed_requests),
+        (2, 0)
+    );
+    assert_eq!(alice_entry.nonce_reservations, 2);
+    let bob_entry = entry("bob.testnet");
+    assert_eq!((bob_entry.signed_requests, bob_entry.failed_requests), (0, 1));
+    assert_eq!(bob_entry.nonce_reservations, 0);
+    assert!(result.accounts[0].last_active_ms >= result.accounts[1].last_active_ms);
+}
+
+#[test]
+fn test_wipe_account_state_leaves_other_accounts_intact() {
+    state::reserve_nonces(&alice(), "req-a", vec!["5".to_string()]);
+    state::record_audit(&alice(), "req-a", "signNep413Message", "Signed", None);
+    state::reserve_nonces(&bob(), "req-b", vec!["6".to_string()]);
+    state::record_audit(&bob(), "req-b", "signNep413Message", "Signed", None);
+    state::store_session_key("ed25519:alice-session", session_key("alice.testnet")).unwrap();
+    state::store_session_key("ed25519:bob-session", session_key("bob.testnet")).unwrap();
+
+    let result = block_on(handle_wipe_account_state(WipeAccountStateRequest {
+        account_id: "alice.testnet".to_string(),
+    }))
+    .unwrap();
+    assert_eq!(result.account_id, "alice.testnet");
+    assert!(result.found);
+    assert_eq!(result.released_nonce_reservations, 1);
+    assert_eq!(result.cleared_audit_entries, 1);
+    assert_eq!(result.cleared_session_keys, 1);
+
+    assert!(state::reserved_nonces(&alice(), "req-a").is_empty());
+    assert!(state::account_audit_entries(&alice()).is_empty());
+    assert_eq!(state::reserved_nonces(&bob(), "req-b"), vec!["6"]);
+    assert_eq!(state::account_audit_entries(&bob()).len(), 1);
+    assert!(state::with_session_key("ed25519:bob-session", |_| ()).is_ok());
+
+    let accounts = state::list_active_accounts();
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0].account_id, bob());
+    assert_eq!(accounts[0].session_keys, 1);
+
+    // Wiping again finds nothing; an empty account ID is refused
+    let again = block_on(handle_wipe_account_state(WipeAccountStateRequest {
+        account_id: "alice.testnet".to_string(),
+    }))
+    .unwrap();
+    assert!(!again.found);
+    assert!(block_on(handle_wipe_account_state(WipeAccountStateRequest {
+        account_id: String::new(),
+    }))
+    .is_err());
+}
+
+#[test]
+fn test_idle_accounts_are_dropped_at_the_limit_but_reservations_are_kept() {
+    state::reserve_nonces(&alice(), "req-held", vec!["1".to_string()]);
+    for i in 0..MAX_ACTIVE_ACCOUNTS {
+        let account = AccountId(format!("user{}.testnet", i));
+        state::record_audit(&account, "req", "signNep413Message", "Signed", None);
+    }
+    let accounts = state::list_active_accounts();
+    assert_eq!(accounts.len(), MAX_ACTIVE_ACCOUNTS);
+    assert!(accounts.iter().any(|a| a.account_id == alice()));
+    assert_eq!(state::reserved_nonces(&alice(), "req-held"), vec!["1"]);
+}
+
+#[test]
+fn test_account_override_layers_over_the_base_policy() {
+    let policy: WorkerPolicy = serde_json::from_value(json!({
+        "preSignHook": false,
+        "hookTimeoutMs": 5000,
+        "allowedRpcOrigins": ["https://rpc.testnet.near.org"],
+        "accountOverrides": {
+            "bob.testnet": {
+                "preSignHook": true,
+                "allowedRpcOrigins": ["https://rpc.bob.example"],
+                "depositEscalationYocto": "1000"
+            }
+        }
+    }))
+    .unwrap();
+
+    let for_alice = policy.for_account(&alice());
+    assert!(!for_alice.pre_sign_hook);
+    assert_eq!(for_alice.allowed_rpc_origins, vec!["https://rpc.testnet.near.org"]);
+    assert_eq!(for_alice.deposit_escalation_yocto, None);
+
+    let for_bob = policy.for_account(&bob());
+    assert!(for_bob.pre_sign_hook);
+    assert_eq!(for_bob.hook_timeout_ms, Some(5000));
+    assert_eq!(for_bob.allowed_rpc_origins, vec!["https://rpc.bob.example"]);
+    assert_eq!(for_bob.deposit_escalation_yocto.as_deref(), Some("1000"));
+    assert!(for_bob.account_overrides.is_empty());
+}
+
+#[test]
+fn test_strict_parsing_checks_each_account_override() {
+    let payload = |override_fields: serde_json::Value| {
+        json!({
+            "txSigningRequests": [],
+            "workerPolicy": {
+                "strictParsing": true,
+                "accountOverrides": { "bob.testnet": override_fields }
+            }
+        })
+    };
+    let sign = WorkerRequestType::SignTransactionsWithActions;
+    assert_eq!(
+        check_unknown_fields(sign, &payload(json!({ "preSignHook": true }))),
+        Ok(())
+    );
+    assert_eq!(
+        check_unknown_fields(sign, &payload(json!({ "preSignHok": true }))),
+        Err(StrictParseError::UnknownField {
+            path: "/workerPolicy/accountOverrides/bob.testnet/preSignHok".to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_batch_must_be_signed_by_one_account() {
+    let request = |signers: &[&str]| -> SignTransactionsWithActionsRequest {
+        serde_json::from_value(json!({
+            "rpcCall": {
+                "contractId": "w3a-v1.testnet",
+                "nearRpcUrl": "https://rpc.testnet.near.org",
+                "nearAccountId": signers[0]
+            },
+            "decryption": { "encryptedPrivateKeyData": "AAAA", "encryptedPrivateKeyIv": "BBBB" },
+            "txSigningRequests": signers.iter().map(|signer| json!({
+                "nearAccountId": signer,
+                "receiverId": "usdc.testnet",
+                "actions": "[]"
+            })).collect::<Vec<_>>(),
+            "confirmationConfig": null
+        }))
+        .unwrap()
+    };
+    assert_eq!(
+        batch_account_id(&request(&["alice.testnet", "alice.testnet"])),
+        Ok(alice())
+    );
+    let err = batch_account_id(&request(&["alice.testnet", "bob.testnet"])).unwrap_err();
+    assert!(err.contains("alice.testnet and bob.testnet"), "{}", err);
+}
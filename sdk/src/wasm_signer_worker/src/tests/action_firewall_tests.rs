@@ -0,0 +1,301 @@
+use crate::action_firewall::{
+    check_high_risk_actions, escalate_confirmation_config, high_risk_actions, HighRiskAction,
+};
+use crate::actions::ActionParams;
+use crate::confirmation_blocks::{
+    transaction_summary_blocks, ConfirmationSummaryBlock, SummaryPolicy, SummaryWarningCode,
+    WarningSeverity,
+};
+use crate::countdown_handshake::countdown_delay_ms;
+use crate::error::{ActionFirewallError, SignerErrorCode};
+use crate::handlers::confirm_tx_details::validate_and_normalize_confirmation_config;
+use crate::handlers::handle_sign_transactions_with_actions::TransactionPayload;
+use crate::handlers::handle_signing_intent::{
+    handle_create_signing_intent, CreateSigningIntentRequest,
+};
+use crate::key_selection::key_fingerprint;
+use crate::strict_parsing::check_unknown_fields;
+use crate::tests::block_on;
+use crate::types::handlers::{
+    ConfirmationBehavior, ConfirmationConfig, ConfirmationUIMode, RpcCallPayload, WorkerPolicy,
+};
+use crate::types::worker_messages::WorkerRequestType;
+use serde_json::json;
+
+const SIGNER: &str = "alice.testnet";
+const KEY_BYTES: [u8; 32] = [7; 32];
+
+fn new_key() -> String {
+    format!("ed25519:{}", bs58::encode(KEY_BYTES).into_string())
+}
+
+fn add_key(public_key: &str, access_key: serde_json::Value) -> ActionParams {
+    ActionParams::AddKey {
+        public_key: public_key.to_string(),
+        access_key: access_key.to_string(),
+    }
+}
+
+fn full_access_add_key() -> ActionParams {
+    add_key(
+        &new_key(),
+        json!({ "nonce": 0, "permission": { "FullAccess": {} } }),
+    )
+}
+
+fn function_call_add_key() -> ActionParams {
+    add_key(
+        &new_key(),
+        json!({
+            "nonce": 0,
+            "permission": { "FunctionCall": {
+                "allowance": null, "receiver_id": "app.testnet", "method_names": []
+            } }
+        }),
+    )
+}
+
+fn delete_account(beneficiary_id: &str) -> ActionParams {
+    ActionParams::DeleteAccount {
+        beneficiary_id: beneficiary_id.to_string(),
+    }
+}
+
+fn batch(actions: Vec<ActionParams>) -> Vec<(String, Vec<ActionParams>)> {
+    vec![(SIGNER.to_string(), actions)]
+}
+
+fn blocking_policy() -> WorkerPolicy {
+    WorkerPolicy {
+        block_full_access_add_key: true,
+        ..WorkerPolicy::default()
+    }
+}
+
+/// Every config an integrator could ask for, including none
+fn requested_configs() -> Vec<Option<ConfirmationConfig>> {
+    let mut configs = vec![None];
+    for ui_mode in [
+        ConfirmationUIMode::Skip,
+        ConfirmationUIMode::Modal,
+        ConfirmationUIMode::Drawer,
+    ] {
+        for behavior in [
+            ConfirmationBehavior::AutoProceed,
+            ConfirmationBehavior::RequireClick,
+        ] {
+            configs.push(Some(
+                ConfirmationConfig::new(ui_mode.clone(), behavior).with_auto_proceed_delay(0),
+            ));
+        }
+    }
+    configs
+}
+
+#[test]
+fn test_high_risk_actions() {
+    let receivers_and_actions = batch(vec![
+        function_call_add_key(),
+        full_access_add_key(),
+        delete_account(SIGNER),
+        delete_account("mallory.testnet"),
+    ]);
+    assert_eq!(
+        high_risk_actions(SIGNER, &receivers_and_actions),
+        vec![
+            HighRiskAction::FullAccessAddKey {
+                receiver_id: SIGNER.to_string(),
+                public_key: new_key(),
+            },
+            HighRiskAction::DeleteAccountToOther {
+                receiver_id: SIGNER.to_string(),
+                beneficiary_id: "mallory.testnet".to_string(),
+            },
+        ]
+    );
+    assert!(high_risk_actions(SIGNER, &batch(vec![function_call_add_key()])).is_empty());
+}
+
+#[test]
+fn test_config_cannot_downgrade_high_risk_confirmations() {
+    for actions in [
+        vec![full_access_add_key()],
+        vec![delete_account("bob.testnet")],
+    ] {
+        let high_risk = high_risk_actions(SIGNER, &batch(actions));
+        for requested in requested_configs() {
+            let escalated = escalate_confirmation_config(requested.as_ref(), &high_risk)
+                .expect("high-risk batches always carry a config");
+            let normalized = validate_and_normalize_confirmation_config(&escalated);
+            assert_ne!(
+                normalized.ui_mode,
+                ConfirmationUIMode::Skip,
+                "{:?}",
+                requested
+            );
+            assert_eq!(
+                normalized.behavior,
+                ConfirmationBehavior::RequireClick,
+                "{:?}",
+                requested
+            );
+            assert_eq!(normalized.auto_proceed_delay, None);
+            assert_eq!(countdown_delay_ms(Some(&normalized)), None);
+        }
+    }
+}
+
+#[test]
+fn test_escalation_keeps_other_config_fields() {
+    let high_risk = high_risk_actions(SIGNER, &batch(vec![full_access_add_key()]));
+    let drawer = ConfirmationConfig::new(
+        ConfirmationUIMode::Drawer,
+        ConfirmationBehavior::AutoProceed,
+    )
+    .with_auto_proceed_delay(1500)
+    .with_theme("light".to_string())
+    .with_locale("es".to_string());
+    let escalated = escalate_confirmation_config(Some(&drawer), &high_risk).unwrap();
+    assert_eq!(escalated.ui_mode, ConfirmationUIMode::Drawer);
+    assert_eq!(escalated.theme.as_deref(), Some("light"));
+    assert_eq!(escalated.locale.as_deref(), Some("es"));
+
+    // Batches without high-risk actions keep the requested config as it is
+    let low_risk = high_risk_actions(SIGNER, &batch(vec![delete_account(SIGNER)]));
+    assert!(low_risk.is_empty());
+    let skip = ConfirmationConfig::new(ConfirmationUIMode::Skip, ConfirmationBehavior::AutoProceed);
+    let kept = escalate_confirmation_config(Some(&skip), &low_risk).unwrap();
+    assert_eq!(kept.ui_mode, ConfirmationUIMode::Skip);
+    assert_eq!(kept.behavior, ConfirmationBehavior::AutoProceed);
+    assert!(escalate_confirmation_config(None, &low_risk).is_none());
+}
+
+#[test]
+fn test_block_full_access_add_key() {
+    let full_access = batch(vec![full_access_add_key()]);
+    assert_eq!(check_high_risk_actions(None, SIGNER, &full_access), Ok(()));
+
+    let err = check_high_risk_actions(Some(&blocking_policy()), SIGNER, &full_access).unwrap_err();
+    assert_eq!(err.code(), Some(SignerErrorCode::FullAccessAddKeyBlocked));
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "FullAccessAddKeyBlocked: adding full access key {} to {} is blocked by \
+             workerPolicy.blockFullAccessAddKey",
+            new_key(),
+            SIGNER
+        )
+    );
+
+    // Only full access keys are blocked
+    let others = batch(vec![function_call_add_key(), delete_account("bob.testnet")]);
+    assert_eq!(
+        check_high_risk_actions(Some(&blocking_policy()), SIGNER, &others),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_full_access_keys_need_a_fingerprint() {
+    let unparseable = batch(vec![add_key(
+        "secp256k1:abc",
+        json!({ "nonce": 0, "permission": "FullAccess" }),
+    )]);
+    let err = check_high_risk_actions(None, SIGNER, &unparseable).unwrap_err();
+    assert!(matches!(
+        err,
+        ActionFirewallError::KeyNotFingerprintable { ref public_key, .. } if public_key == "secp256k1:abc"
+    ));
+    assert_eq!(err.code(), None);
+}
+
+#[test]
+fn test_full_access_add_key_summary_shows_fingerprint() {
+    let fingerprint = key_fingerprint(&KEY_BYTES);
+    let blocks = transaction_summary_blocks(
+        SIGNER,
+        &[full_access_add_key()],
+        None,
+        &SummaryPolicy::default(),
+    );
+    match &blocks[0] {
+        ConfirmationSummaryBlock::Warning {
+            severity,
+            code,
+            text,
+            ..
+        } => {
+            assert_eq!(*severity, WarningSeverity::Danger);
+            assert_eq!(*code, SummaryWarningCode::FullAccessKey);
+            assert!(text.contains(&new_key()), "{}", text);
+            assert!(text.contains(&fingerprint), "{}", text);
+        }
+        other => panic!("expected the full access warning first, got {:?}", other),
+    }
+    let key_rows: Vec<_> = blocks
+        .iter()
+        .filter_map(|block| match block {
+            ConfirmationSummaryBlock::KeyRow {
+                label,
+                public_key,
+                fingerprint,
+                ..
+            } => Some((label.as_str(), public_key.clone(), fingerprint.clone())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        key_rows,
+        vec![("New full access key", new_key(), fingerprint)]
+    );
+
+    // Function call keys get neither
+    let blocks = transaction_summary_blocks(
+        SIGNER,
+        &[function_call_add_key()],
+        None,
+        &SummaryPolicy::default(),
+    );
+    assert!(!blocks.iter().any(|block| matches!(
+        block,
+        ConfirmationSummaryBlock::Warning { .. } | ConfirmationSummaryBlock::KeyRow { .. }
+    )));
+}
+
+#[test]
+fn test_create_signing_intent_refuses_blocked_keys() {
+    let err = block_on(handle_create_signing_intent(CreateSigningIntentRequest {
+        rpc_call: RpcCallPayload {
+            contract_id: "w3a-v1.testnet".to_string(),
+            near_rpc_url: "https://rpc.testnet.near.org".to_string(),
+            near_account_id: SIGNER.to_string(),
+        },
+        tx_signing_requests: vec![TransactionPayload {
+            near_account_id: SIGNER.to_string(),
+            receiver_id: SIGNER.to_string(),
+            actions: serde_json::to_string(&[full_access_add_key()]).unwrap(),
+            depends_on: Vec::new(),
+        }],
+        confirmation_config: None,
+        worker_policy: Some(blocking_policy()),
+        ttl_ms: None,
+    }))
+    .unwrap_err();
+    assert!(err.starts_with("FullAccessAddKeyBlocked: "), "{}", err);
+}
+
+#[test]
+fn test_block_full_access_add_key_policy_field() {
+    let policy: WorkerPolicy =
+        serde_json::from_value(json!({ "blockFullAccessAddKey": true })).unwrap();
+    assert!(policy.block_full_access_add_key);
+    assert!(!WorkerPolicy::default().block_full_access_add_key);
+
+    let payload = json!({
+        "workerPolicy": { "strictParsing": true, "blockFullAccessAddKey": true }
+    });
+    assert_eq!(
+        check_unknown_fields(WorkerRequestType::SignTransactionsWithActions, &payload),
+        Ok(())
+    );
+}
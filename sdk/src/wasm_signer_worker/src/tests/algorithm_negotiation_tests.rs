@@ -0,0 +1,282 @@
+use crate::contract_args::{registration_args_json, ContractInterfaceVersion};
+use crate::credential_options::{
+    negotiated_algorithm, offered_algorithms, PUB_KEY_CRED_ALGORITHMS,
+};
+use crate::encoders::base64_url_encode;
+use crate::error::{AlgorithmNegotiationError, ConfigError, SignerErrorCode};
+use crate::handlers::handle_build_credential_options::{
+    creation_options_for, BuildCredentialCreationOptionsRequest,
+};
+use crate::handlers::handle_derive_near_keypair_and_encrypt::{
+    handle_derive_near_keypair_and_encrypt, DeriveNearKeypairAndEncryptRequest,
+};
+use crate::rpc_calls::VrfData;
+use crate::tests::block_on;
+use crate::types::handlers::AuthenticatorOptions;
+use crate::types::{WebAuthnRegistrationCredential, WebAuthnRegistrationResponse};
+use ciborium::value::Value as CborValue;
+use serde_json::{json, Value};
+
+const ACCOUNT_ID: &str = "alice.testnet";
+const RP_ID: &str = "example.localhost";
+
+fn cbor(value: &CborValue) -> Vec<u8> {
+    let mut out = Vec::new();
+    ciborium::into_writer(value, &mut out).unwrap();
+    out
+}
+
+/// An OKP credential key with COSE algorithm `alg`
+fn cose_key(alg: i64) -> Vec<u8> {
+    cbor(&CborValue::Map(vec![
+        (CborValue::Integer(1.into()), CborValue::Integer(1.into())),
+        (CborValue::Integer(3.into()), CborValue::Integer(alg.into())),
+        (
+            CborValue::Integer((-1).into()),
+            CborValue::Integer(6.into()),
+        ),
+        (
+            CborValue::Integer((-2).into()),
+            CborValue::Bytes(vec![0x11; 32]),
+        ),
+    ]))
+}
+
+/// A "none" attestation object for a credential created with `alg`
+fn attestation_for(alg: i64) -> String {
+    let mut auth_data = vec![0x49u8; 32];
+    auth_data.push(0x45); // UP UV AT
+    auth_data.extend_from_slice(&0u32.to_be_bytes());
+    auth_data.extend_from_slice(&[0u8; 16]);
+    auth_data.extend_from_slice(&3u16.to_be_bytes());
+    auth_data.extend_from_slice(&[1, 2, 3]);
+    auth_data.extend_from_slice(&cose_key(alg));
+    base64_url_encode(&cbor(&CborValue::Map(vec![
+        (
+            CborValue::Text("fmt".to_string()),
+            CborValue::Text("none".to_string()),
+        ),
+        (
+            CborValue::Text("attStmt".to_string()),
+            CborValue::Map(vec![]),
+        ),
+        (
+            CborValue::Text("authData".to_string()),
+            CborValue::Bytes(auth_data),
+        ),
+    ])))
+}
+
+fn allowing(algorithms: &[i32]) -> AuthenticatorOptions {
+    AuthenticatorOptions::new().with_allowed_algorithms(algorithms.to_vec())
+}
+
+fn creation_request(authenticator_options: Value) -> BuildCredentialCreationOptionsRequest {
+    serde_json::from_value(json!({
+        "registration": {
+            "nearAccountId": ACCOUNT_ID,
+            "nonce": "1",
+            "blockHash": "11111111111111111111111111111111",
+            "authenticatorOptions": authenticator_options
+        },
+        "rpId": RP_ID,
+        "vrfChallenge": {
+            "vrfInput": "aW5wdXQ",
+            "vrfOutput": base64_url_encode(&[7u8; 64]),
+            "vrfProof": "cHJvb2Y",
+            "vrfPublicKey": "dnJmLXB1YmxpYy1rZXk",
+            "userId": ACCOUNT_ID,
+            "rpId": RP_ID,
+            "blockHeight": "1000",
+            "blockHash": "11111111111111111111111111111111"
+        }
+    }))
+    .unwrap()
+}
+
+fn offered_in_options(authenticator_options: Value) -> Result<Vec<i64>, String> {
+    let result = creation_options_for(&creation_request(authenticator_options), &[])?;
+    let options = serde_json::to_value(result).unwrap();
+    Ok(options["publicKey"]["pubKeyCredParams"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|param| param["alg"].as_i64().unwrap())
+        .collect())
+}
+
+fn registration_request(
+    attestation_object: String,
+    extra: Value,
+) -> DeriveNearKeypairAndEncryptRequest {
+    let mut request = json!({
+        "nearAccountId": ACCOUNT_ID,
+        "credential": {
+            "id": "cred",
+            "rawId": "cred",
+            "type": "public-key",
+            "authenticatorAttachment": null,
+            "response": {
+                "clientDataJSON": base64_url_encode(b"{}"),
+                "attestationObject": attestation_object,
+                "transports": []
+            },
+            "clientExtensionResults": {}
+        },
+        "prfSupported": false,
+        "prfFallback": { "scheme": "largeBlob" },
+        "workerPolicy": { "prfFallbackSchemes": ["largeBlob"] }
+    });
+    request
+        .as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    serde_json::from_value(request).unwrap()
+}
+
+#[test]
+fn test_allowed_algorithms_order_pub_key_cred_params() {
+    assert_eq!(
+        offered_in_options(json!({})).unwrap(),
+        PUB_KEY_CRED_ALGORITHMS.map(i64::from).to_vec()
+    );
+    assert_eq!(
+        offered_in_options(json!({ "allowedAlgorithms": [-7, -8] })).unwrap(),
+        vec![-7, -8]
+    );
+    assert_eq!(offered_algorithms(None), &PUB_KEY_CRED_ALGORITHMS);
+    assert_eq!(offered_algorithms(Some(&allowing(&[-257]))), &[-257]);
+}
+
+#[test]
+fn test_allowed_algorithms_must_be_supported() {
+    assert_eq!(allowing(&[-7, -257]).validate(), Ok(()));
+    for (allowed, reason) in [
+        (vec![], "must not be empty"),
+        (vec![-8, -35], "-35 is not a supported COSE algorithm"),
+        (vec![-7, -8, -7], "-7 is listed twice"),
+    ] {
+        match allowing(&allowed).validate() {
+            Err(ConfigError::InvalidValue {
+                field,
+                reason: actual,
+            }) => {
+                assert_eq!(field, "allowedAlgorithms");
+                assert!(actual.starts_with(reason), "{}", actual);
+            }
+            other => panic!("{:?} should be refused, got {:?}", allowed, other),
+        }
+    }
+    let err = offered_in_options(json!({ "allowedAlgorithms": [-36] })).unwrap_err();
+    assert!(err.contains("allowedAlgorithms"), "{}", err);
+}
+
+#[test]
+fn test_negotiated_algorithm_is_checked_against_the_offer() {
+    assert_eq!(
+        negotiated_algorithm(&attestation_for(-8), None),
+        Ok(Some(-8))
+    );
+    assert_eq!(
+        negotiated_algorithm(&attestation_for(-7), Some(&allowing(&[-7]))),
+        Ok(Some(-7))
+    );
+
+    let err = negotiated_algorithm(&attestation_for(-8), Some(&allowing(&[-7, -257]))).unwrap_err();
+    assert_eq!(
+        err,
+        AlgorithmNegotiationError::Disallowed {
+            alg: -8,
+            allowed: vec![-7, -257]
+        }
+    );
+    assert_eq!(
+        err.code(),
+        Some(SignerErrorCode::DisallowedAlgorithmNegotiated)
+    );
+    assert!(err
+        .to_string()
+        .starts_with("DisallowedAlgorithmNegotiated: "));
+
+    // Algorithms the worker never offers are refused even without allowedAlgorithms
+    let err = negotiated_algorithm(&attestation_for(-35), None).unwrap_err();
+    assert_eq!(
+        err.code(),
+        Some(SignerErrorCode::DisallowedAlgorithmNegotiated)
+    );
+}
+
+#[test]
+fn test_unreadable_attestations_fail_only_when_restricted() {
+    let unreadable = base64_url_encode(b"not cbor");
+    assert_eq!(negotiated_algorithm(&unreadable, None), Ok(None));
+    assert_eq!(
+        negotiated_algorithm(&unreadable, Some(&AuthenticatorOptions::new())),
+        Ok(None)
+    );
+    let err = negotiated_algorithm(&unreadable, Some(&allowing(&[-8]))).unwrap_err();
+    assert!(matches!(err, AlgorithmNegotiationError::Unreadable { .. }));
+    assert_eq!(err.code(), None);
+}
+
+#[test]
+fn test_registration_reports_negotiated_algorithm() {
+    let result = block_on(handle_derive_near_keypair_and_encrypt(
+        registration_request(attestation_for(-8), json!({})),
+    ))
+    .unwrap();
+    assert_eq!(result.negotiated_algorithm, Some(-8));
+    assert_eq!(
+        serde_json::to_value(&result).unwrap()["negotiatedAlgorithm"],
+        json!(-8)
+    );
+
+    let refused = block_on(handle_derive_near_keypair_and_encrypt(
+        registration_request(
+            attestation_for(-8),
+            json!({ "authenticatorOptions": { "allowedAlgorithms": [-7] } }),
+        ),
+    ))
+    .unwrap_err();
+    assert!(
+        refused.starts_with("DisallowedAlgorithmNegotiated: "),
+        "{}",
+        refused
+    );
+}
+
+#[test]
+fn test_allowed_algorithms_are_not_sent_to_the_contract() {
+    let args = registration_args_json(
+        ContractInterfaceVersion::V2,
+        VrfData {
+            vrf_input_data: vec![1],
+            vrf_output: vec![2],
+            vrf_proof: vec![3],
+            public_key: vec![4],
+            user_id: ACCOUNT_ID.to_string(),
+            rp_id: RP_ID.to_string(),
+            block_height: 1000,
+            block_hash: vec![5],
+        },
+        WebAuthnRegistrationCredential {
+            id: "cred".to_string(),
+            raw_id: "cred".to_string(),
+            response: WebAuthnRegistrationResponse {
+                client_data_json: "e30".to_string(),
+                attestation_object: attestation_for(-8),
+                transports: None,
+            },
+            authenticator_attachment: None,
+            reg_type: "public-key".to_string(),
+        },
+        None,
+        Some(2),
+        Some(allowing(&[-8])),
+    )
+    .unwrap();
+    let args: Value = serde_json::from_str(&args).unwrap();
+    let options = args["authenticator_options"].as_object().unwrap();
+    assert!(options.contains_key("user_verification"));
+    assert!(!options.contains_key("allowed_algorithms"));
+}
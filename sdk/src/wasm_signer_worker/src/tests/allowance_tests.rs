@@ -0,0 +1,262 @@
+use crate::actions::ActionParams;
+use crate::allowance::*;
+use crate::config::{
+    DEFAULT_ALLOWANCE_GAS_PRICE, DEFAULT_LOW_ALLOWANCE_WARNING_YOCTO, YOCTO_PER_NEAR,
+};
+use crate::confirmation_blocks::*;
+use crate::error::{InsufficientAllowanceError, SignerErrorCode};
+use crate::handlers::confirm_tx_details::{
+    compute_confirmation_intent_digest, confirmation_tx_signing_requests_json,
+};
+use crate::handlers::{handle_sign_transactions_with_actions, SignTransactionsWithActionsRequest};
+use crate::tests::block_on;
+use crate::types::handlers::{TransactionContext, WorkerPolicy};
+use serde_json::{json, Value};
+
+const RECEIVER: &str = "dex.testnet";
+/// 30 Tgas
+const GAS: u128 = 30_000_000_000_000;
+
+fn function_call(gas: u128, deposit: u128) -> ActionParams {
+    ActionParams::FunctionCall {
+        method_name: "swap".to_string(),
+        args: "{}".to_string(),
+        gas: gas.to_string(),
+        deposit: deposit.to_string(),
+    }
+}
+
+fn context(allowance: Option<&str>, gas_price: Option<&str>) -> TransactionContext {
+    TransactionContext {
+        access_key_allowance: allowance.map(str::to_string),
+        gas_price: gas_price.map(str::to_string),
+        ..TransactionContext::new(
+            "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".to_string(),
+            "7".to_string(),
+            "1000".to_string(),
+            "11111111111111111111111111111111".to_string(),
+        )
+    }
+}
+
+#[test]
+fn test_batch_cost_is_prepaid_gas_plus_deposits() {
+    let batch = vec![
+        (
+            RECEIVER.to_string(),
+            vec![function_call(GAS, 1), function_call(2 * GAS, 0)],
+        ),
+        (
+            "bob.testnet".to_string(),
+            vec![
+                ActionParams::Transfer {
+                    deposit: "5".to_string(),
+                },
+                ActionParams::DeleteKey {
+                    public_key: "ed25519:key".to_string(),
+                },
+            ],
+        ),
+    ];
+    assert_eq!(estimate_batch_cost(&batch, 100), 3 * GAS * 100 + 1 + 5);
+    assert_eq!(estimate_batch_cost(&[], 100), 0);
+}
+
+#[test]
+fn test_estimate_only_for_function_call_keys() {
+    let batch = vec![(RECEIVER.to_string(), vec![function_call(GAS, 0)])];
+
+    // No context, or a full access key / unlimited allowance: nothing to check
+    assert_eq!(allowance_estimate(None, &batch, None), Ok(None));
+    assert_eq!(
+        allowance_estimate(Some(&context(None, Some("100"))), &batch, None),
+        Ok(None)
+    );
+
+    // Without a block gas price the minimum gas price is assumed
+    let estimate = allowance_estimate(Some(&context(Some("1000"), None)), &batch, None)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        estimate,
+        AllowanceEstimate {
+            remaining: 1000,
+            required: GAS * DEFAULT_ALLOWANCE_GAS_PRICE,
+            warn_below: DEFAULT_LOW_ALLOWANCE_WARNING_YOCTO,
+        }
+    );
+
+    let policy = WorkerPolicy {
+        low_allowance_warning_yocto: Some("42".to_string()),
+        ..WorkerPolicy::default()
+    };
+    let estimate = allowance_estimate(
+        Some(&context(Some("1000"), Some("200"))),
+        &batch,
+        Some(&policy),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(estimate.required, GAS * 200);
+    assert_eq!(estimate.warn_below, 42);
+}
+
+#[test]
+fn test_invalid_amounts_name_their_field() {
+    let batch = vec![(RECEIVER.to_string(), vec![function_call(GAS, 0)])];
+    let err = allowance_estimate(Some(&context(Some("lots"), None)), &batch, None).unwrap_err();
+    assert!(
+        err.contains("transactionContext.accessKeyAllowance"),
+        "{}",
+        err
+    );
+    let err = allowance_estimate(Some(&context(Some("1"), Some("0.1"))), &batch, None).unwrap_err();
+    assert!(err.contains("transactionContext.gasPrice"), "{}", err);
+    let policy = WorkerPolicy {
+        low_allowance_warning_yocto: Some("0.05 NEAR".to_string()),
+        ..WorkerPolicy::default()
+    };
+    let err =
+        allowance_estimate(Some(&context(Some("1"), None)), &batch, Some(&policy)).unwrap_err();
+    assert!(
+        err.contains("workerPolicy.lowAllowanceWarningYocto"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_allowance_below_the_estimate_is_refused() {
+    let estimate = AllowanceEstimate {
+        remaining: 10,
+        required: 10,
+        warn_below: 5,
+    };
+    assert_eq!(estimate.allowance_after(), Some(0));
+    assert!(estimate.check().is_ok());
+    assert!(estimate.is_low());
+
+    let short = AllowanceEstimate {
+        required: 11,
+        ..estimate
+    };
+    assert_eq!(short.allowance_after(), None);
+    assert!(short.is_low());
+    let err = short.check().unwrap_err();
+    assert_eq!(
+        err,
+        InsufficientAllowanceError {
+            remaining: 10,
+            required: 11
+        }
+    );
+    assert_eq!(err.code(), SignerErrorCode::InsufficientAllowance);
+    assert_eq!(
+        err.to_string(),
+        "InsufficientAllowance: the access key has 10 yoctoNEAR of allowance left, the \
+         transactions need up to 11"
+    );
+    let definition = err.code().definition();
+    assert_eq!(definition.code, "InsufficientAllowance");
+    assert!(!definition.retriable);
+
+    let plenty = AllowanceEstimate {
+        remaining: 100,
+        ..estimate
+    };
+    assert!(!plenty.is_low());
+}
+
+#[test]
+fn test_low_allowance_warning_leads_the_first_transaction() {
+    let batch = vec![
+        (RECEIVER.to_string(), vec![function_call(GAS, 0)]),
+        ("pool.testnet".to_string(), vec![function_call(GAS, 0)]),
+    ];
+    let flags = vec![Some(false), Some(false)];
+    let low = SummaryPolicy::default().with_allowance(Some(AllowanceEstimate {
+        remaining: YOCTO_PER_NEAR / 10,
+        required: YOCTO_PER_NEAR / 100,
+        warn_below: YOCTO_PER_NEAR,
+    }));
+
+    let payload = confirmation_tx_signing_requests_json(&batch, &flags, None, Some(&low));
+    let blocks = |tx: &Value| -> Vec<Value> { tx["summaryBlocks"].as_array().unwrap().clone() };
+    let first = blocks(&payload[0]);
+    assert_eq!(first[0]["kind"], json!("warning"));
+    assert_eq!(first[0]["code"], json!("LowAllowance"));
+    assert_eq!(first[0]["severity"], json!("caution"));
+    assert!(first[0]["text"].as_str().unwrap().contains("0.09 NEAR"));
+    assert_eq!(
+        first[1..],
+        json!(transaction_summary_blocks(
+            RECEIVER,
+            &batch[0].1,
+            Some(false),
+            &low
+        ))
+        .as_array()
+        .unwrap()[..]
+    );
+    // Only once per batch
+    assert!(blocks(&payload[1])
+        .iter()
+        .all(|block| block["code"] != json!("LowAllowance")));
+
+    // The warning is digested with the rest of the blocks
+    let ample = SummaryPolicy::default().with_allowance(Some(AllowanceEstimate {
+        warn_below: YOCTO_PER_NEAR / 100,
+        ..low.allowance.unwrap()
+    }));
+    assert!(batch_blocks(&ample).is_empty());
+    assert_eq!(
+        compute_confirmation_intent_digest(&batch, &flags, None, Some(&ample)).unwrap(),
+        compute_confirmation_intent_digest(&batch, &flags, None, Some(&SummaryPolicy::default()))
+            .unwrap()
+    );
+    assert_ne!(
+        compute_confirmation_intent_digest(&batch, &flags, None, Some(&low)).unwrap(),
+        compute_confirmation_intent_digest(&batch, &flags, None, Some(&ample)).unwrap()
+    );
+}
+
+#[test]
+fn test_overdrawn_key_fails_signing_request_before_confirmation() {
+    let request: SignTransactionsWithActionsRequest = serde_json::from_value(json!({
+        "rpcCall": { "contractId": "w3a-v1.testnet", "nearRpcUrl": "https://rpc.testnet.near.org", "nearAccountId": "alice.testnet" },
+        "decryption": { "encryptedPrivateKeyData": "AAAA", "encryptedPrivateKeyIv": "BBBB" },
+        "txSigningRequests": [{
+            "nearAccountId": "alice.testnet",
+            "receiverId": RECEIVER,
+            "actions": json!([{
+                "action_type": "FunctionCall",
+                "method_name": "swap",
+                "args": "{}",
+                "gas": GAS.to_string(),
+                "deposit": "0"
+            }]).to_string()
+        }],
+        "confirmationConfig": null,
+        "transactionContext": {
+            "nearPublicKeyStr": "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp",
+            "nextNonce": "7",
+            "txBlockHeight": "1000",
+            "txBlockHash": "11111111111111111111111111111111",
+            "accessKeyAllowance": "1000",
+            "gasPrice": "100000000"
+        }
+    }))
+    .unwrap();
+
+    let result = block_on(handle_sign_transactions_with_actions(request)).unwrap();
+    assert!(!result.success);
+    assert_eq!(result.error_code.as_deref(), Some("InsufficientAllowance"));
+    assert_eq!(result.error_category.as_deref(), Some("Policy"));
+    let error = result.error.unwrap();
+    assert!(error.contains("1000 yoctoNEAR"), "{}", error);
+    assert!(
+        error.contains(&(GAS * 100_000_000).to_string()),
+        "{}",
+        error
+    );
+}
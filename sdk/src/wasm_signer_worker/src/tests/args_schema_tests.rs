@@ -0,0 +1,444 @@
+use crate::actions::ActionParams;
+use crate::args_schema::{check_batch_args, ArgsSchemas};
+use crate::config::MAX_REPORTED_ARGS_SCHEMA_VIOLATIONS;
+use crate::confirmation_blocks::{
+    transaction_summary_blocks, ConfirmationSummaryBlock, SummaryPolicy, SummaryWarningCode,
+};
+use crate::error::{ArgsSchemaError, ArgsSchemaViolation, SignerErrorCode};
+use crate::handlers::handle_validate_args_schemas::{
+    handle_validate_args_schemas, ValidateArgsSchemasRequest,
+};
+use crate::schema_pattern::{Pattern, PatternMatch};
+use crate::strict_parsing::check_unknown_fields;
+use crate::tests::block_on;
+use crate::types::handlers::{ArgsSchemaEntry, WorkerPolicy};
+use crate::types::worker_messages::WorkerRequestType;
+use serde_json::{json, Value};
+
+const TOKEN: &str = "usdc.testnet";
+
+fn entry(method_name: &str, schema: Value) -> ArgsSchemaEntry {
+    ArgsSchemaEntry {
+        contract_id: TOKEN.to_string(),
+        method_name: method_name.to_string(),
+        schema,
+    }
+}
+
+fn ft_transfer_schema() -> Value {
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "ft_transfer",
+        "type": "object",
+        "required": ["receiver_id", "amount"],
+        "additionalProperties": false,
+        "properties": {
+            "receiver_id": { "type": "string", "minLength": 2, "maxLength": 64 },
+            "amount": { "type": "string", "pattern": "^[0-9]+$" },
+            "memo": { "type": ["string", "null"] }
+        }
+    })
+}
+
+fn call(method_name: &str, args: &str) -> ActionParams {
+    ActionParams::FunctionCall {
+        method_name: method_name.to_string(),
+        args: args.to_string(),
+        gas: "30000000000000".to_string(),
+        deposit: "1".to_string(),
+    }
+}
+
+fn policy(entries: Vec<ArgsSchemaEntry>) -> WorkerPolicy {
+    WorkerPolicy {
+        args_schemas: entries,
+        ..WorkerPolicy::default()
+    }
+}
+
+/// Violations of `args` against `schema` registered for ft_transfer
+fn violations(schema: Value, args: Value) -> Vec<ArgsSchemaViolation> {
+    let schemas = ArgsSchemas::compile(&[entry("ft_transfer", schema)]).unwrap();
+    let batch = vec![(
+        TOKEN.to_string(),
+        vec![call("ft_transfer", &args.to_string())],
+    )];
+    match schemas.check_batch(&batch) {
+        Ok(()) => Vec::new(),
+        Err(ArgsSchemaError::Violations { violations, .. }) => violations,
+        Err(e) => panic!("unexpected {}", e),
+    }
+}
+
+fn compile_error(schema: Value) -> (String, String) {
+    let errors = ArgsSchemas::compile(&[entry("ft_transfer", schema)]).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    (errors[0].path.clone(), errors[0].message.clone())
+}
+
+#[test]
+fn test_unsupported_schemas_are_refused_when_compiled() {
+    assert_eq!(
+        compile_error(json!({ "type": "object", "oneOf": [] })),
+        (
+            "/oneOf".to_string(),
+            "unsupported keyword \"oneOf\"".to_string()
+        )
+    );
+    assert_eq!(compile_error(json!({ "type": "bigint" })).0, "/type");
+    assert_eq!(
+        compile_error(json!({ "properties": { "amount": { "minLength": -1 } } })).0,
+        "/properties/amount/minLength"
+    );
+    assert_eq!(
+        compile_error(json!({ "additionalProperties": { "type": "string" } })).1,
+        "only true or false is supported"
+    );
+    assert_eq!(
+        compile_error(json!({ "items": [{ "type": "string" }] })).1,
+        "tuple items are not supported"
+    );
+    let (path, message) = compile_error(json!({ "pattern": "^(?=a)" }));
+    assert_eq!(path, "/pattern");
+    assert!(message.contains("lookaround"), "{}", message);
+    assert_eq!(compile_error(json!("object")).0, "");
+
+    // Every entry is reported, including duplicates
+    let errors = ArgsSchemas::compile(&[
+        entry("ft_transfer", json!({ "type": "object" })),
+        entry("ft_transfer", json!({ "type": "object" })),
+        entry("storage_deposit", json!({ "if": true })),
+        ArgsSchemaEntry {
+            contract_id: String::new(),
+            ..entry("ft_transfer", json!(true))
+        },
+    ])
+    .unwrap_err();
+    let messages: Vec<&str> = errors.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "registered more than once",
+            "unsupported keyword \"if\"",
+            "contractId and methodName must not be empty",
+        ]
+    );
+}
+
+#[test]
+fn test_each_keyword_is_checked() {
+    let schema = ft_transfer_schema();
+    assert!(violations(
+        schema.clone(),
+        json!({ "receiver_id": "bob.testnet", "amount": "100", "memo": null })
+    )
+    .is_empty());
+
+    let found = violations(
+        schema,
+        json!({ "receiver_id": "b", "amount": "1e6", "memo": 5, "msg": "" }),
+    );
+    let listed: Vec<(&str, &str)> = found
+        .iter()
+        .map(|v| (v.path.as_str(), v.message.as_str()))
+        .collect();
+    let args = "/txSigningRequests/0/actions/0/args";
+    assert_eq!(
+        listed,
+        vec![
+            (&*format!("{}/amount", args), "must match pattern ^[0-9]+$"),
+            (
+                &*format!("{}/memo", args),
+                "expected string or null, got integer"
+            ),
+            (
+                &*format!("{}/receiver_id", args),
+                "must be at least 2 characters long"
+            ),
+            (
+                &*format!("{}/msg", args),
+                "property is not allowed by the schema"
+            ),
+        ]
+    );
+
+    let numbers = json!({
+        "type": "object",
+        "properties": {
+            "count": { "type": "integer", "minimum": 1, "maximum": 10 },
+            "ratio": { "type": "number", "exclusiveMinimum": 0, "exclusiveMaximum": 1 },
+            "mode": { "enum": ["fast", "safe"] },
+            "version": { "const": 2 },
+            "tags": { "type": "array", "minItems": 1, "maxItems": 2, "items": { "type": "string" } }
+        }
+    });
+    let messages = |args: Value| -> Vec<String> {
+        violations(numbers.clone(), args)
+            .into_iter()
+            .map(|v| format!("{}: {}", v.path.rsplit('/').next().unwrap(), v.message))
+            .collect()
+    };
+    assert!(messages(
+        json!({ "count": 10, "ratio": 0.5, "mode": "safe", "version": 2, "tags": ["a"] })
+    )
+    .is_empty());
+    assert_eq!(
+        messages(json!({ "count": 1.5, "ratio": 1, "mode": "slow", "version": "2" })),
+        vec![
+            "count: expected integer, got number",
+            "mode: must be one of [\"fast\",\"safe\"]",
+            "ratio: must be less than 1",
+            "version: must be 2",
+        ]
+    );
+    assert_eq!(
+        messages(json!({ "count": 0, "tags": ["a", 7, "c"] })),
+        vec![
+            "count: must be at least 1",
+            "tags: must have at most 2 items",
+            "1: expected string, got integer",
+        ]
+    );
+    // A missing property is reported at its own path
+    assert_eq!(
+        violations(ft_transfer_schema(), json!({ "amount": "1" })),
+        vec![ArgsSchemaViolation {
+            path: "/txSigningRequests/0/actions/0/args/receiver_id".to_string(),
+            message: "required property is missing".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn test_patterns() {
+    let finds = |pattern: &str, text: &str| Pattern::compile(pattern).unwrap().find_in(text);
+    let cases = [
+        ("^[0-9]+$", "12345", PatternMatch::Matched),
+        ("^[0-9]+$", "12a45", PatternMatch::NotMatched),
+        ("^[0-9]+$", "", PatternMatch::NotMatched),
+        ("near", "the near account", PatternMatch::Matched),
+        (
+            r"^[a-z0-9_-]+\.(testnet|near)$",
+            "alice-1.testnet",
+            PatternMatch::Matched,
+        ),
+        (
+            r"^[a-z0-9_-]+\.(testnet|near)$",
+            "alice.testnet.x",
+            PatternMatch::NotMatched,
+        ),
+        (r"^\d{2,4}-\w{3}$", "2024-abc", PatternMatch::Matched),
+        (r"^\d{2,4}-\w{3}$", "20245-abc", PatternMatch::NotMatched),
+        (r"^(?:ab)+c?$", "ababc", PatternMatch::Matched),
+        (r"^(?:ab)+c?$", "abac", PatternMatch::NotMatched),
+        (r"^[^\s]+$", "no-spaces", PatternMatch::Matched),
+        (r"^\S*$", "has space", PatternMatch::NotMatched),
+        ("^a.c$", "a\nc", PatternMatch::NotMatched),
+        (r"^\x41B{1}$", "AB", PatternMatch::Matched),
+        ("^a{,2}$", "a{,2}", PatternMatch::Matched),
+        ("^(a|ab)(c|bcd)(d*)$", "abcd", PatternMatch::Matched),
+        ("^x*?y$", "xxy", PatternMatch::Matched),
+    ];
+    for (pattern, text, expected) in cases {
+        assert_eq!(finds(pattern, text), expected, "{} on {:?}", pattern, text);
+    }
+
+    for (pattern, error) in [
+        ("(a", "unclosed group"),
+        ("a)", "unmatched ')'"),
+        ("[a-", "unclosed character class"),
+        ("[z-a]", "out of order"),
+        ("*a", "nothing to repeat"),
+        ("a{3,1}", "out of order"),
+        (r"(a)\1", "backreferences"),
+        (r"\bword", "word boundaries"),
+        (r"[\W]", "negated shorthands"),
+        (r"\q", "unsupported escape"),
+        ("a\\", "lone backslash"),
+    ] {
+        let message = Pattern::compile(pattern).unwrap_err();
+        assert!(message.contains(error), "{}: {}", pattern, message);
+    }
+
+    // Catastrophic backtracking runs out of steps instead of hanging, and fails the check
+    let input = format!("{}!", "a".repeat(40));
+    assert_eq!(finds("^(a|a)*$", &input), PatternMatch::LimitExceeded);
+    let found = violations(json!({ "pattern": "^(a|a)*$" }), json!(input));
+    assert_eq!(found.len(), 1);
+    assert!(
+        found[0].message.contains("too expensive"),
+        "{}",
+        found[0].message
+    );
+}
+
+#[test]
+fn test_batch_lists_every_violation_with_its_pointer() {
+    let worker_policy = policy(vec![
+        entry("ft_transfer", ft_transfer_schema()),
+        entry(
+            "storage_deposit",
+            json!({ "type": "object", "required": ["account_id"] }),
+        ),
+    ]);
+    let batch = vec![
+        (
+            TOKEN.to_string(),
+            vec![
+                ActionParams::Transfer {
+                    deposit: "1".to_string(),
+                },
+                call("storage_deposit", "{}"),
+            ],
+        ),
+        // Other receivers and unregistered methods are not checked
+        ("dex.testnet".to_string(), vec![call("ft_transfer", "{}")]),
+        (
+            TOKEN.to_string(),
+            vec![
+                call("ft_balance_of", "{}"),
+                call("ft_transfer", r#"{"receiver_id":7,"amount":"5"}"#),
+            ],
+        ),
+    ];
+    let err = check_batch_args(Some(&worker_policy), &batch).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::ArgsSchemaViolation);
+    assert_eq!(
+        err,
+        ArgsSchemaError::Violations {
+            violations: vec![
+                ArgsSchemaViolation {
+                    path: "/txSigningRequests/0/actions/1/args/account_id".to_string(),
+                    message: "required property is missing".to_string(),
+                },
+                ArgsSchemaViolation {
+                    path: "/txSigningRequests/2/actions/1/args/receiver_id".to_string(),
+                    message: "expected string, got integer".to_string(),
+                },
+            ],
+            total: 2,
+        }
+    );
+    let message = err.to_string();
+    assert!(
+        message.starts_with("ArgsSchemaViolation: 2 schema violation(s) in FunctionCall args: "),
+        "{}",
+        message
+    );
+
+    // Without registered schemas nothing is checked
+    assert_eq!(check_batch_args(None, &batch), Ok(()));
+    assert_eq!(check_batch_args(Some(&policy(vec![])), &batch), Ok(()));
+
+    // A schema that does not compile fails the request as invalid configuration
+    let broken = policy(vec![entry("ft_transfer", json!({ "pattern": "(" }))]);
+    let err = check_batch_args(Some(&broken), &batch).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::InvalidConfig);
+    assert!(
+        err.to_string()
+            .starts_with("InvalidConfig: Invalid workerPolicy.argsSchemas: usdc.testnet"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_reported_violations_are_capped() {
+    let args: serde_json::Map<String, Value> = (0..MAX_REPORTED_ARGS_SCHEMA_VIOLATIONS + 5)
+        .map(|i| (format!("field{:02}", i), json!(i)))
+        .collect();
+    let schemas = ArgsSchemas::compile(&[entry(
+        "ft_transfer",
+        json!({ "additionalProperties": false }),
+    )])
+    .unwrap();
+    let batch = vec![(
+        TOKEN.to_string(),
+        vec![call("ft_transfer", &Value::Object(args).to_string())],
+    )];
+    let err = schemas.check_batch(&batch).unwrap_err();
+    let ArgsSchemaError::Violations { violations, total } = &err else {
+        panic!("unexpected {}", err);
+    };
+    assert_eq!(violations.len(), MAX_REPORTED_ARGS_SCHEMA_VIOLATIONS);
+    assert_eq!(*total, MAX_REPORTED_ARGS_SCHEMA_VIOLATIONS + 5);
+    assert!(err.to_string().ends_with("; and 5 more"), "{}", err);
+}
+
+#[test]
+fn test_binary_args_are_not_validated_and_get_a_warning() {
+    let worker_policy = policy(vec![entry("ft_transfer", ft_transfer_schema())]);
+    let binary = call("ft_transfer", "\u{1}\u{2}not json");
+    let batch = vec![(TOKEN.to_string(), vec![binary.clone()])];
+    assert_eq!(check_batch_args(Some(&worker_policy), &batch), Ok(()));
+
+    let warned = |summary_policy: &SummaryPolicy, action: &ActionParams| -> bool {
+        transaction_summary_blocks(TOKEN, std::slice::from_ref(action), None, summary_policy)
+            .iter()
+            .any(|block| {
+                matches!(
+                    block,
+                    ConfirmationSummaryBlock::Warning {
+                        code: SummaryWarningCode::ArgsNotValidated,
+                        ..
+                    }
+                )
+            })
+    };
+    let summary_policy = SummaryPolicy::from_worker_policy(Some(&worker_policy)).unwrap();
+    assert!(warned(&summary_policy, &binary));
+    // JSON args, and methods without a schema, get no warning
+    assert!(!warned(&summary_policy, &call("ft_transfer", "{}")));
+    assert!(!warned(&summary_policy, &call("ft_balance_of", "\u{1}")));
+    assert!(!warned(&SummaryPolicy::default(), &binary));
+}
+
+#[test]
+fn test_validate_args_schemas_reports_compile_errors() {
+    let result = block_on(handle_validate_args_schemas(ValidateArgsSchemasRequest {
+        args_schemas: vec![
+            entry("ft_transfer", ft_transfer_schema()),
+            entry(
+                "storage_deposit",
+                json!({ "properties": { "account_id": { "pattern": "[a-" } } }),
+            ),
+        ],
+    }))
+    .unwrap();
+    assert!(!result.valid);
+    assert_eq!(result.schema_count, 2);
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].method_name, "storage_deposit");
+    assert_eq!(result.errors[0].path, "/properties/account_id/pattern");
+
+    let result = block_on(handle_validate_args_schemas(ValidateArgsSchemasRequest {
+        args_schemas: vec![entry("ft_transfer", ft_transfer_schema())],
+    }))
+    .unwrap();
+    assert!(result.valid);
+    assert!(result.errors.is_empty());
+}
+
+#[test]
+fn test_strict_parsing_checks_args_schema_entries() {
+    let payload = |schema_entry: Value| {
+        json!({
+            "txSigningRequests": [],
+            "workerPolicy": { "strictParsing": true, "argsSchemas": [schema_entry] }
+        })
+    };
+    let sign = WorkerRequestType::SignTransactionsWithActions;
+    let known = json!({ "contractId": TOKEN, "methodName": "ft_transfer", "schema": {} });
+    assert_eq!(check_unknown_fields(sign, &payload(known)), Ok(()));
+    let err = check_unknown_fields(
+        sign,
+        &payload(json!({ "contractId": TOKEN, "method": "ft_transfer", "schema": {} })),
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("/workerPolicy/argsSchemas/0/method"),
+        "{}",
+        err
+    );
+}
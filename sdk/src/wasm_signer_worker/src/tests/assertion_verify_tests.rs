@@ -0,0 +1,206 @@
+use crate::assertion_verify::*;
+use crate::cose::extract_cose_public_key_from_attestation;
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use ciborium::value::Value as CborValue;
+use serde_json::{json, Value};
+
+/// Assertions in the shape authenticators return them: authenticatorData for rpId
+/// "example.localhost" (UP|UV flags, sign count) and a webauthn.get clientDataJSON, with the
+/// credential's COSE key in the encoding registration stores (OKP Ed25519, EC2 P-256, and
+/// RSA with a 3-byte exponent). Each is signed with a fixed test key so the set can be
+/// regenerated.
+struct Fixture {
+    algorithm: i64,
+    credential_public_key: &'static str,
+    authenticator_data: &'static str,
+    client_data_json: &'static str,
+    signature: &'static str,
+}
+
+const ED25519: Fixture = Fixture {
+    algorithm: COSE_ALG_EDDSA,
+    credential_public_key: "pAEBAycgBiFYINBKsjJ0K7SrOhNovUYV5ObQIkq3GgFrr4UgozLJd4c3",
+    authenticator_data: "y9GqwTRaMpzVDbXq1dyEAXVOxrou08k22ggRC45MKNgFAAAABw",
+    client_data_json: "eyJ0eXBlIjoid2ViYXV0aG4uZ2V0IiwiY2hhbGxlbmdlIjoiUFY5UGxjMnh6Znh4QVU3NkdtYWYxQ1dab000Z0FOa1VwQW5raTh5dTFZUSIsIm9yaWdpbiI6Imh0dHBzOi8vZXhhbXBsZS5sb2NhbGhvc3QiLCJjcm9zc09yaWdpbiI6ZmFsc2V9",
+    signature: "SUjVH0LUnaiSTyUOUB8lnZoEZhQOmMfTGKqF2JJWwVBj0aJPsXxIUCmhllz86_XUCCTk8WvEoi2fR14Ea-kLDw",
+};
+
+const ES256: Fixture = Fixture {
+    algorithm: COSE_ALG_ES256,
+    credential_public_key: "pQECAyYgASFYINZak5d8qj0bCBhS_1ennkZfFmBXcwS66tUF3TpIWJzzIlggUBheiVNy32Ih6joTdVfkc_3bZ1XwW9UHw8Uz_OnJEoU",
+    authenticator_data: "y9GqwTRaMpzVDbXq1dyEAXVOxrou08k22ggRC45MKNgFAAAADA",
+    client_data_json: "eyJ0eXBlIjoid2ViYXV0aG4uZ2V0IiwiY2hhbGxlbmdlIjoiR1VERHQxZHdWSTdSMzdGZGhZTXlKbWZWMDJoWnhLTEJya0N1MHoxbTUyVSIsIm9yaWdpbiI6Imh0dHBzOi8vZXhhbXBsZS5sb2NhbGhvc3QiLCJjcm9zc09yaWdpbiI6ZmFsc2V9",
+    signature: "MEUCIEjkYgYZP9Wd_eei7NbJeMbrH62CGowJdNkL5aRctGB2AiEAzUsa4dpiI0EosUY1XsNZQQfHGXmM1IcsJ2cM8G1lpos",
+};
+
+const RS256: Fixture = Fixture {
+    algorithm: COSE_ALG_RS256,
+    credential_public_key: "pAEDAzkBACBZAQDSR34_GrIbYjavxn4G_a2vH-gTdShimVUwaajU9RLtdM2HUPQi0Lgc-9-jkfXbmcmBgPOUGYLLdr0PnP1iA6rI88Y0oysm7TrBKII3zQiBlrtvqKZV9LKwnF7P2oQMkU7LRwGTEMFek7lepJVZ4cerPZPOodzqzpS4AhKByRD8_Qwc1PgQFT7cGS1im0rUe5K7tXGLL0gKANsNJrjh2rjzYU6peFQ61L5FG5dy6wGL3BFrwjbeEPeMRpZDQwVtu1ykYzdlnJUTdjH5FY5gfQ_5H48ItlxqN3ik7nWNkdaoFvOTFDmJuyOdDR8ho6osf6yncY49L5yR5B4-_HUyQt2jIUMBAAE",
+    authenticator_data: "y9GqwTRaMpzVDbXq1dyEAXVOxrou08k22ggRC45MKNgFAAAAAA",
+    client_data_json: "eyJ0eXBlIjoid2ViYXV0aG4uZ2V0IiwiY2hhbGxlbmdlIjoibVJDY05WOGhRZW9pMWtQNUdtYmJKbExOTllOWHZRcnJwT3hPdWNDdUhwdyIsIm9yaWdpbiI6Imh0dHBzOi8vZXhhbXBsZS5sb2NhbGhvc3QiLCJjcm9zc09yaWdpbiI6ZmFsc2V9",
+    signature: "xvM5O7Ht4lfKNGlw8-zEyRZ3OAEaYcm-HA2uwteCOQX6rU-5fyIL_RF7tmNjYikZQpuwcbspYwkye1oxerl-Q4CSlkNgAKT-35MPNd9sFG2HAIbBQ2Mi6322D3bjofHqM9Hf_W0ZoTtMsoJYA3C_omSME26SKgSI8BE4fTP4S79WlKab8AIbWYrL8l6Yj4IWOnff4tjp_6JlzdPYApgS5ETwW_opxjU0QxQOnS-hjIulXPLCZIbPPPhYNS9oiKrVL46OiF7rbHuN-NKlWqp7tdGZm98uX1jqfnt5UIfh0NPjzqrMSNVEAQ6v3iLT0m8xRPHsuBte4nCqSxvz1S29YA",
+};
+
+const FIXTURES: [&Fixture; 3] = [&ED25519, &ES256, &RS256];
+
+/// Serialized credential as forwarded by the TS layer
+fn credential(fixture: &Fixture) -> Value {
+    json!({
+        "id": "cred",
+        "rawId": "cred",
+        "type": "public-key",
+        "authenticatorAttachment": "platform",
+        "response": {
+            "clientDataJSON": fixture.client_data_json,
+            "authenticatorData": fixture.authenticator_data,
+            "signature": fixture.signature,
+            "userHandle": null,
+            "credentialPublicKey": fixture.credential_public_key
+        },
+        "clientExtensionResults": { "prf": { "results": { "first": "AAAA" } } }
+    })
+}
+
+/// The fixture's credential with its COSE key bytes edited by `edit`
+fn with_cose_key(fixture: &Fixture, edit: impl FnOnce(&mut Vec<u8>)) -> Value {
+    let mut cose_key = base64_url_decode(fixture.credential_public_key).unwrap();
+    edit(&mut cose_key);
+    let mut credential = credential(fixture);
+    credential["response"]["credentialPublicKey"] = json!(base64_url_encode(&cose_key));
+    credential
+}
+
+/// A "none" attestation for the fixture's credential, as registration receives it
+fn attestation_object(fixture: &Fixture) -> String {
+    let mut auth_data = base64_url_decode(fixture.authenticator_data).unwrap();
+    auth_data[32] |= 0x40; // AT
+    auth_data.extend_from_slice(&[0u8; 16]); // AAGUID
+    auth_data.extend_from_slice(&4u16.to_be_bytes());
+    auth_data.extend_from_slice(b"cred");
+    auth_data.extend_from_slice(&base64_url_decode(fixture.credential_public_key).unwrap());
+    let mut bytes = Vec::new();
+    ciborium::into_writer(
+        &CborValue::Map(vec![
+            (
+                CborValue::Text("fmt".to_string()),
+                CborValue::Text("none".to_string()),
+            ),
+            (
+                CborValue::Text("attStmt".to_string()),
+                CborValue::Map(Vec::new()),
+            ),
+            (
+                CborValue::Text("authData".to_string()),
+                CborValue::Bytes(auth_data),
+            ),
+        ]),
+        &mut bytes,
+    )
+    .unwrap();
+    base64_url_encode(&bytes)
+}
+
+fn assert_invalid(credential: &Value) {
+    let err = verify_assertion_locally(credential).unwrap_err();
+    assert!(err.starts_with("AssertionSignatureInvalid: "), "{}", err);
+}
+
+#[test]
+fn test_fixtures_verify_for_each_algorithm() {
+    for fixture in FIXTURES {
+        assert_eq!(
+            verify_assertion_locally(&credential(fixture)),
+            Ok(LocalAssertionCheck::Verified),
+            "algorithm {}",
+            fixture.algorithm
+        );
+    }
+}
+
+#[test]
+fn test_tampered_assertions_fail_locally() {
+    for fixture in FIXTURES {
+        // Replayed with a bumped sign count
+        let mut tampered = credential(fixture);
+        let mut authenticator_data = base64_url_decode(fixture.authenticator_data).unwrap();
+        *authenticator_data.last_mut().unwrap() ^= 0x01;
+        tampered["response"]["authenticatorData"] = json!(base64_url_encode(&authenticator_data));
+        assert_invalid(&tampered);
+
+        // Signed over a different clientDataJSON
+        let other = if fixture.algorithm == COSE_ALG_EDDSA {
+            &ES256
+        } else {
+            &ED25519
+        };
+        let mut tampered = credential(fixture);
+        tampered["response"]["clientDataJSON"] = json!(other.client_data_json);
+        assert_invalid(&tampered);
+    }
+
+    // A key that does not match its declared algorithm: the Ed25519 key declaring ES256
+    let mismatched = with_cose_key(&ED25519, |key| {
+        assert_eq!(key[4], 0x27); // alg -8
+        key[4] = 0x26;
+    });
+    assert_invalid(&mismatched);
+
+    // Another credential's key
+    let mut other_key = credential(&ED25519);
+    other_key["response"]["credentialPublicKey"] = json!(ES256.credential_public_key);
+    assert_invalid(&other_key);
+
+    // A key that is not COSE, or names no algorithm
+    let mut not_cose = credential(&ES256);
+    not_cose["response"]["credentialPublicKey"] = json!("MFkwEwYHKoZIzj0CAQ");
+    assert_invalid(&not_cose);
+    let without_alg = with_cose_key(&ES256, |key| {
+        assert_eq!(&key[..5], &[0xa5, 0x01, 0x02, 0x03, 0x26]);
+        key.splice(0..5, [0xa4, 0x01, 0x02]);
+    });
+    assert_invalid(&without_alg);
+}
+
+#[test]
+fn test_key_stored_at_registration_verifies_later_assertions() {
+    // WebAuthnManager stores what the attestation carries; assertions are checked against it
+    for fixture in FIXTURES {
+        let stored =
+            extract_cose_public_key_from_attestation(&attestation_object(fixture)).unwrap();
+        let mut credential = credential(fixture);
+        credential["response"]["credentialPublicKey"] = json!(base64_url_encode(&stored));
+        assert_eq!(
+            verify_assertion_locally(&credential),
+            Ok(LocalAssertionCheck::Verified),
+            "algorithm {}",
+            fixture.algorithm
+        );
+    }
+}
+
+#[test]
+fn test_assertion_without_forwarded_key_is_left_to_the_contract() {
+    let mut credential_without_key = credential(&ES256);
+    let response = credential_without_key["response"].as_object_mut().unwrap();
+    response.remove("credentialPublicKey");
+    assert_eq!(
+        verify_assertion_locally(&credential_without_key),
+        Ok(LocalAssertionCheck::NoPublicKey)
+    );
+
+    // Even a broken signature is not judged locally without a key
+    credential_without_key["response"]["signature"] = json!("AAAA");
+    assert_eq!(
+        verify_assertion_locally(&credential_without_key),
+        Ok(LocalAssertionCheck::NoPublicKey)
+    );
+
+    // ES384 (-35) cannot be verified in the worker
+    let es384 = with_cose_key(&ES256, |key| {
+        key.splice(4..5, [0x38, 0x22]);
+    });
+    assert_eq!(
+        verify_assertion_locally(&es384),
+        Ok(LocalAssertionCheck::UnsupportedAlgorithm(-35))
+    );
+}
@@ -0,0 +1,202 @@
+use serde_json::json;
+
+use crate::background_refresh::*;
+use crate::clock::{ManualClock, SharedClock};
+use crate::dispatch_signer_message;
+use crate::error::RpcErrorKind;
+use crate::peer_channel::{self, PeerSession};
+use crate::rpc_client::MockRpcClient;
+use crate::state;
+use crate::tests::block_on;
+use crate::types::worker_messages::{SignerWorkerMessage, WorkerRequestType, WorkerResponseType};
+
+const PUBLIC_KEY: &str = "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp";
+
+fn accounts() -> Vec<RefreshAccount> {
+    ["alice.testnet", "bob.testnet"]
+        .iter()
+        .map(|account| RefreshAccount {
+            near_account_id: account.to_string(),
+            public_key: PUBLIC_KEY.to_string(),
+        })
+        .collect()
+}
+
+/// A fresh worker with an unlocked VRF session on the peer port
+fn setup() -> ManualClock {
+    let clock = ManualClock::new(1_000_000.0);
+    state::set_clock(SharedClock::new(clock.clone()));
+    state::wipe_all_state();
+    resume();
+    peer_channel::connect_peer_session(PeerSession {
+        expected_vrf_public_key: "AQIDBAUGBwgJCgsMDQ4PEBESExQVFhcYGRobHB0eHyA".to_string(),
+        rp_id: "example.localhost".to_string(),
+    })
+    .unwrap();
+    clock
+}
+
+fn healthy_rpc() -> MockRpcClient {
+    MockRpcClient::new()
+        .answer("query/view_access_key", Ok(json!({ "nonce": 42 })))
+        .answer(
+            "block",
+            Ok(json!({ "header": { "height": 100, "hash": "11111111111111111111111111111111" } })),
+        )
+}
+
+fn offline_rpc() -> MockRpcClient {
+    MockRpcClient::new()
+        .answer(
+            "query/view_access_key",
+            Err(RpcErrorKind::Unreachable("offline".to_string())),
+        )
+        .answer(
+            "block",
+            Err(RpcErrorKind::Unreachable("offline".to_string())),
+        )
+}
+
+#[test]
+fn test_interval_is_clamped_and_failures_back_off() {
+    assert_eq!(clamp_interval_ms(0), 2_000);
+    assert_eq!(clamp_interval_ms(1_999), 2_000);
+    assert_eq!(clamp_interval_ms(15_000), 15_000);
+
+    assert_eq!(backoff_delay_ms(2_000, 0), 2_000);
+    assert_eq!(backoff_delay_ms(2_000, 1), 4_000);
+    assert_eq!(backoff_delay_ms(2_000, 3), 16_000);
+    assert_eq!(backoff_delay_ms(2_000, 20), 300_000);
+    assert_eq!(backoff_delay_ms(2_000, u32::MAX), 300_000);
+    // An interval above the cap is never shortened
+    assert_eq!(backoff_delay_ms(600_000, 2), 600_000);
+}
+
+#[test]
+fn test_start_validates_and_replaces_the_running_refresh() {
+    setup();
+    assert!(start("", 5_000, accounts()).is_err());
+    assert!(start("https://rpc.testnet.near.org", 5_000, vec![]).is_err());
+    let mut unnamed = accounts();
+    unnamed[1].public_key.clear();
+    assert!(start("https://rpc.testnet.near.org", 5_000, unnamed).is_err());
+    assert!(!status().running);
+
+    let first = start("https://rpc.testnet.near.org", 500, accounts()).unwrap();
+    let status = status();
+    assert!(status.running);
+    assert_eq!(status.interval_ms, 2_000);
+    assert_eq!(status.accounts, accounts());
+    assert_eq!(status.paused, None);
+
+    let second = start("https://rpc.testnet.near.org", 5_000, accounts()).unwrap();
+    assert_ne!(first, second);
+    assert_eq!(block_on(run_tick(&healthy_rpc(), first)), None);
+    assert_eq!(block_on(run_tick(&healthy_rpc(), second)), Some(5_000));
+}
+
+#[test]
+fn test_tick_caches_the_block_and_nonces() {
+    let clock = setup();
+    let generation = start("https://rpc.testnet.near.org", 5_000, accounts()).unwrap();
+    clock.advance(10.0);
+    let rpc = healthy_rpc();
+    assert_eq!(block_on(run_tick(&rpc, generation)), Some(5_000));
+    assert_eq!(rpc.calls_to("query/view_access_key").len(), 2);
+
+    let status = status();
+    assert_eq!(status.consecutive_failures, 0);
+    assert_eq!(status.last_success_at_ms, Some(1_000_010.0));
+    assert_eq!(status.next_refresh_at_ms, Some(1_005_010.0));
+    let block = status.block.unwrap();
+    assert_eq!(block.height, "100");
+    assert_eq!(block.hash, "11111111111111111111111111111111");
+    assert_eq!(status.nonces.len(), 2);
+    assert_eq!(status.nonces[1].near_account_id, "bob.testnet");
+    assert_eq!(status.nonces[1].next_nonce, "43");
+}
+
+#[test]
+fn test_failures_back_off_and_surface_in_the_status() {
+    setup();
+    let generation = start("https://rpc.testnet.near.org", 2_000, accounts()).unwrap();
+    let rpc = offline_rpc();
+    assert_eq!(block_on(run_tick(&rpc, generation)), Some(4_000));
+    assert_eq!(block_on(run_tick(&rpc, generation)), Some(8_000));
+    // A failed tick stops at the first account
+    assert_eq!(rpc.calls_to("query/view_access_key").len(), 2);
+
+    let failing = status();
+    assert_eq!(failing.consecutive_failures, 2);
+    assert_eq!(failing.last_error_code.as_deref(), Some("RpcUnreachable"));
+    assert!(failing.last_error.unwrap().contains("offline"));
+    assert_eq!(failing.last_success_at_ms, None);
+
+    assert_eq!(block_on(run_tick(&healthy_rpc(), generation)), Some(2_000));
+    let recovered = status();
+    assert_eq!(recovered.consecutive_failures, 0);
+    // The last error stays on record after recovering
+    assert_eq!(recovered.last_error_code.as_deref(), Some("RpcUnreachable"));
+    assert!(recovered.block.is_some());
+}
+
+#[test]
+fn test_ticks_pause_while_suspended_or_locked() {
+    setup();
+    let generation = start("https://rpc.testnet.near.org", 2_000, accounts()).unwrap();
+
+    assert!(!suspend());
+    assert_eq!(status().paused.as_deref(), Some("suspended"));
+    let rpc = healthy_rpc();
+    assert_eq!(block_on(run_tick(&rpc, generation)), Some(2_000));
+    assert!(rpc.calls.borrow().is_empty());
+    assert!(status().block.is_none());
+
+    assert!(resume());
+    peer_channel::disconnect_peer_session();
+    assert_eq!(status().paused.as_deref(), Some("noUnlockedKeypair"));
+    assert_eq!(block_on(run_tick(&rpc, generation)), Some(2_000));
+    assert!(rpc.calls.borrow().is_empty());
+}
+
+#[test]
+fn test_messages_start_stop_and_wipe() {
+    setup();
+    let dispatch = |msg_type: WorkerRequestType, payload: serde_json::Value| {
+        block_on(dispatch_signer_message(SignerWorkerMessage {
+            msg_type: msg_type as u32,
+            payload,
+            request_id: None,
+        }))
+        .unwrap()
+    };
+
+    let response = dispatch(
+        WorkerRequestType::StartBackgroundRefresh,
+        json!({
+            "nearRpcUrl": "https://rpc.testnet.near.org",
+            "intervalMs": 100,
+            "accounts": [{ "nearAccountId": "alice.testnet", "publicKey": PUBLIC_KEY }]
+        }),
+    );
+    assert_eq!(
+        WorkerResponseType::from(response.response_type),
+        WorkerResponseType::StartBackgroundRefreshSuccess
+    );
+    assert_eq!(response.payload["intervalMs"], json!(2_000));
+
+    let response = dispatch(WorkerRequestType::SuspendHint, json!({}));
+    assert_eq!(response.payload["wasSuspended"], json!(false));
+    let response = dispatch(WorkerRequestType::BackgroundRefreshStatus, json!({}));
+    assert_eq!(response.payload["paused"], json!("suspended"));
+    let response = dispatch(WorkerRequestType::ResumeHint, json!({}));
+    assert_eq!(response.payload["wasSuspended"], json!(true));
+
+    let response = dispatch(WorkerRequestType::StopBackgroundRefresh, json!({}));
+    assert_eq!(response.payload["wasRunning"], json!(true));
+    assert!(!status().running);
+
+    start("https://rpc.testnet.near.org", 2_000, accounts()).unwrap();
+    assert!(state::wipe_all_state().stopped_background_refresh);
+    assert!(!status().running);
+}
@@ -0,0 +1,188 @@
+use crate::batch_preview::{
+    canonical_batch_digest, deduplicate_transactions, prepare_batch, BatchPreparation,
+};
+use crate::handlers::handle_summarize_transactions::{
+    handle_summarize_transactions, SummarizeTransactionsRequest,
+};
+use crate::handlers::{
+    handle_sign_transactions_with_actions, SignTransactionsWithActionsRequest, TransactionPayload,
+};
+use crate::strict_parsing::check_unknown_fields;
+use crate::tests::block_on;
+use crate::types::worker_messages::WorkerRequestType;
+use serde_json::{json, Value};
+
+fn tx(receiver_id: &str, actions: &str) -> TransactionPayload {
+    TransactionPayload {
+        near_account_id: "alice.testnet".to_string(),
+        receiver_id: receiver_id.to_string(),
+        actions: actions.to_string(),
+        depends_on: Vec::new(),
+    }
+}
+
+fn transfer(deposit: &str) -> String {
+    json!([{ "action_type": "Transfer", "deposit": deposit }]).to_string()
+}
+
+fn sign_request(txs: &[TransactionPayload], extra: Value) -> SignTransactionsWithActionsRequest {
+    let mut payload = json!({
+        "rpcCall": {
+            "contractId": "w3a-v1.testnet",
+            "nearRpcUrl": "https://rpc.testnet.near.org",
+            "nearAccountId": "alice.testnet"
+        },
+        "decryption": { "encryptedPrivateKeyData": "AAAA", "encryptedPrivateKeyIv": "BBBB" },
+        "txSigningRequests": txs,
+        "confirmationConfig": null
+    });
+    payload
+        .as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    serde_json::from_value(payload).unwrap()
+}
+
+#[test]
+fn test_duplicates_match_on_canonical_digest() {
+    let mut txs = vec![
+        tx("bob.testnet", &transfer("1")),
+        // Same transfer, formatted differently
+        tx(
+            "bob.testnet",
+            r#"[ { "deposit": "1", "action_type": "Transfer" } ]"#,
+        ),
+        tx("carol.testnet", &transfer("1")),
+        tx("bob.testnet", &transfer("2")),
+        // Actions that do not parse are never duplicates
+        tx("bob.testnet", "not json"),
+        tx("bob.testnet", "not json"),
+        tx("carol.testnet", &transfer("1")),
+    ];
+    assert_eq!(deduplicate_transactions(&mut txs), vec![1, 6]);
+    let receivers: Vec<&str> = txs.iter().map(|tx| tx.receiver_id.as_str()).collect();
+    assert_eq!(
+        receivers,
+        vec![
+            "bob.testnet",
+            "carol.testnet",
+            "bob.testnet",
+            "bob.testnet",
+            "bob.testnet"
+        ]
+    );
+}
+
+#[test]
+fn test_canonical_digest_follows_transaction_order() {
+    let first = tx("bob.testnet", &transfer("1"));
+    let second = tx("carol.testnet", &transfer("2"));
+    let digest = canonical_batch_digest(&[first.clone(), second.clone()]).unwrap();
+    let reformatted = tx(
+        "bob.testnet",
+        r#"[{"deposit":"1","action_type":"Transfer"}]"#,
+    );
+    assert_eq!(
+        canonical_batch_digest(&[reformatted, second.clone()]).unwrap(),
+        digest
+    );
+    assert_ne!(canonical_batch_digest(&[second, first]).unwrap(), digest);
+    assert!(canonical_batch_digest(&[tx("bob.testnet", "not json")]).is_err());
+}
+
+#[test]
+fn test_summarized_digest_verifies_the_deduplicated_batch() {
+    let txs = vec![
+        tx("bob.testnet", &transfer("1")),
+        tx("bob.testnet", &transfer("1")),
+        tx("carol.testnet", &transfer("2")),
+    ];
+    let summary = block_on(handle_summarize_transactions(
+        SummarizeTransactionsRequest {
+            tx_signing_requests: txs.clone(),
+            deduplicate: true,
+        },
+    ))
+    .unwrap();
+    assert_eq!(summary.transaction_count, 2);
+    assert_eq!(summary.removed_duplicate_indexes, vec![1]);
+
+    let mut request = sign_request(
+        &txs,
+        json!({ "deduplicate": true, "previewDigest": summary.preview_digest }),
+    );
+    assert_eq!(
+        prepare_batch(&mut request),
+        Ok(Some(BatchPreparation {
+            removed_duplicate_indexes: Some(vec![1]),
+            verified_preview_digest: Some(summary.preview_digest.clone()),
+        }))
+    );
+    assert_eq!(request.tx_signing_requests.len(), 2);
+
+    // Without deduplicate the batch is the three transactions, not the one previewed
+    let mut request = sign_request(&txs, json!({ "previewDigest": summary.preview_digest }));
+    let (code, message) = prepare_batch(&mut request).unwrap_err();
+    assert_eq!(code.as_str(), "PayloadChangedSincePreview");
+    assert!(message.contains(&summary.preview_digest), "{}", message);
+
+    // A request setting neither is left alone
+    let mut request = sign_request(&txs, json!({}));
+    assert_eq!(prepare_batch(&mut request), Ok(None));
+    assert_eq!(request.tx_signing_requests.len(), 3);
+}
+
+#[test]
+fn test_changed_batch_fails_signing_request_before_confirmation() {
+    let previewed = vec![tx("bob.testnet", &transfer("1"))];
+    let summary = block_on(handle_summarize_transactions(
+        SummarizeTransactionsRequest {
+            tx_signing_requests: previewed,
+            deduplicate: false,
+        },
+    ))
+    .unwrap();
+    assert!(summary.removed_duplicate_indexes.is_empty());
+
+    let request = sign_request(
+        &[tx("bob.testnet", &transfer("1000"))],
+        json!({ "previewDigest": summary.preview_digest }),
+    );
+    let result = block_on(handle_sign_transactions_with_actions(request)).unwrap();
+    assert!(!result.success);
+    assert_eq!(
+        result.error_code.as_deref(),
+        Some("PayloadChangedSincePreview")
+    );
+    assert_eq!(result.error_category.as_deref(), Some("Policy"));
+    assert_eq!(result.retriable, Some(false));
+}
+
+#[test]
+fn test_summarize_transactions_refuses_unusable_batches() {
+    let summarize = |txs: Vec<TransactionPayload>| {
+        block_on(handle_summarize_transactions(
+            SummarizeTransactionsRequest {
+                tx_signing_requests: txs,
+                deduplicate: true,
+            },
+        ))
+    };
+    assert!(summarize(vec![]).is_err());
+    let err = summarize(vec![tx("bob.testnet", "not json")]).unwrap_err();
+    assert!(err.contains("transaction 0"), "{}", err);
+}
+
+#[test]
+fn test_strict_parsing_accepts_batch_preview_fields() {
+    let payload = json!({
+        "txSigningRequests": [],
+        "deduplicate": true,
+        "previewDigest": "digest",
+        "workerPolicy": { "strictParsing": true }
+    });
+    assert_eq!(
+        check_unknown_fields(WorkerRequestType::SignTransactionsWithActions, &payload),
+        Ok(())
+    );
+}
@@ -0,0 +1,428 @@
+use crate::blob_migration::StoredBlobKind;
+use crate::config::{
+    NEAR_KEY_BLOB_FORMAT_KEY_CHECK, NEAR_KEY_BLOB_FORMAT_LEGACY, VRF_KEYPAIR_BLOB_VERSION,
+    VRF_KEYPAIR_BLOB_VERSION_LEGACY, VRF_KEYPAIR_HKDF_INFO,
+};
+use crate::crypto::{
+    decrypt_private_key_with_prf, derive_and_encrypt_keypair_from_dual_prf,
+    derive_chacha20_key_from_prf, split_key_check_header,
+};
+use crate::dispatch_signer_message;
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::handlers::handle_migrate_encrypted_blobs::{
+    handle_migrate_encrypted_blobs, BlobMigrationReport, BlobMigrationStatus,
+    MigrateEncryptedBlobsRequest, MigrateEncryptedBlobsResult, StoredEncryptedBlob,
+};
+use crate::tests::block_on;
+use crate::types::worker_messages::{SignerWorkerMessage, WorkerResponseType};
+use crate::types::DualPrfOutputs;
+use crate::vrf_blob_meta::VrfKeypairBlobMeta;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use serde_json::{json, Value};
+
+const ACCOUNT_ID: &str = "alice.testnet";
+const CHACHA20_PRF_OUTPUT: &str = "Y2hhY2hhMjAtcHJmLW91dHB1dA";
+const MIGRATE_ENCRYPTED_BLOBS: u32 = 43;
+const PRIVATE_KEY: &str =
+    "ed25519:3D4YudUahN1nawWogh8pAKSj92sUNMdbZGjn7kERKzYoTy8tnFQuwoGUC51DowKqorvkr2pytJSnwuSbsNVfqygr";
+
+fn near_key_record(encrypted_data: String, iv: String) -> Value {
+    json!({
+        "nearAccountId": ACCOUNT_ID,
+        "deviceNumber": 2,
+        "encryptedData": encrypted_data,
+        "iv": iv,
+        "timestamp": 1726000000000u64
+    })
+}
+
+/// Key record as written before the key-check header: bare ChaCha20Poly1305 ciphertext
+fn legacy_near_key() -> Value {
+    use chacha20poly1305::aead::{Aead, KeyInit};
+    let key = derive_chacha20_key_from_prf(CHACHA20_PRF_OUTPUT, ACCOUNT_ID).unwrap();
+    let cipher = chacha20poly1305::ChaCha20Poly1305::new(chacha20poly1305::Key::from_slice(&key));
+    let nonce = [7u8; 12];
+    let ciphertext = cipher
+        .encrypt(
+            chacha20poly1305::Nonce::from_slice(&nonce),
+            PRIVATE_KEY.as_bytes(),
+        )
+        .unwrap();
+    near_key_record(base64_url_encode(&ciphertext), base64_url_encode(&nonce))
+}
+
+fn current_near_key() -> Value {
+    let dual_prf = DualPrfOutputs {
+        chacha20_prf_output_base64: CHACHA20_PRF_OUTPUT.to_string(),
+        ed25519_prf_output_base64: "ZWQyNTUxOS1wcmYtb3V0cHV0".to_string(),
+    };
+    let (_, encrypted) = derive_and_encrypt_keypair_from_dual_prf(&dual_prf, ACCOUNT_ID).unwrap();
+    near_key_record(
+        encrypted.encrypted_near_key_data_b64u,
+        encrypted.chacha20_nonce_b64u,
+    )
+}
+
+/// Stands in for the VRF worker's bincode keypair data
+const VRF_KEYPAIR_DATA: &[u8] = b"bincode-serialized VRFKeypairData";
+
+fn vrf_keypair_meta() -> VrfKeypairBlobMeta {
+    VrfKeypairBlobMeta {
+        account_id: ACCOUNT_ID.to_string(),
+        credential_id_b64u: Some("Y3JlZGVudGlhbC1pZA".to_string()),
+        device_number: Some(2),
+        created_at_ms: 1726000000000.0,
+        derivation_version: 1,
+    }
+}
+
+/// The VRF worker's cipher for the keypair under CHACHA20_PRF_OUTPUT
+fn vrf_keypair_cipher() -> chacha20poly1305::ChaCha20Poly1305 {
+    let prf_output = base64_url_decode(CHACHA20_PRF_OUTPUT).unwrap();
+    let mut key = [0u8; 32];
+    hkdf::Hkdf::<sha2::Sha256>::new(None, &prf_output)
+        .expand(VRF_KEYPAIR_HKDF_INFO, &mut key)
+        .unwrap();
+    chacha20poly1305::ChaCha20Poly1305::new(chacha20poly1305::Key::from_slice(&key))
+}
+
+/// VRF keypair as written before its metadata: no associated data, snake_case fields
+fn legacy_vrf_keypair() -> Value {
+    let nonce = [4u8; 12];
+    let ciphertext = vrf_keypair_cipher()
+        .encrypt(
+            chacha20poly1305::Nonce::from_slice(&nonce),
+            VRF_KEYPAIR_DATA,
+        )
+        .unwrap();
+    json!({
+        "encrypted_vrf_data_b64u": base64_url_encode(&ciphertext),
+        "chacha20_nonce_b64u": base64_url_encode(&nonce)
+    })
+}
+
+fn current_vrf_keypair() -> Value {
+    let nonce = [5u8; 12];
+    let ciphertext = vrf_keypair_cipher()
+        .encrypt(
+            chacha20poly1305::Nonce::from_slice(&nonce),
+            Payload {
+                msg: VRF_KEYPAIR_DATA,
+                aad: &vrf_keypair_meta().aad(),
+            },
+        )
+        .unwrap();
+    json!({
+        "encryptedVrfDataB64u": base64_url_encode(&ciphertext),
+        "chacha20NonceB64u": base64_url_encode(&nonce),
+        "meta": vrf_keypair_meta()
+    })
+}
+
+/// A stored VRF keypair from a release before its metadata (not decryptable in these tests)
+fn vrf_keypair() -> Value {
+    let user: Value = serde_json::from_str(include_str!(
+        "migration_fixtures/user_snake_case_vrf_keypair.json"
+    ))
+    .unwrap();
+    user["encryptedVrfKeypair"].clone()
+}
+
+fn session_snapshot(version: u32) -> Value {
+    json!({
+        "version": version,
+        "snapshotId": "c25hcHNob3QtaWQtMDAwMQ",
+        "epoch": 0,
+        "exportedAtMs": 1726000000000.0,
+        "nonceB64u": base64_url_encode(&[1u8; 12]),
+        "ciphertextB64u": base64_url_encode(&[2u8; 48])
+    })
+}
+
+fn stored(kind: StoredBlobKind, blob: Value) -> StoredEncryptedBlob {
+    StoredEncryptedBlob {
+        blob_id: None,
+        kind,
+        blob,
+        vrf_keypair_meta: None,
+    }
+}
+
+fn stored_legacy_vrf_keypair() -> StoredEncryptedBlob {
+    StoredEncryptedBlob {
+        vrf_keypair_meta: Some(vrf_keypair_meta()),
+        ..stored(StoredBlobKind::VrfKeypair, legacy_vrf_keypair())
+    }
+}
+
+fn migrate(prf_output: &str, blobs: Vec<StoredEncryptedBlob>) -> MigrateEncryptedBlobsResult {
+    block_on(handle_migrate_encrypted_blobs(
+        MigrateEncryptedBlobsRequest {
+            chacha20_prf_output: prf_output.to_string(),
+            blobs,
+        },
+    ))
+    .unwrap()
+}
+
+fn decrypts_to_private_key(record: &Value) -> bool {
+    let signing_key = decrypt_private_key_with_prf(
+        ACCOUNT_ID,
+        CHACHA20_PRF_OUTPUT,
+        record["encryptedData"].as_str().unwrap(),
+        record["iv"].as_str().unwrap(),
+    )
+    .unwrap();
+    let seed = &bs58::decode(PRIVATE_KEY.strip_prefix("ed25519:").unwrap())
+        .into_vec()
+        .unwrap()[..32];
+    signing_key.to_bytes() == seed
+}
+
+#[test]
+fn test_legacy_near_key_is_reencrypted_with_the_key_check_header() {
+    let legacy = legacy_near_key();
+    let result = migrate(
+        CHACHA20_PRF_OUTPUT,
+        vec![StoredEncryptedBlob {
+            blob_id: Some("alice.testnet#2".to_string()),
+            ..stored(StoredBlobKind::NearKey, legacy.clone())
+        }],
+    );
+    assert_eq!(
+        (result.migrated, result.already_current, result.failed),
+        (1, 0, 0)
+    );
+    let report = &result.reports[0];
+    assert_eq!(report.status, BlobMigrationStatus::Migrated);
+    assert_eq!(report.blob_id.as_deref(), Some("alice.testnet#2"));
+    assert_eq!(report.from_version, Some(NEAR_KEY_BLOB_FORMAT_LEGACY));
+    assert_eq!(report.to_version, Some(NEAR_KEY_BLOB_FORMAT_KEY_CHECK));
+    assert_eq!(report.error, None);
+
+    let migrated = &report.blob;
+    assert_ne!(migrated["encryptedData"], legacy["encryptedData"]);
+    assert_ne!(migrated["iv"], legacy["iv"]);
+    let encrypted_data = base64_url_decode(migrated["encryptedData"].as_str().unwrap()).unwrap();
+    assert!(split_key_check_header(&encrypted_data).is_some());
+    // The rest of the record is carried over; no CryptoKey was posted, so no outer wrap
+    assert_eq!(migrated["deviceNumber"], json!(2));
+    assert_eq!(migrated["timestamp"], legacy["timestamp"]);
+    assert!(migrated.get("outerWrap").is_none());
+    assert!(decrypts_to_private_key(migrated));
+    assert!(decrypts_to_private_key(&legacy));
+}
+
+#[test]
+fn test_legacy_vrf_keypair_is_reencrypted_with_its_metadata() {
+    let result = migrate(CHACHA20_PRF_OUTPUT, vec![stored_legacy_vrf_keypair()]);
+    let report = &result.reports[0];
+    assert_eq!(report.status, BlobMigrationStatus::Migrated);
+    assert_eq!(report.from_version, Some(VRF_KEYPAIR_BLOB_VERSION_LEGACY));
+    assert_eq!(report.to_version, Some(VRF_KEYPAIR_BLOB_VERSION));
+
+    // Written back in the current form, with the metadata the caller supplied
+    let migrated = &report.blob;
+    assert!(migrated.get("encrypted_vrf_data_b64u").is_none());
+    assert_eq!(migrated["meta"], json!(vrf_keypair_meta()));
+    assert_eq!(migrated["meta"]["accountId"], json!(ACCOUNT_ID));
+    assert_ne!(
+        migrated["chacha20NonceB64u"],
+        legacy_vrf_keypair()["chacha20_nonce_b64u"]
+    );
+
+    // The metadata is bound: the keypair decrypts with it, and not with an edited copy
+    let nonce = base64_url_decode(migrated["chacha20NonceB64u"].as_str().unwrap()).unwrap();
+    let ciphertext = base64_url_decode(migrated["encryptedVrfDataB64u"].as_str().unwrap()).unwrap();
+    let decrypt = |meta: &VrfKeypairBlobMeta| {
+        vrf_keypair_cipher().decrypt(
+            chacha20poly1305::Nonce::from_slice(&nonce),
+            Payload {
+                msg: &ciphertext,
+                aad: &meta.aad(),
+            },
+        )
+    };
+    assert_eq!(decrypt(&vrf_keypair_meta()).unwrap(), VRF_KEYPAIR_DATA);
+    let edited = VrfKeypairBlobMeta {
+        account_id: "mallory.testnet".to_string(),
+        ..vrf_keypair_meta()
+    };
+    assert!(decrypt(&edited).is_err());
+}
+
+#[test]
+fn test_legacy_vrf_keypair_needs_metadata() {
+    let result = migrate(
+        CHACHA20_PRF_OUTPUT,
+        vec![stored(StoredBlobKind::VrfKeypair, legacy_vrf_keypair())],
+    );
+    let report = &result.reports[0];
+    assert_eq!(report.status, BlobMigrationStatus::Failed);
+    assert_eq!(report.from_version, Some(VRF_KEYPAIR_BLOB_VERSION_LEGACY));
+    assert_eq!(report.blob, legacy_vrf_keypair());
+    assert!(report
+        .error
+        .as_ref()
+        .unwrap()
+        .contains("Missing vrfKeypairMeta"));
+
+    // Another credential's PRF output does not decrypt it
+    let result = migrate("b3RoZXItcHJmLW91dHB1dA", vec![stored_legacy_vrf_keypair()]);
+    assert_eq!(
+        result.reports[0].error_code.as_deref(),
+        Some("DecryptionFailed")
+    );
+}
+
+#[test]
+fn test_current_blobs_are_returned_untouched() {
+    let outer_wrapped: Value = serde_json::from_str(include_str!(
+        "migration_fixtures/near_key_current_outer_wrapped.json"
+    ))
+    .unwrap();
+    let blobs = vec![
+        stored(StoredBlobKind::NearKey, current_near_key()),
+        stored(StoredBlobKind::NearKey, outer_wrapped),
+        stored(StoredBlobKind::VrfKeypair, current_vrf_keypair()),
+        stored(StoredBlobKind::SessionSnapshot, session_snapshot(1)),
+    ];
+    // Nothing is decrypted, so even another credential's PRF output leaves them as they are
+    let result = migrate("b3RoZXItcHJmLW91dHB1dA", blobs.clone());
+    assert_eq!(
+        (result.migrated, result.already_current, result.failed),
+        (0, 4, 0)
+    );
+    for (report, blob) in result.reports.iter().zip(&blobs) {
+        assert_eq!(report.status, BlobMigrationStatus::AlreadyCurrent);
+        assert_eq!(report.from_version, report.to_version);
+        assert_eq!(report.blob, blob.blob);
+    }
+}
+
+#[test]
+fn test_migration_is_idempotent() {
+    let blobs = vec![
+        stored(StoredBlobKind::NearKey, legacy_near_key()),
+        stored(StoredBlobKind::NearKey, current_near_key()),
+        stored_legacy_vrf_keypair(),
+        stored(StoredBlobKind::VrfKeypair, current_vrf_keypair()),
+    ];
+    let first = migrate(CHACHA20_PRF_OUTPUT, blobs);
+    assert_eq!((first.migrated, first.already_current), (2, 2));
+
+    let again: Vec<StoredEncryptedBlob> = first
+        .reports
+        .iter()
+        .map(|report| stored(report.kind, report.blob.clone()))
+        .collect();
+    let second = migrate(CHACHA20_PRF_OUTPUT, again);
+    assert_eq!(
+        (second.migrated, second.already_current, second.failed),
+        (0, 4, 0)
+    );
+    for (first, second) in first.reports.iter().zip(&second.reports) {
+        assert_eq!(second.blob, first.blob);
+    }
+}
+
+#[test]
+fn test_failed_blobs_keep_their_original() {
+    let legacy = legacy_near_key();
+    let truncated = near_key_record(base64_url_encode(&[3u8; 8]), base64_url_encode(&[1u8; 12]));
+    let blobs = vec![
+        stored(StoredBlobKind::NearKey, legacy.clone()),
+        stored(
+            StoredBlobKind::NearKey,
+            json!({ "nearAccountId": ACCOUNT_ID }),
+        ),
+        stored(StoredBlobKind::NearKey, truncated.clone()),
+        stored(StoredBlobKind::SessionSnapshot, session_snapshot(2)),
+        stored(StoredBlobKind::VrfKeypair, vrf_keypair()),
+    ];
+    let wrong_credential = migrate("b3RoZXItcHJmLW91dHB1dA", blobs.clone());
+    assert_eq!(wrong_credential.failed, 5);
+    for (report, blob) in wrong_credential.reports.iter().zip(&blobs) {
+        assert_eq!(report.status, BlobMigrationStatus::Failed);
+        assert_eq!(report.blob, blob.blob);
+        assert_eq!(report.to_version, None);
+        assert!(report.error.is_some());
+    }
+    let summary = |report: &BlobMigrationReport| (report.from_version, report.error_code.clone());
+    assert_eq!(
+        wrong_credential
+            .reports
+            .iter()
+            .map(summary)
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                Some(NEAR_KEY_BLOB_FORMAT_LEGACY),
+                Some("DecryptionFailed".to_string())
+            ),
+            (None, None),
+            (None, Some("CorruptedCiphertext".to_string())),
+            (Some(2), None),
+            (Some(VRF_KEYPAIR_BLOB_VERSION_LEGACY), None),
+        ]
+    );
+    assert!(wrong_credential.reports[3]
+        .error
+        .as_ref()
+        .unwrap()
+        .contains("export a new one"));
+
+    // The failures do not hold back the rest of the batch
+    let result = migrate(CHACHA20_PRF_OUTPUT, blobs);
+    assert_eq!((result.migrated, result.failed), (1, 4));
+    assert!(decrypts_to_private_key(&result.reports[0].blob));
+    assert_eq!(result.reports[2].blob, truncated);
+}
+
+#[test]
+fn test_missing_prf_output_is_refused() {
+    for prf_output in ["", "not base64url!"] {
+        let error = block_on(handle_migrate_encrypted_blobs(
+            MigrateEncryptedBlobsRequest {
+                chacha20_prf_output: prf_output.to_string(),
+                blobs: vec![stored(StoredBlobKind::VrfKeypair, vrf_keypair())],
+            },
+        ))
+        .unwrap_err();
+        assert_eq!(error, "Missing or invalid PRF output");
+    }
+}
+
+#[test]
+fn test_migrate_encrypted_blobs_message() {
+    let response = block_on(dispatch_signer_message(SignerWorkerMessage {
+        msg_type: MIGRATE_ENCRYPTED_BLOBS,
+        payload: json!({
+            "chacha20PrfOutput": CHACHA20_PRF_OUTPUT,
+            "blobs": [
+                { "blobId": "vrf", "kind": "vrfKeypair", "blob": current_vrf_keypair() },
+                { "kind": "nearKey", "blob": legacy_near_key() },
+                {
+                    "kind": "vrfKeypair",
+                    "blob": legacy_vrf_keypair(),
+                    "vrfKeypairMeta": vrf_keypair_meta()
+                }
+            ]
+        }),
+        request_id: None,
+    }))
+    .unwrap();
+    assert_eq!(
+        WorkerResponseType::from(response.response_type),
+        WorkerResponseType::MigrateEncryptedBlobsSuccess
+    );
+    let reports = &response.payload["reports"];
+    assert_eq!(reports[0]["blobId"], json!("vrf"));
+    assert_eq!(reports[0]["kind"], json!("vrfKeypair"));
+    assert_eq!(reports[0]["status"], json!("alreadyCurrent"));
+    assert_eq!(reports[1]["status"], json!("migrated"));
+    assert_eq!(reports[1]["fromVersion"], json!(1));
+    assert_eq!(reports[1]["toVersion"], json!(2));
+    assert_eq!(reports[2]["status"], json!("migrated"));
+    assert_eq!(reports[2]["blob"]["meta"]["deviceNumber"], json!(2));
+}
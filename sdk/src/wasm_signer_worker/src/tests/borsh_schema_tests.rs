@@ -0,0 +1,242 @@
+use crate::handlers::handle_get_borsh_schemas::{
+    borsh_schema_json, handle_get_borsh_schemas, GetBorshSchemasRequest,
+};
+use crate::tests::block_on;
+use crate::types::near::*;
+use serde_json::{json, Value};
+
+// Minimal schema-driven borsh decoder: uses ONLY the exported JSON schema to walk the bytes.
+// Strings and byte arrays are left as arrays of u8 numbers; primitives wider than 8 bytes
+// are rendered as decimal strings.
+fn decode_with_schema(schema: &Value, declaration: &str, bytes: &mut &[u8]) -> Value {
+    let definition = &schema["definitions"][declaration];
+    match definition["kind"].as_str().expect("definition kind") {
+        "primitive" => {
+            let size = definition["size"].as_u64().unwrap() as usize;
+            let (head, tail) = bytes.split_at(size);
+            *bytes = tail;
+            let mut buf = [0u8; 16];
+            buf[..size].copy_from_slice(head);
+            let value = u128::from_le_bytes(buf);
+            if size > 8 {
+                json!(value.to_string())
+            } else {
+                json!(value as u64)
+            }
+        }
+        "sequence" => {
+            let width = definition["lengthWidth"].as_u64().unwrap() as usize;
+            let len = if width == 0 {
+                definition["lengthRange"][0].as_u64().unwrap() as usize
+            } else {
+                let (head, tail) = bytes.split_at(width);
+                *bytes = tail;
+                let mut buf = [0u8; 8];
+                buf[..width].copy_from_slice(head);
+                u64::from_le_bytes(buf) as usize
+            };
+            let elements = definition["elements"].as_str().unwrap();
+            Value::Array(
+                (0..len)
+                    .map(|_| decode_with_schema(schema, elements, bytes))
+                    .collect(),
+            )
+        }
+        "tuple" => Value::Array(
+            definition["elements"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|e| decode_with_schema(schema, e.as_str().unwrap(), bytes))
+                .collect(),
+        ),
+        "enum" => {
+            let tag_width = definition["tagWidth"].as_u64().unwrap() as usize;
+            let (head, tail) = bytes.split_at(tag_width);
+            *bytes = tail;
+            let mut buf = [0u8; 8];
+            buf[..tag_width].copy_from_slice(head);
+            let tag = i64::from_le_bytes(buf);
+            let variant = definition["variants"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|v| v["discriminant"].as_i64() == Some(tag))
+                .expect("unknown enum tag");
+            json!({
+                "variant": variant["name"],
+                "value": decode_with_schema(schema, variant["type"].as_str().unwrap(), bytes),
+            })
+        }
+        "struct" => {
+            let fields = definition["fields"].as_array().unwrap();
+            if fields.iter().all(|f| f.get("name").is_some()) && !fields.is_empty() {
+                let mut out = serde_json::Map::new();
+                for field in fields {
+                    out.insert(
+                        field["name"].as_str().unwrap().to_string(),
+                        decode_with_schema(schema, field["type"].as_str().unwrap(), bytes),
+                    );
+                }
+                Value::Object(out)
+            } else {
+                Value::Array(
+                    fields
+                        .iter()
+                        .map(|f| decode_with_schema(schema, f["type"].as_str().unwrap(), bytes))
+                        .collect(),
+                )
+            }
+        }
+        other => panic!("unsupported definition kind: {}", other),
+    }
+}
+
+fn bytes_to_string(value: &Value) -> String {
+    let bytes: Vec<u8> = value
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| b.as_u64().unwrap() as u8)
+        .collect();
+    String::from_utf8(bytes).unwrap()
+}
+
+fn fixture_transaction() -> Transaction {
+    let public_key = PublicKey::from_ed25519_bytes(&[7u8; 32]);
+    Transaction {
+        signer_id: AccountId("alice.testnet".to_string()),
+        public_key: public_key.clone(),
+        nonce: 42,
+        receiver_id: AccountId("contract.testnet".to_string()),
+        block_hash: CryptoHash::from_bytes([9u8; 32]),
+        actions: vec![
+            Action::CreateAccount,
+            Action::DeployContract { code: vec![0, 97, 115, 109] },
+            Action::FunctionCall(Box::new(FunctionCallAction {
+                method_name: "set_greeting".to_string(),
+                args: b"{\"greeting\":\"hi\"}".to_vec(),
+                gas: 30_000_000_000_000,
+                deposit: 1_000_000_000_000_000_000_000_000,
+            })),
+            Action::Transfer { deposit: 1 },
+            Action::Stake {
+                stake: 5,
+                public_key: public_key.clone(),
+            },
+            Action::AddKey {
+                public_key: public_key.clone(),
+                access_key: AccessKey {
+                    nonce: 0,
+                    permission: AccessKeyPermission::FunctionCall(FunctionCallPermission {
+                        allowance: Some(250),
+                        receiver_id: "contract.testnet".to_string(),
+                        method_names: vec!["set_greeting".to_string()],
+                    }),
+                },
+            },
+            Action::DeleteKey {
+                public_key: public_key.clone(),
+            },
+            Action::DeleteAccount {
+                beneficiary_id: AccountId("bob.testnet".to_string()),
+            },
+        ],
+    }
+}
+
+#[test]
+fn test_transaction_schema_decodes_fixture() {
+    let schema = borsh_schema_json::<Transaction>();
+    assert_eq!(schema["declaration"], "Transaction");
+
+    let tx = fixture_transaction();
+    let encoded = borsh::to_vec(&tx).unwrap();
+    let mut cursor: &[u8] = &encoded;
+    let decoded = decode_with_schema(&schema, "Transaction", &mut cursor);
+
+    assert!(cursor.is_empty(), "schema must consume every byte");
+    assert_eq!(bytes_to_string(&decoded["signer_id"][0]), "alice.testnet");
+    assert_eq!(bytes_to_string(&decoded["receiver_id"][0]), "contract.testnet");
+    assert_eq!(decoded["nonce"], 42);
+    assert_eq!(decoded["public_key"]["key_type"], 0);
+
+    let actions = decoded["actions"].as_array().unwrap();
+    let names: Vec<&str> = actions
+        .iter()
+        .map(|a| a["variant"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            "CreateAccount",
+            "DeployContract",
+            "FunctionCall",
+            "Transfer",
+            "Stake",
+            "AddKey",
+            "DeleteKey",
+            "DeleteAccount"
+        ]
+    );
+
+    let function_call = &actions[2]["value"][0];
+    assert_eq!(bytes_to_string(&function_call["method_name"]), "set_greeting");
+    assert_eq!(function_call["gas"], 30_000_000_000_000u64);
+    assert_eq!(function_call["deposit"], "1000000000000000000000000");
+
+    let permission = &actions[5]["value"]["access_key"]["permission"];
+    assert_eq!(permission["variant"], "FunctionCall");
+    assert_eq!(permission["value"][0]["allowance"]["variant"], "Some");
+    assert_eq!(permission["value"][0]["allowance"]["value"], "250");
+}
+
+#[test]
+fn test_delegate_action_and_nep413_schemas_decode() {
+    let delegate = DelegateAction {
+        sender_id: AccountId("alice.testnet".to_string()),
+        receiver_id: AccountId("contract.testnet".to_string()),
+        actions: vec![NonDelegateAction(Action::Transfer { deposit: 3 })],
+        nonce: 1,
+        max_block_height: 1000,
+        public_key: PublicKey::from_ed25519_bytes(&[1u8; 32]),
+    };
+    let schema = borsh_schema_json::<DelegateAction>();
+    let encoded = borsh::to_vec(&delegate).unwrap();
+    let mut cursor: &[u8] = &encoded;
+    let decoded = decode_with_schema(&schema, "DelegateAction", &mut cursor);
+    assert!(cursor.is_empty());
+    assert_eq!(decoded["max_block_height"], 1000);
+    assert_eq!(decoded["actions"][0][0]["variant"], "Transfer");
+
+    let payload = Nep413Payload {
+        message: "hello".to_string(),
+        recipient: "app.example".to_string(),
+        nonce: [5u8; 32],
+        state: None,
+    };
+    let schema = borsh_schema_json::<Nep413Payload>();
+    let encoded = borsh::to_vec(&payload).unwrap();
+    let mut cursor: &[u8] = &encoded;
+    let decoded = decode_with_schema(&schema, "Nep413Payload", &mut cursor);
+    assert!(cursor.is_empty());
+    assert_eq!(bytes_to_string(&decoded["message"]), "hello");
+    assert_eq!(decoded["nonce"].as_array().unwrap().len(), 32);
+    assert_eq!(decoded["state"]["variant"], "None");
+}
+
+#[test]
+fn test_get_borsh_schemas_handler_returns_json() {
+    let result = block_on(handle_get_borsh_schemas(GetBorshSchemasRequest {})).unwrap();
+    for schema_json in [
+        &result.transaction,
+        &result.signed_transaction,
+        &result.delegate_action,
+        &result.nep413_payload,
+    ] {
+        let schema: Value = serde_json::from_str(schema_json).unwrap();
+        assert!(schema["definitions"].is_object());
+    }
+    let signed: Value = serde_json::from_str(&result.signed_transaction).unwrap();
+    assert_eq!(signed["declaration"], "SignedTransaction");
+}
@@ -0,0 +1,201 @@
+use crate::actions::ActionParams;
+use crate::chunked_deploy::{plan_chunked_deploy, sha256_b58, StagingContractInterface};
+use crate::config::{MAX_DEPLOY_CHUNKS, MAX_DEPLOY_CHUNK_BYTES};
+use crate::encoders::base64_standard_decode;
+use crate::handlers::confirm_tx_details::{
+    compute_confirmation_intent_digest, compute_intent_digest_from_js_inputs,
+    confirmation_tx_signing_requests_json,
+};
+use crate::handlers::{handle_deploy_large_contract, DeployLargeContractRequest};
+use crate::tests::block_on;
+use serde_json::{json, Value};
+
+const SIGNER: &str = "alice.testnet";
+
+fn staging() -> StagingContractInterface {
+    StagingContractInterface {
+        receiver_id: None,
+        stage_method: "stage_code".to_string(),
+        deploy_method: "deploy_staged".to_string(),
+        stage_gas: None,
+        stage_deposit: Some("1000".to_string()),
+        deploy_gas: None,
+    }
+}
+
+/// A wasm header followed by `len - 4` bytes of filler
+fn wasm(len: usize) -> Vec<u8> {
+    let mut code = b"\0asm".to_vec();
+    code.extend((0..len - 4).map(|i| (i % 251) as u8));
+    code
+}
+
+fn function_call(actions: &str) -> (String, Value, String, String) {
+    let actions: Vec<ActionParams> = serde_json::from_str(actions).unwrap();
+    assert_eq!(actions.len(), 1);
+    match &actions[0] {
+        ActionParams::FunctionCall {
+            method_name,
+            args,
+            gas,
+            deposit,
+        } => (
+            method_name.clone(),
+            serde_json::from_str(args).unwrap(),
+            gas.clone(),
+            deposit.clone(),
+        ),
+        other => panic!("expected a FunctionCall, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_plan_stages_chunks_in_order_then_deploys() {
+    let code = wasm(2_500);
+    let plan = plan_chunked_deploy(SIGNER, &code, 1_000, &staging()).unwrap();
+    assert_eq!(plan.transactions.len(), 4);
+    assert_eq!(plan.manifest.step_count(), 4);
+    assert_eq!(plan.manifest.total_bytes, 2_500);
+    assert_eq!(plan.manifest.chunk_size, 1_000);
+
+    let mut staged = Vec::new();
+    for (index, tx) in plan.transactions[..3].iter().enumerate() {
+        assert_eq!(tx.near_account_id, SIGNER);
+        assert_eq!(tx.receiver_id, SIGNER);
+        let (method_name, args, gas, deposit) = function_call(&tx.actions);
+        assert_eq!(method_name, "stage_code");
+        assert_eq!(gas, "100000000000000");
+        assert_eq!(deposit, "1000");
+        assert_eq!(args["index"], json!(index));
+        assert_eq!(args["hash"], json!(plan.manifest.chunk_hashes[index]));
+
+        let chunk = base64_standard_decode(args["data"].as_str().unwrap()).unwrap();
+        assert_eq!(chunk.len(), if index < 2 { 1_000 } else { 500 });
+        staged.extend(chunk);
+    }
+    assert_eq!(staged, code);
+
+    let (method_name, args, gas, deposit) = function_call(&plan.transactions[3].actions);
+    assert_eq!(method_name, "deploy_staged");
+    assert_eq!(gas, "300000000000000");
+    assert_eq!(deposit, "0");
+    assert_eq!(
+        args,
+        json!({ "code_hash": plan.manifest.total_hash, "chunk_count": 3, "total_bytes": 2_500 })
+    );
+
+    // An explicit staging contract receives every call
+    let other = StagingContractInterface {
+        receiver_id: Some("stager.testnet".to_string()),
+        ..staging()
+    };
+    let plan = plan_chunked_deploy(SIGNER, &code, 1_000, &other).unwrap();
+    assert!(plan
+        .transactions
+        .iter()
+        .all(|tx| tx.receiver_id == "stager.testnet" && tx.near_account_id == SIGNER));
+}
+
+#[test]
+fn test_manifest_hashes_are_verifiable() {
+    let code = wasm(3_200_000);
+    let plan = plan_chunked_deploy(SIGNER, &code, 550_000, &staging()).unwrap();
+    let manifest = &plan.manifest;
+    assert_eq!(manifest.chunk_hashes.len(), 6);
+    assert_eq!(manifest.description(), "Deploy 3.2MB contract in 7 steps");
+
+    // Each chunk hash is the SHA-256 of that chunk; the total hash of the whole code
+    for (chunk, hash) in code.chunks(550_000).zip(&manifest.chunk_hashes) {
+        assert_eq!(&sha256_b58(chunk), hash);
+    }
+    assert_eq!(manifest.total_hash, sha256_b58(&code));
+    let raw = bs58::decode(&manifest.total_hash).into_vec().unwrap();
+    assert_eq!(raw.len(), 32);
+
+    let serialized = serde_json::to_value(manifest).unwrap();
+    assert_eq!(serialized["totalBytes"], json!(3_200_000));
+    assert_eq!(serialized["chunkHashes"].as_array().unwrap().len(), 6);
+    assert_eq!(serialized["totalHash"], json!(manifest.total_hash));
+}
+
+#[test]
+fn test_plan_rejects_invalid_inputs() {
+    let code = wasm(2_000);
+    let err = plan_chunked_deploy(SIGNER, b"not wasm", 1_000, &staging()).unwrap_err();
+    assert!(err.contains("not a wasm module"), "{}", err);
+
+    for chunk_size in [0, MAX_DEPLOY_CHUNK_BYTES + 1] {
+        let err = plan_chunked_deploy(SIGNER, &code, chunk_size, &staging()).unwrap_err();
+        assert!(err.starts_with("Chunk size must be between"), "{}", err);
+    }
+    assert!(plan_chunked_deploy(SIGNER, &code, MAX_DEPLOY_CHUNK_BYTES, &staging()).is_ok());
+
+    let too_many = wasm(MAX_DEPLOY_CHUNKS * 10 + 1);
+    let err = plan_chunked_deploy(SIGNER, &too_many, 10, &staging()).unwrap_err();
+    assert!(err.contains("at most 64 are allowed"), "{}", err);
+
+    let unnamed = StagingContractInterface {
+        deploy_method: String::new(),
+        ..staging()
+    };
+    assert!(plan_chunked_deploy(SIGNER, &code, 1_000, &unnamed).is_err());
+}
+
+#[test]
+fn test_deploy_steps_are_covered_by_digest() {
+    let plan = plan_chunked_deploy(SIGNER, &wasm(2_500), 1_000, &staging()).unwrap();
+    let parsed: Vec<(String, Vec<ActionParams>)> = plan
+        .transactions
+        .iter()
+        .map(|tx| (tx.receiver_id.clone(), tx.parsed_actions().unwrap()))
+        .collect();
+    let flags = vec![Some(false); parsed.len()];
+
+    let payload = confirmation_tx_signing_requests_json(&parsed, &flags, Some(&plan.manifest), None);
+    assert_eq!(
+        payload[0]["deployStep"],
+        json!({
+            "step": 1,
+            "stepCount": 4,
+            "totalBytes": 2_500,
+            "totalHash": plan.manifest.total_hash,
+            "chunkHash": plan.manifest.chunk_hashes[0],
+        })
+    );
+    assert_eq!(payload[3]["deployStep"]["step"], json!(4));
+    assert_eq!(payload[3]["deployStep"]["chunkHash"], json!(null));
+    assert_eq!(payload[3]["firstTimeReceiver"], json!(false));
+
+    // Plain batches keep their digest; the deploy annotation changes it
+    let plain = compute_confirmation_intent_digest(&parsed, &flags, None, None).unwrap();
+    assert_eq!(
+        plain,
+        compute_intent_digest_from_js_inputs(&parsed, Some(&flags)).unwrap()
+    );
+    let deploy = compute_confirmation_intent_digest(&parsed, &flags, Some(&plan.manifest), None).unwrap();
+    assert_ne!(plain, deploy);
+
+    let mut tampered = plan.manifest.clone();
+    tampered.total_hash = sha256_b58(b"other code");
+    assert_ne!(
+        deploy,
+        compute_confirmation_intent_digest(&parsed, &flags, Some(&tampered), None).unwrap()
+    );
+}
+
+#[test]
+fn test_deploy_request_with_invalid_chunk_size_fails_before_confirmation() {
+    let code: Vec<u8> = wasm(100);
+    let request: DeployLargeContractRequest = serde_json::from_value(json!({
+        "rpcCall": { "contractId": "w3a-v1.testnet", "nearRpcUrl": "https://rpc.testnet.near.org", "nearAccountId": SIGNER },
+        "decryption": { "encryptedPrivateKeyData": "AAAA", "encryptedPrivateKeyIv": "BBBB" },
+        "code": code,
+        "chunkSize": 0,
+        "staging": { "stageMethod": "stage_code", "deployMethod": "deploy_staged" }
+    }))
+    .unwrap();
+    assert_eq!(request.staging.receiver_id, None);
+
+    let err = block_on(handle_deploy_large_contract(request)).unwrap_err();
+    assert!(err.starts_with("Chunk size must be between"), "{}", err);
+}
@@ -0,0 +1,105 @@
+use crate::clock::{ManualClock, SharedClock};
+use crate::error::SignerErrorCode;
+use crate::handlers::confirm_tx_details::{
+    check_request_expiry, confirmation_timeout_ms, ChallengeExpiryPolicy,
+};
+use crate::handlers::handle_session_keys::{handle_create_session_key, CreateSessionKeyRequest};
+use crate::state;
+use crate::tests::block_on;
+
+/// Installs a ManualClock at `now_ms` in this test thread's worker state
+fn install_manual_clock(now_ms: f64) -> ManualClock {
+    let clock = ManualClock::new(now_ms);
+    state::set_clock(SharedClock::new(clock.clone()));
+    clock
+}
+
+#[test]
+fn test_worker_state_reads_the_installed_clock() {
+    let clock = install_manual_clock(1_000.0);
+    assert_eq!(state::now_ms(), 1_000.0);
+    assert_eq!(state::monotonic_ms(), 1_000.0);
+
+    clock.advance(250.0);
+    assert_eq!(state::now_ms(), 1_250.0);
+
+    // The clock survives WipeAllState
+    state::wipe_all_state();
+    assert_eq!(state::now_ms(), 1_250.0);
+}
+
+#[test]
+fn test_confirmation_timeout_by_advancing_clock() {
+    let clock = install_manual_clock(5_000.0);
+    let policy = ChallengeExpiryPolicy {
+        average_block_time_ms: 1_000,
+        acceptance_window_blocks: 100,
+    };
+    let expires_at_ms = state::now_ms() + confirmation_timeout_ms(None) as f64;
+
+    // Confirmed right at the deadline
+    clock.advance(confirmation_timeout_ms(None) as f64);
+    assert!(check_request_expiry(Some(expires_at_ms), state::now_ms(), None, &policy).is_ok());
+
+    // One millisecond too late
+    clock.advance(1.0);
+    let (code, message) =
+        check_request_expiry(Some(expires_at_ms), state::now_ms(), None, &policy).unwrap_err();
+    assert_eq!(code, SignerErrorCode::ConfirmationExpired);
+    assert!(message.contains("1ms after"));
+}
+
+#[test]
+fn test_session_key_expires_by_advancing_clock() {
+    let clock = install_manual_clock(10_000.0);
+    let created = block_on(handle_create_session_key(CreateSessionKeyRequest {
+        near_account_id: "player.testnet".to_string(),
+        receiver_id: "game.testnet".to_string(),
+        method_names: vec![],
+        allowance: "1000000000000000000000000".to_string(),
+        ttl_ms: Some(60_000),
+    }))
+    .unwrap();
+    assert_eq!(created.expires_at_ms, 70_000.0);
+
+    clock.advance(59_999.0);
+    assert!(state::with_session_key(&created.public_key, |_| ()).is_ok());
+
+    clock.advance(1.0);
+    assert_eq!(
+        state::with_session_key(&created.public_key, |_| ()).unwrap_err(),
+        SignerErrorCode::SessionKeyExpired
+    );
+    // Expired keys are wiped on access
+    assert_eq!(
+        state::with_session_key(&created.public_key, |_| ()).unwrap_err(),
+        SignerErrorCode::SessionKeyNotFound
+    );
+}
+
+#[cfg(feature = "relayer")]
+#[test]
+fn test_relayer_backoff_by_advancing_clock() {
+    use crate::relayer::{run_phase, RelayerPhase};
+    use std::cell::RefCell;
+
+    let clock = install_manual_clock(0.0);
+    let attempted_at = RefCell::new(Vec::new());
+
+    // Each retry waits out its backoff on the clock instead of sleeping
+    let (outcome, attempts) = block_on(run_phase(
+        RelayerPhase::Preflight,
+        || {
+            attempted_at.borrow_mut().push(state::now_ms());
+            async { Err("rate limited".to_string()) }
+        },
+        |ms| {
+            clock.advance(ms as f64);
+            async {}
+        },
+    ));
+    assert!(outcome.is_err());
+    assert_eq!(attempts, 3);
+    assert_eq!(attempted_at.into_inner(), vec![0.0, 500.0, 1_500.0]);
+    assert_eq!(state::now_ms(), 1_500.0);
+}
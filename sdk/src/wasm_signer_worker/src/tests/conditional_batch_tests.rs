@@ -0,0 +1,187 @@
+use crate::actions::ActionParams;
+use crate::conditional_batch::{
+    plan_entries, signed_count, simulate_batch, ConditionalBatch, ConditionalEntryStatus,
+    SimulationOutcome,
+};
+use crate::confirmation_blocks::{batch_blocks, ConfirmationSummaryBlock, SummaryPolicy};
+use crate::error::{RpcErrorKind, SignerErrorCode};
+use crate::rpc_client::MockRpcClient;
+use crate::strict_parsing::check_unknown_fields;
+use crate::tests::block_on;
+use crate::types::worker_messages::WorkerRequestType;
+use serde_json::json;
+
+fn call(method_name: &str) -> ActionParams {
+    ActionParams::FunctionCall {
+        method_name: method_name.to_string(),
+        args: json!({ "amount": "10" }).to_string(),
+        gas: "30000000000000".to_string(),
+        deposit: "1".to_string(),
+    }
+}
+
+fn view_ok(logs: &[&str]) -> Result<serde_json::Value, RpcErrorKind> {
+    Ok(json!({ "result": [], "logs": logs, "block_height": 100 }))
+}
+
+fn contract_error(message: &str) -> Result<serde_json::Value, RpcErrorKind> {
+    Err(RpcErrorKind::Rpc {
+        name: "CONTRACT_EXECUTION_ERROR".to_string(),
+        message: message.to_string(),
+        error: json!({ "cause": { "name": "CONTRACT_EXECUTION_ERROR" } }),
+    })
+}
+
+/// approve on token.testnet, swap on dex.testnet, then a transfer
+fn approve_swap_transfer() -> Vec<(String, Vec<ActionParams>)> {
+    vec![
+        ("token.testnet".to_string(), vec![call("approve")]),
+        ("dex.testnet".to_string(), vec![call("swap")]),
+        (
+            "bob.testnet".to_string(),
+            vec![ActionParams::Transfer {
+                deposit: "1".to_string(),
+            }],
+        ),
+    ]
+}
+
+#[test]
+fn test_later_transactions_are_skipped_after_a_failed_simulation() {
+    let rpc = MockRpcClient::new()
+        .answer("query/call_function/approve", view_ok(&["approved"]))
+        .answer(
+            "query/call_function/swap",
+            contract_error("Smart contract panicked: slippage exceeded"),
+        );
+    let simulations = block_on(simulate_batch(&rpc, &approve_swap_transfer()));
+
+    // Nothing after the failed swap is simulated
+    assert_eq!(rpc.calls.borrow().len(), 2);
+    assert_eq!(
+        simulations[0],
+        Some(SimulationOutcome::Succeeded {
+            logs: vec!["approved".to_string()]
+        })
+    );
+    assert!(matches!(
+        &simulations[1],
+        Some(SimulationOutcome::Failed { method_name, error, .. })
+            if method_name == "swap" && error.contains("slippage exceeded")
+    ));
+    assert_eq!(simulations[2], None);
+
+    // The swap itself is signed (its earlier step succeeded); the transfer after it is not
+    let entries = plan_entries(simulations, &ConditionalBatch::default()).unwrap();
+    let statuses: Vec<_> = entries.iter().map(|e| e.status).collect();
+    assert_eq!(
+        statuses,
+        vec![
+            ConditionalEntryStatus::Signed,
+            ConditionalEntryStatus::Signed,
+            ConditionalEntryStatus::Skipped
+        ]
+    );
+    assert_eq!(signed_count(&entries), 2);
+
+    let json = serde_json::to_value(&entries).unwrap();
+    assert_eq!(json[1]["simulation"]["status"], "failed");
+    assert_eq!(json[1]["simulation"]["methodName"], "swap");
+    assert_eq!(
+        json[2],
+        json!({ "index": 2, "status": "skipped", "simulation": null })
+    );
+}
+
+#[test]
+fn test_simulation_outcomes_of_view_calls() {
+    // A call that reaches a storage write ran cleanly up to it
+    let rpc = MockRpcClient::new()
+        .answer(
+            "query/call_function/approve",
+            contract_error("wasm execution failed with error: HostError(ProhibitedInView { method_name: \"storage_write\" })"),
+        )
+        // Older nodes put the panic in the result
+        .answer(
+            "query/call_function/swap",
+            Ok(json!({ "error": "wasm execution failed with error: FunctionCallError(ExecutionError(\"insufficient balance\"))", "logs": ["checking"] })),
+        );
+    let simulations = block_on(simulate_batch(&rpc, &approve_swap_transfer()));
+    assert!(simulations[0].as_ref().unwrap().succeeded());
+    assert_eq!(
+        simulations[1],
+        Some(SimulationOutcome::Failed {
+            method_name: "swap".to_string(),
+            error: "wasm execution failed with error: FunctionCallError(ExecutionError(\"insufficient balance\"))".to_string(),
+            logs: vec!["checking".to_string()],
+        })
+    );
+
+    // Transactions without FunctionCalls have nothing to simulate
+    let rpc = MockRpcClient::new();
+    let transfer_only = &approve_swap_transfer()[2..];
+    assert_eq!(
+        block_on(simulate_batch(&rpc, transfer_only)),
+        vec![Some(SimulationOutcome::Succeeded { logs: vec![] })]
+    );
+    assert!(rpc.calls.borrow().is_empty());
+}
+
+#[test]
+fn test_unavailable_simulation_fails_unless_falling_back() {
+    let unreachable = || {
+        MockRpcClient::new().answer(
+            "query/call_function/approve",
+            Err(RpcErrorKind::Unreachable(
+                "rpc.testnet.near.org".to_string(),
+            )),
+        )
+    };
+    let simulations = block_on(simulate_batch(&unreachable(), &approve_swap_transfer()));
+    assert!(matches!(
+        simulations[0],
+        Some(SimulationOutcome::Unavailable { .. })
+    ));
+
+    let (code, error) =
+        plan_entries(simulations.clone(), &ConditionalBatch::default()).unwrap_err();
+    assert_eq!(code, SignerErrorCode::SimulationUnavailable);
+    assert!(
+        error.starts_with("SimulationUnavailable: transaction 1"),
+        "{}",
+        error
+    );
+
+    // The fallback signs every transaction, reporting what could not be simulated
+    let fallback = ConditionalBatch {
+        fallback_to_unconditional: true,
+    };
+    let entries = plan_entries(simulations, &fallback).unwrap();
+    assert_eq!(signed_count(&entries), 3);
+    assert!(matches!(
+        entries[0].simulation,
+        Some(SimulationOutcome::Unavailable { .. })
+    ));
+}
+
+#[test]
+fn test_conditional_batch_confirmation_warns_about_skipped_steps() {
+    let warned = |policy: SummaryPolicy| {
+        batch_blocks(&policy).iter().any(|block| {
+            matches!(block, ConfirmationSummaryBlock::Warning { text, .. }
+                if text.starts_with("Later steps may be skipped"))
+        })
+    };
+    assert!(!warned(SummaryPolicy::default()));
+    assert!(warned(SummaryPolicy::default().with_conditional(true)));
+
+    let payload = json!({
+        "txSigningRequests": [],
+        "conditionalBatch": { "fallbackToUnconditional": true },
+        "workerPolicy": { "strictParsing": true }
+    });
+    assert_eq!(
+        check_unknown_fields(WorkerRequestType::SignTransactionsWithActions, &payload),
+        Ok(())
+    );
+}
@@ -0,0 +1,155 @@
+use crate::error::{ConfigError, SignerErrorCode};
+use crate::types::handlers::{
+    AuthenticatorOptions, ConfirmationBehavior, ConfirmationConfig, ConfirmationUIMode,
+    DecryptionPayload, OriginPolicyInput, RpcCallPayload, TransactionContext,
+    UserVerificationPolicy,
+};
+
+fn invalid_field(result: Result<(), ConfigError>) -> &'static str {
+    match result.unwrap_err() {
+        ConfigError::InvalidValue { field, .. } | ConfigError::InvalidCombination { field, .. } => {
+            field
+        }
+    }
+}
+
+#[test]
+fn test_confirmation_config_builder_validation() {
+    let config =
+        ConfirmationConfig::new(ConfirmationUIMode::Modal, ConfirmationBehavior::AutoProceed)
+            .with_auto_proceed_delay(1_500)
+            .with_theme("light".to_string())
+            .with_confirmation_timeout_ms(30_000);
+    assert!(config.validate().is_ok());
+
+    // autoProceed without a delay only passes in skip mode, where behavior is ignored
+    let no_delay = ConfirmationConfig::new(
+        ConfirmationUIMode::Drawer,
+        ConfirmationBehavior::AutoProceed,
+    );
+    let err = no_delay.validate().unwrap_err();
+    assert!(matches!(
+        err,
+        ConfigError::InvalidCombination {
+            field: "autoProceedDelay",
+            ..
+        }
+    ));
+    assert_eq!(err.code(), SignerErrorCode::InvalidConfig);
+    assert!(
+        err.to_string()
+            .starts_with("InvalidConfig: autoProceedDelay"),
+        "{}",
+        err
+    );
+    assert!(
+        ConfirmationConfig::new(ConfirmationUIMode::Skip, ConfirmationBehavior::AutoProceed)
+            .validate()
+            .is_ok()
+    );
+
+    let too_slow = config.clone().with_auto_proceed_delay(45_000);
+    assert_eq!(invalid_field(too_slow.validate()), "autoProceedDelay");
+    let bad_theme = config.clone().with_theme("sepia".to_string());
+    assert_eq!(invalid_field(bad_theme.validate()), "theme");
+    let zero_timeout = config.with_confirmation_timeout_ms(0);
+    assert_eq!(
+        invalid_field(zero_timeout.validate()),
+        "confirmationTimeoutMs"
+    );
+}
+
+#[test]
+fn test_authenticator_options_builder_validation() {
+    let options = AuthenticatorOptions::new();
+    assert_eq!(options, AuthenticatorOptions::default());
+    assert!(options.validate().is_ok());
+
+    let options = AuthenticatorOptions::new()
+        .with_user_verification(UserVerificationPolicy::Required)
+        .with_origin_policy(
+            OriginPolicyInput::new().with_multiple(vec!["https://wallet.example.com".to_string()]),
+        );
+    assert!(options.validate().is_ok());
+
+    let two_set = OriginPolicyInput::new()
+        .with_single(true)
+        .with_all_subdomains(true);
+    let options = AuthenticatorOptions::new().with_origin_policy(two_set);
+    assert!(matches!(
+        options.validate(),
+        Err(ConfigError::InvalidCombination {
+            field: "originPolicy",
+            ..
+        })
+    ));
+    assert_eq!(
+        invalid_field(OriginPolicyInput::new().validate()),
+        "originPolicy"
+    );
+    assert_eq!(
+        invalid_field(OriginPolicyInput::new().with_multiple(vec![]).validate()),
+        "originPolicy.multiple"
+    );
+}
+
+#[test]
+fn test_payload_constructors_validation() {
+    let rpc_call = RpcCallPayload::new(
+        "w3a-v1.testnet".to_string(),
+        "https://rpc.testnet.near.org".to_string(),
+        "alice.testnet".to_string(),
+    );
+    assert!(rpc_call.validate().is_ok());
+    let mut bad_url = rpc_call.clone();
+    bad_url.near_rpc_url = "rpc.testnet.near.org".to_string();
+    assert_eq!(invalid_field(bad_url.validate()), "nearRpcUrl");
+
+    let context = TransactionContext::new(
+        format!("ed25519:{}", bs58::encode([3u8; 32]).into_string()),
+        "42".to_string(),
+        "1000".to_string(),
+        bs58::encode([8u8; 32]).into_string(),
+    );
+    assert!(context.validate().is_ok());
+    let mut bad_nonce = context.clone();
+    bad_nonce.next_nonce = "-1".to_string();
+    assert_eq!(invalid_field(bad_nonce.validate()), "nextNonce");
+    let mut bad_hash = context;
+    bad_hash.tx_block_hash = bs58::encode([8u8; 16]).into_string();
+    assert_eq!(invalid_field(bad_hash.validate()), "txBlockHash");
+
+    assert!(
+        DecryptionPayload::new("AQID".to_string(), "BAUG".to_string())
+            .validate()
+            .is_ok()
+    );
+    assert_eq!(
+        invalid_field(DecryptionPayload::new("AQID".to_string(), String::new()).validate()),
+        "encryptedPrivateKeyIv"
+    );
+}
+
+#[test]
+fn test_built_configs_parse_like_plain_objects() {
+    // toJSON() output goes through the same serde form the handlers parse
+    let config = ConfirmationConfig::new(
+        ConfirmationUIMode::Drawer,
+        ConfirmationBehavior::AutoProceed,
+    )
+    .with_auto_proceed_delay(800)
+    .with_confirmation_timeout_ms(10_000);
+    let plain = serde_json::to_value(&config).unwrap();
+    assert_eq!(plain["uiMode"], "drawer");
+    assert_eq!(plain["behavior"], "autoProceed");
+    let parsed: ConfirmationConfig = serde_json::from_value(plain).unwrap();
+    assert_eq!(parsed.ui_mode, ConfirmationUIMode::Drawer);
+    assert_eq!(parsed.auto_proceed_delay, Some(800));
+    assert_eq!(parsed.confirmation_timeout_ms, Some(10_000));
+
+    let options =
+        AuthenticatorOptions::new().with_origin_policy(OriginPolicyInput::new().with_single(true));
+    let parsed: AuthenticatorOptions =
+        serde_json::from_value(serde_json::to_value(&options).unwrap()).unwrap();
+    assert_eq!(parsed, options);
+}
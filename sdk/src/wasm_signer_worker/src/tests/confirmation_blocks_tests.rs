@@ -0,0 +1,270 @@
+use crate::actions::ActionParams;
+use crate::config::{DEFAULT_DEPOSIT_ESCALATION_YOCTO, YOCTO_PER_NEAR};
+use crate::confirmation_blocks::*;
+use crate::handlers::confirm_tx_details::{
+    compute_confirmation_intent_digest, confirmation_tx_signing_requests_json,
+};
+use crate::types::handlers::WorkerPolicy;
+use serde_json::json;
+
+const RECEIVER: &str = "dex.testnet";
+const PUBLIC_KEY: &str = "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp";
+
+fn transfer(yocto: u128) -> ActionParams {
+    ActionParams::Transfer {
+        deposit: yocto.to_string(),
+    }
+}
+
+fn add_key(permission: serde_json::Value) -> ActionParams {
+    ActionParams::AddKey {
+        public_key: PUBLIC_KEY.to_string(),
+        access_key: json!({ "nonce": 0, "permission": permission }).to_string(),
+    }
+}
+
+fn warning_codes(blocks: &[ConfirmationSummaryBlock]) -> Vec<SummaryWarningCode> {
+    blocks
+        .iter()
+        .filter_map(|block| match block {
+            ConfirmationSummaryBlock::Warning { code, .. } => Some(*code),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_plain_transfer_has_rows_and_no_warnings() {
+    let blocks = transaction_summary_blocks(
+        RECEIVER,
+        &[transfer(YOCTO_PER_NEAR * 3 / 2)],
+        Some(false),
+        &SummaryPolicy::default(),
+    );
+    assert_eq!(
+        blocks,
+        vec![
+            ConfirmationSummaryBlock::AccountRow {
+                label: "Receiver".to_string(),
+                account_id: RECEIVER.to_string(),
+                first_time: Some(false),
+                aria_label: "Receiver: dex dot testnet".to_string(),
+            },
+            ConfirmationSummaryBlock::Heading {
+                text: "Transfer".to_string(),
+                aria_label: "Transfer".to_string(),
+            },
+            ConfirmationSummaryBlock::AmountRow {
+                label: "Amount".to_string(),
+                yocto: "1500000000000000000000000".to_string(),
+                formatted: "1.5 NEAR".to_string(),
+                aria_label: "Amount: one point five NEAR".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_risky_actions_get_worker_generated_warnings() {
+    let policy = SummaryPolicy::default();
+
+    let full_access = transaction_summary_blocks(
+        RECEIVER,
+        &[add_key(json!({ "FullAccess": {} }))],
+        Some(false),
+        &policy,
+    );
+    assert_eq!(
+        warning_codes(&full_access),
+        vec![SummaryWarningCode::FullAccessKey]
+    );
+    // Warnings lead the transaction's blocks
+    assert!(matches!(
+        full_access[0],
+        ConfirmationSummaryBlock::Warning {
+            severity: WarningSeverity::Danger,
+            ..
+        }
+    ));
+
+    let function_call_key = transaction_summary_blocks(
+        RECEIVER,
+        &[add_key(json!({
+            "FunctionCall": { "allowance": null, "receiver_id": RECEIVER, "method_names": [] }
+        }))],
+        Some(false),
+        &policy,
+    );
+    assert!(warning_codes(&function_call_key).is_empty());
+
+    let delete_account = transaction_summary_blocks(
+        "alice.testnet",
+        &[ActionParams::DeleteAccount {
+            beneficiary_id: "bob.testnet".to_string(),
+        }],
+        Some(false),
+        &policy,
+    );
+    assert_eq!(
+        warning_codes(&delete_account),
+        vec![SummaryWarningCode::DeleteAccount]
+    );
+    assert!(
+        delete_account.contains(&ConfirmationSummaryBlock::AccountRow {
+            label: "Beneficiary".to_string(),
+            account_id: "bob.testnet".to_string(),
+            first_time: None,
+            aria_label: "Beneficiary: bob dot testnet".to_string(),
+        })
+    );
+
+    let first_time = transaction_summary_blocks(RECEIVER, &[transfer(1)], Some(true), &policy);
+    assert_eq!(
+        warning_codes(&first_time),
+        vec![SummaryWarningCode::FirstTimeReceiver]
+    );
+    // Unknown history is not flagged
+    let unknown = transaction_summary_blocks(RECEIVER, &[transfer(1)], None, &policy);
+    assert!(warning_codes(&unknown).is_empty());
+}
+
+#[test]
+fn test_deposit_warning_uses_transaction_total_and_policy_threshold() {
+    let policy = SummaryPolicy::default();
+    let call = ActionParams::FunctionCall {
+        method_name: "swap".to_string(),
+        args: json!({ "pool_id": 7 }).to_string(),
+        gas: "30000000000000".to_string(),
+        deposit: (DEFAULT_DEPOSIT_ESCALATION_YOCTO / 2).to_string(),
+    };
+
+    // Each deposit is at the threshold's half; together they exceed it
+    let at_threshold = [call.clone(), transfer(DEFAULT_DEPOSIT_ESCALATION_YOCTO / 2)];
+    assert!(warning_codes(&transaction_summary_blocks(
+        RECEIVER,
+        &at_threshold,
+        Some(false),
+        &policy
+    ))
+    .is_empty());
+    let above = [call, transfer(DEFAULT_DEPOSIT_ESCALATION_YOCTO / 2 + 1)];
+    let blocks = transaction_summary_blocks(RECEIVER, &above, Some(false), &policy);
+    assert_eq!(
+        warning_codes(&blocks),
+        vec![SummaryWarningCode::DepositAboveThreshold]
+    );
+    assert!(blocks.contains(&ConfirmationSummaryBlock::CodeBlock {
+        json: json!({ "args": { "pool_id": 7 }, "gas": "30000000000000" }).to_string(),
+        aria_label: "Call arguments shown as code, thirty trillion gas".to_string(),
+    }));
+
+    let worker_policy: WorkerPolicy =
+        serde_json::from_value(json!({ "depositEscalationYocto": "1000" })).unwrap();
+    let strict = SummaryPolicy::from_worker_policy(Some(&worker_policy)).unwrap();
+    assert_eq!(strict.deposit_escalation_yocto, 1000);
+    assert_eq!(
+        warning_codes(&transaction_summary_blocks(
+            RECEIVER,
+            &[transfer(1001)],
+            Some(false),
+            &strict
+        )),
+        vec![SummaryWarningCode::DepositAboveThreshold]
+    );
+    assert_eq!(
+        SummaryPolicy::from_worker_policy(None).unwrap(),
+        SummaryPolicy::default()
+    );
+
+    let invalid: WorkerPolicy =
+        serde_json::from_value(json!({ "depositEscalationYocto": "10 NEAR" })).unwrap();
+    assert!(SummaryPolicy::from_worker_policy(Some(&invalid))
+        .unwrap_err()
+        .contains("depositEscalationYocto"));
+}
+
+#[test]
+fn test_summary_blocks_serialize_tagged_by_kind() {
+    let blocks = transaction_summary_blocks(
+        "alice.testnet",
+        &[ActionParams::DeleteAccount {
+            beneficiary_id: "bob.testnet".to_string(),
+        }],
+        None,
+        &SummaryPolicy::default(),
+    );
+    let json = serde_json::to_value(&blocks).unwrap();
+    assert_eq!(json[0]["kind"], json!("warning"));
+    assert_eq!(json[0]["severity"], json!("danger"));
+    assert_eq!(json[0]["code"], json!("DeleteAccount"));
+    assert_eq!(
+        json[1],
+        json!({
+            "kind": "accountRow",
+            "label": "Receiver",
+            "accountId": "alice.testnet",
+            "firstTime": null,
+            "ariaLabel": "Receiver: alice dot testnet"
+        })
+    );
+    assert_eq!(
+        json[2],
+        json!({ "kind": "heading", "text": "Delete account", "ariaLabel": "Delete account" })
+    );
+
+    assert_eq!(format_yocto_near(0), "0 NEAR");
+    assert_eq!(format_yocto_near(1), "0.000000000000000000000001 NEAR");
+    assert_eq!(format_yocto_near(12 * YOCTO_PER_NEAR), "12 NEAR");
+}
+
+#[test]
+fn test_summary_blocks_are_covered_by_digest() {
+    let batch = vec![
+        (RECEIVER.to_string(), vec![transfer(YOCTO_PER_NEAR)]),
+        (
+            "alice.testnet".to_string(),
+            vec![add_key(json!({ "FullAccess": {} }))],
+        ),
+    ];
+    let flags = vec![Some(true), Some(false)];
+    let policy = SummaryPolicy::default();
+
+    let payload = confirmation_tx_signing_requests_json(&batch, &flags, None, Some(&policy));
+    assert_eq!(
+        payload[0]["summaryBlocks"][0]["code"],
+        json!("FirstTimeReceiver")
+    );
+    assert_eq!(
+        payload[1]["summaryBlocks"][0]["code"],
+        json!("FullAccessKey")
+    );
+    assert_eq!(
+        payload[1]["summaryBlocks"],
+        json!(transaction_summary_blocks(
+            "alice.testnet",
+            &batch[1].1,
+            Some(false),
+            &policy
+        ))
+    );
+
+    let summarized =
+        compute_confirmation_intent_digest(&batch, &flags, None, Some(&policy)).unwrap();
+    assert_ne!(
+        summarized,
+        compute_confirmation_intent_digest(&batch, &flags, None, None).unwrap()
+    );
+    // A threshold that adds a deposit warning changes the blocks, and so the digest
+    let lower = SummaryPolicy {
+        deposit_escalation_yocto: YOCTO_PER_NEAR / 2,
+        ..SummaryPolicy::default()
+    };
+    assert_ne!(
+        summarized,
+        compute_confirmation_intent_digest(&batch, &flags, None, Some(&lower)).unwrap()
+    );
+    assert_eq!(
+        summarized,
+        compute_confirmation_intent_digest(&batch, &flags, None, Some(&policy)).unwrap()
+    );
+}
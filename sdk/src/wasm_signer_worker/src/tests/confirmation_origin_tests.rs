@@ -0,0 +1,153 @@
+use crate::confirmation_origin::*;
+use crate::error::{ConfigError, SignerErrorCode, UntrustedConfirmationOriginError};
+use crate::error_codes::error_code_of_message;
+use crate::handlers::confirm_tx_details::{
+    check_confirmation_response, register_confirmation_prompt, ConfirmationResult,
+};
+use crate::state;
+use serde_json::{json, Value};
+
+const WALLET_ORIGIN: &str = "https://wallet.example.com";
+
+/// A confirmed decision for `request_id` carrying `attestation` as the bridge forwarded it
+fn decision(request_id: &str, attestation: Option<Value>) -> ConfirmationResult {
+    serde_json::from_value(json!({
+        "request_id": request_id,
+        "confirmed": true,
+        "render_attestation": attestation,
+    }))
+    .unwrap()
+}
+
+/// Issues a prompt for `request_id` and returns its render nonce, as posted to the main thread
+fn prompt(request_id: &str) -> String {
+    let mut request_obj = json!({ "schemaVersion": 2, "requestId": request_id });
+    register_confirmation_prompt(&mut request_obj, request_id);
+    request_obj["renderNonce"].as_str().unwrap().to_string()
+}
+
+#[test]
+fn test_configure_normalizes_and_latches_the_origin() {
+    assert_eq!(
+        configure("HTTPS://Wallet.Example.com:443/service").unwrap(),
+        WALLET_ORIGIN
+    );
+    assert_eq!(
+        state::trusted_confirmation_origin().as_deref(),
+        Some(WALLET_ORIGIN)
+    );
+    // Repeating it is fine; changing it is not
+    assert_eq!(configure(WALLET_ORIGIN).unwrap(), WALLET_ORIGIN);
+    assert!(matches!(
+        configure("https://evil.example"),
+        Err(ConfigError::InvalidCombination { .. })
+    ));
+    assert!(matches!(
+        configure("wallet.example.com"),
+        Err(ConfigError::InvalidValue { .. })
+    ));
+    assert_eq!(
+        state::trusted_confirmation_origin().as_deref(),
+        Some(WALLET_ORIGIN)
+    );
+    state::set_trusted_confirmation_origin(None);
+}
+
+#[test]
+fn test_each_prompt_gets_its_own_render_nonce() {
+    let first = prompt("req-render-1");
+    let second = prompt("req-render-2");
+    assert_eq!(first.len(), 22);
+    assert_ne!(first, second);
+    assert_eq!(
+        state::confirmation_render_nonce("req-render-1"),
+        Some(first)
+    );
+    state::clear_confirmation_nonce("req-render-1");
+    state::clear_confirmation_nonce("req-render-2");
+}
+
+#[test]
+fn test_verify_render_attestation() {
+    let attestation = |nonce: &str, origin: &str| RenderAttestation {
+        render_nonce: nonce.to_string(),
+        origin: origin.to_string(),
+    };
+    let trusted = Some(WALLET_ORIGIN);
+    assert_eq!(
+        verify_render_attestation(Some(&attestation("n1", WALLET_ORIGIN)), "n1", trusted),
+        Ok(())
+    );
+    // Compared as origins
+    assert_eq!(
+        verify_render_attestation(
+            Some(&attestation("n1", "https://wallet.example.com:443")),
+            "n1",
+            trusted
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        verify_render_attestation(None, "n1", trusted),
+        Err(UntrustedConfirmationOriginError::MissingAttestation)
+    );
+    assert_eq!(
+        verify_render_attestation(Some(&attestation("n0", WALLET_ORIGIN)), "n1", trusted),
+        Err(UntrustedConfirmationOriginError::RenderNonceMismatch)
+    );
+    assert_eq!(
+        verify_render_attestation(
+            Some(&attestation("n1", "http://wallet.example.com")),
+            "n1",
+            trusted
+        ),
+        Err(UntrustedConfirmationOriginError::OriginMismatch {
+            attested: "http://wallet.example.com".to_string(),
+            trusted: WALLET_ORIGIN.to_string(),
+        })
+    );
+    // Nothing is required without a trusted origin
+    assert_eq!(verify_render_attestation(None, "n1", None), Ok(()));
+}
+
+#[test]
+fn test_decision_from_a_spoofed_origin_is_rejected() {
+    configure(WALLET_ORIGIN).unwrap();
+    let request_id = "req-spoofed";
+
+    // An imposter overlay on the dapp's page answers the prompt and claims its own origin
+    let render_nonce = prompt(request_id);
+    let spoofed = decision(
+        request_id,
+        Some(json!({ "renderNonce": render_nonce, "origin": "https://dapp.example" })),
+    );
+    let error = check_confirmation_response(spoofed, request_id).unwrap_err();
+    assert!(error.starts_with("UntrustedConfirmationOrigin: "));
+    assert!(error.contains("https://dapp.example"));
+    assert_eq!(
+        error_code_of_message(&error).map(|def| def.code),
+        Some(SignerErrorCode::UntrustedConfirmationOrigin.as_str())
+    );
+
+    // The bridge drops the attestation of a sender that is not the trusted origin
+    let dropped = decision(request_id, None);
+    assert!(check_confirmation_response(dropped, request_id)
+        .unwrap_err()
+        .contains("no render attestation"));
+
+    // A genuine attestation replayed from an earlier prompt
+    let replayed = decision(
+        request_id,
+        Some(json!({ "renderNonce": "AAAAAAAAAAAAAAAAAAAAAA", "origin": WALLET_ORIGIN })),
+    );
+    assert!(check_confirmation_response(replayed, request_id).is_err());
+
+    let genuine = decision(
+        request_id,
+        Some(json!({ "renderNonce": render_nonce, "origin": WALLET_ORIGIN })),
+    );
+    assert!(check_confirmation_response(genuine, request_id).is_ok());
+
+    state::clear_confirmation_nonce(request_id);
+    state::set_trusted_confirmation_origin(None);
+}
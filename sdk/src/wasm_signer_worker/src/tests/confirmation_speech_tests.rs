@@ -0,0 +1,234 @@
+use crate::actions::ActionParams;
+use crate::config::YOCTO_PER_NEAR;
+use crate::confirmation_blocks::*;
+use crate::confirmation_speech::*;
+use crate::handlers::confirm_tx_details::{
+    compute_confirmation_intent_digest, confirmation_tx_signing_requests_json,
+};
+use crate::types::handlers::{ConfirmationBehavior, ConfirmationConfig, ConfirmationUIMode};
+use serde_json::json;
+
+fn transfer(yocto: u128) -> ActionParams {
+    ActionParams::Transfer {
+        deposit: yocto.to_string(),
+    }
+}
+
+fn amount(locale: SpeechLocale, yocto: u128) -> String {
+    locale.say(&Phrase::Amount {
+        label: RowLabel::Amount,
+        yocto: Some(yocto),
+    })
+}
+
+fn config(locale: &str) -> ConfirmationConfig {
+    ConfirmationConfig::new(
+        ConfirmationUIMode::Modal,
+        ConfirmationBehavior::RequireClick,
+    )
+    .with_locale(locale.to_string())
+}
+
+#[test]
+fn test_amounts_are_spoken_in_words() {
+    let en = SpeechLocale::En;
+    let es = SpeechLocale::Es;
+    assert_eq!(
+        amount(en, YOCTO_PER_NEAR * 25 / 2),
+        "Amount: twelve point five NEAR"
+    );
+    assert_eq!(
+        amount(es, YOCTO_PER_NEAR * 25 / 2),
+        "Importe: doce coma cinco NEAR"
+    );
+    assert_eq!(amount(en, YOCTO_PER_NEAR), "Amount: one NEAR");
+    assert_eq!(amount(es, YOCTO_PER_NEAR), "Importe: un NEAR");
+    assert_eq!(amount(en, 0), "Amount: zero NEAR");
+    assert_eq!(
+        amount(en, YOCTO_PER_NEAR / 1000 * 5),
+        "Amount: zero point zero zero five NEAR"
+    );
+    assert_eq!(
+        amount(en, YOCTO_PER_NEAR * 1_234_567),
+        "Amount: one million two hundred thirty-four thousand five hundred sixty-seven NEAR"
+    );
+    assert_eq!(
+        amount(es, YOCTO_PER_NEAR * 1_234_567),
+        "Importe: un millón doscientos treinta y cuatro mil quinientos sesenta y siete NEAR"
+    );
+    assert_eq!(
+        amount(es, YOCTO_PER_NEAR * 21_000),
+        "Importe: veintiún mil NEAR"
+    );
+    assert_eq!(amount(es, YOCTO_PER_NEAR * 100), "Importe: cien NEAR");
+    assert_eq!(amount(es, YOCTO_PER_NEAR * 101), "Importe: ciento un NEAR");
+    assert_eq!(
+        amount(es, YOCTO_PER_NEAR * 2_000_000_000),
+        "Importe: dos mil millones NEAR"
+    );
+    // The largest amount still reads as words
+    assert!(amount(en, u128::MAX).starts_with("Amount: three hundred forty trillion"));
+    assert!(amount(es, u128::MAX).starts_with("Importe: trescientos cuarenta billones"));
+    assert_eq!(
+        en.say(&Phrase::Amount {
+            label: RowLabel::Deposit,
+            yocto: None
+        }),
+        "Deposit: invalid amount"
+    );
+}
+
+#[test]
+fn test_accounts_and_keys_are_spelled_out() {
+    let row = |locale: SpeechLocale, account_id| {
+        locale.say(&Phrase::Account {
+            label: RowLabel::Receiver,
+            account_id,
+            first_time: Some(true),
+        })
+    };
+    assert_eq!(
+        row(SpeechLocale::En, "alice.near"),
+        "Receiver: alice dot near, first time recipient"
+    );
+    assert_eq!(
+        row(SpeechLocale::Es, "my-app_v2.testnet"),
+        "Destinatario: my guion app guion bajo v2 punto testnet, destinatario nuevo"
+    );
+    assert_eq!(
+        row(
+            SpeechLocale::En,
+            "98793cd91a3f870fb126f66285808c7e094afcfc4eda8a970f6648cdf0dbd6de"
+        ),
+        "Receiver: implicit account starting 9 8 7 9, ending d 6 d e, first time recipient"
+    );
+    assert_eq!(
+        SpeechLocale::En.say(&Phrase::PublicKey {
+            public_key: "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+        }),
+        "ed25519 key ending in K E t p"
+    );
+    assert_eq!(
+        SpeechLocale::Es.say(&Phrase::AccessKey {
+            public_key: "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp",
+            access_key: &json!({
+                "nonce": 0,
+                "permission": { "FunctionCall": { "receiver_id": "dex.near", "method_names": [] } }
+            })
+            .to_string(),
+        }),
+        "clave ed25519 que termina en K E t p, puede llamar a dex punto near"
+    );
+}
+
+#[test]
+fn test_warnings_map_severity_to_live_regions() {
+    let blocks = transaction_summary_blocks(
+        "alice.testnet",
+        &[ActionParams::DeleteAccount {
+            beneficiary_id: "bob.testnet".to_string(),
+        }],
+        Some(true),
+        &SummaryPolicy::default(),
+    );
+    let live: Vec<(SummaryWarningCode, AriaLive, &str)> = blocks
+        .iter()
+        .filter_map(|block| match block {
+            ConfirmationSummaryBlock::Warning {
+                code,
+                aria_live,
+                aria_label,
+                ..
+            } => Some((*code, *aria_live, aria_label.as_str())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        live,
+        vec![
+            (
+                SummaryWarningCode::DeleteAccount,
+                AriaLive::Assertive,
+                "Danger: deletes alice dot testnet and sends its remaining balance to bob dot \
+                 testnet"
+            ),
+            (
+                SummaryWarningCode::FirstTimeReceiver,
+                AriaLive::Polite,
+                "Caution: this account has not sent a transaction to alice dot testnet before"
+            ),
+        ]
+    );
+    let json = serde_json::to_value(&blocks).unwrap();
+    assert_eq!(json[0]["ariaLive"], json!("assertive"));
+    assert_eq!(json[1]["ariaLive"], json!("polite"));
+}
+
+#[test]
+fn test_summary_speech_describes_the_whole_batch() {
+    let batch = vec![
+        (
+            "alice.near".to_string(),
+            vec![transfer(YOCTO_PER_NEAR * 25 / 2)],
+        ),
+        (
+            "dex.near".to_string(),
+            vec![ActionParams::FunctionCall {
+                method_name: "ft_transfer".to_string(),
+                args: "{}".to_string(),
+                gas: "30000000000000".to_string(),
+                deposit: (YOCTO_PER_NEAR / 10).to_string(),
+            }],
+        ),
+    ];
+    let flags = vec![Some(true), Some(false)];
+    let policy = SummaryPolicy::default();
+    let payload = confirmation_tx_signing_requests_json(&batch, &flags, None, Some(&policy));
+    assert_eq!(
+        payload[0]["summarySpeech"],
+        json!(
+            "Send twelve point five NEAR to alice dot near, first time recipient; then call ft \
+             underscore transfer on dex dot near attaching zero point one NEAR, with two warnings."
+        )
+    );
+    // Only the first transaction carries it
+    assert!(payload[1].get("summarySpeech").is_none());
+
+    let spanish = policy.with_locale(SpeechLocale::Es);
+    let payload = confirmation_tx_signing_requests_json(&batch[..1], &flags, None, Some(&spanish));
+    assert_eq!(
+        payload[0]["summarySpeech"],
+        json!("Enviar doce coma cinco NEAR a alice punto near, destinatario nuevo, con dos advertencias.")
+    );
+    assert_eq!(
+        payload[0]["summaryBlocks"][2]["ariaLabel"],
+        json!("Destinatario: alice punto near, destinatario nuevo")
+    );
+    // The visual blocks stay as they were
+    assert_eq!(payload[0]["summaryBlocks"][2]["label"], json!("Receiver"));
+}
+
+#[test]
+fn test_locale_comes_from_the_confirmation_config_and_is_digested() {
+    assert_eq!(SpeechLocale::from_config(None), SpeechLocale::En);
+    assert_eq!(
+        SpeechLocale::from_config(Some(&config("es-MX"))),
+        SpeechLocale::Es
+    );
+    assert_eq!(SpeechLocale::parse("EN_gb"), Some(SpeechLocale::En));
+    assert_eq!(SpeechLocale::parse("fr"), None);
+
+    assert!(config("es").validate().is_ok());
+    let error = config("fr-FR").validate().unwrap_err().to_string();
+    assert!(error.contains("locale"), "{}", error);
+    assert!(error.contains("en, es"), "{}", error);
+
+    let batch = vec![("alice.near".to_string(), vec![transfer(YOCTO_PER_NEAR)])];
+    let flags = vec![Some(false)];
+    let digest = |locale| {
+        let policy = SummaryPolicy::default().with_locale(locale);
+        compute_confirmation_intent_digest(&batch, &flags, None, Some(&policy)).unwrap()
+    };
+    assert_ne!(digest(SpeechLocale::En), digest(SpeechLocale::Es));
+    assert_eq!(digest(SpeechLocale::Es), digest(SpeechLocale::Es));
+}
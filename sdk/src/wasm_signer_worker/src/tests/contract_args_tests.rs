@@ -0,0 +1,147 @@
+use crate::contract_args::*;
+use crate::rpc_calls::{parse_contract_interface_version_response, VrfData};
+use crate::types::handlers::AuthenticatorOptions;
+use crate::types::{WebAuthnRegistrationCredential, WebAuthnRegistrationResponse};
+use serde_json::json;
+
+fn vrf_data() -> VrfData {
+    VrfData {
+        vrf_input_data: vec![1],
+        vrf_output: vec![2],
+        vrf_proof: vec![3],
+        public_key: vec![4],
+        user_id: "alice.testnet".to_string(),
+        rp_id: "example.com".to_string(),
+        block_height: 100,
+        block_hash: vec![5],
+    }
+}
+
+fn credential() -> WebAuthnRegistrationCredential {
+    WebAuthnRegistrationCredential {
+        id: "cred".to_string(),
+        raw_id: "cred".to_string(),
+        response: WebAuthnRegistrationResponse {
+            client_data_json: "cdj".to_string(),
+            attestation_object: "att".to_string(),
+            transports: None,
+        },
+        authenticator_attachment: None,
+        reg_type: "public-key".to_string(),
+    }
+}
+
+fn field_names(args_json: &str) -> Vec<String> {
+    let value: serde_json::Value = serde_json::from_str(args_json).unwrap();
+    let mut names: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// Contract view response carrying `bytes` as the return value
+fn view_response(bytes: &[u8]) -> serde_json::Value {
+    json!({ "jsonrpc": "2.0", "id": "probe", "result": { "result": bytes, "logs": [] } })
+}
+
+#[test]
+fn test_resolve_defaults_to_latest() {
+    assert_eq!(
+        ContractInterfaceVersion::resolve(None).unwrap(),
+        ContractInterfaceVersion::LATEST
+    );
+    assert_eq!(
+        ContractInterfaceVersion::resolve(Some(1)).unwrap(),
+        ContractInterfaceVersion::V1
+    );
+    assert!(ContractInterfaceVersion::resolve(Some(9)).is_err());
+}
+
+#[test]
+fn test_registration_args_fields_per_version() {
+    let v1 = registration_args_json(
+        ContractInterfaceVersion::V1,
+        vrf_data(),
+        credential(),
+        Some(vec![7]),
+        Some(2),
+        Some(AuthenticatorOptions::default()),
+    )
+    .unwrap();
+    assert_eq!(
+        field_names(&v1),
+        vec![
+            "deterministic_vrf_public_key",
+            "vrf_data",
+            "webauthn_registration"
+        ]
+    );
+
+    let v2 = registration_args_json(
+        ContractInterfaceVersion::V2,
+        vrf_data(),
+        credential(),
+        Some(vec![7]),
+        Some(2),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        field_names(&v2),
+        vec![
+            "authenticator_options",
+            "deterministic_vrf_public_key",
+            "device_number",
+            "vrf_data",
+            "webauthn_registration"
+        ]
+    );
+}
+
+#[test]
+fn test_link_device_args_fields_per_version() {
+    let v1 = link_device_args_json(
+        ContractInterfaceVersion::V1,
+        vrf_data(),
+        credential(),
+        vec![7],
+        Some(AuthenticatorOptions::default()),
+    )
+    .unwrap();
+    assert!(!field_names(&v1).contains(&"authenticator_options".to_string()));
+
+    let v2 = link_device_args_json(
+        ContractInterfaceVersion::V2,
+        vrf_data(),
+        credential(),
+        vec![7],
+        None,
+    )
+    .unwrap();
+    assert!(field_names(&v2).contains(&"authenticator_options".to_string()));
+}
+
+#[test]
+fn test_parse_interface_version_response() {
+    assert_eq!(
+        parse_contract_interface_version_response(&view_response(b"2")).unwrap(),
+        ContractInterfaceVersion::V2
+    );
+    assert!(parse_contract_interface_version_response(&view_response(b"7")).is_err());
+
+    // Contracts without the version method are V1
+    let method_not_found = json!({
+        "jsonrpc": "2.0",
+        "id": "probe",
+        "result": {
+            "error": "wasm execution failed with error: MethodResolveError(MethodNotFound)",
+            "logs": []
+        }
+    });
+    assert_eq!(
+        parse_contract_interface_version_response(&method_not_found).unwrap(),
+        ContractInterfaceVersion::V1
+    );
+
+    let rpc_error = json!({ "error": { "message": "Server error" } });
+    assert!(parse_contract_interface_version_response(&rpc_error).is_err());
+}
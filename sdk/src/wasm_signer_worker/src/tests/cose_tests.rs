@@ -185,3 +185,300 @@ fn test_extract_cose_public_key_invalid_base64() {
         .unwrap_err()
         .contains("Failed to decode attestation object"));
 }
+
+// === STRUCTURED PARSING (parse_attestation_object / parse_authenticator_data / cose_key_to_jwk) ===
+
+use crate::error::{SignerErrorCode, WebAuthnDataSection};
+
+fn cbor(value: &CborValue) -> Vec<u8> {
+    let mut out = Vec::new();
+    ciborium::into_writer(value, &mut out).unwrap();
+    out
+}
+
+fn ed25519_cose_key() -> Vec<u8> {
+    cbor(&CborValue::Map(vec![
+        (CborValue::Integer(1.into()), CborValue::Integer(1.into())),
+        (
+            CborValue::Integer(3.into()),
+            CborValue::Integer((-8).into()),
+        ),
+        (
+            CborValue::Integer((-1).into()),
+            CborValue::Integer(6.into()),
+        ),
+        (
+            CborValue::Integer((-2).into()),
+            CborValue::Bytes(vec![0x11; 32]),
+        ),
+    ]))
+}
+
+/// Authenticator data with attested credential data (and extension outputs when `extensions`)
+fn auth_data_with(cose_key: &[u8], extensions: Option<&CborValue>) -> Vec<u8> {
+    let mut auth_data = vec![0x49u8; 32];
+    auth_data.push(if extensions.is_some() { 0xDD } else { 0x5D }); // UP UV BE BS AT (ED)
+    auth_data.extend_from_slice(&7u32.to_be_bytes());
+    auth_data.extend_from_slice(&[
+        0xad, 0xce, 0x00, 0x02, 0x35, 0xbc, 0xc6, 0x0a, 0x64, 0x8b, 0x0b, 0x25, 0xf1, 0xf0, 0x55,
+        0x03,
+    ]);
+    auth_data.extend_from_slice(&3u16.to_be_bytes());
+    auth_data.extend_from_slice(&[1, 2, 3]);
+    auth_data.extend_from_slice(cose_key);
+    if let Some(extensions) = extensions {
+        auth_data.extend_from_slice(&cbor(extensions));
+    }
+    auth_data
+}
+
+fn attestation_with(auth_data: Vec<u8>) -> String {
+    Base64UrlUnpadded::encode_string(&cbor(&CborValue::Map(vec![
+        (
+            CborValue::Text("fmt".to_string()),
+            CborValue::Text("packed".to_string()),
+        ),
+        (
+            CborValue::Text("attStmt".to_string()),
+            CborValue::Map(vec![
+                (
+                    CborValue::Text("alg".to_string()),
+                    CborValue::Integer((-8).into()),
+                ),
+                (
+                    CborValue::Text("sig".to_string()),
+                    CborValue::Bytes(vec![0; 64]),
+                ),
+            ]),
+        ),
+        (
+            CborValue::Text("authData".to_string()),
+            CborValue::Bytes(auth_data),
+        ),
+    ])))
+}
+
+#[test]
+fn test_describe_attestation_object() {
+    let attestation_b64u = Base64UrlUnpadded::encode_string(&create_mock_attestation_object());
+    let parsed = describe_attestation_object_b64u(&attestation_b64u).unwrap();
+    assert_eq!(parsed.fmt.as_deref(), Some("none"));
+    assert!(parsed.att_stmt_keys.is_empty());
+
+    let auth_data = parsed.auth_data;
+    assert_eq!(auth_data.sign_count, 1);
+    assert!(auth_data.flags.user_present && auth_data.flags.user_verified);
+    assert!(auth_data.flags.attested_credential_data);
+    assert!(!auth_data.flags.backup_eligible && !auth_data.flags.extension_data);
+    assert_eq!(auth_data.flags.raw, 0x45);
+
+    let credential = auth_data.attested_credential.unwrap();
+    assert_eq!(credential.aaguid, "00000000-0000-0000-0000-000000000000");
+    assert_eq!(
+        credential.credential_id_b64u,
+        Base64UrlUnpadded::encode_string(&[0x42; 32])
+    );
+    assert_eq!(credential.public_key_algorithm, Some(-7));
+    assert_eq!(
+        credential.public_key_algorithm_name.as_deref(),
+        Some("ES256")
+    );
+    assert_eq!(
+        credential.public_key_jwk,
+        Jwk {
+            kty: "EC".to_string(),
+            alg: Some("ES256".to_string()),
+            crv: Some("P-256".to_string()),
+            x: Some(Base64UrlUnpadded::encode_string(&[0x42; 32])),
+            y: Some(Base64UrlUnpadded::encode_string(&[0x84; 32])),
+            n: None,
+            e: None,
+        }
+    );
+
+    // The exports describe exactly the key registration extracts
+    assert_eq!(
+        credential.credential_public_key_b64u,
+        Base64UrlUnpadded::encode_string(
+            &extract_cose_public_key_from_attestation(&attestation_b64u).unwrap()
+        )
+    );
+}
+
+#[test]
+fn test_extension_outputs_are_not_part_of_the_credential_key() {
+    let extensions = CborValue::Map(vec![(
+        CborValue::Text("credProtect".to_string()),
+        CborValue::Integer(2.into()),
+    )]);
+    let attestation_b64u = attestation_with(auth_data_with(&ed25519_cose_key(), Some(&extensions)));
+
+    assert_eq!(
+        extract_cose_public_key_from_attestation(&attestation_b64u).unwrap(),
+        ed25519_cose_key()
+    );
+    let parsed = describe_attestation_object_b64u(&attestation_b64u).unwrap();
+    assert_eq!(parsed.fmt.as_deref(), Some("packed"));
+    assert_eq!(parsed.att_stmt_keys, vec!["alg", "sig"]);
+
+    let auth_data = parsed.auth_data;
+    assert!(auth_data.flags.backup_eligible && auth_data.flags.backup_state);
+    assert!(auth_data.flags.extension_data);
+    assert_eq!(auth_data.sign_count, 7);
+    assert_eq!(
+        auth_data.extensions_b64u,
+        Some(Base64UrlUnpadded::encode_string(&cbor(&extensions)))
+    );
+    let credential = auth_data.attested_credential.unwrap();
+    assert_eq!(credential.aaguid, "adce0002-35bc-c60a-648b-0b25f1f05503");
+    assert_eq!(
+        credential.public_key_algorithm_name.as_deref(),
+        Some("EdDSA")
+    );
+    assert_eq!(credential.public_key_jwk.kty, "OKP");
+    assert_eq!(credential.public_key_jwk.crv.as_deref(), Some("Ed25519"));
+    assert_eq!(credential.public_key_jwk.y, None);
+
+    // Assertion authenticator data carries no attested credential
+    let mut assertion_auth_data = vec![0x49u8; 32];
+    assertion_auth_data.push(0x05);
+    assertion_auth_data.extend_from_slice(&9u32.to_be_bytes());
+    let parsed =
+        describe_authenticator_data_b64u(&Base64UrlUnpadded::encode_string(&assertion_auth_data))
+            .unwrap();
+    assert_eq!(parsed.sign_count, 9);
+    assert_eq!(parsed.attested_credential, None);
+}
+
+#[test]
+fn test_malformed_inputs_report_byte_offsets() {
+    let key = ed25519_cose_key();
+    let auth_data = auth_data_with(&key, None);
+    let key_offset = auth_data.len() - key.len();
+
+    // Credential ID length runs past the end
+    let mut truncated = auth_data[..key_offset - 3].to_vec();
+    truncated[key_offset - 5..key_offset - 3].copy_from_slice(&40u16.to_be_bytes());
+    let err = decode_authenticator_data(&truncated).unwrap_err();
+    assert_eq!(err.section, WebAuthnDataSection::AuthData);
+    assert_eq!(err.offset, truncated.len());
+    assert!(
+        err.message.contains("too short for credential ID"),
+        "{}",
+        err
+    );
+
+    // COSE key cut short: the offset is where the key's CBOR ends early
+    let cut = &auth_data[..auth_data.len() - 4];
+    let err = decode_authenticator_data(cut).unwrap_err();
+    assert_eq!(err.offset, cut.len());
+    assert!(err.message.starts_with("Failed to parse CBOR"), "{}", err);
+
+    let mut trailing = auth_data.clone();
+    trailing.push(0);
+    let err = decode_authenticator_data(&trailing).unwrap_err();
+    assert_eq!(err.offset, auth_data.len());
+    assert_eq!(err.code(), SignerErrorCode::WebAuthnDataMalformed);
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "WebAuthnDataMalformed: Trailing bytes after authenticator data (byte {} of authData)",
+            auth_data.len()
+        )
+    );
+
+    // Invalid CBOR inside the key is reported at its position within authData
+    let mut bad_key = auth_data.clone();
+    bad_key[key_offset] = 0xFF;
+    let err = decode_authenticator_data(&bad_key).unwrap_err();
+    assert_eq!(err.offset, key_offset);
+
+    // Key errors from the attestation path point at the key within authData
+    let mut no_x = key.clone();
+    no_x.truncate(no_x.len() - 35); // drop the x entry (1 + 2 + 32 bytes)
+    no_x[0] = 0xA3; // map of 3
+    let err = describe_attestation_object_b64u(&attestation_with(auth_data_with(&no_x, None)))
+        .unwrap_err();
+    assert_eq!(
+        (err.section, err.offset),
+        (WebAuthnDataSection::AuthData, key_offset)
+    );
+    assert_eq!(err.message, "COSE key is missing x");
+
+    let err = describe_attestation_object_b64u("%%%").unwrap_err();
+    assert_eq!(err.section, WebAuthnDataSection::AttestationObject);
+    assert!(err.message.starts_with("Invalid base64url"), "{}", err);
+}
+
+#[test]
+fn test_cose_key_to_jwk() {
+    let rsa = cbor(&CborValue::Map(vec![
+        (CborValue::Integer(1.into()), CborValue::Integer(3.into())),
+        (
+            CborValue::Integer(3.into()),
+            CborValue::Integer((-257).into()),
+        ),
+        (
+            CborValue::Integer((-1).into()),
+            CborValue::Bytes(vec![0xC5; 256]),
+        ),
+        (
+            CborValue::Integer((-2).into()),
+            CborValue::Bytes(vec![1, 0, 1]),
+        ),
+    ]));
+    let jwk = cose_key_b64u_to_jwk(&Base64UrlUnpadded::encode_string(&rsa)).unwrap();
+    assert_eq!(jwk.kty, "RSA");
+    assert_eq!(jwk.alg.as_deref(), Some("RS256"));
+    assert_eq!(jwk.e.as_deref(), Some("AQAB"));
+    assert_eq!(
+        serde_json::to_value(&jwk)
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .len(),
+        4
+    );
+
+    // Unregistered algorithms convert without an alg name
+    let unnamed = cbor(&CborValue::Map(vec![
+        (CborValue::Integer(1.into()), CborValue::Integer(1.into())),
+        (
+            CborValue::Integer(3.into()),
+            CborValue::Integer((-65535).into()),
+        ),
+        (
+            CborValue::Integer((-1).into()),
+            CborValue::Integer(6.into()),
+        ),
+        (
+            CborValue::Integer((-2).into()),
+            CborValue::Bytes(vec![0x11; 32]),
+        ),
+    ]));
+    let key = decode_cose_key(&unnamed, WebAuthnDataSection::CoseKey, 0).unwrap();
+    assert_eq!(key.alg, Some(-65535));
+    assert_eq!(key.jwk.alg, None);
+
+    let bad_curve = cbor(&CborValue::Map(vec![
+        (CborValue::Integer(1.into()), CborValue::Integer(2.into())),
+        (
+            CborValue::Integer((-1).into()),
+            CborValue::Integer(6.into()),
+        ),
+    ]));
+    let err = cose_key_b64u_to_jwk(&Base64UrlUnpadded::encode_string(&bad_curve)).unwrap_err();
+    assert_eq!((err.section, err.offset), (WebAuthnDataSection::CoseKey, 0));
+    assert_eq!(err.message, "Unsupported COSE curve 6 for kty 2");
+
+    let mut short_x = ed25519_cose_key();
+    short_x.pop();
+    let err = decode_cose_key(&short_x, WebAuthnDataSection::CoseKey, 0).unwrap_err();
+    assert_eq!(err.offset, short_x.len());
+
+    let err = cose_key_b64u_to_jwk(&Base64UrlUnpadded::encode_string(&cbor(&CborValue::Array(
+        vec![],
+    ))))
+    .unwrap_err();
+    assert_eq!(err.message, "COSE key is not a CBOR map");
+}
@@ -0,0 +1,209 @@
+use crate::config::COUNTDOWN_ACK_TIMEOUT_MS;
+use crate::countdown_handshake::*;
+use crate::error::SignerErrorCode;
+use crate::handlers::confirm_tx_details::{
+    enforce_countdown_handshake, validate_and_normalize_confirmation_config, ConfirmationResult,
+};
+use crate::types::handlers::{ConfirmationBehavior, ConfirmationConfig, ConfirmationUIMode};
+use serde_json::json;
+
+const DIGEST: &str = "k9Xc3v2mRtY0bq1nZ8fJ4sWdHhLpA7eGuIoVxC5yN6E";
+const DELAY_MS: u32 = 1_500;
+const PROMPTED_AT_MS: f64 = 1_700_000_000_000.0;
+
+/// The bridge's report for a page that rendered the countdown and acked `ack_after_ms` after
+/// the prompt, with the decision released `released_after_ms` after the prompt
+fn compliant_report(ack_after_ms: f64, released_after_ms: f64) -> CountdownReport {
+    CountdownReport {
+        prompted_at_ms: PROMPTED_AT_MS,
+        started_at_ms: Some(PROMPTED_AT_MS + ack_after_ms),
+        displayed_digest: Some(DIGEST.to_string()),
+        cancelled_at_ms: None,
+        require_click: false,
+        released_at_ms: PROMPTED_AT_MS + released_after_ms,
+    }
+}
+
+fn auto_proceed_config() -> ConfirmationConfig {
+    validate_and_normalize_confirmation_config(
+        &ConfirmationConfig::new(ConfirmationUIMode::Modal, ConfirmationBehavior::AutoProceed)
+            .with_auto_proceed_delay(DELAY_MS),
+    )
+}
+
+fn confirmed_result(countdown: Option<serde_json::Value>) -> ConfirmationResult {
+    serde_json::from_value(json!({
+        "request_id": "req-countdown",
+        "confirmed": true,
+        "intent_digest": DIGEST,
+        "credential": { "id": "cred" },
+        "prf_output": "cHJm",
+        "countdown": countdown,
+    }))
+    .unwrap()
+}
+
+fn code(result: Result<CountdownOutcome, (SignerErrorCode, String)>) -> SignerErrorCode {
+    result.unwrap_err().0
+}
+
+#[test]
+fn test_compliant_page_proceeds_after_its_countdown() {
+    let report = compliant_report(120.0, 120.0 + DELAY_MS as f64);
+    assert_eq!(
+        verify_countdown_handshake(Some(&report), DIGEST, DELAY_MS),
+        Ok(CountdownOutcome::Elapsed)
+    );
+    // An ack right at the deadline still counts
+    let at_deadline = compliant_report(
+        COUNTDOWN_ACK_TIMEOUT_MS as f64,
+        (COUNTDOWN_ACK_TIMEOUT_MS + DELAY_MS) as f64,
+    );
+    assert_eq!(
+        verify_countdown_handshake(Some(&at_deadline), DIGEST, DELAY_MS),
+        Ok(CountdownOutcome::Elapsed)
+    );
+}
+
+#[test]
+fn test_page_that_skips_the_countdown_is_refused() {
+    // No report at all (a bridge that predates the handshake, or a forged response)
+    assert_eq!(
+        code(verify_countdown_handshake(None, DIGEST, DELAY_MS)),
+        SignerErrorCode::CountdownNotDisplayed
+    );
+
+    // Confirmed without ever acking CountdownStarted
+    let never_acked = CountdownReport {
+        started_at_ms: None,
+        displayed_digest: None,
+        ..compliant_report(0.0, 300.0)
+    };
+    let (error_code, message) =
+        verify_countdown_handshake(Some(&never_acked), DIGEST, DELAY_MS).unwrap_err();
+    assert_eq!(error_code, SignerErrorCode::CountdownNotDisplayed);
+    assert!(message.contains("never acked"), "{}", message);
+
+    // Acked a digest it did not display
+    let other_digest = CountdownReport {
+        displayed_digest: Some("other-digest".to_string()),
+        ..compliant_report(50.0, 50.0 + DELAY_MS as f64)
+    };
+    assert_eq!(
+        code(verify_countdown_handshake(
+            Some(&other_digest),
+            DIGEST,
+            DELAY_MS
+        )),
+        SignerErrorCode::CountdownNotDisplayed
+    );
+
+    // Acked after the deadline without the requireClick fallback
+    let late_ack = compliant_report(
+        COUNTDOWN_ACK_TIMEOUT_MS as f64 + 1.0,
+        (COUNTDOWN_ACK_TIMEOUT_MS + DELAY_MS) as f64 + 1.0,
+    );
+    assert_eq!(
+        code(verify_countdown_handshake(
+            Some(&late_ack),
+            DIGEST,
+            DELAY_MS
+        )),
+        SignerErrorCode::CountdownNotDisplayed
+    );
+
+    // Released before the worker's own timer elapsed
+    let (error_code, message) = verify_countdown_handshake(
+        Some(&compliant_report(100.0, 100.0 + DELAY_MS as f64 - 200.0)),
+        DIGEST,
+        DELAY_MS,
+    )
+    .unwrap_err();
+    assert_eq!(error_code, SignerErrorCode::CountdownNotDisplayed);
+    assert!(message.contains("200ms before"), "{}", message);
+}
+
+#[test]
+fn test_cancel_and_require_click_fallback() {
+    // CancelConfirmation during the countdown declines, even with a valid ack
+    let cancelled = CountdownReport {
+        cancelled_at_ms: Some(PROMPTED_AT_MS + 900.0),
+        ..compliant_report(100.0, 100.0 + DELAY_MS as f64)
+    };
+    assert_eq!(
+        code(verify_countdown_handshake(
+            Some(&cancelled),
+            DIGEST,
+            DELAY_MS
+        )),
+        SignerErrorCode::UserDeclined
+    );
+
+    // No ack in time: the request proceeds as requireClick
+    let fallback = CountdownReport {
+        started_at_ms: None,
+        displayed_digest: None,
+        require_click: true,
+        ..compliant_report(0.0, 6_000.0)
+    };
+    assert_eq!(
+        verify_countdown_handshake(Some(&fallback), DIGEST, DELAY_MS),
+        Ok(CountdownOutcome::RequireClick)
+    );
+    // ... but a cancel still wins over the fallback
+    let cancelled_fallback = CountdownReport {
+        cancelled_at_ms: Some(PROMPTED_AT_MS + 3_000.0),
+        ..fallback
+    };
+    assert_eq!(
+        code(verify_countdown_handshake(
+            Some(&cancelled_fallback),
+            DIGEST,
+            DELAY_MS
+        )),
+        SignerErrorCode::UserDeclined
+    );
+}
+
+#[test]
+fn test_enforcement_applies_to_auto_proceed_configs_only() {
+    let config = auto_proceed_config();
+    assert_eq!(countdown_delay_ms(Some(&config)), Some(DELAY_MS));
+    let require_click = ConfirmationConfig::new(
+        ConfirmationUIMode::Modal,
+        ConfirmationBehavior::RequireClick,
+    );
+    assert_eq!(countdown_delay_ms(Some(&require_click)), None);
+    let skip = validate_and_normalize_confirmation_config(&ConfirmationConfig::new(
+        ConfirmationUIMode::Skip,
+        ConfirmationBehavior::RequireClick,
+    ));
+    assert_eq!(countdown_delay_ms(Some(&skip)), None);
+    assert_eq!(countdown_delay_ms(None), None);
+
+    // A non-compliant page's confirmation is turned into a coded decline
+    let mut logs = Vec::new();
+    let mut skipped = confirmed_result(None);
+    enforce_countdown_handshake(&mut skipped, Some(&config), DIGEST, &mut logs);
+    assert!(!skipped.confirmed);
+    assert_eq!(skipped.error_code.as_deref(), Some("CountdownNotDisplayed"));
+    assert!(skipped
+        .error
+        .unwrap()
+        .starts_with("Auto-proceed countdown not verified"));
+
+    let mut compliant = confirmed_result(Some(json!({
+        "prompted_at_ms": PROMPTED_AT_MS,
+        "started_at_ms": PROMPTED_AT_MS + 80.0,
+        "displayed_digest": DIGEST,
+        "released_at_ms": PROMPTED_AT_MS + 80.0 + DELAY_MS as f64,
+    })));
+    enforce_countdown_handshake(&mut compliant, Some(&config), DIGEST, &mut logs);
+    assert!(compliant.confirmed);
+    assert!(compliant.error_code.is_none());
+
+    // requireClick confirmations carry no handshake and are left alone
+    let mut clicked = confirmed_result(None);
+    enforce_countdown_handshake(&mut clicked, Some(&require_click), DIGEST, &mut logs);
+    assert!(clicked.confirmed);
+}
@@ -0,0 +1,336 @@
+use crate::credential_options::{prf_eval_salts, CredentialUser, PUB_KEY_CRED_ALGORITHMS};
+use crate::encoders::base64_url_encode;
+use crate::handlers::handle_build_credential_options::{
+    creation_options_for, registered_authenticators, registered_credentials, request_options_for,
+    BuildCredentialCreationOptionsRequest, BuildCredentialRequestOptionsRequest,
+    ExistingCredential, KnownAuthenticator,
+};
+use crate::rpc_client::MockRpcClient;
+use crate::tests::block_on;
+use serde_json::{json, Value};
+
+const ACCOUNT_ID: &str = "alice.testnet";
+const RP_ID: &str = "example.localhost";
+const CREATION_OPTIONS_SNAPSHOT: &str = include_str!("snapshots/credential_creation_options.json");
+const REQUEST_OPTIONS_SNAPSHOT: &str = include_str!("snapshots/credential_request_options.json");
+
+fn vrf_output() -> String {
+    base64_url_encode(&(0u8..64).collect::<Vec<u8>>())
+}
+
+fn vrf_challenge() -> Value {
+    json!({
+        "vrfInput": "aW5wdXQ",
+        "vrfOutput": vrf_output(),
+        "vrfProof": "cHJvb2Y",
+        "vrfPublicKey": "dnJmLXB1YmxpYy1rZXk",
+        "userId": ACCOUNT_ID,
+        "rpId": RP_ID,
+        "blockHeight": "1000",
+        "blockHash": "11111111111111111111111111111111"
+    })
+}
+
+fn request_payload() -> Value {
+    json!({
+        "registration": {
+            "nearAccountId": ACCOUNT_ID,
+            "nonce": "1",
+            "blockHash": "11111111111111111111111111111111",
+            "deviceNumber": 2,
+            "authenticatorOptions": { "user_verification": "required" }
+        },
+        "rpId": RP_ID,
+        "vrfChallenge": vrf_challenge(),
+        "existingCredentials": [
+            { "credentialId": "Y3JlZC0x", "transports": ["internal", "hybrid"] },
+            { "credentialId": "Y3JlZC0y" }
+        ]
+    })
+}
+
+fn request(payload: Value) -> BuildCredentialCreationOptionsRequest {
+    serde_json::from_value(payload).unwrap()
+}
+
+fn build(payload: Value) -> Result<Value, String> {
+    let request = request(payload);
+    let existing = request.existing_credentials.clone().unwrap_or_default();
+    creation_options_for(&request, &existing).map(|result| serde_json::to_value(result).unwrap())
+}
+
+#[test]
+fn test_creation_options_match_snapshot() {
+    // Compared as text, so member order is locked too
+    let request = request(request_payload());
+    let existing = request.existing_credentials.clone().unwrap();
+    let result = creation_options_for(&request, &existing).unwrap();
+    let actual = serde_json::to_string_pretty(&result).unwrap();
+    assert!(
+        actual == CREATION_OPTIONS_SNAPSHOT.trim_end(),
+        "Creation options differ from tests/snapshots/credential_creation_options.json; if \
+         intended, replace it with:\n{}",
+        actual
+    );
+
+    let actual = serde_json::to_value(&result).unwrap();
+    let algorithms: Vec<i64> = actual["publicKey"]["pubKeyCredParams"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|param| param["alg"].as_i64().unwrap())
+        .collect();
+    assert_eq!(algorithms, vec![-8, -7, -257]);
+    assert_eq!(PUB_KEY_CRED_ALGORITHMS, [-8, -7, -257]);
+}
+
+#[test]
+fn test_creation_options_defaults_and_overrides() {
+    let mut payload = request_payload();
+    payload["registration"]["deviceNumber"] = json!(1);
+    payload["registration"]
+        .as_object_mut()
+        .unwrap()
+        .remove("authenticatorOptions");
+    payload["existingCredentials"] = json!([]);
+    let options = build(payload.clone()).unwrap()["publicKey"].clone();
+    assert_eq!(options["user"]["name"], json!(ACCOUNT_ID));
+    assert_eq!(options["user"]["displayName"], json!("alice"));
+    assert_eq!(
+        options["user"]["id"],
+        json!(base64_url_encode(ACCOUNT_ID.as_bytes()))
+    );
+    assert_eq!(options["rp"]["name"], json!("WebAuthn VRF Passkey"));
+    assert_eq!(
+        options["authenticatorSelection"]["userVerification"],
+        json!("preferred")
+    );
+    assert_eq!(options["excludeCredentials"], json!([]));
+
+    payload["rpName"] = json!("Example");
+    payload["userName"] = json!("alice@example");
+    payload["userDisplayName"] = json!("Alice");
+    let options = build(payload).unwrap()["publicKey"].clone();
+    assert_eq!(options["rp"]["name"], json!("Example"));
+    assert_eq!(options["user"]["name"], json!("alice@example"));
+    assert_eq!(options["user"]["displayName"], json!("Alice"));
+
+    let user = CredentialUser {
+        near_account_id: "bob.w3a-v1.testnet".to_string(),
+        device_number: Some(3),
+        name: None,
+        display_name: None,
+    };
+    assert_eq!(user.device_specific_name(), "bob.w3a-v1.testnet (3)");
+    assert_eq!(user.friendly_display_name(), "bob (device 3)");
+}
+
+#[test]
+fn test_prf_salts_are_prefixed_and_fixed_length() {
+    let (first, second) = prf_eval_salts(ACCOUNT_ID);
+    assert_eq!(&first[..27], b"chacha20-salt:alice.testnet");
+    assert!(first[27..].iter().all(|b| *b == 0));
+    assert_eq!(&second[..26], b"ed25519-salt:alice.testnet");
+
+    // Long account ids are truncated, not hashed
+    let long_id = "a".repeat(64);
+    let (first, _) = prf_eval_salts(&long_id);
+    assert_eq!(&first[..14], b"chacha20-salt:");
+    assert!(first[14..].iter().all(|b| *b == b'a'));
+}
+
+#[test]
+fn test_creation_options_reject_mismatched_inputs() {
+    let mut payload = request_payload();
+    payload["vrfChallenge"]["userId"] = json!("mallory.testnet");
+    assert_eq!(
+        build(payload).unwrap_err(),
+        "VRF challenge is for user mallory.testnet, not alice.testnet"
+    );
+
+    let mut payload = request_payload();
+    payload["rpId"] = json!("other.localhost");
+    assert_eq!(
+        build(payload).unwrap_err(),
+        "VRF challenge is for rpId example.localhost, not other.localhost"
+    );
+
+    let mut payload = request_payload();
+    payload["rpId"] = json!("");
+    assert!(build(payload).is_err());
+
+    let mut payload = request_payload();
+    payload["existingCredentials"] = json!([{ "credentialId": "not base64url!" }]);
+    assert_eq!(
+        build(payload).unwrap_err(),
+        "Credential id is not base64url: not base64url!"
+    );
+}
+
+fn authenticators_response() -> Value {
+    let entries = json!([
+        ["Y3JlZC0x", { "credential_public_key": [1], "transports": ["internal"],
+                       "registered": "2024-01-01", "device_number": 1 }],
+        ["Y3JlZC0y", { "credential_public_key": [2], "transports": null,
+                       "registered": "2024-02-01", "device_number": 2 }]
+    ]);
+    let bytes: Vec<u8> = entries.to_string().into_bytes();
+    json!({ "result": bytes, "logs": [], "block_height": 100 })
+}
+
+#[test]
+fn test_registered_credentials_come_from_the_contract() {
+    let rpc = MockRpcClient::new().answer(
+        "query/call_function/get_authenticators_by_user",
+        Ok(authenticators_response()),
+    );
+    let existing = block_on(registered_credentials(&rpc, "w3a-v1.testnet", ACCOUNT_ID)).unwrap();
+    assert_eq!(
+        existing,
+        vec![
+            ExistingCredential {
+                credential_id: "Y3JlZC0x".to_string(),
+                transports: vec!["internal".to_string()],
+            },
+            ExistingCredential {
+                credential_id: "Y3JlZC0y".to_string(),
+                transports: vec![],
+            },
+        ]
+    );
+
+    let mut payload = request_payload();
+    payload
+        .as_object_mut()
+        .unwrap()
+        .remove("existingCredentials");
+    let options = creation_options_for(&request(payload), &existing).unwrap();
+    assert_eq!(options.public_key.exclude_credentials.len(), 2);
+    assert_eq!(
+        options.public_key.exclude_credentials[0].transports,
+        vec!["internal".to_string()]
+    );
+}
+
+fn assertion_payload() -> Value {
+    json!({
+        "nearAccountId": ACCOUNT_ID,
+        "rpId": RP_ID,
+        "vrfChallenge": vrf_challenge(),
+        "authenticators": [
+            { "credentialId": "Y3JlZC0x", "transports": ["internal"], "deviceNumber": 1,
+              "residentKey": true },
+            { "credentialId": "Y3JlZC0y", "deviceNumber": 2 }
+        ],
+        "userVerification": "required"
+    })
+}
+
+fn assertion_request(payload: Value) -> BuildCredentialRequestOptionsRequest {
+    serde_json::from_value(payload).unwrap()
+}
+
+fn build_assertion(payload: Value) -> Result<Value, String> {
+    let request = assertion_request(payload);
+    let authenticators = request.authenticators.clone().unwrap_or_default();
+    request_options_for(&request, &authenticators)
+        .map(|result| serde_json::to_value(result).unwrap())
+}
+
+fn allowed_ids(result: &Value) -> Vec<String> {
+    result["publicKey"]["allowCredentials"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|credential| credential["id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_request_options_match_snapshot() {
+    // Compared as text, so member order is locked too
+    let request = assertion_request(assertion_payload());
+    let authenticators = request.authenticators.clone().unwrap();
+    let result = request_options_for(&request, &authenticators).unwrap();
+    assert!(!result.discoverable);
+    let actual = serde_json::to_string_pretty(&result).unwrap();
+    assert!(
+        actual == REQUEST_OPTIONS_SNAPSHOT.trim_end(),
+        "Request options differ from tests/snapshots/credential_request_options.json; if \
+         intended, replace it with:\n{}",
+        actual
+    );
+}
+
+#[test]
+fn test_request_options_device_filter() {
+    let mut payload = assertion_payload();
+    payload["deviceNumber"] = json!(2);
+    let result = build_assertion(payload.clone()).unwrap();
+    assert_eq!(allowed_ids(&result), vec!["Y3JlZC0y"]);
+
+    // Device 1's passkey is discoverable, but a device filter still lists it
+    payload["deviceNumber"] = json!(1);
+    let result = build_assertion(payload.clone()).unwrap();
+    assert_eq!(result["discoverable"], json!(false));
+    assert_eq!(allowed_ids(&result), vec!["Y3JlZC0x"]);
+
+    payload["deviceNumber"] = json!(3);
+    assert_eq!(
+        build_assertion(payload).unwrap_err(),
+        "No credential registered for device 3 of alice.testnet"
+    );
+
+    let mut payload = assertion_payload();
+    payload["authenticators"] = json!([]);
+    assert_eq!(
+        build_assertion(payload).unwrap_err(),
+        "No credentials to allow for alice.testnet"
+    );
+}
+
+#[test]
+fn test_request_options_discoverable_mode() {
+    // Every authenticator reported rk=true: allowCredentials is omitted
+    let mut payload = assertion_payload();
+    payload["authenticators"][1]["residentKey"] = json!(true);
+    let result = build_assertion(payload.clone()).unwrap();
+    assert_eq!(result["discoverable"], json!(true));
+    assert!(result["publicKey"].get("allowCredentials").is_none());
+    assert_eq!(result["publicKey"]["rpId"], json!(RP_ID));
+
+    // Explicit choices win over the stored credProps
+    payload["discoverable"] = json!(false);
+    let result = build_assertion(payload).unwrap();
+    assert_eq!(allowed_ids(&result), vec!["Y3JlZC0x", "Y3JlZC0y"]);
+
+    let mut payload = assertion_payload();
+    payload["discoverable"] = json!(true);
+    let result = build_assertion(payload).unwrap();
+    assert!(result["publicKey"].get("allowCredentials").is_none());
+
+    // Unknown rk (fetched from the contract) keeps allowCredentials
+    let rpc = MockRpcClient::new().answer(
+        "query/call_function/get_authenticators_by_user",
+        Ok(authenticators_response()),
+    );
+    let fetched = block_on(registered_authenticators(
+        &rpc,
+        "w3a-v1.testnet",
+        ACCOUNT_ID,
+    ))
+    .unwrap();
+    assert_eq!(
+        fetched[0],
+        KnownAuthenticator {
+            credential_id: "Y3JlZC0x".to_string(),
+            transports: vec!["internal".to_string()],
+            device_number: 1,
+            resident_key: None,
+        }
+    );
+    let request = assertion_request(assertion_payload());
+    let result = request_options_for(&request, &fetched).unwrap();
+    assert!(!result.discoverable);
+    assert_eq!(result.public_key.allow_credentials.unwrap().len(), 2);
+}
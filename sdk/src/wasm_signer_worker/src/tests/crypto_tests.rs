@@ -94,6 +94,108 @@ fn test_decrypt_chacha20_invalid_data() {
     assert!(result.is_err());
 }
 
+/// Blob as written before the key-check header: bare ChaCha20Poly1305 ciphertext
+fn legacy_encrypt(plain_text: &str, key: &[u8]) -> (String, String) {
+    use chacha20poly1305::aead::{Aead, KeyInit};
+    let cipher = chacha20poly1305::ChaCha20Poly1305::new(chacha20poly1305::Key::from_slice(key));
+    let nonce = [9u8; 12];
+    let ciphertext = cipher
+        .encrypt(
+            chacha20poly1305::Nonce::from_slice(&nonce),
+            plain_text.as_bytes(),
+        )
+        .unwrap();
+    (
+        crate::encoders::base64_url_encode(&ciphertext),
+        crate::encoders::base64_url_encode(&nonce),
+    )
+}
+
+#[test]
+fn test_key_check_header_classifies_decryption_failures() {
+    use crate::encoders::{base64_url_decode, base64_url_encode};
+    use crate::error::{BlobDecryptError, SignerErrorCode};
+
+    let key = vec![3u8; 32];
+    let wrong_key = vec![4u8; 32];
+    let encrypted = encrypt_data_chacha20("ed25519:secret", &key).unwrap();
+    let data = &encrypted.encrypted_near_key_data_b64u;
+    let nonce = &encrypted.chacha20_nonce_b64u;
+
+    let bytes = base64_url_decode(data).unwrap();
+    let (key_check, _) = split_key_check_header(&bytes).expect("new blobs carry the header");
+    assert_eq!(key_check.len(), 8);
+
+    assert_eq!(
+        decrypt_data_chacha20(data, nonce, &key).unwrap(),
+        "ed25519:secret"
+    );
+    let err = decrypt_data_chacha20(data, nonce, &wrong_key).unwrap_err();
+    assert_eq!(err, BlobDecryptError::WrongCredentialForBlob);
+    assert_eq!(err.code(), Some(SignerErrorCode::WrongCredentialForBlob));
+    assert!(
+        err.to_string().starts_with("WrongCredentialForBlob"),
+        "{}",
+        err
+    );
+
+    // Damage past the header: the key still checks out, so the data is at fault
+    let mut damaged = bytes.clone();
+    let last = damaged.len() - 1;
+    damaged[last] ^= 0x01;
+    let err = decrypt_data_chacha20(&base64_url_encode(&damaged), nonce, &key).unwrap_err();
+    assert_eq!(err.code(), Some(SignerErrorCode::CorruptedCiphertext));
+
+    // The header is authenticated: a rewritten key-check value does not decrypt
+    let mut forged = bytes.clone();
+    forged[4] ^= 0x01;
+    assert!(decrypt_data_chacha20(&base64_url_encode(&forged), nonce, &key).is_err());
+
+    // The key-check value is per blob, not a fingerprint of the key
+    let again = encrypt_data_chacha20("ed25519:secret", &key).unwrap();
+    let again_bytes = base64_url_decode(&again.encrypted_near_key_data_b64u).unwrap();
+    assert_ne!(split_key_check_header(&again_bytes).unwrap().0, key_check);
+
+    // Legacy blobs still decrypt, and failures stay unclassified
+    let (legacy_data, legacy_nonce) = legacy_encrypt("ed25519:secret", &key);
+    assert_eq!(
+        decrypt_data_chacha20(&legacy_data, &legacy_nonce, &key).unwrap(),
+        "ed25519:secret"
+    );
+    let err = decrypt_data_chacha20(&legacy_data, &legacy_nonce, &wrong_key).unwrap_err();
+    assert_eq!(err.code(), Some(SignerErrorCode::DecryptionFailed));
+}
+
+#[test]
+fn test_decrypt_private_key_reports_wrong_credential() {
+    use crate::error::SignerErrorCode;
+
+    let account_id = "test.testnet";
+    let dual_prf = DualPrfOutputs {
+        chacha20_prf_output_base64: "Y2hhY2hhMjAtcHJmLW91dHB1dA".to_string(),
+        ed25519_prf_output_base64: "ZWQyNTUxOS1wcmYtb3V0cHV0".to_string(),
+    };
+    let (_, encrypted) = derive_and_encrypt_keypair_from_dual_prf(&dual_prf, account_id).unwrap();
+
+    assert!(decrypt_private_key_with_prf(
+        account_id,
+        &dual_prf.chacha20_prf_output_base64,
+        &encrypted.encrypted_near_key_data_b64u,
+        &encrypted.chacha20_nonce_b64u,
+    )
+    .is_ok());
+
+    // Another passkey's PRF output
+    let err = decrypt_private_key_with_prf(
+        account_id,
+        "b3RoZXItcGFzc2tleS1wcmY",
+        &encrypted.encrypted_near_key_data_b64u,
+        &encrypted.chacha20_nonce_b64u,
+    )
+    .unwrap_err();
+    assert_eq!(err.code(), Some(SignerErrorCode::WrongCredentialForBlob));
+}
+
 #[test]
 fn test_decrypt_private_key_with_prf_invalid_formats() {
     let prf_output_b64 = "dGVzdC1wcmYtb3V0cHV0";
@@ -311,3 +413,87 @@ fn test_dual_prf_edge_cases() {
     let result = derive_and_encrypt_keypair_from_dual_prf(&invalid_dual_prf, account_id);
     assert!(result.is_err());
 }
+
+// vrfOutput of vector 0 in wasm_vrf_worker/test_vectors/vrf_input_vectors.json
+const GOLDEN_VRF_OUTPUT: &str =
+    "XjlYUN-J3STGS3qNK6BYVM8hIMrhUHCa4kEYwCksQM_tFa6K6J_UznE4eMwsvS_BXf4NIVklYkdD1ar_8bMe8A";
+
+fn golden_vrf_challenge(challenge_length: Option<u8>) -> crate::types::VrfChallenge {
+    let mut json = serde_json::json!({
+        "vrfInput": "aW5wdXQ",
+        "vrfOutput": GOLDEN_VRF_OUTPUT,
+        "vrfProof": "cHJvb2Y",
+        "vrfPublicKey": "cGs",
+        "userId": "vector-0.testnet",
+        "rpId": "example.localhost",
+        "blockHeight": "1",
+        "blockHash": "aGFzaA"
+    });
+    if let Some(length) = challenge_length {
+        json["challengeLength"] = serde_json::json!(length);
+    }
+    serde_json::from_value(json).unwrap()
+}
+
+fn client_data_json_b64u(challenge: &[u8]) -> String {
+    let client_data = serde_json::json!({
+        "type": "webauthn.get",
+        "challenge": crate::encoders::base64_url_encode(challenge),
+        "origin": "https://example.localhost"
+    });
+    crate::encoders::base64_url_encode(client_data.to_string().as_bytes())
+}
+
+#[test]
+fn test_default_challenge_binding_is_contract_compatible_golden() {
+    // Payloads without challengeLength use the contract-compatible default
+    let vrf_challenge = golden_vrf_challenge(None);
+    assert_eq!(vrf_challenge.challenge_length, 32);
+
+    let vrf_output = crate::encoders::base64_url_decode(GOLDEN_VRF_OUTPUT).unwrap();
+    let challenge = derive_webauthn_challenge(&vrf_output, 32).unwrap();
+    assert_eq!(
+        crate::encoders::base64_url_encode(&challenge),
+        "XjlYUN-J3STGS3qNK6BYVM8hIMrhUHCa4kEYwCksQM8"
+    );
+
+    assert!(
+        verify_vrf_challenge_binding(&vrf_challenge, &client_data_json_b64u(&challenge)).is_ok()
+    );
+    // A WebAuthn challenge for a different length must not bind
+    let sha_challenge = derive_webauthn_challenge(&vrf_output, 16).unwrap();
+    assert!(
+        verify_vrf_challenge_binding(&vrf_challenge, &client_data_json_b64u(&sha_challenge))
+            .is_err()
+    );
+}
+
+#[test]
+fn test_challenge_binding_honors_challenge_length() {
+    let vrf_output = crate::encoders::base64_url_decode(GOLDEN_VRF_OUTPUT).unwrap();
+
+    let vrf_challenge = golden_vrf_challenge(Some(48));
+    let challenge = derive_webauthn_challenge(&vrf_output, 48).unwrap();
+    assert_eq!(
+        crate::encoders::base64_url_encode(&challenge),
+        "td3iIkfvpIeWflxZwB-perySfctf0AQehEZ1mqxb79Nboa5aKi4XsvqKhAel5fB5"
+    );
+    assert!(
+        verify_vrf_challenge_binding(&vrf_challenge, &client_data_json_b64u(&challenge)).is_ok()
+    );
+    assert!(verify_vrf_challenge_binding(
+        &vrf_challenge,
+        &client_data_json_b64u(&vrf_output[..32])
+    )
+    .is_err());
+
+    // Out-of-range lengths are rejected
+    for length in [0u8, 15, 65] {
+        assert!(derive_webauthn_challenge(&vrf_output, length).is_err());
+        let vrf_challenge = golden_vrf_challenge(Some(length));
+        assert!(
+            verify_vrf_challenge_binding(&vrf_challenge, &client_data_json_b64u(&[0u8; 16]))
+                .is_err()
+        );
+    }
+}
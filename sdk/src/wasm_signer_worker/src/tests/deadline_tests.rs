@@ -0,0 +1,163 @@
+use crate::actions::ActionParams;
+use crate::bench::*;
+use crate::confirmation_blocks::*;
+use crate::error::{DeadlineError, SignerErrorCode};
+use crate::handlers::confirm_tx_details::{
+    compute_confirmation_intent_digest, confirmation_tx_signing_requests_json,
+};
+use crate::handlers::handle_sign_transactions_with_actions::{
+    check_deadline, sign_near_transactions_with_actions_impl, TransactionSignResult,
+};
+use crate::rpc_client::MockRpcClient;
+use crate::tests::block_on;
+use serde_json::json;
+
+fn block_answer(height: u64) -> Result<serde_json::Value, crate::error::RpcErrorKind> {
+    Ok(json!({ "header": { "height": height, "hash": bs58::encode([9u8; 32]).into_string() } }))
+}
+
+fn sign_with_deadline(
+    batch: &ConfirmedBatch,
+    rpc: &MockRpcClient,
+    valid_until: u64,
+) -> TransactionSignResult {
+    block_on(sign_near_transactions_with_actions_impl(
+        batch.tx_requests.clone(),
+        &batch.decryption,
+        &batch.confirmation,
+        Some(rpc),
+        Some(valid_until),
+        None,
+        Vec::new(),
+    ))
+    .unwrap()
+}
+
+#[test]
+fn test_check_deadline_allows_the_deadline_block_itself() {
+    assert_eq!(check_deadline(100, 99), Ok(()));
+    assert_eq!(check_deadline(100, 100), Ok(()));
+    let exceeded = check_deadline(100, 101).unwrap_err();
+    assert_eq!(
+        exceeded,
+        DeadlineError::DeadlineExceeded {
+            valid_until: 100,
+            current: 101
+        }
+    );
+    assert_eq!(exceeded.code(), SignerErrorCode::DeadlineExceeded);
+    assert_eq!(
+        exceeded.to_string(),
+        "DeadlineExceeded: valid until block 100, the chain is at block 101"
+    );
+}
+
+#[test]
+fn test_deadline_is_shown_and_covered_by_the_digest() {
+    let batch = vec![(
+        "bob.testnet".to_string(),
+        vec![ActionParams::Transfer {
+            deposit: "1".to_string(),
+        }],
+    )];
+    let flags = vec![Some(false)];
+    let policy = SummaryPolicy::default();
+    let with_deadline = policy.clone().with_deadline(Some(5_000));
+
+    let blocks =
+        transaction_summary_blocks("bob.testnet", &batch[0].1, Some(false), &with_deadline);
+    // Right after the receiver row
+    assert_eq!(
+        blocks[1],
+        ConfirmationSummaryBlock::DeadlineRow {
+            label: "Valid until block".to_string(),
+            block_height: 5_000,
+            aria_label: "Valid until block five thousand".to_string(),
+        }
+    );
+    let json = confirmation_tx_signing_requests_json(&batch, &flags, None, Some(&with_deadline));
+    assert_eq!(
+        json[0]["summaryBlocks"][1],
+        json!({
+            "kind": "deadlineRow",
+            "label": "Valid until block",
+            "blockHeight": 5_000,
+            "ariaLabel": "Valid until block five thousand"
+        })
+    );
+    assert!(
+        !transaction_summary_blocks("bob.testnet", &batch[0].1, Some(false), &policy)
+            .iter()
+            .any(|block| matches!(block, ConfirmationSummaryBlock::DeadlineRow { .. }))
+    );
+
+    let digest = |policy: &SummaryPolicy| {
+        compute_confirmation_intent_digest(&batch, &flags, None, Some(policy)).unwrap()
+    };
+    assert_ne!(digest(&policy), digest(&with_deadline));
+    assert_ne!(
+        digest(&with_deadline),
+        digest(&policy.with_deadline(Some(5_001)))
+    );
+}
+
+#[test]
+fn test_broadcast_within_the_deadline_checks_each_send() {
+    let batch = confirmed_batch_of_5();
+    let rpc = MockRpcClient::new()
+        .answer("block", block_answer(1_000))
+        .answer(
+            "broadcast_tx_commit",
+            Ok(json!({ "status": { "SuccessValue": "" } })),
+        );
+
+    let result = sign_with_deadline(&batch, &rpc, 1_000);
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(rpc.calls_to("block").len(), 5);
+    assert_eq!(
+        rpc.calls_to("block")[0],
+        json!({ "finality": "optimistic" })
+    );
+    assert_eq!(rpc.calls_to("broadcast_tx_commit").len(), 5);
+}
+
+#[test]
+fn test_broadcast_past_the_deadline_is_refused() {
+    let batch = confirmed_batch_of_5();
+    // The chain passes the deadline after two transactions were sent
+    let rpc = MockRpcClient::new()
+        .answer("block", block_answer(999))
+        .answer("block", block_answer(1_000))
+        .answer("block", block_answer(1_001))
+        .answer(
+            "broadcast_tx_commit",
+            Ok(json!({ "status": { "SuccessValue": "" } })),
+        );
+
+    let result = sign_with_deadline(&batch, &rpc, 1_000);
+    assert!(!result.success);
+    assert_eq!(result.error_code.as_deref(), Some("DeadlineExceeded"));
+    assert_eq!(result.retriable, Some(false));
+    assert_eq!(
+        result.error.as_deref(),
+        Some("Transaction 3: DeadlineExceeded: valid until block 1000, the chain is at block 1001")
+    );
+    assert_eq!(rpc.calls_to("broadcast_tx_commit").len(), 2);
+    // The two sent transactions are reported with the refusal
+    assert_eq!(result.transaction_hashes.as_ref().unwrap().len(), 2);
+    assert_eq!(result.broadcast_outcomes.as_ref().unwrap().len(), 2);
+}
+
+#[test]
+fn test_unknown_chain_height_refuses_the_broadcast() {
+    let batch = confirmed_batch_of_5();
+    let rpc = MockRpcClient::new().answer("block", Ok(json!({}))).answer(
+        "broadcast_tx_commit",
+        Ok(json!({ "status": { "SuccessValue": "" } })),
+    );
+
+    let result = sign_with_deadline(&batch, &rpc, 1_000);
+    assert!(!result.success);
+    assert_eq!(result.error_code.as_deref(), Some("RpcResponseInvalid"));
+    assert!(rpc.calls_to("broadcast_tx_commit").is_empty());
+}
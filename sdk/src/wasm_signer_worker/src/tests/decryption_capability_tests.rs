@@ -0,0 +1,117 @@
+use crate::crypto::{
+    derive_and_encrypt_keypair_from_dual_prf, derive_chacha20_key_from_prf, encrypt_data_chacha20,
+};
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::handlers::handle_validate_decryption_capability::{
+    handle_validate_decryption_capability, DecryptionCapability,
+    ValidateDecryptionCapabilityRequest,
+};
+use crate::tests::block_on;
+use crate::types::handlers::DecryptionPayload;
+use crate::types::worker_messages::WorkerRequestType;
+use crate::types::DualPrfOutputs;
+
+const ACCOUNT_ID: &str = "alice.testnet";
+const CHACHA20_PRF_OUTPUT: &str = "Y2hhY2hhMjAtcHJmLW91dHB1dA";
+
+fn stored_blob() -> DecryptionPayload {
+    let dual_prf = DualPrfOutputs {
+        chacha20_prf_output_base64: CHACHA20_PRF_OUTPUT.to_string(),
+        ed25519_prf_output_base64: "ZWQyNTUxOS1wcmYtb3V0cHV0".to_string(),
+    };
+    let (_, encrypted) = derive_and_encrypt_keypair_from_dual_prf(&dual_prf, ACCOUNT_ID).unwrap();
+    DecryptionPayload::new(
+        encrypted.encrypted_near_key_data_b64u,
+        encrypted.chacha20_nonce_b64u,
+    )
+}
+
+fn check(prf_output: &str, decryption: DecryptionPayload) -> Result<DecryptionCapability, String> {
+    block_on(handle_validate_decryption_capability(
+        ValidateDecryptionCapabilityRequest {
+            near_account_id: ACCOUNT_ID.to_string(),
+            chacha20_prf_output: prf_output.to_string(),
+            decryption,
+        },
+    ))
+}
+
+fn reason(capability: DecryptionCapability) -> Option<String> {
+    assert_eq!(capability.can_decrypt, capability.reason.is_none());
+    capability.reason
+}
+
+#[test]
+fn test_blob_decrypts_with_its_credential() {
+    let capability = check(CHACHA20_PRF_OUTPUT, stored_blob()).unwrap();
+    assert_eq!(
+        capability,
+        DecryptionCapability {
+            can_decrypt: true,
+            reason: None,
+        }
+    );
+    let json = serde_json::to_value(&capability).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({ "canDecrypt": true, "reason": null })
+    );
+}
+
+#[test]
+fn test_key_check_value_tells_wrong_credential_from_corruption() {
+    // Another passkey's PRF output
+    let capability = check("b3RoZXItcGFzc2tleS1wcmY", stored_blob()).unwrap();
+    assert_eq!(
+        reason(capability).as_deref(),
+        Some("WrongCredentialForBlob")
+    );
+
+    // The right passkey, but damaged ciphertext
+    let blob = stored_blob();
+    let mut data = base64_url_decode(&blob.encrypted_private_key_data).unwrap();
+    let last = data.len() - 1;
+    data[last] ^= 0x01;
+    let damaged = DecryptionPayload::new(base64_url_encode(&data), blob.encrypted_private_key_iv);
+    let capability = check(CHACHA20_PRF_OUTPUT, damaged).unwrap();
+    assert_eq!(reason(capability).as_deref(), Some("CorruptedCiphertext"));
+}
+
+#[test]
+fn test_plaintext_must_be_a_private_key() {
+    let key = derive_chacha20_key_from_prf(CHACHA20_PRF_OUTPUT, ACCOUNT_ID).unwrap();
+    let encrypted = encrypt_data_chacha20("ed25519:notakey", &key).unwrap();
+    let blob = DecryptionPayload::new(
+        encrypted.encrypted_near_key_data_b64u,
+        encrypted.chacha20_nonce_b64u,
+    );
+    let capability = check(CHACHA20_PRF_OUTPUT, blob).unwrap();
+    assert_eq!(reason(capability).as_deref(), Some("InvalidPlaintext"));
+}
+
+#[test]
+fn test_request_without_usable_prf_output_fails() {
+    let err = check("", stored_blob()).unwrap_err();
+    assert_eq!(err, "Missing or invalid PRF output");
+    assert!(check("not base64url!", stored_blob()).is_err());
+
+    let err = block_on(handle_validate_decryption_capability(
+        ValidateDecryptionCapabilityRequest {
+            near_account_id: String::new(),
+            chacha20_prf_output: CHACHA20_PRF_OUTPUT.to_string(),
+            decryption: stored_blob(),
+        },
+    ))
+    .unwrap_err();
+    assert_eq!(err, "Missing NEAR account ID");
+}
+
+#[test]
+fn test_dry_run_is_admitted_like_a_decryption() {
+    // Occupies a registry slot (TooManyPendingRequests when full) and is refused after a
+    // failed self-test, like the decryptions it stands in for
+    let request_type = WorkerRequestType::ValidateDecryptionCapability;
+    assert!(request_type.is_tracked());
+    assert!(request_type.handles_keys());
+    assert_eq!(WorkerRequestType::from(38), request_type);
+}
@@ -0,0 +1,234 @@
+use crate::crypto::{
+    decrypt_private_key_with_prf, derive_and_encrypt_keypair_from_dual_prf,
+    derive_chacha20_key_from_prf,
+};
+use crate::deprecations;
+use crate::dispatch_signer_message;
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::{BlobDecryptError, SealedSecretError, SignerErrorCode};
+use crate::error_codes::{
+    find_deprecation, lookup_deprecation, DEPRECATIONS, LEGACY_KEY_BLOB,
+    PLAINTEXT_CONFIRMATION_SECRET, PLAINTEXT_SECRET,
+};
+use crate::handlers::confirm_tx_details::ConfirmationResult;
+use crate::sealed_secrets::{open_confirmation_secrets, seal_secret, session_public_key};
+use crate::state;
+use crate::strict_parsing::check_unknown_fields;
+use crate::tests::{block_on, elevation_assertion};
+use crate::types::worker_messages::{
+    SignerWorkerMessage, SignerWorkerResponse, WorkerRequestType, WorkerResponseType,
+};
+use crate::types::DualPrfOutputs;
+use crate::wire_format::encode_json_response;
+use serde_json::{json, Value};
+
+const ACCOUNT_ID: &str = "alice.testnet";
+const CHACHA20_PRF_OUTPUT: &str = "Y2hhY2hhMjAtcHJmLW91dHB1dA";
+const DECRYPT_PRIVATE_KEY_WITH_PRF: u32 = 3;
+const PRIVATE_KEY: &str =
+    "ed25519:3D4YudUahN1nawWogh8pAKSj92sUNMdbZGjn7kERKzYoTy8tnFQuwoGUC51DowKqorvkr2pytJSnwuSbsNVfqygr";
+
+/// (encryptedPrivateKeyData, encryptedPrivateKeyIv) as written before the key-check header
+fn legacy_blob() -> (String, String) {
+    use chacha20poly1305::aead::{Aead, KeyInit};
+    let key = derive_chacha20_key_from_prf(CHACHA20_PRF_OUTPUT, ACCOUNT_ID).unwrap();
+    let cipher = chacha20poly1305::ChaCha20Poly1305::new(chacha20poly1305::Key::from_slice(&key));
+    let nonce = [7u8; 12];
+    let ciphertext = cipher
+        .encrypt(
+            chacha20poly1305::Nonce::from_slice(&nonce),
+            PRIVATE_KEY.as_bytes(),
+        )
+        .unwrap();
+    (base64_url_encode(&ciphertext), base64_url_encode(&nonce))
+}
+
+fn current_blob() -> (String, String) {
+    let dual_prf = DualPrfOutputs {
+        chacha20_prf_output_base64: CHACHA20_PRF_OUTPUT.to_string(),
+        ed25519_prf_output_base64: "ZWQyNTUxOS1wcmYtb3V0cHV0".to_string(),
+    };
+    let (_, encrypted) = derive_and_encrypt_keypair_from_dual_prf(&dual_prf, ACCOUNT_ID).unwrap();
+    (
+        encrypted.encrypted_near_key_data_b64u,
+        encrypted.chacha20_nonce_b64u,
+    )
+}
+
+/// PRF output sealed to the session key, as the TS layer sends it
+fn sealed_prf_output() -> Value {
+    let session_key: [u8; 32] = base64_url_decode(&session_public_key().unwrap())
+        .unwrap()
+        .try_into()
+        .unwrap();
+    json!({
+        "/chacha20PrfOutput":
+            seal_secret(&session_key, "/chacha20PrfOutput", CHACHA20_PRF_OUTPUT).unwrap()
+    })
+}
+
+fn decrypt(blob: (String, String), secret: Value, worker_policy: Value) -> SignerWorkerResponse {
+    let mut payload = json!({
+        "nearAccountId": ACCOUNT_ID,
+        "encryptedPrivateKeyData": blob.0,
+        "encryptedPrivateKeyIv": blob.1,
+        "workerPolicy": worker_policy
+    });
+    payload
+        .as_object_mut()
+        .unwrap()
+        .extend(secret.as_object().unwrap().clone());
+    // The assertion the PRF output came from (see elevation.rs)
+    payload["credential"] = elevation_assertion(ACCOUNT_ID);
+    block_on(dispatch_signer_message(SignerWorkerMessage {
+        msg_type: DECRYPT_PRIVATE_KEY_WITH_PRF,
+        payload,
+        request_id: None,
+    }))
+    .unwrap()
+}
+
+fn warning_codes(response: &SignerWorkerResponse) -> Vec<String> {
+    response.warnings.iter().map(|w| w.code.clone()).collect()
+}
+
+fn plaintext_confirmation() -> ConfirmationResult {
+    serde_json::from_value(json!({
+        "request_id": "req-plain",
+        "confirmed": true,
+        "prf_output": CHACHA20_PRF_OUTPUT
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_each_deprecated_path_emits_its_code() {
+    state::wipe_all_state();
+    let plaintext = json!({ "chacha20PrfOutput": CHACHA20_PRF_OUTPUT });
+
+    // Sealed PRF output, current blob: no warnings, and none in the envelope
+    let response = decrypt(
+        current_blob(),
+        json!({ "sealedSecrets": sealed_prf_output() }),
+        json!({}),
+    );
+    assert_eq!(
+        WorkerResponseType::from(response.response_type),
+        WorkerResponseType::DecryptPrivateKeyWithPrfSuccess
+    );
+    assert!(response.warnings.is_empty());
+    let frame: Value = serde_json::from_str(&encode_json_response(&response).unwrap()).unwrap();
+    assert!(frame.get("warnings").is_none());
+
+    let response = decrypt(current_blob(), plaintext.clone(), json!({}));
+    assert_eq!(warning_codes(&response), vec!["PlaintextSecret"]);
+
+    let response = decrypt(
+        legacy_blob(),
+        json!({ "sealedSecrets": sealed_prf_output() }),
+        json!({}),
+    );
+    assert_eq!(
+        WorkerResponseType::from(response.response_type),
+        WorkerResponseType::DecryptPrivateKeyWithPrfSuccess
+    );
+    assert_eq!(warning_codes(&response), vec!["LegacyKeyBlob"]);
+
+    // Both, in the order they were taken; the envelope carries the catalog entries
+    let response = decrypt(legacy_blob(), plaintext, json!({}));
+    assert_eq!(
+        warning_codes(&response),
+        vec!["PlaintextSecret", "LegacyKeyBlob"]
+    );
+    let frame: Value = serde_json::from_str(&encode_json_response(&response).unwrap()).unwrap();
+    assert_eq!(
+        frame["warnings"][1],
+        json!({
+            "code": "LegacyKeyBlob",
+            "message": LEGACY_KEY_BLOB.message,
+            "removeAfterVersion": LEGACY_KEY_BLOB.remove_after_version
+        })
+    );
+
+    deprecations::begin_request(&json!({}));
+    open_confirmation_secrets(&mut plaintext_confirmation()).unwrap();
+    assert_eq!(
+        deprecations::take_warnings(),
+        vec![PLAINTEXT_CONFIRMATION_SECRET.warning()]
+    );
+    state::wipe_all_state();
+}
+
+#[test]
+fn test_fail_on_deprecated_refuses_each_path() {
+    state::wipe_all_state();
+    let fail = json!({ "failOnDeprecated": true });
+
+    let response = decrypt(
+        current_blob(),
+        json!({ "chacha20PrfOutput": CHACHA20_PRF_OUTPUT }),
+        fail.clone(),
+    );
+    assert_eq!(
+        WorkerResponseType::from(response.response_type),
+        WorkerResponseType::DecryptPrivateKeyWithPrfFailure
+    );
+    assert_eq!(response.payload["errorCode"], json!("DeprecatedUsage"));
+    assert_eq!(response.payload["errorCategory"], json!("Policy"));
+    assert!(response.payload["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("DeprecatedUsage: PlaintextSecret: "));
+    assert!(response.warnings.is_empty());
+
+    // Handler-side paths are refused before they are taken: the legacy blob is not decrypted
+    deprecations::begin_request(&json!({ "workerPolicy": fail }));
+    let (data, iv) = legacy_blob();
+    let error =
+        decrypt_private_key_with_prf(ACCOUNT_ID, CHACHA20_PRF_OUTPUT, &data, &iv).unwrap_err();
+    assert!(matches!(&error, BlobDecryptError::Deprecated(e) if e.deprecation == "LegacyKeyBlob"));
+    assert_eq!(error.code(), Some(SignerErrorCode::DeprecatedUsage));
+
+    let error = open_confirmation_secrets(&mut plaintext_confirmation()).unwrap_err();
+    assert!(
+        matches!(&error, SealedSecretError::Deprecated(e) if e.deprecation == "PlaintextConfirmationSecret")
+    );
+    assert_eq!(error.code(), SignerErrorCode::DeprecatedUsage);
+
+    // requireEncryptedSecrets keeps its own code
+    state::set_require_encrypted_secrets();
+    let response = decrypt(
+        current_blob(),
+        json!({ "chacha20PrfOutput": CHACHA20_PRF_OUTPUT }),
+        fail,
+    );
+    assert_eq!(
+        response.payload["errorCode"],
+        json!("PlaintextSecretRefused")
+    );
+    state::wipe_all_state();
+}
+
+#[test]
+fn test_deprecation_catalog() {
+    for (i, def) in DEPRECATIONS.iter().enumerate() {
+        assert!(
+            DEPRECATIONS[..i].iter().all(|other| other.code != def.code),
+            "duplicate deprecation code {}",
+            def.code
+        );
+        assert_eq!(find_deprecation(def.code), Some(def));
+        assert_eq!(def.remove_after_version.split('.').count(), 3);
+    }
+    assert_eq!(
+        lookup_deprecation("PlaintextSecret"),
+        Some(PLAINTEXT_SECRET.warning())
+    );
+    assert_eq!(lookup_deprecation("DecryptionFailed"), None);
+
+    let payload = json!({ "workerPolicy": { "failOnDeprecated": true, "strictParsing": true } });
+    assert_eq!(
+        check_unknown_fields(WorkerRequestType::SignTransactionsWithActions, &payload),
+        Ok(())
+    );
+}
@@ -0,0 +1,222 @@
+use crate::config::DIAGNOSTICS_MAX_LIST_ITEMS;
+use crate::crypto::encrypt_data_chacha20;
+use crate::diagnostics::{collect, ListDiagnostics};
+use crate::dispatch_signer_message;
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::handlers::handle_dump_diagnostics::{handle_dump_diagnostics, DumpDiagnosticsRequest};
+use crate::handlers::handle_validate_encrypted_blobs::EncryptedKeyBlob;
+use crate::key_selection::key_fingerprint;
+use crate::key_usage::{self, key_usage_id};
+use crate::state::{self, SessionKey};
+use crate::tests::block_on;
+use crate::types::handlers::WorkerPolicy;
+use crate::types::worker_messages::{SignerWorkerMessage, WorkerRequestType, WorkerResponseType};
+use crate::types::AccountId;
+use serde_json::{json, Value};
+
+// Each test runs on its own thread, so the thread_local state starts empty.
+
+const PRF_OUTPUT: &str = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
+
+fn alice() -> AccountId {
+    AccountId("alice.testnet".to_string())
+}
+
+fn session_signing_key() -> ed25519_dalek::SigningKey {
+    ed25519_dalek::SigningKey::from_bytes(&[7u8; 32])
+}
+
+fn session_public_key() -> String {
+    format!(
+        "ed25519:{}",
+        bs58::encode(session_signing_key().verifying_key().as_bytes()).into_string()
+    )
+}
+
+/// A v1 blob for alice holding `secret`, with its key-check header
+fn key_blob(secret: &str) -> EncryptedKeyBlob {
+    let encrypted = encrypt_data_chacha20(secret, &[9u8; 32]).unwrap();
+    EncryptedKeyBlob {
+        blob_id: Some("alice.testnet#1".to_string()),
+        encrypted_private_key_data: encrypted.encrypted_near_key_data_b64u,
+        encrypted_private_key_iv: encrypted.chacha20_nonce_b64u,
+        version: None,
+        near_account_id: Some("alice.testnet".to_string()),
+        outer_wrap: None,
+    }
+}
+
+fn populate_state(blob: &EncryptedKeyBlob) {
+    state::reserve_nonces(&alice(), "req-1", vec!["11".to_string()]);
+    state::record_audit(
+        &alice(),
+        "req-1",
+        "signTransactionsWithActions",
+        "Signed",
+        Some("signed for app.testnet".to_string()),
+    );
+    state::store_session_key(
+        &session_public_key(),
+        SessionKey {
+            signing_key: session_signing_key(),
+            near_account_id: "alice.testnet".to_string(),
+            receiver_id: "app.testnet".to_string(),
+            method_names: vec![],
+            allowance: 1,
+            spent: 0,
+            expires_at_ms: state::now_ms() + 60_000.0,
+        },
+    )
+    .unwrap();
+    let data = base64_url_decode(&blob.encrypted_private_key_data).unwrap();
+    let key_id = key_usage_id(&data).unwrap();
+    key_usage::record_signing(&key_id, PRF_OUTPUT, 1, "app.testnet", "alice.testnet", 1.0).unwrap();
+}
+
+fn dump(policy: WorkerPolicy, key_blobs: Vec<EncryptedKeyBlob>) -> (String, String) {
+    let result = block_on(handle_dump_diagnostics(DumpDiagnosticsRequest {
+        worker_policy: Some(policy),
+        key_blobs,
+    }))
+    .unwrap();
+    (result.document, result.digest)
+}
+
+#[test]
+fn test_two_dumps_of_the_same_state_are_identical() {
+    let blob = key_blob("ed25519:secret-key-material");
+    populate_state(&blob);
+    let policy = WorkerPolicy {
+        allowed_rpc_origins: vec![
+            "https://rpc.testnet.near.org".to_string(),
+            "https://archival.testnet.near.org".to_string(),
+        ],
+        ..Default::default()
+    };
+    let (first, first_digest) = dump(policy.clone(), vec![blob.clone()]);
+    let (second, second_digest) = dump(policy.clone(), vec![blob.clone()]);
+    assert_eq!(first, second);
+    assert_eq!(first_digest, second_digest);
+
+    // Order of the policy's lists does not matter
+    let mut reordered = policy.clone();
+    reordered.allowed_rpc_origins.reverse();
+    assert_eq!(dump(reordered, vec![blob.clone()]).0, first);
+
+    // A change in state shows up in the document
+    state::record_audit(&alice(), "req-2", "signNep413Message", "UserDeclined", None);
+    let (third, third_digest) = dump(policy, vec![blob]);
+    assert_ne!(third_digest, first_digest);
+    let document: Value = serde_json::from_str(&third).unwrap();
+    assert_eq!(document["recentLog"].as_array().unwrap().len(), 2);
+    key_usage::reset();
+}
+
+#[test]
+fn test_secrets_and_full_keys_stay_out_of_the_document() {
+    let blob = key_blob("ed25519:secret-key-material");
+    populate_state(&blob);
+    let (document, _) = dump(
+        WorkerPolicy {
+            indexer_url: Some("https://api.nearblocks.io/v1?apikey=hunter2".to_string()),
+            ..Default::default()
+        },
+        vec![blob.clone()],
+    );
+
+    let public_key = session_public_key();
+    let key_id =
+        key_usage_id(&base64_url_decode(&blob.encrypted_private_key_data).unwrap()).unwrap();
+    for secret in [
+        public_key.as_str(),
+        &public_key["ed25519:".len()..],
+        &blob.encrypted_private_key_data,
+        &blob.encrypted_private_key_iv,
+        &key_id,
+        &base64_url_encode(&session_signing_key().to_bytes()),
+        PRF_OUTPUT,
+        "hunter2",
+        "signed for app.testnet",
+    ] {
+        assert!(!document.contains(secret), "{} leaked", secret);
+    }
+
+    let document: Value = serde_json::from_str(&document).unwrap();
+    let account = &document["accounts"][0];
+    assert_eq!(account["accountId"], json!("alice.testnet"));
+    assert_eq!(
+        account["sessionKeys"][0]["fingerprint"],
+        json!(key_fingerprint(
+            session_signing_key().verifying_key().as_bytes()
+        ))
+    );
+    // The blob and its usage entry report the same fingerprint
+    assert_eq!(account["keyBlobs"][0]["version"], json!(1));
+    assert_eq!(
+        account["keyBlobs"][0]["keyFingerprint"],
+        document["keyUsage"]["keys"][0]["keyFingerprint"]
+    );
+    assert_eq!(
+        document["policy"]["indexerOrigin"],
+        json!("https://api.nearblocks.io")
+    );
+    assert_eq!(document["recentLog"][0]["hasDetail"], json!(true));
+    key_usage::reset();
+}
+
+#[test]
+fn test_long_policy_lists_are_reported_as_a_digest() {
+    let origins = |n: usize| -> Vec<String> {
+        (0..n)
+            .map(|i| format!("https://rpc-{}.example.com", i))
+            .collect()
+    };
+    let short = collect(
+        &WorkerPolicy {
+            allowed_rpc_origins: origins(DIAGNOSTICS_MAX_LIST_ITEMS),
+            ..Default::default()
+        },
+        &[],
+    );
+    assert!(matches!(
+        short.policy.allowed_rpc_origins,
+        ListDiagnostics::Items(ref items) if items.len() == DIAGNOSTICS_MAX_LIST_ITEMS
+    ));
+
+    let long = collect(
+        &WorkerPolicy {
+            allowed_rpc_origins: origins(DIAGNOSTICS_MAX_LIST_ITEMS + 1),
+            ..Default::default()
+        },
+        &[],
+    );
+    let ListDiagnostics::Digest { count, sha256 } = &long.policy.allowed_rpc_origins else {
+        panic!("expected a digest");
+    };
+    assert_eq!(*count, DIAGNOSTICS_MAX_LIST_ITEMS + 1);
+    assert_eq!(sha256.len(), 64);
+    let document = serde_json::to_value(&long).unwrap();
+    assert_eq!(
+        document["policy"]["allowedRpcOrigins"],
+        json!({ "count": DIAGNOSTICS_MAX_LIST_ITEMS + 1, "sha256": sha256 })
+    );
+}
+
+#[test]
+fn test_dump_diagnostics_is_served_by_the_dispatcher() {
+    let response = block_on(dispatch_signer_message(SignerWorkerMessage {
+        msg_type: WorkerRequestType::DumpDiagnostics as u32,
+        payload: json!({}),
+        request_id: None,
+    }))
+    .unwrap();
+    assert_eq!(
+        WorkerResponseType::from(response.response_type),
+        WorkerResponseType::DumpDiagnosticsSuccess
+    );
+    let document: Value =
+        serde_json::from_str(response.payload["document"].as_str().unwrap()).unwrap();
+    assert_eq!(document["schemaVersion"], json!(1));
+    assert_eq!(document["accounts"], json!([]));
+    assert!(document["build"]["version"].is_string());
+}
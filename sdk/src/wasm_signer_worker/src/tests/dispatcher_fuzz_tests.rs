@@ -0,0 +1,484 @@
+use crate::error::ParsePayloadError;
+use crate::handlers;
+use crate::strict_parsing::check_unknown_fields;
+use crate::tests::block_on;
+use crate::tests::wire_format_tests::sample_payload;
+use crate::types::worker_messages::{
+    SignerWorkerMessage, SignerWorkerResponse, WorkerRequestType, WorkerResponseType,
+};
+use crate::wire_format::{decode_cbor_frame, WireFormat};
+use crate::{dispatch_json_frame, dispatch_signer_message, worker_response_type_name};
+use serde_json::{json, Value};
+
+// The dispatcher is fed generated frames: arbitrary JSON, unknown types, non-object payloads
+// and corrupted text (lone surrogates, out-of-range numbers, truncation), then near-miss
+// mutations of each request type's sample payload. Every frame must come back as an error
+// string or a failure response; none may panic or be accepted. Near misses that parse would
+// reach handlers needing the JS host, so they are checked through the typed parse only.
+
+const LAST_REQUEST_TYPE: u32 = 59;
+const ITERATIONS: usize = 3000;
+
+/// xorshift64*: deterministic, so a failing seed and iteration reproduce
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn pick<T: Clone>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len())].clone()
+    }
+}
+
+fn awkward_strings() -> Vec<String> {
+    vec![
+        String::new(),
+        "alice.testnet".to_string(),
+        "ed25519:".to_string(),
+        "\u{0}".to_string(),
+        "\u{FEFF}💥\u{202E}".to_string(),
+        "340282366920938463463374607431768211456".to_string(),
+        "-1".to_string(),
+        "NaN".to_string(),
+        "a".repeat(70_000),
+    ]
+}
+
+fn awkward_numbers() -> Vec<Value> {
+    vec![
+        json!(0),
+        json!(-1),
+        json!(u32::MAX as u64 + 1),
+        json!(u64::MAX),
+        json!(i64::MIN),
+        json!(9_007_199_254_740_993u64),
+        json!(1.5),
+        json!(-0.0),
+        json!(1e300),
+        // u64::MAX + 1 only fits in a float
+        serde_json::from_str("18446744073709551616").unwrap(),
+    ]
+}
+
+fn arbitrary_value(rng: &mut Rng, depth: usize) -> Value {
+    let kinds = if depth == 0 { 4 } else { 6 };
+    match rng.below(kinds) {
+        0 => Value::Null,
+        1 => Value::Bool(rng.below(2) == 0),
+        2 => rng.pick(&awkward_numbers()),
+        3 => Value::String(rng.pick(&awkward_strings())),
+        4 => (0..rng.below(4))
+            .map(|_| arbitrary_value(rng, depth - 1))
+            .collect(),
+        _ => (0..rng.below(4))
+            .map(|_| {
+                let key = rng
+                    .pick(&[
+                        "type",
+                        "payload",
+                        "requestId",
+                        "nearAccountId",
+                        "",
+                        "workerPolicy",
+                    ])
+                    .to_string();
+                (key, arbitrary_value(rng, depth - 1))
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+    }
+}
+
+/// Anything but an object
+fn arbitrary_non_object(rng: &mut Rng) -> Value {
+    loop {
+        let value = arbitrary_value(rng, 2);
+        if !value.is_object() {
+            return value;
+        }
+    }
+}
+
+fn arbitrary_message_type(rng: &mut Rng) -> Value {
+    match rng.below(3) {
+        0 => json!(LAST_REQUEST_TYPE as u64 + 1 + rng.next() % (1 << 40)),
+        1 => rng.pick(&[
+            json!(u32::MAX),
+            json!(-1),
+            json!(1.5),
+            json!("4"),
+            json!(null),
+            json!([4]),
+        ]),
+        _ => arbitrary_value(rng, 1),
+    }
+}
+
+/// A frame that must not reach a handler
+fn arbitrary_frame(rng: &mut Rng) -> Value {
+    match rng.below(3) {
+        0 => arbitrary_value(rng, 3),
+        1 => json!({
+            "type": arbitrary_message_type(rng),
+            "payload": arbitrary_value(rng, 2),
+            "requestId": "fuzz",
+        }),
+        _ => json!({
+            "type": rng.below(LAST_REQUEST_TYPE as usize + 1),
+            "payload": arbitrary_non_object(rng),
+            "requestId": "fuzz",
+        }),
+    }
+}
+
+/// Text corruptions that leave no valid JSON frame
+fn corrupt_text(rng: &mut Rng, text: &str) -> String {
+    match rng.below(4) {
+        0 => {
+            let mut end = rng.below(text.len().max(1));
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            text[..end].to_string()
+        }
+        // Lone UTF-16 surrogates have no UTF-8 form
+        1 => {
+            let surrogate = rng.pick(&["\"\\ud800\"", "\"pay\\udc00load\""]);
+            text.replacen("\"payload\"", surrogate, 1)
+        }
+        2 => text.replacen("\"type\":", "\"type\":1e400,\"_\":", 1),
+        _ => format!("{}{}", text, rng.pick(&["}", ",", "\u{0}", "\"\\udfff\""])),
+    }
+}
+
+fn assert_refused(frame: &str, result: Result<String, String>) {
+    let frame = &frame[..frame.len().min(200)];
+    match result {
+        Err(error) => assert!(!error.is_empty(), "empty error for {}", frame),
+        Ok(response) => {
+            let response: SignerWorkerResponse = serde_json::from_str(&response).unwrap();
+            let name = worker_response_type_name(WorkerResponseType::from(response.response_type));
+            assert!(name.ends_with("_FAILURE"), "{} accepted: {}", frame, name);
+            assert!(
+                response.payload["error"].is_string(),
+                "{} failed without an error: {}",
+                frame,
+                response.payload
+            );
+        }
+    }
+}
+
+/// The typed parse `run_handler` performs for `request_type`; exhaustive so new request types
+/// must be added here
+fn parse_request(
+    request_type: WorkerRequestType,
+    msg: &SignerWorkerMessage,
+) -> Result<(), ParsePayloadError> {
+    macro_rules! parse {
+        ($request:ty) => {
+            msg.parse_payload::<$request>(request_type).map(drop)
+        };
+    }
+    match request_type {
+        WorkerRequestType::DeriveNearKeypairAndEncrypt => {
+            parse!(crate::DeriveNearKeypairAndEncryptRequest)
+        }
+        WorkerRequestType::RecoverKeypairFromPasskey => parse!(handlers::RecoverKeypairRequest),
+        WorkerRequestType::CheckCanRegisterUser => parse!(handlers::CheckCanRegisterUserRequest),
+        WorkerRequestType::DecryptPrivateKeyWithPrf => parse!(crate::DecryptPrivateKeyRequest),
+        WorkerRequestType::SignTransactionsWithActions => {
+            parse!(handlers::SignTransactionsWithActionsRequest)
+        }
+        WorkerRequestType::ExtractCosePublicKey => parse!(handlers::ExtractCoseRequest),
+        #[cfg(feature = "device-linking")]
+        WorkerRequestType::SignTransactionWithKeyPair => {
+            parse!(handlers::SignTransactionWithKeyPairRequest)
+        }
+        #[cfg(feature = "nep413")]
+        WorkerRequestType::SignNep413Message => parse!(handlers::SignNep413Request),
+        WorkerRequestType::RegistrationCredentialConfirmation => {
+            parse!(handlers::RegistrationCredentialConfirmationRequest)
+        }
+        WorkerRequestType::ExportNearKeypairUI => parse!(handlers::ExportNearKeypairUiRequest),
+        WorkerRequestType::GetBorshSchemas => parse!(handlers::GetBorshSchemasRequest),
+        WorkerRequestType::ListPendingRequests => parse!(handlers::ListPendingRequestsRequest),
+        WorkerRequestType::CancelRequest => parse!(handlers::CancelRequestRequest),
+        WorkerRequestType::WipeAllState => parse!(handlers::WipeAllStateRequest),
+        WorkerRequestType::ValidateEncryptedBlobs => {
+            parse!(handlers::ValidateEncryptedBlobsRequest)
+        }
+        WorkerRequestType::CreateSessionKey => parse!(handlers::CreateSessionKeyRequest),
+        WorkerRequestType::SignWithSessionKey => parse!(handlers::SignWithSessionKeyRequest),
+        WorkerRequestType::RevokeSessionKey => parse!(handlers::RevokeSessionKeyRequest),
+        WorkerRequestType::ComposeMultisigRequest => parse!(handlers::ComposeMultisigRequest),
+        WorkerRequestType::GetMemoryStats => parse!(handlers::GetMemoryStatsRequest),
+        WorkerRequestType::TrimCaches => parse!(handlers::TrimCachesRequest),
+        WorkerRequestType::GetRecentReceivers => parse!(handlers::GetRecentReceiversRequest),
+        WorkerRequestType::BuildAccountDescriptor => {
+            parse!(handlers::BuildAccountDescriptorRequest)
+        }
+        WorkerRequestType::DeployLargeContract => parse!(handlers::DeployLargeContractRequest),
+        WorkerRequestType::RunSelfTest => parse!(handlers::RunSelfTestRequest),
+        WorkerRequestType::ExportAccountBundle => parse!(handlers::ExportAccountBundleRequest),
+        WorkerRequestType::ImportAccountBundle => parse!(handlers::ImportAccountBundleRequest),
+        WorkerRequestType::CreateSigningIntent => parse!(handlers::CreateSigningIntentRequest),
+        WorkerRequestType::ExecuteSigningIntent => parse!(handlers::ExecuteSigningIntentRequest),
+        #[cfg(feature = "relayer")]
+        WorkerRequestType::SubmitToRelayer => parse!(handlers::SubmitToRelayerRequest),
+        #[cfg(feature = "telemetry")]
+        WorkerRequestType::GetTelemetrySnapshot => parse!(handlers::GetTelemetrySnapshotRequest),
+        WorkerRequestType::GetKeyUsageStats => parse!(handlers::GetKeyUsageStatsRequest),
+        WorkerRequestType::PrepareRegistration => parse!(handlers::PrepareRegistrationRequest),
+        #[cfg(feature = "relayer")]
+        WorkerRequestType::CompleteRegistration => parse!(handlers::CompleteRegistrationRequest),
+        #[cfg(feature = "device-linking")]
+        WorkerRequestType::CreateRemoteConfirmation => {
+            parse!(handlers::CreateRemoteConfirmationRequest)
+        }
+        #[cfg(feature = "device-linking")]
+        WorkerRequestType::ApproveRemoteConfirmation => {
+            parse!(handlers::ApproveRemoteConfirmationRequest)
+        }
+        #[cfg(feature = "device-linking")]
+        WorkerRequestType::CompleteRemoteConfirmation => {
+            parse!(handlers::CompleteRemoteConfirmationRequest)
+        }
+        WorkerRequestType::GetWorkerInfo => parse!(handlers::GetWorkerInfoRequest),
+        WorkerRequestType::ValidateDecryptionCapability => {
+            parse!(handlers::ValidateDecryptionCapabilityRequest)
+        }
+        WorkerRequestType::ListActiveAccounts => parse!(handlers::ListActiveAccountsRequest),
+        WorkerRequestType::WipeAccountState => parse!(handlers::WipeAccountStateRequest),
+        WorkerRequestType::ValidateArgsSchemas => parse!(handlers::ValidateArgsSchemasRequest),
+        WorkerRequestType::SummarizeTransactions => {
+            parse!(handlers::SummarizeTransactionsRequest)
+        }
+        WorkerRequestType::MigrateEncryptedBlobs => {
+            parse!(handlers::MigrateEncryptedBlobsRequest)
+        }
+        WorkerRequestType::BuildCredentialCreationOptions => {
+            parse!(handlers::BuildCredentialCreationOptionsRequest)
+        }
+        WorkerRequestType::BuildCredentialRequestOptions => {
+            parse!(handlers::BuildCredentialRequestOptionsRequest)
+        }
+        WorkerRequestType::RecoverPendingOperations => {
+            parse!(handlers::RecoverPendingOperationsRequest)
+        }
+        WorkerRequestType::ComposeCreateSubaccount => {
+            parse!(handlers::ComposeCreateSubaccountRequest)
+        }
+        WorkerRequestType::StartBackgroundRefresh => {
+            parse!(handlers::StartBackgroundRefreshRequest)
+        }
+        WorkerRequestType::StopBackgroundRefresh => {
+            parse!(handlers::StopBackgroundRefreshRequest)
+        }
+        WorkerRequestType::BackgroundRefreshStatus => {
+            parse!(handlers::BackgroundRefreshStatusRequest)
+        }
+        WorkerRequestType::SuspendHint => parse!(handlers::SuspendHintRequest),
+        WorkerRequestType::ResumeHint => parse!(handlers::ResumeHintRequest),
+        WorkerRequestType::DeriveSubKey => parse!(handlers::DeriveSubKeyRequest),
+        WorkerRequestType::ViewCall => parse!(handlers::ViewCallRequest),
+        WorkerRequestType::DumpDiagnostics => parse!(handlers::DumpDiagnosticsRequest),
+        WorkerRequestType::SignTransactionsWithFreshChallenge => {
+            parse!(handlers::SignTransactionsWithFreshChallengeRequest)
+        }
+        WorkerRequestType::GetSessionStatus => parse!(handlers::GetSessionStatusRequest),
+        WorkerRequestType::BuildRedirectPayload => parse!(handlers::BuildRedirectPayloadRequest),
+        WorkerRequestType::IssueElevationChallenge => {
+            parse!(handlers::IssueElevationChallengeRequest)
+        }
+        // Refused as not compiled before they are parsed
+        #[allow(unreachable_patterns)]
+        _ => Ok(()),
+    }
+}
+
+/// JSON pointers to every value in `value`, the root excluded
+fn pointers(value: &Value, prefix: &str, out: &mut Vec<String>) {
+    let children: Vec<(String, &Value)> = match value {
+        Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| (k.replace('~', "~0").replace('/', "~1"), v))
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (i.to_string(), v))
+            .collect(),
+        _ => return,
+    };
+    for (token, child) in children {
+        let pointer = format!("{}/{}", prefix, token);
+        out.push(pointer.clone());
+        pointers(child, &pointer, out);
+    }
+}
+
+/// A different kind of value than `value`, never null (optional fields accept null)
+fn type_swaps(value: &Value) -> Vec<Value> {
+    [json!(true), json!(7), json!("x"), json!([]), json!({})]
+        .into_iter()
+        .filter(|swap| std::mem::discriminant(swap) != std::mem::discriminant(value))
+        .collect()
+}
+
+fn remove_at(payload: &mut Value, pointer: &str) -> bool {
+    let (parent, token) = pointer.rsplit_once('/').unwrap();
+    match payload.pointer_mut(parent) {
+        Some(Value::Object(map)) => map
+            .remove(&token.replace("~1", "/").replace("~0", "~"))
+            .is_some(),
+        Some(Value::Array(items)) => {
+            let index: usize = token.parse().unwrap();
+            items.remove(index);
+            true
+        }
+        _ => false,
+    }
+}
+
+#[test]
+fn test_arbitrary_frames_are_refused_without_panicking() {
+    let mut rng = Rng(0x5eed_f00d);
+    for iteration in 0..ITERATIONS {
+        let frame = arbitrary_frame(&mut rng).to_string();
+        let frame = if rng.below(3) == 0 {
+            corrupt_text(&mut rng, &frame)
+        } else {
+            frame
+        };
+        let result = block_on(dispatch_json_frame(&frame));
+        assert_refused(&format!("#{} {}", iteration, frame), result);
+    }
+}
+
+#[test]
+fn test_arbitrary_cbor_frames_are_refused_without_panicking() {
+    let mut rng = Rng(0xc0ff_ee00);
+    for _ in 0..ITERATIONS {
+        let frame = if rng.below(2) == 0 {
+            let mut bytes = Vec::new();
+            ciborium::into_writer(&arbitrary_frame(&mut rng), &mut bytes).unwrap();
+            bytes
+        } else {
+            (0..rng.below(64)).map(|_| rng.next() as u8).collect()
+        };
+        if let Ok(msg) = decode_cbor_frame(WireFormat::Cbor, &frame) {
+            let label = format!("{:?}", msg);
+            let result = block_on(dispatch_signer_message(msg))
+                .map(|response| serde_json::to_string(&response).unwrap());
+            assert_refused(&label, result);
+        }
+    }
+}
+
+#[test]
+fn test_unknown_message_types_are_errors() {
+    for message_type in [LAST_REQUEST_TYPE + 1, 1000, u32::MAX] {
+        let frame = json!({ "type": message_type, "payload": {} }).to_string();
+        assert_eq!(
+            block_on(dispatch_json_frame(&frame)).unwrap_err(),
+            format!("Unknown message type: {}", message_type)
+        );
+    }
+}
+
+#[test]
+fn test_non_object_payloads_fail_with_a_parse_error() {
+    for payload in [json!([]), json!(null), json!("{}"), json!(4)] {
+        let frame = json!({ "type": 10, "payload": payload }).to_string();
+        let response: SignerWorkerResponse =
+            serde_json::from_str(&block_on(dispatch_json_frame(&frame)).unwrap()).unwrap();
+        assert_eq!(
+            WorkerResponseType::from(response.response_type),
+            WorkerResponseType::GetBorshSchemasFailure
+        );
+        let error = response.payload["error"].as_str().unwrap();
+        assert!(
+            error.starts_with("Invalid payload for GET_BORSH_SCHEMAS: invalid type")
+                && error.ends_with("expected a JSON object"),
+            "{}",
+            error
+        );
+    }
+}
+
+/// Fields holding free-form JSON, which their handlers validate (schemas, stored blobs)
+const FREE_FORM_FIELDS: [&str; 2] = ["/argsSchemas/0/schema", "/blobs/0/blob"];
+
+fn parse_near_miss(request_type: WorkerRequestType, payload: &Value) -> Result<(), String> {
+    let msg = SignerWorkerMessage {
+        msg_type: request_type as u32,
+        payload: payload.clone(),
+        request_id: None,
+    };
+    let result = parse_request(request_type, &msg).map_err(|e| e.to_string());
+    if let Err(error) = &result {
+        assert!(
+            error.starts_with(&format!("Invalid payload for {}: ", request_type.name())),
+            "{}",
+            error
+        );
+    }
+    // Strict parsing walks the same payload before the typed parse
+    let mut strict = payload.clone();
+    strict["workerPolicy"] = json!({ "strictParsing": true });
+    let _ = check_unknown_fields(request_type, &strict);
+    result
+}
+
+#[test]
+fn test_near_miss_payloads_are_refused_without_panicking() {
+    for message_type in 0..=LAST_REQUEST_TYPE {
+        let request_type = WorkerRequestType::from(message_type);
+        let sample = sample_payload(request_type);
+        let mut paths = Vec::new();
+        pointers(&sample, "", &mut paths);
+
+        for pointer in &paths {
+            let original = sample.pointer(pointer).unwrap();
+            let mut deleted = sample.clone();
+            let required = remove_at(&mut deleted, pointer)
+                && parse_near_miss(request_type, &deleted).is_err();
+
+            let swaps = type_swaps(original).into_iter().chain(awkward_numbers());
+            for swap in swaps {
+                let mut swapped = sample.clone();
+                *swapped.pointer_mut(pointer).unwrap() = swap.clone();
+                let accepted = parse_near_miss(request_type, &swapped).is_ok();
+                // A field that must be present must also have the right type
+                let wrong_type = !(original.is_number() && swap.is_number());
+                if required && wrong_type && !FREE_FORM_FIELDS.contains(&pointer.as_str()) {
+                    assert!(
+                        !accepted,
+                        "{} accepted {} = {}",
+                        request_type.name(),
+                        pointer,
+                        swap
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_nested_structs_are_refused_as_arrays() {
+    let mut payload = sample_payload(WorkerRequestType::ExportAccountBundle);
+    payload["records"] = json!([[{ "nearAccountId": "alice.testnet" }]]);
+    let error = parse_near_miss(WorkerRequestType::ExportAccountBundle, &payload).unwrap_err();
+    assert!(error.contains("expected a JSON object"), "{}", error);
+}
@@ -0,0 +1,364 @@
+use serde_json::{json, Value};
+
+use crate::actions::ActionParams;
+use crate::clock::{ManualClock, SharedClock};
+use crate::dual_control::*;
+use crate::error::{DualControlError, SessionError, SignerErrorCode};
+use crate::handlers::{handle_sign_transactions_with_actions, SignTransactionsWithActionsRequest};
+use crate::state;
+use crate::tests::{block_on, in_fresh_worker};
+use crate::types::handlers::WorkerPolicy;
+use crate::types::AccountId;
+use crate::worker_state;
+
+const ACCOUNT_ID: &str = "treasury.testnet";
+const FIRST_CREDENTIAL: &str = "Zmlyc3QtY3JlZGVudGlhbA";
+const SECOND_CREDENTIAL: &str = "c2Vjb25kLWNyZWRlbnRpYWw";
+
+fn account() -> AccountId {
+    AccountId(ACCOUNT_ID.to_string())
+}
+
+fn transfer_digest(deposit: &str) -> String {
+    approval_digest(&[(
+        "bob.testnet".to_string(),
+        vec![ActionParams::Transfer {
+            deposit: deposit.to_string(),
+        }],
+    )])
+    .unwrap()
+}
+
+fn setup() -> ManualClock {
+    let clock = ManualClock::new(1_000_000.0);
+    state::set_clock(SharedClock::new(clock.clone()));
+    state::wipe_all_state();
+    clock
+}
+
+#[test]
+fn test_required_approvals_per_account() {
+    assert_eq!(required_approvals(None), Ok(1));
+
+    let policy: WorkerPolicy = serde_json::from_value(json!({
+        "requiredApprovals": 1,
+        "accountOverrides": { "treasury.testnet": { "requiredApprovals": 2 } }
+    }))
+    .unwrap();
+    let treasury = policy.for_account(&account());
+    assert_eq!(required_approvals(Some(&treasury)), Ok(2));
+    let personal = policy.for_account(&AccountId("alice.testnet".to_string()));
+    assert_eq!(required_approvals(Some(&personal)), Ok(1));
+
+    for invalid in [0, 3] {
+        let policy = WorkerPolicy {
+            required_approvals: Some(invalid),
+            ..WorkerPolicy::default()
+        };
+        let err = required_approvals(Some(&policy)).unwrap_err();
+        assert_eq!(err.code(), SignerErrorCode::InvalidConfig);
+    }
+}
+
+#[test]
+fn test_second_approval_from_another_credential_completes() {
+    let clock = setup();
+    let digest = transfer_digest("1");
+    let awaiting = record_first_approval(&account(), "req-1", &digest, FIRST_CREDENTIAL).unwrap();
+    assert_eq!(awaiting.expires_at_ms, 1_000_000.0 + 600_000.0);
+    assert_eq!(
+        awaiting.first_approver,
+        credential_fingerprint(FIRST_CREDENTIAL)
+    );
+    assert_eq!(
+        serde_json::to_value(&awaiting).unwrap()["approvalToken"],
+        json!(awaiting.approval_token)
+    );
+
+    clock.advance(599_999.0);
+    let first = complete_approval(
+        &account(),
+        &awaiting.approval_token,
+        &digest,
+        SECOND_CREDENTIAL,
+        state::now_ms(),
+    )
+    .unwrap();
+    assert_eq!(first.request_id, "req-1");
+    assert_eq!(
+        approvals_detail(&first, SECOND_CREDENTIAL),
+        format!(
+            "approvals: {}, {}",
+            credential_fingerprint(FIRST_CREDENTIAL),
+            credential_fingerprint(SECOND_CREDENTIAL)
+        )
+    );
+
+    // The token is consumed by the approval that signs
+    let again = complete_approval(
+        &account(),
+        &awaiting.approval_token,
+        &digest,
+        SECOND_CREDENTIAL,
+        state::now_ms(),
+    );
+    assert_eq!(again, Err(DualControlError::TokenInvalid));
+}
+
+#[test]
+fn test_same_credential_cannot_approve_twice() {
+    setup();
+    let digest = transfer_digest("1");
+    let awaiting = record_first_approval(&account(), "req-1", &digest, FIRST_CREDENTIAL).unwrap();
+    let token = &awaiting.approval_token;
+
+    let err = complete_approval(
+        &account(),
+        token,
+        &digest,
+        FIRST_CREDENTIAL,
+        state::now_ms(),
+    )
+    .unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::SecondApprovalSameCredential);
+    assert!(err.to_string().contains(&awaiting.first_approver));
+
+    // The first approval is still pending for another passkey
+    assert!(complete_approval(
+        &account(),
+        token,
+        &digest,
+        SECOND_CREDENTIAL,
+        state::now_ms()
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_expired_first_approval() {
+    let clock = setup();
+    let digest = transfer_digest("1");
+    let awaiting = record_first_approval(&account(), "req-1", &digest, FIRST_CREDENTIAL).unwrap();
+    let token = &awaiting.approval_token;
+
+    clock.advance(600_000.0);
+    let err = check_pending_approval(&account(), token, &digest, state::now_ms()).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::ApprovalTokenExpired);
+    assert_eq!(
+        err,
+        DualControlError::TokenExpired {
+            expires_at_ms: 1_600_000.0
+        }
+    );
+    // The token records its expiry, so the second approval fails the same way
+    assert_eq!(
+        complete_approval(
+            &account(),
+            token,
+            &digest,
+            SECOND_CREDENTIAL,
+            state::now_ms()
+        ),
+        Err(err)
+    );
+}
+
+#[test]
+fn test_digest_drift_between_approvals() {
+    setup();
+    let digest = transfer_digest("1");
+    let awaiting = record_first_approval(&account(), "req-1", &digest, FIRST_CREDENTIAL).unwrap();
+    let token = &awaiting.approval_token;
+
+    let drifted = transfer_digest("1000");
+    let err = complete_approval(
+        &account(),
+        token,
+        &drifted,
+        SECOND_CREDENTIAL,
+        state::now_ms(),
+    )
+    .unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::ApprovalDigestMismatch);
+    assert_eq!(
+        err,
+        DualControlError::DigestMismatch {
+            approved: digest.clone(),
+            requested: drifted
+        }
+    );
+    assert!(complete_approval(
+        &account(),
+        token,
+        &digest,
+        SECOND_CREDENTIAL,
+        state::now_ms()
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_tokens_are_bound_to_their_account() {
+    setup();
+    let digest = transfer_digest("1");
+    let awaiting = record_first_approval(&account(), "req-1", &digest, FIRST_CREDENTIAL).unwrap();
+    let other = AccountId("mallory.testnet".to_string());
+    assert_eq!(
+        check_pending_approval(&other, &awaiting.approval_token, &digest, state::now_ms()),
+        Err(DualControlError::TokenInvalid)
+    );
+    state::wipe_all_state();
+    assert_eq!(
+        check_pending_approval(
+            &account(),
+            &awaiting.approval_token,
+            &digest,
+            state::now_ms()
+        ),
+        Err(DualControlError::TokenInvalid)
+    );
+}
+
+#[test]
+fn test_approvals_from_an_earlier_session_epoch_are_dropped() {
+    setup();
+    let digest = transfer_digest("1");
+    let awaiting = record_first_approval(&account(), "req-1", &digest, FIRST_CREDENTIAL).unwrap();
+    let token = &awaiting.approval_token;
+    let epoch = state::session_epoch();
+
+    state::renew_session(state::now_ms());
+    let err = check_pending_approval(&account(), token, &digest, state::now_ms()).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::SessionExpired);
+    assert_eq!(
+        err,
+        DualControlError::Session(SessionError::StaleEpoch {
+            token: "approval token",
+            issued_in: epoch,
+            current: epoch + 1,
+        })
+    );
+    assert_eq!(
+        complete_approval(
+            &account(),
+            token,
+            &digest,
+            SECOND_CREDENTIAL,
+            state::now_ms()
+        ),
+        Err(err)
+    );
+}
+
+#[test]
+fn test_second_approval_in_another_worker() {
+    let digest = transfer_digest("1");
+    let first_digest = digest.clone();
+    let (awaiting, record) = in_fresh_worker(None, move || {
+        let awaiting =
+            record_first_approval(&account(), "req-1", &first_digest, FIRST_CREDENTIAL).unwrap();
+        (awaiting, worker_state::take_changed_record())
+    });
+    let record = record.expect("the first approval creates the MAC key");
+
+    // A worker without the record cannot verify the token
+    let token = awaiting.approval_token.clone();
+    let no_record_digest = digest.clone();
+    let unverified = in_fresh_worker(None, move || {
+        check_pending_approval(&account(), &token, &no_record_digest, state::now_ms())
+    });
+    assert_eq!(unverified, Err(DualControlError::TokenInvalid));
+
+    let token = awaiting.approval_token.clone();
+    let second_digest = digest.clone();
+    let (first, record) = in_fresh_worker(Some(record), move || {
+        let first = complete_approval(
+            &account(),
+            &token,
+            &second_digest,
+            SECOND_CREDENTIAL,
+            state::now_ms(),
+        );
+        (first, worker_state::take_changed_record())
+    });
+    assert_eq!(first.unwrap().request_id, "req-1");
+
+    // The completed approval stays consumed in the workers after that
+    let token = awaiting.approval_token.clone();
+    let replay = in_fresh_worker(record, move || {
+        complete_approval(
+            &account(),
+            &token,
+            &digest,
+            SECOND_CREDENTIAL,
+            state::now_ms(),
+        )
+    });
+    assert_eq!(replay, Err(DualControlError::TokenInvalid));
+}
+
+#[test]
+fn test_waiting_for_a_second_approval_is_not_a_failure() {
+    setup();
+    state::record_audit(
+        &account(),
+        "req-1",
+        "signTransactionsWithActions",
+        "AwaitingSecondApproval",
+        None,
+    );
+    let info = state::list_active_accounts()
+        .into_iter()
+        .find(|a| a.account_id == account())
+        .unwrap();
+    assert_eq!(info.failed_requests, 0);
+    assert_eq!(info.signed_requests, 0);
+}
+
+fn sign_request(worker_policy: Value, approval_token: &str) -> SignTransactionsWithActionsRequest {
+    serde_json::from_value(json!({
+        "rpcCall": {
+            "contractId": "w3a-v1.testnet",
+            "nearRpcUrl": "https://rpc.testnet.near.org",
+            "nearAccountId": ACCOUNT_ID
+        },
+        "decryption": { "encryptedPrivateKeyData": "AAAA", "encryptedPrivateKeyIv": "BBBB" },
+        "txSigningRequests": [{
+            "nearAccountId": ACCOUNT_ID,
+            "receiverId": "bob.testnet",
+            "actions": json!([{ "action_type": "Transfer", "deposit": "1" }]).to_string()
+        }],
+        "confirmationConfig": null,
+        "workerPolicy": worker_policy,
+        "approvalToken": approval_token
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_bad_second_approval_fails_before_the_prompt() {
+    setup();
+    let result = block_on(handle_sign_transactions_with_actions(sign_request(
+        json!({ "requiredApprovals": 2 }),
+        "bm90LWEtdG9rZW4",
+    )))
+    .unwrap();
+    assert!(!result.success);
+    assert_eq!(result.error_code.as_deref(), Some("ApprovalTokenInvalid"));
+
+    let digest = transfer_digest("1000");
+    let awaiting = record_first_approval(&account(), "req-1", &digest, FIRST_CREDENTIAL).unwrap();
+    let result = block_on(handle_sign_transactions_with_actions(sign_request(
+        json!({ "requiredApprovals": 2 }),
+        &awaiting.approval_token,
+    )))
+    .unwrap();
+    assert_eq!(result.error_code.as_deref(), Some("ApprovalDigestMismatch"));
+
+    let result = block_on(handle_sign_transactions_with_actions(sign_request(
+        json!({ "requiredApprovals": 3 }),
+        &awaiting.approval_token,
+    )))
+    .unwrap();
+    assert_eq!(result.error_code.as_deref(), Some("InvalidConfig"));
+}
@@ -0,0 +1,199 @@
+// Synthetic platform authenticator: an ES256 credential with the PRF extension, producing
+// attestations ("none" format) and assertions shaped like navigator.credentials results as the
+// TS layer serializes them. PRF results are HMAC-SHA256(prf secret, salt), so the same
+// authenticator always gives the same outputs for a salt and another one never does.
+
+use ciborium::value::Value as CborValue;
+use hmac::{Hmac, Mac};
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{DerSignature, SigningKey};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::assertion_verify::COSE_ALG_ES256;
+use crate::encoders::base64_url_encode;
+
+/// User present, user verified
+const FLAGS_UP_UV: u8 = 0x05;
+/// Set when authenticatorData carries attested credential data
+const FLAG_AT: u8 = 0x40;
+
+/// PRF salts the SDK evaluates for an account: `first` keys the ChaCha20 blob, `second`
+/// derives the NEAR key
+pub fn prf_salts(account_id: &str) -> (Vec<u8>, Vec<u8>) {
+    (
+        format!("chacha20-salt:{}", account_id).into_bytes(),
+        format!("ed25519-salt:{}", account_id).into_bytes(),
+    )
+}
+
+pub struct SyntheticAuthenticator {
+    signing_key: SigningKey,
+    prf_secret: [u8; 32],
+    pub credential_id: Vec<u8>,
+    sign_count: std::cell::Cell<u32>,
+}
+
+/// What a ceremony is made for; each field defaults to the honest value
+#[derive(Clone, Debug)]
+pub struct Ceremony {
+    pub challenge: Vec<u8>,
+    pub origin: String,
+    pub rp_id: String,
+    pub account_id: String,
+}
+
+impl SyntheticAuthenticator {
+    /// A credential whose keys all follow from `seed`
+    pub fn new(seed: u8) -> Self {
+        let key_bytes: [u8; 32] =
+            Sha256::digest([b"e2e-credential-key".as_slice(), &[seed]].concat()).into();
+        SyntheticAuthenticator {
+            signing_key: SigningKey::from_slice(&key_bytes).unwrap(),
+            prf_secret: Sha256::digest([b"e2e-prf-secret".as_slice(), &[seed]].concat()).into(),
+            credential_id: Sha256::digest([b"e2e-credential-id".as_slice(), &[seed]].concat())
+                [..16]
+                .to_vec(),
+            sign_count: std::cell::Cell::new(0),
+        }
+    }
+
+    pub fn prf(&self, salt: &[u8]) -> Vec<u8> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.prf_secret).unwrap();
+        mac.update(salt);
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    /// base64url PRF results (first, second) for an account's salts
+    pub fn prf_outputs(&self, account_id: &str) -> (String, String) {
+        let (first, second) = prf_salts(account_id);
+        (
+            base64_url_encode(&self.prf(&first)),
+            base64_url_encode(&self.prf(&second)),
+        )
+    }
+
+    fn uncompressed_point(&self) -> Vec<u8> {
+        self.signing_key
+            .verifying_key()
+            .to_encoded_point(false)
+            .as_bytes()
+            .to_vec()
+    }
+
+    /// The credential public key as a COSE_Key (kty EC2, alg ES256, crv P-256)
+    pub fn cose_public_key(&self) -> Vec<u8> {
+        let point = self.uncompressed_point();
+        cbor(&CborValue::Map(vec![
+            (CborValue::Integer(1.into()), CborValue::Integer(2.into())),
+            (
+                CborValue::Integer(3.into()),
+                CborValue::Integer(COSE_ALG_ES256.into()),
+            ),
+            (
+                CborValue::Integer((-1).into()),
+                CborValue::Integer(1.into()),
+            ),
+            (
+                CborValue::Integer((-2).into()),
+                CborValue::Bytes(point[1..33].to_vec()),
+            ),
+            (
+                CborValue::Integer((-3).into()),
+                CborValue::Bytes(point[33..].to_vec()),
+            ),
+        ]))
+    }
+
+    fn next_sign_count(&self) -> u32 {
+        self.sign_count.set(self.sign_count.get() + 1);
+        self.sign_count.get()
+    }
+
+    fn auth_data(&self, rp_id: &str, flags: u8) -> Vec<u8> {
+        let mut auth_data = Sha256::digest(rp_id.as_bytes()).to_vec();
+        auth_data.push(flags);
+        auth_data.extend_from_slice(&self.next_sign_count().to_be_bytes());
+        auth_data
+    }
+
+    /// navigator.credentials.create() result, serialized as SerializedRegistrationCredential
+    pub fn attest(&self, ceremony: &Ceremony) -> Value {
+        let mut auth_data = self.auth_data(&ceremony.rp_id, FLAGS_UP_UV | FLAG_AT);
+        auth_data.extend_from_slice(&[0u8; 16]); // AAGUID
+        auth_data.extend_from_slice(&(self.credential_id.len() as u16).to_be_bytes());
+        auth_data.extend_from_slice(&self.credential_id);
+        auth_data.extend_from_slice(&self.cose_public_key());
+        let attestation_object = cbor(&CborValue::Map(vec![
+            (
+                CborValue::Text("fmt".to_string()),
+                CborValue::Text("none".to_string()),
+            ),
+            (
+                CborValue::Text("attStmt".to_string()),
+                CborValue::Map(vec![]),
+            ),
+            (
+                CborValue::Text("authData".to_string()),
+                CborValue::Bytes(auth_data),
+            ),
+        ]));
+        let (first, second) = self.prf_outputs(&ceremony.account_id);
+        json!({
+            "id": base64_url_encode(&self.credential_id),
+            "rawId": base64_url_encode(&self.credential_id),
+            "type": "public-key",
+            "authenticatorAttachment": "platform",
+            "response": {
+                "clientDataJSON": client_data_json("webauthn.create", ceremony),
+                "attestationObject": base64_url_encode(&attestation_object),
+                "transports": ["internal"]
+            },
+            "clientExtensionResults": { "prf": { "results": { "first": first, "second": second } } }
+        })
+    }
+
+    /// navigator.credentials.get() result, with the COSE key registration stored forwarded as
+    /// the TS layer does for local verification
+    pub fn assert(&self, ceremony: &Ceremony) -> Value {
+        let auth_data = self.auth_data(&ceremony.rp_id, FLAGS_UP_UV);
+        let client_data = client_data_json("webauthn.get", ceremony);
+        let signed = [
+            auth_data.as_slice(),
+            &Sha256::digest(crate::encoders::base64_url_decode(&client_data).unwrap()),
+        ]
+        .concat();
+        let signature: DerSignature = self.signing_key.sign(&signed);
+        let (first, _) = self.prf_outputs(&ceremony.account_id);
+        json!({
+            "id": base64_url_encode(&self.credential_id),
+            "rawId": base64_url_encode(&self.credential_id),
+            "type": "public-key",
+            "authenticatorAttachment": "platform",
+            "response": {
+                "clientDataJSON": client_data,
+                "authenticatorData": base64_url_encode(&auth_data),
+                "signature": base64_url_encode(signature.as_bytes()),
+                "userHandle": null,
+                "credentialPublicKey": base64_url_encode(&self.cose_public_key())
+            },
+            "clientExtensionResults": { "prf": { "results": { "first": first } } }
+        })
+    }
+}
+
+fn client_data_json(ceremony_type: &str, ceremony: &Ceremony) -> String {
+    let client_data = json!({
+        "type": ceremony_type,
+        "challenge": base64_url_encode(&ceremony.challenge),
+        "origin": ceremony.origin,
+        "crossOrigin": false
+    });
+    base64_url_encode(client_data.to_string().as_bytes())
+}
+
+pub fn cbor(value: &CborValue) -> Vec<u8> {
+    let mut out = Vec::new();
+    ciborium::into_writer(value, &mut out).unwrap();
+    out
+}
@@ -0,0 +1,292 @@
+// In-memory NEAR chain behind two JSON-RPC endpoints: blocks, accounts and access keys, view
+// calls into `MockContract`, and broadcast_tx_commit that checks each transaction's signature,
+// nonce and block hash the way a node does. It implements `JsonTransport`, so the worker's
+// own `NearRpcClient` (endpoint failover, error classification) talks to it unchanged.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use ed25519_dalek::Verifier;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use super::contract::MockContract;
+use crate::encoders::base64_standard_decode;
+use crate::rpc_calls::HttpJsonResponse;
+use crate::rpc_client::{BlockReference, JsonTransport, NearRpcClient};
+use crate::transaction::calculate_transaction_hash;
+use crate::types::SignedTransaction;
+
+pub const PRIMARY: &str = "https://rpc-a.e2e.test";
+pub const FALLBACK: &str = "https://rpc-b.e2e.test";
+const GENESIS_HEIGHT: u64 = 1_000;
+const GENESIS_BLOCKS: u64 = 10;
+
+/// How an endpoint fails a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flake {
+    /// No response (connection refused)
+    Unreachable,
+    /// HTTP 503 without a body
+    ServiceUnavailable,
+}
+
+pub struct MockChain {
+    pub contract: MockContract,
+    /// Height to block hash, every block since genesis
+    blocks: RefCell<BTreeMap<u64, [u8; 32]>>,
+    /// (account, "ed25519:…" public key) to the access key nonce
+    access_keys: RefCell<HashMap<(String, String), u64>>,
+    /// Failures each endpoint answers its next requests with
+    flakes: RefCell<HashMap<String, VecDeque<Flake>>>,
+    /// Endpoints failing every request
+    down: RefCell<HashMap<String, Flake>>,
+    /// (endpoint, JSON-RPC method) of every request that got an answer
+    pub served: RefCell<Vec<(String, String)>>,
+    pub broadcasts: RefCell<Vec<SignedTransaction>>,
+}
+
+fn block_hash(height: u64) -> [u8; 32] {
+    Sha256::digest([b"e2e-block".as_slice(), &height.to_le_bytes()].concat()).into()
+}
+
+fn handler_error(cause: &str, data: Value) -> Value {
+    json!({
+        "name": "HANDLER_ERROR",
+        "cause": { "name": cause, "info": {} },
+        "code": -32000,
+        "message": "Server error",
+        "data": data
+    })
+}
+
+impl MockChain {
+    pub fn new() -> Self {
+        let blocks = (GENESIS_HEIGHT..GENESIS_HEIGHT + GENESIS_BLOCKS)
+            .map(|height| (height, block_hash(height)))
+            .collect();
+        MockChain {
+            contract: MockContract::default(),
+            blocks: RefCell::new(blocks),
+            access_keys: RefCell::new(HashMap::new()),
+            flakes: RefCell::new(HashMap::new()),
+            down: RefCell::new(HashMap::new()),
+            served: RefCell::new(Vec::new()),
+            broadcasts: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// The worker's RPC client over both endpoints, primary first
+    pub fn rpc(&self) -> NearRpcClient<&MockChain> {
+        NearRpcClient::with_transport(self, &format!("{}, {}", PRIMARY, FALLBACK))
+    }
+
+    pub fn head(&self) -> BlockReference {
+        let blocks = self.blocks.borrow();
+        let (height, hash) = blocks.iter().next_back().unwrap();
+        BlockReference {
+            height: *height,
+            hash: bs58::encode(hash).into_string(),
+        }
+    }
+
+    pub fn produce_blocks(&self, count: u64) {
+        let next = self.head().height + 1;
+        let mut blocks = self.blocks.borrow_mut();
+        for height in next..next + count {
+            blocks.insert(height, block_hash(height));
+        }
+    }
+
+    /// Account creation with its first full-access key (what registration commits)
+    pub fn add_access_key(&self, account_id: &str, public_key: &str) {
+        let nonce = self.head().height * 1_000_000;
+        self.access_keys
+            .borrow_mut()
+            .insert((account_id.to_string(), public_key.to_string()), nonce);
+    }
+
+    pub fn access_key_nonce(&self, account_id: &str, public_key: &str) -> Option<u64> {
+        self.access_keys
+            .borrow()
+            .get(&(account_id.to_string(), public_key.to_string()))
+            .copied()
+    }
+
+    /// Another client of the key got a transaction in first
+    pub fn bump_nonce(&self, account_id: &str, public_key: &str) {
+        if let Some(nonce) = self
+            .access_keys
+            .borrow_mut()
+            .get_mut(&(account_id.to_string(), public_key.to_string()))
+        {
+            *nonce += 1;
+        }
+    }
+
+    /// `endpoint` fails its next requests with `flakes`, then answers again
+    pub fn flake(&self, endpoint: &str, flakes: &[Flake]) {
+        self.flakes
+            .borrow_mut()
+            .entry(endpoint.to_string())
+            .or_default()
+            .extend(flakes);
+    }
+
+    /// `endpoint` fails every request from now on
+    pub fn take_down(&self, endpoint: &str, flake: Flake) {
+        self.down.borrow_mut().insert(endpoint.to_string(), flake);
+    }
+
+    pub fn served_by(&self, endpoint: &str) -> Vec<String> {
+        self.served
+            .borrow()
+            .iter()
+            .filter(|(served_by, _)| served_by == endpoint)
+            .map(|(_, method)| method.clone())
+            .collect()
+    }
+
+    fn dispatch(&self, method: &str, params: &Value) -> Result<Value, Value> {
+        match method {
+            "block" => {
+                let head = self.head();
+                Ok(json!({ "header": { "height": head.height, "hash": head.hash } }))
+            }
+            "query" => self.query(params),
+            "broadcast_tx_commit" => self.broadcast(params),
+            other => Err(json!({ "name": "REQUEST_VALIDATION_ERROR", "message": other })),
+        }
+    }
+
+    fn query(&self, params: &Value) -> Result<Value, Value> {
+        let field = |name: &str| params.get(name).and_then(Value::as_str).unwrap_or_default();
+        let account_id = field("account_id");
+        match field("request_type") {
+            "view_access_key" => match self.access_key_nonce(account_id, field("public_key")) {
+                Some(nonce) => {
+                    let head = self.head();
+                    Ok(json!({
+                        "nonce": nonce,
+                        "permission": "FullAccess",
+                        "block_height": head.height,
+                        "block_hash": head.hash
+                    }))
+                }
+                None => Err(handler_error("UNKNOWN_ACCESS_KEY", json!(null))),
+            },
+            "view_account" => {
+                let exists = self
+                    .access_keys
+                    .borrow()
+                    .keys()
+                    .any(|(account, _)| account == account_id);
+                if exists {
+                    Ok(json!({ "amount": "1000000000000000000000000", "storage_usage": 182 }))
+                } else {
+                    Err(handler_error("UNKNOWN_ACCOUNT", json!(null)))
+                }
+            }
+            "call_function" => {
+                let args = base64_standard_decode(field("args_base64"))
+                    .map_err(|e| handler_error("PARSE_ERROR", json!(e)))?;
+                let blocks = self.blocks.borrow();
+                let head = blocks.keys().next_back().copied().unwrap_or_default();
+                // Panicked view calls come back as a result with an `error` string
+                Ok(
+                    match self.contract.view(field("method_name"), &args, &blocks) {
+                        Ok(value) => json!({
+                            "result": value.to_string().into_bytes(),
+                            "logs": [],
+                            "block_height": head
+                        }),
+                        Err(reason) => json!({
+                            "error": format!("Smart contract panicked: {}", reason),
+                            "logs": []
+                        }),
+                    },
+                )
+            }
+            other => Err(handler_error("UNKNOWN_REQUEST", json!(other))),
+        }
+    }
+
+    fn broadcast(&self, params: &Value) -> Result<Value, Value> {
+        let bytes = params
+            .get(0)
+            .and_then(Value::as_str)
+            .and_then(|b64| base64_standard_decode(b64).ok())
+            .ok_or_else(|| handler_error("PARSE_ERROR", json!("expected a base64 transaction")))?;
+        let invalid = |reason: Value| handler_error("INVALID_TRANSACTION", reason);
+        let signed: SignedTransaction =
+            borsh::from_slice(&bytes).map_err(|e| invalid(json!(e.to_string())))?;
+        let tx = &signed.transaction;
+        let public_key = format!(
+            "ed25519:{}",
+            bs58::encode(tx.public_key.key_data).into_string()
+        );
+        let ak_nonce = self
+            .access_key_nonce(&tx.signer_id.0, &public_key)
+            .ok_or_else(|| invalid(json!({ "InvalidAccessKeyError": "AccessKeyNotFound" })))?;
+
+        let key = ed25519_dalek::VerifyingKey::from_bytes(&tx.public_key.key_data)
+            .map_err(|e| invalid(json!(e.to_string())))?;
+        let signature = ed25519_dalek::Signature::from_bytes(&signed.signature.signature_data);
+        let (hash, _) = tx.get_hash_and_size();
+        key.verify(&hash.0, &signature)
+            .map_err(|_| invalid(json!({ "InvalidSignature": null })))?;
+        if !self.blocks.borrow().values().any(|h| *h == tx.block_hash.0) {
+            return Err(invalid(json!({ "Expired": null })));
+        }
+        if tx.nonce <= ak_nonce {
+            return Err(invalid(json!({
+                "TxExecutionError": {
+                    "InvalidTxError": { "InvalidNonce": { "tx_nonce": tx.nonce, "ak_nonce": ak_nonce } }
+                }
+            })));
+        }
+
+        self.access_keys
+            .borrow_mut()
+            .insert((tx.signer_id.0.clone(), public_key), tx.nonce);
+        let tx_hash = calculate_transaction_hash(&bytes);
+        self.broadcasts.borrow_mut().push(signed);
+        self.produce_blocks(1);
+        Ok(json!({
+            "status": { "SuccessValue": "" },
+            "transaction": { "hash": tx_hash }
+        }))
+    }
+}
+
+impl JsonTransport for MockChain {
+    async fn request(&self, url: &str, body: Option<&Value>) -> Result<HttpJsonResponse, String> {
+        let flake = self
+            .flakes
+            .borrow_mut()
+            .get_mut(url)
+            .and_then(VecDeque::pop_front)
+            .or_else(|| self.down.borrow().get(url).copied());
+        match flake {
+            Some(Flake::Unreachable) => return Err("connection refused".to_string()),
+            Some(Flake::ServiceUnavailable) => {
+                return Ok(HttpJsonResponse {
+                    status: 503,
+                    body: None,
+                })
+            }
+            None => {}
+        }
+        let body = body.ok_or("GET is not served")?;
+        let method = body["method"].as_str().unwrap_or_default().to_string();
+        let answer = match self.dispatch(&method, &body["params"]) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": body["id"], "result": result }),
+            Err(error) => json!({ "jsonrpc": "2.0", "id": body["id"], "error": error }),
+        };
+        self.served.borrow_mut().push((url.to_string(), method));
+        Ok(HttpJsonResponse {
+            status: 200,
+            body: Some(answer),
+        })
+    }
+}
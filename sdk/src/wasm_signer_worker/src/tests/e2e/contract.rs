@@ -0,0 +1,275 @@
+// Mock of the web3-authn contract's view methods: a Rust re-implementation of the checks
+// `check_can_register_user` and `verify_authentication_response` make on chain, over the
+// arguments the signer worker sends. Rejections are returned as the reason string the contract
+// panics with; `MockChain` wraps them the way NEAR RPC reports a panicked view call.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+
+use p256::ecdsa::signature::Verifier;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use vrf_wasm::ecvrf::{ECVRFProof, ECVRFPublicKey};
+use vrf_wasm::vrf::VRFProof;
+use wasm_vrf_worker::VRF_DOMAIN_SEPARATOR;
+
+use crate::cose::{decode_attestation_object, decode_authenticator_data, decode_cose_key};
+use crate::encoders::base64_url_decode;
+use crate::error::WebAuthnDataSection;
+use crate::rpc_calls::{
+    VrfData, CHECK_CAN_REGISTER_USER_METHOD, VERIFY_AUTHENTICATION_RESPONSE_METHOD,
+};
+use crate::types::{WebAuthnAuthenticationCredential, WebAuthnRegistrationCredential};
+
+/// Blocks a VRF challenge may lag behind the chain head
+pub const MAX_BLOCK_AGE: u64 = 100;
+const FLAG_USER_PRESENT: u8 = 0x01;
+
+/// An authenticator as the contract stores it once registered
+#[derive(Debug, Clone)]
+pub struct StoredAuthenticator {
+    pub user_id: String,
+    /// COSE_Key bytes from the attestation
+    pub credential_public_key: Vec<u8>,
+    /// bincode VRF public keys bound to the authenticator
+    pub vrf_public_keys: Vec<Vec<u8>>,
+}
+
+#[derive(Default)]
+pub struct MockContract {
+    /// Keyed by raw credential id
+    authenticators: RefCell<HashMap<Vec<u8>, StoredAuthenticator>>,
+}
+
+#[derive(Deserialize)]
+struct RegistrationArgs {
+    vrf_data: VrfData,
+    webauthn_registration: WebAuthnRegistrationCredential,
+}
+
+#[derive(Deserialize)]
+struct AuthenticationArgs {
+    vrf_data: VrfData,
+    webauthn_authentication: WebAuthnAuthenticationCredential,
+}
+
+#[derive(Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    ceremony_type: String,
+    challenge: String,
+    origin: String,
+}
+
+impl MockContract {
+    /// What `verify_and_register_user` leaves behind
+    pub fn store_authenticator(&self, credential_id: Vec<u8>, authenticator: StoredAuthenticator) {
+        self.authenticators
+            .borrow_mut()
+            .insert(credential_id, authenticator);
+    }
+
+    /// Runs a view method: its JSON return value, or the reason it panicked
+    pub fn view(
+        &self,
+        method_name: &str,
+        args: &[u8],
+        blocks: &BTreeMap<u64, [u8; 32]>,
+    ) -> Result<Value, String> {
+        match method_name {
+            CHECK_CAN_REGISTER_USER_METHOD => {
+                let args: RegistrationArgs = serde_json::from_slice(args)
+                    .map_err(|e| format!("Failed to deserialize input: {}", e))?;
+                self.check_can_register_user(args, blocks)
+            }
+            VERIFY_AUTHENTICATION_RESPONSE_METHOD => {
+                let args: AuthenticationArgs = serde_json::from_slice(args)
+                    .map_err(|e| format!("Failed to deserialize input: {}", e))?;
+                self.verify_authentication_response(args, blocks)
+            }
+            other => Err(format!("MethodNotFound: {}", other)),
+        }
+    }
+
+    fn check_can_register_user(
+        &self,
+        args: RegistrationArgs,
+        blocks: &BTreeMap<u64, [u8; 32]>,
+    ) -> Result<Value, String> {
+        let vrf = &args.vrf_data;
+        verify_vrf(vrf, blocks)?;
+        let response = &args.webauthn_registration.response;
+        verify_client_data(&response.client_data_json, "webauthn.create", vrf)?;
+
+        let attestation = base64_url_decode(&response.attestation_object)
+            .map_err(|e| format!("Invalid attestationObject: {}", e))?;
+        let attestation = decode_attestation_object(&attestation).map_err(|e| e.to_string())?;
+        let auth_data =
+            decode_authenticator_data(&attestation.auth_data).map_err(|e| e.to_string())?;
+        verify_rp_id_hash(&auth_data.rp_id_hash, auth_data.flags, &vrf.rp_id)?;
+        let attested = auth_data
+            .attested_credential
+            .ok_or("Attestation has no attested credential data")?;
+        decode_cose_key(
+            &attested.credential_public_key,
+            WebAuthnDataSection::CoseKey,
+            0,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(json!({ "verified": true, "user_exists": false }))
+    }
+
+    fn verify_authentication_response(
+        &self,
+        args: AuthenticationArgs,
+        blocks: &BTreeMap<u64, [u8; 32]>,
+    ) -> Result<Value, String> {
+        let vrf = &args.vrf_data;
+        let credential = &args.webauthn_authentication;
+        let raw_id =
+            base64_url_decode(&credential.raw_id).map_err(|e| format!("Invalid rawId: {}", e))?;
+        let stored = self
+            .authenticators
+            .borrow()
+            .get(&raw_id)
+            .cloned()
+            .filter(|stored| stored.user_id == vrf.user_id)
+            .ok_or_else(|| format!("No authenticator {} for {}", credential.raw_id, vrf.user_id))?;
+        if !stored.vrf_public_keys.contains(&vrf.public_key) {
+            return Err("VRF public key is not bound to this authenticator".to_string());
+        }
+        verify_vrf(vrf, blocks)?;
+
+        let response = &credential.response;
+        verify_client_data(&response.client_data_json, "webauthn.get", vrf)?;
+        let authenticator_data = base64_url_decode(&response.authenticator_data)
+            .map_err(|e| format!("Invalid authenticatorData: {}", e))?;
+        let auth_data =
+            decode_authenticator_data(&authenticator_data).map_err(|e| e.to_string())?;
+        verify_rp_id_hash(&auth_data.rp_id_hash, auth_data.flags, &vrf.rp_id)?;
+
+        let jwk = decode_cose_key(
+            &stored.credential_public_key,
+            WebAuthnDataSection::CoseKey,
+            0,
+        )
+        .map_err(|e| e.to_string())?
+        .jwk;
+        let coordinate = |c: Option<String>| base64_url_decode(&c.unwrap_or_default());
+        let point = [
+            vec![0x04],
+            coordinate(jwk.x).map_err(|e| e.to_string())?,
+            coordinate(jwk.y).map_err(|e| e.to_string())?,
+        ]
+        .concat();
+        let key = p256::ecdsa::VerifyingKey::from_sec1_bytes(&point)
+            .map_err(|e| format!("Stored credential key is not P-256: {}", e))?;
+        let client_data_json = base64_url_decode(&response.client_data_json)
+            .map_err(|e| format!("Invalid clientDataJSON: {}", e))?;
+        let signed = [
+            authenticator_data,
+            Sha256::digest(client_data_json).to_vec(),
+        ]
+        .concat();
+        let signature = base64_url_decode(&response.signature)
+            .ok()
+            .and_then(|der| p256::ecdsa::DerSignature::try_from(der.as_slice()).ok())
+            .ok_or("Malformed WebAuthn signature")?;
+        key.verify(&signed, &signature)
+            .map_err(|_| "WebAuthn signature verification failed".to_string())?;
+        Ok(json!({ "verified": true }))
+    }
+}
+
+/// The VRF input the contract rebuilds from the challenge fields
+pub fn vrf_input(user_id: &str, rp_id: &str, block_height: u64, block_hash: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(VRF_DOMAIN_SEPARATOR);
+    hasher.update(user_id.as_bytes());
+    hasher.update(rp_id.as_bytes());
+    hasher.update(block_height.to_le_bytes());
+    hasher.update(block_hash);
+    hasher.finalize().to_vec()
+}
+
+fn verify_vrf(vrf: &VrfData, blocks: &BTreeMap<u64, [u8; 32]>) -> Result<(), String> {
+    let head = blocks.keys().next_back().copied().unwrap_or_default();
+    let known_hash = blocks
+        .get(&vrf.block_height)
+        .ok_or_else(|| format!("Unknown block height {}", vrf.block_height))?;
+    if known_hash.as_slice() != vrf.block_hash.as_slice() {
+        return Err(format!(
+            "Block hash does not match block {}",
+            vrf.block_height
+        ));
+    }
+    let age = head.saturating_sub(vrf.block_height);
+    if age > MAX_BLOCK_AGE {
+        return Err(format!(
+            "StaleChallenge: block {} is {} blocks old (max {})",
+            vrf.block_height, age, MAX_BLOCK_AGE
+        ));
+    }
+
+    let expected_input = vrf_input(&vrf.user_id, &vrf.rp_id, vrf.block_height, &vrf.block_hash);
+    if expected_input != vrf.vrf_input_data {
+        return Err("VRF input does not match the user, rpId and block".to_string());
+    }
+    let public_key: ECVRFPublicKey = bincode::deserialize(&vrf.public_key)
+        .map_err(|e| format!("Invalid VRF public key: {}", e))?;
+    let proof: ECVRFProof =
+        bincode::deserialize(&vrf.vrf_proof).map_err(|e| format!("Invalid VRF proof: {}", e))?;
+    let output: [u8; 64] = vrf
+        .vrf_output
+        .as_slice()
+        .try_into()
+        .map_err(|_| format!("VRF output is {} bytes", vrf.vrf_output.len()))?;
+    proof
+        .verify_output(&expected_input, &public_key, &output)
+        .map_err(|_| "VRF proof verification failed".to_string())
+}
+
+fn verify_client_data(
+    client_data_json_b64u: &str,
+    expected_type: &str,
+    vrf: &VrfData,
+) -> Result<(), String> {
+    let bytes = base64_url_decode(client_data_json_b64u)
+        .map_err(|e| format!("Invalid clientDataJSON: {}", e))?;
+    let client_data: ClientData =
+        serde_json::from_slice(&bytes).map_err(|e| format!("Invalid clientDataJSON: {}", e))?;
+    if client_data.ceremony_type != expected_type {
+        return Err(format!(
+            "Expected a {} ceremony, got {}",
+            expected_type, client_data.ceremony_type
+        ));
+    }
+    let challenge = base64_url_decode(&client_data.challenge)
+        .map_err(|e| format!("Invalid challenge: {}", e))?;
+    if vrf.vrf_output.len() < 32 || challenge != vrf.vrf_output[..32] {
+        return Err("Challenge does not match the VRF output".to_string());
+    }
+    let host = client_data
+        .origin
+        .strip_prefix("https://")
+        .map(|rest| rest.split([':', '/']).next().unwrap_or(rest))
+        .ok_or_else(|| format!("Origin {} is not https", client_data.origin))?;
+    if host != vrf.rp_id && !host.ends_with(&format!(".{}", vrf.rp_id)) {
+        return Err(format!(
+            "Origin {} is not allowed for rpId {}",
+            client_data.origin, vrf.rp_id
+        ));
+    }
+    Ok(())
+}
+
+fn verify_rp_id_hash(rp_id_hash: &[u8], flags: u8, rp_id: &str) -> Result<(), String> {
+    if rp_id_hash != Sha256::digest(rp_id.as_bytes()).as_slice() {
+        return Err(format!("rpIdHash does not match rpId {}", rp_id));
+    }
+    if flags & FLAG_USER_PRESENT == 0 {
+        return Err("User presence flag is not set".to_string());
+    }
+    Ok(())
+}
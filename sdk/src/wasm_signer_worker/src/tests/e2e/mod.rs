@@ -0,0 +1,369 @@
+// End-to-end harness: registration and signing across the VRF worker, the signer worker, a mock
+// NEAR chain and a mock contract, without a browser. The VRF worker's core (wasm_vrf_worker as
+// an rlib) derives VRF keys and proves challenges; a synthetic authenticator answers the WebAuthn
+// ceremonies; the signer runs the same steps as its handlers after the confirmation UI (local
+// assertion check, challenge binding, contract verification, decryption, signing, broadcast),
+// over the real NearRpcClient talking to `MockChain`.
+//
+// Each step takes honest defaults that a test overrides to inject one fault (a stale block, a
+// foreign origin, a wrong PRF output, a flaky endpoint, ...); see e2e_tests.rs.
+
+pub mod authenticator;
+pub mod chain;
+pub mod contract;
+
+use serde_json::{json, Value};
+use vrf_wasm::ecvrf::ECVRFKeyPair;
+use wasm_vrf_worker::{VRFInputData, VRFKeyManager};
+
+use crate::assertion_verify::verify_assertion_locally;
+use crate::crypto::{
+    derive_and_encrypt_keypair_from_dual_prf, derive_webauthn_challenge,
+    verify_vrf_challenge_binding,
+};
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::{RpcErrorKind, SignerErrorCode};
+use crate::handlers::handle_check_can_register_user::{
+    check_can_register_user_with, CheckCanRegisterUserRequest, RegistrationCheckResult,
+};
+use crate::handlers::handle_sign_transactions_with_actions::{
+    sign_near_transactions_with_actions_impl, Decryption, TransactionPayload, TransactionSignResult,
+};
+use crate::rpc_calls::{verify_authentication_response_rpc_call, VrfData};
+use crate::rpc_client::{refresh_nonce_and_block, BlockReference};
+use crate::tests::block_on;
+use crate::types::{
+    DualPrfOutputs, EncryptedDataChaCha20Response, VrfChallenge, WebAuthnAuthenticationCredential,
+};
+use authenticator::{Ceremony, SyntheticAuthenticator};
+use chain::MockChain;
+use contract::StoredAuthenticator;
+
+pub const CONTRACT_ID: &str = "w3a-v1.testnet";
+pub const RP_ID: &str = "wallet.example";
+pub const ORIGIN: &str = "https://wallet.example";
+
+/// A registered account: its passkey, VRF keypair and encrypted NEAR key
+pub struct Account {
+    pub account_id: String,
+    pub authenticator: SyntheticAuthenticator,
+    pub vrf_keypair: ECVRFKeyPair,
+    pub near_public_key: String,
+    pub encrypted_key: EncryptedDataChaCha20Response,
+}
+
+/// What a ceremony is run with
+#[derive(Clone, Debug)]
+pub struct CeremonySetup {
+    pub origin: String,
+    /// rpId the authenticator hashes into authenticatorData (the VRF input always uses RP_ID)
+    pub rp_id: String,
+    /// None: the challenge derived from the VRF output
+    pub challenge: Option<Vec<u8>>,
+}
+
+impl Default for CeremonySetup {
+    fn default() -> Self {
+        CeremonySetup {
+            origin: ORIGIN.to_string(),
+            rp_id: RP_ID.to_string(),
+            challenge: None,
+        }
+    }
+}
+
+/// Inputs of a signing ceremony, honest by default (see `World::signing_setup`)
+pub struct SigningSetup<'a> {
+    pub authenticator: &'a SyntheticAuthenticator,
+    pub vrf_keypair: &'a ECVRFKeyPair,
+    /// Block the VRF challenge is anchored to
+    pub anchor: BlockReference,
+    pub ceremony: CeremonySetup,
+}
+
+/// A confirmed signing request, as the confirmation UI hands it back to the worker
+pub struct Signing {
+    pub vrf_challenge: VrfChallenge,
+    pub credential: Value,
+    /// base64url PRF first output, which decrypts the NEAR key
+    pub prf_output: String,
+    pub next_nonce: u64,
+    pub tx_block: BlockReference,
+}
+
+pub struct World {
+    pub chain: MockChain,
+    vrf: VRFKeyManager,
+}
+
+impl World {
+    pub fn new() -> Self {
+        World {
+            chain: MockChain::new(),
+            vrf: VRFKeyManager::new(None, None, None, None),
+        }
+    }
+
+    fn vrf_input(&self, account_id: &str, anchor: &BlockReference) -> VRFInputData {
+        VRFInputData::near_block(account_id, RP_ID, &anchor.height.to_string(), &anchor.hash)
+    }
+
+    /// The VRF worker's challenge, as the signer receives it
+    fn vrf_challenge(
+        &self,
+        vrf_keypair: &ECVRFKeyPair,
+        account_id: &str,
+        anchor: &BlockReference,
+    ) -> VrfChallenge {
+        let challenge = self
+            .vrf
+            .generate_vrf_challenge_with_keypair(
+                vrf_keypair,
+                self.vrf_input(account_id, anchor),
+                crate::config::DEFAULT_CHALLENGE_LENGTH,
+            )
+            .unwrap();
+        serde_json::from_value(challenge.to_json()).unwrap()
+    }
+
+    fn ceremony(&self, setup: &CeremonySetup, account_id: &str, vrf: &VrfChallenge) -> Ceremony {
+        let vrf_output = base64_url_decode(&vrf.vrf_output).unwrap();
+        Ceremony {
+            challenge: setup.challenge.clone().unwrap_or_else(|| {
+                derive_webauthn_challenge(&vrf_output, vrf.challenge_length).unwrap()
+            }),
+            origin: setup.origin.clone(),
+            rp_id: setup.rp_id.clone(),
+            account_id: account_id.to_string(),
+        }
+    }
+
+    pub fn register(&self, account_id: &str, seed: u8) -> Result<Account, String> {
+        self.register_with(
+            account_id,
+            SyntheticAuthenticator::new(seed),
+            CeremonySetup::default(),
+        )
+    }
+
+    /// A registration ceremony: the VRF keypair and challenge derived from the PRF output, and
+    /// the attestation over the challenge
+    pub fn registration_ceremony(
+        &self,
+        account_id: &str,
+        authenticator: &SyntheticAuthenticator,
+        setup: &CeremonySetup,
+    ) -> (VrfChallenge, Value, ECVRFKeyPair) {
+        let (prf_first, _) = authenticator.prf_outputs(account_id);
+        let (derived, vrf_keypair) = self
+            .vrf
+            .derive_vrf_keypair_from_prf(
+                base64_url_decode(&prf_first).unwrap(),
+                account_id.to_string(),
+                Some(base64_url_encode(&authenticator.credential_id)),
+                None,
+                Some(self.vrf_input(account_id, &self.chain.head())),
+            )
+            .unwrap();
+        let vrf_challenge: VrfChallenge =
+            serde_json::from_value(derived.vrf_challenge_data.unwrap().to_json()).unwrap();
+        let credential = authenticator.attest(&self.ceremony(setup, account_id, &vrf_challenge));
+        (vrf_challenge, credential, vrf_keypair)
+    }
+
+    /// Registration: the ceremony, the contract's check_can_register_user, NEAR key
+    /// derivation, then what the registration transaction commits (the account's access key
+    /// and the stored authenticator)
+    pub fn register_with(
+        &self,
+        account_id: &str,
+        authenticator: SyntheticAuthenticator,
+        setup: CeremonySetup,
+    ) -> Result<Account, String> {
+        let (vrf_challenge, credential, vrf_keypair) =
+            self.registration_ceremony(account_id, &authenticator, &setup);
+        let check = self.check_registration(&vrf_challenge, &credential, false)?;
+        if !check.verified {
+            return Err(check.error.unwrap_or_default());
+        }
+
+        let (prf_first, prf_second) = authenticator.prf_outputs(account_id);
+        let (near_public_key, encrypted_key) = derive_and_encrypt_keypair_from_dual_prf(
+            &DualPrfOutputs {
+                chacha20_prf_output_base64: prf_first,
+                ed25519_prf_output_base64: prf_second,
+            },
+            account_id,
+        )
+        .map_err(|e| e.to_string())?;
+        self.chain.add_access_key(account_id, &near_public_key);
+        self.chain.contract.store_authenticator(
+            authenticator.credential_id.clone(),
+            StoredAuthenticator {
+                user_id: account_id.to_string(),
+                credential_public_key: authenticator.cose_public_key(),
+                vrf_public_keys: vec![base64_url_decode(&vrf_challenge.vrf_public_key).unwrap()],
+            },
+        );
+        Ok(Account {
+            account_id: account_id.to_string(),
+            authenticator,
+            vrf_keypair,
+            near_public_key,
+            encrypted_key,
+        })
+    }
+
+    /// The signer's CheckCanRegisterUser over the mock chain
+    pub fn check_registration(
+        &self,
+        vrf_challenge: &VrfChallenge,
+        credential: &Value,
+        dry_run: bool,
+    ) -> Result<RegistrationCheckResult, String> {
+        let request: CheckCanRegisterUserRequest = serde_json::from_value(json!({
+            "vrfChallenge": vrf_challenge,
+            "credential": credential,
+            "contractId": CONTRACT_ID,
+            "nearRpcUrl": "",
+            "authenticatorOptions": null,
+            "dryRun": dry_run
+        }))
+        .unwrap();
+        block_on(check_can_register_user_with(&self.chain.rpc(), request))
+    }
+
+    /// Honest inputs for signing as `account`, anchored to the chain head
+    pub fn signing_setup<'a>(&self, account: &'a Account) -> SigningSetup<'a> {
+        SigningSetup {
+            authenticator: &account.authenticator,
+            vrf_keypair: &account.vrf_keypair,
+            anchor: self.chain.head(),
+            ceremony: CeremonySetup::default(),
+        }
+    }
+
+    /// What the main thread does before handing the request back: nonce and block, a VRF
+    /// challenge, and the passkey assertion over it
+    pub fn begin_signing(
+        &self,
+        account: &Account,
+        setup: SigningSetup,
+    ) -> Result<Signing, RpcErrorKind> {
+        let (next_nonce, tx_block) = block_on(refresh_nonce_and_block(
+            &self.chain.rpc(),
+            &account.account_id,
+            &account.near_public_key,
+        ))?;
+        let vrf_challenge =
+            self.vrf_challenge(setup.vrf_keypair, &account.account_id, &setup.anchor);
+        let ceremony = self.ceremony(&setup.ceremony, &account.account_id, &vrf_challenge);
+        Ok(Signing {
+            credential: setup.authenticator.assert(&ceremony),
+            prf_output: setup.authenticator.prf_outputs(&account.account_id).0,
+            vrf_challenge,
+            next_nonce,
+            tx_block,
+        })
+    }
+
+    /// The signer's steps after confirmation, as in handle_sign_transactions_with_actions:
+    /// local assertion check, VRF challenge binding, contract verification, then signing and
+    /// broadcasting every transaction
+    pub fn finish_signing(
+        &self,
+        account: &Account,
+        signing: Signing,
+        tx_requests: Vec<TransactionPayload>,
+    ) -> TransactionSignResult {
+        let rpc = self.chain.rpc();
+        let mut logs = Vec::new();
+        if let Err(error_msg) = verify_assertion_locally(&signing.credential) {
+            return TransactionSignResult::failed_with_code(
+                logs,
+                error_msg,
+                SignerErrorCode::AssertionSignatureInvalid,
+            );
+        }
+        let credential: WebAuthnAuthenticationCredential =
+            serde_json::from_value(signing.credential.clone()).unwrap();
+        if let Err(e) = verify_vrf_challenge_binding(
+            &signing.vrf_challenge,
+            &credential.response.client_data_json,
+        ) {
+            return TransactionSignResult::failed(
+                logs,
+                format!("VRF challenge binding failed: {}", e),
+            );
+        }
+
+        let verification = block_on(verify_authentication_response_rpc_call(
+            &rpc,
+            CONTRACT_ID,
+            VrfData::try_from(&signing.vrf_challenge).unwrap(),
+            credential,
+        ));
+        match verification {
+            Ok(result) if result.verified => logs.push("Contract verification successful".into()),
+            Ok(result) => {
+                return TransactionSignResult::failed(logs, result.error.unwrap_or_default())
+            }
+            Err(e) => {
+                return TransactionSignResult::failed(
+                    logs,
+                    format!("Contract verification failed: {}", e),
+                )
+            }
+        }
+
+        let confirmation = serde_json::from_value(json!({
+            "confirmed": true,
+            "request_id": "e2e",
+            "prf_output": signing.prf_output,
+            "transaction_context": {
+                "nearPublicKeyStr": account.near_public_key,
+                "nextNonce": signing.next_nonce.to_string(),
+                "txBlockHeight": signing.tx_block.height.to_string(),
+                "txBlockHash": signing.tx_block.hash
+            }
+        }))
+        .unwrap();
+        let decryption = Decryption::new(
+            signing.prf_output.clone(),
+            account.encrypted_key.encrypted_near_key_data_b64u.clone(),
+            account.encrypted_key.chacha20_nonce_b64u.clone(),
+        );
+        block_on(sign_near_transactions_with_actions_impl(
+            tx_requests,
+            &decryption,
+            &confirmation,
+            Some(&rpc),
+            None,
+            None,
+            logs,
+        ))
+        .unwrap()
+    }
+
+    /// Signs and broadcasts `tx_requests` with honest inputs
+    pub fn sign(
+        &self,
+        account: &Account,
+        tx_requests: Vec<TransactionPayload>,
+    ) -> TransactionSignResult {
+        let signing = self
+            .begin_signing(account, self.signing_setup(account))
+            .unwrap();
+        self.finish_signing(account, signing, tx_requests)
+    }
+}
+
+/// A transfer of `yocto` from `account` to `receiver_id`
+pub fn transfer(account: &Account, receiver_id: &str, yocto: &str) -> TransactionPayload {
+    TransactionPayload {
+        near_account_id: account.account_id.clone(),
+        receiver_id: receiver_id.to_string(),
+        actions: json!([{ "action_type": "Transfer", "deposit": yocto }]).to_string(),
+        depends_on: Vec::new(),
+    }
+}
@@ -0,0 +1,428 @@
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::SignerErrorCode;
+use crate::handlers::handle_sign_transactions_with_actions::TransactionSignResult;
+use crate::rpc_client::BlockReference;
+use crate::tests::e2e::authenticator::{Ceremony, SyntheticAuthenticator};
+use crate::tests::e2e::chain::{Flake, FALLBACK, PRIMARY};
+use crate::tests::e2e::contract::MAX_BLOCK_AGE;
+use crate::tests::e2e::*;
+
+const ALICE: &str = "alice.testnet";
+const BOB: &str = "bob.testnet";
+
+fn registered(world: &World, account_id: &str, seed: u8) -> Account {
+    world.register(account_id, seed).unwrap()
+}
+
+fn assert_rejected(result: &TransactionSignResult, reason: &str) {
+    assert!(!result.success, "signed despite: {}", reason);
+    let error = result.error.as_deref().unwrap_or_default();
+    assert!(error.contains(reason), "{}", error);
+}
+
+/// Signs with `setup` adjusted by `inject`; nothing may reach the chain when it fails
+fn sign_injected<'a>(
+    world: &World,
+    account: &'a Account,
+    inject: impl FnOnce(&mut SigningSetup<'a>),
+) -> TransactionSignResult {
+    let mut setup = world.signing_setup(account);
+    inject(&mut setup);
+    let signing = world.begin_signing(account, setup).unwrap();
+    let result = world.finish_signing(account, signing, vec![transfer(account, BOB, "1")]);
+    if !result.success {
+        assert!(world.chain.broadcasts.borrow().is_empty());
+    }
+    result
+}
+
+// === HAPPY PATHS ===
+
+#[test]
+fn test_register_then_sign_and_broadcast() {
+    let world = World::new();
+    let alice = registered(&world, ALICE, 1);
+    assert!(alice.near_public_key.starts_with("ed25519:"));
+    let registered_nonce = world
+        .chain
+        .access_key_nonce(ALICE, &alice.near_public_key)
+        .unwrap();
+
+    let result = world.sign(&alice, vec![transfer(&alice, BOB, "1000")]);
+    assert!(result.success, "{:?} {:?}", result.error, result.logs);
+    assert!(result
+        .logs
+        .iter()
+        .any(|log| log == "Contract verification successful"));
+
+    let broadcasts = world.chain.broadcasts.borrow();
+    assert_eq!(broadcasts.len(), 1);
+    let tx = &broadcasts[0].transaction;
+    assert_eq!(tx.signer_id.0, ALICE);
+    assert_eq!(tx.receiver_id.0, BOB);
+    assert_eq!(tx.nonce, registered_nonce + 1);
+    assert_eq!(
+        world.chain.access_key_nonce(ALICE, &alice.near_public_key),
+        Some(registered_nonce + 1)
+    );
+    assert_eq!(result.transaction_hashes.as_ref().unwrap().len(), 1);
+}
+
+#[test]
+fn test_batches_and_later_requests_continue_the_nonce_sequence() {
+    let world = World::new();
+    let alice = registered(&world, ALICE, 1);
+    let bob = registered(&world, BOB, 2);
+
+    let batch = (0..3)
+        .map(|i| transfer(&alice, &format!("receiver-{}.testnet", i), "1"))
+        .collect();
+    let result = world.sign(&alice, batch);
+    assert!(result.success, "{:?}", result.error);
+    assert!(world.sign(&bob, vec![transfer(&bob, ALICE, "5")]).success);
+    let result = world.sign(&alice, vec![transfer(&alice, BOB, "2")]);
+    assert!(result.success, "{:?}", result.error);
+
+    let broadcasts = world.chain.broadcasts.borrow();
+    let alice_nonces: Vec<u64> = broadcasts
+        .iter()
+        .filter(|signed| signed.transaction.signer_id.0 == ALICE)
+        .map(|signed| signed.transaction.nonce)
+        .collect();
+    assert_eq!(alice_nonces.len(), 4);
+    assert!(alice_nonces.windows(2).all(|pair| pair[1] == pair[0] + 1));
+    let receivers: Vec<&str> = broadcasts[..3]
+        .iter()
+        .map(|signed| signed.transaction.receiver_id.0.as_str())
+        .collect();
+    assert_eq!(
+        receivers,
+        vec![
+            "receiver-0.testnet",
+            "receiver-1.testnet",
+            "receiver-2.testnet"
+        ]
+    );
+}
+
+#[test]
+fn test_subdomain_origins_and_older_blocks_within_the_window_are_accepted() {
+    let world = World::new();
+    let alice = registered(&world, ALICE, 1);
+    let anchor = world.chain.head();
+    world.chain.produce_blocks(MAX_BLOCK_AGE);
+
+    let result = sign_injected(&world, &alice, |setup| {
+        setup.ceremony.origin = format!("https://app.{}", RP_ID);
+        setup.anchor = anchor;
+    });
+    assert!(result.success, "{:?}", result.error);
+}
+
+// === FAILURE INJECTION ===
+
+#[test]
+fn test_stale_vrf_block_is_rejected_by_the_contract() {
+    let world = World::new();
+    let alice = registered(&world, ALICE, 1);
+    let anchor = world.chain.head();
+    world.chain.produce_blocks(MAX_BLOCK_AGE + 1);
+
+    let result = sign_injected(&world, &alice, |setup| setup.anchor = anchor);
+    assert_rejected(&result, "StaleChallenge");
+}
+
+#[test]
+fn test_vrf_challenge_on_an_unknown_block_hash_is_rejected() {
+    let world = World::new();
+    let alice = registered(&world, ALICE, 1);
+    let head = world.chain.head();
+
+    let result = sign_injected(&world, &alice, |setup| {
+        setup.anchor = BlockReference {
+            height: head.height,
+            hash: bs58::encode([7u8; 32]).into_string(),
+        }
+    });
+    assert_rejected(&result, "Block hash does not match");
+}
+
+#[test]
+fn test_assertion_from_a_foreign_origin_is_rejected() {
+    let world = World::new();
+    let alice = registered(&world, ALICE, 1);
+
+    for origin in [
+        "https://wallet-example.com",
+        "https://evilwallet.example",
+        "http://wallet.example",
+    ] {
+        let result = sign_injected(&world, &alice, |setup| {
+            setup.ceremony.origin = origin.to_string()
+        });
+        assert!(!result.success, "{}", origin);
+        let error = result.error.unwrap();
+        assert!(error.contains("Origin"), "{}: {}", origin, error);
+    }
+}
+
+#[test]
+fn test_assertion_scoped_to_another_rp_id_is_rejected() {
+    let world = World::new();
+    let alice = registered(&world, ALICE, 1);
+
+    let result = sign_injected(&world, &alice, |setup| {
+        setup.ceremony.rp_id = "evil.example".to_string()
+    });
+    assert_rejected(&result, "rpIdHash does not match");
+}
+
+#[test]
+fn test_challenge_binding_mismatch_fails_before_the_contract_is_asked() {
+    let world = World::new();
+    let alice = registered(&world, ALICE, 1);
+    let mut setup = world.signing_setup(&alice);
+    setup.ceremony.challenge = Some(vec![7; 32]);
+    let signing = world.begin_signing(&alice, setup).unwrap();
+    let served = world.chain.served.borrow().len();
+
+    let result = world.finish_signing(&alice, signing, vec![transfer(&alice, BOB, "1")]);
+    assert_rejected(&result, "VRF challenge binding failed");
+    assert_eq!(world.chain.served.borrow().len(), served);
+}
+
+#[test]
+fn test_tampered_vrf_proof_is_rejected() {
+    let world = World::new();
+    let alice = registered(&world, ALICE, 1);
+    let mut signing = world
+        .begin_signing(&alice, world.signing_setup(&alice))
+        .unwrap();
+    let mut proof = base64_url_decode(&signing.vrf_challenge.vrf_proof).unwrap();
+    proof[40] ^= 0x01;
+    signing.vrf_challenge.vrf_proof = base64_url_encode(&proof);
+
+    let result = world.finish_signing(&alice, signing, vec![transfer(&alice, BOB, "1")]);
+    assert_rejected(&result, "VRF proof");
+    assert!(world.chain.broadcasts.borrow().is_empty());
+}
+
+#[test]
+fn test_challenge_proven_with_another_accounts_vrf_key_is_rejected() {
+    let world = World::new();
+    let alice = registered(&world, ALICE, 1);
+    let bob = registered(&world, BOB, 2);
+
+    let result = sign_injected(&world, &alice, |setup| setup.vrf_keypair = &bob.vrf_keypair);
+    assert_rejected(&result, "VRF public key is not bound");
+}
+
+#[test]
+fn test_unregistered_credential_is_rejected() {
+    let world = World::new();
+    let alice = registered(&world, ALICE, 1);
+    let stranger = SyntheticAuthenticator::new(99);
+
+    let result = sign_injected(&world, &alice, |setup| setup.authenticator = &stranger);
+    assert_rejected(&result, "No authenticator");
+
+    // Bob's own passkey does not sign for Alice either
+    let bob = registered(&world, BOB, 2);
+    let result = sign_injected(&world, &alice, |setup| {
+        setup.authenticator = &bob.authenticator
+    });
+    assert_rejected(&result, "No authenticator");
+}
+
+#[test]
+fn test_wrong_prf_output_fails_decryption_after_verification() {
+    let world = World::new();
+    let alice = registered(&world, ALICE, 1);
+    let mut signing = world
+        .begin_signing(&alice, world.signing_setup(&alice))
+        .unwrap();
+    signing.prf_output = SyntheticAuthenticator::new(2).prf_outputs(ALICE).0;
+
+    let result = world.finish_signing(&alice, signing, vec![transfer(&alice, BOB, "1")]);
+    assert_rejected(&result, "Decryption failed");
+    assert_eq!(
+        result.error_code.as_deref(),
+        Some(SignerErrorCode::WrongCredentialForBlob.as_str())
+    );
+    assert!(world.chain.broadcasts.borrow().is_empty());
+}
+
+#[test]
+fn test_forged_assertion_signature_is_caught_locally_or_by_the_contract() {
+    let world = World::new();
+    let alice = registered(&world, ALICE, 1);
+    // A real signature by the credential, over another ceremony
+    let other_ceremony = alice.authenticator.assert(&Ceremony {
+        challenge: vec![1; 32],
+        origin: ORIGIN.to_string(),
+        rp_id: RP_ID.to_string(),
+        account_id: ALICE.to_string(),
+    });
+    let forge = |with_public_key: bool| {
+        let mut signing = world
+            .begin_signing(&alice, world.signing_setup(&alice))
+            .unwrap();
+        signing.credential["response"]["signature"] =
+            other_ceremony["response"]["signature"].clone();
+        if !with_public_key {
+            let response = signing.credential["response"].as_object_mut().unwrap();
+            response.remove("credentialPublicKey");
+        }
+        world.finish_signing(&alice, signing, vec![transfer(&alice, BOB, "1")])
+    };
+
+    // With the forwarded public key the worker refuses it before any contract call
+    let served = world.chain.served.borrow().len();
+    let result = forge(true);
+    assert_eq!(
+        result.error_code.as_deref(),
+        Some(SignerErrorCode::AssertionSignatureInvalid.as_str())
+    );
+    // begin_signing's nonce and block queries only
+    assert_eq!(world.chain.served.borrow().len(), served + 2);
+
+    let result = forge(false);
+    assert_rejected(&result, "WebAuthn signature verification failed");
+    assert!(world.chain.broadcasts.borrow().is_empty());
+}
+
+#[test]
+fn test_nonce_race_is_resigned_on_a_fresh_nonce() {
+    let world = World::new();
+    let alice = registered(&world, ALICE, 1);
+    let signing = world
+        .begin_signing(&alice, world.signing_setup(&alice))
+        .unwrap();
+    let planned_nonce = signing.next_nonce;
+    // Another tab's transaction lands between confirmation and broadcast
+    world.chain.bump_nonce(ALICE, &alice.near_public_key);
+
+    let result = world.finish_signing(&alice, signing, vec![transfer(&alice, BOB, "1")]);
+    assert!(result.success, "{:?}", result.error);
+    assert!(result.logs.iter().any(|log| log.contains("InvalidNonce")));
+    let broadcasts = world.chain.broadcasts.borrow();
+    assert_eq!(broadcasts.len(), 1);
+    assert_eq!(broadcasts[0].transaction.nonce, planned_nonce + 1);
+}
+
+#[test]
+fn test_flaky_primary_endpoint_fails_over_to_the_fallback() {
+    let world = World::new();
+    let alice = registered(&world, ALICE, 1);
+    world.chain.flake(
+        PRIMARY,
+        &[
+            Flake::Unreachable,
+            Flake::ServiceUnavailable,
+            Flake::Unreachable,
+        ],
+    );
+
+    // Nonce, block and contract verification land on the fallback
+    let result = world.sign(&alice, vec![transfer(&alice, BOB, "1")]);
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(world.chain.broadcasts.borrow().len(), 1);
+    let fallback = world.chain.served_by(FALLBACK);
+    assert_eq!(
+        fallback,
+        vec!["query", "block", "query"],
+        "{:?}",
+        world.chain.served.borrow()
+    );
+    // The primary answers again once it recovers
+    assert_eq!(
+        world.chain.served.borrow().last().unwrap(),
+        &(PRIMARY.to_string(), "broadcast_tx_commit".to_string())
+    );
+}
+
+#[test]
+fn test_all_endpoints_down_is_reported_as_unreachable() {
+    let world = World::new();
+    let alice = registered(&world, ALICE, 1);
+
+    // Mid-request: confirmed, then the network goes away before contract verification
+    let signing = world
+        .begin_signing(&alice, world.signing_setup(&alice))
+        .unwrap();
+    world.chain.take_down(PRIMARY, Flake::Unreachable);
+    world.chain.take_down(FALLBACK, Flake::ServiceUnavailable);
+    let result = world.finish_signing(&alice, signing, vec![transfer(&alice, BOB, "1")]);
+    assert_rejected(&result, "RpcUnreachable");
+
+    // Before it: the nonce and block fetch fails with the retriable network code
+    let error = world
+        .begin_signing(&alice, world.signing_setup(&alice))
+        .err()
+        .unwrap();
+    assert_eq!(error.code(), SignerErrorCode::RpcUnreachable);
+    assert!(error.code().definition().retriable);
+    assert!(world.chain.broadcasts.borrow().is_empty());
+}
+
+#[test]
+fn test_registration_rejections() {
+    let world = World::new();
+    let register = |account_id: &str, seed: u8, setup: CeremonySetup| {
+        world
+            .register_with(account_id, SyntheticAuthenticator::new(seed), setup)
+            .err()
+            .unwrap_or_else(|| panic!("{} registered", account_id))
+    };
+
+    let error = register(
+        ALICE,
+        1,
+        CeremonySetup {
+            origin: "https://phish.example".to_string(),
+            ..CeremonySetup::default()
+        },
+    );
+    assert!(
+        error.contains("Origin https://phish.example is not allowed"),
+        "{}",
+        error
+    );
+
+    let error = register(
+        ALICE,
+        1,
+        CeremonySetup {
+            challenge: Some(vec![3; 32]),
+            ..CeremonySetup::default()
+        },
+    );
+    assert!(error.contains("Challenge does not match"), "{}", error);
+
+    let error = register(
+        ALICE,
+        1,
+        CeremonySetup {
+            rp_id: "evil.example".to_string(),
+            ..CeremonySetup::default()
+        },
+    );
+    assert!(error.contains("rpIdHash does not match"), "{}", error);
+    assert!(world.chain.access_key_nonce(ALICE, "").is_none());
+
+    // A dry run over a taken account id reports it without registering anything
+    registered(&world, ALICE, 1);
+    let authenticator = SyntheticAuthenticator::new(5);
+    let (vrf_challenge, credential, _) =
+        world.registration_ceremony(ALICE, &authenticator, &CeremonySetup::default());
+    let check = world
+        .check_registration(&vrf_challenge, &credential, true)
+        .unwrap();
+    assert_eq!(check.dry_run.unwrap().outcome, "AccountExists");
+    let (vrf_challenge, credential, _) =
+        world.registration_ceremony(BOB, &authenticator, &CeremonySetup::default());
+    let check = world
+        .check_registration(&vrf_challenge, &credential, true)
+        .unwrap();
+    assert!(check.dry_run.unwrap().would_succeed);
+}
@@ -0,0 +1,240 @@
+use serde_json::json;
+
+use crate::actions::ActionParams;
+use crate::clock::{ManualClock, SharedClock};
+use crate::dispatch_signer_message;
+use crate::elevation::*;
+use crate::error::SignerErrorCode;
+use crate::state;
+use crate::tests::{assertion_for_challenge, block_on, elevation_assertion, in_fresh_worker};
+use crate::types::handlers::WorkerPolicy;
+use crate::types::worker_messages::{SignerWorkerMessage, WorkerRequestType, WorkerResponseType};
+use crate::worker_state;
+
+const ACCOUNT_ID: &str = "alice.testnet";
+const PUBLIC_KEY: &str = "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp";
+
+fn add_key(access_key: &str) -> ActionParams {
+    ActionParams::AddKey {
+        public_key: PUBLIC_KEY.to_string(),
+        access_key: access_key.to_string(),
+    }
+}
+
+fn full_access() -> ActionParams {
+    add_key(r#"{"nonce":0,"permission":{"FullAccess":{}}}"#)
+}
+
+fn function_call_access() -> ActionParams {
+    add_key(
+        r#"{"nonce":0,"permission":{"FunctionCall":{"allowance":null,"receiver_id":"game.testnet","method_names":[]}}}"#,
+    )
+}
+
+fn delete_key() -> ActionParams {
+    ActionParams::DeleteKey {
+        public_key: PUBLIC_KEY.to_string(),
+    }
+}
+
+fn batch(actions: Vec<ActionParams>) -> Vec<(String, Vec<ActionParams>)> {
+    vec![(ACCOUNT_ID.to_string(), actions)]
+}
+
+#[test]
+fn test_default_and_policy_elevated_operations() {
+    assert!(is_elevated(None, ElevatedOperation::ExportPrivateKey));
+    assert!(is_elevated(None, ElevatedOperation::AddFullAccessKey));
+    assert!(!is_elevated(None, ElevatedOperation::DeleteKey));
+    assert_eq!(max_age_ms(None), 60_000);
+
+    let policy: WorkerPolicy = serde_json::from_value(json!({
+        "elevatedOperations": ["deleteKey", "deployContract"],
+        "elevationMaxAgeMs": 30_000
+    }))
+    .unwrap();
+    assert!(is_elevated(Some(&policy), ElevatedOperation::DeleteKey));
+    assert!(is_elevated(
+        Some(&policy),
+        ElevatedOperation::DeployContract
+    ));
+    assert!(!is_elevated(
+        Some(&policy),
+        ElevatedOperation::DeleteAccount
+    ));
+    // The defaults cannot be turned off
+    assert!(is_elevated(
+        Some(&policy),
+        ElevatedOperation::AddFullAccessKey
+    ));
+    assert_eq!(max_age_ms(Some(&policy)), 30_000);
+
+    assert!(serde_json::from_value::<WorkerPolicy>(json!({
+        "elevatedOperations": ["exportMnemonic"]
+    }))
+    .is_err());
+}
+
+#[test]
+fn test_batch_operations() {
+    // Key rotation: a full access key added, the old one deleted
+    let rotation = batch(vec![full_access(), delete_key()]);
+    assert_eq!(
+        batch_operations(None, &rotation),
+        vec![ElevatedOperation::AddFullAccessKey]
+    );
+    let policy = WorkerPolicy {
+        elevated_operations: vec![ElevatedOperation::DeleteKey],
+        ..WorkerPolicy::default()
+    };
+    assert_eq!(
+        batch_operations(Some(&policy), &rotation),
+        vec![
+            ElevatedOperation::AddFullAccessKey,
+            ElevatedOperation::DeleteKey
+        ]
+    );
+
+    let session_key = batch(vec![function_call_access(), function_call_access()]);
+    assert!(batch_operations(Some(&policy), &session_key).is_empty());
+    let repeated = batch(vec![full_access(), full_access()]);
+    assert_eq!(batch_operations(None, &repeated).len(), 1);
+}
+
+#[test]
+fn test_check_elevation_against_the_window() {
+    let op = ElevatedOperation::AddFullAccessKey;
+    assert!(check_elevation(None, op, Some(40_000.0), 100_000.0).is_ok());
+    assert!(check_elevation(None, op, Some(100_000.0), 100_000.0).is_ok());
+
+    let err = check_elevation(None, op, Some(39_000.0), 100_000.0).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::ElevationRequired);
+    assert_eq!(err.max_age_ms, 60_000);
+    assert_eq!(err.age_ms, Some(61_000.0));
+    assert_eq!(
+        err.to_string(),
+        "ElevationRequired: addFullAccessKey needs a passkey assertion from the last 60000 ms \
+         (maxAgeMs); the latest is 61000 ms old"
+    );
+
+    let err = check_elevation(None, op, None, 100_000.0).unwrap_err();
+    assert_eq!(err.age_ms, None);
+    assert!(err
+        .to_string()
+        .ends_with("none was collected through a worker confirmation"));
+
+    // Operations outside the elevated set need no assertion at all
+    assert!(check_elevation(None, ElevatedOperation::DeleteKey, None, 100_000.0).is_ok());
+    assert!(check_batch_elevation(None, &batch(vec![delete_key()]), None, 100_000.0).is_ok());
+    assert!(check_batch_elevation(None, &batch(vec![full_access()]), None, 100_000.0).is_err());
+}
+
+#[test]
+fn test_collection_time_is_bounded_by_the_confirmation_nonce() {
+    // Without a timestamp: when the prompt was issued
+    assert_eq!(collection_time(None, 1_000.0, 5_000.0), Some(1_000.0));
+    assert_eq!(
+        collection_time(Some(3_000.0), 1_000.0, 5_000.0),
+        Some(3_000.0)
+    );
+    // A timestamp after the response arrived is capped there
+    assert_eq!(
+        collection_time(Some(9_000.0), 1_000.0, 5_000.0),
+        Some(5_000.0)
+    );
+    // A credential collected before the prompt is not evidence for it
+    assert_eq!(collection_time(Some(999.0), 1_000.0, 5_000.0), None);
+    assert_eq!(collection_time(Some(f64::NAN), 1_000.0, 5_000.0), None);
+}
+
+fn decrypt_with(
+    credential: Option<serde_json::Value>,
+    worker_policy: serde_json::Value,
+) -> serde_json::Value {
+    let response = block_on(dispatch_signer_message(SignerWorkerMessage {
+        msg_type: WorkerRequestType::DecryptPrivateKeyWithPrf as u32,
+        payload: json!({
+            "nearAccountId": ACCOUNT_ID,
+            "chacha20PrfOutput": "Y2hhY2hhMjAtcHJmLW91dHB1dA",
+            "encryptedPrivateKeyData": "AAAA",
+            "encryptedPrivateKeyIv": "AAAAAAAAAAAAAAAA",
+            "workerPolicy": worker_policy,
+            "credential": credential
+        }),
+        request_id: None,
+    }))
+    .unwrap();
+    assert_eq!(
+        WorkerResponseType::from(response.response_type),
+        WorkerResponseType::DecryptPrivateKeyWithPrfFailure
+    );
+    response.payload
+}
+
+#[test]
+fn test_raw_prf_export_needs_an_assertion_for_a_recent_elevation_challenge() {
+    let clock = ManualClock::new(1_000_000.0);
+    state::set_clock(SharedClock::new(clock.clone()));
+    state::wipe_all_state();
+
+    let payload = decrypt_with(None, json!({}));
+    assert_eq!(payload["errorCode"], json!("ElevationRequired"));
+    assert!(payload["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("ElevationRequired: exportPrivateKey"));
+
+    // An assertion for a fresh challenge lets the request through to decryption (which fails
+    // on this blob), once
+    let assertion = elevation_assertion(ACCOUNT_ID);
+    let payload = decrypt_with(Some(assertion.clone()), json!({}));
+    assert_ne!(payload["errorCode"], json!("ElevationRequired"));
+    let payload = decrypt_with(Some(assertion), json!({}));
+    assert_eq!(payload["errorCode"], json!("ElevationRequired"));
+
+    // The window runs from the challenge's issue
+    let assertion = elevation_assertion(ACCOUNT_ID);
+    clock.advance(60_001.0);
+    let payload = decrypt_with(Some(assertion.clone()), json!({}));
+    assert_eq!(payload["errorCode"], json!("ElevationRequired"));
+    let payload = decrypt_with(Some(assertion), json!({ "elevationMaxAgeMs": 120_000 }));
+    assert_ne!(payload["errorCode"], json!("ElevationRequired"));
+
+    // Challenges of another account, or not issued by the worker, prove nothing
+    let payload = decrypt_with(Some(elevation_assertion("bob.testnet")), json!({}));
+    assert_eq!(payload["errorCode"], json!("ElevationRequired"));
+    let payload = decrypt_with(
+        Some(assertion_for_challenge("cmFuZG9tLWNoYWxsZW5nZS1ieXRlcw")),
+        json!({}),
+    );
+    assert_eq!(payload["errorCode"], json!("ElevationRequired"));
+
+    // Nor do challenges issued before WipeAllState replaced the MAC key
+    let assertion = elevation_assertion(ACCOUNT_ID);
+    state::wipe_all_state();
+    let payload = decrypt_with(Some(assertion), json!({}));
+    assert_eq!(payload["errorCode"], json!("ElevationRequired"));
+}
+
+#[test]
+fn test_raw_prf_export_in_a_fresh_worker() {
+    // One worker issues the challenge; the export request reaches another one
+    let (assertion, record) = in_fresh_worker(None, || {
+        let assertion = elevation_assertion(ACCOUNT_ID);
+        (assertion, worker_state::take_changed_record())
+    });
+    let record = record.expect("issuing a challenge creates the MAC key");
+
+    let replay = assertion.clone();
+    let payload = in_fresh_worker(Some(record), move || {
+        decrypt_with(Some(assertion), json!({}))
+    });
+    assert_ne!(payload["errorCode"], json!("ElevationRequired"));
+    // Failed requests hand back their state changes too
+    let record = serde_json::from_value(payload["workerStateRecord"].clone()).ok();
+    assert!(record.is_some());
+
+    // The accepted challenge stays accepted in the workers after that
+    let payload = in_fresh_worker(record, move || decrypt_with(Some(replay), json!({})));
+    assert_eq!(payload["errorCode"], json!("ElevationRequired"));
+}
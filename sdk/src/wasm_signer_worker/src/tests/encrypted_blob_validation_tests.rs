@@ -0,0 +1,114 @@
+use crate::crypto::encrypt_data_chacha20;
+use crate::encoders::base64_url_encode;
+use crate::handlers::handle_validate_encrypted_blobs::{
+    handle_validate_encrypted_blobs, validate_encrypted_key_blob, EncryptedKeyBlob,
+    ValidateEncryptedBlobsRequest,
+};
+use crate::tests::block_on;
+
+const ACCOUNT_ID: &str = "alice.testnet";
+
+fn valid_blob() -> EncryptedKeyBlob {
+    let encrypted = encrypt_data_chacha20("ed25519:test-private-key", &[7u8; 32]).unwrap();
+    EncryptedKeyBlob {
+        blob_id: Some("alice.testnet#1".to_string()),
+        encrypted_private_key_data: encrypted.encrypted_near_key_data_b64u,
+        encrypted_private_key_iv: encrypted.chacha20_nonce_b64u,
+        version: None,
+        near_account_id: Some(ACCOUNT_ID.to_string()),
+        outer_wrap: None,
+    }
+}
+
+fn problem_codes(blob: &EncryptedKeyBlob) -> Vec<String> {
+    validate_encrypted_key_blob(blob, ACCOUNT_ID)
+        .into_iter()
+        .map(|p| p.code)
+        .collect()
+}
+
+#[test]
+fn test_freshly_encrypted_blob_is_valid() {
+    assert!(problem_codes(&valid_blob()).is_empty());
+
+    let mut explicit_v1 = valid_blob();
+    explicit_v1.version = Some(1);
+    assert!(problem_codes(&explicit_v1).is_empty());
+}
+
+#[test]
+fn test_structural_problems_are_reported() {
+    let mut truncated = valid_blob();
+    truncated.encrypted_private_key_data = base64_url_encode(&[1u8; 15]);
+    assert_eq!(problem_codes(&truncated), vec!["CiphertextTooShort"]);
+
+    let mut bad_base64 = valid_blob();
+    bad_base64.encrypted_private_key_data = "not+base64url==".to_string();
+    assert_eq!(
+        problem_codes(&bad_base64),
+        vec!["InvalidCiphertextEncoding"]
+    );
+
+    let mut short_nonce = valid_blob();
+    short_nonce.encrypted_private_key_iv = base64_url_encode(&[0u8; 8]);
+    assert_eq!(problem_codes(&short_nonce), vec!["NonceLengthMismatch"]);
+
+    let mut unknown_version = valid_blob();
+    unknown_version.version = Some(9);
+    assert_eq!(problem_codes(&unknown_version), vec!["UnknownVersion"]);
+
+    let mut other_account = valid_blob();
+    other_account.near_account_id = Some("bob.testnet".to_string());
+    assert_eq!(problem_codes(&other_account), vec!["AccountIdMismatch"]);
+
+    // Every problem on a blob is reported, not just the first
+    let mut broken = valid_blob();
+    broken.encrypted_private_key_data = "%%%".to_string();
+    broken.encrypted_private_key_iv = "%%%".to_string();
+    broken.near_account_id = Some("bob.testnet".to_string());
+    assert_eq!(
+        problem_codes(&broken),
+        vec![
+            "InvalidCiphertextEncoding",
+            "InvalidNonceEncoding",
+            "AccountIdMismatch"
+        ]
+    );
+}
+
+#[test]
+fn test_validate_encrypted_blobs_handler_reports_per_blob() {
+    let mut corrupted = valid_blob();
+    corrupted.blob_id = Some("alice.testnet#2".to_string());
+    corrupted.encrypted_private_key_iv = base64_url_encode(&[0u8; 24]);
+
+    let request: ValidateEncryptedBlobsRequest = serde_json::from_value(serde_json::json!({
+        "nearAccountId": ACCOUNT_ID,
+        "blobs": [
+            {
+                "blobId": "alice.testnet#1",
+                "encryptedPrivateKeyData": valid_blob().encrypted_private_key_data,
+                "encryptedPrivateKeyIv": valid_blob().encrypted_private_key_iv,
+            },
+            {
+                "blobId": corrupted.blob_id,
+                "encryptedPrivateKeyData": corrupted.encrypted_private_key_data,
+                "encryptedPrivateKeyIv": corrupted.encrypted_private_key_iv,
+                "version": 1,
+                "nearAccountId": ACCOUNT_ID,
+            }
+        ]
+    }))
+    .unwrap();
+
+    let result = block_on(handle_validate_encrypted_blobs(request)).unwrap();
+    assert!(!result.all_valid);
+    assert_eq!(result.reports.len(), 2);
+    assert!(result.reports[0].valid);
+    assert_eq!(result.reports[1].index, 1);
+    assert_eq!(
+        result.reports[1].blob_id.as_deref(),
+        Some("alice.testnet#2")
+    );
+    assert_eq!(result.reports[1].problems[0].code, "NonceLengthMismatch");
+}
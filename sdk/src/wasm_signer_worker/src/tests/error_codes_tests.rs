@@ -0,0 +1,121 @@
+use crate::error::SignerErrorCode;
+use crate::error_codes::{
+    category_and_retriable, error_code_of_message, find_error_code, lookup_error_code,
+    ErrorCategory, CATALOG,
+};
+use std::collections::HashSet;
+use std::path::Path;
+
+#[test]
+fn test_every_signer_code_is_catalogued() {
+    for code in SignerErrorCode::ALL {
+        let definition = code.definition();
+        assert_eq!(definition.code, code.as_str());
+        assert_eq!(find_error_code(code.as_str()), Some(definition));
+
+        let info = lookup_error_code(code.as_str()).unwrap();
+        assert_eq!(info.id, definition.id);
+        assert_eq!(info.category, definition.category.as_str());
+        assert_eq!(info.retriable, definition.retriable);
+        assert!(!info.default_message.is_empty());
+    }
+    assert!(lookup_error_code("NotACode").is_none());
+
+    // ALL lists every variant once (definition() itself is an exhaustive match)
+    let error_rs = include_str!("../error.rs");
+    let enum_body = error_rs
+        .split("pub enum SignerErrorCode {")
+        .nth(1)
+        .and_then(|rest| rest.split("\n}").next())
+        .unwrap();
+    let variants = enum_body
+        .lines()
+        .filter(|line| line.trim().ends_with(',') && !line.trim().starts_with("//"))
+        .count();
+    let unique: HashSet<&str> = SignerErrorCode::ALL.iter().map(|c| c.as_str()).collect();
+    assert_eq!(unique.len(), SignerErrorCode::ALL.len());
+    assert_eq!(SignerErrorCode::ALL.len(), variants);
+}
+
+#[test]
+fn test_catalog_ids_are_unique_and_grouped_by_category() {
+    let mut ids = HashSet::new();
+    let mut codes = HashSet::new();
+    for def in CATALOG {
+        assert!(ids.insert(def.id), "duplicate id {}", def.id);
+        assert!(codes.insert(def.code), "duplicate code {}", def.code);
+        let hundreds = match def.category {
+            ErrorCategory::UserAction => 1,
+            ErrorCategory::Policy => 2,
+            ErrorCategory::Crypto => 3,
+            ErrorCategory::Network => 4,
+            ErrorCategory::Internal => 5,
+        };
+        assert_eq!(def.id / 100, hundreds, "{} has id {}", def.code, def.id);
+    }
+}
+
+/// String literals starting with a code ("SessionKeyExpired: ...") anywhere under `dir`
+fn code_prefixed_literals(dir: &Path, found: &mut Vec<(String, String)>) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            if path.file_name().unwrap() != "tests" {
+                code_prefixed_literals(&path, found);
+            }
+            continue;
+        }
+        let source = std::fs::read_to_string(&path).unwrap();
+        for literal in source.split('"').skip(1).step_by(2) {
+            let Some((word, _)) = literal.split_once(':') else {
+                continue;
+            };
+            let humps = word.chars().filter(|c| c.is_ascii_uppercase()).count();
+            let camel_case = word.starts_with(|c: char| c.is_ascii_uppercase())
+                && word.contains(|c: char| c.is_ascii_lowercase());
+            if humps >= 2 && camel_case && word.chars().all(|c| c.is_ascii_alphanumeric()) {
+                found.push((path.display().to_string(), word.to_string()));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_handlers_only_prefix_errors_with_catalogued_codes() {
+    let mut found = Vec::new();
+    code_prefixed_literals(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+        &mut found,
+    );
+    let missing: Vec<String> = found
+        .into_iter()
+        .filter(|(_, code)| find_error_code(code).is_none())
+        .map(|(file, code)| format!("{}: {}", file, code))
+        .collect();
+    assert!(
+        missing.is_empty(),
+        "codes missing from the catalog:\n{}",
+        missing.join("\n")
+    );
+}
+
+#[test]
+fn test_codes_prefixed_to_messages_are_found() {
+    // A code prefixed to a handler's error message is reported like an explicit one
+    assert_eq!(
+        error_code_of_message("DecryptionFailed: aead::Error").map(|def| def.code),
+        Some("DecryptionFailed")
+    );
+    assert!(error_code_of_message("Failed to parse: expected value").is_none());
+    assert!(error_code_of_message("no prefix").is_none());
+
+    // Without a catalogued code an error is Internal and not retriable
+    assert_eq!(
+        category_and_retriable(None),
+        (ErrorCategory::Internal, false)
+    );
+    assert_eq!(
+        category_and_retriable(error_code_of_message("RpcTimeout: 504")),
+        (ErrorCategory::Network, true)
+    );
+}
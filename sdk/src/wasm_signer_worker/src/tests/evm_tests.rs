@@ -0,0 +1,221 @@
+use crate::crypto::{
+    decrypt_private_key_with_prf, decrypt_secp256k1_private_key_with_prf,
+    encrypt_private_key_with_prf,
+};
+use crate::encoders::base64_url_encode;
+use crate::error::SignerErrorCode;
+use crate::evm::*;
+use crate::handlers::confirm_tx_details::evm_personal_sign_confirm_request;
+use crate::handlers::handle_sign_nep413_message::{
+    handle_sign_nep413_message, SignNep413Request, SigningChain,
+};
+use crate::tests::block_on;
+use crate::types::crypto::EncryptedDataChaCha20Response;
+use k256::ecdsa::SigningKey;
+use serde_json::json;
+
+const ACCOUNT_ID: &str = "alice.testnet";
+const PRF_OUTPUT: &str = "dGVzdC1wcmYtb3V0cHV0LWZyb20td2ViYXV0aG4";
+
+// web3.js `eth.accounts.sign("Some data", key)` vector
+const VECTOR_KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+fn vector_key() -> SigningKey {
+    let bytes: Vec<u8> = (0..VECTOR_KEY.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&VECTOR_KEY[i..i + 2], 16).unwrap())
+        .collect();
+    SigningKey::from_slice(&bytes).unwrap()
+}
+
+fn encrypted_key(private_key: &str) -> EncryptedDataChaCha20Response {
+    encrypt_private_key_with_prf(private_key, PRF_OUTPUT, ACCOUNT_ID).unwrap()
+}
+
+fn secp256k1_blob() -> EncryptedDataChaCha20Response {
+    encrypted_key(&format!(
+        "secp256k1:{}",
+        bs58::encode(vector_key().to_bytes()).into_string()
+    ))
+}
+
+fn ed25519_blob() -> EncryptedDataChaCha20Response {
+    let (private_key, _) = crate::crypto::generate_near_keypair().unwrap();
+    encrypted_key(&private_key)
+}
+
+#[test]
+fn test_keccak256_and_personal_sign_hash_vectors() {
+    assert_eq!(
+        hex_encode(&keccak256(b"")),
+        "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+    );
+    assert_eq!(
+        hex_encode(&personal_sign_hash(b"Some data")),
+        "0x1da44b586eb0729ff70a73c326926f6ed5a25f5b056e7f47fbc6e58d86871655"
+    );
+    // The length is the decimal byte count, of the bytes rather than the hex
+    assert_eq!(
+        personal_sign_hash(&message_bytes("0x536f6d652064617461").unwrap()),
+        personal_sign_hash(b"Some data")
+    );
+}
+
+#[test]
+fn test_sign_personal_message_matches_web3_vector() {
+    let signature = sign_personal_message(&vector_key(), b"Some data").unwrap();
+    assert_eq!(
+        signature.signature,
+        "0xb91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd\
+         6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a0291c"
+    );
+    assert_eq!(
+        signature.r,
+        "0xb91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd"
+    );
+    assert_eq!(
+        signature.s,
+        "0x6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a029"
+    );
+    assert_eq!(signature.v, 28);
+    assert_eq!(
+        signature.address,
+        "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23"
+    );
+    assert_eq!(
+        serde_json::to_value(&signature).unwrap()["address"],
+        json!("0x2c7536E3605D9C16a7a3D7b1898e529396a65c23")
+    );
+}
+
+#[test]
+fn test_message_bytes_and_display() {
+    assert_eq!(message_bytes("hello").unwrap(), b"hello");
+    assert_eq!(message_bytes("0x68656c6c6f").unwrap(), b"hello");
+    assert_eq!(message_bytes("0x").unwrap(), Vec::<u8>::new());
+    assert!(message_bytes("0x123").unwrap_err().contains("odd number"));
+    assert!(message_bytes("0xzz").unwrap_err().contains("Invalid hex"));
+
+    assert_eq!(display_message(b"hello"), "hello");
+    assert_eq!(display_message(&[0xff, 0x00]), "0xff00");
+}
+
+#[test]
+fn test_keys_sign_only_for_their_chain() {
+    let ed25519 = ed25519_blob();
+    let error = decrypt_secp256k1_private_key_with_prf(
+        ACCOUNT_ID,
+        PRF_OUTPUT,
+        &ed25519.encrypted_near_key_data_b64u,
+        &ed25519.chacha20_nonce_b64u,
+    )
+    .unwrap_err();
+    assert_eq!(error.code(), Some(SignerErrorCode::WrongCurveForChain));
+    assert!(
+        error.to_string().starts_with("WrongCurveForChain: "),
+        "{}",
+        error
+    );
+
+    let secp256k1 = secp256k1_blob();
+    let signing_key = decrypt_secp256k1_private_key_with_prf(
+        ACCOUNT_ID,
+        PRF_OUTPUT,
+        &secp256k1.encrypted_near_key_data_b64u,
+        &secp256k1.chacha20_nonce_b64u,
+    )
+    .unwrap();
+    assert_eq!(signing_key, vector_key());
+
+    let error = decrypt_private_key_with_prf(
+        ACCOUNT_ID,
+        PRF_OUTPUT,
+        &secp256k1.encrypted_near_key_data_b64u,
+        &secp256k1.chacha20_nonce_b64u,
+    )
+    .unwrap_err();
+    assert_eq!(error.code(), Some(SignerErrorCode::WrongCurveForChain));
+}
+
+#[test]
+fn test_nep413_signing_with_a_secp256k1_key_surfaces_wrong_curve() {
+    let secp256k1 = secp256k1_blob();
+    let request: SignNep413Request = serde_json::from_value(json!({
+        "message": "hello",
+        "recipient": "app.testnet",
+        "nonce": crate::encoders::base64_standard_encode(&[7u8; 32]),
+        "accountId": ACCOUNT_ID,
+        "encryptedPrivateKeyData": secp256k1.encrypted_near_key_data_b64u,
+        "encryptedPrivateKeyIv": secp256k1.chacha20_nonce_b64u,
+        "prfOutput": PRF_OUTPUT,
+    }))
+    .unwrap();
+    assert_eq!(request.chain, SigningChain::Near);
+    let error = block_on(handle_sign_nep413_message(request)).unwrap_err();
+    assert!(error.starts_with("WrongCurveForChain: "), "{}", error);
+}
+
+#[test]
+fn test_nep413_signing_requires_its_fields() {
+    let request: SignNep413Request = serde_json::from_value(json!({
+        "recipient": "app.testnet",
+        "accountId": ACCOUNT_ID,
+        "encryptedPrivateKeyData": "AAAA",
+        "encryptedPrivateKeyIv": "BBBB",
+    }))
+    .unwrap();
+    let error = block_on(handle_sign_nep413_message(request)).unwrap_err();
+    assert_eq!(error, "`message` is required for NEP-413 signing");
+}
+
+#[test]
+fn test_signing_chain_wire_format() {
+    let chain: SigningChain =
+        serde_json::from_value(json!({ "kind": "evmPersonalSign", "message": "0x01" })).unwrap();
+    assert_eq!(
+        chain,
+        SigningChain::EvmPersonalSign {
+            message: "0x01".to_string()
+        }
+    );
+    assert_eq!(
+        serde_json::from_value::<SigningChain>(json!({ "kind": "near" })).unwrap(),
+        SigningChain::Near
+    );
+    assert!(serde_json::from_value::<SigningChain>(json!({ "kind": "solana" })).is_err());
+    assert!(serde_json::from_value::<SigningChain>(json!({ "kind": "evmPersonalSign" })).is_err());
+}
+
+#[test]
+fn test_personal_sign_confirm_request_shows_the_decoded_message() {
+    let request =
+        evm_personal_sign_confirm_request("req-evm", ACCOUNT_ID, b"Some data", None, 1_000.0);
+    let digest = base64_url_encode(&personal_sign_hash(b"Some data"));
+    assert_eq!(request["type"], json!("evmPersonalSign"));
+    assert_eq!(request["requestId"], json!("req-evm"));
+    assert_eq!(request["intentDigest"], json!(digest));
+    assert_eq!(request["summary"]["message"], json!("Some data"));
+    assert_eq!(request["payload"]["nearAccountId"], json!(ACCOUNT_ID));
+    assert_eq!(request["payload"]["message"], json!("Some data"));
+    assert_eq!(
+        request["payload"]["messageHex"],
+        json!("0x536f6d652064617461")
+    );
+    assert_eq!(request["payload"]["intentDigest"], json!(digest));
+    assert_eq!(
+        request["payload"]["confirmationExpiresAtMs"],
+        json!(1_000.0)
+    );
+    assert_eq!(
+        request["payload"]["summaryBlocks"][2],
+        json!({
+            "kind": "codeBlock",
+            "json": "\"Some data\"",
+            "ariaLabel": "Message: Some data"
+        })
+    );
+
+    // Bytes that are not UTF-8 are shown as hex
+    let request = evm_personal_sign_confirm_request("req", ACCOUNT_ID, &[0xff], None, 0.0);
+    assert_eq!(request["payload"]["message"], json!("0xff"));
+}
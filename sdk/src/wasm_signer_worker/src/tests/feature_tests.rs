@@ -0,0 +1,270 @@
+use crate::dispatch_signer_message;
+use crate::config::RESPONSE_SCHEMA_VERSION;
+use crate::error::{FeatureNotCompiledError, SignerErrorCode};
+use crate::features::*;
+use crate::tests::block_on;
+use crate::types::wasm_to_json::ToJson;
+use crate::types::worker_messages::{SignerWorkerMessage, WorkerRequestType, WorkerResponseType};
+use serde_json::json;
+use std::path::Path;
+use std::process::Command;
+
+/// Release size of `--no-default-features` for wasm32 (no wasm-opt), with headroom over the
+/// 4.12 MB measured when the features were split out; the default build is about 4.71 MB
+const MINIMAL_WASM_SIZE_BUDGET_BYTES: u64 = 4_300_000;
+
+/// Dependencies every build links; an optional feature's dependency must not land here
+const MINIMAL_BUILD_DEPENDENCIES: [&str; 25] = [
+    "argon2",
+    "bs58",
+    "base64ct",
+    "borsh",
+    "chacha20poly1305",
+    "ciborium",
+    "curve25519-dalek",
+    "ed25519-dalek",
+    "getrandom",
+    "hkdf",
+    "hmac",
+    "p256",
+    "rsa",
+    "sha2",
+    "serde",
+    "serde-wasm-bindgen",
+    "serde_json",
+    "serde_bytes",
+    "wasm-bindgen",
+    "log",
+    "wasm-logger",
+    "web-sys",
+    "wasm-bindgen-futures",
+    "js-sys",
+    "zeroize",
+];
+
+const GET_WORKER_INFO: u32 = 37;
+#[cfg(not(feature = "nep413"))]
+const SIGN_NEP413_MESSAGE: u32 = 7;
+
+const MANIFEST: &str = include_str!("../../Cargo.toml");
+
+/// `name = value` lines of a manifest table, continuation lines skipped
+fn manifest_table(table: &str) -> Vec<(&'static str, &'static str)> {
+    let header = format!("[{}]", table);
+    MANIFEST
+        .lines()
+        .skip_while(|line| line.trim() != header)
+        .skip(1)
+        .take_while(|line| !line.starts_with('['))
+        .filter(|line| !line.starts_with('#') && !line.starts_with(' '))
+        .filter_map(|line| line.split_once(" = "))
+        .collect()
+}
+
+#[test]
+#[cfg(all(
+    feature = "secp256k1",
+    feature = "nep413",
+    feature = "device-linking",
+    feature = "relayer",
+    feature = "telemetry"
+))]
+fn test_default_build_compiles_every_feature_but_wasm_threads() {
+    assert_eq!(
+        compiled_features(),
+        vec![
+            "secp256k1",
+            "nep413",
+            "device-linking",
+            "relayer",
+            "telemetry"
+        ]
+    );
+    assert!(is_compiled("relayer"));
+    assert!(!is_compiled("wasm-threads"));
+    assert!(!is_compiled("not-a-feature"));
+
+    let info = WorkerInfo::current().to_json().unwrap();
+    assert_eq!(info["features"], json!(compiled_features()));
+    assert_eq!(info["version"], json!(env!("CARGO_PKG_VERSION")));
+    assert_eq!(info["responseSchemaVersion"], json!(RESPONSE_SCHEMA_VERSION));
+
+    for msg_type in 0..=59u32 {
+        assert!(check_feature_compiled(WorkerRequestType::from(msg_type)).is_ok());
+    }
+}
+
+#[test]
+fn test_required_feature_of_each_optional_handler() {
+    let cases = [
+        (WorkerRequestType::SignNep413Message, Some("nep413")),
+        (
+            WorkerRequestType::SignTransactionWithKeyPair,
+            Some("device-linking"),
+        ),
+        (
+            WorkerRequestType::CreateRemoteConfirmation,
+            Some("device-linking"),
+        ),
+        (
+            WorkerRequestType::ApproveRemoteConfirmation,
+            Some("device-linking"),
+        ),
+        (
+            WorkerRequestType::CompleteRemoteConfirmation,
+            Some("device-linking"),
+        ),
+        (WorkerRequestType::SubmitToRelayer, Some("relayer")),
+        (WorkerRequestType::CompleteRegistration, Some("relayer")),
+        (WorkerRequestType::GetTelemetrySnapshot, Some("telemetry")),
+        (WorkerRequestType::SignTransactionsWithActions, None),
+        (WorkerRequestType::PrepareRegistration, None),
+        (WorkerRequestType::GetWorkerInfo, None),
+        (WorkerRequestType::ValidateDecryptionCapability, None),
+        (WorkerRequestType::ListActiveAccounts, None),
+        (WorkerRequestType::WipeAccountState, None),
+        (WorkerRequestType::ValidateArgsSchemas, None),
+        (WorkerRequestType::SummarizeTransactions, None),
+        (WorkerRequestType::MigrateEncryptedBlobs, None),
+        (WorkerRequestType::BuildCredentialCreationOptions, None),
+        (WorkerRequestType::BuildCredentialRequestOptions, None),
+        (WorkerRequestType::RecoverPendingOperations, None),
+        (WorkerRequestType::ComposeCreateSubaccount, None),
+        (WorkerRequestType::StartBackgroundRefresh, None),
+        (WorkerRequestType::StopBackgroundRefresh, None),
+        (WorkerRequestType::BackgroundRefreshStatus, None),
+        (WorkerRequestType::SuspendHint, None),
+        (WorkerRequestType::ResumeHint, None),
+        (WorkerRequestType::DeriveSubKey, None),
+        (WorkerRequestType::ViewCall, None),
+        (WorkerRequestType::DumpDiagnostics, None),
+        (WorkerRequestType::SignTransactionsWithFreshChallenge, None),
+        (WorkerRequestType::GetSessionStatus, None),
+        (WorkerRequestType::BuildRedirectPayload, None),
+        (WorkerRequestType::IssueElevationChallenge, None),
+    ];
+    for (request_type, feature) in cases {
+        assert_eq!(
+            required_feature(request_type),
+            feature,
+            "{}",
+            request_type.name()
+        );
+    }
+
+    // Every required feature is a known one
+    for msg_type in 0..=59u32 {
+        if let Some(feature) = required_feature(WorkerRequestType::from(msg_type)) {
+            assert!(
+                FEATURES.iter().any(|(name, _)| *name == feature),
+                "{}",
+                feature
+            );
+        }
+    }
+}
+
+#[test]
+fn test_feature_not_compiled_error_names_the_feature() {
+    let error = FeatureNotCompiledError { feature: "nep413" };
+    assert_eq!(error.code(), SignerErrorCode::FeatureNotCompiled);
+    assert_eq!(
+        error.to_string(),
+        "FeatureNotCompiled: the signer worker was built without the `nep413` feature"
+    );
+    let definition = error.code().definition();
+    assert_eq!(definition.code, "FeatureNotCompiled");
+    assert!(!definition.retriable);
+}
+
+#[test]
+fn test_get_worker_info_reports_the_compiled_features() {
+    let response = block_on(dispatch_signer_message(SignerWorkerMessage {
+        msg_type: GET_WORKER_INFO,
+        payload: json!({}),
+        request_id: None,
+    }))
+    .unwrap();
+    assert_eq!(
+        WorkerResponseType::from(response.response_type),
+        WorkerResponseType::GetWorkerInfoSuccess
+    );
+    assert_eq!(response.payload["features"], json!(compiled_features()));
+}
+
+/// Run with `cargo test --no-default-features feature_tests`
+#[test]
+#[cfg(not(feature = "nep413"))]
+fn test_request_for_a_left_out_feature_is_refused() {
+    let response = block_on(dispatch_signer_message(SignerWorkerMessage {
+        msg_type: SIGN_NEP413_MESSAGE,
+        payload: json!({ "nearAccountId": "alice.testnet", "message": "hello" }),
+        request_id: None,
+    }))
+    .unwrap();
+    assert_eq!(
+        WorkerResponseType::from(response.response_type),
+        WorkerResponseType::SignNep413MessageFailure
+    );
+    assert_eq!(response.payload["errorCode"], json!("FeatureNotCompiled"));
+    assert_eq!(response.payload["retriable"], json!(false));
+    assert!(response.payload["error"]
+        .as_str()
+        .unwrap()
+        .contains("`nep413`"));
+}
+
+#[test]
+fn test_features_match_the_manifest() {
+    let mut manifest_features: Vec<&str> = manifest_table("features")
+        .into_iter()
+        .map(|(name, _)| name)
+        .filter(|name| *name != "default")
+        .collect();
+    manifest_features.sort_unstable();
+    let mut known: Vec<&str> = FEATURES.iter().map(|(name, _)| *name).collect();
+    known.sort_unstable();
+    assert_eq!(manifest_features, known);
+
+    // An unconditional dependency grows the minimal build: make it optional behind its feature
+    let unconditional: Vec<&str> = manifest_table("dependencies")
+        .into_iter()
+        .filter(|(_, spec)| !spec.contains("optional = true"))
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(unconditional, MINIMAL_BUILD_DEPENDENCIES);
+}
+
+/// Size check: run with `cargo test --release minimal_wasm -- --ignored --nocapture`
+/// (builds the wasm32 target into its own target directory)
+#[test]
+#[ignore = "builds the wasm32 release"]
+fn test_minimal_wasm_build_stays_within_size_budget() {
+    let manifest_dir = env!("CARGO_MANIFEST_DIR");
+    let target_dir = Path::new(manifest_dir).join("target/minimal-wasm");
+    let status = Command::new(env!("CARGO"))
+        .args([
+            "build",
+            "--release",
+            "--lib",
+            "--target",
+            "wasm32-unknown-unknown",
+            "--no-default-features",
+        ])
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .current_dir(manifest_dir)
+        .status()
+        .expect("cargo build");
+    assert!(status.success(), "minimal wasm build failed");
+
+    let wasm = target_dir.join("wasm32-unknown-unknown/release/wasm_signer_worker.wasm");
+    let size = std::fs::metadata(&wasm).expect("minimal wasm").len();
+    println!("minimal wasm: {} bytes", size);
+    assert!(
+        size <= MINIMAL_WASM_SIZE_BUDGET_BYTES,
+        "minimal wasm is {} bytes, over its {} byte budget",
+        size,
+        MINIMAL_WASM_SIZE_BUDGET_BYTES
+    );
+}
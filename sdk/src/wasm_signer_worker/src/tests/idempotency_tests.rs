@@ -0,0 +1,137 @@
+use crate::dispatch_signer_message;
+use crate::idempotency::{record_response, replayed_response};
+use crate::tests::{block_on, in_fresh_worker};
+use crate::types::worker_messages::{SignerWorkerMessage, WorkerRequestType, WorkerResponseType};
+use crate::worker_state::WorkerStateRecord;
+use serde_json::{json, Value};
+
+/// WorkerRequestType::SignTransactionWithKeyPair on the wire
+const SIGN_TRANSACTION_WITH_KEYPAIR: u32 = 6;
+
+fn sign_with_keypair_payload(idempotency_key: Option<&str>, nonce: u64) -> Value {
+    let signing_key = ed25519_dalek::SigningKey::from_bytes(&[3u8; 32]);
+    let mut payload = json!({
+        "nearPrivateKey": format!(
+            "ed25519:{}",
+            bs58::encode(signing_key.to_keypair_bytes()).into_string()
+        ),
+        "signerAccountId": "alice.testnet",
+        "receiverId": "bob.testnet",
+        "nonce": nonce.to_string(),
+        "blockHash": bs58::encode([9u8; 32]).into_string(),
+        "actions": json!([{ "action_type": "Transfer", "deposit": "1" }]).to_string(),
+    });
+    if let Some(key) = idempotency_key {
+        payload["idempotencyKey"] = json!(key);
+    }
+    payload
+}
+
+fn dispatch(payload: Value) -> (WorkerResponseType, Value) {
+    let response = block_on(dispatch_signer_message(SignerWorkerMessage {
+        msg_type: SIGN_TRANSACTION_WITH_KEYPAIR,
+        payload,
+        request_id: None,
+    }))
+    .unwrap();
+    (
+        WorkerResponseType::from(response.response_type),
+        response.payload,
+    )
+}
+
+#[test]
+fn test_repeated_key_replays_the_completed_response() {
+    let (response_type, first) = dispatch(sign_with_keypair_payload(Some("retry-1"), 1));
+    assert_eq!(
+        response_type,
+        WorkerResponseType::SignTransactionWithKeyPairSuccess
+    );
+    assert_eq!(first["success"], json!(true));
+    assert!(first.get("replayed").is_none());
+
+    let (response_type, replayed) = dispatch(sign_with_keypair_payload(Some("retry-1"), 1));
+    assert_eq!(
+        response_type,
+        WorkerResponseType::SignTransactionWithKeyPairSuccess
+    );
+    assert_eq!(replayed["replayed"], json!(true));
+    assert_eq!(replayed["signedTransactions"], first["signedTransactions"]);
+    assert_eq!(replayed["transactionHashes"], first["transactionHashes"]);
+
+    // Without a key every request executes
+    let (_, keyless) = dispatch(sign_with_keypair_payload(None, 1));
+    assert!(keyless.get("replayed").is_none());
+}
+
+#[test]
+fn test_retry_in_a_later_worker_replays_the_completed_response() {
+    // The retry lands in a new worker: the completed response travels in the state record
+    // the first response carried
+    let first = in_fresh_worker(None, || {
+        dispatch(sign_with_keypair_payload(Some("retry-5"), 1)).1
+    });
+    assert_eq!(first["success"], json!(true));
+    let record: WorkerStateRecord =
+        serde_json::from_value(first["workerStateRecord"].clone()).unwrap();
+    let (replayed, conflict) = in_fresh_worker(Some(record), || {
+        let (_, replayed) = dispatch(sign_with_keypair_payload(Some("retry-5"), 1));
+        let (_, conflict) = dispatch(sign_with_keypair_payload(Some("retry-5"), 2));
+        (replayed, conflict)
+    });
+    assert_eq!(replayed["replayed"], json!(true));
+    assert_eq!(replayed["signedTransactions"], first["signedTransactions"]);
+    assert_eq!(conflict["errorCode"], json!("IdempotencyKeyConflict"));
+}
+
+#[test]
+fn test_reused_key_with_a_different_payload_is_rejected() {
+    let (_, first) = dispatch(sign_with_keypair_payload(Some("retry-2"), 1));
+    assert_eq!(first["success"], json!(true));
+
+    let (response_type, rejected) = dispatch(sign_with_keypair_payload(Some("retry-2"), 2));
+    assert_eq!(
+        response_type,
+        WorkerResponseType::SignTransactionWithKeyPairFailure
+    );
+    assert_eq!(rejected["errorCode"], json!("IdempotencyKeyConflict"));
+    assert_eq!(rejected["errorCategory"], json!("Policy"));
+    assert_eq!(rejected["retriable"], json!(false));
+    assert!(rejected["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("IdempotencyKeyConflict: idempotency key retry-2"));
+
+    // The original payload still replays
+    let (_, replayed) = dispatch(sign_with_keypair_payload(Some("retry-2"), 1));
+    assert_eq!(replayed["replayed"], json!(true));
+}
+
+#[test]
+fn test_failed_responses_and_other_request_types_are_not_recorded() {
+    let sign = WorkerRequestType::SignTransactionsWithActions;
+    let payload = json!({ "idempotencyKey": "retry-3", "txSigningRequests": [] });
+    record_response(
+        sign,
+        &payload,
+        &json!({ "success": false, "error": "User cancelled" }),
+    );
+    assert_eq!(replayed_response(sign, &payload), Ok(None));
+
+    record_response(sign, &payload, &json!({ "success": true }));
+    assert_eq!(
+        replayed_response(sign, &payload),
+        Ok(Some(json!({ "success": true, "replayed": true })))
+    );
+
+    // Request types without idempotency support ignore the key
+    let nep413 = WorkerRequestType::SignNep413Message;
+    let payload = json!({ "idempotencyKey": "retry-4", "message": "hello" });
+    record_response(nep413, &payload, &json!({ "signature": "abc" }));
+    assert_eq!(replayed_response(nep413, &payload), Ok(None));
+
+    // The key is bound to the request type as well
+    let register = WorkerRequestType::DeriveNearKeypairAndEncrypt;
+    let payload = json!({ "idempotencyKey": "retry-3", "txSigningRequests": [] });
+    assert!(replayed_response(register, &payload).is_err());
+}
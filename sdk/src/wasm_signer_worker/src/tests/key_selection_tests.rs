@@ -0,0 +1,447 @@
+use crate::actions::ActionParams;
+use crate::allowance::AllowanceEstimate;
+use crate::bench::*;
+use crate::config::YOCTO_PER_NEAR;
+use crate::confirmation_blocks::*;
+use crate::crypto::derive_and_encrypt_keypair_from_dual_prf;
+use crate::error::{KeyLacksPermissionError, RpcErrorKind, SignerErrorCode};
+use crate::handlers::confirm_tx_details::{
+    compute_confirmation_intent_digest, confirmation_tx_signing_requests_json,
+};
+use crate::handlers::handle_sign_transactions_with_actions::{
+    fetch_selected_key, sign_near_transactions_with_actions_impl, Decryption,
+};
+use crate::handlers::{handle_sign_transactions_with_actions, SignTransactionsWithActionsRequest};
+use crate::key_selection::*;
+use crate::rpc_client::MockRpcClient;
+use crate::strict_parsing::check_unknown_fields;
+use crate::tests::block_on;
+use crate::types::handlers::{DecryptionPayload, KeyBlobCandidate, TransactionContext};
+use crate::types::worker_messages::WorkerRequestType;
+use serde_json::{json, Value};
+
+const ACCOUNT: &str = "alice.testnet";
+const STAKING_KEY: &str = "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp";
+const POOL: &str = "pool.testnet";
+
+fn call(method_name: &str, deposit: &str) -> ActionParams {
+    ActionParams::FunctionCall {
+        method_name: method_name.to_string(),
+        args: "{}".to_string(),
+        gas: "30000000000000".to_string(),
+        deposit: deposit.to_string(),
+    }
+}
+
+fn function_call_key(method_names: &[&str]) -> OnChainAccessKey {
+    OnChainAccessKey {
+        nonce: 41,
+        permission: AccessKeyPermission::FunctionCall {
+            receiver_id: POOL.to_string(),
+            method_names: method_names.iter().map(|m| m.to_string()).collect(),
+            allowance: Some("250000000000000000000000".to_string()),
+        },
+    }
+}
+
+fn reason(result: Result<OnChainAccessKey, KeyLacksPermissionError>) -> String {
+    result.unwrap_err().reason
+}
+
+#[test]
+fn test_access_key_results_parse_both_permission_kinds() {
+    let full = json!({ "nonce": 7, "permission": "FullAccess", "block_height": 1 });
+    assert_eq!(
+        parse_access_key(&full),
+        Ok(OnChainAccessKey {
+            nonce: 7,
+            permission: AccessKeyPermission::FullAccess
+        })
+    );
+
+    let function_call = json!({
+        "nonce": 41,
+        "permission": { "FunctionCall": {
+            "allowance": "250000000000000000000000",
+            "receiver_id": POOL,
+            "method_names": ["deposit_and_stake"]
+        }}
+    });
+    assert_eq!(
+        parse_access_key(&function_call),
+        Ok(function_call_key(&["deposit_and_stake"]))
+    );
+
+    // An unlimited allowance is null
+    let unlimited = json!({
+        "nonce": 1,
+        "permission": { "FunctionCall": { "allowance": null, "receiver_id": POOL, "method_names": [] }}
+    });
+    assert!(matches!(
+        parse_access_key(&unlimited).unwrap().permission,
+        AccessKeyPermission::FunctionCall {
+            allowance: None,
+            ..
+        }
+    ));
+
+    assert!(parse_access_key(&json!({ "permission": "FullAccess" }))
+        .unwrap_err()
+        .contains("without nonce"));
+    assert!(parse_access_key(&json!({ "nonce": 1, "permission": "Other" })).is_err());
+}
+
+#[test]
+fn test_function_call_keys_are_checked_against_the_batch() {
+    let stake = vec![(POOL.to_string(), vec![call("deposit_and_stake", "0")])];
+    let key = function_call_key(&["deposit_and_stake", "unstake"]);
+
+    assert_eq!(
+        check_key_permission(ACCOUNT, STAKING_KEY, Some(key.clone()), &stake),
+        Ok(key.clone())
+    );
+    // An empty method list allows any method of the receiver
+    assert!(
+        check_key_permission(ACCOUNT, STAKING_KEY, Some(function_call_key(&[])), &stake).is_ok()
+    );
+    // Full access keys sign anything
+    let full = OnChainAccessKey {
+        nonce: 1,
+        permission: AccessKeyPermission::FullAccess,
+    };
+    let transfer = vec![(
+        "bob.testnet".to_string(),
+        vec![ActionParams::Transfer {
+            deposit: "1".to_string(),
+        }],
+    )];
+    assert!(check_key_permission(ACCOUNT, STAKING_KEY, Some(full), &transfer).is_ok());
+
+    assert!(reason(check_key_permission(
+        ACCOUNT,
+        STAKING_KEY,
+        Some(key.clone()),
+        &transfer
+    ))
+    .contains("transaction 1 is to bob.testnet"));
+    let transfer_to_pool = vec![(
+        POOL.to_string(),
+        vec![ActionParams::Transfer {
+            deposit: "1".to_string(),
+        }],
+    )];
+    assert!(reason(check_key_permission(
+        ACCOUNT,
+        STAKING_KEY,
+        Some(key.clone()),
+        &transfer_to_pool
+    ))
+    .contains("an action other than a function call"));
+    let paid = vec![
+        stake[0].clone(),
+        (POOL.to_string(), vec![call("deposit_and_stake", "1")]),
+    ];
+    assert!(reason(check_key_permission(
+        ACCOUNT,
+        STAKING_KEY,
+        Some(key.clone()),
+        &paid
+    ))
+    .contains("transaction 2 attaches a deposit to deposit_and_stake"));
+    let withdraw = vec![(POOL.to_string(), vec![call("withdraw_all", "0")])];
+    assert_eq!(
+        reason(check_key_permission(
+            ACCOUNT,
+            STAKING_KEY,
+            Some(key),
+            &withdraw
+        )),
+        "transaction 1 calls withdraw_all, the function-call key may only call \
+         deposit_and_stake, unstake"
+    );
+}
+
+#[test]
+fn test_key_missing_from_the_account_lacks_permission() {
+    let err = check_key_permission(ACCOUNT, STAKING_KEY, None, &[]).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::KeyLacksPermission);
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "KeyLacksPermission: {} cannot sign this batch: it is not an access key of {}",
+            STAKING_KEY, ACCOUNT
+        )
+    );
+    let definition = err.code().definition();
+    assert_eq!(definition.code, "KeyLacksPermission");
+    assert_eq!(definition.category.as_str(), "Policy");
+    assert!(!definition.retriable);
+
+    // Newer nodes answer UNKNOWN_ACCESS_KEY, older ones a result with an error string
+    let stake = vec![(POOL.to_string(), vec![call("deposit_and_stake", "0")])];
+    let unknown = MockRpcClient::new().answer(
+        "query/view_access_key",
+        Err(RpcErrorKind::Rpc {
+            name: "UNKNOWN_ACCESS_KEY".to_string(),
+            message: "access key does not exist".to_string(),
+            error: json!({ "cause": { "name": "UNKNOWN_ACCESS_KEY" } }),
+        }),
+    );
+    let legacy = MockRpcClient::new().answer(
+        "query/view_access_key",
+        Ok(json!({ "error": "access key ed25519:... does not exist while viewing" })),
+    );
+    for rpc in [unknown, legacy] {
+        let (code, error_msg) =
+            block_on(fetch_selected_key(&rpc, ACCOUNT, STAKING_KEY, &stake)).unwrap_err();
+        assert_eq!(code, SignerErrorCode::KeyLacksPermission);
+        assert!(
+            error_msg.contains("not an access key of alice.testnet"),
+            "{}",
+            error_msg
+        );
+        assert_eq!(
+            rpc.calls_to("query/view_access_key")[0]["public_key"],
+            json!(STAKING_KEY)
+        );
+    }
+
+    let unreachable = MockRpcClient::new();
+    let (code, _) = block_on(fetch_selected_key(
+        &unreachable,
+        ACCOUNT,
+        STAKING_KEY,
+        &stake,
+    ))
+    .unwrap_err();
+    assert_eq!(code, SignerErrorCode::RpcUnreachable);
+}
+
+#[test]
+fn test_candidate_blobs_are_selected_by_key() {
+    let decryption: DecryptionPayload = serde_json::from_value(json!({
+        "encryptedPrivateKeyData": "AAAA",
+        "encryptedPrivateKeyIv": "BBBB",
+        "candidates": [
+            { "publicKey": "not a key", "encryptedPrivateKeyData": "CCCC", "encryptedPrivateKeyIv": "DDDD" },
+            { "publicKey": STAKING_KEY, "encryptedPrivateKeyData": "EEEE", "encryptedPrivateKeyIv": "FFFF" }
+        ]
+    }))
+    .unwrap();
+    assert!(decryption.validate().is_ok());
+
+    let staking = parse_public_key(STAKING_KEY).unwrap();
+    assert_eq!(
+        select_candidate(&decryption, &staking).map(|c| c.encrypted_private_key_data.as_str()),
+        Some("EEEE")
+    );
+    assert_eq!(select_candidate(&decryption, &[0u8; 32]), None);
+    assert_eq!(
+        select_candidate(
+            &DecryptionPayload::new("AAAA".into(), "BBBB".into()),
+            &staking
+        ),
+        None
+    );
+
+    assert!(parse_public_key("6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp").is_err());
+    assert!(parse_public_key("ed25519:111")
+        .unwrap_err()
+        .contains("32 bytes"));
+
+    let mut invalid = decryption.clone();
+    invalid.candidates[1].encrypted_private_key_iv = String::new();
+    assert!(invalid
+        .validate()
+        .unwrap_err()
+        .to_string()
+        .contains("candidates.encryptedPrivateKeyIv"));
+}
+
+#[test]
+fn test_signing_context_takes_the_selected_keys_nonce_and_allowance() {
+    let base = TransactionContext {
+        access_key_allowance: Some("5".to_string()),
+        gas_price: Some("100000000".to_string()),
+        ..TransactionContext::new(
+            "ed25519:passkey".to_string(),
+            "9".to_string(),
+            "1000".to_string(),
+            "11111111111111111111111111111111".to_string(),
+        )
+    };
+    let context = signing_context(Some(&base), STAKING_KEY, &function_call_key(&[]));
+    assert_eq!(context.near_public_key_str, STAKING_KEY);
+    assert_eq!(context.next_nonce, "42");
+    assert_eq!(
+        context.access_key_allowance.as_deref(),
+        Some("250000000000000000000000")
+    );
+    assert_eq!(context.tx_block_height, "1000");
+    assert_eq!(context.tx_block_hash, base.tx_block_hash);
+    assert_eq!(context.gas_price, base.gas_price);
+
+    let full = OnChainAccessKey {
+        nonce: 3,
+        permission: AccessKeyPermission::FullAccess,
+    };
+    let context = signing_context(None, STAKING_KEY, &full);
+    assert_eq!(context.next_nonce, "4");
+    assert_eq!(context.access_key_allowance, None);
+    assert_eq!(context.gas_price, None);
+}
+
+#[test]
+fn test_signing_key_row_leads_the_first_transaction() {
+    let staking = parse_public_key(STAKING_KEY).unwrap();
+    let fingerprint = key_fingerprint(&staking);
+    assert_eq!(fingerprint.len(), 19);
+    assert_eq!(fingerprint.split(':').count(), 4);
+    assert!(fingerprint
+        .chars()
+        .all(|c| c == ':' || c.is_ascii_hexdigit() && !c.is_ascii_uppercase()));
+    assert_ne!(fingerprint, key_fingerprint(&[0u8; 32]));
+
+    let batch = vec![
+        (POOL.to_string(), vec![call("deposit_and_stake", "0")]),
+        (POOL.to_string(), vec![call("unstake", "0")]),
+    ];
+    let flags = vec![Some(false), Some(false)];
+    let policy = SummaryPolicy::default()
+        .with_signing_key(Some(staking))
+        .with_allowance(Some(AllowanceEstimate {
+            remaining: YOCTO_PER_NEAR / 100,
+            required: YOCTO_PER_NEAR / 1000,
+            warn_below: YOCTO_PER_NEAR,
+        }));
+    let payload = confirmation_tx_signing_requests_json(&batch, &flags, None, Some(&policy));
+    let blocks = |tx: &Value| -> Vec<Value> { tx["summaryBlocks"].as_array().unwrap().clone() };
+    let first = blocks(&payload[0]);
+    assert_eq!(
+        first[0],
+        json!({
+            "kind": "keyRow",
+            "label": "Signing key",
+            "publicKey": STAKING_KEY,
+            "fingerprint": fingerprint,
+            "ariaLabel": "Signing ed25519 key ending in K E t p, fingerprint a 5 1 7, 3 b d 9, \
+                          b b 1 3, f 9 8 b"
+        })
+    );
+    assert_eq!(first[1]["code"], json!("LowAllowance"));
+    assert!(blocks(&payload[1])
+        .iter()
+        .all(|block| block["kind"] != json!("keyRow")));
+
+    // The key shown is covered by the intent digest
+    let digest = |policy: &SummaryPolicy| {
+        compute_confirmation_intent_digest(&batch, &flags, None, Some(policy)).unwrap()
+    };
+    let default = SummaryPolicy::default();
+    assert_ne!(
+        digest(&default.clone().with_signing_key(Some(staking))),
+        digest(&default)
+    );
+    assert_ne!(
+        digest(&default.clone().with_signing_key(Some(staking))),
+        digest(&default.clone().with_signing_key(Some([0u8; 32])))
+    );
+}
+
+fn sign_request(signing_public_key: &str, candidates: Value) -> Value {
+    json!({
+        "rpcCall": { "contractId": "w3a-v1.testnet", "nearRpcUrl": "https://rpc.testnet.near.org", "nearAccountId": ACCOUNT },
+        "decryption": {
+            "encryptedPrivateKeyData": "AAAA",
+            "encryptedPrivateKeyIv": "BBBB",
+            "candidates": candidates
+        },
+        "txSigningRequests": [{
+            "nearAccountId": ACCOUNT,
+            "receiverId": POOL,
+            "actions": json!([{
+                "action_type": "FunctionCall",
+                "method_name": "deposit_and_stake",
+                "args": "{}",
+                "gas": "30000000000000",
+                "deposit": "0"
+            }]).to_string()
+        }],
+        "confirmationConfig": null,
+        "signingPublicKey": signing_public_key
+    })
+}
+
+#[test]
+fn test_selected_key_without_a_candidate_fails_before_confirmation() {
+    let payload = sign_request(STAKING_KEY, json!([]));
+    assert_eq!(
+        check_unknown_fields(WorkerRequestType::SignTransactionsWithActions, &payload),
+        Ok(())
+    );
+    let request: SignTransactionsWithActionsRequest = serde_json::from_value(payload).unwrap();
+    let result = block_on(handle_sign_transactions_with_actions(request)).unwrap();
+    assert!(!result.success);
+    assert_eq!(result.error_code.as_deref(), Some("InvalidConfig"));
+    assert!(result
+        .error
+        .unwrap()
+        .contains("No decryption candidate holds signingPublicKey"));
+
+    let candidates = json!([{
+        "publicKey": STAKING_KEY, "encryptedPrivateKeyData": "CCCC", "encryptedPrivateKeyIv": "DDDD"
+    }]);
+    assert_eq!(
+        check_unknown_fields(
+            WorkerRequestType::SignTransactionsWithActions,
+            &sign_request(STAKING_KEY, candidates)
+        ),
+        Ok(())
+    );
+    let request: SignTransactionsWithActionsRequest =
+        serde_json::from_value(sign_request("ed25519:not-base58!", json!([]))).unwrap();
+    let result = block_on(handle_sign_transactions_with_actions(request)).unwrap();
+    assert!(result.error.unwrap().contains("Invalid signingPublicKey"));
+}
+
+#[test]
+fn test_blob_decrypting_to_another_key_than_its_tag_is_refused() {
+    let batch = confirmed_batch_of_5();
+    let (public_key, _) =
+        derive_and_encrypt_keypair_from_dual_prf(&bench_prf_outputs(), BENCH_ACCOUNT_ID).unwrap();
+    let sign = |expected_public_key: &str| {
+        let decryption = Decryption {
+            expected_public_key: Some(expected_public_key.to_string()),
+            ..batch.decryption.clone()
+        };
+        block_on(sign_near_transactions_with_actions_impl(
+            batch.tx_requests.clone(),
+            &decryption,
+            &batch.confirmation,
+            None::<&MockRpcClient>,
+            None,
+            None,
+            Vec::new(),
+        ))
+        .unwrap()
+    };
+
+    let result = sign(&public_key);
+    assert!(result.success, "{:?}", result.error);
+
+    let candidate = KeyBlobCandidate {
+        public_key: STAKING_KEY.to_string(),
+        encrypted_private_key_data: batch.decryption.encrypted_private_key_data.clone(),
+        encrypted_private_key_iv: batch.decryption.encrypted_private_key_iv.clone(),
+    };
+    let result = sign(&candidate.public_key);
+    assert!(!result.success);
+    assert!(result.signed_transactions.is_none());
+    let error = result.error.unwrap();
+    assert!(
+        error.contains(&format!("The blob tagged {}", STAKING_KEY)),
+        "{}",
+        error
+    );
+    assert!(error.contains(&public_key), "{}", error);
+}
@@ -0,0 +1,230 @@
+use crate::bench::*;
+use crate::config::MAX_KEY_USAGE_ENTRIES;
+use crate::dispatch_signer_message;
+use crate::encoders::base64_url_decode;
+use crate::handlers::handle_sign_transactions_with_actions::sign_near_transactions_with_actions_impl;
+use crate::key_usage::*;
+use crate::rpc_client::MockRpcClient;
+use crate::tests::block_on;
+use crate::types::worker_messages::{SignerWorkerMessage, WorkerResponseType};
+use serde_json::json;
+
+const PRF_A: &str = "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE";
+const PRF_B: &str = "AgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgI";
+
+fn sign_once(key_id: &str, prf_output: &str, receiver_id: &str, now_ms: f64) {
+    record_signing(key_id, prf_output, 1, receiver_id, "alice.testnet", now_ms).unwrap();
+}
+
+#[test]
+fn test_receiver_category_and_hash() {
+    let implicit = "a".repeat(64);
+    assert_eq!(
+        ReceiverCategory::of("alice.testnet", "alice.testnet"),
+        ReceiverCategory::Own
+    );
+    assert_eq!(
+        ReceiverCategory::of(&implicit, "alice.testnet"),
+        ReceiverCategory::Implicit
+    );
+    assert_eq!(
+        ReceiverCategory::of(&format!("0x{}", "b".repeat(40)), "alice.testnet"),
+        ReceiverCategory::Implicit
+    );
+    assert_eq!(
+        ReceiverCategory::of(&"A".repeat(64), "alice.testnet"),
+        ReceiverCategory::Named
+    );
+    assert_eq!(
+        ReceiverCategory::of("bob.testnet", "alice.testnet"),
+        ReceiverCategory::Named
+    );
+
+    let hash = receiver_hash("bob.testnet");
+    assert_eq!(base64_url_decode(&hash).unwrap().len(), 8);
+    assert_eq!(hash, receiver_hash("bob.testnet"));
+    assert_ne!(hash, receiver_hash("carol.testnet"));
+}
+
+#[test]
+fn test_record_round_trips_and_keeps_no_receiver_ids() {
+    reset();
+    sign_once("key-a", PRF_A, "bob.testnet", 1_000.0);
+    sign_once("key-a", PRF_A, "carol.testnet", 2_000.0);
+    sign_once("key-b", PRF_B, "alice.testnet", 1_500.0);
+
+    let record = take_changed_record().unwrap();
+    assert_eq!(take_changed_record(), None);
+    let record_json = serde_json::to_string(&record).unwrap();
+    assert!(!record_json.contains("bob.testnet"));
+    assert!(!record_json.contains("carol.testnet"));
+
+    // The next worker loads the stored record and verifies each entry when its key signs
+    reset();
+    assert_eq!(load_record_json(&record_json), Ok(2));
+    let loaded = stats();
+    assert_eq!(loaded.keys.len(), 2);
+    assert!(loaded.keys.iter().all(|key| !key.verified));
+
+    sign_once("key-a", PRF_A, "bob.testnet", 3_000.0);
+    let stats = stats();
+    assert_eq!(stats.keys[0].key_id, "key-a");
+    assert_eq!(stats.keys[0].sign_count, 3);
+    assert_eq!(stats.keys[0].last_used_ms, 3_000.0);
+    assert_eq!(stats.keys[0].last_receiver_category, "named");
+    assert_eq!(
+        stats.keys[0].last_receiver_hash,
+        receiver_hash("bob.testnet")
+    );
+    assert!(stats.keys[0].verified);
+    assert_eq!(stats.keys[1].key_id, "key-b");
+    assert_eq!(stats.keys[1].last_receiver_category, "own");
+    assert!(!stats.keys[1].verified);
+    assert!(stats.tampered_key_ids.is_empty());
+    reset();
+}
+
+#[test]
+fn test_tampered_entry_is_dropped_and_reported() {
+    reset();
+    sign_once("key-a", PRF_A, "bob.testnet", 1_000.0);
+    let mut record = take_changed_record().unwrap();
+    record.entries[0].sign_count = 1_000;
+
+    reset();
+    load_record(record.clone()).unwrap();
+    sign_once("key-a", PRF_A, "bob.testnet", 2_000.0);
+    let stats = stats();
+    assert_eq!(stats.keys[0].sign_count, 1);
+    assert_eq!(stats.tampered_key_ids, vec!["key-a".to_string()]);
+
+    // An untouched entry checked with another passkey's PRF output fails the same way
+    record.entries[0].sign_count = 1;
+    reset();
+    load_record(record).unwrap();
+    sign_once("key-a", PRF_B, "bob.testnet", 2_000.0);
+    assert_eq!(
+        crate::key_usage::stats().tampered_key_ids,
+        vec!["key-a".to_string()]
+    );
+    reset();
+}
+
+#[test]
+fn test_load_record_validation() {
+    reset();
+    let entry = |key_id: &str| {
+        json!({
+            "keyId": key_id,
+            "signCount": 1,
+            "lastUsedMs": 1.0,
+            "lastReceiverCategory": "named",
+            "lastReceiverHash": "AAAAAAAAAAA",
+            "mac": "AAAA",
+        })
+    };
+    let record = |version: u32, entries: Vec<serde_json::Value>| {
+        json!({ "version": version, "entries": entries }).to_string()
+    };
+
+    assert_eq!(
+        load_record_json(&record(1, vec![entry("a"), entry("b")])),
+        Ok(2)
+    );
+    assert!(load_record_json(&record(2, vec![]))
+        .unwrap_err()
+        .starts_with("Unsupported key usage record version 2"));
+    assert_eq!(
+        load_record_json(&record(1, vec![entry("a"), entry("a")])),
+        Err("Key usage record lists key a twice".to_string())
+    );
+    let too_many = (0..=MAX_KEY_USAGE_ENTRIES)
+        .map(|i| entry(&i.to_string()))
+        .collect();
+    assert!(load_record_json(&record(1, too_many)).is_err());
+    // Amounts or full receiver IDs have no field to go in
+    let mut with_receiver = entry("a");
+    with_receiver["receiverId"] = json!("bob.testnet");
+    assert!(load_record_json(&record(1, vec![with_receiver]))
+        .unwrap_err()
+        .starts_with("Invalid key usage record"));
+    // A rejected record leaves the loaded one in place
+    assert_eq!(crate::key_usage::stats().keys.len(), 2);
+    reset();
+}
+
+#[test]
+fn test_full_record_evicts_the_least_recently_used_key() {
+    reset();
+    for i in 0..MAX_KEY_USAGE_ENTRIES {
+        sign_once(
+            &format!("key-{}", i),
+            PRF_A,
+            "bob.testnet",
+            1_000.0 + i as f64,
+        );
+    }
+    sign_once("key-new", PRF_A, "bob.testnet", 5_000.0);
+    let stats = stats();
+    assert_eq!(stats.keys.len(), MAX_KEY_USAGE_ENTRIES);
+    assert_eq!(stats.keys[0].key_id, "key-new");
+    assert!(stats.keys.iter().all(|key| key.key_id != "key-0"));
+    reset();
+}
+
+#[test]
+fn test_signing_a_batch_counts_its_transactions() {
+    reset();
+    let batch = confirmed_batch_of_5();
+    let result = block_on(sign_near_transactions_with_actions_impl(
+        batch.tx_requests.clone(),
+        &batch.decryption,
+        &batch.confirmation,
+        None::<&MockRpcClient>,
+        None,
+        None,
+        Vec::new(),
+    ))
+    .unwrap();
+    assert!(result.success, "{:?}", result.error);
+
+    let stats = stats();
+    assert_eq!(stats.keys.len(), 1);
+    let key = &stats.keys[0];
+    let blob = base64_url_decode(&batch.decryption.encrypted_private_key_data).unwrap();
+    assert_eq!(Some(key.key_id.clone()), key_usage_id(&blob));
+    assert_eq!(key.sign_count, 5);
+    assert_eq!(key.last_receiver_hash, receiver_hash("receiver-4.testnet"));
+    assert!(key.verified);
+    reset();
+}
+
+#[test]
+fn test_dispatch_reports_stats_and_hands_back_the_record() {
+    reset();
+    sign_once("key-a", PRF_A, "bob.testnet", 1_000.0);
+
+    // WorkerRequestType::GetKeyUsageStats
+    let message = || SignerWorkerMessage {
+        msg_type: 31,
+        payload: json!({}),
+        request_id: None,
+    };
+    let response = block_on(dispatch_signer_message(message())).unwrap();
+    assert_eq!(
+        WorkerResponseType::from(response.response_type),
+        WorkerResponseType::GetKeyUsageStatsSuccess
+    );
+    assert_eq!(response.payload["keys"][0]["keyId"], json!("key-a"));
+    assert_eq!(response.payload["keys"][0]["signCount"], json!(1));
+    assert_eq!(response.payload["tamperedKeyIds"], json!([]));
+    assert_eq!(
+        response.payload["keyUsageRecord"]["entries"][0]["keyId"],
+        json!("key-a")
+    );
+
+    // Nothing signed since, so the record is not handed back again
+    let response = block_on(dispatch_signer_message(message())).unwrap();
+    assert!(response.payload.get("keyUsageRecord").is_none());
+    reset();
+}
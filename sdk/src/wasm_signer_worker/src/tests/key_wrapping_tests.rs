@@ -0,0 +1,322 @@
+use crate::crypto::{
+    decrypt_private_key_with_prf, derive_and_encrypt_keypair_from_dual_prf, generate_near_keypair,
+};
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::{KeyWrappingError, SignerErrorCode};
+use crate::handlers::handle_derive_near_keypair_and_encrypt::{
+    handle_derive_near_keypair_and_encrypt, DeriveNearKeypairAndEncryptRequest,
+};
+use crate::handlers::handle_validate_encrypted_blobs::{
+    validate_encrypted_key_blob, EncryptedKeyBlob,
+};
+use crate::key_wrapping::*;
+use crate::tests::block_on;
+use crate::types::handlers::WorkerPolicy;
+use crate::types::DualPrfOutputs;
+use serde_json::json;
+
+const ACCOUNT_ID: &str = "alice.testnet";
+const PASSPHRASE: &str = "correct horse battery staple";
+
+fn policy(schemes: &[&str]) -> WorkerPolicy {
+    serde_json::from_value(json!({ "prfFallbackSchemes": schemes })).unwrap()
+}
+
+fn public_key_of(signing_key: &ed25519_dalek::SigningKey) -> String {
+    format!(
+        "ed25519:{}",
+        bs58::encode(signing_key.verifying_key().to_bytes()).into_string()
+    )
+}
+
+fn stored_blob(data: &str, iv: &str, version: Option<u32>) -> EncryptedKeyBlob {
+    EncryptedKeyBlob {
+        blob_id: None,
+        encrypted_private_key_data: data.to_string(),
+        encrypted_private_key_iv: iv.to_string(),
+        version,
+        near_account_id: Some(ACCOUNT_ID.to_string()),
+        outer_wrap: None,
+    }
+}
+
+/// A registration credential whose authenticator returned no PRF results
+fn registration_request(extra: serde_json::Value) -> DeriveNearKeypairAndEncryptRequest {
+    let mut request = json!({
+        "nearAccountId": ACCOUNT_ID,
+        "credential": {
+            "id": "cred",
+            "rawId": "cred",
+            "type": "public-key",
+            "authenticatorAttachment": null,
+            "response": {
+                "clientDataJSON": base64_url_encode(b"{}"),
+                "attestationObject": base64_url_encode(b"not cbor"),
+                "transports": []
+            },
+            "clientExtensionResults": {}
+        },
+        "prfSupported": false
+    });
+    request
+        .as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    serde_json::from_value(request).unwrap()
+}
+
+#[test]
+fn test_fallback_scheme_selection_follows_worker_policy() {
+    let none_allowed = select_fallback_scheme(None, None).unwrap_err();
+    assert!(matches!(none_allowed, KeyWrappingError::PrfUnsupported(_)));
+    assert!(none_allowed
+        .to_string()
+        .starts_with("PrfUnsupportedByAuthenticator:"));
+    assert!(matches!(
+        select_fallback_scheme(None, Some(&policy(&[]))),
+        Err(KeyWrappingError::PrfUnsupported(_))
+    ));
+
+    let both = policy(&["passphrase", "largeBlob"]);
+    assert_eq!(
+        select_fallback_scheme(None, Some(&both)),
+        Ok(KeyWrappingScheme::Passphrase)
+    );
+    assert_eq!(
+        select_fallback_scheme(Some("largeBlob"), Some(&both)),
+        Ok(KeyWrappingScheme::LargeBlob)
+    );
+    assert!(matches!(
+        select_fallback_scheme(Some("passphrase"), Some(&policy(&["largeBlob"]))),
+        Err(KeyWrappingError::PrfUnsupported(_))
+    ));
+    assert!(matches!(
+        select_fallback_scheme(Some("pin"), Some(&both)),
+        Err(KeyWrappingError::InvalidInput(_))
+    ));
+    // "prf" is not a fallback
+    assert!(matches!(
+        select_fallback_scheme(None, Some(&policy(&["prf"]))),
+        Err(KeyWrappingError::InvalidInput(_))
+    ));
+
+    for scheme in [
+        KeyWrappingScheme::Prf,
+        KeyWrappingScheme::LargeBlob,
+        KeyWrappingScheme::Passphrase,
+    ] {
+        assert_eq!(
+            KeyWrappingScheme::from_blob_version(scheme.blob_version()),
+            Some(scheme)
+        );
+        assert_eq!(KeyWrappingScheme::parse(scheme.as_str()), Some(scheme));
+    }
+}
+
+#[test]
+fn test_large_blob_wrapped_key_decrypts_with_its_secret() {
+    let (near_private_key, public_key) = generate_near_keypair().unwrap();
+    let wrapped = wrap_near_private_key(
+        &near_private_key,
+        ACCOUNT_ID,
+        KeyWrappingScheme::LargeBlob,
+        None,
+    )
+    .unwrap();
+    let secret = wrapped.large_blob_secret.clone().unwrap();
+    assert_eq!(base64_url_decode(&secret).unwrap().len(), 32);
+
+    let data = base64_url_decode(&wrapped.encrypted.encrypted_near_key_data_b64u).unwrap();
+    assert_eq!(&data[..5], b"W3KW\x02");
+    let (scheme, salt, _) = split_key_wrapping_header(&data);
+    assert_eq!(scheme, KeyWrappingScheme::LargeBlob);
+    assert!(salt.is_empty());
+
+    let signing_key = decrypt_private_key_with_prf(
+        ACCOUNT_ID,
+        &secret,
+        &wrapped.encrypted.encrypted_near_key_data_b64u,
+        &wrapped.encrypted.chacha20_nonce_b64u,
+    )
+    .unwrap();
+    assert_eq!(public_key_of(&signing_key), public_key);
+
+    let wrong_secret = decrypt_private_key_with_prf(
+        ACCOUNT_ID,
+        &base64_url_encode(&[9u8; 32]),
+        &wrapped.encrypted.encrypted_near_key_data_b64u,
+        &wrapped.encrypted.chacha20_nonce_b64u,
+    )
+    .unwrap_err();
+    assert_eq!(
+        wrong_secret.code(),
+        Some(SignerErrorCode::WrongCredentialForBlob)
+    );
+
+    // The record's version must repeat the scheme
+    let blob = |version| {
+        stored_blob(
+            &wrapped.encrypted.encrypted_near_key_data_b64u,
+            &wrapped.encrypted.chacha20_nonce_b64u,
+            version,
+        )
+    };
+    assert!(validate_encrypted_key_blob(&blob(Some(2)), ACCOUNT_ID).is_empty());
+    let codes: Vec<String> = validate_encrypted_key_blob(&blob(None), ACCOUNT_ID)
+        .into_iter()
+        .map(|p| p.code)
+        .collect();
+    assert_eq!(codes, vec!["KeyWrappingMismatch"]);
+}
+
+#[test]
+fn test_passphrase_wrapped_key_uses_argon2id_with_a_per_blob_salt() {
+    let (near_private_key, public_key) = generate_near_keypair().unwrap();
+    assert!(matches!(
+        wrap_near_private_key(
+            &near_private_key,
+            ACCOUNT_ID,
+            KeyWrappingScheme::Passphrase,
+            Some("short")
+        ),
+        Err(KeyWrappingError::InvalidInput(_))
+    ));
+
+    let wrapped = wrap_near_private_key(
+        &near_private_key,
+        ACCOUNT_ID,
+        KeyWrappingScheme::Passphrase,
+        Some(PASSPHRASE),
+    )
+    .unwrap();
+    assert!(wrapped.large_blob_secret.is_none());
+    let data = base64_url_decode(&wrapped.encrypted.encrypted_near_key_data_b64u).unwrap();
+    let (scheme, salt, _) = split_key_wrapping_header(&data);
+    assert_eq!(scheme, KeyWrappingScheme::Passphrase);
+    assert_eq!(salt.len(), 16);
+
+    let signing_key = decrypt_private_key_with_prf(
+        ACCOUNT_ID,
+        PASSPHRASE,
+        &wrapped.encrypted.encrypted_near_key_data_b64u,
+        &wrapped.encrypted.chacha20_nonce_b64u,
+    )
+    .unwrap();
+    assert_eq!(public_key_of(&signing_key), public_key);
+
+    let wrong_passphrase = decrypt_private_key_with_prf(
+        ACCOUNT_ID,
+        "incorrect horse battery staple",
+        &wrapped.encrypted.encrypted_near_key_data_b64u,
+        &wrapped.encrypted.chacha20_nonce_b64u,
+    )
+    .unwrap_err();
+    assert_eq!(
+        wrong_passphrase.code(),
+        Some(SignerErrorCode::WrongCredentialForBlob)
+    );
+    assert!(validate_encrypted_key_blob(
+        &stored_blob(
+            &wrapped.encrypted.encrypted_near_key_data_b64u,
+            &wrapped.encrypted.chacha20_nonce_b64u,
+            Some(3)
+        ),
+        ACCOUNT_ID
+    )
+    .is_empty());
+}
+
+#[test]
+fn test_registration_without_prf_fails_clearly_or_falls_back() {
+    // No clientExtensionResults.prf at all: a clear error instead of a missing-field one
+    let refused = block_on(handle_derive_near_keypair_and_encrypt(
+        registration_request(json!({})),
+    ))
+    .unwrap_err();
+    assert!(
+        refused.starts_with("PrfUnsupportedByAuthenticator:"),
+        "{}",
+        refused
+    );
+
+    let result = block_on(handle_derive_near_keypair_and_encrypt(
+        registration_request(json!({
+            "prfFallback": { "scheme": "largeBlob" },
+            "workerPolicy": { "prfFallbackSchemes": ["largeBlob"] }
+        })),
+    ))
+    .unwrap();
+    assert_eq!(result.version, 2);
+    assert_eq!(result.key_wrapping, "largeBlob");
+    let signing_key = decrypt_private_key_with_prf(
+        ACCOUNT_ID,
+        &result.large_blob_secret.unwrap(),
+        &result.encrypted_data,
+        &result.iv,
+    )
+    .unwrap();
+    assert_eq!(public_key_of(&signing_key), result.public_key);
+
+    // prfSupported: false wins over (empty) PRF outputs
+    let refused = block_on(handle_derive_near_keypair_and_encrypt(
+        registration_request(
+            json!({ "dualPrfOutputs": { "chacha20PrfOutput": "", "ed25519PrfOutput": "" } }),
+        ),
+    ))
+    .unwrap_err();
+    assert!(refused.starts_with("PrfUnsupportedByAuthenticator:"));
+}
+
+#[test]
+fn test_devices_of_one_account_can_mix_schemes() {
+    // Device 1 registered with PRF, device 2 (a security key without PRF) with largeBlob
+    let dual_prf = DualPrfOutputs {
+        chacha20_prf_output_base64: base64_url_encode(&[1u8; 32]),
+        ed25519_prf_output_base64: base64_url_encode(&[2u8; 32]),
+    };
+    let (prf_public_key, prf_blob) =
+        derive_and_encrypt_keypair_from_dual_prf(&dual_prf, ACCOUNT_ID).unwrap();
+    let (near_private_key, large_blob_public_key) = generate_near_keypair().unwrap();
+    let large_blob = wrap_near_private_key(
+        &near_private_key,
+        ACCOUNT_ID,
+        KeyWrappingScheme::LargeBlob,
+        None,
+    )
+    .unwrap();
+
+    let device_1 = decrypt_private_key_with_prf(
+        ACCOUNT_ID,
+        &dual_prf.chacha20_prf_output_base64,
+        &prf_blob.encrypted_near_key_data_b64u,
+        &prf_blob.chacha20_nonce_b64u,
+    )
+    .unwrap();
+    let device_2 = decrypt_private_key_with_prf(
+        ACCOUNT_ID,
+        large_blob.large_blob_secret.as_ref().unwrap(),
+        &large_blob.encrypted.encrypted_near_key_data_b64u,
+        &large_blob.encrypted.chacha20_nonce_b64u,
+    )
+    .unwrap();
+    assert_eq!(public_key_of(&device_1), prf_public_key);
+    assert_eq!(public_key_of(&device_2), large_blob_public_key);
+
+    // Each secret only opens its own device's blob
+    assert!(decrypt_private_key_with_prf(
+        ACCOUNT_ID,
+        &dual_prf.chacha20_prf_output_base64,
+        &large_blob.encrypted.encrypted_near_key_data_b64u,
+        &large_blob.encrypted.chacha20_nonce_b64u,
+    )
+    .is_err());
+    assert!(validate_encrypted_key_blob(
+        &stored_blob(
+            &prf_blob.encrypted_near_key_data_b64u,
+            &prf_blob.chacha20_nonce_b64u,
+            Some(1)
+        ),
+        ACCOUNT_ID
+    )
+    .is_empty());
+}
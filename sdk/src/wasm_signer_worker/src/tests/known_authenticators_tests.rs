@@ -0,0 +1,207 @@
+use crate::cose::describe_attestation_object_b64u;
+use crate::encoders::base64_url_encode;
+use crate::error::SignerErrorCode;
+use crate::handlers::handle_derive_near_keypair_and_encrypt::{
+    handle_derive_near_keypair_and_encrypt, DeriveNearKeypairAndEncryptRequest,
+};
+use crate::known_authenticators::{
+    authenticator_of_attestation, configure, lookup, AuthenticatorInfo, AuthenticatorModel,
+};
+use crate::tests::block_on;
+use ciborium::value::Value as CborValue;
+use serde_json::json;
+
+const ICLOUD_KEYCHAIN: [u8; 16] = [
+    0xfb, 0xfc, 0x30, 0x07, 0x15, 0x4e, 0x4e, 0xcc, 0x8c, 0x0b, 0x6e, 0x02, 0x05, 0x57, 0xd7, 0xbd,
+];
+const YUBIKEY_5: [u8; 16] = [
+    0xcb, 0x69, 0x48, 0x1e, 0x8f, 0xf7, 0x40, 0x39, 0x93, 0xec, 0x0a, 0x27, 0x29, 0xa1, 0x54, 0xa8,
+];
+const UNKNOWN: [u8; 16] = [0x42; 16];
+
+fn cbor(value: &CborValue) -> Vec<u8> {
+    let mut out = Vec::new();
+    ciborium::into_writer(value, &mut out).unwrap();
+    out
+}
+
+/// A "none" attestation object for an Ed25519 credential created on `aaguid`
+fn attestation_on(aaguid: [u8; 16]) -> String {
+    let cose_key = cbor(&CborValue::Map(vec![
+        (CborValue::Integer(1.into()), CborValue::Integer(1.into())),
+        (
+            CborValue::Integer(3.into()),
+            CborValue::Integer((-8).into()),
+        ),
+        (
+            CborValue::Integer((-1).into()),
+            CborValue::Integer(6.into()),
+        ),
+        (
+            CborValue::Integer((-2).into()),
+            CborValue::Bytes(vec![0x11; 32]),
+        ),
+    ]));
+    let mut auth_data = vec![0x49u8; 32];
+    auth_data.push(0x5D); // UP UV BE BS AT
+    auth_data.extend_from_slice(&0u32.to_be_bytes());
+    auth_data.extend_from_slice(&aaguid);
+    auth_data.extend_from_slice(&3u16.to_be_bytes());
+    auth_data.extend_from_slice(&[1, 2, 3]);
+    auth_data.extend_from_slice(&cose_key);
+    base64_url_encode(&cbor(&CborValue::Map(vec![
+        (
+            CborValue::Text("fmt".to_string()),
+            CborValue::Text("none".to_string()),
+        ),
+        (
+            CborValue::Text("attStmt".to_string()),
+            CborValue::Map(vec![]),
+        ),
+        (
+            CborValue::Text("authData".to_string()),
+            CborValue::Bytes(auth_data),
+        ),
+    ])))
+}
+
+fn entry(aaguid: &str, provider_name: &str, is_synced_passkey: Option<bool>) -> AuthenticatorModel {
+    AuthenticatorModel {
+        aaguid: aaguid.to_string(),
+        provider_name: provider_name.to_string(),
+        is_synced_passkey,
+    }
+}
+
+#[test]
+fn test_built_in_authenticators_are_named() {
+    assert_eq!(
+        lookup(&ICLOUD_KEYCHAIN),
+        AuthenticatorInfo {
+            aaguid: "fbfc3007-154e-4ecc-8c0b-6e020557d7bd".to_string(),
+            provider_name: Some("iCloud Keychain".to_string()),
+            is_synced_passkey: Some(true),
+        }
+    );
+    let yubikey = lookup(&YUBIKEY_5);
+    assert_eq!(yubikey.provider_name.as_deref(), Some("YubiKey 5 Series"));
+    assert_eq!(yubikey.is_synced_passkey, Some(false));
+}
+
+#[test]
+fn test_unknown_aaguid_is_reported_as_is() {
+    assert_eq!(
+        lookup(&UNKNOWN),
+        AuthenticatorInfo {
+            aaguid: "42424242-4242-4242-4242-424242424242".to_string(),
+            provider_name: None,
+            is_synced_passkey: None,
+        }
+    );
+    // "none" attestations may hide the model behind the all-zero AAGUID
+    assert_eq!(lookup(&[0u8; 16]).provider_name, None);
+}
+
+#[test]
+fn test_configured_entries_extend_and_replace_the_table() {
+    configure(vec![
+        entry(
+            "42424242-4242-4242-4242-424242424242",
+            "Acme Vault",
+            Some(true),
+        ),
+        entry(
+            "CB69481E-8FF7-4039-93EC-0A2729A154A8",
+            "Corporate YubiKey",
+            None,
+        ),
+    ])
+    .unwrap();
+    assert_eq!(
+        lookup(&UNKNOWN).provider_name.as_deref(),
+        Some("Acme Vault")
+    );
+    assert_eq!(lookup(&UNKNOWN).is_synced_passkey, Some(true));
+    let replaced = lookup(&YUBIKEY_5);
+    assert_eq!(replaced.provider_name.as_deref(), Some("Corporate YubiKey"));
+    assert_eq!(replaced.is_synced_passkey, None);
+    // Built-in entries not replaced stay
+    assert_eq!(
+        lookup(&ICLOUD_KEYCHAIN).provider_name.as_deref(),
+        Some("iCloud Keychain")
+    );
+
+    let refused = configure(vec![entry("not-a-uuid", "Acme Vault", None)]).unwrap_err();
+    assert_eq!(refused.code(), SignerErrorCode::InvalidConfig);
+    assert!(refused.to_string().contains("\"not-a-uuid\" is not a UUID"));
+    assert!(configure(vec![entry(
+        "42424242-4242-4242-4242-424242424242",
+        " ",
+        None
+    )])
+    .is_err());
+    // A refused configuration leaves the earlier one in place
+    assert_eq!(
+        lookup(&UNKNOWN).provider_name.as_deref(),
+        Some("Acme Vault")
+    );
+
+    configure(Vec::new()).unwrap();
+    assert_eq!(
+        lookup(&YUBIKEY_5).provider_name.as_deref(),
+        Some("YubiKey 5 Series")
+    );
+}
+
+#[test]
+fn test_parsed_attestation_names_the_provider() {
+    let parsed = describe_attestation_object_b64u(&attestation_on(ICLOUD_KEYCHAIN)).unwrap();
+    let credential = parsed.auth_data.attested_credential.unwrap();
+    assert_eq!(credential.aaguid, "fbfc3007-154e-4ecc-8c0b-6e020557d7bd");
+    assert_eq!(credential.provider_name.as_deref(), Some("iCloud Keychain"));
+    assert_eq!(credential.is_synced_passkey, Some(true));
+
+    let parsed = describe_attestation_object_b64u(&attestation_on(UNKNOWN)).unwrap();
+    let value = serde_json::to_value(parsed.auth_data.attested_credential.unwrap()).unwrap();
+    assert_eq!(
+        value["aaguid"],
+        json!("42424242-4242-4242-4242-424242424242")
+    );
+    assert_eq!(value["providerName"], json!(null));
+    assert_eq!(value["isSyncedPasskey"], json!(null));
+
+    assert_eq!(authenticator_of_attestation("not base64!"), None);
+}
+
+#[test]
+fn test_registration_result_carries_the_authenticator() {
+    let request: DeriveNearKeypairAndEncryptRequest = serde_json::from_value(json!({
+        "nearAccountId": "alice.testnet",
+        "credential": {
+            "id": "cred",
+            "rawId": "cred",
+            "type": "public-key",
+            "authenticatorAttachment": null,
+            "response": {
+                "clientDataJSON": base64_url_encode(b"{}"),
+                "attestationObject": attestation_on(YUBIKEY_5),
+                "transports": []
+            },
+            "clientExtensionResults": {}
+        },
+        "prfSupported": false,
+        "prfFallback": { "scheme": "largeBlob" },
+        "workerPolicy": { "prfFallbackSchemes": ["largeBlob"] }
+    }))
+    .unwrap();
+    let result = block_on(handle_derive_near_keypair_and_encrypt(request)).unwrap();
+    assert_eq!(result.authenticator, Some(lookup(&YUBIKEY_5)));
+    assert_eq!(
+        serde_json::to_value(&result).unwrap()["authenticator"],
+        json!({
+            "aaguid": "cb69481e-8ff7-4039-93ec-0a2729a154a8",
+            "providerName": "YubiKey 5 Series",
+            "isSyncedPasskey": false
+        })
+    );
+}
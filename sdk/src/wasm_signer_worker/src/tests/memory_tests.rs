@@ -0,0 +1,107 @@
+use crate::config::TRANSIENT_ARENA_RETAIN_MAX_BYTES;
+use crate::handlers::handle_memory::{
+    handle_get_memory_stats, handle_trim_caches, GetMemoryStatsRequest, TrimCachesRequest,
+};
+use crate::handlers::handle_sign_transaction_with_keypair::{
+    handle_sign_transaction_with_keypair, SignTransactionWithKeyPairRequest,
+};
+use crate::memory;
+use crate::tests::block_on;
+use serde_json::json;
+
+const DEPLOY_CODE_BYTES: usize = 3 * 1024 * 1024;
+
+fn near_private_key() -> String {
+    let signing_key = ed25519_dalek::SigningKey::from_bytes(&[1u8; 32]);
+    format!(
+        "ed25519:{}",
+        bs58::encode(signing_key.to_keypair_bytes()).into_string()
+    )
+}
+
+fn deploy_request(code: &[u8], nonce: u64) -> SignTransactionWithKeyPairRequest {
+    let actions = json!([{ "action_type": "DeployContract", "code": code }]);
+    SignTransactionWithKeyPairRequest {
+        near_private_key: near_private_key(),
+        signer_account_id: "alice.testnet".to_string(),
+        receiver_id: "alice.testnet".to_string(),
+        nonce: nonce.to_string(),
+        block_hash: bs58::encode([7u8; 32]).into_string(),
+        actions: actions.to_string(),
+        idempotency_key: None,
+    }
+}
+
+#[test]
+fn test_transient_buffers_are_reused_and_released() {
+    memory::with_transient_buffer(|buf| buf.extend_from_slice(&[0u8; 1024]));
+    memory::with_transient_buffer(|buf| {
+        // Same buffer, cleared, capacity kept
+        assert!(buf.is_empty());
+        assert!(buf.capacity() >= 1024);
+    });
+    assert!(memory::transient_buffer_bytes() >= 1024);
+
+    // Small buffers survive the per-request release; everything goes on a full trim
+    assert_eq!(memory::release_transient_buffers(64 * 1024), 0);
+    assert!(memory::release_transient_buffers(0) >= 1024);
+    assert_eq!(memory::transient_buffer_bytes(), 0);
+}
+
+#[test]
+fn test_repeated_large_deploys_do_not_ratchet_peak() {
+    let code = vec![0x42u8; DEPLOY_CODE_BYTES];
+    let mut peaks = Vec::new();
+    let mut live = Vec::new();
+
+    for nonce in 1..=10u64 {
+        let result = block_on(handle_sign_transaction_with_keypair(deploy_request(
+            &code, nonce,
+        )))
+        .unwrap();
+        assert!(result.success);
+        drop(result);
+        // As at the end of every dispatched request
+        memory::release_transient_buffers(TRANSIENT_ARENA_RETAIN_MAX_BYTES);
+
+        // The 3MB serialization buffer does not outlive the request
+        assert_eq!(memory::transient_buffer_bytes(), 0);
+        peaks.push(memory::peak_heap_bytes());
+        live.push(memory::live_heap_bytes());
+    }
+
+    // Peak is set by the first deploy and stays there
+    assert!(peaks[0] > DEPLOY_CODE_BYTES);
+    assert!(
+        peaks[9] <= peaks[1] + 64 * 1024,
+        "peak grew across deploys: {:?}",
+        peaks
+    );
+    assert!(
+        live[9] <= live[0] + 64 * 1024,
+        "live heap grew across deploys: {:?}",
+        live
+    );
+}
+
+#[test]
+fn test_memory_stats_and_trim() {
+    let account = crate::types::AccountId("alice.testnet".to_string());
+    crate::state::record_audit(&account, "mem-1", "TEST", "ok", None);
+    memory::with_transient_buffer(|buf| buf.resize(256 * 1024, 0));
+
+    let stats = block_on(handle_get_memory_stats(GetMemoryStatsRequest {})).unwrap();
+    assert_eq!(stats.memory_pages, None); // native build
+    assert!(stats.audit_entries >= 1);
+    assert!(stats.transient_buffer_bytes >= (256 * 1024) as f64);
+    assert!(stats.peak_heap_bytes >= stats.live_heap_bytes);
+
+    let trimmed = block_on(handle_trim_caches(TrimCachesRequest {})).unwrap();
+    assert!(trimmed.dropped_transient_buffer_bytes >= (256 * 1024) as f64);
+    assert!(trimmed.freed_heap_bytes >= (256 * 1024) as f64);
+    assert_eq!(trimmed.stats.transient_buffer_bytes, 0.0);
+    // Audit log is not a cache
+    assert!(trimmed.stats.audit_entries >= 1);
+    // Peak restarts from the trimmed live size
+    assert!(trimmed.stats.peak_heap_bytes < stats.peak_heap_bytes);
+}
@@ -0,0 +1,287 @@
+use crate::config::{DEFAULT_MAX_DEPLOY_MESSAGE_BYTES, DEFAULT_MAX_MESSAGE_BYTES};
+use crate::dispatch_signer_message;
+use crate::error::SignerErrorCode;
+use crate::message_limits::*;
+use crate::state;
+use crate::tests::block_on;
+use crate::types::handlers::MessageSizeLimits;
+use crate::types::worker_messages::{SignerWorkerMessage, WorkerRequestType, WorkerResponseType};
+use crate::wire_format::{decode_cbor_frame, decode_json_frame, WireFormat};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+const SIGN_TRANSACTIONS_WITH_ACTIONS: u32 = 4;
+const DEPLOY_LARGE_CONTRACT: u32 = 23;
+const GET_WORKER_INFO: u32 = 37;
+
+/// A JSON frame of about `bytes` bytes: the payload pads it with a number array, the way JSON
+/// frames carry contract code
+fn json_frame(msg_type: u32, bytes: usize) -> String {
+    let code = vec![0u8; bytes / 2];
+    json!({ "type": msg_type, "payload": { "code": code } }).to_string()
+}
+
+fn cbor_frame(value: &Value) -> Vec<u8> {
+    let mut frame = Vec::new();
+    ciborium::into_writer(value, &mut frame).unwrap();
+    frame
+}
+
+fn worker_info_is_served() {
+    let frame = json!({ "type": GET_WORKER_INFO, "payload": {} }).to_string();
+    let message = decode_json_frame(WireFormat::Json, &frame).unwrap();
+    let response = block_on(dispatch_signer_message(message)).unwrap();
+    assert_eq!(
+        WorkerResponseType::from(response.response_type),
+        WorkerResponseType::GetWorkerInfoSuccess
+    );
+}
+
+fn limits(default_bytes: Option<u32>, by_type: &[(&str, u32)]) -> MessageSizeLimits {
+    MessageSizeLimits {
+        default_bytes,
+        deploy_bytes: None,
+        by_type: by_type
+            .iter()
+            .map(|(name, limit)| (name.to_string(), *limit))
+            .collect(),
+    }
+}
+
+#[test]
+fn test_limit_for_request_types() {
+    let defaults = MessageSizeLimits::default();
+    assert_eq!(
+        defaults.limit_for(WorkerRequestType::SignTransactionsWithActions),
+        DEFAULT_MAX_MESSAGE_BYTES
+    );
+    assert_eq!(
+        defaults.limit_for(WorkerRequestType::DeployLargeContract),
+        DEFAULT_MAX_DEPLOY_MESSAGE_BYTES
+    );
+    assert_eq!(defaults.ceiling(), DEFAULT_MAX_DEPLOY_MESSAGE_BYTES);
+
+    let custom = limits(
+        Some(64 * 1024),
+        &[("SIGN_TRANSACTIONS_WITH_ACTIONS", 16 * 1024 * 1024)],
+    );
+    assert_eq!(
+        custom.limit_for(WorkerRequestType::GetWorkerInfo),
+        64 * 1024
+    );
+    assert_eq!(
+        custom.limit_for(WorkerRequestType::SignTransactionsWithActions),
+        16 * 1024 * 1024
+    );
+    assert_eq!(custom.ceiling(), 16 * 1024 * 1024);
+}
+
+#[test]
+fn test_oversized_json_frame_is_refused_and_the_next_one_served() {
+    let frame = json_frame(SIGN_TRANSACTIONS_WITH_ACTIONS, 2 * 1024 * 1024);
+    let error = decode_json_frame(WireFormat::Json, &frame).unwrap_err();
+    assert!(
+        error.starts_with(SignerErrorCode::MessageTooLarge.as_str()),
+        "{}",
+        error
+    );
+    assert!(
+        error.contains(&format!("the limit is {} bytes", DEFAULT_MAX_MESSAGE_BYTES)),
+        "{}",
+        error
+    );
+
+    worker_info_is_served();
+    let frame = json_frame(SIGN_TRANSACTIONS_WITH_ACTIONS, 512 * 1024);
+    assert!(decode_json_frame(WireFormat::Json, &frame).is_ok());
+}
+
+#[test]
+fn test_deploy_frames_get_the_larger_limit() {
+    let frame = json_frame(DEPLOY_LARGE_CONTRACT, 4 * 1024 * 1024);
+    let message = decode_json_frame(WireFormat::Json, &frame).unwrap();
+    assert_eq!(message.msg_type, DEPLOY_LARGE_CONTRACT);
+
+    let frame = json_frame(DEPLOY_LARGE_CONTRACT, 9 * 1024 * 1024);
+    let error = decode_json_frame(WireFormat::Json, &frame).unwrap_err();
+    assert!(
+        error.contains(&format!(
+            "the limit is {} bytes",
+            DEFAULT_MAX_DEPLOY_MESSAGE_BYTES
+        )),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn test_frames_above_every_limit_are_refused_unread() {
+    // Not JSON at all: a parse would fail, so the refusal shows it was never parsed
+    let frame = "x".repeat(DEFAULT_MAX_DEPLOY_MESSAGE_BYTES as usize + 1);
+    let error = decode_json_frame(WireFormat::Json, &frame).unwrap_err();
+    assert!(error.starts_with("MessageTooLarge"), "{}", error);
+    // Same for a worker initialized for the other wire format
+    let error = decode_json_frame(WireFormat::Cbor, &frame).unwrap_err();
+    assert!(error.starts_with("MessageTooLarge"), "{}", error);
+
+    let error = decode_cbor_frame(WireFormat::Cbor, frame.as_bytes()).unwrap_err();
+    assert!(error.starts_with("MessageTooLarge"), "{}", error);
+    worker_info_is_served();
+}
+
+#[test]
+fn test_oversized_cbor_frame_is_refused() {
+    let code = ciborium::Value::Bytes(vec![0u8; 2 * 1024 * 1024]);
+    let message = ciborium::Value::Map(vec![
+        (
+            ciborium::Value::Text("type".to_string()),
+            ciborium::Value::Integer(SIGN_TRANSACTIONS_WITH_ACTIONS.into()),
+        ),
+        (
+            ciborium::Value::Text("payload".to_string()),
+            ciborium::Value::Map(vec![(ciborium::Value::Text("code".to_string()), code)]),
+        ),
+    ]);
+    let mut frame = Vec::new();
+    ciborium::into_writer(&message, &mut frame).unwrap();
+
+    let error = decode_cbor_frame(WireFormat::Cbor, &frame).unwrap_err();
+    assert!(error.starts_with("MessageTooLarge"), "{}", error);
+    let valid = cbor_frame(&json!({ "type": GET_WORKER_INFO, "payload": {} }));
+    assert_eq!(
+        decode_cbor_frame(WireFormat::Cbor, &valid)
+            .unwrap()
+            .msg_type,
+        GET_WORKER_INFO
+    );
+}
+
+#[test]
+fn test_cbor_lengths_declared_beyond_the_frame_are_refused() {
+    // { "type": 4, "payload": <byte string of 2^32 bytes> } with 3 bytes of content
+    let mut byte_string = cbor_frame(&json!({ "type": 4 }));
+    byte_string[0] = 0xa2;
+    byte_string.extend_from_slice(&[0x67]);
+    byte_string.extend_from_slice(b"payload");
+    let header_offset = byte_string.len();
+    byte_string.extend_from_slice(&[0x5a, 0xff, 0xff, 0xff, 0xff, 1, 2, 3]);
+
+    let error = decode_cbor_frame(WireFormat::Cbor, &byte_string).unwrap_err();
+    assert_eq!(
+        error,
+        format!(
+            "MessageTooLarge: CBOR header at byte {} declares 4294967295 bytes or items, the frame has 3 left",
+            header_offset
+        )
+    );
+
+    // Arrays, maps and text declare counts the same way, also when nested or tagged
+    let cases: [&[u8]; 4] = [
+        &[0x9b, 0, 0, 0, 1, 0, 0, 0, 0, 0],
+        &[0xa1, 0x61, b'a', 0xbb, 0x40, 0, 0, 0, 0, 0, 0, 0],
+        &[0x82, 0x01, 0x79, 0x10, 0x00, b'a'],
+        &[0xc2, 0x9f, 0x5a, 0, 1, 0, 0, 0xff],
+    ];
+    for frame in cases {
+        let error = decode_cbor_frame(WireFormat::Cbor, frame).unwrap_err();
+        assert!(
+            error.starts_with("MessageTooLarge: CBOR header"),
+            "{:02x?}: {}",
+            frame,
+            error
+        );
+    }
+    worker_info_is_served();
+}
+
+#[test]
+fn test_well_formed_cbor_frames_pass_the_length_walk() {
+    let message = json!({
+        "type": GET_WORKER_INFO,
+        "payload": { "nested": [[1, -2, 3.5], { "text": "é", "flag": true, "none": null }] },
+        "requestId": "req-1"
+    });
+    let decoded = decode_cbor_frame(WireFormat::Cbor, &cbor_frame(&message)).unwrap();
+    assert_eq!(decoded.payload, message["payload"]);
+
+    // Indefinite-length map, array and text string, closed by breaks
+    let mut frame = vec![0xbf, 0x64];
+    frame.extend_from_slice(b"type");
+    frame.push(0x18);
+    frame.push(GET_WORKER_INFO as u8);
+    frame.push(0x67);
+    frame.extend_from_slice(b"payload");
+    frame.extend_from_slice(&[
+        0xbf, 0x61, b'a', 0x9f, 0x7f, 0x61, b'x', 0xff, 0xff, 0xff, 0xff,
+    ]);
+    let decoded = decode_cbor_frame(WireFormat::Cbor, &frame).unwrap();
+    assert_eq!(decoded.payload, json!({ "a": ["x"] }));
+}
+
+#[test]
+fn test_policy_limits_apply_to_later_frames() {
+    let dispatch = |payload: Value| {
+        block_on(dispatch_signer_message(SignerWorkerMessage {
+            msg_type: GET_WORKER_INFO,
+            payload,
+            request_id: None,
+        }))
+        .unwrap()
+    };
+
+    let response = dispatch(json!({
+        "workerPolicy": {
+            "messageSizeLimits": {
+                "defaultBytes": 8192,
+                "byType": { "SIGN_TRANSACTIONS_WITH_ACTIONS": 65536 }
+            }
+        }
+    }));
+    assert_eq!(
+        WorkerResponseType::from(response.response_type),
+        WorkerResponseType::GetWorkerInfoSuccess
+    );
+    let error =
+        decode_json_frame(WireFormat::Json, &json_frame(GET_WORKER_INFO, 16 * 1024)).unwrap_err();
+    assert!(error.contains("the limit is 8192 bytes"), "{}", error);
+    let frame = json_frame(SIGN_TRANSACTIONS_WITH_ACTIONS, 32 * 1024);
+    assert!(decode_json_frame(WireFormat::Json, &frame).is_ok());
+    // DeployLargeContract keeps its default, the largest limit
+    let frame = json_frame(DEPLOY_LARGE_CONTRACT, 2 * 1024 * 1024);
+    assert!(decode_json_frame(WireFormat::Json, &frame).is_ok());
+
+    // Invalid limits fail the request and leave the previous ones in force
+    for invalid in [
+        json!({ "defaultBytes": 10 }),
+        json!({ "byType": { "SIGN_EVERYTHING": 65536 } }),
+        json!({ "deployBytes": "8MB" }),
+    ] {
+        let response = dispatch(json!({ "workerPolicy": { "messageSizeLimits": invalid } }));
+        assert_eq!(
+            WorkerResponseType::from(response.response_type),
+            WorkerResponseType::GetWorkerInfoFailure
+        );
+        assert_eq!(response.payload["errorCode"], json!("InvalidConfig"));
+    }
+    assert_eq!(
+        state::message_size_limits(),
+        limits(Some(8192), &[("SIGN_TRANSACTIONS_WITH_ACTIONS", 65536)])
+    );
+}
+
+#[test]
+fn test_configure_validates_limits() {
+    assert!(configure(limits(Some(2048), &[])).is_err());
+    assert!(configure(limits(None, &[("DEPLOY_LARGE_CONTRACT", 1024)])).is_err());
+    assert_eq!(state::message_size_limits(), MessageSizeLimits::default());
+
+    let custom = MessageSizeLimits {
+        default_bytes: Some(256 * 1024),
+        deploy_bytes: Some(32 * 1024 * 1024),
+        by_type: HashMap::new(),
+    };
+    configure(custom.clone()).unwrap();
+    assert_eq!(state::message_size_limits(), custom);
+    let frame = json_frame(DEPLOY_LARGE_CONTRACT, 12 * 1024 * 1024);
+    assert!(decode_json_frame(WireFormat::Json, &frame).is_ok());
+}
@@ -1,7 +1,186 @@
 // Test modules
+pub mod account_bundle_tests;
+pub mod account_descriptor_tests;
+pub mod account_state_tests;
+pub mod action_firewall_tests;
 pub mod actions_tests;
+pub mod algorithm_negotiation_tests;
+pub mod allowance_tests;
+pub mod args_schema_tests;
+pub mod assertion_verify_tests;
+pub mod background_refresh_tests;
+pub mod batch_preview_tests;
+pub mod blob_migration_tests;
+pub mod borsh_schema_tests;
+pub mod chunked_deploy_tests;
+pub mod clock_tests;
+pub mod conditional_batch_tests;
+pub mod deprecation_tests;
+pub mod diagnostics_tests;
+pub mod dual_control_tests;
+pub mod dispatcher_fuzz_tests;
+pub mod config_builder_tests;
+pub mod confirmation_blocks_tests;
+pub mod confirmation_origin_tests;
+pub mod confirmation_speech_tests;
+pub mod contract_args_tests;
 pub mod cose_tests;
+pub mod countdown_handshake_tests;
+pub mod credential_options_tests;
 pub mod crypto_tests;
+pub mod decryption_capability_tests;
+pub mod deadline_tests;
+pub mod e2e;
+pub mod e2e_tests;
+pub mod elevation_tests;
+pub mod encrypted_blob_validation_tests;
+pub mod error_codes_tests;
+#[cfg(feature = "secp256k1")]
+pub mod evm_tests;
+pub mod feature_tests;
+#[cfg(feature = "device-linking")]
+pub mod idempotency_tests;
+pub mod key_selection_tests;
+pub mod key_usage_tests;
+pub mod key_wrapping_tests;
+pub mod known_authenticators_tests;
+#[cfg(feature = "device-linking")]
+pub mod memory_tests;
+pub mod message_limits_tests;
+pub mod multisig_tests;
+pub mod operation_journal_tests;
+pub mod outer_wrap_tests;
+pub mod peer_channel_tests;
+pub mod perf_budget_tests;
 pub mod progress_tests;
+#[cfg(feature = "device-linking")]
+pub mod qr_payload_tests;
+pub mod recent_receivers_tests;
+pub mod redaction_tests;
+pub mod redirect_payload_tests;
+pub mod render_budget_tests;
+#[cfg(feature = "relayer")]
+pub mod registration_resume_tests;
+#[cfg(feature = "device-linking")]
+pub mod remote_confirmation_tests;
+#[cfg(feature = "relayer")]
+pub mod relayer_tests;
+pub mod registration_dry_run_tests;
+pub mod request_registry_tests;
+pub mod response_keys_tests;
 pub mod rpc_calls_tests;
+pub mod rpc_client_tests;
+pub mod rpc_endpoints_tests;
+pub mod sealed_secrets_tests;
+pub mod security_tests;
+pub mod self_test_tests;
+pub mod session_duration_tests;
+pub mod session_key_tests;
+pub mod sign_phases_tests;
+pub mod storage_deposit_tests;
+pub mod stored_record_migration_tests;
+pub mod signature_verify_tests;
+pub mod signed_tx_decoder_tests;
+pub mod signing_hook_tests;
+pub mod signing_intent_tests;
+pub mod strict_parsing_tests;
+pub mod sub_key_tests;
+pub mod subaccount_tests;
+#[cfg(feature = "telemetry")]
+pub mod telemetry_tests;
 pub mod transaction_tests;
+pub mod tx_audit_tests;
+pub mod tx_dependencies_tests;
+pub mod tx_diff_tests;
+#[cfg(feature = "device-linking")]
+pub mod unsigned_transaction_tests;
+pub mod view_call_tests;
+pub mod wire_format_tests;
+pub mod worker_state_tests;
+
+/// Drives a handler future that never awaits a JS promise (single poll) in native tests
+pub fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+    fn noop_raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            noop_raw_waker()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+    let mut cx = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    match future.as_mut().poll(&mut cx) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("future did not complete synchronously"),
+    }
+}
+
+/// State key and wrapped form the native tests seal worker state records under
+pub const TEST_STATE_KEY: [u8; 32] = [0x5a; 32];
+pub const TEST_WRAPPED_STATE_KEY: &str = "test-wrapped-state-key";
+
+/// Runs `f` in a fresh worker: a new thread, hence fresh thread-local state, with the test
+/// state key installed and `record` (a previous worker's `workerStateRecord`) opened first
+pub fn in_fresh_worker<R: Send + 'static>(
+    record: Option<crate::worker_state::WorkerStateRecord>,
+    f: impl FnOnce() -> R + Send + 'static,
+) -> R {
+    std::thread::spawn(move || {
+        crate::worker_state::install_state_key(TEST_STATE_KEY, TEST_WRAPPED_STATE_KEY.to_string());
+        if let Some(record) = record {
+            crate::worker_state::open_record(&record).expect("worker state record opens");
+        }
+        f()
+    })
+    .join()
+    .expect("fresh worker thread panicked")
+}
+
+/// A serialized passkey assertion made for `challenge`, as the TS layer forwards it
+pub fn assertion_for_challenge(challenge: &str) -> serde_json::Value {
+    let client_data = serde_json::json!({
+        "type": "webauthn.get",
+        "challenge": challenge,
+        "origin": "https://wallet.example.com"
+    });
+    serde_json::json!({
+        "id": "credential-id",
+        "rawId": "credential-id",
+        "type": "public-key",
+        "response": {
+            "clientDataJSON": crate::encoders::base64_url_encode(client_data.to_string().as_bytes()),
+            "authenticatorData": "AAAA",
+            "signature": "AAAA"
+        }
+    })
+}
+
+/// An assertion made for an elevation challenge the worker just issued for `near_account_id`
+pub fn elevation_assertion(near_account_id: &str) -> serde_json::Value {
+    let issued = block_on(crate::handlers::handle_issue_elevation_challenge(
+        crate::handlers::IssueElevationChallengeRequest {
+            near_account_id: near_account_id.to_string(),
+        },
+    ))
+    .unwrap();
+    assertion_for_challenge(&issued.challenge)
+}
+
+/// A VRF challenge for `user_id` with placeholder proof bytes, for handlers that carry the
+/// challenge without verifying it
+pub fn vrf_challenge(user_id: &str) -> crate::types::VrfChallenge {
+    serde_json::from_value(serde_json::json!({
+        "vrfInput": "AQID",
+        "vrfOutput": "BAUG",
+        "vrfProof": "BwgJ",
+        "vrfPublicKey": "CgsM",
+        "userId": user_id,
+        "rpId": "example.com",
+        "blockHeight": "100",
+        "blockHash": "11111111111111111111111111111111"
+    }))
+    .unwrap()
+}
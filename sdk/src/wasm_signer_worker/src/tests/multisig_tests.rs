@@ -0,0 +1,133 @@
+use crate::actions::ActionParams;
+use crate::encoders::base64_standard_decode;
+use crate::handlers::handle_compose_multisig_request::{
+    handle_compose_multisig_request, ComposeMultisigRequest,
+};
+use crate::multisig::build_multisig_request;
+use crate::tests::block_on;
+use crate::transaction::build_actions_from_params;
+use serde_json::{json, Value};
+
+fn compose(
+    operation: &str,
+    actions: Option<Vec<ActionParams>>,
+    request_id: Option<u32>,
+) -> Result<Value, String> {
+    let result = block_on(handle_compose_multisig_request(ComposeMultisigRequest {
+        operation: operation.to_string(),
+        receiver_id: Some("dao.testnet".to_string()),
+        actions: actions.map(|a| serde_json::to_string(&a).unwrap()),
+        request_id,
+        gas: None,
+    }))?;
+    let action: ActionParams = serde_json::from_str(&result.action).unwrap();
+    // The composed action must be signable as-is
+    build_actions_from_params(vec![action.clone()]).unwrap();
+    match action {
+        ActionParams::FunctionCall {
+            method_name,
+            args,
+            deposit,
+            ..
+        } => {
+            assert_eq!(method_name, result.method_name);
+            assert_eq!(deposit, "0");
+            Ok(serde_json::from_str(&args).unwrap())
+        }
+        other => panic!("expected FunctionCall, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_add_request_and_confirm_maps_actions_to_multisig_schema() {
+    let args = compose(
+        "addRequestAndConfirm",
+        Some(vec![
+            ActionParams::Transfer {
+                deposit: "1000".to_string(),
+            },
+            ActionParams::FunctionCall {
+                method_name: "vote".to_string(),
+                args: r#"{"proposal":7}"#.to_string(),
+                gas: "30000000000000".to_string(),
+                deposit: "1".to_string(),
+            },
+            ActionParams::AddKey {
+                public_key: "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".to_string(),
+                access_key: json!({
+                    "nonce": 0,
+                    "permission": { "FunctionCall": {
+                        "allowance": "250",
+                        "receiver_id": "dao.testnet",
+                        "method_names": ["vote"]
+                    }}
+                })
+                .to_string(),
+            },
+        ]),
+        None,
+    )
+    .unwrap();
+
+    let request = &args["request"];
+    assert_eq!(request["receiver_id"], "dao.testnet");
+    assert_eq!(
+        request["actions"][0],
+        json!({ "type": "Transfer", "amount": "1000" })
+    );
+
+    let call = &request["actions"][1];
+    assert_eq!(call["type"], "FunctionCall");
+    assert_eq!(call["method_name"], "vote");
+    assert_eq!(call["deposit"], "1");
+    assert_eq!(call["gas"], "30000000000000");
+    let inner_args = base64_standard_decode(call["args"].as_str().unwrap()).unwrap();
+    assert_eq!(inner_args, br#"{"proposal":7}"#);
+
+    let add_key = &request["actions"][2];
+    assert_eq!(add_key["type"], "AddKey");
+    assert_eq!(add_key["permission"]["allowance"], "250");
+    assert_eq!(add_key["permission"]["method_names"], json!(["vote"]));
+}
+
+#[test]
+fn test_confirm_and_delete_request_are_keyed_by_request_id() {
+    assert_eq!(
+        compose("confirm", None, Some(12)).unwrap(),
+        json!({ "request_id": 12 })
+    );
+    assert_eq!(
+        compose("deleteRequest", None, Some(3)).unwrap(),
+        json!({ "request_id": 3 })
+    );
+    assert!(compose("confirm", None, None).is_err());
+    assert!(compose("executeEverything", None, Some(1)).is_err());
+}
+
+#[test]
+fn test_unsupported_inner_actions_are_rejected() {
+    let stake = ActionParams::Stake {
+        stake: "1".to_string(),
+        public_key: "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".to_string(),
+    };
+    let delete_account = ActionParams::DeleteAccount {
+        beneficiary_id: "bob.testnet".to_string(),
+    };
+    for action in [stake, delete_account] {
+        let err = build_multisig_request("dao.testnet", &[action]).unwrap_err();
+        assert!(
+            err.contains("not supported by the multisig contract"),
+            "{}",
+            err
+        );
+    }
+
+    let invalid_args = ActionParams::FunctionCall {
+        method_name: "vote".to_string(),
+        args: "not json".to_string(),
+        gas: "30000000000000".to_string(),
+        deposit: "0".to_string(),
+    };
+    assert!(build_multisig_request("dao.testnet", &[invalid_args]).is_err());
+    assert!(build_multisig_request("dao.testnet", &[]).is_err());
+}
@@ -0,0 +1,260 @@
+use crate::bench::confirmed_batch_of_5;
+use crate::config::MAX_RECOVERABLE_OPERATIONS;
+use crate::error::RpcErrorKind;
+use crate::handlers::handle_recover_pending_operations::{
+    handle_recover_pending_operations, RecoverPendingOperationsRequest,
+};
+use crate::handlers::handle_sign_transactions_with_actions::sign_near_transactions_with_actions_impl;
+use crate::operation_journal::{
+    parse_tx_status, recover_pending_operations, take_emitted_entries, JournalEntry, JournalStep,
+    RecoveryStatus,
+};
+use crate::rpc_client::{rpc_error_kind, MockRpcClient};
+use crate::tests::block_on;
+use serde_json::{json, Value};
+
+const SIGNER: &str = "alice.testnet";
+const TX_HASH: &str = "11111111111111111111111111111111";
+
+fn broadcast_entry() -> JournalEntry {
+    JournalEntry::broadcast("req-1", TX_HASH, SIGNER, Some("idem-1"))
+}
+
+fn unknown_transaction() -> Result<Value, RpcErrorKind> {
+    Err(rpc_error_kind(&json!({
+        "name": "HANDLER_ERROR",
+        "cause": { "name": "UNKNOWN_TRANSACTION" }
+    })))
+}
+
+fn statuses(rpc: &MockRpcClient, entries: Vec<JournalEntry>) -> Vec<RecoveryStatus> {
+    block_on(recover_pending_operations(rpc, entries))
+        .into_iter()
+        .map(|operation| operation.status)
+        .collect()
+}
+
+#[test]
+fn test_parse_tx_status() {
+    assert_eq!(
+        parse_tx_status(&json!({
+            "final_execution_status": "EXECUTED_OPTIMISTIC",
+            "status": { "SuccessValue": "" }
+        })),
+        (RecoveryStatus::Confirmed, Some(true))
+    );
+    assert_eq!(
+        parse_tx_status(&json!({
+            "final_execution_status": "FINAL",
+            "status": { "Failure": { "ActionError": {} } }
+        })),
+        (RecoveryStatus::Confirmed, Some(false))
+    );
+    for pending in ["NONE", "INCLUDED", "INCLUDED_FINAL"] {
+        assert_eq!(
+            parse_tx_status(&json!({ "final_execution_status": pending })),
+            (RecoveryStatus::Pending, None),
+            "{}",
+            pending
+        );
+    }
+    // Older nodes: an outcome means the transaction executed
+    assert_eq!(
+        parse_tx_status(&json!({ "status": { "SuccessValue": "" } })),
+        (RecoveryStatus::Confirmed, Some(true))
+    );
+}
+
+#[test]
+fn test_broadcast_recovery() {
+    let rpc = MockRpcClient::new().answer(
+        "tx",
+        Ok(json!({ "final_execution_status": "FINAL", "status": { "SuccessValue": "" } })),
+    );
+    let operations = block_on(recover_pending_operations(&rpc, vec![broadcast_entry()]));
+    assert_eq!(operations[0].status, RecoveryStatus::Confirmed);
+    assert_eq!(operations[0].succeeded, Some(true));
+    assert_eq!(
+        operations[0].entry,
+        broadcast_entry_with(&operations[0].entry)
+    );
+    assert_eq!(
+        rpc.calls_to("tx"),
+        vec![json!({ "tx_hash": TX_HASH, "sender_account_id": SIGNER, "wait_until": "NONE" })]
+    );
+
+    let rpc =
+        MockRpcClient::new().answer("tx", Ok(json!({ "final_execution_status": "INCLUDED" })));
+    assert_eq!(
+        statuses(&rpc, vec![broadcast_entry()]),
+        vec![RecoveryStatus::Pending]
+    );
+
+    let rpc = MockRpcClient::new().answer("tx", unknown_transaction());
+    assert_eq!(
+        statuses(&rpc, vec![broadcast_entry()]),
+        vec![RecoveryStatus::NotFound]
+    );
+
+    let rpc = MockRpcClient::new().answer("tx", Err(RpcErrorKind::Timeout("tx".to_string())));
+    let operations = block_on(recover_pending_operations(&rpc, vec![broadcast_entry()]));
+    assert_eq!(operations[0].status, RecoveryStatus::Unknown);
+    assert!(operations[0].detail.is_some());
+}
+
+/// `entry` with the request's fields; only createdAtMs is set by the clock
+fn broadcast_entry_with(entry: &JournalEntry) -> JournalEntry {
+    JournalEntry {
+        created_at_ms: entry.created_at_ms,
+        ..broadcast_entry()
+    }
+}
+
+#[test]
+fn test_relayer_submission_recovery() {
+    let entry = JournalEntry::relayer_submission("req-2", "bob.testnet");
+    assert_eq!(entry.step, JournalStep::RelayerSubmission);
+    assert_eq!(entry.tx_hash, None);
+
+    let rpc = MockRpcClient::new().answer("query/view_account", Ok(json!({ "amount": "1" })));
+    assert_eq!(
+        statuses(&rpc, vec![entry.clone()]),
+        vec![RecoveryStatus::Confirmed]
+    );
+
+    let rpc = MockRpcClient::new().answer(
+        "query/view_account",
+        Err(rpc_error_kind(&json!({
+            "name": "HANDLER_ERROR",
+            "cause": { "name": "UNKNOWN_ACCOUNT" }
+        }))),
+    );
+    assert_eq!(
+        statuses(&rpc, vec![entry.clone()]),
+        vec![RecoveryStatus::NotFound]
+    );
+
+    let rpc = MockRpcClient::new().answer(
+        "query/view_account",
+        Err(RpcErrorKind::Unreachable("offline".to_string())),
+    );
+    assert_eq!(statuses(&rpc, vec![entry]), vec![RecoveryStatus::Unknown]);
+}
+
+#[test]
+fn test_recovery_is_repeatable() {
+    let rpc = MockRpcClient::new()
+        .answer("tx", Ok(json!({ "final_execution_status": "INCLUDED" })))
+        .answer(
+            "tx",
+            Ok(json!({ "final_execution_status": "FINAL", "status": { "SuccessValue": "" } })),
+        );
+    let entries = vec![broadcast_entry()];
+    assert_eq!(
+        statuses(&rpc, entries.clone()),
+        vec![RecoveryStatus::Pending]
+    );
+    assert_eq!(
+        statuses(&rpc, entries.clone()),
+        vec![RecoveryStatus::Confirmed]
+    );
+    assert_eq!(statuses(&rpc, entries), vec![RecoveryStatus::Confirmed]);
+    // Only status lookups, never a broadcast
+    assert!(rpc.calls.borrow().iter().all(|(route, _)| route == "tx"));
+}
+
+#[test]
+fn test_journal_entries_hold_identifiers_only() {
+    let entry = serde_json::to_value(broadcast_entry()).unwrap();
+    let mut keys: Vec<&str> = entry
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    keys.sort();
+    assert_eq!(
+        keys,
+        vec![
+            "accountId",
+            "createdAtMs",
+            "idempotencyKey",
+            "requestId",
+            "step",
+            "txHash"
+        ]
+    );
+    assert_eq!(entry["step"], json!("broadcast"));
+
+    let parsed: JournalEntry = serde_json::from_value(entry).unwrap();
+    assert_eq!(parsed, broadcast_entry_with(&parsed));
+}
+
+#[test]
+fn test_broadcasts_are_journaled() {
+    let batch = confirmed_batch_of_5();
+    let rpc = MockRpcClient::new().answer(
+        "broadcast_tx_commit",
+        Ok(json!({ "status": { "SuccessValue": "" } })),
+    );
+    take_emitted_entries();
+    let result = block_on(sign_near_transactions_with_actions_impl(
+        batch.tx_requests.clone(),
+        &batch.decryption,
+        &batch.confirmation,
+        Some(&rpc),
+        None,
+        Some("idem-7"),
+        Vec::new(),
+    ))
+    .unwrap();
+
+    let entries = take_emitted_entries();
+    let hashes: Vec<String> = entries
+        .iter()
+        .map(|entry| entry.tx_hash.clone().unwrap())
+        .collect();
+    assert_eq!(Some(hashes), result.transaction_hashes);
+    for entry in &entries {
+        assert_eq!(entry.step, JournalStep::Broadcast);
+        assert_eq!(entry.request_id, "bench");
+        assert_eq!(entry.idempotency_key.as_deref(), Some("idem-7"));
+    }
+
+    // Signing without broadcasting has nothing to journal
+    block_on(sign_near_transactions_with_actions_impl(
+        batch.tx_requests.clone(),
+        &batch.decryption,
+        &batch.confirmation,
+        None::<&MockRpcClient>,
+        None,
+        None,
+        Vec::new(),
+    ))
+    .unwrap();
+    assert!(take_emitted_entries().is_empty());
+}
+
+#[test]
+fn test_recover_pending_operations_request_limits() {
+    let request = |near_rpc_url: &str, count: usize| RecoverPendingOperationsRequest {
+        near_rpc_url: near_rpc_url.to_string(),
+        entries: vec![broadcast_entry(); count],
+    };
+    let err = block_on(handle_recover_pending_operations(request("", 1))).unwrap_err();
+    assert!(err.contains("nearRpcUrl"), "{}", err);
+    let err = block_on(handle_recover_pending_operations(request(
+        "https://rpc.testnet.near.org",
+        MAX_RECOVERABLE_OPERATIONS + 1,
+    )))
+    .unwrap_err();
+    assert!(err.starts_with("At most"), "{}", err);
+
+    let result = block_on(handle_recover_pending_operations(request(
+        "https://rpc.testnet.near.org",
+        0,
+    )))
+    .unwrap();
+    assert!(result.operations.is_empty());
+    assert_eq!(result.unresolved, 0);
+}
@@ -0,0 +1,120 @@
+use crate::crypto::{
+    decrypt_stored_private_key_with_prf, derive_and_encrypt_keypair_from_dual_prf,
+};
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::SignerErrorCode;
+use crate::handlers::handle_validate_encrypted_blobs::{
+    validate_encrypted_key_blob, EncryptedKeyBlob,
+};
+use crate::outer_wrap::{frame_outer_wrap, split_outer_wrap, wrap_encrypted_data};
+use crate::tests::block_on;
+use crate::types::DualPrfOutputs;
+
+const ACCOUNT_ID: &str = "alice.testnet";
+
+fn dual_prf() -> DualPrfOutputs {
+    DualPrfOutputs {
+        chacha20_prf_output_base64: "Y2hhY2hhMjAtcHJmLW91dHB1dA".to_string(),
+        ed25519_prf_output_base64: "ZWQyNTUxOS1wcmYtb3V0cHV0".to_string(),
+    }
+}
+
+/// Stand-in for a WebCrypto wrap: the framing is real, the AES-GCM bytes are not
+fn fake_wrap(encrypted_data_b64u: &str) -> String {
+    let mut ciphertext = base64_url_decode(encrypted_data_b64u).unwrap();
+    ciphertext.extend_from_slice(&[0u8; 16]);
+    base64_url_encode(&frame_outer_wrap(&[5u8; 12], &ciphertext))
+}
+
+#[test]
+fn test_outer_wrap_framing_roundtrip() {
+    let framed = frame_outer_wrap(&[1u8; 12], &[2u8; 40]);
+    assert!(framed.starts_with(b"W3OW"));
+    let (iv, ciphertext) = split_outer_wrap(&framed).unwrap();
+    assert_eq!(iv, &[1u8; 12]);
+    assert_eq!(ciphertext, &[2u8; 40]);
+
+    // PRF-encrypted bytes (key-check header first) and truncated frames are not wrapped
+    assert!(split_outer_wrap(b"W3KC0123456789abcdef0123456789").is_none());
+    assert!(split_outer_wrap(&framed[..4 + 12 + 15]).is_none());
+}
+
+#[test]
+fn test_unwrapped_blobs_decrypt_and_stay_unwrapped() {
+    let (_, encrypted) = derive_and_encrypt_keypair_from_dual_prf(&dual_prf(), ACCOUNT_ID).unwrap();
+
+    assert!(block_on(decrypt_stored_private_key_with_prf(
+        ACCOUNT_ID,
+        &dual_prf().chacha20_prf_output_base64,
+        &encrypted.encrypted_near_key_data_b64u,
+        &encrypted.chacha20_nonce_b64u,
+    ))
+    .is_ok());
+
+    // Requests without a CryptoKey store the blob as encrypted
+    let (data, outer_wrap) =
+        block_on(wrap_encrypted_data(&encrypted.encrypted_near_key_data_b64u)).unwrap();
+    assert_eq!(data, encrypted.encrypted_near_key_data_b64u);
+    assert_eq!(outer_wrap, None);
+}
+
+#[test]
+fn test_wrapped_blob_without_key_reports_outer_wrap_key_unavailable() {
+    let (_, encrypted) = derive_and_encrypt_keypair_from_dual_prf(&dual_prf(), ACCOUNT_ID).unwrap();
+
+    let err = block_on(decrypt_stored_private_key_with_prf(
+        ACCOUNT_ID,
+        &dual_prf().chacha20_prf_output_base64,
+        &fake_wrap(&encrypted.encrypted_near_key_data_b64u),
+        &encrypted.chacha20_nonce_b64u,
+    ))
+    .unwrap_err();
+    assert_eq!(err.code(), Some(SignerErrorCode::OuterWrapKeyUnavailable));
+    assert!(
+        err.to_string().contains("link this device again"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_validation_checks_outer_wrap_metadata() {
+    let (_, encrypted) = derive_and_encrypt_keypair_from_dual_prf(&dual_prf(), ACCOUNT_ID).unwrap();
+    let blob = |data: String, outer_wrap: Option<&str>| EncryptedKeyBlob {
+        blob_id: None,
+        encrypted_private_key_data: data,
+        encrypted_private_key_iv: encrypted.chacha20_nonce_b64u.clone(),
+        version: None,
+        near_account_id: Some(ACCOUNT_ID.to_string()),
+        outer_wrap: outer_wrap.map(str::to_string),
+    };
+    let problem_codes = |blob: &EncryptedKeyBlob| -> Vec<String> {
+        validate_encrypted_key_blob(blob, ACCOUNT_ID)
+            .into_iter()
+            .map(|p| p.code)
+            .collect()
+    };
+    let wrapped = fake_wrap(&encrypted.encrypted_near_key_data_b64u);
+    let unwrapped = encrypted.encrypted_near_key_data_b64u.clone();
+
+    assert!(problem_codes(&blob(wrapped.clone(), Some("aes-gcm-webcrypto"))).is_empty());
+    assert_eq!(
+        problem_codes(&blob(wrapped.clone(), None)),
+        vec!["OuterWrapMismatch"]
+    );
+    assert_eq!(
+        problem_codes(&blob(unwrapped, Some("aes-gcm-webcrypto"))),
+        vec!["OuterWrapMismatch"]
+    );
+    assert_eq!(
+        problem_codes(&blob(wrapped, Some("rsa-oaep"))),
+        vec!["UnknownOuterWrap"]
+    );
+
+    // The inner ciphertext length is still checked through the wrap
+    let short = base64_url_encode(&frame_outer_wrap(&[5u8; 12], &[0u8; 16 + 15]));
+    assert_eq!(
+        problem_codes(&blob(short, Some("aes-gcm-webcrypto"))),
+        vec!["CiphertextTooShort"]
+    );
+}
@@ -0,0 +1,153 @@
+use crate::error::SignerErrorCode;
+use crate::peer_channel::*;
+use crate::state;
+use crate::tests::block_on;
+use crate::types::VrfChallenge;
+use serde_json::json;
+
+const SESSION_KEY: &str = "AQIDBAUGBwgJCgsMDQ4PEBESExQVFhcYGRobHB0eHyA";
+const OTHER_KEY: &str = "IB8eHRwbGhkYFxYVFBMSERAPDg0MCwoJCAcGBQQDAgE";
+
+fn session() -> PeerSession {
+    PeerSession {
+        expected_vrf_public_key: SESSION_KEY.to_string(),
+        rp_id: "example.localhost".to_string(),
+    }
+}
+
+fn request() -> PeerChallengeRequest {
+    PeerChallengeRequest {
+        user_id: "alice.testnet".to_string(),
+        rp_id: "example.localhost".to_string(),
+        anchor: None,
+    }
+}
+
+/// A VRF worker REQUEST_CHALLENGE response proven with `vrf_public_key`
+fn challenge_response(vrf_public_key: &str) -> serde_json::Value {
+    json!({
+        "id": "peer-1",
+        "success": true,
+        "data": {
+            "vrfInput": "aW5wdXQ",
+            "vrfOutput": "b3V0cHV0",
+            "vrfProof": "cHJvb2Y",
+            "vrfPublicKey": vrf_public_key,
+            "userId": "alice.testnet",
+            "rpId": "example.localhost",
+            "blockHeight": "300",
+            "blockHash": "11111111111111111111111111111111"
+        }
+    })
+}
+
+fn challenge(vrf_public_key: &str) -> VrfChallenge {
+    serde_json::from_value(challenge_response(vrf_public_key)["data"].clone()).unwrap()
+}
+
+#[test]
+fn test_peer_challenge_must_use_the_session_key() {
+    let from_session = parse_peer_challenge_response(
+        &challenge_response(SESSION_KEY).to_string(),
+        &request(),
+        &session(),
+    )
+    .unwrap();
+    assert_eq!(from_session.vrf_public_key, SESSION_KEY);
+    assert_eq!(from_session.block_height, "300");
+
+    // Proven with another key (e.g. another account's VRF session)
+    let (code, message) = parse_peer_challenge_response(
+        &challenge_response(OTHER_KEY).to_string(),
+        &request(),
+        &session(),
+    )
+    .unwrap_err();
+    assert_eq!(code, SignerErrorCode::PeerChallengeRejected);
+    assert!(message.contains(OTHER_KEY), "{}", message);
+
+    // For another rpId
+    let other_rp = PeerChallengeRequest {
+        rp_id: "other.localhost".to_string(),
+        ..request()
+    };
+    assert_eq!(
+        verify_peer_challenge(&challenge(SESSION_KEY), &other_rp, &session())
+            .unwrap_err()
+            .0,
+        SignerErrorCode::PeerChallengeRejected
+    );
+
+    // VRF worker errors are unavailability, not rejections
+    let failed = json!({ "id": "peer-1", "success": false, "error": "VRF keypair not unlocked - please login first" });
+    let (code, message) =
+        parse_peer_challenge_response(&failed.to_string(), &request(), &session()).unwrap_err();
+    assert_eq!(code, SignerErrorCode::PeerChallengeUnavailable);
+    assert!(message.ends_with("please login first"), "{}", message);
+    assert_eq!(
+        parse_peer_challenge_response("not json", &request(), &session())
+            .unwrap_err()
+            .0,
+        SignerErrorCode::PeerChallengeUnavailable
+    );
+}
+
+#[test]
+fn test_confirmation_must_return_the_peer_challenge() {
+    let requested = challenge(SESSION_KEY);
+    assert!(check_decision_challenge(&requested, Some(&requested)).is_ok());
+
+    // A main thread that swapped in its own challenge, or dropped it
+    let swapped = VrfChallenge {
+        vrf_output: "b3RoZXI".to_string(),
+        ..requested.clone()
+    };
+    assert_eq!(
+        check_decision_challenge(&requested, Some(&swapped))
+            .unwrap_err()
+            .0,
+        SignerErrorCode::PeerChallengeRejected
+    );
+    assert!(check_decision_challenge(&requested, None).is_err());
+}
+
+#[test]
+fn test_without_a_port_requests_keep_the_main_thread_path() {
+    assert_eq!(peer_session(), None);
+    assert!(block_on(request_peer_challenge("alice.testnet"))
+        .unwrap()
+        .is_none());
+
+    // Once connected, a port that does not answer fails the request instead of falling back
+    connect_peer_session(session()).unwrap();
+    assert_eq!(
+        block_on(request_peer_challenge("alice.testnet"))
+            .unwrap_err()
+            .0,
+        SignerErrorCode::PeerChallengeUnavailable
+    );
+
+    // The session needs a well-formed expected key and an rpId
+    assert!(connect_peer_session(PeerSession {
+        expected_vrf_public_key: "not base64url!".to_string(),
+        ..session()
+    })
+    .is_err());
+    assert!(connect_peer_session(PeerSession {
+        rp_id: String::new(),
+        ..session()
+    })
+    .is_err());
+    assert_eq!(peer_session(), Some(session()));
+}
+
+#[test]
+fn test_wipe_all_state_tears_down_the_peer_channel() {
+    connect_peer_session(session()).unwrap();
+    assert!(state::wipe_all_state().closed_peer_port);
+    assert_eq!(peer_session(), None);
+    assert!(block_on(request_peer_challenge("alice.testnet"))
+        .unwrap()
+        .is_none());
+    assert!(!state::wipe_all_state().closed_peer_port);
+}
@@ -0,0 +1,71 @@
+use crate::bench::*;
+use crate::rpc_client::{rpc_error_kind, MockRpcClient};
+use serde_json::json;
+
+/// Few runs: the budgets only need to catch order-of-magnitude regressions
+const BUDGET_ITERATIONS: usize = 5;
+
+#[test]
+fn test_hot_paths_stay_within_perf_budget() {
+    let over_budget: Vec<String> = BENCH_CASES
+        .iter()
+        .filter_map(|case| {
+            let median = run_case(case, BUDGET_ITERATIONS);
+            (median > case.budget).then(|| {
+                format!(
+                    "{}: median {:?} over its {:?} budget",
+                    case.name, median, case.budget
+                )
+            })
+        })
+        .collect();
+    assert!(over_budget.is_empty(), "{}", over_budget.join("\n"));
+}
+
+#[test]
+fn test_build_and_sign_runs_on_the_mock_rpc() {
+    let batch = confirmed_batch_of_5();
+    let hashes = verify_and_sign(&batch).unwrap();
+    assert_eq!(hashes.len(), 5);
+
+    // The verification call went through the mock, not the network
+    let calls = batch.rpc.calls_to(&verification_route());
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0]["account_id"], json!(BENCH_CONTRACT_ID));
+    assert_eq!(calls[0]["finality"], json!("final"));
+    assert_eq!(batch.rpc.calls.borrow().len(), 1);
+
+    // A contract that refuses the credential stops the batch before signing
+    let refused = ConfirmedBatch {
+        rpc: MockRpcClient::new().answer(
+            &verification_route(),
+            call_function_answer(&json!({ "verified": false })),
+        ),
+        ..confirmed_batch_of_5()
+    };
+    assert_eq!(
+        verify_and_sign(&refused).unwrap_err(),
+        "Contract verification failed"
+    );
+    let rpc_error = ConfirmedBatch {
+        rpc: MockRpcClient::new().answer(
+            &verification_route(),
+            Err(rpc_error_kind(&json!({ "message": "Server error" }))),
+        ),
+        ..confirmed_batch_of_5()
+    };
+    assert_eq!(verify_and_sign(&rpc_error).unwrap_err(), "Server error");
+}
+
+/// Benchmark: run with `cargo test --release bench_signer_hot_paths -- --ignored --nocapture`
+#[test]
+#[ignore = "benchmark"]
+fn bench_signer_hot_paths() {
+    for case in BENCH_CASES {
+        let median = run_case(case, 200);
+        println!(
+            "{:<40} median {:>12?} (budget {:?})",
+            case.name, median, case.budget
+        );
+    }
+}
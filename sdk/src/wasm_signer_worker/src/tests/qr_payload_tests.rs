@@ -0,0 +1,225 @@
+use crate::config::{QR_PART_MAX_CHARS, QR_PAYLOAD_PREFIX};
+use crate::deflate::{deflate, inflate};
+use crate::encoders::base64_url_decode;
+use crate::error::{QrPayloadError, SignerErrorCode};
+use crate::qr_payload::{
+    base45_decode, base45_encode, decode_qr_parts, QrPayloadDecoder, QrPayloadEncoder,
+    QrPayloadKind,
+};
+
+const SIZES: [usize; 7] = [200, 512, 1024, 1500, 2048, 4096, 8192];
+
+/// Deterministic noise (xorshift), which does not compress
+fn noise(len: usize, seed: u64) -> Vec<u8> {
+    let mut state = seed | 1;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+/// Something shaped like a CBOR request: repeated keys and account IDs between random bytes
+fn request_like(len: usize, seed: u64) -> Vec<u8> {
+    let mut blob = Vec::with_capacity(len);
+    let random = noise(len, seed);
+    let mut i = 0;
+    while blob.len() < len {
+        blob.extend_from_slice(b"\xa2kreceiverIdmalice.testnetgactions\x81");
+        blob.extend_from_slice(&random[i..(i + 24).min(len)]);
+        i = (i + 24) % len;
+    }
+    blob.truncate(len);
+    blob
+}
+
+fn decode(parts: &[String]) -> Result<Vec<u8>, QrPayloadError> {
+    let mut decoder = QrPayloadDecoder::new();
+    for part in parts {
+        decoder.add_part(part)?;
+    }
+    decoder.finish()
+}
+
+#[test]
+fn test_base45_matches_rfc_9285() {
+    assert_eq!(base45_encode(b"AB"), "BB8");
+    assert_eq!(base45_encode(b"Hello!!"), "%69 VD92EX0");
+    assert_eq!(base45_encode(b"base-45"), "UJCLQE7W581");
+    assert_eq!(base45_decode("QED8WEX0").unwrap(), b"ietf!");
+    assert!(base45_decode("GGW").is_err()); // 65535 + 1
+    assert!(base45_decode("ZZZZ").is_err()); // dangling character
+    assert!(base45_decode("ab").is_err()); // lowercase is not in the alphabet
+}
+
+#[test]
+fn test_deflate_round_trips_and_is_deterministic() {
+    for size in [0, 1, 200, 8192, 70_000] {
+        for blob in [noise(size, 7), request_like(size, 7), vec![0x61; size]] {
+            let compressed = deflate(&blob);
+            assert_eq!(compressed, deflate(&blob));
+            assert_eq!(inflate(&compressed, blob.len()).unwrap(), blob);
+        }
+    }
+    // Repetitive input compresses; noise costs at most the stored block headers
+    assert!(deflate(&request_like(4096, 1)).len() < 4096 / 2);
+    assert!(deflate(&noise(4096, 1)).len() <= 4096 + 5);
+}
+
+#[test]
+fn test_inflate_reads_dynamic_huffman_blocks() {
+    // zlib level 9, raw DEFLATE: one dynamic Huffman block
+    let compressed = [
+        0xb5, 0x8e, 0xc9, 0x11, 0x80, 0x20, 0x14, 0xc5, 0x5a, 0x79, 0x15, 0x78, 0xb1, 0x15, 0x1a,
+        0x60, 0x13, 0x10, 0xe5, 0x23, 0xb2, 0x88, 0xd5, 0x4b, 0x11, 0x72, 0x4e, 0x66, 0x12, 0x66,
+        0x35, 0xae, 0xe2, 0xa4, 0x87, 0x48, 0xd4, 0x02, 0x36, 0x7a, 0xb0, 0x97, 0x33, 0xde, 0xa0,
+        0xaa, 0x13, 0xf2, 0xc0, 0x07, 0x7f, 0x3b, 0x14, 0x99, 0x05, 0x6c, 0x9a, 0xdc, 0xb4, 0x58,
+        0x79, 0xc9, 0x36, 0x40, 0xe9, 0xea, 0xe4, 0x40, 0x2e, 0x78, 0x17, 0x0c, 0x22, 0xef, 0x07,
+        0x71, 0x35, 0x31, 0xfd, 0xef, 0xe7, 0x07,
+    ];
+    let expected = [
+        b"The quick brown fox jumps over the lazy dog. ".repeat(3),
+        b"web3authn device linking payload ".to_vec(),
+    ]
+    .concat()
+    .repeat(2);
+    assert_eq!(inflate(&compressed, 4096).unwrap(), expected);
+    // The output limit holds whatever the stream claims
+    assert!(inflate(&compressed, 100)
+        .unwrap_err()
+        .contains("past 100 bytes"));
+    assert!(inflate(&compressed[..40], 4096).is_err());
+}
+
+#[test]
+fn test_parts_round_trip_across_sizes() {
+    let encoder = QrPayloadEncoder::default();
+    for size in SIZES {
+        for blob in [noise(size, size as u64), request_like(size, size as u64)] {
+            let parts = encoder
+                .encode(QrPayloadKind::RemoteConfirmation, &blob)
+                .unwrap();
+            assert_eq!(
+                parts,
+                encoder
+                    .encode(QrPayloadKind::RemoteConfirmation, &blob)
+                    .unwrap()
+            );
+            for part in &parts {
+                assert!(part.len() <= QR_PART_MAX_CHARS, "{} chars", part.len());
+                assert!(part.starts_with(QR_PAYLOAD_PREFIX));
+                // QR alphanumeric mode only
+                assert!(part
+                    .bytes()
+                    .all(|c| b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:".contains(&c)));
+            }
+            assert_eq!(decode(&parts).unwrap(), blob, "{} bytes", size);
+        }
+    }
+    // Noise fits in one code at 200 bytes and needs several at 8KB
+    let parts = |blob: &[u8]| encoder.encode(QrPayloadKind::DeviceLinking, blob).unwrap();
+    assert_eq!(parts(&noise(200, 1)).len(), 1);
+    assert_eq!(parts(&noise(8192, 1)).len(), 11);
+    // Compression keeps request-shaped blobs in fewer codes
+    assert!(parts(&request_like(8192, 1)).len() < 11);
+}
+
+#[test]
+fn test_parts_reassemble_in_any_order() {
+    let blob = noise(4096, 3);
+    let mut parts = QrPayloadEncoder::default()
+        .encode(QrPayloadKind::DeviceLinking, &blob)
+        .unwrap();
+    assert_eq!(parts.len(), 6);
+    parts.reverse();
+    parts.swap(1, 4);
+
+    let decoded = decode_qr_parts(&parts[..4]).unwrap();
+    assert_eq!(decoded.kind, QrPayloadKind::DeviceLinking);
+    assert_eq!(decoded.part_count, 6);
+    assert_eq!(decoded.missing_parts, vec![0, 4]);
+    assert_eq!(decoded.blob_b64u, None);
+    // Rescanning a part is harmless
+    let mut rescanned = parts.clone();
+    rescanned.push(parts[2].clone());
+    let decoded = decode_qr_parts(&rescanned).unwrap();
+    assert!(decoded.missing_parts.is_empty());
+    assert_eq!(
+        base64_url_decode(&decoded.blob_b64u.unwrap()).unwrap(),
+        blob
+    );
+
+    let mut decoder = QrPayloadDecoder::new();
+    assert!(decoder.missing_parts().is_empty());
+    decoder.add_part(&parts[0]).unwrap();
+    assert_eq!(
+        decoder.finish(),
+        Err(QrPayloadError::Incomplete {
+            missing_parts: vec![0, 1, 2, 3, 4]
+        })
+    );
+}
+
+#[test]
+fn test_corrupted_parts_are_refused() {
+    for size in SIZES {
+        let blob = request_like(size, 11);
+        let parts = QrPayloadEncoder::new(400)
+            .unwrap()
+            .encode(QrPayloadKind::RemoteConfirmation, &blob)
+            .unwrap();
+        // Change one character of the data in the last part, keeping it valid base45
+        let mut corrupted = parts.clone();
+        let last = corrupted.last_mut().unwrap();
+        let at = last.len() - 3;
+        let swapped = if &last[at..at + 1] == "0" { "1" } else { "0" };
+        last.replace_range(at..at + 1, swapped);
+        let err = decode(&corrupted).unwrap_err();
+        assert_eq!(err.code(), SignerErrorCode::QrPayloadInvalid);
+        assert!(
+            matches!(
+                err,
+                QrPayloadError::ChecksumMismatch | QrPayloadError::Malformed(_)
+            ),
+            "{} bytes: {:?}",
+            size,
+            err
+        );
+    }
+
+    let parts = QrPayloadEncoder::default()
+        .encode(QrPayloadKind::DeviceLinking, &noise(4096, 5))
+        .unwrap();
+    let other = QrPayloadEncoder::default()
+        .encode(QrPayloadKind::DeviceLinking, &noise(4096, 6))
+        .unwrap();
+    let err = decode(&[parts[0].clone(), other[1].clone()]).unwrap_err();
+    assert!(matches!(err, QrPayloadError::Mismatched(_)), "{:?}", err);
+    assert!(decode(&["W3A:".to_string()]).is_err());
+    assert!(decode(&[parts[0].replacen("W3A:", "W3B:", 1)]).is_err());
+    assert_eq!(
+        decode_qr_parts(&[]).unwrap_err().to_string(),
+        "QrPayloadInvalid: no parts were given"
+    );
+}
+
+#[test]
+fn test_encoder_refuses_what_it_cannot_carry() {
+    assert!(QrPayloadEncoder::new(20).is_err());
+    let tiny = QrPayloadEncoder::new(64).unwrap();
+    let err = tiny
+        .encode(QrPayloadKind::DeviceLinking, &noise(20_000, 1))
+        .unwrap_err();
+    assert!(err.to_string().contains("more than 255"), "{}", err);
+    assert!(QrPayloadEncoder::default()
+        .encode(QrPayloadKind::DeviceLinking, &vec![0; 64 * 1024 + 1])
+        .is_err());
+    assert_eq!(
+        QrPayloadKind::parse("remoteConfirmation"),
+        Some(QrPayloadKind::RemoteConfirmation)
+    );
+    assert_eq!(QrPayloadKind::parse("RemoteConfirmation"), None);
+}
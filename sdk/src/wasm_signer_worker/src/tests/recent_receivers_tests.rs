@@ -0,0 +1,140 @@
+use crate::actions::ActionParams;
+use crate::handlers::confirm_tx_details::{
+    compute_intent_digest_from_js_inputs, tx_signing_requests_json,
+};
+use crate::handlers::handle_get_recent_receivers::{
+    handle_get_recent_receivers, GetRecentReceiversRequest,
+};
+use crate::recent_receivers::*;
+use crate::state::{self, RecentReceiver};
+use crate::tests::block_on;
+use crate::types::AccountId;
+use serde_json::json;
+
+const ACCOUNT_ID: &str = "alice.testnet";
+
+fn receiver(receiver_id: &str, last_seen_ms: f64) -> RecentReceiver {
+    RecentReceiver {
+        receiver_id: receiver_id.to_string(),
+        last_seen_ms,
+    }
+}
+
+fn request(limit: Option<u32>) -> GetRecentReceiversRequest {
+    GetRecentReceiversRequest {
+        account_id: ACCOUNT_ID.to_string(),
+        limit,
+        worker_policy: None,
+    }
+}
+
+#[test]
+fn test_parse_indexer_receivers_keeps_distinct_outgoing_receivers() {
+    let response = json!({
+        "txns": [
+            { "predecessor_account_id": ACCOUNT_ID, "receiver_account_id": "bob.testnet",
+              "block_timestamp": "1700000001000000000" },
+            // Incoming: not a receiver of this account
+            { "predecessor_account_id": "carol.testnet", "receiver_account_id": ACCOUNT_ID,
+              "block_timestamp": "1700000005000000000" },
+            { "signer_account_id": ACCOUNT_ID, "predecessor_account_id": "relayer.testnet",
+              "receiver_account_id": "usdc.testnet", "block_timestamp": 1700000002000000000u64 },
+            // Older repeat of bob: keeps the latest timestamp
+            { "predecessor_account_id": ACCOUNT_ID, "receiver_account_id": "bob.testnet",
+              "block_timestamp": "1700000000000000000" },
+            // Self-transaction (e.g. AddKey)
+            { "predecessor_account_id": ACCOUNT_ID, "receiver_account_id": ACCOUNT_ID,
+              "block_timestamp": "1700000003000000000" }
+        ]
+    });
+    let receivers = parse_indexer_receivers(ACCOUNT_ID, &response).unwrap();
+    assert_eq!(
+        receivers,
+        vec![
+            receiver("usdc.testnet", 1_700_000_002_000.0),
+            receiver("bob.testnet", 1_700_000_001_000.0),
+        ]
+    );
+
+    assert!(parse_indexer_receivers(ACCOUNT_ID, &json!({ "txns": "nope" })).is_err());
+    assert_eq!(
+        indexer_txns_url("https://api.nearblocks.io/v1/", ACCOUNT_ID),
+        "https://api.nearblocks.io/v1/account/alice.testnet/txns?per_page=100"
+    );
+}
+
+#[test]
+fn test_first_time_receiver_flags() {
+    let known = ReceiverHistory {
+        receivers: Some(vec![receiver("bob.testnet", 1.0)]),
+        source: ReceiversSource::Cache,
+    };
+    let receiver_ids = ["bob.testnet", "mallory.testnet", ACCOUNT_ID];
+    assert_eq!(
+        first_time_receiver_flags(ACCOUNT_ID, &known, &receiver_ids),
+        vec![Some(false), Some(true), Some(false)]
+    );
+
+    let unknown = ReceiverHistory {
+        receivers: None,
+        source: ReceiversSource::Unknown,
+    };
+    assert_eq!(
+        first_time_receiver_flags(ACCOUNT_ID, &unknown, &receiver_ids),
+        vec![None, None, None]
+    );
+}
+
+#[test]
+fn test_get_recent_receivers_degrades_to_unknown_and_uses_cache() {
+    state::wipe_all_state();
+
+    // No indexer configured: unknown, not an error
+    let result = block_on(handle_get_recent_receivers(request(None))).unwrap();
+    assert_eq!(result.source, "unknown");
+    assert!(result.receivers.is_empty());
+
+    state::cache_recent_receivers(
+        &AccountId(ACCOUNT_ID.to_string()),
+        vec![receiver("usdc.testnet", 2.0), receiver("bob.testnet", 1.0)],
+    );
+    let result = block_on(handle_get_recent_receivers(request(Some(1)))).unwrap();
+    assert_eq!(result.source, "cache");
+    assert_eq!(result.receivers, vec![receiver("usdc.testnet", 2.0)]);
+
+    // Signing to a new receiver moves it to the front of the session history
+    state::note_recent_receiver(&AccountId(ACCOUNT_ID.to_string()), "bob.testnet");
+    let result = block_on(handle_get_recent_receivers(request(None))).unwrap();
+    let ids: Vec<&str> = result
+        .receivers
+        .iter()
+        .map(|r| r.receiver_id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["bob.testnet", "usdc.testnet"]);
+
+    state::wipe_all_state();
+    assert!(state::cached_recent_receivers(&AccountId(ACCOUNT_ID.to_string())).is_none());
+}
+
+#[test]
+fn test_first_time_receiver_annotation_is_covered_by_digest() {
+    let txs = vec![(
+        "mallory.testnet".to_string(),
+        vec![ActionParams::Transfer {
+            deposit: "1".to_string(),
+        }],
+    )];
+
+    let payload = tx_signing_requests_json(&txs, Some(&[Some(true)]));
+    assert_eq!(payload[0]["firstTimeReceiver"], json!(true));
+    let unknown_payload = tx_signing_requests_json(&txs, Some(&[None]));
+    assert_eq!(unknown_payload[0]["firstTimeReceiver"], json!(null));
+
+    let first_time = compute_intent_digest_from_js_inputs(&txs, Some(&[Some(true)])).unwrap();
+    let known = compute_intent_digest_from_js_inputs(&txs, Some(&[Some(false)])).unwrap();
+    let unknown = compute_intent_digest_from_js_inputs(&txs, Some(&[None])).unwrap();
+    let unannotated = compute_intent_digest_from_js_inputs(&txs, None).unwrap();
+    assert_ne!(first_time, known);
+    assert_ne!(known, unknown);
+    assert_ne!(unknown, unannotated);
+}
@@ -0,0 +1,233 @@
+use crate::actions::ActionParams;
+use crate::confirmation_blocks::{
+    transaction_summary_blocks, ConfirmationSummaryBlock, SummaryPolicy,
+};
+use crate::error::{RedactionError, SignerErrorCode};
+use crate::handlers::confirm_tx_details::{
+    compute_confirmation_intent_digest, confirmation_tx_signing_requests_json,
+};
+use crate::handlers::{
+    handle_sign_transactions_with_actions, SignTransactionsWithActionsRequest, TransactionPayload,
+};
+use crate::redaction::*;
+use crate::tests::block_on;
+use serde_json::{json, Value};
+
+const RECEIVER: &str = "profiles.testnet";
+
+fn paths(paths: &[&str]) -> Vec<RedactPath> {
+    parse_redact_paths(&paths.iter().map(|p| p.to_string()).collect::<Vec<_>>()).unwrap()
+}
+
+fn call(args: Value) -> ActionParams {
+    ActionParams::FunctionCall {
+        method_name: "set_profile".to_string(),
+        args: args.to_string(),
+        gas: "30000000000000".to_string(),
+        deposit: "0".to_string(),
+    }
+}
+
+fn shown_args(action: &ActionParams) -> Value {
+    match action {
+        ActionParams::FunctionCall { args, .. } => serde_json::from_str(args).unwrap(),
+        other => panic!("not a FunctionCall: {:?}", other),
+    }
+}
+
+fn profile_args(email: &str) -> Value {
+    json!({
+        "email": email,
+        "note": { "text": "call after 6pm" },
+        "tags": ["public", "vip"],
+        "name": "Alice"
+    })
+}
+
+#[test]
+fn test_only_args_paths_can_be_redacted() {
+    for path in ["receiverId", "methodName", "deposit", "gas", "gas.limit"] {
+        let error = parse_redact_paths(&[path.to_string()]).unwrap_err();
+        assert_eq!(
+            error,
+            RedactionError::CriticalField {
+                path: path.to_string()
+            }
+        );
+        assert_eq!(error.code(), SignerErrorCode::CannotRedactCriticalField);
+        assert!(
+            error.to_string().starts_with("CannotRedactCriticalField: "),
+            "{}",
+            error
+        );
+    }
+    for path in ["args", "email", "args..email", "args.email.", ""] {
+        let error = parse_redact_paths(&[path.to_string()]).unwrap_err();
+        assert!(
+            matches!(error, RedactionError::InvalidPath { .. }),
+            "{}: {:?}",
+            path,
+            error
+        );
+        assert_eq!(error.code(), SignerErrorCode::InvalidConfig);
+    }
+    assert_eq!(paths(&["args.email", "args.tags.1"]).len(), 2);
+    assert!(parse_redact_paths(&[]).unwrap().is_empty());
+}
+
+#[test]
+fn test_values_are_replaced_with_their_hash() {
+    let args = profile_args("alice@example.com");
+    let (shown, redactions) = redact_actions(
+        &[call(args.clone())],
+        &paths(&[
+            "args.email",
+            "args.note.text",
+            "args.tags.1",
+            "args.missing",
+        ]),
+    );
+    let email_hash = value_sha256(&json!("alice@example.com"));
+    assert_eq!(email_hash.len(), 64);
+    assert_eq!(
+        shown_args(&shown[0]),
+        json!({
+            "email": redaction_marker(&email_hash),
+            "note": { "text": redaction_marker(&value_sha256(&json!("call after 6pm"))) },
+            "tags": ["public", redaction_marker(&value_sha256(&json!("vip")))],
+            "name": "Alice"
+        })
+    );
+    assert_eq!(
+        redaction_marker(&email_hash),
+        format!("«redacted (sha256: {}…)»", &email_hash[..8])
+    );
+    // Paths without a value are not listed
+    let listed: Vec<&str> = redactions.iter().map(|r| r.path.as_str()).collect();
+    assert_eq!(listed, vec!["args.email", "args.note.text", "args.tags.1"]);
+    assert_eq!(
+        serde_json::to_value(&redactions[0]).unwrap(),
+        json!({ "actionIndex": 0, "path": "args.email", "sha256": email_hash })
+    );
+
+    // A whole object is hashed as its compact JSON
+    let (_, redactions) = redact_actions(&[call(args)], &paths(&["args.note"]));
+    assert_eq!(
+        redactions[0].sha256,
+        value_sha256(&json!({ "text": "call after 6pm" }))
+    );
+}
+
+#[test]
+fn test_other_actions_and_binary_args_are_shown_unchanged() {
+    let transfer = ActionParams::Transfer {
+        deposit: "1".to_string(),
+    };
+    let binary = ActionParams::FunctionCall {
+        method_name: "upload".to_string(),
+        args: "not json".to_string(),
+        gas: "30000000000000".to_string(),
+        deposit: "0".to_string(),
+    };
+    let actions = vec![transfer, binary, call(profile_args("a@b.c"))];
+    let (shown, redactions) = redact_actions(&actions, &paths(&["args.email"]));
+    assert_eq!(shown[..2], actions[..2]);
+    assert_eq!(redactions.len(), 1);
+    assert_eq!(redactions[0].action_index, 2);
+}
+
+#[test]
+fn test_confirmation_shows_redacted_args_and_digest_covers_the_values() {
+    let batch = |email: &str| vec![(RECEIVER.to_string(), vec![call(profile_args(email))])];
+    let policy = SummaryPolicy::default().with_redactions(paths(&["args.email"]));
+    let flags = [Some(false)];
+
+    let txs = confirmation_tx_signing_requests_json(
+        &batch("alice@example.com"),
+        &flags,
+        None,
+        Some(&policy),
+    );
+    let shown = txs[0]["actions"][0]["args"].as_str().unwrap();
+    assert!(!shown.contains("alice@example.com"), "{}", shown);
+    let email_hash = value_sha256(&json!("alice@example.com"));
+    assert_eq!(
+        txs[0]["redactions"],
+        json!([{ "actionIndex": 0, "path": "args.email", "sha256": email_hash }])
+    );
+    let summary = serde_json::to_string(&txs[0]["summaryBlocks"]).unwrap();
+    assert!(!summary.contains("alice@example.com"), "{}", summary);
+    assert!(summary.contains(&email_hash[..8]), "{}", summary);
+
+    // Another true value gives another digest, though both are shown redacted
+    let digest = |email: &str| {
+        compute_confirmation_intent_digest(&batch(email), &flags, None, Some(&policy)).unwrap()
+    };
+    assert_ne!(digest("alice@example.com"), digest("bob@example.com"));
+
+    // Without redactPaths nothing changes
+    let plain = confirmation_tx_signing_requests_json(
+        &batch("alice@example.com"),
+        &flags,
+        None,
+        Some(&SummaryPolicy::default()),
+    );
+    assert!(plain[0].get("redactions").is_none());
+    let blocks = transaction_summary_blocks(
+        RECEIVER,
+        &[call(profile_args("alice@example.com"))],
+        Some(false),
+        &SummaryPolicy::default(),
+    );
+    assert!(blocks.iter().any(|block| matches!(
+        block,
+        ConfirmationSummaryBlock::CodeBlock { json, .. } if json.contains("alice@example.com")
+    )));
+}
+
+#[test]
+fn test_audit_detail_lists_full_hashes() {
+    let batch = vec![
+        (RECEIVER.to_string(), vec![call(json!({ "name": "x" }))]),
+        (RECEIVER.to_string(), vec![call(profile_args("a@b.c"))]),
+    ];
+    let redact = paths(&["args.email"]);
+    assert_eq!(
+        audit_detail(&batch, &redact).unwrap(),
+        format!(
+            "Redacted tx 1 action 0 args.email sha256:{}",
+            value_sha256(&json!("a@b.c"))
+        )
+    );
+    assert_eq!(audit_detail(&batch[..1], &redact), None);
+}
+
+#[test]
+fn test_signing_refuses_critical_field_redaction_before_prompting() {
+    let request: SignTransactionsWithActionsRequest = serde_json::from_value(json!({
+        "rpcCall": {
+            "contractId": "w3a-v1.testnet",
+            "nearRpcUrl": "https://rpc.testnet.near.org",
+            "nearAccountId": "alice.testnet"
+        },
+        "decryption": { "encryptedPrivateKeyData": "AAAA", "encryptedPrivateKeyIv": "BBBB" },
+        "txSigningRequests": [TransactionPayload {
+            near_account_id: "alice.testnet".to_string(),
+            receiver_id: RECEIVER.to_string(),
+            actions: json!([call(profile_args("a@b.c"))]).to_string(),
+            depends_on: Vec::new(),
+        }],
+        "confirmationConfig": null,
+        "redactPaths": ["args.email", "deposit"]
+    }))
+    .unwrap();
+    assert_eq!(request.redact_paths, vec!["args.email", "deposit"]);
+    let result = block_on(handle_sign_transactions_with_actions(request)).unwrap();
+    assert!(!result.success);
+    assert_eq!(
+        result.error_code.as_deref(),
+        Some("CannotRedactCriticalField")
+    );
+    assert_eq!(result.error_category.as_deref(), Some("Policy"));
+    assert_eq!(result.retriable, Some(false));
+}
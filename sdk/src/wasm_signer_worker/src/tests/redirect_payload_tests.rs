@@ -0,0 +1,268 @@
+use crate::clock::{ManualClock, SharedClock};
+use crate::config::{MAX_REDIRECT_URL_CHARS, REDIRECT_PAYLOAD_PARAM};
+use crate::encoders::base64_url_decode;
+use crate::error::{RedirectPayloadError, SignerErrorCode};
+use crate::handlers::handle_build_redirect_payload::{
+    handle_build_redirect_payload, BuildRedirectPayloadRequest, BuildRedirectPayloadResult,
+};
+use crate::handlers::handle_session_keys::{handle_create_session_key, CreateSessionKeyRequest};
+use crate::redirect_payload::{check_callback_url, compose_redirect_url, verify_redirect_payload};
+use crate::state;
+use crate::strict_parsing::check_unknown_fields;
+use crate::tests::{block_on, in_fresh_worker, vrf_challenge};
+use crate::types::handlers::WorkerPolicy;
+use crate::types::worker_messages::WorkerRequestType;
+use serde_json::json;
+
+const ACCOUNT: &str = "alice.testnet";
+const CALLBACK_URL: &str = "https://app.example.com/signed-in";
+
+fn policy(allowed: &[&str]) -> WorkerPolicy {
+    WorkerPolicy {
+        allowed_redirect_origins: allowed.iter().map(|o| o.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+/// A session key for `account`, under a ManualClock at 1_000ms
+fn session_key(account: &str) -> (ManualClock, String) {
+    let clock = ManualClock::new(1_000.0);
+    state::set_clock(SharedClock::new(clock.clone()));
+    let created = block_on(handle_create_session_key(CreateSessionKeyRequest {
+        near_account_id: account.to_string(),
+        receiver_id: "app.testnet".to_string(),
+        method_names: vec![],
+        allowance: "250000000000000000000000".to_string(),
+        ttl_ms: Some(60 * 60 * 1000),
+    }))
+    .unwrap();
+    (clock, created.public_key)
+}
+
+fn build(
+    session_public_key: &str,
+    callback_url: &str,
+    state: &str,
+    ttl_ms: Option<u32>,
+) -> Result<BuildRedirectPayloadResult, String> {
+    block_on(handle_build_redirect_payload(BuildRedirectPayloadRequest {
+        near_account_id: ACCOUNT.to_string(),
+        session_public_key: session_public_key.to_string(),
+        vrf_challenge: vrf_challenge(ACCOUNT),
+        callback_url: callback_url.to_string(),
+        state: state.to_string(),
+        ttl_ms,
+        worker_policy: Some(policy(&["https://app.example.com"])),
+    }))
+}
+
+#[test]
+fn test_redirect_payloads_verify_for_their_callback_and_state_only() {
+    let (clock, public_key) = session_key(ACCOUNT);
+    let built = build(&public_key, CALLBACK_URL, "csrf-123", Some(60_000)).unwrap();
+    assert_eq!(built.expires_at_ms, 61_000.0);
+    assert_eq!(
+        built.url,
+        format!(
+            "{}?{}={}&state=csrf-123",
+            CALLBACK_URL, REDIRECT_PAYLOAD_PARAM, built.token
+        )
+    );
+    assert!(built.url.len() <= MAX_REDIRECT_URL_CHARS);
+
+    let verified =
+        verify_redirect_payload(&built.token, CALLBACK_URL, "csrf-123", 2_000.0).unwrap();
+    assert_eq!(verified.near_account_id, ACCOUNT);
+    assert_eq!(verified.session_public_key, public_key);
+    assert_eq!(verified.vrf_challenge.vrf_output, "BAUG");
+    assert_eq!(verified.vrf_challenge.block_height, "100");
+    assert_eq!(
+        verified.vrf_challenge.block_hash,
+        "11111111111111111111111111111111"
+    );
+    assert_eq!(verified.issued_at_ms, 1_000.0);
+
+    // The token holds no private key material
+    let envelope = base64_url_decode(&built.token).unwrap();
+    let seed = state::with_session_key(&public_key, |k| k.signing_key.to_bytes()).unwrap();
+    assert!(!envelope.windows(seed.len()).any(|w| w == seed));
+
+    let invalid = |result: Result<_, RedirectPayloadError>| {
+        result.unwrap_err().code() == SignerErrorCode::RedirectPayloadInvalid
+    };
+    assert!(invalid(verify_redirect_payload(
+        &built.token,
+        "https://app.example.com/other",
+        "csrf-123",
+        2_000.0
+    )));
+    assert!(invalid(verify_redirect_payload(
+        &built.token,
+        CALLBACK_URL,
+        "csrf-456",
+        2_000.0
+    )));
+    let mut tampered = envelope.clone();
+    let last = tampered.len() - 70;
+    tampered[last] ^= 1;
+    assert!(invalid(verify_redirect_payload(
+        &crate::encoders::base64_url_encode(&tampered),
+        CALLBACK_URL,
+        "csrf-123",
+        2_000.0
+    )));
+    assert!(invalid(verify_redirect_payload(
+        "not a token",
+        CALLBACK_URL,
+        "csrf-123",
+        2_000.0
+    )));
+
+    clock.advance(60_000.0);
+    assert_eq!(
+        verify_redirect_payload(&built.token, CALLBACK_URL, "csrf-123", state::now_ms())
+            .unwrap_err(),
+        RedirectPayloadError::Expired {
+            expires_at_ms: 61_000.0
+        }
+    );
+}
+
+#[test]
+fn test_open_redirect_callbacks_are_refused() {
+    let allowed = policy(&["https://app.example.com"]);
+    let scheme = |url: &str| match check_callback_url(url, Some(&allowed)) {
+        Err(RedirectPayloadError::SchemeNotAllowed { scheme }) => Some(scheme),
+        _ => None,
+    };
+    assert_eq!(
+        scheme("javascript:alert(document.cookie)").unwrap(),
+        "javascript"
+    );
+    assert_eq!(scheme("JavaScript:alert(1)").unwrap(), "javascript");
+    assert_eq!(
+        scheme("data:text/html,<script>alert(1)</script>").unwrap(),
+        "data"
+    );
+    assert_eq!(scheme("http://app.example.com/signed-in").unwrap(), "http");
+    assert_eq!(scheme("//evil.example/signed-in").unwrap(), "");
+    assert_eq!(scheme(" https://app.example.com").unwrap(), "");
+
+    for url in [
+        "https://evil.example/signed-in",
+        "https://app.example.com.evil.example/",
+        "https://app.example.com@evil.example/",
+        "https://evil.example\\@app.example.com/",
+        "https://app.example.com:8443/",
+        "https://app.example.com/signed in",
+        "https:///signed-in",
+    ] {
+        assert_eq!(
+            check_callback_url(url, Some(&allowed)).unwrap_err().code(),
+            SignerErrorCode::RedirectOriginNotAllowed,
+            "{}",
+            url
+        );
+    }
+    assert_eq!(
+        check_callback_url("HTTPS://App.Example.com:443/x?y=1", Some(&allowed)).unwrap(),
+        "https://app.example.com"
+    );
+    // Without a policy, or with an empty list, nothing is allowed
+    assert!(check_callback_url(CALLBACK_URL, None).is_err());
+    assert!(check_callback_url(CALLBACK_URL, Some(&policy(&[]))).is_err());
+
+    let (_clock, public_key) = session_key(ACCOUNT);
+    let err = build(&public_key, "javascript:alert(1)", "csrf-123", None).unwrap_err();
+    assert!(err.starts_with("RedirectSchemeNotAllowed: "), "{}", err);
+    let err = build(&public_key, "https://evil.example/", "csrf-123", None).unwrap_err();
+    assert!(err.starts_with("RedirectOriginNotAllowed: "), "{}", err);
+}
+
+#[test]
+fn test_session_key_state_and_lifetime_are_checked() {
+    let (_clock, public_key) = session_key("bob.testnet");
+    let err = build(&public_key, CALLBACK_URL, "csrf-123", None).unwrap_err();
+    assert!(err.starts_with("SessionKeyPermissionDenied: "), "{}", err);
+    let err = build(
+        "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp",
+        CALLBACK_URL,
+        "csrf-123",
+        None,
+    )
+    .unwrap_err();
+    assert!(err.starts_with("SessionKeyNotFound: "), "{}", err);
+
+    let (_clock, public_key) = session_key(ACCOUNT);
+    assert!(build(&public_key, CALLBACK_URL, "csrf-123", Some(0)).is_err());
+    assert!(build(&public_key, CALLBACK_URL, "csrf-123", Some(60 * 60 * 1000)).is_err());
+    assert!(build(&public_key, CALLBACK_URL, "", None).is_err());
+    assert!(build(&public_key, CALLBACK_URL, "a&b=c", None).is_err());
+    assert!(build(&public_key, CALLBACK_URL, &"s".repeat(129), None).is_err());
+    let long_callback = format!("{}?q={}", CALLBACK_URL, "x".repeat(MAX_REDIRECT_URL_CHARS));
+    let err = build(&public_key, &long_callback, "csrf-123", None).unwrap_err();
+    assert!(err.contains("character budget"), "{}", err);
+
+    // Default lifetime, within the session key's
+    let built = build(&public_key, CALLBACK_URL, "csrf-123", None).unwrap();
+    assert_eq!(built.expires_at_ms, 1_000.0 + 5.0 * 60.0 * 1000.0);
+}
+
+#[test]
+fn test_session_key_from_an_earlier_worker_signs_the_payload() {
+    // CreateSessionKey runs in one worker, BuildRedirectPayload in the next
+    let (public_key, record) = in_fresh_worker(None, || {
+        let (_clock, public_key) = session_key(ACCOUNT);
+        (public_key, crate::worker_state::take_changed_record())
+    });
+    assert!(record.is_some());
+    let key = public_key.clone();
+    let built = in_fresh_worker(record, move || {
+        state::set_clock(SharedClock::new(ManualClock::new(2_000.0)));
+        build(&key, CALLBACK_URL, "csrf-123", None)
+    })
+    .unwrap();
+    assert_eq!(built.session_public_key, public_key);
+    verify_redirect_payload(&built.token, CALLBACK_URL, "csrf-123", 3_000.0).unwrap();
+
+    // Without the record the key is unknown
+    let key = public_key.clone();
+    let err = in_fresh_worker(None, move || {
+        state::set_clock(SharedClock::new(ManualClock::new(2_000.0)));
+        build(&key, CALLBACK_URL, "csrf-123", None)
+    })
+    .unwrap_err();
+    assert!(err.starts_with("SessionKeyNotFound: "), "{}", err);
+}
+
+#[test]
+fn test_redirect_urls_keep_existing_queries_and_fragments() {
+    assert_eq!(
+        compose_redirect_url("https://app.example.com/cb?lang=en#done", "TOKEN", "s1"),
+        "https://app.example.com/cb?lang=en&w3a_payload=TOKEN&state=s1#done"
+    );
+    assert_eq!(
+        compose_redirect_url("https://app.example.com/cb?", "TOKEN", "s1"),
+        "https://app.example.com/cb?w3a_payload=TOKEN&state=s1"
+    );
+
+    let payload = json!({
+        "nearAccountId": ACCOUNT,
+        "sessionPublicKey": "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp",
+        "vrfChallenge": {},
+        "callbackUrl": CALLBACK_URL,
+        "state": "csrf-123",
+        "workerPolicy": {
+            "strictParsing": true,
+            "allowedRedirectOrigins": ["https://app.example.com"],
+            "accountOverrides": { "alice.testnet": { "allowedRedirectOrigins": [] } }
+        }
+    });
+    assert_eq!(
+        check_unknown_fields(WorkerRequestType::BuildRedirectPayload, &payload),
+        Ok(())
+    );
+    let mut misspelled = payload.clone();
+    misspelled["workerPolicy"]["allowedRedirectOrigin"] = json!(["https://evil.example"]);
+    assert!(check_unknown_fields(WorkerRequestType::BuildRedirectPayload, &misspelled).is_err());
+}
@@ -0,0 +1,162 @@
+use crate::config::STORAGE_PRICE_PER_BYTE_YOCTO;
+use crate::encoders::base64_url_encode;
+use crate::handlers::handle_check_can_register_user::*;
+use crate::rpc_calls::parse_view_account_response;
+use crate::tests::block_on;
+use crate::types::handlers::OriginPolicyInput;
+use serde_json::json;
+
+fn client_data_b64u(ceremony_type: &str, challenge: &[u8], origin: &str) -> String {
+    let client_data = json!({
+        "type": ceremony_type,
+        "challenge": base64_url_encode(challenge),
+        "origin": origin,
+    });
+    base64_url_encode(client_data.to_string().as_bytes())
+}
+
+fn policy(
+    single: Option<bool>,
+    all_subdomains: Option<bool>,
+    multiple: Option<Vec<&str>>,
+) -> OriginPolicyInput {
+    OriginPolicyInput {
+        single,
+        all_subdomains,
+        multiple: multiple.map(|m| m.into_iter().map(|s| s.to_string()).collect()),
+    }
+}
+
+#[test]
+fn test_challenge_binding() {
+    let vrf_output = [7u8; 64];
+    let vrf_output_b64u = base64_url_encode(&vrf_output);
+
+    let bound = client_data_b64u(
+        "webauthn.create",
+        &vrf_output[..32],
+        "https://wallet.example.com",
+    );
+    assert_eq!(
+        check_registration_challenge_binding(&bound, &vrf_output_b64u).unwrap(),
+        "https://wallet.example.com"
+    );
+
+    let wrong_challenge = client_data_b64u("webauthn.create", &[8u8; 32], "https://example.com");
+    assert!(check_registration_challenge_binding(&wrong_challenge, &vrf_output_b64u).is_err());
+
+    let wrong_ceremony = client_data_b64u("webauthn.get", &vrf_output[..32], "https://example.com");
+    assert!(check_registration_challenge_binding(&wrong_ceremony, &vrf_output_b64u).is_err());
+}
+
+#[test]
+fn test_origin_policy() {
+    // Default: rpId and its subdomains
+    assert!(origin_allowed("https://example.com", "example.com", None));
+    assert!(origin_allowed(
+        "https://wallet.example.com:8443",
+        "example.com",
+        None
+    ));
+    assert!(!origin_allowed(
+        "https://badexample.com",
+        "example.com",
+        None
+    ));
+
+    let single = policy(Some(true), None, None);
+    assert!(origin_allowed(
+        "https://example.com",
+        "example.com",
+        Some(&single)
+    ));
+    assert!(!origin_allowed(
+        "https://wallet.example.com",
+        "example.com",
+        Some(&single)
+    ));
+
+    let multiple = policy(None, None, Some(vec!["https://app.other.org"]));
+    assert!(origin_allowed(
+        "https://app.other.org",
+        "example.com",
+        Some(&multiple)
+    ));
+    assert!(!origin_allowed(
+        "https://wallet.example.com",
+        "example.com",
+        Some(&multiple)
+    ));
+}
+
+#[test]
+fn test_storage_cost_estimate() {
+    let base = estimate_registration_storage_cost(0, 0, 0);
+    assert_eq!(
+        estimate_registration_storage_cost(16, 77, 1) - base,
+        (16 + 77 + 32) * STORAGE_PRICE_PER_BYTE_YOCTO
+    );
+}
+
+#[test]
+fn test_parse_view_account_response() {
+    let exists =
+        json!({ "jsonrpc": "2.0", "id": "1", "result": { "amount": "1", "block_height": 1 } });
+    assert!(parse_view_account_response(&exists).unwrap());
+
+    let unknown = json!({
+        "jsonrpc": "2.0",
+        "id": "1",
+        "error": { "name": "HANDLER_ERROR", "cause": { "name": "UNKNOWN_ACCOUNT" }, "message": "Server error" }
+    });
+    assert!(!parse_view_account_response(&unknown).unwrap());
+
+    let timeout =
+        json!({ "error": { "name": "HANDLER_ERROR", "cause": { "name": "TIMEOUT_ERROR" } } });
+    assert!(parse_view_account_response(&timeout).is_err());
+}
+
+#[test]
+fn test_dry_run_reports_first_failure_without_erroring() {
+    let vrf_output = base64_url_encode(&[7u8; 64]);
+    let request: CheckCanRegisterUserRequest = serde_json::from_value(json!({
+        "vrfChallenge": {
+            "vrfInput": base64_url_encode(&[1u8; 32]),
+            "vrfOutput": vrf_output,
+            "vrfProof": base64_url_encode(&[2u8; 80]),
+            "vrfPublicKey": base64_url_encode(&[3u8; 32]),
+            "userId": "alice.testnet",
+            "rpId": "example.com",
+            "blockHeight": "100",
+            "blockHash": "11111111111111111111111111111111"
+        },
+        "credential": {
+            "id": "cred",
+            "rawId": "cred",
+            "type": "public-key",
+            "authenticatorAttachment": null,
+            "response": {
+                "clientDataJSON": client_data_b64u("webauthn.create", &[7u8; 32], "https://example.com"),
+                "attestationObject": base64_url_encode(b"not cbor"),
+                "transports": []
+            },
+            "clientExtensionResults": { "prf": { "results": { "first": null, "second": null } } }
+        },
+        "contractId": "w3a-v1.testnet",
+        "nearRpcUrl": "https://rpc.testnet.near.org",
+        "dryRun": true
+    }))
+    .unwrap();
+
+    let result = block_on(handle_check_can_register_user(request)).unwrap();
+    let report = result.dry_run.expect("dry run report");
+    assert!(!result.verified);
+    assert!(!report.would_succeed);
+    assert_eq!(
+        report.outcome,
+        RegistrationDryRunOutcome::InvalidAttestation.as_str()
+    );
+    assert!(report.detail.is_some());
+    assert!(report.contract_args.is_none());
+    assert!(result.signed_transaction.is_none());
+}
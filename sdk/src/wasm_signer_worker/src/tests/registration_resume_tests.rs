@@ -0,0 +1,201 @@
+use crate::bench::bench_prf_outputs;
+use crate::config::REGISTRATION_RESUME_TTL_MS;
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::{RegistrationResumeError, SignerErrorCode};
+use crate::handlers::handle_resumable_registration::{
+    handle_prepare_registration, resumed_relayer_request, CompleteRegistrationRequest,
+    PrepareRegistrationRequest, PrepareRegistrationResult,
+};
+use crate::handlers::handle_submit_to_relayer::relayer_request_body;
+use crate::registration_resume::*;
+use crate::strict_parsing::check_unknown_fields;
+use crate::tests::block_on;
+use crate::types::worker_messages::WorkerRequestType;
+use serde_json::{json, Value};
+
+const ACCOUNT: &str = "alice.testnet";
+
+fn prf_outputs_json() -> Value {
+    let prf = bench_prf_outputs();
+    json!({
+        "chacha20PrfOutput": prf.chacha20_prf_output_base64,
+        "ed25519PrfOutput": prf.ed25519_prf_output_base64,
+    })
+}
+
+fn credential(raw_id: &str) -> Value {
+    json!({
+        "id": raw_id,
+        "rawId": raw_id,
+        "type": "public-key",
+        "authenticatorAttachment": null,
+        "response": {
+            "clientDataJSON": "e30",
+            "attestationObject": "o2Nm",
+            "transports": ["internal"]
+        }
+    })
+}
+
+fn prepare() -> PrepareRegistrationResult {
+    let request: PrepareRegistrationRequest = serde_json::from_value(json!({
+        "dualPrfOutputs": prf_outputs_json(),
+        "nearAccountId": ACCOUNT,
+        "credential": credential("Y3JlZA"),
+    }))
+    .unwrap();
+    block_on(handle_prepare_registration(request)).unwrap()
+}
+
+fn complete_request(resume_token: &str) -> Value {
+    json!({
+        "resumeToken": resume_token,
+        "dualPrfOutputs": prf_outputs_json(),
+        "nearAccountId": ACCOUNT,
+        "deviceNumber": 1,
+        "vrfChallenge": {
+            "vrfInput": "AQID",
+            "vrfOutput": "BAUG",
+            "vrfProof": "BwgJ",
+            "vrfPublicKey": "CgsM",
+            "userId": ACCOUNT,
+            "rpId": "example.com",
+            "blockHeight": "100",
+            "blockHash": "DQ4P"
+        },
+        "credential": credential("Y3JlZA"),
+        "deterministicVrfPublicKey": "CQk",
+        "workerPolicy": { "relayer": { "url": "https://relay.example.com" } }
+    })
+}
+
+fn resume(request: &Value, now_ms: f64) -> Result<String, RegistrationResumeError> {
+    let request: CompleteRegistrationRequest = serde_json::from_value(request.clone()).unwrap();
+    resumed_relayer_request(&request, now_ms).map(|submission| submission.new_public_key)
+}
+
+#[test]
+fn test_prepare_returns_keys_and_a_token_for_them() {
+    let prepared = prepare();
+    assert_eq!(prepared.keys.near_account_id, ACCOUNT);
+    assert!(prepared.keys.public_key.starts_with("ed25519:"));
+    assert_eq!(prepared.keys.key_wrapping, "prf");
+
+    let key = resume_mac_key(&bench_prf_outputs().chacha20_prf_output_base64).unwrap();
+    let now_ms = prepared.resume_expires_at_ms - REGISTRATION_RESUME_TTL_MS as f64;
+    let state =
+        verify_resume_token(&prepared.resume_token, &key, ACCOUNT, "Y3JlZA", now_ms).unwrap();
+    assert_eq!(state.public_key, prepared.keys.public_key);
+    assert_eq!(state.derivation_version, prepared.keys.version);
+    assert_eq!(state.expires_at_ms, prepared.resume_expires_at_ms);
+    // The token carries no PRF output
+    let token =
+        String::from_utf8_lossy(&base64_url_decode(&prepared.resume_token).unwrap()).to_string();
+    assert!(!token.contains(&bench_prf_outputs().ed25519_prf_output_base64));
+}
+
+#[test]
+fn test_retried_completion_submits_the_prepared_key() {
+    let prepared = prepare();
+    let now_ms = prepared.resume_expires_at_ms - 1.0;
+    let request = complete_request(&prepared.resume_token);
+
+    let first = resume(&request, now_ms).unwrap();
+    let retry = resume(&request, now_ms).unwrap();
+    assert_eq!(first, prepared.keys.public_key);
+    assert_eq!(retry, prepared.keys.public_key);
+
+    let parsed: CompleteRegistrationRequest = serde_json::from_value(request).unwrap();
+    let submission = resumed_relayer_request(&parsed, now_ms).unwrap();
+    let body = relayer_request_body(&submission).unwrap();
+    assert_eq!(body["new_account_id"], ACCOUNT);
+    assert_eq!(body["new_public_key"], json!(prepared.keys.public_key));
+}
+
+#[test]
+fn test_completion_refuses_other_accounts_passkeys_and_keys() {
+    let prepared = prepare();
+    let now_ms = prepared.resume_expires_at_ms - 1.0;
+    let request = complete_request(&prepared.resume_token);
+
+    let mut other_account = request.clone();
+    other_account["nearAccountId"] = json!("bob.testnet");
+    let err = resume(&other_account, now_ms).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::RegistrationResumeMismatch);
+    assert_eq!(
+        err.to_string(),
+        "RegistrationResumeMismatch: token was issued for alice.testnet, not bob.testnet"
+    );
+
+    let mut other_credential = request.clone();
+    other_credential["credential"] = credential("b3RoZXI");
+    let err = resume(&other_credential, now_ms).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::RegistrationResumeMismatch);
+
+    // Another passkey's PRF output cannot verify the token
+    let mut other_passkey = request.clone();
+    other_passkey["dualPrfOutputs"]["chacha20PrfOutput"] = json!(base64_url_encode(&[1u8; 32]));
+    let err = resume(&other_passkey, now_ms).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::RegistrationResumeInvalid);
+
+    // The same token with PRF output deriving another key is refused
+    let mut other_key = request;
+    other_key["dualPrfOutputs"]["ed25519PrfOutput"] = json!(base64_url_encode(&[2u8; 32]));
+    let err = resume(&other_key, now_ms).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::RegistrationResumeMismatch);
+}
+
+#[test]
+fn test_tokens_expire_and_detect_tampering() {
+    let prepared = prepare();
+    let request = complete_request(&prepared.resume_token);
+    let err = resume(&request, prepared.resume_expires_at_ms).unwrap_err();
+    assert_eq!(
+        err,
+        RegistrationResumeError::Expired {
+            expires_at_ms: prepared.resume_expires_at_ms
+        }
+    );
+    assert_eq!(err.code(), SignerErrorCode::RegistrationResumeExpired);
+
+    // A token claiming a longer lifetime is still capped at 10 minutes
+    let key = resume_mac_key(&bench_prf_outputs().chacha20_prf_output_base64).unwrap();
+    let mut state = RegistrationResumeState::new(ACCOUNT, "Y3JlZA", 1, "ed25519:x", 1_000.0);
+    state.expires_at_ms = 1_000.0 + 2.0 * REGISTRATION_RESUME_TTL_MS as f64;
+    let token = issue_resume_token(&state, &key).unwrap();
+    assert!(verify_resume_token(&token, &key, ACCOUNT, "Y3JlZA", 1_000.0).is_ok());
+    assert!(matches!(
+        verify_resume_token(
+            &token,
+            &key,
+            ACCOUNT,
+            "Y3JlZA",
+            1_000.0 + REGISTRATION_RESUME_TTL_MS as f64
+        ),
+        Err(RegistrationResumeError::Expired { .. })
+    ));
+
+    let mut tampered = base64_url_decode(&prepared.resume_token).unwrap();
+    let last = tampered.len() - 1;
+    tampered[last] ^= 1;
+    let mut request = request;
+    request["resumeToken"] = json!(base64_url_encode(&tampered));
+    let err = resume(&request, prepared.resume_expires_at_ms - 1.0).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::RegistrationResumeInvalid);
+    request["resumeToken"] = json!("not a token");
+    let err = resume(&request, prepared.resume_expires_at_ms - 1.0).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::RegistrationResumeInvalid);
+}
+
+#[test]
+fn test_strict_parsing_covers_completion() {
+    let mut request = complete_request("AAAA");
+    request["workerPolicy"]["strictParsing"] = json!(true);
+    assert_eq!(
+        check_unknown_fields(WorkerRequestType::CompleteRegistration, &request),
+        Ok(())
+    );
+    request["resumeTokn"] = json!("AAAA");
+    let err = check_unknown_fields(WorkerRequestType::CompleteRegistration, &request).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::UnknownField);
+}
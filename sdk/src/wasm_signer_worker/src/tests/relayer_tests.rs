@@ -0,0 +1,343 @@
+use std::cell::RefCell;
+use std::future::ready;
+
+use crate::encoders::base64_url_encode;
+use crate::error::SignerErrorCode;
+use crate::handlers::handle_submit_to_relayer::relayer_request_body;
+use crate::handlers::SubmitToRelayerRequest;
+use crate::relayer::{
+    parse_relayer_response, pinned_relayer, retry_delay_ms, submit_to_relayer, RelayerPhase,
+    RelayerResult,
+};
+use crate::rpc_calls::HttpJsonResponse;
+use crate::strict_parsing::check_unknown_fields;
+use crate::tests::block_on;
+use crate::types::handlers::{RelayerPolicy, WorkerPolicy};
+use crate::types::worker_messages::WorkerRequestType;
+use serde_json::{json, Value};
+
+const TX_HASH: &str = "11111111111111111111111111111111";
+
+fn response(status: u16, body: Value) -> HttpJsonResponse {
+    HttpJsonResponse {
+        status,
+        body: Some(body),
+    }
+}
+
+fn error_kind(result: &RelayerResult) -> &str {
+    result.error_kind.as_deref().unwrap_or("none")
+}
+
+fn relayer(auth_header_name: Option<&str>) -> RelayerPolicy {
+    RelayerPolicy {
+        url: "https://relay.example.com/".to_string(),
+        auth_header_name: auth_header_name.map(str::to_string),
+        response_schema_version: None,
+    }
+}
+
+type Call = (&'static str, String, Vec<(String, String)>);
+
+/// Submits through a fake transport answering GETs and POSTs from the given closures
+fn submit(
+    relayer: &RelayerPolicy,
+    preflight: impl Fn() -> Result<HttpJsonResponse, String>,
+    submission: impl Fn() -> Result<HttpJsonResponse, String>,
+) -> (RelayerResult, Vec<Call>, Vec<u32>) {
+    let calls = RefCell::new(Vec::new());
+    let sleeps = RefCell::new(Vec::new());
+    let result = block_on(submit_to_relayer(
+        relayer,
+        Some("secret"),
+        json!({}),
+        |method, url, _body, headers| {
+            calls.borrow_mut().push((method, url, headers));
+            ready(if method == "GET" {
+                preflight()
+            } else {
+                submission()
+            })
+        },
+        |ms| {
+            sleeps.borrow_mut().push(ms);
+            ready(())
+        },
+    ));
+    (result, calls.into_inner(), sleeps.into_inner())
+}
+
+#[test]
+fn test_relayer_responses_map_to_typed_results() {
+    let created = parse_relayer_response(
+        &response(200, json!({ "success": true, "transactionHash": TX_HASH })),
+        1,
+    );
+    assert_eq!(created, RelayerResult::created(TX_HASH.to_string()));
+    let created = parse_relayer_response(
+        &response(200, json!({ "success": true, "txHash": TX_HASH })),
+        1,
+    );
+    assert!(created.account_created);
+
+    let cases = [
+        (409, json!({ "success": false }), "RelayerAccountExists"),
+        (
+            400,
+            json!({ "success": false, "error": "Account alice.testnet already exists" }),
+            "RelayerAccountExists",
+        ),
+        (429, json!({ "success": false }), "RelayerQuotaExceeded"),
+        (
+            403,
+            json!({ "success": false, "message": "Daily quota exhausted" }),
+            "RelayerQuotaExceeded",
+        ),
+        (
+            400,
+            json!({ "success": false, "errorKind": "quotaExceeded", "error": "try tomorrow" }),
+            "RelayerQuotaExceeded",
+        ),
+        (
+            400,
+            json!({ "success": false, "error": "invalid attestation" }),
+            "RelayerRejected",
+        ),
+        (
+            200,
+            json!({ "success": false, "error": "contract call failed" }),
+            "RelayerRejected",
+        ),
+        (503, json!({ "success": false }), "RelayerUnavailable"),
+        // Malformed successes
+        (200, json!({ "success": true }), "RelayerResponseInvalid"),
+        (
+            200,
+            json!({ "success": true, "transactionHash": "not-a-hash" }),
+            "RelayerResponseInvalid",
+        ),
+        (200, json!({ "ok": true }), "RelayerResponseInvalid"),
+        (200, json!([true]), "RelayerResponseInvalid"),
+    ];
+    for (status, body, expected) in cases {
+        let result = parse_relayer_response(&response(status, body.clone()), 1);
+        assert!(!result.success && !result.account_created);
+        assert_eq!(error_kind(&result), expected, "{} {}", status, body);
+        assert!(result.error.unwrap().starts_with(expected));
+    }
+
+    // Bodies that are not JSON
+    let html = |status| HttpJsonResponse { status, body: None };
+    assert_eq!(
+        error_kind(&parse_relayer_response(&html(200), 1)),
+        "RelayerResponseInvalid"
+    );
+    assert_eq!(
+        error_kind(&parse_relayer_response(&html(502), 1)),
+        "RelayerUnavailable"
+    );
+    assert_eq!(
+        parse_relayer_response(&html(404), 1).error.as_deref(),
+        Some("RelayerRejected: HTTP 404")
+    );
+}
+
+#[test]
+fn test_response_schema_version_is_checked() {
+    let success = |version: Option<u32>| {
+        let mut body = json!({ "success": true, "transactionHash": TX_HASH });
+        if let Some(version) = version {
+            body["schemaVersion"] = json!(version);
+        }
+        response(200, body)
+    };
+    // Version 1 responses may omit schemaVersion
+    assert!(parse_relayer_response(&success(None), 1).success);
+    assert!(parse_relayer_response(&success(Some(1)), 1).success);
+    assert!(parse_relayer_response(&success(Some(2)), 2).success);
+
+    for (version, expected) in [(Some(2), 1), (Some(1), 2), (None, 2)] {
+        let result = parse_relayer_response(&success(version), expected);
+        assert_eq!(error_kind(&result), "RelayerResponseInvalid");
+    }
+    // A failure in an unexpected schema is not trusted either
+    let result = parse_relayer_response(
+        &response(409, json!({ "schemaVersion": 3, "success": false })),
+        1,
+    );
+    assert_eq!(error_kind(&result), "RelayerResponseInvalid");
+}
+
+#[test]
+fn test_only_the_preflight_is_retried() {
+    assert!(RelayerPhase::Preflight.is_idempotent());
+    assert!(!RelayerPhase::Submit.is_idempotent());
+    assert_eq!(RelayerPhase::Submit.max_attempts(), 1);
+    assert_eq!([1, 2, 3].map(retry_delay_ms), [500, 1000, 2000]);
+
+    let created = || Ok(response(200, json!({ "success": true, "txHash": TX_HASH })));
+
+    // An unreachable relayer: three probes with backoff, nothing submitted
+    let (result, calls, sleeps) = submit(&relayer(None), || Err("offline".to_string()), created);
+    assert_eq!(error_kind(&result), "RelayerUnavailable");
+    assert!(result.error.unwrap().contains("nothing was submitted"));
+    assert_eq!(calls.len(), 3);
+    assert!(calls
+        .iter()
+        .all(|(method, url, _)| *method == "GET" && url == "https://relay.example.com/healthz"));
+    assert_eq!(sleeps, vec![500, 1000]);
+
+    // A flaky preflight recovers; any non-5xx answer counts as reachable
+    let probes = RefCell::new(0);
+    let (result, calls, sleeps) = submit(
+        &relayer(None),
+        || {
+            *probes.borrow_mut() += 1;
+            Ok(response(
+                if *probes.borrow() == 1 { 502 } else { 404 },
+                json!({}),
+            ))
+        },
+        created,
+    );
+    assert!(result.success);
+    assert_eq!(calls.len(), 3);
+    assert_eq!(
+        calls[2].1,
+        "https://relay.example.com/create_account_and_register_user"
+    );
+    assert_eq!(sleeps, vec![500]);
+
+    // A failed submission is never repeated
+    let (result, calls, _) = submit(
+        &relayer(None),
+        || Ok(response(200, json!({}))),
+        || Ok(response(503, json!({ "success": false }))),
+    );
+    assert_eq!(error_kind(&result), "RelayerUnavailable");
+    assert_eq!(calls.iter().filter(|(m, ..)| *m == "POST").count(), 1);
+
+    let (result, calls, _) = submit(
+        &relayer(None),
+        || Ok(response(200, json!({}))),
+        || Err("connection reset".to_string()),
+    );
+    assert!(result.error.unwrap().contains("may have been created"));
+    assert_eq!(calls.iter().filter(|(m, ..)| *m == "POST").count(), 1);
+}
+
+#[test]
+fn test_auth_token_goes_in_the_configured_header() {
+    let ok = || Ok(response(200, json!({ "success": true, "txHash": TX_HASH })));
+    let (_, calls, _) = submit(&relayer(Some("X-Relayer-Key")), ok, ok);
+    assert!(calls[0].2.is_empty());
+    assert_eq!(
+        calls[1].2,
+        vec![("X-Relayer-Key".to_string(), "secret".to_string())]
+    );
+
+    let (_, calls, _) = submit(&relayer(None), ok, ok);
+    assert!(calls[1].2.is_empty());
+}
+
+#[test]
+fn test_relayer_url_is_origin_pinned() {
+    let policy = |url: &str, allowed: &[&str]| WorkerPolicy {
+        allowed_rpc_origins: allowed.iter().map(|o| o.to_string()).collect(),
+        relayer: Some(RelayerPolicy {
+            url: url.to_string(),
+            ..RelayerPolicy::default()
+        }),
+        ..WorkerPolicy::default()
+    };
+
+    let allowed = policy(
+        "https://relay.example.com/v1",
+        &["https://relay.example.com"],
+    );
+    assert_eq!(
+        pinned_relayer(Some(&allowed)).unwrap().url,
+        "https://relay.example.com/v1"
+    );
+
+    for refused in [
+        policy("https://evil.example", &["https://relay.example.com"]),
+        policy(
+            "https://relay.example.com@evil.example",
+            &["https://relay.example.com"],
+        ),
+        policy("https://relay.example.com", &[]),
+    ] {
+        let err = pinned_relayer(Some(&refused)).unwrap_err();
+        assert!(
+            err.starts_with("RpcOriginNotAllowed: relayer endpoint"),
+            "{}",
+            err
+        );
+    }
+    assert!(pinned_relayer(None).is_err());
+    assert!(pinned_relayer(Some(&WorkerPolicy::default())).is_err());
+}
+
+#[test]
+fn test_request_body_carries_registration_args() {
+    let mut request = json!({
+        "nearAccountId": "alice.testnet",
+        "newPublicKey": "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp",
+        "deviceNumber": 2,
+        "vrfChallenge": {
+            "vrfInput": "AQID",
+            "vrfOutput": "BAUG",
+            "vrfProof": "BwgJ",
+            "vrfPublicKey": "CgsM",
+            "userId": "alice.testnet",
+            "rpId": "example.com",
+            "blockHeight": "100",
+            "blockHash": "DQ4P"
+        },
+        "credential": {
+            "id": "cred",
+            "rawId": "Y3JlZA",
+            "type": "public-key",
+            "authenticatorAttachment": null,
+            "response": {
+                "clientDataJSON": "e30",
+                "attestationObject": "o2Nm",
+                "transports": ["internal"]
+            },
+            "clientExtensionResults": { "prf": { "results": { "first": "secret-prf", "second": null } } }
+        },
+        "deterministicVrfPublicKey": base64_url_encode(&[9, 9]),
+        "authToken": "secret",
+        "workerPolicy": { "relayer": { "url": "https://relay.example.com" } }
+    });
+    let parsed: SubmitToRelayerRequest = serde_json::from_value(request.clone()).unwrap();
+    let body = relayer_request_body(&parsed).unwrap();
+
+    assert_eq!(body["new_account_id"], "alice.testnet");
+    assert_eq!(body["new_public_key"], request["newPublicKey"]);
+    assert_eq!(body["device_number"], 2);
+    assert_eq!(body["deterministic_vrf_public_key"], json!([9, 9]));
+    assert_eq!(body["vrf_data"]["user_id"], "alice.testnet");
+    assert_eq!(body["webauthn_registration"]["rawId"], "Y3JlZA");
+    assert!(body.get("signed_delegate_action").is_none());
+    // Only the worker sees PRF outputs
+    assert!(!body.to_string().contains("secret-prf"));
+
+    request["signedDelegateAction"] = json!("AQID");
+    let parsed: SubmitToRelayerRequest = serde_json::from_value(request.clone()).unwrap();
+    assert_eq!(
+        relayer_request_body(&parsed).unwrap()["signed_delegate_action"],
+        "AQID"
+    );
+
+    // Strict parsing covers the relayer configuration
+    request["workerPolicy"]["strictParsing"] = json!(true);
+    assert_eq!(
+        check_unknown_fields(WorkerRequestType::SubmitToRelayer, &request),
+        Ok(())
+    );
+    request["workerPolicy"]["relayer"]["authHeadr"] = json!("X-Key");
+    let err = check_unknown_fields(WorkerRequestType::SubmitToRelayer, &request).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::UnknownField);
+}
@@ -0,0 +1,386 @@
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::SignerErrorCode;
+use crate::handlers::confirm_tx_details::ConfirmationResult;
+use crate::handlers::handle_remote_confirmation::{
+    accept_remote_approval, handle_approve_remote_confirmation,
+    handle_complete_remote_confirmation, handle_create_remote_confirmation,
+    remote_approval_from_confirmation, CreateRemoteConfirmationResult,
+};
+use crate::handlers::{
+    ApproveRemoteConfirmationRequest, CompleteRemoteConfirmationRequest,
+    CreateRemoteConfirmationRequest,
+};
+use crate::remote_confirmation::*;
+use crate::state;
+use crate::strict_parsing::check_unknown_fields;
+use crate::tests::{block_on, in_fresh_worker, vrf_challenge};
+use crate::types::worker_messages::WorkerRequestType;
+use crate::types::VrfChallenge;
+use crate::worker_state;
+use serde_json::{json, Value};
+
+const ACCOUNT: &str = "alice.testnet";
+
+fn rpc_call(near_account_id: &str) -> Value {
+    json!({
+        "contractId": "w3a-v1.testnet",
+        "nearRpcUrl": "https://rpc.testnet.near.org",
+        "nearAccountId": near_account_id,
+    })
+}
+
+fn transactions(deposit: &str) -> Value {
+    json!([{
+        "nearAccountId": ACCOUNT,
+        "receiverId": "bob.testnet",
+        "actions": json!([{ "action_type": "Transfer", "deposit": deposit }]).to_string(),
+    }])
+}
+
+fn create(ttl_ms: Option<u32>) -> Result<CreateRemoteConfirmationResult, String> {
+    let request: CreateRemoteConfirmationRequest = serde_json::from_value(json!({
+        "rpcCall": rpc_call(ACCOUNT),
+        "txSigningRequests": transactions("1"),
+        "vrfChallenge": vrf_challenge(ACCOUNT),
+        "ttlMs": ttl_ms,
+    }))
+    .unwrap();
+    block_on(handle_create_remote_confirmation(request))
+}
+
+fn complete_request(approval: &str, deposit: &str) -> CompleteRemoteConfirmationRequest {
+    serde_json::from_value(json!({
+        "approval": approval,
+        "rpcCall": rpc_call(ACCOUNT),
+        "decryption": { "encryptedPrivateKeyData": "AAAA", "encryptedPrivateKeyIv": "BBBB" },
+        "txSigningRequests": transactions(deposit),
+        "transactionContext": {
+            "nearPublicKeyStr": "ed25519:11111111111111111111111111111111",
+            "nextNonce": "1",
+            "txBlockHeight": "100",
+            "txBlockHash": "11111111111111111111111111111111"
+        },
+    }))
+    .unwrap()
+}
+
+/// What the approving device's confirmation UI returns for `request`
+fn confirmed(request: &RemoteConfirmationRequest) -> ConfirmationResult {
+    ConfirmationResult {
+        confirmed: true,
+        request_id: "ui-1".to_string(),
+        intent_digest: Some(request.intent_digest.clone()),
+        credential: Some(json!({ "id": "Y3JlZA", "type": "public-key" })),
+        prf_output: Some("BwcH".to_string()),
+        prf_supported: Some(true),
+        vrf_challenge: Some(request.vrf_challenge.clone()),
+        transaction_context: None,
+        reserved_nonces: None,
+        collection_error: None,
+        error: None,
+        error_code: None,
+        countdown: None,
+        render_attestation: None,
+        sealed_secrets: None,
+        credential_collected_at_ms: None,
+        confirmation_expires_at_ms: None,
+    }
+}
+
+/// Opens a created request and seals an approval to it, as the approving device does
+fn approve(created: &CreateRemoteConfirmationResult) -> String {
+    let request = open_remote_request(&created.confirmation_request, state::now_ms()).unwrap();
+    let approval =
+        remote_approval_from_confirmation(&request, &confirmed(&request), state::now_ms()).unwrap();
+    seal_approval(&approval, &request.session_public_key).unwrap()
+}
+
+#[test]
+fn test_remote_confirmation_round_trips() {
+    let created = create(None).unwrap();
+    let request = open_remote_request(&created.confirmation_request, state::now_ms()).unwrap();
+    assert_eq!(request.request_id, created.request_id);
+    assert_eq!(request.near_account_id, ACCOUNT);
+    assert_eq!(request.intent_digest, created.intent_digest);
+    assert_eq!(request.tx_signing_requests.len(), 1);
+    assert_eq!(request.tx_signing_requests[0]["receiverId"], "bob.testnet");
+    assert_eq!(
+        base64_url_encode(&request.session_public_key),
+        created.session_public_key
+    );
+    assert_eq!(
+        created.expires_at_ms - request.issued_at_ms,
+        crate::config::DEFAULT_REMOTE_CONFIRMATION_TTL_MS as f64
+    );
+
+    let approval = approve(&created);
+    let (request_id, session, opened) =
+        accept_remote_approval(&complete_request(&approval, "1"), state::now_ms()).unwrap();
+    assert_eq!(request_id, created.request_id);
+    assert_eq!(session.near_account_id, ACCOUNT);
+    assert_eq!(opened.prf_output, "BwcH");
+    assert_eq!(opened.credential["id"], "Y3JlZA");
+
+    // An approval is used once
+    let err =
+        accept_remote_approval(&complete_request(&approval, "1"), state::now_ms()).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::RemoteConfirmationReplayed);
+    state::release_remote_session(&request_id);
+    assert!(accept_remote_approval(&complete_request(&approval, "1"), state::now_ms()).is_ok());
+}
+
+#[test]
+fn test_approvals_complete_in_a_later_worker() {
+    let (created, record) = in_fresh_worker(None, || {
+        (create(None).unwrap(), worker_state::take_changed_record())
+    });
+    let record = record.expect("the remote session is persisted");
+    let approval = approve(&created);
+
+    let (accepted, record) = in_fresh_worker(Some(record), {
+        let approval = approval.clone();
+        move || {
+            let accepted =
+                accept_remote_approval(&complete_request(&approval, "1"), state::now_ms())
+                    .map(|(request_id, _, opened)| (request_id, opened.prf_output));
+            (accepted, worker_state::take_changed_record())
+        }
+    });
+    assert_eq!(
+        accepted.unwrap(),
+        (created.request_id.clone(), "BwcH".to_string())
+    );
+
+    // The used approval stays used
+    let err = in_fresh_worker(record, {
+        let approval = approval.clone();
+        move || {
+            accept_remote_approval(&complete_request(&approval, "1"), state::now_ms())
+                .unwrap_err()
+                .code()
+        }
+    });
+    assert_eq!(err, SignerErrorCode::RemoteConfirmationReplayed);
+
+    // A worker without the record holds no session for it
+    let err = in_fresh_worker(None, move || {
+        accept_remote_approval(&complete_request(&approval, "1"), state::now_ms())
+            .unwrap_err()
+            .code()
+    });
+    assert_eq!(err, SignerErrorCode::RemoteConfirmationWrongSession);
+}
+
+#[test]
+fn test_ttl_and_challenge_are_checked_on_create() {
+    assert!(create(Some(0)).is_err());
+    assert!(create(Some(crate::config::MAX_REMOTE_CONFIRMATION_TTL_MS + 1)).is_err());
+    assert!(create(Some(1_000)).is_ok());
+
+    let request: CreateRemoteConfirmationRequest = serde_json::from_value(json!({
+        "rpcCall": rpc_call(ACCOUNT),
+        "txSigningRequests": transactions("1"),
+        "vrfChallenge": vrf_challenge("mallory.testnet"),
+    }))
+    .unwrap();
+    let err = block_on(handle_create_remote_confirmation(request)).unwrap_err();
+    assert_eq!(
+        err,
+        "vrfChallenge was issued for mallory.testnet, not alice.testnet"
+    );
+}
+
+#[test]
+fn test_tampered_or_expired_requests_are_refused() {
+    let created = create(Some(60_000)).unwrap();
+    let mut bytes = base64_url_decode(&created.confirmation_request).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    let err = open_remote_request(&base64_url_encode(&bytes), state::now_ms()).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::RemoteConfirmationInvalid);
+
+    let err =
+        open_remote_request(&created.confirmation_request, created.expires_at_ms).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::RemoteConfirmationExpired);
+
+    // A request re-signed with other transactions than its digest covers
+    let mut request = open_remote_request(&created.confirmation_request, state::now_ms()).unwrap();
+    request.tx_signing_requests[0]["receiverId"] = json!("mallory.testnet");
+    let key = generate_session_key().unwrap();
+    request.session_public_key = key.verifying_key().to_bytes().to_vec();
+    let blob = issue_remote_request(&request, &key).unwrap();
+    let err = open_remote_request(&blob, state::now_ms()).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::RemoteConfirmationInvalid);
+}
+
+#[test]
+fn test_approvals_only_open_with_their_session() {
+    let created = create(None).unwrap();
+    let request = open_remote_request(&created.confirmation_request, state::now_ms()).unwrap();
+    let approval =
+        remote_approval_from_confirmation(&request, &confirmed(&request), state::now_ms()).unwrap();
+
+    // Sealed to another key than the session's
+    let other = generate_session_key().unwrap();
+    let misdirected = seal_approval(&approval, &other.verifying_key().to_bytes()).unwrap();
+    let err =
+        accept_remote_approval(&complete_request(&misdirected, "1"), state::now_ms()).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::RemoteConfirmationWrongSession);
+
+    // Tampered ciphertext
+    let sealed = seal_approval(&approval, &request.session_public_key).unwrap();
+    let mut bytes = base64_url_decode(&sealed).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    let err = accept_remote_approval(
+        &complete_request(&base64_url_encode(&bytes), "1"),
+        state::now_ms(),
+    )
+    .unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::RemoteConfirmationInvalid);
+
+    // Other transactions than the approved ones
+    let err = accept_remote_approval(&complete_request(&sealed, "2"), state::now_ms()).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::RemoteConfirmationWrongSession);
+
+    // After the session expired
+    let err =
+        accept_remote_approval(&complete_request(&sealed, "1"), created.expires_at_ms).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::RemoteConfirmationExpired);
+
+    // None of the refusals used the approval up
+    assert!(accept_remote_approval(&complete_request(&sealed, "1"), state::now_ms()).is_ok());
+}
+
+#[test]
+fn test_approval_needs_the_requests_challenge_and_prf_output() {
+    let created = create(None).unwrap();
+    let request = open_remote_request(&created.confirmation_request, state::now_ms()).unwrap();
+
+    let mut other_challenge = confirmed(&request);
+    let mut challenge: VrfChallenge = request.vrf_challenge.clone();
+    challenge.vrf_output = "DQ4P".to_string();
+    other_challenge.vrf_challenge = Some(challenge);
+    let err =
+        remote_approval_from_confirmation(&request, &other_challenge, state::now_ms()).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::RemoteConfirmationInvalid);
+
+    let mut no_prf = confirmed(&request);
+    no_prf.prf_output = None;
+    let err = remote_approval_from_confirmation(&request, &no_prf, state::now_ms()).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::RemoteConfirmationInvalid);
+
+    let err =
+        remote_approval_from_confirmation(&request, &confirmed(&request), created.expires_at_ms)
+            .unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::RemoteConfirmationExpired);
+}
+
+#[test]
+fn test_approve_refuses_skipped_ui_other_accounts_and_replays() {
+    let created = create(None).unwrap();
+    let approve_request = |near_account_id: &str, ui_mode: &str| {
+        serde_json::from_value::<ApproveRemoteConfirmationRequest>(json!({
+            "confirmationRequest": created.confirmation_request,
+            "rpcCall": rpc_call(near_account_id),
+            "confirmationConfig": { "uiMode": ui_mode, "behavior": "requireClick" },
+        }))
+        .unwrap()
+    };
+
+    let err = block_on(handle_approve_remote_confirmation(approve_request(
+        ACCOUNT, "skip",
+    )))
+    .unwrap_err();
+    assert_eq!(
+        err,
+        "ApproveRemoteConfirmation always shows the confirmation UI"
+    );
+
+    let err = block_on(handle_approve_remote_confirmation(approve_request(
+        "bob.testnet",
+        "modal",
+    )))
+    .unwrap_err();
+    assert!(
+        err.starts_with("RemoteConfirmationWrongSession: "),
+        "{}",
+        err
+    );
+
+    // Already being approved on this device
+    assert!(state::note_remote_approval(
+        &created.request_id,
+        created.expires_at_ms
+    ));
+    let err = block_on(handle_approve_remote_confirmation(approve_request(
+        ACCOUNT, "modal",
+    )))
+    .unwrap_err();
+    assert_eq!(
+        err,
+        format!(
+            "RemoteConfirmationReplayed: remote confirmation {} was already used",
+            created.request_id
+        )
+    );
+    state::release_remote_approval(&created.request_id);
+}
+
+#[test]
+fn test_complete_reports_coded_failures() {
+    let result = block_on(handle_complete_remote_confirmation(complete_request(
+        "AAAA", "1",
+    )))
+    .unwrap();
+    assert!(!result.success);
+    assert_eq!(
+        result.error_code.as_deref(),
+        Some("RemoteConfirmationInvalid")
+    );
+
+    // Approving a request this worker holds no session for (say, one created before a reload)
+    let created = create(None).unwrap();
+    let mut request = open_remote_request(&created.confirmation_request, state::now_ms()).unwrap();
+    request.request_id = "req-unknown".to_string();
+    let key = generate_session_key().unwrap();
+    request.session_public_key = key.verifying_key().to_bytes().to_vec();
+    let orphan = CreateRemoteConfirmationResult {
+        confirmation_request: issue_remote_request(&request, &key).unwrap(),
+        ..created
+    };
+    let approval = approve(&orphan);
+    let result = block_on(handle_complete_remote_confirmation(complete_request(
+        &approval, "1",
+    )))
+    .unwrap();
+    assert_eq!(
+        result.error_code.as_deref(),
+        Some("RemoteConfirmationWrongSession")
+    );
+}
+
+#[test]
+fn test_strict_parsing_covers_remote_confirmation_requests() {
+    let payload = json!({
+        "approval": "AAAA",
+        "txSigningRequests": [],
+        "transactionContext": {},
+        "workerPolicy": { "strictParsing": true }
+    });
+    assert_eq!(
+        check_unknown_fields(WorkerRequestType::CompleteRemoteConfirmation, &payload),
+        Ok(())
+    );
+
+    let mut misspelled = payload;
+    misspelled["aproval"] = json!("AAAA");
+    let err = check_unknown_fields(WorkerRequestType::CompleteRemoteConfirmation, &misspelled)
+        .unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::UnknownField);
+
+    let misspelled = json!({ "ttlMS": 1, "workerPolicy": { "strictParsing": true } });
+    assert!(
+        check_unknown_fields(WorkerRequestType::CreateRemoteConfirmation, &misspelled).is_err()
+    );
+}
@@ -0,0 +1,316 @@
+use crate::actions::ActionParams;
+use crate::config::{DEFAULT_MAX_ARG_PREVIEW_BYTES, DEFAULT_MAX_SUMMARY_BLOCKS};
+use crate::confirmation_blocks::{
+    batch_summary_blocks, ConfirmationSummaryBlock, RolledUpAmount, SummaryPolicy,
+    SummaryWarningCode,
+};
+use crate::confirmation_speech::SpeechLocale;
+use crate::handlers::confirm_tx_details::{
+    compute_confirmation_intent_digest, confirmation_tx_signing_requests_json,
+};
+use crate::redaction::value_sha256;
+use crate::render_budget::*;
+use crate::strict_parsing::check_unknown_fields;
+use crate::types::handlers::WorkerPolicy;
+use crate::types::worker_messages::WorkerRequestType;
+use serde_json::{json, Value};
+
+const SIGNER: &str = "alice.testnet";
+const RECEIVER: &str = "dapp.testnet";
+
+fn call(args: &str) -> ActionParams {
+    ActionParams::FunctionCall {
+        method_name: "store".to_string(),
+        args: args.to_string(),
+        gas: "30000000000000".to_string(),
+        deposit: "0".to_string(),
+    }
+}
+
+fn transfer(yocto: &str) -> ActionParams {
+    ActionParams::Transfer {
+        deposit: yocto.to_string(),
+    }
+}
+
+fn shown_args(action: &ActionParams) -> &str {
+    match action {
+        ActionParams::FunctionCall { args, .. } => args,
+        other => panic!("not a FunctionCall: {:?}", other),
+    }
+}
+
+fn budget(max_arg_preview_bytes: usize, max_summary_blocks: usize) -> RenderBudget {
+    RenderBudget {
+        max_arg_preview_bytes,
+        max_summary_blocks,
+    }
+}
+
+fn policy_with(render_budget: RenderBudget) -> SummaryPolicy {
+    SummaryPolicy {
+        render_budget,
+        ..SummaryPolicy::default()
+    }
+}
+
+/// Every block of a batch, in order
+fn flatten(blocks: Vec<Vec<ConfirmationSummaryBlock>>) -> Vec<ConfirmationSummaryBlock> {
+    blocks.into_iter().flatten().collect()
+}
+
+#[test]
+fn test_oversized_args_values_are_truncated_with_their_hash() {
+    let blob = "x".repeat(5 * 1024 * 1024);
+    let args = json!({ "blob": blob, "to": "bob.testnet" });
+    let (shown, truncations) =
+        truncate_actions(&[call(&args.to_string())], &RenderBudget::default());
+
+    let shown: Value = serde_json::from_str(shown_args(&shown[0])).unwrap();
+    assert_eq!(shown["to"], json!("bob.testnet"));
+    let marker = &shown["blob"];
+    assert_eq!(marker["truncated"], json!(true));
+    assert_eq!(marker["bytes"], json!(blob.len() + 2));
+    assert_eq!(marker["sha256"], json!(value_sha256(&json!(blob))));
+    assert!(marker["preview"].as_str().unwrap().len() <= DEFAULT_MAX_ARG_PREVIEW_BYTES);
+    assert!(shown.to_string().len() < 2 * DEFAULT_MAX_ARG_PREVIEW_BYTES);
+
+    assert_eq!(
+        truncations,
+        vec![ArgTruncation {
+            action_index: 0,
+            path: "args.blob".to_string(),
+            bytes: blob.len() + 2,
+            sha256: value_sha256(&json!(blob)),
+        }]
+    );
+
+    // Within the budget, the actions are shown as they are
+    let small = [
+        call(&json!({ "to": "bob.testnet" }).to_string()),
+        transfer("1"),
+    ];
+    let (shown, truncations) = truncate_actions(&small, &RenderBudget::default());
+    assert_eq!(shown, small);
+    assert!(truncations.is_empty());
+}
+
+#[test]
+fn test_args_that_are_not_objects_are_truncated_whole() {
+    let (shown, truncations) =
+        truncate_actions(&[call(&json!(vec![1; 100]).to_string())], &budget(16, 10));
+    let shown: Value = serde_json::from_str(shown_args(&shown[0])).unwrap();
+    assert_eq!(shown["truncated"], json!(true));
+    assert_eq!(truncations[0].path, "args");
+
+    // An oversized key cannot hide behind small values
+    let long_key = "k".repeat(64);
+    let args = json!({ long_key: 1 }).to_string();
+    let (_, truncations) = truncate_actions(&[call(&args)], &budget(16, 10));
+    assert_eq!(truncations[0].path, "args");
+    assert_eq!(truncations[0].bytes, args.len());
+}
+
+#[test]
+fn test_text_args_stay_text_when_truncated() {
+    let text = format!("{}é", "a".repeat(15));
+    let (shown, truncations) = truncate_actions(&[call(&text.repeat(4))], &budget(16, 10));
+    let shown = shown_args(&shown[0]);
+    assert!(serde_json::from_str::<Value>(shown).is_err(), "{}", shown);
+    // The preview stops before the split two-byte character
+    assert!(shown.starts_with(&format!("{}«truncated: 68 bytes, sha256: ", "a".repeat(15))));
+    assert_eq!(truncations[0].bytes, 68);
+    assert_eq!(truncations[0].sha256.len(), 64);
+}
+
+#[test]
+fn test_confirmation_payload_lists_truncations_in_the_digest() {
+    let flags = [None];
+    let policy = policy_with(budget(64, DEFAULT_MAX_SUMMARY_BLOCKS));
+    let batch = |tail: &str| {
+        vec![(
+            RECEIVER.to_string(),
+            vec![call(
+                &json!({ "data": format!("{}{}", "y".repeat(1000), tail) }).to_string(),
+            )],
+        )]
+    };
+
+    let txs = confirmation_tx_signing_requests_json(&batch("a"), &flags, None, Some(&policy));
+    let truncations = txs[0]["truncations"].as_array().unwrap();
+    assert_eq!(truncations.len(), 1);
+    assert_eq!(truncations[0]["path"], json!("args.data"));
+    let code_block = txs[0]["summaryBlocks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|block| block["kind"] == json!("codeBlock"))
+        .unwrap();
+    assert!(code_block["json"]
+        .as_str()
+        .unwrap()
+        .contains("\"truncated\":true"));
+
+    // Values that differ only past the preview still digest differently
+    let digest = |tail: &str| {
+        compute_confirmation_intent_digest(&batch(tail), &flags, None, Some(&policy)).unwrap()
+    };
+    assert_ne!(digest("a"), digest("b"));
+
+    let plain = confirmation_tx_signing_requests_json(
+        &[(RECEIVER.to_string(), vec![transfer("1")])],
+        &flags,
+        None,
+        Some(&policy),
+    );
+    assert!(plain[0].get("truncations").is_none());
+}
+
+#[test]
+fn test_block_budget_rolls_up_actions_but_never_warnings_receivers_or_deposits() {
+    let mut actions: Vec<ActionParams> = (1..=40).map(|i| transfer(&i.to_string())).collect();
+    actions.push(ActionParams::DeleteAccount {
+        beneficiary_id: "mallory.testnet".to_string(),
+    });
+    let batch = vec![
+        (SIGNER.to_string(), actions.clone()),
+        ("carol.testnet".to_string(), vec![transfer("7")]),
+    ];
+    let policy = policy_with(budget(DEFAULT_MAX_ARG_PREVIEW_BYTES, 10));
+    let blocks = batch_summary_blocks(&batch, &[None, None], &policy);
+
+    let first = &blocks[0];
+    assert!(matches!(
+        &first[0],
+        ConfirmationSummaryBlock::Warning {
+            code: SummaryWarningCode::DeleteAccount,
+            ..
+        }
+    ));
+    let all = flatten(blocks.clone());
+    let receivers: Vec<&str> = all
+        .iter()
+        .filter_map(|block| match block {
+            ConfirmationSummaryBlock::AccountRow {
+                label, account_id, ..
+            } if label == "Receiver" => Some(account_id.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(receivers, vec![SIGNER, "carol.testnet"]);
+
+    // Every transfer shows up, in a row or in the rollup
+    let mut amounts: Vec<String> = Vec::new();
+    for block in all {
+        match block {
+            ConfirmationSummaryBlock::AmountRow { yocto, .. } => amounts.push(yocto),
+            ConfirmationSummaryBlock::Rollup {
+                amounts: rolled, ..
+            } => amounts.extend(rolled.into_iter().map(|amount| amount.yocto)),
+            _ => {}
+        }
+    }
+    let expected: Vec<String> = (1..=40).chain([7]).map(|i: u32| i.to_string()).collect();
+    assert_eq!(amounts, expected);
+
+    match first.last().unwrap() {
+        ConfirmationSummaryBlock::Rollup {
+            text,
+            hidden_actions,
+            receiver_id,
+            amounts,
+            aria_label,
+        } => {
+            assert_eq!(receiver_id, SIGNER);
+            assert_eq!(text, &format!("{} more actions", hidden_actions));
+            // The DeleteAccount is among the hidden actions; its warning still leads
+            assert_eq!(amounts.last().unwrap().action_index, 39);
+            assert_eq!(
+                amounts[0],
+                RolledUpAmount {
+                    action_index: 41 - hidden_actions,
+                    label: "Amount".to_string(),
+                    yocto: (42 - hidden_actions).to_string(),
+                    formatted: crate::confirmation_blocks::format_yocto_near(
+                        (42 - hidden_actions) as u128
+                    ),
+                }
+            );
+            assert!(aria_label.contains("more actions"), "{}", aria_label);
+        }
+        other => panic!("expected a rollup, got {:?}", other),
+    }
+    // The second transaction's action comes after a rolled up one, so it is rolled up too
+    assert!(matches!(
+        blocks[1].last().unwrap(),
+        ConfirmationSummaryBlock::Rollup {
+            hidden_actions: 1,
+            ..
+        }
+    ));
+    let rollups = flatten(blocks.clone())
+        .iter()
+        .filter(|block| matches!(block, ConfirmationSummaryBlock::Rollup { .. }))
+        .count();
+    assert!(flatten(blocks).len() <= 10 + rollups);
+
+    // Within the budget nothing is rolled up
+    let blocks = flatten(batch_summary_blocks(
+        &batch,
+        &[None, None],
+        &SummaryPolicy::default(),
+    ));
+    assert!(!blocks
+        .iter()
+        .any(|block| matches!(block, ConfirmationSummaryBlock::Rollup { .. })));
+}
+
+#[test]
+fn test_rollup_is_spoken() {
+    let batch = vec![(SIGNER.to_string(), vec![transfer("1"), transfer("2")])];
+    for (locale, expected) in [
+        (SpeechLocale::En, "more actions for"),
+        (SpeechLocale::Es, "acciones más para"),
+    ] {
+        let policy = SummaryPolicy {
+            locale,
+            ..policy_with(budget(DEFAULT_MAX_ARG_PREVIEW_BYTES, 1))
+        };
+        let blocks = flatten(batch_summary_blocks(&batch, &[None], &policy));
+        match blocks.last().unwrap() {
+            ConfirmationSummaryBlock::Rollup { aria_label, .. } => {
+                assert!(aria_label.contains(expected), "{}", aria_label)
+            }
+            other => panic!("expected a rollup, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn test_render_budget_policy_fields() {
+    assert_eq!(
+        RenderBudget::from_worker_policy(None),
+        Ok(RenderBudget::default())
+    );
+    let policy: WorkerPolicy =
+        serde_json::from_value(json!({ "maxArgPreviewBytes": 256, "maxSummaryBlocks": 50 }))
+            .unwrap();
+    assert_eq!(
+        SummaryPolicy::from_worker_policy(Some(&policy))
+            .unwrap()
+            .render_budget,
+        budget(256, 50)
+    );
+
+    let zero: WorkerPolicy = serde_json::from_value(json!({ "maxSummaryBlocks": 0 })).unwrap();
+    let err = SummaryPolicy::from_worker_policy(Some(&zero)).unwrap_err();
+    assert!(err.contains("maxSummaryBlocks"), "{}", err);
+
+    let payload = json!({
+        "workerPolicy": { "strictParsing": true, "maxArgPreviewBytes": 256, "maxSummaryBlocks": 50 }
+    });
+    assert_eq!(
+        check_unknown_fields(WorkerRequestType::SignTransactionsWithActions, &payload),
+        Ok(())
+    );
+}
@@ -0,0 +1,180 @@
+use crate::error::SignerErrorCode;
+use crate::handlers::handle_cancel_request::{handle_cancel_request, CancelRequestRequest};
+use crate::handlers::handle_list_pending_requests::{
+    handle_list_pending_requests, ListPendingRequestsRequest,
+};
+use crate::handlers::handle_wipe_all_state::{handle_wipe_all_state, WipeAllStateRequest};
+use crate::state::{self, RequestLimits, RequestPhase, RequestSlot};
+use crate::tests::block_on;
+use crate::types::AccountId;
+use std::future::Future;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+// Each test runs on its own thread, so the thread_local registry starts empty.
+
+fn poll_started(slot: &RequestSlot) -> Poll<Result<(), SignerErrorCode>> {
+    fn noop_raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            noop_raw_waker()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+    let mut cx = Context::from_waker(&waker);
+    let mut wait = Box::pin(slot.started());
+    wait.as_mut().poll(&mut cx)
+}
+
+fn admit_n(prefix: &str, n: usize) -> Vec<RequestSlot> {
+    (0..n)
+        .map(|i| {
+            state::admit_request(
+                &format!("{}-{}", prefix, i),
+                "SIGN_TRANSACTIONS_WITH_ACTIONS",
+            )
+            .unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn test_registry_bounds_running_and_queued_requests() {
+    assert_eq!(state::request_limits(), RequestLimits::default());
+    let running = admit_n("run", 4);
+    let queued = admit_n("queue", 16);
+
+    assert!(running.iter().all(|slot| poll_started(slot).is_ready()));
+    assert!(queued.iter().all(|slot| poll_started(slot).is_pending()));
+
+    let rejected = state::admit_request("one-too-many", "SIGN_NEP413_MESSAGE");
+    assert_eq!(
+        rejected.err(),
+        Some(SignerErrorCode::TooManyPendingRequests)
+    );
+    assert_eq!(
+        state::admit_request("run-0", "SIGN_NEP413_MESSAGE").err(),
+        Some(SignerErrorCode::DuplicateRequestId)
+    );
+
+    // Finishing a running request starts the oldest queued one
+    drop(running);
+    assert!(queued[..4]
+        .iter()
+        .all(|slot| poll_started(slot) == Poll::Ready(Ok(()))));
+    assert!(poll_started(&queued[4]).is_pending());
+}
+
+#[test]
+fn test_list_pending_requests_reports_phases_and_ages() {
+    state::set_request_limits(RequestLimits {
+        max_concurrent: 1,
+        max_queued: 2,
+    })
+    .unwrap();
+    let _running = state::admit_request("list-a", "SIGN_TRANSACTIONS_WITH_ACTIONS").unwrap();
+    let _queued = state::admit_request("list-b", "SIGN_NEP413_MESSAGE").unwrap();
+
+    let result = block_on(handle_list_pending_requests(ListPendingRequestsRequest {})).unwrap();
+    assert_eq!(result.max_concurrent, 1);
+    assert_eq!(result.max_queued, 2);
+    let summary: Vec<(&str, &str, &str)> = result
+        .requests
+        .iter()
+        .map(|r| {
+            (
+                r.request_id.as_str(),
+                r.operation.as_str(),
+                r.phase.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("list-a", "SIGN_TRANSACTIONS_WITH_ACTIONS", "running"),
+            ("list-b", "SIGN_NEP413_MESSAGE", "queued"),
+        ]
+    );
+    assert!(result.requests.iter().all(|r| r.age_ms >= 0.0));
+
+    assert!(state::set_request_limits(RequestLimits {
+        max_concurrent: 0,
+        max_queued: 2,
+    })
+    .is_err());
+}
+
+#[test]
+fn test_cancel_request_only_cancels_queued_requests() {
+    state::set_request_limits(RequestLimits {
+        max_concurrent: 1,
+        max_queued: 4,
+    })
+    .unwrap();
+    let running = state::admit_request("cancel-running", "SIGN_TRANSACTIONS_WITH_ACTIONS").unwrap();
+    let queued = state::admit_request("cancel-queued", "SIGN_TRANSACTIONS_WITH_ACTIONS").unwrap();
+    let next = state::admit_request("cancel-next", "SIGN_TRANSACTIONS_WITH_ACTIONS").unwrap();
+
+    let cancel = |id: &str| {
+        block_on(handle_cancel_request(CancelRequestRequest {
+            request_id: id.to_string(),
+        }))
+        .unwrap()
+    };
+
+    let result = cancel("cancel-queued");
+    assert!(result.cancelled);
+    assert_eq!(result.phase.as_deref(), Some("queued"));
+    assert_eq!(
+        poll_started(&queued),
+        Poll::Ready(Err(SignerErrorCode::RequestCancelled))
+    );
+
+    let result = cancel("cancel-running");
+    assert!(!result.cancelled);
+    assert_eq!(result.phase.as_deref(), Some("running"));
+
+    let result = cancel("unknown");
+    assert!(!result.cancelled);
+    assert_eq!(result.phase, None);
+
+    // The cancelled entry never takes a slot: the next queued request starts instead
+    drop(queued);
+    drop(running);
+    assert_eq!(poll_started(&next), Poll::Ready(Ok(())));
+    assert_eq!(state::list_pending_requests().len(), 1);
+}
+
+#[test]
+fn test_wipe_all_state_cancels_queued_and_clears_request_state() {
+    state::set_request_limits(RequestLimits {
+        max_concurrent: 1,
+        max_queued: 4,
+    })
+    .unwrap();
+    let running = state::admit_request("wipe-running", "SIGN_TRANSACTIONS_WITH_ACTIONS").unwrap();
+    let queued = admit_n("wipe-queued", 2);
+    state::register_confirmation_nonce("wipe-running");
+    let account = AccountId("alice.testnet".to_string());
+    state::reserve_nonces(&account, "wipe-running", vec!["7".to_string()]);
+
+    let result = block_on(handle_wipe_all_state(WipeAllStateRequest {})).unwrap();
+    assert_eq!(result.cancelled_queued_requests, 2);
+    assert_eq!(result.running_requests, 1);
+    assert_eq!(result.cleared_confirmations, 1);
+    assert_eq!(result.released_nonce_reservations, 1);
+
+    assert!(queued
+        .iter()
+        .all(|slot| poll_started(slot) == Poll::Ready(Err(SignerErrorCode::RequestCancelled))));
+    assert!(!state::has_confirmation_nonce("wipe-running"));
+    assert!(state::reserved_nonces(&account, "wipe-running").is_empty());
+
+    let pending = state::list_pending_requests();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].phase, RequestPhase::Running);
+    drop(running);
+    assert!(state::list_pending_requests().is_empty());
+}
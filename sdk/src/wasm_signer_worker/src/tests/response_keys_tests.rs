@@ -0,0 +1,798 @@
+use crate::account_bundle::AccountBundleRecords;
+use crate::background_refresh::{
+    BackgroundRefreshStatus, RefreshAccount, RefreshedBlock, RefreshedNonce,
+};
+use crate::blob_migration::StoredBlobKind;
+use crate::chunked_deploy::DeployManifest;
+use crate::features::{is_compiled, required_feature, WorkerInfo};
+use crate::handlers::handle_account_bundle::{
+    ExportAccountBundleResult, ImportAccountBundleResult,
+};
+use crate::handlers::handle_background_refresh::{StopBackgroundRefreshResult, SuspendHintResult};
+use crate::handlers::handle_build_account_descriptor::BuildAccountDescriptorResult;
+use crate::handlers::handle_build_redirect_payload::BuildRedirectPayloadResult;
+use crate::handlers::handle_build_credential_options::{
+    BuildCredentialCreationOptionsResult, BuildCredentialRequestOptionsResult,
+};
+use crate::handlers::handle_cancel_request::CancelRequestResult;
+use crate::handlers::handle_issue_elevation_challenge::IssueElevationChallengeResult;
+use crate::handlers::handle_check_can_register_user::{
+    RegistrationCheckResult, RegistrationDryRunReport, RegistrationInfoStruct,
+};
+use crate::handlers::handle_compose_create_subaccount::ComposeCreateSubaccountResult;
+use crate::handlers::handle_compose_multisig_request::ComposeMultisigResult;
+use crate::handlers::handle_decrypt_private_key_with_prf::{
+    DecryptPrivateKeyResult, ExportNearKeypairUiResult,
+};
+use crate::handlers::handle_deploy_large_contract::DeployLargeContractResult;
+use crate::handlers::handle_derive_near_keypair_and_encrypt::DeriveNearKeypairAndEncryptResult;
+use crate::handlers::handle_derive_sub_key::DeriveSubKeyResult;
+use crate::handlers::handle_dump_diagnostics::DumpDiagnosticsResult;
+use crate::handlers::handle_extract_cose_public_key::CoseExtractionResult;
+use crate::handlers::handle_get_borsh_schemas::GetBorshSchemasResult;
+use crate::handlers::handle_get_recent_receivers::GetRecentReceiversResult;
+use crate::handlers::handle_list_pending_requests::{
+    ListPendingRequestsResult, PendingRequestEntry,
+};
+use crate::handlers::handle_memory::{MemoryStats, TrimCachesResult};
+use crate::handlers::handle_migrate_encrypted_blobs::{
+    BlobMigrationReport, BlobMigrationStatus, MigrateEncryptedBlobsResult,
+};
+use crate::handlers::handle_recover_keypair_from_passkey::RecoverKeypairResult;
+use crate::handlers::handle_recover_pending_operations::RecoverPendingOperationsResult;
+use crate::handlers::handle_request_registration_credential_confirmation::RegistrationCredentialConfirmationResult;
+use crate::handlers::handle_resumable_registration::PrepareRegistrationResult;
+use crate::handlers::handle_session_keys::{CreateSessionKeyResult, RevokeSessionKeyResult};
+use crate::handlers::handle_sign_transactions_with_actions::TransactionSignResult;
+use crate::handlers::handle_sign_transactions_with_fresh_challenge::SignTransactionsWithFreshChallengeResult;
+use crate::handlers::handle_list_active_accounts::{ActiveAccountEntry, ListActiveAccountsResult};
+use crate::handlers::handle_signing_intent::CreateSigningIntentResult;
+use crate::handlers::handle_summarize_transactions::SummarizeTransactionsResult;
+use crate::handlers::handle_validate_args_schemas::{
+    ArgsSchemaErrorEntry, ValidateArgsSchemasResult,
+};
+use crate::handlers::handle_validate_decryption_capability::DecryptionCapability;
+use crate::handlers::handle_validate_encrypted_blobs::{
+    BlobProblem, BlobValidationReport, ValidateEncryptedBlobsResult,
+};
+use crate::handlers::handle_wipe_account_state::WipeAccountStateResult;
+use crate::handlers::handle_wipe_all_state::WipeAllStateResult;
+use crate::key_usage::{KeyUsageStat, KeyUsageStats};
+use crate::known_authenticators::AuthenticatorInfo;
+use crate::operation_journal::{JournalEntry, RecoveredOperation, RecoveryStatus};
+use crate::self_test::{SelfTestReport, SelfTestResult};
+use crate::session_duration::SessionStatus;
+use crate::state::RecentReceiver;
+use crate::sub_keys::SubKeyEntry;
+use crate::types::handlers::TransactionContext;
+use crate::types::wasm_to_json::{
+    ToJson, WasmPublicKey, WasmSignature, WasmSignedTransaction, WasmTransaction,
+};
+use crate::types::worker_messages::{SignerWorkerResponse, WorkerRequestType};
+use crate::types::{VrfAnchor, VrfChallenge};
+use crate::view_call::ViewCallResult;
+use crate::wire_format::{cbor_to_json, encode_cbor_response, encode_json_response};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// JS-visible key paths of every handler result ("a.b" nests, "a[].b" is inside a list).
+/// Regenerate from the failure message when a result's shape changes on purpose, and bump
+/// RESPONSE_SCHEMA_VERSION when a key is renamed or removed.
+const RESPONSE_KEYS_SNAPSHOT: &str = include_str!("snapshots/response_keys.json");
+const CREDENTIAL_CREATION_OPTIONS_SNAPSHOT: &str =
+    include_str!("snapshots/credential_creation_options.json");
+const CREDENTIAL_REQUEST_OPTIONS_SNAPSHOT: &str =
+    include_str!("snapshots/credential_request_options.json");
+
+const PUBLIC_KEY: &str = "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp";
+
+fn signed_transaction() -> WasmSignedTransaction {
+    WasmSignedTransaction::new(
+        WasmTransaction::new(
+            "alice.testnet".to_string(),
+            WasmPublicKey::new(0, vec![1; 32]),
+            8,
+            "bob.testnet".to_string(),
+            vec![2; 32],
+            "[]".to_string(),
+        ),
+        WasmSignature::new(0, vec![3; 64]),
+        vec![4, 5, 6],
+    )
+}
+
+fn transaction_context() -> TransactionContext {
+    TransactionContext {
+        access_key_allowance: Some("1000".to_string()),
+        gas_price: Some("100000000".to_string()),
+        ..TransactionContext::new(
+            PUBLIC_KEY.to_string(),
+            "8".to_string(),
+            "1000".to_string(),
+            "11111111111111111111111111111111".to_string(),
+        )
+    }
+}
+
+fn vrf_challenge() -> VrfChallenge {
+    VrfChallenge {
+        vrf_input: "input".to_string(),
+        vrf_output: "output".to_string(),
+        vrf_proof: "proof".to_string(),
+        vrf_public_key: "vrf-public-key".to_string(),
+        user_id: "alice.testnet".to_string(),
+        rp_id: "example.localhost".to_string(),
+        block_height: "1000".to_string(),
+        block_hash: "11111111111111111111111111111111".to_string(),
+        anchor: Some(VrfAnchor::NearBlock {
+            block_height: "1000".to_string(),
+            block_hash: "11111111111111111111111111111111".to_string(),
+        }),
+        challenge_length: 32,
+    }
+}
+
+fn transaction_sign_result() -> TransactionSignResult {
+    TransactionSignResult {
+        error_code: Some("UserDeclined".to_string()),
+        error_category: Some("User".to_string()),
+        retriable: Some(false),
+        broadcast_rpc_url: Some("https://rpc.testnet.near.org".to_string()),
+        // NEAR RPC outcomes are passed through as the node returns them
+        broadcast_outcomes: Some(vec![]),
+        valid_until_block_height: Some(1100),
+        removed_duplicate_indexes: Some(vec![1]),
+        conditional_entries: Some(vec![crate::conditional_batch::ConditionalBatchEntry {
+            index: 0,
+            status: crate::conditional_batch::ConditionalEntryStatus::Signed,
+            simulation: Some(crate::conditional_batch::SimulationOutcome::Failed {
+                method_name: "swap".to_string(),
+                error: "panicked".to_string(),
+                logs: vec![],
+            }),
+        }]),
+        ..TransactionSignResult::new(
+            true,
+            Some(vec!["hash".to_string()]),
+            Some(vec![signed_transaction()]),
+            vec!["log".to_string()],
+            Some("error".to_string()),
+        )
+    }
+}
+
+fn derive_result() -> DeriveNearKeypairAndEncryptResult {
+    DeriveNearKeypairAndEncryptResult {
+        near_account_id: "alice.testnet".to_string(),
+        public_key: PUBLIC_KEY.to_string(),
+        encrypted_data: "data".to_string(),
+        iv: "iv".to_string(),
+        stored: true,
+        signed_transaction: Some(signed_transaction()),
+        outer_wrap: Some("wrap".to_string()),
+        version: 3,
+        key_wrapping: "prf".to_string(),
+        large_blob_secret: Some("secret".to_string()),
+        negotiated_algorithm: Some(-8),
+        sub_keys: vec![sub_key_entry()],
+        authenticator: Some(AuthenticatorInfo {
+            aaguid: "fbfc3007-154e-4ecc-8c0b-6e020557d7bd".to_string(),
+            provider_name: Some("iCloud Keychain".to_string()),
+            is_synced_passkey: Some(true),
+        }),
+    }
+}
+
+fn sub_key_entry() -> SubKeyEntry {
+    SubKeyEntry {
+        path: "m/44'/397'/0'/0'/1'".to_string(),
+        public_key: PUBLIC_KEY.to_string(),
+    }
+}
+
+fn memory_stats() -> MemoryStats {
+    MemoryStats {
+        memory_pages: Some(17),
+        memory_bytes: Some(1_114_112.0),
+        live_heap_bytes: 1.0,
+        peak_heap_bytes: 2.0,
+        transient_buffer_bytes: 3.0,
+        pending_requests: 1,
+        confirmation_nonces: 1,
+        nonce_reservations: 1,
+        audit_entries: 1,
+        session_keys: 1,
+    }
+}
+
+#[cfg(feature = "relayer")]
+fn relayer_result() -> crate::relayer::RelayerResult {
+    crate::relayer::RelayerResult {
+        success: true,
+        account_created: true,
+        tx_hash: Some("hash".to_string()),
+        error_kind: Some("Rejected".to_string()),
+        error: Some("error".to_string()),
+    }
+}
+
+/// What each handler returns, with every optional field set; exhaustive so new request types
+/// must be added here
+fn sample_result(request_type: WorkerRequestType) -> Value {
+    let result = match request_type {
+        WorkerRequestType::DeriveNearKeypairAndEncrypt => derive_result().to_json(),
+        WorkerRequestType::RecoverKeypairFromPasskey => RecoverKeypairResult {
+            public_key: PUBLIC_KEY.to_string(),
+            encrypted_data: "data".to_string(),
+            iv: "iv".to_string(),
+            account_id_hint: Some("alice.testnet".to_string()),
+            outer_wrap: Some("wrap".to_string()),
+            sub_keys: vec![sub_key_entry()],
+        }
+        .to_json(),
+        WorkerRequestType::CheckCanRegisterUser => RegistrationCheckResult {
+            verified: true,
+            registration_info: Some(RegistrationInfoStruct {
+                credential_id: vec![1],
+                credential_public_key: vec![2],
+                user_id: "alice.testnet".to_string(),
+                vrf_public_key: Some(vec![3]),
+            }),
+            logs: vec!["log".to_string()],
+            signed_transaction: Some(signed_transaction()),
+            error: Some("error".to_string()),
+            dry_run: Some(RegistrationDryRunReport {
+                outcome: "WouldSucceed".to_string(),
+                would_succeed: true,
+                detail: Some("detail".to_string()),
+                near_public_key: Some(PUBLIC_KEY.to_string()),
+                vrf_public_key: "vrf-public-key".to_string(),
+                credential_public_key: Some(vec![2]),
+                contract_args: Some("{}".to_string()),
+                estimated_storage_cost_yocto: Some("1".to_string()),
+            }),
+        }
+        .to_json(),
+        WorkerRequestType::DecryptPrivateKeyWithPrf => DecryptPrivateKeyResult {
+            private_key: "ed25519:private".to_string(),
+            near_account_id: "alice.testnet".to_string(),
+        }
+        .to_json(),
+        WorkerRequestType::SignTransactionsWithActions
+        | WorkerRequestType::SignTransactionWithKeyPair
+        | WorkerRequestType::SignWithSessionKey
+        | WorkerRequestType::ExecuteSigningIntent
+        | WorkerRequestType::CompleteRemoteConfirmation => transaction_sign_result().to_json(),
+        WorkerRequestType::ExtractCosePublicKey => CoseExtractionResult {
+            cose_public_key_bytes: vec![1, 2],
+        }
+        .to_json(),
+        #[cfg(feature = "nep413")]
+        WorkerRequestType::SignNep413Message => {
+            crate::handlers::handle_sign_nep413_message::SignNep413Result::new(
+                "alice.testnet".to_string(),
+                PUBLIC_KEY.to_string(),
+                "signature".to_string(),
+                Some("state".to_string()),
+            )
+            .to_json()
+        }
+        WorkerRequestType::RegistrationCredentialConfirmation => {
+            RegistrationCredentialConfirmationResult {
+                confirmed: true,
+                request_id: "request".to_string(),
+                intent_digest: "digest".to_string(),
+                // The serialized WebAuthn credential is passed through as the main thread built it
+                credential: Some(json!({})),
+                prf_output: Some("prf".to_string()),
+                prf_supported: Some(true),
+                vrf_challenge: Some(vrf_challenge()),
+                transaction_context: Some(transaction_context()),
+                error: Some("error".to_string()),
+            }
+            .to_json()
+        }
+        WorkerRequestType::ExportNearKeypairUI => ExportNearKeypairUiResult {
+            near_account_id: "alice.testnet".to_string(),
+            public_key: PUBLIC_KEY.to_string(),
+        }
+        .to_json(),
+        WorkerRequestType::GetBorshSchemas => GetBorshSchemasResult {
+            transaction: "{}".to_string(),
+            signed_transaction: "{}".to_string(),
+            delegate_action: "{}".to_string(),
+            nep413_payload: "{}".to_string(),
+        }
+        .to_json(),
+        WorkerRequestType::ListPendingRequests => ListPendingRequestsResult {
+            requests: vec![PendingRequestEntry {
+                request_id: "request".to_string(),
+                operation: "SIGN_TRANSACTIONS_WITH_ACTIONS".to_string(),
+                phase: "running".to_string(),
+                age_ms: 1.0,
+            }],
+            max_concurrent: 4,
+            max_queued: 16,
+        }
+        .to_json(),
+        WorkerRequestType::CancelRequest => CancelRequestResult {
+            request_id: "request".to_string(),
+            cancelled: true,
+            phase: Some("queued".to_string()),
+        }
+        .to_json(),
+        WorkerRequestType::WipeAllState => WipeAllStateResult {
+            cancelled_queued_requests: 1,
+            running_requests: 1,
+            cleared_confirmations: 1,
+            released_nonce_reservations: 1,
+            cleared_session_keys: 1,
+            closed_peer_port: true,
+            stopped_background_refresh: true,
+        }
+        .to_json(),
+        WorkerRequestType::ValidateEncryptedBlobs => ValidateEncryptedBlobsResult {
+            near_account_id: "alice.testnet".to_string(),
+            all_valid: false,
+            reports: vec![BlobValidationReport {
+                index: 0,
+                blob_id: Some("blob".to_string()),
+                valid: false,
+                problems: vec![BlobProblem {
+                    code: "NonceLengthMismatch".to_string(),
+                    message: "message".to_string(),
+                }],
+            }],
+        }
+        .to_json(),
+        WorkerRequestType::CreateSessionKey => CreateSessionKeyResult {
+            public_key: PUBLIC_KEY.to_string(),
+            near_account_id: "alice.testnet".to_string(),
+            expires_at_ms: 1.0,
+            add_key_action: "{}".to_string(),
+        }
+        .to_json(),
+        WorkerRequestType::RevokeSessionKey => RevokeSessionKeyResult {
+            public_key: PUBLIC_KEY.to_string(),
+            revoked: true,
+            delete_key_action: "{}".to_string(),
+        }
+        .to_json(),
+        WorkerRequestType::ComposeMultisigRequest => ComposeMultisigResult {
+            method_name: "add_request".to_string(),
+            action: "{}".to_string(),
+        }
+        .to_json(),
+        WorkerRequestType::ComposeCreateSubaccount => ComposeCreateSubaccountResult {
+            receiver_id: "alice.myapp.testnet".to_string(),
+            actions: "[]".to_string(),
+        }
+        .to_json(),
+        WorkerRequestType::StartBackgroundRefresh | WorkerRequestType::BackgroundRefreshStatus => {
+            BackgroundRefreshStatus {
+                running: true,
+                interval_ms: 2_000,
+                accounts: vec![RefreshAccount {
+                    near_account_id: "alice.testnet".to_string(),
+                    public_key: PUBLIC_KEY.to_string(),
+                }],
+                paused: Some("suspended".to_string()),
+                consecutive_failures: 1,
+                next_refresh_at_ms: Some(1.0),
+                last_success_at_ms: Some(1.0),
+                last_error_code: Some("RpcUnreachable".to_string()),
+                last_error: Some("RpcUnreachable: offline".to_string()),
+                last_error_at_ms: Some(1.0),
+                block: Some(RefreshedBlock {
+                    height: "100".to_string(),
+                    hash: "11111111111111111111111111111111".to_string(),
+                    refreshed_at_ms: 1.0,
+                }),
+                nonces: vec![RefreshedNonce {
+                    near_account_id: "alice.testnet".to_string(),
+                    public_key: PUBLIC_KEY.to_string(),
+                    next_nonce: "43".to_string(),
+                    refreshed_at_ms: 1.0,
+                }],
+            }
+            .to_json()
+        }
+        WorkerRequestType::StopBackgroundRefresh => {
+            StopBackgroundRefreshResult { was_running: true }.to_json()
+        }
+        WorkerRequestType::SuspendHint | WorkerRequestType::ResumeHint => SuspendHintResult {
+            was_suspended: true,
+        }
+        .to_json(),
+        WorkerRequestType::DeriveSubKey => DeriveSubKeyResult {
+            near_account_id: "alice.testnet".to_string(),
+            path: "m/44'/397'/0'/0'/1'".to_string(),
+            public_key: PUBLIC_KEY.to_string(),
+            encrypted_data: Some("data".to_string()),
+            iv: Some("iv".to_string()),
+            outer_wrap: Some("wrap".to_string()),
+        }
+        .to_json(),
+        WorkerRequestType::ViewCall => ViewCallResult {
+            result_json: Some(json!("hello")),
+            result_base64: "ImhlbGxvIg==".to_string(),
+            logs: vec!["log".to_string()],
+            block_height: "100".to_string(),
+            block_hash: "11111111111111111111111111111111".to_string(),
+        }
+        .to_json(),
+        WorkerRequestType::SignTransactionsWithFreshChallenge => {
+            SignTransactionsWithFreshChallengeResult {
+                sign_result: transaction_sign_result(),
+                failed_phase: Some("verification".to_string()),
+            }
+            .to_json()
+        }
+        WorkerRequestType::GetSessionStatus => SessionStatus {
+            epoch: 1,
+            started_at_ms: Some(1_000.0),
+            max_session_duration_ms: Some(3_600_000),
+            expires_at_ms: Some(3_601_000.0),
+            remaining_ms: Some(600_000.0),
+            expired: false,
+        }
+        .to_json(),
+        WorkerRequestType::IssueElevationChallenge => IssueElevationChallengeResult {
+            challenge: "AAAA".to_string(),
+            issued_at_ms: 1_000.0,
+        }
+        .to_json(),
+        WorkerRequestType::BuildRedirectPayload => BuildRedirectPayloadResult {
+            token: "AAAA".to_string(),
+            url: "https://app.example.com/signed-in?w3a_payload=AAAA&state=xyz".to_string(),
+            session_public_key: PUBLIC_KEY.to_string(),
+            expires_at_ms: 301_000.0,
+        }
+        .to_json(),
+        WorkerRequestType::DumpDiagnostics => DumpDiagnosticsResult {
+            document: "{}".to_string(),
+            digest: "44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a".to_string(),
+        }
+        .to_json(),
+        WorkerRequestType::GetMemoryStats => memory_stats().to_json(),
+        WorkerRequestType::TrimCaches => TrimCachesResult {
+            freed_heap_bytes: 1.0,
+            dropped_transient_buffer_bytes: 1.0,
+            dropped_expired_session_keys: 1,
+            stats: memory_stats(),
+        }
+        .to_json(),
+        WorkerRequestType::GetRecentReceivers => GetRecentReceiversResult {
+            account_id: "alice.testnet".to_string(),
+            receivers: vec![RecentReceiver {
+                receiver_id: "bob.testnet".to_string(),
+                last_seen_ms: 1.0,
+            }],
+            source: "worker".to_string(),
+        }
+        .to_json(),
+        WorkerRequestType::BuildAccountDescriptor => BuildAccountDescriptorResult {
+            descriptor: "descriptor".to_string(),
+            near_public_key: PUBLIC_KEY.to_string(),
+            expires_at_ms: 1.0,
+        }
+        .to_json(),
+        WorkerRequestType::DeployLargeContract => DeployLargeContractResult {
+            sign_result: transaction_sign_result(),
+            manifest: DeployManifest {
+                total_bytes: 1,
+                chunk_size: 1,
+                chunk_hashes: vec!["hash".to_string()],
+                total_hash: "hash".to_string(),
+            },
+        }
+        .to_json(),
+        WorkerRequestType::RunSelfTest => SelfTestReport {
+            passed: false,
+            results: vec![SelfTestResult {
+                primitive: "ed25519".to_string(),
+                passed: false,
+                duration_ms: 1.0,
+                error: Some("error".to_string()),
+            }],
+            total_duration_ms: 1.0,
+            failed_primitive: Some("ed25519".to_string()),
+        }
+        .to_json(),
+        WorkerRequestType::ExportAccountBundle => ExportAccountBundleResult {
+            bundle: vec![1],
+            version: 1,
+            record_count: 1,
+        }
+        .to_json(),
+        WorkerRequestType::ImportAccountBundle => ImportAccountBundleResult {
+            // Stored records are passed through as the storage layer wrote them
+            records: AccountBundleRecords::default(),
+            version: 1,
+            exported_at_ms: 1.0,
+        }
+        .to_json(),
+        WorkerRequestType::CreateSigningIntent => CreateSigningIntentResult {
+            intent_token: "token".to_string(),
+            intent_id: "intent".to_string(),
+            near_account_id: "alice.testnet".to_string(),
+            intent_digest: "digest".to_string(),
+            tx_count: 1,
+            expires_at_ms: 1.0,
+        }
+        .to_json(),
+        #[cfg(feature = "relayer")]
+        WorkerRequestType::SubmitToRelayer | WorkerRequestType::CompleteRegistration => {
+            relayer_result().to_json()
+        }
+        #[cfg(feature = "telemetry")]
+        WorkerRequestType::GetTelemetrySnapshot => crate::telemetry::TelemetrySnapshot {
+            handlers: vec![crate::telemetry::HandlerTelemetry {
+                handler: "SIGN_TRANSACTIONS_WITH_ACTIONS".to_string(),
+                requests: 1,
+                successes: 1,
+                failures: 1,
+                failure_codes: vec![crate::telemetry::FailureCodeCount {
+                    code: "UserDeclined".to_string(),
+                    count: 1,
+                }],
+                latency_buckets: vec![1],
+                p50_latency_ms: Some(100),
+                p95_latency_ms: Some(100),
+            }],
+            latency_bucket_bounds_ms: vec![100],
+            remote_endpoint: Some("https://telemetry.example".to_string()),
+            sampling_rate: Some(1.0),
+            window_start_ms: Some(1.0),
+            reports_sent: 1,
+        }
+        .to_json(),
+        WorkerRequestType::GetKeyUsageStats => KeyUsageStats {
+            keys: vec![KeyUsageStat {
+                key_id: "key".to_string(),
+                sign_count: 1,
+                last_used_ms: 1.0,
+                last_receiver_category: "named".to_string(),
+                last_receiver_hash: "hash".to_string(),
+                verified: true,
+            }],
+            tampered_key_ids: vec!["key".to_string()],
+        }
+        .to_json(),
+        WorkerRequestType::PrepareRegistration => PrepareRegistrationResult {
+            keys: derive_result(),
+            resume_token: "token".to_string(),
+            resume_expires_at_ms: 1.0,
+        }
+        .to_json(),
+        #[cfg(feature = "device-linking")]
+        WorkerRequestType::CreateRemoteConfirmation => {
+            crate::handlers::handle_remote_confirmation::CreateRemoteConfirmationResult {
+                confirmation_request: "request".to_string(),
+                request_id: "request".to_string(),
+                session_public_key: "session".to_string(),
+                intent_digest: "digest".to_string(),
+                expires_at_ms: 1.0,
+            }
+            .to_json()
+        }
+        #[cfg(feature = "device-linking")]
+        WorkerRequestType::ApproveRemoteConfirmation => {
+            crate::handlers::handle_remote_confirmation::ApproveRemoteConfirmationResult {
+                approval: "approval".to_string(),
+                request_id: "request".to_string(),
+            }
+            .to_json()
+        }
+        WorkerRequestType::GetWorkerInfo => WorkerInfo::current().to_json(),
+        WorkerRequestType::ValidateDecryptionCapability => DecryptionCapability {
+            can_decrypt: false,
+            reason: Some("WrongCredentialForBlob".to_string()),
+        }
+        .to_json(),
+        WorkerRequestType::ListActiveAccounts => ListActiveAccountsResult {
+            accounts: vec![ActiveAccountEntry {
+                account_id: "alice.testnet".to_string(),
+                signed_requests: 1,
+                failed_requests: 1,
+                nonce_reservations: 1,
+                audit_entries: 2,
+                session_keys: 1,
+                last_active_ms: 1.0,
+            }],
+        }
+        .to_json(),
+        WorkerRequestType::WipeAccountState => WipeAccountStateResult {
+            account_id: "alice.testnet".to_string(),
+            found: true,
+            released_nonce_reservations: 1,
+            cleared_audit_entries: 2,
+            cleared_session_keys: 1,
+        }
+        .to_json(),
+        WorkerRequestType::ValidateArgsSchemas => ValidateArgsSchemasResult {
+            valid: false,
+            schema_count: 2,
+            errors: vec![ArgsSchemaErrorEntry {
+                contract_id: "usdc.testnet".to_string(),
+                method_name: "ft_transfer".to_string(),
+                path: "/properties/amount/pattern".to_string(),
+                message: "unclosed group at offset 0".to_string(),
+            }],
+        }
+        .to_json(),
+        WorkerRequestType::SummarizeTransactions => SummarizeTransactionsResult {
+            preview_digest: "digest".to_string(),
+            transaction_count: 1,
+            removed_duplicate_indexes: vec![1],
+        }
+        .to_json(),
+        WorkerRequestType::MigrateEncryptedBlobs => MigrateEncryptedBlobsResult {
+            migrated: 0,
+            already_current: 0,
+            failed: 1,
+            reports: vec![BlobMigrationReport {
+                index: 0,
+                blob_id: Some("alice.testnet#1".to_string()),
+                kind: StoredBlobKind::NearKey,
+                status: BlobMigrationStatus::Failed,
+                from_version: Some(1),
+                to_version: None,
+                blob: json!({}),
+                error_code: Some("DecryptionFailed".to_string()),
+                error: Some("DecryptionFailed: Decryption error: aead::Error".to_string()),
+            }],
+        }
+        .to_json(),
+        WorkerRequestType::BuildCredentialCreationOptions => {
+            let snapshot: Value =
+                serde_json::from_str(CREDENTIAL_CREATION_OPTIONS_SNAPSHOT).unwrap();
+            BuildCredentialCreationOptionsResult {
+                public_key: serde_json::from_value(snapshot["publicKey"].clone()).unwrap(),
+            }
+            .to_json()
+        }
+        WorkerRequestType::BuildCredentialRequestOptions => {
+            let snapshot: Value =
+                serde_json::from_str(CREDENTIAL_REQUEST_OPTIONS_SNAPSHOT).unwrap();
+            BuildCredentialRequestOptionsResult {
+                public_key: serde_json::from_value(snapshot["publicKey"].clone()).unwrap(),
+                discoverable: false,
+            }
+            .to_json()
+        }
+        WorkerRequestType::RecoverPendingOperations => {
+            let mut broadcast =
+                JournalEntry::broadcast("req-1", "tx-hash", "alice.testnet", Some("key-1"));
+            broadcast.created_at_ms = 0.0;
+            let mut relayer_submission = JournalEntry::relayer_submission("req-2", "bob.testnet");
+            relayer_submission.created_at_ms = 0.0;
+            RecoverPendingOperationsResult {
+                operations: vec![
+                    RecoveredOperation {
+                        entry: broadcast,
+                        status: RecoveryStatus::Confirmed,
+                        succeeded: Some(true),
+                        detail: None,
+                    },
+                    RecoveredOperation {
+                        entry: relayer_submission,
+                        status: RecoveryStatus::Unknown,
+                        succeeded: None,
+                        detail: Some("Transport error: timeout".to_string()),
+                    },
+                ],
+                unresolved: 1,
+            }
+            .to_json()
+        }
+        // Handlers this build left out
+        #[cfg(not(all(
+            feature = "nep413",
+            feature = "device-linking",
+            feature = "relayer",
+            feature = "telemetry"
+        )))]
+        _ => unreachable!("{} is not compiled into this build", request_type.name()),
+    };
+    result.unwrap()
+}
+
+fn collect_key_paths(value: &Value, prefix: &str, paths: &mut BTreeSet<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                paths.insert(path.clone());
+                collect_key_paths(child, &path, paths);
+            }
+        }
+        Value::Array(items) => {
+            let path = format!("{}[]", prefix);
+            for item in items {
+                collect_key_paths(item, &path, paths);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The payload as JS reads it: through the dispatcher's response frame, in both wire formats
+fn js_visible_payload(payload: Value) -> Value {
+    let response = SignerWorkerResponse {
+        response_type: 0,
+        payload,
+        warnings: vec![],
+    };
+    let from_json: SignerWorkerResponse =
+        serde_json::from_str(&encode_json_response(&response).unwrap()).unwrap();
+    let cbor_frame = encode_cbor_response(&response).unwrap();
+    let value: ciborium::Value = ciborium::from_reader(cbor_frame.as_slice()).unwrap();
+    let from_cbor: SignerWorkerResponse =
+        serde_json::from_value(cbor_to_json(value).unwrap()).unwrap();
+    assert_eq!(from_json.payload, from_cbor.payload);
+    from_json.payload
+}
+
+fn is_compiled_request(request_type: WorkerRequestType) -> bool {
+    required_feature(request_type).is_none_or(is_compiled)
+}
+
+#[test]
+fn test_handler_result_keys_match_snapshot() {
+    let mut actual: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for msg_type in 0..=59u32 {
+        let request_type = WorkerRequestType::from(msg_type);
+        if !is_compiled_request(request_type) {
+            continue;
+        }
+        let payload = js_visible_payload(sample_result(request_type));
+        assert!(
+            payload.is_object(),
+            "{} is not an object",
+            request_type.name()
+        );
+
+        let mut paths = BTreeSet::new();
+        collect_key_paths(&payload, "", &mut paths);
+        for path in &paths {
+            let key = path.rsplit('.').next().unwrap().trim_end_matches("[]");
+            assert!(
+                key.starts_with(|c: char| c.is_ascii_lowercase())
+                    && key.chars().all(|c| c.is_ascii_alphanumeric()),
+                "{} has a key that is not camelCase: {}",
+                request_type.name(),
+                path
+            );
+        }
+        actual.insert(request_type.name().to_string(), paths.into_iter().collect());
+    }
+
+    let mut expected: BTreeMap<String, Vec<String>> =
+        serde_json::from_str(RESPONSE_KEYS_SNAPSHOT).unwrap();
+    for msg_type in 0..=59u32 {
+        let request_type = WorkerRequestType::from(msg_type);
+        if !is_compiled_request(request_type) {
+            expected.remove(request_type.name());
+        }
+    }
+    assert!(
+        actual == expected,
+        "Handler result keys differ from tests/snapshots/response_keys.json; if intended, \
+         replace it with:\n{}",
+        serde_json::to_string_pretty(&actual).unwrap()
+    );
+}
+
+#[test]
+fn test_signed_transaction_bytes_are_a_camel_case_number_array() {
+    let payload = js_visible_payload(transaction_sign_result().to_json().unwrap());
+    let signed = &payload["signedTransactions"][0];
+    assert_eq!(signed["borshBytes"], json!([4, 5, 6]));
+    assert!(signed.get("borsh_bytes").is_none());
+    assert_eq!(signed["transaction"]["publicKey"]["keyType"], json!(0));
+}
@@ -0,0 +1,522 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use crate::bench::*;
+use crate::config::INVALID_NONCE_MAX_RESIGNS;
+use crate::encoders::base64_url_encode;
+use crate::error::{RpcErrorKind, SignerErrorCode};
+use crate::handlers::handle_check_can_register_user::*;
+use crate::handlers::handle_sign_transactions_with_actions::{
+    sign_near_transactions_with_actions_impl, TransactionSignResult,
+};
+use crate::rpc_calls::{view_account_exists_rpc_call, HttpJsonResponse};
+use crate::rpc_client::*;
+use crate::tests::block_on;
+use crate::view_call::{view_call, view_call_params};
+use serde_json::{json, Value};
+
+const PRIMARY: &str = "https://rpc-a.invalid";
+const FALLBACK: &str = "https://rpc-b.invalid";
+
+/// One scripted answer per request, in order; every requested URL and body is recorded
+#[derive(Default)]
+struct ScriptedTransport {
+    answers: RefCell<VecDeque<Result<HttpJsonResponse, String>>>,
+    requests: RefCell<Vec<(String, Value)>>,
+}
+
+impl ScriptedTransport {
+    fn new(answers: Vec<Result<HttpJsonResponse, String>>) -> Self {
+        ScriptedTransport {
+            answers: RefCell::new(answers.into()),
+            ..Default::default()
+        }
+    }
+
+    fn urls(&self) -> Vec<String> {
+        self.requests
+            .borrow()
+            .iter()
+            .map(|(url, _)| url.clone())
+            .collect()
+    }
+}
+
+impl JsonTransport for ScriptedTransport {
+    async fn request(&self, url: &str, body: Option<&Value>) -> Result<HttpJsonResponse, String> {
+        self.requests
+            .borrow_mut()
+            .push((url.to_string(), body.cloned().unwrap_or(Value::Null)));
+        self.answers
+            .borrow_mut()
+            .pop_front()
+            .unwrap_or_else(|| Err("no scripted answer".to_string()))
+    }
+}
+
+fn http(status: u16, body: Value) -> Result<HttpJsonResponse, String> {
+    Ok(HttpJsonResponse {
+        status,
+        body: Some(body),
+    })
+}
+
+fn two_endpoints(transport: &ScriptedTransport) -> NearRpcClient<&ScriptedTransport> {
+    NearRpcClient::with_transport(transport, &format!("{}, {}", PRIMARY, FALLBACK))
+}
+
+/// The node's error for a transaction whose nonce the access key has already used
+fn invalid_nonce_error(tx_nonce: u64, ak_nonce: u64) -> Value {
+    json!({
+        "name": "HANDLER_ERROR",
+        "cause": { "name": "INVALID_TRANSACTION", "info": {} },
+        "code": -32000,
+        "message": "Server error",
+        "data": {
+            "TxExecutionError": {
+                "InvalidTxError": { "InvalidNonce": { "tx_nonce": tx_nonce, "ak_nonce": ak_nonce } }
+            }
+        }
+    })
+}
+
+fn final_block_answer(height: u64) -> Result<Value, RpcErrorKind> {
+    Ok(json!({ "header": { "height": height, "hash": bs58::encode([9u8; 32]).into_string() } }))
+}
+
+fn sign_and_broadcast(batch: &ConfirmedBatch, rpc: &MockRpcClient) -> TransactionSignResult {
+    block_on(sign_near_transactions_with_actions_impl(
+        batch.tx_requests.clone(),
+        &batch.decryption,
+        &batch.confirmation,
+        Some(rpc),
+        None,
+        None,
+        Vec::new(),
+    ))
+    .unwrap()
+}
+
+fn nonces(result: &TransactionSignResult) -> Vec<u64> {
+    result
+        .signed_transactions
+        .iter()
+        .flatten()
+        .map(|tx| tx.transaction.nonce)
+        .collect()
+}
+
+#[test]
+fn test_invalid_nonce_is_re_signed_on_a_fresh_nonce() {
+    let batch = confirmed_batch_of_5();
+    let rpc = MockRpcClient::new()
+        .answer(
+            "broadcast_tx_commit",
+            Err(rpc_error_kind(&invalid_nonce_error(100, 120))),
+        )
+        .answer(
+            "broadcast_tx_commit",
+            Ok(json!({ "status": { "SuccessValue": "" } })),
+        )
+        .answer(
+            "query/view_access_key",
+            Ok(json!({ "nonce": 120, "block_height": 2000 })),
+        )
+        .answer("block", final_block_answer(2001));
+
+    let result = sign_and_broadcast(&batch, &rpc);
+    assert!(result.success, "{:?}", result.error);
+    // The rejected transaction was rebuilt on nonce 121; the rest of the batch follows it
+    assert_eq!(nonces(&result), vec![121, 122, 123, 124, 125]);
+    let signed = result.signed_transactions.as_ref().unwrap();
+    assert!(signed
+        .iter()
+        .all(|tx| tx.transaction.block_hash == vec![9u8; 32]));
+    assert_eq!(result.broadcast_outcomes.as_ref().unwrap().len(), 5);
+    assert_eq!(rpc.calls_to("broadcast_tx_commit").len(), 6);
+    assert!(result.broadcast_rpc_url.is_none());
+
+    // Nonce and block were fetched together, for the signing key
+    let calls: Vec<String> = rpc
+        .calls
+        .borrow()
+        .iter()
+        .map(|(route, _)| route.clone())
+        .collect();
+    assert_eq!(
+        &calls[1..3],
+        &["query/view_access_key".to_string(), "block".to_string()]
+    );
+    let access_key_query = &rpc.calls_to("query/view_access_key")[0];
+    assert_eq!(access_key_query["account_id"], json!(BENCH_ACCOUNT_ID));
+    assert!(access_key_query["public_key"]
+        .as_str()
+        .unwrap()
+        .starts_with("ed25519:"));
+}
+
+#[test]
+fn test_invalid_nonce_retry_is_bounded() {
+    let batch = confirmed_batch_of_5();
+    let rpc = MockRpcClient::new()
+        .answer(
+            "broadcast_tx_commit",
+            Err(rpc_error_kind(&invalid_nonce_error(100, 150))),
+        )
+        .answer("query/view_access_key", Ok(json!({ "nonce": 120 })))
+        .answer("block", final_block_answer(2001));
+
+    let result = sign_and_broadcast(&batch, &rpc);
+    assert!(!result.success);
+    assert_eq!(result.error_code.as_deref(), Some("InvalidNonce"));
+    assert_eq!(
+        rpc.calls_to("broadcast_tx_commit").len(),
+        1 + INVALID_NONCE_MAX_RESIGNS as usize
+    );
+    assert_eq!(result.transaction_hashes, Some(vec![]));
+}
+
+#[test]
+fn test_broadcast_failure_reports_the_transactions_already_on_chain() {
+    let batch = confirmed_batch_of_5();
+    let node_error = json!({ "name": "HANDLER_ERROR", "cause": { "name": "INVALID_TRANSACTION" } });
+    let rpc = MockRpcClient::new()
+        .answer(
+            "broadcast_tx_commit",
+            Ok(json!({ "status": { "SuccessValue": "" } })),
+        )
+        .answer(
+            "broadcast_tx_commit",
+            Ok(json!({ "status": { "SuccessValue": "" } })),
+        )
+        .answer("broadcast_tx_commit", Err(rpc_error_kind(&node_error)));
+
+    let result = sign_and_broadcast(&batch, &rpc);
+    assert!(!result.success);
+    assert_eq!(result.error_code.as_deref(), Some("RpcRequestFailed"));
+    assert!(result
+        .error
+        .unwrap()
+        .starts_with("Transaction 3: Broadcast failed"));
+    assert_eq!(result.transaction_hashes.unwrap().len(), 2);
+    assert_eq!(result.broadcast_outcomes.unwrap().len(), 2);
+    // No re-sign for errors other than InvalidNonce
+    assert!(rpc.calls_to("query/view_access_key").is_empty());
+
+    // Without a broadcast client the batch is only signed
+    let result = block_on(sign_near_transactions_with_actions_impl(
+        batch.tx_requests.clone(),
+        &batch.decryption,
+        &batch.confirmation,
+        None::<&MockRpcClient>,
+        None,
+        None,
+        Vec::new(),
+    ))
+    .unwrap();
+    assert!(result.success);
+    assert_eq!(nonces(&result), vec![100, 101, 102, 103, 104]);
+    assert!(result.broadcast_outcomes.is_none());
+}
+
+#[test]
+fn test_unreachable_endpoints_fail_over() {
+    let transport = ScriptedTransport::new(vec![
+        Err("connection refused".to_string()),
+        http(200, json!({ "jsonrpc": "2.0", "result": { "nonce": 7 } })),
+    ]);
+    let rpc = two_endpoints(&transport);
+    assert_eq!(
+        block_on(rpc.call("query", json!({}))),
+        Ok(json!({ "nonce": 7 }))
+    );
+    assert_eq!(transport.urls(), vec![PRIMARY, FALLBACK]);
+    let (_, body) = transport.requests.borrow()[0].clone();
+    assert_eq!(body["method"], json!("query"));
+    assert_eq!(body["id"], json!("query_from_wasm"));
+
+    let transport = ScriptedTransport::new(vec![
+        http(503, json!({})),
+        http(200, json!({ "result": true })),
+    ]);
+    assert_eq!(
+        block_on(two_endpoints(&transport).call("status", json!([]))),
+        Ok(json!(true))
+    );
+    assert_eq!(transport.urls().len(), 2);
+
+    // A node that answered with an error is not retried elsewhere
+    let transport = ScriptedTransport::new(vec![http(
+        200,
+        json!({ "error": { "name": "HANDLER_ERROR", "cause": { "name": "UNKNOWN_ACCOUNT" } } }),
+    )]);
+    let error = block_on(two_endpoints(&transport).call("query", json!({}))).unwrap_err();
+    assert!(matches!(&error, RpcErrorKind::Rpc { name, .. } if name == "UNKNOWN_ACCOUNT"));
+    assert_eq!(error.code(), SignerErrorCode::RpcRequestFailed);
+    assert_eq!(transport.urls(), vec![PRIMARY]);
+
+    let unreachable = ScriptedTransport::new(vec![]);
+    let error = block_on(two_endpoints(&unreachable).call("block", json!({}))).unwrap_err();
+    assert_eq!(error.code(), SignerErrorCode::RpcUnreachable);
+    assert_eq!(unreachable.urls().len(), 2);
+    let empty_url = NearRpcClient::with_transport(&unreachable, " , ");
+    assert!(matches!(
+        block_on(empty_url.call("block", json!({}))),
+        Err(RpcErrorKind::Unreachable(_))
+    ));
+}
+
+#[test]
+fn test_timeout_mapping() {
+    for status in [408, 504] {
+        let response = HttpJsonResponse { status, body: None };
+        let error = parse_rpc_response(PRIMARY, response).unwrap_err();
+        assert_eq!(error.code(), SignerErrorCode::RpcTimeout);
+        assert!(error.is_failover());
+    }
+    let node_timeout = json!({ "name": "HANDLER_ERROR", "cause": { "name": "TIMEOUT_ERROR" } });
+    assert!(matches!(
+        rpc_error_kind(&node_timeout),
+        RpcErrorKind::Timeout(_)
+    ));
+
+    // Every endpoint timing out surfaces as a coded timeout, not as a node error
+    let transport = ScriptedTransport::new(vec![
+        http(504, json!({})),
+        http(200, json!({ "error": node_timeout })),
+    ]);
+    let rpc = two_endpoints(&transport);
+    let error = block_on(rpc.call("broadcast_tx_commit", json!(["dHg="]))).unwrap_err();
+    assert_eq!(error.code(), SignerErrorCode::RpcTimeout);
+    assert_eq!(transport.urls().len(), 2);
+    assert!(String::from(error).starts_with("RpcTimeout: "));
+
+    // ... and a view call that timed out is an error, never "the account does not exist"
+    let rpc = MockRpcClient::new().answer(
+        "query/view_account",
+        Err(RpcErrorKind::Timeout("view_account".to_string())),
+    );
+    assert!(block_on(view_account_exists_rpc_call(&rpc, "alice.testnet")).is_err());
+}
+
+#[test]
+fn test_view_call_fails_over_and_reads_contract_panics_from_the_node_error() {
+    let params = view_call_params("contract.testnet", "get_greeting", b"{}", &Default::default())
+        .unwrap();
+    let transport = ScriptedTransport::new(vec![
+        http(504, json!({})),
+        http(
+            200,
+            json!({ "result": { "result": [49], "logs": [], "block_height": 7, "block_hash": "11111111111111111111111111111111" } }),
+        ),
+    ]);
+    let rpc = two_endpoints(&transport);
+    let result = block_on(view_call(&rpc, params.clone())).unwrap();
+    assert_eq!(result.result_json, Some(json!(1)));
+    assert_eq!(transport.urls(), vec![PRIMARY, FALLBACK]);
+
+    // A panic is the contract's answer: no other endpoint is tried
+    let transport = ScriptedTransport::new(vec![http(
+        200,
+        json!({ "error": {
+            "name": "HANDLER_ERROR",
+            "cause": {
+                "name": "CONTRACT_EXECUTION_ERROR",
+                "info": { "vm_error": "wasm execution failed with error: FunctionCallError(ExecutionError(\"Smart contract panicked: closed\"))" }
+            },
+            "message": "Server error"
+        } }),
+    )]);
+    let rpc = two_endpoints(&transport);
+    let error = block_on(view_call(&rpc, params)).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "ContractPanic: Smart contract panicked: closed"
+    );
+    assert_eq!(transport.urls(), vec![PRIMARY]);
+}
+
+#[test]
+fn test_invalid_nonce_is_found_wherever_the_node_puts_it() {
+    assert_eq!(
+        rpc_error_kind(&invalid_nonce_error(5, 7)),
+        RpcErrorKind::InvalidNonce {
+            tx_nonce: 5,
+            ak_nonce: 7
+        }
+    );
+    let in_cause = json!({
+        "name": "HANDLER_ERROR",
+        "cause": {
+            "name": "INVALID_TRANSACTION",
+            "info": { "InvalidNonce": { "tx_nonce": 9, "ak_nonce": 9 } }
+        }
+    });
+    assert_eq!(
+        rpc_error_kind(&in_cause).code(),
+        SignerErrorCode::InvalidNonce
+    );
+}
+
+#[test]
+fn test_batch_rpc_fetches_nonce_and_block_together() {
+    let rpc = MockRpcClient::new()
+        .answer(
+            "query/view_access_key",
+            Ok(json!({ "nonce": 41, "block_height": 10 })),
+        )
+        .answer("block", final_block_answer(11));
+    let (nonce, block) = block_on(refresh_nonce_and_block(
+        &rpc,
+        "alice.testnet",
+        "ed25519:key",
+    ))
+    .unwrap();
+    assert_eq!(nonce, 42);
+    assert_eq!(
+        block,
+        BlockReference {
+            height: 11,
+            hash: bs58::encode([9u8; 32]).into_string()
+        }
+    );
+    let query = &rpc.calls_to("query/view_access_key")[0];
+    assert_eq!(query["public_key"], json!("ed25519:key"));
+    assert_eq!(rpc.calls_to("block")[0], json!({ "finality": "final" }));
+
+    // Each call of a batch fails on its own
+    let rpc = MockRpcClient::new()
+        .answer("query/view_access_key", Ok(json!({ "nonce": 41 })))
+        .answer("block", Err(RpcErrorKind::Unreachable("down".to_string())));
+    let results = block_on(rpc.call_batch(vec![
+        ("query", json!({ "request_type": "view_access_key" })),
+        ("block", json!({ "finality": "final" })),
+    ]));
+    assert_eq!(results[0], Ok(json!({ "nonce": 41 })));
+    assert!(results[1].is_err());
+    assert_eq!(
+        block_on(refresh_nonce_and_block(
+            &rpc,
+            "alice.testnet",
+            "ed25519:key"
+        ))
+        .unwrap_err()
+        .code(),
+        SignerErrorCode::RpcUnreachable
+    );
+
+    // Over JSON-RPC the batch is sent one call after another, in order
+    let transport = ScriptedTransport::new(vec![
+        http(200, json!({ "result": { "nonce": 3 } })),
+        http(
+            200,
+            json!({ "result": { "header": { "height": 5, "hash": "11111111111111111111111111111111" } } }),
+        ),
+    ]);
+    let rpc = NearRpcClient::with_transport(&transport, PRIMARY);
+    let (nonce, block) = block_on(refresh_nonce_and_block(
+        &rpc,
+        "alice.testnet",
+        "ed25519:key",
+    ))
+    .unwrap();
+    assert_eq!((nonce, block.height), (4, 5));
+    let ids: Vec<Value> = transport
+        .requests
+        .borrow()
+        .iter()
+        .map(|(_, body)| body["id"].clone())
+        .collect();
+    assert_eq!(
+        ids,
+        vec![json!("query_from_wasm"), json!("block_from_wasm")]
+    );
+}
+
+fn dry_run_request() -> CheckCanRegisterUserRequest {
+    let vrf_output = [7u8; 64];
+    let client_data = json!({
+        "type": "webauthn.create",
+        "challenge": base64_url_encode(&vrf_output[..32]),
+        "origin": "https://example.com",
+    });
+    serde_json::from_value(json!({
+        "vrfChallenge": {
+            "vrfInput": base64_url_encode(&[1u8; 32]),
+            "vrfOutput": base64_url_encode(&vrf_output),
+            "vrfProof": base64_url_encode(&[2u8; 80]),
+            "vrfPublicKey": base64_url_encode(&[3u8; 32]),
+            "userId": "alice.testnet",
+            "rpId": "example.com",
+            "blockHeight": "100",
+            "blockHash": "11111111111111111111111111111111"
+        },
+        "credential": {
+            "id": "cred",
+            "rawId": "cred",
+            "type": "public-key",
+            "authenticatorAttachment": null,
+            "response": {
+                "clientDataJSON": base64_url_encode(client_data.to_string().as_bytes()),
+                "attestationObject": bench_attestation_object_b64u(),
+                "transports": []
+            },
+            "clientExtensionResults": {
+                "prf": {
+                    "results": {
+                        "first": null,
+                        "second": bench_prf_outputs().ed25519_prf_output_base64
+                    }
+                }
+            }
+        },
+        "contractId": "w3a-v1.testnet",
+        "nearRpcUrl": "https://rpc.testnet.near.org",
+        "dryRun": true
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_registration_dry_run_on_the_mock_rpc() {
+    let dry_run = |rpc: &MockRpcClient| {
+        let result = block_on(check_can_register_user_with(rpc, dry_run_request())).unwrap();
+        result.dry_run.expect("dry run report")
+    };
+    let unknown_account = Err(rpc_error_kind(&json!({
+        "name": "HANDLER_ERROR",
+        "cause": { "name": "UNKNOWN_ACCOUNT" }
+    })));
+    let check_route = "query/call_function/check_can_register_user";
+
+    let rpc = MockRpcClient::new()
+        .answer("query/view_account", unknown_account.clone())
+        .answer(
+            check_route,
+            call_function_answer(&json!({ "verified": true })),
+        );
+    let report = dry_run(&rpc);
+    assert!(report.would_succeed, "{:?}", report.detail);
+    assert_eq!(
+        rpc.calls_to(check_route)[0]["finality"],
+        json!("optimistic")
+    );
+
+    let rpc = MockRpcClient::new().answer("query/view_account", Ok(json!({ "amount": "1" })));
+    let report = dry_run(&rpc);
+    assert_eq!(
+        report.outcome,
+        RegistrationDryRunOutcome::AccountExists.as_str()
+    );
+    assert!(rpc.calls_to(check_route).is_empty());
+
+    let rpc = MockRpcClient::new()
+        .answer("query/view_account", unknown_account)
+        .answer(check_route, Err(RpcErrorKind::Timeout("check".to_string())));
+    let report = dry_run(&rpc);
+    assert_eq!(
+        report.outcome,
+        RegistrationDryRunOutcome::RpcUnavailable.as_str()
+    );
+    assert!(report.detail.unwrap().starts_with("RpcTimeout: "));
+}
@@ -0,0 +1,209 @@
+use crate::error::{RpcOriginError, SignerErrorCode};
+use crate::handlers::{handle_sign_transactions_with_actions, SignTransactionsWithActionsRequest};
+use crate::rpc_endpoints::{resolve_rpc_endpoints, rpc_origin, RpcEndpoints};
+use crate::state;
+use crate::tests::block_on;
+use crate::types::AccountId;
+use crate::types::handlers::{RpcCallPayload, RpcOverrides, WorkerPolicy};
+use serde_json::json;
+
+const RPC_URL: &str = "https://rpc.testnet.near.org";
+const PROXY_URL: &str = "https://rpc-proxy.example.com/near";
+
+fn rpc_call() -> RpcCallPayload {
+    RpcCallPayload::new(
+        "w3a-v1.testnet".to_string(),
+        RPC_URL.to_string(),
+        "alice.testnet".to_string(),
+    )
+}
+
+fn policy(allowed_rpc_origins: &[&str]) -> WorkerPolicy {
+    WorkerPolicy {
+        allowed_rpc_origins: allowed_rpc_origins.iter().map(|o| o.to_string()).collect(),
+        ..WorkerPolicy::default()
+    }
+}
+
+fn overrides(verification: Option<&str>, broadcast: Option<&str>) -> RpcOverrides {
+    RpcOverrides {
+        verification: verification.map(str::to_string),
+        broadcast: broadcast.map(str::to_string),
+    }
+}
+
+#[test]
+fn test_rpc_origin_normalization() {
+    assert_eq!(
+        rpc_origin("HTTPS://RPC-Proxy.Example.com:443/near?x=1").as_deref(),
+        Some("https://rpc-proxy.example.com")
+    );
+    assert_eq!(
+        rpc_origin("http://localhost:3030").as_deref(),
+        Some("http://localhost:3030")
+    );
+    assert_eq!(
+        rpc_origin("http://[::1]:3030/").as_deref(),
+        Some("http://[::1]:3030")
+    );
+    assert_eq!(rpc_origin("http://[::1]").as_deref(), Some("http://[::1]"));
+
+    for url in [
+        "wss://rpc.example.com",
+        "rpc.example.com",
+        "https://",
+        "https://rpc.example.com:abc",
+        "https://rpc-proxy.example.com@evil.example/",
+    ] {
+        assert_eq!(rpc_origin(url), None, "{}", url);
+    }
+}
+
+#[test]
+fn test_overrides_on_allowed_origins_take_precedence() {
+    let allowed = policy(&[
+        "https://rpc-proxy.example.com",
+        "https://free.rpc.fastnear.com/",
+    ]);
+
+    let endpoints = resolve_rpc_endpoints(&rpc_call(), None, Some(&allowed)).unwrap();
+    assert_eq!(
+        endpoints,
+        RpcEndpoints {
+            verification: RPC_URL.to_string(),
+            broadcast: None,
+        }
+    );
+
+    let endpoints = resolve_rpc_endpoints(
+        &rpc_call(),
+        Some(&overrides(
+            Some(PROXY_URL),
+            Some("https://free.rpc.fastnear.com"),
+        )),
+        Some(&allowed),
+    )
+    .unwrap();
+    assert_eq!(endpoints.verification, PROXY_URL);
+    assert_eq!(
+        endpoints.broadcast.as_deref(),
+        Some("https://free.rpc.fastnear.com")
+    );
+
+    // A broadcast-only override keeps verification on rpcCall.nearRpcUrl
+    let endpoints = resolve_rpc_endpoints(
+        &rpc_call(),
+        Some(&overrides(None, Some(PROXY_URL))),
+        Some(&allowed),
+    )
+    .unwrap();
+    assert_eq!(endpoints.verification, RPC_URL);
+    assert_eq!(endpoints.broadcast.as_deref(), Some(PROXY_URL));
+}
+
+#[test]
+fn test_overrides_off_the_allowed_origins_are_refused() {
+    let allowed = policy(&["https://rpc-proxy.example.com"]);
+    for (verification, broadcast, endpoint, url) in [
+        (
+            Some("https://evil.example/rpc"),
+            None,
+            "verification",
+            "https://evil.example/rpc",
+        ),
+        (
+            Some("https://rpc-proxy.example.com.evil.example"),
+            None,
+            "verification",
+            "https://rpc-proxy.example.com.evil.example",
+        ),
+        (
+            Some("http://rpc-proxy.example.com"),
+            None,
+            "verification",
+            "http://rpc-proxy.example.com",
+        ),
+        (
+            Some(PROXY_URL),
+            Some("https://evil.example"),
+            "broadcast",
+            "https://evil.example",
+        ),
+    ] {
+        let err = resolve_rpc_endpoints(
+            &rpc_call(),
+            Some(&overrides(verification, broadcast)),
+            Some(&allowed),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            RpcOriginError::NotAllowed {
+                endpoint,
+                url: url.to_string(),
+            }
+        );
+        assert_eq!(err.code(), SignerErrorCode::RpcOriginNotAllowed);
+    }
+
+    // Without allowed origins no override is accepted
+    let with_proxy = overrides(Some(PROXY_URL), None);
+    assert!(resolve_rpc_endpoints(&rpc_call(), Some(&with_proxy), None).is_err());
+    assert!(resolve_rpc_endpoints(&rpc_call(), Some(&with_proxy), Some(&policy(&[]))).is_err());
+}
+
+#[test]
+fn test_disallowed_override_fails_signing_request_before_confirmation() {
+    let request: SignTransactionsWithActionsRequest = serde_json::from_value(json!({
+        "rpcCall": { "contractId": "w3a-v1.testnet", "nearRpcUrl": RPC_URL, "nearAccountId": "alice.testnet" },
+        "decryption": { "encryptedPrivateKeyData": "AAAA", "encryptedPrivateKeyIv": "BBBB" },
+        "txSigningRequests": [{
+            "nearAccountId": "alice.testnet",
+            "receiverId": "bob.testnet",
+            "actions": json!([{ "action_type": "Transfer", "deposit": "1" }]).to_string()
+        }],
+        "confirmationConfig": null,
+        "workerPolicy": { "allowedRpcOrigins": ["https://rpc-proxy.example.com"] },
+        "rpcOverrides": { "verification": "https://evil.example/rpc" }
+    }))
+    .unwrap();
+
+    let result = block_on(handle_sign_transactions_with_actions(request)).unwrap();
+    assert!(!result.success);
+    assert_eq!(result.error_code.as_deref(), Some("RpcOriginNotAllowed"));
+    assert!(
+        result
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("https://evil.example/rpc"),
+        "{:?}",
+        result.error
+    );
+    assert_eq!(result.broadcast_rpc_url, None);
+}
+
+#[test]
+fn test_audit_entry_records_rpc_endpoints() {
+    let endpoints = RpcEndpoints {
+        verification: PROXY_URL.to_string(),
+        broadcast: Some(RPC_URL.to_string()),
+    };
+    state::record_audit_with_rpc_endpoints(
+        &AccountId("alice.testnet".to_string()),
+        "rpc-endpoints-1",
+        "signTransactionsWithActions",
+        "Signed",
+        None,
+        Some(endpoints.clone()),
+    );
+    let entries = state::audit_entries_for("rpc-endpoints-1");
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].rpc_endpoints, Some(endpoints));
+
+    let serialized = serde_json::to_value(&entries[0]).unwrap();
+    assert_eq!(
+        serialized["rpcEndpoints"],
+        json!({ "verification": PROXY_URL, "broadcast": RPC_URL })
+    );
+}
@@ -0,0 +1,252 @@
+use crate::dispatch_signer_message;
+use crate::encoders::base64_url_decode;
+use crate::error::{SealedSecretError, SignerErrorCode};
+use crate::handlers::confirm_tx_details::ConfirmationResult;
+use crate::sealed_secrets::*;
+use crate::state;
+use crate::strict_parsing::check_unknown_fields;
+use crate::tests::block_on;
+use crate::types::worker_messages::{SignerWorkerMessage, WorkerRequestType, WorkerResponseType};
+use serde_json::{json, Value};
+
+const DECRYPT_PRIVATE_KEY_WITH_PRF: u32 = 3;
+const PRF_OUTPUT: &str = "cHJmLW91dHB1dC1ieXRlcw";
+
+/// RFC 7748 §6.1 private key of Bob, standing in for the worker's session key
+const TS_SESSION_SECRET: &str = "XasIfmJKikt54X-Lg4AO5m87sSkmGLb9HC-LJ_-I4Os";
+
+/// `PRF_OUTPUT` sealed for /chacha20PrfOutput to Bob's public key by sealedSecrets.ts, with
+/// RFC 7748 §6.1 Alice as the ephemeral key; __tests__/unit/sealedSecrets.test.ts checks the
+/// TS layer still produces exactly this box
+const TS_SEALED_BOX: &str =
+    "AYUg8AmJMKdUdIt93LQ-91oNvzoNJjga9OukqY6qm05qq6J8qIIj1yUhGtkUk4zQFS8acU95X4rM9LuRT_MolNELMA9iWEM";
+
+fn session_key() -> [u8; 32] {
+    base64_url_decode(&session_public_key().unwrap())
+        .unwrap()
+        .try_into()
+        .unwrap()
+}
+
+fn sealed(path: &str, plaintext: &str) -> String {
+    seal_secret(&session_key(), path, plaintext).unwrap()
+}
+
+fn decrypt_payload(extra: Value) -> Value {
+    let mut payload = json!({
+        "nearAccountId": "alice.testnet",
+        "encryptedPrivateKeyData": "AAAA",
+        "encryptedPrivateKeyIv": "BBBB"
+    });
+    payload
+        .as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    payload
+}
+
+fn invalid_reason(result: Result<(), SealedSecretError>) -> String {
+    match result {
+        Err(SealedSecretError::Invalid { reason, .. }) => reason,
+        other => panic!("expected Invalid, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_sealed_secret_opens_only_for_its_field_and_session() {
+    state::wipe_all_state();
+    let secret = state::sealed_secrets_key().unwrap();
+    let sealed_box = sealed("/chacha20PrfOutput", PRF_OUTPUT);
+    assert_eq!(
+        open_sealed_secret(&secret, "/chacha20PrfOutput", &sealed_box)
+            .unwrap()
+            .as_str(),
+        PRF_OUTPUT
+    );
+    // Bound to its field through the associated data
+    let error = open_sealed_secret(&secret, "/prfOutput", &sealed_box).unwrap_err();
+    assert_eq!(error.code(), SignerErrorCode::SealedSecretInvalid);
+
+    // Initialize and WipeAllState rotate the key: earlier boxes no longer open
+    state::rotate_sealed_secrets_key();
+    let rotated = state::sealed_secrets_key().unwrap();
+    assert_ne!(rotated, secret);
+    assert!(open_sealed_secret(&rotated, "/chacha20PrfOutput", &sealed_box).is_err());
+    state::wipe_all_state();
+    assert_ne!(state::sealed_secrets_key().unwrap(), rotated);
+
+    let error = open_sealed_secret(&secret, "/chacha20PrfOutput", "AQID").unwrap_err();
+    assert!(
+        error.to_string().contains("sealed box is 3 bytes"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn test_boxes_sealed_by_the_ts_layer_open() {
+    let secret: [u8; 32] = base64_url_decode(TS_SESSION_SECRET)
+        .unwrap()
+        .try_into()
+        .unwrap();
+    assert_eq!(
+        open_sealed_secret(&secret, "/chacha20PrfOutput", TS_SEALED_BOX)
+            .unwrap()
+            .as_str(),
+        PRF_OUTPUT
+    );
+    assert!(open_sealed_secret(&secret, "/prfOutput", TS_SEALED_BOX).is_err());
+}
+
+#[test]
+fn test_request_secrets_are_opened_into_the_payload() {
+    state::wipe_all_state();
+    let mut payload = decrypt_payload(json!({
+        "sealedSecrets": { "/chacha20PrfOutput": sealed("/chacha20PrfOutput", PRF_OUTPUT) }
+    }));
+    open_request_secrets(WorkerRequestType::DecryptPrivateKeyWithPrf, &mut payload).unwrap();
+    assert_eq!(payload["chacha20PrfOutput"], json!(PRF_OUTPUT));
+    assert!(payload.get("sealedSecrets").is_none());
+
+    // Nested fields are created on the way
+    let path = "/dualPrfOutputs/ed25519PrfOutput";
+    let mut payload = json!({ "sealedSecrets": { path: sealed(path, PRF_OUTPUT) } });
+    open_request_secrets(WorkerRequestType::PrepareRegistration, &mut payload).unwrap();
+    assert_eq!(
+        payload["dualPrfOutputs"]["ed25519PrfOutput"],
+        json!(PRF_OUTPUT)
+    );
+
+    // Only into the request type's secret fields
+    let mut payload = decrypt_payload(json!({
+        "sealedSecrets": { "/nearAccountId": sealed("/nearAccountId", "mallory.testnet") }
+    }));
+    let reason = invalid_reason(open_request_secrets(
+        WorkerRequestType::DecryptPrivateKeyWithPrf,
+        &mut payload,
+    ));
+    assert!(reason.contains("not a secret field"), "{}", reason);
+}
+
+#[test]
+fn test_require_encrypted_secrets_refuses_plaintext_until_wipe() {
+    state::wipe_all_state();
+    let mut plaintext = decrypt_payload(json!({ "chacha20PrfOutput": PRF_OUTPUT }));
+    assert_eq!(
+        open_request_secrets(WorkerRequestType::DecryptPrivateKeyWithPrf, &mut plaintext),
+        Ok(())
+    );
+
+    let mut payload = decrypt_payload(json!({
+        "chacha20PrfOutput": PRF_OUTPUT,
+        "workerPolicy": { "requireEncryptedSecrets": true }
+    }));
+    assert_eq!(
+        open_request_secrets(WorkerRequestType::DecryptPrivateKeyWithPrf, &mut payload),
+        Err(SealedSecretError::PlaintextRefused {
+            path: "/chacha20PrfOutput".to_string()
+        })
+    );
+
+    // Later requests cannot lift it by leaving the flag out
+    let mut plaintext = decrypt_payload(json!({ "chacha20PrfOutput": PRF_OUTPUT }));
+    let error = open_request_secrets(WorkerRequestType::DecryptPrivateKeyWithPrf, &mut plaintext)
+        .unwrap_err();
+    assert_eq!(error.code(), SignerErrorCode::PlaintextSecretRefused);
+    let mut sealed_payload = decrypt_payload(json!({
+        "sealedSecrets": { "/chacha20PrfOutput": sealed("/chacha20PrfOutput", PRF_OUTPUT) }
+    }));
+    assert_eq!(
+        open_request_secrets(
+            WorkerRequestType::DecryptPrivateKeyWithPrf,
+            &mut sealed_payload
+        ),
+        Ok(())
+    );
+
+    state::wipe_all_state();
+    assert!(!state::require_encrypted_secrets());
+}
+
+#[test]
+fn test_dispatch_refuses_plaintext_and_invalid_boxes() {
+    state::wipe_all_state();
+    let dispatch = |payload: Value| {
+        block_on(dispatch_signer_message(SignerWorkerMessage {
+            msg_type: DECRYPT_PRIVATE_KEY_WITH_PRF,
+            payload,
+            request_id: None,
+        }))
+        .unwrap()
+    };
+
+    let response = dispatch(decrypt_payload(json!({
+        "sealedSecrets": { "/chacha20PrfOutput": "not-a-box" }
+    })));
+    assert_eq!(
+        WorkerResponseType::from(response.response_type),
+        WorkerResponseType::DecryptPrivateKeyWithPrfFailure
+    );
+    assert_eq!(response.payload["errorCode"], json!("SealedSecretInvalid"));
+
+    let response = dispatch(decrypt_payload(json!({
+        "chacha20PrfOutput": PRF_OUTPUT,
+        "workerPolicy": { "requireEncryptedSecrets": true }
+    })));
+    assert_eq!(
+        response.payload["errorCode"],
+        json!("PlaintextSecretRefused")
+    );
+    assert_eq!(response.payload["errorCategory"], json!("Policy"));
+    state::wipe_all_state();
+}
+
+#[test]
+fn test_confirmation_secrets_are_opened_into_the_result() {
+    state::wipe_all_state();
+    let first = "/credential/clientExtensionResults/prf/results/first";
+    let mut result: ConfirmationResult = serde_json::from_value(json!({
+        "request_id": "req-sealed",
+        "confirmed": true,
+        "credential": { "id": "cred", "clientExtensionResults": { "prf": { "results": {} } } },
+        "sealed_secrets": {
+            "/prf_output": sealed("/prf_output", PRF_OUTPUT),
+            first: sealed(first, PRF_OUTPUT)
+        }
+    }))
+    .unwrap();
+    open_confirmation_secrets(&mut result).unwrap();
+    assert_eq!(result.prf_output.as_deref(), Some(PRF_OUTPUT));
+    assert_eq!(
+        result.credential.as_ref().unwrap().pointer(&first[11..]),
+        Some(&json!(PRF_OUTPUT))
+    );
+    assert_eq!(result.credential.as_ref().unwrap()["id"], json!("cred"));
+
+    state::set_require_encrypted_secrets();
+    let mut plaintext: ConfirmationResult = serde_json::from_value(json!({
+        "request_id": "req-plain",
+        "confirmed": true,
+        "prf_output": PRF_OUTPUT
+    }))
+    .unwrap();
+    assert_eq!(
+        open_confirmation_secrets(&mut plaintext),
+        Err(SealedSecretError::PlaintextRefused {
+            path: "/prf_output".to_string()
+        })
+    );
+    state::wipe_all_state();
+}
+
+#[test]
+fn test_strict_parsing_accepts_require_encrypted_secrets() {
+    let payload = json!({
+        "txSigningRequests": [],
+        "workerPolicy": { "strictParsing": true, "requireEncryptedSecrets": true }
+    });
+    assert_eq!(
+        check_unknown_fields(WorkerRequestType::SignTransactionsWithActions, &payload),
+        Ok(())
+    );
+}
@@ -0,0 +1,286 @@
+// === SECURITY REGRESSION CORPUS ===
+// Adversarial payloads against the worker's policy checks, each asserted to fail with the code
+// of the check that stops it. A refactor that weakens or reorders one of these checks turns
+// its fixture red; a new check gets its fixture here. The EncryptedVRFKeypair fixture lives
+// with the VRF worker's tests (wasm_vrf_worker/src/tests.rs), where the keypair is unlocked.
+
+use crate::batch_preview::canonical_batch_digest;
+use crate::confirmation_origin;
+use crate::encoders::base64_url_encode;
+use crate::error::SignerErrorCode;
+use crate::error_codes::error_code_of_message;
+use crate::handlers::confirm_tx_details::{
+    check_confirmation_response, register_confirmation_prompt, ConfirmationResult,
+};
+use crate::handlers::handle_check_can_register_user::{
+    handle_check_can_register_user, CheckCanRegisterUserRequest, RegistrationDryRunOutcome,
+};
+use crate::handlers::handle_sign_transactions_with_actions::TransactionSignResult;
+use crate::handlers::{
+    handle_sign_transactions_with_actions, SignTransactionsWithActionsRequest, TransactionPayload,
+};
+use crate::state;
+use crate::tests::block_on;
+use ciborium::value::Value as CborValue;
+use serde_json::{json, Value};
+
+const SIGNER: &str = "alice.testnet";
+const WALLET_ORIGIN: &str = "https://wallet.example.com";
+
+fn tx(actions: Value) -> TransactionPayload {
+    TransactionPayload {
+        near_account_id: SIGNER.to_string(),
+        receiver_id: "bob.testnet".to_string(),
+        actions: actions.to_string(),
+        depends_on: Vec::new(),
+    }
+}
+
+fn transfer(deposit: &str) -> Value {
+    json!({ "action_type": "Transfer", "deposit": deposit })
+}
+
+fn sign(txs: &[TransactionPayload], extra: Value) -> TransactionSignResult {
+    let mut payload = json!({
+        "rpcCall": {
+            "contractId": "w3a-v1.testnet",
+            "nearRpcUrl": "https://rpc.testnet.near.org",
+            "nearAccountId": SIGNER
+        },
+        "decryption": { "encryptedPrivateKeyData": "AAAA", "encryptedPrivateKeyIv": "BBBB" },
+        "txSigningRequests": txs,
+        "confirmationConfig": null
+    });
+    payload
+        .as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    let request: SignTransactionsWithActionsRequest = serde_json::from_value(payload).unwrap();
+    block_on(handle_sign_transactions_with_actions(request)).unwrap()
+}
+
+fn assert_refused(result: &TransactionSignResult, code: SignerErrorCode) {
+    assert!(!result.success);
+    assert_eq!(
+        result.error_code.as_deref(),
+        Some(code.as_str()),
+        "{:?}",
+        result.error
+    );
+}
+
+fn cbor(value: &CborValue) -> Vec<u8> {
+    let mut out = Vec::new();
+    ciborium::into_writer(value, &mut out).unwrap();
+    out
+}
+
+/// A "none" attestation object for an Ed25519 credential
+fn ed25519_attestation() -> String {
+    let cose_key = cbor(&CborValue::Map(vec![
+        (CborValue::Integer(1.into()), CborValue::Integer(1.into())),
+        (
+            CborValue::Integer(3.into()),
+            CborValue::Integer((-8).into()),
+        ),
+        (
+            CborValue::Integer((-1).into()),
+            CborValue::Integer(6.into()),
+        ),
+        (
+            CborValue::Integer((-2).into()),
+            CborValue::Bytes(vec![0x11; 32]),
+        ),
+    ]));
+    let mut auth_data = vec![0x49u8; 32];
+    auth_data.push(0x45); // UP UV AT
+    auth_data.extend_from_slice(&0u32.to_be_bytes());
+    auth_data.extend_from_slice(&[0u8; 16]);
+    auth_data.extend_from_slice(&3u16.to_be_bytes());
+    auth_data.extend_from_slice(&[1, 2, 3]);
+    auth_data.extend_from_slice(&cose_key);
+    base64_url_encode(&cbor(&CborValue::Map(vec![
+        (
+            CborValue::Text("fmt".to_string()),
+            CborValue::Text("none".to_string()),
+        ),
+        (
+            CborValue::Text("attStmt".to_string()),
+            CborValue::Map(vec![]),
+        ),
+        (
+            CborValue::Text("authData".to_string()),
+            CborValue::Bytes(auth_data),
+        ),
+    ])))
+}
+
+#[test]
+fn test_tx_tree_differing_from_the_signed_bytes() {
+    // The confirmation showed a 1 yocto transfer; the bytes to sign carry another amount
+    let previewed = [tx(json!([transfer("1")]))];
+    let preview_digest = canonical_batch_digest(&previewed).unwrap();
+    let signed = [tx(json!([transfer("1000000000000000000000000")]))];
+    let result = sign(&signed, json!({ "previewDigest": preview_digest }));
+    assert_refused(&result, SignerErrorCode::PayloadChangedSincePreview);
+}
+
+#[test]
+fn test_confirmation_decision_replayed_across_requests() {
+    confirmation_origin::configure(WALLET_ORIGIN).unwrap();
+    let prompt = |request_id: &str| {
+        let mut request_obj = json!({ "schemaVersion": 2, "requestId": request_id });
+        register_confirmation_prompt(&mut request_obj, request_id);
+        request_obj["renderNonce"].as_str().unwrap().to_string()
+    };
+    let decision = |request_id: &str, render_nonce: &str| -> ConfirmationResult {
+        serde_json::from_value(json!({
+            "request_id": request_id,
+            "confirmed": true,
+            "render_attestation": { "renderNonce": render_nonce, "origin": WALLET_ORIGIN },
+        }))
+        .unwrap()
+    };
+    let first_nonce = prompt("req-first");
+    prompt("req-second");
+
+    // The approval of the first prompt, relabeled for the second request
+    let error = check_confirmation_response(decision("req-second", &first_nonce), "req-second")
+        .unwrap_err();
+    assert_eq!(
+        error_code_of_message(&error).map(|def| def.code),
+        Some(SignerErrorCode::UntrustedConfirmationOrigin.as_str()),
+        "{}",
+        error
+    );
+    // Replayed as is, it names a request the second prompt is not
+    assert!(
+        check_confirmation_response(decision("req-first", &first_nonce), "req-second")
+            .unwrap_err()
+            .starts_with("Confirmation response for unknown request")
+    );
+
+    state::clear_confirmation_nonce("req-first");
+    state::clear_confirmation_nonce("req-second");
+    state::set_trusted_confirmation_origin(None);
+}
+
+#[test]
+fn test_credential_with_an_origin_for_another_rp_id() {
+    let vrf_output = [7u8; 64];
+    let client_data = json!({
+        "type": "webauthn.create",
+        "challenge": base64_url_encode(&vrf_output[..32]),
+        "origin": "https://example.com.attacker.net",
+    });
+    let request: CheckCanRegisterUserRequest = serde_json::from_value(json!({
+        "vrfChallenge": {
+            "vrfInput": base64_url_encode(&[1u8; 32]),
+            "vrfOutput": base64_url_encode(&vrf_output),
+            "vrfProof": base64_url_encode(&[2u8; 80]),
+            "vrfPublicKey": base64_url_encode(&[3u8; 32]),
+            "userId": SIGNER,
+            "rpId": "example.com",
+            "blockHeight": "100",
+            "blockHash": "11111111111111111111111111111111"
+        },
+        "credential": {
+            "id": "cred",
+            "rawId": "cred",
+            "type": "public-key",
+            "authenticatorAttachment": null,
+            "response": {
+                "clientDataJSON": base64_url_encode(client_data.to_string().as_bytes()),
+                "attestationObject": ed25519_attestation(),
+                "transports": []
+            },
+            "clientExtensionResults": { "prf": { "results": { "first": null, "second": null } } }
+        },
+        "contractId": "w3a-v1.testnet",
+        "nearRpcUrl": "https://rpc.testnet.near.org",
+        "dryRun": true
+    }))
+    .unwrap();
+
+    let report = block_on(handle_check_can_register_user(request))
+        .unwrap()
+        .dry_run
+        .unwrap();
+    assert_eq!(
+        report.outcome,
+        RegistrationDryRunOutcome::OriginNotAllowed.as_str(),
+        "{:?}",
+        report.detail
+    );
+}
+
+#[test]
+fn test_add_key_hidden_as_the_47th_action() {
+    let mut actions: Vec<Value> = (0..46).map(|_| transfer("1")).collect();
+    actions.push(json!({
+        "action_type": "AddKey",
+        "public_key": format!("ed25519:{}", bs58::encode([7u8; 32]).into_string()),
+        "access_key": json!({ "nonce": 0, "permission": { "FullAccess": {} } }).to_string()
+    }));
+    let result = sign(
+        &[tx(Value::Array(actions))],
+        json!({ "workerPolicy": { "blockFullAccessAddKey": true } }),
+    );
+    assert_refused(&result, SignerErrorCode::FullAccessAddKeyBlocked);
+}
+
+#[test]
+fn test_delegate_action_with_a_raised_max_block_height() {
+    // The worker signs no DelegateActions, so there is no confirmation one could outlive: one
+    // smuggled into a batch, whatever its max_block_height, is refused before the user is
+    // prompted
+    let delegate = json!({
+        "action_type": "Delegate",
+        "delegate_action": {
+            "sender_id": SIGNER,
+            "receiver_id": "bob.testnet",
+            "actions": [transfer("1")],
+            "nonce": 1,
+            "max_block_height": u64::MAX,
+            "public_key": format!("ed25519:{}", bs58::encode([7u8; 32]).into_string())
+        }
+    });
+    let result = sign(&[tx(json!([transfer("1"), delegate]))], json!({}));
+    assert_refused(&result, SignerErrorCode::InvalidConfig);
+    assert!(result
+        .error
+        .unwrap()
+        .starts_with("Transaction 1: Failed to parse actions: unknown variant `Delegate`"));
+}
+
+#[test]
+fn test_args_crafted_to_overflow_the_deposit_sum() {
+    // Two deposits whose sum wraps to 1 yocto in u128: the estimate must saturate instead
+    let function_call = |deposit: String| {
+        json!({
+            "action_type": "FunctionCall",
+            "method_name": "ft_transfer_call",
+            "args": json!({ "receiver_id": "bob.testnet", "amount": "1", "msg": "" }).to_string(),
+            "gas": "0",
+            "deposit": deposit
+        })
+    };
+    let actions = json!([
+        function_call(u128::MAX.to_string()),
+        function_call("2".to_string())
+    ]);
+    let result = sign(
+        &[tx(actions)],
+        json!({
+            "transactionContext": {
+                "nearPublicKeyStr": format!("ed25519:{}", bs58::encode([7u8; 32]).into_string()),
+                "nextNonce": "1",
+                "txBlockHeight": "100",
+                "txBlockHash": "11111111111111111111111111111111",
+                "accessKeyAllowance": "1000000000000000000000000",
+                "gasPrice": "100000000"
+            }
+        }),
+    );
+    assert_refused(&result, SignerErrorCode::InsufficientAllowance);
+}
@@ -0,0 +1,113 @@
+use crate::error::SignerErrorCode;
+use crate::handlers::{handle_run_self_test, RunSelfTestRequest};
+use crate::self_test::{
+    check_key_handling_allowed, run_known_answer_tests, run_self_test, KnownAnswerTest,
+    KNOWN_ANSWER_TESTS,
+};
+use crate::state;
+use crate::tests::block_on;
+use crate::types::worker_messages::WorkerRequestType;
+use serde_json::json;
+
+fn miscompiled() -> Result<(), String> {
+    Err("digest of a 3-byte message".to_string())
+}
+
+#[test]
+fn test_known_answer_tests_pass() {
+    let report = run_known_answer_tests(KNOWN_ANSWER_TESTS);
+    let primitives: Vec<&str> = report
+        .results
+        .iter()
+        .map(|r| r.primitive.as_str())
+        .collect();
+    assert_eq!(
+        primitives,
+        [
+            "sha256",
+            "chacha20poly1305",
+            "hkdfSha256",
+            "ed25519",
+            "base64url",
+            "borsh"
+        ]
+    );
+    for result in &report.results {
+        assert!(result.passed, "{}: {:?}", result.primitive, result.error);
+        assert!(result.duration_ms >= 0.0);
+    }
+    assert!(report.passed);
+    assert_eq!(report.failed_primitive, None);
+
+    let serialized = serde_json::to_value(&report).unwrap();
+    assert_eq!(serialized["passed"], json!(true));
+    assert_eq!(serialized["results"][0]["primitive"], json!("sha256"));
+    assert!(serialized["results"][0]["durationMs"].is_number());
+    assert!(serialized["totalDurationMs"].is_number());
+}
+
+#[test]
+fn test_failed_primitive_is_reported() {
+    let tests: &[KnownAnswerTest] = &[
+        KNOWN_ANSWER_TESTS[0],
+        ("chacha20poly1305", miscompiled),
+        KNOWN_ANSWER_TESTS[2],
+    ];
+    let report = run_known_answer_tests(tests);
+    assert!(!report.passed);
+    assert_eq!(report.failed_primitive.as_deref(), Some("chacha20poly1305"));
+    assert!(report.results[0].passed);
+    assert_eq!(
+        report.results[1].error.as_deref(),
+        Some("digest of a 3-byte message")
+    );
+    // Later primitives still run
+    assert!(report.results[2].passed);
+}
+
+#[test]
+fn test_failure_refuses_key_handling_until_reinitialized() {
+    assert!(
+        block_on(handle_run_self_test(RunSelfTestRequest {}))
+            .unwrap()
+            .passed
+    );
+    assert!(check_key_handling_allowed(WorkerRequestType::SignTransactionsWithActions).is_ok());
+
+    state::record_self_test_failure("sha256");
+    state::record_self_test_failure("ed25519");
+    for request_type in [
+        WorkerRequestType::SignTransactionsWithActions,
+        WorkerRequestType::DecryptPrivateKeyWithPrf,
+        WorkerRequestType::SignWithSessionKey,
+        WorkerRequestType::DeployLargeContract,
+    ] {
+        let err = check_key_handling_allowed(request_type).unwrap_err();
+        assert_eq!(err.code(), SignerErrorCode::SelfTestFailed);
+        assert!(
+            err.to_string().starts_with("SelfTestFailed: sha256 "),
+            "{}",
+            err
+        );
+    }
+    // Requests that handle no key material still run
+    for request_type in [
+        WorkerRequestType::GetMemoryStats,
+        WorkerRequestType::CheckCanRegisterUser,
+        WorkerRequestType::RunSelfTest,
+    ] {
+        assert!(check_key_handling_allowed(request_type).is_ok());
+    }
+
+    // A passing re-run does not leave the refusing state
+    let report = run_self_test();
+    assert!(report.results.iter().all(|r| r.passed));
+    assert!(!report.passed);
+    assert_eq!(report.failed_primitive.as_deref(), Some("sha256"));
+    assert!(check_key_handling_allowed(WorkerRequestType::SignNep413Message).is_err());
+
+    // Initialize clears the state before re-running the self-test
+    state::clear_self_test_failure();
+    assert!(run_self_test().passed);
+    assert!(check_key_handling_allowed(WorkerRequestType::SignNep413Message).is_ok());
+}
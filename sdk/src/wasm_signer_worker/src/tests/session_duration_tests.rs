@@ -0,0 +1,304 @@
+use serde_json::json;
+
+use crate::clock::{ManualClock, SharedClock};
+use crate::dispatch_signer_message;
+use crate::error::{ConfigError, SessionError, SignerErrorCode};
+use crate::handlers::confirm_tx_details::{
+    check_confirmation_response, register_confirmation_prompt, ConfirmationResult,
+};
+use crate::session_duration::*;
+use crate::state;
+use crate::tests::{block_on, in_fresh_worker};
+use crate::types::worker_messages::{SignerWorkerMessage, WorkerRequestType, WorkerResponseType};
+
+const STARTED_AT_MS: f64 = 1_000_000.0;
+const MAX_DURATION_MS: u32 = 60_000;
+const EXPIRES_AT_MS: f64 = STARTED_AT_MS + MAX_DURATION_MS as f64;
+
+/// A session started at STARTED_AT_MS under a one-minute maximum
+fn setup() -> ManualClock {
+    let clock = ManualClock::new(STARTED_AT_MS);
+    state::set_clock(SharedClock::new(clock.clone()));
+    state::wipe_all_state();
+    apply_policy_duration(&json!({ "workerPolicy": { "maxSessionDurationMs": MAX_DURATION_MS } }))
+        .unwrap();
+    state::start_session(state::now_ms());
+    clock
+}
+
+/// A confirmed decision for `request_id` carrying a PRF output collected at `collected_at_ms`
+fn decision(request_id: &str, collected_at_ms: f64) -> ConfirmationResult {
+    serde_json::from_value(json!({
+        "request_id": request_id,
+        "confirmed": true,
+        "prf_output": "Y2hhY2hhMjAtcHJmLW91dHB1dA",
+        "credential_collected_at_ms": collected_at_ms,
+    }))
+    .unwrap()
+}
+
+fn prompt(request_id: &str) {
+    let mut request_obj = json!({ "schemaVersion": 2, "requestId": request_id });
+    register_confirmation_prompt(&mut request_obj, request_id);
+}
+
+#[test]
+fn test_session_expires_exactly_at_the_boundary() {
+    let clock = setup();
+    let decrypt = WorkerRequestType::DecryptPrivateKeyWithPrf;
+
+    clock.set(EXPIRES_AT_MS - 1.0);
+    assert_eq!(check_session_allowed(decrypt), Ok(()));
+    let session = status(state::now_ms());
+    assert_eq!(session.expires_at_ms, Some(EXPIRES_AT_MS));
+    assert_eq!(session.remaining_ms, Some(1.0));
+    assert!(!session.expired);
+
+    clock.set(EXPIRES_AT_MS);
+    let err = check_session_allowed(decrypt).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::SessionExpired);
+    assert_eq!(
+        err,
+        SessionError::Expired {
+            started_at_ms: STARTED_AT_MS,
+            expired_at_ms: EXPIRES_AT_MS,
+        }
+    );
+    let session = status(state::now_ms());
+    assert_eq!(session.remaining_ms, Some(0.0));
+    assert!(session.expired);
+
+    // Requests that collect a credential may renew it; requests without keys are unaffected
+    for request_type in [
+        WorkerRequestType::SignTransactionsWithActions,
+        WorkerRequestType::ExportNearKeypairUI,
+        WorkerRequestType::GetSessionStatus,
+        WorkerRequestType::CheckCanRegisterUser,
+    ] {
+        assert_eq!(check_session_allowed(request_type), Ok(()));
+    }
+}
+
+#[test]
+fn test_sessions_do_not_expire_without_a_maximum() {
+    let clock = ManualClock::new(STARTED_AT_MS);
+    state::set_clock(SharedClock::new(clock.clone()));
+    state::wipe_all_state();
+    state::start_session(state::now_ms());
+    clock.advance(365.0 * 24.0 * 3_600_000.0);
+    assert_eq!(
+        check_session_allowed(WorkerRequestType::DecryptPrivateKeyWithPrf),
+        Ok(())
+    );
+    let session = status(state::now_ms());
+    assert_eq!(session.started_at_ms, Some(STARTED_AT_MS));
+    assert_eq!(session.max_session_duration_ms, None);
+    assert_eq!(session.remaining_ms, None);
+    assert!(!session.expired);
+}
+
+#[test]
+fn test_policy_duration_must_be_positive_milliseconds() {
+    setup();
+    for duration in [
+        json!(0),
+        json!(-1),
+        json!(1.5),
+        json!("60000"),
+        json!(1u64 << 32),
+    ] {
+        let err = apply_policy_duration(&json!({
+            "workerPolicy": { "maxSessionDurationMs": duration }
+        }))
+        .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                ConfigError::InvalidValue {
+                    field: "maxSessionDurationMs",
+                    ..
+                }
+            ),
+            "{:?}",
+            err
+        );
+    }
+    // A policy without the field keeps the last duration set
+    apply_policy_duration(&json!({ "workerPolicy": {} })).unwrap();
+    apply_policy_duration(&json!({ "workerPolicy": { "maxSessionDurationMs": null } })).unwrap();
+    apply_policy_duration(&json!({})).unwrap();
+    assert_eq!(state::session().max_duration_ms, Some(MAX_DURATION_MS));
+    apply_policy_duration(&json!({ "workerPolicy": { "maxSessionDurationMs": 30_000 } })).unwrap();
+    assert_eq!(state::session().max_duration_ms, Some(30_000));
+}
+
+fn dispatch(
+    request_type: WorkerRequestType,
+    payload: serde_json::Value,
+) -> (WorkerResponseType, serde_json::Value) {
+    let response = block_on(dispatch_signer_message(SignerWorkerMessage {
+        msg_type: request_type as u32,
+        payload,
+        request_id: None,
+    }))
+    .unwrap();
+    (
+        WorkerResponseType::from(response.response_type),
+        response.payload,
+    )
+}
+
+#[test]
+fn test_dispatcher_refuses_key_requests_once_expired() {
+    let clock = setup();
+    clock.set(EXPIRES_AT_MS);
+    let (response_type, payload) = dispatch(
+        WorkerRequestType::DecryptPrivateKeyWithPrf,
+        json!({
+            "nearAccountId": "alice.testnet",
+            "chacha20PrfOutput": "Y2hhY2hhMjAtcHJmLW91dHB1dA",
+            "encryptedPrivateKeyData": "AAAA",
+            "encryptedPrivateKeyIv": "AAAAAAAAAAAAAAAA",
+        }),
+    );
+    assert_eq!(
+        response_type,
+        WorkerResponseType::DecryptPrivateKeyWithPrfFailure
+    );
+    assert_eq!(payload["errorCode"], json!("SessionExpired"));
+    assert_eq!(
+        payload["error"],
+        json!("SessionExpired: the session started at 1000000 expired at 1060000; a fresh passkey assertion starts a new one")
+    );
+
+    let (response_type, payload) = dispatch(WorkerRequestType::GetSessionStatus, json!({}));
+    assert_eq!(response_type, WorkerResponseType::GetSessionStatusSuccess);
+    assert_eq!(
+        payload,
+        json!({
+            "epoch": state::session_epoch(),
+            "startedAtMs": STARTED_AT_MS,
+            "maxSessionDurationMs": MAX_DURATION_MS,
+            "expiresAtMs": EXPIRES_AT_MS,
+            "remainingMs": 0.0,
+            "expired": true,
+        })
+    );
+}
+
+#[test]
+fn test_fresh_credential_renews_the_expired_session() {
+    let clock = setup();
+    let epoch = state::session_epoch();
+
+    // Prompted before the expiry, collected before it, answered after it
+    clock.set(EXPIRES_AT_MS - 2_000.0);
+    prompt("req-stale");
+    clock.set(EXPIRES_AT_MS + 1_000.0);
+    let error =
+        check_confirmation_response(decision("req-stale", EXPIRES_AT_MS - 1_000.0), "req-stale")
+            .unwrap_err();
+    assert!(error.starts_with("SessionExpired: "), "{}", error);
+    assert_eq!(state::session_epoch(), epoch);
+
+    // Collected exactly at the expiry: a new session starts then, in the next epoch
+    clock.set(EXPIRES_AT_MS - 1_000.0);
+    prompt("req-fresh");
+    clock.set(EXPIRES_AT_MS + 1_000.0);
+    let result =
+        check_confirmation_response(decision("req-fresh", EXPIRES_AT_MS), "req-fresh").unwrap();
+    assert_eq!(result.credential_collected_at_ms, Some(EXPIRES_AT_MS));
+    assert_eq!(state::session_epoch(), epoch + 1);
+    let session = status(state::now_ms());
+    assert_eq!(session.started_at_ms, Some(EXPIRES_AT_MS));
+    assert_eq!(session.remaining_ms, Some(MAX_DURATION_MS as f64 - 1_000.0));
+    assert_eq!(
+        check_session_allowed(WorkerRequestType::DecryptPrivateKeyWithPrf),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_tokens_from_an_earlier_epoch_are_refused() {
+    setup();
+    let epoch = state::session_epoch();
+    assert_eq!(check_epoch("signing intent", epoch), Ok(()));
+    state::renew_session(state::now_ms());
+    let err = check_epoch("signing intent", epoch).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::SessionExpired);
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "SessionExpired: the signing intent was issued in session epoch {}, which ended (now epoch {})",
+            epoch,
+            epoch + 1
+        )
+    );
+}
+
+#[test]
+fn test_session_expires_in_a_later_worker() {
+    // The session starts in one worker and, through its record, expires in the next
+    let (epoch, record) = in_fresh_worker(None, || {
+        setup();
+        (
+            state::session_epoch(),
+            crate::worker_state::take_changed_record(),
+        )
+    });
+    let record = in_fresh_worker(record, move || {
+        let clock = ManualClock::new(EXPIRES_AT_MS - 1.0);
+        state::set_clock(SharedClock::new(clock.clone()));
+        let decrypt = WorkerRequestType::DecryptPrivateKeyWithPrf;
+        assert_eq!(check_session_allowed(decrypt), Ok(()));
+        clock.set(EXPIRES_AT_MS);
+        assert_eq!(
+            check_session_allowed(decrypt),
+            Err(SessionError::Expired {
+                started_at_ms: STARTED_AT_MS,
+                expired_at_ms: EXPIRES_AT_MS,
+            })
+        );
+
+        // A fresh credential renews it here, in the next epoch
+        prompt("req-fresh");
+        check_confirmation_response(decision("req-fresh", EXPIRES_AT_MS), "req-fresh").unwrap();
+        assert_eq!(state::session_epoch(), epoch + 1);
+        crate::worker_state::take_changed_record()
+    });
+    in_fresh_worker(record, move || {
+        let clock = ManualClock::new(EXPIRES_AT_MS + 1_000.0);
+        state::set_clock(SharedClock::new(clock));
+        let session = status(state::now_ms());
+        assert_eq!(session.epoch, epoch + 1);
+        assert_eq!(session.started_at_ms, Some(EXPIRES_AT_MS));
+        assert!(!session.expired);
+        // Tokens of the first worker's epoch stay refused
+        assert!(check_epoch("signing intent", epoch).is_err());
+    });
+}
+
+#[test]
+fn test_wipe_ends_the_session_and_moves_to_the_next_epoch() {
+    setup();
+    let epoch = state::session_epoch();
+    state::wipe_all_state();
+    let session = status(state::now_ms());
+    assert_eq!(session.epoch, epoch + 1);
+    assert_eq!(session.started_at_ms, None);
+    assert!(!session.expired);
+
+    // The first key-using request that succeeds starts the next session
+    note_completed(
+        WorkerRequestType::SignTransactionsWithActions,
+        &json!({ "success": false }),
+    );
+    assert_eq!(state::session().started_at_ms, None);
+    note_completed(WorkerRequestType::GetSessionStatus, &json!({}));
+    assert_eq!(state::session().started_at_ms, None);
+    note_completed(
+        WorkerRequestType::SignTransactionsWithActions,
+        &json!({ "success": true }),
+    );
+    assert_eq!(state::session().started_at_ms, Some(STARTED_AT_MS));
+}
@@ -0,0 +1,216 @@
+use crate::actions::ActionParams;
+use crate::error::SignerErrorCode;
+use crate::handlers::handle_session_keys::{
+    handle_create_session_key, handle_revoke_session_key, handle_sign_with_session_key,
+    CreateSessionKeyRequest, CreateSessionKeyResult, RevokeSessionKeyRequest,
+    SignWithSessionKeyRequest,
+};
+use crate::handlers::handle_wipe_all_state::{handle_wipe_all_state, WipeAllStateRequest};
+use crate::state;
+use crate::tests::block_on;
+use crate::transaction::build_actions_from_params;
+use crate::types::{AccessKeyPermission, Action, SignedTransaction};
+
+const ACCOUNT_ID: &str = "player.testnet";
+const GAME_CONTRACT: &str = "game.testnet";
+
+fn create_session_key(allowance: &str) -> CreateSessionKeyResult {
+    block_on(handle_create_session_key(CreateSessionKeyRequest {
+        near_account_id: ACCOUNT_ID.to_string(),
+        receiver_id: GAME_CONTRACT.to_string(),
+        method_names: vec!["make_move".to_string()],
+        allowance: allowance.to_string(),
+        ttl_ms: None,
+    }))
+    .unwrap()
+}
+
+fn function_call(method_name: &str, gas: &str, deposit: &str) -> ActionParams {
+    ActionParams::FunctionCall {
+        method_name: method_name.to_string(),
+        args: "{}".to_string(),
+        gas: gas.to_string(),
+        deposit: deposit.to_string(),
+    }
+}
+
+fn sign_request(
+    public_key: &str,
+    receiver_id: &str,
+    actions: Vec<ActionParams>,
+) -> SignWithSessionKeyRequest {
+    SignWithSessionKeyRequest {
+        session_public_key: public_key.to_string(),
+        signer_account_id: ACCOUNT_ID.to_string(),
+        receiver_id: receiver_id.to_string(),
+        nonce: "1".to_string(),
+        block_hash: bs58::encode([4u8; 32]).into_string(),
+        actions: serde_json::to_string(&actions).unwrap(),
+        gas_price: Some("100000000".to_string()),
+        idempotency_key: None,
+    }
+}
+
+#[test]
+fn test_create_session_key_returns_function_call_add_key_action() {
+    let session = create_session_key("250000000000000000000000");
+    assert!(session.public_key.starts_with("ed25519:"));
+
+    let add_key: ActionParams = serde_json::from_str(&session.add_key_action).unwrap();
+    let actions = build_actions_from_params(vec![add_key]).unwrap();
+    match &actions[0] {
+        Action::AddKey { access_key, .. } => match &access_key.permission {
+            AccessKeyPermission::FunctionCall(permission) => {
+                assert_eq!(permission.receiver_id, GAME_CONTRACT);
+                assert_eq!(permission.method_names, vec!["make_move".to_string()]);
+                assert_eq!(permission.allowance, Some(250_000_000_000_000_000_000_000));
+            }
+            other => panic!("expected FunctionCall permission, got {:?}", other),
+        },
+        other => panic!("expected AddKey action, got {:?}", other),
+    }
+
+    let zero_allowance = block_on(handle_create_session_key(CreateSessionKeyRequest {
+        near_account_id: ACCOUNT_ID.to_string(),
+        receiver_id: GAME_CONTRACT.to_string(),
+        method_names: vec![],
+        allowance: "0".to_string(),
+        ttl_ms: None,
+    }));
+    assert!(zero_allowance.is_err());
+}
+
+#[test]
+fn test_sign_with_session_key_enforces_permissions() {
+    let session = create_session_key("250000000000000000000000");
+
+    let result = block_on(handle_sign_with_session_key(sign_request(
+        &session.public_key,
+        GAME_CONTRACT,
+        vec![function_call("make_move", "30000000000000", "0")],
+    )))
+    .unwrap();
+    assert!(result.success, "{:?}", result.error);
+    let signed_tx = result.signed_transactions.unwrap().remove(0);
+    let signed = SignedTransaction::from_borsh_bytes(&signed_tx.borsh_bytes).unwrap();
+    assert_eq!(
+        format!(
+            "ed25519:{}",
+            bs58::encode(signed.transaction.public_key.key_data).into_string()
+        ),
+        session.public_key
+    );
+
+    let rejected = [
+        sign_request(
+            &session.public_key,
+            "other.testnet",
+            vec![function_call("make_move", "30000000000000", "0")],
+        ),
+        sign_request(
+            &session.public_key,
+            GAME_CONTRACT,
+            vec![function_call("withdraw", "30000000000000", "0")],
+        ),
+        sign_request(
+            &session.public_key,
+            GAME_CONTRACT,
+            vec![function_call("make_move", "30000000000000", "1")],
+        ),
+        sign_request(
+            &session.public_key,
+            GAME_CONTRACT,
+            vec![ActionParams::Transfer {
+                deposit: "1".to_string(),
+            }],
+        ),
+    ];
+    for request in rejected {
+        let result = block_on(handle_sign_with_session_key(request)).unwrap();
+        assert!(!result.success);
+        assert_eq!(
+            result.error_code.as_deref(),
+            Some(SignerErrorCode::SessionKeyPermissionDenied.as_str())
+        );
+    }
+}
+
+#[test]
+fn test_sign_with_session_key_charges_allowance() {
+    // 30 TGas at 1e8 yocto/gas = 3e21 yocto per call: the allowance covers exactly two calls
+    let session = create_session_key("6000000000000000000000");
+    let make_move = || {
+        block_on(handle_sign_with_session_key(sign_request(
+            &session.public_key,
+            GAME_CONTRACT,
+            vec![function_call("make_move", "30000000000000", "0")],
+        )))
+        .unwrap()
+    };
+
+    assert!(make_move().success);
+    assert!(make_move().success);
+    let exhausted = make_move();
+    assert!(!exhausted.success);
+    assert_eq!(
+        exhausted.error_code.as_deref(),
+        Some(SignerErrorCode::SessionKeyAllowanceExceeded.as_str())
+    );
+}
+
+#[test]
+fn test_revoke_and_wipe_remove_session_keys() {
+    let session = create_session_key("250000000000000000000000");
+    let revoked = block_on(handle_revoke_session_key(RevokeSessionKeyRequest {
+        session_public_key: session.public_key.clone(),
+    }))
+    .unwrap();
+    assert!(revoked.revoked);
+    let delete_key: ActionParams = serde_json::from_str(&revoked.delete_key_action).unwrap();
+    assert_eq!(
+        delete_key,
+        ActionParams::DeleteKey {
+            public_key: session.public_key.clone()
+        }
+    );
+
+    let after_revoke = block_on(handle_sign_with_session_key(sign_request(
+        &session.public_key,
+        GAME_CONTRACT,
+        vec![function_call("make_move", "30000000000000", "0")],
+    )))
+    .unwrap();
+    assert_eq!(
+        after_revoke.error_code.as_deref(),
+        Some(SignerErrorCode::SessionKeyNotFound.as_str())
+    );
+
+    let again = block_on(handle_revoke_session_key(RevokeSessionKeyRequest {
+        session_public_key: session.public_key,
+    }))
+    .unwrap();
+    assert!(!again.revoked);
+
+    let wiped = create_session_key("250000000000000000000000");
+    let summary = block_on(handle_wipe_all_state(WipeAllStateRequest {})).unwrap();
+    assert_eq!(summary.cleared_session_keys, 1);
+    assert_eq!(
+        state::with_session_key(&wiped.public_key, |_| ()).err(),
+        Some(SignerErrorCode::SessionKeyNotFound)
+    );
+}
+
+#[test]
+fn test_expired_session_key_is_wiped() {
+    let session = create_session_key("250000000000000000000000");
+    state::with_session_key(&session.public_key, |key| key.expires_at_ms = 0.0).unwrap();
+
+    assert_eq!(
+        state::with_session_key(&session.public_key, |_| ()).err(),
+        Some(SignerErrorCode::SessionKeyExpired)
+    );
+    assert_eq!(
+        state::with_session_key(&session.public_key, |_| ()).err(),
+        Some(SignerErrorCode::SessionKeyNotFound)
+    );
+}
@@ -0,0 +1,126 @@
+use crate::dispatch_signer_message;
+use crate::error::SignerErrorCode;
+use crate::handlers::handle_sign_transactions_with_fresh_challenge::{
+    handle_sign_transactions_with_fresh_challenge, SignTransactionsWithFreshChallengeRequest,
+};
+use crate::peer_channel::{connect_peer_session, PeerSession};
+use crate::sign_phases::{PhaseTracker, SignPhase};
+use crate::tests::block_on;
+use crate::types::worker_messages::{SignerWorkerMessage, WorkerRequestType, WorkerResponseType};
+use serde_json::{json, Value};
+
+// Each test runs on its own thread, so no peer port is connected until a test connects one.
+
+fn payload(signers: &[&str]) -> Value {
+    json!({
+        "rpcCall": {
+            "contractId": "w3a-v1.testnet",
+            "nearRpcUrl": "https://rpc.testnet.near.org",
+            "nearAccountId": signers[0]
+        },
+        "decryption": { "encryptedPrivateKeyData": "AAAA", "encryptedPrivateKeyIv": "BBBB" },
+        "txSigningRequests": signers.iter().map(|signer| json!({
+            "nearAccountId": signer,
+            "receiverId": "bob.testnet",
+            "actions": json!([{ "action_type": "Transfer", "deposit": "1" }]).to_string()
+        })).collect::<Vec<_>>(),
+        "confirmationConfig": null
+    })
+}
+
+fn request(signers: &[&str]) -> SignTransactionsWithFreshChallengeRequest {
+    serde_json::from_value(payload(signers)).unwrap()
+}
+
+fn connect_peer_port() {
+    connect_peer_session(PeerSession {
+        expected_vrf_public_key: "AQIDBAUGBwgJCgsMDQ4PEBESExQVFhcYGRobHB0eHyA".to_string(),
+        rp_id: "example.localhost".to_string(),
+    })
+    .unwrap();
+}
+
+#[test]
+fn test_phase_tracker_starts_in_preparation() {
+    let phases = PhaseTracker::default();
+    assert_eq!(phases.current(), SignPhase::Preparation);
+    phases.enter(SignPhase::Verification);
+    assert_eq!(phases.current().as_str(), "verification");
+}
+
+#[test]
+fn test_without_a_peer_port_the_fast_path_is_refused() {
+    let result =
+        block_on(handle_sign_transactions_with_fresh_challenge(request(&["alice.testnet"])))
+            .unwrap();
+    assert!(!result.sign_result.success);
+    assert_eq!(result.failed_phase.as_deref(), Some("challenge"));
+    assert_eq!(
+        result.sign_result.error_code.as_deref(),
+        Some(SignerErrorCode::PeerChallengeUnavailable.as_str())
+    );
+    assert!(result
+        .sign_result
+        .error
+        .unwrap()
+        .contains("use SignTransactionsWithActions"));
+}
+
+#[test]
+fn test_failures_report_the_phase_they_happened_in() {
+    connect_peer_port();
+
+    // Refused by the shared flow's checks before any challenge is requested
+    let result = block_on(handle_sign_transactions_with_fresh_challenge(request(&[
+        "alice.testnet",
+        "bob.testnet",
+    ])))
+    .unwrap();
+    assert_eq!(result.failed_phase.as_deref(), Some("preparation"));
+    assert!(!result.sign_result.success);
+
+    // The port is connected but the VRF worker never answers
+    let result =
+        block_on(handle_sign_transactions_with_fresh_challenge(request(&["alice.testnet"])))
+            .unwrap();
+    assert_eq!(result.failed_phase.as_deref(), Some("challenge"));
+    assert_eq!(
+        result.sign_result.error_code.as_deref(),
+        Some(SignerErrorCode::PeerChallengeUnavailable.as_str())
+    );
+}
+
+#[test]
+fn test_dispatcher_serves_the_fast_path_next_to_the_granular_message() {
+    connect_peer_port();
+    let dispatch = |request_type: WorkerRequestType, payload: Value| {
+        block_on(dispatch_signer_message(SignerWorkerMessage {
+            msg_type: request_type as u32,
+            payload,
+            request_id: None,
+        }))
+        .unwrap()
+    };
+
+    let response = dispatch(
+        WorkerRequestType::SignTransactionsWithFreshChallenge,
+        payload(&["alice.testnet"]),
+    );
+    assert_eq!(
+        WorkerResponseType::from(response.response_type),
+        WorkerResponseType::SignTransactionsWithFreshChallengeSuccess
+    );
+    assert_eq!(response.payload["success"], json!(false));
+    assert_eq!(response.payload["failedPhase"], json!("challenge"));
+
+    // The granular message takes the same payload and still reports a failure response
+    let response = dispatch(
+        WorkerRequestType::SignTransactionsWithActions,
+        payload(&["alice.testnet", "bob.testnet"]),
+    );
+    assert_eq!(
+        WorkerResponseType::from(response.response_type),
+        WorkerResponseType::SignTransactionsWithActionsFailure
+    );
+    assert!(response.payload.get("failedPhase").is_none());
+}
@@ -0,0 +1,113 @@
+use crate::encoders::base64_url_encode;
+use crate::signature_verify::*;
+use ed25519_dalek::{Signer, SigningKey};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+fn signing_key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+fn ed25519_item(seed: u8, message: &[u8]) -> SignatureVerifyItem {
+    let key = signing_key(seed);
+    SignatureVerifyItem {
+        public_key: base64_url_encode(key.verifying_key().as_bytes()),
+        message: base64_url_encode(message),
+        signature: base64_url_encode(&key.sign(message).to_bytes()),
+        kind: SignatureKind::Ed25519,
+    }
+}
+
+fn hmac_item(key: &[u8], message: &[u8]) -> SignatureVerifyItem {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).unwrap();
+    mac.update(message);
+    SignatureVerifyItem {
+        public_key: base64_url_encode(key),
+        message: base64_url_encode(message),
+        signature: base64_url_encode(&mac.finalize().into_bytes()),
+        kind: SignatureKind::HmacSha256,
+    }
+}
+
+fn failures(results: &[VerifyResult]) -> Vec<Option<VerifyFailure>> {
+    results.iter().map(|r| r.failure).collect()
+}
+
+#[test]
+fn test_batch_of_valid_ed25519_signatures() {
+    let items: Vec<_> = (1..=5u8).map(|i| ed25519_item(i, &[i; 40])).collect();
+    let results = verify_signatures(&items);
+    assert_eq!(results.len(), 5);
+    assert!(results.iter().all(|r| r.valid));
+    assert_eq!(
+        results.iter().map(|r| r.index).collect::<Vec<_>>(),
+        vec![0, 1, 2, 3, 4]
+    );
+}
+
+#[test]
+fn test_failed_batch_reports_the_bad_item() {
+    let mut items: Vec<_> = (1..=4u8).map(|i| ed25519_item(i, b"payload")).collect();
+    // Signature from a different key
+    items[2].signature = ed25519_item(9, b"payload").signature;
+
+    let results = verify_signatures(&items);
+    assert_eq!(
+        failures(&results),
+        vec![None, None, Some(VerifyFailure::SignatureMismatch), None]
+    );
+}
+
+#[test]
+fn test_encoding_failures_are_distinguished() {
+    let mut bad_key = ed25519_item(1, b"m");
+    bad_key.public_key = base64_url_encode(&[1u8; 31]);
+    let mut bad_signature = ed25519_item(2, b"m");
+    bad_signature.signature = base64_url_encode(&[0u8; 10]);
+    let mut bad_message = ed25519_item(3, b"m");
+    bad_message.message = "not base64url!".to_string();
+
+    let results = verify_signatures(&[bad_key, bad_signature, bad_message, ed25519_item(4, b"m")]);
+    assert_eq!(
+        failures(&results),
+        vec![
+            Some(VerifyFailure::KeyEncoding),
+            Some(VerifyFailure::SignatureEncoding),
+            Some(VerifyFailure::MessageEncoding),
+            None
+        ]
+    );
+}
+
+#[test]
+fn test_mixed_kinds_verify_individually() {
+    let key = signing_key(5);
+    let tx_bytes = b"borsh transaction bytes";
+    let near_item = SignatureVerifyItem {
+        public_key: format!(
+            "ed25519:{}",
+            bs58::encode(key.verifying_key().as_bytes()).into_string()
+        ),
+        message: base64_url_encode(tx_bytes),
+        signature: base64_url_encode(&key.sign(&Sha256::digest(tx_bytes)).to_bytes()),
+        kind: SignatureKind::Ed25519Sha256,
+    };
+    let mut bad_hmac = hmac_item(b"transfer-key", b"transfer payload");
+    bad_hmac.message = base64_url_encode(b"tampered payload");
+
+    let results = verify_signatures(&[
+        ed25519_item(1, b"assertion"),
+        near_item,
+        hmac_item(b"transfer-key", b"transfer payload"),
+        bad_hmac,
+    ]);
+    assert_eq!(
+        failures(&results),
+        vec![None, None, None, Some(VerifyFailure::SignatureMismatch)]
+    );
+}
+
+#[test]
+fn test_empty_input() {
+    assert!(verify_signatures(&[]).is_empty());
+}
@@ -0,0 +1,216 @@
+use crate::actions::ActionParams;
+use crate::confirmation_blocks::{ConfirmationSummaryBlock, SummaryWarningCode};
+use crate::encoders::base64_standard_encode;
+use crate::signed_tx_decoder::{inspect_signed_transaction, DecodedAction, UnknownAction};
+use crate::transaction::{
+    build_actions_from_params, build_transaction_for_public_key, sign_transaction,
+};
+use crate::types::{PublicKey, SignedTransaction};
+use ed25519_dalek::{Signer, SigningKey};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+const RECEIVER_ID: &str = "contract.testnet";
+/// Where the actions start: "alice.testnet" (4 + 13), the public key (33), the nonce (8),
+/// RECEIVER_ID (4 + 16), the block hash (32) and the actions length (4)
+const ACTIONS_OFFSET: usize = 17 + 33 + 8 + 20 + 32 + 4;
+
+fn signing_key() -> SigningKey {
+    SigningKey::from_bytes(&[11u8; 32])
+}
+
+fn transfer() -> ActionParams {
+    ActionParams::Transfer {
+        deposit: "1000000000000000000000000".to_string(),
+    }
+}
+
+fn signed_tx_bytes(actions: Vec<ActionParams>) -> Vec<u8> {
+    let transaction = build_transaction_for_public_key(
+        "alice.testnet",
+        RECEIVER_ID,
+        42,
+        &[8u8; 32],
+        PublicKey::from_ed25519_bytes(&signing_key().verifying_key().to_bytes()),
+        build_actions_from_params(actions).unwrap(),
+    )
+    .unwrap();
+    sign_transaction(transaction, &signing_key()).unwrap()
+}
+
+/// `tx` (borsh Transaction bytes) signed by signing_key()
+fn signed(tx: &[u8]) -> String {
+    let mut bytes = tx.to_vec();
+    bytes.push(0);
+    bytes.extend_from_slice(&signing_key().sign(&Sha256::digest(tx)).to_bytes());
+    base64_standard_encode(&bytes)
+}
+
+fn error_at(result: Result<impl std::fmt::Debug, String>, offset: usize) -> String {
+    let err = result.unwrap_err();
+    assert!(
+        err.starts_with(&format!("Malformed transaction at byte {}: ", offset)),
+        "{}",
+        err
+    );
+    err
+}
+
+#[test]
+fn test_signed_transactions_decode_and_render_like_confirmations() {
+    let actions = vec![
+        transfer(),
+        ActionParams::AddKey {
+            public_key: "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".to_string(),
+            access_key: "{\"nonce\":0,\"permission\":{\"FullAccess\":{}}}".to_string(),
+        },
+    ];
+    let bytes = signed_tx_bytes(actions.clone());
+    let decoded = inspect_signed_transaction(&base64_standard_encode(&bytes)).unwrap();
+
+    let (tx_hash, _) = SignedTransaction::from_borsh_bytes(&bytes)
+        .unwrap()
+        .transaction
+        .get_hash_and_size();
+    assert_eq!(decoded.tx_hash_b58, bs58::encode(tx_hash.0).into_string());
+    assert!(decoded.signature_valid);
+    assert_eq!(decoded.signer_id, "alice.testnet");
+    assert_eq!(
+        decoded.public_key,
+        format!(
+            "ed25519:{}",
+            bs58::encode(signing_key().verifying_key().to_bytes()).into_string()
+        )
+    );
+    assert_eq!(decoded.nonce, "42");
+    assert_eq!(decoded.receiver_id, RECEIVER_ID);
+    assert_eq!(decoded.block_hash, bs58::encode([8u8; 32]).into_string());
+    assert_eq!(decoded.action_count, 2);
+    assert_eq!(
+        decoded.actions,
+        actions
+            .into_iter()
+            .map(DecodedAction::Known)
+            .collect::<Vec<_>>()
+    );
+
+    assert_eq!(decoded.tx_tree["receiverId"], RECEIVER_ID);
+    assert_eq!(decoded.tx_tree["actions"][0]["action_type"], "Transfer");
+    assert_eq!(
+        decoded.tx_tree["summaryBlocks"],
+        json!(decoded.summary_blocks)
+    );
+    assert!(matches!(
+        &decoded.summary_blocks[0],
+        ConfirmationSummaryBlock::KeyRow { public_key, .. } if *public_key == decoded.public_key
+    ));
+    assert!(decoded.summary_blocks.iter().any(|block| matches!(
+        block,
+        ConfirmationSummaryBlock::Warning {
+            code: SummaryWarningCode::FullAccessKey,
+            ..
+        }
+    )));
+
+    // Tampered with: still decoded, but the signature no longer verifies
+    let mut tampered = bytes.clone();
+    tampered[ACTIONS_OFFSET + 1] ^= 1;
+    let decoded = inspect_signed_transaction(&base64_standard_encode(&tampered)).unwrap();
+    assert!(!decoded.signature_valid);
+}
+
+#[test]
+fn test_unknown_actions_decode_into_one_node_holding_the_rest() {
+    // A Transfer, then an action of a type this SDK does not know, then a CreateAccount
+    let bytes = signed_tx_bytes(vec![transfer()]);
+    let mut tx = bytes[..bytes.len() - 65].to_vec();
+    tx[ACTIONS_OFFSET - 4..ACTIONS_OFFSET].copy_from_slice(&3u32.to_le_bytes());
+    tx.extend_from_slice(&[9, 1, 2, 3, 4, 0]);
+    let decoded = inspect_signed_transaction(&signed(&tx)).unwrap();
+
+    assert!(decoded.signature_valid);
+    assert_eq!(
+        decoded.tx_hash_b58,
+        bs58::encode(Sha256::digest(&tx)).into_string()
+    );
+    assert_eq!(decoded.action_count, 3);
+    let unknown = UnknownAction {
+        discriminant: 9,
+        raw_bytes: base64_standard_encode(&[9, 1, 2, 3, 4, 0]),
+    };
+    assert_eq!(
+        decoded.actions,
+        vec![
+            DecodedAction::Known(transfer()),
+            DecodedAction::Unknown(unknown.clone())
+        ]
+    );
+    assert_eq!(
+        json!(decoded.actions[1]),
+        json!({ "action_type": "UnknownAction", "discriminant": 9, "rawBytes": "CQECAwQA" })
+    );
+    assert_eq!(decoded.tx_tree["actions"][1], json!(unknown));
+    assert!(decoded.summary_blocks.iter().any(|block| matches!(
+        block,
+        ConfirmationSummaryBlock::Warning { code: SummaryWarningCode::UnknownActions, text, .. }
+            if text == "2 actions could not be decoded and what they do is not shown"
+    )));
+
+    // No signature after it
+    let err = error_at(
+        inspect_signed_transaction(&base64_standard_encode(&tx)),
+        ACTIONS_OFFSET + 17,
+    );
+    assert!(err.contains("actions[1]: unknown action type 9"), "{}", err);
+}
+
+#[test]
+fn test_malformed_transactions_name_the_byte_offset() {
+    let bytes = signed_tx_bytes(vec![transfer()]);
+    let at = |bytes: &[u8], offset| {
+        error_at(
+            inspect_signed_transaction(&base64_standard_encode(bytes)),
+            offset,
+        )
+    };
+
+    // Truncated inside the Transfer's deposit, the block hash, and the signature
+    let err = at(&bytes[..ACTIONS_OFFSET + 5], ACTIONS_OFFSET + 5);
+    assert!(err.contains("actions[0]"), "{}", err);
+    let err = at(&bytes[..ACTIONS_OFFSET - 10], ACTIONS_OFFSET - 10);
+    assert!(err.contains("blockHash"), "{}", err);
+    at(&bytes[..bytes.len() - 1], bytes.len() - 1);
+
+    // A secp256k1 signer key, a signature that is not ed25519, trailing bytes
+    let mut secp = bytes.clone();
+    secp[17] = 1;
+    let err = at(&secp, 17);
+    assert!(
+        err.contains("publicKey: key type 1 is not supported"),
+        "{}",
+        err
+    );
+    let mut signature = bytes.clone();
+    let signature_offset = bytes.len() - 65;
+    signature[signature_offset] = 1;
+    at(&signature, signature_offset);
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    let err = at(&trailing, bytes.len());
+    assert!(err.ends_with("1 trailing bytes"), "{}", err);
+
+    // An AddKey with an invalid permission tag: decoding stops just past it
+    let add_key = signed_tx_bytes(vec![ActionParams::AddKey {
+        public_key: "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".to_string(),
+        access_key: "{\"nonce\":0,\"permission\":{\"FullAccess\":{}}}".to_string(),
+    }]);
+    let mut tag = add_key[..add_key.len() - 65].to_vec();
+    let permission = ACTIONS_OFFSET + 1 + 33 + 8;
+    assert_eq!(tag[permission], 1);
+    tag[permission] = 7;
+    error_at(inspect_signed_transaction(&signed(&tag)), permission + 1);
+
+    assert!(inspect_signed_transaction("not base64!")
+        .unwrap_err()
+        .starts_with("Invalid signed transaction"));
+}
@@ -0,0 +1,94 @@
+use crate::error::SignerErrorCode;
+use crate::hooks::{
+    enforce_pre_sign_decision, hook_timeout_ms, pre_sign_decision, request_pre_sign_decision,
+    PreSignHookRequest, PreSignHookResponse, HOOK_REASON_DENIED, HOOK_REASON_UNAVAILABLE,
+};
+use crate::state;
+use crate::tests::block_on;
+use crate::types::AccountId;
+use crate::types::handlers::WorkerPolicy;
+
+fn response(json: &str) -> Option<PreSignHookResponse> {
+    Some(serde_json::from_str(json).unwrap())
+}
+
+#[test]
+fn test_worker_policy_defaults_to_no_hooks() {
+    let policy: WorkerPolicy = serde_json::from_str("{}").unwrap();
+    assert!(!policy.pre_sign_hook);
+    assert!(!policy.post_sign_hook);
+    assert_eq!(hook_timeout_ms(&policy), 10_000);
+
+    let policy: WorkerPolicy =
+        serde_json::from_str(r#"{"preSignHook":true,"postSignHook":true,"hookTimeoutMs":2500}"#)
+            .unwrap();
+    assert!(policy.pre_sign_hook && policy.post_sign_hook);
+    assert_eq!(hook_timeout_ms(&policy), 2500);
+}
+
+#[test]
+fn test_pre_sign_decision_only_proceeds_on_explicit_allow() {
+    assert_eq!(pre_sign_decision(response(r#"{"allow":true}"#)), Ok(()));
+    assert_eq!(
+        pre_sign_decision(response(r#"{"allow":false,"reasonCode":"SanctionedReceiver"}"#)),
+        Err("SanctionedReceiver".to_string())
+    );
+    assert_eq!(
+        pre_sign_decision(response(r#"{"allow":false}"#)),
+        Err(HOOK_REASON_DENIED.to_string())
+    );
+    // Bridge timeouts resolve as a denial with their own reason code
+    assert_eq!(
+        pre_sign_decision(response(r#"{"allow":false,"reasonCode":"HookTimeout"}"#)),
+        Err("HookTimeout".to_string())
+    );
+    assert_eq!(
+        pre_sign_decision(None),
+        Err(HOOK_REASON_UNAVAILABLE.to_string())
+    );
+}
+
+#[test]
+fn test_pre_sign_denial_is_audited_with_reason_code() {
+    let request_id = "hook-deny-1";
+    let err = enforce_pre_sign_decision(
+        &AccountId("alice.testnet".to_string()),
+        request_id,
+        "signTransactionsWithActions",
+        response(r#"{"allow":false,"reasonCode":"DailyLimit"}"#),
+    )
+    .unwrap_err();
+    assert_eq!(err.0, SignerErrorCode::PreSignHookDenied);
+    assert!(err.1.contains("DailyLimit"));
+
+    let entries = state::audit_entries_for(request_id);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].operation, "signTransactionsWithActions");
+    assert_eq!(entries[0].outcome, "PreSignHookDenied");
+    assert_eq!(entries[0].detail.as_deref(), Some("DailyLimit"));
+}
+
+#[test]
+fn test_pre_sign_allow_leaves_no_audit_entry() {
+    let request_id = "hook-allow-1";
+    assert!(enforce_pre_sign_decision(
+        &AccountId("alice.testnet".to_string()),
+        request_id,
+        "signTransactionsWithActions",
+        response(r#"{"allow":true}"#)
+    )
+    .is_ok());
+    assert!(state::audit_entries_for(request_id).is_empty());
+}
+
+#[test]
+fn test_pre_sign_hook_without_bridge_fails_closed() {
+    let request = PreSignHookRequest {
+        request_id: "hook-native-1".to_string(),
+        tx_summary: serde_json::json!({ "to": "bob.testnet", "totalAmount": "1" }),
+        digest: "digest".to_string(),
+    };
+    let response = block_on(request_pre_sign_decision(&request, 1000));
+    assert!(response.is_none());
+    assert!(pre_sign_decision(response).is_err());
+}
@@ -0,0 +1,356 @@
+use crate::config::{
+    DEFAULT_MAX_SIGNING_INTENT_TTL_MS, MAX_SIGNING_INTENT_TTL_MS, SIGNING_INTENT_VERSION,
+};
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::error::{SignerErrorCode, SigningIntentError};
+use crate::handlers::{
+    handle_create_signing_intent, handle_execute_signing_intent, CreateSigningIntentRequest,
+    ExecuteSigningIntentRequest, TransactionPayload,
+};
+use crate::signing_intent::{
+    issue_signing_intent, signing_intent_payload, signing_intent_ttl_ceiling_ms,
+    verify_signing_intent, SigningIntent,
+};
+use crate::state;
+use crate::strict_parsing::check_unknown_fields;
+use crate::tests::{block_on, in_fresh_worker};
+use crate::types::handlers::{RpcCallPayload, WorkerPolicy};
+use crate::types::worker_messages::WorkerRequestType;
+use crate::types::DecryptionPayload;
+use ciborium::Value as CborValue;
+use serde_json::json;
+
+const KEY: [u8; 32] = [5; 32];
+const ISSUED_AT_MS: f64 = 1_700_000_000_000.0;
+
+fn transactions(deposit: &str) -> Vec<TransactionPayload> {
+    vec![TransactionPayload {
+        near_account_id: "alice.testnet".to_string(),
+        receiver_id: "bob.testnet".to_string(),
+        actions: json!([{ "action_type": "Transfer", "deposit": deposit }]).to_string(),
+        depends_on: Vec::new(),
+    }]
+}
+
+fn intent_for(txs: &[TransactionPayload], expires_in_ms: f64) -> SigningIntent {
+    let (near_account_id, intent_digest) = signing_intent_payload(txs).unwrap();
+    SigningIntent {
+        version: SIGNING_INTENT_VERSION,
+        intent_id: "intent-1".to_string(),
+        near_account_id,
+        intent_digest,
+        tx_count: txs.len() as u32,
+        confirmation_request_id: "req-1".to_string(),
+        issued_at_ms: ISSUED_AT_MS,
+        expires_at_ms: ISSUED_AT_MS + expires_in_ms,
+        session_epoch: 0,
+    }
+}
+
+fn verify(
+    token: &str,
+    txs: &[TransactionPayload],
+    now_ms: f64,
+    ceiling_ms: u32,
+) -> Result<SigningIntent, SigningIntentError> {
+    let (near_account_id, intent_digest) = signing_intent_payload(txs).unwrap();
+    verify_signing_intent(
+        token,
+        &KEY,
+        &near_account_id,
+        &intent_digest,
+        now_ms,
+        ceiling_ms,
+    )
+}
+
+fn rpc_call() -> RpcCallPayload {
+    RpcCallPayload {
+        contract_id: "w3a-v1.testnet".to_string(),
+        near_rpc_url: "https://rpc.testnet.near.org".to_string(),
+        near_account_id: "alice.testnet".to_string(),
+    }
+}
+
+fn execute_request(token: &str) -> ExecuteSigningIntentRequest {
+    ExecuteSigningIntentRequest {
+        intent_token: token.to_string(),
+        rpc_call: rpc_call(),
+        decryption: DecryptionPayload::new("AAAA".to_string(), "BBBB".to_string()),
+        tx_signing_requests: transactions("1"),
+        worker_policy: None,
+        rpc_overrides: None,
+    }
+}
+
+#[test]
+fn test_intent_token_round_trips() {
+    let txs = transactions("1");
+    let intent = intent_for(&txs, 60_000.0);
+    let token = issue_signing_intent(&intent, &KEY).unwrap();
+    assert_eq!(
+        verify(&token, &txs, ISSUED_AT_MS + 1.0, MAX_SIGNING_INTENT_TTL_MS),
+        Ok(intent.clone())
+    );
+
+    // The digest covers receivers and actions, and does not depend on the signer's history
+    let (account, digest) = signing_intent_payload(&txs).unwrap();
+    assert_eq!(account, "alice.testnet");
+    assert_eq!(digest, intent.intent_digest);
+    assert_ne!(
+        signing_intent_payload(&transactions("2")).unwrap().1,
+        digest
+    );
+
+    let mut mixed = transactions("1");
+    mixed.push(TransactionPayload {
+        near_account_id: "carol.testnet".to_string(),
+        ..mixed[0].clone()
+    });
+    assert!(signing_intent_payload(&mixed).is_err());
+    assert!(signing_intent_payload(&[]).is_err());
+}
+
+#[test]
+fn test_tampered_tokens_are_invalid() {
+    let txs = transactions("1");
+    let intent = intent_for(&txs, 60_000.0);
+    let token = issue_signing_intent(&intent, &KEY).unwrap();
+    let now = ISSUED_AT_MS + 1.0;
+
+    // Re-encodes the envelope with the intent's expiry pushed out, keeping the MAC
+    let envelope = base64_url_decode(&token).unwrap();
+    let mut signed: CborValue = ciborium::from_reader(envelope.as_slice()).unwrap();
+    let fields = signed.as_map_mut().unwrap();
+    let extended = SigningIntent {
+        expires_at_ms: intent.expires_at_ms + 3_600_000.0,
+        ..intent.clone()
+    };
+    let mut extended_cbor = Vec::new();
+    ciborium::into_writer(&extended, &mut extended_cbor).unwrap();
+    fields[0].1 = CborValue::Bytes(extended_cbor);
+    let mut forged = Vec::new();
+    ciborium::into_writer(&signed, &mut forged).unwrap();
+
+    let other_session = issue_signing_intent(&intent, &[6; 32]).unwrap();
+    for bad in [
+        base64_url_encode(&forged),
+        other_session,
+        "not a token".to_string(),
+        base64_url_encode(&[0x01]),
+    ] {
+        let err = verify(&bad, &txs, now, MAX_SIGNING_INTENT_TTL_MS).unwrap_err();
+        assert_eq!(err.code(), SignerErrorCode::SigningIntentInvalid, "{}", err);
+    }
+
+    // A different payload under a valid token is a mismatch, not a forgery
+    let err = verify(
+        &token,
+        &transactions("1000"),
+        now,
+        MAX_SIGNING_INTENT_TTL_MS,
+    )
+    .unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::SigningIntentPayloadMismatch);
+    assert!(
+        err.to_string()
+            .starts_with("SigningIntentPayloadMismatch: "),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_expiry_and_policy_ttl_ceiling() {
+    let txs = transactions("1");
+    let token = issue_signing_intent(&intent_for(&txs, 60_000.0), &KEY).unwrap();
+
+    let err = verify(
+        &token,
+        &txs,
+        ISSUED_AT_MS + 60_000.0,
+        MAX_SIGNING_INTENT_TTL_MS,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        SigningIntentError::Expired {
+            expires_at_ms: ISSUED_AT_MS + 60_000.0
+        }
+    );
+    assert_eq!(err.code(), SignerErrorCode::SigningIntentExpired);
+
+    // A stricter ceiling at execution shortens outstanding intents
+    assert!(verify(&token, &txs, ISSUED_AT_MS + 20_000.0, 30_000).is_ok());
+    let err = verify(&token, &txs, ISSUED_AT_MS + 40_000.0, 30_000).unwrap_err();
+    assert_eq!(
+        err,
+        SigningIntentError::Expired {
+            expires_at_ms: ISSUED_AT_MS + 30_000.0
+        }
+    );
+
+    assert_eq!(
+        signing_intent_ttl_ceiling_ms(None),
+        DEFAULT_MAX_SIGNING_INTENT_TTL_MS
+    );
+    let policy = |max| WorkerPolicy {
+        max_signing_intent_ttl_ms: Some(max),
+        ..WorkerPolicy::default()
+    };
+    assert_eq!(signing_intent_ttl_ceiling_ms(Some(&policy(5_000))), 5_000);
+    assert_eq!(
+        signing_intent_ttl_ceiling_ms(Some(&policy(u32::MAX))),
+        MAX_SIGNING_INTENT_TTL_MS
+    );
+
+    // Creation refuses a TTL above the ceiling before prompting the user
+    let err = block_on(handle_create_signing_intent(CreateSigningIntentRequest {
+        rpc_call: rpc_call(),
+        tx_signing_requests: txs,
+        confirmation_config: None,
+        worker_policy: Some(policy(5_000)),
+        ttl_ms: Some(5_001),
+    }))
+    .unwrap_err();
+    assert_eq!(
+        err,
+        "ttlMs must be between 1 and 5000 (workerPolicy.maxSigningIntentTtlMs)"
+    );
+}
+
+#[test]
+fn test_executed_intents_cannot_be_replayed() {
+    assert!(state::consume_signing_intent("intent-a", f64::MAX));
+    assert!(!state::consume_signing_intent("intent-a", f64::MAX));
+    state::release_signing_intent("intent-a");
+    assert!(state::consume_signing_intent("intent-a", f64::MAX));
+
+    // Issued under this worker session's key and already consumed
+    let now = state::now_ms();
+    let intent = SigningIntent {
+        intent_id: "intent-b".to_string(),
+        issued_at_ms: now,
+        expires_at_ms: now + 60_000.0,
+        ..intent_for(&transactions("1"), 0.0)
+    };
+    let token = issue_signing_intent(&intent, &state::signing_intent_key().unwrap()).unwrap();
+    assert!(state::consume_signing_intent(
+        "intent-b",
+        intent.expires_at_ms
+    ));
+
+    let result = block_on(handle_execute_signing_intent(execute_request(&token))).unwrap();
+    assert!(!result.success);
+    assert_eq!(result.error_code.as_deref(), Some("SigningIntentReplayed"));
+    assert_eq!(
+        result.error.as_deref(),
+        Some("SigningIntentReplayed: signing intent intent-b was already executed")
+    );
+
+    // Wiping the worker rotates the session key, so the token no longer verifies
+    state::wipe_all_state();
+    let result = block_on(handle_execute_signing_intent(execute_request(&token))).unwrap();
+    assert_eq!(result.error_code.as_deref(), Some("SigningIntentInvalid"));
+}
+
+#[test]
+fn test_intents_verify_and_stay_consumed_in_later_workers() {
+    // Created in one worker...
+    let (token, intent, record) = in_fresh_worker(None, || {
+        let now = state::now_ms();
+        let intent = SigningIntent {
+            issued_at_ms: now,
+            expires_at_ms: now + 60_000.0,
+            ..intent_for(&transactions("1"), 0.0)
+        };
+        let token = issue_signing_intent(&intent, &state::signing_intent_key().unwrap()).unwrap();
+        (token, intent, crate::worker_state::take_changed_record())
+    });
+    let record = record.expect("the signing intent key is persisted");
+
+    // ...verified and consumed in the next one...
+    let (executing, consumed_record) = in_fresh_worker(Some(record.clone()), {
+        let token = token.clone();
+        let intent = intent.clone();
+        move || {
+            let (near_account_id, intent_digest) =
+                signing_intent_payload(&transactions("1")).unwrap();
+            let verified = verify_signing_intent(
+                &token,
+                &state::signing_intent_key().unwrap(),
+                &near_account_id,
+                &intent_digest,
+                state::now_ms(),
+                MAX_SIGNING_INTENT_TTL_MS,
+            );
+            assert_eq!(verified, Ok(intent.clone()));
+            (
+                state::consume_signing_intent(&intent.intent_id, intent.expires_at_ms),
+                crate::worker_state::take_changed_record(),
+            )
+        }
+    });
+    assert!(executing);
+
+    // ...and refused as a replay in the one after
+    let result = in_fresh_worker(consumed_record, {
+        let token = token.clone();
+        move || block_on(handle_execute_signing_intent(execute_request(&token))).unwrap()
+    });
+    assert_eq!(result.error_code.as_deref(), Some("SigningIntentReplayed"));
+
+    // Without the record the key is a new one
+    let result = in_fresh_worker(None, move || {
+        block_on(handle_execute_signing_intent(execute_request(&token))).unwrap()
+    });
+    assert_eq!(result.error_code.as_deref(), Some("SigningIntentInvalid"));
+}
+
+#[test]
+fn test_intents_from_an_earlier_session_epoch_are_refused() {
+    let now = state::now_ms();
+    let intent = SigningIntent {
+        issued_at_ms: now,
+        expires_at_ms: now + 60_000.0,
+        session_epoch: state::session_epoch(),
+        ..intent_for(&transactions("1"), 0.0)
+    };
+    let token = issue_signing_intent(&intent, &state::signing_intent_key().unwrap()).unwrap();
+
+    // A fresh passkey assertion renewed the session after the intent was confirmed
+    state::renew_session(now);
+    let result = block_on(handle_execute_signing_intent(execute_request(&token))).unwrap();
+    assert!(!result.success);
+    assert_eq!(result.error_code.as_deref(), Some("SessionExpired"));
+    assert!(result
+        .error
+        .as_deref()
+        .unwrap()
+        .contains("the signing intent was issued in session epoch 0"));
+    // Refused before it was consumed
+    assert!(state::consume_signing_intent(&intent.intent_id, intent.expires_at_ms));
+}
+
+#[test]
+fn test_strict_parsing_covers_signing_intent_requests() {
+    let payload = json!({
+        "intentToken": "AAAA",
+        "txSigningRequests": [],
+        "workerPolicy": { "strictParsing": true, "maxSigningIntentTtlMs": 60000 }
+    });
+    assert_eq!(
+        check_unknown_fields(WorkerRequestType::ExecuteSigningIntent, &payload),
+        Ok(())
+    );
+
+    let mut misspelled = payload.clone();
+    misspelled["intentTokn"] = json!("AAAA");
+    let err =
+        check_unknown_fields(WorkerRequestType::ExecuteSigningIntent, &misspelled).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::UnknownField);
+
+    let mut misspelled = payload;
+    misspelled["ttlMS"] = json!(1);
+    assert!(check_unknown_fields(WorkerRequestType::CreateSigningIntent, &misspelled).is_err());
+}
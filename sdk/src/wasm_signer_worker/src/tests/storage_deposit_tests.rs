@@ -0,0 +1,236 @@
+use crate::actions::ActionParams;
+use crate::config::STORAGE_DEPOSIT_GAS;
+use crate::confirmation_blocks::{batch_blocks, ConfirmationSummaryBlock, SummaryPolicy};
+use crate::error::{RpcErrorKind, SignerErrorCode};
+use crate::handlers::confirm_tx_details::{
+    compute_confirmation_intent_digest, confirmation_tx_signing_requests_json,
+};
+use crate::handlers::TransactionPayload;
+use crate::rpc_client::MockRpcClient;
+use crate::storage_deposit::{auto_inserted_actions, insert_storage_deposits, show_insertions};
+use crate::strict_parsing::check_unknown_fields;
+use crate::tests::block_on;
+use crate::types::handlers::{ConfirmationBehavior, ConfirmationConfig, ConfirmationUIMode};
+use crate::types::worker_messages::WorkerRequestType;
+use serde_json::{json, Value};
+
+const MIN_DEPOSIT: &str = "1250000000000000000000";
+
+fn call(method_name: &str, args: Value) -> Value {
+    json!({
+        "action_type": "FunctionCall",
+        "method_name": method_name,
+        "args": args.to_string(),
+        "gas": "30000000000000",
+        "deposit": "1"
+    })
+}
+
+fn tx(receiver_id: &str, actions: &[Value]) -> TransactionPayload {
+    TransactionPayload {
+        near_account_id: "alice.testnet".to_string(),
+        receiver_id: receiver_id.to_string(),
+        actions: json!(actions).to_string(),
+        depends_on: Vec::new(),
+    }
+}
+
+fn ft_transfer_call(receiver_id: &str) -> Value {
+    call(
+        "ft_transfer_call",
+        json!({ "receiver_id": receiver_id, "amount": "10", "msg": "" }),
+    )
+}
+
+/// A call_function result returning `bytes`
+fn view_returning(bytes: &[u8]) -> Result<Value, RpcErrorKind> {
+    Ok(json!({
+        "result": bytes,
+        "logs": [],
+        "block_height": 100,
+        "block_hash": "11111111111111111111111111111111"
+    }))
+}
+
+fn token_contract(balance: &str) -> MockRpcClient {
+    MockRpcClient::new()
+        .answer(
+            "query/call_function/storage_balance_of",
+            view_returning(balance.as_bytes()),
+        )
+        .answer(
+            "query/call_function/storage_balance_bounds",
+            view_returning(
+                json!({ "min": MIN_DEPOSIT, "max": null })
+                    .to_string()
+                    .as_bytes(),
+            ),
+        )
+}
+
+#[test]
+fn test_unregistered_receivers_get_one_storage_deposit_before_their_first_transfer() {
+    let rpc = token_contract("null");
+    let mut txs = vec![
+        tx("wrap.testnet", &[ft_transfer_call("dex.testnet")]),
+        tx(
+            "wrap.testnet",
+            &[
+                call(
+                    "ft_transfer",
+                    json!({ "receiver_id": "dex.testnet", "amount": "1" }),
+                ),
+                ft_transfer_call("bob.testnet"),
+            ],
+        ),
+    ];
+    let insertions = block_on(insert_storage_deposits(&rpc, &mut txs)).unwrap();
+
+    assert_eq!(insertions.len(), 2);
+    assert_eq!((insertions[0].tx_index, insertions[0].action_index), (0, 0));
+    assert_eq!(insertions[0].account_id, "dex.testnet");
+    assert_eq!(
+        insertions[0].deposit_yocto,
+        MIN_DEPOSIT.parse::<u128>().unwrap()
+    );
+    assert_eq!((insertions[1].tx_index, insertions[1].action_index), (1, 1));
+    assert_eq!(insertions[1].account_id, "bob.testnet");
+    assert_eq!(
+        auto_inserted_actions(&insertions, txs.len()),
+        vec![vec![0], vec![1]]
+    );
+
+    let first = txs[0].parsed_actions().unwrap();
+    assert_eq!(first.len(), 2);
+    assert_eq!(
+        first[0],
+        ActionParams::FunctionCall {
+            method_name: "storage_deposit".to_string(),
+            args: json!({ "account_id": "dex.testnet", "registration_only": true }).to_string(),
+            gas: STORAGE_DEPOSIT_GAS.to_string(),
+            deposit: MIN_DEPOSIT.to_string(),
+        }
+    );
+    assert!(
+        matches!(&first[1], ActionParams::FunctionCall { method_name, .. }
+        if method_name == "ft_transfer_call")
+    );
+    // dex.testnet is looked up once, and registered by the first insertion
+    assert_eq!(txs[1].parsed_actions().unwrap().len(), 3);
+    assert_eq!(
+        rpc.calls_to("query/call_function/storage_balance_of").len(),
+        2
+    );
+}
+
+#[test]
+fn test_registered_receivers_and_other_contracts_are_left_untouched() {
+    let untouched = |rpc: MockRpcClient, actions: &[Value]| {
+        let mut txs = vec![tx("wrap.testnet", actions)];
+        let before = txs[0].actions.clone();
+        let insertions = block_on(insert_storage_deposits(&rpc, &mut txs)).unwrap();
+        insertions.is_empty() && txs[0].actions == before
+    };
+    let transfer = [ft_transfer_call("dex.testnet")];
+
+    let registered = json!({ "total": MIN_DEPOSIT, "available": "0" }).to_string();
+    assert!(untouched(token_contract(&registered), &transfer));
+    // No storage standard: the view panics, or returns something other than JSON
+    let panicking = MockRpcClient::new().answer(
+        "query/call_function/storage_balance_of",
+        Err(RpcErrorKind::Rpc {
+            name: "CONTRACT_EXECUTION_ERROR".to_string(),
+            message: "MethodResolveError(MethodNotFound)".to_string(),
+            error: json!({ "cause": { "name": "CONTRACT_EXECUTION_ERROR" } }),
+        }),
+    );
+    assert!(untouched(panicking, &transfer));
+    assert!(untouched(token_contract("not json"), &transfer));
+    let without_bounds = MockRpcClient::new()
+        .answer(
+            "query/call_function/storage_balance_of",
+            view_returning(b"null"),
+        )
+        .answer(
+            "query/call_function/storage_balance_bounds",
+            view_returning(b"{}"),
+        );
+    assert!(untouched(without_bounds, &transfer));
+
+    // The batch registers the receiver itself, or names no receiver: nothing is looked up
+    // (an unanswered lookup would fail)
+    assert!(untouched(
+        MockRpcClient::new(),
+        &[
+            call("storage_deposit", json!({ "account_id": "dex.testnet" })),
+            ft_transfer_call("dex.testnet"),
+        ]
+    ));
+    assert!(untouched(
+        MockRpcClient::new(),
+        &[call("ft_transfer", json!({ "amount": "1" }))]
+    ));
+}
+
+#[test]
+fn test_lookup_failures_fail_the_request() {
+    let rpc = MockRpcClient::new().answer(
+        "query/call_function/storage_balance_of",
+        Err(RpcErrorKind::Unreachable("connection refused".to_string())),
+    );
+    let mut txs = vec![tx("wrap.testnet", &[ft_transfer_call("dex.testnet")])];
+    let before = txs[0].actions.clone();
+    let err = block_on(insert_storage_deposits(&rpc, &mut txs)).unwrap_err();
+    assert_eq!(err.code(), SignerErrorCode::RpcUnreachable);
+    assert_eq!(txs[0].actions, before);
+}
+
+#[test]
+fn test_insertions_are_shown_marked_and_digested() {
+    let rpc = token_contract("null");
+    let mut txs = vec![tx("wrap.testnet", &[ft_transfer_call("dex.testnet")])];
+    let insertions = block_on(insert_storage_deposits(&rpc, &mut txs)).unwrap();
+    let inserted = auto_inserted_actions(&insertions, txs.len());
+    let batch = vec![("wrap.testnet".to_string(), txs[0].parsed_actions().unwrap())];
+    let flags = vec![None];
+
+    let policy = SummaryPolicy::default();
+    let marked = SummaryPolicy::default().with_auto_inserted_actions(inserted.clone());
+    let shown = confirmation_tx_signing_requests_json(&batch, &flags, None, Some(&marked));
+    assert_eq!(shown[0]["autoInsertedActions"], json!([0]));
+    let unmarked = confirmation_tx_signing_requests_json(&batch, &flags, None, Some(&policy));
+    assert!(unmarked[0].get("autoInsertedActions").is_none());
+    assert_ne!(
+        compute_confirmation_intent_digest(&batch, &flags, None, Some(&policy)).unwrap(),
+        compute_confirmation_intent_digest(&batch, &flags, None, Some(&marked)).unwrap()
+    );
+    assert!(batch_blocks(&marked).iter().any(|block| matches!(
+        block,
+        ConfirmationSummaryBlock::Warning { text, .. }
+            if text.starts_with("1 storage_deposit was added automatically")
+    )));
+    assert!(batch_blocks(&policy).is_empty());
+
+    // A skipped confirmation is shown when something was inserted
+    let skip = Some(ConfirmationConfig::new(
+        ConfirmationUIMode::Skip,
+        ConfirmationBehavior::AutoProceed,
+    ));
+    let mut logs = Vec::new();
+    let shown = show_insertions(skip.clone(), &inserted, &mut logs).unwrap();
+    assert_eq!(shown.ui_mode, ConfirmationUIMode::Modal);
+    assert_eq!(
+        show_insertions(skip, &[vec![]], &mut logs).unwrap().ui_mode,
+        ConfirmationUIMode::Skip
+    );
+
+    let payload = json!({
+        "txSigningRequests": [],
+        "autoStorageDeposit": true,
+        "workerPolicy": { "strictParsing": true }
+    });
+    assert_eq!(
+        check_unknown_fields(WorkerRequestType::SignTransactionsWithActions, &payload),
+        Ok(())
+    );
+}
@@ -0,0 +1,111 @@
+use crate::stored_records::{migrate_stored_record, StoredRecord};
+use serde_json::Value;
+
+/// Records as written by each earlier release, oldest first per store
+const FIXTURES: &[(&str, &str)] = &[
+    (
+        "user_snake_case_vrf_keypair",
+        include_str!("migration_fixtures/user_snake_case_vrf_keypair.json"),
+    ),
+    (
+        "user_camel_case_device_number",
+        include_str!("migration_fixtures/user_camel_case_device_number.json"),
+    ),
+    (
+        "user_current_server_encrypted_vrf",
+        include_str!("migration_fixtures/user_current_server_encrypted_vrf.json"),
+    ),
+    (
+        "near_key_without_device_number",
+        include_str!("migration_fixtures/near_key_without_device_number.json"),
+    ),
+    (
+        "near_key_with_device_number",
+        include_str!("migration_fixtures/near_key_with_device_number.json"),
+    ),
+    (
+        "near_key_current_outer_wrapped",
+        include_str!("migration_fixtures/near_key_current_outer_wrapped.json"),
+    ),
+];
+
+fn fixture(name: &str) -> Value {
+    let (_, json) = FIXTURES.iter().find(|(n, _)| *n == name).unwrap();
+    serde_json::from_str(json).unwrap()
+}
+
+#[test]
+fn test_every_fixture_deserializes_into_current_types() {
+    for (name, json) in FIXTURES {
+        let record: Value = serde_json::from_str(json).unwrap();
+        let parsed = StoredRecord::from_json(record).unwrap_or_else(|e| panic!("{}: {}", name, e));
+        match (&parsed, name.starts_with("user_")) {
+            (StoredRecord::User(user), true) => {
+                assert!(!user
+                    .encrypted_vrf_keypair
+                    .encrypted_vrf_data_b64u
+                    .is_empty());
+                assert!(!user.encrypted_vrf_keypair.chacha20_nonce_b64u.is_empty());
+            }
+            (StoredRecord::NearKey(key), false) => {
+                assert!(!key.encrypted_data.is_empty());
+                assert!(!key.iv.is_empty());
+            }
+            _ => panic!("{} parsed as the wrong record kind", name),
+        }
+
+        // Migrating is idempotent
+        let migrated = migrate_stored_record(serde_json::from_str(json).unwrap()).unwrap();
+        assert_eq!(
+            migrate_stored_record(migrated.clone()).unwrap(),
+            migrated,
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_migration_upgrades_legacy_fields() {
+    let legacy = fixture("user_snake_case_vrf_keypair");
+    let migrated = migrate_stored_record(legacy.clone()).unwrap();
+    let vrf = &migrated["encryptedVrfKeypair"];
+    assert_eq!(
+        vrf["encryptedVrfDataB64u"],
+        legacy["encryptedVrfKeypair"]["encrypted_vrf_data_b64u"]
+    );
+    assert_eq!(
+        vrf["chacha20NonceB64u"],
+        legacy["encryptedVrfKeypair"]["chacha20_nonce_b64u"]
+    );
+    assert!(vrf.get("encrypted_vrf_data_b64u").is_none());
+    assert_eq!(migrated["deviceNumber"], 1);
+    // Fields the migration does not model are kept
+    assert_eq!(migrated["passkeyCredential"], legacy["passkeyCredential"]);
+    assert_eq!(migrated["preferences"], legacy["preferences"]);
+
+    let migrated = migrate_stored_record(fixture("near_key_without_device_number")).unwrap();
+    assert_eq!(migrated["deviceNumber"], 1);
+    assert!(migrated.get("outerWrap").is_none());
+}
+
+#[test]
+fn test_current_records_are_unchanged() {
+    for name in [
+        "user_current_server_encrypted_vrf",
+        "near_key_with_device_number",
+        "near_key_current_outer_wrapped",
+    ] {
+        assert_eq!(
+            migrate_stored_record(fixture(name)).unwrap(),
+            fixture(name),
+            "{}",
+            name
+        );
+    }
+    assert!(
+        migrate_stored_record(serde_json::json!({ "key": "lastUserAccountId" }))
+            .unwrap_err()
+            .starts_with("Unrecognized stored record")
+    );
+}
@@ -0,0 +1,215 @@
+use crate::error::{SignerErrorCode, StrictParseError};
+use crate::handlers::SignTransactionsWithActionsRequest;
+use crate::strict_parsing::{check_unknown_fields, strict_parsing_requested};
+use crate::types::worker_messages::WorkerRequestType;
+use serde_json::{json, Value};
+
+const SIGN: WorkerRequestType = WorkerRequestType::SignTransactionsWithActions;
+
+/// A well-formed SignTransactionsWithActions payload using every checked field
+fn sign_payload(strict: bool) -> Value {
+    let actions = json!([
+        { "action_type": "FunctionCall", "method_name": "ft_transfer", "args": "{}",
+          "gas": "30000000000000", "deposit": "1" },
+        { "action_type": "Transfer", "deposit": "1000" },
+        { "action_type": "CreateAccount" }
+    ]);
+    json!({
+        "rpcCall": {
+            "contractId": "w3a-v1.testnet",
+            "nearRpcUrl": "https://rpc.testnet.near.org",
+            "nearAccountId": "alice.testnet"
+        },
+        "decryption": { "encryptedPrivateKeyData": "AAAA", "encryptedPrivateKeyIv": "BBBB" },
+        "txSigningRequests": [{
+            "nearAccountId": "alice.testnet",
+            "receiverId": "usdc.testnet",
+            "actions": actions.to_string()
+        }],
+        "confirmationConfig": {
+            "uiMode": "modal", "behavior": "requireClick", "autoProceedDelay": null,
+            "confirmationTimeoutMs": 60000, "theme": "dark"
+        },
+        "workerPolicy": {
+            "preSignHook": true, "postSignHook": false, "hookTimeoutMs": 5000,
+            "averageBlockTimeMs": 1000, "indexerUrl": null, "strictParsing": strict
+        }
+    })
+}
+
+fn misspell(payload: &mut Value, pointer: &str, misspelling: &str) {
+    let (parent, key) = pointer.rsplit_once('/').unwrap();
+    let object = payload
+        .pointer_mut(parent)
+        .unwrap()
+        .as_object_mut()
+        .unwrap();
+    let value = object.remove(key).unwrap();
+    object.insert(misspelling.to_string(), value);
+}
+
+fn misspell_action(payload: &mut Value, action: usize, key: &str, misspelling: &str) {
+    let actions_json = payload.pointer("/txSigningRequests/0/actions").unwrap();
+    let mut actions: Value = serde_json::from_str(actions_json.as_str().unwrap()).unwrap();
+    misspell(&mut actions, &format!("/{}/{}", action, key), misspelling);
+    *payload.pointer_mut("/txSigningRequests/0/actions").unwrap() = json!(actions.to_string());
+}
+
+fn unknown_at(path: &str) -> Result<(), StrictParseError> {
+    Err(StrictParseError::UnknownField {
+        path: path.to_string(),
+    })
+}
+
+#[test]
+fn test_well_formed_payload_passes_strict_parsing() {
+    let payload = sign_payload(true);
+    assert!(strict_parsing_requested(&payload));
+    assert_eq!(check_unknown_fields(SIGN, &payload), Ok(()));
+    assert!(serde_json::from_value::<SignTransactionsWithActionsRequest>(payload).is_ok());
+}
+
+#[test]
+fn test_common_misspellings_are_rejected_with_their_pointer() {
+    let fixtures: &[(&str, &str, &str)] = &[
+        (
+            "/confirmationConfig",
+            "confirmationConifg",
+            "/confirmationConifg",
+        ),
+        (
+            "/confirmationConfig/uiMode",
+            "uimode",
+            "/confirmationConfig/uimode",
+        ),
+        (
+            "/confirmationConfig/behavior",
+            "behaviour",
+            "/confirmationConfig/behaviour",
+        ),
+        (
+            "/confirmationConfig/autoProceedDelay",
+            "autoProceedDelayMs",
+            "/confirmationConfig/autoProceedDelayMs",
+        ),
+        (
+            "/txSigningRequests/0/receiverId",
+            "recieverId",
+            "/txSigningRequests/0/recieverId",
+        ),
+        (
+            "/txSigningRequests",
+            "txSigningRequest",
+            "/txSigningRequest",
+        ),
+        ("/rpcCall/nearRpcUrl", "nearRpcURL", "/rpcCall/nearRpcURL"),
+        ("/rpcCall/contractId", "contractID", "/rpcCall/contractID"),
+        (
+            "/decryption/encryptedPrivateKeyIv",
+            "encryptedPrivateKeyIV",
+            "/decryption/encryptedPrivateKeyIV",
+        ),
+        (
+            "/workerPolicy/preSignHook",
+            "preSignHooks",
+            "/workerPolicy/preSignHooks",
+        ),
+        (
+            "/workerPolicy/hookTimeoutMs",
+            "hookTimeout",
+            "/workerPolicy/hookTimeout",
+        ),
+    ];
+    for (pointer, misspelling, expected_path) in fixtures {
+        let mut payload = sign_payload(true);
+        misspell(&mut payload, pointer, misspelling);
+        assert_eq!(
+            check_unknown_fields(SIGN, &payload),
+            unknown_at(expected_path),
+            "{}",
+            misspelling
+        );
+    }
+
+    let action_fixtures: &[(usize, &str, &str, &str)] = &[
+        (
+            0,
+            "method_name",
+            "methodName",
+            "/txSigningRequests/0/actions/0/methodName",
+        ),
+        (
+            0,
+            "deposit",
+            "attached_deposit",
+            "/txSigningRequests/0/actions/0/attached_deposit",
+        ),
+        (
+            1,
+            "deposit",
+            "amount",
+            "/txSigningRequests/0/actions/1/amount",
+        ),
+    ];
+    for (action, key, misspelling, expected_path) in action_fixtures {
+        let mut payload = sign_payload(true);
+        misspell_action(&mut payload, *action, key, misspelling);
+        assert_eq!(
+            check_unknown_fields(SIGN, &payload),
+            unknown_at(expected_path),
+            "{}",
+            misspelling
+        );
+    }
+}
+
+#[test]
+fn test_lenient_parsing_is_the_default() {
+    // The misspelled config is silently dropped when strict parsing is off
+    for omit_flag in [false, true] {
+        let mut payload = sign_payload(false);
+        if omit_flag {
+            payload["workerPolicy"]
+                .as_object_mut()
+                .unwrap()
+                .remove("strictParsing");
+        }
+        misspell(&mut payload, "/confirmationConfig", "confirmationConifg");
+        assert!(!strict_parsing_requested(&payload));
+        assert_eq!(check_unknown_fields(SIGN, &payload), Ok(()));
+        let request: SignTransactionsWithActionsRequest = serde_json::from_value(payload).unwrap();
+        assert!(request.confirmation_config.is_none());
+    }
+
+    // A misspelled strictParsing flag leaves the request lenient (it is itself unknown)
+    let mut payload = sign_payload(true);
+    misspell(&mut payload, "/workerPolicy/strictParsing", "strictParse");
+    assert_eq!(check_unknown_fields(SIGN, &payload), Ok(()));
+
+    // Request types without a WorkerPolicy are never checked
+    let payload = json!({ "workerPolicy": { "strictParsing": true }, "anything": 1 });
+    assert_eq!(
+        check_unknown_fields(WorkerRequestType::SignNep413Message, &payload),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_unknown_field_error_reports_code_and_escaped_pointer() {
+    let mut payload = sign_payload(true);
+    payload["rpcCall"]["a/b~c"] = json!(1);
+    let err = check_unknown_fields(SIGN, &payload).unwrap_err();
+    assert_eq!(err, unknown_at("/rpcCall/a~1b~0c").unwrap_err());
+    assert_eq!(err.code(), SignerErrorCode::UnknownField);
+    assert_eq!(
+        err.to_string(),
+        "UnknownField: unknown field at /rpcCall/a~1b~0c"
+    );
+
+    let mut payload = json!({ "accountId": "alice.testnet", "limti": 5 });
+    payload["workerPolicy"] = json!({ "strictParsing": true });
+    assert_eq!(
+        check_unknown_fields(WorkerRequestType::GetRecentReceivers, &payload),
+        unknown_at("/limti")
+    );
+}
@@ -0,0 +1,373 @@
+use serde_json::json;
+
+use crate::crypto::{decrypt_private_key_with_prf, derive_ed25519_key_from_prf_output};
+use crate::encoders::base64_url_encode;
+use crate::error::SignerErrorCode;
+use crate::handlers::handle_derive_near_keypair_and_encrypt::handle_derive_near_keypair_and_encrypt;
+use crate::handlers::handle_recover_keypair_from_passkey::handle_recover_keypair_from_passkey;
+use crate::handlers::{handle_derive_sub_key, DeriveSubKeyRequest};
+use crate::sub_keys::*;
+use crate::tests::block_on;
+
+const ACCOUNT_ID: &str = "alice.testnet";
+const PATH: &str = "m/44'/397'/0'/0'/1'";
+
+// SLIP-0010 test vectors for ed25519
+const VECTOR_1_SEED: &str = "000102030405060708090a0b0c0d0e0f";
+const VECTOR_2_SEED: &str = "fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a29f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542";
+
+fn chacha20_prf_output() -> String {
+    base64_url_encode(&[1u8; 32])
+}
+
+fn ed25519_prf_output() -> String {
+    base64_url_encode(&[2u8; 32])
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn unhex(digits: &str) -> Vec<u8> {
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn path(path: &str) -> DerivationPath {
+    DerivationPath::parse(path).unwrap()
+}
+
+/// (path, private key, public key with SLIP-0010's 00 prefix) in hex
+fn assert_vectors(seed: &str, vectors: &[(&str, &str, &str)]) {
+    let seed = unhex(seed);
+    for (derivation_path, private_key, public_key) in vectors {
+        let node = slip10_derive_path(&seed, &path(derivation_path));
+        assert_eq!(hex(node.key.as_slice()), *private_key, "{}", derivation_path);
+        assert_eq!(
+            format!("00{}", hex(&node.public_key())),
+            *public_key,
+            "{}",
+            derivation_path
+        );
+    }
+}
+
+#[test]
+fn test_slip10_ed25519_vector_1() {
+    let seed = unhex(VECTOR_1_SEED);
+    let master = slip10_master_key(&seed);
+    assert_eq!(
+        hex(master.key.as_slice()),
+        "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7"
+    );
+    assert_eq!(
+        hex(master.chain_code.as_slice()),
+        "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb"
+    );
+    let child = slip10_derive_child(&master, 0);
+    assert_eq!(
+        hex(child.chain_code.as_slice()),
+        "8b59aa11380b624e81507a27fedda59fea6d0b779a778918a2fd3590e16e9c69"
+    );
+
+    assert_vectors(
+        VECTOR_1_SEED,
+        &[
+            (
+                "m/0'",
+                "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3",
+                "008c8a13df77a28f3445213a0f432fde644acaa215fc72dcdf300d5efaa85d350c",
+            ),
+            (
+                "m/0'/1'/2'/2'/1000000000'",
+                "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793",
+                "003c24da049451555d51a7014a37337aa4e12d41e485abccfa46b47dfb2af54b7a",
+            ),
+        ],
+    );
+    let seed = unhex(VECTOR_1_SEED);
+    for (derivation_path, private_key) in [
+        (
+            "m/0H/1H",
+            "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2",
+        ),
+        (
+            "m/0H/1H/2H",
+            "92a5b23c0b8a99e37d07df3fb9966917f5d06e02ddbd909c7e184371463e9fc9",
+        ),
+        (
+            "m/0H/1H/2H/2H",
+            "30d1dc7e5fc04c31219ab25a27ae00b50f6fd66622f6e9c913253d6511d1e662",
+        ),
+    ] {
+        let node = slip10_derive_path(&seed, &path(derivation_path));
+        assert_eq!(hex(node.key.as_slice()), private_key, "{}", derivation_path);
+    }
+}
+
+#[test]
+fn test_slip10_ed25519_vector_2() {
+    assert_vectors(
+        VECTOR_2_SEED,
+        &[
+            (
+                "m/0'",
+                "1559eb2bbec5790b0c65d8693e4d0875b1747f4970ae8b650486ed7470845635",
+                "0086fab68dcb57aa196c77c5f264f215a112c22a912c10d123b0d03c3c28ef1037",
+            ),
+            (
+                "m/0'/2147483647'",
+                "ea4f5bfe8694d8bb74b7b59404632fd5968b774ed545e810de9c32a4fb4192f4",
+                "005ba3b9ac6e90e83effcd25ac4e58a1365a9e35a3d3ae5eb07b9e4d90bcf7506d",
+            ),
+        ],
+    );
+    let seed = unhex(VECTOR_2_SEED);
+    let master = slip10_master_key(&seed);
+    assert_eq!(
+        hex(master.key.as_slice()),
+        "171cb88b1b3c1db25add599712e36245d75bc65a1a5c9e18d76f9f2b1eab4012"
+    );
+    assert_eq!(
+        format!("00{}", hex(&master.public_key())),
+        "008fe9693f8fa62a4305a140b9764c5ee01e455963744fe18204b4fb948249308a"
+    );
+    let node = slip10_derive_path(&seed, &path("m/0'/2147483647'/1'/2147483646'/2'"));
+    assert_eq!(
+        hex(node.key.as_slice()),
+        "551d333177df541ad876a60ea71f00447931c0a9da16f227c11ea080d7391b8d"
+    );
+}
+
+#[test]
+fn test_paths_are_hardened_only() {
+    let parsed = path("m/44h/397H/0'/0'/1'");
+    assert_eq!(parsed.indexes(), &[44, 397, 0, 0, 1]);
+    assert_eq!(parsed.to_string(), PATH);
+    assert_eq!(path("m/2147483647'").indexes(), &[2_147_483_647]);
+
+    for (invalid, reason) in [
+        ("m/44'/397'/0'/0/1'", "not hardened"),
+        ("44'/397'", "must start with \"m\""),
+        ("M/44'", "must start with \"m\""),
+        ("", "must start with \"m\""),
+        ("m", "master key"),
+        ("m/", "not hardened"),
+        ("m/'", "not a number"),
+        ("m/-1'", "not a number"),
+        ("m/+1'", "not a number"),
+        ("m/0x1'", "not a number"),
+        ("m/2147483648'", "2^31 or more"),
+        ("m/99999999999'", "2^31 or more"),
+        ("m/0'/", "not hardened"),
+        ("m/0'/1'/2'/3'/4'/5'/6'/7'/8'/9'/10'", "11 deep"),
+    ] {
+        let err = DerivationPath::parse(invalid).unwrap_err();
+        assert_eq!(err.code(), SignerErrorCode::InvalidDerivationPath);
+        assert!(err.to_string().contains(reason), "{}: {}", invalid, err);
+        assert!(err.to_string().starts_with("InvalidDerivationPath: "));
+    }
+    assert!(DerivationPath::parse("m/0'/1'/2'/3'/4'/5'/6'/7'/8'/9'").is_ok());
+}
+
+#[test]
+fn test_sub_key_path_lists_are_bounded_and_unique() {
+    let paths: Vec<String> = (0..32)
+        .map(|k| format!("m/44'/397'/0'/0'/{}'", k))
+        .collect();
+    assert_eq!(parse_sub_key_paths(&paths).unwrap().len(), 32);
+
+    let mut too_many = paths.clone();
+    too_many.push("m/44'/397'/0'/0'/32'".to_string());
+    assert!(parse_sub_key_paths(&too_many).is_err());
+
+    // Duplicates are found in canonical form
+    let duplicate = vec![PATH.to_string(), "m/44h/397h/0h/0h/1h".to_string()];
+    let err = parse_sub_key_paths(&duplicate).unwrap_err();
+    assert!(err.to_string().contains("listed twice"));
+}
+
+#[test]
+fn test_sub_keys_are_deterministic_and_separate_from_the_account_key() {
+    let prf = ed25519_prf_output();
+    let (first_private, first_public) = derive_sub_key(&prf, ACCOUNT_ID, &path(PATH)).unwrap();
+    let (second_private, second_public) = derive_sub_key(&prf, ACCOUNT_ID, &path(PATH)).unwrap();
+    assert_eq!(*first_private, *second_private);
+    assert_eq!(first_public, second_public);
+
+    let (_, account_public) = derive_ed25519_key_from_prf_output(&prf, ACCOUNT_ID).unwrap();
+    let (_, other_path) = derive_sub_key(&prf, ACCOUNT_ID, &path("m/44'/397'/0'/0'/2'")).unwrap();
+    let (_, other_account) = derive_sub_key(&prf, "bob.testnet", &path(PATH)).unwrap();
+    let distinct = [&account_public, &first_public, &other_path, &other_account];
+    for (i, a) in distinct.iter().enumerate() {
+        for b in &distinct[i + 1..] {
+            assert_ne!(a, b);
+        }
+    }
+
+    // The NEAR private key is the seed followed by the public key
+    let private_bytes = bs58::decode(first_private.trim_start_matches("ed25519:"))
+        .into_vec()
+        .unwrap();
+    let public_bytes = bs58::decode(first_public.trim_start_matches("ed25519:"))
+        .into_vec()
+        .unwrap();
+    assert_eq!(private_bytes.len(), 64);
+    assert_eq!(&private_bytes[32..], public_bytes.as_slice());
+
+    assert!(derive_sub_key("", ACCOUNT_ID, &path(PATH)).is_err());
+}
+
+#[test]
+fn test_derive_sub_key_message_encrypts_on_request() {
+    let request = |encrypt: bool| -> DeriveSubKeyRequest {
+        serde_json::from_value(json!({
+            "nearAccountId": ACCOUNT_ID,
+            "dualPrfOutputs": {
+                "chacha20PrfOutput": chacha20_prf_output(),
+                "ed25519PrfOutput": ed25519_prf_output()
+            },
+            "path": "m/44h/397h/0h/0h/1h",
+            "encrypt": encrypt
+        }))
+        .unwrap()
+    };
+
+    let public_only = block_on(handle_derive_sub_key(request(false))).unwrap();
+    assert_eq!(public_only.path, PATH);
+    assert_eq!(public_only.encrypted_data, None);
+    assert_eq!(public_only.iv, None);
+
+    let encrypted = block_on(handle_derive_sub_key(request(true))).unwrap();
+    assert_eq!(encrypted.public_key, public_only.public_key);
+    let signing_key = decrypt_private_key_with_prf(
+        ACCOUNT_ID,
+        &chacha20_prf_output(),
+        &encrypted.encrypted_data.unwrap(),
+        &encrypted.iv.unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        format!(
+            "ed25519:{}",
+            bs58::encode(signing_key.verifying_key().to_bytes()).into_string()
+        ),
+        public_only.public_key
+    );
+
+    let mut unhardened = request(false);
+    unhardened.path = "m/44'/397'/0'/0'/1".to_string();
+    let err = block_on(handle_derive_sub_key(unhardened)).unwrap_err();
+    assert!(err.starts_with("InvalidDerivationPath: "), "{}", err);
+}
+
+fn registration_request(extra: serde_json::Value) -> serde_json::Value {
+    let mut request = json!({
+        "nearAccountId": ACCOUNT_ID,
+        "credential": {
+            "id": "cred",
+            "rawId": "cred",
+            "type": "public-key",
+            "authenticatorAttachment": null,
+            "response": {
+                "clientDataJSON": base64_url_encode(b"{}"),
+                "attestationObject": base64_url_encode(b"not cbor"),
+                "transports": []
+            },
+            "clientExtensionResults": {}
+        },
+        "dualPrfOutputs": {
+            "chacha20PrfOutput": chacha20_prf_output(),
+            "ed25519PrfOutput": ed25519_prf_output()
+        }
+    });
+    request
+        .as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    request
+}
+
+#[test]
+fn test_registration_and_recovery_record_sub_keys() {
+    let (_, account_public) =
+        derive_ed25519_key_from_prf_output(&ed25519_prf_output(), ACCOUNT_ID).unwrap();
+    let (_, sub_key_public) =
+        derive_sub_key(&ed25519_prf_output(), ACCOUNT_ID, &path(PATH)).unwrap();
+
+    // Without subKeyPaths the account key is derived as before, with no sub-keys
+    let plain = block_on(handle_derive_near_keypair_and_encrypt(
+        serde_json::from_value(registration_request(json!({}))).unwrap(),
+    ))
+    .unwrap();
+    assert_eq!(plain.public_key, account_public);
+    assert!(plain.sub_keys.is_empty());
+
+    let registered = block_on(handle_derive_near_keypair_and_encrypt(
+        serde_json::from_value(registration_request(
+            json!({ "subKeyPaths": ["m/44h/397h/0h/0h/1h"] }),
+        ))
+        .unwrap(),
+    ))
+    .unwrap();
+    assert_eq!(registered.public_key, account_public);
+    assert_eq!(
+        registered.sub_keys,
+        vec![SubKeyEntry {
+            path: PATH.to_string(),
+            public_key: sub_key_public.clone(),
+        }]
+    );
+
+    let recovered = block_on(handle_recover_keypair_from_passkey(
+        serde_json::from_value(json!({
+            "credential": {
+                "id": "cred",
+                "rawId": "cred",
+                "type": "public-key",
+                "authenticatorAttachment": null,
+                "response": {
+                    "clientDataJSON": base64_url_encode(b"{}"),
+                    "authenticatorData": "AAAA",
+                    "signature": "AAAA",
+                    "userHandle": null
+                },
+                "clientExtensionResults": { "prf": { "results": {
+                    "first": chacha20_prf_output(),
+                    "second": ed25519_prf_output()
+                } } }
+            },
+            "accountIdHint": ACCOUNT_ID,
+            "subKeyPaths": [PATH]
+        }))
+        .unwrap(),
+    ))
+    .unwrap();
+    assert_eq!(recovered.public_key, account_public);
+    assert_eq!(recovered.sub_keys, registered.sub_keys);
+}
+
+#[test]
+fn test_registration_refuses_sub_keys_it_cannot_derive() {
+    let without_prf = registration_request(json!({
+        "dualPrfOutputs": null,
+        "prfSupported": false,
+        "prfFallback": { "scheme": "largeBlob" },
+        "workerPolicy": { "prfFallbackSchemes": ["largeBlob"] },
+        "subKeyPaths": [PATH]
+    }));
+    let err = block_on(handle_derive_near_keypair_and_encrypt(
+        serde_json::from_value(without_prf).unwrap(),
+    ))
+    .unwrap_err();
+    assert!(err.contains("subKeyPaths need PRF output"), "{}", err);
+
+    let unhardened = registration_request(json!({ "subKeyPaths": ["m/44'/397'/0/0/1"] }));
+    let err = block_on(handle_derive_near_keypair_and_encrypt(
+        serde_json::from_value(unhardened).unwrap(),
+    ))
+    .unwrap_err();
+    assert!(err.starts_with("InvalidDerivationPath: "), "{}", err);
+}
@@ -0,0 +1,196 @@
+use crate::actions::ActionParams;
+use crate::confirmation_blocks::{
+    transaction_summary_blocks, ConfirmationSummaryBlock, SummaryPolicy, SummaryWarningCode,
+};
+use crate::confirmation_speech::SpeechLocale;
+use crate::error::{SignerErrorCode, SubaccountError};
+use crate::handlers::handle_compose_create_subaccount::{
+    handle_compose_create_subaccount, ComposeCreateSubaccountRequest,
+};
+use crate::subaccount::*;
+use crate::tests::block_on;
+use crate::transaction::build_actions_from_params;
+
+const PARENT: &str = "myapp.testnet";
+const PUBLIC_KEY: &str = "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp";
+/// 0.1 NEAR
+const INITIAL_BALANCE: &str = "100000000000000000000000";
+
+fn request(signer: &str, prefix: &str) -> ComposeCreateSubaccountRequest {
+    ComposeCreateSubaccountRequest {
+        near_account_id: signer.to_string(),
+        parent_account_id: PARENT.to_string(),
+        child_prefix: prefix.to_string(),
+        initial_balance: INITIAL_BALANCE.to_string(),
+        public_key: PUBLIC_KEY.to_string(),
+    }
+}
+
+fn compose(prefix: &str) -> Result<(String, Vec<ActionParams>), SubaccountError> {
+    compose_create_subaccount(
+        PARENT,
+        &AccountSuffixPolicy::new(PARENT).unwrap(),
+        prefix,
+        INITIAL_BALANCE,
+        PUBLIC_KEY,
+    )
+}
+
+#[test]
+fn test_compose_create_subaccount_emits_the_canonical_transaction() {
+    let result = block_on(handle_compose_create_subaccount(request(PARENT, "alice"))).unwrap();
+    assert_eq!(result.receiver_id, "alice.myapp.testnet");
+    let actions: Vec<ActionParams> = serde_json::from_str(&result.actions).unwrap();
+    assert_eq!(actions.len(), 3);
+    assert_eq!(actions[0], ActionParams::CreateAccount);
+    assert_eq!(
+        actions[1],
+        ActionParams::Transfer {
+            deposit: INITIAL_BALANCE.to_string()
+        }
+    );
+    match &actions[2] {
+        ActionParams::AddKey {
+            public_key,
+            access_key,
+        } => {
+            assert_eq!(public_key, PUBLIC_KEY);
+            assert!(crate::confirmation_blocks::is_full_access_key(access_key));
+        }
+        other => panic!("expected AddKey, got {:?}", other),
+    }
+    // Signable as-is
+    build_actions_from_params(actions.clone()).unwrap();
+    assert_eq!(as_create_subaccount(&actions), Some(10u128.pow(23)));
+}
+
+#[test]
+fn test_child_prefix_validation() {
+    for prefix in ["bob", "b0b", "bob-1", "bob_smith", "7"] {
+        assert!(compose(prefix).is_ok(), "{}", prefix);
+    }
+    for prefix in ["", "Bob", "bob!", "-bob", "bob-", "bo--b", "bo_-b", "bób"] {
+        match compose(prefix) {
+            Err(SubaccountError::Invalid { field, .. }) => assert_eq!(field, "childPrefix"),
+            other => panic!("{:?} accepted: {:?}", prefix, other),
+        }
+    }
+
+    // The full name, not the prefix, is bounded: 64 characters at most
+    let longest = "a".repeat(64 - PARENT.len() - 1);
+    assert_eq!(compose(&longest).unwrap().0.len(), 64);
+    let err = compose(&format!("{}a", longest)).unwrap_err();
+    assert!(err.to_string().contains("above 64"), "{}", err);
+    assert_eq!(err.code(), None);
+
+    let err = AccountSuffixPolicy::new("Not.Valid").unwrap_err();
+    assert!(
+        err.to_string().starts_with("Invalid parentAccountId"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_non_child_accounts_are_refused() {
+    // The signer is not the parent
+    let err = block_on(handle_compose_create_subaccount(request(
+        "mallory.testnet",
+        "alice",
+    )))
+    .unwrap_err();
+    assert_eq!(
+        err,
+        "NotAChildAccount: alice.myapp.testnet is not a direct subaccount of mallory.testnet, \
+         which signs its creation"
+    );
+
+    // A dotted prefix would be a grandchild
+    let err = compose("alice.evil").unwrap_err();
+    assert_eq!(err.code(), Some(SignerErrorCode::NotAChildAccount));
+    assert_eq!(
+        err,
+        SubaccountError::NotAChildAccount {
+            account_id: "alice.evil.myapp.testnet".to_string(),
+            signer_id: PARENT.to_string(),
+        }
+    );
+
+    let policy = AccountSuffixPolicy::new(PARENT).unwrap();
+    assert!(policy.is_child("alice.myapp.testnet"));
+    for account_id in [
+        "myapp.testnet",
+        "alice.evil.myapp.testnet",
+        "alicemyapp.testnet",
+        ".myapp.testnet",
+        "alice.testnet",
+    ] {
+        assert!(!policy.is_child(account_id), "{}", account_id);
+    }
+}
+
+#[test]
+fn test_other_inputs_are_validated() {
+    let policy = AccountSuffixPolicy::new(PARENT).unwrap();
+    let err = compose_create_subaccount(PARENT, &policy, "alice", "0.1", PUBLIC_KEY).unwrap_err();
+    assert!(
+        err.to_string().starts_with("Invalid initialBalance"),
+        "{}",
+        err
+    );
+    let err = compose_create_subaccount(PARENT, &policy, "alice", INITIAL_BALANCE, "ed25519:abc")
+        .unwrap_err();
+    assert!(err.to_string().starts_with("Invalid publicKey"), "{}", err);
+}
+
+#[test]
+fn test_subaccount_creation_is_summarized_as_one_block() {
+    let (receiver_id, actions) = compose("alice").unwrap();
+    let blocks =
+        transaction_summary_blocks(&receiver_id, &actions, None, &SummaryPolicy::default());
+
+    let headings: Vec<&str> = blocks
+        .iter()
+        .filter_map(|block| match block {
+            ConfirmationSummaryBlock::Heading { text, .. } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        headings,
+        vec!["Create account alice.myapp.testnet with 0.1 NEAR"]
+    );
+    // The warning for the new full access key is kept; the action rows are not repeated
+    assert!(matches!(
+        &blocks[0],
+        ConfirmationSummaryBlock::Warning {
+            code: SummaryWarningCode::FullAccessKey,
+            ..
+        }
+    ));
+    assert!(!blocks.iter().any(|block| matches!(
+        block,
+        ConfirmationSummaryBlock::AmountRow { .. }
+            | ConfirmationSummaryBlock::KeyRow { .. }
+            | ConfirmationSummaryBlock::CodeBlock { .. }
+    )));
+
+    let policy = SummaryPolicy::default().with_locale(SpeechLocale::Es);
+    let blocks = transaction_summary_blocks(&receiver_id, &actions, None, &policy);
+    match blocks.last().unwrap() {
+        ConfirmationSummaryBlock::Heading { aria_label, .. } => {
+            assert!(aria_label.starts_with("Crear la cuenta"), "{}", aria_label)
+        }
+        other => panic!("expected a heading, got {:?}", other),
+    }
+
+    // Any other shape keeps its per-action blocks
+    let mut reordered = actions.clone();
+    reordered.swap(1, 2);
+    let blocks =
+        transaction_summary_blocks(&receiver_id, &reordered, None, &SummaryPolicy::default());
+    assert!(blocks.iter().any(|block| matches!(
+        block,
+        ConfirmationSummaryBlock::Heading { text, .. } if text == "Transfer"
+    )));
+}
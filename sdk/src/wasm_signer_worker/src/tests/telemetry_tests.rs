@@ -0,0 +1,223 @@
+use crate::config::{DEFAULT_TELEMETRY_FLUSH_INTERVAL_MINUTES, TELEMETRY_LATENCY_BUCKETS_MS};
+use crate::dispatch_signer_message;
+use crate::telemetry::*;
+use crate::tests::block_on;
+use crate::types::worker_messages::{SignerWorkerMessage, WorkerResponseType};
+use crate::types::{TelemetryPolicy, WorkerPolicy};
+use serde_json::{json, Value};
+
+const MINUTE_MS: f64 = 60_000.0;
+
+fn policy(endpoint: &str, sampling_rate: Option<f64>, interval: Option<u32>) -> WorkerPolicy {
+    WorkerPolicy {
+        allowed_rpc_origins: vec!["https://telemetry.example.com".to_string()],
+        telemetry: Some(TelemetryPolicy {
+            endpoint: endpoint.to_string(),
+            sampling_rate,
+            flush_interval_minutes: interval,
+        }),
+        ..WorkerPolicy::default()
+    }
+}
+
+#[test]
+fn test_counters_buckets_and_percentiles() {
+    assert_eq!(latency_bucket(0.0), 0);
+    assert_eq!(latency_bucket(10.0), 0);
+    assert_eq!(latency_bucket(10.5), 1);
+    assert_eq!(
+        latency_bucket(10_000.0),
+        TELEMETRY_LATENCY_BUCKETS_MS.len() - 1
+    );
+    assert_eq!(latency_bucket(60_000.0), TELEMETRY_LATENCY_BUCKETS_MS.len());
+
+    let mut counters = HandlerCounters::default();
+    assert_eq!(counters.latency_percentile_ms(95.0), None);
+    for _ in 0..18 {
+        counters.record(40.0, RequestOutcome::Success);
+    }
+    counters.record(400.0, RequestOutcome::Failure(Some("UserDeclined")));
+    counters.record(20_000.0, RequestOutcome::Failure(None));
+    assert_eq!(counters.requests, 20);
+    assert_eq!(counters.successes, 18);
+    assert_eq!(counters.failures, 2);
+    assert_eq!(counters.failure_codes.get("UserDeclined"), Some(&1));
+    assert_eq!(counters.failure_codes.len(), 1);
+    assert_eq!(counters.latency_percentile_ms(50.0), Some(50));
+    assert_eq!(counters.latency_percentile_ms(95.0), Some(500));
+    // The slowest request is beyond the last bucket
+    assert_eq!(counters.latency_percentile_ms(100.0), None);
+}
+
+#[test]
+fn test_outcomes_keep_only_catalogued_codes() {
+    assert_eq!(
+        outcome_of(false, &json!({ "success": true })),
+        RequestOutcome::Success
+    );
+    assert_eq!(
+        outcome_of(
+            false,
+            &json!({ "success": false, "errorCode": "ChallengeExpired" })
+        ),
+        RequestOutcome::Failure(Some("ChallengeExpired"))
+    );
+    assert_eq!(
+        outcome_of(
+            true,
+            &json!({ "error": "x", "errorCode": "IdempotencyKeyConflict" })
+        ),
+        RequestOutcome::Failure(Some("IdempotencyKeyConflict"))
+    );
+    // Codes nobody catalogued (or free text) are never recorded
+    assert_eq!(
+        outcome_of(true, &json!({ "errorCode": "alice.near sent 5 NEAR" })),
+        RequestOutcome::Failure(None)
+    );
+}
+
+#[test]
+fn test_telemetry_sink_validation() {
+    assert_eq!(telemetry_sink(&WorkerPolicy::default()), Ok(None));
+
+    let sink = telemetry_sink(&policy("https://telemetry.example.com/v1", None, None))
+        .unwrap()
+        .unwrap();
+    assert_eq!(sink.sampling_rate, 1.0);
+    assert_eq!(
+        sink.flush_interval_ms,
+        DEFAULT_TELEMETRY_FLUSH_INTERVAL_MINUTES as f64 * MINUTE_MS
+    );
+
+    let not_allowed = telemetry_sink(&policy("https://other.example.com", None, None));
+    assert!(not_allowed.unwrap_err().starts_with("RpcOriginNotAllowed"));
+    assert!(telemetry_sink(&policy("https://telemetry.example.com", Some(1.5), None)).is_err());
+    assert!(telemetry_sink(&policy(
+        "https://telemetry.example.com",
+        Some(f64::NAN),
+        None
+    ))
+    .is_err());
+    assert!(telemetry_sink(&policy("https://telemetry.example.com", None, Some(0))).is_err());
+}
+
+#[test]
+fn test_sampled_report_is_taken_once_per_interval() {
+    reset();
+    let start = 1_000_000.0;
+    configure(
+        &policy("https://telemetry.example.com/v1", Some(0.5), Some(5)),
+        start,
+    );
+    // Sampled in, sampled out, and a sampled-in failure
+    record_request("SIGN_NEP413_MESSAGE", 30.0, RequestOutcome::Success, 0.1);
+    record_request("SIGN_NEP413_MESSAGE", 30.0, RequestOutcome::Success, 0.9);
+    record_request(
+        "SIGN_TRANSACTIONS_WITH_ACTIONS",
+        800.0,
+        RequestOutcome::Failure(Some("UserDeclined")),
+        0.2,
+    );
+
+    assert!(take_due_report(start + 4.0 * MINUTE_MS).is_none());
+    let (endpoint, report) = take_due_report(start + 5.0 * MINUTE_MS).unwrap();
+    assert_eq!(endpoint, "https://telemetry.example.com/v1");
+    assert_eq!(report.sampling_rate, 0.5);
+    assert_eq!(report.window_start_ms, start);
+    assert_eq!(report.handlers["SIGN_NEP413_MESSAGE"].requests, 1);
+    assert_eq!(
+        report.handlers["SIGN_TRANSACTIONS_WITH_ACTIONS"].failure_codes["UserDeclined"],
+        1
+    );
+    // The next window starts empty: nothing is due until it has sampled requests
+    assert!(take_due_report(start + 11.0 * MINUTE_MS).is_none());
+
+    // The local snapshot counts every request, sampled or not
+    let local = snapshot();
+    let nep413 = local
+        .handlers
+        .iter()
+        .find(|h| h.handler == "SIGN_NEP413_MESSAGE")
+        .unwrap();
+    assert_eq!(nep413.requests, 2);
+    assert_eq!(nep413.p95_latency_ms, Some(50));
+    assert_eq!(
+        local.remote_endpoint.as_deref(),
+        Some("https://telemetry.example.com/v1")
+    );
+
+    // The CBOR report only holds names, codes and counts
+    let decoded: ciborium::Value = ciborium::from_reader(&report.to_cbor().unwrap()[..]).unwrap();
+    let as_json: Value = serde_json::to_value(&decoded).unwrap();
+    assert_eq!(
+        as_json["handlers"]["SIGN_NEP413_MESSAGE"]["successes"],
+        json!(1)
+    );
+    let keys: Vec<&str> = as_json
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(|k| k.as_str())
+        .collect();
+    assert_eq!(
+        keys,
+        [
+            "handlers",
+            "samplingRate",
+            "version",
+            "windowEndMs",
+            "windowStartMs"
+        ]
+    );
+
+    // An invalid sink turns remote reporting off
+    configure(&policy("https://other.example.com", None, None), start);
+    assert!(snapshot().remote_endpoint.is_none());
+    reset();
+}
+
+#[test]
+fn test_dispatch_counts_tracked_requests() {
+    reset();
+    let signing_key = ed25519_dalek::SigningKey::from_bytes(&[5u8; 32]);
+    let payload = json!({
+        "nearPrivateKey": format!(
+            "ed25519:{}",
+            bs58::encode(signing_key.to_keypair_bytes()).into_string()
+        ),
+        "signerAccountId": "alice.testnet",
+        "receiverId": "bob.testnet",
+        "nonce": "1",
+        "blockHash": bs58::encode([9u8; 32]).into_string(),
+        "actions": json!([{ "action_type": "Transfer", "deposit": "1" }]).to_string(),
+    });
+    // WorkerRequestType::SignTransactionWithKeyPair
+    block_on(dispatch_signer_message(SignerWorkerMessage {
+        msg_type: 6,
+        payload,
+        request_id: None,
+    }))
+    .unwrap();
+
+    // WorkerRequestType::GetTelemetrySnapshot, which is not counted itself
+    let response = block_on(dispatch_signer_message(SignerWorkerMessage {
+        msg_type: 30,
+        payload: json!({}),
+        request_id: None,
+    }))
+    .unwrap();
+    assert_eq!(
+        WorkerResponseType::from(response.response_type),
+        WorkerResponseType::GetTelemetrySnapshotSuccess
+    );
+    let handlers = response.payload["handlers"].as_array().unwrap();
+    assert_eq!(handlers.len(), 1);
+    assert_eq!(
+        handlers[0]["handler"],
+        json!("SIGN_TRANSACTION_WITH_KEYPAIR")
+    );
+    assert_eq!(handlers[0]["requests"], json!(1));
+    assert_eq!(handlers[0]["successes"], json!(1));
+    assert_eq!(response.payload["remoteEndpoint"], Value::Null);
+    reset();
+}
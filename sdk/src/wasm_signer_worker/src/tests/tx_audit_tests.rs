@@ -0,0 +1,233 @@
+use crate::actions::ActionParams;
+use crate::encoders::base64_standard_encode;
+use crate::handlers::confirm_tx_details::{
+    compute_intent_digest_from_js_inputs, tx_signing_requests_json,
+};
+use crate::transaction::{
+    build_actions_from_params, build_transaction_for_public_key, sign_transaction,
+};
+use crate::tx_audit::{audit_signed_transaction, AuditFieldCheck};
+use crate::types::{PublicKey, SignedTransaction};
+use crate::unsigned_transaction::{build_unsigned_transaction, UnsignedTransactionRequest};
+use ed25519_dalek::SigningKey;
+use serde_json::{json, Value};
+
+const RECEIVER_ID: &str = "contract.testnet";
+
+fn signing_key() -> SigningKey {
+    SigningKey::from_bytes(&[11u8; 32])
+}
+
+fn public_key_b58() -> String {
+    format!(
+        "ed25519:{}",
+        bs58::encode(signing_key().verifying_key().to_bytes()).into_string()
+    )
+}
+
+/// Actions as the TS layer sends them (toActionArgsWasm)
+fn actions() -> Vec<ActionParams> {
+    serde_json::from_value(json!([
+        { "action_type": "Transfer", "deposit": "1000000000000000000000000" },
+        {
+            "action_type": "FunctionCall",
+            "method_name": "set_greeting",
+            "args": "{\"greeting\":\"hi\"}",
+            "gas": "30000000000000",
+            "deposit": "0"
+        },
+        {
+            "action_type": "AddKey",
+            "public_key": public_key_b58(),
+            "access_key": "{\"nonce\":0,\"permission\":{\"FullAccess\":{}}}"
+        },
+        { "action_type": "DeployContract", "code": [0, 97, 115, 109, 1, 0, 0, 0] }
+    ]))
+    .unwrap()
+}
+
+fn signed_tx_base64(actions: Vec<ActionParams>) -> String {
+    let transaction = build_transaction_for_public_key(
+        "alice.testnet",
+        RECEIVER_ID,
+        42,
+        &[8u8; 32],
+        PublicKey::from_ed25519_bytes(&signing_key().verifying_key().to_bytes()),
+        build_actions_from_params(actions).unwrap(),
+    )
+    .unwrap();
+    base64_standard_encode(&sign_transaction(transaction, &signing_key()).unwrap())
+}
+
+/// The txSigningRequests entry shown and the intent digest of the confirmation
+fn confirmation(first_time_receiver: Option<bool>) -> (Value, String) {
+    let receivers_and_actions = [(RECEIVER_ID.to_string(), actions())];
+    let shown = tx_signing_requests_json(&receivers_and_actions, Some(&[first_time_receiver]));
+    let digest =
+        compute_intent_digest_from_js_inputs(&receivers_and_actions, Some(&[first_time_receiver]))
+            .unwrap();
+    (shown[0].clone(), digest)
+}
+
+fn mismatches(fields: &[AuditFieldCheck]) -> Vec<&str> {
+    fields
+        .iter()
+        .filter(|field| !field.matches)
+        .map(|field| field.field.as_str())
+        .collect()
+}
+
+#[test]
+fn test_signed_transaction_matches_its_confirmation() {
+    let signed = signed_tx_base64(actions());
+    for first_time_receiver in [None, Some(true), Some(false)] {
+        let (shown, digest) = confirmation(first_time_receiver);
+        let result = audit_signed_transaction(&signed, &digest, &shown.to_string()).unwrap();
+        assert!(result.valid, "{:?}", result);
+        assert!(result.signature_valid);
+        assert_eq!(result.digest, digest);
+        assert!(result.digest_matches && result.summary_matches);
+        assert!(result.fields.iter().any(|f| f.field == "receiverId"));
+        assert!(result
+            .fields
+            .iter()
+            .any(|f| f.field == "actions[2].access_key"));
+    }
+
+    let (_, digest) = confirmation(None);
+    let result = audit_signed_transaction(&signed, &digest, "{}").unwrap();
+    assert_eq!(result.transaction.signer_id, "alice.testnet");
+    assert_eq!(result.transaction.receiver_id, RECEIVER_ID);
+    assert_eq!(result.transaction.nonce, "42");
+    assert_eq!(result.transaction.public_key, public_key_b58());
+    assert_eq!(result.transaction.actions, actions());
+    // Digested without the firstTimeReceiver annotation the confirmation carried
+    assert!(!result.digest_matches);
+    assert!(result.summary_matches && result.fields.is_empty());
+}
+
+#[test]
+fn test_parsed_summary_of_the_unsigned_transaction_matches() {
+    let actions_json = serde_json::to_string(&actions()).unwrap();
+    let unsigned = build_unsigned_transaction(&UnsignedTransactionRequest {
+        signer_account_id: "alice.testnet".to_string(),
+        signer_public_key: public_key_b58(),
+        receiver_id: RECEIVER_ID.to_string(),
+        nonce: "42".to_string(),
+        block_hash: bs58::encode([8u8; 32]).into_string(),
+        actions: actions_json,
+    })
+    .unwrap();
+    let (_, digest) = confirmation(None);
+    let result = audit_signed_transaction(
+        &signed_tx_base64(actions()),
+        &digest,
+        &serde_json::to_string(&unsigned.parsed_summary).unwrap(),
+    )
+    .unwrap();
+    assert!(result.summary_matches, "{:?}", mismatches(&result.fields));
+    assert_eq!(result.tx_hash_b58, unsigned.tx_hash_b58);
+    for field in ["signerId", "publicKey", "nonce", "receiverId", "blockHash"] {
+        assert!(result.fields.iter().any(|f| f.field == field), "{}", field);
+    }
+}
+
+#[test]
+fn test_summary_differences_are_reported_by_field() {
+    let (mut shown, digest) = confirmation(None);
+    shown["receiverId"] = json!("mallory.testnet");
+    shown["actions"][0]["deposit"] = json!("1");
+    shown["actions"][1]["args"] = json!("{\"greeting\":\"bye\"}");
+    shown["actions"][3]["code"] = json!([0, 97, 115, 109]);
+    let result =
+        audit_signed_transaction(&signed_tx_base64(actions()), &digest, &shown.to_string())
+            .unwrap();
+    assert!(result.signature_valid && result.digest_matches);
+    assert!(!result.summary_matches && !result.valid);
+    assert_eq!(
+        mismatches(&result.fields),
+        vec![
+            "receiverId",
+            "actions[0].deposit",
+            "actions[1].args",
+            "actions[3].code"
+        ]
+    );
+    let deposit = result
+        .fields
+        .iter()
+        .find(|f| f.field == "actions[0].deposit")
+        .unwrap();
+    assert_eq!(deposit.expected, json!("1"));
+    assert_eq!(deposit.actual, json!("1000000000000000000000000"));
+
+    // An action the summary left out
+    let (mut shown, _) = confirmation(None);
+    shown["actions"].as_array_mut().unwrap().pop();
+    let result =
+        audit_signed_transaction(&signed_tx_base64(actions()), &digest, &shown.to_string())
+            .unwrap();
+    assert_eq!(mismatches(&result.fields), vec!["actions[3]"]);
+    let missing = result.fields.iter().find(|f| !f.matches).unwrap();
+    assert_eq!(missing.expected, Value::Null);
+    assert_eq!(missing.actual["action_type"], json!("DeployContract"));
+}
+
+#[test]
+fn test_equivalent_summary_spellings_match() {
+    // Base64url key, and the access key JSON spelled differently
+    let (mut shown, digest) = confirmation(None);
+    shown["publicKey"] = json!(crate::encoders::base64_url_encode(
+        &signing_key().verifying_key().to_bytes()
+    ));
+    shown["actions"][2]["access_key"] =
+        json!("{ \"permission\": { \"FullAccess\": {} }, \"nonce\": 0 }");
+    let result =
+        audit_signed_transaction(&signed_tx_base64(actions()), &digest, &shown.to_string())
+            .unwrap();
+    assert!(result.valid, "{:?}", mismatches(&result.fields));
+}
+
+#[test]
+fn test_tampered_signature_or_transaction_fails() {
+    let (shown, digest) = confirmation(None);
+    let signed = signed_tx_base64(actions());
+    let bytes = crate::encoders::base64_standard_decode(&signed).unwrap();
+    let mut tx = SignedTransaction::from_borsh_bytes(&bytes).unwrap();
+
+    let mut bad_signature = tx.clone();
+    bad_signature.signature.signature_data[0] ^= 1;
+    let tampered = base64_standard_encode(&bad_signature.to_borsh_bytes().unwrap());
+    let result = audit_signed_transaction(&tampered, &digest, &shown.to_string()).unwrap();
+    assert!(!result.signature_valid && !result.valid);
+    assert!(result.digest_matches && result.summary_matches);
+
+    // A changed nonce keeps the summary but breaks the signature
+    tx.transaction.nonce += 1;
+    let tampered = base64_standard_encode(&tx.to_borsh_bytes().unwrap());
+    let result = audit_signed_transaction(&tampered, &digest, &shown.to_string()).unwrap();
+    assert!(!result.signature_valid && result.digest_matches);
+
+    let result = audit_signed_transaction(&signed, "not-the-digest", &shown.to_string()).unwrap();
+    assert!(result.signature_valid && !result.digest_matches && !result.valid);
+}
+
+#[test]
+fn test_undecodable_inputs_are_errors() {
+    let (shown, digest) = confirmation(None);
+    let signed = signed_tx_base64(actions());
+    let err = audit_signed_transaction("!!", &digest, &shown.to_string()).unwrap_err();
+    assert!(err.starts_with("Invalid signed transaction"), "{}", err);
+    let err =
+        audit_signed_transaction(&base64_standard_encode(&[1, 2, 3]), &digest, "{}").unwrap_err();
+    assert!(err.starts_with("Invalid signed transaction"), "{}", err);
+    let err = audit_signed_transaction(&signed, &digest, "not json").unwrap_err();
+    assert!(err.starts_with("Invalid expected summary JSON"), "{}", err);
+    let err = audit_signed_transaction(
+        &signed,
+        &digest,
+        &json!({ "actions": [{ "action_type": "Transfer", "deposit": "x" }] }).to_string(),
+    )
+    .unwrap_err();
+    assert!(err.starts_with("Invalid expected summary"), "{}", err);
+}
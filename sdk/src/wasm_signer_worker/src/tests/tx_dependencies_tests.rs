@@ -0,0 +1,224 @@
+use crate::actions::ActionParams;
+use crate::bench::confirmed_batch_of_5;
+use crate::confirmation_blocks::SummaryPolicy;
+use crate::error::{InvalidDependencyGraphError, SignerErrorCode};
+use crate::handlers::confirm_tx_details::{
+    compute_confirmation_intent_digest, confirmation_tx_signing_requests_json,
+};
+use crate::handlers::handle_sign_transactions_with_actions::{
+    sign_near_transactions_with_actions_impl, TransactionSignResult,
+};
+use crate::handlers::{
+    handle_sign_transactions_with_actions, SignTransactionsWithActionsRequest, TransactionPayload,
+};
+use crate::rpc_client::MockRpcClient;
+use crate::strict_parsing::check_unknown_fields;
+use crate::tests::block_on;
+use crate::tx_dependencies::{check_dependencies, dependents, depends_on};
+use crate::types::worker_messages::WorkerRequestType;
+use serde_json::{json, Value};
+
+fn tx(receiver_id: &str, depends_on: &[u32]) -> TransactionPayload {
+    TransactionPayload {
+        near_account_id: "alice.testnet".to_string(),
+        receiver_id: receiver_id.to_string(),
+        actions: json!([{ "action_type": "Transfer", "deposit": "1" }]).to_string(),
+        depends_on: depends_on.to_vec(),
+    }
+}
+
+fn request(txs: &[TransactionPayload], extra: Value) -> SignTransactionsWithActionsRequest {
+    let mut payload = json!({
+        "rpcCall": {
+            "contractId": "w3a-v1.testnet",
+            "nearRpcUrl": "https://rpc.testnet.near.org",
+            "nearAccountId": "alice.testnet"
+        },
+        "decryption": { "encryptedPrivateKeyData": "AAAA", "encryptedPrivateKeyIv": "BBBB" },
+        "txSigningRequests": txs,
+        "confirmationConfig": null
+    });
+    payload
+        .as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    serde_json::from_value(payload).unwrap()
+}
+
+fn sign_and_broadcast(txs: Vec<TransactionPayload>, rpc: &MockRpcClient) -> TransactionSignResult {
+    let batch = confirmed_batch_of_5();
+    block_on(sign_near_transactions_with_actions_impl(
+        txs,
+        &batch.decryption,
+        &batch.confirmation,
+        Some(rpc),
+        None,
+        None,
+        Vec::new(),
+    ))
+    .unwrap()
+}
+
+/// The bench batch, with `dependsOn` per transaction
+fn bench_batch_depending(graph: &[&[u32]]) -> Vec<TransactionPayload> {
+    let mut txs = confirmed_batch_of_5().tx_requests;
+    for (tx, depends_on) in txs.iter_mut().zip(graph) {
+        tx.depends_on = depends_on.to_vec();
+    }
+    txs
+}
+
+#[test]
+fn test_dependency_graph_is_checked() {
+    let check = |txs: &[TransactionPayload]| check_dependencies(&request(txs, json!({})));
+    assert_eq!(
+        check(&[tx("a.testnet", &[]), tx("b.testnet", &[0])]),
+        Ok(())
+    );
+    assert_eq!(
+        check(&[tx("a.testnet", &[1]), tx("b.testnet", &[0])]),
+        Err(InvalidDependencyGraphError::Cycle { cycle: vec![0, 1] })
+    );
+    assert_eq!(
+        check(&[tx("a.testnet", &[0])]),
+        Err(InvalidDependencyGraphError::Cycle { cycle: vec![0] })
+    );
+    assert_eq!(
+        check(&[tx("a.testnet", &[1]), tx("b.testnet", &[])]),
+        Err(InvalidDependencyGraphError::ForwardReference {
+            index: 0,
+            depends_on: 1
+        })
+    );
+    assert_eq!(
+        check(&[tx("a.testnet", &[]), tx("b.testnet", &[7])]),
+        Err(InvalidDependencyGraphError::ForwardReference {
+            index: 1,
+            depends_on: 7
+        })
+    );
+    let deduplicated = request(
+        &[tx("a.testnet", &[]), tx("b.testnet", &[0])],
+        json!({ "deduplicate": true }),
+    );
+    assert_eq!(
+        check_dependencies(&deduplicated),
+        Err(InvalidDependencyGraphError::WithDeduplicate)
+    );
+
+    let cycle = InvalidDependencyGraphError::Cycle {
+        cycle: vec![0, 2, 1],
+    };
+    assert_eq!(cycle.code(), SignerErrorCode::InvalidDependencyGraph);
+    assert_eq!(
+        cycle.to_string(),
+        "InvalidDependencyGraph: transactions 0 -> 2 -> 1 -> 0 depend on each other in a cycle"
+    );
+}
+
+#[test]
+fn test_invalid_graph_is_refused_before_the_prompt() {
+    let txs = [tx("a.testnet", &[1]), tx("b.testnet", &[])];
+    let result = block_on(handle_sign_transactions_with_actions(request(
+        &txs,
+        json!({}),
+    )))
+    .unwrap();
+    assert!(!result.success);
+    assert_eq!(
+        result.error_code.as_deref(),
+        Some(SignerErrorCode::InvalidDependencyGraph.as_str())
+    );
+}
+
+#[test]
+fn test_dependents_are_transitive() {
+    let txs = [
+        tx("a.testnet", &[]),
+        tx("b.testnet", &[0]),
+        tx("c.testnet", &[]),
+        tx("d.testnet", &[1]),
+        tx("e.testnet", &[2]),
+    ];
+    assert_eq!(dependents(&txs, 0), vec![1, 3]);
+    assert_eq!(dependents(&txs, 2), vec![4]);
+    assert!(dependents(&txs, 4).is_empty());
+}
+
+#[test]
+fn test_dependencies_are_shown_and_covered_by_the_digest() {
+    let txs = [tx("a.testnet", &[]), tx("b.testnet", &[0])];
+    let batch: Vec<(String, Vec<ActionParams>)> = txs
+        .iter()
+        .map(|tx| (tx.receiver_id.clone(), tx.parsed_actions().unwrap()))
+        .collect();
+    let flags = vec![Some(false); 2];
+    let policy = SummaryPolicy::default();
+    let with_dependencies = policy.clone().with_dependencies(depends_on(&txs));
+
+    let json =
+        confirmation_tx_signing_requests_json(&batch, &flags, None, Some(&with_dependencies));
+    assert!(json[0].get("dependsOn").is_none());
+    assert_eq!(json[1]["dependsOn"], json!([0]));
+    assert_ne!(
+        compute_confirmation_intent_digest(&batch, &flags, None, Some(&policy)).unwrap(),
+        compute_confirmation_intent_digest(&batch, &flags, None, Some(&with_dependencies)).unwrap()
+    );
+    // Without annotations the confirmation is unchanged
+    assert!(depends_on(&[tx("a.testnet", &[]), tx("b.testnet", &[])]).is_empty());
+}
+
+#[test]
+fn test_failed_dependency_withholds_the_rest_of_the_batch() {
+    let rpc = MockRpcClient::new()
+        .answer(
+            "broadcast_tx_commit",
+            Ok(json!({ "status": { "SuccessValue": "" } })),
+        )
+        .answer(
+            "broadcast_tx_commit",
+            Ok(json!({ "status": { "Failure": { "ActionError": {} } } })),
+        );
+    let result = sign_and_broadcast(bench_batch_depending(&[&[], &[], &[1], &[], &[]]), &rpc);
+
+    assert!(!result.success);
+    assert_eq!(result.withheld_transactions, Some(vec![2, 3, 4]));
+    assert_eq!(rpc.calls_to("broadcast_tx_commit").len(), 2);
+    assert_eq!(result.broadcast_outcomes.unwrap().len(), 2);
+    // The withheld transactions are signed, on the nonces following the broadcast ones
+    assert_eq!(result.signed_transactions.unwrap().len(), 5);
+    assert!(result
+        .error
+        .unwrap()
+        .starts_with("Transaction 2: Failed on chain"));
+}
+
+#[test]
+fn test_failure_without_dependents_does_not_stop_the_batch() {
+    let rpc = MockRpcClient::new().answer(
+        "broadcast_tx_commit",
+        Ok(json!({ "status": { "Failure": { "ActionError": {} } } })),
+    );
+    // Transactions 0 to 2 fail with no dependents; 3 fails and withholds 4, which depends on it
+    let result = sign_and_broadcast(bench_batch_depending(&[&[], &[], &[], &[], &[3]]), &rpc);
+    assert_eq!(result.withheld_transactions, Some(vec![4]));
+    assert_eq!(rpc.calls_to("broadcast_tx_commit").len(), 4);
+
+    let result = sign_and_broadcast(confirmed_batch_of_5().tx_requests, &rpc);
+    assert!(result.success, "{:?}", result.error);
+    assert!(result.withheld_transactions.is_none());
+    assert_eq!(rpc.calls_to("broadcast_tx_commit").len(), 4 + 5);
+}
+
+#[test]
+fn test_strict_parsing_accepts_depends_on_when_signing_only() {
+    let payload = json!({
+        "txSigningRequests": [{ "receiverId": "a.testnet", "actions": "[]", "dependsOn": [] }],
+        "workerPolicy": { "strictParsing": true }
+    });
+    assert_eq!(
+        check_unknown_fields(WorkerRequestType::SignTransactionsWithActions, &payload),
+        Ok(())
+    );
+    assert!(check_unknown_fields(WorkerRequestType::CreateSigningIntent, &payload).is_err());
+}
@@ -0,0 +1,191 @@
+use crate::actions::ActionParams;
+use crate::config::{MAX_DIFF_ARGS_BYTES, MAX_DIFF_ARG_PATHS, RECENT_CONFIRMED_INTENTS_LIMIT};
+use crate::handlers::confirm_tx_details::compute_intent_digest_from_js_inputs;
+use crate::state;
+use crate::tests::in_fresh_worker;
+use crate::tx_diff::*;
+use crate::types::AccountId;
+use crate::worker_state;
+use serde_json::json;
+
+const ACCOUNT_ID: &str = "alice.testnet";
+const DEX: &str = "dex.testnet";
+
+fn account(account_id: &str) -> AccountId {
+    AccountId(account_id.to_string())
+}
+
+fn swap(args: serde_json::Value, deposit: &str) -> (String, Vec<ActionParams>) {
+    (
+        DEX.to_string(),
+        vec![ActionParams::FunctionCall {
+            method_name: "swap".to_string(),
+            args: args.to_string(),
+            gas: "30000000000000".to_string(),
+            deposit: deposit.to_string(),
+        }],
+    )
+}
+
+fn swap_args(min_amount_out: &str) -> serde_json::Value {
+    json!({
+        "actions": [{ "pool_id": 7, "token_in": "usdc.testnet", "min_amount_out": min_amount_out }],
+        "referral_id": null
+    })
+}
+
+#[test]
+fn test_re_request_reports_changed_arg_paths_and_deposit() {
+    state::wipe_all_state();
+    let confirmed = vec![swap(swap_args("990"), "1")];
+    record_confirmed_intent(&account(ACCOUNT_ID), "digest-1", &confirmed);
+
+    let mut bumped_args = swap_args("950");
+    bumped_args["deadline"] = json!(1700000000);
+    let request = vec![
+        (
+            "usdc.testnet".to_string(),
+            vec![ActionParams::Transfer {
+                deposit: "1".to_string(),
+            }],
+        ),
+        swap(bumped_args, "2"),
+    ];
+    let diffs = diff_from_previous(&request, &state::recent_confirmed_intents(&account(ACCOUNT_ID)));
+    assert_eq!(diffs.len(), 1);
+    let diff = &diffs[0];
+    assert_eq!((diff.tx_index, diff.action_index), (1, 0));
+    assert_eq!(diff.method_name, "swap");
+    assert_eq!(diff.previous_intent_digest, "digest-1");
+    assert!(diff.args_changed);
+    assert_eq!(
+        diff.changed_arg_paths,
+        Some(vec![
+            "/actions/0/min_amount_out".to_string(),
+            "/deadline".to_string()
+        ])
+    );
+    assert_eq!(
+        diff.deposit,
+        Some(ValueChange {
+            from: "1".to_string(),
+            to: "2".to_string()
+        })
+    );
+    assert_eq!(diff.gas, None);
+
+    // Other accounts, receivers and methods are not compared
+    assert!(
+        diff_from_previous(&request, &state::recent_confirmed_intents(&account("bob.testnet"))).is_empty()
+    );
+    let mut other_method = swap(swap_args("950"), "1");
+    if let ActionParams::FunctionCall { method_name, .. } = &mut other_method.1[0] {
+        *method_name = "add_liquidity".to_string();
+    }
+    assert!(diff_from_previous(
+        &[other_method],
+        &state::recent_confirmed_intents(&account(ACCOUNT_ID))
+    )
+    .is_empty());
+
+    // An identical re-request is reported with no changes
+    let same = diff_from_previous(&confirmed, &state::recent_confirmed_intents(&account(ACCOUNT_ID)));
+    assert!(!same[0].args_changed);
+    assert_eq!(same[0].changed_arg_paths, Some(vec![]));
+    state::wipe_all_state();
+}
+
+#[test]
+fn test_diff_caps_work_on_large_args() {
+    state::wipe_all_state();
+    let big = |fill: char| json!({ "code": fill.to_string().repeat(MAX_DIFF_ARGS_BYTES) });
+    record_confirmed_intent(&account(ACCOUNT_ID), "digest-big", &[swap(big('a'), "0")]);
+    let diffs = diff_from_previous(
+        &[swap(big('b'), "0")],
+        &state::recent_confirmed_intents(&account(ACCOUNT_ID)),
+    );
+    assert!(diffs[0].args_changed);
+    assert_eq!(diffs[0].changed_arg_paths, None);
+
+    // Too many changed paths also falls back to "args changed"
+    let many = |v: u32| {
+        json!((0..=MAX_DIFF_ARG_PATHS as u32)
+            .map(|i| (format!("k{}", i), json!(v)))
+            .collect::<serde_json::Map<String, serde_json::Value>>())
+    };
+    record_confirmed_intent(&account(ACCOUNT_ID), "digest-many", &[swap(many(0), "0")]);
+    let diffs = diff_from_previous(
+        &[swap(many(1), "0")],
+        &state::recent_confirmed_intents(&account(ACCOUNT_ID)),
+    );
+    assert_eq!(diffs[0].previous_intent_digest, "digest-many");
+    assert!(diffs[0].args_changed);
+    assert_eq!(diffs[0].changed_arg_paths, None);
+    state::wipe_all_state();
+}
+
+#[test]
+fn test_confirmed_intents_are_bounded_and_not_part_of_the_digest() {
+    state::wipe_all_state();
+    for i in 0..RECENT_CONFIRMED_INTENTS_LIMIT + 2 {
+        let min_out = i.to_string();
+        record_confirmed_intent(
+            &account(ACCOUNT_ID),
+            &format!("digest-{}", i),
+            &[swap(swap_args(&min_out), "1")],
+        );
+    }
+    // Batches without FunctionCalls are not kept
+    record_confirmed_intent(
+        &account(ACCOUNT_ID),
+        "digest-transfer",
+        &[(
+            "bob.testnet".to_string(),
+            vec![ActionParams::Transfer {
+                deposit: "1".to_string(),
+            }],
+        )],
+    );
+    let recent = state::recent_confirmed_intents(&account(ACCOUNT_ID));
+    assert_eq!(recent.len(), RECENT_CONFIRMED_INTENTS_LIMIT);
+    assert_eq!(
+        recent[0].intent_digest,
+        format!("digest-{}", RECENT_CONFIRMED_INTENTS_LIMIT + 1)
+    );
+
+    // The digest of a request depends only on the request, whatever the history holds
+    let request = vec![swap(swap_args("1"), "1")];
+    let with_history = compute_intent_digest_from_js_inputs(&request, None).unwrap();
+    state::wipe_all_state();
+    assert!(state::recent_confirmed_intents(&account(ACCOUNT_ID)).is_empty());
+    assert_eq!(
+        compute_intent_digest_from_js_inputs(&request, None).unwrap(),
+        with_history
+    );
+}
+
+#[test]
+fn test_re_request_is_diffed_in_a_later_worker() {
+    // Every request runs in a new worker: the confirmed batch travels in the state record
+    let record = in_fresh_worker(None, || {
+        record_confirmed_intent(
+            &account(ACCOUNT_ID),
+            "digest-1",
+            &[swap(swap_args("990"), "1")],
+        );
+        worker_state::take_changed_record()
+    });
+    assert!(record.is_some());
+    let diffs = in_fresh_worker(record, || {
+        diff_from_previous(
+            &[swap(swap_args("950"), "1")],
+            &state::recent_confirmed_intents(&account(ACCOUNT_ID)),
+        )
+    });
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].previous_intent_digest, "digest-1");
+    assert_eq!(
+        diffs[0].changed_arg_paths,
+        Some(vec!["/actions/0/min_amount_out".to_string()])
+    );
+}
@@ -0,0 +1,132 @@
+use crate::encoders::base64_standard_decode;
+use crate::handlers::handle_sign_transaction_with_keypair::{
+    handle_sign_transaction_with_keypair, SignTransactionWithKeyPairRequest,
+};
+use crate::tests::block_on;
+use crate::types::SignedTransaction;
+use crate::unsigned_transaction::{
+    build_unsigned_transaction, UnsignedTransaction, UnsignedTransactionRequest,
+};
+use ed25519_dalek::{Signature, SigningKey, Verifier};
+use serde_json::json;
+
+fn signing_key() -> SigningKey {
+    SigningKey::from_bytes(&[11u8; 32])
+}
+
+fn public_key_b58() -> String {
+    format!(
+        "ed25519:{}",
+        bs58::encode(signing_key().verifying_key().to_bytes()).into_string()
+    )
+}
+
+/// (receiver, actions JSON) pairs covering the action families
+fn fixtures() -> Vec<(&'static str, String)> {
+    vec![
+        (
+            "bob.testnet",
+            json!([{ "action_type": "Transfer", "deposit": "1000000000000000000000000" }])
+                .to_string(),
+        ),
+        (
+            "contract.testnet",
+            json!([{
+                "action_type": "FunctionCall",
+                "method_name": "set_greeting",
+                "args": "{\"greeting\":\"hi\"}",
+                "gas": "30000000000000",
+                "deposit": "0"
+            }])
+            .to_string(),
+        ),
+        (
+            "alice.testnet",
+            json!([
+                { "action_type": "DeployContract", "code": [0, 97, 115, 109, 1, 0, 0, 0] },
+                { "action_type": "DeleteKey", "public_key": public_key_b58() },
+            ])
+            .to_string(),
+        ),
+    ]
+}
+
+fn sign(receiver_id: &str, actions: &str) -> Vec<u8> {
+    let result = block_on(handle_sign_transaction_with_keypair(
+        SignTransactionWithKeyPairRequest {
+            near_private_key: format!(
+                "ed25519:{}",
+                bs58::encode(signing_key().to_keypair_bytes()).into_string()
+            ),
+            signer_account_id: "alice.testnet".to_string(),
+            receiver_id: receiver_id.to_string(),
+            nonce: "42".to_string(),
+            block_hash: bs58::encode([8u8; 32]).into_string(),
+            actions: actions.to_string(),
+            idempotency_key: None,
+        },
+    ))
+    .unwrap();
+    result.signed_transactions.unwrap()[0].borsh_bytes.clone()
+}
+
+fn build(receiver_id: &str, actions: &str) -> Result<UnsignedTransaction, String> {
+    build_unsigned_transaction(&UnsignedTransactionRequest {
+        signer_account_id: "alice.testnet".to_string(),
+        signer_public_key: public_key_b58(),
+        receiver_id: receiver_id.to_string(),
+        nonce: "42".to_string(),
+        block_hash: bs58::encode([8u8; 32]).into_string(),
+        actions: actions.to_string(),
+    })
+}
+
+#[test]
+fn test_unsigned_bytes_match_the_signing_path() {
+    for (receiver_id, actions) in fixtures() {
+        let unsigned = build(receiver_id, &actions).unwrap();
+        let unsigned_bytes = base64_standard_decode(&unsigned.borsh_base64).unwrap();
+
+        // A signed transaction is the transaction's borsh bytes followed by the signature
+        let signed_bytes = sign(receiver_id, &actions);
+        let signed = SignedTransaction::from_borsh_bytes(&signed_bytes).unwrap();
+        assert_eq!(borsh::to_vec(&signed.transaction).unwrap(), unsigned_bytes);
+        assert!(signed_bytes.starts_with(&unsigned_bytes));
+
+        // The signature is over the reported hash
+        let hash = bs58::decode(&unsigned.tx_hash_b58).into_vec().unwrap();
+        let signature = Signature::from_bytes(&signed.signature.signature_data);
+        assert!(signing_key()
+            .verifying_key()
+            .verify(&hash, &signature)
+            .is_ok());
+
+        assert_eq!(unsigned.parsed_summary.receiver_id, receiver_id);
+        assert_eq!(unsigned.parsed_summary.nonce, "42");
+        assert_eq!(unsigned.parsed_summary.public_key, public_key_b58());
+    }
+}
+
+#[test]
+fn test_unsigned_build_rejects_what_signing_rejects() {
+    let invalid_deposit = json!([{ "action_type": "Transfer", "deposit": "" }]).to_string();
+    assert!(build("bob.testnet", &invalid_deposit)
+        .unwrap_err()
+        .starts_with("Failed to build actions"));
+
+    let err = build("bob.testnet", "{\"action_type\":\"Transfer\"}").unwrap_err();
+    assert!(err.starts_with("Failed to parse actions"), "{}", err);
+
+    let mut request = UnsignedTransactionRequest {
+        signer_account_id: "alice.testnet".to_string(),
+        signer_public_key: "ed25519:not-base58".to_string(),
+        receiver_id: "bob.testnet".to_string(),
+        nonce: "42".to_string(),
+        block_hash: bs58::encode([8u8; 32]).into_string(),
+        actions: "[]".to_string(),
+    };
+    assert!(build_unsigned_transaction(&request).is_err());
+    request.signer_public_key = public_key_b58();
+    request.block_hash = bs58::encode([8u8; 16]).into_string();
+    assert!(build_unsigned_transaction(&request).is_err());
+}
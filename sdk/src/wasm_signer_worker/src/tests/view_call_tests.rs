@@ -0,0 +1,206 @@
+use crate::dispatch_signer_message;
+use crate::encoders::base64_standard_decode;
+use crate::error::{RpcErrorKind, SignerErrorCode, ViewCallError};
+use crate::rpc_client::MockRpcClient;
+use crate::tests::block_on;
+use crate::types::wasm_to_json::ToJson;
+use crate::types::worker_messages::{SignerWorkerMessage, WorkerRequestType, WorkerResponseType};
+use crate::view_call::*;
+use serde_json::{json, Value};
+
+const BLOCK_HASH: &str = "11111111111111111111111111111111";
+
+fn reference(value: Value) -> ViewBlockReference {
+    serde_json::from_value(value).unwrap()
+}
+
+fn params(block_reference: Value) -> Result<Value, String> {
+    view_call_params(
+        "contract.testnet",
+        "get_greeting",
+        br#"{"account_id":"alice.testnet"}"#,
+        &reference(block_reference),
+    )
+}
+
+/// A call_function result returning `bytes`
+fn view_result(bytes: &[u8]) -> Result<Value, RpcErrorKind> {
+    Ok(json!({
+        "result": bytes,
+        "logs": ["read greeting"],
+        "block_height": 100,
+        "block_hash": BLOCK_HASH
+    }))
+}
+
+#[test]
+fn test_params_encode_args_and_name_the_block() {
+    let by_finality = params(json!({})).unwrap();
+    assert_eq!(by_finality["request_type"], json!("call_function"));
+    assert_eq!(by_finality["account_id"], json!("contract.testnet"));
+    assert_eq!(by_finality["method_name"], json!("get_greeting"));
+    assert_eq!(
+        base64_standard_decode(by_finality["args_base64"].as_str().unwrap()).unwrap(),
+        br#"{"account_id":"alice.testnet"}"#
+    );
+    assert_eq!(by_finality["finality"], json!("optimistic"));
+    assert!(by_finality.get("block_id").is_none());
+
+    assert_eq!(
+        params(json!({ "finality": "final" })).unwrap()["finality"],
+        json!("final")
+    );
+    let by_height = params(json!({ "blockId": 100 })).unwrap();
+    assert_eq!(by_height["block_id"], json!(100));
+    assert!(by_height.get("finality").is_none());
+    assert_eq!(
+        params(json!({ "blockId": BLOCK_HASH })).unwrap()["block_id"],
+        json!(BLOCK_HASH)
+    );
+
+    assert!(params(json!({ "finality": "latest" })).is_err());
+    assert!(params(json!({ "blockId": "not-a-hash" })).is_err());
+    assert!(params(json!({ "finality": "final", "blockId": 100 })).is_err());
+}
+
+#[test]
+fn test_results_decode_as_json_with_a_base64_fallback() {
+    let rpc = MockRpcClient::new()
+        .answer(
+            "query/call_function/get_greeting",
+            view_result(b"\"hello\""),
+        )
+        .answer("query/call_function/get_blob", view_result(&[0xff, 0x00]));
+
+    let result = block_on(view_call(&rpc, params(json!({})).unwrap())).unwrap();
+    assert_eq!(result.result_json, Some(json!("hello")));
+    assert_eq!(result.result_base64, "ImhlbGxvIg==");
+    assert_eq!(result.logs, vec!["read greeting".to_string()]);
+    assert_eq!(result.block_height, "100");
+    assert_eq!(result.block_hash, BLOCK_HASH);
+
+    let blob_params =
+        view_call_params("contract.testnet", "get_blob", b"{}", &Default::default()).unwrap();
+    let blob = block_on(view_call(&rpc, blob_params)).unwrap();
+    assert_eq!(blob.result_json, None);
+    assert_eq!(blob.result_base64, "/wA=");
+    assert_eq!(blob.to_json().unwrap()["resultJson"], Value::Null);
+}
+
+#[test]
+fn test_contract_panics_are_typed_whichever_way_the_node_reports_them() {
+    let vm_error = "wasm execution failed with error: FunctionCallError(ExecutionError(\"Smart contract panicked: no \\\"greeting\\\" set\"))";
+    assert_eq!(
+        contract_panic_message(vm_error),
+        "Smart contract panicked: no \"greeting\" set"
+    );
+    assert_eq!(
+        contract_panic_message("HostError(GuestPanic { panic_msg: \"out of range\" })"),
+        "out of range"
+    );
+    assert_eq!(contract_panic_message("MethodNotFound"), "MethodNotFound");
+
+    let expected = ViewCallError::ContractPanic {
+        message: "Smart contract panicked: no \"greeting\" set".to_string(),
+    };
+    // Current nodes: a CONTRACT_EXECUTION_ERROR carrying the VM error
+    let rpc = MockRpcClient::new().answer(
+        "query/call_function/get_greeting",
+        Err(RpcErrorKind::Rpc {
+            name: "CONTRACT_EXECUTION_ERROR".to_string(),
+            message: "Server error".to_string(),
+            error: json!({
+                "name": "HANDLER_ERROR",
+                "cause": { "name": "CONTRACT_EXECUTION_ERROR", "info": { "vm_error": vm_error } }
+            }),
+        }),
+    );
+    let error = block_on(view_call(&rpc, params(json!({})).unwrap())).unwrap_err();
+    assert_eq!(error, expected);
+    assert_eq!(error.code(), SignerErrorCode::ContractPanic);
+    assert!(error
+        .to_string()
+        .starts_with("ContractPanic: Smart contract panicked"));
+
+    // Older nodes: a result with an error string
+    let rpc = MockRpcClient::new().answer(
+        "query/call_function/get_greeting",
+        Ok(json!({ "error": vm_error, "logs": [], "block_height": 100, "block_hash": BLOCK_HASH })),
+    );
+    assert_eq!(
+        block_on(view_call(&rpc, params(json!({})).unwrap())).unwrap_err(),
+        expected
+    );
+}
+
+#[test]
+fn test_other_failures_stay_rpc_errors() {
+    let unknown_account = RpcErrorKind::Rpc {
+        name: "UNKNOWN_ACCOUNT".to_string(),
+        message: "account contract.testnet does not exist".to_string(),
+        error: json!({ "cause": { "name": "UNKNOWN_ACCOUNT" } }),
+    };
+    let rpc = MockRpcClient::new().answer(
+        "query/call_function/get_greeting",
+        Err(unknown_account.clone()),
+    );
+    let error = block_on(view_call(&rpc, params(json!({})).unwrap())).unwrap_err();
+    assert_eq!(error, ViewCallError::Rpc(unknown_account));
+    assert_eq!(error.code(), SignerErrorCode::RpcRequestFailed);
+
+    let rpc = MockRpcClient::new().answer(
+        "query/call_function/get_greeting",
+        Err(RpcErrorKind::Timeout("slow".to_string())),
+    );
+    let error = block_on(view_call(&rpc, params(json!({})).unwrap())).unwrap_err();
+    assert_eq!(error.code(), SignerErrorCode::RpcTimeout);
+
+    let rpc = MockRpcClient::new().answer(
+        "query/call_function/get_greeting",
+        Ok(json!({ "result": [256], "block_height": 100, "block_hash": BLOCK_HASH })),
+    );
+    let error = block_on(view_call(&rpc, params(json!({})).unwrap())).unwrap_err();
+    assert_eq!(error.code(), SignerErrorCode::RpcResponseInvalid);
+}
+
+#[test]
+fn test_invalid_requests_fail_before_any_rpc_call() {
+    let dispatch = |payload: Value| {
+        block_on(dispatch_signer_message(SignerWorkerMessage {
+            msg_type: WorkerRequestType::ViewCall as u32,
+            payload,
+            request_id: None,
+        }))
+        .unwrap()
+    };
+    let request = |extra: Value| {
+        let mut payload = json!({
+            "nearRpcUrl": "https://rpc.testnet.near.org",
+            "contractId": "contract.testnet",
+            "method": "get_greeting"
+        });
+        payload
+            .as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        payload
+    };
+
+    let response = dispatch(request(json!({ "argsJson": "{not json" })));
+    assert_eq!(
+        WorkerResponseType::from(response.response_type),
+        WorkerResponseType::ViewCallFailure
+    );
+    assert!(response.payload["error"]
+        .as_str()
+        .unwrap()
+        .contains("argsJson is not valid JSON"));
+
+    let response = dispatch(request(
+        json!({ "blockReference": { "finality": "latest" } }),
+    ));
+    assert_eq!(
+        WorkerResponseType::from(response.response_type),
+        WorkerResponseType::ViewCallFailure
+    );
+}
@@ -0,0 +1,333 @@
+use crate::error::SignerErrorCode;
+use crate::error_codes::LEGACY_KEY_BLOB;
+use crate::state;
+use crate::types::worker_messages::{
+    SignerWorkerMessage, SignerWorkerResponse, WorkerRequestType, WorkerResponseType,
+};
+use crate::wire_format::*;
+use serde_json::json;
+
+/// Representative payload per request type; exhaustive so new request types must be added here
+pub(crate) fn sample_payload(request_type: WorkerRequestType) -> serde_json::Value {
+    match request_type {
+        WorkerRequestType::DeriveNearKeypairAndEncrypt
+        | WorkerRequestType::RecoverKeypairFromPasskey
+        | WorkerRequestType::CheckCanRegisterUser
+        | WorkerRequestType::RegistrationCredentialConfirmation => json!({
+            "credential": { "id": "cred", "response": { "clientDataJSON": "e30" } },
+            "nearAccountId": "alice.testnet",
+            "dryRun": false
+        }),
+        WorkerRequestType::DecryptPrivateKeyWithPrf | WorkerRequestType::ExportNearKeypairUI => {
+            json!({ "nearAccountId": "alice.testnet", "prfOutput": "AAEC" })
+        }
+        WorkerRequestType::SignTransactionsWithActions
+        | WorkerRequestType::SignTransactionsWithFreshChallenge
+        | WorkerRequestType::SignTransactionWithKeyPair
+        | WorkerRequestType::SignWithSessionKey
+        | WorkerRequestType::ComposeMultisigRequest => json!({
+            "txSigningRequests": [{
+                "receiverId": "bob.testnet",
+                "actions": [{ "action_type": "DeployContract", "code": [0, 97, 115, 109] }]
+            }],
+            "nonce": 42,
+            "blockHeight": "100"
+        }),
+        WorkerRequestType::ExtractCosePublicKey => json!({ "attestationObjectBase64url": "o2Nm" }),
+        WorkerRequestType::SignNep413Message => {
+            json!({ "message": "hello", "recipient": "app.testnet", "nonce": "AAAA" })
+        }
+        WorkerRequestType::GetBorshSchemas
+        | WorkerRequestType::ListPendingRequests
+        | WorkerRequestType::WipeAllState
+        | WorkerRequestType::GetMemoryStats
+        | WorkerRequestType::TrimCaches
+        | WorkerRequestType::RunSelfTest
+        | WorkerRequestType::GetTelemetrySnapshot
+        | WorkerRequestType::GetKeyUsageStats
+        | WorkerRequestType::ListActiveAccounts
+        | WorkerRequestType::StopBackgroundRefresh
+        | WorkerRequestType::BackgroundRefreshStatus
+        | WorkerRequestType::SuspendHint
+        | WorkerRequestType::ResumeHint => json!({}),
+        WorkerRequestType::GetRecentReceivers => json!({
+            "accountId": "alice.testnet",
+            "limit": 10,
+            "workerPolicy": { "indexerUrl": "https://api.nearblocks.io/v1" }
+        }),
+        WorkerRequestType::BuildAccountDescriptor => json!({
+            "rpcCall": {
+                "contractId": "w3a-v1.testnet",
+                "nearRpcUrl": "https://rpc.testnet.near.org",
+                "nearAccountId": "alice.testnet"
+            },
+            "vrfPublicKey": "dnJmLXB1YmxpYy1rZXk",
+            "decryption": { "encryptedPrivateKeyData": "AAAA", "encryptedPrivateKeyIv": "BBBB" },
+            "ttlMs": 3600000
+        }),
+        WorkerRequestType::DeployLargeContract => json!({
+            "rpcCall": {
+                "contractId": "w3a-v1.testnet",
+                "nearRpcUrl": "https://rpc.testnet.near.org",
+                "nearAccountId": "alice.testnet"
+            },
+            "decryption": { "encryptedPrivateKeyData": "AAAA", "encryptedPrivateKeyIv": "BBBB" },
+            "code": [0, 97, 115, 109, 1, 0, 0, 0],
+            "chunkSize": 4,
+            "staging": { "stageMethod": "stage_code", "deployMethod": "deploy_staged" }
+        }),
+        WorkerRequestType::ExportAccountBundle => json!({
+            "passphrase": "correct horse battery staple",
+            "records": { "users": [], "nearKeys": [{ "nearAccountId": "alice.testnet", "encryptedData": "AAAA", "iv": "BBBB" }] }
+        }),
+        WorkerRequestType::ImportAccountBundle => {
+            json!({ "passphrase": "correct horse battery staple", "bundle": [162, 102] })
+        }
+        WorkerRequestType::CancelRequest | WorkerRequestType::RevokeSessionKey => {
+            json!({ "requestId": "req-1", "sessionId": "session-1" })
+        }
+        WorkerRequestType::ValidateEncryptedBlobs => {
+            json!({ "blobs": [{ "encryptedData": "AAAA", "iv": "BBBB" }] })
+        }
+        WorkerRequestType::CreateSigningIntent | WorkerRequestType::ExecuteSigningIntent => {
+            json!({ "intentToken": "AAAA", "ttlMs": 600000, "txSigningRequests": [] })
+        }
+        WorkerRequestType::CreateSessionKey => {
+            json!({ "ttlMs": 60000, "allowance": null, "methodNames": ["ping"] })
+        }
+        WorkerRequestType::SubmitToRelayer => json!({
+            "nearAccountId": "alice.testnet",
+            "newPublicKey": "ed25519:11111111111111111111111111111111",
+            "authToken": "secret",
+            "workerPolicy": { "relayer": { "url": "https://relay.example.com" } }
+        }),
+        WorkerRequestType::DeriveSubKey => json!({
+            "nearAccountId": "alice.testnet",
+            "dualPrfOutputs": { "chacha20PrfOutput": "AQID", "ed25519PrfOutput": "BAUG" },
+            "path": "m/44'/397'/0'/0'/1'",
+            "encrypt": true
+        }),
+        WorkerRequestType::ViewCall => json!({
+            "nearRpcUrl": "https://rpc.testnet.near.org",
+            "contractId": "contract.testnet",
+            "method": "get_greeting",
+            "argsJson": "{\"account_id\":\"alice.testnet\"}",
+            "blockReference": { "blockId": 100 }
+        }),
+        WorkerRequestType::DumpDiagnostics => json!({
+            "workerPolicy": { "allowedRpcOrigins": ["https://rpc.testnet.near.org"] },
+            "keyBlobs": [{ "encryptedPrivateKeyData": "AQID", "encryptedPrivateKeyIv": "BAUG" }]
+        }),
+        WorkerRequestType::PrepareRegistration => json!({
+            "dualPrfOutputs": { "chacha20PrfOutput": "AQID", "ed25519PrfOutput": "BAUG" },
+            "nearAccountId": "alice.testnet",
+        }),
+        WorkerRequestType::CompleteRegistration => json!({
+            "resumeToken": "AAAA",
+            "nearAccountId": "alice.testnet",
+            "authToken": "secret",
+        }),
+        WorkerRequestType::CreateRemoteConfirmation => {
+            json!({ "ttlMs": 120000, "txSigningRequests": [] })
+        }
+        WorkerRequestType::ApproveRemoteConfirmation => {
+            json!({ "confirmationRequest": "AAAA", "confirmationConfig": { "uiMode": "modal" } })
+        }
+        WorkerRequestType::CompleteRemoteConfirmation => {
+            json!({ "approval": "AAAA", "txSigningRequests": [] })
+        }
+        WorkerRequestType::GetWorkerInfo => json!({}),
+        WorkerRequestType::GetSessionStatus => json!({}),
+        WorkerRequestType::IssueElevationChallenge => json!({ "nearAccountId": "alice.testnet" }),
+        WorkerRequestType::BuildRedirectPayload => json!({
+            "nearAccountId": "alice.testnet",
+            "sessionPublicKey": "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp",
+            "vrfChallenge": { "vrfOutput": "AAAA", "userId": "alice.testnet" },
+            "callbackUrl": "https://app.example.com/signed-in",
+            "state": "xyz",
+            "workerPolicy": { "allowedRedirectOrigins": ["https://app.example.com"] }
+        }),
+        WorkerRequestType::ValidateDecryptionCapability => json!({
+            "nearAccountId": "alice.testnet",
+            "chacha20PrfOutput": "AAAA",
+            "decryption": { "encryptedPrivateKeyData": "AAAA", "encryptedPrivateKeyIv": "BBBB" },
+        }),
+        WorkerRequestType::WipeAccountState => json!({ "accountId": "alice.testnet" }),
+        WorkerRequestType::ValidateArgsSchemas => json!({
+            "argsSchemas": [{
+                "contractId": "usdc.testnet",
+                "methodName": "ft_transfer",
+                "schema": { "type": "object", "required": ["receiver_id"] }
+            }]
+        }),
+        WorkerRequestType::SummarizeTransactions => json!({
+            "txSigningRequests": [{
+                "nearAccountId": "alice.testnet",
+                "receiverId": "bob.testnet",
+                "actions": "[]"
+            }],
+            "deduplicate": true
+        }),
+        WorkerRequestType::MigrateEncryptedBlobs => json!({
+            "chacha20PrfOutput": "AAAA",
+            "blobs": [{ "kind": "vrfKeypair", "blob": { "encryptedVrfDataB64u": "AAAA" } }]
+        }),
+        WorkerRequestType::BuildCredentialCreationOptions => json!({
+            "registration": { "nearAccountId": "alice.testnet", "nonce": "1", "blockHash": "AAAA" },
+            "rpId": "example.localhost",
+            "vrfChallenge": { "vrfOutput": "AAAA", "userId": "alice.testnet" },
+            "existingCredentials": [{ "credentialId": "Y3JlZC0x", "transports": ["internal"] }]
+        }),
+        WorkerRequestType::BuildCredentialRequestOptions => json!({
+            "nearAccountId": "alice.testnet",
+            "rpId": "example.localhost",
+            "vrfChallenge": { "vrfOutput": "AAAA", "userId": "alice.testnet" },
+            "authenticators": [
+                { "credentialId": "Y3JlZC0x", "deviceNumber": 1, "residentKey": true }
+            ],
+            "deviceNumber": 1,
+            "userVerification": "required"
+        }),
+        WorkerRequestType::RecoverPendingOperations => json!({
+            "nearRpcUrl": "https://rpc.testnet.near.org",
+            "entries": [{
+                "requestId": "req-1",
+                "step": "broadcast",
+                "txHash": "11111111111111111111111111111111",
+                "accountId": "alice.testnet",
+                "createdAtMs": 0.0
+            }]
+        }),
+        WorkerRequestType::ComposeCreateSubaccount => json!({
+            "nearAccountId": "myapp.testnet",
+            "parentAccountId": "myapp.testnet",
+            "childPrefix": "alice",
+            "initialBalance": "100000000000000000000000",
+            "publicKey": "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+        }),
+        WorkerRequestType::StartBackgroundRefresh => json!({
+            "nearRpcUrl": "https://rpc.testnet.near.org",
+            "intervalMs": 5_000,
+            "accounts": [{
+                "nearAccountId": "alice.testnet",
+                "publicKey": "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+            }]
+        }),
+    }
+}
+
+fn cbor_bytes<T: serde::Serialize>(value: &T) -> Vec<u8> {
+    let mut frame = Vec::new();
+    ciborium::into_writer(value, &mut frame).unwrap();
+    frame
+}
+
+#[test]
+fn test_parse_wire_format() {
+    assert_eq!(WireFormat::parse("json").unwrap(), WireFormat::Json);
+    assert_eq!(WireFormat::parse("cbor").unwrap(), WireFormat::Cbor);
+    assert!(WireFormat::parse("msgpack").is_err());
+    assert_eq!(WireFormat::default(), WireFormat::Json);
+}
+
+#[test]
+fn test_every_request_type_round_trips_in_both_formats() {
+    for msg_type in 0..=58u32 {
+        let request_type = WorkerRequestType::from(msg_type);
+        let message = SignerWorkerMessage {
+            msg_type,
+            payload: sample_payload(request_type),
+            request_id: Some(format!("req-{}", msg_type)),
+        };
+
+        let json_frame = serde_json::to_string(&message).unwrap();
+        let from_json = decode_json_frame(WireFormat::Json, &json_frame).unwrap();
+        let from_cbor = decode_cbor_frame(WireFormat::Cbor, &cbor_bytes(&message)).unwrap();
+
+        for decoded in [from_json, from_cbor] {
+            assert_eq!(decoded.msg_type, msg_type, "{}", request_type.name());
+            assert_eq!(decoded.payload, message.payload, "{}", request_type.name());
+            assert_eq!(decoded.request_id, message.request_id);
+        }
+    }
+}
+
+#[test]
+fn test_every_response_type_round_trips_in_both_formats() {
+    for response_type in 0..=121u32 {
+        // Panics on a gap in the numbering
+        let _ = WorkerResponseType::from(response_type);
+        let response = SignerWorkerResponse {
+            response_type,
+            payload: json!({ "success": true, "data": { "bytes": [1, 2, 3], "label": "ok" } }),
+            warnings: vec![LEGACY_KEY_BLOB.warning()],
+        };
+
+        let json_frame = encode_json_response(&response).unwrap();
+        let from_json: SignerWorkerResponse = serde_json::from_str(&json_frame).unwrap();
+
+        let cbor_frame = encode_cbor_response(&response).unwrap();
+        let value: ciborium::Value = ciborium::from_reader(cbor_frame.as_slice()).unwrap();
+        let from_cbor: SignerWorkerResponse =
+            serde_json::from_value(cbor_to_json(value).unwrap()).unwrap();
+
+        for decoded in [from_json, from_cbor] {
+            assert_eq!(decoded.response_type, response_type);
+            assert_eq!(decoded.payload, response.payload);
+            assert_eq!(decoded.warnings, response.warnings);
+        }
+    }
+}
+
+#[test]
+fn test_cbor_byte_strings_decode_as_number_arrays() {
+    let frame = cbor_bytes(&ciborium::Value::Map(vec![
+        (
+            ciborium::Value::Text("type".into()),
+            ciborium::Value::Integer(4.into()),
+        ),
+        (
+            ciborium::Value::Text("payload".into()),
+            ciborium::Value::Map(vec![(
+                ciborium::Value::Text("code".into()),
+                ciborium::Value::Bytes(vec![0, 97, 115, 109]),
+            )]),
+        ),
+    ]));
+
+    let decoded = decode_cbor_frame(WireFormat::Cbor, &frame).unwrap();
+    assert_eq!(decoded.payload, json!({ "code": [0, 97, 115, 109] }));
+    assert_eq!(decoded.request_id, None);
+}
+
+#[test]
+fn test_mismatched_frames_are_rejected() {
+    let message = SignerWorkerMessage {
+        msg_type: 10,
+        payload: json!({}),
+        request_id: None,
+    };
+    let json_frame = serde_json::to_string(&message).unwrap();
+    let mismatch = SignerErrorCode::WireFormatMismatch.as_str();
+
+    // CBOR frame sent to a JSON worker
+    let err = decode_cbor_frame(WireFormat::Json, &cbor_bytes(&message)).unwrap_err();
+    assert!(err.starts_with(mismatch), "{}", err);
+
+    // JSON frame sent to a CBOR worker, through either entry point
+    let err = decode_json_frame(WireFormat::Cbor, &json_frame).unwrap_err();
+    assert!(err.starts_with(mismatch), "{}", err);
+    let err = decode_cbor_frame(WireFormat::Cbor, json_frame.as_bytes()).unwrap_err();
+    assert!(err.starts_with(mismatch), "{}", err);
+}
+
+#[test]
+fn test_initialize_sets_worker_wire_format() {
+    assert_eq!(state::wire_format(), WireFormat::Json);
+    assert_eq!(initialize("cbor").unwrap(), WireFormat::Cbor);
+    assert_eq!(state::wire_format(), WireFormat::Cbor);
+    assert!(initialize("xml").is_err());
+    assert_eq!(state::wire_format(), WireFormat::Cbor);
+    initialize("json").unwrap();
+    assert_eq!(state::wire_format(), WireFormat::Json);
+}
@@ -0,0 +1,152 @@
+use crate::actions::ActionParams;
+use crate::error::SignerErrorCode;
+use crate::handlers::handle_session_keys::{
+    handle_create_session_key, handle_revoke_session_key, handle_sign_with_session_key,
+    CreateSessionKeyRequest, RevokeSessionKeyRequest, SignWithSessionKeyRequest,
+};
+use crate::tests::{block_on, in_fresh_worker};
+use crate::worker_state::{self, WorkerStateRecord};
+
+const ACCOUNT_ID: &str = "player.testnet";
+const GAME_CONTRACT: &str = "game.testnet";
+
+fn create_session_key(allowance: &str) -> String {
+    block_on(handle_create_session_key(CreateSessionKeyRequest {
+        near_account_id: ACCOUNT_ID.to_string(),
+        receiver_id: GAME_CONTRACT.to_string(),
+        method_names: vec!["make_move".to_string()],
+        allowance: allowance.to_string(),
+        ttl_ms: None,
+    }))
+    .unwrap()
+    .public_key
+}
+
+fn make_move(public_key: &str) -> Option<String> {
+    let actions = vec![ActionParams::FunctionCall {
+        method_name: "make_move".to_string(),
+        args: "{}".to_string(),
+        gas: "30000000000000".to_string(),
+        deposit: "0".to_string(),
+    }];
+    let result = block_on(handle_sign_with_session_key(SignWithSessionKeyRequest {
+        session_public_key: public_key.to_string(),
+        signer_account_id: ACCOUNT_ID.to_string(),
+        receiver_id: GAME_CONTRACT.to_string(),
+        nonce: "1".to_string(),
+        block_hash: bs58::encode([4u8; 32]).into_string(),
+        actions: serde_json::to_string(&actions).unwrap(),
+        gas_price: Some("100000000".to_string()),
+        idempotency_key: None,
+    }))
+    .unwrap();
+    result.error_code
+}
+
+#[test]
+fn test_session_key_signs_in_a_later_worker() {
+    // 30 TGas at 1e8 yocto/gas = 3e21 yocto per call: the allowance covers exactly two calls
+    let (public_key, record) = in_fresh_worker(None, || {
+        let public_key = create_session_key("6000000000000000000000");
+        (public_key, worker_state::take_changed_record())
+    });
+    let record = record.expect("creating a session key changes the persisted state");
+    assert!(!record.sealed_state.contains(GAME_CONTRACT));
+
+    let key = public_key.clone();
+    let (first, record) = in_fresh_worker(Some(record), move || {
+        (make_move(&key), worker_state::take_changed_record())
+    });
+    assert_eq!(first, None);
+
+    // The charged allowance travels with the key
+    let key = public_key.clone();
+    let (second, third) = in_fresh_worker(record, move || (make_move(&key), make_move(&key)));
+    assert_eq!(second, None);
+    assert_eq!(
+        third.as_deref(),
+        Some(SignerErrorCode::SessionKeyAllowanceExceeded.as_str())
+    );
+}
+
+#[test]
+fn test_revoked_session_key_stays_revoked_in_a_later_worker() {
+    let (public_key, record) = in_fresh_worker(None, || {
+        let public_key = create_session_key("250000000000000000000000");
+        (public_key, worker_state::take_changed_record())
+    });
+    let key = public_key.clone();
+    let record = in_fresh_worker(record, move || {
+        let revoked = block_on(handle_revoke_session_key(RevokeSessionKeyRequest {
+            session_public_key: key,
+        }))
+        .unwrap();
+        assert!(revoked.revoked);
+        worker_state::take_changed_record()
+    });
+    let outcome = in_fresh_worker(record, move || make_move(&public_key));
+    assert_eq!(
+        outcome.as_deref(),
+        Some(SignerErrorCode::SessionKeyNotFound.as_str())
+    );
+}
+
+#[test]
+fn test_unchanged_state_exports_no_record() {
+    let record = in_fresh_worker(None, || {
+        create_session_key("250000000000000000000000");
+        worker_state::take_changed_record()
+    });
+    let unchanged = in_fresh_worker(record, worker_state::take_changed_record);
+    assert_eq!(unchanged, None);
+    // Without a state key nothing is persisted
+    let without_key = std::thread::spawn(|| {
+        create_session_key("250000000000000000000000");
+        worker_state::take_changed_record()
+    })
+    .join()
+    .unwrap();
+    assert_eq!(without_key, None);
+}
+
+#[test]
+fn test_edited_record_does_not_open() {
+    let record = in_fresh_worker(None, || {
+        create_session_key("250000000000000000000000");
+        worker_state::take_changed_record()
+    })
+    .unwrap();
+
+    let mut sealed = crate::encoders::base64_url_decode(&record.sealed_state).unwrap();
+    let last = sealed.len() - 1;
+    sealed[last] ^= 1;
+    let edited = WorkerStateRecord {
+        sealed_state: crate::encoders::base64_url_encode(&sealed),
+        ..record.clone()
+    };
+    let moved = WorkerStateRecord {
+        wrapped_state_key: "another-wrapped-state-key".to_string(),
+        ..record
+    };
+    for record in [edited, moved] {
+        let opened = std::thread::spawn(move || {
+            worker_state::install_state_key(
+                crate::tests::TEST_STATE_KEY,
+                crate::tests::TEST_WRAPPED_STATE_KEY.to_string(),
+            );
+            worker_state::open_record(&record)
+        })
+        .join()
+        .unwrap();
+        assert!(opened.is_err());
+    }
+}
+
+#[test]
+fn test_record_without_wrap_key_is_not_persisted() {
+    // Native builds have no WebCrypto, like a worker that was given no wrap CryptoKey
+    let persisted = std::thread::spawn(|| block_on(worker_state::load_record(None)))
+        .join()
+        .unwrap();
+    assert_eq!(persisted, Ok(false));
+}
@@ -3,6 +3,9 @@ use ed25519_dalek::{Signer, SigningKey};
 use sha2::{Digest, Sha256};
 
 use crate::actions::{get_action_handler, ActionParams};
+use crate::contract_args::{
+    link_device_args_json, registration_args_json, ContractInterfaceVersion,
+};
 use crate::encoders::base64_url_decode;
 use crate::rpc_calls::{
     ContractRegistrationResult, VrfData, LINK_DEVICE_REGISTER_USER_METHOD,
@@ -19,6 +22,30 @@ pub fn build_transaction_with_actions(
     block_hash_bytes: &[u8],
     private_key: &SigningKey,
     actions: Vec<Action>,
+) -> Result<Transaction, String> {
+    // Create PublicKey from ed25519 verifying key
+    let public_key_bytes = private_key.verifying_key().to_bytes();
+    let public_key = PublicKey::from_ed25519_bytes(&public_key_bytes);
+
+    build_transaction_for_public_key(
+        signer_account_id,
+        receiver_account_id,
+        nonce,
+        block_hash_bytes,
+        public_key,
+        actions,
+    )
+}
+
+/// Build a transaction for a signer public key, without key material
+/// (shared by the signing path and `build_unsigned_transaction`)
+pub fn build_transaction_for_public_key(
+    signer_account_id: &str,
+    receiver_account_id: &str,
+    nonce: u64,
+    block_hash_bytes: &[u8],
+    public_key: PublicKey,
+    actions: Vec<Action>,
 ) -> Result<Transaction, String> {
     // Parse account IDs
     let signer_id: AccountId = signer_account_id
@@ -36,10 +63,6 @@ pub fn build_transaction_with_actions(
     block_hash_array.copy_from_slice(block_hash_bytes);
     let block_hash = CryptoHash::from_bytes(block_hash_array);
 
-    // Create PublicKey from ed25519 verifying key
-    let public_key_bytes = private_key.verifying_key().to_bytes();
-    let public_key = PublicKey::from_ed25519_bytes(&public_key_bytes);
-
     // Build transaction
     Ok(Transaction {
         signer_id,
@@ -118,6 +141,7 @@ pub async fn sign_registration_tx_wasm(
     block_hash_bytes: &[u8],
     device_number: Option<u8>, // Device number for multi-device support (defaults to 1)
     authenticator_options: Option<AuthenticatorOptions>, // Authenticator options for registration
+    interface_version: ContractInterfaceVersion, // Contract interface the args are shaped for
 ) -> Result<ContractRegistrationResult, String> {
     use log::debug;
     use log::info;
@@ -125,13 +149,14 @@ pub async fn sign_registration_tx_wasm(
     info!("RUST: Performing dual VRF user registration (state-changing function)");
 
     // Step 1: Decrypt the private key using PRF with account-specific HKDF
-    let private_key = crate::crypto::decrypt_private_key_with_prf(
+    let private_key = crate::crypto::decrypt_stored_private_key_with_prf(
         signer_account_id,          // 1st parameter: Account ID
         prf_output_base64,          // 2nd parameter: PRF output
         encrypted_private_key_data, // 3rd parameter: Encrypted data
         encrypted_private_key_iv,   // 4th parameter: IV
     )
-    .map_err(|e| format!("Failed to decrypt private key: {:?}", e))?;
+    .await
+    .map_err(|e| format!("Failed to decrypt private key: {}", e))?;
 
     // Step 2: Build dual VRF data for contract arguments
     let deterministic_vrf_key_bytes = if let Some(det_vrf_key) = deterministic_vrf_public_key {
@@ -144,18 +169,19 @@ pub async fn sign_registration_tx_wasm(
     };
 
     // Step 3: Build contract arguments for verify_and_register_user with dual VRF support
-    let contract_args = serde_json::json!({
-        "vrf_data": vrf_data,
-        "webauthn_registration": webauthn_registration_credential,
-        "deterministic_vrf_public_key": deterministic_vrf_key_bytes,
-        "device_number": device_number, // Include device number for multi-device support
-        "authenticator_options": authenticator_options // Include authenticator options
-    });
+    let contract_args = registration_args_json(
+        interface_version,
+        vrf_data,
+        webauthn_registration_credential,
+        deterministic_vrf_key_bytes,
+        device_number,         // Include device number for multi-device support (V2+)
+        authenticator_options, // Include authenticator options (V2+)
+    )?;
 
     // Step 4: Create FunctionCall action using existing infrastructure
     let action_params = vec![crate::actions::ActionParams::FunctionCall {
         method_name: VERIFY_AND_REGISTER_USER_METHOD.to_string(),
-        args: contract_args.to_string(),
+        args: contract_args,
         gas: crate::config::VERIFY_REGISTRATION_GAS.to_string(),
         deposit: "0".to_string(),
     }];
@@ -241,6 +267,7 @@ pub async fn sign_link_device_registration_tx(
     nonce: u64,
     block_hash_bytes: &[u8],
     authenticator_options: Option<AuthenticatorOptions>, // Authenticator options for registration
+    interface_version: ContractInterfaceVersion, // Contract interface the args are shaped for
 ) -> Result<ContractRegistrationResult, String> {
     use bs58;
     use ed25519_dalek::SigningKey;
@@ -275,13 +302,13 @@ pub async fn sign_link_device_registration_tx(
     // Build verify_and_register_user transaction actions
     let action_params = vec![crate::actions::ActionParams::FunctionCall {
         method_name: LINK_DEVICE_REGISTER_USER_METHOD.to_string(),
-        args: serde_json::json!({
-            "vrf_data": vrf_data,
-            "webauthn_registration": webauthn_registration,
-            "deterministic_vrf_public_key": deterministic_vrf_public_key,
-            "authenticator_options": authenticator_options
-        })
-        .to_string(),
+        args: link_device_args_json(
+            interface_version,
+            vrf_data,
+            webauthn_registration,
+            deterministic_vrf_public_key,
+            authenticator_options,
+        )?,
         gas: crate::config::LINK_DEVICE_REGISTRATION_GAS.to_string(),
         deposit: "0".to_string(),
     }];
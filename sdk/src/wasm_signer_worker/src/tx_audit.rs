@@ -0,0 +1,306 @@
+// === SIGNED TRANSACTION AUDIT ===
+// Lets a third party check, from public artifacts only, that a signed transaction is the one the
+// user confirmed: the signed transaction, the intent digest of its confirmation and the summary
+// shown. The digest is recomputed through the same txSigningRequests pipeline the confirmation
+// used (confirm_tx_details.rs) from the actions decoded out of the transaction, the signature is
+// verified against the embedded public key, and the summary is compared field by field. No key
+// material or worker session is involved, so this also links natively (rlib) for server-side
+// audit tooling.
+//
+// The digest reproduces one-transaction confirmations without a summary policy or deploy
+// manifest; a `firstTimeReceiver` in the expected summary is digested as the confirmation did.
+// AddKey access keys are digested in the JSON form the TS layer writes (toActionArgsWasm).
+
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+
+use crate::actions::ActionParams;
+use crate::encoders::{base64_standard_decode, base64_url_encode};
+use crate::handlers::confirm_tx_details::compute_intent_digest_from_js_inputs;
+use crate::signature_verify::decode_ed25519_key;
+use crate::transaction::build_actions_from_params;
+use crate::types::{AccessKeyPermission, Action, PublicKey, SignedTransaction};
+use crate::unsigned_transaction::UnsignedTransactionSummary;
+
+/// Summary the user was shown, in the shape of a confirmation's txSigningRequests entry or of
+/// build_unsigned_transaction's parsedSummary. Only the fields present are compared.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ExpectedTransactionSummary {
+    signer_id: Option<String>,
+    public_key: Option<String>,
+    nonce: Option<String>,
+    receiver_id: Option<String>,
+    block_hash: Option<String>,
+    actions: Option<Vec<ActionParams>>,
+}
+
+/// One compared field, e.g. `receiverId` or `actions[0].deposit`
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditFieldCheck {
+    pub field: String,
+    pub matches: bool,
+    /// Null when the expected summary has no such action
+    pub expected: Value,
+    /// Null when the transaction has no such action
+    pub actual: Value,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditVerificationResult {
+    /// Signature, digest and every compared field check out
+    pub valid: bool,
+    /// Ed25519 signature over the transaction hash verifies against the embedded public key
+    pub signature_valid: bool,
+    /// Intent digest recomputed from the transaction
+    pub digest: String,
+    pub digest_matches: bool,
+    pub summary_matches: bool,
+    pub fields: Vec<AuditFieldCheck>,
+    /// SHA-256 of the borsh-serialized transaction, base58
+    pub tx_hash_b58: String,
+    /// The transaction as decoded, actions in request form
+    pub transaction: UnsignedTransactionSummary,
+}
+
+pub(crate) fn public_key_string(public_key: &PublicKey) -> String {
+    format!(
+        "ed25519:{}",
+        bs58::encode(public_key.key_data).into_string()
+    )
+}
+
+/// ActionParams that build `action`: the request form the confirmation digested
+pub(crate) fn action_params_of(action: &Action) -> ActionParams {
+    match action {
+        Action::CreateAccount => ActionParams::CreateAccount,
+        Action::DeployContract { code } => ActionParams::DeployContract { code: code.clone() },
+        Action::FunctionCall(call) => ActionParams::FunctionCall {
+            method_name: call.method_name.clone(),
+            // Built from the args string's bytes
+            args: String::from_utf8_lossy(&call.args).into_owned(),
+            gas: call.gas.to_string(),
+            deposit: call.deposit.to_string(),
+        },
+        Action::Transfer { deposit } => ActionParams::Transfer {
+            deposit: deposit.to_string(),
+        },
+        Action::Stake { stake, public_key } => ActionParams::Stake {
+            stake: stake.to_string(),
+            public_key: public_key_string(public_key),
+        },
+        Action::AddKey {
+            public_key,
+            access_key,
+        } => {
+            let permission = match &access_key.permission {
+                AccessKeyPermission::FullAccess => json!({ "FullAccess": {} }),
+                AccessKeyPermission::FunctionCall(permission) => json!({
+                    "FunctionCall": {
+                        "allowance": permission.allowance.map(|a| a.to_string()),
+                        "receiver_id": permission.receiver_id,
+                        "method_names": permission.method_names,
+                    }
+                }),
+            };
+            ActionParams::AddKey {
+                public_key: public_key_string(public_key),
+                access_key: json!({ "nonce": access_key.nonce, "permission": permission })
+                    .to_string(),
+            }
+        }
+        Action::DeleteKey { public_key } => ActionParams::DeleteKey {
+            public_key: public_key_string(public_key),
+        },
+        Action::DeleteAccount { beneficiary_id } => ActionParams::DeleteAccount {
+            beneficiary_id: beneficiary_id.as_str().to_string(),
+        },
+    }
+}
+
+/// The fields of an action as compared; contract code by length and hash
+fn action_fields(params: &ActionParams) -> Map<String, Value> {
+    let mut fields = match serde_json::to_value(params) {
+        Ok(Value::Object(fields)) => fields,
+        _ => Map::new(),
+    };
+    if let ActionParams::DeployContract { code } = params {
+        fields.insert(
+            "code".to_string(),
+            json!(format!(
+                "{} bytes, sha256 {}",
+                code.len(),
+                base64_url_encode(&Sha256::digest(code))
+            )),
+        );
+    }
+    fields
+}
+
+fn check(fields: &mut Vec<AuditFieldCheck>, field: String, expected: Value, actual: Value) {
+    fields.push(AuditFieldCheck {
+        field,
+        matches: expected == actual,
+        expected,
+        actual,
+    });
+}
+
+fn check_actions(
+    fields: &mut Vec<AuditFieldCheck>,
+    expected: &[ActionParams],
+    actual: &[ActionParams],
+) {
+    for i in 0..expected.len().max(actual.len()) {
+        match (expected.get(i), actual.get(i)) {
+            (Some(expected), Some(actual)) => {
+                let expected = action_fields(expected);
+                let actual = action_fields(actual);
+                let mut keys: Vec<&String> = expected.keys().chain(actual.keys()).collect();
+                keys.sort();
+                keys.dedup();
+                for key in keys {
+                    let value = |fields: &Map<String, Value>| fields.get(key).cloned();
+                    check(
+                        fields,
+                        format!("actions[{}].{}", i, key),
+                        value(&expected).unwrap_or(Value::Null),
+                        value(&actual).unwrap_or(Value::Null),
+                    );
+                }
+            }
+            (expected, actual) => {
+                let value = |params: Option<&ActionParams>| {
+                    params.map_or(Value::Null, |p| Value::Object(action_fields(p)))
+                };
+                check(
+                    fields,
+                    format!("actions[{}]", i),
+                    value(expected),
+                    value(actual),
+                );
+            }
+        }
+    }
+}
+
+/// Checks `signed_tx_base64` (standard base64 borsh SignedTransaction) against the intent digest
+/// of its confirmation and the summary shown (`expected_summary_json`). Mismatches are reported
+/// in the result; only undecodable inputs are errors.
+pub fn audit_signed_transaction(
+    signed_tx_base64: &str,
+    expected_digest: &str,
+    expected_summary_json: &str,
+) -> Result<AuditVerificationResult, String> {
+    let bytes = base64_standard_decode(signed_tx_base64)
+        .map_err(|e| format!("Invalid signed transaction: {}", e))?;
+    let signed = SignedTransaction::from_borsh_bytes(&bytes)
+        .map_err(|e| format!("Invalid signed transaction: {}", e))?;
+    let expected_value: Value = serde_json::from_str(expected_summary_json)
+        .map_err(|e| format!("Invalid expected summary JSON: {}", e))?;
+    let expected: ExpectedTransactionSummary = serde_json::from_value(expected_value.clone())
+        .map_err(|e| format!("Invalid expected summary: {}", e))?;
+
+    let tx = &signed.transaction;
+    let (tx_hash, _size) = tx.get_hash_and_size();
+    let signature_valid = tx.public_key.key_type == 0
+        && signed.signature.key_type == 0
+        && VerifyingKey::from_bytes(&tx.public_key.key_data).is_ok_and(|key| {
+            key.verify(
+                &tx_hash.0,
+                &Signature::from_bytes(&signed.signature.signature_data),
+            )
+            .is_ok()
+        });
+
+    let actions: Vec<ActionParams> = tx.actions.iter().map(action_params_of).collect();
+    let receivers_and_actions = [(tx.get_receiver_id(), actions.clone())];
+    let first_time_receiver = expected_value
+        .get("firstTimeReceiver")
+        .map(|flag| flag.as_bool());
+    let digest = compute_intent_digest_from_js_inputs(
+        &receivers_and_actions,
+        first_time_receiver.as_ref().map(std::slice::from_ref),
+    )?;
+
+    let transaction = UnsignedTransactionSummary {
+        signer_id: tx.get_signer_id(),
+        public_key: public_key_string(&tx.public_key),
+        nonce: tx.nonce.to_string(),
+        receiver_id: tx.get_receiver_id(),
+        block_hash: bs58::encode(tx.block_hash.0).into_string(),
+        actions,
+    };
+
+    let mut fields = Vec::new();
+    if let Some(signer_id) = expected.signer_id {
+        check(
+            &mut fields,
+            "signerId".to_string(),
+            json!(signer_id),
+            json!(transaction.signer_id),
+        );
+    }
+    if let Some(public_key) = expected.public_key {
+        // Either key encoding compares as NEAR format
+        let public_key = decode_ed25519_key(&public_key)
+            .map(|key| public_key_string(&PublicKey::from_ed25519_bytes(&key.to_bytes())))
+            .unwrap_or(public_key);
+        check(
+            &mut fields,
+            "publicKey".to_string(),
+            json!(public_key),
+            json!(transaction.public_key),
+        );
+    }
+    if let Some(nonce) = expected.nonce {
+        check(
+            &mut fields,
+            "nonce".to_string(),
+            json!(nonce),
+            json!(transaction.nonce),
+        );
+    }
+    if let Some(receiver_id) = expected.receiver_id {
+        check(
+            &mut fields,
+            "receiverId".to_string(),
+            json!(receiver_id),
+            json!(transaction.receiver_id),
+        );
+    }
+    if let Some(block_hash) = expected.block_hash {
+        check(
+            &mut fields,
+            "blockHash".to_string(),
+            json!(block_hash),
+            json!(transaction.block_hash),
+        );
+    }
+    if let Some(expected_actions) = expected.actions {
+        // Built and decoded again, so amounts and access keys compare in canonical form
+        let expected_actions: Vec<ActionParams> = build_actions_from_params(expected_actions)
+            .map_err(|e| format!("Invalid expected summary: {}", e))?
+            .iter()
+            .map(action_params_of)
+            .collect();
+        check_actions(&mut fields, &expected_actions, &transaction.actions);
+    }
+
+    let digest_matches = digest == expected_digest;
+    let summary_matches = fields.iter().all(|field| field.matches);
+    Ok(AuditVerificationResult {
+        valid: signature_valid && digest_matches && summary_matches,
+        signature_valid,
+        digest,
+        digest_matches,
+        summary_matches,
+        fields,
+        tx_hash_b58: bs58::encode(tx_hash.0).into_string(),
+        transaction,
+    })
+}
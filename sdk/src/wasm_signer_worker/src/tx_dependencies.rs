@@ -0,0 +1,123 @@
+// === TRANSACTION DEPENDENCIES ===
+// Cross-contract flows often need one transaction of a batch to land before the next makes
+// sense (storage_deposit, then ft_transfer_call). Callers annotate a transaction with
+// `dependsOn: [indexes]`, 0-based in the batch as sent, and SignTransactionsWithActions:
+//   - refuses the batch before the user is prompted with InvalidDependencyGraph when a
+//     transaction depends on itself, on a later transaction or on one outside the batch, or
+//     when the request also asks to `deduplicate` (which would shift the indexes);
+//   - shows each transaction's `dependsOn` in the confirmation (covered by the intent digest),
+//     which the TxTree renders as indented children;
+//   - with `broadcast`, stops broadcasting when a transaction that later transactions depend
+//     on fails on chain. The rest of the batch is still signed and returned, unbroadcast, as
+//     `withheldTransactions`. Nonces are consecutive, so broadcasting any later transaction
+//     first would invalidate the withheld ones: independent transactions after the failed one
+//     are withheld too, and the withheld signed transactions stay valid until their block hash
+//     expires.
+// Without annotations nothing changes: a transaction failing on chain does not stop the batch.
+
+use serde_json::Value;
+
+use crate::error::InvalidDependencyGraphError;
+use crate::handlers::handle_sign_transactions_with_actions::{
+    SignTransactionsWithActionsRequest, TransactionPayload,
+};
+
+/// Each transaction's `dependsOn`, for the confirmation (see SummaryPolicy)
+pub fn depends_on(txs: &[TransactionPayload]) -> Vec<Vec<u32>> {
+    match txs.iter().any(|tx| !tx.depends_on.is_empty()) {
+        true => txs.iter().map(|tx| tx.depends_on.clone()).collect(),
+        false => Vec::new(),
+    }
+}
+
+/// The first cycle found following `dependsOn` edges, in transaction order
+fn find_cycle(txs: &[TransactionPayload]) -> Option<Vec<u32>> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Visit {
+        New,
+        OnPath,
+        Done,
+    }
+
+    fn visit(
+        txs: &[TransactionPayload],
+        index: usize,
+        visits: &mut [Visit],
+        path: &mut Vec<u32>,
+    ) -> Option<Vec<u32>> {
+        visits[index] = Visit::OnPath;
+        path.push(index as u32);
+        for &next in &txs[index].depends_on {
+            let next = next as usize;
+            match visits.get(next) {
+                Some(Visit::OnPath) => {
+                    let start = path.iter().position(|&i| i as usize == next)?;
+                    return Some(path[start..].to_vec());
+                }
+                Some(Visit::New) => {
+                    if let Some(cycle) = visit(txs, next, visits, path) {
+                        return Some(cycle);
+                    }
+                }
+                // Done, or outside the batch (a forward reference)
+                _ => {}
+            }
+        }
+        path.pop();
+        visits[index] = Visit::Done;
+        None
+    }
+
+    let mut visits = vec![Visit::New; txs.len()];
+    (0..txs.len()).find_map(|index| match visits[index] {
+        Visit::New => visit(txs, index, &mut visits, &mut Vec::new()),
+        _ => None,
+    })
+}
+
+/// Checks the batch's `dependsOn` annotations: acyclic, each naming earlier transactions only
+pub fn check_dependencies(
+    request: &SignTransactionsWithActionsRequest,
+) -> Result<(), InvalidDependencyGraphError> {
+    let txs = &request.tx_signing_requests;
+    if txs.iter().all(|tx| tx.depends_on.is_empty()) {
+        return Ok(());
+    }
+    if request.deduplicate {
+        return Err(InvalidDependencyGraphError::WithDeduplicate);
+    }
+    if let Some(cycle) = find_cycle(txs) {
+        return Err(InvalidDependencyGraphError::Cycle { cycle });
+    }
+    for (index, tx) in txs.iter().enumerate() {
+        if let Some(&depends_on) = tx.depends_on.iter().find(|&&d| d as usize >= index) {
+            return Err(InvalidDependencyGraphError::ForwardReference {
+                index: index as u32,
+                depends_on,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Transactions depending on `index`, directly or through others, in order
+pub fn dependents(txs: &[TransactionPayload], index: usize) -> Vec<u32> {
+    let mut affected = vec![false; txs.len()];
+    if let Some(failed) = affected.get_mut(index) {
+        *failed = true;
+    }
+    // Dependencies come first in a checked batch, so one pass in order reaches every dependent
+    let mut downstream = Vec::new();
+    for (i, tx) in txs.iter().enumerate().skip(index + 1) {
+        if tx.depends_on.iter().any(|&d| affected.get(d as usize) == Some(&true)) {
+            affected[i] = true;
+            downstream.push(i as u32);
+        }
+    }
+    downstream
+}
+
+/// Whether a broadcast_tx_commit outcome failed on chain (`status.Failure`)
+pub fn outcome_failed(outcome: &Value) -> bool {
+    outcome.pointer("/status/Failure").is_some()
+}
@@ -0,0 +1,207 @@
+// === DIFFERENTIAL CONFIRMATION ===
+// Dapps often re-submit an almost identical transaction after a small change (a slippage
+// bump), and users confirm without spotting the difference. Each FunctionCall in a new
+// request is compared with the most recent confirmed call to the same receiver and method,
+// and the changes (argument paths, deposit, gas) are attached to the confirmation payload
+// as `diffFromPrevious`. The diff is informational only: it is not part of the intent
+// digest, which keeps covering the full new payload. The confirmed calls travel in the sealed
+// worker state record (see worker_state.rs), so a re-request is diffed in the next worker.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::actions::ActionParams;
+use crate::config::{MAX_DIFF_ARGS_BYTES, MAX_DIFF_ARG_PATHS};
+use crate::state::{self, ConfirmedIntent};
+use crate::types::AccountId;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ValueChange {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionDiff {
+    /// Position of the action in the new request
+    pub tx_index: usize,
+    pub action_index: usize,
+    pub receiver_id: String,
+    pub method_name: String,
+    /// Digest of the confirmed batch the action is compared against
+    pub previous_intent_digest: String,
+    pub args_changed: bool,
+    /// JSON pointers of the argument values that were added, removed or changed; None when
+    /// the args are too large or not JSON, or differ in too many places to list
+    pub changed_arg_paths: Option<Vec<String>>,
+    pub deposit: Option<ValueChange>,
+    pub gas: Option<ValueChange>,
+}
+
+/// Borrowed fields of a FunctionCall action
+struct FunctionCall<'a> {
+    method_name: &'a str,
+    args: &'a str,
+    gas: &'a str,
+    deposit: &'a str,
+}
+
+fn function_call(action: &ActionParams) -> Option<FunctionCall<'_>> {
+    match action {
+        ActionParams::FunctionCall {
+            method_name,
+            args,
+            gas,
+            deposit,
+        } => Some(FunctionCall {
+            method_name,
+            args,
+            gas,
+            deposit,
+        }),
+        _ => None,
+    }
+}
+
+/// Remembers a confirmed batch (its FunctionCall actions) for later diffs
+pub fn record_confirmed_intent(
+    account: &AccountId,
+    intent_digest: &str,
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+) {
+    let receivers_and_actions: Vec<(String, Vec<ActionParams>)> = receivers_and_actions
+        .iter()
+        .map(|(receiver_id, actions)| {
+            let calls: Vec<ActionParams> = actions
+                .iter()
+                .filter(|a| function_call(a).is_some())
+                .cloned()
+                .collect();
+            (receiver_id.clone(), calls)
+        })
+        .filter(|(_, calls)| !calls.is_empty())
+        .collect();
+    if receivers_and_actions.is_empty() {
+        return;
+    }
+    state::record_confirmed_intent(
+        account,
+        ConfirmedIntent {
+            intent_digest: intent_digest.to_string(),
+            receivers_and_actions,
+        },
+    );
+}
+
+/// Diffs each FunctionCall of the new request against the most recent confirmed call with
+/// the same receiver and method (`recent` is most recent first)
+pub fn diff_from_previous(
+    receivers_and_actions: &[(String, Vec<ActionParams>)],
+    recent: &[ConfirmedIntent],
+) -> Vec<ActionDiff> {
+    let mut diffs = Vec::new();
+    for (tx_index, (receiver_id, actions)) in receivers_and_actions.iter().enumerate() {
+        for (action_index, action) in actions.iter().enumerate() {
+            let Some(call) = function_call(action) else {
+                continue;
+            };
+            let Some((previous, intent_digest)) =
+                previous_call(recent, receiver_id, call.method_name)
+            else {
+                continue;
+            };
+            diffs.push(ActionDiff {
+                tx_index,
+                action_index,
+                receiver_id: receiver_id.clone(),
+                method_name: call.method_name.to_string(),
+                previous_intent_digest: intent_digest.to_string(),
+                args_changed: previous.args != call.args,
+                changed_arg_paths: if previous.args == call.args {
+                    Some(Vec::new())
+                } else {
+                    changed_arg_paths(previous.args, call.args)
+                },
+                deposit: value_change(previous.deposit, call.deposit),
+                gas: value_change(previous.gas, call.gas),
+            });
+        }
+    }
+    diffs
+}
+
+fn previous_call<'a>(
+    recent: &'a [ConfirmedIntent],
+    receiver_id: &str,
+    method_name: &str,
+) -> Option<(FunctionCall<'a>, &'a str)> {
+    recent.iter().find_map(|intent| {
+        intent
+            .receivers_and_actions
+            .iter()
+            .filter(|(r, _)| r == receiver_id)
+            .flat_map(|(_, actions)| actions.iter().filter_map(function_call))
+            .find(|call| call.method_name == method_name)
+            .map(|call| (call, intent.intent_digest.as_str()))
+    })
+}
+
+fn value_change(from: &str, to: &str) -> Option<ValueChange> {
+    (from != to).then(|| ValueChange {
+        from: from.to_string(),
+        to: to.to_string(),
+    })
+}
+
+/// Leaf paths that differ between two JSON args strings, or None to fall back to "args changed"
+fn changed_arg_paths(previous: &str, current: &str) -> Option<Vec<String>> {
+    if previous.len() > MAX_DIFF_ARGS_BYTES || current.len() > MAX_DIFF_ARGS_BYTES {
+        return None;
+    }
+    let previous: Value = serde_json::from_str(previous).ok()?;
+    let current: Value = serde_json::from_str(current).ok()?;
+    let mut paths = Vec::new();
+    diff_values(&previous, &current, "", &mut paths).ok()?;
+    Some(paths)
+}
+
+/// Collects differing leaf paths; Err once more than MAX_DIFF_ARG_PATHS were found
+fn diff_values(a: &Value, b: &Value, path: &str, paths: &mut Vec<String>) -> Result<(), ()> {
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child_path = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+                match (a.get(key), b.get(key)) {
+                    (Some(a), Some(b)) => diff_values(a, b, &child_path, paths)?,
+                    _ => push_path(paths, child_path)?,
+                }
+            }
+            Ok(())
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for i in 0..a.len().max(b.len()) {
+                let child_path = format!("{}/{}", path, i);
+                match (a.get(i), b.get(i)) {
+                    (Some(a), Some(b)) => diff_values(a, b, &child_path, paths)?,
+                    _ => push_path(paths, child_path)?,
+                }
+            }
+            Ok(())
+        }
+        _ if a == b => Ok(()),
+        _ => push_path(paths, path.to_string()),
+    }
+}
+
+fn push_path(paths: &mut Vec<String>, path: String) -> Result<(), ()> {
+    if paths.len() >= MAX_DIFF_ARG_PATHS {
+        return Err(());
+    }
+    paths.push(path);
+    Ok(())
+}
@@ -1,8 +1,22 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
 
+use crate::config::DEFAULT_CONFIRMATION_TIMEOUT_MS;
+use crate::confirmation_speech::SpeechLocale;
+use crate::credential_options::PUB_KEY_CRED_ALGORITHMS;
+use crate::elevation::ElevatedOperation;
+use crate::encoders::base64_url_decode;
+use crate::error::ConfigError;
 use crate::handlers::handle_derive_near_keypair_and_encrypt::DeriveNearKeypairAndEncryptResult;
-use crate::types::{SerializedCredential, SerializedRegistrationCredential, VrfChallenge};
+use crate::types::wasm_to_json::to_js_value;
+use crate::types::{AccountId, VrfChallenge};
+
+/// `toJSON()` for the classes below: the plain-object form the worker parses, so class
+/// instances and plain objects can be posted interchangeably
+fn to_plain_object<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    to_js_value(value).map_err(|e| JsValue::from_str(&e.to_string()))
+}
 
 // ******************************************************************************
 // *                                                                            *
@@ -44,6 +58,84 @@ pub struct OriginPolicyInput {
     pub multiple: Option<Vec<String>>,
 }
 
+#[wasm_bindgen]
+impl OriginPolicyInput {
+    /// Empty policy; set exactly one of the three options with the `with*` builders
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> OriginPolicyInput {
+        OriginPolicyInput {
+            single: None,
+            all_subdomains: None,
+            multiple: None,
+        }
+    }
+
+    /// Only the rpId origin itself
+    #[wasm_bindgen(js_name = "withSingle")]
+    pub fn with_single(mut self, single: bool) -> OriginPolicyInput {
+        self.single = Some(single);
+        self
+    }
+
+    /// The rpId and any of its subdomains
+    #[wasm_bindgen(js_name = "withAllSubdomains")]
+    pub fn with_all_subdomains(mut self, all_subdomains: bool) -> OriginPolicyInput {
+        self.all_subdomains = Some(all_subdomains);
+        self
+    }
+
+    /// The rpId and the listed origins
+    #[wasm_bindgen(js_name = "withMultiple")]
+    pub fn with_multiple(mut self, origins: Vec<String>) -> OriginPolicyInput {
+        self.multiple = Some(origins);
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let set_count = [
+            self.single.is_some(),
+            self.all_subdomains.is_some(),
+            self.multiple.is_some(),
+        ]
+        .iter()
+        .filter(|set| **set)
+        .count();
+        if set_count != 1 {
+            return Err(ConfigError::InvalidCombination {
+                field: "originPolicy",
+                reason: format!(
+                    "exactly one of single, allSubdomains and multiple must be set ({} are)",
+                    set_count
+                ),
+            });
+        }
+        match &self.multiple {
+            Some(origins) if origins.is_empty() => Err(ConfigError::InvalidValue {
+                field: "originPolicy.multiple",
+                reason: "must list at least one origin".to_string(),
+            }),
+            Some(origins) if origins.iter().any(|origin| origin.trim().is_empty()) => {
+                Err(ConfigError::InvalidValue {
+                    field: "originPolicy.multiple",
+                    reason: "origins must not be empty".to_string(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    #[wasm_bindgen(js_name = "toJSON")]
+    pub fn to_json(&self) -> Result<JsValue, JsValue> {
+        to_plain_object(self)
+    }
+}
+
+impl Default for OriginPolicyInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Options for configuring WebAuthn authenticator behavior during registration
 #[wasm_bindgen]
 #[wasm_bindgen]
@@ -53,6 +145,16 @@ pub struct AuthenticatorOptions {
     pub user_verification: Option<UserVerificationPolicy>,
     #[wasm_bindgen(getter_with_clone, js_name = "originPolicy")]
     pub origin_policy: Option<OriginPolicyInput>,
+    /// COSE algorithm identifiers to offer at registration, in preference order; a subset of
+    /// PUB_KEY_CRED_ALGORITHMS (all of them when unset). Worker-side only: not sent to the
+    /// contract.
+    #[wasm_bindgen(getter_with_clone, js_name = "allowedAlgorithms")]
+    #[serde(
+        default,
+        alias = "allowedAlgorithms",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub allowed_algorithms: Option<Vec<i32>>,
 }
 
 impl Default for AuthenticatorOptions {
@@ -64,6 +166,79 @@ impl Default for AuthenticatorOptions {
                 all_subdomains: Some(true),
                 multiple: None,
             }),
+            allowed_algorithms: None,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl AuthenticatorOptions {
+    /// Contract defaults: preferred user verification, rpId and all its subdomains
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> AuthenticatorOptions {
+        AuthenticatorOptions::default()
+    }
+
+    #[wasm_bindgen(js_name = "withUserVerification")]
+    pub fn with_user_verification(
+        mut self,
+        user_verification: UserVerificationPolicy,
+    ) -> AuthenticatorOptions {
+        self.user_verification = Some(user_verification);
+        self
+    }
+
+    #[wasm_bindgen(js_name = "withOriginPolicy")]
+    pub fn with_origin_policy(mut self, origin_policy: OriginPolicyInput) -> AuthenticatorOptions {
+        self.origin_policy = Some(origin_policy);
+        self
+    }
+
+    #[wasm_bindgen(js_name = "withAllowedAlgorithms")]
+    pub fn with_allowed_algorithms(mut self, allowed_algorithms: Vec<i32>) -> AuthenticatorOptions {
+        self.allowed_algorithms = Some(allowed_algorithms);
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if let Some(allowed) = &self.allowed_algorithms {
+            let invalid = |reason: String| ConfigError::InvalidValue {
+                field: "allowedAlgorithms",
+                reason,
+            };
+            if allowed.is_empty() {
+                return Err(invalid("must not be empty".to_string()));
+            }
+            for (i, alg) in allowed.iter().enumerate() {
+                if !PUB_KEY_CRED_ALGORITHMS.contains(alg) {
+                    return Err(invalid(format!(
+                        "{} is not a supported COSE algorithm (supported: {:?})",
+                        alg, PUB_KEY_CRED_ALGORITHMS
+                    )));
+                }
+                if allowed[..i].contains(alg) {
+                    return Err(invalid(format!("{} is listed twice", alg)));
+                }
+            }
+        }
+        match &self.origin_policy {
+            Some(origin_policy) => origin_policy.validate(),
+            None => Ok(()),
+        }
+    }
+
+    #[wasm_bindgen(js_name = "toJSON")]
+    pub fn to_json(&self) -> Result<JsValue, JsValue> {
+        to_plain_object(self)
+    }
+}
+
+impl AuthenticatorOptions {
+    /// The options as sent to the contract, without worker-side fields
+    pub fn for_contract(self) -> AuthenticatorOptions {
+        AuthenticatorOptions {
+            allowed_algorithms: None,
+            ..self
         }
     }
 }
@@ -90,6 +265,124 @@ pub struct RpcCallPayload {
     pub near_account_id: String,
 }
 
+#[wasm_bindgen]
+impl RpcCallPayload {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        contract_id: String,
+        near_rpc_url: String,
+        near_account_id: String,
+    ) -> RpcCallPayload {
+        RpcCallPayload {
+            contract_id,
+            near_rpc_url,
+            near_account_id,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.contract_id.is_empty() {
+            return Err(ConfigError::InvalidValue {
+                field: "contractId",
+                reason: "must not be empty".to_string(),
+            });
+        }
+        if self.near_account_id.is_empty() {
+            return Err(ConfigError::InvalidValue {
+                field: "nearAccountId",
+                reason: "must not be empty".to_string(),
+            });
+        }
+        if !self.near_rpc_url.starts_with("https://") && !self.near_rpc_url.starts_with("http://") {
+            return Err(ConfigError::InvalidValue {
+                field: "nearRpcUrl",
+                reason: format!("not an http(s) URL: {}", self.near_rpc_url),
+            });
+        }
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = "toJSON")]
+    pub fn to_json(&self) -> Result<JsValue, JsValue> {
+        to_plain_object(self)
+    }
+}
+
+/// Per-request RPC endpoints, taking precedence over `RpcCallPayload.nearRpcUrl`.
+/// Each must be on one of `WorkerPolicy.allowedRpcOrigins` (see rpc_endpoints.rs).
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcOverrides {
+    /// Endpoint for contract verification of the WebAuthn assertion
+    #[wasm_bindgen(getter_with_clone)]
+    #[serde(default)]
+    pub verification: Option<String>,
+    /// Endpoint the main thread broadcasts the signed transactions to
+    #[wasm_bindgen(getter_with_clone)]
+    #[serde(default)]
+    pub broadcast: Option<String>,
+}
+
+/// Relayer sponsoring account creation (see relayer.rs). `url` must be on one of
+/// `WorkerPolicy.allowedRpcOrigins`, like RPC overrides.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayerPolicy {
+    /// Base URL; accounts are created at `{url}/create_account_and_register_user`
+    #[wasm_bindgen(getter_with_clone)]
+    pub url: String,
+    /// Header carrying the request's `authToken` (e.g. "Authorization"); without it the
+    /// token is not sent
+    #[wasm_bindgen(getter_with_clone, js_name = "authHeaderName")]
+    #[serde(default)]
+    pub auth_header_name: Option<String>,
+    /// Schema version the relayer's responses must declare (defaults to
+    /// RELAYER_RESPONSE_SCHEMA_VERSION)
+    #[wasm_bindgen(js_name = "responseSchemaVersion")]
+    #[serde(default)]
+    pub response_schema_version: Option<u32>,
+}
+
+/// Opt-in remote telemetry: per-handler counters and latency histograms, POSTed as CBOR
+/// (see telemetry.rs). Reports never carry account IDs, receivers or amounts.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryPolicy {
+    /// URL reports are POSTed to; must be on allowedRpcOrigins
+    #[wasm_bindgen(getter_with_clone)]
+    pub endpoint: String,
+    /// Fraction of requests (0 to 1) counted in reports (defaults to 1). The in-memory
+    /// snapshot counts every request.
+    #[wasm_bindgen(js_name = "samplingRate")]
+    #[serde(default)]
+    pub sampling_rate: Option<f64>,
+    /// Minutes between reports (defaults to DEFAULT_TELEMETRY_FLUSH_INTERVAL_MINUTES, at
+    /// least MIN_TELEMETRY_FLUSH_INTERVAL_MINUTES)
+    #[wasm_bindgen(js_name = "flushIntervalMinutes")]
+    #[serde(default)]
+    pub flush_interval_minutes: Option<u32>,
+}
+
+/// Largest message frames the worker parses, in bytes (see message_limits.rs). Frames above
+/// the limit of their request type are refused with MessageTooLarge before they are parsed.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageSizeLimits {
+    /// Request types without a limit of their own (defaults to DEFAULT_MAX_MESSAGE_BYTES)
+    #[serde(default)]
+    pub default_bytes: Option<u32>,
+    /// DeployLargeContract (defaults to DEFAULT_MAX_DEPLOY_MESSAGE_BYTES)
+    #[serde(default)]
+    pub deploy_bytes: Option<u32>,
+    /// Limits keyed by request type name (e.g. "SIGN_TRANSACTIONS_WITH_ACTIONS"), replacing
+    /// the two above for that type
+    #[serde(default)]
+    pub by_type: HashMap<String, u32>,
+}
+
 // === TRANSACTION CONTEXT TYPE ===
 
 /// Transaction context containing NEAR blockchain data
@@ -106,6 +399,67 @@ pub struct TransactionContext {
     pub tx_block_height: String,
     #[wasm_bindgen(getter_with_clone, js_name = "txBlockHash")]
     pub tx_block_hash: String,
+    /// Remaining allowance (yoctoNEAR) of the access key, when it is a function-call key with
+    /// a finite allowance
+    #[wasm_bindgen(getter_with_clone, js_name = "accessKeyAllowance")]
+    #[serde(default)]
+    pub access_key_allowance: Option<String>,
+    /// Gas price (yoctoNEAR per gas unit) from the block header, for allowance estimates
+    #[wasm_bindgen(getter_with_clone, js_name = "gasPrice")]
+    #[serde(default)]
+    pub gas_price: Option<String>,
+}
+
+#[wasm_bindgen]
+impl TransactionContext {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        near_public_key_str: String,
+        next_nonce: String,
+        tx_block_height: String,
+        tx_block_hash: String,
+    ) -> TransactionContext {
+        TransactionContext {
+            near_public_key_str,
+            next_nonce,
+            tx_block_height,
+            tx_block_hash,
+            access_key_allowance: None,
+            gas_price: None,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !self.near_public_key_str.starts_with("ed25519:") {
+            return Err(ConfigError::InvalidValue {
+                field: "nearPublicKeyStr",
+                reason: "must be an ed25519:<base58> public key".to_string(),
+            });
+        }
+        for (field, value) in [
+            ("nextNonce", &self.next_nonce),
+            ("txBlockHeight", &self.tx_block_height),
+        ] {
+            if value.parse::<u64>().is_err() {
+                return Err(ConfigError::InvalidValue {
+                    field,
+                    reason: format!("not an unsigned integer: {}", value),
+                });
+            }
+        }
+        match bs58::decode(&self.tx_block_hash).into_vec() {
+            Ok(hash) if hash.len() == 32 => Ok(()),
+            _ => Err(ConfigError::InvalidValue {
+                field: "txBlockHash",
+                reason: "must be a base58 32-byte block hash".to_string(),
+            }),
+        }
+    }
+
+    #[wasm_bindgen(js_name = "toJSON")]
+    pub fn to_json(&self) -> Result<JsValue, JsValue> {
+        to_plain_object(self)
+    }
 }
 
 // === VERIFICATION TYPE (deprecated - use RpcCallPayload) ===
@@ -176,6 +530,121 @@ pub struct ConfirmationConfig {
     /// UI theme preference (dark/light)
     #[wasm_bindgen(getter_with_clone)]
     pub theme: Option<String>,
+
+    /// How long the confirmation stays valid once requested (defaults to
+    /// DEFAULT_CONFIRMATION_TIMEOUT_MS); confirming later fails with ConfirmationExpired
+    #[wasm_bindgen(js_name = "confirmationTimeoutMs")]
+    #[serde(default)]
+    pub confirmation_timeout_ms: Option<u32>,
+
+    /// BCP 47 locale of the summary blocks' ariaLabel and summarySpeech ("en" or "es"; region
+    /// subtags are ignored). Defaults to English.
+    #[wasm_bindgen(getter_with_clone)]
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+#[wasm_bindgen]
+impl ConfirmationConfig {
+    /// Config with no delay, theme or timeout set (the worker applies its defaults)
+    #[wasm_bindgen(constructor)]
+    pub fn new(ui_mode: ConfirmationUIMode, behavior: ConfirmationBehavior) -> ConfirmationConfig {
+        ConfirmationConfig {
+            ui_mode,
+            behavior,
+            auto_proceed_delay: None,
+            theme: None,
+            confirmation_timeout_ms: None,
+            locale: None,
+        }
+    }
+
+    #[wasm_bindgen(js_name = "withAutoProceedDelay")]
+    pub fn with_auto_proceed_delay(mut self, auto_proceed_delay: u32) -> ConfirmationConfig {
+        self.auto_proceed_delay = Some(auto_proceed_delay);
+        self
+    }
+
+    #[wasm_bindgen(js_name = "withTheme")]
+    pub fn with_theme(mut self, theme: String) -> ConfirmationConfig {
+        self.theme = Some(theme);
+        self
+    }
+
+    #[wasm_bindgen(js_name = "withConfirmationTimeoutMs")]
+    pub fn with_confirmation_timeout_ms(
+        mut self,
+        confirmation_timeout_ms: u32,
+    ) -> ConfirmationConfig {
+        self.confirmation_timeout_ms = Some(confirmation_timeout_ms);
+        self
+    }
+
+    #[wasm_bindgen(js_name = "withLocale")]
+    pub fn with_locale(mut self, locale: String) -> ConfirmationConfig {
+        self.locale = Some(locale);
+        self
+    }
+
+    /// Stricter than the worker's normalization, which fills in or clamps these values:
+    /// catches configs that would not behave as written
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if let Some(theme) = self.theme.as_deref() {
+            if theme != "dark" && theme != "light" {
+                return Err(ConfigError::InvalidValue {
+                    field: "theme",
+                    reason: format!("must be 'dark' or 'light', got '{}'", theme),
+                });
+            }
+        }
+        if let Some(locale) = self.locale.as_deref() {
+            if SpeechLocale::parse(locale).is_none() {
+                return Err(ConfigError::InvalidValue {
+                    field: "locale",
+                    reason: format!(
+                        "'{}' is not supported, expected one of {}",
+                        locale,
+                        SpeechLocale::SUPPORTED.join(", ")
+                    ),
+                });
+            }
+        }
+        if self.confirmation_timeout_ms == Some(0) {
+            return Err(ConfigError::InvalidValue {
+                field: "confirmationTimeoutMs",
+                reason: "must be greater than 0".to_string(),
+            });
+        }
+        let auto_proceeds = self.ui_mode != ConfirmationUIMode::Skip
+            && self.behavior == ConfirmationBehavior::AutoProceed;
+        if !auto_proceeds {
+            return Ok(());
+        }
+        let Some(delay) = self.auto_proceed_delay else {
+            return Err(ConfigError::InvalidCombination {
+                field: "autoProceedDelay",
+                reason: "behavior 'autoProceed' needs an autoProceedDelay".to_string(),
+            });
+        };
+        let timeout_ms = self
+            .confirmation_timeout_ms
+            .unwrap_or(DEFAULT_CONFIRMATION_TIMEOUT_MS);
+        if delay > timeout_ms {
+            return Err(ConfigError::InvalidCombination {
+                field: "autoProceedDelay",
+                reason: format!(
+                    "{}ms is longer than the {}ms confirmation timeout",
+                    delay, timeout_ms
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = "toJSON")]
+    pub fn to_json(&self) -> Result<JsValue, JsValue> {
+        to_plain_object(self)
+    }
 }
 
 impl Default for ConfirmationConfig {
@@ -185,7 +654,252 @@ impl Default for ConfirmationConfig {
             behavior: ConfirmationBehavior::RequireClick,
             auto_proceed_delay: Some(2000),
             theme: Some("dark".to_string()),
+            confirmation_timeout_ms: None,
+            locale: None,
+        }
+    }
+}
+
+// === WORKER POLICY TYPES ===
+
+/// Integrator policy applied by the worker to signing requests
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkerPolicy {
+    /// Ask the main thread's pre-sign hook to allow each request before signing
+    #[wasm_bindgen(js_name = "preSignHook")]
+    #[serde(default)]
+    pub pre_sign_hook: bool,
+
+    /// Notify the main thread's post-sign hook of every signed transaction
+    #[wasm_bindgen(js_name = "postSignHook")]
+    #[serde(default)]
+    pub post_sign_hook: bool,
+
+    /// How long to wait for the pre-sign hook (defaults to DEFAULT_SIGN_HOOK_TIMEOUT_MS)
+    #[wasm_bindgen(js_name = "hookTimeoutMs")]
+    #[serde(default)]
+    pub hook_timeout_ms: Option<u32>,
+
+    /// Average block time used for challenge expiry estimates on non-standard networks
+    /// (defaults to the mainnet/testnet value inferred from the RPC URL)
+    #[wasm_bindgen(js_name = "averageBlockTimeMs")]
+    #[serde(default)]
+    pub average_block_time_ms: Option<u32>,
+
+    /// NearBlocks-compatible indexer API base URL, used to look up the account's recent
+    /// receivers. Without it, receivers are never flagged as first-time ("unknown").
+    #[wasm_bindgen(getter_with_clone, js_name = "indexerUrl")]
+    #[serde(default)]
+    pub indexer_url: Option<String>,
+
+    /// Refuse requests whose security-relevant payloads (rpcCall, decryption,
+    /// confirmationConfig, transactions and this policy) contain unknown fields
+    #[wasm_bindgen(js_name = "strictParsing")]
+    #[serde(default)]
+    pub strict_parsing: bool,
+
+    /// Origins (`scheme://host[:port]`) that `rpcOverrides` endpoints may point at.
+    /// Empty: overrides are refused.
+    #[wasm_bindgen(getter_with_clone, js_name = "allowedRpcOrigins")]
+    #[serde(default)]
+    pub allowed_rpc_origins: Vec<String>,
+
+    /// Longest lifetime a signing intent may have, checked when it is created and again when
+    /// it is executed (defaults to DEFAULT_MAX_SIGNING_INTENT_TTL_MS, capped at
+    /// MAX_SIGNING_INTENT_TTL_MS)
+    #[wasm_bindgen(js_name = "maxSigningIntentTtlMs")]
+    #[serde(default)]
+    pub max_signing_intent_ttl_ms: Option<u32>,
+
+    /// Relayer used by SubmitToRelayer
+    #[wasm_bindgen(getter_with_clone)]
+    #[serde(default)]
+    pub relayer: Option<RelayerPolicy>,
+
+    /// Total deposit per transaction (yoctoNEAR) above which the confirmation warns
+    /// (defaults to DEFAULT_DEPOSIT_ESCALATION_YOCTO)
+    #[wasm_bindgen(getter_with_clone, js_name = "depositEscalationYocto")]
+    #[serde(default)]
+    pub deposit_escalation_yocto: Option<String>,
+
+    /// Allowance (yoctoNEAR) a function-call key may have left after a confirmation's
+    /// estimated cost before the confirmation warns (defaults to
+    /// DEFAULT_LOW_ALLOWANCE_WARNING_YOCTO)
+    #[wasm_bindgen(getter_with_clone, js_name = "lowAllowanceWarningYocto")]
+    #[serde(default)]
+    pub low_allowance_warning_yocto: Option<String>,
+
+    /// Key-wrapping schemes ("largeBlob", "passphrase") a registration may fall back to when
+    /// the authenticator returns no PRF output. Empty: such registrations fail with
+    /// PrfUnsupportedByAuthenticator.
+    #[wasm_bindgen(getter_with_clone, js_name = "prfFallbackSchemes")]
+    #[serde(default)]
+    pub prf_fallback_schemes: Vec<String>,
+    /// Remote telemetry sink; without it nothing leaves the worker (GetTelemetrySnapshot
+    /// still reports the in-memory counters)
+    #[wasm_bindgen(getter_with_clone)]
+    #[serde(default)]
+    pub telemetry: Option<TelemetryPolicy>,
+
+    /// Policy fields replaced for the requests of one account, keyed by NEAR account ID
+    /// (see `WorkerPolicy::for_account`)
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub account_overrides: HashMap<AccountId, AccountPolicyOverride>,
+
+    /// JSON Schemas the args of FunctionCalls to a contract method must satisfy, checked
+    /// before the user is prompted (see args_schema.rs)
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub args_schemas: Vec<ArgsSchemaEntry>,
+
+    /// Refuse secrets (PRF outputs, private keys, passphrases) sent in plaintext rather than
+    /// sealed to the session key, from this request until WipeAllState (see sealed_secrets.rs)
+    #[wasm_bindgen(js_name = "requireEncryptedSecrets")]
+    #[serde(default)]
+    pub require_encrypted_secrets: bool,
+
+    /// Largest frames the worker parses per request type, from the next frame on (see
+    /// message_limits.rs)
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub message_size_limits: Option<MessageSizeLimits>,
+
+    /// Fail requests that take a deprecated path with DeprecatedUsage, instead of reporting it
+    /// in the response's `warnings` (see deprecations.rs)
+    #[wasm_bindgen(js_name = "failOnDeprecated")]
+    #[serde(default)]
+    pub fail_on_deprecated: bool,
+
+    /// Refuse batches that add a full access key with FullAccessAddKeyBlocked, before the user
+    /// is prompted (see action_firewall.rs)
+    #[wasm_bindgen(js_name = "blockFullAccessAddKey")]
+    #[serde(default)]
+    pub block_full_access_add_key: bool,
+
+    /// Compact JSON bytes of a FunctionCall argument shown in confirmations before it is
+    /// truncated (defaults to DEFAULT_MAX_ARG_PREVIEW_BYTES; see render_budget.rs)
+    #[wasm_bindgen(js_name = "maxArgPreviewBytes")]
+    #[serde(default)]
+    pub max_arg_preview_bytes: Option<u32>,
+
+    /// Summary blocks shown in confirmations before the remaining actions are rolled up
+    /// (defaults to DEFAULT_MAX_SUMMARY_BLOCKS; see render_budget.rs)
+    #[wasm_bindgen(js_name = "maxSummaryBlocks")]
+    #[serde(default)]
+    pub max_summary_blocks: Option<u32>,
+
+    /// Operations that need a fresh passkey assertion, on top of the default ones (see
+    /// elevation.rs)
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub elevated_operations: Vec<ElevatedOperation>,
+
+    /// How recent an elevated operation's passkey assertion must be (defaults to
+    /// DEFAULT_ELEVATION_MAX_AGE_MS)
+    #[wasm_bindgen(js_name = "elevationMaxAgeMs")]
+    #[serde(default)]
+    pub elevation_max_age_ms: Option<u32>,
+
+    /// How long a session of key-using requests lasts before a fresh passkey assertion must
+    /// renew it; unlimited when unset. Applies from the next request on, until a later policy
+    /// sets another value (see session_duration.rs).
+    #[wasm_bindgen(js_name = "maxSessionDurationMs")]
+    #[serde(default)]
+    pub max_session_duration_ms: Option<u32>,
+
+    /// Distinct passkeys that must approve a batch before it is signed: 1 (default) or 2 (see
+    /// dual_control.rs)
+    #[wasm_bindgen(js_name = "requiredApprovals")]
+    #[serde(default)]
+    pub required_approvals: Option<u8>,
+
+    /// Origin of the wallet iframe that renders confirmations; decisions must carry a render
+    /// attestation from it. Read at Initialize only (see confirmation_origin.rs).
+    #[wasm_bindgen(getter_with_clone, js_name = "trustedConfirmationOrigin")]
+    #[serde(default)]
+    pub trusted_confirmation_origin: Option<String>,
+
+    /// Origins (`https://host[:port]`) BuildRedirectPayload may redirect to. Empty: redirect
+    /// payloads are refused (see redirect_payload.rs).
+    #[wasm_bindgen(getter_with_clone, js_name = "allowedRedirectOrigins")]
+    #[serde(default)]
+    pub allowed_redirect_origins: Vec<String>,
+}
+
+/// A JSON Schema (draft-07 subset) for the args of one contract method
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArgsSchemaEntry {
+    pub contract_id: String,
+    pub method_name: String,
+    pub schema: serde_json::Value,
+}
+
+/// Fields of a WorkerPolicy that an account may set differently; unset fields keep the
+/// global policy's value
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountPolicyOverride {
+    #[serde(default)]
+    pub pre_sign_hook: Option<bool>,
+    #[serde(default)]
+    pub post_sign_hook: Option<bool>,
+    #[serde(default)]
+    pub hook_timeout_ms: Option<u32>,
+    #[serde(default)]
+    pub allowed_rpc_origins: Option<Vec<String>>,
+    #[serde(default)]
+    pub max_signing_intent_ttl_ms: Option<u32>,
+    #[serde(default)]
+    pub deposit_escalation_yocto: Option<String>,
+    #[serde(default)]
+    pub low_allowance_warning_yocto: Option<String>,
+    #[serde(default)]
+    pub required_approvals: Option<u8>,
+    #[serde(default)]
+    pub allowed_redirect_origins: Option<Vec<String>>,
+}
+
+impl WorkerPolicy {
+    /// The policy applied to `account`'s requests: its override layered over this policy.
+    /// The result carries no overrides, so resolving it again for another account is a no-op.
+    pub fn for_account(&self, account: &AccountId) -> WorkerPolicy {
+        let mut policy = WorkerPolicy {
+            account_overrides: HashMap::new(),
+            ..self.clone()
+        };
+        let Some(o) = self.account_overrides.get(account) else {
+            return policy;
+        };
+        if let Some(pre_sign_hook) = o.pre_sign_hook {
+            policy.pre_sign_hook = pre_sign_hook;
+        }
+        if let Some(post_sign_hook) = o.post_sign_hook {
+            policy.post_sign_hook = post_sign_hook;
+        }
+        if let Some(allowed_rpc_origins) = &o.allowed_rpc_origins {
+            policy.allowed_rpc_origins = allowed_rpc_origins.clone();
         }
+        if let Some(allowed_redirect_origins) = &o.allowed_redirect_origins {
+            policy.allowed_redirect_origins = allowed_redirect_origins.clone();
+        }
+        policy.hook_timeout_ms = o.hook_timeout_ms.or(policy.hook_timeout_ms);
+        policy.max_signing_intent_ttl_ms = o
+            .max_signing_intent_ttl_ms
+            .or(policy.max_signing_intent_ttl_ms);
+        policy.deposit_escalation_yocto = o
+            .deposit_escalation_yocto
+            .clone()
+            .or(policy.deposit_escalation_yocto);
+        policy.low_allowance_warning_yocto = o
+            .low_allowance_warning_yocto
+            .clone()
+            .or(policy.low_allowance_warning_yocto);
+        policy.required_approvals = o.required_approvals.or(policy.required_approvals);
+        policy
     }
 }
 
@@ -194,13 +908,28 @@ impl Default for ConfirmationConfig {
 /// Decryption payload (consolidated for deserialization and WASM binding)
 /// Note: chacha20_prf_output is collected during user confirmation flow
 #[wasm_bindgen]
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DecryptionPayload {
     #[wasm_bindgen(getter_with_clone, js_name = "encryptedPrivateKeyData")]
     pub encrypted_private_key_data: String,
     #[wasm_bindgen(getter_with_clone, js_name = "encryptedPrivateKeyIv")]
     pub encrypted_private_key_iv: String,
+    /// Other key blobs the worker holds for the account (e.g. a staking key), selected by the
+    /// request's signingPublicKey (see key_selection.rs)
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub candidates: Vec<KeyBlobCandidate>,
+}
+
+/// An encrypted key blob tagged with the public key of the key it holds
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyBlobCandidate {
+    /// "ed25519:<base58>"
+    pub public_key: String,
+    pub encrypted_private_key_data: String,
+    pub encrypted_private_key_iv: String,
 }
 
 #[wasm_bindgen]
@@ -213,8 +942,44 @@ impl DecryptionPayload {
         DecryptionPayload {
             encrypted_private_key_data,
             encrypted_private_key_iv,
+            candidates: Vec::new(),
         }
     }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let candidates = self.candidates.iter().flat_map(|candidate| {
+            [
+                (
+                    "candidates.encryptedPrivateKeyData",
+                    &candidate.encrypted_private_key_data,
+                ),
+                (
+                    "candidates.encryptedPrivateKeyIv",
+                    &candidate.encrypted_private_key_iv,
+                ),
+            ]
+        });
+        for (field, value) in [
+            ("encryptedPrivateKeyData", &self.encrypted_private_key_data),
+            ("encryptedPrivateKeyIv", &self.encrypted_private_key_iv),
+        ]
+        .into_iter()
+        .chain(candidates)
+        {
+            if value.is_empty() || base64_url_decode(value).is_err() {
+                return Err(ConfigError::InvalidValue {
+                    field,
+                    reason: "must be non-empty base64url".to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = "toJSON")]
+    pub fn to_json(&self) -> Result<JsValue, JsValue> {
+        to_plain_object(self)
+    }
 }
 
 // === REGISTRATION TYPES ===
@@ -235,4 +1000,8 @@ pub struct RegistrationPayload {
     pub device_number: Option<u8>,
     #[wasm_bindgen(getter_with_clone, js_name = "authenticatorOptions")]
     pub authenticator_options: Option<AuthenticatorOptions>,
+    /// Contract registration interface version (defaults to the latest)
+    #[wasm_bindgen(js_name = "contractInterfaceVersion")]
+    #[serde(default)]
+    pub contract_interface_version: Option<u32>,
 }
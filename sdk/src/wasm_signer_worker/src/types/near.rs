@@ -2,7 +2,7 @@
 // WASM-compatible structs that mirror near-primitives
 
 use crate::types::ToJson;
-use borsh::{BorshDeserialize, BorshSerialize};
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use serde::{Deserialize, Serialize};
 use serde_bytes;
 use sha2::{Digest, Sha256};
@@ -10,7 +10,7 @@ use wasm_bindgen::prelude::*;
 
 // === CORE NEAR TYPES ===
 
-#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AccountId(pub String);
 
@@ -21,6 +21,16 @@ impl AccountId {
         }
         Ok(AccountId(account_id))
     }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for AccountId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
 }
 
 impl std::str::FromStr for AccountId {
@@ -31,7 +41,7 @@ impl std::str::FromStr for AccountId {
     }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PublicKey {
     pub key_type: u8, // 0 for ED25519
@@ -53,7 +63,7 @@ impl PublicKey {
     }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Signature {
     pub key_type: u8, // 0 for ED25519
@@ -75,7 +85,7 @@ impl Signature {
     }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CryptoHash(#[serde(with = "serde_bytes")] pub [u8; 32]); // [u8; 32] for proper borsh serialization
 
@@ -94,7 +104,7 @@ pub type Nonce = u64;
 pub type Gas = u64;
 pub type Balance = u128;
 
-#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FunctionCallAction {
     pub method_name: String,
@@ -104,7 +114,7 @@ pub struct FunctionCallAction {
     pub deposit: Balance,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum Action {
     CreateAccount,
@@ -132,21 +142,21 @@ pub enum Action {
     },
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AccessKey {
     pub nonce: Nonce,
     pub permission: AccessKeyPermission,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum AccessKeyPermission {
     FunctionCall(FunctionCallPermission),
     FullAccess,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FunctionCallPermission {
     pub allowance: Option<Balance>,
@@ -155,7 +165,7 @@ pub struct FunctionCallPermission {
 }
 
 // Internal Transaction representation for borsh serialization
-#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Transaction {
     pub signer_id: AccountId,
@@ -170,15 +180,16 @@ impl Transaction {
     /// Computes a hash of the transaction for signing
     /// This mirrors the logic from near-primitives Transaction::get_hash_and_size()
     pub fn get_hash_and_size(&self) -> (CryptoHash, u64) {
-        let bytes = borsh::to_vec(&self).expect("Failed to serialize transaction");
-        let hash_bytes = {
+        // Serialized into an arena buffer: for DeployContract this is a full copy of the code
+        let (hash_bytes, size) = crate::memory::with_transient_buffer(|bytes| {
+            borsh::to_writer(&mut *bytes, &self).expect("Failed to serialize transaction");
             let mut hasher = Sha256::new();
-            hasher.update(&bytes);
-            hasher.finalize()
-        };
+            hasher.update(&*bytes);
+            (hasher.finalize(), bytes.len())
+        });
         let mut hash_array = [0u8; 32];
         hash_array.copy_from_slice(&hash_bytes);
-        (CryptoHash::from_bytes(hash_array), bytes.len() as u64)
+        (CryptoHash::from_bytes(hash_array), size as u64)
     }
 
     // WASM-friendly getters
@@ -200,7 +211,7 @@ impl Transaction {
     }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SignedTransaction {
     pub transaction: Transaction,
@@ -226,6 +237,36 @@ impl SignedTransaction {
     }
 }
 
+// === DELEGATE ACTIONS (NEP-366) ===
+
+/// Wrapper mirroring near-primitives `NonDelegateAction`.
+/// Borsh layout is identical to the wrapped `Action`.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NonDelegateAction(pub Action);
+
+/// Mirrors near-primitives `DelegateAction` (meta-transaction inner payload)
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DelegateAction {
+    pub sender_id: AccountId,
+    pub receiver_id: AccountId,
+    pub actions: Vec<NonDelegateAction>,
+    pub nonce: Nonce,
+    pub max_block_height: u64,
+    pub public_key: PublicKey,
+}
+
+// === NEP-413 ===
+
+/// NEP-413 message payload, borsh-serialized (after the NEP-413 prefix tag) before hashing
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct Nep413Payload {
+    pub message: String,
+    pub recipient: String,
+    pub nonce: [u8; 32],
+    pub state: Option<String>,
+}
+
 // === TO_JSON IMPLEMENTATIONS ===
 // All NEAR types now use the default ToJson implementation since they have Serialize + camelCase
 // This eliminates manual camelCase conversions and reduces code duplication
@@ -14,6 +14,13 @@ impl<T: Serialize> ToJson for T {
     }
 }
 
+/// Converts a value handed to JS directly (wasm exports, `toJSON()`, thrown errors) into the
+/// shape a dispatcher response has after JSON.parse: plain objects for maps and tagged enums,
+/// null for None, arrays for bytes
+pub fn to_js_value<T: Serialize + ?Sized>(value: &T) -> Result<JsValue, serde_wasm_bindgen::Error> {
+    value.serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+}
+
 #[wasm_bindgen]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
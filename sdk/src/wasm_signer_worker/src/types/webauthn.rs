@@ -136,7 +136,7 @@ impl From<&WebAuthnRegistrationCredentialStruct> for WebAuthnRegistrationCredent
 }
 
 /// WebAuthn authentication data for contract verification
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct WebAuthnAuthenticationCredential {
     pub id: String,
     #[serde(rename = "rawId")]
@@ -199,6 +199,7 @@ pub struct SerializedCredential {
     #[wasm_bindgen(getter_with_clone, js_name = "response")]
     pub response: AuthenticationResponse,
     #[wasm_bindgen(getter_with_clone, js_name = "clientExtensionResults")]
+    #[serde(default)]
     pub client_extension_results: ClientExtensionResults,
 }
 
@@ -218,6 +219,7 @@ pub struct SerializedRegistrationCredential {
     #[wasm_bindgen(getter_with_clone, js_name = "response")]
     pub response: RegistrationResponse,
     #[wasm_bindgen(getter_with_clone, js_name = "clientExtensionResults")]
+    #[serde(default)]
     pub client_extension_results: ClientExtensionResults,
 }
 
@@ -253,21 +255,24 @@ pub struct RegistrationResponse {
 }
 
 #[wasm_bindgen]
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct ClientExtensionResults {
+    /// Empty when the authenticator does not support the PRF extension
     #[wasm_bindgen(getter_with_clone, js_name = "prf")]
+    #[serde(default)]
     pub prf: PrfResults,
 }
 
 #[wasm_bindgen]
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct PrfResults {
     #[wasm_bindgen(getter_with_clone, js_name = "results")]
+    #[serde(default)]
     pub results: PrfOutputs,
 }
 
 #[wasm_bindgen]
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct PrfOutputs {
     #[wasm_bindgen(getter_with_clone, js_name = "first")]
     pub first: Option<String>,
@@ -291,8 +296,44 @@ pub struct VrfChallenge {
     pub user_id: String,
     #[wasm_bindgen(getter_with_clone, js_name = "rpId")]
     pub rp_id: String,
+    /// Empty for generic anchors
     #[wasm_bindgen(getter_with_clone, js_name = "blockHeight")]
     pub block_height: String,
+    /// Empty for generic anchors
     #[wasm_bindgen(getter_with_clone, js_name = "blockHash")]
     pub block_hash: String,
+    /// Anchor the VRF input was built from, echoed by the VRF worker
+    /// (absent in challenges predating anchors, which are NEAR-anchored)
+    #[wasm_bindgen(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<VrfAnchor>,
+    /// Length in bytes of the WebAuthn challenge derived from `vrf_output`
+    /// (payloads predating this field use the default)
+    #[wasm_bindgen(js_name = "challengeLength")]
+    #[serde(default = "default_challenge_length")]
+    pub challenge_length: u8,
+}
+
+fn default_challenge_length() -> u8 {
+    crate::config::DEFAULT_CHALLENGE_LENGTH
+}
+
+/// Freshness anchor of a VRF challenge (mirrors the VRF worker's VrfAnchor)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum VrfAnchor {
+    #[serde(rename_all = "camelCase")]
+    NearBlock {
+        block_height: String,
+        block_hash: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Generic { label: String, value_b64u: String },
+}
+
+impl VrfChallenge {
+    /// Whether the challenge is bound to a NEAR block (and so subject to NEAR block-age checks)
+    pub fn is_near_anchored(&self) -> bool {
+        !matches!(self.anchor, Some(VrfAnchor::Generic { .. }))
+    }
 }
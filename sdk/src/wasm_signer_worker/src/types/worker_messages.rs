@@ -2,7 +2,8 @@
 // Enums and message structures for worker communication
 
 use crate::error::ParsePayloadError;
-use serde::de::DeserializeOwned;
+use crate::error_codes::DeprecationWarning;
+use serde::de::{DeserializeOwned, Error as _, Unexpected};
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
@@ -23,26 +24,132 @@ pub enum WorkerRequestType {
     RegistrationCredentialConfirmation,
     // Two-phase export: collect PRF (skip UI), decrypt, then show private key UI
     ExportNearKeypairUI,
+    GetBorshSchemas,
+    ListPendingRequests,
+    CancelRequest,
+    WipeAllState,
+    ValidateEncryptedBlobs,
+    CreateSessionKey,
+    SignWithSessionKey,
+    RevokeSessionKey,
+    ComposeMultisigRequest,
+    GetMemoryStats,
+    TrimCaches,
+    GetRecentReceivers,
+    BuildAccountDescriptor,
+    DeployLargeContract,
+    RunSelfTest,
+    ExportAccountBundle,
+    ImportAccountBundle,
+    CreateSigningIntent,
+    ExecuteSigningIntent,
+    SubmitToRelayer,
+    GetTelemetrySnapshot,
+    GetKeyUsageStats,
+    PrepareRegistration,
+    CompleteRegistration,
+    CreateRemoteConfirmation,
+    ApproveRemoteConfirmation,
+    CompleteRemoteConfirmation,
+    GetWorkerInfo,
+    ValidateDecryptionCapability,
+    ListActiveAccounts,
+    WipeAccountState,
+    ValidateArgsSchemas,
+    SummarizeTransactions,
+    MigrateEncryptedBlobs,
+    BuildCredentialCreationOptions,
+    BuildCredentialRequestOptions,
+    RecoverPendingOperations,
+    ComposeCreateSubaccount,
+    StartBackgroundRefresh,
+    StopBackgroundRefresh,
+    BackgroundRefreshStatus,
+    SuspendHint,
+    ResumeHint,
+    DeriveSubKey,
+    ViewCall,
+    DumpDiagnostics,
+    SignTransactionsWithFreshChallenge,
+    GetSessionStatus,
+    BuildRedirectPayload,
+    IssueElevationChallenge,
 }
 
 impl From<u32> for WorkerRequestType {
     fn from(value: u32) -> Self {
-        match value {
-            0 => WorkerRequestType::DeriveNearKeypairAndEncrypt,
-            1 => WorkerRequestType::RecoverKeypairFromPasskey,
-            2 => WorkerRequestType::CheckCanRegisterUser,
-            3 => WorkerRequestType::DecryptPrivateKeyWithPrf,
-            4 => WorkerRequestType::SignTransactionsWithActions,
-            5 => WorkerRequestType::ExtractCosePublicKey,
-            6 => WorkerRequestType::SignTransactionWithKeyPair,
-            7 => WorkerRequestType::SignNep413Message,
-            8 => WorkerRequestType::RegistrationCredentialConfirmation,
-            9 => WorkerRequestType::ExportNearKeypairUI,
-            _ => panic!("Invalid WorkerRequestType value: {}", value),
-        }
+        WorkerRequestType::from_u32(value)
+            .unwrap_or_else(|| panic!("Invalid WorkerRequestType value: {}", value))
     }
 }
 impl WorkerRequestType {
+    /// The request type with numeric value `value`, if there is one
+    pub fn from_u32(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(WorkerRequestType::DeriveNearKeypairAndEncrypt),
+            1 => Some(WorkerRequestType::RecoverKeypairFromPasskey),
+            2 => Some(WorkerRequestType::CheckCanRegisterUser),
+            3 => Some(WorkerRequestType::DecryptPrivateKeyWithPrf),
+            4 => Some(WorkerRequestType::SignTransactionsWithActions),
+            5 => Some(WorkerRequestType::ExtractCosePublicKey),
+            6 => Some(WorkerRequestType::SignTransactionWithKeyPair),
+            7 => Some(WorkerRequestType::SignNep413Message),
+            8 => Some(WorkerRequestType::RegistrationCredentialConfirmation),
+            9 => Some(WorkerRequestType::ExportNearKeypairUI),
+            10 => Some(WorkerRequestType::GetBorshSchemas),
+            11 => Some(WorkerRequestType::ListPendingRequests),
+            12 => Some(WorkerRequestType::CancelRequest),
+            13 => Some(WorkerRequestType::WipeAllState),
+            14 => Some(WorkerRequestType::ValidateEncryptedBlobs),
+            15 => Some(WorkerRequestType::CreateSessionKey),
+            16 => Some(WorkerRequestType::SignWithSessionKey),
+            17 => Some(WorkerRequestType::RevokeSessionKey),
+            18 => Some(WorkerRequestType::ComposeMultisigRequest),
+            19 => Some(WorkerRequestType::GetMemoryStats),
+            20 => Some(WorkerRequestType::TrimCaches),
+            21 => Some(WorkerRequestType::GetRecentReceivers),
+            22 => Some(WorkerRequestType::BuildAccountDescriptor),
+            23 => Some(WorkerRequestType::DeployLargeContract),
+            24 => Some(WorkerRequestType::RunSelfTest),
+            25 => Some(WorkerRequestType::ExportAccountBundle),
+            26 => Some(WorkerRequestType::ImportAccountBundle),
+            27 => Some(WorkerRequestType::CreateSigningIntent),
+            28 => Some(WorkerRequestType::ExecuteSigningIntent),
+            29 => Some(WorkerRequestType::SubmitToRelayer),
+            30 => Some(WorkerRequestType::GetTelemetrySnapshot),
+            31 => Some(WorkerRequestType::GetKeyUsageStats),
+            32 => Some(WorkerRequestType::PrepareRegistration),
+            33 => Some(WorkerRequestType::CompleteRegistration),
+            34 => Some(WorkerRequestType::CreateRemoteConfirmation),
+            35 => Some(WorkerRequestType::ApproveRemoteConfirmation),
+            36 => Some(WorkerRequestType::CompleteRemoteConfirmation),
+            37 => Some(WorkerRequestType::GetWorkerInfo),
+            38 => Some(WorkerRequestType::ValidateDecryptionCapability),
+            39 => Some(WorkerRequestType::ListActiveAccounts),
+            40 => Some(WorkerRequestType::WipeAccountState),
+            41 => Some(WorkerRequestType::ValidateArgsSchemas),
+            42 => Some(WorkerRequestType::SummarizeTransactions),
+            43 => Some(WorkerRequestType::MigrateEncryptedBlobs),
+            44 => Some(WorkerRequestType::BuildCredentialCreationOptions),
+            45 => Some(WorkerRequestType::BuildCredentialRequestOptions),
+            46 => Some(WorkerRequestType::RecoverPendingOperations),
+            47 => Some(WorkerRequestType::ComposeCreateSubaccount),
+            48 => Some(WorkerRequestType::StartBackgroundRefresh),
+            49 => Some(WorkerRequestType::StopBackgroundRefresh),
+            50 => Some(WorkerRequestType::BackgroundRefreshStatus),
+            51 => Some(WorkerRequestType::SuspendHint),
+            52 => Some(WorkerRequestType::ResumeHint),
+            53 => Some(WorkerRequestType::DeriveSubKey),
+            54 => Some(WorkerRequestType::ViewCall),
+            55 => Some(WorkerRequestType::DumpDiagnostics),
+            56 => Some(WorkerRequestType::SignTransactionsWithFreshChallenge),
+            57 => Some(WorkerRequestType::GetSessionStatus),
+            58 => Some(WorkerRequestType::BuildRedirectPayload),
+            59 => Some(WorkerRequestType::IssueElevationChallenge),
+            _ => None,
+        }
+    }
+
     pub fn name(&self) -> &'static str {
         match self {
             WorkerRequestType::DeriveNearKeypairAndEncrypt => "DERIVE_NEAR_KEYPAIR_AND_ENCRYPT",
@@ -57,8 +164,143 @@ impl WorkerRequestType {
                 "REGISTRATION_CREDENTIAL_CONFIRMATION"
             }
             WorkerRequestType::ExportNearKeypairUI => "EXPORT_NEAR_KEYPAIR_UI",
+            WorkerRequestType::GetBorshSchemas => "GET_BORSH_SCHEMAS",
+            WorkerRequestType::ListPendingRequests => "LIST_PENDING_REQUESTS",
+            WorkerRequestType::CancelRequest => "CANCEL_REQUEST",
+            WorkerRequestType::WipeAllState => "WIPE_ALL_STATE",
+            WorkerRequestType::ValidateEncryptedBlobs => "VALIDATE_ENCRYPTED_BLOBS",
+            WorkerRequestType::CreateSessionKey => "CREATE_SESSION_KEY",
+            WorkerRequestType::SignWithSessionKey => "SIGN_WITH_SESSION_KEY",
+            WorkerRequestType::RevokeSessionKey => "REVOKE_SESSION_KEY",
+            WorkerRequestType::ComposeMultisigRequest => "COMPOSE_MULTISIG_REQUEST",
+            WorkerRequestType::GetMemoryStats => "GET_MEMORY_STATS",
+            WorkerRequestType::TrimCaches => "TRIM_CACHES",
+            WorkerRequestType::GetRecentReceivers => "GET_RECENT_RECEIVERS",
+            WorkerRequestType::BuildAccountDescriptor => "BUILD_ACCOUNT_DESCRIPTOR",
+            WorkerRequestType::DeployLargeContract => "DEPLOY_LARGE_CONTRACT",
+            WorkerRequestType::RunSelfTest => "RUN_SELF_TEST",
+            WorkerRequestType::ExportAccountBundle => "EXPORT_ACCOUNT_BUNDLE",
+            WorkerRequestType::ImportAccountBundle => "IMPORT_ACCOUNT_BUNDLE",
+            WorkerRequestType::CreateSigningIntent => "CREATE_SIGNING_INTENT",
+            WorkerRequestType::ExecuteSigningIntent => "EXECUTE_SIGNING_INTENT",
+            WorkerRequestType::SubmitToRelayer => "SUBMIT_TO_RELAYER",
+            WorkerRequestType::GetTelemetrySnapshot => "GET_TELEMETRY_SNAPSHOT",
+            WorkerRequestType::GetKeyUsageStats => "GET_KEY_USAGE_STATS",
+            WorkerRequestType::PrepareRegistration => "PREPARE_REGISTRATION",
+            WorkerRequestType::CompleteRegistration => "COMPLETE_REGISTRATION",
+            WorkerRequestType::CreateRemoteConfirmation => "CREATE_REMOTE_CONFIRMATION",
+            WorkerRequestType::ApproveRemoteConfirmation => "APPROVE_REMOTE_CONFIRMATION",
+            WorkerRequestType::CompleteRemoteConfirmation => "COMPLETE_REMOTE_CONFIRMATION",
+            WorkerRequestType::GetWorkerInfo => "GET_WORKER_INFO",
+            WorkerRequestType::ValidateDecryptionCapability => "VALIDATE_DECRYPTION_CAPABILITY",
+            WorkerRequestType::ListActiveAccounts => "LIST_ACTIVE_ACCOUNTS",
+            WorkerRequestType::WipeAccountState => "WIPE_ACCOUNT_STATE",
+            WorkerRequestType::ValidateArgsSchemas => "VALIDATE_ARGS_SCHEMAS",
+            WorkerRequestType::SummarizeTransactions => "SUMMARIZE_TRANSACTIONS",
+            WorkerRequestType::MigrateEncryptedBlobs => "MIGRATE_ENCRYPTED_BLOBS",
+            WorkerRequestType::BuildCredentialCreationOptions => "BUILD_CREDENTIAL_CREATION_OPTIONS",
+            WorkerRequestType::BuildCredentialRequestOptions => "BUILD_CREDENTIAL_REQUEST_OPTIONS",
+            WorkerRequestType::RecoverPendingOperations => "RECOVER_PENDING_OPERATIONS",
+            WorkerRequestType::ComposeCreateSubaccount => "COMPOSE_CREATE_SUBACCOUNT",
+            WorkerRequestType::StartBackgroundRefresh => "START_BACKGROUND_REFRESH",
+            WorkerRequestType::StopBackgroundRefresh => "STOP_BACKGROUND_REFRESH",
+            WorkerRequestType::BackgroundRefreshStatus => "BACKGROUND_REFRESH_STATUS",
+            WorkerRequestType::SuspendHint => "SUSPEND_HINT",
+            WorkerRequestType::ResumeHint => "RESUME_HINT",
+            WorkerRequestType::DeriveSubKey => "DERIVE_SUB_KEY",
+            WorkerRequestType::ViewCall => "VIEW_CALL",
+            WorkerRequestType::DumpDiagnostics => "DUMP_DIAGNOSTICS",
+            WorkerRequestType::SignTransactionsWithFreshChallenge => {
+                "SIGN_TRANSACTIONS_WITH_FRESH_CHALLENGE"
+            }
+            WorkerRequestType::GetSessionStatus => "GET_SESSION_STATUS",
+            WorkerRequestType::BuildRedirectPayload => "BUILD_REDIRECT_PAYLOAD",
+            WorkerRequestType::IssueElevationChallenge => "ISSUE_ELEVATION_CHALLENGE",
         }
     }
+
+    /// Whether the request occupies a slot in the pending-request registry.
+    /// Introspection/control messages bypass the registry so they work while it is full.
+    pub fn is_tracked(&self) -> bool {
+        !matches!(
+            self,
+            WorkerRequestType::ExtractCosePublicKey
+                | WorkerRequestType::GetBorshSchemas
+                | WorkerRequestType::ListPendingRequests
+                | WorkerRequestType::CancelRequest
+                | WorkerRequestType::WipeAllState
+                | WorkerRequestType::ValidateEncryptedBlobs
+                | WorkerRequestType::ComposeMultisigRequest
+                | WorkerRequestType::GetMemoryStats
+                | WorkerRequestType::TrimCaches
+                | WorkerRequestType::GetRecentReceivers
+                | WorkerRequestType::RunSelfTest
+                | WorkerRequestType::GetTelemetrySnapshot
+                | WorkerRequestType::GetKeyUsageStats
+                | WorkerRequestType::GetWorkerInfo
+                | WorkerRequestType::DumpDiagnostics
+                | WorkerRequestType::ListActiveAccounts
+                | WorkerRequestType::WipeAccountState
+                | WorkerRequestType::ValidateArgsSchemas
+                | WorkerRequestType::SummarizeTransactions
+                | WorkerRequestType::StartBackgroundRefresh
+                | WorkerRequestType::StopBackgroundRefresh
+                | WorkerRequestType::BackgroundRefreshStatus
+                | WorkerRequestType::SuspendHint
+                | WorkerRequestType::ResumeHint
+                | WorkerRequestType::GetSessionStatus
+        )
+    }
+
+    /// Whether the request decrypts, derives or signs with key material (or collects the PRF
+    /// output it is derived from). These are refused after a failed self-test.
+    pub fn handles_keys(&self) -> bool {
+        matches!(
+            self,
+            WorkerRequestType::DeriveNearKeypairAndEncrypt
+                | WorkerRequestType::RecoverKeypairFromPasskey
+                | WorkerRequestType::DecryptPrivateKeyWithPrf
+                | WorkerRequestType::SignTransactionsWithActions
+                | WorkerRequestType::SignTransactionsWithFreshChallenge
+                | WorkerRequestType::SignTransactionWithKeyPair
+                | WorkerRequestType::SignNep413Message
+                | WorkerRequestType::RegistrationCredentialConfirmation
+                | WorkerRequestType::ExportNearKeypairUI
+                | WorkerRequestType::CreateSessionKey
+                | WorkerRequestType::SignWithSessionKey
+                | WorkerRequestType::BuildAccountDescriptor
+                | WorkerRequestType::DeployLargeContract
+                | WorkerRequestType::ExportAccountBundle
+                | WorkerRequestType::ImportAccountBundle
+                | WorkerRequestType::CreateSigningIntent
+                | WorkerRequestType::ExecuteSigningIntent
+                | WorkerRequestType::PrepareRegistration
+                | WorkerRequestType::CompleteRegistration
+                | WorkerRequestType::CreateRemoteConfirmation
+                | WorkerRequestType::ApproveRemoteConfirmation
+                | WorkerRequestType::CompleteRemoteConfirmation
+                | WorkerRequestType::ValidateDecryptionCapability
+                | WorkerRequestType::MigrateEncryptedBlobs
+                | WorkerRequestType::DeriveSubKey
+                | WorkerRequestType::BuildRedirectPayload
+        )
+    }
+
+    /// Whether the request prompts for the passkey through the confirmation bridge, where the
+    /// worker vouches for the credential's collection time. These renew an expired session
+    /// instead of being refused up front (see session_duration.rs).
+    pub fn collects_credential(&self) -> bool {
+        matches!(
+            self,
+            WorkerRequestType::SignTransactionsWithActions
+                | WorkerRequestType::SignTransactionsWithFreshChallenge
+                | WorkerRequestType::RegistrationCredentialConfirmation
+                | WorkerRequestType::ExportNearKeypairUI
+                | WorkerRequestType::DeployLargeContract
+                | WorkerRequestType::CreateSigningIntent
+                | WorkerRequestType::ApproveRemoteConfirmation
+        )
+    }
 }
 
 /// Worker response types enum - corresponds to TypeScript WorkerResponseType
@@ -94,6 +336,106 @@ pub enum WorkerResponseType {
     RegistrationComplete,
     ExecuteActionsProgress,
     ExecuteActionsComplete,
+    GetBorshSchemasSuccess,
+    GetBorshSchemasFailure,
+    ListPendingRequestsSuccess,
+    ListPendingRequestsFailure,
+    CancelRequestSuccess,
+    CancelRequestFailure,
+    WipeAllStateSuccess,
+    WipeAllStateFailure,
+    ValidateEncryptedBlobsSuccess,
+    ValidateEncryptedBlobsFailure,
+    CreateSessionKeySuccess,
+    CreateSessionKeyFailure,
+    SignWithSessionKeySuccess,
+    SignWithSessionKeyFailure,
+    RevokeSessionKeySuccess,
+    RevokeSessionKeyFailure,
+    ComposeMultisigRequestSuccess,
+    ComposeMultisigRequestFailure,
+    GetMemoryStatsSuccess,
+    GetMemoryStatsFailure,
+    TrimCachesSuccess,
+    TrimCachesFailure,
+    GetRecentReceiversSuccess,
+    GetRecentReceiversFailure,
+    BuildAccountDescriptorSuccess,
+    BuildAccountDescriptorFailure,
+    DeployLargeContractSuccess,
+    DeployLargeContractFailure,
+    RunSelfTestSuccess,
+    RunSelfTestFailure,
+    ExportAccountBundleSuccess,
+    ExportAccountBundleFailure,
+    ImportAccountBundleSuccess,
+    ImportAccountBundleFailure,
+    CreateSigningIntentSuccess,
+    CreateSigningIntentFailure,
+    ExecuteSigningIntentSuccess,
+    ExecuteSigningIntentFailure,
+    SubmitToRelayerSuccess,
+    SubmitToRelayerFailure,
+    GetTelemetrySnapshotSuccess,
+    GetTelemetrySnapshotFailure,
+    GetKeyUsageStatsSuccess,
+    GetKeyUsageStatsFailure,
+    PrepareRegistrationSuccess,
+    PrepareRegistrationFailure,
+    CompleteRegistrationSuccess,
+    CompleteRegistrationFailure,
+    CreateRemoteConfirmationSuccess,
+    CreateRemoteConfirmationFailure,
+    ApproveRemoteConfirmationSuccess,
+    ApproveRemoteConfirmationFailure,
+    CompleteRemoteConfirmationSuccess,
+    CompleteRemoteConfirmationFailure,
+    GetWorkerInfoSuccess,
+    GetWorkerInfoFailure,
+    ValidateDecryptionCapabilitySuccess,
+    ValidateDecryptionCapabilityFailure,
+    ListActiveAccountsSuccess,
+    ListActiveAccountsFailure,
+    WipeAccountStateSuccess,
+    WipeAccountStateFailure,
+    ValidateArgsSchemasSuccess,
+    ValidateArgsSchemasFailure,
+    SummarizeTransactionsSuccess,
+    SummarizeTransactionsFailure,
+    MigrateEncryptedBlobsSuccess,
+    MigrateEncryptedBlobsFailure,
+    BuildCredentialCreationOptionsSuccess,
+    BuildCredentialCreationOptionsFailure,
+    BuildCredentialRequestOptionsSuccess,
+    BuildCredentialRequestOptionsFailure,
+    RecoverPendingOperationsSuccess,
+    RecoverPendingOperationsFailure,
+    ComposeCreateSubaccountSuccess,
+    ComposeCreateSubaccountFailure,
+    StartBackgroundRefreshSuccess,
+    StartBackgroundRefreshFailure,
+    StopBackgroundRefreshSuccess,
+    StopBackgroundRefreshFailure,
+    BackgroundRefreshStatusSuccess,
+    BackgroundRefreshStatusFailure,
+    SuspendHintSuccess,
+    SuspendHintFailure,
+    ResumeHintSuccess,
+    ResumeHintFailure,
+    DeriveSubKeySuccess,
+    DeriveSubKeyFailure,
+    ViewCallSuccess,
+    ViewCallFailure,
+    DumpDiagnosticsSuccess,
+    DumpDiagnosticsFailure,
+    SignTransactionsWithFreshChallengeSuccess,
+    SignTransactionsWithFreshChallengeFailure,
+    GetSessionStatusSuccess,
+    GetSessionStatusFailure,
+    BuildRedirectPayloadSuccess,
+    BuildRedirectPayloadFailure,
+    IssueElevationChallengeSuccess,
+    IssueElevationChallengeFailure,
 }
 impl From<WorkerResponseType> for u32 {
     fn from(value: WorkerResponseType) -> Self {
@@ -127,6 +469,106 @@ impl From<WorkerResponseType> for u32 {
             WorkerResponseType::RegistrationComplete => 21,
             WorkerResponseType::ExecuteActionsProgress => 22,
             WorkerResponseType::ExecuteActionsComplete => 23,
+            WorkerResponseType::GetBorshSchemasSuccess => 24,
+            WorkerResponseType::GetBorshSchemasFailure => 25,
+            WorkerResponseType::ListPendingRequestsSuccess => 26,
+            WorkerResponseType::ListPendingRequestsFailure => 27,
+            WorkerResponseType::CancelRequestSuccess => 28,
+            WorkerResponseType::CancelRequestFailure => 29,
+            WorkerResponseType::WipeAllStateSuccess => 30,
+            WorkerResponseType::WipeAllStateFailure => 31,
+            WorkerResponseType::ValidateEncryptedBlobsSuccess => 32,
+            WorkerResponseType::ValidateEncryptedBlobsFailure => 33,
+            WorkerResponseType::CreateSessionKeySuccess => 34,
+            WorkerResponseType::CreateSessionKeyFailure => 35,
+            WorkerResponseType::SignWithSessionKeySuccess => 36,
+            WorkerResponseType::SignWithSessionKeyFailure => 37,
+            WorkerResponseType::RevokeSessionKeySuccess => 38,
+            WorkerResponseType::RevokeSessionKeyFailure => 39,
+            WorkerResponseType::ComposeMultisigRequestSuccess => 40,
+            WorkerResponseType::ComposeMultisigRequestFailure => 41,
+            WorkerResponseType::GetMemoryStatsSuccess => 42,
+            WorkerResponseType::GetMemoryStatsFailure => 43,
+            WorkerResponseType::TrimCachesSuccess => 44,
+            WorkerResponseType::TrimCachesFailure => 45,
+            WorkerResponseType::GetRecentReceiversSuccess => 46,
+            WorkerResponseType::GetRecentReceiversFailure => 47,
+            WorkerResponseType::BuildAccountDescriptorSuccess => 48,
+            WorkerResponseType::BuildAccountDescriptorFailure => 49,
+            WorkerResponseType::DeployLargeContractSuccess => 50,
+            WorkerResponseType::DeployLargeContractFailure => 51,
+            WorkerResponseType::RunSelfTestSuccess => 52,
+            WorkerResponseType::RunSelfTestFailure => 53,
+            WorkerResponseType::ExportAccountBundleSuccess => 54,
+            WorkerResponseType::ExportAccountBundleFailure => 55,
+            WorkerResponseType::ImportAccountBundleSuccess => 56,
+            WorkerResponseType::ImportAccountBundleFailure => 57,
+            WorkerResponseType::CreateSigningIntentSuccess => 58,
+            WorkerResponseType::CreateSigningIntentFailure => 59,
+            WorkerResponseType::ExecuteSigningIntentSuccess => 60,
+            WorkerResponseType::ExecuteSigningIntentFailure => 61,
+            WorkerResponseType::SubmitToRelayerSuccess => 62,
+            WorkerResponseType::SubmitToRelayerFailure => 63,
+            WorkerResponseType::GetTelemetrySnapshotSuccess => 64,
+            WorkerResponseType::GetTelemetrySnapshotFailure => 65,
+            WorkerResponseType::GetKeyUsageStatsSuccess => 66,
+            WorkerResponseType::GetKeyUsageStatsFailure => 67,
+            WorkerResponseType::PrepareRegistrationSuccess => 68,
+            WorkerResponseType::PrepareRegistrationFailure => 69,
+            WorkerResponseType::CompleteRegistrationSuccess => 70,
+            WorkerResponseType::CompleteRegistrationFailure => 71,
+            WorkerResponseType::CreateRemoteConfirmationSuccess => 72,
+            WorkerResponseType::CreateRemoteConfirmationFailure => 73,
+            WorkerResponseType::ApproveRemoteConfirmationSuccess => 74,
+            WorkerResponseType::ApproveRemoteConfirmationFailure => 75,
+            WorkerResponseType::CompleteRemoteConfirmationSuccess => 76,
+            WorkerResponseType::CompleteRemoteConfirmationFailure => 77,
+            WorkerResponseType::GetWorkerInfoSuccess => 78,
+            WorkerResponseType::GetWorkerInfoFailure => 79,
+            WorkerResponseType::ValidateDecryptionCapabilitySuccess => 80,
+            WorkerResponseType::ValidateDecryptionCapabilityFailure => 81,
+            WorkerResponseType::ListActiveAccountsSuccess => 82,
+            WorkerResponseType::ListActiveAccountsFailure => 83,
+            WorkerResponseType::WipeAccountStateSuccess => 84,
+            WorkerResponseType::WipeAccountStateFailure => 85,
+            WorkerResponseType::ValidateArgsSchemasSuccess => 86,
+            WorkerResponseType::ValidateArgsSchemasFailure => 87,
+            WorkerResponseType::SummarizeTransactionsSuccess => 88,
+            WorkerResponseType::SummarizeTransactionsFailure => 89,
+            WorkerResponseType::MigrateEncryptedBlobsSuccess => 90,
+            WorkerResponseType::MigrateEncryptedBlobsFailure => 91,
+            WorkerResponseType::BuildCredentialCreationOptionsSuccess => 92,
+            WorkerResponseType::BuildCredentialCreationOptionsFailure => 93,
+            WorkerResponseType::BuildCredentialRequestOptionsSuccess => 94,
+            WorkerResponseType::BuildCredentialRequestOptionsFailure => 95,
+            WorkerResponseType::RecoverPendingOperationsSuccess => 96,
+            WorkerResponseType::RecoverPendingOperationsFailure => 97,
+            WorkerResponseType::ComposeCreateSubaccountSuccess => 98,
+            WorkerResponseType::ComposeCreateSubaccountFailure => 99,
+            WorkerResponseType::StartBackgroundRefreshSuccess => 100,
+            WorkerResponseType::StartBackgroundRefreshFailure => 101,
+            WorkerResponseType::StopBackgroundRefreshSuccess => 102,
+            WorkerResponseType::StopBackgroundRefreshFailure => 103,
+            WorkerResponseType::BackgroundRefreshStatusSuccess => 104,
+            WorkerResponseType::BackgroundRefreshStatusFailure => 105,
+            WorkerResponseType::SuspendHintSuccess => 106,
+            WorkerResponseType::SuspendHintFailure => 107,
+            WorkerResponseType::ResumeHintSuccess => 108,
+            WorkerResponseType::ResumeHintFailure => 109,
+            WorkerResponseType::DeriveSubKeySuccess => 110,
+            WorkerResponseType::DeriveSubKeyFailure => 111,
+            WorkerResponseType::ViewCallSuccess => 112,
+            WorkerResponseType::ViewCallFailure => 113,
+            WorkerResponseType::DumpDiagnosticsSuccess => 114,
+            WorkerResponseType::DumpDiagnosticsFailure => 115,
+            WorkerResponseType::SignTransactionsWithFreshChallengeSuccess => 116,
+            WorkerResponseType::SignTransactionsWithFreshChallengeFailure => 117,
+            WorkerResponseType::GetSessionStatusSuccess => 118,
+            WorkerResponseType::GetSessionStatusFailure => 119,
+            WorkerResponseType::BuildRedirectPayloadSuccess => 120,
+            WorkerResponseType::BuildRedirectPayloadFailure => 121,
+            WorkerResponseType::IssueElevationChallengeSuccess => 122,
+            WorkerResponseType::IssueElevationChallengeFailure => 123,
         }
     }
 }
@@ -143,6 +585,7 @@ impl From<u32> for WorkerResponseType {
             6 => WorkerResponseType::SignTransactionWithKeyPairSuccess,
             7 => WorkerResponseType::SignNep413MessageSuccess,
             8 => WorkerResponseType::RegistrationCredentialConfirmationSuccess,
+            9 => WorkerResponseType::ExportNearKeypairUiSuccess,
 
             // Failure responses
             10 => WorkerResponseType::DeriveNearKeypairAndEncryptFailure,
@@ -154,12 +597,113 @@ impl From<u32> for WorkerResponseType {
             16 => WorkerResponseType::SignTransactionWithKeyPairFailure,
             17 => WorkerResponseType::SignNep413MessageFailure,
             18 => WorkerResponseType::RegistrationCredentialConfirmationFailure,
+            19 => WorkerResponseType::ExportNearKeypairUiFailure,
 
             // Progress responses - for real-time updates during operations
             20 => WorkerResponseType::RegistrationProgress,
             21 => WorkerResponseType::RegistrationComplete,
             22 => WorkerResponseType::ExecuteActionsProgress,
             23 => WorkerResponseType::ExecuteActionsComplete,
+            24 => WorkerResponseType::GetBorshSchemasSuccess,
+            25 => WorkerResponseType::GetBorshSchemasFailure,
+            26 => WorkerResponseType::ListPendingRequestsSuccess,
+            27 => WorkerResponseType::ListPendingRequestsFailure,
+            28 => WorkerResponseType::CancelRequestSuccess,
+            29 => WorkerResponseType::CancelRequestFailure,
+            30 => WorkerResponseType::WipeAllStateSuccess,
+            31 => WorkerResponseType::WipeAllStateFailure,
+            32 => WorkerResponseType::ValidateEncryptedBlobsSuccess,
+            33 => WorkerResponseType::ValidateEncryptedBlobsFailure,
+            34 => WorkerResponseType::CreateSessionKeySuccess,
+            35 => WorkerResponseType::CreateSessionKeyFailure,
+            36 => WorkerResponseType::SignWithSessionKeySuccess,
+            37 => WorkerResponseType::SignWithSessionKeyFailure,
+            38 => WorkerResponseType::RevokeSessionKeySuccess,
+            39 => WorkerResponseType::RevokeSessionKeyFailure,
+            40 => WorkerResponseType::ComposeMultisigRequestSuccess,
+            41 => WorkerResponseType::ComposeMultisigRequestFailure,
+            42 => WorkerResponseType::GetMemoryStatsSuccess,
+            43 => WorkerResponseType::GetMemoryStatsFailure,
+            44 => WorkerResponseType::TrimCachesSuccess,
+            45 => WorkerResponseType::TrimCachesFailure,
+            46 => WorkerResponseType::GetRecentReceiversSuccess,
+            47 => WorkerResponseType::GetRecentReceiversFailure,
+            48 => WorkerResponseType::BuildAccountDescriptorSuccess,
+            49 => WorkerResponseType::BuildAccountDescriptorFailure,
+            50 => WorkerResponseType::DeployLargeContractSuccess,
+            51 => WorkerResponseType::DeployLargeContractFailure,
+            52 => WorkerResponseType::RunSelfTestSuccess,
+            53 => WorkerResponseType::RunSelfTestFailure,
+            54 => WorkerResponseType::ExportAccountBundleSuccess,
+            55 => WorkerResponseType::ExportAccountBundleFailure,
+            56 => WorkerResponseType::ImportAccountBundleSuccess,
+            57 => WorkerResponseType::ImportAccountBundleFailure,
+            58 => WorkerResponseType::CreateSigningIntentSuccess,
+            59 => WorkerResponseType::CreateSigningIntentFailure,
+            60 => WorkerResponseType::ExecuteSigningIntentSuccess,
+            61 => WorkerResponseType::ExecuteSigningIntentFailure,
+            62 => WorkerResponseType::SubmitToRelayerSuccess,
+            63 => WorkerResponseType::SubmitToRelayerFailure,
+            64 => WorkerResponseType::GetTelemetrySnapshotSuccess,
+            65 => WorkerResponseType::GetTelemetrySnapshotFailure,
+            66 => WorkerResponseType::GetKeyUsageStatsSuccess,
+            67 => WorkerResponseType::GetKeyUsageStatsFailure,
+            68 => WorkerResponseType::PrepareRegistrationSuccess,
+            69 => WorkerResponseType::PrepareRegistrationFailure,
+            70 => WorkerResponseType::CompleteRegistrationSuccess,
+            71 => WorkerResponseType::CompleteRegistrationFailure,
+            72 => WorkerResponseType::CreateRemoteConfirmationSuccess,
+            73 => WorkerResponseType::CreateRemoteConfirmationFailure,
+            74 => WorkerResponseType::ApproveRemoteConfirmationSuccess,
+            75 => WorkerResponseType::ApproveRemoteConfirmationFailure,
+            76 => WorkerResponseType::CompleteRemoteConfirmationSuccess,
+            77 => WorkerResponseType::CompleteRemoteConfirmationFailure,
+            78 => WorkerResponseType::GetWorkerInfoSuccess,
+            79 => WorkerResponseType::GetWorkerInfoFailure,
+            80 => WorkerResponseType::ValidateDecryptionCapabilitySuccess,
+            81 => WorkerResponseType::ValidateDecryptionCapabilityFailure,
+            82 => WorkerResponseType::ListActiveAccountsSuccess,
+            83 => WorkerResponseType::ListActiveAccountsFailure,
+            84 => WorkerResponseType::WipeAccountStateSuccess,
+            85 => WorkerResponseType::WipeAccountStateFailure,
+            86 => WorkerResponseType::ValidateArgsSchemasSuccess,
+            87 => WorkerResponseType::ValidateArgsSchemasFailure,
+            88 => WorkerResponseType::SummarizeTransactionsSuccess,
+            89 => WorkerResponseType::SummarizeTransactionsFailure,
+            90 => WorkerResponseType::MigrateEncryptedBlobsSuccess,
+            91 => WorkerResponseType::MigrateEncryptedBlobsFailure,
+            92 => WorkerResponseType::BuildCredentialCreationOptionsSuccess,
+            93 => WorkerResponseType::BuildCredentialCreationOptionsFailure,
+            94 => WorkerResponseType::BuildCredentialRequestOptionsSuccess,
+            95 => WorkerResponseType::BuildCredentialRequestOptionsFailure,
+            96 => WorkerResponseType::RecoverPendingOperationsSuccess,
+            97 => WorkerResponseType::RecoverPendingOperationsFailure,
+            98 => WorkerResponseType::ComposeCreateSubaccountSuccess,
+            99 => WorkerResponseType::ComposeCreateSubaccountFailure,
+            100 => WorkerResponseType::StartBackgroundRefreshSuccess,
+            101 => WorkerResponseType::StartBackgroundRefreshFailure,
+            102 => WorkerResponseType::StopBackgroundRefreshSuccess,
+            103 => WorkerResponseType::StopBackgroundRefreshFailure,
+            104 => WorkerResponseType::BackgroundRefreshStatusSuccess,
+            105 => WorkerResponseType::BackgroundRefreshStatusFailure,
+            106 => WorkerResponseType::SuspendHintSuccess,
+            107 => WorkerResponseType::SuspendHintFailure,
+            108 => WorkerResponseType::ResumeHintSuccess,
+            109 => WorkerResponseType::ResumeHintFailure,
+            110 => WorkerResponseType::DeriveSubKeySuccess,
+            111 => WorkerResponseType::DeriveSubKeyFailure,
+            112 => WorkerResponseType::ViewCallSuccess,
+            113 => WorkerResponseType::ViewCallFailure,
+            114 => WorkerResponseType::DumpDiagnosticsSuccess,
+            115 => WorkerResponseType::DumpDiagnosticsFailure,
+            116 => WorkerResponseType::SignTransactionsWithFreshChallengeSuccess,
+            117 => WorkerResponseType::SignTransactionsWithFreshChallengeFailure,
+            118 => WorkerResponseType::GetSessionStatusSuccess,
+            119 => WorkerResponseType::GetSessionStatusFailure,
+            120 => WorkerResponseType::BuildRedirectPayloadSuccess,
+            121 => WorkerResponseType::BuildRedirectPayloadFailure,
+            122 => WorkerResponseType::IssueElevationChallengeSuccess,
+            123 => WorkerResponseType::IssueElevationChallengeFailure,
             _ => panic!("Invalid WorkerResponseType value: {}", value),
         }
     }
@@ -171,6 +715,9 @@ pub struct SignerWorkerMessage {
     #[serde(rename = "type")]
     pub msg_type: u32,
     pub payload: serde_json::Value,
+    /// Caller-chosen ID used by the pending-request registry (generated when absent)
+    #[serde(rename = "requestId", default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 
 impl SignerWorkerMessage {
@@ -178,15 +725,33 @@ impl SignerWorkerMessage {
         &self,
         request_type: WorkerRequestType,
     ) -> Result<T, ParsePayloadError> {
+        expect_object(&self.payload).map_err(|e| ParsePayloadError::new(request_type.name(), e))?;
         serde_json::from_value(self.payload.clone())
             .map_err(|e| ParsePayloadError::new(request_type.name(), e))
     }
 }
 
+/// Refuses anything but a JSON object where a request struct is expected: derived
+/// `Deserialize` impls also accept a struct's fields as a positional array
+pub fn expect_object(value: &serde_json::Value) -> Result<(), serde_json::Error> {
+    let unexpected = match value {
+        serde_json::Value::Object(_) => return Ok(()),
+        serde_json::Value::Array(_) => Unexpected::Seq,
+        serde_json::Value::Null => Unexpected::Unit,
+        serde_json::Value::Bool(b) => Unexpected::Bool(*b),
+        serde_json::Value::String(s) => Unexpected::Str(s),
+        serde_json::Value::Number(_) => Unexpected::Other("number"),
+    };
+    Err(serde_json::Error::invalid_type(unexpected, &"a JSON object"))
+}
+
 /// Main worker response structure
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SignerWorkerResponse {
     #[serde(rename = "type")]
     pub response_type: u32,
     pub payload: serde_json::Value,
+    /// Deprecated paths the request took (see deprecations.rs); omitted when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<DeprecationWarning>,
 }
@@ -0,0 +1,99 @@
+// === UNSIGNED TRANSACTIONS ===
+// The exact bytes the worker would sign, for tooling (transaction inspectors, relayer cost
+// estimators) that must not touch key material. Action parsing, validation, transaction
+// assembly and hashing go through the same functions as the signing path in transaction.rs,
+// so the two cannot drift apart; only the signature step is left out.
+
+use serde::{Deserialize, Serialize};
+
+use crate::actions::ActionParams;
+use crate::encoders::base64_standard_encode;
+use crate::signature_verify::decode_ed25519_key;
+use crate::transaction::{build_actions_from_params, build_transaction_for_public_key};
+use crate::types::PublicKey;
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsignedTransactionRequest {
+    pub signer_account_id: String,
+    /// Access key the transaction will be signed with (`ed25519:<base58>` or base64url)
+    pub signer_public_key: String,
+    pub receiver_id: String,
+    pub nonce: String,
+    /// Base58 block hash
+    pub block_hash: String,
+    /// JSON string of ActionParams[], as in the signing requests
+    pub actions: String,
+}
+
+/// Decoded view of the built transaction, for display (actions as validated, in request form)
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsignedTransactionSummary {
+    pub signer_id: String,
+    pub public_key: String,
+    pub nonce: String,
+    pub receiver_id: String,
+    pub block_hash: String,
+    pub actions: Vec<ActionParams>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsignedTransaction {
+    /// Borsh-serialized Transaction (standard base64)
+    pub borsh_base64: String,
+    /// SHA-256 of the borsh bytes in base58: the hash that gets signed, and the transaction's
+    /// hash on-chain
+    pub tx_hash_b58: String,
+    pub parsed_summary: UnsignedTransactionSummary,
+}
+
+/// Builds and serializes the transaction described by `request` without signing it
+pub fn build_unsigned_transaction(
+    request: &UnsignedTransactionRequest,
+) -> Result<UnsignedTransaction, String> {
+    let verifying_key = decode_ed25519_key(&request.signer_public_key)?;
+    let nonce: u64 = request
+        .nonce
+        .parse()
+        .map_err(|e| format!("Invalid nonce: {}", e))?;
+    let block_hash = bs58::decode(&request.block_hash)
+        .into_vec()
+        .map_err(|e| format!("Invalid block hash: {}", e))?;
+
+    let action_params: Vec<ActionParams> = serde_json::from_str(&request.actions)
+        .map_err(|e| format!("Failed to parse actions: {}", e))?;
+    let actions = build_actions_from_params(action_params.clone())
+        .map_err(|e| format!("Failed to build actions: {}", e))?;
+
+    let transaction = build_transaction_for_public_key(
+        &request.signer_account_id,
+        &request.receiver_id,
+        nonce,
+        &block_hash,
+        PublicKey::from_ed25519_bytes(&verifying_key.to_bytes()),
+        actions,
+    )
+    .map_err(|e| format!("Failed to build transaction: {}", e))?;
+
+    let borsh_bytes = borsh::to_vec(&transaction)
+        .map_err(|e| format!("Transaction serialization failed: {}", e))?;
+    let (transaction_hash, _size) = transaction.get_hash_and_size();
+
+    Ok(UnsignedTransaction {
+        borsh_base64: base64_standard_encode(&borsh_bytes),
+        tx_hash_b58: bs58::encode(transaction_hash.0).into_string(),
+        parsed_summary: UnsignedTransactionSummary {
+            signer_id: transaction.get_signer_id(),
+            public_key: format!(
+                "ed25519:{}",
+                bs58::encode(transaction.public_key.key_data).into_string()
+            ),
+            nonce: transaction.nonce.to_string(),
+            receiver_id: transaction.get_receiver_id(),
+            block_hash: request.block_hash.clone(),
+            actions: action_params,
+        },
+    })
+}
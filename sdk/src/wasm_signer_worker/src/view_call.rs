@@ -0,0 +1,204 @@
+// === VIEW CALLS ===
+// Dapps read contract state through ViewCall instead of bundling a second NEAR client for it.
+// A view call is a `query` of request_type `call_function` sent through the worker's
+// RpcClient, so it gets the same endpoint failover and timeout handling as every other call;
+// no key is touched and nothing is confirmed.
+//
+// The call reads the block the caller names: a finality ("optimistic" by default,
+// "near-final" or "final") or a block id, its height or base58 hash. The contract returns
+// bytes; the result carries them in base64 and, when they are JSON, parsed as well.
+//
+// A contract that panics is reported as ContractPanic with the panic message, whichever way
+// the node answers it: current nodes send a CONTRACT_EXECUTION_ERROR error, older ones a
+// result with an `error` string. Both wrap the message in the VM's error
+// (`wasm execution failed with error: FunctionCallError(ExecutionError("..."))`), which is
+// stripped. Other errors (unknown account, no contract code, unknown block) stay RPC errors.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::encoders::base64_standard_encode;
+use crate::error::{RpcErrorKind, ViewCallError};
+use crate::rpc_client::RpcClient;
+
+/// Finality of view calls that name no block
+pub const DEFAULT_VIEW_FINALITY: &str = "optimistic";
+
+const FINALITIES: [&str; 3] = ["optimistic", "near-final", "final"];
+
+/// A block by height or base58 hash
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum BlockId {
+    Height(u64),
+    Hash(String),
+}
+
+/// The block a view call reads: `{ finality }` or `{ blockId }`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewBlockReference {
+    #[serde(default)]
+    pub finality: Option<String>,
+    #[serde(default)]
+    pub block_id: Option<BlockId>,
+}
+
+impl ViewBlockReference {
+    /// The `finality` or `block_id` param of the query
+    fn query_param(&self) -> Result<(&'static str, Value), String> {
+        match (&self.finality, &self.block_id) {
+            (Some(_), Some(_)) => {
+                Err("blockReference takes either finality or blockId, not both".to_string())
+            }
+            (None, Some(BlockId::Height(height))) => Ok(("block_id", json!(height))),
+            (None, Some(BlockId::Hash(hash))) => {
+                if bs58::decode(hash).into_vec().map(|b| b.len()) != Ok(32) {
+                    return Err(format!("blockId \"{}\" is not a base58 block hash", hash));
+                }
+                Ok(("block_id", json!(hash)))
+            }
+            (finality, None) => {
+                let finality = finality.as_deref().unwrap_or(DEFAULT_VIEW_FINALITY);
+                if !FINALITIES.contains(&finality) {
+                    return Err(format!(
+                        "finality \"{}\" is not one of {}",
+                        finality,
+                        FINALITIES.join(", ")
+                    ));
+                }
+                Ok(("finality", json!(finality)))
+            }
+        }
+    }
+}
+
+/// What a view call returned
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewCallResult {
+    /// The returned bytes parsed as JSON; None when they are not JSON
+    #[wasm_bindgen(skip)]
+    pub result_json: Option<Value>,
+    /// The returned bytes, base64 (standard alphabet)
+    #[wasm_bindgen(getter_with_clone, js_name = "resultBase64")]
+    pub result_base64: String,
+    #[wasm_bindgen(getter_with_clone)]
+    pub logs: Vec<String>,
+    #[wasm_bindgen(getter_with_clone, js_name = "blockHeight")]
+    pub block_height: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "blockHash")]
+    pub block_hash: String,
+}
+
+/// `query` params calling `method_name` on `contract_id` with the raw `args` bytes
+pub fn view_call_params(
+    contract_id: &str,
+    method_name: &str,
+    args: &[u8],
+    block_reference: &ViewBlockReference,
+) -> Result<Value, String> {
+    let (key, value) = block_reference.query_param()?;
+    let mut params = json!({
+        "request_type": "call_function",
+        "account_id": contract_id,
+        "method_name": method_name,
+        "args_base64": base64_standard_encode(args),
+    });
+    params[key] = value;
+    Ok(params)
+}
+
+/// The panic message inside a VM error string (the string itself when it wraps none)
+pub fn contract_panic_message(vm_error: &str) -> String {
+    for opening in ["ExecutionError(\"", "panic_msg: \""] {
+        if let Some(start) = vm_error.find(opening) {
+            let inner = &vm_error[start + opening.len()..];
+            if let Some(end) = inner.rfind('"') {
+                return inner[..end].replace("\\\"", "\"");
+            }
+        }
+    }
+    vm_error.to_string()
+}
+
+/// Maps a CONTRACT_EXECUTION_ERROR to ContractPanic; other errors pass through
+fn view_call_error(error: RpcErrorKind) -> ViewCallError {
+    match error {
+        RpcErrorKind::Rpc {
+            name,
+            message,
+            error,
+        } if name == "CONTRACT_EXECUTION_ERROR" => {
+            let vm_error = error
+                .get("cause")
+                .and_then(|c| c.get("info"))
+                .and_then(|i| i.get("vm_error"))
+                .and_then(|v| v.as_str())
+                .unwrap_or(&message);
+            ViewCallError::ContractPanic {
+                message: contract_panic_message(vm_error),
+            }
+        }
+        e => ViewCallError::Rpc(e),
+    }
+}
+
+/// Decodes a call_function result: its bytes, logs and block
+pub fn parse_view_call_result(result: &Value) -> Result<ViewCallResult, ViewCallError> {
+    // Older nodes answer a panicking view call with an error string in the result
+    if let Some(error) = result.get("error").and_then(|e| e.as_str()) {
+        return Err(ViewCallError::ContractPanic {
+            message: contract_panic_message(error),
+        });
+    }
+    let invalid = |what: &str| {
+        ViewCallError::Rpc(RpcErrorKind::InvalidResponse(format!(
+            "call_function result without {}",
+            what
+        )))
+    };
+    let bytes = result
+        .get("result")
+        .and_then(|r| r.as_array())
+        .ok_or_else(|| invalid("result bytes"))?
+        .iter()
+        .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| invalid("result bytes"))?;
+    let block_height = result
+        .get("block_height")
+        .and_then(|h| h.as_u64())
+        .ok_or_else(|| invalid("block_height"))?;
+    let block_hash = result
+        .get("block_hash")
+        .and_then(|h| h.as_str())
+        .ok_or_else(|| invalid("block_hash"))?;
+    let logs = result
+        .get("logs")
+        .and_then(|logs| logs.as_array())
+        .map(|logs| {
+            logs.iter()
+                .filter_map(|log| log.as_str().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(ViewCallResult {
+        result_json: serde_json::from_slice(&bytes).ok(),
+        result_base64: base64_standard_encode(&bytes),
+        logs,
+        block_height: block_height.to_string(),
+        block_hash: block_hash.to_string(),
+    })
+}
+
+/// Runs a view call built by view_call_params
+pub async fn view_call<R: RpcClient>(
+    rpc: &R,
+    params: Value,
+) -> Result<ViewCallResult, ViewCallError> {
+    let result = rpc.call("query", params).await.map_err(view_call_error)?;
+    parse_view_call_result(&result)
+}
@@ -0,0 +1,157 @@
+// === WORKER MESSAGE WIRE FORMAT ===
+// Worker message envelopes travel either as JSON strings (default) or as CBOR frames.
+// The format is chosen once per worker by the Initialize handshake; afterwards frames in
+// the other format are rejected with WireFormatMismatch. CBOR byte strings are accepted
+// wherever the JSON encoding uses arrays of numbers (e.g. DeployContract code), so large
+// binary payloads skip the number-array encoding on the way in.
+
+use crate::error::{MessageSizeError, SignerErrorCode};
+use crate::message_limits;
+use crate::state;
+use crate::types::worker_messages::{SignerWorkerMessage, SignerWorkerResponse};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WireFormat {
+    #[default]
+    Json,
+    Cbor,
+}
+
+impl WireFormat {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "json" => Ok(WireFormat::Json),
+            "cbor" => Ok(WireFormat::Cbor),
+            other => Err(format!(
+                "Unknown wireFormat: {} (expected \"json\" or \"cbor\")",
+                other
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WireFormat::Json => "json",
+            WireFormat::Cbor => "cbor",
+        }
+    }
+}
+
+fn mismatch(expected: WireFormat, received: WireFormat) -> String {
+    format!(
+        "{}: worker was initialized for {} frames but received a {} frame",
+        SignerErrorCode::WireFormatMismatch,
+        expected.as_str(),
+        received.as_str()
+    )
+}
+
+/// A frame whose first non-whitespace byte opens a JSON object or array
+fn looks_like_json(frame: &[u8]) -> bool {
+    matches!(
+        frame.iter().find(|b| !b.is_ascii_whitespace()),
+        Some(b'{') | Some(b'[')
+    )
+}
+
+/// Decodes a JSON frame for a worker using `format`; frames above their size limit are
+/// refused before they are parsed
+pub fn decode_json_frame(
+    format: WireFormat,
+    message_json: &str,
+) -> Result<SignerWorkerMessage, String> {
+    message_limits::check_json_frame(message_json).map_err(|e| e.to_string())?;
+    if format != WireFormat::Json {
+        return Err(mismatch(format, WireFormat::Json));
+    }
+    serde_json::from_str(message_json).map_err(|e| format!("Failed to parse message: {:?}", e))
+}
+
+/// Decodes a CBOR frame for a worker using `format`; frames above their size limit, or
+/// declaring more bytes than they hold, are refused before they are decoded
+pub fn decode_cbor_frame(format: WireFormat, frame: &[u8]) -> Result<SignerWorkerMessage, String> {
+    match message_limits::check_cbor_frame(frame) {
+        // A JSON frame's opening brace reads as a CBOR text header declaring a huge length
+        Err(MessageSizeError::DeclaredLengthExceedsFrame { .. })
+            if format == WireFormat::Cbor && looks_like_json(frame) =>
+        {
+            return Err(mismatch(format, WireFormat::Json))
+        }
+        Err(e) => return Err(e.to_string()),
+        Ok(()) => {}
+    }
+    if format != WireFormat::Cbor {
+        return Err(mismatch(format, WireFormat::Cbor));
+    }
+    let value: ciborium::Value = match ciborium::from_reader(frame) {
+        Ok(value) => value,
+        Err(_) if looks_like_json(frame) => return Err(mismatch(format, WireFormat::Json)),
+        Err(e) => return Err(format!("Failed to parse CBOR message: {}", e)),
+    };
+    serde_json::from_value(cbor_to_json(value)?)
+        .map_err(|e| format!("Failed to parse message: {:?}", e))
+}
+
+pub fn encode_json_response(response: &SignerWorkerResponse) -> Result<String, String> {
+    serde_json::to_string(response).map_err(|e| format!("Failed to serialize response: {:?}", e))
+}
+
+pub fn encode_cbor_response(response: &SignerWorkerResponse) -> Result<Vec<u8>, String> {
+    let mut frame = Vec::new();
+    ciborium::into_writer(response, &mut frame)
+        .map_err(|e| format!("Failed to serialize CBOR response: {}", e))?;
+    Ok(frame)
+}
+
+/// Converts a decoded CBOR value to the JSON value handlers parse payloads from.
+/// Byte strings become arrays of numbers; map keys must be text.
+pub fn cbor_to_json(value: ciborium::Value) -> Result<serde_json::Value, String> {
+    use ciborium::Value as Cbor;
+    use serde_json::Value as Json;
+
+    Ok(match value {
+        Cbor::Null => Json::Null,
+        Cbor::Bool(b) => Json::Bool(b),
+        Cbor::Integer(i) => {
+            let i = i128::from(i);
+            if let Ok(u) = u64::try_from(i) {
+                Json::from(u)
+            } else if let Ok(s) = i64::try_from(i) {
+                Json::from(s)
+            } else {
+                return Err(format!("CBOR integer out of range: {}", i));
+            }
+        }
+        Cbor::Float(f) => serde_json::Number::from_f64(f)
+            .map(Json::Number)
+            .ok_or_else(|| format!("CBOR float is not representable in JSON: {}", f))?,
+        Cbor::Text(s) => Json::String(s),
+        Cbor::Bytes(bytes) => Json::Array(bytes.into_iter().map(Json::from).collect()),
+        Cbor::Array(items) => Json::Array(
+            items
+                .into_iter()
+                .map(cbor_to_json)
+                .collect::<Result<_, _>>()?,
+        ),
+        Cbor::Map(entries) => {
+            let mut map = serde_json::Map::new();
+            for (key, value) in entries {
+                let key = match key {
+                    Cbor::Text(key) => key,
+                    other => return Err(format!("CBOR map key must be text, got {:?}", other)),
+                };
+                map.insert(key, cbor_to_json(value)?);
+            }
+            Json::Object(map)
+        }
+        Cbor::Tag(_, inner) => cbor_to_json(*inner)?,
+        other => return Err(format!("Unsupported CBOR value: {:?}", other)),
+    })
+}
+
+/// The Initialize handshake: fixes the wire format for the rest of the worker's life
+pub fn initialize(wire_format: &str) -> Result<WireFormat, String> {
+    let format = WireFormat::parse(wire_format)?;
+    state::set_wire_format(format);
+    Ok(format)
+}
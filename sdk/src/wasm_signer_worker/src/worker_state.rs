@@ -0,0 +1,266 @@
+// === PERSISTED WORKER STATE ===
+// SignerWorkerManager runs every request in a fresh worker, so state that has to outlive the
+// request (session keys) travels through the TS layer: responses carry the updated
+// WorkerStateRecord when that state changed, and the next worker loads it before its request
+// runs, as with the key usage record.
+//
+// The record is sealed: the state snapshot is ChaCha20-Poly1305 under a 32-byte state key the
+// worker generates when none is loaded, and the state key travels in the record AES-GCM-wrapped
+// under a non-extractable CryptoKey that SignerWorkerManager keeps in IndexedDB (handed over
+// through `getWorkerStateWrapKey`). The page only ever holds ciphertext: the record can neither
+// be read nor edited without that CryptoKey. Like the outer wrap, this does not hold against
+// script that can call WebCrypto with the CryptoKey itself, and a stored record can be rolled
+// back to an older one.
+// A record that does not open (edited, or wrapped under another CryptoKey) is discarded along
+// with its state key, so nothing issued under that key verifies again. Without a wrap key the
+// state stays in the worker that made it.
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Nonce,
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+use zeroize::Zeroizing;
+
+use crate::config::{
+    WORKER_STATE_AAD_DOMAIN, WORKER_STATE_KEY_IV_SIZE, WORKER_STATE_RECORD_VERSION,
+};
+use crate::encoders::{base64_url_decode, base64_url_encode};
+use crate::state::{self, PersistedState};
+
+// Bridge implemented in web3authn-signer.worker.ts: SignerWorkerManager's state wrap CryptoKey,
+// undefined when none was given
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = getWorkerStateWrapKey)]
+    fn get_worker_state_wrap_key() -> JsValue;
+}
+
+/// The sealed state the TS layer stores and hands back
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WorkerStateRecord {
+    pub version: u32,
+    /// base64url AES-GCM IV || ciphertext of the state key under the wrap CryptoKey
+    pub wrapped_state_key: String,
+    /// base64url ChaCha20-Poly1305 nonce || ciphertext of the state snapshot
+    pub sealed_state: String,
+}
+
+struct LoadedKey {
+    key: Zeroizing<[u8; 32]>,
+    wrapped: String,
+}
+
+#[derive(Default)]
+struct WorkerStateKeeper {
+    key: Option<LoadedKey>,
+    /// Snapshot (JSON) the record was loaded with or last exported as
+    snapshot: Option<Zeroizing<String>>,
+}
+
+thread_local! {
+    static WORKER_STATE: RefCell<WorkerStateKeeper> = RefCell::new(WorkerStateKeeper::default());
+}
+
+/// AES-GCM with the state wrap CryptoKey; Ok(None) when no key was provided
+#[cfg(target_arch = "wasm32")]
+async fn aes_gcm_with_wrap_key(
+    encrypt: bool,
+    iv: &[u8],
+    data: &[u8],
+) -> Result<Option<Vec<u8>>, String> {
+    crate::outer_wrap::aes_gcm_with_crypto_key(
+        get_worker_state_wrap_key(),
+        encrypt,
+        iv,
+        data,
+        WORKER_STATE_AAD_DOMAIN.as_bytes(),
+    )
+    .await
+}
+
+/// Non-WASM fallback (native tests): WebCrypto keys never exist here
+#[cfg(not(target_arch = "wasm32"))]
+async fn aes_gcm_with_wrap_key(
+    _encrypt: bool,
+    _iv: &[u8],
+    _data: &[u8],
+) -> Result<Option<Vec<u8>>, String> {
+    Ok(None)
+}
+
+fn state_aad(wrapped_state_key: &str) -> Vec<u8> {
+    [
+        WORKER_STATE_AAD_DOMAIN.as_bytes(),
+        &WORKER_STATE_RECORD_VERSION.to_be_bytes(),
+        wrapped_state_key.as_bytes(),
+    ]
+    .concat()
+}
+
+/// Uses `key` (wrapped as `wrapped`) for the records this worker exports. The state is left
+/// as it is: call `open_record` to load a record sealed under the key.
+pub fn install_state_key(key: [u8; 32], wrapped: String) {
+    WORKER_STATE.with(|w| {
+        *w.borrow_mut() = WorkerStateKeeper {
+            key: Some(LoadedKey {
+                key: Zeroizing::new(key),
+                wrapped,
+            }),
+            snapshot: None,
+        }
+    });
+}
+
+/// Opens a record sealed under the installed state key into the worker's state
+pub fn open_record(record: &WorkerStateRecord) -> Result<(), String> {
+    if record.version != WORKER_STATE_RECORD_VERSION {
+        return Err(format!(
+            "Unsupported worker state record version {} (expected {})",
+            record.version, WORKER_STATE_RECORD_VERSION
+        ));
+    }
+    let key = WORKER_STATE.with(|w| {
+        w.borrow()
+            .key
+            .as_ref()
+            .filter(|k| k.wrapped == record.wrapped_state_key)
+            .map(|k| k.key.clone())
+    });
+    let key = key.ok_or("Worker state record was sealed under another state key")?;
+    let sealed = base64_url_decode(&record.sealed_state)
+        .map_err(|e| format!("Invalid sealed worker state: {}", e))?;
+    if sealed.len() < 12 {
+        return Err("Sealed worker state is truncated".to_string());
+    }
+    let (nonce, ciphertext) = sealed.split_at(12);
+    let plaintext = ChaCha20Poly1305::new(chacha20poly1305::Key::from_slice(&key[..]))
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: &state_aad(&record.wrapped_state_key),
+            },
+        )
+        .map_err(|_| "Worker state record does not open under its state key".to_string())?;
+    let snapshot = Zeroizing::new(
+        String::from_utf8(plaintext).map_err(|_| "Worker state is not UTF-8".to_string())?,
+    );
+    let persisted: PersistedState = serde_json::from_str(&snapshot)
+        .map_err(|e| format!("Invalid worker state snapshot: {}", e))?;
+    state::restore_persisted_state(persisted)?;
+    // Compare against the state as restored, so a record from an older layout is re-exported
+    let restored = serde_json::to_string(&state::persisted_state())
+        .map_err(|e| format!("Failed to serialize worker state: {}", e))?;
+    WORKER_STATE.with(|w| w.borrow_mut().snapshot = Some(Zeroizing::new(restored)));
+    Ok(())
+}
+
+/// Loads the record the TS layer stored (None on first use): unwraps its state key with the
+/// wrap CryptoKey and opens it. A record that does not open is discarded with a warning and a
+/// new state key is generated, as it is when there is no record. Returns whether the state
+/// will be persisted (false without a wrap CryptoKey).
+pub async fn load_record(record_json: Option<&str>) -> Result<bool, String> {
+    let mut discarded = false;
+    if let Some(record_json) = record_json {
+        match open_stored_record(record_json).await {
+            Ok(persisted) => return Ok(persisted),
+            Err(e) => {
+                warn!("RUST: Worker state record discarded: {}", e);
+                discarded = true;
+            }
+        }
+    }
+    let mut key = Zeroizing::new([0u8; 32]);
+    let mut iv = [0u8; WORKER_STATE_KEY_IV_SIZE];
+    getrandom::getrandom(&mut key[..])
+        .and_then(|_| getrandom::getrandom(&mut iv))
+        .map_err(|e| format!("Failed to generate worker state key: {}", e))?;
+    let Some(wrapped) = aes_gcm_with_wrap_key(true, &iv, &key[..]).await? else {
+        return Ok(false);
+    };
+    install_state_key(*key, base64_url_encode(&[&iv[..], &wrapped].concat()));
+    if discarded {
+        // Replaces the discarded record even if this request changes nothing
+        WORKER_STATE.with(|w| w.borrow_mut().snapshot = Some(Zeroizing::new(String::new())));
+    }
+    Ok(true)
+}
+
+async fn open_stored_record(record_json: &str) -> Result<bool, String> {
+    let record: WorkerStateRecord = serde_json::from_str(record_json)
+        .map_err(|e| format!("Invalid worker state record: {}", e))?;
+    let wrapped = base64_url_decode(&record.wrapped_state_key)
+        .map_err(|e| format!("Invalid wrapped state key: {}", e))?;
+    if wrapped.len() <= WORKER_STATE_KEY_IV_SIZE {
+        return Err("Wrapped state key is truncated".to_string());
+    }
+    let (iv, ciphertext) = wrapped.split_at(WORKER_STATE_KEY_IV_SIZE);
+    let Some(key) = aes_gcm_with_wrap_key(false, iv, ciphertext).await? else {
+        return Ok(false);
+    };
+    let key = Zeroizing::new(key);
+    let key: [u8; 32] = key
+        .as_slice()
+        .try_into()
+        .map_err(|_| "Wrapped state key has the wrong size".to_string())?;
+    install_state_key(key, record.wrapped_state_key.clone());
+    open_record(&record).inspect_err(|_| {
+        // A record that does not open takes its key along
+        WORKER_STATE.with(|w| *w.borrow_mut() = WorkerStateKeeper::default());
+    })?;
+    Ok(true)
+}
+
+/// The record to hand back to the TS layer, when the persisted state changed since the record
+/// was loaded or last exported. None while no state key is installed.
+pub fn take_changed_record() -> Option<WorkerStateRecord> {
+    let (key, wrapped) = WORKER_STATE.with(|w| {
+        w.borrow()
+            .key
+            .as_ref()
+            .map(|k| (k.key.clone(), k.wrapped.clone()))
+    })?;
+    let snapshot = match serde_json::to_string(&state::persisted_state()) {
+        Ok(snapshot) => Zeroizing::new(snapshot),
+        Err(e) => {
+            warn!("RUST: Failed to serialize worker state: {}", e);
+            return None;
+        }
+    };
+    let unchanged = WORKER_STATE.with(|w| {
+        w.borrow().snapshot.as_ref().map_or_else(
+            || *snapshot == serde_json::to_string(&PersistedState::default()).unwrap_or_default(),
+            |loaded| **loaded == *snapshot,
+        )
+    });
+    if unchanged {
+        return None;
+    }
+    let mut nonce = [0u8; 12];
+    if let Err(e) = getrandom::getrandom(&mut nonce) {
+        warn!("RUST: Failed to generate worker state nonce: {}", e);
+        return None;
+    }
+    let ciphertext = ChaCha20Poly1305::new(chacha20poly1305::Key::from_slice(&key[..]))
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: snapshot.as_bytes(),
+                aad: &state_aad(&wrapped),
+            },
+        )
+        .ok()?;
+    WORKER_STATE.with(|w| w.borrow_mut().snapshot = Some(snapshot));
+    Some(WorkerStateRecord {
+        version: WORKER_STATE_RECORD_VERSION,
+        wrapped_state_key: wrapped,
+        sealed_state: base64_url_encode(&[&nonce[..], &ciphertext].concat()),
+    })
+}
@@ -0,0 +1,108 @@
+// Generates $OUT_DIR/build_info.rs: the git commit, build profile and target, and the locked
+// versions of the crypto dependencies, reported in BuildInfo (see src/build_info.rs).
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Dependencies that handle key material or randomness
+const CRYPTO_DEPENDENCIES: &[&str] = &[
+    "chacha20poly1305",
+    "curve25519-dalek",
+    "ed25519-dalek",
+    "getrandom",
+    "hkdf",
+    "hmac",
+    "num-bigint",
+    "rand_core",
+    "sha2",
+    "vrf-wasm",
+    "zeroize",
+];
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8(output.stdout).ok()?.trim().to_string())
+}
+
+/// HEAD commit, suffixed with "-dirty" when tracked files in the crate differ from it.
+/// W3A_BUILD_GIT_COMMIT takes precedence (builds from a source tarball).
+fn git_commit() -> String {
+    if let Ok(commit) = env::var("W3A_BUILD_GIT_COMMIT") {
+        return commit;
+    }
+    let Some(commit) = git(&["rev-parse", "HEAD"]) else {
+        return "unknown".to_string();
+    };
+    let dirty = git(&["status", "--porcelain", "--untracked-files=no", "--", "."])
+        .is_some_and(|status| !status.is_empty());
+    if dirty {
+        format!("{}-dirty", commit)
+    } else {
+        commit
+    }
+}
+
+/// (name, version, checksum) of every locked package named in CRYPTO_DEPENDENCIES
+fn locked_crypto_dependencies(lockfile: &str) -> Vec<(String, String, Option<String>)> {
+    let mut dependencies = Vec::new();
+    for package in lockfile.split("[[package]]").skip(1) {
+        let field = |key: &str| {
+            package.lines().find_map(|line| {
+                let value = line.strip_prefix(key)?.trim().strip_prefix('=')?.trim();
+                Some(value.trim_matches('"').to_string())
+            })
+        };
+        let (Some(name), Some(version)) = (field("name "), field("version ")) else {
+            continue;
+        };
+        if CRYPTO_DEPENDENCIES.contains(&name.as_str()) {
+            dependencies.push((name, version, field("checksum ")));
+        }
+    }
+    dependencies.sort();
+    dependencies
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    // Source edits change the dirty flag, commits the HEAD ref
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-env-changed=W3A_BUILD_GIT_COMMIT");
+    if let Some(git_path) = git(&["rev-parse", "--git-path", "HEAD"]) {
+        println!("cargo:rerun-if-changed={}", git_path);
+    }
+    if let Some(head_ref) = git(&["symbolic-ref", "-q", "HEAD"]) {
+        if let Some(git_path) = git(&["rev-parse", "--git-path", &head_ref]) {
+            println!("cargo:rerun-if-changed={}", git_path);
+        }
+    }
+
+    let lockfile = fs::read_to_string("Cargo.lock").unwrap_or_default();
+    let dependencies = locked_crypto_dependencies(&lockfile)
+        .into_iter()
+        .map(|(name, version, checksum)| {
+            format!("    ({:?}, {:?}, {:?}),\n", name, version, checksum)
+        })
+        .collect::<String>();
+
+    let generated = format!(
+        "pub const GIT_COMMIT: &str = {:?};\n\
+         pub const BUILD_PROFILE: &str = {:?};\n\
+         pub const BUILD_TARGET: &str = {:?};\n\
+         /// (name, version, crates.io checksum)\n\
+         pub const CRYPTO_DEPENDENCIES: &[(&str, &str, Option<&str>)] = &[\n{}];\n",
+        git_commit(),
+        env::var("PROFILE").unwrap_or_default(),
+        env::var("TARGET").unwrap_or_default(),
+        dependencies,
+    );
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    fs::write(Path::new(&out_dir).join("build_info.rs"), generated)
+        .expect("failed to write build_info.rs");
+}
@@ -0,0 +1,193 @@
+// === BUILD ATTESTATION ===
+// What was built, for integrators verifying the worker they run: build.rs embeds the git commit,
+// build profile and target, and the locked versions and checksums of the crypto dependencies.
+//
+// The module's content hash cannot be compiled into the module itself, so it is defined over the
+// binary with its attestation sections removed: SHA-256 of every byte except custom sections
+// named `w3a_build_attestation`. The release build computes that hash and appends it to the
+// .wasm in such a section (the "embedded" hash). The loader passes the instantiated module's
+// bytes to `verify_build_attestation`, which rehashes them and compares against the embedded
+// hash and, when given, the hash from the SDK release manifest.
+
+use std::cell::RefCell;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::utils::{base64_url_decode, base64_url_encode};
+
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/build_info.rs"));
+}
+
+/// Name of the custom section carrying the expected attestation hash (base64url SHA-256)
+pub const ATTESTATION_SECTION_NAME: &str = "w3a_build_attestation";
+
+const WASM_MAGIC_AND_VERSION: [u8; 8] = [0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+const CUSTOM_SECTION_ID: u8 = 0;
+
+thread_local! {
+    /// Report of the loader's verify_build_attestation call, reported with PING
+    static MODULE_ATTESTATION: RefCell<Option<BuildAttestationReport>> = const { RefCell::new(None) };
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyVersion {
+    pub name: String,
+    pub version: String,
+    /// crates.io checksum from Cargo.lock (absent for path or git dependencies)
+    pub checksum: Option<String>,
+}
+
+/// Build metadata reported with PING (in `workerInfo.buildInfo`)
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildInfo {
+    pub crate_version: String,
+    /// HEAD at build time, "-dirty" when the crate had uncommitted changes
+    pub git_commit: String,
+    pub profile: String,
+    pub target: String,
+    pub crypto_dependencies: Vec<DependencyVersion>,
+    /// Attestation hash of the running module, once the loader has verified it
+    pub wasm_sha256_b64u: Option<String>,
+    pub attestation: Option<BuildAttestationReport>,
+}
+
+impl BuildInfo {
+    pub fn current() -> Self {
+        let attestation = MODULE_ATTESTATION.with(|report| report.borrow().clone());
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            git_commit: generated::GIT_COMMIT.to_string(),
+            profile: generated::BUILD_PROFILE.to_string(),
+            target: generated::BUILD_TARGET.to_string(),
+            crypto_dependencies: generated::CRYPTO_DEPENDENCIES
+                .iter()
+                .map(|(name, version, checksum)| DependencyVersion {
+                    name: name.to_string(),
+                    version: version.to_string(),
+                    checksum: checksum.map(str::to_string),
+                })
+                .collect(),
+            wasm_sha256_b64u: attestation
+                .as_ref()
+                .map(|report| report.actual_sha256_b64u.clone()),
+            attestation,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildAttestationReport {
+    /// The module hash matches every reference hash, and there was at least one
+    pub matches: bool,
+    pub actual_sha256_b64u: String,
+    /// From the caller (the SDK release manifest)
+    pub expected_sha256_b64u: Option<String>,
+    /// From the module's attestation section
+    pub embedded_sha256_b64u: Option<String>,
+    pub mismatches: Vec<String>,
+}
+
+fn read_leb128_u32(bytes: &[u8], pos: &mut usize) -> Result<u32, String> {
+    let mut value: u32 = 0;
+    for shift in (0..35).step_by(7) {
+        let byte = *bytes
+            .get(*pos)
+            .ok_or_else(|| format!("truncated LEB128 at offset {}", *pos))?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u32) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(format!("LEB128 longer than 5 bytes at offset {}", *pos))
+}
+
+/// Attestation hash of `module_bytes` and the hash its attestation section declares, if any
+pub fn attestation_hash(module_bytes: &[u8]) -> Result<(String, Option<String>), String> {
+    if !module_bytes.starts_with(&WASM_MAGIC_AND_VERSION) {
+        return Err("not a WebAssembly module".to_string());
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(WASM_MAGIC_AND_VERSION);
+    let mut embedded = None;
+    let mut pos = WASM_MAGIC_AND_VERSION.len();
+    while pos < module_bytes.len() {
+        let section_start = pos;
+        let id = module_bytes[pos];
+        pos += 1;
+        let size = read_leb128_u32(module_bytes, &mut pos)? as usize;
+        let payload_start = pos;
+        let section_end = payload_start
+            .checked_add(size)
+            .filter(|end| *end <= module_bytes.len())
+            .ok_or_else(|| format!("section at offset {} overruns the module", section_start))?;
+        pos = section_end;
+
+        if id == CUSTOM_SECTION_ID {
+            let mut name_pos = payload_start;
+            let name_len = read_leb128_u32(&module_bytes[..section_end], &mut name_pos)? as usize;
+            let name_end = name_pos
+                .checked_add(name_len)
+                .filter(|end| *end <= section_end)
+                .ok_or_else(|| {
+                    format!("custom section at offset {} is malformed", section_start)
+                })?;
+            if &module_bytes[name_pos..name_end] == ATTESTATION_SECTION_NAME.as_bytes() {
+                let declared = String::from_utf8_lossy(&module_bytes[name_end..section_end]);
+                embedded = Some(declared.trim().to_string());
+                continue;
+            }
+        }
+        hasher.update(&module_bytes[section_start..section_end]);
+    }
+    Ok((base64_url_encode(&hasher.finalize()), embedded))
+}
+
+fn is_sha256_b64u(hash: &str) -> bool {
+    base64_url_decode(hash).is_ok_and(|bytes| bytes.len() == 32)
+}
+
+/// Hashes `module_bytes` and compares against the embedded hash and `expected_sha256_b64u`
+pub fn verify_module_attestation(
+    module_bytes: &[u8],
+    expected_sha256_b64u: Option<&str>,
+) -> Result<BuildAttestationReport, String> {
+    let (actual, embedded) = attestation_hash(module_bytes)?;
+    let mut mismatches = Vec::new();
+    for (source, reference) in [
+        ("expected", expected_sha256_b64u),
+        ("embedded", embedded.as_deref()),
+    ] {
+        match reference {
+            Some(hash) if !is_sha256_b64u(hash) => mismatches.push(format!(
+                "{} hash {} is not a base64url SHA-256",
+                source, hash
+            )),
+            Some(hash) if hash != actual => mismatches.push(format!(
+                "{} hash {} does not match module hash {}",
+                source, hash, actual
+            )),
+            _ => {}
+        }
+    }
+    if expected_sha256_b64u.is_none() && embedded.is_none() {
+        mismatches.push("no expected or embedded hash to compare against".to_string());
+    }
+    Ok(BuildAttestationReport {
+        matches: mismatches.is_empty(),
+        actual_sha256_b64u: actual,
+        expected_sha256_b64u: expected_sha256_b64u.map(str::to_string),
+        embedded_sha256_b64u: embedded,
+        mismatches,
+    })
+}
+
+/// Records the loader's report so PING can return it
+pub fn record_module_attestation(report: BuildAttestationReport) {
+    MODULE_ATTESTATION.with(|recorded| *recorded.borrow_mut() = Some(report));
+}
@@ -0,0 +1,46 @@
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::config::{DEFAULT_CHALLENGE_LENGTH, MAX_CHALLENGE_LENGTH, MIN_CHALLENGE_LENGTH};
+use crate::errors::{VrfResult, VrfWorkerError};
+
+// === WEBAUTHN CHALLENGE DERIVATION ===
+// How the 64-byte VRF output becomes the WebAuthn challenge.
+// The signer worker mirrors this when binding the credential to the VRF challenge.
+
+/// Validates a requested challenge length, falling back to the default when absent
+pub fn resolve_challenge_length(requested: Option<u8>) -> VrfResult<u8> {
+    let length = requested.unwrap_or(DEFAULT_CHALLENGE_LENGTH);
+    if !(MIN_CHALLENGE_LENGTH..=MAX_CHALLENGE_LENGTH).contains(&length) {
+        return Err(VrfWorkerError::invalid_format(&format!(
+            "challengeLength must be between {} and {} bytes, got {}",
+            MIN_CHALLENGE_LENGTH, MAX_CHALLENGE_LENGTH, length
+        )));
+    }
+    Ok(length)
+}
+
+/// Derives the WebAuthn challenge of `challenge_length` bytes from the VRF output:
+/// - the default length is the raw VRF output prefix (what the contract verifies)
+/// - other lengths up to 32 bytes are a prefix of SHA-256(vrf_output)
+/// - longer lengths are a prefix of SHA-512(vrf_output)
+pub fn derive_webauthn_challenge(vrf_output: &[u8], challenge_length: u8) -> VrfResult<Vec<u8>> {
+    let length = resolve_challenge_length(Some(challenge_length))? as usize;
+    if challenge_length == DEFAULT_CHALLENGE_LENGTH {
+        return vrf_output
+            .get(..length)
+            .map(|prefix| prefix.to_vec())
+            .ok_or_else(|| {
+                VrfWorkerError::invalid_format(&format!(
+                    "VRF output too short: {} bytes",
+                    vrf_output.len()
+                ))
+            });
+    }
+
+    let digest = if length <= 32 {
+        Sha256::digest(vrf_output).to_vec()
+    } else {
+        Sha512::digest(vrf_output).to_vec()
+    };
+    Ok(digest[..length].to_vec())
+}
@@ -22,6 +22,11 @@ pub const LOG_INCLUDE_LEVEL: bool = true;
 /// Used to ensure VRF challenges are domain-specific and cannot be replayed across different contexts
 pub const VRF_DOMAIN_SEPARATOR: &[u8] = b"web3_authn_vrf_challenge_v1";
 
+/// Domain separator for VRF inputs built from a generic (non-NEAR) anchor.
+/// Neither separator is a prefix of the other, so generic and NEAR-anchored inputs
+/// differ from the first bytes on and can never produce the same preimage.
+pub const VRF_GENERIC_ANCHOR_DOMAIN_SEPARATOR: &[u8] = b"web3_authn_vrf_generic_anchor_v1";
+
 /// HKDF info string for ChaCha20 key derivation from PRF output
 /// Used for both VRF keypair encryption and general ChaCha20 operations
 pub const HKDF_CHACHA20_KEY_INFO: &[u8] = b"vrf-chacha20-key";
@@ -30,6 +35,16 @@ pub const HKDF_CHACHA20_KEY_INFO: &[u8] = b"vrf-chacha20-key";
 /// Used for deterministic VRF keypair generation during account recovery
 pub const HKDF_VRF_KEYPAIR_INFO: &[u8] = b"vrf-keypair-derivation-v1";
 
+/// Default WebAuthn challenge length in bytes.
+/// At this length the challenge is the raw prefix of the VRF output, which is what the contract verifies.
+pub const DEFAULT_CHALLENGE_LENGTH: u8 = 32;
+
+/// Minimum accepted WebAuthn challenge length in bytes (WebAuthn requires at least 16)
+pub const MIN_CHALLENGE_LENGTH: u8 = 16;
+
+/// Maximum accepted WebAuthn challenge length in bytes (SHA-512 digest size)
+pub const MAX_CHALLENGE_LENGTH: u8 = 64;
+
 // === ENCRYPTION PARAMETERS ===
 
 /// ChaCha20Poly1305 key size in bytes (256 bits)
@@ -38,6 +53,21 @@ pub const CHACHA20_KEY_SIZE: usize = 32;
 /// ChaCha20Poly1305 nonce/IV size in bytes (96 bits)
 pub const CHACHA20_NONCE_SIZE: usize = 12;
 
+/// Poly1305 authentication tag size in bytes (appended to every ciphertext)
+pub const CHACHA20_POLY1305_TAG_SIZE: usize = 16;
+
+/// EncryptedVRFKeypair format v1: ChaCha20Poly1305 with a 12-byte random nonce.
+/// Keypairs stored before versioning carry no version field and are treated as v1.
+pub const ENCRYPTED_BLOB_VERSION_V1: u32 = 1;
+
+/// EncryptedVRFKeypair format v2: v1 with an embedded `meta` bound as associated data
+/// (see wasm_shared/vrf_blob_meta.rs). Written by every release since; v1 blobs still decrypt.
+pub const ENCRYPTED_BLOB_VERSION_V2: u32 = 2;
+
+/// `derivationVersion` of blob metadata: HKDF-SHA256 seed from the PRF output salted with the
+/// account ID (generate_vrf_keypair_from_seed)
+pub const VRF_KEYPAIR_DERIVATION_VERSION: u32 = 1;
+
 /// VRF seed size in bytes for deterministic generation (256 bits)
 pub const VRF_SEED_SIZE: usize = 32;
 
@@ -48,6 +78,54 @@ pub const SHAMIR_AEAD_HKDF_INFO: &[u8] = b"web3authn-shamir3pass-kek-to-aead-key
 // Shamir 3-pass public parameters (base64url-encoded BigUint values)
 pub const SHAMIR_P_B64U: Option<&'static str> = option_env!("SHAMIR_P_B64U");
 
+// === KEYPAIR ENTROPY ===
+
+/// Size of each of the two RNG samples drawn before generating a bootstrap keypair
+pub const ENTROPY_SAMPLE_SIZE: usize = 32;
+
+/// Monobit bounds on the number of set bits in one sample: 6 standard deviations around the
+/// 128 expected of 256 random bits, so a healthy RNG practically never trips them
+pub const ENTROPY_MONOBIT_MIN_ONES: u32 = 80;
+pub const ENTROPY_MONOBIT_MAX_ONES: u32 = 176;
+
+/// HKDF info string for mixing the RNG samples and extra entropy into a keypair seed
+pub const HKDF_BOOTSTRAP_ENTROPY_INFO: &[u8] = b"web3authn-vrf-bootstrap-entropy-v1";
+
+// === SESSION SNAPSHOTS ===
+
+/// Session snapshot format version (2 adds the granted scopes, 3 the session limits and the
+/// signer session epoch)
+pub const SESSION_SNAPSHOT_VERSION: u32 = 3;
+
+/// How long an exported session snapshot can be restored (5 minutes)
+pub const SESSION_SNAPSHOT_MAX_AGE_MS: f64 = 5.0 * 60.0 * 1000.0;
+
+/// Size of the random snapshot identifier in bytes
+pub const SESSION_SNAPSHOT_ID_SIZE: usize = 16;
+
+/// Domain separator bound into the snapshot's associated data
+pub const SESSION_SNAPSHOT_AAD_DOMAIN: &[u8] = b"web3_authn_vrf_session_snapshot_v1";
+
+// === BATCH CHALLENGES ===
+
+/// Maximum number of inputs in one GENERATE_VRF_CHALLENGES_BATCH request
+pub const MAX_VRF_CHALLENGE_BATCH_SIZE: usize = 256;
+
+// === CHALLENGE PREFETCH ===
+
+/// How many blocks behind a signing request's block a prefetched challenge may be and still
+/// be used in place of a fresh proof
+pub const PREFETCHED_CHALLENGE_FRESHNESS_BLOCKS: u64 = 10;
+
+// === NEAR BLOCK ANCHORS ===
+
+/// Length of a NEAR block hash once base58-decoded
+pub const NEAR_BLOCK_HASH_SIZE: usize = 32;
+
+/// Block heights at or above this are refused as implausible (NEAR produces about one block a
+/// second, so this is centuries away)
+pub const MAX_PLAUSIBLE_BLOCK_HEIGHT: u64 = 10_000_000_000;
+
 // === SHAMIR 3-PASS CONFIGURATION ===
 
 /// Minimum prime size in bits for Shamir 3-pass security validation
@@ -77,6 +155,7 @@ pub mod vrf_challenge_fields {
     pub const RP_ID: &str = "rpId";
     pub const BLOCK_HEIGHT: &str = "blockHeight";
     pub const BLOCK_HASH: &str = "blockHash";
+    pub const CHALLENGE_LENGTH: &str = "challengeLength";
 }
 
 /// JSON field names for encrypted VRF keypair data
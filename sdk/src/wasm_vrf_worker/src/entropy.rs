@@ -0,0 +1,88 @@
+// === KEYPAIR GENERATION ENTROPY ===
+// Bootstrap keypairs are generated from randomness only, so a misconfigured getrandom (an
+// exotic host, a stubbed crypto.getRandomValues) would silently yield weak keys. Before each
+// generation the worker draws two independent samples and checks them: they must differ and
+// each must have a plausible number of set bits (monobit). The samples, and the caller's
+// `extraEntropyB64u` when given, are mixed with HKDF-SHA256 into the ChaCha20 seed the keypair
+// is generated from. A failed check refuses generation with InsecureRandomness.
+
+use getrandom::getrandom;
+use hkdf::Hkdf;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::config::{
+    ENTROPY_MONOBIT_MAX_ONES, ENTROPY_MONOBIT_MIN_ONES, ENTROPY_SAMPLE_SIZE,
+    HKDF_BOOTSTRAP_ENTROPY_INFO, VRF_SEED_SIZE,
+};
+use crate::errors::{VrfResult, VrfWorkerError};
+
+/// Entropy source name of the worker's own RNG
+pub const ENTROPY_SOURCE_GETRANDOM: &str = "getrandom";
+/// Entropy source name of the request's `extraEntropyB64u`
+pub const ENTROPY_SOURCE_EXTRA: &str = "extraEntropyB64u";
+
+/// A seed for keypair generation and the sources mixed into it
+pub struct KeypairSeed {
+    pub seed: Zeroizing<[u8; VRF_SEED_SIZE]>,
+    pub sources: Vec<String>,
+}
+
+/// Checks two RNG samples: they must differ and each pass the monobit check
+pub fn check_entropy_samples(first: &[u8], second: &[u8]) -> Result<(), String> {
+    if first == second {
+        return Err("two random samples are identical".to_string());
+    }
+    for (name, sample) in [("first", first), ("second", second)] {
+        let ones: u32 = sample.iter().map(|byte| byte.count_ones()).sum();
+        if !(ENTROPY_MONOBIT_MIN_ONES..=ENTROPY_MONOBIT_MAX_ONES).contains(&ones) {
+            return Err(format!(
+                "{} random sample has {} of {} bits set (expected {}..={})",
+                name,
+                ones,
+                sample.len() * 8,
+                ENTROPY_MONOBIT_MIN_ONES,
+                ENTROPY_MONOBIT_MAX_ONES
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// HKDF-SHA256 of both samples, salted with the extra entropy
+pub fn mix_entropy(
+    first: &[u8],
+    second: &[u8],
+    extra: Option<&[u8]>,
+) -> Zeroizing<[u8; VRF_SEED_SIZE]> {
+    let mut ikm = Zeroizing::new(Vec::with_capacity(first.len() + second.len()));
+    ikm.extend_from_slice(first);
+    ikm.extend_from_slice(second);
+    let hk = Hkdf::<Sha256>::new(extra, &ikm);
+    let mut seed = Zeroizing::new([0u8; VRF_SEED_SIZE]);
+    hk.expand(HKDF_BOOTSTRAP_ENTROPY_INFO, seed.as_mut())
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    seed
+}
+
+/// Draws and checks two samples from getrandom and mixes them with `extra` (empty is ignored)
+pub fn keypair_seed(extra: Option<&[u8]>) -> VrfResult<KeypairSeed> {
+    let mut first = Zeroizing::new([0u8; ENTROPY_SAMPLE_SIZE]);
+    let mut second = Zeroizing::new([0u8; ENTROPY_SAMPLE_SIZE]);
+    for sample in [&mut first, &mut second] {
+        getrandom(sample.as_mut())
+            .map_err(|e| VrfWorkerError::InsecureRandomness(format!("getrandom failed: {}", e)))?;
+    }
+    check_entropy_samples(first.as_ref(), second.as_ref())
+        .map_err(VrfWorkerError::InsecureRandomness)?;
+
+    let extra = extra.filter(|bytes| !bytes.is_empty());
+    let mut sources = vec![ENTROPY_SOURCE_GETRANDOM.to_string()];
+    if extra.is_some() {
+        sources.push(ENTROPY_SOURCE_EXTRA.to_string());
+    }
+    Ok(KeypairSeed {
+        seed: mix_entropy(first.as_ref(), second.as_ref(), extra),
+        sources,
+    })
+}
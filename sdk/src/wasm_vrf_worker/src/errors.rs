@@ -4,6 +4,9 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 use wasm_bindgen::JsValue;
 
+use crate::error_codes::{self, ErrorCodeDef};
+use crate::types::VrfScope;
+
 /// VRF Worker Error Types
 ///
 /// This module defines all error types used by the VRF worker,
@@ -46,6 +49,156 @@ pub enum VrfWorkerError {
 
     /// Block Height parsing error
     BlockHeightParsingError(String),
+
+    /// Session snapshot could not be restored
+    SessionSnapshot(SessionSnapshotError),
+
+    /// A startup known-answer test failed; key-handling requests are refused
+    SelfTestFailed { primitive: String },
+
+    /// REQUEST_CHALLENGE before the signer worker's port was connected (or after a wipe)
+    PeerPortNotConnected,
+
+    /// The RNG failed its health check before keypair generation
+    InsecureRandomness(String),
+
+    /// A NEAR anchor's blockHash is not 32 bytes of base58, or is not the block at its
+    /// blockHeight
+    InvalidBlockHash { reason: String },
+
+    /// The block of a NEAR anchor could not be looked up for the cross-check
+    BlockCrossCheckFailed(String),
+
+    /// The encrypted VRF keypair's metadata names another account than the one unlocking
+    BlobAccountMismatch { expected: String, actual: String },
+
+    /// The keypair was unlocked without the scope the operation needs
+    ScopeNotGranted { required: VrfScope },
+
+    /// The session unlocked at `started_at_ms` passed maxSessionDurationMs at `expired_at_ms`
+    SessionExpired {
+        started_at_ms: f64,
+        expired_at_ms: f64,
+    },
+}
+
+/// Stable error codes sent with failed responses (`errorCode`), so the TS layer does not
+/// have to match on messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VrfErrorCode {
+    /// No VRF keypair is loaded in memory
+    NoVrfKeypair,
+    /// The VRF keypair is not unlocked (no active session)
+    VrfNotUnlocked,
+    /// PRF output is empty or invalid
+    InvalidPrfOutput,
+    /// HKDF key derivation failed
+    HkdfDerivationFailed,
+    /// AES-GCM encryption or decryption of the VRF keypair failed
+    AesGcmFailed,
+    /// The AES-GCM IV has the wrong length
+    InvalidIvLength,
+    /// A VRF key or keypair could not be (de)serialized
+    SerializationFailed,
+    /// The VRF public key differs from the expected one
+    VrfPublicKeyMismatch,
+    /// The worker message does not parse
+    MessageParsingFailed,
+    /// A required field is missing from the worker message
+    MissingRequiredData,
+    /// The worker message is malformed
+    InvalidMessageFormat,
+    /// A block height does not parse
+    BlockHeightInvalid,
+    /// The session snapshot, or its session, has expired
+    SessionSnapshotExpired,
+    /// The session snapshot was already restored
+    SessionSnapshotReused,
+    /// The session snapshot was exported under another epoch (a wipe or an unloaded restart)
+    SessionSnapshotRevoked,
+    /// Wrong token, tampered snapshot or unsupported version
+    SessionSnapshotInvalid,
+    /// A startup known-answer test failed
+    SelfTestFailed,
+    /// REQUEST_CHALLENGE without a connected signer worker port
+    PeerPortNotConnected,
+    /// The RNG failed its health check; no keypair was generated
+    InsecureRandomness,
+    /// A blockHash is not 32 bytes of base58, or not the block at the given height
+    InvalidBlockHash,
+    /// The RPC lookup of the block to cross-check failed
+    BlockCrossCheckFailed,
+    /// The encrypted VRF keypair belongs to another account
+    BlobAccountMismatch,
+    /// The VRF keypair was unlocked without the operation's scope
+    ScopeNotGranted,
+    /// The session is past maxSessionDurationMs
+    SessionExpired,
+}
+
+impl VrfErrorCode {
+    /// Every code, for catalog tests
+    #[cfg(test)]
+    pub const ALL: [VrfErrorCode; 24] = [
+        VrfErrorCode::NoVrfKeypair,
+        VrfErrorCode::VrfNotUnlocked,
+        VrfErrorCode::InvalidPrfOutput,
+        VrfErrorCode::HkdfDerivationFailed,
+        VrfErrorCode::AesGcmFailed,
+        VrfErrorCode::InvalidIvLength,
+        VrfErrorCode::SerializationFailed,
+        VrfErrorCode::VrfPublicKeyMismatch,
+        VrfErrorCode::MessageParsingFailed,
+        VrfErrorCode::MissingRequiredData,
+        VrfErrorCode::InvalidMessageFormat,
+        VrfErrorCode::BlockHeightInvalid,
+        VrfErrorCode::SessionSnapshotExpired,
+        VrfErrorCode::SessionSnapshotReused,
+        VrfErrorCode::SessionSnapshotRevoked,
+        VrfErrorCode::SessionSnapshotInvalid,
+        VrfErrorCode::SelfTestFailed,
+        VrfErrorCode::PeerPortNotConnected,
+        VrfErrorCode::InsecureRandomness,
+        VrfErrorCode::InvalidBlockHash,
+        VrfErrorCode::BlockCrossCheckFailed,
+        VrfErrorCode::BlobAccountMismatch,
+        VrfErrorCode::ScopeNotGranted,
+        VrfErrorCode::SessionExpired,
+    ];
+
+    /// Catalog entry (src/wasm_shared/error_codes.rs); new codes need one there
+    pub fn definition(&self) -> &'static ErrorCodeDef {
+        match self {
+            VrfErrorCode::NoVrfKeypair => &error_codes::NO_VRF_KEYPAIR,
+            VrfErrorCode::VrfNotUnlocked => &error_codes::VRF_NOT_UNLOCKED,
+            VrfErrorCode::InvalidPrfOutput => &error_codes::INVALID_PRF_OUTPUT,
+            VrfErrorCode::HkdfDerivationFailed => &error_codes::HKDF_DERIVATION_FAILED,
+            VrfErrorCode::AesGcmFailed => &error_codes::AES_GCM_FAILED,
+            VrfErrorCode::InvalidIvLength => &error_codes::INVALID_IV_LENGTH,
+            VrfErrorCode::SerializationFailed => &error_codes::SERIALIZATION_FAILED,
+            VrfErrorCode::VrfPublicKeyMismatch => &error_codes::VRF_PUBLIC_KEY_MISMATCH,
+            VrfErrorCode::MessageParsingFailed => &error_codes::MESSAGE_PARSING_FAILED,
+            VrfErrorCode::MissingRequiredData => &error_codes::MISSING_REQUIRED_DATA,
+            VrfErrorCode::InvalidMessageFormat => &error_codes::INVALID_MESSAGE_FORMAT,
+            VrfErrorCode::BlockHeightInvalid => &error_codes::BLOCK_HEIGHT_INVALID,
+            VrfErrorCode::SessionSnapshotExpired => &error_codes::SESSION_SNAPSHOT_EXPIRED,
+            VrfErrorCode::SessionSnapshotReused => &error_codes::SESSION_SNAPSHOT_REUSED,
+            VrfErrorCode::SessionSnapshotRevoked => &error_codes::SESSION_SNAPSHOT_REVOKED,
+            VrfErrorCode::SessionSnapshotInvalid => &error_codes::SESSION_SNAPSHOT_INVALID,
+            VrfErrorCode::SelfTestFailed => &error_codes::SELF_TEST_FAILED,
+            VrfErrorCode::PeerPortNotConnected => &error_codes::PEER_PORT_NOT_CONNECTED,
+            VrfErrorCode::InsecureRandomness => &error_codes::INSECURE_RANDOMNESS,
+            VrfErrorCode::InvalidBlockHash => &error_codes::INVALID_BLOCK_HASH,
+            VrfErrorCode::BlockCrossCheckFailed => &error_codes::BLOCK_CROSS_CHECK_FAILED,
+            VrfErrorCode::BlobAccountMismatch => &error_codes::BLOB_ACCOUNT_MISMATCH,
+            VrfErrorCode::ScopeNotGranted => &error_codes::SCOPE_NOT_GRANTED,
+            VrfErrorCode::SessionExpired => &error_codes::SESSION_EXPIRED,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.definition().code
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -82,6 +235,32 @@ pub enum SerializationError {
     Base64Error(String),
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SessionSnapshotError {
+    /// Older than SESSION_SNAPSHOT_MAX_AGE_MS (or dated in the future), or of a session past
+    /// its autoLockAfterMs or maxSessionDurationMs
+    Expired,
+    /// Already restored once
+    Reused,
+    /// Exported under another epoch: before the last WipeAllState, by a worker whose
+    /// revocation state was not loaded, or in an earlier signer session epoch
+    Revoked,
+    /// Wrong token, tampered snapshot or unsupported version
+    Invalid(String),
+}
+
+impl SessionSnapshotError {
+    /// Stable code prefixed to the error message
+    pub fn code(&self) -> &'static str {
+        match self {
+            SessionSnapshotError::Expired => "SessionSnapshotExpired",
+            SessionSnapshotError::Reused => "SessionSnapshotReused",
+            SessionSnapshotError::Revoked => "SessionSnapshotRevoked",
+            SessionSnapshotError::Invalid(_) => "SessionSnapshotInvalid",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MessageError {
     JsonParsingFailed(String),
@@ -139,6 +318,47 @@ impl fmt::Display for VrfWorkerError {
             VrfWorkerError::BlockHeightParsingError(msg) => {
                 write!(f, "Block height parsing error: {}", msg)
             }
+            VrfWorkerError::SessionSnapshot(err) => write!(f, "{}: {}", err.code(), err),
+            VrfWorkerError::SelfTestFailed { primitive } => write!(
+                f,
+                "SelfTestFailed: {} known-answer test failed; key-handling requests are refused until the worker is restarted",
+                primitive
+            ),
+            VrfWorkerError::PeerPortNotConnected => write!(
+                f,
+                "PeerPortNotConnected: no signer worker port is connected - send CONNECT_PEER_PORT first"
+            ),
+            VrfWorkerError::InsecureRandomness(msg) => write!(
+                f,
+                "InsecureRandomness: {}; refusing to generate a VRF keypair",
+                msg
+            ),
+            VrfWorkerError::InvalidBlockHash { reason } => {
+                write!(f, "InvalidBlockHash: invalid blockHash: {}", reason)
+            }
+            VrfWorkerError::BlockCrossCheckFailed(msg) => write!(
+                f,
+                "BlockCrossCheckFailed: could not look up the blockHash to cross-check: {}",
+                msg
+            ),
+            VrfWorkerError::BlobAccountMismatch { expected, actual } => write!(
+                f,
+                "BlobAccountMismatch: the encrypted VRF keypair belongs to {}, not {}",
+                actual, expected
+            ),
+            VrfWorkerError::ScopeNotGranted { required } => write!(
+                f,
+                "ScopeNotGranted: the VRF keypair was unlocked without the {} scope; unlock it again to grant it",
+                required
+            ),
+            VrfWorkerError::SessionExpired {
+                started_at_ms,
+                expired_at_ms,
+            } => write!(
+                f,
+                "SessionExpired: the session unlocked at {} expired at {}; unlock it again to start a new one",
+                started_at_ms, expired_at_ms
+            ),
         }
     }
 }
@@ -188,6 +408,24 @@ impl fmt::Display for SerializationError {
     }
 }
 
+impl fmt::Display for SessionSnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionSnapshotError::Expired => write!(f, "Session snapshot has expired"),
+            SessionSnapshotError::Reused => {
+                write!(f, "Session snapshot has already been restored")
+            }
+            SessionSnapshotError::Revoked => {
+                write!(
+                    f,
+                    "Session snapshot was revoked by a state wipe, worker restart or new signer session"
+                )
+            }
+            SessionSnapshotError::Invalid(msg) => write!(f, "Invalid session snapshot: {}", msg),
+        }
+    }
+}
+
 impl fmt::Display for MessageError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -203,6 +441,7 @@ impl std::error::Error for VrfWorkerError {}
 impl std::error::Error for HkdfError {}
 impl std::error::Error for AesError {}
 impl std::error::Error for SerializationError {}
+impl std::error::Error for SessionSnapshotError {}
 // impl std::error::Error for VrfCryptoError {}
 impl std::error::Error for MessageError {}
 
@@ -246,6 +485,37 @@ pub type VrfResult<T> = Result<T, VrfWorkerError>;
 
 // Helper functions for creating specific errors
 impl VrfWorkerError {
+    pub fn code(&self) -> VrfErrorCode {
+        match self {
+            VrfWorkerError::NoVrfKeypair => VrfErrorCode::NoVrfKeypair,
+            VrfWorkerError::VrfNotUnlocked => VrfErrorCode::VrfNotUnlocked,
+            VrfWorkerError::InvalidPrfOutput(_) => VrfErrorCode::InvalidPrfOutput,
+            VrfWorkerError::HkdfDerivationFailed(_) => VrfErrorCode::HkdfDerivationFailed,
+            VrfWorkerError::AesGcmError(_) => VrfErrorCode::AesGcmFailed,
+            VrfWorkerError::InvalidIvLength { .. } => VrfErrorCode::InvalidIvLength,
+            VrfWorkerError::SerializationError(_) => VrfErrorCode::SerializationFailed,
+            VrfWorkerError::PublicKeyMismatch { .. } => VrfErrorCode::VrfPublicKeyMismatch,
+            VrfWorkerError::MessageParsingError(_) => VrfErrorCode::MessageParsingFailed,
+            VrfWorkerError::MissingRequiredData(_) => VrfErrorCode::MissingRequiredData,
+            VrfWorkerError::InvalidMessageFormat(_) => VrfErrorCode::InvalidMessageFormat,
+            VrfWorkerError::BlockHeightParsingError(_) => VrfErrorCode::BlockHeightInvalid,
+            VrfWorkerError::SessionSnapshot(err) => match err {
+                SessionSnapshotError::Expired => VrfErrorCode::SessionSnapshotExpired,
+                SessionSnapshotError::Reused => VrfErrorCode::SessionSnapshotReused,
+                SessionSnapshotError::Revoked => VrfErrorCode::SessionSnapshotRevoked,
+                SessionSnapshotError::Invalid(_) => VrfErrorCode::SessionSnapshotInvalid,
+            },
+            VrfWorkerError::SelfTestFailed { .. } => VrfErrorCode::SelfTestFailed,
+            VrfWorkerError::PeerPortNotConnected => VrfErrorCode::PeerPortNotConnected,
+            VrfWorkerError::InsecureRandomness(_) => VrfErrorCode::InsecureRandomness,
+            VrfWorkerError::InvalidBlockHash { .. } => VrfErrorCode::InvalidBlockHash,
+            VrfWorkerError::BlockCrossCheckFailed(_) => VrfErrorCode::BlockCrossCheckFailed,
+            VrfWorkerError::BlobAccountMismatch { .. } => VrfErrorCode::BlobAccountMismatch,
+            VrfWorkerError::ScopeNotGranted { .. } => VrfErrorCode::ScopeNotGranted,
+            VrfWorkerError::SessionExpired { .. } => VrfErrorCode::SessionExpired,
+        }
+    }
+
     pub fn empty_prf_output() -> Self {
         VrfWorkerError::InvalidPrfOutput("PRF output cannot be empty".to_string())
     }
@@ -258,6 +528,12 @@ impl VrfWorkerError {
         VrfWorkerError::InvalidMessageFormat(msg.to_string())
     }
 
+    pub fn invalid_block_hash(reason: &str) -> Self {
+        VrfWorkerError::InvalidBlockHash {
+            reason: reason.to_string(),
+        }
+    }
+
     pub fn public_key_mismatch(expected: &str, actual: &str) -> Self {
         VrfWorkerError::PublicKeyMismatch {
             expected: expected.to_string(),
@@ -0,0 +1,163 @@
+use crate::challenge::resolve_challenge_length;
+use crate::issuance_receipt::IssuanceReceiptKey;
+use crate::manager::{ChallengePrefetch, VRFKeyManager};
+use crate::types::VrfWorkerResponse;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UpdateBlockInfoRequest {
+    #[wasm_bindgen(getter_with_clone, js_name = "blockHeight")]
+    #[serde(rename = "blockHeight")]
+    pub block_height: String,
+    /// Base58 block hash
+    #[wasm_bindgen(getter_with_clone, js_name = "blockHash")]
+    #[serde(rename = "blockHash")]
+    pub block_hash: String,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ConfigureSessionOptionsRequest {
+    /// Keep a challenge proven for the latest block pushed with UPDATE_BLOCK_INFO
+    #[wasm_bindgen(js_name = "prefetchChallenge")]
+    #[serde(rename = "prefetchChallenge", default)]
+    pub prefetch_challenge: bool,
+    /// User of the prefetched challenge (required with prefetchChallenge)
+    #[wasm_bindgen(getter_with_clone, js_name = "userId")]
+    #[serde(rename = "userId", default)]
+    pub user_id: Option<String>,
+    /// rpId of the prefetched challenge (required with prefetchChallenge)
+    #[wasm_bindgen(getter_with_clone, js_name = "rpId")]
+    #[serde(rename = "rpId", default)]
+    pub rp_id: Option<String>,
+    /// WebAuthn challenge length in bytes (16-64, defaults to 32)
+    #[wasm_bindgen(js_name = "challengeLength")]
+    #[serde(rename = "challengeLength", default)]
+    pub challenge_length: Option<u8>,
+    /// Lock the session this long after unlock, counting wall-clock time (including time the
+    /// page spent frozen between SUSPEND_HINT and RESUME_HINT)
+    #[wasm_bindgen(js_name = "autoLockAfterMs")]
+    #[serde(rename = "autoLockAfterMs", default)]
+    pub auto_lock_after_ms: Option<f64>,
+    /// 32-byte key (base64url) the integrator shares with its backend: while set, challenges
+    /// carry `issuanceReceiptB64u`, checked with verify_issuance_receipt
+    #[wasm_bindgen(getter_with_clone, js_name = "issuanceReceiptKeyB64u")]
+    #[serde(rename = "issuanceReceiptKeyB64u", default)]
+    pub issuance_receipt_key_b64u: Option<String>,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UpdateSessionPolicyRequest {
+    /// The signer worker's session epoch (GetSessionStatus)
+    #[wasm_bindgen(js_name = "sessionEpoch")]
+    #[serde(rename = "sessionEpoch")]
+    pub session_epoch: u32,
+    /// The signer worker's WorkerPolicy.maxSessionDurationMs; sessions do not expire without it
+    #[wasm_bindgen(js_name = "maxSessionDurationMs")]
+    #[serde(rename = "maxSessionDurationMs", default)]
+    pub max_session_duration_ms: Option<f64>,
+}
+
+/// Handle UPDATE_BLOCK_INFO message: a push from the main thread's block stream.
+/// Responds with `{ prefetched }`, whether a challenge was proven for the block.
+pub fn handle_update_block_info(
+    manager: Rc<RefCell<VRFKeyManager>>,
+    message_id: Option<String>,
+    payload: UpdateBlockInfoRequest,
+) -> VrfWorkerResponse {
+    let mut manager_mut = manager.borrow_mut();
+    match manager_mut.update_block_info(&payload.block_height, &payload.block_hash) {
+        Ok(prefetched) => VrfWorkerResponse::success(
+            message_id,
+            Some(serde_json::json!({ "prefetched": prefetched })),
+        ),
+        Err(e) => {
+            warn!("Block info update rejected: {}", e);
+            VrfWorkerResponse::from_error(message_id, &e)
+        }
+    }
+}
+
+/// Handle CONFIGURE_SESSION_OPTIONS message. Options last until LOGOUT.
+/// Responds with `{ prefetched }`, whether a challenge was proven right away.
+pub fn handle_configure_session_options(
+    manager: Rc<RefCell<VRFKeyManager>>,
+    message_id: Option<String>,
+    payload: ConfigureSessionOptionsRequest,
+) -> VrfWorkerResponse {
+    let prefetch = if payload.prefetch_challenge {
+        let challenge_length = match resolve_challenge_length(payload.challenge_length) {
+            Ok(length) => length,
+            Err(e) => return VrfWorkerResponse::from_error(message_id, &e),
+        };
+        match (payload.user_id, payload.rp_id) {
+            (Some(user_id), Some(rp_id)) if !user_id.is_empty() && !rp_id.is_empty() => {
+                Some(ChallengePrefetch {
+                    user_id,
+                    rp_id,
+                    challenge_length,
+                })
+            }
+            _ => {
+                return VrfWorkerResponse::fail(
+                    message_id,
+                    "prefetchChallenge requires userId and rpId",
+                )
+            }
+        }
+    } else {
+        None
+    };
+    if matches!(payload.auto_lock_after_ms, Some(ms) if !(ms.is_finite() && ms > 0.0)) {
+        return VrfWorkerResponse::fail(
+            message_id,
+            "autoLockAfterMs must be a positive number of milliseconds",
+        );
+    }
+    let issuance_receipt_key = match payload.issuance_receipt_key_b64u.as_deref() {
+        Some(key_b64u) => match IssuanceReceiptKey::from_b64u(key_b64u) {
+            Ok(key) => Some(key),
+            Err(e) => return VrfWorkerResponse::fail(message_id, e),
+        },
+        None => None,
+    };
+
+    let mut manager_mut = manager.borrow_mut();
+    manager_mut.auto_lock_after_ms = payload.auto_lock_after_ms;
+    manager_mut.issuance_receipt_key = issuance_receipt_key;
+    match manager_mut.configure_challenge_prefetch(prefetch) {
+        Ok(prefetched) => VrfWorkerResponse::success(
+            message_id,
+            Some(serde_json::json!({ "prefetched": prefetched })),
+        ),
+        Err(e) => VrfWorkerResponse::from_error(message_id, &e),
+    }
+}
+
+/// Handle UPDATE_SESSION_POLICY message: the signer session this VRF session belongs to.
+/// Responds with `{ snapshotsRevoked }`, whether the signer epoch moved on since the last push.
+pub fn handle_update_session_policy(
+    manager: Rc<RefCell<VRFKeyManager>>,
+    message_id: Option<String>,
+    payload: UpdateSessionPolicyRequest,
+) -> VrfWorkerResponse {
+    if matches!(payload.max_session_duration_ms, Some(ms) if !(ms.is_finite() && ms > 0.0)) {
+        return VrfWorkerResponse::fail(
+            message_id,
+            "maxSessionDurationMs must be a positive number of milliseconds",
+        );
+    }
+    let snapshots_revoked = manager
+        .borrow_mut()
+        .update_session_policy(payload.session_epoch, payload.max_session_duration_ms);
+    VrfWorkerResponse::success(
+        message_id,
+        Some(serde_json::json!({ "snapshotsRevoked": snapshots_revoked })),
+    )
+}
@@ -1,21 +1,15 @@
-use wasm_bindgen::prelude::*;
 use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::rc::Rc;
+use wasm_bindgen::prelude::*;
 
 use crate::config::CHACHA20_KEY_SIZE;
 use crate::handlers::handle_shamir3pass_client::{
-    perform_shamir3pass_client_encrypt_current_vrf_keypair,
-    Shamir3PassEncryptVrfKeypairResult,
+    perform_shamir3pass_client_encrypt_current_vrf_keypair, Shamir3PassEncryptVrfKeypairResult,
 };
 use crate::manager::VRFKeyManager;
-use crate::types::{
-    EncryptedVRFKeypair,
-    VRFChallengeData,
-    VRFInputData,
-    VrfWorkerResponse,
-};
+use crate::types::{EncryptedVRFKeypair, VRFChallengeData, VRFInputData, VrfWorkerResponse};
 use crate::utils::base64_url_decode;
 
 #[wasm_bindgen]
@@ -27,6 +21,14 @@ pub struct DeriveVrfKeypairFromPrfRequest {
     #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
     #[serde(rename = "nearAccountId")]
     pub near_account_id: String,
+    /// Recorded in the encrypted keypair's metadata
+    #[wasm_bindgen(getter_with_clone, js_name = "credentialIdB64u")]
+    #[serde(default, rename = "credentialIdB64u")]
+    pub credential_id_b64u: Option<String>,
+    /// Recorded in the encrypted keypair's metadata
+    #[wasm_bindgen(getter_with_clone, js_name = "deviceNumber")]
+    #[serde(default, rename = "deviceNumber")]
+    pub device_number: Option<u32>,
     #[wasm_bindgen(getter_with_clone, js_name = "saveInMemory")]
     #[serde(default = "default_true", rename = "saveInMemory")]
     pub save_in_memory: bool,
@@ -88,12 +90,14 @@ pub async fn handle_derive_vrf_keypair_from_prf(
         match manager_ref.derive_vrf_keypair_from_prf(
             prf_output,
             payload.near_account_id.clone(),
+            payload.credential_id_b64u.clone(),
+            payload.device_number,
             payload.vrf_input_data.clone(),
         ) {
             Ok((result, keypair)) => (result, keypair),
             Err(e) => {
                 error!("VRF keypair derivation failed: {}", e);
-                return VrfWorkerResponse::fail(message_id, e.to_string());
+                return VrfWorkerResponse::from_error(message_id, &e);
             }
         }
     };
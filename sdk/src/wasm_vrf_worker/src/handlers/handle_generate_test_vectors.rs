@@ -0,0 +1,151 @@
+use crate::config::DEFAULT_CHALLENGE_LENGTH;
+use crate::errors::VrfResult;
+use crate::manager::VRFKeyManager;
+use crate::types::{VRFInputData, VrfWorkerResponse};
+use crate::utils::base64_url_encode;
+use crate::vrf_input::build_vrf_input;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+/// Upper bound on vectors per request (each vector derives a keypair and a proof)
+pub const MAX_TEST_VECTORS: u32 = 256;
+
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GenerateTestVectorsRequest {
+    pub count: u32,
+    #[wasm_bindgen(getter_with_clone)]
+    pub seed: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VrfTestVector {
+    pub index: u32,
+    /// PRF-output stand-in the keypair is derived from (same HKDF path as account recovery)
+    pub prf_output_b64u: String,
+    pub vrf_public_key: String,
+    pub user_id: String,
+    pub rp_id: String,
+    pub block_height: String,
+    pub block_hash: String,
+    pub input_bytes_b64u: String,
+    pub vrf_input: String,
+    pub vrf_output: String,
+    pub vrf_proof: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VrfTestVectorSet {
+    pub seed: String,
+    pub count: u32,
+    pub domain_tag: String,
+    pub vectors: Vec<VrfTestVector>,
+}
+
+/// Derives 32 deterministic bytes for vector `index` from the seed and a label
+fn seeded_bytes(seed: &str, label: &str, index: u32) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(seed.as_bytes());
+    hasher.update(label.as_bytes());
+    hasher.update(index.to_le_bytes());
+    hasher.finalize().into()
+}
+
+/// Generates `count` deterministic VRF test vectors from `seed`.
+/// Same (count, seed) always yields identical vectors: keypairs come from the recovery HKDF path
+/// and ECVRF proofs use deterministic nonces.
+pub fn generate_test_vectors(
+    manager: &VRFKeyManager,
+    count: u32,
+    seed: &str,
+) -> VrfResult<VrfTestVectorSet> {
+    let mut vectors = Vec::with_capacity(count as usize);
+    let mut domain_tag = String::new();
+
+    for index in 0..count {
+        let prf_output = seeded_bytes(seed, "prf", index);
+        let block_hash_bytes = seeded_bytes(seed, "blockHash", index);
+        let height_bytes = seeded_bytes(seed, "blockHeight", index);
+        let block_height = u32::from_le_bytes([
+            height_bytes[0],
+            height_bytes[1],
+            height_bytes[2],
+            height_bytes[3],
+        ]);
+
+        let block_height = block_height.to_string();
+        let block_hash = bs58::encode(block_hash_bytes).into_string();
+        let input_data = VRFInputData::near_block(
+            &format!("vector-{}.testnet", index),
+            "example.localhost",
+            &block_height,
+            &block_hash,
+        );
+
+        let construction = build_vrf_input(&input_data)?;
+        domain_tag = construction.domain_tag.clone();
+
+        let keypair = manager.generate_vrf_keypair_from_seed(&prf_output, &input_data.user_id)?;
+        let challenge = manager.generate_vrf_challenge_with_keypair(
+            &keypair,
+            input_data.clone(),
+            DEFAULT_CHALLENGE_LENGTH,
+        )?;
+
+        vectors.push(VrfTestVector {
+            index,
+            prf_output_b64u: base64_url_encode(&prf_output),
+            vrf_public_key: challenge.vrf_public_key,
+            user_id: input_data.user_id,
+            rp_id: input_data.rp_id,
+            block_height,
+            block_hash,
+            input_bytes_b64u: construction.input_bytes_b64u,
+            vrf_input: challenge.vrf_input,
+            vrf_output: challenge.vrf_output,
+            vrf_proof: challenge.vrf_proof,
+        });
+    }
+
+    Ok(VrfTestVectorSet {
+        seed: seed.to_string(),
+        count,
+        domain_tag,
+        vectors,
+    })
+}
+
+/// Handle GENERATE_TEST_VECTORS message
+///
+/// Produces deterministic keypairs, inputs, outputs and proofs for cross-language
+/// conformance suites. Does not touch the in-memory VRF keypair.
+pub fn handle_generate_test_vectors(
+    manager: Rc<RefCell<VRFKeyManager>>,
+    message_id: Option<String>,
+    payload: GenerateTestVectorsRequest,
+) -> VrfWorkerResponse {
+    if payload.count == 0 || payload.count > MAX_TEST_VECTORS {
+        return VrfWorkerResponse::fail(
+            message_id,
+            format!("count must be between 1 and {}", MAX_TEST_VECTORS),
+        );
+    }
+
+    let manager_ref = manager.borrow();
+    match generate_test_vectors(&manager_ref, payload.count, &payload.seed) {
+        Ok(vector_set) => {
+            info!("Generated {} VRF test vectors", vector_set.count);
+            VrfWorkerResponse::success(message_id, Some(serde_json::to_value(&vector_set).unwrap()))
+        }
+        Err(e) => {
+            error!("VRF test vector generation failed: {}", e);
+            VrfWorkerResponse::from_error(message_id, &e)
+        }
+    }
+}
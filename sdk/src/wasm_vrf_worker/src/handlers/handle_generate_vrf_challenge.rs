@@ -1,7 +1,15 @@
+use crate::challenge::resolve_challenge_length;
+use crate::clock::Clock;
+use crate::config::MAX_VRF_CHALLENGE_BATCH_SIZE;
+use crate::errors::{VrfResult, VrfWorkerError};
+use crate::http::fetch_near_block_header;
 use crate::manager::VRFKeyManager;
-use crate::types::VRFInputData;
+use crate::parallel;
+use crate::types::http::NearBlockHeader;
 use crate::types::VrfWorkerResponse;
-use log::{error, info};
+use crate::types::{VRFChallengeData, VRFInputData, VrfAnchor};
+use crate::utils::{decode_block_hash, parse_block_height};
+use log::{debug, error, info};
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -13,27 +21,231 @@ pub struct GenerateVrfChallengeRequest {
     #[wasm_bindgen(getter_with_clone, js_name = "vrfInputData")]
     #[serde(rename = "vrfInputData")]
     pub vrf_input_data: VRFInputData,
+    /// WebAuthn challenge length in bytes (16-64, defaults to 32)
+    #[wasm_bindgen(js_name = "challengeLength")]
+    #[serde(rename = "challengeLength", default)]
+    pub challenge_length: Option<u8>,
+    /// Accept the session's prefetched challenge (for a recent block rather than the requested
+    /// one) when it is still fresh; see CONFIGURE_SESSION_OPTIONS
+    #[wasm_bindgen(js_name = "allowPrefetched")]
+    #[serde(rename = "allowPrefetched", default)]
+    pub allow_prefetched: Option<bool>,
+    /// NEAR RPC to look the anchor's blockHash up on before proving: the request fails unless
+    /// the RPC reports that block at the anchor's blockHeight
+    #[wasm_bindgen(getter_with_clone, js_name = "crossCheckRpcUrl")]
+    #[serde(rename = "crossCheckRpcUrl", default)]
+    pub cross_check_rpc_url: Option<String>,
 }
 
-/// Handle GENERATE_VRF_CHALLENGE message
+/// Reported with GENERATE_VRF_CHALLENGE results as `timings`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VrfChallengeTimings {
+    pub used_prefetched_challenge: bool,
+    /// Time spent answering the request
+    pub duration_ms: f64,
+}
+
+/// Checks a NEAR anchor against the header the RPC returned for its blockHash (None when the
+/// RPC does not know the block). Generic anchors have no block to check.
+pub fn check_block_header(input: &VRFInputData, header: Option<NearBlockHeader>) -> VrfResult<()> {
+    let VrfAnchor::NearBlock {
+        block_height,
+        block_hash,
+    } = &input.anchor
+    else {
+        return Ok(());
+    };
+    let height = parse_block_height(block_height)?;
+    let header = header.ok_or_else(|| {
+        VrfWorkerError::invalid_block_hash(&format!("block {} is unknown to the RPC", block_hash))
+    })?;
+    if header.hash != *block_hash {
+        return Err(VrfWorkerError::invalid_block_hash(&format!(
+            "the RPC returned block {} for {}",
+            header.hash, block_hash
+        )));
+    }
+    if header.height != height {
+        return Err(VrfWorkerError::invalid_block_hash(&format!(
+            "block {} is at height {}, not blockHeight {}",
+            block_hash, header.height, height
+        )));
+    }
+    Ok(())
+}
+
+/// With `crossCheckRpcUrl`, looks the anchor's block up by hash and checks it is at the anchor's
+/// height, catching height/hash mismatches before they fail on-chain
+pub async fn cross_check_vrf_input_block(payload: &GenerateVrfChallengeRequest) -> VrfResult<()> {
+    let (
+        Some(rpc_url),
+        VrfAnchor::NearBlock {
+            block_height,
+            block_hash,
+        },
+    ) = (&payload.cross_check_rpc_url, &payload.vrf_input_data.anchor)
+    else {
+        return Ok(());
+    };
+    // Malformed anchors fail the same way with or without the cross-check
+    parse_block_height(block_height)?;
+    decode_block_hash(block_hash)?;
+    let header = fetch_near_block_header(rpc_url, block_hash)
+        .await
+        .map_err(VrfWorkerError::BlockCrossCheckFailed)?;
+    check_block_header(&payload.vrf_input_data, header)?;
+    debug!(
+        "blockHash {} cross-checked at height {}",
+        block_hash, block_height
+    );
+    Ok(())
+}
+
+/// Handle GENERATE_VRF_CHALLENGE message (after cross_check_vrf_input_block)
 pub fn handle_generate_vrf_challenge(
     manager: Rc<RefCell<VRFKeyManager>>,
     message_id: Option<String>,
     payload: GenerateVrfChallengeRequest,
 ) -> VrfWorkerResponse {
+    let challenge_length = match resolve_challenge_length(payload.challenge_length) {
+        Ok(length) => length,
+        Err(e) => return VrfWorkerResponse::from_error(message_id, &e),
+    };
+
     let manager_ref = manager.borrow();
+    let started = manager_ref.clock.monotonic_ms();
+    let prefetched = if payload.allow_prefetched.unwrap_or(false) {
+        manager_ref.prefetched_challenge_for(&payload.vrf_input_data, challenge_length)
+    } else {
+        None
+    };
+    let used_prefetched_challenge = prefetched.is_some();
+    let result = match prefetched {
+        Some(challenge_data) => Ok(challenge_data),
+        None => manager_ref.generate_vrf_challenge(payload.vrf_input_data, challenge_length),
+    }
+    .and_then(|challenge| manager_ref.with_issuance_receipt(challenge, message_id.as_deref()));
 
-    return match manager_ref.generate_vrf_challenge(payload.vrf_input_data) {
+    match result {
         Ok(challenge_data) => {
-            info!("VRF challenge generated successfully");
-            VrfWorkerResponse::success(
-                message_id,
-                Some(serde_json::to_value(&challenge_data).unwrap()),
-            )
+            info!(
+                "VRF challenge {}",
+                if used_prefetched_challenge {
+                    "served from prefetch"
+                } else {
+                    "generated successfully"
+                }
+            );
+            let timings = VrfChallengeTimings {
+                used_prefetched_challenge,
+                duration_ms: manager_ref.clock.monotonic_ms() - started,
+            };
+            let mut data = serde_json::to_value(&challenge_data).unwrap();
+            data["timings"] = serde_json::to_value(&timings).unwrap();
+            VrfWorkerResponse::success(message_id, Some(data))
         }
         Err(e) => {
             error!("VRF challenge generation failed: {}", e);
-            VrfWorkerResponse::fail(message_id, e.to_string())
+            VrfWorkerResponse::from_error(message_id, &e)
         }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GenerateVrfChallengesBatchRequest {
+    #[wasm_bindgen(getter_with_clone, js_name = "vrfInputs")]
+    #[serde(rename = "vrfInputs")]
+    pub vrf_inputs: Vec<VRFInputData>,
+    /// WebAuthn challenge length in bytes for every item (16-64, defaults to 32)
+    #[wasm_bindgen(js_name = "challengeLength")]
+    #[serde(rename = "challengeLength", default)]
+    pub challenge_length: Option<u8>,
+}
+
+/// One batch item: either the challenge or the reason it failed
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchChallengeItem {
+    /// Position of the input in the request
+    pub index: u32,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub challenge: Option<VRFChallengeData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateVrfChallengesBatchResult {
+    /// Items in input order
+    pub results: Vec<BatchChallengeItem>,
+    /// Whether the proofs were spread over the thread pool
+    pub parallel: bool,
+    pub thread_count: u32,
+}
+
+/// Handle GENERATE_VRF_CHALLENGES_BATCH message
+pub fn handle_generate_vrf_challenges_batch(
+    manager: Rc<RefCell<VRFKeyManager>>,
+    message_id: Option<String>,
+    payload: GenerateVrfChallengesBatchRequest,
+) -> VrfWorkerResponse {
+    let challenge_length = match resolve_challenge_length(payload.challenge_length) {
+        Ok(length) => length,
+        Err(e) => return VrfWorkerResponse::from_error(message_id, &e),
     };
+    if payload.vrf_inputs.len() > MAX_VRF_CHALLENGE_BATCH_SIZE {
+        return VrfWorkerResponse::fail(
+            message_id,
+            format!(
+                "Batch of {} inputs exceeds the maximum of {}",
+                payload.vrf_inputs.len(),
+                MAX_VRF_CHALLENGE_BATCH_SIZE
+            ),
+        );
+    }
+
+    let manager_ref = manager.borrow();
+    let parallel = parallel::parallel_available();
+    match manager_ref.generate_vrf_challenges_batch(payload.vrf_inputs, challenge_length, parallel)
+    {
+        Ok(outcomes) => {
+            let results: Vec<BatchChallengeItem> = outcomes
+                .into_iter()
+                .map(|outcome| {
+                    outcome.and_then(|challenge| {
+                        manager_ref
+                            .with_issuance_receipt(challenge, message_id.as_deref())
+                            .map_err(|e| e.to_string())
+                    })
+                })
+                .enumerate()
+                .map(|(index, outcome)| BatchChallengeItem {
+                    index: index as u32,
+                    success: outcome.is_ok(),
+                    error: outcome.as_ref().err().cloned(),
+                    challenge: outcome.ok(),
+                })
+                .collect();
+            let failed = results.iter().filter(|item| !item.success).count();
+            info!(
+                "VRF challenge batch generated: {} items, {} failed",
+                results.len(),
+                failed
+            );
+            let result = GenerateVrfChallengesBatchResult {
+                results,
+                parallel,
+                thread_count: parallel::thread_count() as u32,
+            };
+            VrfWorkerResponse::success(message_id, serde_json::to_value(&result).ok())
+        }
+        Err(e) => {
+            error!("VRF challenge batch generation failed: {}", e);
+            VrfWorkerResponse::from_error(message_id, &e)
+        }
+    }
 }
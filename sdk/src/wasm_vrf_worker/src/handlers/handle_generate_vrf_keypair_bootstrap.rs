@@ -1,6 +1,7 @@
 use crate::manager::VRFKeyManager;
 use crate::types::VRFInputData;
 use crate::types::VrfWorkerResponse;
+use crate::utils::base64_url_decode;
 use log::{error, info};
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
@@ -13,6 +14,11 @@ pub struct GenerateVrfKeypairBootstrapRequest {
     #[wasm_bindgen(getter_with_clone, js_name = "vrfInputData")]
     #[serde(rename = "vrfInputData")]
     pub vrf_input_data: Option<VRFInputData>,
+    /// Additional entropy mixed into the keypair seed (e.g. crypto.getRandomValues on the
+    /// main thread)
+    #[wasm_bindgen(getter_with_clone, js_name = "extraEntropyB64u")]
+    #[serde(default, rename = "extraEntropyB64u")]
+    pub extra_entropy_b64u: Option<String>,
 }
 
 /// Handle GENERATE_VRF_KEYPAIR_BOOTSTRAP message
@@ -21,22 +27,30 @@ pub fn handle_generate_vrf_keypair_bootstrap(
     message_id: Option<String>,
     payload: GenerateVrfKeypairBootstrapRequest,
 ) -> VrfWorkerResponse {
+    let extra_entropy = match payload.extra_entropy_b64u.as_deref().map(base64_url_decode) {
+        None => None,
+        Some(Ok(bytes)) => Some(bytes),
+        Some(Err(e)) => {
+            return VrfWorkerResponse::fail(message_id, format!("Invalid extraEntropyB64u: {}", e))
+        }
+    };
     let mut manager_mut = manager.borrow_mut();
     info!("Generating bootstrap VRF keypair");
 
-    match manager_mut.generate_vrf_keypair_bootstrap(payload.vrf_input_data) {
+    match manager_mut.generate_vrf_keypair_bootstrap(payload.vrf_input_data, extra_entropy) {
         Ok(bootstrap_data) => {
             info!("VRF keypair bootstrap completed successfully");
             // Structure response to match expected format
             let response_data = serde_json::json!({
                 "vrf_public_key": bootstrap_data.vrf_public_key,
-                "vrf_challenge_data": bootstrap_data.vrf_challenge_data
+                "vrf_challenge_data": bootstrap_data.vrf_challenge_data,
+                "entropy_sources": bootstrap_data.entropy_sources
             });
             VrfWorkerResponse::success(message_id, Some(response_data))
         }
         Err(e) => {
             error!("VRF keypair bootstrap failed: {}", e);
-            VrfWorkerResponse::fail(message_id, e.to_string())
+            VrfWorkerResponse::from_error(message_id, &e)
         }
     }
 }
@@ -0,0 +1,71 @@
+use crate::challenge::resolve_challenge_length;
+use crate::manager::VRFKeyManager;
+use crate::types::{VrfAnchor, VrfWorkerResponse};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+/// REQUEST_CHALLENGE payload, sent by the signer worker over its peer port
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RequestChallengeRequest {
+    #[wasm_bindgen(getter_with_clone, js_name = "userId")]
+    #[serde(rename = "userId")]
+    pub user_id: String,
+    #[wasm_bindgen(getter_with_clone, js_name = "rpId")]
+    #[serde(rename = "rpId")]
+    pub rp_id: String,
+    /// Freshness anchor; defaults to the latest block pushed with UPDATE_BLOCK_INFO
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub anchor: Option<VrfAnchor>,
+    /// WebAuthn challenge length in bytes (16-64, defaults to 32)
+    #[wasm_bindgen(js_name = "challengeLength")]
+    #[serde(rename = "challengeLength", default)]
+    pub challenge_length: Option<u8>,
+}
+
+/// Handle CONNECT_PEER_PORT message. The port itself stays in JS (web3authn-vrf.worker.ts);
+/// this enables REQUEST_CHALLENGE until the next WIPE_ALL_STATE.
+pub fn handle_connect_peer_port(
+    manager: Rc<RefCell<VRFKeyManager>>,
+    message_id: Option<String>,
+) -> VrfWorkerResponse {
+    manager.borrow_mut().peer_connected = true;
+    info!("Signer worker port connected");
+    VrfWorkerResponse::success(message_id, None)
+}
+
+/// Handle REQUEST_CHALLENGE message: a signing challenge for the signer worker, without a
+/// round trip through the main thread. Responds with VRFChallengeData.
+pub fn handle_request_challenge(
+    manager: Rc<RefCell<VRFKeyManager>>,
+    message_id: Option<String>,
+    payload: RequestChallengeRequest,
+) -> VrfWorkerResponse {
+    let challenge_length = match resolve_challenge_length(payload.challenge_length) {
+        Ok(length) => length,
+        Err(e) => return VrfWorkerResponse::from_error(message_id, &e),
+    };
+    let manager_ref = manager.borrow();
+    match manager_ref
+        .generate_peer_challenge(
+            &payload.user_id,
+            &payload.rp_id,
+            payload.anchor,
+            challenge_length,
+        )
+        .and_then(|challenge| manager_ref.with_issuance_receipt(challenge, message_id.as_deref()))
+    {
+        Ok(challenge_data) => VrfWorkerResponse::success(
+            message_id,
+            Some(serde_json::to_value(&challenge_data).unwrap()),
+        ),
+        Err(e) => {
+            error!("Peer VRF challenge failed: {}", e);
+            VrfWorkerResponse::from_error(message_id, &e)
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use crate::config::SESSION_SNAPSHOT_MAX_AGE_MS;
+use crate::manager::VRFKeyManager;
+use crate::types::VrfWorkerResponse;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+/// Encrypted copy of the unlocked VRF session, for the main thread to hold while the worker
+/// may be restarted. Useless without the one-time token returned alongside it.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SessionSnapshot {
+    pub version: u32,
+    /// Random id used to refuse a second restore
+    #[wasm_bindgen(getter_with_clone, js_name = "snapshotId")]
+    #[serde(rename = "snapshotId")]
+    pub snapshot_id: String,
+    /// Worker epoch at export; random per worker, WipeAllState rotates it
+    pub epoch: u32,
+    #[wasm_bindgen(js_name = "exportedAtMs")]
+    #[serde(rename = "exportedAtMs")]
+    pub exported_at_ms: f64,
+    #[wasm_bindgen(getter_with_clone, js_name = "nonceB64u")]
+    #[serde(rename = "nonceB64u")]
+    pub nonce_b64u: String,
+    /// ChaCha20Poly1305 over the session state, with the fields above as associated data
+    #[wasm_bindgen(getter_with_clone, js_name = "ciphertextB64u")]
+    #[serde(rename = "ciphertextB64u")]
+    pub ciphertext_b64u: String,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RestoreSessionSnapshotRequest {
+    #[wasm_bindgen(getter_with_clone)]
+    pub snapshot: SessionSnapshot,
+    /// Token returned by EXPORT_SESSION_SNAPSHOT
+    #[wasm_bindgen(getter_with_clone)]
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSessionSnapshotResult {
+    pub snapshot: SessionSnapshot,
+    /// Base64url snapshot key. Keep it in memory only, apart from the snapshot.
+    pub token: String,
+    pub expires_at_ms: f64,
+    pub revocation_state: SnapshotRevocationState,
+}
+
+/// A snapshot already restored, kept until it would have expired anyway
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsumedSnapshot {
+    pub snapshot_id: String,
+    pub exported_at_ms: f64,
+}
+
+/// What decides whether a snapshot may still be restored: the worker epoch and the snapshots
+/// already restored. A new worker starts from a random epoch, revoking every snapshot, so the
+/// main thread hands the state returned by EXPORT, RESTORE and WIPE to the worker replacing
+/// this one (LOAD_SNAPSHOT_REVOCATION_STATE).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotRevocationState {
+    pub epoch: u32,
+    pub consumed_snapshots: Vec<ConsumedSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct RevocationStateResult {
+    revocation_state: SnapshotRevocationState,
+}
+
+fn revocation_state_response(
+    manager: &VRFKeyManager,
+    message_id: Option<String>,
+) -> VrfWorkerResponse {
+    let result = RevocationStateResult {
+        revocation_state: manager.revocation_state(),
+    };
+    VrfWorkerResponse::success(message_id, serde_json::to_value(&result).ok())
+}
+
+/// Handle EXPORT_SESSION_SNAPSHOT message
+pub fn handle_export_session_snapshot(
+    manager: Rc<RefCell<VRFKeyManager>>,
+    message_id: Option<String>,
+) -> VrfWorkerResponse {
+    let manager_ref = manager.borrow();
+    match manager_ref.export_session_snapshot() {
+        Ok((snapshot, token)) => {
+            info!("VRF session snapshot exported");
+            let result = ExportSessionSnapshotResult {
+                expires_at_ms: snapshot.exported_at_ms + SESSION_SNAPSHOT_MAX_AGE_MS,
+                snapshot,
+                token,
+                revocation_state: manager_ref.revocation_state(),
+            };
+            VrfWorkerResponse::success(message_id, serde_json::to_value(&result).ok())
+        }
+        Err(e) => VrfWorkerResponse::from_error(message_id, &e),
+    }
+}
+
+/// Handle RESTORE_SESSION_SNAPSHOT message
+pub fn handle_restore_session_snapshot(
+    manager: Rc<RefCell<VRFKeyManager>>,
+    message_id: Option<String>,
+    payload: RestoreSessionSnapshotRequest,
+) -> VrfWorkerResponse {
+    let mut manager_mut = manager.borrow_mut();
+    match manager_mut.restore_session_snapshot(&payload.snapshot, &payload.token) {
+        Ok(()) => {
+            info!("VRF session restored from snapshot");
+            revocation_state_response(&manager_mut, message_id)
+        }
+        Err(e) => {
+            warn!("VRF session snapshot restore failed: {}", e);
+            VrfWorkerResponse::from_error(message_id, &e)
+        }
+    }
+}
+
+/// Handle WIPE_ALL_STATE message
+pub fn handle_wipe_all_state(
+    manager: Rc<RefCell<VRFKeyManager>>,
+    message_id: Option<String>,
+) -> VrfWorkerResponse {
+    let mut manager_mut = manager.borrow_mut();
+    match manager_mut.wipe_all_state() {
+        Ok(()) => revocation_state_response(&manager_mut, message_id),
+        Err(e) => VrfWorkerResponse::from_error(message_id, &e),
+    }
+}
+
+/// Handle LOAD_SNAPSHOT_REVOCATION_STATE message
+pub fn handle_load_snapshot_revocation_state(
+    manager: Rc<RefCell<VRFKeyManager>>,
+    message_id: Option<String>,
+    payload: SnapshotRevocationState,
+) -> VrfWorkerResponse {
+    let mut manager_mut = manager.borrow_mut();
+    match manager_mut.load_revocation_state(payload) {
+        Ok(()) => revocation_state_response(&manager_mut, message_id),
+        Err(e) => {
+            warn!("VRF snapshot revocation state not loaded: {}", e);
+            VrfWorkerResponse::from_error(message_id, &e)
+        }
+    }
+}
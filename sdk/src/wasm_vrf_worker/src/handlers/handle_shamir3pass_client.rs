@@ -1,7 +1,7 @@
 use crate::http::{post_apply_server_lock, post_remove_server_lock};
 use crate::manager::VRFKeyManager;
 use crate::shamir3pass::{decode_biguint_b64u, encode_biguint_b64u};
-use crate::types::VrfWorkerResponse;
+use crate::types::{VrfScope, VrfWorkerResponse};
 use log::error;
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
@@ -14,11 +14,7 @@ fn normalize_relay_url(base: &str, route: &str) -> String {
     if route_trimmed.starts_with("http://") || route_trimmed.starts_with("https://") {
         route_trimmed.to_string()
     } else {
-        format!(
-            "{}/{}",
-            base_trimmed,
-            route_trimmed.trim_start_matches('/')
-        )
+        format!("{}/{}", base_trimmed, route_trimmed.trim_start_matches('/'))
     }
 }
 
@@ -43,6 +39,10 @@ pub struct Shamir3PassClientDecryptVrfKeypairRequest {
     #[wasm_bindgen(getter_with_clone, js_name = "keyId")]
     #[serde(rename = "keyId")]
     pub key_id: String,
+    /// As UNLOCK_VRF_KEYPAIR's `scopes` (all scopes when absent)
+    #[wasm_bindgen(skip)]
+    #[serde(default = "VrfScope::all")]
+    pub scopes: Vec<VrfScope>,
 }
 
 #[wasm_bindgen]
@@ -126,6 +126,8 @@ pub async fn perform_shamir3pass_client_encrypt_current_vrf_keypair(
         if !mgr.session_active || mgr.vrf_keypair.is_none() {
             return Err("No VRF keypair in memory".to_string());
         }
+        mgr.require_scope(VrfScope::Rotation)
+            .map_err(|e| e.to_string())?;
         let kp = mgr.vrf_keypair.as_ref().unwrap().inner();
         let vrf_keypair_bytes = match bincode::serialize(kp) {
             Ok(b) => b,
@@ -217,6 +219,7 @@ pub async fn handle_shamir3pass_client_decrypt_vrf_keypair(
         || relay_url.is_empty()
         || payload.kek_s_b64u.is_empty()
         || payload.ciphertext_vrf_b64u.is_empty()
+        || payload.scopes.is_empty()
     {
         return VrfWorkerResponse::fail(message_id, "missing required fields");
     };
@@ -258,7 +261,8 @@ pub async fn handle_shamir3pass_client_decrypt_vrf_keypair(
 
     // POST KEK_cs to server /remove-server-lock and receive KEK_c back
     let url = normalize_relay_url(&relay_url, &remove_route);
-    let kek_c_b64u = match post_remove_server_lock(&url, &kek_cs_b64u, payload.key_id.clone()).await {
+    let kek_c_b64u = match post_remove_server_lock(&url, &kek_cs_b64u, payload.key_id.clone()).await
+    {
         Ok(v) => v.kek_c_b64u,
         Err(e) => return VrfWorkerResponse::fail(message_id, e),
     };
@@ -289,11 +293,12 @@ pub async fn handle_shamir3pass_client_decrypt_vrf_keypair(
             }
         };
 
-    if let Err(e) = manager
-        .borrow_mut()
-        .load_plaintext_vrf_keypair(payload.near_account_id, keypair_payload)
-    {
-        return VrfWorkerResponse::fail(message_id, e.to_string());
+    if let Err(e) = manager.borrow_mut().load_plaintext_vrf_keypair(
+        payload.near_account_id,
+        keypair_payload,
+        payload.scopes,
+    ) {
+        return VrfWorkerResponse::from_error(message_id, &e);
     }
 
     VrfWorkerResponse::success(
@@ -0,0 +1,59 @@
+use crate::manager::VRFKeyManager;
+use crate::types::VrfWorkerResponse;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Something RESUME_HINT found stale, for the UI to refresh
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ResumeInvalidation {
+    /// The challenge prefetched for the latest block
+    PrefetchedChallenge,
+    /// The latest block from UPDATE_BLOCK_INFO; prefetch resumes with the next push
+    BlockInfo,
+    /// `autoLockAfterMs` passed while suspended: the session was locked
+    SessionLocked,
+}
+
+/// RESUME_HINT result
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumedState {
+    /// Wall-clock time since SUSPEND_HINT (0 without one)
+    pub suspended_ms: f64,
+    pub invalidated: Vec<ResumeInvalidation>,
+    /// Whether the session is still unlocked
+    pub session_active: bool,
+}
+
+/// Handle SUSPEND_HINT message, sent on pagehide. Responds with `{ suspendedAtMs }`.
+pub fn handle_suspend_hint(
+    manager: Rc<RefCell<VRFKeyManager>>,
+    message_id: Option<String>,
+) -> VrfWorkerResponse {
+    let suspension = manager.borrow_mut().suspend();
+    VrfWorkerResponse::success(
+        message_id,
+        Some(serde_json::json!({ "suspendedAtMs": suspension.suspended_at_ms })),
+    )
+}
+
+/// Handle RESUME_HINT message, sent on pageshow. Responds with the ResumedState.
+pub fn handle_resume_hint(
+    manager: Rc<RefCell<VRFKeyManager>>,
+    message_id: Option<String>,
+) -> VrfWorkerResponse {
+    let mut manager_mut = manager.borrow_mut();
+    match manager_mut.resume() {
+        Ok(state) => {
+            info!(
+                "VRF worker resumed after {} ms, invalidated {:?}",
+                state.suspended_ms, state.invalidated
+            );
+            VrfWorkerResponse::success(message_id, serde_json::to_value(&state).ok())
+        }
+        Err(e) => VrfWorkerResponse::from_error(message_id, &e),
+    }
+}
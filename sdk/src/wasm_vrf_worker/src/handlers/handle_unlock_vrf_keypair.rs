@@ -1,6 +1,6 @@
 use crate::manager::VRFKeyManager;
-use crate::types::EncryptedVRFKeypair;
 use crate::types::VrfWorkerResponse;
+use crate::types::{EncryptedVRFKeypair, VrfScope};
 use log::{error, info};
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
@@ -19,6 +19,11 @@ pub struct UnlockVrfKeypairRequest {
     #[wasm_bindgen(getter_with_clone, js_name = "prfKey")]
     #[serde(rename = "prfKey")]
     pub prf_key: String, // base64url
+    /// What the unlocked keypair may be used for (all scopes when absent). Replaces the
+    /// scopes of the session's previous unlock.
+    #[wasm_bindgen(skip)]
+    #[serde(default = "VrfScope::all")]
+    pub scopes: Vec<VrfScope>,
 }
 
 // === Shamir 3-pass unlock handlers ===
@@ -38,12 +43,16 @@ pub fn handle_unlock_vrf_keypair(
     if payload.near_account_id.is_empty() {
         return VrfWorkerResponse::fail(message_id, "Missing nearAccountId");
     }
+    if payload.scopes.is_empty() {
+        return VrfWorkerResponse::fail(message_id, "scopes must name at least one scope");
+    }
 
     let mut manager_mut = manager.borrow_mut();
     match manager_mut.unlock_vrf_keypair(
         payload.near_account_id,
         payload.encrypted_vrf_keypair,
         prf_key,
+        payload.scopes,
     ) {
         Ok(_) => {
             info!("VRF keypair unlock successful");
@@ -51,7 +60,7 @@ pub fn handle_unlock_vrf_keypair(
         }
         Err(e) => {
             error!("VRF keypair unlock failed: {}", e);
-            VrfWorkerResponse::fail(message_id, e.to_string())
+            VrfWorkerResponse::from_error(message_id, &e)
         }
     }
 }
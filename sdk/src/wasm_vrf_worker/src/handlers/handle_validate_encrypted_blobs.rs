@@ -0,0 +1,217 @@
+use crate::config::{
+    CHACHA20_NONCE_SIZE, CHACHA20_POLY1305_TAG_SIZE, ENCRYPTED_BLOB_VERSION_V1,
+    ENCRYPTED_BLOB_VERSION_V2,
+};
+use crate::types::{EncryptedVRFKeypair, VrfWorkerResponse};
+use crate::utils::base64_url_decode;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// A stored EncryptedVRFKeypair plus the metadata it was persisted with
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct EncryptedVrfKeypairBlob {
+    /// Caller-chosen identifier echoed back in the report
+    #[wasm_bindgen(getter_with_clone, js_name = "blobId")]
+    #[serde(rename = "blobId", default)]
+    pub blob_id: Option<String>,
+    #[wasm_bindgen(getter_with_clone, js_name = "encryptedVrfKeypair")]
+    #[serde(rename = "encryptedVrfKeypair")]
+    pub encrypted_vrf_keypair: EncryptedVRFKeypair,
+    /// Encryption format version; when absent, read from the keypair (v2 with `meta`, else v1)
+    #[serde(default)]
+    pub version: Option<u32>,
+    /// Account the keypair is bound to (the user record's nearAccountId)
+    #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
+    #[serde(rename = "nearAccountId", default)]
+    pub near_account_id: Option<String>,
+}
+
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ValidateEncryptedBlobsRequest {
+    /// Account the blobs are expected to belong to
+    #[wasm_bindgen(getter_with_clone, js_name = "nearAccountId")]
+    #[serde(rename = "nearAccountId")]
+    pub near_account_id: String,
+    #[wasm_bindgen(getter_with_clone)]
+    pub blobs: Vec<EncryptedVrfKeypairBlob>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BlobProblem {
+    /// Stable problem code, e.g. "NonceLengthMismatch"
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BlobValidationReport {
+    pub index: u32,
+    pub blob_id: Option<String>,
+    pub valid: bool,
+    pub problems: Vec<BlobProblem>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidateEncryptedBlobsResult {
+    pub near_account_id: String,
+    pub all_valid: bool,
+    pub reports: Vec<BlobValidationReport>,
+}
+
+fn problem(code: &str, message: String) -> BlobProblem {
+    BlobProblem {
+        code: code.to_string(),
+        message,
+    }
+}
+
+/// Expected nonce length for a known blob version (None for unknown versions)
+fn nonce_size_for_version(version: u32) -> Option<usize> {
+    match version {
+        ENCRYPTED_BLOB_VERSION_V1 | ENCRYPTED_BLOB_VERSION_V2 => Some(CHACHA20_NONCE_SIZE),
+        _ => None,
+    }
+}
+
+/// Structural checks on a single encrypted VRF keypair. Never decrypts: no PRF output is needed.
+pub fn validate_encrypted_vrf_keypair_blob(
+    blob: &EncryptedVrfKeypairBlob,
+    near_account_id: &str,
+) -> Vec<BlobProblem> {
+    let mut problems = Vec::new();
+
+    let version = blob
+        .version
+        .unwrap_or_else(|| blob.encrypted_vrf_keypair.version());
+    let expected_nonce_size = nonce_size_for_version(version);
+    if expected_nonce_size.is_none() {
+        problems.push(problem(
+            "UnknownVersion",
+            format!("Unknown encryption version {}", version),
+        ));
+    }
+    if version == ENCRYPTED_BLOB_VERSION_V2 && blob.encrypted_vrf_keypair.meta.is_none() {
+        problems.push(problem(
+            "MissingMetadata",
+            format!("Version {} keypairs carry meta, this one does not", version),
+        ));
+    }
+
+    match base64_url_decode(&blob.encrypted_vrf_keypair.encrypted_vrf_data_b64u) {
+        Ok(ciphertext) if ciphertext.len() < CHACHA20_POLY1305_TAG_SIZE => {
+            problems.push(problem(
+                "CiphertextTooShort",
+                format!(
+                    "Ciphertext is {} bytes, shorter than the {}-byte authentication tag",
+                    ciphertext.len(),
+                    CHACHA20_POLY1305_TAG_SIZE
+                ),
+            ));
+        }
+        Ok(_) => {}
+        Err(e) => problems.push(problem(
+            "InvalidCiphertextEncoding",
+            format!("encryptedVrfDataB64u is not valid base64url: {}", e),
+        )),
+    }
+
+    match base64_url_decode(&blob.encrypted_vrf_keypair.chacha20_nonce_b64u) {
+        Ok(nonce) => {
+            if let Some(expected) = expected_nonce_size {
+                if nonce.len() != expected {
+                    problems.push(problem(
+                        "NonceLengthMismatch",
+                        format!(
+                            "Nonce is {} bytes, version {} requires {}",
+                            nonce.len(),
+                            version,
+                            expected
+                        ),
+                    ));
+                }
+            }
+        }
+        Err(e) => problems.push(problem(
+            "InvalidNonceEncoding",
+            format!("chacha20NonceB64u is not valid base64url: {}", e),
+        )),
+    }
+
+    // The record's account, then the one the keypair's metadata names
+    let bound_account_ids = [
+        blob.near_account_id.as_deref(),
+        blob.encrypted_vrf_keypair
+            .meta
+            .as_ref()
+            .map(|meta| meta.account_id.as_str()),
+    ];
+    for bound_account_id in bound_account_ids.into_iter().flatten() {
+        if bound_account_id != near_account_id {
+            problems.push(problem(
+                "AccountIdMismatch",
+                format!(
+                    "Keypair is bound to {} but was supplied for {}",
+                    bound_account_id, near_account_id
+                ),
+            ));
+            break;
+        }
+    }
+
+    problems
+}
+
+/// Checks every blob in the request and builds the per-blob report
+pub fn validate_encrypted_blobs(
+    request: &ValidateEncryptedBlobsRequest,
+) -> ValidateEncryptedBlobsResult {
+    let reports: Vec<BlobValidationReport> = request
+        .blobs
+        .iter()
+        .enumerate()
+        .map(|(index, blob)| {
+            let problems = validate_encrypted_vrf_keypair_blob(blob, &request.near_account_id);
+            BlobValidationReport {
+                index: index as u32,
+                blob_id: blob.blob_id.clone(),
+                valid: problems.is_empty(),
+                problems,
+            }
+        })
+        .collect();
+
+    ValidateEncryptedBlobsResult {
+        near_account_id: request.near_account_id.clone(),
+        all_valid: reports.iter().all(|r| r.valid),
+        reports,
+    }
+}
+
+/// Handle VALIDATE_ENCRYPTED_BLOBS message
+///
+/// Lets the TS layer detect corrupted EncryptedVRFKeypair records (and offer re-registration
+/// or device-linking recovery) before an unlock fails mid-login. Does not touch the in-memory keypair.
+pub fn handle_validate_encrypted_blobs(
+    message_id: Option<String>,
+    payload: ValidateEncryptedBlobsRequest,
+) -> VrfWorkerResponse {
+    let result = validate_encrypted_blobs(&payload);
+    if result.all_valid {
+        info!(
+            "Validated {} encrypted VRF keypair blobs",
+            result.reports.len()
+        );
+    } else {
+        warn!(
+            "Encrypted VRF keypair blobs failed validation for {}",
+            result.near_account_id
+        );
+    }
+    VrfWorkerResponse::success(message_id, Some(serde_json::to_value(&result).unwrap()))
+}
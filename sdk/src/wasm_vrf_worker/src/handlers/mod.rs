@@ -1,35 +1,82 @@
+pub mod handle_challenge_prefetch;
 pub mod handle_derive_vrf_keypair_from_prf;
+pub mod handle_generate_test_vectors;
 pub mod handle_generate_vrf_challenge;
 pub mod handle_generate_vrf_keypair_bootstrap;
+pub mod handle_peer_port;
+pub mod handle_session_snapshot;
 pub mod handle_shamir3pass_client;
 pub mod handle_shamir3pass_config;
 pub mod handle_shamir3pass_server;
+pub mod handle_suspend_resume;
 pub mod handle_unlock_vrf_keypair;
+pub mod handle_validate_encrypted_blobs;
 
+pub use handle_challenge_prefetch::*;
 pub use handle_derive_vrf_keypair_from_prf::*;
+pub use handle_generate_test_vectors::*;
 pub use handle_generate_vrf_challenge::*;
 pub use handle_generate_vrf_keypair_bootstrap::*;
+pub use handle_peer_port::*;
+pub use handle_session_snapshot::*;
 pub use handle_shamir3pass_client::*;
 pub use handle_shamir3pass_config::*;
 pub use handle_shamir3pass_server::*;
+pub use handle_suspend_resume::*;
 pub use handle_unlock_vrf_keypair::*;
+pub use handle_validate_encrypted_blobs::*;
 
+use crate::build_info::BuildInfo;
+use crate::clock::{Clock, SystemClock};
 use crate::manager::VRFKeyManager;
+use crate::parallel;
+use crate::self_test;
 use crate::types::VrfWorkerResponse;
+use serde::Serialize;
 use std::cell::RefCell;
 use std::rc::Rc;
 
+/// Build capabilities reported with PING
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkerInfo {
+    /// e.g. "wasm-threads" (compiled in), "parallel-batch-proofs" (thread pool running)
+    pub features: Vec<String>,
+    pub thread_count: u32,
+    pub build_info: BuildInfo,
+}
+
+impl WorkerInfo {
+    pub fn current() -> Self {
+        Self {
+            features: parallel::worker_features(),
+            thread_count: parallel::thread_count() as u32,
+            build_info: BuildInfo::current(),
+        }
+    }
+}
+
 /// Handle PING message
 pub fn handle_ping(message_id: Option<String>) -> VrfWorkerResponse {
     VrfWorkerResponse::success(
         message_id,
         Some(serde_json::json!({
             "status": "alive",
-            "timestamp": js_sys::Date::now()
+            "timestamp": SystemClock.now_ms(),
+            "workerInfo": WorkerInfo::current()
         })),
     )
 }
 
+/// Handle RUN_SELF_TEST message: re-runs the startup known-answer tests (see self_test.rs).
+/// A failure puts the worker into the refusing state; a pass does not leave it.
+pub fn handle_run_self_test(message_id: Option<String>) -> VrfWorkerResponse {
+    match serde_json::to_value(self_test::run_self_test()) {
+        Ok(report) => VrfWorkerResponse::success(message_id, Some(report)),
+        Err(e) => VrfWorkerResponse::fail(message_id, e.to_string()),
+    }
+}
+
 /// Handle CHECK_VRF_STATUS message
 pub fn handle_check_vrf_status(
     manager: Rc<RefCell<VRFKeyManager>>,
@@ -48,6 +95,6 @@ pub fn handle_logout(
     let mut manager_mut = manager.borrow_mut();
     match manager_mut.logout() {
         Ok(_) => VrfWorkerResponse::success(message_id, None),
-        Err(e) => VrfWorkerResponse::fail(message_id, e.to_string()),
+        Err(e) => VrfWorkerResponse::from_error(message_id, &e),
     }
 }
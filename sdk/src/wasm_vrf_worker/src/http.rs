@@ -1,6 +1,7 @@
 use crate::types::http::{
-    ShamirApplyServerLockHTTPRequest, ShamirApplyServerLockHTTPResponse,
-    ShamirRemoveServerLockHTTPRequest, ShamirRemoveServerLockHTTPResponse,
+    NearBlockHeader, NearBlockRpcRequest, NearBlockRpcResponse, ShamirApplyServerLockHTTPRequest,
+    ShamirApplyServerLockHTTPResponse, ShamirRemoveServerLockHTTPRequest,
+    ShamirRemoveServerLockHTTPResponse,
 };
 use js_sys::{Function, Promise, Reflect};
 use log::debug;
@@ -146,3 +147,52 @@ pub(crate) async fn post_remove_server_lock(
 
     ShamirRemoveServerLockHTTPResponse::from_str(&response_text)
 }
+
+/// Looks up a NEAR block by hash: its header, or None when the RPC does not know the block
+pub(crate) async fn fetch_near_block_header(
+    rpc_url: &str,
+    block_hash: &str,
+) -> Result<Option<NearBlockHeader>, String> {
+    debug!("NEAR RPC block lookup: {}", rpc_url);
+
+    let headers = Headers::new().map_err(|e| format!("Failed to create headers: {:?}", e))?;
+    headers
+        .set("Content-Type", "application/json")
+        .map_err(|e| format!("Failed to set content type: {:?}", e))?;
+
+    let opts = RequestInit::new();
+    opts.set_method("POST");
+    opts.set_headers(&headers);
+    opts.set_body(&NearBlockRpcRequest::by_hash(block_hash).to_js_value());
+
+    let request = Request::new_with_str_and_init(rpc_url, &opts)
+        .map_err(|e| format!("Failed to create request: {:?}", e))?;
+
+    let resp_value = fetch_global(&request)?
+        .await
+        .map_err(|e| format!("Fetch failed: {:?}", e))?;
+
+    let resp: Response = resp_value
+        .dyn_into()
+        .map_err(|_| "Failed to cast response")?;
+
+    if !resp.ok() {
+        return Err(format!(
+            "HTTP error: {} {}",
+            resp.status(),
+            resp.status_text()
+        ));
+    }
+
+    let text_promise = resp
+        .text()
+        .map_err(|e| format!("Failed to get response text promise: {:?}", e))?;
+    let text_value = JsFuture::from(text_promise)
+        .await
+        .map_err(|e| format!("Failed to get response text: {:?}", e))?;
+    let response_text = text_value
+        .as_string()
+        .ok_or("Response text is not a string")?;
+
+    NearBlockRpcResponse::from_str(&response_text)?.into_header()
+}
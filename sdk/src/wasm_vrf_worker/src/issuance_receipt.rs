@@ -0,0 +1,153 @@
+// === ISSUANCE RECEIPTS ===
+// Opt-in evidence, for the integrator's backend, that a VRF challenge was issued by this
+// worker during a session the integrator set up. CONFIGURE_SESSION_OPTIONS with
+// `issuanceReceiptKeyB64u` gives the worker a 32-byte key the integrator shares with its
+// backend over its own channel; until LOGOUT every VRFChallengeData then carries
+// `issuanceReceiptB64u`:
+//   version (1) || issuedAtMs (u64 BE) || HMAC-SHA256 (32) || requestId (UTF-8, rest)
+// The MAC is keyed with the receipt key over RECEIPT_DOMAIN and the length-prefixed vrfInput
+// bytes, vrfOutput bytes, issuedAtMs and requestId (the worker message id, or empty).
+//
+// The receipt key is unrelated to the VRF keypair, and everything the MAC covers is already
+// public in the challenge: a receipt neither depends on nor reveals the VRF secret key.
+// This module has no wasm dependencies, so backends can link it natively.
+
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::utils::{base64_url_decode, base64_url_encode};
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const RECEIPT_VERSION: u8 = 1;
+pub const RECEIPT_KEY_SIZE: usize = 32;
+const RECEIPT_DOMAIN: &[u8] = b"web3authn-vrf-issuance-receipt-v1";
+const MAC_SIZE: usize = 32;
+const HEADER_SIZE: usize = 1 + 8;
+
+/// What a verified receipt attests, besides the challenge it was checked against
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IssuanceReceipt {
+    pub issued_at_ms: u64,
+    pub request_id: String,
+}
+
+/// The session's receipt key, zeroized when dropped (LOGOUT or reconfiguration)
+pub struct IssuanceReceiptKey(Zeroizing<[u8; RECEIPT_KEY_SIZE]>);
+
+impl IssuanceReceiptKey {
+    pub fn from_b64u(key_b64u: &str) -> Result<Self, String> {
+        let bytes = Zeroizing::new(
+            base64_url_decode(key_b64u)
+                .map_err(|e| format!("Invalid issuance receipt key: {}", e))?,
+        );
+        let key: [u8; RECEIPT_KEY_SIZE] = bytes.as_slice().try_into().map_err(|_| {
+            format!(
+                "Issuance receipt key must be {} bytes, got {}",
+                RECEIPT_KEY_SIZE,
+                bytes.len()
+            )
+        })?;
+        Ok(Self(Zeroizing::new(key)))
+    }
+
+    fn mac(
+        &self,
+        vrf_input: &[u8],
+        vrf_output: &[u8],
+        issued_at_ms: u64,
+        request_id: &str,
+    ) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(self.0.as_slice()).expect("HMAC accepts any key length");
+        mac.update(RECEIPT_DOMAIN);
+        for field in [
+            vrf_input,
+            vrf_output,
+            &issued_at_ms.to_be_bytes()[..],
+            request_id.as_bytes(),
+        ] {
+            mac.update(&(field.len() as u32).to_be_bytes());
+            mac.update(field);
+        }
+        mac
+    }
+
+    /// Base64url receipt for a challenge's (base64url) vrfInput and vrfOutput
+    pub fn issue(
+        &self,
+        vrf_input_b64u: &str,
+        vrf_output_b64u: &str,
+        issued_at_ms: u64,
+        request_id: &str,
+    ) -> Result<String, String> {
+        let (vrf_input, vrf_output) = decode_challenge(vrf_input_b64u, vrf_output_b64u)?;
+        let tag = self
+            .mac(&vrf_input, &vrf_output, issued_at_ms, request_id)
+            .finalize()
+            .into_bytes();
+
+        let mut receipt = Vec::with_capacity(HEADER_SIZE + MAC_SIZE + request_id.len());
+        receipt.push(RECEIPT_VERSION);
+        receipt.extend_from_slice(&issued_at_ms.to_be_bytes());
+        receipt.extend_from_slice(&tag);
+        receipt.extend_from_slice(request_id.as_bytes());
+        Ok(base64_url_encode(&receipt))
+    }
+}
+
+fn decode_challenge(
+    vrf_input_b64u: &str,
+    vrf_output_b64u: &str,
+) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let vrf_input =
+        base64_url_decode(vrf_input_b64u).map_err(|e| format!("Invalid vrfInput: {}", e))?;
+    let vrf_output =
+        base64_url_decode(vrf_output_b64u).map_err(|e| format!("Invalid vrfOutput: {}", e))?;
+    Ok((vrf_input, vrf_output))
+}
+
+/// Backend check of a challenge's `issuanceReceiptB64u` under the shared receipt key.
+/// Returns when and for which request the challenge was issued; errors when the receipt is
+/// malformed or was not issued for this (vrfInput, vrfOutput) under this key. Freshness of
+/// `issued_at_ms` is the caller's policy.
+pub fn verify_issuance_receipt(
+    receipt_b64u: &str,
+    key_b64u: &str,
+    vrf_input_b64u: &str,
+    vrf_output_b64u: &str,
+) -> Result<IssuanceReceipt, String> {
+    let key = IssuanceReceiptKey::from_b64u(key_b64u)?;
+    let receipt =
+        base64_url_decode(receipt_b64u).map_err(|e| format!("Invalid issuance receipt: {}", e))?;
+    if receipt.len() < HEADER_SIZE + MAC_SIZE {
+        return Err(format!(
+            "Issuance receipt is {} bytes, shorter than {}",
+            receipt.len(),
+            HEADER_SIZE + MAC_SIZE
+        ));
+    }
+    if receipt[0] != RECEIPT_VERSION {
+        return Err(format!(
+            "Unsupported issuance receipt version {}",
+            receipt[0]
+        ));
+    }
+    let issued_at_ms = u64::from_be_bytes(receipt[1..HEADER_SIZE].try_into().unwrap());
+    let tag = &receipt[HEADER_SIZE..HEADER_SIZE + MAC_SIZE];
+    let request_id = std::str::from_utf8(&receipt[HEADER_SIZE + MAC_SIZE..])
+        .map_err(|_| "Issuance receipt requestId is not UTF-8".to_string())?;
+
+    let (vrf_input, vrf_output) = decode_challenge(vrf_input_b64u, vrf_output_b64u)?;
+    key.mac(&vrf_input, &vrf_output, issued_at_ms, request_id)
+        .verify_slice(tag)
+        .map_err(|_| "Issuance receipt does not match the challenge or key".to_string())?;
+
+    Ok(IssuanceReceipt {
+        issued_at_ms,
+        request_id: request_id.to_string(),
+    })
+}
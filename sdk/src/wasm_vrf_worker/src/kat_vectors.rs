@@ -0,0 +1,23 @@
+// === KNOWN-ANSWER TEST VECTORS ===
+// VRF vector for the startup self-test (self_test.rs). vrf-wasm implements ECVRF over
+// ristretto255 as in draft-irtf-cfrg-vrf-15 with the "sui_vrf" suite string, which has no
+// vector in the draft itself; this one is the published vector of the reference
+// implementation (fastcrypto, `vrf_tests.rs::test_ecvrf_verify`). Re-check any change against
+// that source, not against the output of this crate.
+
+pub const VRF_ALPHA: &[u8] = b"Hello, world!";
+/// Compressed ristretto255 point
+pub const VRF_PUBLIC_KEY: &str = "1ea6f0f467574295a2cd5d21a3fd3a712ade354d520d3bd0fe6088d7b7c2e00e";
+/// Gamma (32 bytes) || challenge (16 bytes) || scalar (32 bytes)
+pub const VRF_PROOF: &str = concat!(
+    "d8ad2eafb4f2eaf317447726e541359f26dfce248431fe09984fdc73144abb6c",
+    "eb006c57a29a742eae5a81dd04239870769e310a81046cbbaff8b0bd27a6d6af",
+    "fee167ebba50549b58ffdf9aa192f506",
+);
+/// proof_to_hash output (64 bytes)
+pub const VRF_OUTPUT: &str = concat!(
+    "4fad431c7402fa1d4a7652e975aeb9a2b746540eca0b1b1e59c8d19c14a77019",
+    "18a8249136e355455b8bc73851f7fc62c84f2e39f685b281e681043970026ed8",
+);
+/// Seed of the keypair used for the prove half of the test (any fixed value)
+pub const VRF_PROVE_SEED: [u8; 32] = [7u8; 32];
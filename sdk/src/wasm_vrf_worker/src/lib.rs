@@ -4,15 +4,29 @@ use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsValue;
 
+mod build_info;
+mod challenge;
+#[path = "../../wasm_shared/clock.rs"]
+mod clock;
 mod config;
+mod entropy;
+#[path = "../../wasm_shared/error_codes.rs"]
+mod error_codes;
 mod errors;
 mod handlers;
 mod http;
+mod issuance_receipt;
+mod kat_vectors;
 mod manager;
+mod parallel;
+mod self_test;
 mod shamir3pass;
 mod tests;
 mod types;
 mod utils;
+#[path = "../../wasm_shared/vrf_blob_meta.rs"]
+mod vrf_blob_meta;
+mod vrf_input;
 
 // Re-export important types and functions
 pub use config::*;
@@ -22,12 +36,18 @@ pub use shamir3pass::*;
 pub use utils::*;
 
 // Import specific types to avoid ambiguity
-pub use types::{VrfWorkerMessage, VrfWorkerResponse, WorkerRequestType};
+pub use types::{
+    VRFChallengeData, VRFInputData, VrfWorkerMessage, VrfWorkerResponse, WorkerRequestType,
+};
 
 // Import request types from their respective handler files
 pub use handlers::handle_derive_vrf_keypair_from_prf::DeriveVrfKeypairFromPrfRequest;
-pub use handlers::handle_generate_vrf_challenge::GenerateVrfChallengeRequest;
+pub use handlers::handle_generate_test_vectors::GenerateTestVectorsRequest;
+pub use handlers::handle_generate_vrf_challenge::{
+    GenerateVrfChallengeRequest, GenerateVrfChallengesBatchRequest,
+};
 pub use handlers::handle_generate_vrf_keypair_bootstrap::GenerateVrfKeypairBootstrapRequest;
+pub use handlers::handle_session_snapshot::RestoreSessionSnapshotRequest;
 pub use handlers::handle_shamir3pass_client::{
     Shamir3PassClientDecryptVrfKeypairRequest, Shamir3PassClientEncryptCurrentVrfKeypairRequest,
 };
@@ -39,6 +59,12 @@ pub use handlers::handle_shamir3pass_server::{
     Shamir3PassRemoveServerLockRequest,
 };
 pub use handlers::handle_unlock_vrf_keypair::UnlockVrfKeypairRequest;
+pub use handlers::handle_validate_encrypted_blobs::ValidateEncryptedBlobsRequest;
+
+// Thread pool for parallel batch proofs; the worker script calls initThreadPool when the
+// page is cross-origin isolated, then mark_thread_pool_ready
+#[cfg(all(feature = "wasm-threads", target_arch = "wasm32"))]
+pub use wasm_bindgen_rayon::init_thread_pool;
 
 // Import JSON functions for message serialization
 #[wasm_bindgen]
@@ -60,6 +86,8 @@ pub fn main() {
         "Logging system initialized with level: {:?}",
         config::CURRENT_LOG_LEVEL
     );
+    // Known-answer tests; a failure refuses key-handling requests until the worker restarts
+    self_test::run_self_test();
 }
 
 // === GLOBAL STATE ===
@@ -94,6 +122,85 @@ pub fn configure_shamir_server_urls(
     })
 }
 
+/// Records that initThreadPool succeeded, enabling parallel batch proofs
+#[wasm_bindgen]
+pub fn mark_thread_pool_ready() {
+    parallel::mark_thread_pool_ready();
+}
+
+/// Returns the exact byte construction of the VRF input for `input`
+/// ({ inputBytesB64u, vrfInputB64u, domainTag, layoutDescription }).
+/// `input` is a plain VRFInputData object, with either an `anchor` or flat blockHeight/blockHash.
+#[wasm_bindgen]
+pub fn build_vrf_input(input: JsValue) -> Result<JsValue, JsValue> {
+    let input: types::VRFInputData = serde_wasm_bindgen::from_value(input)
+        .map_err(|e| JsValue::from_str(&format!("Invalid VRF input data: {}", e)))?;
+    let construction = vrf_input::build_vrf_input(&input)?;
+    serde_wasm_bindgen::to_value(&construction)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize VRF input: {}", e)))
+}
+
+/// Derives the WebAuthn challenge (base64url) from a base64url VRF output.
+/// `challenge_length` defaults to 32 bytes; see `challenge::derive_webauthn_challenge`.
+#[wasm_bindgen]
+pub fn derive_webauthn_challenge(
+    vrf_output_b64u: &str,
+    challenge_length: Option<u8>,
+) -> Result<String, JsValue> {
+    let vrf_output = utils::base64_url_decode(vrf_output_b64u)?;
+    let challenge_length = challenge::resolve_challenge_length(challenge_length)?;
+    let challenge = challenge::derive_webauthn_challenge(&vrf_output, challenge_length)?;
+    Ok(utils::base64_url_encode(&challenge))
+}
+
+/// Hashes the instantiated module's bytes (see `build_info::attestation_hash`) and compares
+/// against the hash embedded at build time and `expected_hash_b64u` (the release manifest's).
+/// Returns the BuildAttestationReport, which PING then reports in `buildInfo.attestation`;
+/// errors only when `module_bytes` is not a well-formed module.
+#[wasm_bindgen]
+pub fn verify_build_attestation(
+    expected_hash_b64u: Option<String>,
+    module_bytes: &[u8],
+) -> Result<JsValue, JsValue> {
+    let report = build_info::verify_module_attestation(module_bytes, expected_hash_b64u.as_deref())
+        .map_err(|e| JsValue::from_str(&format!("Invalid WASM module: {}", e)))?;
+    build_info::record_module_attestation(report.clone());
+    serde_wasm_bindgen::to_value(&report)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize attestation report: {}", e)))
+}
+
+/// Checks a challenge's `issuanceReceiptB64u` against its vrfInput and vrfOutput under the
+/// integrator's receipt key; returns `{ issuedAtMs, requestId }`. Backends can call
+/// `issuance_receipt::verify_issuance_receipt` natively instead.
+#[wasm_bindgen]
+pub fn verify_issuance_receipt(
+    receipt_b64u: String,
+    key_b64u: String,
+    vrf_input_b64u: String,
+    vrf_output_b64u: String,
+) -> Result<JsValue, JsValue> {
+    let receipt = issuance_receipt::verify_issuance_receipt(
+        &receipt_b64u,
+        &key_b64u,
+        &vrf_input_b64u,
+        &vrf_output_b64u,
+    )
+    .map_err(|e| JsValue::from_str(&e))?;
+    serde_wasm_bindgen::to_value(&receipt)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize issuance receipt: {}", e)))
+}
+
+/// Reads `{ version, meta }` from a stored EncryptedVRFKeypair without decrypting it, so
+/// storage can tell which account and credential a blob belongs to. The metadata is unverified
+/// until the blob is decrypted (UNLOCK_VRF_KEYPAIR), which fails if it was edited.
+#[wasm_bindgen]
+pub fn describe_encrypted_blob(blob: JsValue) -> Result<JsValue, JsValue> {
+    let blob: types::EncryptedVRFKeypair = serde_wasm_bindgen::from_value(blob)
+        .map_err(|e| JsValue::from_str(&format!("Invalid encrypted VRF keypair: {}", e)))?;
+    serde_wasm_bindgen::to_value(&blob.describe())
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize blob metadata: {}", e)))
+}
+
 // === WASM EXPORTS ===
 
 #[wasm_bindgen]
@@ -110,7 +217,19 @@ pub async fn handle_message(message: JsValue) -> Result<JsValue, JsValue> {
     debug!("Received message: {}", message.msg_type);
     let request_type = WorkerRequestType::from(message.msg_type.as_str());
 
+    // Self-test: after a failed known-answer test, key-handling requests are refused
+    if let Err(e) = self_test::check_key_handling_allowed(request_type) {
+        let response = VrfWorkerResponse::from_error(message.id, &e);
+        let response_json = serde_json::to_string(&response)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize response: {}", e)))?;
+        return Ok(parse(&response_json));
+    }
+
     let manager_rc = VRF_MANAGER.with(|m| m.clone());
+    // Auto-lock counts wall-clock time, so it is checked on arrival rather than by a timer
+    if let Err(e) = manager_rc.borrow_mut().enforce_auto_lock() {
+        debug!("Auto-lock check failed: {}", e);
+    }
 
     let response = match request_type {
         // Test VRF worker health
@@ -134,11 +253,25 @@ pub async fn handle_message(message: JsValue) -> Result<JsValue, JsValue> {
         WorkerRequestType::Logout => {
             handlers::handle_logout(manager_rc.clone(), message.id.clone())
         }
-        WorkerRequestType::GenerateVrfChallenge => handlers::handle_generate_vrf_challenge(
-            manager_rc.clone(),
-            message.id.clone(),
-            message.parse_payload(request_type).map_err(JsValue::from)?,
-        ),
+        WorkerRequestType::GenerateVrfChallenge => {
+            let payload: handlers::GenerateVrfChallengeRequest =
+                message.parse_payload(request_type).map_err(JsValue::from)?;
+            match handlers::cross_check_vrf_input_block(&payload).await {
+                Ok(()) => handlers::handle_generate_vrf_challenge(
+                    manager_rc.clone(),
+                    message.id.clone(),
+                    payload,
+                ),
+                Err(e) => VrfWorkerResponse::from_error(message.id.clone(), &e),
+            }
+        }
+        WorkerRequestType::GenerateVrfChallengesBatch => {
+            handlers::handle_generate_vrf_challenges_batch(
+                manager_rc.clone(),
+                message.id.clone(),
+                message.parse_payload(request_type).map_err(JsValue::from)?,
+            )
+        }
         WorkerRequestType::DeriveVrfKeypairFromPrf => {
             handlers::handle_derive_vrf_keypair_from_prf(
                 manager_rc.clone(),
@@ -201,6 +334,69 @@ pub async fn handle_message(message: JsValue) -> Result<JsValue, JsValue> {
                 message.parse_payload(request_type).map_err(JsValue::from)?,
             )
         }
+        // Deterministic conformance vectors for cross-language verifiers
+        WorkerRequestType::GenerateTestVectors => handlers::handle_generate_test_vectors(
+            manager_rc.clone(),
+            message.id.clone(),
+            message.parse_payload(request_type).map_err(JsValue::from)?,
+        ),
+        // Structural self-check of stored keypair blobs (no PRF output needed)
+        WorkerRequestType::ValidateEncryptedBlobs => handlers::handle_validate_encrypted_blobs(
+            message.id.clone(),
+            message.parse_payload(request_type).map_err(JsValue::from)?,
+        ),
+        // Encrypted session snapshots, to survive worker restarts without re-unlocking
+        WorkerRequestType::ExportSessionSnapshot => {
+            handlers::handle_export_session_snapshot(manager_rc.clone(), message.id.clone())
+        }
+        WorkerRequestType::RestoreSessionSnapshot => handlers::handle_restore_session_snapshot(
+            manager_rc.clone(),
+            message.id.clone(),
+            message.parse_payload(request_type).map_err(JsValue::from)?,
+        ),
+        WorkerRequestType::WipeAllState => {
+            handlers::handle_wipe_all_state(manager_rc.clone(), message.id.clone())
+        }
+        WorkerRequestType::LoadSnapshotRevocationState => {
+            handlers::handle_load_snapshot_revocation_state(
+                manager_rc.clone(),
+                message.id.clone(),
+                message.parse_payload(request_type).map_err(JsValue::from)?,
+            )
+        }
+        WorkerRequestType::RunSelfTest => handlers::handle_run_self_test(message.id.clone()),
+        // Block stream from the main thread, and the challenge prefetch it drives
+        WorkerRequestType::UpdateBlockInfo => handlers::handle_update_block_info(
+            manager_rc.clone(),
+            message.id.clone(),
+            message.parse_payload(request_type).map_err(JsValue::from)?,
+        ),
+        WorkerRequestType::ConfigureSessionOptions => handlers::handle_configure_session_options(
+            manager_rc.clone(),
+            message.id.clone(),
+            message.parse_payload(request_type).map_err(JsValue::from)?,
+        ),
+        WorkerRequestType::UpdateSessionPolicy => handlers::handle_update_session_policy(
+            manager_rc.clone(),
+            message.id.clone(),
+            message.parse_payload(request_type).map_err(JsValue::from)?,
+        ),
+        // Challenges requested by the signer worker over its peer port
+        WorkerRequestType::ConnectPeerPort => {
+            handlers::handle_connect_peer_port(manager_rc.clone(), message.id.clone())
+        }
+        WorkerRequestType::RequestChallenge => handlers::handle_request_challenge(
+            manager_rc.clone(),
+            message.id.clone(),
+            message.parse_payload(request_type).map_err(JsValue::from)?,
+        ),
+        // Page lifecycle hints (pagehide/pageshow), for bfcache and frozen mobile tabs
+        WorkerRequestType::SuspendHint => {
+            handlers::handle_suspend_hint(manager_rc.clone(), message.id.clone())
+        }
+        WorkerRequestType::ResumeHint => {
+            handlers::handle_resume_hint(manager_rc.clone(), message.id.clone())
+        }
     };
 
     // Convert response to JsValue
@@ -1,41 +1,89 @@
-use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
 use chacha20poly1305::{ChaCha20Poly1305, Nonce};
 use getrandom::getrandom;
 use hkdf::Hkdf;
-use js_sys::Date;
 use log::{debug, info, warn};
 use rand_core::SeedableRng;
-use sha2::{Digest, Sha256};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 // VRF and crypto imports
 use vrf_wasm::ecvrf::ECVRFKeyPair;
 use vrf_wasm::traits::WasmRngFromSeed;
 use vrf_wasm::vrf::{VRFKeyPair, VRFProof};
-use zeroize::ZeroizeOnDrop;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
+use crate::challenge::resolve_challenge_length;
+use crate::clock::{Clock, SharedClock};
 use crate::config::*;
-use crate::errors::{AesError, HkdfError, SerializationError, VrfResult, VrfWorkerError};
-use crate::handlers::DeterministicVrfKeypairResponse;
+use crate::entropy::{keypair_seed, KeypairSeed};
+use crate::errors::{
+    AesError, HkdfError, SerializationError, SessionSnapshotError, VrfResult, VrfWorkerError,
+};
+use crate::handlers::{
+    ConsumedSnapshot, DeterministicVrfKeypairResponse, ResumeInvalidation, ResumedState,
+    SessionSnapshot, SnapshotRevocationState,
+};
+use crate::issuance_receipt::IssuanceReceiptKey;
 use crate::shamir3pass::Shamir3Pass;
 use crate::types::*;
 use crate::types::{EncryptedVrfKeypairResponse, GenerateVrfKeypairBootstrapResponse};
-use crate::utils::{base64_url_decode, base64_url_encode, parse_block_height};
+use crate::utils::{base64_url_decode, base64_url_encode, decode_block_hash, parse_block_height};
+use crate::vrf_input::build_vrf_input_bytes;
 
 // === SECURE VRF KEYPAIR WRAPPER ===
 
-/// Secure VRF keypair wrapper with automatic memory zeroization
+/// Secure VRF keypair wrapper with automatic memory zeroization.
+/// Carries the scopes its unlock granted, so replacing or dropping the keypair drops them too.
 #[derive(ZeroizeOnDrop)]
 pub struct SecureVRFKeyPair {
     keypair: ECVRFKeyPair,
+    #[zeroize(skip)]
+    scopes: Vec<VrfScope>,
 }
 
 impl SecureVRFKeyPair {
+    /// Keypair with every scope (bootstrap, derivation)
     pub fn new(keypair: ECVRFKeyPair) -> Self {
-        Self { keypair }
+        Self::with_scopes(keypair, VrfScope::all())
+    }
+
+    pub fn with_scopes(keypair: ECVRFKeyPair, scopes: Vec<VrfScope>) -> Self {
+        Self { keypair, scopes }
     }
 
     pub fn inner(&self) -> &ECVRFKeyPair {
         &self.keypair
     }
+
+    pub fn scopes(&self) -> &[VrfScope] {
+        &self.scopes
+    }
+}
+
+// === CHALLENGE PREFETCH ===
+
+/// Whose challenge to keep warm: set by CONFIGURE_SESSION_OPTIONS with `prefetchChallenge`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChallengePrefetch {
+    pub user_id: String,
+    pub rp_id: String,
+    pub challenge_length: u8,
+}
+
+/// Challenge proven for the latest known block, ahead of the signing request that needs it
+pub struct PrefetchedChallenge {
+    pub block_height: u64,
+    pub challenge: VRFChallengeData,
+}
+
+// === SUSPEND / RESUME ===
+
+/// Recorded by SUSPEND_HINT while the page is hidden (bfcache, frozen mobile tab)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Suspension {
+    /// Wall-clock time of the hint
+    pub suspended_at_ms: f64,
+    pub dropped_prefetched_challenge: bool,
 }
 
 // === VRF KEY MANAGER ===
@@ -49,6 +97,42 @@ pub struct VRFKeyManager {
     pub relay_server_url: Option<String>,
     pub apply_lock_route: Option<String>,
     pub remove_lock_route: Option<String>,
+    // Session snapshots
+    /// Bound into every exported snapshot. Random per worker, so a restarted worker restores
+    /// nothing until LOAD_SNAPSHOT_REVOCATION_STATE; WipeAllState rotates it.
+    pub session_epoch: u32,
+    /// Snapshots already restored (pruned once expired)
+    pub consumed_snapshots: Vec<ConsumedSnapshot>,
+    /// Set once LOAD_SNAPSHOT_REVOCATION_STATE or WIPE_ALL_STATE has run: a loaded state
+    /// must not roll back a later one
+    pub revocation_state_settled: bool,
+    // Challenge prefetch
+    /// Latest block pushed by UPDATE_BLOCK_INFO (height, base58 hash)
+    pub latest_block: Option<(u64, String)>,
+    pub challenge_prefetch: Option<ChallengePrefetch>,
+    pub prefetched_challenge: Option<PrefetchedChallenge>,
+    // Worker-to-worker channel
+    /// Set by CONNECT_PEER_PORT: the signer worker may send REQUEST_CHALLENGE over its port
+    pub peer_connected: bool,
+    // Auto-lock and suspend/resume
+    /// Set by CONFIGURE_SESSION_OPTIONS with `autoLockAfterMs`: the session locks this long
+    /// (wall-clock) after it was unlocked
+    pub auto_lock_after_ms: Option<f64>,
+    /// Set by SUSPEND_HINT until the next RESUME_HINT
+    pub suspension: Option<Suspension>,
+    // Signer session policy
+    /// Set by UPDATE_SESSION_POLICY (the signer's WorkerPolicy.maxSessionDurationMs): challenges
+    /// and snapshots are refused once this long has passed since unlock. Kept across LOGOUT.
+    pub max_session_duration_ms: Option<f64>,
+    /// Signer session epoch last pushed with UPDATE_SESSION_POLICY. Snapshots carry it and do
+    /// not restore in a later one (after the signer re-authenticated or wiped its state).
+    pub signer_session_epoch: Option<u32>,
+    // Issuance receipts
+    /// Set by CONFIGURE_SESSION_OPTIONS with `issuanceReceiptKeyB64u`: challenges handed out
+    /// carry a receipt MACed with it
+    pub issuance_receipt_key: Option<IssuanceReceiptKey>,
+    /// Source of every time read (a ManualClock in tests)
+    pub clock: SharedClock,
 }
 
 impl VRFKeyManager {
@@ -80,6 +164,19 @@ impl VRFKeyManager {
             relay_server_url,
             apply_lock_route,
             remove_lock_route,
+            session_epoch: random_session_epoch(None),
+            consumed_snapshots: Vec::new(),
+            revocation_state_settled: false,
+            latest_block: None,
+            challenge_prefetch: None,
+            prefetched_challenge: None,
+            peer_connected: false,
+            auto_lock_after_ms: None,
+            suspension: None,
+            max_session_duration_ms: None,
+            signer_session_epoch: None,
+            issuance_receipt_key: None,
+            clock: SharedClock::default(),
         }
     }
 
@@ -96,6 +193,7 @@ impl VRFKeyManager {
     pub fn generate_vrf_keypair_bootstrap(
         &mut self,
         vrf_input_data: Option<VRFInputData>,
+        extra_entropy: Option<Vec<u8>>,
     ) -> VrfResult<GenerateVrfKeypairBootstrapResponse> {
         info!("Generating VRF keypair for bootstrapping");
         debug!("VRF keypair will be stored in memory unencrypted until PRF encryption");
@@ -103,8 +201,8 @@ impl VRFKeyManager {
         // Clear any existing keypair (automatic zeroization via ZeroizeOnDrop)
         self.vrf_keypair.take();
 
-        // Generate VRF keypair with cryptographically secure randomness
-        let vrf_keypair = self.generate_vrf_keypair()?;
+        // Generate VRF keypair from health-checked randomness
+        let (vrf_keypair, entropy_sources) = self.generate_vrf_keypair(extra_entropy.as_deref())?;
 
         // Get public key bytes for response
         let vrf_public_key_bytes = bincode::serialize(&vrf_keypair.pk).map_err(|e| {
@@ -117,19 +215,23 @@ impl VRFKeyManager {
         // Store VRF keypair in memory (unencrypted)
         self.vrf_keypair = Some(SecureVRFKeyPair::new(vrf_keypair));
         self.session_active = true;
-        self.session_start_time = Date::now();
+        self.session_start_time = self.clock.now_ms();
 
         let mut result = GenerateVrfKeypairBootstrapResponse {
             vrf_public_key: vrf_public_key_b64,
             vrf_challenge_data: None,
+            entropy_sources,
         };
 
         // Generate VRF challenge if input parameters provided
         if let Some(vrf_input_data) = vrf_input_data {
             debug!("Generating VRF challenge using bootstrapped keypair");
             let vrf_keypair = self.vrf_keypair.as_ref().unwrap().inner();
-            let challenge_result =
-                self.generate_vrf_challenge_with_keypair(vrf_keypair, vrf_input_data)?;
+            let challenge_result = self.generate_vrf_challenge_with_keypair(
+                vrf_keypair,
+                vrf_input_data,
+                DEFAULT_CHALLENGE_LENGTH,
+            )?;
             result.vrf_challenge_data = Some(challenge_result);
         }
 
@@ -143,6 +245,7 @@ impl VRFKeyManager {
         &mut self,
         expected_public_key: String,
         prf_key: Vec<u8>,
+        meta: VrfKeypairBlobMeta,
     ) -> VrfResult<EncryptedVrfKeypairResponse> {
         debug!(
             "Encrypting VRF keypair with PRF output. Expected public key: {}...",
@@ -153,6 +256,7 @@ impl VRFKeyManager {
         if !self.session_active || self.vrf_keypair.is_none() {
             return Err(VrfWorkerError::NoVrfKeypair);
         }
+        self.require_scope(VrfScope::DeviceLinking)?;
 
         // Get the VRF keypair from memory and extract its public key
         let vrf_keypair = self.vrf_keypair.as_ref().unwrap().inner();
@@ -171,7 +275,7 @@ impl VRFKeyManager {
 
         // Encrypt the VRF keypair
         let (vrf_public_key, encrypted_vrf_keypair) =
-            self.encrypt_vrf_keypair_data(vrf_keypair, &prf_key)?;
+            self.encrypt_vrf_keypair_data(vrf_keypair, &prf_key, meta)?;
         debug!("VRF keypair encrypted with PRF output");
 
         Ok(EncryptedVrfKeypairResponse {
@@ -180,13 +284,25 @@ impl VRFKeyManager {
         })
     }
 
+    /// Decrypts the keypair with the PRF output and installs it with `scopes`, replacing the
+    /// session's keypair and scopes
     pub fn unlock_vrf_keypair(
         &mut self,
         near_account_id: String,
         encrypted_vrf_keypair: EncryptedVRFKeypair,
         prf_key: Vec<u8>,
+        scopes: Vec<VrfScope>,
     ) -> VrfResult<()> {
         debug!("Unlocking VRF keypair for {}", near_account_id);
+        // Refuse another account's blob before spending a decryption on it
+        if let Some(meta) = &encrypted_vrf_keypair.meta {
+            if meta.account_id != near_account_id {
+                return Err(VrfWorkerError::BlobAccountMismatch {
+                    expected: near_account_id,
+                    actual: meta.account_id.clone(),
+                });
+            }
+        }
         // Clear any existing keypair (automatic zeroization via ZeroizeOnDrop)
         self.vrf_keypair.take();
 
@@ -194,9 +310,9 @@ impl VRFKeyManager {
         let decrypted_keypair = self.decrypt_vrf_keypair(encrypted_vrf_keypair, prf_key)?;
 
         // Wrap in secure container for automatic zeroization
-        self.vrf_keypair = Some(SecureVRFKeyPair::new(decrypted_keypair));
+        self.vrf_keypair = Some(SecureVRFKeyPair::with_scopes(decrypted_keypair, scopes));
         self.session_active = true;
-        self.session_start_time = Date::now();
+        self.session_start_time = self.clock.now_ms();
 
         debug!("VRF keypair unlocked successfully");
         Ok(())
@@ -207,27 +323,34 @@ impl VRFKeyManager {
         &mut self,
         near_account_id: String,
         keypair_data: VRFKeypairData,
+        scopes: Vec<VrfScope>,
     ) -> VrfResult<()> {
         info!("Loading VRF keypair for {}", near_account_id);
         // Clear any existing keypair
         self.vrf_keypair.take();
         // Reconstruct ECVRFKeyPair from stored bytes
         let keypair: ECVRFKeyPair = bincode::deserialize(&keypair_data.keypair_bytes)?;
-        self.vrf_keypair = Some(SecureVRFKeyPair::new(keypair));
+        self.vrf_keypair = Some(SecureVRFKeyPair::with_scopes(keypair, scopes));
         self.session_active = true;
-        self.session_start_time = Date::now();
+        self.session_start_time = self.clock.now_ms();
         Ok(())
     }
 
-    pub fn generate_vrf_challenge(&self, input_data: VRFInputData) -> VrfResult<VRFChallengeData> {
+    pub fn generate_vrf_challenge(
+        &self,
+        input_data: VRFInputData,
+        challenge_length: u8,
+    ) -> VrfResult<VRFChallengeData> {
         if !self.session_active || self.vrf_keypair.is_none() {
             return Err(VrfWorkerError::VrfNotUnlocked);
         }
+        self.require_scope(VrfScope::ChallengeGeneration)?;
+        self.require_session_within_duration()?;
 
         info!("Generating VRF challenge");
         let vrf_keypair = self.vrf_keypair.as_ref().unwrap().inner();
 
-        self.generate_vrf_challenge_with_keypair(vrf_keypair, input_data)
+        self.generate_vrf_challenge_with_keypair(vrf_keypair, input_data, challenge_length)
     }
 
     /// Generate VRF challenge using a specific keypair (can be in-memory or provided)
@@ -235,69 +358,209 @@ impl VRFKeyManager {
         &self,
         vrf_keypair: &ECVRFKeyPair,
         input_data: VRFInputData,
+        challenge_length: u8,
     ) -> VrfResult<VRFChallengeData> {
         debug!("Generating VRF challenge using provided keypair");
+        prove_vrf_challenge(vrf_keypair, input_data, challenge_length)
+    }
 
-        // Construct VRF input according to specification from the contract test
-        let domain_separator = VRF_DOMAIN_SEPARATOR;
-        let user_id_bytes = input_data.user_id.as_bytes();
-        let rp_id_bytes = input_data.rp_id.as_bytes();
-        let block_height_num = parse_block_height(&input_data.block_height)?;
-        let block_height_bytes = block_height_num.to_le_bytes();
-
-        // Decode block_hash from base58 string to bytes
-        let block_hash_bytes = bs58::decode(&input_data.block_hash)
-            .into_vec()
-            .map_err(|e| VrfWorkerError::invalid_format(&format!("invalid blockHash: {}", e)))?;
-
-        // Concatenate all input components following the test pattern
-        let mut vrf_input_data = Vec::new();
-        vrf_input_data.extend_from_slice(domain_separator);
-        vrf_input_data.extend_from_slice(user_id_bytes);
-        vrf_input_data.extend_from_slice(rp_id_bytes);
-        vrf_input_data.extend_from_slice(&block_height_bytes);
-        vrf_input_data.extend_from_slice(&block_hash_bytes);
-
-        // Hash the input data (VRF input should be hashed)
-        let vrf_input = Sha256::digest(&vrf_input_data).to_vec();
-
-        // Generate VRF proof and output using the proper vrf-wasm API
-        let proof = vrf_keypair.prove(&vrf_input);
-        let vrf_output = proof.to_hash().to_vec();
-
-        let proof_bytes = bincode::serialize(&proof).map_err(|e| {
-            VrfWorkerError::SerializationError(SerializationError::VrfKeypairSerialization(
-                format!("{:?}", e),
-            ))
-        })?;
-        let pk_bytes = bincode::serialize(&vrf_keypair.pk).map_err(|e| {
-            VrfWorkerError::SerializationError(SerializationError::VrfPublicKeySerialization(
-                format!("{:?}", e),
-            ))
-        })?;
-        let result = VRFChallengeData {
-            vrf_input: base64_url_encode(&vrf_input),
-            vrf_output: base64_url_encode(&vrf_output),
-            vrf_proof: base64_url_encode(&proof_bytes),
-            vrf_public_key: base64_url_encode(&pk_bytes),
-            user_id: input_data.user_id,
-            rp_id: input_data.rp_id,
-            block_height: input_data.block_height,
-            block_hash: base64_url_encode(&block_hash_bytes),
+    /// Generate challenges for several inputs with the session keypair, in input order.
+    /// Items fail individually (e.g. a malformed anchor); only a locked session fails the batch.
+    pub fn generate_vrf_challenges_batch(
+        &self,
+        inputs: Vec<VRFInputData>,
+        challenge_length: u8,
+        parallel: bool,
+    ) -> VrfResult<Vec<Result<VRFChallengeData, String>>> {
+        if !self.session_active || self.vrf_keypair.is_none() {
+            return Err(VrfWorkerError::VrfNotUnlocked);
+        }
+        self.require_scope(VrfScope::ChallengeGeneration)?;
+        self.require_session_within_duration()?;
+        let challenge_length = resolve_challenge_length(Some(challenge_length))?;
+        let vrf_keypair = self.vrf_keypair.as_ref().unwrap().inner();
+
+        info!("Generating {} VRF challenges", inputs.len());
+        Ok(crate::parallel::map_in_order(inputs, parallel, |input| {
+            prove_vrf_challenge(vrf_keypair, input, challenge_length).map_err(|e| e.to_string())
+        }))
+    }
+
+    /// Sets (or with `None` stops) the session's challenge prefetch, discarding the challenge
+    /// prefetched for the previous target. Returns whether a challenge was prefetched now.
+    pub fn configure_challenge_prefetch(
+        &mut self,
+        prefetch: Option<ChallengePrefetch>,
+    ) -> VrfResult<bool> {
+        self.prefetched_challenge = None;
+        self.challenge_prefetch = prefetch;
+        self.prefetch_challenge()
+    }
+
+    /// Records a block from the main thread's block stream. When the block advances, the
+    /// prefetched challenge is stale: it is dropped and, if prefetching in an unlocked session,
+    /// replaced by one for the new block. Returns whether a challenge was prefetched.
+    pub fn update_block_info(&mut self, block_height: &str, block_hash: &str) -> VrfResult<bool> {
+        let height = parse_block_height(block_height)?;
+        decode_block_hash(block_hash)?;
+        if matches!(&self.latest_block, Some((latest, _)) if height <= *latest) {
+            return Ok(false);
+        }
+        self.latest_block = Some((height, block_hash.to_string()));
+        self.prefetched_challenge = None;
+        self.prefetch_challenge()
+    }
+
+    fn prefetch_challenge(&mut self) -> VrfResult<bool> {
+        let (Some(prefetch), Some((block_height, block_hash)), Some(vrf_keypair)) = (
+            &self.challenge_prefetch,
+            &self.latest_block,
+            &self.vrf_keypair,
+        ) else {
+            return Ok(false);
+        };
+        if !self.session_active
+            || !vrf_keypair
+                .scopes()
+                .contains(&VrfScope::ChallengeGeneration)
+        {
+            return Ok(false);
+        }
+        let block_height = *block_height;
+        let input = VRFInputData::near_block(
+            &prefetch.user_id,
+            &prefetch.rp_id,
+            &block_height.to_string(),
+            block_hash,
+        );
+        let challenge = prove_vrf_challenge(vrf_keypair.inner(), input, prefetch.challenge_length)?;
+        debug!("Prefetched VRF challenge for block {}", block_height);
+        self.prefetched_challenge = Some(PrefetchedChallenge {
+            block_height,
+            challenge,
+        });
+        Ok(true)
+    }
+
+    /// The prefetched challenge, if it can stand in for a fresh one for `input`: same user,
+    /// rpId and length, proven with the session keypair, and at most
+    /// PREFETCHED_CHALLENGE_FRESHNESS_BLOCKS behind the input's block
+    pub fn prefetched_challenge_for(
+        &self,
+        input: &VRFInputData,
+        challenge_length: u8,
+    ) -> Option<VRFChallengeData> {
+        let prefetch = self.challenge_prefetch.as_ref()?;
+        let prefetched = self.prefetched_challenge.as_ref()?;
+        let VrfAnchor::NearBlock { block_height, .. } = &input.anchor else {
+            return None;
         };
+        let requested_height = parse_block_height(block_height).ok()?;
+        let fresh = prefetched
+            .block_height
+            .saturating_add(PREFETCHED_CHALLENGE_FRESHNESS_BLOCKS)
+            >= requested_height;
+        let matches = prefetch.user_id == input.user_id
+            && prefetch.rp_id == input.rp_id
+            && prefetch.challenge_length == challenge_length;
+        // A keypair installed since the prefetch (unlock, restore) makes the proof useless
+        let public_key = bincode::serialize(&self.vrf_keypair.as_ref()?.inner().pk).ok()?;
+        let same_keypair = prefetched.challenge.vrf_public_key == base64_url_encode(&public_key);
+
+        (self.session_active
+            && fresh
+            && matches
+            && same_keypair
+            && self.has_scope(VrfScope::ChallengeGeneration)
+            && self.require_session_within_duration().is_ok())
+        .then(|| prefetched.challenge.clone())
+    }
 
-        Ok(result)
+    /// Whether the unlocked keypair was granted `scope`
+    pub fn has_scope(&self, scope: VrfScope) -> bool {
+        self.vrf_keypair
+            .as_ref()
+            .is_some_and(|keypair| keypair.scopes().contains(&scope))
+    }
+
+    /// Fails with ScopeNotGranted unless the unlocked keypair was granted `scope`
+    pub fn require_scope(&self, scope: VrfScope) -> VrfResult<()> {
+        if self.has_scope(scope) {
+            Ok(())
+        } else {
+            Err(VrfWorkerError::ScopeNotGranted { required: scope })
+        }
+    }
+
+    /// Challenge for a signer worker's REQUEST_CHALLENGE. Without an anchor the challenge is
+    /// for the latest block pushed with UPDATE_BLOCK_INFO, served from the prefetch when it
+    /// matches.
+    pub fn generate_peer_challenge(
+        &self,
+        user_id: &str,
+        rp_id: &str,
+        anchor: Option<VrfAnchor>,
+        challenge_length: u8,
+    ) -> VrfResult<VRFChallengeData> {
+        if !self.peer_connected {
+            return Err(VrfWorkerError::PeerPortNotConnected);
+        }
+        let input = match anchor {
+            Some(anchor) => VRFInputData {
+                user_id: user_id.to_string(),
+                rp_id: rp_id.to_string(),
+                anchor,
+            },
+            None => {
+                let (block_height, block_hash) = self
+                    .latest_block
+                    .as_ref()
+                    .ok_or_else(|| VrfWorkerError::missing_field("anchor (no block pushed yet)"))?;
+                VRFInputData::near_block(user_id, rp_id, &block_height.to_string(), block_hash)
+            }
+        };
+        if let Some(prefetched) = self.prefetched_challenge_for(&input, challenge_length) {
+            return Ok(prefetched);
+        }
+        self.generate_vrf_challenge(input, challenge_length)
+    }
+
+    /// Attaches an issuance receipt for `request_id` when the session issues them; the
+    /// challenge is returned unchanged otherwise
+    pub fn with_issuance_receipt(
+        &self,
+        mut challenge: VRFChallengeData,
+        request_id: Option<&str>,
+    ) -> VrfResult<VRFChallengeData> {
+        let Some(key) = &self.issuance_receipt_key else {
+            return Ok(challenge);
+        };
+        let receipt = key
+            .issue(
+                &challenge.vrf_input,
+                &challenge.vrf_output,
+                self.clock.now_ms().max(0.0) as u64,
+                request_id.unwrap_or(""),
+            )
+            .map_err(|e| VrfWorkerError::invalid_format(&e))?;
+        challenge.issuance_receipt_b64u = Some(receipt);
+        Ok(challenge)
     }
 
     pub fn get_vrf_status(&self) -> serde_json::Value {
         let session_duration = if self.session_active {
-            Date::now() - self.session_start_time
+            self.clock.now_ms() - self.session_start_time
         } else {
             0.0
         };
+        let scopes = match &self.vrf_keypair {
+            Some(keypair) if self.session_active => keypair.scopes().to_vec(),
+            _ => Vec::new(),
+        };
         serde_json::json!({
             "active": self.session_active,
-            "sessionDuration": session_duration
+            "sessionDuration": session_duration,
+            "scopes": scopes
         })
     }
 
@@ -309,16 +572,344 @@ impl VRFKeyManager {
         // Clear session data
         self.session_active = false;
         self.session_start_time = 0.0;
+        self.challenge_prefetch = None;
+        self.prefetched_challenge = None;
+        self.auto_lock_after_ms = None;
+        self.issuance_receipt_key = None;
+        Ok(())
+    }
+
+    /// Locks the session once `auto_lock_after_ms` of wall-clock time has passed since unlock.
+    /// Paused while suspended: RESUME_HINT applies it, counting the time spent frozen.
+    /// Snapshots of the locked session carry the limit and stop restoring with it.
+    /// Returns whether the session was locked.
+    pub fn enforce_auto_lock(&mut self) -> VrfResult<bool> {
+        if self.suspension.is_some() || !self.auto_lock_due(self.clock.now_ms()) {
+            return Ok(false);
+        }
+        info!("VRF session auto-locked");
+        self.logout()?;
+        Ok(true)
+    }
+
+    /// UPDATE_SESSION_POLICY: the signer worker's session epoch and maxSessionDurationMs.
+    /// Returns whether the epoch moved on, revoking the snapshots exported before.
+    pub fn update_session_policy(
+        &mut self,
+        signer_session_epoch: u32,
+        max_session_duration_ms: Option<f64>,
+    ) -> bool {
+        let moved_on = self
+            .signer_session_epoch
+            .is_some_and(|epoch| epoch != signer_session_epoch);
+        if moved_on {
+            info!(
+                "Signer session moved on to epoch {}; earlier snapshots are revoked",
+                signer_session_epoch
+            );
+        }
+        self.signer_session_epoch = Some(signer_session_epoch);
+        self.max_session_duration_ms = max_session_duration_ms;
+        moved_on
+    }
+
+    /// Refuses an unlocked session past `max_session_duration_ms`, at the boundary itself
+    /// (as the signer worker does)
+    pub fn require_session_within_duration(&self) -> VrfResult<()> {
+        match self.max_session_duration_ms {
+            Some(max_ms) if self.session_active => {
+                let expired_at_ms = self.session_start_time + max_ms;
+                if self.clock.now_ms() >= expired_at_ms {
+                    return Err(VrfWorkerError::SessionExpired {
+                        started_at_ms: self.session_start_time,
+                        expired_at_ms,
+                    });
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn auto_lock_due(&self, now_ms: f64) -> bool {
+        match self.auto_lock_after_ms {
+            Some(after_ms) if self.session_active => now_ms - self.session_start_time >= after_ms,
+            _ => false,
+        }
+    }
+
+    /// SUSPEND_HINT: drops the prefetched challenge (its block will be stale on resume) and
+    /// records the wall-clock time. A repeated hint keeps the first suspension.
+    pub fn suspend(&mut self) -> Suspension {
+        let now_ms = self.clock.now_ms();
+        let dropped = self.prefetched_challenge.take().is_some();
+        let suspension = self.suspension.get_or_insert(Suspension {
+            suspended_at_ms: now_ms,
+            dropped_prefetched_challenge: false,
+        });
+        suspension.dropped_prefetched_challenge |= dropped;
+        *suspension
+    }
+
+    /// RESUME_HINT: applies the auto-lock with the time spent suspended included, prunes
+    /// expired snapshot records and forgets the latest block, so prefetch waits for the next
+    /// UPDATE_BLOCK_INFO. Reports what was invalidated; also valid without a SUSPEND_HINT.
+    pub fn resume(&mut self) -> VrfResult<ResumedState> {
+        let now_ms = self.clock.now_ms();
+        let suspension = self.suspension.take();
+        let mut invalidated = Vec::new();
+        if suspension.is_some_and(|s| s.dropped_prefetched_challenge)
+            || self.prefetched_challenge.take().is_some()
+        {
+            invalidated.push(ResumeInvalidation::PrefetchedChallenge);
+        }
+        if self.latest_block.take().is_some() {
+            invalidated.push(ResumeInvalidation::BlockInfo);
+        }
+        if self.enforce_auto_lock()? {
+            invalidated.push(ResumeInvalidation::SessionLocked);
+        }
+        prune_consumed_snapshots(&mut self.consumed_snapshots, now_ms);
+
+        Ok(ResumedState {
+            suspended_ms: suspension
+                .map(|s| (now_ms - s.suspended_at_ms).max(0.0))
+                .unwrap_or(0.0),
+            invalidated,
+            session_active: self.session_active,
+        })
+    }
+
+    /// Clears the session and rotates the snapshot epoch, so snapshots exported before the
+    /// wipe can no longer be restored, here or in a worker loading the returned state
+    pub fn wipe_all_state(&mut self) -> VrfResult<()> {
+        self.logout()?;
+        self.session_epoch = random_session_epoch(Some(self.session_epoch));
+        self.consumed_snapshots.clear();
+        self.revocation_state_settled = true;
+        self.peer_connected = false;
+        self.suspension = None;
+        Ok(())
+    }
+
+    /// The epoch and the unexpired consumed snapshots, for the main thread to hand to the
+    /// worker that replaces this one
+    pub fn revocation_state(&self) -> SnapshotRevocationState {
+        let mut consumed_snapshots = self.consumed_snapshots.clone();
+        prune_consumed_snapshots(&mut consumed_snapshots, self.clock.now_ms());
+        SnapshotRevocationState {
+            epoch: self.session_epoch,
+            consumed_snapshots,
+        }
+    }
+
+    /// LOAD_SNAPSHOT_REVOCATION_STATE: continues the revocation state of the worker this one
+    /// replaces. Accepted once, before WIPE_ALL_STATE; consumed snapshots already recorded are
+    /// kept.
+    pub fn load_revocation_state(&mut self, state: SnapshotRevocationState) -> VrfResult<()> {
+        if self.revocation_state_settled {
+            return Err(VrfWorkerError::SessionSnapshot(
+                SessionSnapshotError::Invalid("revocation state already loaded or wiped".into()),
+            ));
+        }
+        self.revocation_state_settled = true;
+        if state.epoch != self.session_epoch {
+            // Snapshots this worker exported belong to its own random epoch
+            self.consumed_snapshots.clear();
+        }
+        self.session_epoch = state.epoch;
+        for consumed in state.consumed_snapshots {
+            if !self
+                .consumed_snapshots
+                .iter()
+                .any(|c| c.snapshot_id == consumed.snapshot_id)
+            {
+                self.consumed_snapshots.push(consumed);
+            }
+        }
+        prune_consumed_snapshots(&mut self.consumed_snapshots, self.clock.now_ms());
+        Ok(())
+    }
+
+    /// Encrypts the unlocked session under a fresh random key.
+    /// Returns the snapshot and the key as a base64url one-time token.
+    pub fn export_session_snapshot(&self) -> VrfResult<(SessionSnapshot, String)> {
+        if !self.session_active {
+            return Err(VrfWorkerError::VrfNotUnlocked);
+        }
+        let vrf_keypair = self
+            .vrf_keypair
+            .as_ref()
+            .ok_or(VrfWorkerError::NoVrfKeypair)?;
+        self.require_scope(VrfScope::Export)?;
+        self.require_session_within_duration()?;
+
+        let mut token_key = [0u8; CHACHA20_KEY_SIZE];
+        let mut snapshot_id = [0u8; SESSION_SNAPSHOT_ID_SIZE];
+        let mut nonce_bytes = [0u8; CHACHA20_NONCE_SIZE];
+        for buf in [
+            &mut token_key[..],
+            &mut snapshot_id[..],
+            &mut nonce_bytes[..],
+        ] {
+            getrandom(buf).map_err(|e| {
+                VrfWorkerError::AesGcmError(AesError::IvGenerationFailed(e.to_string()))
+            })?;
+        }
+
+        let mut snapshot = SessionSnapshot {
+            version: SESSION_SNAPSHOT_VERSION,
+            snapshot_id: base64_url_encode(&snapshot_id),
+            epoch: self.session_epoch,
+            exported_at_ms: self.clock.now_ms(),
+            nonce_b64u: base64_url_encode(&nonce_bytes),
+            ciphertext_b64u: String::new(),
+        };
+
+        let mut plaintext = bincode::serialize(&SessionSnapshotPayload {
+            keypair_bytes: bincode::serialize(vrf_keypair.inner())?,
+            session_start_time: self.session_start_time,
+            scopes: vrf_keypair.scopes().to_vec(),
+            auto_lock_after_ms: self.auto_lock_after_ms,
+            max_session_duration_ms: self.max_session_duration_ms,
+            signer_session_epoch: self.signer_session_epoch,
+        })?;
+        let cipher = ChaCha20Poly1305::new(chacha20poly1305::Key::from_slice(&token_key));
+        let ciphertext = cipher.encrypt(
+            Nonce::from_slice(&nonce_bytes),
+            Payload {
+                msg: &plaintext,
+                aad: &session_snapshot_aad(&snapshot, &snapshot_id),
+            },
+        );
+        plaintext.zeroize();
+        let ciphertext = ciphertext
+            .map_err(|e| VrfWorkerError::AesGcmError(AesError::EncryptionFailed(e.to_string())))?;
+        snapshot.ciphertext_b64u = base64_url_encode(&ciphertext);
+
+        let token = base64_url_encode(&token_key);
+        token_key.zeroize();
+        Ok((snapshot, token))
+    }
+
+    /// Decrypts a snapshot with its token and reinstates the session.
+    /// Each snapshot restores at most once, within SESSION_SNAPSHOT_MAX_AGE_MS of export, only
+    /// in the epoch and signer session epoch it was exported under, and only while its session
+    /// is within the auto-lock and maxSessionDurationMs it had (or this worker's, if shorter).
+    pub fn restore_session_snapshot(
+        &mut self,
+        snapshot: &SessionSnapshot,
+        token: &str,
+    ) -> VrfResult<()> {
+        let invalid =
+            |msg: &str| VrfWorkerError::SessionSnapshot(SessionSnapshotError::Invalid(msg.into()));
+        if snapshot.version != SESSION_SNAPSHOT_VERSION {
+            return Err(invalid(&format!(
+                "unsupported version {}",
+                snapshot.version
+            )));
+        }
+        let snapshot_id = base64_url_decode(&snapshot.snapshot_id)
+            .map_err(|_| invalid("snapshotId is not base64url"))?;
+        let nonce_bytes = base64_url_decode(&snapshot.nonce_b64u)
+            .map_err(|_| invalid("nonce is not base64url"))?;
+        let ciphertext = base64_url_decode(&snapshot.ciphertext_b64u)
+            .map_err(|_| invalid("ciphertext is not base64url"))?;
+        let mut token_key =
+            base64_url_decode(token).map_err(|_| invalid("token is not base64url"))?;
+        if token_key.len() != CHACHA20_KEY_SIZE || nonce_bytes.len() != CHACHA20_NONCE_SIZE {
+            token_key.zeroize();
+            return Err(invalid("token or nonce has the wrong length"));
+        }
+
+        // Authenticates the header (epoch, export time) along with the session state
+        let cipher = ChaCha20Poly1305::new(chacha20poly1305::Key::from_slice(&token_key));
+        token_key.zeroize();
+        let mut plaintext = cipher
+            .decrypt(
+                Nonce::from_slice(&nonce_bytes),
+                Payload {
+                    msg: &ciphertext,
+                    aad: &session_snapshot_aad(snapshot, &snapshot_id),
+                },
+            )
+            .map_err(|_| invalid("token does not match snapshot"))?;
+
+        let result = self.check_and_reinstate_snapshot(snapshot, &plaintext);
+        plaintext.zeroize();
+        result
+    }
+
+    fn check_and_reinstate_snapshot(
+        &mut self,
+        snapshot: &SessionSnapshot,
+        plaintext: &[u8],
+    ) -> VrfResult<()> {
+        let now_ms = self.clock.now_ms();
+        if snapshot.epoch != self.session_epoch {
+            return Err(VrfWorkerError::SessionSnapshot(
+                SessionSnapshotError::Revoked,
+            ));
+        }
+        prune_consumed_snapshots(&mut self.consumed_snapshots, now_ms);
+        if self
+            .consumed_snapshots
+            .iter()
+            .any(|c| c.snapshot_id == snapshot.snapshot_id)
+        {
+            return Err(VrfWorkerError::SessionSnapshot(
+                SessionSnapshotError::Reused,
+            ));
+        }
+        let age_ms = now_ms - snapshot.exported_at_ms;
+        if !(0.0..=SESSION_SNAPSHOT_MAX_AGE_MS).contains(&age_ms) {
+            return Err(VrfWorkerError::SessionSnapshot(
+                SessionSnapshotError::Expired,
+            ));
+        }
+
+        let payload: SessionSnapshotPayload = bincode::deserialize(plaintext)?;
+        if matches!(
+            (payload.signer_session_epoch, self.signer_session_epoch),
+            (Some(exported_in), Some(current)) if exported_in != current
+        ) {
+            return Err(VrfWorkerError::SessionSnapshot(
+                SessionSnapshotError::Revoked,
+            ));
+        }
+        let max_session_duration_ms = shorter_limit(
+            payload.max_session_duration_ms,
+            self.max_session_duration_ms,
+        );
+        let session_limit_ms = shorter_limit(payload.auto_lock_after_ms, max_session_duration_ms);
+        if session_limit_ms.is_some_and(|limit| now_ms - payload.session_start_time >= limit) {
+            return Err(VrfWorkerError::SessionSnapshot(
+                SessionSnapshotError::Expired,
+            ));
+        }
+        let vrf_keypair: ECVRFKeyPair = bincode::deserialize(&payload.keypair_bytes)?;
+        self.consumed_snapshots.push(ConsumedSnapshot {
+            snapshot_id: snapshot.snapshot_id.clone(),
+            exported_at_ms: snapshot.exported_at_ms,
+        });
+        self.vrf_keypair = Some(SecureVRFKeyPair::with_scopes(vrf_keypair, payload.scopes));
+        self.session_active = true;
+        self.session_start_time = payload.session_start_time;
+        self.auto_lock_after_ms = payload.auto_lock_after_ms;
+        self.max_session_duration_ms = max_session_duration_ms;
         Ok(())
     }
 
     /// Derive deterministic VRF keypair from PRF output for recovery
     /// Optionally generates VRF challenge if input parameters are provided
     /// This is the main entry point for deterministic VRF derivation
+    /// The credential and device, when known, are recorded in the encrypted keypair's metadata
     pub fn derive_vrf_keypair_from_prf(
         &self,
         prf_output: Vec<u8>,
         near_account_id: String,
+        credential_id_b64u: Option<String>,
+        device_number: Option<u32>,
         vrf_input_params: Option<VRFInputData>,
     ) -> VrfResult<(DeterministicVrfKeypairResponse, ECVRFKeyPair)> {
         if prf_output.is_empty() {
@@ -337,13 +928,23 @@ impl VRFKeyManager {
         let vrf_public_key_b64 = base64_url_encode(&vrf_public_key_bytes);
 
         // Encrypt the VRF keypair with the same PRF output used for derivation (for local storage)
+        let meta = VrfKeypairBlobMeta {
+            account_id: near_account_id,
+            credential_id_b64u,
+            device_number,
+            created_at_ms: self.clock.now_ms(),
+            derivation_version: VRF_KEYPAIR_DERIVATION_VERSION,
+        };
         let (_public_key, encrypted_vrf_keypair) =
-            self.encrypt_vrf_keypair_data(&vrf_keypair, &prf_output)?;
+            self.encrypt_vrf_keypair_data(&vrf_keypair, &prf_output, meta)?;
 
         // Generate VRF challenge if input parameters provided
         let vrf_challenge_data = if let Some(vrf_input_params) = vrf_input_params {
-            let challenge_data =
-                self.generate_vrf_challenge_with_keypair(&vrf_keypair, vrf_input_params)?;
+            let challenge_data = self.generate_vrf_challenge_with_keypair(
+                &vrf_keypair,
+                vrf_input_params,
+                DEFAULT_CHALLENGE_LENGTH,
+            )?;
             Some(challenge_data)
         } else {
             None
@@ -375,7 +976,7 @@ impl VRFKeyManager {
         self.vrf_keypair.take();
         self.vrf_keypair = Some(SecureVRFKeyPair::new(vrf_keypair));
         self.session_active = true;
-        self.session_start_time = js_sys::Date::now();
+        self.session_start_time = self.clock.now_ms();
         debug!("VRF keypair stored in memory for future operations");
     }
 
@@ -412,8 +1013,15 @@ impl VRFKeyManager {
         let cipher = ChaCha20Poly1305::new(key);
         let nonce = Nonce::from_slice(&iv_nonce_bytes);
 
+        // v1 blobs have no associated data; v2 blobs fail here if their metadata was edited
         let decrypted_data = cipher
-            .decrypt(nonce, encrypted_data.as_ref())
+            .decrypt(
+                nonce,
+                Payload {
+                    msg: &encrypted_data,
+                    aad: &encrypted_vrf_keypair.aad(),
+                },
+            )
             .map_err(|e| VrfWorkerError::AesGcmError(AesError::DecryptionFailed(e.to_string())))?;
 
         // Parse decrypted keypair data using bincode (not JSON)
@@ -436,22 +1044,27 @@ impl VRFKeyManager {
         Ok(keypair)
     }
 
-    /// Generate a new VRF keypair with cryptographically secure randomness
-    fn generate_vrf_keypair(&self) -> VrfResult<ECVRFKeyPair> {
+    /// Generate a new VRF keypair from health-checked randomness mixed with `extra_entropy`;
+    /// also returns the entropy sources used
+    fn generate_vrf_keypair(
+        &self,
+        extra_entropy: Option<&[u8]>,
+    ) -> VrfResult<(ECVRFKeyPair, Vec<String>)> {
         debug!("Generating VRF keypair with secure randomness");
 
-        // Generate VRF keypair with cryptographically secure randomness
-        let mut rng = WasmRngFromSeed::from_entropy();
+        // Refuses with InsecureRandomness when the RNG fails its health check
+        let KeypairSeed { seed, sources } = keypair_seed(extra_entropy)?;
+        let mut rng = WasmRngFromSeed::from_seed(*seed);
         let vrf_keypair = ECVRFKeyPair::generate(&mut rng);
 
-        debug!("VRF keypair generated successfully");
+        debug!("VRF keypair generated from entropy sources {:?}", sources);
 
-        Ok(vrf_keypair)
+        Ok((vrf_keypair, sources))
     }
 
     /// Generate deterministic VRF keypair from seed material (PRF output)
     /// This enables deterministic VRF key derivation for account recovery
-    fn generate_vrf_keypair_from_seed(
+    pub(crate) fn generate_vrf_keypair_from_seed(
         &self,
         seed: &[u8],
         account_id: &str,
@@ -483,6 +1096,7 @@ impl VRFKeyManager {
         &self,
         vrf_keypair: &ECVRFKeyPair,
         prf_key: &[u8],
+        meta: VrfKeypairBlobMeta,
     ) -> VrfResult<(String, EncryptedVRFKeypair)> {
         debug!("Encrypting VRF keypair data");
 
@@ -506,7 +1120,7 @@ impl VRFKeyManager {
         })?;
 
         // Encrypt the VRF keypair data using AES-GCM
-        let encrypted_keypair = self.encrypt_vrf_keypair(&keypair_data_bytes, prf_key)?;
+        let encrypted_keypair = self.encrypt_vrf_keypair(&keypair_data_bytes, prf_key, meta)?;
 
         debug!("VRF keypair encrypted successfully");
 
@@ -514,7 +1128,12 @@ impl VRFKeyManager {
     }
 
     /// Enhanced VRF keypair generation with explicit control over memory storage and challenge generation
-    fn encrypt_vrf_keypair(&self, data: &[u8], key: &[u8]) -> VrfResult<EncryptedVRFKeypair> {
+    fn encrypt_vrf_keypair(
+        &self,
+        data: &[u8],
+        key: &[u8],
+        meta: VrfKeypairBlobMeta,
+    ) -> VrfResult<EncryptedVRFKeypair> {
         debug!("Deriving ChaCha20 key using HKDF-SHA256 for encryption");
 
         // Use HKDF-SHA256 to derive ChaCha20 key from PRF key for better security
@@ -534,12 +1153,129 @@ impl VRFKeyManager {
         let nonce = Nonce::from_slice(&iv_nonce_bytes);
 
         let ciphertext = cipher
-            .encrypt(nonce, data)
+            .encrypt(
+                nonce,
+                Payload {
+                    msg: data,
+                    aad: &meta.aad(),
+                },
+            )
             .map_err(|e| VrfWorkerError::AesGcmError(AesError::EncryptionFailed(e.to_string())))?;
 
         Ok(EncryptedVRFKeypair {
             encrypted_vrf_data_b64u: base64_url_encode(&ciphertext),
             chacha20_nonce_b64u: base64_url_encode(&iv_nonce_bytes),
+            meta: Some(meta),
         })
     }
 }
+
+// === SESSION SNAPSHOT HELPERS ===
+
+/// Session state carried inside a snapshot
+#[derive(Serialize, Deserialize)]
+struct SessionSnapshotPayload {
+    keypair_bytes: Vec<u8>,
+    session_start_time: f64,
+    /// Restored with the keypair, so a snapshot grants no more than the exporting session
+    scopes: Vec<VrfScope>,
+    /// Limits of the exporting session, so a restore cannot outlast them
+    auto_lock_after_ms: Option<f64>,
+    max_session_duration_ms: Option<f64>,
+    signer_session_epoch: Option<u32>,
+}
+
+/// The shorter of two optional limits
+fn shorter_limit(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Random snapshot epoch, other than `previous`. Without randomness it falls back to stepping
+/// on from `previous` (0 for a new worker), which a restart no longer revokes.
+fn random_session_epoch(previous: Option<u32>) -> u32 {
+    let mut bytes = [0u8; 4];
+    loop {
+        if let Err(e) = getrandom(&mut bytes) {
+            warn!("Failed to pick a random snapshot epoch: {}", e);
+            return previous.map_or(0, |epoch| epoch.wrapping_add(1));
+        }
+        let epoch = u32::from_le_bytes(bytes);
+        if Some(epoch) != previous {
+            return epoch;
+        }
+    }
+}
+
+/// Drops records of snapshots too old to restore anyway
+fn prune_consumed_snapshots(consumed_snapshots: &mut Vec<ConsumedSnapshot>, now_ms: f64) {
+    consumed_snapshots.retain(|c| now_ms - c.exported_at_ms <= SESSION_SNAPSHOT_MAX_AGE_MS);
+}
+
+/// Associated data binding the snapshot header to its ciphertext
+fn session_snapshot_aad(snapshot: &SessionSnapshot, snapshot_id: &[u8]) -> Vec<u8> {
+    let mut aad = SESSION_SNAPSHOT_AAD_DOMAIN.to_vec();
+    aad.extend_from_slice(&snapshot.version.to_le_bytes());
+    aad.extend_from_slice(snapshot_id);
+    aad.extend_from_slice(&snapshot.epoch.to_le_bytes());
+    aad.extend_from_slice(&snapshot.exported_at_ms.to_le_bytes());
+    aad
+}
+
+/// VRF proof and challenge data for `input_data` under `vrf_keypair`
+fn prove_vrf_challenge(
+    vrf_keypair: &ECVRFKeyPair,
+    input_data: VRFInputData,
+    challenge_length: u8,
+) -> VrfResult<VRFChallengeData> {
+    let challenge_length = resolve_challenge_length(Some(challenge_length))?;
+
+    // Construct VRF input according to specification from the contract test
+    // (see vrf_input::build_vrf_input_bytes for the exact layout)
+    let vrf_input = build_vrf_input_bytes(&input_data)?.vrf_input;
+    // The flat block fields are only meaningful for NEAR anchors
+    let (block_height, block_hash) = match &input_data.anchor {
+        VrfAnchor::NearBlock {
+            block_height,
+            block_hash,
+        } => {
+            let block_hash_bytes = decode_block_hash(block_hash)?;
+            (block_height.clone(), base64_url_encode(&block_hash_bytes))
+        }
+        VrfAnchor::Generic { .. } => (String::new(), String::new()),
+    };
+
+    // Generate VRF proof and output using the proper vrf-wasm API
+    let proof = vrf_keypair.prove(&vrf_input);
+    let vrf_output = proof.to_hash().to_vec();
+
+    let proof_bytes = bincode::serialize(&proof).map_err(|e| {
+        VrfWorkerError::SerializationError(SerializationError::VrfKeypairSerialization(format!(
+            "{:?}",
+            e
+        )))
+    })?;
+    let pk_bytes = bincode::serialize(&vrf_keypair.pk).map_err(|e| {
+        VrfWorkerError::SerializationError(SerializationError::VrfPublicKeySerialization(format!(
+            "{:?}",
+            e
+        )))
+    })?;
+    let result = VRFChallengeData {
+        vrf_input: base64_url_encode(&vrf_input),
+        vrf_output: base64_url_encode(&vrf_output),
+        vrf_proof: base64_url_encode(&proof_bytes),
+        vrf_public_key: base64_url_encode(&pk_bytes),
+        user_id: input_data.user_id,
+        rp_id: input_data.rp_id,
+        block_height,
+        block_hash,
+        anchor: Some(input_data.anchor),
+        challenge_length,
+        issuance_receipt_b64u: None,
+    };
+
+    Ok(result)
+}
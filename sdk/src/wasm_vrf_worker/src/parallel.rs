@@ -0,0 +1,64 @@
+// === PARALLEL BATCH PROOFS ===
+// Batch challenge generation (e.g. bulk authenticator migration) is CPU-bound. With the
+// `wasm-threads` feature, items are proven on a rayon pool; in the browser that pool is
+// wasm-bindgen-rayon's Web Worker pool, which the worker script starts only when the page
+// is cross-origin isolated. Without the feature or the pool, the serial path is used.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set once the JS side has started the thread pool (native builds use rayon's global pool)
+static THREAD_POOL_READY: AtomicBool = AtomicBool::new(cfg!(not(target_arch = "wasm32")));
+
+pub fn mark_thread_pool_ready() {
+    THREAD_POOL_READY.store(true, Ordering::SeqCst);
+}
+
+/// Whether batch work runs on the thread pool
+pub fn parallel_available() -> bool {
+    cfg!(feature = "wasm-threads") && THREAD_POOL_READY.load(Ordering::SeqCst)
+}
+
+/// Number of threads batch work is spread over (1 on the serial path)
+pub fn thread_count() -> usize {
+    #[cfg(feature = "wasm-threads")]
+    if parallel_available() {
+        return rayon::current_num_threads();
+    }
+    1
+}
+
+/// Compile-time and runtime capabilities, reported in WorkerInfo.features
+pub fn worker_features() -> Vec<String> {
+    let mut features = Vec::new();
+    if cfg!(feature = "wasm-threads") {
+        features.push("wasm-threads".to_string());
+    }
+    if parallel_available() {
+        features.push("parallel-batch-proofs".to_string());
+    }
+    features
+}
+
+/// Runs `f` over `items`, on the thread pool when `parallel` is set and available.
+/// Results are in input order regardless of completion order. A panicking item becomes
+/// an `Err` for that item only, so one bad input cannot take down the pool or the batch.
+pub fn map_in_order<T, R, F>(items: Vec<T>, parallel: bool, f: F) -> Vec<Result<R, String>>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> Result<R, String> + Sync,
+{
+    let run_item = |item: T| {
+        catch_unwind(AssertUnwindSafe(|| f(item)))
+            .unwrap_or_else(|_| Err("Panicked while processing batch item".to_string()))
+    };
+
+    #[cfg(feature = "wasm-threads")]
+    if parallel && parallel_available() {
+        use rayon::prelude::*;
+        return items.into_par_iter().map(run_item).collect();
+    }
+    let _ = parallel;
+    items.into_iter().map(run_item).collect()
+}
@@ -0,0 +1,161 @@
+// === STARTUP SELF-TEST ===
+// Counterpart of the signer worker's self-test for the primitive only this worker has: ECVRF
+// prove/verify. It runs when the module starts (and on RUN_SELF_TEST); any failure puts the
+// worker into a refusing state where key-handling requests fail with SelfTestFailed until the
+// worker is restarted.
+
+use log::{info, warn};
+use rand_core::SeedableRng;
+use serde::Serialize;
+use std::cell::RefCell;
+use vrf_wasm::ecvrf::{ECVRFKeyPair, ECVRFProof, ECVRFPublicKey};
+use vrf_wasm::traits::WasmRngFromSeed;
+use vrf_wasm::vrf::{VRFKeyPair, VRFProof};
+
+use crate::errors::VrfWorkerError;
+use crate::kat_vectors::*;
+use crate::types::WorkerRequestType;
+use crate::clock::{Clock, SystemClock};
+
+/// A named known-answer test; `Err` describes the mismatch
+pub type KnownAnswerTest = (&'static str, fn() -> Result<(), String>);
+
+pub const KNOWN_ANSWER_TESTS: &[KnownAnswerTest] = &[("vrf", check_vrf)];
+
+thread_local! {
+    /// First primitive whose known-answer test failed since the worker started
+    static SELF_TEST_FAILURE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestResult {
+    pub primitive: String,
+    pub passed: bool,
+    pub duration_ms: f64,
+    /// What did not match the reference vector (absent when passed)
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestReport {
+    /// False while the worker refuses key handling, even if every primitive of this run passed
+    pub passed: bool,
+    pub results: Vec<SelfTestResult>,
+    pub total_duration_ms: f64,
+    pub failed_primitive: Option<String>,
+}
+
+/// Runs `tests` in order without touching worker state
+pub fn run_known_answer_tests(tests: &[KnownAnswerTest]) -> SelfTestReport {
+    let now_ms = || SystemClock.monotonic_ms();
+    let started = now_ms();
+    let results: Vec<SelfTestResult> = tests
+        .iter()
+        .map(|(primitive, test)| {
+            let test_started = now_ms();
+            let outcome = test();
+            SelfTestResult {
+                primitive: primitive.to_string(),
+                passed: outcome.is_ok(),
+                duration_ms: now_ms() - test_started,
+                error: outcome.err(),
+            }
+        })
+        .collect();
+    SelfTestReport {
+        passed: results.iter().all(|r| r.passed),
+        failed_primitive: results
+            .iter()
+            .find(|r| !r.passed)
+            .map(|r| r.primitive.clone()),
+        results,
+        total_duration_ms: now_ms() - started,
+    }
+}
+
+/// Runs KNOWN_ANSWER_TESTS and enters the refusing state on any failure
+pub fn run_self_test() -> SelfTestReport {
+    let mut report = run_known_answer_tests(KNOWN_ANSWER_TESTS);
+    for result in report.results.iter().filter(|r| !r.passed) {
+        warn!(
+            "Self-test failed for {}: {}",
+            result.primitive,
+            result.error.as_deref().unwrap_or("")
+        );
+        record_self_test_failure(&result.primitive);
+    }
+    report.failed_primitive = self_test_failure();
+    report.passed = report.failed_primitive.is_none();
+    info!(
+        "Self-test {} in {}ms",
+        if report.passed { "passed" } else { "failed" },
+        report.total_duration_ms
+    );
+    report
+}
+
+pub fn self_test_failure() -> Option<String> {
+    SELF_TEST_FAILURE.with(|failure| failure.borrow().clone())
+}
+
+/// Puts the worker into the refusing state; the first failed primitive is kept
+pub fn record_self_test_failure(primitive: &str) {
+    SELF_TEST_FAILURE.with(|failure| {
+        failure
+            .borrow_mut()
+            .get_or_insert_with(|| primitive.to_string());
+    });
+}
+
+/// Refuses key-handling requests while the worker is in the refusing state
+pub fn check_key_handling_allowed(request_type: WorkerRequestType) -> Result<(), VrfWorkerError> {
+    if !request_type.handles_keys() {
+        return Ok(());
+    }
+    match self_test_failure() {
+        Some(primitive) => Err(VrfWorkerError::SelfTestFailed { primitive }),
+        None => Ok(()),
+    }
+}
+
+// === KNOWN-ANSWER TESTS ===
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).expect("kat_vectors holds valid hex"))
+        .collect()
+}
+
+fn check_vrf() -> Result<(), String> {
+    // Verify half: the reference proof verifies and hashes to the reference output
+    let public_key: ECVRFPublicKey = bincode::deserialize(&hex(VRF_PUBLIC_KEY))
+        .map_err(|e| format!("public key decode: {}", e))?;
+    let proof: ECVRFProof =
+        bincode::deserialize(&hex(VRF_PROOF)).map_err(|e| format!("proof decode: {}", e))?;
+    let output: [u8; 64] = hex(VRF_OUTPUT)
+        .try_into()
+        .map_err(|_| "output length".to_string())?;
+    proof
+        .verify_output(VRF_ALPHA, &public_key, &output)
+        .map_err(|_| "reference proof did not verify".to_string())?;
+    if proof.verify(b"Farewell, world!", &public_key).is_ok() {
+        return Err("the reference proof verified for another input".to_string());
+    }
+
+    // Prove half: a fresh proof verifies, and proving is deterministic
+    let keypair = ECVRFKeyPair::generate(&mut WasmRngFromSeed::from_seed(VRF_PROVE_SEED));
+    let (output, proof) = keypair.output(VRF_ALPHA);
+    proof
+        .verify_output(VRF_ALPHA, &keypair.pk, &output)
+        .map_err(|_| "a fresh proof did not verify".to_string())?;
+    if keypair.output(VRF_ALPHA).0 != output {
+        return Err("proving the same input twice gave different outputs".to_string());
+    }
+    if proof.verify(VRF_ALPHA, &public_key).is_ok() {
+        return Err("a fresh proof verified under another public key".to_string());
+    }
+    Ok(())
+}
@@ -62,3 +62,93 @@ impl ShamirRemoveServerLockHTTPResponse {
         JsValue::from_str(&serde_json::to_string(self).unwrap())
     }
 }
+
+// === NEAR RPC block lookup (blockHash cross-check) ===
+
+/// JSON-RPC `block` query by hash
+#[derive(Serialize, Clone)]
+pub struct NearBlockRpcRequest {
+    pub jsonrpc: String,
+    pub id: String,
+    pub method: String,
+    pub params: NearBlockRpcParams,
+}
+
+#[derive(Serialize, Clone)]
+pub struct NearBlockRpcParams {
+    pub block_id: String,
+}
+
+impl NearBlockRpcRequest {
+    pub fn by_hash(block_hash: &str) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id: "vrf-block-cross-check".to_string(),
+            method: "block".to_string(),
+            params: NearBlockRpcParams {
+                block_id: block_hash.to_string(),
+            },
+        }
+    }
+    pub fn to_js_value(&self) -> JsValue {
+        JsValue::from_str(&serde_json::to_string(self).unwrap())
+    }
+}
+
+/// The fields of a `block` response the cross-check reads
+#[derive(Deserialize, Clone, Debug)]
+pub struct NearBlockRpcResponse {
+    #[serde(default)]
+    pub result: Option<NearBlockRpcResult>,
+    #[serde(default)]
+    pub error: Option<NearRpcError>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct NearBlockRpcResult {
+    pub header: NearBlockHeader,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct NearBlockHeader {
+    pub height: u64,
+    pub hash: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct NearRpcError {
+    #[serde(default)]
+    pub cause: Option<NearRpcErrorCause>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct NearRpcErrorCause {
+    pub name: String,
+}
+
+impl NearBlockRpcResponse {
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        serde_json::from_str(s).map_err(|e| format!("Failed to parse response JSON: {}", e))
+    }
+
+    /// The block's header, or None when the RPC does not know the block (UNKNOWN_BLOCK)
+    pub fn into_header(self) -> Result<Option<NearBlockHeader>, String> {
+        if let Some(error) = self.error {
+            let cause = error.cause.map(|cause| cause.name);
+            if cause.as_deref() == Some("UNKNOWN_BLOCK") {
+                return Ok(None);
+            }
+            return Err(format!(
+                "RPC error: {}",
+                cause
+                    .or(error.message)
+                    .unwrap_or_else(|| "unknown".to_string())
+            ));
+        }
+        self.result
+            .map(|result| Some(result.header))
+            .ok_or_else(|| "RPC response has neither result nor error".to_string())
+    }
+}
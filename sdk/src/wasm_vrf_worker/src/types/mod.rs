@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
+use crate::config::{ENCRYPTED_BLOB_VERSION_V1, ENCRYPTED_BLOB_VERSION_V2};
+pub use crate::vrf_blob_meta::VrfKeypairBlobMeta;
+
 pub mod http;
 pub mod worker_messages;
 
@@ -20,29 +23,217 @@ pub struct VRFKeypairData {
 #[wasm_bindgen]
 #[derive(Serialize, Deserialize, Clone)]
 pub struct EncryptedVRFKeypair {
+    /// Written as `encrypted_vrf_data_b64u` before the camelCase migration
     #[wasm_bindgen(getter_with_clone, js_name = "encryptedVrfDataB64u")]
-    #[serde(rename = "encryptedVrfDataB64u")]
+    #[serde(rename = "encryptedVrfDataB64u", alias = "encrypted_vrf_data_b64u")]
     pub encrypted_vrf_data_b64u: String,
     #[wasm_bindgen(getter_with_clone, js_name = "chacha20NonceB64u")]
-    #[serde(rename = "chacha20NonceB64u")]
+    #[serde(rename = "chacha20NonceB64u", alias = "chacha20_nonce_b64u")]
     pub chacha20_nonce_b64u: String,
+    /// Bound into the ciphertext's associated data (v2); absent on v1 blobs
+    #[wasm_bindgen(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<VrfKeypairBlobMeta>,
+}
+
+impl EncryptedVRFKeypair {
+    /// v2 when the blob carries metadata, v1 otherwise
+    pub fn version(&self) -> u32 {
+        match self.meta {
+            Some(_) => ENCRYPTED_BLOB_VERSION_V2,
+            None => ENCRYPTED_BLOB_VERSION_V1,
+        }
+    }
+
+    /// Associated data of the ciphertext: the metadata's, none for v1
+    pub fn aad(&self) -> Vec<u8> {
+        self.meta
+            .as_ref()
+            .map(VrfKeypairBlobMeta::aad)
+            .unwrap_or_default()
+    }
+
+    pub fn describe(&self) -> BlobMetadata {
+        BlobMetadata {
+            version: self.version(),
+            meta: self.meta.clone(),
+        }
+    }
+}
+
+/// What `describe_encrypted_blob` reads from an EncryptedVRFKeypair without decrypting it.
+/// `meta` is what the blob claims; decryption is what proves it was not edited.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BlobMetadata {
+    pub version: u32,
+    pub meta: Option<VrfKeypairBlobMeta>,
+}
+
+/// Freshness anchor bound into the VRF input
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type")]
+pub enum VrfAnchor {
+    /// A NEAR block; the contract checks the height against its recent-block window
+    #[serde(rename_all = "camelCase")]
+    NearBlock {
+        block_height: String,
+        /// Base58 block hash
+        block_hash: String,
+    },
+    /// Any other source of fresh public randomness (e.g. a drand round).
+    /// `label` names the source and is bound into the input alongside the value.
+    #[serde(rename_all = "camelCase")]
+    Generic { label: String, value_b64u: String },
+}
+
+impl VrfAnchor {
+    pub fn is_near_block(&self) -> bool {
+        matches!(self, VrfAnchor::NearBlock { .. })
+    }
+}
+
+/// What an unlocked keypair may be used for, granted by the unlock that installed it.
+/// Granting more takes another unlock (a fresh PRF output).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum VrfScope {
+    /// GENERATE_VRF_CHALLENGE(S_BATCH), REQUEST_CHALLENGE and challenge prefetch
+    ChallengeGeneration,
+    /// Re-encrypting the keypair under the current Shamir 3-pass server key
+    Rotation,
+    /// EXPORT_SESSION_SNAPSHOT
+    Export,
+    /// Re-encrypting the keypair under another credential's PRF output
+    DeviceLinking,
+}
+
+impl VrfScope {
+    /// Granted when an unlock names no scopes
+    pub const ALL: [VrfScope; 4] = [
+        VrfScope::ChallengeGeneration,
+        VrfScope::Rotation,
+        VrfScope::Export,
+        VrfScope::DeviceLinking,
+    ];
+
+    pub fn all() -> Vec<VrfScope> {
+        Self::ALL.to_vec()
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VrfScope::ChallengeGeneration => "challengeGeneration",
+            VrfScope::Rotation => "rotation",
+            VrfScope::Export => "export",
+            VrfScope::DeviceLinking => "deviceLinking",
+        }
+    }
+}
+
+impl std::fmt::Display for VrfScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 #[wasm_bindgen]
 #[derive(Serialize, Deserialize, Clone)]
+#[serde(try_from = "VRFInputDataWire", into = "VRFInputDataWire")]
 pub struct VRFInputData {
     #[wasm_bindgen(getter_with_clone, js_name = "userId")]
-    #[serde(rename = "userId")]
     pub user_id: String,
     #[wasm_bindgen(getter_with_clone, js_name = "rpId")]
-    #[serde(rename = "rpId")]
     pub rp_id: String,
-    #[wasm_bindgen(getter_with_clone, js_name = "blockHeight")]
-    #[serde(rename = "blockHeight")]
-    pub block_height: String,
-    #[wasm_bindgen(getter_with_clone, js_name = "blockHash")]
-    #[serde(rename = "blockHash")]
-    pub block_hash: String,
+    #[wasm_bindgen(skip)]
+    pub anchor: VrfAnchor,
+}
+
+impl VRFInputData {
+    pub fn near_block(user_id: &str, rp_id: &str, block_height: &str, block_hash: &str) -> Self {
+        Self {
+            user_id: user_id.to_string(),
+            rp_id: rp_id.to_string(),
+            anchor: VrfAnchor::NearBlock {
+                block_height: block_height.to_string(),
+                block_hash: block_hash.to_string(),
+            },
+        }
+    }
+}
+
+/// Wire shape of VRFInputData: either an explicit `anchor`, or the flat
+/// `blockHeight`/`blockHash` fields payloads used before anchors existed.
+/// NEAR anchors are serialized in both forms so older readers keep working.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VRFInputDataWire {
+    user_id: String,
+    rp_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    anchor: Option<VrfAnchor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    block_height: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    block_hash: Option<String>,
+}
+
+impl TryFrom<VRFInputDataWire> for VRFInputData {
+    type Error = String;
+
+    fn try_from(wire: VRFInputDataWire) -> Result<Self, Self::Error> {
+        let anchor = match (wire.anchor, wire.block_height, wire.block_hash) {
+            (Some(anchor), None, None) => anchor,
+            (Some(anchor), block_height, block_hash) => {
+                // Flat fields alongside an anchor must describe the same NEAR block
+                let matches = match &anchor {
+                    VrfAnchor::NearBlock {
+                        block_height: height,
+                        block_hash: hash,
+                    } => {
+                        block_height.as_ref().is_none_or(|h| h == height)
+                            && block_hash.as_ref().is_none_or(|h| h == hash)
+                    }
+                    VrfAnchor::Generic { .. } => false,
+                };
+                if !matches {
+                    return Err("blockHeight/blockHash conflict with anchor".to_string());
+                }
+                anchor
+            }
+            (None, Some(block_height), Some(block_hash)) => VrfAnchor::NearBlock {
+                block_height,
+                block_hash,
+            },
+            (None, _, _) => {
+                return Err("missing anchor (or blockHeight and blockHash)".to_string());
+            }
+        };
+        Ok(Self {
+            user_id: wire.user_id,
+            rp_id: wire.rp_id,
+            anchor,
+        })
+    }
+}
+
+impl From<VRFInputData> for VRFInputDataWire {
+    fn from(input: VRFInputData) -> Self {
+        let (block_height, block_hash) = match &input.anchor {
+            VrfAnchor::NearBlock {
+                block_height,
+                block_hash,
+            } => (Some(block_height.clone()), Some(block_hash.clone())),
+            VrfAnchor::Generic { .. } => (None, None),
+        };
+        Self {
+            user_id: input.user_id,
+            rp_id: input.rp_id,
+            anchor: Some(input.anchor),
+            block_height,
+            block_hash,
+        }
+    }
 }
 
 #[wasm_bindgen]
@@ -67,13 +258,38 @@ pub struct VRFChallengeData {
     #[wasm_bindgen(getter_with_clone, js_name = "rpId")]
     #[serde(rename = "rpId")]
     pub rp_id: String,
+    /// Empty for generic anchors
     #[wasm_bindgen(getter_with_clone, js_name = "blockHeight")]
     #[serde(rename = "blockHeight")]
     pub block_height: String,
+    /// Base64url of the block hash bytes; empty for generic anchors
     #[wasm_bindgen(getter_with_clone, js_name = "blockHash")]
     #[serde(rename = "blockHash")]
     pub block_hash: String,
+    /// Anchor the VRF input was built from (absent in payloads predating anchors)
+    #[wasm_bindgen(skip)]
+    #[serde(default)]
+    pub anchor: Option<VrfAnchor>,
+    /// Length in bytes of the WebAuthn challenge derived from `vrf_output`
+    /// (payloads predating this field use the default)
+    #[wasm_bindgen(js_name = "challengeLength")]
+    #[serde(rename = "challengeLength", default = "default_challenge_length")]
+    pub challenge_length: u8,
+    /// Issuance receipt for the integrator's backend, when the session was configured with
+    /// `issuanceReceiptKeyB64u` (see issuance_receipt.rs)
+    #[wasm_bindgen(getter_with_clone, js_name = "issuanceReceiptB64u")]
+    #[serde(
+        rename = "issuanceReceiptB64u",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub issuance_receipt_b64u: Option<String>,
 }
+
+fn default_challenge_length() -> u8 {
+    crate::config::DEFAULT_CHALLENGE_LENGTH
+}
+
 impl VRFChallengeData {
     pub fn to_json(&self) -> serde_json::Value {
         serde_json::to_value(self).unwrap()
@@ -84,6 +300,8 @@ impl VRFChallengeData {
 pub struct GenerateVrfKeypairBootstrapResponse {
     pub vrf_public_key: String,
     pub vrf_challenge_data: Option<VRFChallengeData>,
+    /// Entropy mixed into the keypair seed: "getrandom", plus "extraEntropyB64u" when given
+    pub entropy_sources: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize)]
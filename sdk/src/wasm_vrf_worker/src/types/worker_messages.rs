@@ -1,5 +1,6 @@
 // === WORKER MESSAGES: REQUEST & RESPONSE TYPES ===
 
+use crate::error_codes::{self, ErrorCodeDef};
 use crate::errors::{MessageError, VrfWorkerError};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
@@ -25,6 +26,21 @@ pub enum WorkerRequestType {
     Shamir3PassRemoveServerLock,
     Shamir3PassConfigP,
     Shamir3PassConfigServerUrls,
+    GenerateTestVectors,
+    ValidateEncryptedBlobs,
+    ExportSessionSnapshot,
+    RestoreSessionSnapshot,
+    WipeAllState,
+    GenerateVrfChallengesBatch,
+    RunSelfTest,
+    UpdateBlockInfo,
+    ConfigureSessionOptions,
+    ConnectPeerPort,
+    RequestChallenge,
+    SuspendHint,
+    ResumeHint,
+    LoadSnapshotRevocationState,
+    UpdateSessionPolicy,
 }
 
 impl From<u32> for WorkerRequestType {
@@ -44,6 +60,21 @@ impl From<u32> for WorkerRequestType {
             11 => WorkerRequestType::Shamir3PassRemoveServerLock,
             12 => WorkerRequestType::Shamir3PassConfigP,
             13 => WorkerRequestType::Shamir3PassConfigServerUrls,
+            14 => WorkerRequestType::GenerateTestVectors,
+            15 => WorkerRequestType::ValidateEncryptedBlobs,
+            16 => WorkerRequestType::ExportSessionSnapshot,
+            17 => WorkerRequestType::RestoreSessionSnapshot,
+            18 => WorkerRequestType::WipeAllState,
+            19 => WorkerRequestType::GenerateVrfChallengesBatch,
+            20 => WorkerRequestType::RunSelfTest,
+            21 => WorkerRequestType::UpdateBlockInfo,
+            22 => WorkerRequestType::ConfigureSessionOptions,
+            23 => WorkerRequestType::ConnectPeerPort,
+            24 => WorkerRequestType::RequestChallenge,
+            25 => WorkerRequestType::SuspendHint,
+            26 => WorkerRequestType::ResumeHint,
+            27 => WorkerRequestType::LoadSnapshotRevocationState,
+            28 => WorkerRequestType::UpdateSessionPolicy,
             _ => panic!("Invalid WorkerRequestType value: {}", value),
         }
     }
@@ -72,6 +103,21 @@ impl From<&str> for WorkerRequestType {
             "SHAMIR3PASS_REMOVE_SERVER_LOCK_KEK" => WorkerRequestType::Shamir3PassRemoveServerLock,
             "SHAMIR3PASS_CONFIG_P" => WorkerRequestType::Shamir3PassConfigP,
             "SHAMIR3PASS_CONFIG_SERVER_URLS" => WorkerRequestType::Shamir3PassConfigServerUrls,
+            "GENERATE_TEST_VECTORS" => WorkerRequestType::GenerateTestVectors,
+            "VALIDATE_ENCRYPTED_BLOBS" => WorkerRequestType::ValidateEncryptedBlobs,
+            "EXPORT_SESSION_SNAPSHOT" => WorkerRequestType::ExportSessionSnapshot,
+            "RESTORE_SESSION_SNAPSHOT" => WorkerRequestType::RestoreSessionSnapshot,
+            "WIPE_ALL_STATE" => WorkerRequestType::WipeAllState,
+            "GENERATE_VRF_CHALLENGES_BATCH" => WorkerRequestType::GenerateVrfChallengesBatch,
+            "RUN_SELF_TEST" => WorkerRequestType::RunSelfTest,
+            "UPDATE_BLOCK_INFO" => WorkerRequestType::UpdateBlockInfo,
+            "CONFIGURE_SESSION_OPTIONS" => WorkerRequestType::ConfigureSessionOptions,
+            "CONNECT_PEER_PORT" => WorkerRequestType::ConnectPeerPort,
+            "REQUEST_CHALLENGE" => WorkerRequestType::RequestChallenge,
+            "SUSPEND_HINT" => WorkerRequestType::SuspendHint,
+            "RESUME_HINT" => WorkerRequestType::ResumeHint,
+            "LOAD_SNAPSHOT_REVOCATION_STATE" => WorkerRequestType::LoadSnapshotRevocationState,
+            "UPDATE_SESSION_POLICY" => WorkerRequestType::UpdateSessionPolicy,
             _ => panic!("Invalid WorkerRequestType string: {}", value),
         }
     }
@@ -100,8 +146,47 @@ impl WorkerRequestType {
             WorkerRequestType::Shamir3PassRemoveServerLock => "SHAMIR3PASS_REMOVE_SERVER_LOCK_KEK",
             WorkerRequestType::Shamir3PassConfigP => "SHAMIR3PASS_CONFIG_P",
             WorkerRequestType::Shamir3PassConfigServerUrls => "SHAMIR3PASS_CONFIG_SERVER_URLS",
+            WorkerRequestType::GenerateTestVectors => "GENERATE_TEST_VECTORS",
+            WorkerRequestType::ValidateEncryptedBlobs => "VALIDATE_ENCRYPTED_BLOBS",
+            WorkerRequestType::ExportSessionSnapshot => "EXPORT_SESSION_SNAPSHOT",
+            WorkerRequestType::RestoreSessionSnapshot => "RESTORE_SESSION_SNAPSHOT",
+            WorkerRequestType::WipeAllState => "WIPE_ALL_STATE",
+            WorkerRequestType::GenerateVrfChallengesBatch => "GENERATE_VRF_CHALLENGES_BATCH",
+            WorkerRequestType::RunSelfTest => "RUN_SELF_TEST",
+            WorkerRequestType::UpdateBlockInfo => "UPDATE_BLOCK_INFO",
+            WorkerRequestType::ConfigureSessionOptions => "CONFIGURE_SESSION_OPTIONS",
+            WorkerRequestType::ConnectPeerPort => "CONNECT_PEER_PORT",
+            WorkerRequestType::RequestChallenge => "REQUEST_CHALLENGE",
+            WorkerRequestType::SuspendHint => "SUSPEND_HINT",
+            WorkerRequestType::ResumeHint => "RESUME_HINT",
+            WorkerRequestType::LoadSnapshotRevocationState => "LOAD_SNAPSHOT_REVOCATION_STATE",
+            WorkerRequestType::UpdateSessionPolicy => "UPDATE_SESSION_POLICY",
         }
     }
+
+    /// Whether the request derives, decrypts, proves with or (un)locks key material.
+    /// These are refused after a failed self-test.
+    pub fn handles_keys(&self) -> bool {
+        matches!(
+            self,
+            WorkerRequestType::GenerateVrfChallenge
+                | WorkerRequestType::GenerateVrfKeypairBootstrap
+                | WorkerRequestType::UnlockVrfKeypair
+                | WorkerRequestType::DeriveVrfKeypairFromPrf
+                | WorkerRequestType::Shamir3PassClientEncryptCurrentVrfKeypair
+                | WorkerRequestType::Shamir3PassClientDecryptVrfKeypair
+                | WorkerRequestType::Shamir3PassGenerateServerKeypair
+                | WorkerRequestType::Shamir3PassApplyServerLock
+                | WorkerRequestType::Shamir3PassRemoveServerLock
+                | WorkerRequestType::GenerateTestVectors
+                | WorkerRequestType::ExportSessionSnapshot
+                | WorkerRequestType::RestoreSessionSnapshot
+                | WorkerRequestType::GenerateVrfChallengesBatch
+                | WorkerRequestType::UpdateBlockInfo
+                | WorkerRequestType::ConfigureSessionOptions
+                | WorkerRequestType::RequestChallenge
+        )
+    }
 }
 
 /// Worker response types enum - corresponds to TypeScript WorkerResponseType
@@ -123,6 +208,21 @@ pub enum WorkerResponseType {
     Shamir3PassRemoveServerLockSuccess,
     Shamir3PassConfigPSuccess,
     Shamir3PassConfigServerUrlsSuccess,
+    GenerateTestVectorsSuccess,
+    ValidateEncryptedBlobsSuccess,
+    ExportSessionSnapshotSuccess,
+    RestoreSessionSnapshotSuccess,
+    WipeAllStateSuccess,
+    GenerateVrfChallengesBatchSuccess,
+    RunSelfTestSuccess,
+    UpdateBlockInfoSuccess,
+    ConfigureSessionOptionsSuccess,
+    ConnectPeerPortSuccess,
+    RequestChallengeSuccess,
+    SuspendHintSuccess,
+    ResumeHintSuccess,
+    LoadSnapshotRevocationStateSuccess,
+    UpdateSessionPolicySuccess,
 }
 
 impl From<WorkerResponseType> for u32 {
@@ -142,6 +242,21 @@ impl From<WorkerResponseType> for u32 {
             WorkerResponseType::Shamir3PassRemoveServerLockSuccess => 11,
             WorkerResponseType::Shamir3PassConfigPSuccess => 12,
             WorkerResponseType::Shamir3PassConfigServerUrlsSuccess => 13,
+            WorkerResponseType::GenerateTestVectorsSuccess => 14,
+            WorkerResponseType::ValidateEncryptedBlobsSuccess => 15,
+            WorkerResponseType::ExportSessionSnapshotSuccess => 16,
+            WorkerResponseType::RestoreSessionSnapshotSuccess => 17,
+            WorkerResponseType::WipeAllStateSuccess => 18,
+            WorkerResponseType::GenerateVrfChallengesBatchSuccess => 19,
+            WorkerResponseType::RunSelfTestSuccess => 20,
+            WorkerResponseType::UpdateBlockInfoSuccess => 21,
+            WorkerResponseType::ConfigureSessionOptionsSuccess => 22,
+            WorkerResponseType::ConnectPeerPortSuccess => 23,
+            WorkerResponseType::RequestChallengeSuccess => 24,
+            WorkerResponseType::SuspendHintSuccess => 25,
+            WorkerResponseType::ResumeHintSuccess => 26,
+            WorkerResponseType::LoadSnapshotRevocationStateSuccess => 27,
+            WorkerResponseType::UpdateSessionPolicySuccess => 28,
         }
     }
 }
@@ -163,6 +278,21 @@ impl From<u32> for WorkerResponseType {
             11 => WorkerResponseType::Shamir3PassRemoveServerLockSuccess,
             12 => WorkerResponseType::Shamir3PassConfigPSuccess,
             13 => WorkerResponseType::Shamir3PassConfigServerUrlsSuccess,
+            14 => WorkerResponseType::GenerateTestVectorsSuccess,
+            15 => WorkerResponseType::ValidateEncryptedBlobsSuccess,
+            16 => WorkerResponseType::ExportSessionSnapshotSuccess,
+            17 => WorkerResponseType::RestoreSessionSnapshotSuccess,
+            18 => WorkerResponseType::WipeAllStateSuccess,
+            19 => WorkerResponseType::GenerateVrfChallengesBatchSuccess,
+            20 => WorkerResponseType::RunSelfTestSuccess,
+            21 => WorkerResponseType::UpdateBlockInfoSuccess,
+            22 => WorkerResponseType::ConfigureSessionOptionsSuccess,
+            23 => WorkerResponseType::ConnectPeerPortSuccess,
+            24 => WorkerResponseType::RequestChallengeSuccess,
+            25 => WorkerResponseType::SuspendHintSuccess,
+            26 => WorkerResponseType::ResumeHintSuccess,
+            27 => WorkerResponseType::LoadSnapshotRevocationStateSuccess,
+            28 => WorkerResponseType::UpdateSessionPolicySuccess,
             _ => panic!("Invalid WorkerResponseType value: {}", value),
         }
     }
@@ -203,20 +333,53 @@ pub struct VrfWorkerResponse {
     pub success: bool,
     pub data: Option<serde_json::Value>,
     pub error: Option<String>,
+    /// Catalogued code of `error` (src/wasm_shared/error_codes.rs)
+    #[serde(rename = "errorCode", default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    /// Set on every failed response: the code's category, Internal without a code
+    #[serde(
+        rename = "errorCategory",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub error_category: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retriable: Option<bool>,
 }
 
 impl VrfWorkerResponse {
+    /// Failed responses take their code from the message prefix ("SessionSnapshotExpired: ...")
     pub fn new(
         id: Option<String>,
         success: bool,
         data: Option<serde_json::Value>,
         error: Option<String>,
     ) -> Self {
+        let definition = error
+            .as_deref()
+            .and_then(error_codes::error_code_of_message);
         Self {
             id,
             success,
             data,
             error,
+            error_code: None,
+            error_category: None,
+            retriable: None,
+        }
+        .with_error_code(success, definition)
+    }
+
+    fn with_error_code(self, success: bool, definition: Option<&ErrorCodeDef>) -> Self {
+        if success {
+            return self;
+        }
+        let (category, retriable) = error_codes::category_and_retriable(definition);
+        Self {
+            error_code: definition.map(|def| def.code.to_string()),
+            error_category: Some(category.as_str().to_string()),
+            retriable: Some(retriable),
+            ..self
         }
     }
 
@@ -231,4 +394,10 @@ impl VrfWorkerResponse {
     pub fn error(id: Option<String>, error: String) -> Self {
         Self::new(id, false, None, Some(error))
     }
+
+    /// Failed response with the error's own code
+    pub fn from_error(id: Option<String>, error: &VrfWorkerError) -> Self {
+        Self::new(id, false, None, Some(error.to_string()))
+            .with_error_code(false, Some(error.code().definition()))
+    }
 }
@@ -1,3 +1,4 @@
+use crate::config::{MAX_PLAUSIBLE_BLOCK_HEIGHT, NEAR_BLOCK_HASH_SIZE};
 use crate::errors::VrfWorkerError;
 use base64ct::{Base64UrlUnpadded, Encoding};
 
@@ -13,8 +14,30 @@ pub fn base64_url_decode(s: &str) -> Result<Vec<u8>, String> {
     Base64UrlUnpadded::decode_vec(s).map_err(|e| format!("Base64 decode error: {}", e))
 }
 
+/// Parses a NEAR block height, refusing 0 and heights at or above MAX_PLAUSIBLE_BLOCK_HEIGHT
 pub fn parse_block_height(block_height: &str) -> Result<u64, VrfWorkerError> {
-    block_height.parse().map_err(|_| {
+    let height: u64 = block_height.parse().map_err(|_| {
         VrfWorkerError::BlockHeightParsingError(format!("Invalid block height: {}", block_height))
+    })?;
+    if height == 0 || height >= MAX_PLAUSIBLE_BLOCK_HEIGHT {
+        return Err(VrfWorkerError::BlockHeightParsingError(format!(
+            "Implausible block height: {} (expected 1..{})",
+            block_height, MAX_PLAUSIBLE_BLOCK_HEIGHT
+        )));
+    }
+    Ok(height)
+}
+
+/// Decodes a NEAR block hash, which must be exactly NEAR_BLOCK_HASH_SIZE bytes of base58
+pub fn decode_block_hash(block_hash: &str) -> Result<[u8; NEAR_BLOCK_HASH_SIZE], VrfWorkerError> {
+    let bytes = bs58::decode(block_hash)
+        .into_vec()
+        .map_err(|e| VrfWorkerError::invalid_block_hash(&format!("not base58: {}", e)))?;
+    bytes.as_slice().try_into().map_err(|_| {
+        VrfWorkerError::invalid_block_hash(&format!(
+            "decodes to {} bytes, expected {}",
+            bytes.len(),
+            NEAR_BLOCK_HASH_SIZE
+        ))
     })
 }
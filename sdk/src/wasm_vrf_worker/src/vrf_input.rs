@@ -0,0 +1,180 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::{VRF_DOMAIN_SEPARATOR, VRF_GENERIC_ANCHOR_DOMAIN_SEPARATOR};
+use crate::errors::{VrfResult, VrfWorkerError};
+use crate::types::{VRFInputData, VrfAnchor};
+use crate::utils::{base64_url_decode, base64_url_encode, decode_block_hash, parse_block_height};
+
+// === VRF INPUT CONSTRUCTION ===
+// Single source of truth for how VRFInputData becomes the VRF alpha string.
+// The contract reconstructs the same bytes, so any change here is a protocol change.
+// Each anchor type has its own domain separator; NEAR-anchored inputs keep the original
+// layout, generic anchors length-prefix every variable field.
+
+/// Raw bytes produced from VRFInputData
+pub struct VrfInputBytes {
+    /// Concatenated fields before hashing
+    pub input_bytes: Vec<u8>,
+    /// SHA-256(input_bytes): the VRF input that gets proven
+    pub vrf_input: Vec<u8>,
+    /// Byte ranges of each field within input_bytes
+    pub layout: VrfInputLayout,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VrfInputLayoutField {
+    pub name: String,
+    pub encoding: String,
+    pub offset: usize,
+    pub length: usize,
+    /// "none" for NEAR-anchored inputs; "u32 little-endian" for generic-anchor fields,
+    /// in which case the prefix sits immediately before `offset`
+    pub length_prefix: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VrfInputLayout {
+    pub fields: Vec<VrfInputLayoutField>,
+    pub total_length: usize,
+    pub hash: String,
+    pub notes: String,
+}
+
+/// Output of the `build_vrf_input` wasm export
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VrfInputConstruction {
+    pub input_bytes_b64u: String,
+    pub vrf_input_b64u: String,
+    pub domain_tag: String,
+    pub layout_description: VrfInputLayout,
+}
+
+/// Domain separator for inputs built from `anchor`
+pub fn anchor_domain_separator(anchor: &VrfAnchor) -> &'static [u8] {
+    match anchor {
+        VrfAnchor::NearBlock { .. } => VRF_DOMAIN_SEPARATOR,
+        VrfAnchor::Generic { .. } => VRF_GENERIC_ANCHOR_DOMAIN_SEPARATOR,
+    }
+}
+
+/// Builds the VRF input bytes, then hashes the concatenation with SHA-256.
+/// NEAR block anchors:
+/// `domain_separator || user_id || rp_id || block_height (u64 LE) || block_hash (bs58-decoded)`.
+/// Generic anchors:
+/// `generic_domain_separator || len || label || len || user_id || len || rp_id || len || value`,
+/// each `len` a u32 LE byte count.
+pub fn build_vrf_input_bytes(input_data: &VRFInputData) -> VrfResult<VrfInputBytes> {
+    match &input_data.anchor {
+        VrfAnchor::NearBlock {
+            block_height,
+            block_hash,
+        } => {
+            let block_height_num = parse_block_height(block_height)?;
+            let block_hash_bytes = decode_block_hash(block_hash)?;
+            let segments: [(&str, &str, &[u8]); 5] = [
+                ("domainSeparator", "ASCII bytes", VRF_DOMAIN_SEPARATOR),
+                ("userId", "UTF-8 bytes", input_data.user_id.as_bytes()),
+                ("rpId", "UTF-8 bytes", input_data.rp_id.as_bytes()),
+                (
+                    "blockHeight",
+                    "u64 little-endian",
+                    &block_height_num.to_le_bytes(),
+                ),
+                ("blockHash", "base58-decoded bytes", &block_hash_bytes),
+            ];
+            Ok(concat_segments(
+                &segments,
+                false,
+                "Fields are concatenated in order without separators or length prefixes; \
+                 the VRF is evaluated over SHA-256 of the concatenation",
+            ))
+        }
+        VrfAnchor::Generic { label, value_b64u } => {
+            if label.is_empty() {
+                return Err(VrfWorkerError::invalid_format(
+                    "generic anchor label must not be empty",
+                ));
+            }
+            let value_bytes = base64_url_decode(value_b64u).map_err(|e| {
+                VrfWorkerError::invalid_format(&format!("invalid anchor valueB64u: {}", e))
+            })?;
+            let segments: [(&str, &str, &[u8]); 5] = [
+                (
+                    "domainSeparator",
+                    "ASCII bytes",
+                    VRF_GENERIC_ANCHOR_DOMAIN_SEPARATOR,
+                ),
+                ("anchorLabel", "UTF-8 bytes", label.as_bytes()),
+                ("userId", "UTF-8 bytes", input_data.user_id.as_bytes()),
+                ("rpId", "UTF-8 bytes", input_data.rp_id.as_bytes()),
+                ("anchorValue", "base64url-decoded bytes", &value_bytes),
+            ];
+            Ok(concat_segments(
+                &segments,
+                true,
+                "After the domain separator, each field is preceded by its byte length \
+                 as a u32 little-endian; the VRF is evaluated over SHA-256 of the concatenation",
+            ))
+        }
+    }
+}
+
+/// Concatenates segments (the first, the domain separator, is never length-prefixed)
+/// and records the layout
+fn concat_segments(
+    segments: &[(&str, &str, &[u8])],
+    length_prefixed: bool,
+    notes: &str,
+) -> VrfInputBytes {
+    let mut input_bytes = Vec::new();
+    let mut fields = Vec::with_capacity(segments.len());
+    for (i, (name, encoding, bytes)) in segments.iter().enumerate() {
+        let prefixed = length_prefixed && i > 0;
+        if prefixed {
+            input_bytes.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        }
+        fields.push(VrfInputLayoutField {
+            name: name.to_string(),
+            encoding: encoding.to_string(),
+            offset: input_bytes.len(),
+            length: bytes.len(),
+            length_prefix: if prefixed {
+                "u32 little-endian"
+            } else {
+                "none"
+            }
+            .to_string(),
+        });
+        input_bytes.extend_from_slice(bytes);
+    }
+
+    let vrf_input = Sha256::digest(&input_bytes).to_vec();
+    let layout = VrfInputLayout {
+        total_length: input_bytes.len(),
+        fields,
+        hash: "sha256".to_string(),
+        notes: notes.to_string(),
+    };
+
+    VrfInputBytes {
+        input_bytes,
+        vrf_input,
+        layout,
+    }
+}
+
+/// Describes the exact byte construction for VRFInputData (for cross-language verifiers)
+pub fn build_vrf_input(input_data: &VRFInputData) -> VrfResult<VrfInputConstruction> {
+    let built = build_vrf_input_bytes(input_data)?;
+    Ok(VrfInputConstruction {
+        input_bytes_b64u: base64_url_encode(&built.input_bytes),
+        vrf_input_b64u: base64_url_encode(&built.vrf_input),
+        domain_tag: String::from_utf8_lossy(anchor_domain_separator(&input_data.anchor))
+            .to_string(),
+        layout_description: built.layout,
+    })
+}